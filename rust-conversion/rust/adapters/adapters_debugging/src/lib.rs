@@ -16,7 +16,8 @@
 //!   tracing and monitoring
 //!
 //! - **[`tracer`](tracer/index.html)**: Tracer operations for collecting and managing
-//!   trace data
+//!   trace data, including the call trace events emitted by the emulator loop, delivered either
+//!   to a mailbox or to an `erl_tracer` tracer module
 //!
 //! ## Architecture
 //!
@@ -32,5 +33,5 @@ pub mod trace_nif;
 pub mod tracer;
 
 pub use trace_nif::TraceNif;
-pub use tracer::Tracer;
+pub use tracer::{TraceStatus, TraceTag, Tracer, TracerModule};
 
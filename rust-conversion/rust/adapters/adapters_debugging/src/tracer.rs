@@ -1,25 +1,265 @@
 //! Tracer Module
 //!
-//! Provides tracer operations.
-//! Based on erl_tracer_nif.c
+//! Provides tracer operations. A [`Tracer`] receives the call trace events
+//! emitted by the emulator loop and either keeps them until they are taken,
+//! as a tracer process's mailbox would, or dispatches them to a tracer module.
+//!
+//! A tracer module implements the `erl_tracer` behaviour: for every event,
+//! `enabled/3` decides whether to `trace`, `discard` or `remove`, and `trace/5`
+//! delivers the event. Both callbacks get the tracer state given when the
+//! tracer was set up (`{Module, State}` as the tracer of `erlang:trace/3`).
+//! Tracer modules are normally implemented as NIFs, so [`TracerModule`] is
+//! the Rust side of those NIF callbacks.
+//!
+//! Based on erl_tracer_nif.c and the tracer dispatch in erl_trace.c
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use entities_data_handling::term_hashing::Term;
+use entities_process::ProcessId;
+use infrastructure_emulator_loop::{CallTracer, TraceEvent};
+
+/// Trace tag passed to the `erl_tracer` callbacks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceTag {
+    /// `call`
+    Call,
+    /// `return_to`
+    ReturnTo,
+    /// `trace_status`: asks whether the tracer is still alive
+    TraceStatus,
+}
+
+impl TraceTag {
+    /// The tag of a trace event
+    pub fn of(event: &TraceEvent) -> Self {
+        match event {
+            TraceEvent::Call { .. } => TraceTag::Call,
+            TraceEvent::ReturnTo { .. } => TraceTag::ReturnTo,
+        }
+    }
+
+    /// Atom name of the tag
+    pub fn name(self) -> &'static str {
+        match self {
+            TraceTag::Call => "call",
+            TraceTag::ReturnTo => "return_to",
+            TraceTag::TraceStatus => "trace_status",
+        }
+    }
+}
+
+/// Answer of the `enabled/3` callback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceStatus {
+    /// Call `trace/5` with the event
+    Trace,
+    /// Drop this event
+    Discard,
+    /// Drop this event and remove the tracer
+    Remove,
+}
+
+/// Callbacks of the `erl_tracer` behaviour
+pub trait TracerModule: Send + Sync {
+    /// `enabled(TraceTag, TracerState, Tracee)`
+    fn enabled(&self, tag: TraceTag, state: &Term, tracee: ProcessId) -> TraceStatus;
+
+    /// `trace(TraceTag, TracerState, Tracee, TraceTerm, Opts)`
+    ///
+    /// The emulator loop does not produce per-event options (timestamps,
+    /// `extra`, match spec results), so `Opts` is always empty.
+    fn trace(&self, tag: TraceTag, state: &Term, tracee: ProcessId, event: &TraceEvent);
+}
+
+/// Where a tracer delivers events
+enum Backend {
+    /// Keep events until taken, like a tracer process's mailbox
+    Mailbox(Mutex<Vec<TraceEvent>>),
+    /// Dispatch events to a tracer module with its state
+    Module {
+        module: Arc<dyn TracerModule>,
+        state: Term,
+    },
+}
 
 /// Tracer operations
-pub struct Tracer;
+pub struct Tracer {
+    backend: Backend,
+    /// Set once a tracer module has answered `remove`
+    removed: AtomicBool,
+}
 
 impl Tracer {
-    /// Create a new tracer
+    /// Create a new tracer that keeps events until taken
     pub fn new() -> Self {
-        Self
+        Self {
+            backend: Backend::Mailbox(Mutex::new(Vec::new())),
+            removed: AtomicBool::new(false),
+        }
+    }
+
+    /// Create a tracer that dispatches events to a tracer module
+    ///
+    /// # Arguments
+    /// * `module` - The `erl_tracer` callbacks
+    /// * `state` - Tracer state passed to every callback
+    pub fn with_module(module: Arc<dyn TracerModule>, state: Term) -> Self {
+        Self {
+            backend: Backend::Module { module, state },
+            removed: AtomicBool::new(false),
+        }
+    }
+
+    /// Get the number of trace events waiting to be taken
+    ///
+    /// Always 0 for a tracer module, which gets events as they happen.
+    pub fn pending(&self) -> usize {
+        match &self.backend {
+            Backend::Mailbox(events) => events.lock().unwrap().len(),
+            Backend::Module { .. } => 0,
+        }
+    }
+
+    /// Take the trace events received so far, in order of arrival
+    pub fn take_events(&self) -> Vec<TraceEvent> {
+        match &self.backend {
+            Backend::Mailbox(events) => std::mem::take(&mut events.lock().unwrap()),
+            Backend::Module { .. } => Vec::new(),
+        }
+    }
+
+    /// Check whether a tracer module has asked to be removed
+    ///
+    /// A removed tracer drops all further events.
+    pub fn is_removed(&self) -> bool {
+        self.removed.load(Ordering::Acquire)
+    }
+
+    /// Ask the tracer whether it is still alive (`enabled(trace_status, ...)`)
+    ///
+    /// A tracer that keeps events always answers [`TraceStatus::Trace`].
+    pub fn trace_status(&self, tracee: ProcessId) -> TraceStatus {
+        self.enabled(TraceTag::TraceStatus, tracee)
+    }
+
+    fn enabled(&self, tag: TraceTag, tracee: ProcessId) -> TraceStatus {
+        if self.is_removed() {
+            return TraceStatus::Remove;
+        }
+        let Backend::Module { module, state } = &self.backend else {
+            return TraceStatus::Trace;
+        };
+        let status = module.enabled(tag, state, tracee);
+        if status == TraceStatus::Remove {
+            self.removed.store(true, Ordering::Release);
+        }
+        status
+    }
+}
+
+impl Default for Tracer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CallTracer for Tracer {
+    fn trace(&self, event: TraceEvent) {
+        let tag = TraceTag::of(&event);
+        let tracee = match &event {
+            TraceEvent::Call { pid, .. } | TraceEvent::ReturnTo { pid, .. } => *pid,
+        };
+        if self.enabled(tag, tracee) != TraceStatus::Trace {
+            return;
+        }
+        match &self.backend {
+            Backend::Mailbox(events) => events.lock().unwrap().push(event),
+            Backend::Module { module, state } => module.trace(tag, state, tracee, &event),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use infrastructure_emulator_loop::CodeMfa;
+
+    /// Tracer module that counts traced events in its state and discards
+    /// `return_to`, asking to be removed after `limit` calls
+    struct CountingModule {
+        limit: usize,
+        traced: Mutex<Vec<(Term, ProcessId, TraceEvent)>>,
+    }
+
+    impl TracerModule for CountingModule {
+        fn enabled(&self, tag: TraceTag, _state: &Term, _tracee: ProcessId) -> TraceStatus {
+            match tag {
+                TraceTag::ReturnTo => TraceStatus::Discard,
+                _ if self.traced.lock().unwrap().len() >= self.limit => TraceStatus::Remove,
+                _ => TraceStatus::Trace,
+            }
+        }
+
+        fn trace(&self, _tag: TraceTag, state: &Term, tracee: ProcessId, event: &TraceEvent) {
+            self.traced.lock().unwrap().push((state.clone(), tracee, event.clone()));
+        }
+    }
+
+    fn call(pid: ProcessId) -> TraceEvent {
+        TraceEvent::Call { pid, mfa: CodeMfa::new(2, 3, 0), args: Vec::new() }
+    }
 
     #[test]
     fn test_tracer() {
         let _tracer = Tracer::new();
     }
-}
 
+    #[test]
+    fn test_tracer_collects_events() {
+        let tracer = Tracer::new();
+        tracer.trace(TraceEvent::Call { pid: 1, mfa: CodeMfa::new(2, 3, 0), args: Vec::new() });
+        tracer.trace(TraceEvent::ReturnTo { pid: 1, mfa: None });
+        assert_eq!(tracer.pending(), 2);
+
+        let events = tracer.take_events();
+        assert_eq!(events[1], TraceEvent::ReturnTo { pid: 1, mfa: None });
+        assert_eq!(tracer.pending(), 0);
+        assert_eq!(tracer.trace_status(1), TraceStatus::Trace);
+    }
+
+    #[test]
+    fn test_tracer_module_gets_state_and_events() {
+        let module = Arc::new(CountingModule { limit: 10, traced: Mutex::new(Vec::new()) });
+        let tracer = Tracer::with_module(module.clone(), Term::Small(7));
+        tracer.trace(call(1));
+        tracer.trace(TraceEvent::ReturnTo { pid: 1, mfa: None });
+        tracer.trace(call(2));
+
+        let traced = module.traced.lock().unwrap();
+        assert_eq!(traced.len(), 2);
+        assert_eq!(traced[0], (Term::Small(7), 1, call(1)));
+        assert_eq!(traced[1].1, 2);
+        assert_eq!(tracer.pending(), 0);
+    }
+
+    #[test]
+    fn test_tracer_module_remove() {
+        let module = Arc::new(CountingModule { limit: 1, traced: Mutex::new(Vec::new()) });
+        let tracer = Tracer::with_module(module.clone(), Term::Nil);
+        tracer.trace(call(1));
+        assert!(!tracer.is_removed());
+        tracer.trace(call(1));
+        assert!(tracer.is_removed());
+        tracer.trace(call(1));
+        assert_eq!(module.traced.lock().unwrap().len(), 1);
+        assert_eq!(tracer.trace_status(1), TraceStatus::Remove);
+    }
+
+    #[test]
+    fn test_trace_tag_names() {
+        assert_eq!(TraceTag::of(&call(1)).name(), "call");
+        assert_eq!(TraceTag::TraceStatus.name(), "trace_status");
+    }
+}
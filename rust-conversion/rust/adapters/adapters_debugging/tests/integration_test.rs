@@ -18,3 +18,96 @@ fn test_trace_nif_operations() {
     // Should not panic
 }
 
+
+#[test]
+fn test_tracer_receives_emulator_call_trace() {
+    use entities_process::Process;
+    use infrastructure_emulator_loop::{
+        opcodes, process_main, CallTrace, CodeMfa, EmulatorLoop, TraceEvent, TracePattern,
+    };
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+    use usecases_bifs::trace::{TraceBif, TraceFlags, TraceTarget};
+
+    // caller/0 calls callee/1 in module 1, then both return
+    let code: Vec<u8> = vec![
+        opcodes::FUNC_INFO, 1, 10, 0,
+        opcodes::CALL, 1, 8,
+        opcodes::RETURN,
+        opcodes::FUNC_INFO, 1, 20, 1,
+        opcodes::RETURN,
+    ];
+    let flags = TraceFlags { call: true, return_to: true, ..Default::default() };
+    TraceBif::trace(None, TraceTarget::Process(40685), true, flags).unwrap();
+
+    let tracer = Arc::new(Tracer::new());
+    let call_trace = Arc::new(CallTrace::new());
+    call_trace.set_tracer(Some(tracer.clone()));
+    call_trace.set_trace_pattern(TracePattern::new(1, Some(20), Some(1)));
+
+    let mut emulator_loop = EmulatorLoop::new();
+    emulator_loop.set_call_trace(call_trace);
+    emulator_loop.set_current_process(Some(Arc::new(Process::new(40685))));
+    emulator_loop.set_current_mfa(Some(CodeMfa::new(1, 10, 0)));
+    emulator_loop.set_instruction_ptr(unsafe { code.as_ptr().add(4) });
+    process_main(&mut emulator_loop, Arc::new(AtomicBool::new(true))).unwrap();
+
+    let events = tracer.take_events();
+    assert_eq!(events.len(), 2);
+    assert!(matches!(&events[0], TraceEvent::Call { pid: 40685, mfa, .. } if *mfa == CodeMfa::new(1, 20, 1)));
+    assert_eq!(events[1], TraceEvent::ReturnTo { pid: 40685, mfa: Some(CodeMfa::new(1, 10, 0)) });
+}
+
+#[test]
+fn test_tracer_module_receives_emulator_call_trace_with_state() {
+    use entities_data_handling::term_hashing::Term;
+    use entities_process::{Process, ProcessId};
+    use infrastructure_emulator_loop::{
+        opcodes, process_main, CallTrace, CodeMfa, EmulatorLoop, TraceEvent, TracePattern,
+    };
+    use std::sync::atomic::AtomicBool;
+    use std::sync::{Arc, Mutex};
+    use usecases_bifs::trace::{TraceBif, TraceFlags, TraceTarget};
+
+    /// Tracer module that only wants `call` events
+    struct CallsOnly(Mutex<Vec<(Term, ProcessId, TraceEvent)>>);
+
+    impl TracerModule for CallsOnly {
+        fn enabled(&self, tag: TraceTag, _state: &Term, _tracee: ProcessId) -> TraceStatus {
+            if tag == TraceTag::ReturnTo { TraceStatus::Discard } else { TraceStatus::Trace }
+        }
+
+        fn trace(&self, _tag: TraceTag, state: &Term, tracee: ProcessId, event: &TraceEvent) {
+            self.0.lock().unwrap().push((state.clone(), tracee, event.clone()));
+        }
+    }
+
+    let code: Vec<u8> = vec![
+        opcodes::FUNC_INFO, 1, 10, 0,
+        opcodes::CALL, 1, 8,
+        opcodes::RETURN,
+        opcodes::FUNC_INFO, 1, 20, 1,
+        opcodes::RETURN,
+    ];
+    let flags = TraceFlags { call: true, return_to: true, ..Default::default() };
+    TraceBif::trace(None, TraceTarget::Process(40686), true, flags).unwrap();
+
+    let module = Arc::new(CallsOnly(Mutex::new(Vec::new())));
+    let tracer = Arc::new(Tracer::with_module(module.clone(), Term::Small(99)));
+    let call_trace = Arc::new(CallTrace::new());
+    call_trace.set_tracer(Some(tracer.clone()));
+    call_trace.set_trace_pattern(TracePattern::new(1, Some(20), Some(1)));
+
+    let mut emulator_loop = EmulatorLoop::new();
+    emulator_loop.set_call_trace(call_trace);
+    emulator_loop.set_current_process(Some(Arc::new(Process::new(40686))));
+    emulator_loop.set_current_mfa(Some(CodeMfa::new(1, 10, 0)));
+    emulator_loop.set_instruction_ptr(unsafe { code.as_ptr().add(4) });
+    process_main(&mut emulator_loop, Arc::new(AtomicBool::new(true))).unwrap();
+
+    let traced = module.0.lock().unwrap();
+    assert_eq!(traced.len(), 1);
+    assert_eq!((&traced[0].0, traced[0].1), (&Term::Small(99), 40686));
+    assert!(matches!(&traced[0].2, TraceEvent::Call { mfa, .. } if *mfa == CodeMfa::new(1, 20, 1)));
+    assert_eq!(tracer.pending(), 0);
+}
@@ -0,0 +1,1166 @@
+//! C-Node Module
+//!
+//! Provides the C-node side of the Erlang distribution protocol, the equivalent
+//! of `ei_connect` in erl_interface. A Rust program can join an Erlang cluster
+//! as a hidden node: connect to Erlang nodes and accept their connections,
+//! exchange messages with their processes, and make and serve `rpc` calls
+//! through `rex`.
+//!
+//! ## Overview
+//!
+//! - [`CNode`]: The local node, its name, cookie and creation (`ei_cnode`)
+//! - [`Connection`]: An established connection to another node
+//! - [`ErlMessage`]: A message received on a connection (`erlang_msg`)
+//! - [`RpcRequest`]: An `rpc` request sent to the `rex` server of this node
+//! - [`epmd_port_please`] and [`epmd_publish`]: EPMD lookup and registration
+//!
+//! ## Protocol
+//!
+//! Connections are set up with the version 6 handshake of OTP 23 and later,
+//! in which each side proves it knows the cookie with the MD5 digest of the
+//! cookie and a random challenge. Distribution messages then have 4-byte
+//! big-endian length headers, and a length of zero is a tick, which
+//! [`Connection::receive`] answers. As the node does not use the atom cache,
+//! messages are pass-through: a control tuple followed by the message, each in
+//! external format.
+//!
+//! Terms are built and read with [`EiBuffer`]; message buffers start with the
+//! version byte, as made by [`EiBuffer::new_with_version`].
+//!
+//! ## Examples
+//!
+//! ```rust,no_run
+//! use adapters_distribution::cnode::CNode;
+//! use infrastructure_code_loading::EiBuffer;
+//!
+//! let node = CNode::new("rust@localhost", "secret", 1)?;
+//! let mut connection = node.connect("erl@localhost")?;
+//!
+//! let mut args = EiBuffer::new_with_version();
+//! args.encode_empty_list()?;
+//! let mut reply = connection.rpc("erlang", "node", &args)?;
+//! reply.decode_version()?;
+//! assert_eq!(reply.decode_atom()?, "erl@localhost");
+//! # Ok::<(), adapters_distribution::cnode::CNodeError>(())
+//! ```
+//!
+//! ## See Also
+//!
+//! - [`uds`](super::uds/index.html): Distribution over Unix Domain Sockets
+//! - [`ei_buffer`](../../infrastructure_code_loading/ei_buffer/index.html): Encoding and decoding message terms
+//!
+//! Based on `ei_connect.c`, `epmd_port.c` and `epmd_publish.c` in
+//! `lib/erl_interface/src/`
+
+use std::io::{self, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, ToSocketAddrs};
+use std::time::Duration;
+use adapters_socket::{AddressFamily, SocketError, TcpSocket};
+use infrastructure_code_loading::constants::ERL_VERSION;
+use infrastructure_code_loading::decode_skip::skip_term;
+use infrastructure_code_loading::{EiBuffer, EiBufferError, ErlangPid};
+
+/// Default EPMD port, used unless `ERL_EPMD_PORT` is set
+pub const EPMD_PORT: u16 = 4369;
+
+/// Highest distribution version published to EPMD
+const DIST_HIGH_VERSION: u16 = 6;
+/// Lowest distribution version published to EPMD; version 5 name messages
+/// are accepted from nodes that also support version 6
+const DIST_LOW_VERSION: u16 = 5;
+
+const EPMD_ALIVE2_X_RESP: u8 = 118;
+const EPMD_PORT2_RESP: u8 = 119;
+const EPMD_ALIVE2_REQ: u8 = 120;
+const EPMD_ALIVE2_RESP: u8 = 121;
+const EPMD_PORT2_REQ: u8 = 122;
+/// EPMD node type of hidden nodes
+const EPMD_HIDDEN_NODE: u8 = 72;
+/// EPMD protocol of TCP/IP
+const EPMD_PROTOCOL_TCP: u8 = 0;
+
+/// Distribution capability flags (`DFLAG_*` in dist.h)
+pub const DFLAG_PUBLISHED: u64 = 0x01;
+pub const DFLAG_EXTENDED_REFERENCES: u64 = 0x04;
+pub const DFLAG_DIST_MONITOR: u64 = 0x08;
+pub const DFLAG_FUN_TAGS: u64 = 0x10;
+pub const DFLAG_NEW_FUN_TAGS: u64 = 0x80;
+pub const DFLAG_EXTENDED_PIDS_PORTS: u64 = 0x100;
+pub const DFLAG_EXPORT_PTR_TAG: u64 = 0x200;
+pub const DFLAG_BIT_BINARIES: u64 = 0x400;
+pub const DFLAG_NEW_FLOATS: u64 = 0x800;
+pub const DFLAG_UNICODE_IO: u64 = 0x1000;
+pub const DFLAG_SMALL_ATOM_TAGS: u64 = 0x4000;
+pub const DFLAG_UTF8_ATOMS: u64 = 0x10000;
+pub const DFLAG_MAP_TAG: u64 = 0x20000;
+pub const DFLAG_BIG_CREATION: u64 = 0x40000;
+pub const DFLAG_HANDSHAKE_23: u64 = 0x1000000;
+pub const DFLAG_UNLINK_ID: u64 = 0x2000000;
+pub const DFLAG_V4_NC: u64 = 1 << 34;
+
+/// Capabilities every peer must have, as required since OTP 25
+const DFLAG_MANDATORY: u64 = DFLAG_EXTENDED_REFERENCES
+    | DFLAG_FUN_TAGS
+    | DFLAG_NEW_FUN_TAGS
+    | DFLAG_EXTENDED_PIDS_PORTS
+    | DFLAG_EXPORT_PTR_TAG
+    | DFLAG_BIT_BINARIES
+    | DFLAG_NEW_FLOATS
+    | DFLAG_UTF8_ATOMS
+    | DFLAG_MAP_TAG
+    | DFLAG_BIG_CREATION
+    | DFLAG_HANDSHAKE_23;
+
+/// Capabilities of this node; without `DFLAG_PUBLISHED` it is hidden
+const CNODE_FLAGS: u64 = DFLAG_MANDATORY
+    | DFLAG_DIST_MONITOR
+    | DFLAG_UNICODE_IO
+    | DFLAG_SMALL_ATOM_TAGS
+    | DFLAG_UNLINK_ID
+    | DFLAG_V4_NC;
+
+/// Tag of pass-through distribution messages
+const PASS_THROUGH: u8 = 112;
+
+// Control message operations (`DOP_*` in dist.h)
+const DOP_LINK: i64 = 1;
+const DOP_SEND: i64 = 2;
+const DOP_EXIT: i64 = 3;
+const DOP_UNLINK: i64 = 4;
+const DOP_REG_SEND: i64 = 6;
+const DOP_EXIT2: i64 = 8;
+const DOP_SEND_TT: i64 = 12;
+const DOP_EXIT_TT: i64 = 13;
+const DOP_REG_SEND_TT: i64 = 16;
+const DOP_EXIT2_TT: i64 = 18;
+const DOP_SEND_SENDER: i64 = 22;
+const DOP_SEND_SENDER_TT: i64 = 23;
+const DOP_UNLINK_ID: i64 = 35;
+const DOP_UNLINK_ID_ACK: i64 = 36;
+
+/// C-node error types
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CNodeError {
+    /// Node name not of the form `alive@host`, or host not found
+    InvalidNodeName(String),
+    /// Socket error
+    Socket(SocketError),
+    /// EPMD request failed
+    Epmd(String),
+    /// Connection setup failed
+    Handshake(String),
+    /// The peer proved a different cookie
+    BadCookie,
+    /// The peer closed the connection
+    ConnectionClosed,
+    /// Nothing was received within the timeout
+    Timeout,
+    /// Malformed distribution message
+    Protocol(String),
+    /// Term encoding or decoding failed
+    Ei(EiBufferError),
+    /// An `rpc` reply was expected
+    UnexpectedMessage,
+}
+
+impl From<SocketError> for CNodeError {
+    fn from(err: SocketError) -> Self {
+        CNodeError::Socket(err)
+    }
+}
+
+impl From<io::Error> for CNodeError {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::UnexpectedEof => CNodeError::ConnectionClosed,
+            // A blocking socket would only block past its timeout
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => CNodeError::Timeout,
+            _ => CNodeError::Socket(err.into()),
+        }
+    }
+}
+
+impl From<EiBufferError> for CNodeError {
+    fn from(err: EiBufferError) -> Self {
+        CNodeError::Ei(err)
+    }
+}
+
+/// Kind of a received message (`msgtype` of `erlang_msg`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageType {
+    /// Message sent to a process identifier (`ERL_SEND`)
+    Send,
+    /// Message sent to a registered name (`ERL_REG_SEND`)
+    RegSend,
+    /// Link request (`ERL_LINK`)
+    Link,
+    /// Unlink request (`ERL_UNLINK`)
+    Unlink,
+    /// Exit signal from a linked process (`ERL_EXIT`)
+    Exit,
+    /// Exit signal sent with `exit/2` (`ERL_EXIT2`)
+    Exit2,
+    /// Any other control message, by operation number
+    Other(i64),
+}
+
+/// Message received on a connection (`erlang_msg`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErlMessage {
+    /// Kind of message
+    pub msg_type: MessageType,
+    /// Sending process, if the control message names it
+    pub from: Option<ErlangPid>,
+    /// Receiving process, unless sent to a registered name
+    pub to: Option<ErlangPid>,
+    /// Receiving registered name
+    pub to_name: Option<String>,
+    /// The message, or the exit reason, starting with the version byte;
+    /// empty for link and unlink requests
+    pub payload: EiBuffer,
+}
+
+/// The local node (`ei_cnode`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CNode {
+    name: String,
+    cookie: String,
+    creation: u32,
+    epmd_port: u16,
+    timeout: Option<Duration>,
+}
+
+/// Registration of a node with EPMD, kept while this value lives
+pub struct EpmdRegistration {
+    _socket: TcpSocket,
+    creation: u32,
+}
+
+/// Connection to another node
+pub struct Connection {
+    socket: TcpSocket,
+    self_pid: ErlangPid,
+    peer_node: String,
+    peer_flags: u64,
+    peer_creation: u32,
+}
+
+/// An `rpc` request `{From, {call, Module, Function, Args, GroupLeader}}`
+/// sent to the `rex` server of this node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpcRequest {
+    /// Process waiting for the reply
+    pub from: ErlangPid,
+    /// Module name
+    pub module: String,
+    /// Function name
+    pub function: String,
+    /// Argument list, starting with the version byte
+    pub args: EiBuffer,
+}
+
+/// Name, capabilities and creation of the peer, from the handshake
+struct Peer {
+    name: String,
+    flags: u64,
+    creation: u32,
+}
+
+impl CNode {
+    /// Create a local node (`ei_connect_init`)
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Node name, `alive@host`
+    /// * `cookie` - Cookie shared with the nodes to connect to
+    /// * `creation` - Incarnation of the node; replaced by [`publish`](Self::publish)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CNode)` - The node
+    /// * `Err(CNodeError)` - Invalid node name
+    pub fn new(name: &str, cookie: &str, creation: u32) -> Result<Self, CNodeError> {
+        split_node_name(name)?;
+        let epmd_port = std::env::var("ERL_EPMD_PORT")
+            .ok()
+            .and_then(|port| port.parse().ok())
+            .unwrap_or(EPMD_PORT);
+        Ok(Self {
+            name: name.to_string(),
+            cookie: cookie.to_string(),
+            creation,
+            epmd_port,
+            timeout: None,
+        })
+    }
+
+    /// Get the node name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the alive part of the node name
+    pub fn alive(&self) -> &str {
+        self.name.split_once('@').map_or(&self.name, |(alive, _)| alive)
+    }
+
+    /// Get the cookie
+    pub fn cookie(&self) -> &str {
+        &self.cookie
+    }
+
+    /// Get the creation
+    pub fn creation(&self) -> u32 {
+        self.creation
+    }
+
+    /// Set the port EPMD listens on
+    pub fn set_epmd_port(&mut self, port: u16) {
+        self.epmd_port = port;
+    }
+
+    /// Limit how long setting up a connection may wait (`ei_connect_tmo`)
+    ///
+    /// Applies to each step of [`connect`](Self::connect), the EPMD lookup,
+    /// connecting and each message of the handshake, and to the handshake
+    /// of [`accept`](Self::accept); `None` waits forever.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Get the process identifier messages from this node are sent from
+    pub fn self_pid(&self) -> ErlangPid {
+        ErlangPid {
+            node: self.name.clone(),
+            num: 0,
+            serial: 0,
+            creation: self.creation,
+        }
+    }
+
+    /// Register the node with the local EPMD (`ei_publish`)
+    ///
+    /// The node takes the creation EPMD assigns. It stays registered until
+    /// the returned registration is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `port` - Port the node accepts connections on
+    pub fn publish(&mut self, port: u16) -> Result<EpmdRegistration, CNodeError> {
+        let epmd = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), self.epmd_port);
+        let registration = epmd_publish(&epmd, self.alive(), port)?;
+        self.creation = registration.creation;
+        Ok(registration)
+    }
+
+    /// Create a socket listening for connections (`ei_listen`)
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - Address to listen on; port 0 picks a free port
+    pub fn listen(&self, addr: &SocketAddr) -> Result<TcpSocket, CNodeError> {
+        let socket = TcpSocket::new(address_family(addr))?;
+        socket.inner().inner().set_nonblocking(false)?;
+        socket.inner().set_reuse_address(true)?;
+        socket.bind(addr)?;
+        socket.listen(5)?;
+        Ok(socket)
+    }
+
+    /// Accept a connection from another node (`ei_accept`)
+    ///
+    /// Blocks until a node connects to `listener`, made by
+    /// [`listen`](Self::listen), and completes the handshake.
+    pub fn accept(&self, listener: &TcpSocket) -> Result<Connection, CNodeError> {
+        let (mut socket, _) = listener.accept()?;
+        socket.inner().inner().set_nonblocking(false)?;
+        socket.inner().inner().set_read_timeout(self.timeout)?;
+        let peer = self.handshake_accept(&mut socket)?;
+        Ok(self.connection(socket, peer))
+    }
+
+    /// Connect to a node, found through EPMD on its host (`ei_connect`)
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - Node name, `alive@host`
+    pub fn connect(&self, node: &str) -> Result<Connection, CNodeError> {
+        let (alive, host) = split_node_name(node)?;
+        let epmd = (host, self.epmd_port)
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut addrs| addrs.next())
+            .ok_or_else(|| CNodeError::InvalidNodeName(node.to_string()))?;
+        let port = port_please(&epmd, alive, self.timeout)?;
+        self.connect_addr(&SocketAddr::new(epmd.ip(), port), node)
+    }
+
+    /// Connect to a node at a known address (`ei_xconnect`)
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - Address the node accepts connections on
+    /// * `node` - Node name, `alive@host`
+    pub fn connect_addr(&self, addr: &SocketAddr, node: &str) -> Result<Connection, CNodeError> {
+        let mut socket = tcp_connect(addr, self.timeout)?;
+        let peer = self.handshake_initiate(&mut socket, node)?;
+        Ok(self.connection(socket, peer))
+    }
+
+    fn connection(&self, socket: TcpSocket, peer: Peer) -> Connection {
+        // The setup timeout does not carry over to messages
+        let _ = socket.inner().inner().set_read_timeout(None);
+        let _ = socket.inner().inner().set_write_timeout(None);
+        // Distribution messages are small and latency bound
+        let _ = socket.inner().inner().set_nodelay(true);
+        Connection {
+            socket,
+            self_pid: self.self_pid(),
+            peer_node: peer.name,
+            peer_flags: peer.flags,
+            peer_creation: peer.creation,
+        }
+    }
+
+    /// Set up a connection as the initiating node
+    fn handshake_initiate(&self, socket: &mut TcpSocket, node: &str) -> Result<Peer, CNodeError> {
+        let mut send_name = vec![b'N'];
+        send_name.extend_from_slice(&CNODE_FLAGS.to_be_bytes());
+        send_name.extend_from_slice(&self.creation.to_be_bytes());
+        send_name.extend_from_slice(&(self.name.len() as u16).to_be_bytes());
+        send_name.extend_from_slice(self.name.as_bytes());
+        write_packet2(socket, &send_name)?;
+
+        let status = read_packet2(socket)?;
+        match status.split_first() {
+            Some((b's', b"ok" | b"ok_simultaneous")) => {}
+            // The peer still has a connection from this node; replace it
+            Some((b's', b"alive")) => write_packet2(socket, b"strue")?,
+            Some((b's', status)) => {
+                return Err(CNodeError::Handshake(format!(
+                    "{} refused the connection: {}",
+                    node,
+                    String::from_utf8_lossy(status)
+                )));
+            }
+            _ => return Err(CNodeError::Handshake("expected a status message".to_string())),
+        }
+
+        // 'N', Flags:8, Challenge:4, Creation:4, NameLength:2, Name
+        let challenge = read_packet2(socket)?;
+        if challenge.len() < 19 || challenge[0] != b'N' {
+            return Err(CNodeError::Handshake("expected a challenge message".to_string()));
+        }
+        let flags = be_u64(&challenge[1..9]);
+        let their_challenge = be_u32(&challenge[9..13]);
+        let creation = be_u32(&challenge[13..17]);
+        let name = read_name(&challenge[17..])?;
+        if name != node {
+            return Err(CNodeError::Handshake(format!("connected to {} instead of {}", name, node)));
+        }
+        check_flags(&name, flags)?;
+
+        let our_challenge = gen_challenge()?;
+        let mut reply = vec![b'r'];
+        reply.extend_from_slice(&our_challenge.to_be_bytes());
+        reply.extend_from_slice(&gen_digest(their_challenge, &self.cookie));
+        write_packet2(socket, &reply)?;
+
+        let ack = read_packet2(socket)?;
+        if ack.len() != 17 || ack[0] != b'a' {
+            return Err(CNodeError::Handshake("expected a challenge acknowledgement".to_string()));
+        }
+        if ack[1..] != gen_digest(our_challenge, &self.cookie) {
+            return Err(CNodeError::BadCookie);
+        }
+        Ok(Peer { name, flags, creation })
+    }
+
+    /// Set up a connection as the accepting node
+    fn handshake_accept(&self, socket: &mut TcpSocket) -> Result<Peer, CNodeError> {
+        let send_name = read_packet2(socket)?;
+        let (mut flags, mut creation, name) = match send_name.first() {
+            // 'N', Flags:8, Creation:4, NameLength:2, Name
+            Some(b'N') if send_name.len() >= 15 => (
+                be_u64(&send_name[1..9]),
+                be_u32(&send_name[9..13]),
+                read_name(&send_name[13..])?,
+            ),
+            // 'n', Version:2, Flags:4, Name; completed by a complement message
+            Some(b'n') if send_name.len() >= 7 => (
+                u64::from(be_u32(&send_name[3..7])),
+                0,
+                String::from_utf8_lossy(&send_name[7..]).into_owned(),
+            ),
+            _ => return Err(CNodeError::Handshake("expected a name message".to_string())),
+        };
+        if let Err(err) = check_flags(&name, flags) {
+            let _ = write_packet2(socket, b"snot_allowed");
+            return Err(err);
+        }
+        write_packet2(socket, b"sok")?;
+
+        let our_challenge = gen_challenge()?;
+        let mut challenge = vec![b'N'];
+        challenge.extend_from_slice(&CNODE_FLAGS.to_be_bytes());
+        challenge.extend_from_slice(&our_challenge.to_be_bytes());
+        challenge.extend_from_slice(&self.creation.to_be_bytes());
+        challenge.extend_from_slice(&(self.name.len() as u16).to_be_bytes());
+        challenge.extend_from_slice(self.name.as_bytes());
+        write_packet2(socket, &challenge)?;
+
+        let mut reply = read_packet2(socket)?;
+        if send_name[0] == b'n' {
+            // 'c', FlagsHigh:4, Creation:4
+            if reply.len() != 9 || reply[0] != b'c' {
+                return Err(CNodeError::Handshake("expected a complement message".to_string()));
+            }
+            flags |= u64::from(be_u32(&reply[1..5])) << 32;
+            creation = be_u32(&reply[5..9]);
+            reply = read_packet2(socket)?;
+        }
+        // 'r', Challenge:4, Digest:16
+        if reply.len() != 21 || reply[0] != b'r' {
+            return Err(CNodeError::Handshake("expected a challenge reply".to_string()));
+        }
+        if reply[5..] != gen_digest(our_challenge, &self.cookie) {
+            return Err(CNodeError::BadCookie);
+        }
+
+        let mut ack = vec![b'a'];
+        ack.extend_from_slice(&gen_digest(be_u32(&reply[1..5]), &self.cookie));
+        write_packet2(socket, &ack)?;
+        Ok(Peer { name, flags, creation })
+    }
+}
+
+impl EpmdRegistration {
+    /// Get the creation EPMD assigned to the node
+    pub fn creation(&self) -> u32 {
+        self.creation
+    }
+}
+
+impl Connection {
+    /// Get the name of the connected node
+    pub fn peer_node(&self) -> &str {
+        &self.peer_node
+    }
+
+    /// Get the capability flags of the connected node
+    pub fn peer_flags(&self) -> u64 {
+        self.peer_flags
+    }
+
+    /// Get the creation of the connected node
+    pub fn peer_creation(&self) -> u32 {
+        self.peer_creation
+    }
+
+    /// Get the process identifier messages are sent from
+    pub fn self_pid(&self) -> &ErlangPid {
+        &self.self_pid
+    }
+
+    /// Limit how long [`receive`](Self::receive) waits; `None` waits forever
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> Result<(), CNodeError> {
+        self.socket.inner().inner().set_read_timeout(timeout)?;
+        Ok(())
+    }
+
+    /// Send a message to a process (`ei_send`)
+    ///
+    /// # Arguments
+    ///
+    /// * `to` - Receiving process
+    /// * `message` - The message, starting with the version byte
+    pub fn send(&mut self, to: &ErlangPid, message: &EiBuffer) -> Result<(), CNodeError> {
+        let mut control = EiBuffer::new_with_version();
+        control.encode_tuple_header(3)?;
+        control.encode_long(DOP_SEND)?;
+        control.encode_atom("")?;
+        control.encode_pid(to)?;
+        self.send_control(&control, Some(message))
+    }
+
+    /// Send a message to a registered name on the connected node (`ei_reg_send`)
+    ///
+    /// # Arguments
+    ///
+    /// * `to_name` - Receiving registered name
+    /// * `message` - The message, starting with the version byte
+    pub fn reg_send(&mut self, to_name: &str, message: &EiBuffer) -> Result<(), CNodeError> {
+        let mut control = EiBuffer::new_with_version();
+        control.encode_tuple_header(4)?;
+        control.encode_long(DOP_REG_SEND)?;
+        control.encode_pid(&self.self_pid)?;
+        control.encode_atom("")?;
+        control.encode_atom(to_name)?;
+        self.send_control(&control, Some(message))
+    }
+
+    /// Receive the next message (`ei_xreceive_msg`)
+    ///
+    /// Ticks are answered while waiting, so a connection must be read from
+    /// within the peer's net tick time to stay up. Unlink requests with an
+    /// identifier are acknowledged before they are returned.
+    pub fn receive(&mut self) -> Result<ErlMessage, CNodeError> {
+        loop {
+            let mut header = [0; 4];
+            self.socket.read_exact(&mut header)?;
+            let len = u32::from_be_bytes(header) as usize;
+            if len == 0 {
+                self.socket.write_all(&header)?;
+                continue;
+            }
+            let mut body = vec![0; len];
+            self.socket.read_exact(&mut body)?;
+            let (message, unlink_id) = parse_message(&body)?;
+            if let (Some(id), Some(from), Some(to)) = (unlink_id, &message.from, &message.to) {
+                let mut control = EiBuffer::new_with_version();
+                control.encode_tuple_header(4)?;
+                control.encode_long(DOP_UNLINK_ID_ACK)?;
+                control.encode_ulong(id)?;
+                control.encode_pid(to)?;
+                control.encode_pid(from)?;
+                self.send_control(&control, None)?;
+            }
+            return Ok(message);
+        }
+    }
+
+    /// Call a function on the connected node through `rex` (`ei_rpc`)
+    ///
+    /// # Arguments
+    ///
+    /// * `module` - Module name
+    /// * `function` - Function name
+    /// * `args` - Argument list, starting with the version byte
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(EiBuffer)` - The result, starting with the version byte
+    /// * `Err(CNodeError)` - Error, `UnexpectedMessage` if another message arrived first
+    pub fn rpc(&mut self, module: &str, function: &str, args: &EiBuffer) -> Result<EiBuffer, CNodeError> {
+        self.rpc_to(module, function, args)?;
+        self.rpc_from()
+    }
+
+    /// Send an `rpc` request to `rex` on the connected node (`ei_rpc_to`)
+    pub fn rpc_to(&mut self, module: &str, function: &str, args: &EiBuffer) -> Result<(), CNodeError> {
+        let mut request = EiBuffer::new_with_version();
+        request.encode_tuple_header(2)?;
+        request.encode_pid(&self.self_pid)?;
+        request.encode_tuple_header(5)?;
+        request.encode_atom("call")?;
+        request.encode_atom(module)?;
+        request.encode_atom(function)?;
+        append_term(&mut request, args)?;
+        request.encode_atom("user")?;
+        self.reg_send("rex", &request)
+    }
+
+    /// Receive the reply `{rex, Reply}` to an `rpc` request (`ei_rpc_from`)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(EiBuffer)` - `Reply`, starting with the version byte
+    /// * `Err(CNodeError)` - Error, `UnexpectedMessage` if another message arrived
+    pub fn rpc_from(&mut self) -> Result<EiBuffer, CNodeError> {
+        let message = self.receive()?;
+        if message.msg_type != MessageType::Send {
+            return Err(CNodeError::UnexpectedMessage);
+        }
+        let mut payload = message.payload;
+        if payload.decode_version().is_err()
+            || payload.decode_tuple_header() != Ok(2)
+            || payload.decode_atom().as_deref() != Ok("rex")
+        {
+            return Err(CNodeError::UnexpectedMessage);
+        }
+        let mut reply = vec![ERL_VERSION];
+        reply.extend_from_slice(&payload.as_bytes()[payload.index()..]);
+        Ok(EiBuffer::from_bytes(reply))
+    }
+
+    /// Reply `{rex, Reply}` to an `rpc` request received by this node
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request
+    /// * `reply` - The result, starting with the version byte
+    pub fn rpc_reply(&mut self, request: &RpcRequest, reply: &EiBuffer) -> Result<(), CNodeError> {
+        let mut message = EiBuffer::new_with_version();
+        message.encode_tuple_header(2)?;
+        message.encode_atom("rex")?;
+        append_term(&mut message, reply)?;
+        self.send(&request.from, &message)
+    }
+
+    /// Send a pass-through distribution message
+    fn send_control(&mut self, control: &EiBuffer, message: Option<&EiBuffer>) -> Result<(), CNodeError> {
+        let message = match message {
+            Some(message) => term_bytes(message)?,
+            None => &[],
+        };
+        let len = u32::try_from(1 + control.len() + message.len())
+            .map_err(|_| CNodeError::Protocol("message too large".to_string()))?;
+        let mut packet = Vec::with_capacity(4 + len as usize);
+        packet.extend_from_slice(&len.to_be_bytes());
+        packet.push(PASS_THROUGH);
+        packet.extend_from_slice(control.as_bytes());
+        packet.extend_from_slice(message);
+        self.socket.write_all(&packet)?;
+        Ok(())
+    }
+}
+
+impl RpcRequest {
+    /// Read an `rpc` request from a message sent to `rex`
+    ///
+    /// # Returns
+    ///
+    /// * `Some(RpcRequest)` - The request
+    /// * `None` - The message is not an `rpc` request
+    pub fn from_message(message: &ErlMessage) -> Option<Self> {
+        if message.msg_type != MessageType::RegSend || message.to_name.as_deref() != Some("rex") {
+            return None;
+        }
+        let mut payload = EiBuffer::from_bytes(message.payload.as_bytes().to_vec());
+        payload.decode_version().ok()?;
+        if payload.decode_tuple_header().ok()? != 2 {
+            return None;
+        }
+        let from = payload.decode_pid().ok()?;
+        if payload.decode_tuple_header().ok()? != 5 || payload.decode_atom().ok()? != "call" {
+            return None;
+        }
+        let module = payload.decode_atom().ok()?;
+        let function = payload.decode_atom().ok()?;
+        let start = payload.index();
+        payload.skip_term().ok()?;
+        let mut args = vec![ERL_VERSION];
+        args.extend_from_slice(&payload.as_bytes()[start..payload.index()]);
+        Some(Self { from, module, function, args: EiBuffer::from_bytes(args) })
+    }
+}
+
+/// Look up the port of a node with EPMD (`ei_epmd_port`)
+///
+/// # Arguments
+///
+/// * `epmd` - Address of EPMD on the host of the node
+/// * `alive` - Alive part of the node name
+///
+/// # Returns
+///
+/// * `Ok(u16)` - Port the node accepts connections on
+/// * `Err(CNodeError)` - Error, `Epmd` if the node is not registered
+pub fn epmd_port_please(epmd: &SocketAddr, alive: &str) -> Result<u16, CNodeError> {
+    port_please(epmd, alive, None)
+}
+
+fn port_please(epmd: &SocketAddr, alive: &str, timeout: Option<Duration>) -> Result<u16, CNodeError> {
+    let mut socket = tcp_connect(epmd, timeout)?;
+    let mut request = vec![EPMD_PORT2_REQ];
+    request.extend_from_slice(alive.as_bytes());
+    write_packet2(&mut socket, &request)?;
+
+    // 119, Result:1, then Port:2, NodeType:1, Protocol:1, HighestVersion:2, LowestVersion:2, ...
+    let mut reply = [0; 10];
+    socket.read_exact(&mut reply[..2])?;
+    if reply[0] != EPMD_PORT2_RESP {
+        return Err(CNodeError::Epmd(format!("unexpected reply {}", reply[0])));
+    }
+    if reply[1] != 0 {
+        return Err(CNodeError::Epmd(format!("{} is not registered", alive)));
+    }
+    socket.read_exact(&mut reply[2..])?;
+    if u16::from_be_bytes([reply[6], reply[7]]) < DIST_HIGH_VERSION {
+        return Err(CNodeError::Epmd(format!("{} does not support distribution version 6", alive)));
+    }
+    Ok(u16::from_be_bytes([reply[2], reply[3]]))
+}
+
+/// Register a hidden node with EPMD (`ei_publish`)
+///
+/// # Arguments
+///
+/// * `epmd` - Address of EPMD on the local host
+/// * `alive` - Alive part of the node name
+/// * `port` - Port the node accepts connections on
+///
+/// # Returns
+///
+/// * `Ok(EpmdRegistration)` - The registration, with the assigned creation
+/// * `Err(CNodeError)` - Error, `Epmd` if the name is taken
+pub fn epmd_publish(epmd: &SocketAddr, alive: &str, port: u16) -> Result<EpmdRegistration, CNodeError> {
+    let mut socket = tcp_connect(epmd, None)?;
+    let mut request = vec![EPMD_ALIVE2_REQ];
+    request.extend_from_slice(&port.to_be_bytes());
+    request.push(EPMD_HIDDEN_NODE);
+    request.push(EPMD_PROTOCOL_TCP);
+    request.extend_from_slice(&DIST_HIGH_VERSION.to_be_bytes());
+    request.extend_from_slice(&DIST_LOW_VERSION.to_be_bytes());
+    request.extend_from_slice(&(alive.len() as u16).to_be_bytes());
+    request.extend_from_slice(alive.as_bytes());
+    request.extend_from_slice(&0u16.to_be_bytes());
+    write_packet2(&mut socket, &request)?;
+
+    let mut reply = [0; 6];
+    socket.read_exact(&mut reply[..2])?;
+    if reply[0] != EPMD_ALIVE2_X_RESP && reply[0] != EPMD_ALIVE2_RESP {
+        return Err(CNodeError::Epmd(format!("unexpected reply {}", reply[0])));
+    }
+    if reply[1] != 0 {
+        return Err(CNodeError::Epmd(format!("{} could not be registered", alive)));
+    }
+    let creation = if reply[0] == EPMD_ALIVE2_X_RESP {
+        socket.read_exact(&mut reply[2..6])?;
+        be_u32(&reply[2..6])
+    } else {
+        socket.read_exact(&mut reply[2..4])?;
+        u32::from(u16::from_be_bytes([reply[2], reply[3]]))
+    };
+    Ok(EpmdRegistration { _socket: socket, creation })
+}
+
+/// Read a distribution message: its control tuple, and the message or exit
+/// reason; also the identifier of an unlink request to acknowledge
+fn parse_message(body: &[u8]) -> Result<(ErlMessage, Option<u64>), CNodeError> {
+    let protocol = |e| CNodeError::Protocol(format!("{:?}", e));
+    if body.first() != Some(&PASS_THROUGH) {
+        return Err(CNodeError::Protocol("expected a pass-through message".to_string()));
+    }
+    let bytes = &body[1..];
+    let mut control_end = 1;
+    skip_term(bytes, &mut control_end).map_err(protocol)?;
+
+    let mut control = EiBuffer::from_bytes(bytes[..control_end].to_vec());
+    control.decode_version()?;
+    control.decode_tuple_header()?;
+    let op = control.decode_long()?;
+    let mut message = ErlMessage {
+        msg_type: MessageType::Other(op),
+        from: None,
+        to: None,
+        to_name: None,
+        payload: EiBuffer::new(),
+    };
+    let mut unlink_id = None;
+    match op {
+        DOP_SEND | DOP_SEND_TT => {
+            control.skip_term()?;
+            message.msg_type = MessageType::Send;
+            message.to = Some(control.decode_pid()?);
+        }
+        DOP_SEND_SENDER | DOP_SEND_SENDER_TT => {
+            message.msg_type = MessageType::Send;
+            message.from = Some(control.decode_pid()?);
+            message.to = Some(control.decode_pid()?);
+        }
+        DOP_REG_SEND | DOP_REG_SEND_TT => {
+            message.msg_type = MessageType::RegSend;
+            message.from = Some(control.decode_pid()?);
+            control.skip_term()?;
+            message.to_name = Some(control.decode_atom()?);
+        }
+        DOP_LINK | DOP_UNLINK | DOP_UNLINK_ID => {
+            if op == DOP_UNLINK_ID {
+                unlink_id = Some(control.decode_ulong()?);
+            }
+            message.msg_type = if op == DOP_LINK { MessageType::Link } else { MessageType::Unlink };
+            message.from = Some(control.decode_pid()?);
+            message.to = Some(control.decode_pid()?);
+        }
+        DOP_EXIT | DOP_EXIT_TT | DOP_EXIT2 | DOP_EXIT2_TT => {
+            message.msg_type = if op == DOP_EXIT || op == DOP_EXIT_TT {
+                MessageType::Exit
+            } else {
+                MessageType::Exit2
+            };
+            message.from = Some(control.decode_pid()?);
+            message.to = Some(control.decode_pid()?);
+            if op == DOP_EXIT_TT || op == DOP_EXIT2_TT {
+                control.skip_term()?;
+            }
+            let start = control.index();
+            control.skip_term()?;
+            let mut reason = vec![ERL_VERSION];
+            reason.extend_from_slice(&control.as_bytes()[start..control.index()]);
+            message.payload = EiBuffer::from_bytes(reason);
+        }
+        _ => {}
+    }
+    if matches!(message.msg_type, MessageType::Send | MessageType::RegSend) {
+        message.payload = EiBuffer::from_bytes(bytes[control_end..].to_vec());
+    }
+    Ok((message, unlink_id))
+}
+
+/// The bytes of a buffer holding one term, checked to start with the version byte
+fn term_bytes(term: &EiBuffer) -> Result<&[u8], CNodeError> {
+    match term.as_bytes().first() {
+        Some(&ERL_VERSION) => Ok(term.as_bytes()),
+        Some(&byte) => Err(CNodeError::Ei(EiBufferError::BadVersion(byte))),
+        None => Err(CNodeError::Ei(EiBufferError::EncodeError("empty term".to_string()))),
+    }
+}
+
+/// Append the term in another buffer, without its version byte
+fn append_term(buffer: &mut EiBuffer, term: &EiBuffer) -> Result<(), CNodeError> {
+    let bytes = term_bytes(term)?;
+    buffer.append(&EiBuffer::from_bytes(bytes[1..].to_vec()));
+    Ok(())
+}
+
+/// Split a node name into its alive and host parts
+fn split_node_name(node: &str) -> Result<(&str, &str), CNodeError> {
+    match node.split_once('@') {
+        Some((alive, host)) if !alive.is_empty() && !host.is_empty() && node.len() <= 255 => Ok((alive, host)),
+        _ => Err(CNodeError::InvalidNodeName(node.to_string())),
+    }
+}
+
+/// Check that a peer has the capabilities this node requires
+fn check_flags(node: &str, flags: u64) -> Result<(), CNodeError> {
+    if flags & DFLAG_MANDATORY != DFLAG_MANDATORY {
+        return Err(CNodeError::Handshake(format!(
+            "{} lacks capabilities {:#x}",
+            node,
+            DFLAG_MANDATORY & !flags
+        )));
+    }
+    Ok(())
+}
+
+/// Digest proving knowledge of the cookie: MD5 of the cookie followed by the
+/// challenge in decimal
+fn gen_digest(challenge: u32, cookie: &str) -> [u8; 16] {
+    md5::compute(format!("{}{}", cookie, challenge)).0
+}
+
+/// Random challenge for the peer to answer
+fn gen_challenge() -> Result<u32, CNodeError> {
+    let mut bytes = [0; 4];
+    getrandom::fill(&mut bytes).map_err(|e| CNodeError::Handshake(e.to_string()))?;
+    Ok(u32::from_ne_bytes(bytes))
+}
+
+/// Read a name preceded by its 2-byte length
+fn read_name(bytes: &[u8]) -> Result<String, CNodeError> {
+    let len = usize::from(u16::from_be_bytes([bytes[0], bytes[1]]));
+    bytes
+        .get(2..2 + len)
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .ok_or_else(|| CNodeError::Handshake("truncated node name".to_string()))
+}
+
+fn be_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn be_u64(bytes: &[u8]) -> u64 {
+    u64::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]])
+}
+
+/// Write a handshake or EPMD message with a 2-byte length header
+fn write_packet2(socket: &mut TcpSocket, data: &[u8]) -> Result<(), CNodeError> {
+    let mut packet = Vec::with_capacity(2 + data.len());
+    packet.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    packet.extend_from_slice(data);
+    socket.write_all(&packet)?;
+    Ok(())
+}
+
+/// Read a handshake message with a 2-byte length header
+fn read_packet2(socket: &mut TcpSocket) -> Result<Vec<u8>, CNodeError> {
+    let mut header = [0; 2];
+    socket.read_exact(&mut header)?;
+    let mut data = vec![0; usize::from(u16::from_be_bytes(header))];
+    socket.read_exact(&mut data)?;
+    Ok(data)
+}
+
+fn address_family(addr: &SocketAddr) -> AddressFamily {
+    if addr.is_ipv4() {
+        AddressFamily::Ipv4
+    } else {
+        AddressFamily::Ipv6
+    }
+}
+
+/// Open a blocking TCP connection, optionally bounding the wait for it and
+/// for each read and write on it
+fn tcp_connect(addr: &SocketAddr, timeout: Option<Duration>) -> Result<TcpSocket, CNodeError> {
+    let socket = TcpSocket::new(address_family(addr))?;
+    let inner = socket.inner().inner();
+    inner.set_nonblocking(false)?;
+    match timeout {
+        Some(timeout) => inner.connect_timeout(&(*addr).into(), timeout)?,
+        None => socket.connect(addr)?,
+    }
+    inner.set_read_timeout(timeout)?;
+    inner.set_write_timeout(timeout)?;
+    Ok(socket)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn listening(node: &CNode) -> (TcpSocket, SocketAddr) {
+        let listener = node.listen(&SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0)).unwrap();
+        let addr = listener.local_addr().unwrap();
+        (listener, addr)
+    }
+
+    fn atom(name: &str) -> EiBuffer {
+        let mut x = EiBuffer::new_with_version();
+        x.encode_atom(name).unwrap();
+        x
+    }
+
+    #[test]
+    fn test_node_names() {
+        let node = CNode::new("rust@localhost", "secret", 3).unwrap();
+        assert_eq!(node.alive(), "rust");
+        assert_eq!(node.self_pid().node, "rust@localhost");
+        assert_eq!(node.self_pid().creation, 3);
+        for name in ["rust", "@localhost", "rust@", ""] {
+            assert_eq!(CNode::new(name, "secret", 1), Err(CNodeError::InvalidNodeName(name.to_string())));
+        }
+    }
+
+    #[test]
+    fn test_digest() {
+        let digest = [195, 147, 155, 49, 215, 169, 19, 216, 46, 42, 206, 9, 36, 124, 123, 65];
+        assert_eq!(gen_digest(0xDEADBEEF, "secret"), digest);
+    }
+
+    #[test]
+    fn test_connect_send_and_rpc() {
+        let server = CNode::new("server@localhost", "secret", 7).unwrap();
+        let (listener, addr) = listening(&server);
+        let acceptor = thread::spawn(move || {
+            let mut connection = server.accept(&listener).unwrap();
+            assert_eq!(connection.peer_node(), "client@localhost");
+            assert_eq!(connection.peer_creation(), 9);
+            assert_eq!(connection.peer_flags() & DFLAG_PUBLISHED, 0);
+
+            let message = connection.receive().unwrap();
+            assert_eq!(message.msg_type, MessageType::RegSend);
+            assert_eq!(message.to_name.as_deref(), Some("logger"));
+            let from = message.from.unwrap();
+            assert_eq!(from.node, "client@localhost");
+            connection.send(&from, &atom("hello")).unwrap();
+
+            // A tick is answered, and the message after it returned
+            connection.socket.write_all(&[0; 4]).unwrap();
+            let request = RpcRequest::from_message(&connection.receive().unwrap()).unwrap();
+            assert_eq!((request.module.as_str(), request.function.as_str()), ("erlang", "node"));
+            assert_eq!(request.args.as_bytes(), [ERL_VERSION, 106]);
+            connection.rpc_reply(&request, &atom("server@localhost")).unwrap();
+
+            let mut tock = [0xff; 4];
+            connection.socket.read_exact(&mut tock).unwrap();
+            assert_eq!(tock, [0; 4]);
+        });
+
+        let client = CNode::new("client@localhost", "secret", 9).unwrap();
+        let mut connection = client.connect_addr(&addr, "server@localhost").unwrap();
+        assert_eq!(connection.peer_creation(), 7);
+
+        connection.reg_send("logger", &atom("ping")).unwrap();
+        let mut message = connection.receive().unwrap();
+        assert_eq!(message.msg_type, MessageType::Send);
+        assert_eq!(message.to.as_ref(), Some(connection.self_pid()));
+        message.payload.decode_version().unwrap();
+        assert_eq!(message.payload.decode_atom().unwrap(), "hello");
+
+        let mut args = EiBuffer::new_with_version();
+        args.encode_empty_list().unwrap();
+        let mut reply = connection.rpc("erlang", "node", &args).unwrap();
+        reply.decode_version().unwrap();
+        assert_eq!(reply.decode_atom().unwrap(), "server@localhost");
+        acceptor.join().unwrap();
+    }
+
+    #[test]
+    fn test_bad_cookie() {
+        let server = CNode::new("server@localhost", "secret", 1).unwrap();
+        let (listener, addr) = listening(&server);
+        let acceptor = thread::spawn(move || server.accept(&listener).map(|_| ()));
+
+        let client = CNode::new("client@localhost", "guess", 1).unwrap();
+        assert!(client.connect_addr(&addr, "server@localhost").is_err());
+        assert_eq!(acceptor.join().unwrap(), Err(CNodeError::BadCookie));
+    }
+
+    #[test]
+    fn test_parse_control_messages() {
+        let pid = |num| ErlangPid { node: "erl@host".to_string(), num, serial: 0, creation: 1 };
+        let mut control = EiBuffer::new_with_version();
+        control.encode_tuple_header(4).unwrap();
+        control.encode_long(DOP_UNLINK_ID).unwrap();
+        control.encode_long(42).unwrap();
+        control.encode_pid(&pid(1)).unwrap();
+        control.encode_pid(&pid(2)).unwrap();
+        let mut body = vec![PASS_THROUGH];
+        body.extend_from_slice(control.as_bytes());
+        let (message, unlink_id) = parse_message(&body).unwrap();
+        assert_eq!(message.msg_type, MessageType::Unlink);
+        assert_eq!((message.from, message.to), (Some(pid(1)), Some(pid(2))));
+        assert_eq!(unlink_id, Some(42));
+
+        let mut control = EiBuffer::new_with_version();
+        control.encode_tuple_header(4).unwrap();
+        control.encode_long(DOP_EXIT).unwrap();
+        control.encode_pid(&pid(1)).unwrap();
+        control.encode_pid(&pid(2)).unwrap();
+        control.encode_atom("normal").unwrap();
+        let mut body = vec![PASS_THROUGH];
+        body.extend_from_slice(control.as_bytes());
+        let (message, _) = parse_message(&body).unwrap();
+        assert_eq!(message.msg_type, MessageType::Exit);
+        assert_eq!(message.payload.as_bytes(), atom("normal").as_bytes());
+        assert_eq!(RpcRequest::from_message(&message), None);
+
+        assert!(parse_message(&[ERL_VERSION]).is_err());
+    }
+
+    #[test]
+    fn test_epmd() {
+        let epmd = CNode::new("epmd@localhost", "", 0).unwrap();
+        let (listener, addr) = listening(&epmd);
+        let server = thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            socket.inner().inner().set_nonblocking(false).unwrap();
+            let request = read_packet2(&mut socket).unwrap();
+            assert_eq!(request, [EPMD_ALIVE2_REQ, 0x12, 0x34, 72, 0, 0, 6, 0, 5, 0, 4, b'r', b'u', b's', b't', 0, 0]);
+            socket.write_all(&[EPMD_ALIVE2_X_RESP, 0, 0, 0, 1, 2]).unwrap();
+
+            let (mut socket, _) = listener.accept().unwrap();
+            socket.inner().inner().set_nonblocking(false).unwrap();
+            assert_eq!(read_packet2(&mut socket).unwrap(), b"zerl");
+            socket.write_all(&[EPMD_PORT2_RESP, 0, 0x43, 0x21, 77, 0, 0, 6, 0, 5, 0, 3, b'e', b'r', b'l', 0, 0]).unwrap();
+
+            let (mut socket, _) = listener.accept().unwrap();
+            socket.inner().inner().set_nonblocking(false).unwrap();
+            read_packet2(&mut socket).unwrap();
+            socket.write_all(&[EPMD_PORT2_RESP, 1]).unwrap();
+        });
+
+        let mut node = CNode::new("rust@localhost", "secret", 0).unwrap();
+        node.set_epmd_port(addr.port());
+        let registration = node.publish(0x1234).unwrap();
+        assert_eq!(registration.creation(), 258);
+        assert_eq!(node.creation(), 258);
+
+        assert_eq!(epmd_port_please(&addr, "erl").unwrap(), 0x4321);
+        assert!(matches!(epmd_port_please(&addr, "none"), Err(CNodeError::Epmd(_))));
+        server.join().unwrap();
+    }
+}
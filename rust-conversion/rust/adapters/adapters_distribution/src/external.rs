@@ -24,10 +24,15 @@
 use entities_data_handling::term_hashing::Term;
 use entities_data_handling::atom::{AtomEncoding, AtomTable};
 use entities_utilities::BigNumber;
-use infrastructure_code_loading::constants::ERL_VERSION;
+use infrastructure_code_loading::constants::*;
 use infrastructure_data_handling::{decode_ei_term, DecodeError as EiDecodeError};
 use infrastructure_code_loading::encode_integers::encode_longlong;
 use infrastructure_code_loading::encode_headers::{encode_tuple_header, encode_map_header, encode_list_header};
+use infrastructure_code_loading::decode_headers::{decode_tuple_header, decode_map_header, decode_list_header};
+use infrastructure_code_loading::decode_pid::decode_pid;
+use infrastructure_code_loading::decode_port::decode_port;
+use infrastructure_code_loading::decode_ref::decode_ref;
+use infrastructure_data_handling::decode_atom::decode_atom_name;
 use infrastructure_data_handling::encode_atom::encode_atom;
 use infrastructure_data_handling::encode_binary::encode_binary;
 use infrastructure_bignum_encoding::BignumCodec;
@@ -120,6 +125,132 @@ impl ExternalTerm {
 
         Ok(term)
     }
+
+    /// Decode term from external format (ETF), creating its atoms in an atom table
+    ///
+    /// Unlike [`decode`](Self::decode), which leaves placeholders, atoms and the
+    /// nodes of pids, ports and references decode to their index in
+    /// `atom_table`, so terms received from another node can be read by name
+    /// and encoded back with the same table.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The encoded bytes in ETF format
+    /// * `atom_table` - Atom table to look up and create atoms in
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Term)` - Decoded Erlang term
+    /// * `Err(DecodeError)` - Decoding error
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use adapters_distribution::external::ExternalTerm;
+    /// use entities_data_handling::atom::AtomTable;
+    /// use entities_data_handling::term_hashing::Term;
+    ///
+    /// let table = AtomTable::new(1000);
+    /// let data = vec![131, 119, 2, b'o', b'k']; // version, SMALL_ATOM_UTF8_EXT, "ok"
+    /// let Term::Atom(index) = ExternalTerm::decode_with_atom_table(&data, &table)? else { panic!() };
+    /// assert_eq!(table.get_name(index as usize).unwrap(), b"ok");
+    /// # Ok::<(), adapters_distribution::external::DecodeError>(())
+    /// ```
+    pub fn decode_with_atom_table(data: &[u8], atom_table: &AtomTable) -> Result<Term, DecodeError> {
+        if data.first() != Some(&ERL_VERSION) {
+            return Err(DecodeError::InvalidFormat);
+        }
+        let (term, _) = decode_term_internal(data, 1, atom_table)?;
+        Ok(term)
+    }
+}
+
+/// Internal helper to decode a term recursively, resolving atoms in a table
+///
+/// Terms without atoms are decoded by `decode_ei_term`.
+fn decode_term_internal(buf: &[u8], index: usize, atom_table: &AtomTable) -> Result<(Term, usize), DecodeError> {
+    let tag = *buf.get(index).ok_or(DecodeError::BufferTooShort)?;
+    let mut pos = index;
+    match tag {
+        ERL_ATOM_EXT | ERL_SMALL_ATOM_EXT | ERL_ATOM_UTF8_EXT | ERL_SMALL_ATOM_UTF8_EXT => {
+            let (name, pos) = decode_atom_name(buf, index).map_err(|_| DecodeError::AtomDecodeError)?;
+            Ok((Term::Atom(put_atom(atom_table, &name)?), pos))
+        }
+        ERL_SMALL_TUPLE_EXT | ERL_LARGE_TUPLE_EXT => {
+            let arity = decode_tuple_header(buf, &mut pos).map_err(|_| DecodeError::InvalidFormat)?;
+            let mut elements = Vec::with_capacity(arity.min(buf.len()));
+            for _ in 0..arity {
+                let (element, next) = decode_term_internal(buf, pos, atom_table)?;
+                elements.push(element);
+                pos = next;
+            }
+            Ok((Term::Tuple(elements), pos))
+        }
+        ERL_MAP_EXT => {
+            let arity = decode_map_header(buf, &mut pos).map_err(|_| DecodeError::InvalidFormat)?;
+            let mut pairs = Vec::with_capacity(arity.min(buf.len()));
+            for _ in 0..arity {
+                let (key, next) = decode_term_internal(buf, pos, atom_table)?;
+                let (value, next) = decode_term_internal(buf, next, atom_table)?;
+                pairs.push((key, value));
+                pos = next;
+            }
+            Ok((Term::Map(pairs), pos))
+        }
+        ERL_LIST_EXT => {
+            let length = decode_list_header(buf, &mut pos).map_err(|_| DecodeError::InvalidFormat)?;
+            let mut elements = Vec::with_capacity(length.min(buf.len()));
+            for _ in 0..length {
+                let (element, next) = decode_term_internal(buf, pos, atom_table)?;
+                elements.push(element);
+                pos = next;
+            }
+            let (tail, pos) = decode_term_internal(buf, pos, atom_table)?;
+            Ok((cons_list(elements, tail), pos))
+        }
+        ERL_STRING_EXT => {
+            // A list of bytes, as sent for lists of small integers
+            let length = buf
+                .get(index + 1..index + 3)
+                .map(|len| u16::from_be_bytes([len[0], len[1]]) as usize)
+                .ok_or(DecodeError::BufferTooShort)?;
+            let bytes = buf.get(index + 3..index + 3 + length).ok_or(DecodeError::BufferTooShort)?;
+            let elements = bytes.iter().map(|&byte| Term::Small(byte as i64)).collect();
+            Ok((cons_list(elements, Term::Nil), index + 3 + length))
+        }
+        ERL_PID_EXT | ERL_NEW_PID_EXT => {
+            let pid = decode_pid(buf, &mut pos).map_err(|_| DecodeError::InvalidFormat)?;
+            let node = put_atom(atom_table, &pid.node)?;
+            Ok((Term::Pid { node, id: pid.num, serial: pid.serial, creation: pid.creation }, pos))
+        }
+        ERL_PORT_EXT | ERL_NEW_PORT_EXT | ERL_V4_PORT_EXT => {
+            let port = decode_port(buf, &mut pos).map_err(|_| DecodeError::InvalidFormat)?;
+            let node = put_atom(atom_table, &port.node)?;
+            Ok((Term::Port { node, id: port.id, creation: port.creation }, pos))
+        }
+        ERL_REFERENCE_EXT | ERL_NEW_REFERENCE_EXT | ERL_NEWER_REFERENCE_EXT => {
+            let reference = decode_ref(buf, &mut pos).map_err(|_| DecodeError::InvalidFormat)?;
+            let node = put_atom(atom_table, &reference.node)?;
+            Ok((Term::Ref { node, ids: reference.ids, creation: reference.creation }, pos))
+        }
+        _ => decode_ei_term(buf, index).map_err(DecodeError::from),
+    }
+}
+
+/// Get the index of an atom, creating it if needed
+fn put_atom(atom_table: &AtomTable, name: &str) -> Result<u32, DecodeError> {
+    atom_table
+        .put_index(name.as_bytes(), AtomEncoding::Utf8, false)
+        .map(|index| index as u32)
+        .map_err(|_| DecodeError::AtomDecodeError)
+}
+
+/// Build a list from its elements and tail
+fn cons_list(elements: Vec<Term>, tail: Term) -> Term {
+    elements.into_iter().rev().fold(tail, |tail, head| Term::List {
+        head: Box::new(head),
+        tail: Box::new(tail),
+    })
 }
 
 /// Internal helper to encode a term recursively
@@ -704,6 +835,28 @@ mod tests {
         assert!(encoded.len() > 3);
     }
 
+    #[test]
+    fn test_external_term_decode_with_atom_table() {
+        use entities_data_handling::atom::AtomTable;
+        let table = AtomTable::new(1000);
+        let key = table.put_index(b"key", AtomEncoding::SevenBitAscii, false).unwrap() as u32;
+        let term = Term::Map(vec![(Term::Atom(key), Term::Tuple(vec![Term::Small(1), Term::Nil]))]);
+        let encoded = ExternalTerm::encode(&term, Some(&table)).unwrap();
+        assert_eq!(ExternalTerm::decode_with_atom_table(&encoded, &table).unwrap(), term);
+
+        // STRING_EXT decodes to a list of small integers
+        let decoded = ExternalTerm::decode_with_atom_table(&[131, 107, 0, 2, b'h', b'i'], &table).unwrap();
+        let expected = Term::List {
+            head: Box::new(Term::Small(b'h' as i64)),
+            tail: Box::new(Term::List { head: Box::new(Term::Small(b'i' as i64)), tail: Box::new(Term::Nil) }),
+        };
+        assert_eq!(decoded, expected);
+        assert_eq!(
+            ExternalTerm::decode_with_atom_table(&[131, 107, 0, 3, b'h'], &table),
+            Err(DecodeError::BufferTooShort)
+        );
+    }
+
     #[test]
     fn test_external_term_encode_atom_without_table() {
         let term = Term::Atom(42);
@@ -12,6 +12,12 @@
 //!
 //! ## Modules
 //!
+//! - **[`cnode`](cnode/index.html)**: C-node connections to Erlang nodes: the
+//!   distribution handshake, EPMD, messages and `rpc` through `rex`
+//!
+//! - **[`rpc_client`](rpc_client/index.html)**: `erl_call`-style client calling
+//!   functions on Erlang nodes with `rpc:call/4` semantics
+//!
 //! - **[`external`](external/index.html)**: External term format (ETF) encoding and
 //!   decoding for serializing Erlang terms for network transmission
 //!
@@ -20,17 +26,22 @@
 //!
 //! ## Architecture
 //!
-//! This crate is based on the C implementation in `external.c` and `uds_drv.c`.
-//! It depends on the Entities layer for fundamental data types.
+//! This crate is based on the C implementation in `external.c` and `uds_drv.c`,
+//! and on `ei_connect.c` of erl_interface. It depends on the Entities layer for
+//! fundamental data types, and on `adapters_socket` for TCP connections.
 //!
 //! ## See Also
 //!
 //! - [`entities_data_handling`](../../entities/entities_data_handling/index.html): Term types for distribution
 //! - [`frameworks_system_integration`](../../frameworks/frameworks_system_integration/index.html): System integration framework
 
+pub mod cnode;
 pub mod external;
+pub mod rpc_client;
 pub mod uds;
 
+pub use cnode::{CNode, CNodeError, Connection, ErlMessage, MessageType, RpcRequest};
 pub use external::ExternalTerm;
+pub use rpc_client::{rpc_call, RpcClient, RpcError};
 pub use uds::UdsDistribution;
 
@@ -0,0 +1,293 @@
+//! RPC Client Module
+//!
+//! Provides a client that calls functions on Erlang nodes with the semantics
+//! of `rpc:call/4`, in the manner of `erl_call`: it connects to the node as a
+//! hidden C node, sends each call to the node's `rex` server and decodes the
+//! reply. Useful for tooling, and for testing the distribution stack against
+//! real nodes.
+//!
+//! ## Overview
+//!
+//! Arguments and replies are [`Term`]s, with their atoms in an [`AtomTable`]
+//! given to each call. A reply `{badrpc, Reason}`, as `rpc:call/4` returns
+//! when the call fails on the node, is [`RpcError::BadRpc`].
+//!
+//! After a call times out, the client is closed: its reply may still arrive
+//! and would be taken for the reply to the next call.
+//!
+//! ## Examples
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use adapters_distribution::rpc_client::RpcClient;
+//! use entities_data_handling::atom::AtomTable;
+//! use entities_data_handling::term_hashing::Term;
+//!
+//! let atoms = AtomTable::new(1000);
+//! let mut client = RpcClient::connect("erl@localhost", "secret", Some(Duration::from_secs(5)))?;
+//! let list = Term::List { head: Box::new(Term::Small(1)), tail: Box::new(Term::Nil) };
+//! let reversed = client.call("lists", "reverse", &[list], &atoms)?;
+//! # Ok::<(), adapters_distribution::rpc_client::RpcError>(())
+//! ```
+//!
+//! ## See Also
+//!
+//! - [`cnode`](super::cnode/index.html): Connections and messages between nodes
+//! - [`external`](super::external/index.html): Encoding of the arguments and reply
+//!
+//! Based on `lib/erl_interface/src/prog/erl_call.c`
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use entities_data_handling::atom::{AtomEncoding, AtomTable};
+use entities_data_handling::term_hashing::Term;
+use infrastructure_code_loading::EiBuffer;
+use super::cnode::{CNode, CNodeError, Connection};
+use super::external::{DecodeError, EncodeError, ExternalTerm};
+
+/// Number of clients created, to give each a distinct node name
+static CLIENTS: AtomicU32 = AtomicU32::new(0);
+
+/// RPC client error types
+#[derive(Debug, Clone, PartialEq)]
+pub enum RpcError {
+    /// Connecting to the node or exchanging messages with it failed
+    Connection(CNodeError),
+    /// The node did not reply within the timeout; the client is closed
+    Timeout,
+    /// An argument could not be encoded
+    Encode(EncodeError),
+    /// The reply could not be decoded
+    Decode(DecodeError),
+    /// The call failed on the node, which replied `{badrpc, Reason}`
+    BadRpc(Term),
+}
+
+impl From<CNodeError> for RpcError {
+    fn from(err: CNodeError) -> Self {
+        match err {
+            CNodeError::Timeout => RpcError::Timeout,
+            err => RpcError::Connection(err),
+        }
+    }
+}
+
+/// Client calling functions on one Erlang node
+pub struct RpcClient {
+    connection: Option<Connection>,
+    timeout: Option<Duration>,
+}
+
+impl RpcClient {
+    /// Connect to a node, found through EPMD on its host
+    ///
+    /// The client joins as a hidden node named after the process, on the
+    /// host of `node` so that both use short or long names alike.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - Node name, `alive@host`
+    /// * `cookie` - Cookie of the node
+    /// * `timeout` - Longest wait for the node to accept or reply; `None` waits forever
+    pub fn connect(node: &str, cookie: &str, timeout: Option<Duration>) -> Result<Self, RpcError> {
+        let (_, host) = node
+            .split_once('@')
+            .ok_or_else(|| CNodeError::InvalidNodeName(node.to_string()))?;
+        let name = format!(
+            "rpc_client_{}_{}@{}",
+            std::process::id(),
+            CLIENTS.fetch_add(1, Ordering::Relaxed),
+            host
+        );
+        let mut local = CNode::new(&name, cookie, 1)?;
+        local.set_timeout(timeout);
+        let connection = local.connect(node)?;
+        Ok(Self::from_connection(connection, timeout))
+    }
+
+    /// Make calls over an established connection
+    ///
+    /// # Arguments
+    ///
+    /// * `connection` - Connection to the node
+    /// * `timeout` - Longest wait for a reply; `None` waits forever
+    pub fn from_connection(connection: Connection, timeout: Option<Duration>) -> Self {
+        Self {
+            connection: Some(connection),
+            timeout,
+        }
+    }
+
+    /// Get the name of the node, unless the client is closed
+    pub fn peer_node(&self) -> Option<&str> {
+        self.connection.as_ref().map(Connection::peer_node)
+    }
+
+    /// Call `Module:Function(Args...)` on the node (`rpc:call/4`)
+    ///
+    /// # Arguments
+    ///
+    /// * `module` - Module name
+    /// * `function` - Function name
+    /// * `args` - Arguments, with their atoms in `atom_table`
+    /// * `atom_table` - Atom table the atoms of the reply are created in
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Term)` - The result
+    /// * `Err(RpcError)` - Error, `BadRpc` if the call failed on the node
+    pub fn call(
+        &mut self,
+        module: &str,
+        function: &str,
+        args: &[Term],
+        atom_table: &AtomTable,
+    ) -> Result<Term, RpcError> {
+        let mut arg_list = EiBuffer::new_with_version();
+        if !args.is_empty() {
+            arg_list.encode_list_header(args.len()).map_err(CNodeError::from)?;
+            for arg in args {
+                let encoded = ExternalTerm::encode(arg, Some(atom_table)).map_err(RpcError::Encode)?;
+                arg_list.append(&EiBuffer::from_bytes(encoded[1..].to_vec()));
+            }
+        }
+        arg_list.encode_empty_list().map_err(CNodeError::from)?;
+
+        let connection = self.connection.as_mut().ok_or(CNodeError::ConnectionClosed)?;
+        connection.set_read_timeout(self.timeout)?;
+        let reply = match connection.rpc(module, function, &arg_list) {
+            Ok(reply) => reply,
+            Err(CNodeError::Timeout) => {
+                self.connection = None;
+                return Err(RpcError::Timeout);
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let reply = ExternalTerm::decode_with_atom_table(reply.as_bytes(), atom_table).map_err(RpcError::Decode)?;
+        let badrpc = atom_table.get(b"badrpc", AtomEncoding::SevenBitAscii);
+        match reply {
+            Term::Tuple(mut elements)
+                if elements.len() == 2 && matches!(elements[0], Term::Atom(a) if Some(a as usize) == badrpc) =>
+            {
+                Err(RpcError::BadRpc(elements.pop().unwrap()))
+            }
+            reply => Ok(reply),
+        }
+    }
+}
+
+/// Call `Module:Function(Args...)` on a node over a new connection, as
+/// `erl_call -a` does
+///
+/// # Arguments
+///
+/// * `node` - Node name, `alive@host`
+/// * `cookie` - Cookie of the node
+/// * `module` - Module name
+/// * `function` - Function name
+/// * `args` - Arguments, with their atoms in `atom_table`
+/// * `atom_table` - Atom table the atoms of the reply are created in
+/// * `timeout` - Longest wait for the node to accept or reply; `None` waits forever
+pub fn rpc_call(
+    node: &str,
+    cookie: &str,
+    module: &str,
+    function: &str,
+    args: &[Term],
+    atom_table: &AtomTable,
+    timeout: Option<Duration>,
+) -> Result<Term, RpcError> {
+    RpcClient::connect(node, cookie, timeout)?.call(module, function, args, atom_table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cnode::RpcRequest;
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::thread;
+
+    /// Serve `rex` requests: `echo` replies with its argument list, `fail`
+    /// with `{badrpc, nodedown}`, and `hang` not at all
+    fn serve(server: CNode) -> (SocketAddr, thread::JoinHandle<()>) {
+        let listener = server.listen(&SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0)).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = thread::spawn(move || {
+            let mut connection = server.accept(&listener).unwrap();
+            while let Ok(message) = connection.receive() {
+                let request = RpcRequest::from_message(&message).unwrap();
+                let reply = match request.function.as_str() {
+                    "echo" => request.args.clone(),
+                    "fail" => {
+                        let mut reply = EiBuffer::new_with_version();
+                        reply.encode_tuple_header(2).unwrap();
+                        reply.encode_atom("badrpc").unwrap();
+                        reply.encode_atom("nodedown").unwrap();
+                        reply
+                    }
+                    _ => continue,
+                };
+                connection.rpc_reply(&request, &reply).unwrap();
+            }
+        });
+        (addr, handle)
+    }
+
+    fn client(addr: &SocketAddr, timeout: Option<Duration>) -> RpcClient {
+        let local = CNode::new("client@localhost", "secret", 1).unwrap();
+        RpcClient::from_connection(local.connect_addr(addr, "server@localhost").unwrap(), timeout)
+    }
+
+    #[test]
+    fn test_call() {
+        let (addr, server) = serve(CNode::new("server@localhost", "secret", 2).unwrap());
+        let mut client = client(&addr, None);
+        assert_eq!(client.peer_node(), Some("server@localhost"));
+
+        let atoms = AtomTable::new(100);
+        let ok = atoms.put_index(b"ok", AtomEncoding::SevenBitAscii, false).unwrap() as u32;
+        let node = atoms.put_index(b"client@localhost", AtomEncoding::SevenBitAscii, false).unwrap() as u32;
+        let args = [
+            Term::Tuple(vec![Term::Atom(ok), Term::Small(-7)]),
+            Term::Pid { node, id: 5, serial: 0, creation: 1 },
+            Term::Nil,
+        ];
+        let reply = client.call("test", "echo", &args, &atoms).unwrap();
+        let expected = args.iter().rev().fold(Term::Nil, |tail, head| Term::List {
+            head: Box::new(head.clone()),
+            tail: Box::new(tail),
+        });
+        assert_eq!(reply, expected);
+        assert_eq!(client.call("test", "echo", &[], &atoms).unwrap(), Term::Nil);
+
+        let Err(RpcError::BadRpc(Term::Atom(reason))) = client.call("test", "fail", &[], &atoms) else {
+            panic!("expected badrpc");
+        };
+        assert_eq!(atoms.get_name(reason as usize).unwrap(), b"nodedown");
+
+        drop(client);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_timeout_closes_client() {
+        let (addr, server) = serve(CNode::new("server@localhost", "secret", 2).unwrap());
+        let mut client = client(&addr, Some(Duration::from_millis(100)));
+        let atoms = AtomTable::new(100);
+
+        assert_eq!(client.call("test", "hang", &[], &atoms), Err(RpcError::Timeout));
+        assert_eq!(client.peer_node(), None);
+        assert_eq!(
+            client.call("test", "echo", &[], &atoms),
+            Err(RpcError::Connection(CNodeError::ConnectionClosed))
+        );
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_invalid_node_name() {
+        let err = RpcClient::connect("erl", "secret", None).err();
+        assert_eq!(err, Some(RpcError::Connection(CNodeError::InvalidNodeName("erl".to_string()))));
+    }
+}
@@ -0,0 +1,183 @@
+//! Completion Module
+//!
+//! Provides the platform-independent part of completion-based polling, as used
+//! by the I/O completion port backend on Windows. A completion port reports
+//! finished operations rather than ready descriptors, so readiness is probed
+//! with zero-byte overlapped operations: a zero-byte read completes once data
+//! is available. This module keeps the bookkeeping of the outstanding
+//! overlapped operations and converts their completions into the same
+//! [`IoEvent`] stream the readiness-based backends produce.
+
+use std::collections::HashMap;
+
+use crate::nif_io::{IoEvent, SysFdType};
+use crate::pollset::{ready_events, READY_ERROR, READY_READ, READY_WRITE};
+
+/// Kind of an overlapped readiness probe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverlappedOp {
+    /// Zero-byte read, completing once the descriptor is readable
+    Read,
+    /// Write probe, completing once the descriptor is writable
+    Write,
+}
+
+/// Final status of an overlapped operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionStatus {
+    /// The operation succeeded
+    Success,
+    /// The operation was cancelled, for example because the descriptor was
+    /// deselected
+    Cancelled,
+    /// The operation failed with an OS status code
+    Failed(u32),
+}
+
+/// Completion of an overlapped operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompletionEvent {
+    /// File descriptor (socket or handle) of the operation
+    pub fd: SysFdType,
+    /// Kind of the operation
+    pub op: OverlappedOp,
+    /// Number of bytes transferred
+    pub bytes: u32,
+    /// Final status
+    pub status: CompletionStatus,
+}
+
+impl CompletionEvent {
+    /// Readiness reported by the completion, as `READY_*` flags
+    ///
+    /// A failed operation reports the probed event together with an error,
+    /// so that the owner of the descriptor tries the operation and sees the
+    /// error itself. A cancelled operation reports nothing.
+    pub(crate) fn ready(&self) -> u32 {
+        let flag = match self.op {
+            OverlappedOp::Read => READY_READ,
+            OverlappedOp::Write => READY_WRITE,
+        };
+        match self.status {
+            CompletionStatus::Success => flag,
+            CompletionStatus::Cancelled => 0,
+            CompletionStatus::Failed(_) => flag | READY_ERROR,
+        }
+    }
+
+    /// I/O events reported by the completion
+    pub fn io_events(&self) -> Vec<IoEvent> {
+        ready_events(self.fd, self.ready()).collect()
+    }
+}
+
+/// Outstanding overlapped operations
+///
+/// Each operation gets an ID, stored alongside its `OVERLAPPED` structure, so
+/// that a completion can be traced back to its descriptor. At most one
+/// operation of each kind is outstanding per descriptor.
+#[derive(Debug, Default)]
+pub struct OverlappedTable {
+    /// ID of the next operation
+    next_id: u64,
+    /// Descriptor and kind of each outstanding operation
+    pending: HashMap<u64, (SysFdType, OverlappedOp)>,
+    /// Outstanding operation of each descriptor and kind
+    by_fd: HashMap<(SysFdType, OverlappedOp), u64>,
+}
+
+impl OverlappedTable {
+    /// Create an empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the start of an operation
+    ///
+    /// # Returns
+    ///
+    /// ID of the operation, or `None` if one of the same kind is already
+    /// outstanding for `fd`
+    pub fn start(&mut self, fd: SysFdType, op: OverlappedOp) -> Option<u64> {
+        if self.by_fd.contains_key(&(fd, op)) {
+            return None;
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.pending.insert(id, (fd, op));
+        self.by_fd.insert((fd, op), id);
+        Some(id)
+    }
+
+    /// Record the completion of an operation, or an operation that failed to
+    /// start
+    ///
+    /// # Returns
+    ///
+    /// Descriptor and kind of the operation, or `None` for an unknown ID
+    pub fn complete(&mut self, id: u64) -> Option<(SysFdType, OverlappedOp)> {
+        let (fd, op) = self.pending.remove(&id)?;
+        self.by_fd.remove(&(fd, op));
+        Some((fd, op))
+    }
+
+    /// Whether an operation of kind `op` is outstanding for `fd`
+    pub fn is_pending(&self, fd: SysFdType, op: OverlappedOp) -> bool {
+        self.by_fd.contains_key(&(fd, op))
+    }
+
+    /// Number of outstanding operations
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Whether no operation is outstanding
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nif_io::IoEventType;
+
+    #[test]
+    fn test_overlapped_table_bookkeeping() {
+        let mut table = OverlappedTable::new();
+        let read = table.start(5, OverlappedOp::Read).unwrap();
+        let write = table.start(5, OverlappedOp::Write).unwrap();
+        assert_ne!(read, write);
+        // One outstanding operation of each kind per descriptor
+        assert_eq!(table.start(5, OverlappedOp::Read), None);
+        assert!(table.is_pending(5, OverlappedOp::Read));
+        assert_eq!(table.len(), 2);
+
+        assert_eq!(table.complete(read), Some((5, OverlappedOp::Read)));
+        assert_eq!(table.complete(read), None);
+        assert!(!table.is_pending(5, OverlappedOp::Read));
+        assert!(table.start(5, OverlappedOp::Read).is_some());
+        assert_eq!(table.complete(write), Some((5, OverlappedOp::Write)));
+        assert_eq!(table.len(), 1);
+        assert!(!table.is_empty());
+    }
+
+    #[test]
+    fn test_completion_events_convert_to_io_events() {
+        let completion = |op, status| CompletionEvent { fd: 7, op, bytes: 0, status };
+        let types = |event: CompletionEvent| -> Vec<IoEventType> {
+            event.io_events().iter().map(|e| {
+                assert_eq!(e.fd, 7);
+                e.event_type
+            }).collect()
+        };
+
+        assert_eq!(types(completion(OverlappedOp::Read, CompletionStatus::Success)), vec![IoEventType::Read]);
+        assert_eq!(types(completion(OverlappedOp::Write, CompletionStatus::Success)), vec![IoEventType::Write]);
+        assert_eq!(
+            types(completion(OverlappedOp::Read, CompletionStatus::Failed(64))),
+            vec![IoEventType::Read, IoEventType::Error]
+        );
+        assert!(types(completion(OverlappedOp::Write, CompletionStatus::Cancelled)).is_empty());
+    }
+}
@@ -0,0 +1,1063 @@
+//! File System Event Module
+//!
+//! Provides file system monitoring for Erlang processes. A process subscribes
+//! to a file or directory and receives a `{fs_event, Path, Flags}` message
+//! for every change below it, where `Flags` is a list of atoms such as
+//! `created` or `modified`. Subscriptions serve the `fs` application as well
+//! as the code server's path watching, which reloads changed modules in
+//! development mode.
+//!
+//! Changes are observed with the native mechanism of the platform:
+//!
+//! - **inotify** on Linux and Android
+//! - **`ReadDirectoryChangesW`** on Windows
+//! - **polling** elsewhere, e.g. on macOS, by scanning the watched trees and
+//!   comparing modification times, sizes and permissions
+//!
+//! A single watcher thread waits for changes and passes each one to the
+//! [`FsEventHandler`] for every subscription it concerns, in the same way
+//! [`CheckIo`](crate::CheckIo) delivers select messages.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::nif_io::ErlangPid;
+
+/// Longest time the watcher thread waits before checking for shutdown
+const WAIT_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Interval between scans of the polling backend
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Kind of change reported in a file system event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FsEventFlag {
+    /// The file was created
+    Created,
+    /// The contents of the file changed
+    Modified,
+    /// The file was deleted
+    Removed,
+    /// The file was renamed, from or to this path
+    Renamed,
+    /// Permissions, ownership or timestamps changed
+    Attribute,
+    /// Events were lost; the subscriber should rescan its path
+    Overflow,
+}
+
+impl FsEventFlag {
+    /// The flag atom, e.g. `"created"`
+    pub fn atom(&self) -> &'static str {
+        match self {
+            FsEventFlag::Created => "created",
+            FsEventFlag::Modified => "modified",
+            FsEventFlag::Removed => "removed",
+            FsEventFlag::Renamed => "renamed",
+            FsEventFlag::Attribute => "attribute",
+            FsEventFlag::Overflow => "overflow",
+        }
+    }
+}
+
+/// File system event, delivered as `{fs_event, Path, Flags}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FsEvent {
+    /// Path of the changed file; the subscribed path for overflows
+    pub path: PathBuf,
+    /// What changed
+    pub flags: Vec<FsEventFlag>,
+}
+
+/// Handler delivering file system events to Erlang processes
+///
+/// Installed by the runtime integration layer when creating the
+/// [`FsWatcher`]. It receives the subscribing process and the event, and is
+/// responsible for building the message term and queueing it to the process.
+pub type FsEventHandler = Arc<dyn Fn(ErlangPid, FsEvent) + Send + Sync>;
+
+/// Identifier of a subscription
+pub type SubscriptionId = u64;
+
+/// Mechanism used to observe file system changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsWatchBackend {
+    /// `inotify(7)` (Linux and Android)
+    Inotify,
+    /// `ReadDirectoryChangesW` (Windows)
+    ReadDirectoryChanges,
+    /// Periodic scans, available everywhere
+    Polling,
+}
+
+impl FsWatchBackend {
+    /// The native backend of this platform, or [`FsWatchBackend::Polling`]
+    pub fn native() -> Self {
+        if cfg!(any(target_os = "linux", target_os = "android")) {
+            FsWatchBackend::Inotify
+        } else if cfg!(windows) {
+            FsWatchBackend::ReadDirectoryChanges
+        } else {
+            FsWatchBackend::Polling
+        }
+    }
+
+    /// Whether the backend can be used on this platform
+    pub fn is_available(&self) -> bool {
+        match self {
+            FsWatchBackend::Inotify => cfg!(any(target_os = "linux", target_os = "android")),
+            FsWatchBackend::ReadDirectoryChanges => cfg!(windows),
+            FsWatchBackend::Polling => true,
+        }
+    }
+
+    /// Name of the backend
+    pub fn name(&self) -> &'static str {
+        match self {
+            FsWatchBackend::Inotify => "inotify",
+            FsWatchBackend::ReadDirectoryChanges => "ReadDirectoryChangesW",
+            FsWatchBackend::Polling => "polling",
+        }
+    }
+}
+
+impl Default for FsWatchBackend {
+    fn default() -> Self {
+        Self::native()
+    }
+}
+
+/// File system watcher errors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsEventError {
+    /// The path cannot be watched
+    Io(io::ErrorKind),
+    /// The backend is not available on this platform
+    NotSupported,
+    /// No subscription with the given ID
+    UnknownSubscription,
+}
+
+impl std::fmt::Display for FsEventError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FsEventError::Io(kind) => write!(f, "Cannot watch path: {}", kind),
+            FsEventError::NotSupported => write!(f, "File system watch backend not supported"),
+            FsEventError::UnknownSubscription => write!(f, "Unknown subscription"),
+        }
+    }
+}
+
+impl std::error::Error for FsEventError {}
+
+impl From<io::Error> for FsEventError {
+    fn from(error: io::Error) -> Self {
+        FsEventError::Io(error.kind())
+    }
+}
+
+/// Change observed by a backend
+///
+/// A path of `None` stands for lost events, reported to every subscription.
+type RawEvent = (Option<PathBuf>, Vec<FsEventFlag>);
+
+/// Subscription of a process to a path
+#[derive(Debug, Clone)]
+struct Subscription {
+    /// Subscribing process
+    pid: ErlangPid,
+    /// Watched path, canonicalized
+    path: PathBuf,
+    /// Whether changes in subdirectories are reported
+    recursive: bool,
+}
+
+impl Subscription {
+    /// Whether a change of `path` concerns the subscription
+    fn covers(&self, path: &Path) -> bool {
+        path == self.path
+            || path.parent() == Some(self.path.as_path())
+            || (self.recursive && path.starts_with(&self.path))
+    }
+}
+
+/// State shared between the watcher and its thread
+struct Shared {
+    /// Backend observing the watched paths
+    backend: Backend,
+    /// Subscriptions by ID
+    subscriptions: Mutex<HashMap<SubscriptionId, Subscription>>,
+    /// Number of subscriptions of each watched path
+    roots: Mutex<HashMap<PathBuf, usize>>,
+    /// ID of the next subscription
+    next_id: AtomicU64,
+    /// Set to stop the watcher thread
+    shutdown: AtomicBool,
+    /// Handler delivering events
+    handler: FsEventHandler,
+}
+
+impl Shared {
+    /// Deliver the events of a backend to the subscriptions they concern
+    fn dispatch(&self, events: Vec<RawEvent>) {
+        let subscriptions: Vec<Subscription> = self.subscriptions.lock().unwrap().values().cloned().collect();
+        for (path, flags) in events {
+            for subscription in &subscriptions {
+                let path = match &path {
+                    Some(path) if subscription.covers(path) => path.clone(),
+                    Some(_) => continue,
+                    None => subscription.path.clone(),
+                };
+                (self.handler)(subscription.pid, FsEvent { path, flags: flags.clone() });
+            }
+        }
+    }
+}
+
+/// File system watcher
+///
+/// Watching starts with the first subscription to a path and stops when its
+/// last subscription is removed. Dropping the watcher stops its thread.
+pub struct FsWatcher {
+    /// State shared with the watcher thread
+    shared: Arc<Shared>,
+    /// Watcher thread
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FsWatcher {
+    /// Create a watcher using the native backend of the platform
+    ///
+    /// Falls back to polling if the native backend cannot be initialized,
+    /// e.g. when the inotify instance limit is reached.
+    pub fn new(handler: FsEventHandler) -> Self {
+        Self::with_backend(FsWatchBackend::native(), handler.clone())
+            .unwrap_or_else(|_| Self::start(Backend::Polling(polling::Polling::new()), handler))
+    }
+
+    /// Create a watcher using `backend`
+    pub fn with_backend(backend: FsWatchBackend, handler: FsEventHandler) -> Result<Self, FsEventError> {
+        Ok(Self::start(Backend::new(backend)?, handler))
+    }
+
+    fn start(backend: Backend, handler: FsEventHandler) -> Self {
+        let shared = Arc::new(Shared {
+            backend,
+            subscriptions: Mutex::new(HashMap::new()),
+            roots: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+            shutdown: AtomicBool::new(false),
+            handler,
+        });
+        let thread_shared = Arc::clone(&shared);
+        let thread = std::thread::Builder::new()
+            .name("fs_event".to_string())
+            .spawn(move || {
+                while !thread_shared.shutdown.load(Ordering::Acquire) {
+                    match thread_shared.backend.wait(WAIT_TIMEOUT) {
+                        Ok(events) => thread_shared.dispatch(events),
+                        Err(_err) => {
+                            #[cfg(debug_assertions)]
+                            eprintln!("fs_event: wait failed: {}", _err);
+                            std::thread::sleep(WAIT_TIMEOUT);
+                        }
+                    }
+                }
+            })
+            .expect("failed to spawn fs_event thread");
+        Self {
+            shared,
+            thread: Some(thread),
+        }
+    }
+
+    /// Backend used by the watcher
+    pub fn backend(&self) -> FsWatchBackend {
+        self.shared.backend.kind()
+    }
+
+    /// Subscribe a process to changes of a path
+    ///
+    /// # Arguments
+    /// * `pid` - Process to deliver `{fs_event, Path, Flags}` messages to
+    /// * `path` - File or directory to watch; it must exist
+    /// * `recursive` - Whether to report changes in subdirectories of a
+    ///   directory, rather than only its entries
+    ///
+    /// # Returns
+    /// ID of the subscription, to pass to [`FsWatcher::unsubscribe`]
+    pub fn subscribe<P: AsRef<Path>>(&self, pid: ErlangPid, path: P, recursive: bool) -> Result<SubscriptionId, FsEventError> {
+        let path = path.as_ref().canonicalize()?;
+        {
+            let mut roots = self.shared.roots.lock().unwrap();
+            match roots.get_mut(&path) {
+                Some(count) => *count += 1,
+                None => {
+                    self.shared.backend.add(&path)?;
+                    roots.insert(path.clone(), 1);
+                }
+            }
+        }
+        let id = self.shared.next_id.fetch_add(1, Ordering::Relaxed);
+        self.shared.subscriptions.lock().unwrap().insert(id, Subscription { pid, path, recursive });
+        Ok(id)
+    }
+
+    /// Remove a subscription
+    pub fn unsubscribe(&self, id: SubscriptionId) -> Result<(), FsEventError> {
+        let subscription = self
+            .shared
+            .subscriptions
+            .lock()
+            .unwrap()
+            .remove(&id)
+            .ok_or(FsEventError::UnknownSubscription)?;
+        let mut roots = self.shared.roots.lock().unwrap();
+        if let Some(count) = roots.get_mut(&subscription.path) {
+            *count -= 1;
+            if *count == 0 {
+                roots.remove(&subscription.path);
+                self.shared.backend.remove(&subscription.path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove every subscription of a process, e.g. when it exits
+    ///
+    /// # Returns
+    /// Number of subscriptions removed
+    pub fn unsubscribe_all(&self, pid: ErlangPid) -> usize {
+        let ids: Vec<SubscriptionId> = self
+            .shared
+            .subscriptions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, subscription)| subscription.pid == pid)
+            .map(|(id, _)| *id)
+            .collect();
+        ids.iter().filter(|id| self.unsubscribe(**id).is_ok()).count()
+    }
+
+    /// Number of subscriptions
+    pub fn len(&self) -> usize {
+        self.shared.subscriptions.lock().unwrap().len()
+    }
+
+    /// Whether there are no subscriptions
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Drop for FsWatcher {
+    fn drop(&mut self) {
+        self.shared.shutdown.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Backend observing the watched paths
+///
+/// Watched paths are canonical and each is added once, however many
+/// subscriptions it has. Directories are always watched recursively;
+/// non-recursive subscriptions filter out deeper changes.
+enum Backend {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    Inotify(inotify::Inotify),
+    #[cfg(windows)]
+    ReadDirectoryChanges(directory_changes::DirectoryChanges),
+    Polling(polling::Polling),
+}
+
+impl Backend {
+    fn new(kind: FsWatchBackend) -> Result<Self, FsEventError> {
+        match kind {
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            FsWatchBackend::Inotify => Ok(Backend::Inotify(inotify::Inotify::new()?)),
+            #[cfg(windows)]
+            FsWatchBackend::ReadDirectoryChanges => Ok(Backend::ReadDirectoryChanges(directory_changes::DirectoryChanges::new())),
+            FsWatchBackend::Polling => Ok(Backend::Polling(polling::Polling::new())),
+            #[allow(unreachable_patterns)]
+            _ => Err(FsEventError::NotSupported),
+        }
+    }
+
+    fn kind(&self) -> FsWatchBackend {
+        match self {
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Backend::Inotify(_) => FsWatchBackend::Inotify,
+            #[cfg(windows)]
+            Backend::ReadDirectoryChanges(_) => FsWatchBackend::ReadDirectoryChanges,
+            Backend::Polling(_) => FsWatchBackend::Polling,
+        }
+    }
+
+    fn add(&self, root: &Path) -> io::Result<()> {
+        match self {
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Backend::Inotify(backend) => backend.add(root),
+            #[cfg(windows)]
+            Backend::ReadDirectoryChanges(backend) => backend.add(root),
+            Backend::Polling(backend) => backend.add(root),
+        }
+    }
+
+    fn remove(&self, root: &Path) {
+        match self {
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Backend::Inotify(backend) => backend.remove(root),
+            #[cfg(windows)]
+            Backend::ReadDirectoryChanges(backend) => backend.remove(root),
+            Backend::Polling(backend) => backend.remove(root),
+        }
+    }
+
+    /// Wait up to `timeout` for changes
+    fn wait(&self, timeout: Duration) -> io::Result<Vec<RawEvent>> {
+        match self {
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Backend::Inotify(backend) => backend.wait(timeout),
+            #[cfg(windows)]
+            Backend::ReadDirectoryChanges(backend) => backend.wait(timeout),
+            Backend::Polling(backend) => Ok(backend.wait(timeout)),
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod inotify {
+    use super::{FsEventFlag, RawEvent};
+    use std::collections::HashMap;
+    use std::ffi::{CString, OsStr};
+    use std::io;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::{Path, PathBuf};
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    /// Events watched on every directory and file
+    const MASK: u32 = libc::IN_CREATE
+        | libc::IN_MODIFY
+        | libc::IN_ATTRIB
+        | libc::IN_DELETE
+        | libc::IN_DELETE_SELF
+        | libc::IN_MOVED_FROM
+        | libc::IN_MOVED_TO
+        | libc::IN_MOVE_SELF;
+
+    /// Size of the buffer events are read into
+    const BUFFER_SIZE: usize = 64 * 1024;
+
+    /// Watch descriptors and the paths they watch
+    #[derive(Default)]
+    struct Watches {
+        /// Path of each watch descriptor
+        paths: HashMap<i32, PathBuf>,
+        /// Watched roots
+        roots: Vec<PathBuf>,
+    }
+
+    pub(super) struct Inotify {
+        fd: OwnedFd,
+        watches: Mutex<Watches>,
+    }
+
+    impl Inotify {
+        pub(super) fn new() -> io::Result<Self> {
+            // SAFETY: plain system call without pointer arguments
+            let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Self {
+                // SAFETY: `fd` is a new descriptor owned by nobody else
+                fd: unsafe { OwnedFd::from_raw_fd(fd) },
+                watches: Mutex::new(Watches::default()),
+            })
+        }
+
+        /// Watch a root and, for a directory, every directory below it
+        pub(super) fn add(&self, root: &Path) -> io::Result<()> {
+            let mut watches = self.watches.lock().unwrap();
+            self.add_watch(&mut watches, root)?;
+            watches.roots.push(root.to_path_buf());
+            if root.is_dir() {
+                self.add_tree(&mut watches, root);
+            }
+            Ok(())
+        }
+
+        /// Stop watching a root, keeping watches other roots still need
+        pub(super) fn remove(&self, root: &Path) {
+            let mut watches = self.watches.lock().unwrap();
+            watches.roots.retain(|r| r != root);
+            let unused: Vec<i32> = watches
+                .paths
+                .iter()
+                .filter(|(_, path)| !watches.roots.iter().any(|r| path.starts_with(r)))
+                .map(|(wd, _)| *wd)
+                .collect();
+            for wd in unused {
+                watches.paths.remove(&wd);
+                // SAFETY: plain system call without pointer arguments
+                unsafe { libc::inotify_rm_watch(self.fd.as_raw_fd(), wd) };
+            }
+        }
+
+        fn add_watch(&self, watches: &mut Watches, path: &Path) -> io::Result<()> {
+            let c_path = CString::new(path.as_os_str().as_bytes())
+                .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+            // SAFETY: `c_path` is a valid NUL-terminated string
+            let wd = unsafe { libc::inotify_add_watch(self.fd.as_raw_fd(), c_path.as_ptr(), MASK) };
+            if wd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            watches.paths.insert(wd, path.to_path_buf());
+            Ok(())
+        }
+
+        /// Watch the directories below `dir`, skipping those that vanish or
+        /// cannot be read
+        fn add_tree(&self, watches: &mut Watches, dir: &Path) {
+            let Ok(entries) = std::fs::read_dir(dir) else { return };
+            for entry in entries.flatten() {
+                if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                    let path = entry.path();
+                    if self.add_watch(watches, &path).is_ok() {
+                        self.add_tree(watches, &path);
+                    }
+                }
+            }
+        }
+
+        pub(super) fn wait(&self, timeout: Duration) -> io::Result<Vec<RawEvent>> {
+            let mut pollfd = libc::pollfd { fd: self.fd.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+            // SAFETY: `pollfd` is a valid array of one element
+            let ready = unsafe { libc::poll(&mut pollfd, 1, timeout.as_millis() as libc::c_int) };
+            if ready < 0 {
+                let err = io::Error::last_os_error();
+                return if err.kind() == io::ErrorKind::Interrupted { Ok(Vec::new()) } else { Err(err) };
+            }
+            if ready == 0 {
+                return Ok(Vec::new());
+            }
+
+            // u64 elements keep the buffer aligned for `inotify_event`
+            let mut buffer = vec![0u64; BUFFER_SIZE / 8];
+            // SAFETY: the buffer is valid for writes of BUFFER_SIZE bytes
+            let len = unsafe { libc::read(self.fd.as_raw_fd(), buffer.as_mut_ptr().cast(), BUFFER_SIZE) };
+            if len < 0 {
+                let err = io::Error::last_os_error();
+                return if err.kind() == io::ErrorKind::WouldBlock { Ok(Vec::new()) } else { Err(err) };
+            }
+            // SAFETY: the first `len` bytes were written by `read`
+            let bytes = unsafe { std::slice::from_raw_parts(buffer.as_ptr().cast::<u8>(), len as usize) };
+
+            let mut watches = self.watches.lock().unwrap();
+            let mut events = Vec::new();
+            let header = std::mem::size_of::<libc::inotify_event>();
+            let mut offset = 0;
+            while offset + header <= bytes.len() {
+                // SAFETY: a complete header lies at `offset`
+                let event: libc::inotify_event = unsafe { std::ptr::read_unaligned(bytes[offset..].as_ptr().cast()) };
+                let name_end = (offset + header + event.len as usize).min(bytes.len());
+                let name = &bytes[offset + header..name_end];
+                offset = name_end;
+
+                if event.mask & libc::IN_Q_OVERFLOW != 0 {
+                    events.push((None, vec![FsEventFlag::Overflow]));
+                    continue;
+                }
+                if event.mask & libc::IN_IGNORED != 0 {
+                    watches.paths.remove(&event.wd);
+                    continue;
+                }
+                let Some(dir) = watches.paths.get(&event.wd) else { continue };
+                let name = name.split(|b| *b == 0).next().unwrap_or_default();
+                let path = if name.is_empty() { dir.clone() } else { dir.join(OsStr::from_bytes(name)) };
+
+                // Watch directories created or moved into a watched tree
+                if event.mask & libc::IN_ISDIR != 0
+                    && event.mask & (libc::IN_CREATE | libc::IN_MOVED_TO) != 0
+                    && self.add_watch(&mut watches, &path).is_ok()
+                {
+                    self.add_tree(&mut watches, &path);
+                }
+                let flags = flags(event.mask);
+                if !flags.is_empty() {
+                    events.push((Some(path), flags));
+                }
+            }
+            Ok(events)
+        }
+    }
+
+    fn flags(mask: u32) -> Vec<FsEventFlag> {
+        [
+            (libc::IN_CREATE, FsEventFlag::Created),
+            (libc::IN_MODIFY, FsEventFlag::Modified),
+            (libc::IN_DELETE | libc::IN_DELETE_SELF, FsEventFlag::Removed),
+            (libc::IN_MOVED_FROM | libc::IN_MOVED_TO | libc::IN_MOVE_SELF, FsEventFlag::Renamed),
+            (libc::IN_ATTRIB, FsEventFlag::Attribute),
+        ]
+        .into_iter()
+        .filter(|(bits, _)| mask & bits != 0)
+        .map(|(_, flag)| flag)
+        .collect()
+    }
+}
+
+#[cfg(windows)]
+mod directory_changes {
+    use super::{FsEventFlag, RawEvent};
+    use std::collections::HashMap;
+    use std::ffi::OsString;
+    use std::io;
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+    use std::os::windows::io::AsRawHandle;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+    use std::sync::{Arc, Mutex};
+    use std::thread::JoinHandle;
+    use std::time::Duration;
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::Storage::FileSystem::{
+        CreateFileW, ReadDirectoryChangesW, FILE_ACTION_ADDED, FILE_ACTION_MODIFIED, FILE_ACTION_REMOVED,
+        FILE_ACTION_RENAMED_NEW_NAME, FILE_ACTION_RENAMED_OLD_NAME, FILE_FLAG_BACKUP_SEMANTICS, FILE_LIST_DIRECTORY,
+        FILE_NOTIFY_CHANGE_ATTRIBUTES, FILE_NOTIFY_CHANGE_DIR_NAME, FILE_NOTIFY_CHANGE_FILE_NAME,
+        FILE_NOTIFY_CHANGE_LAST_WRITE, FILE_NOTIFY_CHANGE_SIZE, FILE_NOTIFY_INFORMATION, FILE_SHARE_DELETE,
+        FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
+    };
+    use windows_sys::Win32::System::IO::CancelSynchronousIo;
+
+    /// Changes reported by `ReadDirectoryChangesW`
+    const FILTER: u32 = FILE_NOTIFY_CHANGE_FILE_NAME
+        | FILE_NOTIFY_CHANGE_DIR_NAME
+        | FILE_NOTIFY_CHANGE_ATTRIBUTES
+        | FILE_NOTIFY_CHANGE_SIZE
+        | FILE_NOTIFY_CHANGE_LAST_WRITE;
+
+    /// Size of the buffer changes are read into
+    const BUFFER_SIZE: usize = 64 * 1024;
+
+    /// Directory handle, used by the reading thread and closed by the owner
+    struct DirHandle(HANDLE);
+
+    // SAFETY: a directory handle may be used from any thread
+    unsafe impl Send for DirHandle {}
+    unsafe impl Sync for DirHandle {}
+
+    /// Watched root, read by its own thread
+    struct Watch {
+        handle: Arc<DirHandle>,
+        stop: Arc<AtomicBool>,
+        thread: JoinHandle<()>,
+    }
+
+    pub(super) struct DirectoryChanges {
+        sender: Sender<RawEvent>,
+        events: Mutex<Receiver<RawEvent>>,
+        watches: Mutex<HashMap<PathBuf, Watch>>,
+    }
+
+    impl DirectoryChanges {
+        pub(super) fn new() -> Self {
+            let (sender, events) = mpsc::channel();
+            Self {
+                sender,
+                events: Mutex::new(events),
+                watches: Mutex::new(HashMap::new()),
+            }
+        }
+
+        /// Watch a directory tree, or a file through its parent directory
+        pub(super) fn add(&self, root: &Path) -> io::Result<()> {
+            let is_dir = root.is_dir();
+            let dir = if is_dir { root.to_path_buf() } else { root.parent().unwrap_or(root).to_path_buf() };
+            let wide: Vec<u16> = dir.as_os_str().encode_wide().chain(Some(0)).collect();
+            // SAFETY: `wide` is a valid NUL-terminated wide string
+            let handle = unsafe {
+                CreateFileW(
+                    wide.as_ptr(),
+                    FILE_LIST_DIRECTORY,
+                    FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+                    std::ptr::null(),
+                    OPEN_EXISTING,
+                    FILE_FLAG_BACKUP_SEMANTICS,
+                    std::ptr::null_mut(),
+                )
+            };
+            if handle == INVALID_HANDLE_VALUE {
+                return Err(io::Error::last_os_error());
+            }
+            let handle = Arc::new(DirHandle(handle));
+            let stop = Arc::new(AtomicBool::new(false));
+            let only = if is_dir { None } else { Some(root.to_path_buf()) };
+            let (thread_handle, thread_stop, sender) = (Arc::clone(&handle), Arc::clone(&stop), self.sender.clone());
+            let thread = std::thread::Builder::new()
+                .name("fs_event_dir".to_string())
+                .spawn(move || read_changes(&thread_handle, &thread_stop, &dir, is_dir, only.as_deref(), &sender))?;
+            self.watches.lock().unwrap().insert(root.to_path_buf(), Watch { handle, stop, thread });
+            Ok(())
+        }
+
+        pub(super) fn remove(&self, root: &Path) {
+            let Some(watch) = self.watches.lock().unwrap().remove(root) else { return };
+            watch.stop.store(true, Ordering::Release);
+            // The reading thread may be between two reads; retry until it exits
+            while !watch.thread.is_finished() {
+                // SAFETY: the thread handle is valid until the thread is joined
+                unsafe { CancelSynchronousIo(watch.thread.as_raw_handle() as HANDLE) };
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            let _ = watch.thread.join();
+            // SAFETY: the handle is open and no longer used by the thread
+            unsafe { CloseHandle(watch.handle.0) };
+        }
+
+        pub(super) fn wait(&self, timeout: Duration) -> io::Result<Vec<RawEvent>> {
+            let events = self.events.lock().unwrap();
+            let mut ready = match events.recv_timeout(timeout) {
+                Ok(event) => vec![event],
+                Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => return Ok(Vec::new()),
+            };
+            ready.extend(events.try_iter());
+            Ok(ready)
+        }
+    }
+
+    impl Drop for DirectoryChanges {
+        fn drop(&mut self) {
+            let roots: Vec<PathBuf> = self.watches.lock().unwrap().keys().cloned().collect();
+            for root in roots {
+                self.remove(&root);
+            }
+        }
+    }
+
+    /// Read changes of `dir` until stopped
+    fn read_changes(handle: &DirHandle, stop: &AtomicBool, dir: &Path, subtree: bool, only: Option<&Path>, sender: &Sender<RawEvent>) {
+        // u32 elements keep the buffer aligned for FILE_NOTIFY_INFORMATION
+        let mut buffer = vec![0u32; BUFFER_SIZE / 4];
+        while !stop.load(Ordering::Acquire) {
+            let mut returned = 0u32;
+            // SAFETY: the buffer is valid for writes of BUFFER_SIZE bytes and
+            // the call is synchronous
+            let ok = unsafe {
+                ReadDirectoryChangesW(
+                    handle.0,
+                    buffer.as_mut_ptr().cast(),
+                    BUFFER_SIZE as u32,
+                    i32::from(subtree),
+                    FILTER,
+                    &mut returned,
+                    std::ptr::null_mut(),
+                    None,
+                )
+            };
+            if ok == 0 {
+                return;
+            }
+            if returned == 0 {
+                // The buffer overflowed
+                let _ = sender.send((None, vec![FsEventFlag::Overflow]));
+                continue;
+            }
+            let bytes = &buffer[..];
+            let mut offset = 0usize;
+            loop {
+                // SAFETY: the system wrote a FILE_NOTIFY_INFORMATION at this
+                // 4-byte aligned offset
+                let info = unsafe { &*bytes.as_ptr().cast::<u8>().add(offset).cast::<FILE_NOTIFY_INFORMATION>() };
+                // SAFETY: the name follows the header within the buffer
+                let name = unsafe {
+                    std::slice::from_raw_parts(info.FileName.as_ptr(), info.FileNameLength as usize / 2)
+                };
+                let path = dir.join(OsString::from_wide(name));
+                let flag = match info.Action {
+                    FILE_ACTION_ADDED => Some(FsEventFlag::Created),
+                    FILE_ACTION_REMOVED => Some(FsEventFlag::Removed),
+                    FILE_ACTION_MODIFIED => Some(FsEventFlag::Modified),
+                    FILE_ACTION_RENAMED_OLD_NAME | FILE_ACTION_RENAMED_NEW_NAME => Some(FsEventFlag::Renamed),
+                    _ => None,
+                };
+                if let Some(flag) = flag {
+                    if only.is_none_or(|only| only == path) && sender.send((Some(path), vec![flag])).is_err() {
+                        return;
+                    }
+                }
+                if info.NextEntryOffset == 0 {
+                    break;
+                }
+                offset += info.NextEntryOffset as usize;
+            }
+        }
+    }
+}
+
+mod polling {
+    use super::{FsEventFlag, RawEvent, POLL_INTERVAL};
+    use std::collections::HashMap;
+    use std::fs::Metadata;
+    use std::path::{Path, PathBuf};
+    use std::sync::Mutex;
+    use std::time::{Duration, SystemTime};
+
+    /// State of a file compared between scans
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Stamp {
+        modified: Option<SystemTime>,
+        len: u64,
+        readonly: bool,
+        #[cfg(unix)]
+        mode: u32,
+    }
+
+    impl Stamp {
+        fn new(metadata: &Metadata) -> Self {
+            Self {
+                modified: metadata.modified().ok(),
+                len: metadata.len(),
+                readonly: metadata.permissions().readonly(),
+                #[cfg(unix)]
+                mode: std::os::unix::fs::PermissionsExt::mode(&metadata.permissions()),
+            }
+        }
+
+        fn attributes_differ(&self, other: &Stamp) -> bool {
+            #[cfg(unix)]
+            if self.mode != other.mode {
+                return true;
+            }
+            self.readonly != other.readonly
+        }
+    }
+
+    /// Files of a watched tree
+    type Snapshot = HashMap<PathBuf, Stamp>;
+
+    pub(super) struct Polling {
+        /// Last scan of each watched root
+        roots: Mutex<HashMap<PathBuf, Snapshot>>,
+    }
+
+    impl Polling {
+        pub(super) fn new() -> Self {
+            Self { roots: Mutex::new(HashMap::new()) }
+        }
+
+        pub(super) fn add(&self, root: &Path) -> std::io::Result<()> {
+            std::fs::symlink_metadata(root)?;
+            self.roots.lock().unwrap().insert(root.to_path_buf(), scan(root));
+            Ok(())
+        }
+
+        pub(super) fn remove(&self, root: &Path) {
+            self.roots.lock().unwrap().remove(root);
+        }
+
+        /// Sleep for the poll interval, at most `timeout`, and rescan
+        pub(super) fn wait(&self, timeout: Duration) -> Vec<RawEvent> {
+            std::thread::sleep(timeout.min(POLL_INTERVAL));
+            let mut roots = self.roots.lock().unwrap();
+            let mut events: Vec<RawEvent> = Vec::new();
+            for (root, snapshot) in roots.iter_mut() {
+                let current = scan(root);
+                for (path, stamp) in &current {
+                    let flags = match snapshot.get(path) {
+                        None => vec![FsEventFlag::Created],
+                        Some(old) => {
+                            let mut flags = Vec::new();
+                            if old.modified != stamp.modified || old.len != stamp.len {
+                                flags.push(FsEventFlag::Modified);
+                            }
+                            if old.attributes_differ(stamp) {
+                                flags.push(FsEventFlag::Attribute);
+                            }
+                            flags
+                        }
+                    };
+                    if !flags.is_empty() {
+                        events.push((Some(path.clone()), flags));
+                    }
+                }
+                for path in snapshot.keys().filter(|path| !current.contains_key(*path)) {
+                    events.push((Some(path.clone()), vec![FsEventFlag::Removed]));
+                }
+                *snapshot = current;
+            }
+            // Nested roots see the same change
+            events.sort_by(|a, b| a.0.cmp(&b.0));
+            events.dedup();
+            events
+        }
+    }
+
+    /// Scan a file, or a directory tree without following symbolic links
+    fn scan(root: &Path) -> Snapshot {
+        let mut snapshot = Snapshot::new();
+        let mut pending = vec![root.to_path_buf()];
+        while let Some(path) = pending.pop() {
+            let Ok(metadata) = std::fs::symlink_metadata(&path) else { continue };
+            if metadata.is_dir() {
+                if let Ok(entries) = std::fs::read_dir(&path) {
+                    pending.extend(entries.flatten().map(|entry| entry.path()));
+                }
+            }
+            snapshot.insert(path, Stamp::new(&metadata));
+        }
+        snapshot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::{self, Receiver};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.canonicalize().unwrap()
+    }
+
+    fn collector() -> (FsEventHandler, Receiver<(ErlangPid, FsEvent)>) {
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+        let handler: FsEventHandler = Arc::new(move |pid, event| {
+            let _ = sender.lock().unwrap().send((pid, event));
+        });
+        (handler, receiver)
+    }
+
+    /// Wait for an event of `pid` about `path` with `flag`
+    fn expect_event(events: &Receiver<(ErlangPid, FsEvent)>, pid: ErlangPid, path: &Path, flag: FsEventFlag) {
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while let Some(left) = deadline.checked_duration_since(std::time::Instant::now()) {
+            match events.recv_timeout(left) {
+                Ok((p, event)) if p == pid && event.path == path && event.flags.contains(&flag) => return,
+                Ok(_) => {}
+                Err(_) => break,
+            }
+        }
+        panic!("no {:?} event for {} of pid {}", flag, path.display(), pid);
+    }
+
+    /// Whether any event of `pid` about `path` arrives within a short time
+    fn any_event(events: &Receiver<(ErlangPid, FsEvent)>, pid: ErlangPid, path: &Path) -> bool {
+        let deadline = std::time::Instant::now() + Duration::from_millis(400);
+        while let Some(left) = deadline.checked_duration_since(std::time::Instant::now()) {
+            match events.recv_timeout(left) {
+                Ok((p, event)) if p == pid && event.path == path => return true,
+                Ok(_) => {}
+                Err(_) => break,
+            }
+        }
+        false
+    }
+
+    fn check_backend(backend: FsWatchBackend) {
+        let dir = temp_dir(&format!("test_fs_event_{}", backend.name()));
+        std::fs::create_dir(dir.join("sub")).unwrap();
+        let (handler, events) = collector();
+        let watcher = FsWatcher::with_backend(backend, handler).unwrap();
+        assert_eq!(watcher.backend(), backend);
+
+        let recursive = watcher.subscribe(1, &dir, true).unwrap();
+        watcher.subscribe(2, &dir, false).unwrap();
+        assert_eq!(watcher.len(), 2);
+
+        let file = dir.join("file.txt");
+        std::fs::write(&file, b"one").unwrap();
+        expect_event(&events, 1, &file, FsEventFlag::Created);
+        std::thread::sleep(Duration::from_millis(20));
+        std::fs::write(&file, b"two, longer").unwrap();
+        expect_event(&events, 2, &file, FsEventFlag::Modified);
+
+        // Only the recursive subscription sees changes in subdirectories
+        let nested = dir.join("sub").join("nested.txt");
+        std::fs::write(&nested, b"x").unwrap();
+        expect_event(&events, 1, &nested, FsEventFlag::Created);
+        assert!(!any_event(&events, 2, &nested));
+
+        std::fs::remove_file(&file).unwrap();
+        expect_event(&events, 1, &file, FsEventFlag::Removed);
+
+        watcher.unsubscribe(recursive).unwrap();
+        assert_eq!(watcher.unsubscribe(recursive), Err(FsEventError::UnknownSubscription));
+        assert_eq!(watcher.unsubscribe_all(2), 1);
+        assert!(watcher.is_empty());
+        std::fs::write(&file, b"three").unwrap();
+        assert!(!any_event(&events, 1, &file));
+
+        drop(watcher);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_flag_atoms() {
+        assert_eq!(FsEventFlag::Created.atom(), "created");
+        assert_eq!(FsEventFlag::Attribute.atom(), "attribute");
+        assert_eq!(FsEventFlag::Overflow.atom(), "overflow");
+        assert!(FsWatchBackend::Polling.is_available());
+        assert!(FsWatchBackend::native().is_available());
+    }
+
+    #[test]
+    fn test_polling_backend() {
+        check_backend(FsWatchBackend::Polling);
+    }
+
+    #[test]
+    fn test_native_backend() {
+        check_backend(FsWatchBackend::native());
+    }
+
+    #[test]
+    fn test_subscribe_errors() {
+        let (handler, _events) = collector();
+        let watcher = FsWatcher::new(handler);
+        let missing = std::env::temp_dir().join(format!("test_fs_event_missing_{}", std::process::id()));
+        assert_eq!(watcher.subscribe(1, &missing, true), Err(FsEventError::Io(io::ErrorKind::NotFound)));
+        assert_eq!(watcher.unsubscribe(42), Err(FsEventError::UnknownSubscription));
+        if !FsWatchBackend::ReadDirectoryChanges.is_available() {
+            let (handler, _events) = collector();
+            assert!(matches!(
+                FsWatcher::with_backend(FsWatchBackend::ReadDirectoryChanges, handler),
+                Err(FsEventError::NotSupported)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_watch_single_file() {
+        let dir = temp_dir("test_fs_event_file");
+        let file = dir.join("watched");
+        let other = dir.join("other");
+        std::fs::write(&file, b"a").unwrap();
+        let (handler, events) = collector();
+        let watcher = FsWatcher::new(handler);
+        watcher.subscribe(3, &file, false).unwrap();
+
+        std::fs::write(&other, b"b").unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        std::fs::write(&file, b"changed").unwrap();
+        expect_event(&events, 3, &file, FsEventFlag::Modified);
+        assert!(!any_event(&events, 3, &other));
+
+        drop(watcher);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -16,6 +16,12 @@
 //!
 //! - **[`nif_io`](nif_io/index.html)**: I/O polling and event management for NIFs
 //!   and network communication
+//! - **[`pollset`](pollset/index.html)**: OS polling backends (epoll, kqueue, I/O
+//!   completion ports and poll)
+//! - **[`completion`](completion/index.html)**: Overlapped operation bookkeeping and
+//!   conversion of completions into I/O events
+//! - **[`fs_event`](fs_event/index.html)**: File system monitoring, delivering
+//!   `{fs_event, Path, Flags}` messages to subscribing processes
 //!
 //! ## Architecture
 //!
@@ -28,11 +34,18 @@
 //! - [`adapters_nifs`](../adapters_nifs/index.html): NIF implementations
 //! - [`adapters_system_integration_unix`](../adapters_system_integration_unix/index.html): Unix-specific system integration
 
+pub mod completion;
+pub mod fs_event;
 pub mod nif_io;
+pub mod pollset;
 
 pub use nif_io::{
     CheckIo, CheckIoConfig, CheckIoInfo, CheckIoError,
     PollThreadId, IoEvent, IoEventType,
     NifIOQueue, NifIOQueueOpts, NifIOVec, NifBinary, SysIOVec,
     NifSelectFlags, NifSelectResult, enif_select, SysFdType,
+    SelectMessage, SelectEventAtom, SelectMessageHandler, DriverInputHandler,
 };
+pub use pollset::PollBackend;
+pub use completion::{CompletionEvent, CompletionStatus, OverlappedOp, OverlappedTable};
+pub use fs_event::{FsEvent, FsEventError, FsEventFlag, FsEventHandler, FsWatchBackend, FsWatcher, SubscriptionId};
@@ -9,6 +9,8 @@
 //! - Manages polling sets for file descriptors
 //! - Waits for I/O events with configurable timeouts
 //! - Dispatches events to NIFs through `enif_select`
+//! - Applies the `driver_select` requests of port drivers and reports
+//!   readable fds to their ports
 //! - Handles thread-safe polling operations
 //!
 //! ## Overview
@@ -21,7 +23,8 @@
 //!
 //! The NIF I/O polling subsystem consists of:
 //! - **Event state management**: File descriptor event state tracking (shared infrastructure)
-//! - **Polling layer**: Platform-specific polling mechanisms (epoll, kqueue, poll, select)
+//! - **Polling layer**: Platform-specific polling mechanisms (epoll, kqueue, I/O completion
+//!   ports, poll), see [`pollset`](crate::pollset) and [`completion`](crate::completion)
 //! - **Event dispatching**: Cross-platform event management and message delivery to NIFs
 //!
 //! ## Note on Naming
@@ -38,9 +41,15 @@
 
 use std::time::Duration;
 use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::collections::HashMap;
 use std::ptr;
 use std::hash::Hash;
+use std::thread::JoinHandle;
+
+use infrastructure_driver_api::{DriverSelectFlags, SelectTable};
+
+use crate::pollset::{ready_events, PollBackend, PollSet, READY_ERROR, READY_READ, READY_WRITE};
 
 /// Erlang Process ID type
 ///
@@ -75,7 +84,7 @@ pub enum IoEventType {
 #[derive(Debug, Clone)]
 pub struct IoEvent {
     /// File descriptor that triggered the event
-    pub fd: i32,
+    pub fd: SysFdType,
     /// Type of event
     pub event_type: IoEventType,
 }
@@ -107,10 +116,15 @@ impl PollThreadId {
 pub struct CheckIoConfig {
     /// Maximum number of file descriptors
     pub max_files: usize,
-    /// Number of pollsets
+    /// Number of pollsets (`+IOp`)
+    ///
+    /// File descriptors are spread over the pollsets by hashing. At most
+    /// `num_poll_threads` pollsets are created.
     pub num_pollsets: usize,
-    /// Number of poll threads
+    /// Number of poll threads (`+IOt`)
     pub num_poll_threads: usize,
+    /// OS polling mechanism
+    pub backend: PollBackend,
 }
 
 impl Default for CheckIoConfig {
@@ -119,6 +133,7 @@ impl Default for CheckIoConfig {
             max_files: 1024,
             num_pollsets: 1,
             num_poll_threads: 1,
+            backend: PollBackend::native(),
         }
     }
 }
@@ -127,67 +142,48 @@ impl Default for CheckIoConfig {
 struct PollThreadState {
     id: PollThreadId,
     interrupted: bool,
+    /// Index of the pollset the thread waits on
+    pollset: usize,
 }
 
-/// Pollset for tracking file descriptors
+/// Handler delivering select messages to Erlang processes
 ///
-/// Maintains a set of file descriptors to monitor for I/O events.
-/// This is a simplified version that uses poll() on Unix and select() on Windows.
-struct PollSet {
-    /// File descriptors and their events to monitor
-    fds: Vec<(SysFdType, u32)>, // (fd, events: read=1, write=2, error=4)
-    /// Wakeup pipe for interrupting polling (Unix only)
-    #[cfg(unix)]
-    wakeup_pipe: Option<(i32, i32)>, // (read_fd, write_fd)
-}
+/// Installed by the runtime integration layer with
+/// [`CheckIo::set_select_handler`]. It receives the process to deliver to and
+/// the message, and is responsible for building the message term and queueing
+/// it to the process.
+pub type SelectMessageHandler = Arc<dyn Fn(ErlangPid, SelectMessage) + Send + Sync>;
 
-impl PollSet {
-    fn new() -> Self {
-        Self {
-            fds: Vec::new(),
-            #[cfg(unix)]
-            wakeup_pipe: None,
-        }
-    }
-    
-    /// Add a file descriptor to monitor
-    fn add_fd(&mut self, fd: SysFdType, events: u32) {
-        // Check if fd already exists
-        if let Some((_, existing_events)) = self.fds.iter_mut().find(|(f, _)| *f == fd) {
-            *existing_events |= events;
-        } else {
-            self.fds.push((fd, events));
-        }
-    }
-    
-    /// Remove a file descriptor
-    fn remove_fd(&mut self, fd: SysFdType) {
-        self.fds.retain(|(f, _)| *f != fd);
-    }
-    
-    /// Update events for a file descriptor
-    fn update_fd(&mut self, fd: SysFdType, events: u32) {
-        if let Some((_, existing_events)) = self.fds.iter_mut().find(|(f, _)| *f == fd) {
-            *existing_events = events;
-        } else if events != 0 {
-            self.fds.push((fd, events));
-        }
-    }
-    
-    /// Get all file descriptors
-    fn get_fds(&self) -> &[(SysFdType, u32)] {
-        &self.fds
-    }
-}
+/// Handler running a driver's `ready_input` for a readable fd
+///
+/// Installed with [`CheckIo::set_driver_select`]. It receives the port and
+/// the fd, and must make the port run `ready_input` once, typically by
+/// scheduling an input port task. Input on the fd is not polled again until
+/// the port has re-armed it through `SelectTable::input_handled`.
+pub type DriverInputHandler = Arc<dyn Fn(u64, i32) + Send + Sync>;
 
 /// Check I/O manager
+///
+/// File descriptors are assigned to a pollset by hashing, and each poll
+/// thread waits on one pollset: poll thread `i` on pollset `i % num_pollsets`.
+///
+/// Clones share the poll threads, pollsets, event state and select handler.
+#[derive(Clone)]
 pub struct CheckIo {
     config: CheckIoConfig,
     poll_threads: Arc<Mutex<HashMap<PollThreadId, PollThreadState>>>,
-    /// Pollset for each thread
-    pollsets: Arc<RwLock<HashMap<PollThreadId, PollSet>>>,
+    /// Pollsets, indexed by `fd_hash(fd) % pollsets.len()`
+    pollsets: Arc<Vec<PollSet>>,
     /// Event state manager
     event_state_manager: Arc<FdEventStateManager>,
+    /// Handler delivering select messages
+    select_handler: Arc<RwLock<Option<SelectMessageHandler>>>,
+    /// Select table of the port drivers and the handler running `ready_input`
+    driver_select: Arc<RwLock<Option<(&'static SelectTable, DriverInputHandler)>>>,
+    /// OS threads started by [`CheckIo::start_poll_threads`]
+    poll_thread_handles: Arc<Mutex<Vec<JoinHandle<()>>>>,
+    /// Set while the poll threads are being stopped
+    shutdown: Arc<AtomicBool>,
 }
 
 impl CheckIo {
@@ -197,38 +193,90 @@ impl CheckIo {
     }
     
     /// Create a new check I/O manager with custom configuration
-    pub fn with_config(config: CheckIoConfig) -> Self {
-        let mut poll_threads = HashMap::new();
-        let mut pollsets = HashMap::new();
+    ///
+    /// If the configured backend is not available, or its pollsets cannot be
+    /// created, the `poll` backend is used instead.
+    pub fn with_config(mut config: CheckIoConfig) -> Self {
+        config.num_poll_threads = config.num_poll_threads.max(1);
+        config.num_pollsets = config.num_pollsets.clamp(1, config.num_poll_threads);
+        
+        let pollsets: Vec<PollSet> = match (0..config.num_pollsets)
+            .map(|_| PollSet::new(config.backend))
+            .collect::<Result<_, _>>()
+        {
+            Ok(pollsets) => pollsets,
+            Err(_) => {
+                config.backend = PollBackend::Poll;
+                (0..config.num_pollsets)
+                    .map(|_| PollSet::new(PollBackend::Poll).expect("poll backend is always available"))
+                    .collect()
+            }
+        };
         
-        // Create default poll thread
-        let thread_id = PollThreadId::new(0);
-        poll_threads.insert(
-            thread_id,
-            PollThreadState {
-                id: thread_id,
-                interrupted: false,
-            },
-        );
-        pollsets.insert(thread_id, PollSet::new());
+        // Create the poll threads
+        let mut poll_threads = HashMap::new();
+        for id in 0..config.num_poll_threads {
+            let thread_id = PollThreadId::new(id as i32);
+            poll_threads.insert(
+                thread_id,
+                PollThreadState {
+                    id: thread_id,
+                    interrupted: false,
+                    pollset: id % config.num_pollsets,
+                },
+            );
+        }
         
         let event_state_manager = Arc::new(FdEventStateManager::new(config.max_files));
         
         Self {
             config,
             poll_threads: Arc::new(Mutex::new(poll_threads)),
-            pollsets: Arc::new(RwLock::new(pollsets)),
+            pollsets: Arc::new(pollsets),
             event_state_manager,
+            select_handler: Arc::new(RwLock::new(None)),
+            driver_select: Arc::new(RwLock::new(None)),
+            poll_thread_handles: Arc::new(Mutex::new(Vec::new())),
+            shutdown: Arc::new(AtomicBool::new(false)),
         }
     }
     
+    /// Install the handler delivering select messages
+    ///
+    /// Without a handler, select messages are dropped (and logged in debug
+    /// builds).
+    pub fn set_select_handler(&self, handler: SelectMessageHandler) {
+        *self.select_handler.write().unwrap() = Some(handler);
+    }
+    
+    /// Poll the fds selected by port drivers
+    ///
+    /// Pollset changes requested through `driver_select` are applied by the
+    /// poll threads; the table wakes them whenever changes are queued. An fd
+    /// removed from the pollset is confirmed with `pollset_removed`, which
+    /// runs a deferred `stop_select`. Readable fds are handed to `handler`.
+    pub fn set_driver_select(&self, table: &'static SelectTable, handler: DriverInputHandler) {
+        *self.driver_select.write().unwrap() = Some((table, handler));
+        let pollsets = Arc::clone(&self.pollsets);
+        table.set_pollset_notifier(Arc::new(move || {
+            for pollset in pollsets.iter() {
+                pollset.wake();
+            }
+        }));
+        self.apply_driver_select_updates();
+    }
+    
     /// Check for I/O events
     ///
-    /// Waits for I/O events on registered file descriptors until either:
+    /// Waits for I/O events on the file descriptors of the thread's pollset
+    /// until either:
     /// - An event occurs
     /// - The timeout expires
     /// - The poll thread is interrupted
     ///
+    /// Select messages for the ready file descriptors are delivered before
+    /// returning.
+    ///
     /// # Arguments
     ///
     /// * `thread_id` - Poll thread to use for checking
@@ -243,7 +291,7 @@ impl CheckIo {
     pub fn check(
         &self,
         thread_id: PollThreadId,
-        _timeout: Option<Duration>,
+        timeout: Option<Duration>,
         _poll_only_thread: bool,
     ) -> Result<Option<Vec<IoEvent>>, CheckIoError> {
         let mut threads = self.poll_threads.lock().unwrap();
@@ -257,33 +305,29 @@ impl CheckIo {
             return Ok(None);
         }
         
-        // Perform actual polling using platform-specific mechanisms
-        // On Unix: uses poll() system call
-        // On Windows: uses select() (can be enhanced with WaitForMultipleObjects)
-        
-        // Get the pollset for this thread
-        let pollsets = self.pollsets.read().unwrap();
-        let pollset = pollsets.get(&thread_id)
-            .ok_or(CheckIoError::InvalidThreadId)?;
-        
-        // Get all file descriptors to monitor
-        let fds_to_poll = pollset.get_fds().to_vec();
-        drop(pollsets);
+        let pollset = &self.pollsets[thread_state.pollset];
         drop(threads);
         
-        if fds_to_poll.is_empty() {
-            // No file descriptors to monitor, just wait for timeout or interrupt
-            if let Some(timeout) = _timeout {
-                std::thread::sleep(timeout);
+        self.apply_driver_select_updates();
+        let (ready, woken) = pollset.wait(timeout)?;
+        if woken {
+            // While shutting down the wakeup is left pending, so that every
+            // poll thread returns
+            if !self.shutdown.load(Ordering::Acquire) {
+                pollset.drain_wakeup();
+            }
+            if let Some(thread_state) = self.poll_threads.lock().unwrap().get_mut(&thread_id) {
+                thread_state.interrupted = false;
             }
-            return Ok(None);
         }
         
-        // Perform platform-specific polling
-        let events = self.poll_fds(&fds_to_poll, _timeout)?;
+        let mut events = Vec::new();
+        for (fd, ready) in ready {
+            self.dispatch(fd, ready, &mut events);
+        }
         
         if events.is_empty() {
-            Ok(None) // Timeout or no events
+            Ok(None) // Timeout, interrupted or no events
         } else {
             Ok(Some(events))
         }
@@ -292,7 +336,8 @@ impl CheckIo {
     /// Interrupt a poll thread
     ///
     /// Wakes up a poll thread that is waiting in `check_io`, allowing it
-    /// to execute other code or exit.
+    /// to execute other code or exit. Other poll threads waiting on the same
+    /// pollset may wake up as well.
     ///
     /// # Arguments
     ///
@@ -305,13 +350,17 @@ impl CheckIo {
             .ok_or(CheckIoError::InvalidThreadId)?;
         
         thread_state.interrupted = set;
+        if set {
+            self.pollsets[thread_state.pollset].wake();
+        }
         Ok(())
     }
     
     /// Create a new poll thread
     ///
     /// Creates a new poll thread structure associated with the given ID.
-    /// The ID must be unique.
+    /// The ID must be unique. The thread waits on pollset
+    /// `id % num_pollsets`.
     ///
     /// # Arguments
     ///
@@ -334,18 +383,71 @@ impl CheckIo {
             PollThreadState {
                 id: thread_id,
                 interrupted: false,
+                pollset: id.rem_euclid(self.pollsets.len() as i32) as usize,
             },
         );
         
-        // Create pollset for this thread
-        let mut pollsets = self.pollsets.write().unwrap();
-        pollsets.insert(thread_id, PollSet::new());
-        drop(pollsets);
-        drop(threads);
-        
         Ok(thread_id)
     }
     
+    /// Poll thread waiting on the pollset of a file descriptor
+    ///
+    /// Callers driving polling themselves use this thread with
+    /// [`CheckIo::check`] to see the events of `fd`.
+    pub fn poll_thread_for(&self, fd: SysFdType) -> PollThreadId {
+        PollThreadId::new(self.pollset_index(fd) as i32)
+    }
+    
+    /// Start the configured number of poll threads (`+IOt`)
+    ///
+    /// Each OS thread runs [`CheckIo::check`] for poll thread `0..num_poll_threads`
+    /// until [`CheckIo::stop_poll_threads`] is called, delivering select
+    /// messages through the select message handler.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - Threads started
+    /// * `Err(CheckIoError::ThreadIdExists)` - Poll threads already running
+    pub fn start_poll_threads(&self) -> Result<(), CheckIoError> {
+        let mut handles = self.poll_thread_handles.lock().unwrap();
+        if !handles.is_empty() {
+            return Err(CheckIoError::ThreadIdExists);
+        }
+        for id in 0..self.config.num_poll_threads {
+            let check_io = self.clone();
+            let thread_id = PollThreadId::new(id as i32);
+            let handle = std::thread::Builder::new()
+                .name(format!("erts_poll_{}", id))
+                .spawn(move || {
+                    while !check_io.shutdown.load(Ordering::Acquire) {
+                        if check_io.check(thread_id, None, true).is_err() {
+                            break;
+                        }
+                    }
+                })
+                .map_err(|_| CheckIoError::PollFailed)?;
+            handles.push(handle);
+        }
+        Ok(())
+    }
+    
+    /// Stop the poll threads started by [`CheckIo::start_poll_threads`] and
+    /// wait for them to exit
+    pub fn stop_poll_threads(&self) {
+        let handles: Vec<_> = self.poll_thread_handles.lock().unwrap().drain(..).collect();
+        self.shutdown.store(true, Ordering::Release);
+        for pollset in self.pollsets.iter() {
+            pollset.wake();
+        }
+        for handle in handles {
+            let _ = handle.join();
+        }
+        self.shutdown.store(false, Ordering::Release);
+        for pollset in self.pollsets.iter() {
+            pollset.drain_wakeup();
+        }
+    }
+    
     /// Get check I/O information
     ///
     /// Returns information about the current state of the check I/O subsystem,
@@ -361,6 +463,7 @@ impl CheckIo {
             config: self.config.clone(),
             num_active_threads: threads.len(),
             max_files: self.config.max_files,
+            num_monitored_fds: self.pollsets.iter().map(PollSet::len).sum(),
         }
     }
     
@@ -390,136 +493,85 @@ impl CheckIo {
         // This is a no-op for now, but could be used to re-enable events
     }
     
-    /// Poll file descriptors for events
-    ///
-    /// Performs platform-specific polling on the given file descriptors.
+    /// Turn the readiness of a file descriptor into events and select messages
     ///
     /// # Arguments
     ///
-    /// * `fds` - File descriptors and their events to monitor
-    /// * `timeout` - Maximum time to wait for events
-    ///
-    /// # Returns
-    ///
-    /// * `Ok(Vec<IoEvent>)` - Events that occurred
-    /// * `Err(CheckIoError)` - Error during polling
-    #[cfg(unix)]
-    fn poll_fds(&self, fds: &[(SysFdType, u32)], timeout: Option<Duration>) -> Result<Vec<IoEvent>, CheckIoError> {
-        use libc::{poll, pollfd, POLLIN, POLLOUT, POLLERR, POLLHUP, POLLNVAL};
-        
-        // Convert to pollfd structures
-        let mut poll_fds: Vec<pollfd> = fds.iter().map(|(fd, events)| {
-            let mut poll_events: libc::c_short = 0;
-            if (events & (NifSelectFlags::Read as u32)) != 0 {
-                poll_events |= POLLIN;
-            }
-            if (events & (NifSelectFlags::Write as u32)) != 0 {
-                poll_events |= POLLOUT;
-            }
-            if (events & (NifSelectFlags::Error as u32)) != 0 {
-                poll_events |= POLLERR;
-            }
-            
-            pollfd {
-                fd: *fd,
-                events: poll_events,
-                revents: 0,
-            }
-        }).collect();
-        
-        if poll_fds.is_empty() {
-            return Ok(Vec::new());
-        }
-        
-        // Convert timeout to milliseconds
-        let timeout_ms = timeout.map(|d| {
-            let ms = d.as_millis() as libc::c_int;
-            if ms > 0 { ms } else { -1 } // -1 means wait indefinitely
-        }).unwrap_or(-1);
-        
-        // Perform poll - this is the only unsafe block, but it's properly encapsulated
-        // Safety: poll_fds is a Vec, so:
-        // - as_mut_ptr() returns a valid pointer to the Vec's data
-        // - The pointer remains valid for the duration of the call (Vec owns the data)
-        // - The length is correct (from Vec.len())
-        // - poll() is a well-defined system call that doesn't invalidate the pointer
-        let result = unsafe {
-            poll(poll_fds.as_mut_ptr(), poll_fds.len() as libc::nfds_t, timeout_ms)
-        };
-        
-        if result < 0 {
-            let errno = std::io::Error::last_os_error().raw_os_error().unwrap_or(0);
-            if errno == libc::EINTR {
-                // Interrupted by signal, return empty (will be handled by interrupt check)
-                return Ok(Vec::new());
-            }
-            return Err(CheckIoError::PollFailed);
+    /// * `fd` - Ready file descriptor
+    /// * `ready` - `READY_*` flags reported by the pollset
+    /// * `events` - Events to append to
+    fn dispatch(&self, fd: SysFdType, ready: u32, events: &mut Vec<IoEvent>) {
+        events.extend(ready_events(fd, ready));
+        if ready & (READY_READ | READY_ERROR) != 0 {
+            self.dispatch_driver_input(fd);
         }
         
-        if result == 0 {
-            // Timeout
-            return Ok(Vec::new());
-        }
-        
-        // Convert results to IoEvent structures
-        let mut events = Vec::new();
-        for (i, poll_fd) in poll_fds.iter().enumerate() {
-            if poll_fd.revents != 0 {
-                let fd = fds[i].0;
-                
-                // Check for read events
-                if (poll_fd.revents & (POLLIN | POLLHUP)) != 0 {
-                    events.push(IoEvent {
-                        fd,
-                        event_type: IoEventType::Read,
-                    });
-                }
-                
-                // Check for write events
-                if (poll_fd.revents & POLLOUT) != 0 {
-                    events.push(IoEvent {
-                        fd,
-                        event_type: IoEventType::Write,
-                    });
-                }
-                
-                // Check for error events
-                if (poll_fd.revents & (POLLERR | POLLNVAL)) != 0 {
-                    events.push(IoEvent {
-                        fd,
-                        event_type: IoEventType::Error,
-                    });
-                }
-                
-                // Notify the event state manager and send select messages
-                if let Ok(state_arc) = self.event_state_manager.get_or_create_state(fd) {
-                    let state = state_arc.lock().unwrap();
-                    if state.pid != 0 {
-                        // Send select messages for each event type
-                        // Note: send_select_msg constructs the message but requires
-                        // runtime integration to actually queue it to the process
-                        if (poll_fd.revents & (POLLIN | POLLHUP)) != 0 {
-                            send_select_msg(fd, IoEventType::Read, state.pid, state.ref_term);
-                        }
-                        if (poll_fd.revents & POLLOUT) != 0 {
-                            send_select_msg(fd, IoEventType::Write, state.pid, state.ref_term);
-                        }
-                        if (poll_fd.revents & (POLLERR | POLLNVAL)) != 0 {
-                            send_select_msg(fd, IoEventType::Error, state.pid, state.ref_term);
-                        }
+        // Deliver select messages for the selected events. A select
+        // is one-shot: the event is deselected once its message is sent
+        // and must be selected again for the next notification.
+        if let Ok(state_arc) = self.event_state_manager.get_or_create_state(fd) {
+            let mut state = state_arc.lock().unwrap();
+            if state.pid != 0 {
+                let is_ready = [
+                    (IoEventType::Read, ready & (READY_READ | READY_ERROR) != 0),
+                    (IoEventType::Write, ready & (READY_WRITE | READY_ERROR) != 0),
+                    (IoEventType::Error, ready & READY_ERROR != 0),
+                ];
+                for (event_type, is_ready) in is_ready {
+                    if is_ready && state.active_events & event_flag(event_type) != 0 {
+                        let handler = self.select_handler.read().unwrap().clone();
+                        send_select_msg(handler.as_ref(), fd, event_type, &state);
+                        clear_select_event(&mut state, event_type);
                     }
                 }
+                // A descriptor that can no longer be re-armed stays
+                // deselected; selecting it again reports the failure
+                let _ = self.update_pollset(fd, state.active_events);
             }
         }
-        
-        Ok(events)
     }
     
-    #[cfg(windows)]
-    fn poll_fds(&self, _fds: &[(SysFdType, u32)], _timeout: Option<Duration>) -> Result<Vec<IoEvent>, CheckIoError> {
-        // Windows implementation using select() or WaitForMultipleObjects
-        // For now, return empty (Windows support can be added later)
-        Ok(Vec::new())
+    /// Apply the pollset changes queued by `driver_select`
+    fn apply_driver_select_updates(&self) {
+        let Some((table, _)) = self.driver_select.read().unwrap().clone() else {
+            return;
+        };
+        for update in table.take_pollset_updates() {
+            // A descriptor the kernel rejects is left out of the pollset
+            let _ = self.update_pollset(driver_fd(update.fd), update.events);
+            if update.events == 0 {
+                table.pollset_removed(update.fd);
+            }
+        }
+    }
+    
+    /// Hand a readable fd selected by a driver to the driver input handler
+    ///
+    /// Input is masked in the pollset before the handler runs, so the fd is
+    /// reported once until the port re-arms it.
+    fn dispatch_driver_input(&self, fd: SysFdType) {
+        let Some((table, handler)) = self.driver_select.read().unwrap().clone() else {
+            return;
+        };
+        let Some(event) = driver_event(fd) else {
+            return;
+        };
+        let Some(port) = table.input_ready(event) else {
+            return;
+        };
+        let events = table.selected_events(event).unwrap_or(0) & !(DriverSelectFlags::Read as u32);
+        let _ = self.update_pollset(fd, events);
+        handler(port, event);
+    }
+    
+    /// Index of the pollset monitoring `fd`
+    fn pollset_index(&self, fd: SysFdType) -> usize {
+        fd_hash(fd) % self.pollsets.len()
+    }
+    
+    /// Update the events monitored for `fd`, removing it when none are left
+    fn update_pollset(&self, fd: SysFdType, events: u32) -> std::io::Result<()> {
+        self.pollsets[self.pollset_index(fd)].update_fd(fd, events)
     }
 }
 
@@ -538,6 +590,8 @@ pub struct CheckIoInfo {
     pub num_active_threads: usize,
     /// Maximum number of file descriptors
     pub max_files: usize,
+    /// Number of file descriptors monitored in the pollsets
+    pub num_monitored_fds: usize,
 }
 
 /// Check I/O errors
@@ -778,6 +832,28 @@ fn fd_hash(fd: SysFdType) -> usize {
     hasher.finish() as usize
 }
 
+/// Pollset fd of a `driver_select` event
+#[cfg(unix)]
+fn driver_fd(event: i32) -> SysFdType {
+    event
+}
+
+#[cfg(windows)]
+fn driver_fd(event: i32) -> SysFdType {
+    event as SysFdType
+}
+
+/// `driver_select` event of a pollset fd, if it can be one
+#[cfg(unix)]
+fn driver_event(fd: SysFdType) -> Option<i32> {
+    Some(fd)
+}
+
+#[cfg(windows)]
+fn driver_event(fd: SysFdType) -> Option<i32> {
+    i32::try_from(fd).ok()
+}
+
 /// Grow event state array
 ///
 /// Ensures that the event state array is large enough to accommodate the given
@@ -850,114 +926,62 @@ impl From<IoEventType> for SelectEventAtom {
 
 /// Send select message
 ///
-/// Constructs and sends a message to an Erlang process when a select event occurs.
-/// The message format is: `{select, Resource, Ref, ready_input | ready_output | ready_error}`
+/// Constructs the message sent to an Erlang process when a select event occurs,
+/// `{select, Resource, Ref, ready_input | ready_output | ready_error}`, and hands
+/// it to the select message handler, which builds the term and queues it to the
+/// process. Without a handler the message is dropped.
 ///
 /// # Arguments
 ///
+/// * `handler` - Handler delivering the message, if one is installed
 /// * `fd` - File descriptor that triggered the event
 /// * `event_type` - Type of I/O event (read, write, error)
-/// * `pid` - Process ID to send the message to
-/// * `ref_term` - Reference term for message identification
-///
-/// # Implementation Notes
-///
-/// This function constructs the message structure. In a full implementation integrated
-/// with the Erlang runtime, this would:
-/// 1. Create an Erlang tuple: `{select, Resource, Ref, EventAtom}`
-///    - `select` is the atom `am_select`
-///    - `Resource` is a magic reference to the resource object
-///    - `Ref` is the reference term
-///    - `EventAtom` is one of `am_ready_input`, `am_ready_output`, or `am_ready_error`
-/// 2. Send the message to the process identified by `pid` using the runtime's message queue
-/// 3. Handle process death (if process no longer exists, clear the event)
-///
-/// Currently, this is a placeholder that constructs the message structure but doesn't
-/// actually send it. The actual message sending must be done by the runtime integration
-/// layer which has access to the process registry and message queue.
+/// * `state` - Event state holding the resource, process and reference
 ///
 /// # See Also
 ///
 /// - `erts/emulator/sys/common/erl_check_io.c:send_select_msg()` - C implementation
 /// - `erts/emulator/sys/common/erl_check_io.c:prepare_select_msg()` - Message preparation
-fn send_select_msg(fd: SysFdType, event_type: IoEventType, pid: ErlangPid, ref_term: ErlangTerm) {
-    // Get the event state to retrieve the resource
-    // Note: In a full implementation, we'd need access to the event state manager
-    // For now, we construct the message structure that would be sent
-    
-    let event_atom = SelectEventAtom::from(event_type);
-    
-    // Construct the select message
-    // In a full implementation, this would create an Erlang term tuple:
-    // {select, Resource, Ref, EventAtom}
-    let _message = SelectMessage {
-        resource: ptr::null_mut(), // Would be retrieved from event state
-        ref_term,
-        event_atom,
+fn send_select_msg(
+    handler: Option<&SelectMessageHandler>,
+    fd: SysFdType,
+    event_type: IoEventType,
+    state: &NifSelectEventState,
+) {
+    let message = SelectMessage {
+        resource: state.resource,
+        ref_term: state.ref_term,
+        event_atom: SelectEventAtom::from(event_type),
     };
     
-    // ========================================================================
-    // Runtime Integration Required
-    // ========================================================================
-    //
-    // The following steps need to be performed by the runtime integration layer
-    // to actually send the message to the Erlang process:
-    //
-    // 1. Process Lookup:
-    //    - Look up the process by pid using erts_proc_lookup() or equivalent
-    //    - If process doesn't exist (process has died), clear the event and return
-    //    - This prevents sending messages to dead processes
-    //
-    // 2. Message Term Creation:
-    //    - Create the Erlang tuple: {select, Resource, Ref, EventAtom}
-    //    - Use enif_make_tuple4() or equivalent:
-    //      * enif_make_atom(env, "select") -> atom "select"
-    //      * enif_make_resource(env, resource) or enif_make_magic_ref() -> Resource
-    //      * ref_term (already an Erlang term, may need validation)
-    //      * enif_make_atom(env, "ready_input" | "ready_output" | "ready_error") -> EventAtom
-    //
-    // 3. Message Queueing:
-    //    - Send the message using erts_queue_message() or equivalent runtime function
-    //    - The message will be delivered to the process's mailbox
-    //    - Handle any errors (e.g., process mailbox full, process dying)
-    //
-    // 4. Error Handling:
-    //    - If process lookup fails, clear the event state
-    //    - If message creation fails, log error and clear event
-    //    - If message queueing fails, handle appropriately (retry or clear)
-    //
-    // For now, we log the message that would be sent (in debug builds only)
-    #[cfg(debug_assertions)]
-    {
-        let event_str = match event_atom {
-            SelectEventAtom::ReadyInput => "ready_input",
-            SelectEventAtom::ReadyOutput => "ready_output",
-            SelectEventAtom::ReadyError => "ready_error",
-        };
-        eprintln!(
-            "select message would be sent: fd={}, pid={}, ref={}, event={}",
-            fd, pid, ref_term, event_str
-        );
+    match handler {
+        Some(handler) => handler(state.pid, message),
+        None => {
+            #[cfg(debug_assertions)]
+            eprintln!(
+                "select message dropped, no handler: fd={}, pid={}, ref={}, event={:?}",
+                fd, state.pid, message.ref_term, message.event_atom
+            );
+            #[cfg(not(debug_assertions))]
+            let _ = fd;
+        }
+    }
+}
+
+/// Select flag of an event type
+fn event_flag(event_type: IoEventType) -> u32 {
+    match event_type {
+        IoEventType::Read => NifSelectFlags::Read as u32,
+        IoEventType::Write => NifSelectFlags::Write as u32,
+        IoEventType::Error => NifSelectFlags::Error as u32,
     }
-    
-    // In a production implementation, the runtime integration layer would:
-    // - Look up the process by pid
-    // - Create the Erlang term tuple
-    // - Queue the message to the process's mailbox
-    // - Handle process death by clearing the event
 }
 
 /// Clear select event
 ///
 /// Clears a select event, cleaning up any pending messages.
 fn clear_select_event(state: &mut NifSelectEventState, event_type: IoEventType) {
-    let flag = match event_type {
-        IoEventType::Read => NifSelectFlags::Read as u32,
-        IoEventType::Write => NifSelectFlags::Write as u32,
-        IoEventType::Error => NifSelectFlags::Error as u32,
-    };
-    
-    state.active_events &= !flag;
+    state.active_events &= !event_flag(event_type);
     
     // If no events are active, clear the resource
     if state.active_events == 0 {
@@ -1000,6 +1024,8 @@ pub fn enif_select(
     if (mode & flags) != 0 {
         // Stop monitoring
         erase_fd_ev_state(&manager, event);
+        // The descriptor is forgotten even if it was already closed
+        let _ = check_io.pollsets[check_io.pollset_index(event)].remove_fd(event);
         return Ok(NifSelectResult::new(NifSelectResult::STOP_CALLED));
     }
     
@@ -1023,6 +1049,9 @@ pub fn enif_select(
                 clear_select_event(&mut state, IoEventType::Error);
                 result_flags |= NifSelectResult::ERROR_CANCELLED;
             }
+            if check_io.update_pollset(event, state.active_events).is_err() {
+                result_flags |= NifSelectResult::FAILED;
+            }
             
             return Ok(NifSelectResult::new(result_flags));
         }
@@ -1036,6 +1065,7 @@ pub fn enif_select(
     
     let state_arc = manager.get_or_create_state(event)?;
     let mut state = state_arc.lock().unwrap();
+    let previous_events = state.active_events;
     
     // Update active events
     if (mode & (NifSelectFlags::Read as u32)) != 0 {
@@ -1053,13 +1083,12 @@ pub fn enif_select(
     state.pid = _pid.unwrap_or(0);
     state.ref_term = _ref;
     
-    // Add file descriptor to pollset
-    let mut pollsets = check_io.pollsets.write().unwrap();
-    let default_thread_id = PollThreadId::new(0);
-    if let Some(pollset) = pollsets.get_mut(&default_thread_id) {
-        pollset.add_fd(event, state.active_events);
+    // Add file descriptor to its pollset. If the kernel rejects it, no
+    // select message would ever arrive, so the select fails instead.
+    if check_io.pollsets[check_io.pollset_index(event)].add_fd(event, state.active_events).is_err() {
+        state.active_events = previous_events;
+        return Ok(NifSelectResult::new(NifSelectResult::FAILED));
     }
-    drop(pollsets);
     
     Ok(NifSelectResult::SUCCESS)
 }
@@ -1378,6 +1407,7 @@ mod tests {
             max_files: 2048,
             num_pollsets: 2,
             num_poll_threads: 2,
+            ..Default::default()
         };
         let check_io = CheckIo::with_config(config);
         assert_eq!(check_io.max_files(), 2048);
@@ -1613,10 +1643,21 @@ mod tests {
         assert!(result2.has_flag(NifSelectResult::WRITE_CANCELLED));
     }
     
+    /// A socket to select on, open while the returned stream is alive
+    #[cfg(unix)]
+    fn test_socket() -> (std::os::unix::net::UnixStream, SysFdType) {
+        use std::os::unix::io::AsRawFd;
+
+        let (socket, _) = std::os::unix::net::UnixStream::pair().unwrap();
+        let fd = socket.as_raw_fd();
+        (socket, fd)
+    }
+
     #[test]
+    #[cfg(unix)]
     fn test_enif_select_read() {
         let check_io = CheckIo::new();
-        let fd: SysFdType = 5; // Test file descriptor
+        let (_socket, fd) = test_socket();
         
         // Select for read events
         let mode = NifSelectFlags::Read as u32;
@@ -1628,9 +1669,10 @@ mod tests {
     }
     
     #[test]
+    #[cfg(unix)]
     fn test_enif_select_write() {
         let check_io = CheckIo::new();
-        let fd: SysFdType = 6;
+        let (_socket, fd) = test_socket();
         
         // Select for write events
         let mode = NifSelectFlags::Write as u32;
@@ -1642,9 +1684,10 @@ mod tests {
     }
     
     #[test]
+    #[cfg(unix)]
     fn test_enif_select_read_write() {
         let check_io = CheckIo::new();
-        let fd: SysFdType = 7;
+        let (_socket, fd) = test_socket();
         
         // Select for both read and write events
         let mode = NifSelectFlags::combine(&[NifSelectFlags::Read, NifSelectFlags::Write]);
@@ -1655,6 +1698,19 @@ mod tests {
         assert!(select_result.is_success());
     }
     
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn test_enif_select_fails_when_kernel_rejects_fd() {
+        use std::os::unix::io::AsRawFd;
+
+        // epoll does not poll /dev/null, so no select message could arrive
+        let check_io = CheckIo::new();
+        let file = std::fs::File::open("/dev/null").unwrap();
+        let result = enif_select(&check_io, file.as_raw_fd(), NifSelectFlags::Read as u32, ptr::null_mut(), None, 0);
+        assert!(result.unwrap().has_flag(NifSelectResult::FAILED));
+        assert_eq!(check_io.info().num_monitored_fds, 0);
+    }
+
     #[test]
     fn test_enif_select_stop() {
         let check_io = CheckIo::new();
@@ -1750,28 +1806,161 @@ mod tests {
     }
     
     #[test]
-    fn test_pollset_operations() {
-        let mut pollset = PollSet::new();
+    #[cfg(unix)]
+    fn test_select_message_delivered_once() {
+        use std::os::unix::io::AsRawFd;
+        use std::net::{TcpListener, TcpStream};
+        use std::time::Duration;
+        
+        let check_io = CheckIo::new();
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+        let sink = delivered.clone();
+        check_io.set_select_handler(Arc::new(move |pid, message: SelectMessage| {
+            sink.lock().unwrap().push((pid, message.ref_term, message.event_atom));
+        }));
+        
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let fd = listener.as_raw_fd();
+        enif_select(&check_io, fd, NifSelectFlags::Read as u32, ptr::null_mut(), Some(42), 7).unwrap();
+        let _client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        
+        // Clones share the pollset, so either may drive polling
+        let events = check_io.clone().check(PollThreadId::new(0), Some(Duration::from_secs(5)), false).unwrap();
+        assert!(events.unwrap().iter().any(|e| e.fd == fd && e.event_type == IoEventType::Read));
+        assert_eq!(*delivered.lock().unwrap(), vec![(42, 7, SelectEventAtom::ReadyInput)]);
+        
+        // The select was consumed; nothing is monitored until selected again
+        let events = check_io.check(PollThreadId::new(0), Some(Duration::from_millis(10)), false).unwrap();
+        assert!(events.is_none());
+        assert_eq!(delivered.lock().unwrap().len(), 1);
+    }
+    
+    #[test]
+    fn test_fds_hashed_over_pollsets() {
+        let check_io = CheckIo::with_config(CheckIoConfig {
+            num_pollsets: 2,
+            num_poll_threads: 4,
+            ..Default::default()
+        });
+        assert_eq!(check_io.poll_thread_for(4), PollThreadId::new(0));
+        assert_eq!(check_io.poll_thread_for(5), PollThreadId::new(1));
+        
+        enif_select(&check_io, 4, NifSelectFlags::Read as u32, ptr::null_mut(), None, 0).unwrap();
+        enif_select(&check_io, 5, NifSelectFlags::Read as u32, ptr::null_mut(), None, 0).unwrap();
+        assert_eq!(check_io.pollsets[0].get_fds(), vec![(4, NifSelectFlags::Read as u32)]);
+        assert_eq!(check_io.pollsets[1].get_fds(), vec![(5, NifSelectFlags::Read as u32)]);
+        assert_eq!(check_io.info().num_monitored_fds, 2);
+        
+        // More pollsets than poll threads are not created
+        let check_io = CheckIo::with_config(CheckIoConfig {
+            num_pollsets: 4,
+            num_poll_threads: 2,
+            ..Default::default()
+        });
+        assert_eq!(check_io.info().config.num_pollsets, 2);
+    }
+    
+    #[test]
+    #[cfg(unix)]
+    fn test_interrupt_wakes_waiting_thread() {
+        let check_io = CheckIo::new();
+        let waiter = check_io.clone();
+        let handle = std::thread::spawn(move || waiter.check(PollThreadId::new(0), None, false));
+        std::thread::sleep(Duration::from_millis(20));
+        check_io.interrupt(PollThreadId::new(0), true).unwrap();
+        assert!(matches!(handle.join().unwrap(), Ok(None)));
+    }
+    
+    #[test]
+    #[cfg(unix)]
+    fn test_poll_threads_deliver_select_messages() {
+        use std::os::unix::io::AsRawFd;
+        use std::os::unix::net::UnixStream;
+        use std::io::Write;
+        
+        let check_io = CheckIo::with_config(CheckIoConfig {
+            num_pollsets: 2,
+            num_poll_threads: 2,
+            ..Default::default()
+        });
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let sender = Mutex::new(sender);
+        check_io.set_select_handler(Arc::new(move |pid, message: SelectMessage| {
+            sender.lock().unwrap().send((pid, message.ref_term)).unwrap();
+        }));
+        check_io.start_poll_threads().unwrap();
+        assert_eq!(check_io.start_poll_threads(), Err(CheckIoError::ThreadIdExists));
+        
+        let pairs: Vec<_> = (0..4).map(|_| UnixStream::pair().unwrap()).collect();
+        for (i, (_, reader)) in pairs.iter().enumerate() {
+            enif_select(&check_io, reader.as_raw_fd(), NifSelectFlags::Read as u32, ptr::null_mut(), Some(1), i as u64).unwrap();
+        }
+        for (writer, _) in &pairs {
+            let mut writer: &UnixStream = writer;
+            writer.write_all(b"x").unwrap();
+        }
+        let mut refs: Vec<u64> = (0..4)
+            .map(|_| receiver.recv_timeout(Duration::from_secs(5)).unwrap().1)
+            .collect();
+        refs.sort();
+        assert_eq!(refs, vec![0, 1, 2, 3]);
+        
+        check_io.stop_poll_threads();
+    }
+    
+    #[test]
+    #[cfg(unix)]
+    fn test_driver_select_input_dispatched_once() {
+        use infrastructure_driver_api::{DriverEntry, DriverError, DriverPort, DriverRegistry, PortTable};
+        use std::io::Write;
+        use std::os::unix::io::AsRawFd;
+        use std::os::unix::net::UnixStream;
         
-        // Add file descriptors
-        pollset.add_fd(5, NifSelectFlags::Read as u32);
-        pollset.add_fd(6, NifSelectFlags::Write as u32);
-        pollset.add_fd(7, NifSelectFlags::combine(&[NifSelectFlags::Read, NifSelectFlags::Write]));
+        struct InputDriver;
         
-        let fds = pollset.get_fds();
-        assert_eq!(fds.len(), 3);
+        impl DriverEntry for InputDriver {
+            fn driver_name(&self) -> &str {
+                "nif_io_input_drv"
+            }
+            
+            fn output(&self, _port: &DriverPort, _data: &[u8]) -> Result<(), DriverError> {
+                Ok(())
+            }
+        }
+        
+        let drivers = DriverRegistry::new();
+        drivers.add_driver_entry(Arc::new(InputDriver)).unwrap();
+        let port = PortTable::new().open_port(&drivers, "nif_io_input_drv").unwrap();
+        let table: &'static SelectTable = Box::leak(Box::new(SelectTable::new()));
         
-        // Update existing FD
-        pollset.update_fd(5, NifSelectFlags::combine(&[NifSelectFlags::Read, NifSelectFlags::Write]));
-        let fds = pollset.get_fds();
-        let fd5 = fds.iter().find(|(fd, _)| *fd == 5).unwrap();
-        assert_eq!(fd5.1, NifSelectFlags::combine(&[NifSelectFlags::Read, NifSelectFlags::Write]));
+        let check_io = CheckIo::new();
+        let inputs = Arc::new(Mutex::new(Vec::new()));
+        let sink = inputs.clone();
+        check_io.set_driver_select(table, Arc::new(move |port, event| {
+            sink.lock().unwrap().push((port, event));
+        }));
+        
+        let (mut writer, reader) = UnixStream::pair().unwrap();
+        let fd = reader.as_raw_fd();
+        let mode = DriverSelectFlags::Read as u32 | DriverSelectFlags::Use as u32;
+        table.driver_select(&port, fd, mode, true).unwrap();
+        writer.write_all(b"x").unwrap();
         
-        // Remove FD
-        pollset.remove_fd(6);
-        let fds = pollset.get_fds();
-        assert_eq!(fds.len(), 2);
-        assert!(fds.iter().find(|(fd, _)| *fd == 6).is_none());
+        let thread_id = PollThreadId::new(0);
+        check_io.check(thread_id, Some(Duration::from_secs(5)), false).unwrap();
+        assert_eq!(*inputs.lock().unwrap(), vec![(port.id(), fd)]);
+        
+        // Not reported again until the port has handled the input
+        check_io.check(thread_id, Some(Duration::from_millis(10)), false).unwrap();
+        assert_eq!(inputs.lock().unwrap().len(), 1);
+        table.input_handled(fd);
+        check_io.check(thread_id, Some(Duration::from_secs(5)), false).unwrap();
+        assert_eq!(inputs.lock().unwrap().len(), 2);
+        
+        // Deselecting removes the fd from the pollset
+        table.driver_select(&port, fd, DriverSelectFlags::Read as u32, false).unwrap();
+        check_io.check(thread_id, Some(Duration::from_millis(10)), false).unwrap();
+        assert_eq!(inputs.lock().unwrap().len(), 2);
+        assert!(check_io.pollsets[0].get_fds().is_empty());
     }
 }
-
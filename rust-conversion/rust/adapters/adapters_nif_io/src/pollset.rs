@@ -0,0 +1,1093 @@
+//! Pollset Module
+//!
+//! Provides the OS polling backends used by [`CheckIo`](crate::CheckIo), based on
+//! `erl_poll.c`. Each pollset keeps the events selected for its file descriptors
+//! and registers them with the kernel:
+//!
+//! - **epoll** on Linux and Android
+//! - **kqueue** on macOS, iOS and the BSDs
+//! - **I/O completion ports** on Windows
+//! - **poll** on other Unix platforms, or when selected explicitly
+//!
+//! The epoll and kqueue backends register descriptors edge-triggered
+//! (`EPOLLET`, `EV_CLEAR`), so waiting costs time in the number of ready
+//! descriptors rather than the number of registered ones. Changing the events
+//! of a descriptor re-arms it, which reports it again if it is still ready.
+//! The poll backend is level-triggered and scans all registered descriptors.
+//! The completion port backend probes readiness with overlapped operations,
+//! see [`completion`](crate::completion).
+//!
+//! Each pollset owns a wakeup pipe (a wakeup packet on Windows), so that a
+//! thread waiting on it can be interrupted.
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::nif_io::{CheckIoError, IoEvent, IoEventType, NifSelectFlags, SysFdType};
+
+/// Ready for reading, or the peer hung up
+pub(crate) const READY_READ: u32 = NifSelectFlags::Read as u32;
+/// Ready for writing
+pub(crate) const READY_WRITE: u32 = NifSelectFlags::Write as u32;
+/// Error condition on the descriptor
+pub(crate) const READY_ERROR: u32 = NifSelectFlags::Error as u32;
+
+/// Maximum number of events returned by one wait
+const MAX_EVENTS: usize = 256;
+
+/// I/O events of a descriptor with the `READY_*` flags `ready`
+pub(crate) fn ready_events(fd: SysFdType, ready: u32) -> impl Iterator<Item = IoEvent> {
+    [
+        (READY_READ, IoEventType::Read),
+        (READY_WRITE, IoEventType::Write),
+        (READY_ERROR, IoEventType::Error),
+    ]
+    .into_iter()
+    .filter(move |(flag, _)| ready & flag != 0)
+    .map(move |(_, event_type)| IoEvent { fd, event_type })
+}
+
+/// OS polling mechanism of a pollset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PollBackend {
+    /// `epoll(7)`, edge-triggered (Linux and Android)
+    Epoll,
+    /// `kqueue(2)`, edge-triggered (macOS, iOS and the BSDs)
+    Kqueue,
+    /// I/O completion port, level-triggered for reads (Windows)
+    Iocp,
+    /// `poll(2)`, level-triggered, available on all Unix platforms
+    Poll,
+}
+
+impl PollBackend {
+    /// The scalable backend of this platform, or [`PollBackend::Poll`]
+    pub fn native() -> Self {
+        if cfg!(any(target_os = "linux", target_os = "android")) {
+            PollBackend::Epoll
+        } else if cfg!(any(
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd",
+            target_os = "dragonfly"
+        )) {
+            PollBackend::Kqueue
+        } else if cfg!(windows) {
+            PollBackend::Iocp
+        } else {
+            PollBackend::Poll
+        }
+    }
+
+    /// Whether the backend is available on this platform
+    pub fn is_available(self) -> bool {
+        self == PollBackend::Poll || self == Self::native()
+    }
+
+    /// Backend name
+    pub fn name(self) -> &'static str {
+        match self {
+            PollBackend::Epoll => "epoll",
+            PollBackend::Kqueue => "kqueue",
+            PollBackend::Iocp => "iocp",
+            PollBackend::Poll => "poll",
+        }
+    }
+}
+
+impl Default for PollBackend {
+    fn default() -> Self {
+        Self::native()
+    }
+}
+
+/// Kernel side of a pollset
+enum Backend {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    Epoll(epoll::Epoll),
+    #[cfg(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "dragonfly"
+    ))]
+    Kqueue(kqueue::Kqueue),
+    #[cfg(windows)]
+    Iocp(Box<iocp::Iocp>),
+    Poll,
+}
+
+/// Pollset for tracking file descriptors
+///
+/// Maintains the events selected for each file descriptor and mirrors them in
+/// the kernel backend. All methods take `&self`, so that one thread can wait
+/// while others change the selected events.
+pub(crate) struct PollSet {
+    /// Kernel backend
+    backend: Backend,
+    /// Selected events of each file descriptor (read=1, write=2, error=32)
+    fds: Mutex<HashMap<SysFdType, u32>>,
+    /// Wakeup pipe for interrupting a wait (read end, write end)
+    #[cfg(unix)]
+    wakeup_pipe: Option<(i32, i32)>,
+}
+
+impl PollSet {
+    /// Create a pollset using `backend`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(PollSet)` - Created pollset
+    /// * `Err(CheckIoError::NotSupported)` - Backend not available on this platform
+    /// * `Err(CheckIoError::PollFailed)` - Kernel backend could not be created
+    pub(crate) fn new(backend: PollBackend) -> Result<Self, CheckIoError> {
+        if !backend.is_available() {
+            return Err(CheckIoError::NotSupported);
+        }
+        let backend = match backend {
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            PollBackend::Epoll => Backend::Epoll(epoll::Epoll::new().map_err(|_| CheckIoError::PollFailed)?),
+            #[cfg(any(
+                target_os = "macos",
+                target_os = "ios",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd",
+                target_os = "dragonfly"
+            ))]
+            PollBackend::Kqueue => Backend::Kqueue(kqueue::Kqueue::new().map_err(|_| CheckIoError::PollFailed)?),
+            #[cfg(windows)]
+            PollBackend::Iocp => Backend::Iocp(Box::new(iocp::Iocp::new().map_err(|_| CheckIoError::PollFailed)?)),
+            _ => Backend::Poll,
+        };
+        let pollset = Self {
+            backend,
+            fds: Mutex::new(HashMap::new()),
+            #[cfg(unix)]
+            wakeup_pipe: wakeup::create(),
+        };
+        #[cfg(unix)]
+        if let Some((read_fd, _)) = pollset.wakeup_pipe {
+            pollset.register_wakeup(read_fd);
+        }
+        Ok(pollset)
+    }
+
+    /// Add events to monitor for a file descriptor
+    ///
+    /// # Returns
+    ///
+    /// * `Err(io::Error)` - The kernel backend rejected the descriptor; its
+    ///   previous events stay selected
+    pub(crate) fn add_fd(&self, fd: SysFdType, events: u32) -> io::Result<()> {
+        let mut fds = self.fds.lock().unwrap();
+        let old = fds.get(&fd).copied().unwrap_or(0);
+        self.set(&mut fds, fd, old, old | events)
+    }
+
+    /// Remove a file descriptor
+    ///
+    /// The descriptor is forgotten even if the kernel backend fails to
+    /// remove it, as it does for a descriptor that was already closed.
+    pub(crate) fn remove_fd(&self, fd: SysFdType) -> io::Result<()> {
+        let mut fds = self.fds.lock().unwrap();
+        match fds.get(&fd).copied() {
+            Some(old) => self.set(&mut fds, fd, old, 0),
+            None => Ok(()),
+        }
+    }
+
+    /// Replace the events monitored for a file descriptor, removing it when
+    /// none are left
+    ///
+    /// The descriptor is re-armed even if its events are unchanged.
+    pub(crate) fn update_fd(&self, fd: SysFdType, events: u32) -> io::Result<()> {
+        let mut fds = self.fds.lock().unwrap();
+        let old = fds.get(&fd).copied().unwrap_or(0);
+        if old != 0 || events != 0 {
+            self.set(&mut fds, fd, old, events)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Get all file descriptors and their events
+    #[cfg_attr(windows, allow(dead_code))]
+    pub(crate) fn get_fds(&self) -> Vec<(SysFdType, u32)> {
+        self.fds.lock().unwrap().iter().map(|(fd, events)| (*fd, *events)).collect()
+    }
+
+    /// Number of monitored file descriptors
+    pub(crate) fn len(&self) -> usize {
+        self.fds.lock().unwrap().len()
+    }
+
+    /// Register the events of `fd` with the kernel and record them
+    ///
+    /// If the kernel backend rejects new events, such as for a regular file
+    /// with epoll, the events recorded before are kept and the error is
+    /// returned.
+    fn set(&self, fds: &mut HashMap<SysFdType, u32>, fd: SysFdType, old: u32, events: u32) -> io::Result<()> {
+        let result = match &self.backend {
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Backend::Epoll(epoll) => epoll.set(fd, old, events),
+            #[cfg(any(
+                target_os = "macos",
+                target_os = "ios",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd",
+                target_os = "dragonfly"
+            ))]
+            Backend::Kqueue(kqueue) => kqueue.set(fd, old, events),
+            #[cfg(windows)]
+            Backend::Iocp(iocp) => iocp.set(fd, old, events),
+            Backend::Poll => Ok(()),
+        };
+        if events == 0 {
+            fds.remove(&fd);
+        } else if result.is_ok() {
+            fds.insert(fd, events);
+        }
+        result
+    }
+
+    /// Wait for ready file descriptors
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - Maximum time to wait (None = wait until ready or woken)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((ready, woken))` - Ready descriptors with their `READY_*` flags,
+    ///   and whether the wait was interrupted through the wakeup pipe
+    /// * `Err(CheckIoError::PollFailed)` - Polling failed
+    #[cfg(unix)]
+    pub(crate) fn wait(&self, timeout: Option<Duration>) -> Result<(Vec<(SysFdType, u32)>, bool), CheckIoError> {
+        let wakeup_fd = self.wakeup_pipe.map(|(read_fd, _)| read_fd);
+        let result = match &self.backend {
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Backend::Epoll(epoll) => epoll.wait(timeout),
+            #[cfg(any(
+                target_os = "macos",
+                target_os = "ios",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd",
+                target_os = "dragonfly"
+            ))]
+            Backend::Kqueue(kqueue) => kqueue.wait(timeout),
+            Backend::Poll => poll::wait(&self.get_fds(), wakeup_fd, timeout),
+        };
+        let mut ready = match result {
+            Ok(ready) => ready,
+            // Interrupted by a signal, handled like a spurious wakeup
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => Vec::new(),
+            Err(_) => return Err(CheckIoError::PollFailed),
+        };
+        let woken = wakeup_fd.is_some_and(|wakeup_fd| ready.iter().any(|(fd, _)| *fd == wakeup_fd));
+        if woken {
+            ready.retain(|(fd, _)| Some(*fd) != wakeup_fd);
+        }
+        Ok((ready, woken))
+    }
+
+    #[cfg(windows)]
+    pub(crate) fn wait(&self, timeout: Option<Duration>) -> Result<(Vec<(SysFdType, u32)>, bool), CheckIoError> {
+        match &self.backend {
+            Backend::Iocp(iocp) => iocp.wait(timeout).map_err(|_| CheckIoError::PollFailed),
+            // poll() is not implemented on Windows; just honour the timeout
+            Backend::Poll => {
+                if let Some(timeout) = timeout {
+                    std::thread::sleep(timeout);
+                }
+                Ok((Vec::new(), false))
+            }
+        }
+    }
+
+    /// Interrupt a thread waiting on the pollset
+    ///
+    /// The wakeup stays pending until [`PollSet::drain_wakeup`] is called, so
+    /// every thread waiting on the pollset returns.
+    pub(crate) fn wake(&self) {
+        #[cfg(unix)]
+        if let Some((_, write_fd)) = self.wakeup_pipe {
+            wakeup::signal(write_fd);
+        }
+        #[cfg(windows)]
+        if let Backend::Iocp(iocp) = &self.backend {
+            iocp.wake();
+        }
+    }
+
+    /// Clear a pending wakeup
+    pub(crate) fn drain_wakeup(&self) {
+        #[cfg(unix)]
+        if let Some((read_fd, _)) = self.wakeup_pipe {
+            wakeup::drain(read_fd);
+        }
+        #[cfg(windows)]
+        if let Backend::Iocp(iocp) = &self.backend {
+            iocp.drain_wakeup();
+        }
+    }
+
+    /// Register the wakeup pipe, level-triggered so that it stays ready until
+    /// drained
+    #[cfg(unix)]
+    fn register_wakeup(&self, read_fd: i32) {
+        let _ = match &self.backend {
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Backend::Epoll(epoll) => epoll.add_level_triggered(read_fd),
+            #[cfg(any(
+                target_os = "macos",
+                target_os = "ios",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd",
+                target_os = "dragonfly"
+            ))]
+            Backend::Kqueue(kqueue) => kqueue.add_level_triggered(read_fd),
+            Backend::Poll => Ok(()),
+        };
+    }
+}
+
+impl Drop for PollSet {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some((read_fd, write_fd)) = self.wakeup_pipe.take() {
+            // Safety: the pipe is owned by the pollset and closed only here
+            unsafe {
+                libc::close(read_fd);
+                libc::close(write_fd);
+            }
+        }
+    }
+}
+
+/// Convert a timeout to milliseconds for `poll` and `epoll_wait`
+///
+/// Sub-millisecond timeouts are rounded up, so that they do not busy-wait.
+#[cfg(unix)]
+fn timeout_ms(timeout: Option<Duration>) -> libc::c_int {
+    match timeout {
+        None => -1,
+        Some(d) if d.is_zero() => 0,
+        Some(d) => d.as_millis().clamp(1, libc::c_int::MAX as u128) as libc::c_int,
+    }
+}
+
+/// Wakeup pipe of a pollset
+#[cfg(unix)]
+mod wakeup {
+    /// Create a non-blocking, close-on-exec pipe
+    pub(super) fn create() -> Option<(i32, i32)> {
+        let mut fds = [0; 2];
+        // Safety: `fds` has room for the two descriptors
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } < 0 {
+            return None;
+        }
+        for fd in fds {
+            // Safety: `fd` was just created by pipe
+            unsafe {
+                libc::fcntl(fd, libc::F_SETFL, libc::fcntl(fd, libc::F_GETFL) | libc::O_NONBLOCK);
+                libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+            }
+        }
+        Some((fds[0], fds[1]))
+    }
+
+    pub(super) fn signal(write_fd: i32) {
+        let byte = 1u8;
+        // Safety: writes one byte from a valid buffer; a full pipe already
+        // holds a pending wakeup
+        unsafe {
+            libc::write(write_fd, &byte as *const u8 as *const libc::c_void, 1);
+        }
+    }
+
+    pub(super) fn drain(read_fd: i32) {
+        let mut buf = [0u8; 64];
+        // Safety: reads into a valid buffer of the given length
+        while unsafe { libc::read(read_fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) } > 0 {}
+    }
+}
+
+/// epoll backend
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod epoll {
+    use super::*;
+    use std::io;
+
+    pub(super) struct Epoll {
+        fd: i32,
+    }
+
+    impl Epoll {
+        pub(super) fn new() -> io::Result<Self> {
+            // Safety: epoll_create1 has no memory safety requirements
+            let fd = unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) };
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Self { fd })
+        }
+
+        /// Register, re-arm or remove `fd`
+        pub(super) fn set(&self, fd: SysFdType, old: u32, events: u32) -> io::Result<()> {
+            if events == 0 {
+                return self.ctl(libc::EPOLL_CTL_DEL, fd, 0);
+            }
+            let mut flags = libc::EPOLLET as u32;
+            if events & READY_READ != 0 {
+                flags |= (libc::EPOLLIN | libc::EPOLLRDHUP) as u32;
+            }
+            if events & READY_WRITE != 0 {
+                flags |= libc::EPOLLOUT as u32;
+            }
+            // EPOLLERR and EPOLLHUP are always reported
+            let (op, fallback) = if old == 0 {
+                (libc::EPOLL_CTL_ADD, libc::EPOLL_CTL_MOD)
+            } else {
+                (libc::EPOLL_CTL_MOD, libc::EPOLL_CTL_ADD)
+            };
+            match self.ctl(op, fd, flags) {
+                // The descriptor was closed and reopened, or registered twice
+                Err(err) if matches!(err.raw_os_error(), Some(libc::EEXIST) | Some(libc::ENOENT)) => {
+                    self.ctl(fallback, fd, flags)
+                }
+                result => result,
+            }
+        }
+
+        pub(super) fn add_level_triggered(&self, fd: i32) -> io::Result<()> {
+            self.ctl(libc::EPOLL_CTL_ADD, fd, libc::EPOLLIN as u32)
+        }
+
+        fn ctl(&self, op: libc::c_int, fd: SysFdType, flags: u32) -> io::Result<()> {
+            let mut event = libc::epoll_event { events: flags, u64: fd as u64 };
+            // Safety: `event` is valid for the duration of the call
+            if unsafe { libc::epoll_ctl(self.fd, op, fd, &mut event) } < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        }
+
+        pub(super) fn wait(&self, timeout: Option<Duration>) -> io::Result<Vec<(SysFdType, u32)>> {
+            let mut events = vec![libc::epoll_event { events: 0, u64: 0 }; MAX_EVENTS];
+            // Safety: `events` has room for MAX_EVENTS entries
+            let n = unsafe {
+                libc::epoll_wait(self.fd, events.as_mut_ptr(), MAX_EVENTS as libc::c_int, timeout_ms(timeout))
+            };
+            if n < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(events[..n as usize]
+                .iter()
+                .map(|event| {
+                    let flags = event.events as libc::c_int;
+                    let mut ready = 0;
+                    if flags & (libc::EPOLLIN | libc::EPOLLHUP | libc::EPOLLRDHUP) != 0 {
+                        ready |= READY_READ;
+                    }
+                    if flags & libc::EPOLLOUT != 0 {
+                        ready |= READY_WRITE;
+                    }
+                    if flags & libc::EPOLLERR != 0 {
+                        ready |= READY_ERROR;
+                    }
+                    (event.u64 as SysFdType, ready)
+                })
+                .collect())
+        }
+    }
+
+    impl Drop for Epoll {
+        fn drop(&mut self) {
+            // Safety: the epoll descriptor is owned and closed only here
+            unsafe {
+                libc::close(self.fd);
+            }
+        }
+    }
+}
+
+/// kqueue backend
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly"
+))]
+mod kqueue {
+    use super::*;
+    use std::io;
+
+    pub(super) struct Kqueue {
+        fd: i32,
+    }
+
+    fn kevent(fd: SysFdType, filter: i64, flags: u32) -> libc::kevent {
+        // Safety: a zeroed kevent is valid
+        let mut event: libc::kevent = unsafe { std::mem::zeroed() };
+        event.ident = fd as _;
+        event.filter = filter as _;
+        event.flags = flags as _;
+        event
+    }
+
+    impl Kqueue {
+        pub(super) fn new() -> io::Result<Self> {
+            // Safety: kqueue has no memory safety requirements
+            let fd = unsafe { libc::kqueue() };
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            // Safety: `fd` was just created by kqueue
+            unsafe {
+                libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+            }
+            Ok(Self { fd })
+        }
+
+        /// Register, re-arm or remove the read and write filters of `fd`
+        pub(super) fn set(&self, fd: SysFdType, old: u32, events: u32) -> io::Result<()> {
+            let mut changes = Vec::with_capacity(2);
+            for (flag, filter) in [(READY_READ, libc::EVFILT_READ), (READY_WRITE, libc::EVFILT_WRITE)] {
+                if events & flag != 0 {
+                    changes.push(kevent(fd, filter as i64, (libc::EV_ADD | libc::EV_CLEAR) as u32));
+                } else if old & flag != 0 {
+                    changes.push(kevent(fd, filter as i64, libc::EV_DELETE as u32));
+                }
+            }
+            self.apply(&changes)
+        }
+
+        pub(super) fn add_level_triggered(&self, fd: i32) -> io::Result<()> {
+            self.apply(&[kevent(fd, libc::EVFILT_READ as i64, libc::EV_ADD as u32)])
+        }
+
+        fn apply(&self, changes: &[libc::kevent]) -> io::Result<()> {
+            if changes.is_empty() {
+                return Ok(());
+            }
+            // Safety: `changes` is valid for reads of its length; no events
+            // are returned
+            let result = unsafe {
+                libc::kevent(self.fd, changes.as_ptr(), changes.len() as _, std::ptr::null_mut(), 0, std::ptr::null())
+            };
+            if result < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        }
+
+        pub(super) fn wait(&self, timeout: Option<Duration>) -> io::Result<Vec<(SysFdType, u32)>> {
+            // Safety: a zeroed kevent is valid
+            let mut events: Vec<libc::kevent> = vec![unsafe { std::mem::zeroed() }; MAX_EVENTS];
+            let timespec = timeout.map(|d| libc::timespec {
+                tv_sec: d.as_secs() as libc::time_t,
+                tv_nsec: d.subsec_nanos() as _,
+            });
+            let timespec_ptr = timespec.as_ref().map_or(std::ptr::null(), |t| t as *const libc::timespec);
+            // Safety: `events` has room for MAX_EVENTS entries and the
+            // timespec outlives the call
+            let n = unsafe {
+                libc::kevent(self.fd, std::ptr::null(), 0, events.as_mut_ptr(), MAX_EVENTS as _, timespec_ptr)
+            };
+            if n < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(events[..n as usize]
+                .iter()
+                .map(|event| {
+                    let mut ready = 0;
+                    if event.flags as u32 & libc::EV_ERROR as u32 != 0 {
+                        ready |= READY_ERROR;
+                    } else if event.filter as i64 == libc::EVFILT_READ as i64 {
+                        ready |= READY_READ;
+                    } else if event.filter as i64 == libc::EVFILT_WRITE as i64 {
+                        ready |= READY_WRITE;
+                    }
+                    if event.flags as u32 & libc::EV_EOF as u32 != 0 {
+                        ready |= READY_READ;
+                    }
+                    (event.ident as SysFdType, ready)
+                })
+                .collect())
+        }
+    }
+
+    impl Drop for Kqueue {
+        fn drop(&mut self) {
+            // Safety: the kqueue descriptor is owned and closed only here
+            unsafe {
+                libc::close(self.fd);
+            }
+        }
+    }
+}
+
+/// I/O completion port backend
+///
+/// Readiness is probed with overlapped operations whose completions are
+/// queued to the port: a zero-byte `WSARecv` (or `ReadFile` for pipes)
+/// completes once data is available, and is reissued after each completion
+/// while the descriptor stays selected for reading. Sockets and pipes are
+/// writable unless their buffers are full, so a write probe completes
+/// immediately, once per arming.
+#[cfg(windows)]
+mod iocp {
+    use super::*;
+    use crate::completion::{CompletionEvent, CompletionStatus, OverlappedOp, OverlappedTable};
+    use std::collections::HashSet;
+    use std::io;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use windows_sys::Win32::Foundation::{CloseHandle, GetLastError, ERROR_IO_PENDING, HANDLE, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::Networking::WinSock::{WSAGetLastError, WSARecv, WSABUF, WSAENOTSOCK, WSA_IO_PENDING};
+    use windows_sys::Win32::Storage::FileSystem::ReadFile;
+    use windows_sys::Win32::System::IO::{
+        CancelIoEx, CreateIoCompletionPort, GetQueuedCompletionStatusEx, PostQueuedCompletionStatus, OVERLAPPED,
+        OVERLAPPED_ENTRY,
+    };
+
+    /// Completion key of wakeup packets
+    const WAKEUP_KEY: usize = usize::MAX;
+    /// NTSTATUS of a cancelled operation (`STATUS_CANCELLED`)
+    const STATUS_CANCELLED: u32 = 0xC000_0120;
+
+    /// Overlapped operation, allocated for the duration of the operation
+    ///
+    /// `overlapped` is the first field, so the `OVERLAPPED` pointer returned
+    /// by the port is also a pointer to the operation.
+    #[repr(C)]
+    struct Operation {
+        overlapped: OVERLAPPED,
+        id: u64,
+    }
+
+    pub(super) struct Iocp {
+        port: HANDLE,
+        /// Outstanding operations
+        table: Mutex<OverlappedTable>,
+        /// Selected events of each descriptor, to reissue read probes
+        selected: Mutex<HashMap<SysFdType, u32>>,
+        /// Descriptors associated with the port
+        associated: Mutex<HashSet<SysFdType>>,
+        /// Set from `wake` until `drain_wakeup`
+        wakeup_pending: AtomicBool,
+    }
+
+    // Safety: the port handle may be used from any thread
+    unsafe impl Send for Iocp {}
+    unsafe impl Sync for Iocp {}
+
+    impl Iocp {
+        pub(super) fn new() -> io::Result<Self> {
+            // Safety: creates a new port, no pointers are passed
+            let port = unsafe { CreateIoCompletionPort(INVALID_HANDLE_VALUE, std::ptr::null_mut(), 0, 0) };
+            if port.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Self {
+                port,
+                table: Mutex::new(OverlappedTable::new()),
+                selected: Mutex::new(HashMap::new()),
+                associated: Mutex::new(HashSet::new()),
+                wakeup_pending: AtomicBool::new(false),
+            })
+        }
+
+        /// Associate `fd` with the port and issue probes for `events`, or
+        /// cancel the outstanding probes when no events are left
+        pub(super) fn set(&self, fd: SysFdType, _old: u32, events: u32) -> io::Result<()> {
+            if events == 0 {
+                self.selected.lock().unwrap().remove(&fd);
+                // Safety: cancels all operations issued on the handle by
+                // this process; their completions free the operations
+                unsafe {
+                    CancelIoEx(fd as HANDLE, std::ptr::null());
+                }
+                return Ok(());
+            }
+            self.selected.lock().unwrap().insert(fd, events);
+            self.associate(fd)?;
+            if events & READY_READ != 0 {
+                self.probe(fd, OverlappedOp::Read)?;
+            }
+            if events & READY_WRITE != 0 {
+                self.probe(fd, OverlappedOp::Write)?;
+            }
+            Ok(())
+        }
+
+        fn associate(&self, fd: SysFdType) -> io::Result<()> {
+            let mut associated = self.associated.lock().unwrap();
+            if associated.contains(&fd) {
+                return Ok(());
+            }
+            // Safety: associates the handle with the port; the completion
+            // key is the descriptor itself
+            let port = unsafe { CreateIoCompletionPort(fd as HANDLE, self.port, fd as usize, 0) };
+            if port.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+            associated.insert(fd);
+            Ok(())
+        }
+
+        /// Issue a probe of kind `op` unless one is outstanding
+        fn probe(&self, fd: SysFdType, op: OverlappedOp) -> io::Result<()> {
+            let Some(id) = self.table.lock().unwrap().start(fd, op) else {
+                return Ok(());
+            };
+            let operation = Box::into_raw(Box::new(Operation {
+                // Safety: a zeroed OVERLAPPED is valid
+                overlapped: unsafe { std::mem::zeroed() },
+                id,
+            }));
+            let overlapped = operation as *mut OVERLAPPED;
+            let result = match op {
+                OverlappedOp::Read => Self::zero_byte_read(fd, overlapped),
+                // Safety: the operation stays allocated until its
+                // completion is dequeued
+                OverlappedOp::Write => match unsafe { PostQueuedCompletionStatus(self.port, 0, fd as usize, overlapped) } {
+                    0 => Err(io::Error::last_os_error()),
+                    _ => Ok(()),
+                },
+            };
+            if result.is_err() {
+                self.table.lock().unwrap().complete(id);
+                // Safety: the operation was not queued, so it is still owned here
+                drop(unsafe { Box::from_raw(operation) });
+            }
+            result
+        }
+
+        /// Issue a zero-byte overlapped read on a socket, or on a pipe
+        fn zero_byte_read(fd: SysFdType, overlapped: *mut OVERLAPPED) -> io::Result<()> {
+            let buf = WSABUF { len: 0, buf: std::ptr::null_mut() };
+            let mut flags = 0u32;
+            // Safety: the buffer array outlives the call and the operation
+            // stays allocated until its completion is dequeued
+            let result = unsafe { WSARecv(fd as usize, &buf, 1, std::ptr::null_mut(), &mut flags, overlapped, None) };
+            if result == 0 {
+                return Ok(());
+            }
+            // Safety: reads the calling thread's last error
+            match unsafe { WSAGetLastError() } {
+                WSA_IO_PENDING => return Ok(()),
+                WSAENOTSOCK => {}
+                err => return Err(io::Error::from_raw_os_error(err)),
+            }
+            let mut byte = 0u8;
+            // Safety: as above; zero bytes are read into `byte`
+            if unsafe { ReadFile(fd as HANDLE, &mut byte, 0, std::ptr::null_mut(), overlapped) } != 0 {
+                return Ok(());
+            }
+            // Safety: reads the calling thread's last error
+            match unsafe { GetLastError() } {
+                ERROR_IO_PENDING => Ok(()),
+                err => Err(io::Error::from_raw_os_error(err as i32)),
+            }
+        }
+
+        pub(super) fn wait(&self, timeout: Option<Duration>) -> io::Result<(Vec<(SysFdType, u32)>, bool)> {
+            // Safety: a zeroed OVERLAPPED_ENTRY is valid
+            let mut entries: Vec<OVERLAPPED_ENTRY> = vec![unsafe { std::mem::zeroed() }; MAX_EVENTS];
+            let mut removed = 0u32;
+            let timeout_ms = match timeout {
+                None => u32::MAX,
+                Some(d) if d.is_zero() => 0,
+                Some(d) => d.as_millis().clamp(1, u32::MAX as u128 - 1) as u32,
+            };
+            // Safety: `entries` has room for MAX_EVENTS entries
+            let ok = unsafe {
+                GetQueuedCompletionStatusEx(self.port, entries.as_mut_ptr(), MAX_EVENTS as u32, &mut removed, timeout_ms, 0)
+            };
+            if ok == 0 {
+                let err = io::Error::last_os_error();
+                // WAIT_TIMEOUT
+                if err.raw_os_error() == Some(258) {
+                    return Ok((Vec::new(), false));
+                }
+                return Err(err);
+            }
+
+            let mut ready = Vec::new();
+            let mut woken = false;
+            for entry in &entries[..removed as usize] {
+                if entry.lpCompletionKey == WAKEUP_KEY && entry.lpOverlapped.is_null() {
+                    woken = true;
+                    continue;
+                }
+                // Safety: every OVERLAPPED queued to the port is the first
+                // field of an Operation allocated by `probe`
+                let operation = unsafe { Box::from_raw(entry.lpOverlapped as *mut Operation) };
+                let Some((fd, op)) = self.table.lock().unwrap().complete(operation.id) else {
+                    continue;
+                };
+                let status = match operation.overlapped.Internal as u32 {
+                    0 => CompletionStatus::Success,
+                    STATUS_CANCELLED => CompletionStatus::Cancelled,
+                    status => CompletionStatus::Failed(status),
+                };
+                let completion = CompletionEvent {
+                    fd,
+                    op,
+                    bytes: entry.dwNumberOfBytesTransferred,
+                    status,
+                };
+                let flags = completion.ready();
+                if flags != 0 {
+                    ready.push((fd, flags));
+                }
+            }
+            self.rearm_reads(&ready);
+            if woken && self.wakeup_pending.load(Ordering::Acquire) {
+                // Keep the wakeup pending for the other waiting threads
+                self.post_wakeup();
+            }
+            Ok((ready, woken))
+        }
+
+        pub(super) fn wake(&self) {
+            self.wakeup_pending.store(true, Ordering::Release);
+            self.post_wakeup();
+        }
+
+        pub(super) fn drain_wakeup(&self) {
+            self.wakeup_pending.store(false, Ordering::Release);
+        }
+
+        fn post_wakeup(&self) {
+            // Safety: posts a packet without an OVERLAPPED
+            unsafe {
+                PostQueuedCompletionStatus(self.port, 0, WAKEUP_KEY, std::ptr::null());
+            }
+        }
+
+        /// Reissue the read probes of descriptors still selected for reading
+        fn rearm_reads(&self, ready: &[(SysFdType, u32)]) {
+            for (fd, flags) in ready {
+                let selected = self.selected.lock().unwrap().get(fd).copied().unwrap_or(0);
+                if flags & READY_READ != 0 && selected & READY_READ != 0 {
+                    let _ = self.probe(*fd, OverlappedOp::Read);
+                }
+            }
+        }
+    }
+
+    impl Drop for Iocp {
+        fn drop(&mut self) {
+            // Outstanding operations are cancelled when the descriptors are
+            // closed; operations still queued to the port are leaked
+            // Safety: the port is owned and closed only here
+            unsafe {
+                CloseHandle(self.port);
+            }
+        }
+    }
+}
+
+/// poll fallback backend
+#[cfg(unix)]
+mod poll {
+    use super::*;
+    use std::io;
+
+    /// Poll `fds` and the wakeup pipe
+    pub(super) fn wait(
+        fds: &[(SysFdType, u32)],
+        wakeup_fd: Option<i32>,
+        timeout: Option<Duration>,
+    ) -> io::Result<Vec<(SysFdType, u32)>> {
+        let mut poll_fds: Vec<libc::pollfd> = fds
+            .iter()
+            .map(|(fd, events)| {
+                let mut poll_events: libc::c_short = 0;
+                if events & READY_READ != 0 {
+                    poll_events |= libc::POLLIN;
+                }
+                if events & READY_WRITE != 0 {
+                    poll_events |= libc::POLLOUT;
+                }
+                if events & READY_ERROR != 0 {
+                    poll_events |= libc::POLLERR;
+                }
+                libc::pollfd { fd: *fd, events: poll_events, revents: 0 }
+            })
+            .collect();
+        if let Some(fd) = wakeup_fd {
+            poll_fds.push(libc::pollfd { fd, events: libc::POLLIN, revents: 0 });
+        }
+
+        // Safety: `poll_fds` is valid for its length for the duration of the call
+        let result = unsafe { libc::poll(poll_fds.as_mut_ptr(), poll_fds.len() as libc::nfds_t, timeout_ms(timeout)) };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(poll_fds
+            .iter()
+            .filter(|poll_fd| poll_fd.revents != 0)
+            .map(|poll_fd| {
+                let mut ready = 0;
+                if poll_fd.revents & (libc::POLLIN | libc::POLLHUP) != 0 {
+                    ready |= READY_READ;
+                }
+                if poll_fd.revents & libc::POLLOUT != 0 {
+                    ready |= READY_WRITE;
+                }
+                if poll_fd.revents & (libc::POLLERR | libc::POLLNVAL) != 0 {
+                    ready |= READY_ERROR;
+                }
+                (poll_fd.fd, ready)
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pollset_operations() {
+        let pollset = PollSet::new(PollBackend::Poll).unwrap();
+
+        // Add file descriptors
+        pollset.add_fd(5, NifSelectFlags::Read as u32).unwrap();
+        pollset.add_fd(6, NifSelectFlags::Write as u32).unwrap();
+        pollset.add_fd(7, NifSelectFlags::combine(&[NifSelectFlags::Read, NifSelectFlags::Write])).unwrap();
+
+        let fds = pollset.get_fds();
+        assert_eq!(fds.len(), 3);
+
+        // Update existing FD
+        pollset.update_fd(5, NifSelectFlags::combine(&[NifSelectFlags::Read, NifSelectFlags::Write])).unwrap();
+        let fds = pollset.get_fds();
+        let fd5 = fds.iter().find(|(fd, _)| *fd == 5).unwrap();
+        assert_eq!(fd5.1, NifSelectFlags::combine(&[NifSelectFlags::Read, NifSelectFlags::Write]));
+
+        // Remove FD
+        pollset.remove_fd(6).unwrap();
+        let fds = pollset.get_fds();
+        assert_eq!(fds.len(), 2);
+        assert!(fds.iter().find(|(fd, _)| *fd == 6).is_none());
+    }
+
+    #[test]
+    fn test_native_backend_available() {
+        assert!(PollBackend::native().is_available());
+        assert!(PollBackend::Poll.is_available());
+        assert!(PollSet::new(PollBackend::native()).is_ok());
+        if cfg!(target_os = "linux") {
+            assert_eq!(PollBackend::native(), PollBackend::Epoll);
+            assert_eq!(PollSet::new(PollBackend::Kqueue).err(), Some(CheckIoError::NotSupported));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_backends_report_ready_descriptors() {
+        use std::io::Write;
+        use std::os::unix::io::AsRawFd;
+        use std::os::unix::net::UnixStream;
+
+        for backend in [PollBackend::native(), PollBackend::Poll] {
+            let pollset = PollSet::new(backend).unwrap();
+            let (mut writer, reader) = UnixStream::pair().unwrap();
+            let fd = reader.as_raw_fd();
+            pollset.add_fd(fd, READY_READ).unwrap();
+
+            let (ready, woken) = pollset.wait(Some(Duration::from_millis(10))).unwrap();
+            assert!(ready.is_empty() && !woken, "{:?}", backend);
+
+            writer.write_all(b"x").unwrap();
+            let (ready, _) = pollset.wait(Some(Duration::from_secs(5))).unwrap();
+            assert_eq!(ready, vec![(fd, READY_READ)], "{:?}", backend);
+
+            // Re-arming reports a descriptor that is still ready
+            pollset.update_fd(fd, READY_READ).unwrap();
+            let (ready, _) = pollset.wait(Some(Duration::from_secs(5))).unwrap();
+            assert_eq!(ready, vec![(fd, READY_READ)], "{:?}", backend);
+
+            pollset.remove_fd(fd).unwrap();
+            let (ready, _) = pollset.wait(Some(Duration::from_millis(10))).unwrap();
+            assert!(ready.is_empty(), "{:?}", backend);
+        }
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn test_epoll_is_edge_triggered() {
+        use std::io::Write;
+        use std::os::unix::io::AsRawFd;
+        use std::os::unix::net::UnixStream;
+
+        let pollset = PollSet::new(PollBackend::Epoll).unwrap();
+        let (mut writer, reader) = UnixStream::pair().unwrap();
+        pollset.add_fd(reader.as_raw_fd(), READY_READ).unwrap();
+        writer.write_all(b"x").unwrap();
+        assert_eq!(pollset.wait(Some(Duration::from_secs(5))).unwrap().0.len(), 1);
+        // The unread data does not report the descriptor again
+        assert!(pollset.wait(Some(Duration::from_millis(10))).unwrap().0.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_wake_interrupts_wait() {
+        for backend in [PollBackend::native(), PollBackend::Poll] {
+            let pollset = PollSet::new(backend).unwrap();
+            pollset.wake();
+            let (ready, woken) = pollset.wait(None).unwrap();
+            assert!(ready.is_empty() && woken, "{:?}", backend);
+            // The wakeup stays pending until drained
+            assert!(pollset.wait(None).unwrap().1);
+            pollset.drain_wakeup();
+            assert!(!pollset.wait(Some(Duration::from_millis(10))).unwrap().1);
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_iocp_reports_readable_socket() {
+        use std::io::Write;
+        use std::net::{TcpListener, TcpStream};
+        use std::os::windows::io::AsRawSocket;
+
+        let pollset = PollSet::new(PollBackend::Iocp).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut writer = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (reader, _) = listener.accept().unwrap();
+        let fd = reader.as_raw_socket();
+        pollset.add_fd(fd, READY_READ).unwrap();
+
+        let (ready, woken) = pollset.wait(Some(Duration::from_millis(10))).unwrap();
+        assert!(ready.is_empty() && !woken);
+
+        writer.write_all(b"x").unwrap();
+        let (ready, _) = pollset.wait(Some(Duration::from_secs(5))).unwrap();
+        assert_eq!(ready, vec![(fd, READY_READ)]);
+
+        // Deselecting cancels the reissued probe, which reports nothing
+        pollset.remove_fd(fd).unwrap();
+        let (ready, _) = pollset.wait(Some(Duration::from_millis(100))).unwrap();
+        assert!(ready.is_empty());
+
+        pollset.wake();
+        assert!(pollset.wait(None).unwrap().1);
+        pollset.drain_wakeup();
+    }
+}
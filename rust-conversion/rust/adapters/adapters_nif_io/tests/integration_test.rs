@@ -73,3 +73,37 @@ fn test_check_io_error_variants() {
     let _ = format!("{:?}", error1);
 }
 
+
+#[test]
+fn test_fs_watcher_delivers_events() {
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::time::Duration;
+
+    let dir = std::env::temp_dir().join(format!("test_fs_watcher_integration_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let dir = dir.canonicalize().unwrap();
+
+    let (sender, events) = mpsc::channel();
+    let sender = Mutex::new(sender);
+    let handler: FsEventHandler = Arc::new(move |pid, event| {
+        let _ = sender.lock().unwrap().send((pid, event));
+    });
+    let watcher = FsWatcher::new(handler);
+    watcher.subscribe(7, &dir, true).unwrap();
+
+    let file = dir.join("module.beam");
+    std::fs::write(&file, b"beam").unwrap();
+    let (pid, event) = loop {
+        let (pid, event) = events.recv_timeout(Duration::from_secs(5)).expect("no fs_event");
+        if event.path == file {
+            break (pid, event);
+        }
+    };
+    assert_eq!(pid, 7);
+    assert!(event.flags.contains(&FsEventFlag::Created));
+
+    assert_eq!(watcher.unsubscribe_all(7), 1);
+    drop(watcher);
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,304 @@
+//! Async File Module
+//!
+//! Offloads file operations to the dirty I/O schedulers, as `prim_file` does
+//! by declaring its NIFs `ERL_NIF_DIRTY_JOB_IO_BOUND`. A slow operation, such
+//! as a read from an unresponsive network file system, then blocks a dirty
+//! I/O scheduler instead of a normal scheduler.
+//!
+//! Each operation returns a [`FileTask`] immediately. Operations on the same
+//! file are serialized, like operations on a `prim_file` file descriptor.
+//! Without dirty I/O schedulers, operations run on the calling thread.
+//!
+//! Based on prim_file_nif.c
+
+use std::collections::VecDeque;
+use std::fs::OpenOptions;
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+
+use infrastructure_nif_api::{get_global_dirty_schedulers, DirtySchedulers, ErlNifDirtyFlags};
+
+use infrastructure_time_management::time_unit::TimeUnit;
+
+use crate::file::{Advice, FileHandle, FileInfo, FileNifError, IpreadResult};
+
+/// File operation in progress on a dirty I/O scheduler
+#[derive(Debug)]
+pub struct FileTask<T> {
+    /// Receives the result when the operation completes
+    result: Receiver<Result<T, FileNifError>>,
+}
+
+impl<T> FileTask<T> {
+    fn new() -> (Sender<Result<T, FileNifError>>, Self) {
+        let (sender, result) = mpsc::channel();
+        (sender, Self { result })
+    }
+
+    /// Block until the operation completes
+    pub fn wait(self) -> Result<T, FileNifError> {
+        self.result.recv().unwrap_or(Err(FileNifError::SchedulerStopped))
+    }
+
+    /// Take the result if the operation has completed, without blocking
+    ///
+    /// # Returns
+    /// `None` while the operation is in progress. The result can be taken
+    /// once; later calls report `SchedulerStopped`.
+    pub fn try_wait(&self) -> Option<Result<T, FileNifError>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(FileNifError::SchedulerStopped)),
+        }
+    }
+}
+
+/// Queued operation on a file
+type FileOp = Box<dyn FnOnce(&mut FileHandle) + Send>;
+
+/// Operations waiting to run on a file
+#[derive(Default)]
+struct OpQueue {
+    /// Operations in submission order
+    ops: VecDeque<FileOp>,
+    /// Whether a dirty I/O scheduler is draining the queue
+    draining: bool,
+}
+
+/// File whose operations run on the dirty I/O schedulers
+pub struct AsyncFile<'a> {
+    /// The file, locked by the scheduler draining the queue
+    handle: Arc<Mutex<FileHandle>>,
+    /// Operations waiting to run
+    queue: Arc<Mutex<OpQueue>>,
+    /// Schedulers running the operations
+    schedulers: &'a DirtySchedulers,
+}
+
+impl AsyncFile<'static> {
+    /// Run the operations of `handle` on the global dirty I/O schedulers
+    pub fn new(handle: FileHandle) -> Self {
+        Self::with_schedulers(handle, get_global_dirty_schedulers())
+    }
+}
+
+impl<'a> AsyncFile<'a> {
+    /// Run the operations of `handle` on the dirty I/O schedulers of `schedulers`
+    pub fn with_schedulers(handle: FileHandle, schedulers: &'a DirtySchedulers) -> Self {
+        Self {
+            handle: Arc::new(Mutex::new(handle)),
+            queue: Arc::new(Mutex::new(OpQueue::default())),
+            schedulers,
+        }
+    }
+
+    /// Open a file on a dirty I/O scheduler
+    ///
+    /// Opening can block as long as reading, e.g. on a network file system
+    /// or a FIFO without a writer.
+    pub fn open(schedulers: &DirtySchedulers, path: PathBuf, options: OpenOptions) -> FileTask<FileHandle> {
+        let (sender, task) = FileTask::new();
+        spawn(schedulers, move || {
+            let _ = sender.send(options.open(path).map(FileHandle::from).map_err(FileNifError::from));
+        });
+        task
+    }
+
+    /// Read up to `size` bytes at the current position
+    ///
+    /// # Returns
+    /// The data read, or `None` (`eof`) at end of file
+    pub fn read(&self, size: usize) -> FileTask<Option<Vec<u8>>> {
+        self.run(move |handle| {
+            let mut data = vec![0u8; size];
+            let len = handle.read(&mut data)?;
+            if len == 0 && size > 0 {
+                return Ok(None);
+            }
+            data.truncate(len);
+            Ok(Some(data))
+        })
+    }
+
+    /// Write all of `data` at the current position
+    pub fn write(&self, data: Vec<u8>) -> FileTask<()> {
+        self.run(move |handle| handle.write_vectored(&[&data]).map(|_| ()))
+    }
+
+    /// Read `size` bytes at `offset`, see [`FileHandle::pread`]
+    pub fn pread(&self, offset: u64, size: usize) -> FileTask<Option<Vec<u8>>> {
+        self.run(move |handle| handle.pread(offset, size))
+    }
+
+    /// Write all of `data` at `offset`, see [`FileHandle::pwrite`]
+    pub fn pwrite(&self, offset: u64, data: Vec<u8>) -> FileTask<()> {
+        self.run(move |handle| handle.pwrite(offset, &data))
+    }
+
+    /// Move the file position, see [`FileHandle::position`]
+    pub fn position(&self, location: SeekFrom) -> FileTask<u64> {
+        self.run(move |handle| handle.position(location))
+    }
+
+    /// Truncate the file at the current position
+    pub fn truncate(&self) -> FileTask<()> {
+        self.run(|handle| handle.truncate())
+    }
+
+    /// Preallocate disk space, see [`FileHandle::allocate`]
+    pub fn allocate(&self, offset: u64, length: u64) -> FileTask<()> {
+        self.run(move |handle| handle.allocate(offset, length))
+    }
+
+    /// Declare the access pattern, see [`FileHandle::advise`]
+    pub fn advise(&self, offset: u64, length: u64, advice: Advice) -> FileTask<()> {
+        self.run(move |handle| handle.advise(offset, length, advice))
+    }
+
+    /// Flush data and metadata to disk
+    pub fn sync(&self) -> FileTask<()> {
+        self.run(|handle| handle.sync())
+    }
+
+    /// Flush data, but not necessarily metadata, to disk
+    pub fn datasync(&self) -> FileTask<()> {
+        self.run(|handle| handle.datasync())
+    }
+
+    /// Read the information of the file, see [`FileHandle::read_info`]
+    pub fn read_info(&self, unit: TimeUnit) -> FileTask<FileInfo> {
+        self.run(move |handle| handle.read_info(unit))
+    }
+
+    /// Indirect positional read, see [`FileHandle::ipread_s32bu_p32bu`]
+    pub fn ipread_s32bu_p32bu(&self, offset: u64, max_size: u32) -> FileTask<Option<IpreadResult>> {
+        self.run(move |handle| handle.ipread_s32bu_p32bu(offset, max_size))
+    }
+
+    /// Queue `op` on the file
+    ///
+    /// The first operation queued on an idle file starts a job on a dirty I/O
+    /// scheduler that runs queued operations until the queue is empty, so
+    /// operations run in submission order without occupying more than one
+    /// scheduler per file.
+    fn run<T, F>(&self, op: F) -> FileTask<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut FileHandle) -> Result<T, FileNifError> + Send + 'static,
+    {
+        let (sender, task) = FileTask::new();
+        let op: FileOp = Box::new(move |handle| {
+            let _ = sender.send(op(handle));
+        });
+        let start = {
+            let mut queue = self.queue.lock().unwrap();
+            queue.ops.push_back(op);
+            !std::mem::replace(&mut queue.draining, true)
+        };
+        if start {
+            let (handle, queue) = (Arc::clone(&self.handle), Arc::clone(&self.queue));
+            if !spawn(self.schedulers, move || drain(&handle, &queue)) {
+                // Dropping the operations reports them as stopped
+                let mut queue = self.queue.lock().unwrap();
+                queue.ops.clear();
+                queue.draining = false;
+            }
+        }
+        task
+    }
+}
+
+/// Run queued operations until the queue is empty
+fn drain(handle: &Mutex<FileHandle>, queue: &Mutex<OpQueue>) {
+    loop {
+        let op = {
+            let mut queue = queue.lock().unwrap();
+            match queue.ops.pop_front() {
+                Some(op) => op,
+                None => {
+                    queue.draining = false;
+                    return;
+                }
+            }
+        };
+        op(&mut handle.lock().unwrap());
+    }
+}
+
+/// Run `work` on a dirty I/O scheduler, or on the calling thread if there
+/// are none
+///
+/// # Returns
+/// `false` if the dirty I/O schedulers have stopped
+fn spawn<F: FnOnce() + Send + 'static>(schedulers: &DirtySchedulers, work: F) -> bool {
+    if schedulers.io_schedulers() == 0 {
+        work();
+        return true;
+    }
+    schedulers.spawn(ErlNifDirtyFlags::DirtyIo, work).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::PosixError;
+    use std::fs;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{}_{}", name, std::process::id()))
+    }
+
+    fn read_write() -> OpenOptions {
+        let mut options = OpenOptions::new();
+        options.read(true).write(true).create(true).truncate(true);
+        options
+    }
+
+    #[test]
+    fn test_async_file_operations() {
+        let schedulers = DirtySchedulers::new(0, 2);
+        let path = temp_path("test_async_file");
+        let handle = AsyncFile::open(&schedulers, path.clone(), read_write()).wait().unwrap();
+        let file = AsyncFile::with_schedulers(handle, &schedulers);
+
+        // Operations on one file run in submission order
+        let write = file.write(b"hello world".to_vec());
+        let rewind = file.position(SeekFrom::Start(0));
+        let read = file.read(5);
+        write.wait().unwrap();
+        assert_eq!(rewind.wait(), Ok(0));
+        assert_eq!(read.wait(), Ok(Some(b"hello".to_vec())));
+
+        file.pwrite(6, b"there".to_vec()).wait().unwrap();
+        assert_eq!(file.pread(6, 10).wait(), Ok(Some(b"there".to_vec())));
+        assert_eq!(file.pread(11, 1).wait(), Ok(None));
+        file.advise(0, 0, Advice::Sequential).wait().unwrap();
+        file.datasync().wait().unwrap();
+        file.sync().wait().unwrap();
+        file.truncate().wait().unwrap();
+        assert_eq!(file.read_info(TimeUnit::Second).wait().unwrap().size, 5);
+        assert_eq!(fs::read(&path).unwrap(), b"hello");
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_async_file_without_dirty_schedulers() {
+        let schedulers = DirtySchedulers::new(0, 0);
+        let missing = temp_path("test_async_file_missing");
+        let task = AsyncFile::open(&schedulers, missing, OpenOptions::new().read(true).clone());
+        assert!(matches!(task.try_wait(), Some(Err(FileNifError::Posix(PosixError::Enoent)))));
+
+        let path = temp_path("test_async_file_inline");
+        let handle = AsyncFile::open(&schedulers, path.clone(), read_write()).wait().unwrap();
+        let file = AsyncFile::with_schedulers(handle, &schedulers);
+        let write = file.pwrite(0, b"inline".to_vec());
+        assert_eq!(write.try_wait(), Some(Ok(())));
+        assert_eq!(file.pread(0, 6).wait(), Ok(Some(b"inline".to_vec())));
+
+        let _ = fs::remove_file(&path);
+    }
+}
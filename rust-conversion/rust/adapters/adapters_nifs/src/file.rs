@@ -2,54 +2,808 @@
 //!
 //! Provides file operations for NIFs.
 //! Based on prim_file_nif.c
+//!
+//! Errors are reported as posix error atoms (`enoent`, `eacces`, ...), the
+//! same reasons `prim_file` returns in `{error, Reason}` tuples.
+//!
+//! Besides file handles, the module covers the directory and metadata
+//! operations of `prim_file`: listing, creating and deleting directories,
+//! symbolic and hard links, `file_info` records, and renaming and copying.
+
+use std::ffi::OsString;
+use std::fs::{self, File, Metadata, OpenOptions};
+use std::io::{self, IoSlice, IoSliceMut, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 
-use std::fs::File;
-use std::io::{Read, Write};
-use std::path::Path;
+use infrastructure_nif_api::{iolist_to_iovec, NifEnv, NifTerm};
+use infrastructure_time_management::time_unit::{convert_time_unit, TimeUnit};
 
 /// File NIF operations
 pub struct FileNif;
 
 impl FileNif {
     /// Open a file
-    pub fn open<P: AsRef<Path>>(path: P) -> Result<FileHandle, NifError> {
-        File::open(path)
-            .map(|f| FileHandle { file: f })
-            .map_err(|_| NifError::BadArg)
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<FileHandle, FileNifError> {
+        Ok(FileHandle { file: File::open(path)? })
     }
 
     /// Create a file
-    pub fn create<P: AsRef<Path>>(path: P) -> Result<FileHandle, NifError> {
-        File::create(path)
-            .map(|f| FileHandle { file: f })
-            .map_err(|_| NifError::BadArg)
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<FileHandle, FileNifError> {
+        Ok(FileHandle { file: File::create(path)? })
+    }
+
+    /// Open a file with explicit modes, e.g. `[read, write, exclusive]`
+    pub fn open_with<P: AsRef<Path>>(path: P, options: &OpenOptions) -> Result<FileHandle, FileNifError> {
+        Ok(FileHandle { file: options.open(path)? })
+    }
+
+    /// Read the information of a file, following symbolic links (`stat`)
+    ///
+    /// # Arguments
+    /// * `path` - File to read the information of
+    /// * `unit` - Unit of the timestamps, counted from the Unix epoch
+    pub fn read_info<P: AsRef<Path>>(path: P, unit: TimeUnit) -> Result<FileInfo, FileNifError> {
+        Ok(FileInfo::from_metadata(&fs::metadata(path)?, unit))
+    }
+
+    /// Read the information of a file without following a symbolic link
+    /// at `path` (`lstat`)
+    pub fn read_link_info<P: AsRef<Path>>(path: P, unit: TimeUnit) -> Result<FileInfo, FileNifError> {
+        Ok(FileInfo::from_metadata(&fs::symlink_metadata(path)?, unit))
+    }
+
+    /// List the names of the entries of a directory, excluding `.` and `..`
+    pub fn list_dir<P: AsRef<Path>>(path: P) -> Result<Vec<OsString>, FileNifError> {
+        let mut names = Vec::new();
+        for entry in fs::read_dir(path)? {
+            names.push(entry?.file_name());
+        }
+        Ok(names)
+    }
+
+    /// Create a directory; its parent must exist
+    pub fn make_dir<P: AsRef<Path>>(path: P) -> Result<(), FileNifError> {
+        Ok(fs::create_dir(path)?)
+    }
+
+    /// Delete an empty directory
+    pub fn del_dir<P: AsRef<Path>>(path: P) -> Result<(), FileNifError> {
+        Ok(fs::remove_dir(path)?)
+    }
+
+    /// Delete a file
+    pub fn delete<P: AsRef<Path>>(path: P) -> Result<(), FileNifError> {
+        Ok(fs::remove_file(path)?)
+    }
+
+    /// Read the target of a symbolic link; `einval` if `path` is not one
+    pub fn read_link<P: AsRef<Path>>(path: P) -> Result<PathBuf, FileNifError> {
+        Ok(fs::read_link(path)?)
+    }
+
+    /// Create a symbolic link `new` pointing at `existing`
+    ///
+    /// On Windows, file and directory links differ; a link to a missing
+    /// target is created as a file link.
+    pub fn make_symlink<P: AsRef<Path>, Q: AsRef<Path>>(existing: P, new: Q) -> Result<(), FileNifError> {
+        #[cfg(unix)]
+        {
+            Ok(std::os::unix::fs::symlink(existing, new)?)
+        }
+        #[cfg(windows)]
+        {
+            let target = new.as_ref().parent().unwrap_or(Path::new("")).join(existing.as_ref());
+            if target.is_dir() {
+                Ok(std::os::windows::fs::symlink_dir(existing, new)?)
+            } else {
+                Ok(std::os::windows::fs::symlink_file(existing, new)?)
+            }
+        }
+    }
+
+    /// Create a hard link `new` to `existing`
+    pub fn make_link<P: AsRef<Path>, Q: AsRef<Path>>(existing: P, new: Q) -> Result<(), FileNifError> {
+        Ok(fs::hard_link(existing, new)?)
+    }
+
+    /// Rename a file or directory
+    ///
+    /// A regular file cannot be renamed across file systems, so in that case
+    /// it is copied and the source deleted. Directories still fail with
+    /// `exdev`.
+    pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<(), FileNifError> {
+        let (from, to) = (from.as_ref(), to.as_ref());
+        match fs::rename(from, to) {
+            Ok(()) => Ok(()),
+            Err(error) => match PosixError::from(error) {
+                PosixError::Exdev => move_across_devices(from, to),
+                error => Err(FileNifError::Posix(error)),
+            },
+        }
+    }
+
+    /// Copy the contents and permissions of a file
+    ///
+    /// Uses in-kernel copying where available, falling back to reading and
+    /// writing when source and destination are on different file systems.
+    ///
+    /// # Returns
+    /// Number of bytes copied
+    pub fn copy<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<u64, FileNifError> {
+        Ok(fs::copy(from, to)?)
+    }
+}
+
+/// Move a regular file by copying it and deleting the source
+fn move_across_devices(from: &Path, to: &Path) -> Result<(), FileNifError> {
+    if !fs::symlink_metadata(from)?.is_file() {
+        return Err(FileNifError::Posix(PosixError::Exdev));
+    }
+    fs::copy(from, to)?;
+    if let Err(error) = fs::remove_file(from) {
+        let _ = fs::remove_file(to);
+        return Err(error.into());
+    }
+    Ok(())
+}
+
+/// Type of a file, the `type` field of `#file_info{}`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileType {
+    /// Block or character device
+    Device,
+    /// Directory
+    Directory,
+    /// FIFO, socket or anything else
+    Other,
+    /// Regular file
+    Regular,
+    /// Symbolic link
+    Symlink,
+}
+
+impl FileType {
+    /// The type atom, e.g. `"regular"`
+    pub fn atom(&self) -> &'static str {
+        match self {
+            FileType::Device => "device",
+            FileType::Directory => "directory",
+            FileType::Other => "other",
+            FileType::Regular => "regular",
+            FileType::Symlink => "symlink",
+        }
+    }
+}
+
+/// Access of the owner to a file, the `access` field of `#file_info{}`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileAccess {
+    /// Readable only
+    Read,
+    /// Writable only
+    Write,
+    /// Readable and writable
+    ReadWrite,
+    /// Neither readable nor writable
+    None,
+}
+
+impl FileAccess {
+    /// The access atom, e.g. `"read_write"`
+    pub fn atom(&self) -> &'static str {
+        match self {
+            FileAccess::Read => "read",
+            FileAccess::Write => "write",
+            FileAccess::ReadWrite => "read_write",
+            FileAccess::None => "none",
+        }
+    }
+
+    fn from_bits(read: bool, write: bool) -> Self {
+        match (read, write) {
+            (true, true) => FileAccess::ReadWrite,
+            (true, false) => FileAccess::Read,
+            (false, true) => FileAccess::Write,
+            (false, false) => FileAccess::None,
+        }
+    }
+}
+
+/// File information, as in the `#file_info{}` record
+///
+/// Timestamps count from the Unix epoch in the unit requested when the
+/// information was read. On Windows `ctime` is the creation time, and the
+/// mode is derived from the file type and the read-only attribute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileInfo {
+    /// Size in bytes
+    pub size: u64,
+    /// Type of the file
+    pub file_type: FileType,
+    /// Access of the owner
+    pub access: FileAccess,
+    /// Last access time
+    pub atime: i64,
+    /// Last modification time
+    pub mtime: i64,
+    /// Last status change time
+    pub ctime: i64,
+    /// File type and permission bits (`st_mode`)
+    pub mode: u32,
+    /// Number of hard links
+    pub links: u64,
+    /// Device the file resides on (`st_dev`)
+    pub major_device: u64,
+    /// Device a special file represents (`st_rdev`)
+    pub minor_device: u64,
+    /// Inode number
+    pub inode: u64,
+    /// Owner user ID
+    pub uid: u32,
+    /// Owner group ID
+    pub gid: u32,
+}
+
+impl FileInfo {
+    /// Build the information from file metadata
+    pub fn from_metadata(metadata: &Metadata, unit: TimeUnit) -> Self {
+        let file_type = metadata.file_type();
+        let kind = if file_type.is_symlink() {
+            FileType::Symlink
+        } else if file_type.is_dir() {
+            FileType::Directory
+        } else if file_type.is_file() {
+            FileType::Regular
+        } else if is_device(&file_type) {
+            FileType::Device
+        } else {
+            FileType::Other
+        };
+        let time = |seconds: i64, nanos: i64| {
+            convert_time_unit(seconds.saturating_mul(1_000_000_000).saturating_add(nanos), TimeUnit::Nanosecond, unit)
+        };
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let mode = metadata.mode();
+            Self {
+                size: metadata.size(),
+                file_type: kind,
+                access: FileAccess::from_bits(mode & 0o400 != 0, mode & 0o200 != 0),
+                atime: time(metadata.atime(), metadata.atime_nsec()),
+                mtime: time(metadata.mtime(), metadata.mtime_nsec()),
+                ctime: time(metadata.ctime(), metadata.ctime_nsec()),
+                mode,
+                links: metadata.nlink(),
+                major_device: metadata.dev(),
+                minor_device: metadata.rdev(),
+                inode: metadata.ino(),
+                uid: metadata.uid(),
+                gid: metadata.gid(),
+            }
+        }
+        #[cfg(windows)]
+        {
+            let system_time = |result: io::Result<std::time::SystemTime>| match result {
+                Ok(at) => match at.duration_since(std::time::UNIX_EPOCH) {
+                    Ok(since) => time(since.as_secs() as i64, i64::from(since.subsec_nanos())),
+                    Err(before) => -time(before.duration().as_secs() as i64, i64::from(before.duration().subsec_nanos())),
+                },
+                Err(_) => 0,
+            };
+            let read_only = metadata.permissions().readonly();
+            let permissions = if read_only { 0o444 } else { 0o666 };
+            let mode = match kind {
+                FileType::Directory => 0o040000 | permissions | 0o111,
+                _ => 0o100000 | permissions,
+            };
+            Self {
+                size: metadata.len(),
+                file_type: kind,
+                access: FileAccess::from_bits(true, !read_only),
+                atime: system_time(metadata.accessed()),
+                mtime: system_time(metadata.modified()),
+                ctime: system_time(metadata.created()),
+                mode,
+                links: 1,
+                major_device: 0,
+                minor_device: 0,
+                inode: 0,
+                uid: 0,
+                gid: 0,
+            }
+        }
     }
 }
 
+#[cfg(unix)]
+fn is_device(file_type: &fs::FileType) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    file_type.is_block_device() || file_type.is_char_device()
+}
+
+#[cfg(windows)]
+fn is_device(_file_type: &fs::FileType) -> bool {
+    false
+}
+
+/// Access pattern hint for [`FileHandle::advise`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Advice {
+    /// No particular access pattern
+    Normal,
+    /// Data is read sequentially
+    Sequential,
+    /// Data is read in random order
+    Random,
+    /// Data is read only once
+    NoReuse,
+    /// Data will be read in the near future
+    WillNeed,
+    /// Data will not be read in the near future
+    DontNeed,
+}
+
+/// Result of [`FileHandle::ipread_s32bu_p32bu`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpreadResult {
+    /// Size read from the header
+    pub size: u32,
+    /// Pointer read from the header
+    pub pointer: u32,
+    /// Data at the pointer, or `None` if the pointer is at or beyond end of file
+    pub data: Option<Vec<u8>>,
+}
+
 /// File handle wrapper
+#[derive(Debug)]
 pub struct FileHandle {
     file: File,
 }
 
+impl From<File> for FileHandle {
+    fn from(file: File) -> Self {
+        FileHandle { file }
+    }
+}
+
 impl FileHandle {
     /// Read from file
-    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, NifError> {
-        self.file.read(buf).map_err(|_| NifError::BadArg)
+    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, FileNifError> {
+        Ok(self.file.read(buf)?)
     }
 
     /// Write to file
-    pub fn write(&mut self, buf: &[u8]) -> Result<usize, NifError> {
-        self.file.write(buf).map_err(|_| NifError::BadArg)
+    pub fn write(&mut self, buf: &[u8]) -> Result<usize, FileNifError> {
+        Ok(self.file.write(buf)?)
+    }
+
+    /// Write the segments of an iolist without flattening them
+    ///
+    /// Uses `writev`, continuing after partial writes until every segment
+    /// is written.
+    ///
+    /// # Returns
+    /// Number of bytes written
+    pub fn write_vectored(&mut self, segments: &[&[u8]]) -> Result<usize, FileNifError> {
+        let mut slices: Vec<IoSlice> = segments.iter().map(|s| IoSlice::new(s)).collect();
+        let mut remaining = &mut slices[..];
+        let mut written = 0;
+        while !remaining.is_empty() {
+            match self.file.write_vectored(remaining) {
+                Ok(0) => return Err(FileNifError::Posix(PosixError::Eio)),
+                Ok(n) => {
+                    written += n;
+                    IoSlice::advance_slices(&mut remaining, n);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(written)
+    }
+
+    /// Write an iolist term built in a NIF environment
+    ///
+    /// The iolist is split into segments with `iolist_to_iovec` and written
+    /// with [`FileHandle::write_vectored`].
+    ///
+    /// # Returns
+    /// Number of bytes written, or `BadArg` if `iolist` is not an iolist
+    pub fn write_iolist(&mut self, env: &NifEnv, iolist: NifTerm) -> Result<usize, FileNifError> {
+        let segments = iolist_to_iovec(env, iolist).ok_or(FileNifError::BadArg)?;
+        let slices: Vec<&[u8]> = segments.iter().map(|segment| segment.data()).collect();
+        self.write_vectored(&slices)
+    }
+
+    /// Read into pre-allocated buffers with a single `readv`
+    ///
+    /// Buffers are filled in order.
+    ///
+    /// # Returns
+    /// Number of bytes read, 0 at end of file
+    pub fn read_vectored(&mut self, buffers: &mut [&mut [u8]]) -> Result<usize, FileNifError> {
+        let mut slices: Vec<IoSliceMut> = buffers.iter_mut().map(|b| IoSliceMut::new(b)).collect();
+        Ok(self.file.read_vectored(&mut slices)?)
+    }
+
+    /// Read `size` bytes at `offset` (`pread`)
+    ///
+    /// Reads until `size` bytes are read or end of file is reached. The file
+    /// position is left unchanged on Unix; on Windows the read moves it.
+    ///
+    /// # Returns
+    /// The data read, or `None` (`eof`) if `offset` is at or beyond end of file
+    pub fn pread(&self, offset: u64, size: usize) -> Result<Option<Vec<u8>>, FileNifError> {
+        let mut data = vec![0u8; size];
+        let mut filled = 0;
+        while filled < size {
+            match read_at(&self.file, &mut data[filled..], offset + filled as u64) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        if filled == 0 && size > 0 {
+            return Ok(None);
+        }
+        data.truncate(filled);
+        Ok(Some(data))
+    }
+
+    /// Write all of `data` at `offset` (`pwrite`)
+    ///
+    /// The file position is left unchanged on Unix; on Windows the write
+    /// moves it.
+    pub fn pwrite(&self, offset: u64, data: &[u8]) -> Result<(), FileNifError> {
+        let mut written = 0;
+        while written < data.len() {
+            match write_at(&self.file, &data[written..], offset + written as u64) {
+                Ok(0) => return Err(FileNifError::Posix(PosixError::Eio)),
+                Ok(n) => written += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Move the file position
+    ///
+    /// `SeekFrom::End` corresponds to the `eof` location of `file:position/2`.
+    /// Seeking to a negative position fails with `einval`.
+    ///
+    /// # Returns
+    /// The new position
+    pub fn position(&mut self, location: SeekFrom) -> Result<u64, FileNifError> {
+        Ok(self.file.seek(location)?)
+    }
+
+    /// Truncate the file at the current position
+    pub fn truncate(&mut self) -> Result<(), FileNifError> {
+        let position = self.file.stream_position()?;
+        Ok(self.file.set_len(position)?)
+    }
+
+    /// Preallocate `length` bytes of disk space starting at `offset`
+    ///
+    /// Uses `posix_fallocate` where available and fails with `enotsup`
+    /// elsewhere, like `file:allocate/3`.
+    pub fn allocate(&self, offset: u64, length: u64) -> Result<(), FileNifError> {
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+        {
+            use std::os::unix::io::AsRawFd;
+            let (offset, length) = (to_off_t(offset)?, to_off_t(length)?);
+            // SAFETY: the descriptor is owned by `self.file` and stays open
+            // for the duration of the call
+            let result = unsafe { libc::posix_fallocate(self.file.as_raw_fd(), offset, length) };
+            if result != 0 {
+                return Err(FileNifError::Posix(PosixError::from_errno(result)));
+            }
+            Ok(())
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+        {
+            let _ = (offset, length);
+            Err(FileNifError::Posix(PosixError::Enotsup))
+        }
+    }
+
+    /// Flush data and metadata to disk (`fsync`)
+    pub fn sync(&self) -> Result<(), FileNifError> {
+        Ok(self.file.sync_all()?)
+    }
+
+    /// Flush data, but not necessarily metadata, to disk (`fdatasync`)
+    pub fn datasync(&self) -> Result<(), FileNifError> {
+        Ok(self.file.sync_data()?)
+    }
+
+    /// Declare the access pattern for `length` bytes at `offset`
+    ///
+    /// A `length` of 0 covers everything up to end of file. Uses
+    /// `posix_fadvise` where available; elsewhere the advice is ignored and
+    /// the call succeeds, as in `prim_file`.
+    pub fn advise(&self, offset: u64, length: u64, advice: Advice) -> Result<(), FileNifError> {
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+        {
+            use std::os::unix::io::AsRawFd;
+            let advice = match advice {
+                Advice::Normal => libc::POSIX_FADV_NORMAL,
+                Advice::Sequential => libc::POSIX_FADV_SEQUENTIAL,
+                Advice::Random => libc::POSIX_FADV_RANDOM,
+                Advice::NoReuse => libc::POSIX_FADV_NOREUSE,
+                Advice::WillNeed => libc::POSIX_FADV_WILLNEED,
+                Advice::DontNeed => libc::POSIX_FADV_DONTNEED,
+            };
+            let (offset, length) = (to_off_t(offset)?, to_off_t(length)?);
+            // SAFETY: the descriptor is owned by `self.file` and stays open
+            // for the duration of the call
+            let result = unsafe { libc::posix_fadvise(self.file.as_raw_fd(), offset, length, advice) };
+            if result != 0 {
+                return Err(FileNifError::Posix(PosixError::from_errno(result)));
+            }
+            Ok(())
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+        {
+            let _ = (offset, length, advice);
+            Ok(())
+        }
+    }
+
+    /// Indirect positional read, as used by `dets` and `disk_log`
+    ///
+    /// Reads a header of two big-endian 32-bit integers, `Size` and
+    /// `Pointer`, at `offset` and then `Size` bytes at `Pointer`.
+    ///
+    /// # Returns
+    /// * `Ok(Some(result))` - The header and the data it points to
+    /// * `Ok(None)` - The header is beyond end of file (`eof`)
+    /// * `Err(einval)` - `Size` exceeds `max_size`
+    pub fn ipread_s32bu_p32bu(&self, offset: u64, max_size: u32) -> Result<Option<IpreadResult>, FileNifError> {
+        let header = match self.pread(offset, 8)? {
+            Some(header) if header.len() == 8 => header,
+            _ => return Ok(None),
+        };
+        let size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+        let pointer = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+        if size > max_size {
+            return Err(FileNifError::Posix(PosixError::Einval));
+        }
+        let data = self.pread(u64::from(pointer), size as usize)?;
+        Ok(Some(IpreadResult { size, pointer, data }))
+    }
+
+    /// Read the information of the open file (`fstat`)
+    pub fn read_info(&self, unit: TimeUnit) -> Result<FileInfo, FileNifError> {
+        Ok(FileInfo::from_metadata(&self.file.metadata()?, unit))
+    }
+
+    /// Get the underlying file, e.g. to send it with `sendfile`
+    pub fn file(&self) -> &File {
+        &self.file
     }
 }
 
-use super::buffer::BufferNifError as NifError;
+#[cfg(unix)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::read_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_read(file, buf, offset)
+}
+
+#[cfg(unix)]
+fn write_at(file: &File, buf: &[u8], offset: u64) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::write_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn write_at(file: &File, buf: &[u8], offset: u64) -> io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_write(file, buf, offset)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+fn to_off_t(value: u64) -> Result<libc::off_t, FileNifError> {
+    libc::off_t::try_from(value).map_err(|_| FileNifError::Posix(PosixError::Efbig))
+}
+
+/// File NIF error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileNifError {
+    /// Bad argument
+    BadArg,
+    /// Operation failed with a posix error
+    Posix(PosixError),
+    /// The dirty scheduler running the operation stopped before completing
+    /// it; the operation may or may not have taken effect
+    SchedulerStopped,
+}
+
+impl FileNifError {
+    /// Reason atom of the `{error, Reason}` tuple
+    pub fn reason(&self) -> &'static str {
+        match self {
+            FileNifError::BadArg => "badarg",
+            FileNifError::Posix(error) => error.atom(),
+            FileNifError::SchedulerStopped => "eio",
+        }
+    }
+}
+
+impl std::fmt::Display for FileNifError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.reason())
+    }
+}
+
+impl std::error::Error for FileNifError {}
+
+impl From<io::Error> for FileNifError {
+    fn from(error: io::Error) -> Self {
+        FileNifError::Posix(PosixError::from(error))
+    }
+}
+
+/// Posix error, named after its `errno` value like in `erl_posix_str`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PosixError {
+    Eperm,
+    Enoent,
+    Eintr,
+    Eio,
+    Ebadf,
+    Eagain,
+    Enomem,
+    Eacces,
+    Ebusy,
+    Eexist,
+    Exdev,
+    Enotdir,
+    Eisdir,
+    Einval,
+    Enfile,
+    Emfile,
+    Efbig,
+    Enospc,
+    Espipe,
+    Erofs,
+    Epipe,
+    Enametoolong,
+    Enosys,
+    Enotempty,
+    Eloop,
+    Enotsup,
+    Eoverflow,
+    Edquot,
+    /// An `errno` value without a name
+    Unknown(i32),
+}
+
+impl PosixError {
+    /// The error atom, e.g. `"enoent"`
+    pub fn atom(&self) -> &'static str {
+        match self {
+            PosixError::Eperm => "eperm",
+            PosixError::Enoent => "enoent",
+            PosixError::Eintr => "eintr",
+            PosixError::Eio => "eio",
+            PosixError::Ebadf => "ebadf",
+            PosixError::Eagain => "eagain",
+            PosixError::Enomem => "enomem",
+            PosixError::Eacces => "eacces",
+            PosixError::Ebusy => "ebusy",
+            PosixError::Eexist => "eexist",
+            PosixError::Exdev => "exdev",
+            PosixError::Enotdir => "enotdir",
+            PosixError::Eisdir => "eisdir",
+            PosixError::Einval => "einval",
+            PosixError::Enfile => "enfile",
+            PosixError::Emfile => "emfile",
+            PosixError::Efbig => "efbig",
+            PosixError::Enospc => "enospc",
+            PosixError::Espipe => "espipe",
+            PosixError::Erofs => "erofs",
+            PosixError::Epipe => "epipe",
+            PosixError::Enametoolong => "enametoolong",
+            PosixError::Enosys => "enosys",
+            PosixError::Enotempty => "enotempty",
+            PosixError::Eloop => "eloop",
+            PosixError::Enotsup => "enotsup",
+            PosixError::Eoverflow => "eoverflow",
+            PosixError::Edquot => "edquot",
+            PosixError::Unknown(_) => "unknown",
+        }
+    }
+
+    /// Map an `errno` value
+    #[cfg(unix)]
+    pub fn from_errno(errno: i32) -> Self {
+        // A table rather than a match, since some values are aliases of each
+        // other on some platforms (EOPNOTSUPP and ENOTSUP, EWOULDBLOCK and EAGAIN)
+        const ERRNOS: &[(i32, PosixError)] = &[
+            (libc::EPERM, PosixError::Eperm),
+            (libc::ENOENT, PosixError::Enoent),
+            (libc::EINTR, PosixError::Eintr),
+            (libc::EIO, PosixError::Eio),
+            (libc::EBADF, PosixError::Ebadf),
+            (libc::EAGAIN, PosixError::Eagain),
+            (libc::EWOULDBLOCK, PosixError::Eagain),
+            (libc::ENOMEM, PosixError::Enomem),
+            (libc::EACCES, PosixError::Eacces),
+            (libc::EBUSY, PosixError::Ebusy),
+            (libc::EEXIST, PosixError::Eexist),
+            (libc::EXDEV, PosixError::Exdev),
+            (libc::ENOTDIR, PosixError::Enotdir),
+            (libc::EISDIR, PosixError::Eisdir),
+            (libc::EINVAL, PosixError::Einval),
+            (libc::ENFILE, PosixError::Enfile),
+            (libc::EMFILE, PosixError::Emfile),
+            (libc::EFBIG, PosixError::Efbig),
+            (libc::ENOSPC, PosixError::Enospc),
+            (libc::ESPIPE, PosixError::Espipe),
+            (libc::EROFS, PosixError::Erofs),
+            (libc::EPIPE, PosixError::Epipe),
+            (libc::ENAMETOOLONG, PosixError::Enametoolong),
+            (libc::ENOSYS, PosixError::Enosys),
+            (libc::ENOTEMPTY, PosixError::Enotempty),
+            (libc::ELOOP, PosixError::Eloop),
+            (libc::ENOTSUP, PosixError::Enotsup),
+            (libc::EOPNOTSUPP, PosixError::Enotsup),
+            (libc::EOVERFLOW, PosixError::Eoverflow),
+            (libc::EDQUOT, PosixError::Edquot),
+        ];
+        ERRNOS
+            .iter()
+            .find(|(value, _)| *value == errno)
+            .map_or(PosixError::Unknown(errno), |(_, error)| *error)
+    }
+
+    /// Map an `io::ErrorKind`, for errors without an `errno` value
+    fn from_kind(kind: io::ErrorKind) -> Self {
+        match kind {
+            io::ErrorKind::NotFound => PosixError::Enoent,
+            io::ErrorKind::PermissionDenied => PosixError::Eacces,
+            io::ErrorKind::AlreadyExists => PosixError::Eexist,
+            io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData => PosixError::Einval,
+            io::ErrorKind::Interrupted => PosixError::Eintr,
+            io::ErrorKind::WouldBlock => PosixError::Eagain,
+            io::ErrorKind::BrokenPipe => PosixError::Epipe,
+            io::ErrorKind::OutOfMemory => PosixError::Enomem,
+            io::ErrorKind::Unsupported => PosixError::Enotsup,
+            io::ErrorKind::NotADirectory => PosixError::Enotdir,
+            io::ErrorKind::IsADirectory => PosixError::Eisdir,
+            io::ErrorKind::DirectoryNotEmpty => PosixError::Enotempty,
+            io::ErrorKind::CrossesDevices => PosixError::Exdev,
+            io::ErrorKind::ReadOnlyFilesystem => PosixError::Erofs,
+            io::ErrorKind::StorageFull => PosixError::Enospc,
+            io::ErrorKind::FileTooLarge => PosixError::Efbig,
+            io::ErrorKind::ResourceBusy => PosixError::Ebusy,
+            io::ErrorKind::NotSeekable => PosixError::Espipe,
+            _ => PosixError::Eio,
+        }
+    }
+}
+
+impl From<io::Error> for PosixError {
+    fn from(error: io::Error) -> Self {
+        #[cfg(unix)]
+        if let Some(errno) = error.raw_os_error() {
+            return PosixError::from_errno(errno);
+        }
+        PosixError::from_kind(error.kind())
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::fs;
-    use std::io::Write;
     use std::path::PathBuf;
 
     #[test]
@@ -70,5 +824,249 @@ mod tests {
         // Cleanup
         let _ = fs::remove_file(&test_file);
     }
-}
 
+    #[test]
+    fn test_file_nif_vectored_io() {
+        let path = std::env::temp_dir().join(format!("test_nif_file_vectored_{}", std::process::id()));
+
+        let mut handle = FileNif::create(&path).unwrap();
+        let written = handle.write_vectored(&[b"hello", b"", b", ", b"world"]).unwrap();
+        assert_eq!(written, 12);
+        assert_eq!(handle.write_vectored(&[]).unwrap(), 0);
+        assert_eq!(fs::read(&path).unwrap(), b"hello, world");
+
+        let mut handle = FileNif::open(&path).unwrap();
+        let mut head = [0u8; 5];
+        let mut tail = vec![0u8; 16];
+        let read = handle.read_vectored(&mut [&mut head, &mut tail]).unwrap();
+        assert_eq!(read, 12);
+        assert_eq!(&head, b"hello");
+        assert_eq!(&tail[..7], b", world");
+        assert_eq!(handle.read_vectored(&mut [&mut head]).unwrap(), 0);
+        assert!(handle.file().metadata().unwrap().is_file());
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_file_nif_write_iolist() {
+        use entities_process::Process;
+        use infrastructure_nif_api::{enif_make_binary, enif_make_int, enif_make_list};
+        use std::sync::Arc;
+
+        let path = std::env::temp_dir().join(format!("test_nif_file_iolist_{}", std::process::id()));
+        let env = NifEnv::from_process(Arc::new(Process::new(41382)));
+        let nested = enif_make_list(&env, &[enif_make_binary(&env, b", ".to_vec())]);
+        let iolist = enif_make_list(&env, &[
+            enif_make_binary(&env, b"hello".to_vec()),
+            nested,
+            enif_make_int(&env, b'!' as i32),
+        ]);
+
+        let mut handle = FileNif::create(&path).unwrap();
+        assert_eq!(handle.write_iolist(&env, iolist).unwrap(), 8);
+        assert_eq!(handle.write_iolist(&env, enif_make_int(&env, 256)), Err(FileNifError::BadArg));
+        assert_eq!(fs::read(&path).unwrap(), b"hello, !");
+
+        let _ = fs::remove_file(&path);
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{}_{}", name, std::process::id()))
+    }
+
+    fn read_write(path: &PathBuf) -> FileHandle {
+        FileNif::open_with(path, OpenOptions::new().read(true).write(true).create(true).truncate(true)).unwrap()
+    }
+
+    #[test]
+    fn test_file_nif_posix_error_atoms() {
+        let missing = temp_path("test_nif_file_missing");
+        let error = FileNif::open(&missing).err().unwrap();
+        assert_eq!(error, FileNifError::Posix(PosixError::Enoent));
+        assert_eq!(error.reason(), "enoent");
+
+        let path = temp_path("test_nif_file_exclusive");
+        let _ = FileNif::create(&path).unwrap();
+        let error = FileNif::open_with(&path, OpenOptions::new().write(true).create_new(true)).err().unwrap();
+        assert_eq!(error.reason(), "eexist");
+        let _ = fs::remove_file(&path);
+
+        let error = FileNif::open(std::env::temp_dir()).and_then(|mut dir| dir.read(&mut [0u8; 1])).err().unwrap();
+        assert_eq!(error.reason(), "eisdir");
+    }
+
+    #[test]
+    fn test_file_nif_pread_pwrite() {
+        let path = temp_path("test_nif_file_positional");
+        let mut handle = read_write(&path);
+        handle.pwrite(0, b"hello world").unwrap();
+        handle.pwrite(6, b"there").unwrap();
+
+        assert_eq!(handle.pread(0, 5).unwrap(), Some(b"hello".to_vec()));
+        // Short read at end of file, then eof
+        assert_eq!(handle.pread(6, 100).unwrap(), Some(b"there".to_vec()));
+        assert_eq!(handle.pread(11, 4).unwrap(), None);
+        assert_eq!(handle.pread(3, 0).unwrap(), Some(Vec::new()));
+        // Positional operations leave the file position alone
+        #[cfg(unix)]
+        assert_eq!(handle.position(SeekFrom::Current(0)).unwrap(), 0);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_file_nif_position_and_truncate() {
+        let path = temp_path("test_nif_file_truncate");
+        let mut handle = read_write(&path);
+        handle.write(b"0123456789").unwrap();
+
+        assert_eq!(handle.position(SeekFrom::End(0)).unwrap(), 10);
+        assert_eq!(handle.position(SeekFrom::Start(4)).unwrap(), 4);
+        assert_eq!(handle.position(SeekFrom::Current(-1)).unwrap(), 3);
+        assert_eq!(handle.position(SeekFrom::Current(-10)).err().unwrap().reason(), "einval");
+
+        handle.position(SeekFrom::Start(4)).unwrap();
+        handle.truncate().unwrap();
+        handle.sync().unwrap();
+        handle.datasync().unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"0123");
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_file_nif_allocate_and_advise() {
+        let path = temp_path("test_nif_file_allocate");
+        let handle = read_write(&path);
+
+        handle.advise(0, 0, Advice::Sequential).unwrap();
+        handle.advise(0, 4096, Advice::DontNeed).unwrap();
+        match handle.allocate(0, 4096) {
+            Ok(()) => assert_eq!(handle.file().metadata().unwrap().len(), 4096),
+            // Not every platform or file system supports preallocation
+            Err(error) => assert_eq!(error.reason(), "enotsup"),
+        }
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_file_nif_ipread() {
+        let path = temp_path("test_nif_file_ipread");
+        let handle = read_write(&path);
+        // Header at 0 pointing at 16, header at 8 pointing beyond end of file
+        handle.pwrite(0, &[0, 0, 0, 5, 0, 0, 0, 16]).unwrap();
+        handle.pwrite(8, &[0, 0, 0, 2, 0, 0, 1, 0]).unwrap();
+        handle.pwrite(16, b"abcde").unwrap();
+
+        let result = handle.ipread_s32bu_p32bu(0, 100).unwrap().unwrap();
+        assert_eq!(result, IpreadResult { size: 5, pointer: 16, data: Some(b"abcde".to_vec()) });
+        let result = handle.ipread_s32bu_p32bu(8, 100).unwrap().unwrap();
+        assert_eq!((result.size, result.pointer, result.data), (2, 256, None));
+        assert_eq!(handle.ipread_s32bu_p32bu(0, 4).err().unwrap().reason(), "einval");
+        // Incomplete header
+        assert_eq!(handle.ipread_s32bu_p32bu(17, 100).unwrap(), None);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_file_nif_read_info() {
+        let path = temp_path("test_nif_file_info");
+        let mut handle = read_write(&path);
+        handle.write(b"twelve bytes").unwrap();
+
+        let info = FileNif::read_info(&path, TimeUnit::Second).unwrap();
+        assert_eq!(info.size, 12);
+        assert_eq!(info.file_type, FileType::Regular);
+        assert_eq!(info.file_type.atom(), "regular");
+        assert!(info.mtime > 1_600_000_000);
+        let nanos = handle.read_info(TimeUnit::Nanosecond).unwrap();
+        assert_eq!(nanos.mtime / 1_000_000_000, info.mtime);
+        assert_eq!(FileNif::read_info(&path, TimeUnit::Millisecond).unwrap().mtime / 1000, info.mtime);
+        #[cfg(unix)]
+        {
+            assert_eq!(info.mode & 0o170000, 0o100000);
+            assert_eq!(info.access, FileAccess::ReadWrite);
+            assert_eq!(info.links, 1);
+            assert_ne!(info.inode, 0);
+            assert_eq!(info.uid, unsafe { libc::getuid() });
+        }
+
+        let dir = FileNif::read_info(std::env::temp_dir(), TimeUnit::Second).unwrap();
+        assert_eq!(dir.file_type.atom(), "directory");
+        assert_eq!(FileNif::read_info(temp_path("test_nif_file_no_info"), TimeUnit::Second).err().unwrap().reason(), "enoent");
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_nif_links() {
+        let dir = temp_path("test_nif_file_links");
+        FileNif::make_dir(&dir).unwrap();
+        let target = dir.join("target");
+        fs::write(&target, b"data").unwrap();
+
+        let link = dir.join("symlink");
+        FileNif::make_symlink("target", &link).unwrap();
+        assert_eq!(FileNif::read_link(&link).unwrap(), PathBuf::from("target"));
+        assert_eq!(FileNif::read_link(&target).err().unwrap().reason(), "einval");
+        assert_eq!(FileNif::read_link_info(&link, TimeUnit::Second).unwrap().file_type, FileType::Symlink);
+        assert_eq!(FileNif::read_info(&link, TimeUnit::Second).unwrap().size, 4);
+
+        let hard = dir.join("hardlink");
+        FileNif::make_link(&target, &hard).unwrap();
+        assert_eq!(FileNif::read_info(&target, TimeUnit::Second).unwrap().links, 2);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_file_nif_directories() {
+        let dir = temp_path("test_nif_file_dir");
+        let _ = fs::remove_dir_all(&dir);
+        FileNif::make_dir(&dir).unwrap();
+        assert_eq!(FileNif::make_dir(&dir).err().unwrap().reason(), "eexist");
+        assert_eq!(FileNif::make_dir(dir.join("a").join("b")).err().unwrap().reason(), "enoent");
+
+        fs::write(dir.join("one"), b"1").unwrap();
+        FileNif::make_dir(dir.join("two")).unwrap();
+        let mut names = FileNif::list_dir(&dir).unwrap();
+        names.sort();
+        assert_eq!(names, vec![OsString::from("one"), OsString::from("two")]);
+
+        assert_eq!(FileNif::del_dir(&dir).err().unwrap().reason(), "enotempty");
+        assert_eq!(FileNif::list_dir(dir.join("one")).err().unwrap().reason(), "enotdir");
+        FileNif::delete(dir.join("one")).unwrap();
+        FileNif::del_dir(dir.join("two")).unwrap();
+        FileNif::del_dir(&dir).unwrap();
+        assert_eq!(FileNif::list_dir(&dir).err().unwrap().reason(), "enoent");
+    }
+
+    #[test]
+    fn test_file_nif_rename_and_copy() {
+        let dir = temp_path("test_nif_file_rename");
+        let _ = fs::remove_dir_all(&dir);
+        FileNif::make_dir(&dir).unwrap();
+        let (a, b, c) = (dir.join("a"), dir.join("b"), dir.join("c"));
+        fs::write(&a, b"contents").unwrap();
+
+        assert_eq!(FileNif::copy(&a, &b).unwrap(), 8);
+        FileNif::rename(&b, &c).unwrap();
+        assert!(!b.exists());
+        assert_eq!(fs::read(&c).unwrap(), b"contents");
+        assert_eq!(FileNif::rename(&b, &c).err().unwrap().reason(), "enoent");
+
+        // The cross-device fallback moves regular files only
+        move_across_devices(&c, &b).unwrap();
+        assert!(!c.exists());
+        assert_eq!(fs::read(&b).unwrap(), b"contents");
+        let sub = dir.join("sub");
+        FileNif::make_dir(&sub).unwrap();
+        assert_eq!(move_across_devices(&sub, &dir.join("moved")).err().unwrap().reason(), "exdev");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
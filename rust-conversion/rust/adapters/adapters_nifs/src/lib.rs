@@ -13,10 +13,15 @@
 //!
 //! ## Modules
 //!
+//! - **[`async_file`](async_file/index.html)**: File operations offloaded to
+//!   the dirty I/O schedulers
+//!
 //! - **[`buffer`](buffer/index.html)**: Buffer NIFs for efficient binary data
 //!   manipulation operations
 //!
-//! - **[`file`](file/index.html)**: File NIFs for file system operations
+//! - **[`file`](file/index.html)**: File NIFs for file system operations,
+//!   including positional I/O, preallocation, directory and metadata
+//!   operations, and posix error atoms
 //!
 //! - **[`nif_common`](nif_common/index.html)**: Common NIF infrastructure and
 //!   utilities shared across NIF modules
@@ -35,16 +40,19 @@
 //! - [`usecases_nif_compilation`](../../usecases/usecases_nif_compilation/index.html): NIF compilation use cases
 //! - [`entities_data_handling`](../../entities/entities_data_handling/index.html): Term types for NIFs
 
+pub mod async_file;
 pub mod buffer;
 pub mod file;
 pub mod nif_common;
 pub mod nif_loader;
 
+pub use async_file::{AsyncFile, FileTask};
 pub use buffer::BufferNif;
-pub use file::FileNif;
+pub use file::{FileInfo, FileNif, FileNifError, PosixError};
 pub use nif_loader::{
     NifLoader, NifLibrary, NifLibraryRef, NifFunction, NifRegistry, NifFunctionPtr,
     NifLoadError, NifUnloadError, NifError,
     RustNifMetadata, FunctionMetadata, NifGetMetadataFn,
+    NifLifecycle, NifPrivData, NifLoadFn, NifUpgradeFn, NifUnloadFn,
 };
 
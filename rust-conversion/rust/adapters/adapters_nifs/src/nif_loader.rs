@@ -26,6 +26,19 @@
 //! 2. **NIF Function Registration**: Register NIF function pointers when libraries are loaded
 //! 3. **Process-NIF Association**: Associate NIF pointers with processes when NIFs are called
 //! 4. **NIF Pointer Tracking**: Store NIF function pointers in Process struct for code purging safety
+//! 5. **Library Lifecycle**: Call the library's `nif_load`, `nif_upgrade` and `nif_unload`
+//!    callbacks when it is loaded, when a reloaded module loads a new version of it,
+//!    and when the module instance it belongs to is purged
+//!
+//! ## Hot Code Reloading
+//!
+//! A module has at most a current and an old instance, and each may have a NIF
+//! library. When the current instance of a module with a NIF library is replaced,
+//! its library becomes the old instance's library and stays loaded, since processes
+//! may still run old code calling its NIFs. If the new instance loads a NIF
+//! library, the new library's `nif_upgrade` callback receives the old library's
+//! private data. The old library's `nif_unload` callback runs, and the library is
+//! unloaded, only when the old instance is purged.
 //!
 //! ## Integration with Process Struct
 //!
@@ -57,13 +70,15 @@
  * %CopyrightEnd%
  */
 
+use std::any::Any;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 use std::ffi::CString;
 use libloading::Library;
 
 use entities_process::Process;
+use code_management_code_loading::{get_global_code_permissions, CodeOperation};
 
 /// Reference to a NIF library (reference counted)
 pub type NifLibraryRef = Arc<NifLibrary>;
@@ -109,6 +124,39 @@ pub struct FunctionMetadata {
 /// Rust NIF libraries export this function to provide metadata
 pub type NifGetMetadataFn = unsafe extern "C" fn() -> *const RustNifMetadata;
 
+/// Private data of a NIF library (`priv_data`), set by its lifecycle callbacks
+pub type NifPrivData = Option<Box<dyn Any + Send + Sync>>;
+
+/// Called when the library is loaded for a module without an old instance
+/// with a NIF library (`load`)
+///
+/// Receives the library's private data to set and the load info term passed
+/// to `erlang:load_nif/2`. Returns 0 on success.
+pub type NifLoadFn = unsafe extern "C" fn(priv_data: &mut NifPrivData, load_info: u64) -> i32;
+
+/// Called when the library is loaded for a module whose old instance has a
+/// NIF library (`upgrade`)
+///
+/// Receives the library's private data to set, the old library's private
+/// data, which it may take over, and the load info term. Returns 0 on success.
+pub type NifUpgradeFn =
+    unsafe extern "C" fn(priv_data: &mut NifPrivData, old_priv_data: &mut NifPrivData, load_info: u64) -> i32;
+
+/// Called before the library is unloaded (`unload`)
+pub type NifUnloadFn = unsafe extern "C" fn(priv_data: &mut NifPrivData);
+
+/// Lifecycle callbacks exported by a NIF library as `nif_load`,
+/// `nif_upgrade` and `nif_unload`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NifLifecycle {
+    /// `load` callback
+    pub load: Option<NifLoadFn>,
+    /// `upgrade` callback; without it the library cannot replace an old version
+    pub upgrade: Option<NifUpgradeFn>,
+    /// `unload` callback
+    pub unload: Option<NifUnloadFn>,
+}
+
 /// Represents a loaded NIF library
 ///
 /// This struct contains information about a dynamically loaded NIF library,
@@ -138,6 +186,10 @@ pub struct NifLibrary {
     functions: HashMap<String, NifFunctionPtr>,
     /// Reference count (number of processes using this library)
     ref_count: Arc<RwLock<usize>>,
+    /// Lifecycle callbacks
+    lifecycle: NifLifecycle,
+    /// Private data set by the lifecycle callbacks
+    priv_data: Mutex<NifPrivData>,
 }
 
 impl NifLibrary {
@@ -163,9 +215,17 @@ impl NifLibrary {
             library_path,
             functions,
             ref_count: Arc::new(RwLock::new(1)),
+            lifecycle: NifLifecycle::default(),
+            priv_data: Mutex::new(None),
         }
     }
 
+    /// Set the lifecycle callbacks of the library
+    fn with_lifecycle(mut self, lifecycle: NifLifecycle) -> Self {
+        self.lifecycle = lifecycle;
+        self
+    }
+
     /// Create a new NIF library instance for testing
     ///
     /// This is a test-only public constructor that allows creating NifLibrary
@@ -267,6 +327,16 @@ impl NifLibrary {
     pub fn ref_count(&self) -> usize {
         *self.ref_count.read().unwrap()
     }
+
+    /// Get the lifecycle callbacks of the library
+    pub fn lifecycle(&self) -> NifLifecycle {
+        self.lifecycle
+    }
+
+    /// Run a function with the library's private data (`enif_priv_data`)
+    pub fn with_priv_data<R>(&self, f: impl FnOnce(&mut NifPrivData) -> R) -> R {
+        f(&mut self.priv_data.lock().unwrap())
+    }
 }
 
 // Safety: NifLibrary is Send + Sync because:
@@ -308,6 +378,9 @@ pub struct NifFunction {
 pub struct NifRegistry {
     /// Map of module names to NIF libraries
     libraries: Arc<RwLock<HashMap<String, NifLibraryRef>>>,
+    /// Map of module names to the NIF libraries of their old instances,
+    /// kept loaded until the old instance is purged
+    old_libraries: Arc<RwLock<HashMap<String, NifLibraryRef>>>,
     /// Map of function pointers to function metadata
     functions: Arc<RwLock<HashMap<NifFunctionPtr, NifFunction>>>,
 }
@@ -325,6 +398,7 @@ impl NifRegistry {
     fn new() -> Self {
         Self {
             libraries: Arc::new(RwLock::new(HashMap::new())),
+            old_libraries: Arc::new(RwLock::new(HashMap::new())),
             functions: Arc::new(RwLock::new(HashMap::new())),
         }
     }
@@ -392,6 +466,18 @@ impl NifRegistry {
         libraries.get(module_name).cloned()
     }
 
+    /// Get the NIF library of a module's old instance
+    ///
+    /// # Arguments
+    /// * `module_name` - Module name
+    ///
+    /// # Returns
+    /// Reference to NIF library if the old instance has one, None otherwise
+    pub fn get_old_library(&self, module_name: &str) -> Option<NifLibraryRef> {
+        let old_libraries = self.old_libraries.read().unwrap();
+        old_libraries.get(module_name).cloned()
+    }
+
     /// Register a NIF function
     ///
     /// # Arguments
@@ -412,6 +498,23 @@ impl NifRegistry {
         let functions = self.functions.read().unwrap();
         functions.get(&pointer).cloned()
     }
+
+    /// Remove the function metadata of an unloaded library
+    ///
+    /// Functions at the same address in the module's current library, which
+    /// a reload of the same library file shares, are kept.
+    fn unregister_functions(&self, library: &NifLibrary) {
+        let current = self.get_library(library.module_name());
+        let mut functions = self.functions.write().unwrap();
+        for pointer in library.get_all_functions() {
+            let shared = current
+                .as_ref()
+                .is_some_and(|current| current.get_all_functions().contains(&pointer));
+            if !shared {
+                functions.remove(&pointer);
+            }
+        }
+    }
 }
 
 /// NIF loader operations
@@ -449,6 +552,25 @@ impl NifLoader {
     pub fn load_nif_library(
         path: &Path,
         module_name: &str,
+    ) -> Result<NifLibraryRef, NifLoadError> {
+        Self::load_nif_library_with_info(path, module_name, 0)
+    }
+
+    /// Load a NIF library from a file path, passing a load info term to its
+    /// `load` or `upgrade` callback (`erlang:load_nif/2`)
+    ///
+    /// # Arguments
+    /// * `path` - Path to the library file
+    /// * `module_name` - Module name to associate with this library
+    /// * `load_info` - Load info term passed to the callback
+    ///
+    /// # Errors
+    /// As [`load_nif_library`](Self::load_nif_library) and
+    /// [`install_nif_library`](Self::install_nif_library)
+    pub fn load_nif_library_with_info(
+        path: &Path,
+        module_name: &str,
+        load_info: u64,
     ) -> Result<NifLibraryRef, NifLoadError> {
         // Check if library file exists
         if !path.exists() {
@@ -468,19 +590,132 @@ impl NifLoader {
         // For now, we'll create an empty function map.
         let functions = Self::discover_nif_functions(&library, module_name)?;
 
+        let lifecycle = Self::discover_lifecycle(&library);
+
         // Create NIF library instance
-        let nif_library = Arc::new(NifLibrary::new(
-            library,
-            module_name.to_string(),
-            path.to_path_buf(),
-            functions,
-        ));
+        let nif_library = Arc::new(
+            NifLibrary::new(library, module_name.to_string(), path.to_path_buf(), functions)
+                .with_lifecycle(lifecycle),
+        );
+
+        Self::install_nif_library(nif_library, load_info)
+    }
 
-        // Register in global registry
+    /// Make a NIF library the current library of its module
+    ///
+    /// If the module's old instance has a NIF library, left there by
+    /// [`retire_nif_library`](Self::retire_nif_library) when the module was
+    /// reloaded, this is an upgrade: the new library's `upgrade` callback is
+    /// called with the old library's private data, and the old library stays
+    /// loaded until [`purge_old_nif_library`](Self::purge_old_nif_library).
+    /// Otherwise the `load` callback is called.
+    ///
+    /// # Arguments
+    /// * `library` - Library to install
+    /// * `load_info` - Load info term passed to the callback
+    ///
+    /// # Errors
+    /// - `NotAllowed`: The code access policy refuses NIF libraries for the module
+    /// - `ModuleAlreadyLoaded`: The current module instance already has a NIF library
+    /// - `UpgradeNotSupported`: The old instance has a NIF library and the new
+    ///   library has no `upgrade` callback
+    /// - `CallbackFailed`: The `load` or `upgrade` callback failed
+    pub fn install_nif_library(library: NifLibraryRef, load_info: u64) -> Result<NifLibraryRef, NifLoadError> {
+        let module_name = library.module_name().to_string();
+        get_global_code_permissions()
+            .check_module_access(&module_name, CodeOperation::LoadNif, None)
+            .map_err(|_| NifLoadError::NotAllowed(module_name.clone()))?;
         let registry = NifRegistry::get_instance();
-        registry.register_library(module_name.to_string(), nif_library.clone())?;
+        let mut libraries = registry.libraries.write().unwrap();
+        if libraries.contains_key(&module_name) {
+            return Err(NifLoadError::ModuleAlreadyLoaded(module_name));
+        }
+        let old = registry.get_old_library(&module_name);
+
+        let lifecycle = library.lifecycle();
+        let result = match &old {
+            Some(old) => {
+                let upgrade = lifecycle
+                    .upgrade
+                    .ok_or_else(|| NifLoadError::UpgradeNotSupported(module_name.clone()))?;
+                let mut priv_data = library.priv_data.lock().unwrap();
+                let mut old_priv_data = old.priv_data.lock().unwrap();
+                unsafe { upgrade(&mut priv_data, &mut old_priv_data, load_info) }
+            }
+            None => match lifecycle.load {
+                Some(load) => unsafe { load(&mut library.priv_data.lock().unwrap(), load_info) },
+                None => 0,
+            },
+        };
+        if result != 0 {
+            let callback = if old.is_some() { "upgrade" } else { "load" };
+            return Err(NifLoadError::CallbackFailed(format!(
+                "{} callback of {} returned {}",
+                callback, module_name, result
+            )));
+        }
 
-        Ok(nif_library)
+        libraries.insert(module_name, library.clone());
+        Ok(library)
+    }
+
+    /// Move a module's NIF library to its old instance
+    ///
+    /// Called when a new instance of the module is loaded; the library stays
+    /// loaded until the old instance is purged.
+    ///
+    /// # Returns
+    /// `true` if the module had a NIF library
+    ///
+    /// # Errors
+    /// - `OldCodeNotPurged`: The old instance still has a NIF library
+    pub fn retire_nif_library(module_name: &str) -> Result<bool, NifLoadError> {
+        let registry = NifRegistry::get_instance();
+        let mut libraries = registry.libraries.write().unwrap();
+        let mut old_libraries = registry.old_libraries.write().unwrap();
+        if !libraries.contains_key(module_name) {
+            return Ok(false);
+        }
+        if old_libraries.contains_key(module_name) {
+            return Err(NifLoadError::OldCodeNotPurged(module_name.to_string()));
+        }
+        let library = libraries.remove(module_name).expect("checked above");
+        old_libraries.insert(module_name.to_string(), library);
+        Ok(true)
+    }
+
+    /// Unload the NIF library of a module's old instance
+    ///
+    /// Called when the old instance is purged. The library's `unload`
+    /// callback is called and its functions are unregistered; the library
+    /// itself is unloaded when the last reference to it is dropped.
+    ///
+    /// # Errors
+    /// - `LibraryNotFound`: The old instance has no NIF library
+    pub fn purge_old_nif_library(module_name: &str) -> Result<(), NifUnloadError> {
+        let registry = NifRegistry::get_instance();
+        let library = registry
+            .old_libraries
+            .write()
+            .unwrap()
+            .remove(module_name)
+            .ok_or_else(|| NifUnloadError::LibraryNotFound(module_name.to_string()))?;
+        if let Some(unload) = library.lifecycle().unload {
+            unsafe { unload(&mut library.priv_data.lock().unwrap()) };
+        }
+        registry.unregister_functions(&library);
+        Ok(())
+    }
+
+    /// Look up the optional lifecycle callbacks of a library
+    fn discover_lifecycle(library: &Library) -> NifLifecycle {
+        unsafe {
+            NifLifecycle {
+                load: library.get::<NifLoadFn>(b"nif_load\0").ok().map(|symbol| *symbol),
+                upgrade: library.get::<NifUpgradeFn>(b"nif_upgrade\0").ok().map(|symbol| *symbol),
+                unload: library.get::<NifUnloadFn>(b"nif_unload\0").ok().map(|symbol| *symbol),
+            }
+        }
     }
 
     /// Discover NIF functions in a loaded library
@@ -804,6 +1039,15 @@ pub enum NifLoadError {
     EntryPointNotFound(String),
     /// Module already has a NIF library loaded
     ModuleAlreadyLoaded(String),
+    /// The code access policy does not allow NIF libraries for the module
+    NotAllowed(String),
+    /// The old module instance still has a NIF library that must be purged first
+    OldCodeNotPurged(String),
+    /// The old module instance has a NIF library and the new library has no
+    /// `upgrade` callback
+    UpgradeNotSupported(String),
+    /// The library's `load` or `upgrade` callback failed
+    CallbackFailed(String),
 }
 
 impl std::fmt::Display for NifLoadError {
@@ -822,6 +1066,18 @@ impl std::fmt::Display for NifLoadError {
             NifLoadError::ModuleAlreadyLoaded(module) => {
                 write!(f, "Module already has NIF library loaded: {}", module)
             }
+            NifLoadError::NotAllowed(module) => {
+                write!(f, "NIF library not allowed for module: {}", module)
+            }
+            NifLoadError::OldCodeNotPurged(module) => {
+                write!(f, "Old code of module still has NIF library loaded: {}", module)
+            }
+            NifLoadError::UpgradeNotSupported(module) => {
+                write!(f, "NIF library does not support upgrade: {}", module)
+            }
+            NifLoadError::CallbackFailed(msg) => {
+                write!(f, "NIF library callback failed: {}", msg)
+            }
         }
     }
 }
@@ -3424,5 +3680,133 @@ mod tests {
         assert_eq!(count, 0);
         assert_eq!(library.ref_count(), 0);
     }
-}
 
+    unsafe extern "C" fn lifecycle_test_load(priv_data: &mut NifPrivData, load_info: u64) -> i32 {
+        *priv_data = Some(Box::new(vec![load_info]));
+        0
+    }
+
+    unsafe extern "C" fn lifecycle_test_upgrade(
+        priv_data: &mut NifPrivData,
+        old_priv_data: &mut NifPrivData,
+        load_info: u64,
+    ) -> i32 {
+        let mut history = old_priv_data
+            .as_ref()
+            .and_then(|data| data.downcast_ref::<Vec<u64>>())
+            .cloned()
+            .unwrap_or_default();
+        history.push(load_info);
+        *priv_data = Some(Box::new(history));
+        0
+    }
+
+    unsafe extern "C" fn lifecycle_test_failing_upgrade(
+        _priv_data: &mut NifPrivData,
+        _old_priv_data: &mut NifPrivData,
+        _load_info: u64,
+    ) -> i32 {
+        1
+    }
+
+    unsafe extern "C" fn lifecycle_test_unload(priv_data: &mut NifPrivData) {
+        *priv_data = None;
+    }
+
+    fn lifecycle_test_library(module_name: &str, lifecycle: NifLifecycle) -> NifLibraryRef {
+        Arc::new(
+            NifLibrary::new_for_testing(module_name.to_string(), PathBuf::from("/test/path"), HashMap::new())
+                .with_lifecycle(lifecycle),
+        )
+    }
+
+    fn lifecycle_test_history(library: &NifLibrary) -> Option<Vec<u64>> {
+        library.with_priv_data(|data| data.as_ref().and_then(|data| data.downcast_ref::<Vec<u64>>()).cloned())
+    }
+
+    #[test]
+    fn test_nif_loader_upgrade_on_reload_and_unload_on_purge() {
+        let module = "lifecycle_upgrade_test";
+        let lifecycle = NifLifecycle {
+            load: Some(lifecycle_test_load),
+            upgrade: Some(lifecycle_test_upgrade),
+            unload: Some(lifecycle_test_unload),
+        };
+        let registry = NifRegistry::get_instance();
+
+        let v1 = NifLoader::install_nif_library(lifecycle_test_library(module, lifecycle), 1).unwrap();
+        assert_eq!(lifecycle_test_history(&v1), Some(vec![1]));
+        assert!(matches!(
+            NifLoader::install_nif_library(lifecycle_test_library(module, lifecycle), 2),
+            Err(NifLoadError::ModuleAlreadyLoaded(_))
+        ));
+
+        // Reloading the module keeps the old library loaded for the old instance
+        assert_eq!(NifLoader::retire_nif_library(module), Ok(true));
+        assert!(registry.get_library(module).is_none());
+        let v2 = NifLoader::install_nif_library(lifecycle_test_library(module, lifecycle), 2).unwrap();
+        assert_eq!(lifecycle_test_history(&v2), Some(vec![1, 2]));
+        assert!(Arc::ptr_eq(&registry.get_old_library(module).unwrap(), &v1));
+        assert!(Arc::ptr_eq(&registry.get_library(module).unwrap(), &v2));
+
+        // The next reload must wait for the old instance to be purged
+        assert_eq!(
+            NifLoader::retire_nif_library(module),
+            Err(NifLoadError::OldCodeNotPurged(module.to_string()))
+        );
+        NifLoader::purge_old_nif_library(module).unwrap();
+        assert_eq!(lifecycle_test_history(&v1), None);
+        assert!(registry.get_old_library(module).is_none());
+        assert_eq!(
+            NifLoader::purge_old_nif_library(module),
+            Err(NifUnloadError::LibraryNotFound(module.to_string()))
+        );
+        assert_eq!(lifecycle_test_history(&v2), Some(vec![1, 2]));
+
+        let _ = NifLoader::unload_nif_library(&v2);
+    }
+
+    #[test]
+    fn test_nif_loader_upgrade_not_supported_or_failing() {
+        let module = "lifecycle_no_upgrade_test";
+        let registry = NifRegistry::get_instance();
+        let load_only = NifLifecycle { load: Some(lifecycle_test_load), ..NifLifecycle::default() };
+
+        NifLoader::install_nif_library(lifecycle_test_library(module, load_only), 1).unwrap();
+        assert_eq!(NifLoader::retire_nif_library(module), Ok(true));
+        assert_eq!(
+            NifLoader::install_nif_library(lifecycle_test_library(module, load_only), 2).unwrap_err(),
+            NifLoadError::UpgradeNotSupported(module.to_string())
+        );
+        let failing = NifLifecycle { upgrade: Some(lifecycle_test_failing_upgrade), ..NifLifecycle::default() };
+        assert!(matches!(
+            NifLoader::install_nif_library(lifecycle_test_library(module, failing), 2),
+            Err(NifLoadError::CallbackFailed(_))
+        ));
+        assert!(registry.get_library(module).is_none());
+
+        NifLoader::purge_old_nif_library(module).unwrap();
+        assert_eq!(NifLoader::retire_nif_library(module), Ok(false));
+    }
+
+    struct DenyNifPolicy;
+
+    impl code_management_code_loading::CodeAccessPolicy for DenyNifPolicy {
+        fn allow(&self, module: &str, operation: CodeOperation, _requester: Option<u64>) -> bool {
+            !(module == "policy_denied_nif_test" && operation == CodeOperation::LoadNif)
+        }
+    }
+
+    #[test]
+    fn test_nif_loader_install_refused_by_access_policy() {
+        let module = "policy_denied_nif_test";
+        let permissions = get_global_code_permissions();
+        permissions.set_access_policy(Arc::new(DenyNifPolicy));
+        assert_eq!(
+            NifLoader::install_nif_library(lifecycle_test_library(module, NifLifecycle::default()), 1).unwrap_err(),
+            NifLoadError::NotAllowed(module.to_string())
+        );
+        assert!(NifRegistry::get_instance().get_library(module).is_none());
+        permissions.clear_access_policy();
+    }
+}
@@ -108,6 +108,27 @@ fn test_file_nif_error_cases() {
     let non_existent = PathBuf::from("/nonexistent/path/file.txt");
     let result = FileNif::open(&non_existent);
     assert!(result.is_err());
+    assert_eq!(result.err().unwrap().reason(), "enoent");
+}
+
+#[test]
+fn test_file_nif_directory_and_info() {
+    use infrastructure_time_management::time_unit::TimeUnit;
+
+    let dir = std::env::temp_dir().join(format!("test_nif_file_integration_dir_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    FileNif::make_dir(&dir).unwrap();
+    let mut handle = FileNif::create(dir.join("data")).unwrap();
+    handle.write(b"abc").unwrap();
+
+    assert_eq!(FileNif::list_dir(&dir).unwrap(), vec![std::ffi::OsString::from("data")]);
+    let info: FileInfo = FileNif::read_info(dir.join("data"), TimeUnit::Millisecond).unwrap();
+    assert_eq!(info.size, 3);
+    assert_eq!(info.file_type.atom(), "regular");
+
+    FileNif::rename(dir.join("data"), dir.join("renamed")).unwrap();
+    FileNif::delete(dir.join("renamed")).unwrap();
+    FileNif::del_dir(&dir).unwrap();
 }
 
 #[test]
@@ -381,3 +402,60 @@ fn test_buffer_nif_error_eq() {
     assert_ne!(BufferNifError::BadArg, BufferNifError::NotImplemented);
 }
 
+
+/// A read of a FIFO without a writer blocks in `open`, standing in for a
+/// slow network file system
+#[cfg(unix)]
+#[test]
+fn test_blocked_file_operation_does_not_stall_normal_scheduler() {
+    use infrastructure_nif_api::DirtySchedulers;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use std::time::{Duration, Instant};
+
+    let schedulers = DirtySchedulers::new(0, 2);
+    let fifo = std::env::temp_dir().join(format!("test_nif_file_fifo_{}", std::process::id()));
+    let _ = fs::remove_file(&fifo);
+    let c_path = std::ffi::CString::new(fifo.to_str().unwrap()).unwrap();
+    assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+
+    let submitted = Instant::now();
+    let blocked = AsyncFile::open(&schedulers, fifo.clone(), OpenOptions::new().read(true).clone());
+    assert!(submitted.elapsed() < Duration::from_millis(100));
+
+    // The normal scheduler keeps running while the open is blocked
+    let mut last_tick = Instant::now();
+    let mut max_gap = Duration::ZERO;
+    let mut ticks = 0;
+    while submitted.elapsed() < Duration::from_millis(100) {
+        assert!(blocked.try_wait().is_none());
+        max_gap = max_gap.max(last_tick.elapsed());
+        last_tick = Instant::now();
+        ticks += 1;
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert!(ticks > 10);
+    assert!(max_gap < Duration::from_millis(50), "normal scheduler stalled for {:?}", max_gap);
+
+    // Other files are served by the remaining dirty I/O scheduler
+    let path = std::env::temp_dir().join(format!("test_nif_file_unblocked_{}", std::process::id()));
+    let mut options = OpenOptions::new();
+    options.read(true).write(true).create(true).truncate(true);
+    let started = Instant::now();
+    let file = AsyncFile::with_schedulers(AsyncFile::open(&schedulers, path.clone(), options).wait().unwrap(), &schedulers);
+    file.pwrite(0, b"fast".to_vec()).wait().unwrap();
+    assert_eq!(file.pread(0, 4).wait().unwrap(), Some(b"fast".to_vec()));
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert!(blocked.try_wait().is_none());
+
+    // Unblock the FIFO and finish the read
+    let mut writer = OpenOptions::new().write(true).open(&fifo).unwrap();
+    writer.write_all(b"slow").unwrap();
+    drop(writer);
+    let fifo_file = AsyncFile::with_schedulers(blocked.wait().unwrap(), &schedulers);
+    assert_eq!(fifo_file.read(16).wait().unwrap(), Some(b"slow".to_vec()));
+    assert_eq!(fifo_file.read(16).wait().unwrap(), None);
+
+    let _ = fs::remove_file(&fifo);
+    let _ = fs::remove_file(&path);
+}
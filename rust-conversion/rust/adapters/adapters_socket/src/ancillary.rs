@@ -0,0 +1,370 @@
+//! Ancillary Data Module
+//!
+//! Provides `recvmsg` with access to control messages (ancillary data), as in
+//! the socket NIF's `recvmsg`: the datagram is returned together with the
+//! control messages the kernel attached to it, such as the packet info
+//! (`IP_PKTINFO`/`IPV6_PKTINFO`), the type of service (`IP_TOS`/`IPV6_TCLASS`)
+//! and the receive timestamp (`SO_TIMESTAMP`).
+//!
+//! The kernel only attaches a control message once the matching receive
+//! option is enabled with [`Socket::set_recv_pktinfo`],
+//! [`Socket::set_recv_tos`] or [`Socket::set_recv_timestamp`]. Decoding of the
+//! known control messages is implemented for Linux and Android; other control
+//! messages, and all of them on other platforms, are returned undecoded.
+
+use std::mem::MaybeUninit;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::SystemTime;
+
+use socket2::{MaybeUninitSlice, MsgHdrMut, SockAddr};
+
+use crate::socket::{AddressFamily, Socket, SocketError};
+
+/// Size of the buffer receiving control messages
+const CONTROL_BUFFER_SIZE: usize = 256;
+
+/// Control message received with a datagram
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlMessage {
+    /// Packet info (`IP_PKTINFO` or `IPV6_PKTINFO`)
+    PacketInfo {
+        /// Index of the interface the datagram arrived on
+        interface: u32,
+        /// Destination address of the datagram
+        destination: IpAddr,
+    },
+    /// Type of service of an IPv4 datagram (`IP_TOS`)
+    Tos(u8),
+    /// Traffic class of an IPv6 datagram (`IPV6_TCLASS`)
+    TrafficClass(u32),
+    /// Time the datagram was received (`SO_TIMESTAMP`)
+    Timestamp(SystemTime),
+    /// Control message that is not decoded
+    Other {
+        /// Protocol level (`cmsg_level`)
+        level: i32,
+        /// Protocol-specific type (`cmsg_type`)
+        kind: i32,
+        /// Raw data
+        data: Vec<u8>,
+    },
+}
+
+/// Datagram received with `recvmsg`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceivedMessage {
+    /// Number of bytes received into the buffer
+    pub len: usize,
+    /// Sender address
+    pub source: SocketAddr,
+    /// Control messages attached to the datagram
+    pub control: Vec<ControlMessage>,
+    /// Whether the datagram was larger than the buffer and got truncated
+    pub truncated: bool,
+}
+
+impl Socket {
+    /// Receive a datagram with its control messages
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - Buffer to receive the datagram into
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ReceivedMessage)` - Received datagram
+    /// * `Err(SocketError)` - Error receiving
+    pub fn recv_msg(&self, buf: &mut [u8]) -> Result<ReceivedMessage, SocketError> {
+        // The address buffer must be large enough for the socket's family
+        let unspecified: IpAddr = match self.family() {
+            AddressFamily::Ipv4 => Ipv4Addr::UNSPECIFIED.into(),
+            AddressFamily::Ipv6 => Ipv6Addr::UNSPECIFIED.into(),
+        };
+        let mut source = SockAddr::from(SocketAddr::new(unspecified, 0));
+
+        // Convert &mut [u8] to &mut [MaybeUninit<u8>]
+        // Safety: u8 and MaybeUninit<u8> have the same layout, and recvmsg
+        // only writes initialized bytes into the buffers
+        let uninit_buf: &mut [MaybeUninit<u8>] = unsafe {
+            std::slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut MaybeUninit<u8>, buf.len())
+        };
+        let mut control = [MaybeUninit::<u8>::uninit(); CONTROL_BUFFER_SIZE];
+        let mut buffers = [MaybeUninitSlice::new(uninit_buf)];
+        let (len, truncated, control_len) = {
+            let mut msg = MsgHdrMut::new()
+                .with_addr(&mut source)
+                .with_buffers(&mut buffers)
+                .with_control(&mut control);
+            let len = self.inner().recvmsg(&mut msg, 0).map_err(SocketError::from)?;
+            (len, msg.flags().is_truncated(), msg.control_len())
+        };
+
+        // Safety: recvmsg initialized the first `control_len` bytes
+        let control_bytes = unsafe {
+            std::slice::from_raw_parts(control.as_ptr() as *const u8, control_len)
+        };
+        Ok(ReceivedMessage {
+            len,
+            source: source.as_socket().ok_or(SocketError::InvalidAddress)?,
+            control: parse_control_messages(control_bytes),
+            truncated,
+        })
+    }
+
+    /// Receive packet info control messages (`IP_PKTINFO` or
+    /// `IPV6_RECVPKTINFO`)
+    ///
+    /// Available on Linux and Android.
+    pub fn set_recv_pktinfo(&self, enable: bool) -> Result<(), SocketError> {
+        cmsg::set_recv_pktinfo(self, enable)
+    }
+
+    /// Receive type of service control messages (`IP_RECVTOS` or
+    /// `IPV6_RECVTCLASS`)
+    ///
+    /// Available on Linux and Android.
+    pub fn set_recv_tos(&self, enable: bool) -> Result<(), SocketError> {
+        cmsg::set_recv_tos(self, enable)
+    }
+
+    /// Receive timestamp control messages (`SO_TIMESTAMP`)
+    ///
+    /// Available on Linux and Android.
+    pub fn set_recv_timestamp(&self, enable: bool) -> Result<(), SocketError> {
+        cmsg::set_recv_timestamp(self, enable)
+    }
+}
+
+/// Split a control buffer into control messages
+fn parse_control_messages(mut bytes: &[u8]) -> Vec<ControlMessage> {
+    let mut messages = Vec::new();
+    while bytes.len() >= cmsg::HEADER_SIZE {
+        let (len, level, kind) = cmsg::read_header(bytes);
+        if len < cmsg::HEADER_SIZE || len > bytes.len() {
+            break;
+        }
+        messages.push(cmsg::decode(level, kind, &bytes[cmsg::HEADER_SIZE..len]));
+        bytes = &bytes[cmsg::align(len).min(bytes.len())..];
+    }
+    messages
+}
+
+/// Control message layout and decoding for Linux and Android
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod cmsg {
+    use super::*;
+    use std::mem::size_of;
+    use std::time::Duration;
+
+    /// Size of `struct cmsghdr`, after which the data starts
+    pub(super) const HEADER_SIZE: usize = align(size_of::<libc::cmsghdr>());
+
+    /// Round `len` up to the alignment of control messages (`CMSG_ALIGN`)
+    pub(super) const fn align(len: usize) -> usize {
+        let alignment = size_of::<usize>();
+        (len + alignment - 1) & !(alignment - 1)
+    }
+
+    /// Read `cmsg_len`, `cmsg_level` and `cmsg_type`
+    pub(super) fn read_header(bytes: &[u8]) -> (usize, i32, i32) {
+        let len_size = size_of::<usize>();
+        let len = usize::from_ne_bytes(bytes[..len_size].try_into().unwrap());
+        let level = i32::from_ne_bytes(bytes[len_size..len_size + 4].try_into().unwrap());
+        let kind = i32::from_ne_bytes(bytes[len_size + 4..len_size + 8].try_into().unwrap());
+        (len, level, kind)
+    }
+
+    pub(super) fn decode(level: i32, kind: i32, data: &[u8]) -> ControlMessage {
+        match (level, kind) {
+            // struct in_pktinfo { int ipi_ifindex; in_addr ipi_spec_dst; in_addr ipi_addr; }
+            (libc::IPPROTO_IP, libc::IP_PKTINFO) if data.len() >= 12 => ControlMessage::PacketInfo {
+                interface: u32::from_ne_bytes(data[0..4].try_into().unwrap()),
+                destination: IpAddr::from(<[u8; 4]>::try_from(&data[8..12]).unwrap()),
+            },
+            // struct in6_pktinfo { in6_addr ipi6_addr; unsigned ipi6_ifindex; }
+            (libc::IPPROTO_IPV6, libc::IPV6_PKTINFO) if data.len() >= 20 => ControlMessage::PacketInfo {
+                interface: u32::from_ne_bytes(data[16..20].try_into().unwrap()),
+                destination: IpAddr::from(<[u8; 16]>::try_from(&data[0..16]).unwrap()),
+            },
+            (libc::IPPROTO_IP, libc::IP_TOS) if !data.is_empty() => ControlMessage::Tos(data[0]),
+            (libc::IPPROTO_IPV6, libc::IPV6_TCLASS) if data.len() >= 4 => {
+                ControlMessage::TrafficClass(u32::from_ne_bytes(data[0..4].try_into().unwrap()))
+            }
+            // struct timeval { time_t tv_sec; suseconds_t tv_usec; }
+            (libc::SOL_SOCKET, libc::SCM_TIMESTAMP) if data.len() >= size_of::<libc::timeval>() => {
+                let field = size_of::<libc::time_t>();
+                let seconds = libc::time_t::from_ne_bytes(data[..field].try_into().unwrap());
+                let micros = libc::suseconds_t::from_ne_bytes(data[field..2 * field].try_into().unwrap());
+                let since_epoch = Duration::from_secs(seconds as u64) + Duration::from_micros(micros as u64);
+                ControlMessage::Timestamp(SystemTime::UNIX_EPOCH + since_epoch)
+            }
+            _ => ControlMessage::Other {
+                level,
+                kind,
+                data: data.to_vec(),
+            },
+        }
+    }
+
+    pub(super) fn set_recv_pktinfo(socket: &Socket, enable: bool) -> Result<(), SocketError> {
+        match socket.family() {
+            AddressFamily::Ipv4 => set_bool(socket, libc::IPPROTO_IP, libc::IP_PKTINFO, enable),
+            AddressFamily::Ipv6 => set_bool(socket, libc::IPPROTO_IPV6, libc::IPV6_RECVPKTINFO, enable),
+        }
+    }
+
+    pub(super) fn set_recv_tos(socket: &Socket, enable: bool) -> Result<(), SocketError> {
+        match socket.family() {
+            AddressFamily::Ipv4 => set_bool(socket, libc::IPPROTO_IP, libc::IP_RECVTOS, enable),
+            AddressFamily::Ipv6 => set_bool(socket, libc::IPPROTO_IPV6, libc::IPV6_RECVTCLASS, enable),
+        }
+    }
+
+    pub(super) fn set_recv_timestamp(socket: &Socket, enable: bool) -> Result<(), SocketError> {
+        set_bool(socket, libc::SOL_SOCKET, libc::SO_TIMESTAMP, enable)
+    }
+
+    fn set_bool(socket: &Socket, level: i32, name: i32, enable: bool) -> Result<(), SocketError> {
+        let value = libc::c_int::from(enable);
+        // Safety: `value` is a valid c_int for the duration of the call and
+        // its size is passed along
+        let result = unsafe {
+            libc::setsockopt(
+                socket.as_raw_fd(),
+                level,
+                name,
+                &value as *const libc::c_int as *const libc::c_void,
+                size_of::<libc::c_int>() as libc::socklen_t,
+            )
+        };
+        if result < 0 {
+            return Err(SocketError::from(std::io::Error::last_os_error()));
+        }
+        Ok(())
+    }
+}
+
+/// Control message layout on other platforms; messages are not decoded
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+mod cmsg {
+    use super::*;
+    use std::mem::size_of;
+
+    pub(super) const HEADER_SIZE: usize = align(size_of::<u32>() * 3);
+
+    pub(super) const fn align(len: usize) -> usize {
+        let alignment = size_of::<u32>();
+        (len + alignment - 1) & !(alignment - 1)
+    }
+
+    /// Read `cmsg_len`, `cmsg_level` and `cmsg_type` (BSD layout)
+    pub(super) fn read_header(bytes: &[u8]) -> (usize, i32, i32) {
+        let len = u32::from_ne_bytes(bytes[0..4].try_into().unwrap()) as usize;
+        let level = i32::from_ne_bytes(bytes[4..8].try_into().unwrap());
+        let kind = i32::from_ne_bytes(bytes[8..12].try_into().unwrap());
+        (len, level, kind)
+    }
+
+    pub(super) fn decode(level: i32, kind: i32, data: &[u8]) -> ControlMessage {
+        ControlMessage::Other {
+            level,
+            kind,
+            data: data.to_vec(),
+        }
+    }
+
+    pub(super) fn set_recv_pktinfo(_socket: &Socket, _enable: bool) -> Result<(), SocketError> {
+        Err(SocketError::option_not_available("IP_PKTINFO"))
+    }
+
+    pub(super) fn set_recv_tos(_socket: &Socket, _enable: bool) -> Result<(), SocketError> {
+        Err(SocketError::option_not_available("IP_RECVTOS"))
+    }
+
+    pub(super) fn set_recv_timestamp(_socket: &Socket, _enable: bool) -> Result<(), SocketError> {
+        Err(SocketError::option_not_available("SO_TIMESTAMP"))
+    }
+}
+
+#[cfg(all(test, any(target_os = "linux", target_os = "android")))]
+mod tests {
+    use super::*;
+    use crate::socket::{Protocol, SocketType};
+    use std::thread;
+    use std::time::Duration;
+
+    fn udp_pair() -> (Socket, Socket, SocketAddr) {
+        let receiver = Socket::new(AddressFamily::Ipv4, SocketType::Datagram, Protocol::Udp).unwrap();
+        receiver.bind(&SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0)).unwrap();
+        let sender = Socket::new(AddressFamily::Ipv4, SocketType::Datagram, Protocol::Udp).unwrap();
+        sender.bind(&SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0)).unwrap();
+        let addr = receiver.local_addr().unwrap();
+        (receiver, sender, addr)
+    }
+
+    fn recv_msg_retry(socket: &Socket, buf: &mut [u8]) -> ReceivedMessage {
+        for _ in 0..100 {
+            match socket.recv_msg(buf) {
+                Ok(message) => return message,
+                Err(SocketError::WouldBlock) => thread::sleep(Duration::from_millis(10)),
+                Err(e) => panic!("recv_msg failed: {:?}", e),
+            }
+        }
+        panic!("no datagram received");
+    }
+
+    #[test]
+    fn test_recv_msg_with_control_messages() {
+        let (receiver, sender, addr) = udp_pair();
+        receiver.set_recv_pktinfo(true).unwrap();
+        receiver.set_recv_tos(true).unwrap();
+        receiver.set_recv_timestamp(true).unwrap();
+        sender.set_tos(0x20).unwrap();
+        let before = SystemTime::now() - Duration::from_secs(1);
+        sender.inner().send_to(b"ping", &SockAddr::from(addr)).unwrap();
+
+        let mut buf = [0u8; 16];
+        let message = recv_msg_retry(&receiver, &mut buf);
+        assert_eq!(&buf[..message.len], b"ping");
+        assert_eq!(message.source, sender.local_addr().unwrap());
+        assert!(!message.truncated);
+        assert!(message.control.iter().any(|c| matches!(
+            c,
+            ControlMessage::PacketInfo { destination, .. } if *destination == IpAddr::from(Ipv4Addr::LOCALHOST)
+        )));
+        assert!(message.control.contains(&ControlMessage::Tos(0x20)));
+        assert!(message.control.iter().any(|c| matches!(c, ControlMessage::Timestamp(t) if *t > before)));
+    }
+
+    #[test]
+    fn test_recv_msg_truncated_without_control() {
+        let (receiver, sender, addr) = udp_pair();
+        sender.inner().send_to(b"a longer datagram", &SockAddr::from(addr)).unwrap();
+
+        let mut buf = [0u8; 4];
+        let message = recv_msg_retry(&receiver, &mut buf);
+        assert_eq!(message.len, 4);
+        assert_eq!(&buf, b"a lo");
+        assert!(message.truncated);
+        assert!(message.control.is_empty());
+    }
+
+    #[test]
+    fn test_parse_control_messages_keeps_unknown() {
+        let mut bytes = vec![0u8; cmsg::HEADER_SIZE + 8];
+        let len = cmsg::HEADER_SIZE + 3;
+        bytes[..std::mem::size_of::<usize>()].copy_from_slice(&len.to_ne_bytes());
+        let level_at = std::mem::size_of::<usize>();
+        bytes[level_at..level_at + 4].copy_from_slice(&99i32.to_ne_bytes());
+        bytes[level_at + 4..level_at + 8].copy_from_slice(&7i32.to_ne_bytes());
+        bytes[cmsg::HEADER_SIZE..len].copy_from_slice(&[1, 2, 3]);
+
+        assert_eq!(
+            parse_control_messages(&bytes),
+            vec![ControlMessage::Other { level: 99, kind: 7, data: vec![1, 2, 3] }]
+        );
+        // A header claiming more data than received ends parsing
+        bytes[..std::mem::size_of::<usize>()].copy_from_slice(&1000usize.to_ne_bytes());
+        assert!(parse_control_messages(&bytes).is_empty());
+    }
+}
@@ -8,9 +8,15 @@
 //!
 //! The `adapters_socket` crate provides:
 //! - **TCP sockets**: Stream-based reliable communication
-//! - **UDP sockets**: Datagram-based communication
+//! - **UDP sockets**: Datagram-based communication, multicast, broadcast and
+//!   ancillary data
+//! - **Local sockets**: Unix domain stream and datagram sockets, with peer
+//!   credentials and file descriptor passing
 //! - **Socket operations**: bind, listen, accept, connect, send, recv
+//! - **Vectored I/O**: writev/readv scatter-gather and `sendfile`
+//! - **Socket options**: typed getters and setters for the options used by `inet`
 //! - **Integration with NIF I/O**: Uses `adapters_nif_io` for I/O polling
+//! - **Asynchronous connect and accept**: Select-style readiness messages, as `socket.erl` expects
 //!
 //! ## Architecture
 //!
@@ -25,10 +31,20 @@
 //! - [`adapters_nif_io`](../adapters_nif_io/index.html): I/O polling infrastructure
 //! - [`adapters_nifs`](../adapters_nifs/index.html): NIF implementations
 
+pub mod ancillary;
+#[cfg(unix)]
+pub mod local;
+pub mod options;
+pub mod select;
 pub mod socket;
 pub mod tcp;
 pub mod udp;
+pub mod vectored;
 
 pub use socket::{Socket, SocketError, SocketType, AddressFamily, Protocol};
+pub use ancillary::{ControlMessage, ReceivedMessage};
+#[cfg(unix)]
+pub use local::{LocalAddress, LocalSocket, PeerCredentials};
+pub use select::{SelectInfo, SelectOutcome, SelectTag};
 pub use tcp::TcpSocket;
 pub use udp::UdpSocket;
@@ -0,0 +1,605 @@
+//! Local Socket Module
+//!
+//! Provides Unix domain (`AF_LOCAL`) stream and datagram sockets for local
+//! communication from user code, as `gen_tcp` and `gen_udp` offer with
+//! `{local, Path}` addresses. Addresses are either a filesystem path or, on
+//! Linux and Android, a name in the abstract namespace.
+//!
+//! On top of the usual socket operations, local sockets can report the
+//! credentials of the connected peer (`SO_PEERCRED`) and pass open file
+//! descriptors to the peer (`SCM_RIGHTS`).
+
+use std::io;
+use std::mem::size_of;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd};
+use std::path::{Path, PathBuf};
+
+use socket2::{Domain, SockAddr, Socket as Socket2};
+
+use crate::socket::{SocketError, SocketType};
+
+/// Address of a local socket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalAddress {
+    /// Filesystem path
+    Path(PathBuf),
+    /// Name in the abstract namespace, without the leading NUL byte
+    /// (Linux and Android only)
+    Abstract(Vec<u8>),
+    /// Address of an unbound socket
+    Unnamed,
+}
+
+impl LocalAddress {
+    /// Create a filesystem path address
+    pub fn path(path: impl AsRef<Path>) -> Self {
+        LocalAddress::Path(path.as_ref().to_path_buf())
+    }
+
+    /// Create an abstract namespace address
+    pub fn abstract_name(name: impl AsRef<[u8]>) -> Self {
+        LocalAddress::Abstract(name.as_ref().to_vec())
+    }
+
+    fn to_sock_addr(&self) -> Result<SockAddr, SocketError> {
+        match self {
+            LocalAddress::Path(path) => SockAddr::unix(path).map_err(|_| SocketError::InvalidAddress),
+            LocalAddress::Abstract(name) => {
+                if !cfg!(any(target_os = "linux", target_os = "android")) {
+                    return Err(SocketError::NotSupported);
+                }
+                // socket2 treats a path starting with NUL as an abstract name
+                let mut bytes = Vec::with_capacity(name.len() + 1);
+                bytes.push(0);
+                bytes.extend_from_slice(name);
+                SockAddr::unix(std::ffi::OsStr::from_bytes(&bytes)).map_err(|_| SocketError::InvalidAddress)
+            }
+            LocalAddress::Unnamed => Err(SocketError::InvalidAddress),
+        }
+    }
+
+    fn from_sock_addr(addr: &SockAddr) -> Result<Self, SocketError> {
+        if let Some(path) = addr.as_pathname() {
+            Ok(LocalAddress::Path(path.to_path_buf()))
+        } else if let Some(name) = addr.as_abstract_namespace() {
+            Ok(LocalAddress::Abstract(name.to_vec()))
+        } else if addr.is_unnamed() {
+            Ok(LocalAddress::Unnamed)
+        } else {
+            Err(SocketError::InvalidAddress)
+        }
+    }
+}
+
+/// Credentials of the process at the other end of a local socket
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeerCredentials {
+    /// Process ID, if the platform reports it
+    pub pid: Option<u32>,
+    /// Effective user ID
+    pub uid: u32,
+    /// Effective group ID
+    pub gid: u32,
+}
+
+/// Local (Unix domain) socket
+///
+/// Like [`Socket`](crate::Socket), the socket is created in non-blocking mode
+/// for integration with NIF I/O polling.
+pub struct LocalSocket {
+    inner: Socket2,
+    socket_type: SocketType,
+}
+
+impl LocalSocket {
+    /// Create a new local socket
+    ///
+    /// # Arguments
+    ///
+    /// * `socket_type` - Socket type (Stream or Datagram)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(LocalSocket)` - Created socket
+    /// * `Err(SocketError)` - Error creating socket
+    pub fn new(socket_type: SocketType) -> Result<Self, SocketError> {
+        let socket = Socket2::new(Domain::UNIX, socket_type.into(), None)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self { inner: socket, socket_type })
+    }
+
+    /// Create a pair of connected local sockets, as `socketpair(2)`
+    ///
+    /// # Arguments
+    ///
+    /// * `socket_type` - Socket type (Stream or Datagram)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((LocalSocket, LocalSocket))` - Both ends of the connection
+    /// * `Err(SocketError)` - Error creating the sockets
+    pub fn pair(socket_type: SocketType) -> Result<(Self, Self), SocketError> {
+        let (a, b) = Socket2::pair(Domain::UNIX, socket_type.into(), None)?;
+        a.set_nonblocking(true)?;
+        b.set_nonblocking(true)?;
+        Ok((
+            Self { inner: a, socket_type },
+            Self { inner: b, socket_type },
+        ))
+    }
+
+    /// Socket type
+    pub fn socket_type(&self) -> SocketType {
+        self.socket_type
+    }
+
+    /// Bind socket to a local address
+    ///
+    /// Binding to a path creates the socket file; it is not removed when the
+    /// socket is closed.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - Local address to bind to
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - Success
+    /// * `Err(SocketError)` - Error binding
+    pub fn bind(&self, addr: &LocalAddress) -> Result<(), SocketError> {
+        self.inner.bind(&addr.to_sock_addr()?).map_err(SocketError::from)
+    }
+
+    /// Listen for incoming connections (Stream only)
+    ///
+    /// # Arguments
+    ///
+    /// * `backlog` - Maximum number of pending connections
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - Success
+    /// * `Err(SocketError)` - Error listening
+    pub fn listen(&self, backlog: i32) -> Result<(), SocketError> {
+        self.require_stream()?;
+        self.inner.listen(backlog).map_err(SocketError::from)
+    }
+
+    /// Accept an incoming connection (Stream only)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((LocalSocket, LocalAddress))` - Connected socket and peer address
+    /// * `Err(SocketError)` - Error accepting
+    pub fn accept(&self) -> Result<(LocalSocket, LocalAddress), SocketError> {
+        self.require_stream()?;
+        let (socket, addr) = self.inner.accept()?;
+        socket.set_nonblocking(true)?;
+        Ok((
+            Self { inner: socket, socket_type: self.socket_type },
+            LocalAddress::from_sock_addr(&addr)?,
+        ))
+    }
+
+    /// Connect to a local address
+    ///
+    /// A datagram socket only records the default destination.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - Local address to connect to
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - Success
+    /// * `Err(SocketError)` - Error connecting
+    pub fn connect(&self, addr: &LocalAddress) -> Result<(), SocketError> {
+        self.inner.connect(&addr.to_sock_addr()?).map_err(SocketError::from)
+    }
+
+    /// Send data on a connected socket
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - Number of bytes sent
+    /// * `Err(SocketError)` - Error sending
+    pub fn send(&self, data: &[u8]) -> Result<usize, SocketError> {
+        self.inner.send(data).map_err(SocketError::from)
+    }
+
+    /// Receive data from a connected socket
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - Number of bytes received
+    /// * `Err(SocketError)` - Error receiving
+    pub fn recv(&self, buf: &mut [u8]) -> Result<usize, SocketError> {
+        io::Read::read(&mut &self.inner, buf).map_err(SocketError::from)
+    }
+
+    /// Send a datagram to a local address (Datagram only)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - Number of bytes sent
+    /// * `Err(SocketError)` - Error sending
+    pub fn send_to(&self, data: &[u8], addr: &LocalAddress) -> Result<usize, SocketError> {
+        self.require_datagram()?;
+        self.inner.send_to(data, &addr.to_sock_addr()?).map_err(SocketError::from)
+    }
+
+    /// Receive a datagram and the address of its sender (Datagram only)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((usize, LocalAddress))` - Number of bytes received and sender
+    /// * `Err(SocketError)` - Error receiving
+    pub fn recv_from(&self, buf: &mut [u8]) -> Result<(usize, LocalAddress), SocketError> {
+        self.require_datagram()?;
+        let (len, addr) = self.inner.recv_from(uninit(buf))?;
+        Ok((len, LocalAddress::from_sock_addr(&addr)?))
+    }
+
+    /// Local address of the socket
+    pub fn local_addr(&self) -> Result<LocalAddress, SocketError> {
+        LocalAddress::from_sock_addr(&self.inner.local_addr()?)
+    }
+
+    /// Address of the connected peer
+    pub fn peer_addr(&self) -> Result<LocalAddress, SocketError> {
+        LocalAddress::from_sock_addr(&self.inner.peer_addr()?)
+    }
+
+    /// Set blocking mode
+    pub fn set_nonblocking(&self, nonblocking: bool) -> Result<(), SocketError> {
+        self.inner.set_nonblocking(nonblocking).map_err(SocketError::from)
+    }
+
+    /// Credentials of the connected peer (`SO_PEERCRED`)
+    ///
+    /// The credentials are those the peer had when it connected, or when
+    /// the pair was created.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(PeerCredentials)` - Peer credentials
+    /// * `Err(SocketError)` - Not connected, or not available on this platform
+    pub fn peer_credentials(&self) -> Result<PeerCredentials, SocketError> {
+        credentials::peer_credentials(self.as_raw_fd())
+    }
+
+    /// Send data together with open file descriptors (`SCM_RIGHTS`)
+    ///
+    /// The descriptors stay open in this process; the peer receives
+    /// duplicates of them. At least one byte of data must be sent along.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Data to send
+    /// * `fds` - File descriptors to pass
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - Number of bytes sent
+    /// * `Err(SocketError)` - Error sending
+    pub fn send_with_fds(&self, data: &[u8], fds: &[BorrowedFd<'_>]) -> Result<usize, SocketError> {
+        if data.is_empty() {
+            return Err(SocketError::Other("data must not be empty".to_string()));
+        }
+        let fds_len = size_of::<RawFd>() * fds.len();
+        let mut control = ControlBuffer::new(fds_len);
+        let mut iov = libc::iovec {
+            iov_base: data.as_ptr() as *mut libc::c_void,
+            iov_len: data.len(),
+        };
+        // Safety: a zeroed msghdr is valid
+        let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        if !fds.is_empty() {
+            msg.msg_control = control.as_mut_ptr();
+            msg.msg_controllen = control.len() as _;
+            // Safety: the control buffer is aligned for cmsghdr and has room
+            // for one header followed by `fds_len` bytes
+            unsafe {
+                let header = libc::CMSG_FIRSTHDR(&msg);
+                (*header).cmsg_level = libc::SOL_SOCKET;
+                (*header).cmsg_type = libc::SCM_RIGHTS;
+                (*header).cmsg_len = libc::CMSG_LEN(fds_len as u32) as _;
+                let data = libc::CMSG_DATA(header) as *mut RawFd;
+                for (i, fd) in fds.iter().enumerate() {
+                    data.add(i).write_unaligned(fd.as_raw_fd());
+                }
+            }
+        }
+        // Safety: msg points to the iovec and control buffer, both live for
+        // the duration of the call
+        let sent = unsafe { libc::sendmsg(self.as_raw_fd(), &msg, 0) };
+        if sent < 0 {
+            return Err(SocketError::from(io::Error::last_os_error()));
+        }
+        Ok(sent as usize)
+    }
+
+    /// Receive data together with passed file descriptors (`SCM_RIGHTS`)
+    ///
+    /// Descriptors beyond `max_fds` are closed by the kernel. The received
+    /// descriptors are close-on-exec where the platform supports it.
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - Buffer to receive the data into
+    /// * `max_fds` - Maximum number of file descriptors to receive
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((usize, Vec<OwnedFd>))` - Number of bytes and received descriptors
+    /// * `Err(SocketError)` - Error receiving
+    pub fn recv_with_fds(&self, buf: &mut [u8], max_fds: usize) -> Result<(usize, Vec<OwnedFd>), SocketError> {
+        let mut control = ControlBuffer::new(size_of::<RawFd>() * max_fds);
+        let mut iov = libc::iovec {
+            iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+            iov_len: buf.len(),
+        };
+        // Safety: a zeroed msghdr is valid
+        let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        if max_fds > 0 {
+            msg.msg_control = control.as_mut_ptr();
+            msg.msg_controllen = control.len() as _;
+        }
+        // Safety: msg points to the iovec and control buffer, both live for
+        // the duration of the call
+        let received = unsafe { libc::recvmsg(self.as_raw_fd(), &mut msg, RECV_FLAGS) };
+        if received < 0 {
+            return Err(SocketError::from(io::Error::last_os_error()));
+        }
+
+        let mut fds = Vec::new();
+        // Safety: recvmsg filled in the control buffer and updated
+        // msg_controllen, so the CMSG macros stay within it
+        unsafe {
+            let mut header = libc::CMSG_FIRSTHDR(&msg);
+            while !header.is_null() {
+                if (*header).cmsg_level == libc::SOL_SOCKET && (*header).cmsg_type == libc::SCM_RIGHTS {
+                    let data = libc::CMSG_DATA(header) as *const RawFd;
+                    let data_len = (*header).cmsg_len as usize - (data as usize - header as usize);
+                    for i in 0..data_len / size_of::<RawFd>() {
+                        fds.push(OwnedFd::from_raw_fd(data.add(i).read_unaligned()));
+                    }
+                }
+                header = libc::CMSG_NXTHDR(&msg, header);
+            }
+        }
+        Ok((received as usize, fds))
+    }
+
+    fn require_stream(&self) -> Result<(), SocketError> {
+        match self.socket_type {
+            SocketType::Stream => Ok(()),
+            SocketType::Datagram => Err(SocketError::NotSupported),
+        }
+    }
+
+    fn require_datagram(&self) -> Result<(), SocketError> {
+        match self.socket_type {
+            SocketType::Datagram => Ok(()),
+            SocketType::Stream => Err(SocketError::NotSupported),
+        }
+    }
+}
+
+impl AsRawFd for LocalSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl AsFd for LocalSocket {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.inner.as_fd()
+    }
+}
+
+/// Flags for receiving passed file descriptors
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd", target_os = "dragonfly"))]
+const RECV_FLAGS: libc::c_int = libc::MSG_CMSG_CLOEXEC;
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd", target_os = "dragonfly")))]
+const RECV_FLAGS: libc::c_int = 0;
+
+/// Control message buffer aligned for `cmsghdr`
+struct ControlBuffer {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl ControlBuffer {
+    /// Buffer with room for one control message carrying `data_len` bytes
+    fn new(data_len: usize) -> Self {
+        // Safety: CMSG_SPACE only computes a size
+        let len = unsafe { libc::CMSG_SPACE(data_len as u32) } as usize;
+        Self {
+            words: vec![0; len.div_ceil(size_of::<u64>())],
+            len,
+        }
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut libc::c_void {
+        self.words.as_mut_ptr() as *mut libc::c_void
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// Convert `&mut [u8]` to `&mut [MaybeUninit<u8>]`
+fn uninit(buf: &mut [u8]) -> &mut [std::mem::MaybeUninit<u8>] {
+    // Safety: u8 and MaybeUninit<u8> have the same layout, and the socket
+    // only writes initialized bytes into the buffer
+    unsafe { std::slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut std::mem::MaybeUninit<u8>, buf.len()) }
+}
+
+/// Peer credentials through `SO_PEERCRED`
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod credentials {
+    use super::*;
+
+    pub(super) fn peer_credentials(fd: RawFd) -> Result<PeerCredentials, SocketError> {
+        // Safety: a zeroed ucred is valid
+        let mut cred: libc::ucred = unsafe { std::mem::zeroed() };
+        let mut len = size_of::<libc::ucred>() as libc::socklen_t;
+        // Safety: `cred` is valid for writes of `len` bytes
+        let result = unsafe {
+            libc::getsockopt(
+                fd,
+                libc::SOL_SOCKET,
+                libc::SO_PEERCRED,
+                &mut cred as *mut libc::ucred as *mut libc::c_void,
+                &mut len,
+            )
+        };
+        if result < 0 {
+            return Err(SocketError::from(io::Error::last_os_error()));
+        }
+        Ok(PeerCredentials {
+            pid: Some(cred.pid as u32),
+            uid: cred.uid,
+            gid: cred.gid,
+        })
+    }
+}
+
+/// Peer credentials through `getpeereid`, which does not report the pid
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd", target_os = "dragonfly"))]
+mod credentials {
+    use super::*;
+
+    pub(super) fn peer_credentials(fd: RawFd) -> Result<PeerCredentials, SocketError> {
+        let mut uid: libc::uid_t = 0;
+        let mut gid: libc::gid_t = 0;
+        // Safety: `uid` and `gid` are valid for writes
+        if unsafe { libc::getpeereid(fd, &mut uid, &mut gid) } < 0 {
+            return Err(SocketError::from(io::Error::last_os_error()));
+        }
+        Ok(PeerCredentials { pid: None, uid, gid })
+    }
+}
+
+/// Peer credentials are not available on other platforms
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd", target_os = "dragonfly")))]
+mod credentials {
+    use super::*;
+
+    pub(super) fn peer_credentials(_fd: RawFd) -> Result<PeerCredentials, SocketError> {
+        Err(SocketError::option_not_available("SO_PEERCRED"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Seek, Write};
+
+    fn socket_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("iron_beam_{}_{}.sock", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_stream_bind_listen_accept_connect() {
+        let path = socket_path("stream");
+        let listener = LocalSocket::new(SocketType::Stream).unwrap();
+        listener.bind(&LocalAddress::path(&path)).unwrap();
+        listener.listen(8).unwrap();
+        assert_eq!(listener.local_addr().unwrap(), LocalAddress::Path(path.clone()));
+
+        let client = LocalSocket::new(SocketType::Stream).unwrap();
+        client.connect(&LocalAddress::path(&path)).unwrap();
+        let (server, peer) = listener.accept().unwrap();
+        assert_eq!(peer, LocalAddress::Unnamed);
+        assert_eq!(client.peer_addr().unwrap(), LocalAddress::Path(path.clone()));
+
+        assert_eq!(client.send(b"ping").unwrap(), 4);
+        let mut buf = [0u8; 16];
+        assert_eq!(server.recv(&mut buf).unwrap(), 4);
+        assert_eq!(&buf[..4], b"ping");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_datagram_send_to_recv_from() {
+        let server_path = socket_path("dgram_server");
+        let client_path = socket_path("dgram_client");
+        let server = LocalSocket::new(SocketType::Datagram).unwrap();
+        server.bind(&LocalAddress::path(&server_path)).unwrap();
+        let client = LocalSocket::new(SocketType::Datagram).unwrap();
+        client.bind(&LocalAddress::path(&client_path)).unwrap();
+
+        client.send_to(b"hello", &LocalAddress::path(&server_path)).unwrap();
+        let mut buf = [0u8; 16];
+        let (len, from) = server.recv_from(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"hello");
+        assert_eq!(from, LocalAddress::Path(client_path.clone()));
+        assert_eq!(server.listen(1), Err(SocketError::NotSupported));
+        std::fs::remove_file(&server_path).unwrap();
+        std::fs::remove_file(&client_path).unwrap();
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn test_abstract_address() {
+        let name = format!("iron_beam_abstract_{}", std::process::id());
+        let listener = LocalSocket::new(SocketType::Stream).unwrap();
+        listener.bind(&LocalAddress::abstract_name(&name)).unwrap();
+        listener.listen(1).unwrap();
+        assert_eq!(listener.local_addr().unwrap(), LocalAddress::Abstract(name.clone().into_bytes()));
+
+        let client = LocalSocket::new(SocketType::Stream).unwrap();
+        client.connect(&LocalAddress::abstract_name(&name)).unwrap();
+        assert!(listener.accept().is_ok());
+    }
+
+    #[test]
+    fn test_peer_credentials() {
+        let (a, _b) = LocalSocket::pair(SocketType::Stream).unwrap();
+        let cred = a.peer_credentials().unwrap();
+        // Safety: getuid and getgid cannot fail
+        assert_eq!(cred.uid, unsafe { libc::getuid() });
+        assert_eq!(cred.gid, unsafe { libc::getgid() });
+        if cfg!(any(target_os = "linux", target_os = "android")) {
+            assert_eq!(cred.pid, Some(std::process::id()));
+        }
+    }
+
+    #[test]
+    fn test_fd_passing() {
+        let (a, b) = LocalSocket::pair(SocketType::Stream).unwrap();
+        let path = std::env::temp_dir().join(format!("iron_beam_fd_passing_{}", std::process::id()));
+        let mut file = std::fs::File::options().read(true).write(true).create(true).truncate(true).open(&path).unwrap();
+        file.write_all(b"passed").unwrap();
+
+        assert_eq!(a.send_with_fds(b"x", &[file.as_fd()]).unwrap(), 1);
+        let mut buf = [0u8; 4];
+        let (len, fds) = b.recv_with_fds(&mut buf, 4).unwrap();
+        assert_eq!((len, &buf[..1]), (1, &b"x"[..]));
+        assert_eq!(fds.len(), 1);
+
+        // The received descriptor refers to the same open file
+        let mut received = std::fs::File::from(fds.into_iter().next().unwrap());
+        received.rewind().unwrap();
+        let mut contents = String::new();
+        received.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "passed");
+        std::fs::remove_file(&path).unwrap();
+
+        // Data without descriptors is received as such
+        a.send_with_fds(b"y", &[]).unwrap();
+        let (len, fds) = b.recv_with_fds(&mut buf, 4).unwrap();
+        assert_eq!((len, fds.len()), (1, 0));
+        assert!(a.send_with_fds(b"", &[]).is_err());
+    }
+}
@@ -0,0 +1,583 @@
+//! Socket Options Module
+//!
+//! Provides typed getters and setters for the socket options used by `inet`:
+//! address and port reuse, `TCP_NODELAY`, `SO_LINGER`, keepalive and its
+//! tunables, `IP_TOS`, `IPV6_V6ONLY`, buffer sizes, `SO_BROADCAST` and the
+//! UDP multicast options.
+//!
+//! Options that only apply to one kind of socket (TCP options on a UDP
+//! socket, IPv6 options on an IPv4 socket, ...) fail with
+//! [`SocketError::NotSupported`]. Options the platform does not provide fail
+//! with [`SocketError::OptionNotAvailable`], naming the option and the
+//! platform.
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+
+use crate::socket::{AddressFamily, Socket, SocketError, SocketType};
+
+impl Socket {
+    /// Get the `SO_REUSEADDR` option
+    pub fn reuse_address(&self) -> Result<bool, SocketError> {
+        self.inner().reuse_address().map_err(SocketError::from)
+    }
+
+    /// Set the `SO_REUSEPORT` option
+    ///
+    /// Not available on Windows, Solaris and illumos.
+    pub fn set_reuse_port(&self, reuse: bool) -> Result<(), SocketError> {
+        platform::set_reuse_port(self, reuse)
+    }
+
+    /// Get the `SO_REUSEPORT` option
+    pub fn reuse_port(&self) -> Result<bool, SocketError> {
+        platform::reuse_port(self)
+    }
+
+    /// Set the `TCP_NODELAY` option (TCP only)
+    ///
+    /// When set, segments are sent as soon as possible instead of being
+    /// coalesced by the Nagle algorithm.
+    pub fn set_nodelay(&self, nodelay: bool) -> Result<(), SocketError> {
+        self.require_socket_type(SocketType::Stream)?;
+        self.inner().set_nodelay(nodelay).map_err(SocketError::from)
+    }
+
+    /// Get the `TCP_NODELAY` option (TCP only)
+    pub fn nodelay(&self) -> Result<bool, SocketError> {
+        self.require_socket_type(SocketType::Stream)?;
+        self.inner().nodelay().map_err(SocketError::from)
+    }
+
+    /// Set the `SO_LINGER` option
+    ///
+    /// # Arguments
+    ///
+    /// * `linger` - How long a close waits for unsent data, or `None` to
+    ///   close immediately in the background. The kernel keeps whole seconds.
+    pub fn set_linger(&self, linger: Option<Duration>) -> Result<(), SocketError> {
+        self.inner().set_linger(linger).map_err(SocketError::from)
+    }
+
+    /// Get the `SO_LINGER` option
+    pub fn linger(&self) -> Result<Option<Duration>, SocketError> {
+        self.inner().linger().map_err(SocketError::from)
+    }
+
+    /// Set the `SO_KEEPALIVE` option
+    pub fn set_keepalive(&self, keepalive: bool) -> Result<(), SocketError> {
+        self.inner().set_keepalive(keepalive).map_err(SocketError::from)
+    }
+
+    /// Get the `SO_KEEPALIVE` option
+    pub fn keepalive(&self) -> Result<bool, SocketError> {
+        self.inner().keepalive().map_err(SocketError::from)
+    }
+
+    /// Set the idle time before the first keepalive probe (`TCP_KEEPIDLE`,
+    /// TCP only)
+    ///
+    /// Setting any keepalive tunable also enables `SO_KEEPALIVE`. The
+    /// tunables are available on Linux, Android, the BSDs, macOS, iOS and
+    /// Fuchsia.
+    pub fn set_keepalive_idle(&self, idle: Duration) -> Result<(), SocketError> {
+        self.require_socket_type(SocketType::Stream)?;
+        keepalive::set_idle(self, idle)
+    }
+
+    /// Get the idle time before the first keepalive probe (`TCP_KEEPIDLE`,
+    /// TCP only)
+    pub fn keepalive_idle(&self) -> Result<Duration, SocketError> {
+        self.require_socket_type(SocketType::Stream)?;
+        keepalive::idle(self)
+    }
+
+    /// Set the time between keepalive probes (`TCP_KEEPINTVL`, TCP only)
+    pub fn set_keepalive_interval(&self, interval: Duration) -> Result<(), SocketError> {
+        self.require_socket_type(SocketType::Stream)?;
+        keepalive::set_interval(self, interval)
+    }
+
+    /// Get the time between keepalive probes (`TCP_KEEPINTVL`, TCP only)
+    pub fn keepalive_interval(&self) -> Result<Duration, SocketError> {
+        self.require_socket_type(SocketType::Stream)?;
+        keepalive::interval(self)
+    }
+
+    /// Set the number of unanswered keepalive probes before the connection
+    /// is dropped (`TCP_KEEPCNT`, TCP only)
+    pub fn set_keepalive_count(&self, count: u32) -> Result<(), SocketError> {
+        self.require_socket_type(SocketType::Stream)?;
+        keepalive::set_count(self, count)
+    }
+
+    /// Get the number of unanswered keepalive probes before the connection
+    /// is dropped (`TCP_KEEPCNT`, TCP only)
+    pub fn keepalive_count(&self) -> Result<u32, SocketError> {
+        self.require_socket_type(SocketType::Stream)?;
+        keepalive::count(self)
+    }
+
+    /// Set the `IP_TOS` option (IPv4 only)
+    ///
+    /// Not available on Fuchsia, Redox, Solaris, illumos and Haiku.
+    pub fn set_tos(&self, tos: u32) -> Result<(), SocketError> {
+        self.require_family(AddressFamily::Ipv4)?;
+        platform::set_tos(self, tos)
+    }
+
+    /// Get the `IP_TOS` option (IPv4 only)
+    pub fn tos(&self) -> Result<u32, SocketError> {
+        self.require_family(AddressFamily::Ipv4)?;
+        platform::tos(self)
+    }
+
+    /// Set the `IPV6_V6ONLY` option (IPv6 only)
+    ///
+    /// Must be set before the socket is bound.
+    pub fn set_only_v6(&self, only_v6: bool) -> Result<(), SocketError> {
+        self.require_family(AddressFamily::Ipv6)?;
+        self.inner().set_only_v6(only_v6).map_err(SocketError::from)
+    }
+
+    /// Get the `IPV6_V6ONLY` option (IPv6 only)
+    pub fn only_v6(&self) -> Result<bool, SocketError> {
+        self.require_family(AddressFamily::Ipv6)?;
+        self.inner().only_v6().map_err(SocketError::from)
+    }
+
+    /// Set the `SO_RCVBUF` option
+    ///
+    /// The kernel may round or scale the size; Linux doubles it.
+    pub fn set_recv_buffer_size(&self, size: usize) -> Result<(), SocketError> {
+        self.inner().set_recv_buffer_size(size).map_err(SocketError::from)
+    }
+
+    /// Get the `SO_RCVBUF` option
+    pub fn recv_buffer_size(&self) -> Result<usize, SocketError> {
+        self.inner().recv_buffer_size().map_err(SocketError::from)
+    }
+
+    /// Set the `SO_SNDBUF` option
+    ///
+    /// The kernel may round or scale the size; Linux doubles it.
+    pub fn set_send_buffer_size(&self, size: usize) -> Result<(), SocketError> {
+        self.inner().set_send_buffer_size(size).map_err(SocketError::from)
+    }
+
+    /// Get the `SO_SNDBUF` option
+    pub fn send_buffer_size(&self) -> Result<usize, SocketError> {
+        self.inner().send_buffer_size().map_err(SocketError::from)
+    }
+
+    /// Set the `SO_BROADCAST` option (UDP only)
+    pub fn set_broadcast(&self, broadcast: bool) -> Result<(), SocketError> {
+        self.require_socket_type(SocketType::Datagram)?;
+        self.inner().set_broadcast(broadcast).map_err(SocketError::from)
+    }
+
+    /// Get the `SO_BROADCAST` option (UDP only)
+    pub fn broadcast(&self) -> Result<bool, SocketError> {
+        self.require_socket_type(SocketType::Datagram)?;
+        self.inner().broadcast().map_err(SocketError::from)
+    }
+
+    /// Join an IPv4 multicast group (`IP_ADD_MEMBERSHIP`, UDP over IPv4 only)
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - Multicast group address
+    /// * `interface` - Address of the local interface to join on, or
+    ///   [`Ipv4Addr::UNSPECIFIED`] to let the system choose
+    pub fn join_multicast_v4(&self, group: &Ipv4Addr, interface: &Ipv4Addr) -> Result<(), SocketError> {
+        self.require_multicast(AddressFamily::Ipv4)?;
+        self.inner().join_multicast_v4(group, interface).map_err(SocketError::from)
+    }
+
+    /// Leave an IPv4 multicast group (`IP_DROP_MEMBERSHIP`, UDP over IPv4 only)
+    pub fn leave_multicast_v4(&self, group: &Ipv4Addr, interface: &Ipv4Addr) -> Result<(), SocketError> {
+        self.require_multicast(AddressFamily::Ipv4)?;
+        self.inner().leave_multicast_v4(group, interface).map_err(SocketError::from)
+    }
+
+    /// Join an IPv6 multicast group (`IPV6_ADD_MEMBERSHIP`, UDP over IPv6 only)
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - Multicast group address
+    /// * `interface` - Index of the local interface to join on, or 0 to let
+    ///   the system choose
+    pub fn join_multicast_v6(&self, group: &Ipv6Addr, interface: u32) -> Result<(), SocketError> {
+        self.require_multicast(AddressFamily::Ipv6)?;
+        self.inner().join_multicast_v6(group, interface).map_err(SocketError::from)
+    }
+
+    /// Leave an IPv6 multicast group (`IPV6_DROP_MEMBERSHIP`, UDP over IPv6 only)
+    pub fn leave_multicast_v6(&self, group: &Ipv6Addr, interface: u32) -> Result<(), SocketError> {
+        self.require_multicast(AddressFamily::Ipv6)?;
+        self.inner().leave_multicast_v6(group, interface).map_err(SocketError::from)
+    }
+
+    /// Set how many hops outgoing multicast datagrams may take (UDP only)
+    ///
+    /// Sets `IP_MULTICAST_TTL` on IPv4 sockets and `IPV6_MULTICAST_HOPS` on
+    /// IPv6 sockets.
+    pub fn set_multicast_ttl(&self, ttl: u32) -> Result<(), SocketError> {
+        match self.require_multicast(self.family())? {
+            AddressFamily::Ipv4 => self.inner().set_multicast_ttl_v4(ttl),
+            AddressFamily::Ipv6 => self.inner().set_multicast_hops_v6(ttl),
+        }
+        .map_err(SocketError::from)
+    }
+
+    /// Get how many hops outgoing multicast datagrams may take (UDP only)
+    pub fn multicast_ttl(&self) -> Result<u32, SocketError> {
+        match self.require_multicast(self.family())? {
+            AddressFamily::Ipv4 => self.inner().multicast_ttl_v4(),
+            AddressFamily::Ipv6 => self.inner().multicast_hops_v6(),
+        }
+        .map_err(SocketError::from)
+    }
+
+    /// Set whether outgoing multicast datagrams are looped back to the
+    /// sending host (UDP only)
+    ///
+    /// Sets `IP_MULTICAST_LOOP` on IPv4 sockets and `IPV6_MULTICAST_LOOP` on
+    /// IPv6 sockets.
+    pub fn set_multicast_loop(&self, multicast_loop: bool) -> Result<(), SocketError> {
+        match self.require_multicast(self.family())? {
+            AddressFamily::Ipv4 => self.inner().set_multicast_loop_v4(multicast_loop),
+            AddressFamily::Ipv6 => self.inner().set_multicast_loop_v6(multicast_loop),
+        }
+        .map_err(SocketError::from)
+    }
+
+    /// Get whether outgoing multicast datagrams are looped back to the
+    /// sending host (UDP only)
+    pub fn multicast_loop(&self) -> Result<bool, SocketError> {
+        match self.require_multicast(self.family())? {
+            AddressFamily::Ipv4 => self.inner().multicast_loop_v4(),
+            AddressFamily::Ipv6 => self.inner().multicast_loop_v6(),
+        }
+        .map_err(SocketError::from)
+    }
+
+    /// Set the interface outgoing multicast datagrams are sent on
+    /// (`IP_MULTICAST_IF`, UDP over IPv4 only)
+    pub fn set_multicast_if_v4(&self, interface: &Ipv4Addr) -> Result<(), SocketError> {
+        self.require_multicast(AddressFamily::Ipv4)?;
+        self.inner().set_multicast_if_v4(interface).map_err(SocketError::from)
+    }
+
+    /// Get the interface outgoing multicast datagrams are sent on
+    /// (`IP_MULTICAST_IF`, UDP over IPv4 only)
+    pub fn multicast_if_v4(&self) -> Result<Ipv4Addr, SocketError> {
+        self.require_multicast(AddressFamily::Ipv4)?;
+        self.inner().multicast_if_v4().map_err(SocketError::from)
+    }
+
+    /// Set the index of the interface outgoing multicast datagrams are sent
+    /// on (`IPV6_MULTICAST_IF`, UDP over IPv6 only)
+    pub fn set_multicast_if_v6(&self, interface: u32) -> Result<(), SocketError> {
+        self.require_multicast(AddressFamily::Ipv6)?;
+        self.inner().set_multicast_if_v6(interface).map_err(SocketError::from)
+    }
+
+    /// Get the index of the interface outgoing multicast datagrams are sent
+    /// on (`IPV6_MULTICAST_IF`, UDP over IPv6 only)
+    pub fn multicast_if_v6(&self) -> Result<u32, SocketError> {
+        self.require_multicast(AddressFamily::Ipv6)?;
+        self.inner().multicast_if_v6().map_err(SocketError::from)
+    }
+
+    pub(crate) fn require_socket_type(&self, socket_type: SocketType) -> Result<(), SocketError> {
+        if self.socket_type() != socket_type {
+            return Err(SocketError::NotSupported);
+        }
+        Ok(())
+    }
+
+    fn require_family(&self, family: AddressFamily) -> Result<(), SocketError> {
+        if self.family() != family {
+            return Err(SocketError::NotSupported);
+        }
+        Ok(())
+    }
+
+    /// Check that the socket is a datagram socket of `family`, returning the
+    /// family
+    fn require_multicast(&self, family: AddressFamily) -> Result<AddressFamily, SocketError> {
+        self.require_socket_type(SocketType::Datagram)?;
+        self.require_family(family)?;
+        Ok(family)
+    }
+}
+
+/// Options whose availability differs between platforms
+mod platform {
+    use super::*;
+
+    #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos", target_os = "cygwin"))))]
+    pub(super) fn set_reuse_port(socket: &Socket, reuse: bool) -> Result<(), SocketError> {
+        socket.inner().set_reuse_port(reuse).map_err(SocketError::from)
+    }
+
+    #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos", target_os = "cygwin"))))]
+    pub(super) fn reuse_port(socket: &Socket) -> Result<bool, SocketError> {
+        socket.inner().reuse_port().map_err(SocketError::from)
+    }
+
+    #[cfg(not(all(unix, not(any(target_os = "solaris", target_os = "illumos", target_os = "cygwin")))))]
+    pub(super) fn set_reuse_port(_socket: &Socket, _reuse: bool) -> Result<(), SocketError> {
+        Err(SocketError::option_not_available("SO_REUSEPORT"))
+    }
+
+    #[cfg(not(all(unix, not(any(target_os = "solaris", target_os = "illumos", target_os = "cygwin")))))]
+    pub(super) fn reuse_port(_socket: &Socket) -> Result<bool, SocketError> {
+        Err(SocketError::option_not_available("SO_REUSEPORT"))
+    }
+
+    #[cfg(not(any(
+        target_os = "fuchsia",
+        target_os = "redox",
+        target_os = "solaris",
+        target_os = "illumos",
+        target_os = "haiku",
+    )))]
+    pub(super) fn set_tos(socket: &Socket, tos: u32) -> Result<(), SocketError> {
+        socket.inner().set_tos(tos).map_err(SocketError::from)
+    }
+
+    #[cfg(not(any(
+        target_os = "fuchsia",
+        target_os = "redox",
+        target_os = "solaris",
+        target_os = "illumos",
+        target_os = "haiku",
+    )))]
+    pub(super) fn tos(socket: &Socket) -> Result<u32, SocketError> {
+        socket.inner().tos().map_err(SocketError::from)
+    }
+
+    #[cfg(any(
+        target_os = "fuchsia",
+        target_os = "redox",
+        target_os = "solaris",
+        target_os = "illumos",
+        target_os = "haiku",
+    ))]
+    pub(super) fn set_tos(_socket: &Socket, _tos: u32) -> Result<(), SocketError> {
+        Err(SocketError::option_not_available("IP_TOS"))
+    }
+
+    #[cfg(any(
+        target_os = "fuchsia",
+        target_os = "redox",
+        target_os = "solaris",
+        target_os = "illumos",
+        target_os = "haiku",
+    ))]
+    pub(super) fn tos(_socket: &Socket) -> Result<u32, SocketError> {
+        Err(SocketError::option_not_available("IP_TOS"))
+    }
+}
+
+/// Keepalive tunables, on the platforms providing all three of them
+#[cfg(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "fuchsia",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "netbsd",
+))]
+mod keepalive {
+    use super::*;
+    use socket2::TcpKeepalive;
+
+    pub(super) fn set_idle(socket: &Socket, idle: Duration) -> Result<(), SocketError> {
+        let params = TcpKeepalive::new().with_time(idle);
+        socket.inner().set_tcp_keepalive(&params).map_err(SocketError::from)
+    }
+
+    pub(super) fn idle(socket: &Socket) -> Result<Duration, SocketError> {
+        socket.inner().keepalive_time().map_err(SocketError::from)
+    }
+
+    pub(super) fn set_interval(socket: &Socket, interval: Duration) -> Result<(), SocketError> {
+        let params = TcpKeepalive::new().with_interval(interval);
+        socket.inner().set_tcp_keepalive(&params).map_err(SocketError::from)
+    }
+
+    pub(super) fn interval(socket: &Socket) -> Result<Duration, SocketError> {
+        socket.inner().keepalive_interval().map_err(SocketError::from)
+    }
+
+    pub(super) fn set_count(socket: &Socket, count: u32) -> Result<(), SocketError> {
+        let params = TcpKeepalive::new().with_retries(count);
+        socket.inner().set_tcp_keepalive(&params).map_err(SocketError::from)
+    }
+
+    pub(super) fn count(socket: &Socket) -> Result<u32, SocketError> {
+        socket.inner().keepalive_retries().map_err(SocketError::from)
+    }
+}
+
+#[cfg(not(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "fuchsia",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "netbsd",
+)))]
+mod keepalive {
+    use super::*;
+
+    pub(super) fn set_idle(_socket: &Socket, _idle: Duration) -> Result<(), SocketError> {
+        Err(SocketError::option_not_available("TCP_KEEPIDLE"))
+    }
+
+    pub(super) fn idle(_socket: &Socket) -> Result<Duration, SocketError> {
+        Err(SocketError::option_not_available("TCP_KEEPIDLE"))
+    }
+
+    pub(super) fn set_interval(_socket: &Socket, _interval: Duration) -> Result<(), SocketError> {
+        Err(SocketError::option_not_available("TCP_KEEPINTVL"))
+    }
+
+    pub(super) fn interval(_socket: &Socket) -> Result<Duration, SocketError> {
+        Err(SocketError::option_not_available("TCP_KEEPINTVL"))
+    }
+
+    pub(super) fn set_count(_socket: &Socket, _count: u32) -> Result<(), SocketError> {
+        Err(SocketError::option_not_available("TCP_KEEPCNT"))
+    }
+
+    pub(super) fn count(_socket: &Socket) -> Result<u32, SocketError> {
+        Err(SocketError::option_not_available("TCP_KEEPCNT"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::socket::Protocol;
+
+    fn tcp() -> Socket {
+        Socket::new(AddressFamily::Ipv4, SocketType::Stream, Protocol::Tcp).unwrap()
+    }
+
+    fn udp() -> Socket {
+        Socket::new(AddressFamily::Ipv4, SocketType::Datagram, Protocol::Udp).unwrap()
+    }
+
+    #[test]
+    fn test_reuse_options() {
+        let socket = tcp();
+        socket.set_reuse_address(true).unwrap();
+        assert!(socket.reuse_address().unwrap());
+        socket.set_reuse_port(true).unwrap();
+        assert!(socket.reuse_port().unwrap());
+        socket.set_reuse_port(false).unwrap();
+        assert!(!socket.reuse_port().unwrap());
+    }
+
+    #[test]
+    fn test_nodelay_is_tcp_only() {
+        let socket = tcp();
+        socket.set_nodelay(true).unwrap();
+        assert!(socket.nodelay().unwrap());
+        assert_eq!(udp().set_nodelay(true), Err(SocketError::NotSupported));
+        assert_eq!(udp().nodelay(), Err(SocketError::NotSupported));
+    }
+
+    #[test]
+    fn test_linger() {
+        let socket = tcp();
+        assert_eq!(socket.linger().unwrap(), None);
+        socket.set_linger(Some(Duration::from_secs(5))).unwrap();
+        assert_eq!(socket.linger().unwrap(), Some(Duration::from_secs(5)));
+        socket.set_linger(None).unwrap();
+        assert_eq!(socket.linger().unwrap(), None);
+    }
+
+    #[test]
+    fn test_keepalive_tunables() {
+        let socket = tcp();
+        assert!(!socket.keepalive().unwrap());
+        socket.set_keepalive_idle(Duration::from_secs(60)).unwrap();
+        socket.set_keepalive_interval(Duration::from_secs(10)).unwrap();
+        socket.set_keepalive_count(4).unwrap();
+        assert!(socket.keepalive().unwrap());
+        assert_eq!(socket.keepalive_idle().unwrap(), Duration::from_secs(60));
+        assert_eq!(socket.keepalive_interval().unwrap(), Duration::from_secs(10));
+        assert_eq!(socket.keepalive_count().unwrap(), 4);
+
+        socket.set_keepalive(false).unwrap();
+        assert!(!socket.keepalive().unwrap());
+        assert_eq!(udp().keepalive_count(), Err(SocketError::NotSupported));
+    }
+
+    #[test]
+    fn test_tos_and_only_v6_check_family() {
+        let socket = tcp();
+        socket.set_tos(0x10).unwrap();
+        assert_eq!(socket.tos().unwrap(), 0x10);
+        assert_eq!(socket.set_only_v6(true), Err(SocketError::NotSupported));
+
+        // IPv6 may be disabled where the tests run
+        if let Ok(socket) = Socket::new(AddressFamily::Ipv6, SocketType::Stream, Protocol::Tcp) {
+            socket.set_only_v6(true).unwrap();
+            assert!(socket.only_v6().unwrap());
+            assert_eq!(socket.tos(), Err(SocketError::NotSupported));
+        }
+    }
+
+    #[test]
+    fn test_buffer_sizes() {
+        let socket = udp();
+        socket.set_recv_buffer_size(64 * 1024).unwrap();
+        socket.set_send_buffer_size(32 * 1024).unwrap();
+        assert!(socket.recv_buffer_size().unwrap() >= 64 * 1024);
+        assert!(socket.send_buffer_size().unwrap() >= 32 * 1024);
+    }
+
+    #[test]
+    fn test_multicast_options() {
+        let socket = udp();
+        socket.set_multicast_ttl(4).unwrap();
+        assert_eq!(socket.multicast_ttl().unwrap(), 4);
+        socket.set_multicast_loop(false).unwrap();
+        assert!(!socket.multicast_loop().unwrap());
+        socket.set_multicast_if_v4(&Ipv4Addr::LOCALHOST).unwrap();
+        assert_eq!(socket.multicast_if_v4().unwrap(), Ipv4Addr::LOCALHOST);
+
+        socket.set_broadcast(true).unwrap();
+        assert!(socket.broadcast().unwrap());
+
+        assert_eq!(socket.multicast_if_v6(), Err(SocketError::NotSupported));
+        assert_eq!(tcp().set_broadcast(true), Err(SocketError::NotSupported));
+        let group = Ipv4Addr::new(224, 0, 0, 251);
+        assert_eq!(
+            tcp().join_multicast_v4(&group, &Ipv4Addr::UNSPECIFIED),
+            Err(SocketError::NotSupported)
+        );
+        assert_eq!(tcp().multicast_ttl(), Err(SocketError::NotSupported));
+    }
+
+    #[test]
+    fn test_option_not_available_names_platform() {
+        assert_eq!(
+            SocketError::option_not_available("SO_REUSEPORT"),
+            SocketError::OptionNotAvailable {
+                option: "SO_REUSEPORT",
+                platform: std::env::consts::OS,
+            }
+        );
+    }
+}
@@ -0,0 +1,41 @@
+//! Select Module
+//!
+//! Provides the types of socket operations that complete asynchronously, as in
+//! `socket.erl`. When a non-blocking connect or accept cannot complete
+//! immediately, the socket is selected in the NIF I/O poller on behalf of the
+//! owning process and the operation returns a [`SelectInfo`]. Once the socket
+//! is ready the poller delivers `{select, Resource, Ref, ready_input |
+//! ready_output}` to that process, which then retries or completes the
+//! operation.
+//!
+//! A select is one-shot: each ready notification consumes it, so an accept
+//! loop selects again whenever accepting would block.
+
+use adapters_nif_io::nif_io::ErlangTerm;
+
+/// Operation waiting for a select
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectTag {
+    /// Connect waiting for write readiness
+    Connect,
+    /// Accept waiting for read readiness
+    Accept,
+}
+
+/// Information about a pending select, returned in place of a result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelectInfo {
+    /// Operation waiting for the select
+    pub tag: SelectTag,
+    /// Reference carried by the select message
+    pub ref_term: ErlangTerm,
+}
+
+/// Outcome of an operation that may have to wait for readiness
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectOutcome<T> {
+    /// The operation completed immediately
+    Completed(T),
+    /// The operation is waiting for a select message
+    Selected(SelectInfo),
+}
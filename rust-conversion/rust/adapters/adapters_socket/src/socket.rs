@@ -35,12 +35,29 @@ pub enum SocketError {
     InvalidSocket,
     /// Operation not supported
     NotSupported,
+    /// Socket option not provided by this platform
+    OptionNotAvailable {
+        /// Option name, such as `SO_REUSEPORT`
+        option: &'static str,
+        /// Platform name, as in `std::env::consts::OS`
+        platform: &'static str,
+    },
     /// I/O error
     IoError(String),
     /// Other error
     Other(String),
 }
 
+impl SocketError {
+    /// Error for a socket option this platform does not provide
+    pub fn option_not_available(option: &'static str) -> Self {
+        SocketError::OptionNotAvailable {
+            option,
+            platform: std::env::consts::OS,
+        }
+    }
+}
+
 impl From<io::Error> for SocketError {
     fn from(err: io::Error) -> Self {
         use std::io::ErrorKind;
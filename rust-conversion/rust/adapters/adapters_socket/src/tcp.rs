@@ -4,9 +4,15 @@
 //! stream-based communication.
 
 use super::socket::{Socket, SocketError, AddressFamily};
+use super::select::{SelectInfo, SelectOutcome, SelectTag};
 use std::net::SocketAddr;
 use std::io::{self, Read, Write};
-use adapters_nif_io::CheckIo;
+use std::ptr;
+use std::time::Duration;
+use adapters_nif_io::nif_io::{ErlangPid, ErlangTerm};
+use adapters_nif_io::{enif_select, CheckIo, IoEvent, NifSelectFlags, NifSelectResult};
+use infrastructure_nif_api::{NifEnv, NifTerm};
+use socket2::SockAddr;
 
 /// TCP Socket
 ///
@@ -99,10 +105,8 @@ impl TcpSocket {
             check_io: None,
         };
         
-        // Copy I/O polling reference if available
-        if self.check_io.is_some() {
-            new_socket.check_io = Some(CheckIo::new()); // Create new instance for new connection
-        }
+        // The connection is polled by the listener's I/O poller
+        new_socket.check_io = self.check_io.clone();
         
         Ok((new_socket, addr))
     }
@@ -151,6 +155,34 @@ impl TcpSocket {
             .map_err(|e| SocketError::from(e))
     }
     
+    /// Send the segments of an iolist without flattening them
+    ///
+    /// See [`Socket::send_vectored`].
+    pub fn send_vectored(&self, segments: &[&[u8]]) -> Result<usize, SocketError> {
+        self.socket.send_vectored(segments)
+    }
+    
+    /// Send an iolist term built in a NIF environment
+    ///
+    /// See [`Socket::send_iolist`].
+    pub fn send_iolist(&self, env: &NifEnv, iolist: NifTerm) -> Result<usize, SocketError> {
+        self.socket.send_iolist(env, iolist)
+    }
+    
+    /// Receive into pre-allocated buffers
+    ///
+    /// See [`Socket::recv_vectored`].
+    pub fn recv_vectored(&self, buffers: &mut [&mut [u8]]) -> Result<usize, SocketError> {
+        self.socket.recv_vectored(buffers)
+    }
+    
+    /// Send part of a file to the peer
+    ///
+    /// See [`Socket::sendfile`].
+    pub fn sendfile(&self, file: &std::fs::File, offset: u64, count: usize) -> Result<usize, SocketError> {
+        self.socket.sendfile(file, offset, count)
+    }
+    
     /// Get the local address
     ///
     /// # Returns
@@ -186,6 +218,160 @@ impl TcpSocket {
     pub fn inner(&self) -> &Socket {
         &self.socket
     }
+    
+    /// Get the I/O polling manager, if the socket has one
+    pub fn check_io(&self) -> Option<&CheckIo> {
+        self.check_io.as_ref()
+    }
+    
+    /// Start a non-blocking connect
+    ///
+    /// If the connection cannot be established immediately, the socket is
+    /// selected for writing on behalf of `pid`. When it becomes writable `pid`
+    /// receives a `ready_output` select message carrying `ref_term`, and
+    /// completes the connection with [`TcpSocket::complete_connect`].
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - Remote address to connect to
+    /// * `pid` - Process to notify when the connect can complete
+    /// * `ref_term` - Reference carried by the select message
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SelectOutcome::Completed(()))` - Connected immediately
+    /// * `Ok(SelectOutcome::Selected(info))` - Connect in progress
+    /// * `Err(SocketError)` - Error connecting, or the socket has no I/O polling
+    pub fn connect_select(
+        &self,
+        addr: &SocketAddr,
+        pid: ErlangPid,
+        ref_term: ErlangTerm,
+    ) -> Result<SelectOutcome<()>, SocketError> {
+        match self.socket.inner().connect(&SockAddr::from(*addr)) {
+            Ok(()) => Ok(SelectOutcome::Completed(())),
+            Err(e) if connect_in_progress(&e) => {
+                self.select(NifSelectFlags::Write, pid, ref_term)?;
+                Ok(SelectOutcome::Selected(SelectInfo {
+                    tag: SelectTag::Connect,
+                    ref_term,
+                }))
+            }
+            Err(e) => Err(SocketError::from(e)),
+        }
+    }
+    
+    /// Complete a connect started with [`TcpSocket::connect_select`]
+    ///
+    /// Called once the socket is writable.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - Connected
+    /// * `Err(SocketError)` - The connect failed
+    pub fn complete_connect(&self) -> Result<(), SocketError> {
+        if let Some(err) = self.socket.inner().take_error()? {
+            return Err(SocketError::from(err));
+        }
+        self.socket.peer_addr().map(|_| ())
+    }
+    
+    /// Accept an incoming connection without blocking
+    ///
+    /// If no connection is pending, the listening socket is selected for
+    /// reading on behalf of `pid`. When a connection arrives `pid` receives a
+    /// `ready_input` select message carrying `ref_term` and calls this again.
+    ///
+    /// # Arguments
+    ///
+    /// * `pid` - Process to notify when a connection is pending
+    /// * `ref_term` - Reference carried by the select message
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SelectOutcome::Completed((TcpSocket, SocketAddr)))` - Accepted connection
+    /// * `Ok(SelectOutcome::Selected(info))` - No connection is pending
+    /// * `Err(SocketError)` - Error accepting, or the socket has no I/O polling
+    pub fn accept_select(
+        &self,
+        pid: ErlangPid,
+        ref_term: ErlangTerm,
+    ) -> Result<SelectOutcome<(TcpSocket, SocketAddr)>, SocketError> {
+        match self.accept() {
+            Ok(accepted) => Ok(SelectOutcome::Completed(accepted)),
+            Err(SocketError::WouldBlock) => {
+                self.select(NifSelectFlags::Read, pid, ref_term)?;
+                Ok(SelectOutcome::Selected(SelectInfo {
+                    tag: SelectTag::Accept,
+                    ref_term,
+                }))
+            }
+            Err(e) => Err(e),
+        }
+    }
+    
+    /// Cancel pending selects for the socket
+    pub fn cancel_select(&self) -> Result<(), SocketError> {
+        let check_io = self.check_io.as_ref().ok_or(SocketError::NotSupported)?;
+        let mode = NifSelectFlags::combine(&[
+            NifSelectFlags::Cancel,
+            NifSelectFlags::Read,
+            NifSelectFlags::Write,
+        ]);
+        enif_select(check_io, self.as_raw_fd(), mode, ptr::null_mut(), None, 0)
+            .map_err(|e| SocketError::Other(format!("enif_select failed: {:?}", e)))?;
+        Ok(())
+    }
+    
+    /// Wait for readiness of the sockets selected in the I/O poller
+    ///
+    /// Select messages for the ready sockets are delivered through the
+    /// poller's select message handler.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - Maximum time to wait (None = wait indefinitely)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<IoEvent>)` - Events that occurred, empty on timeout
+    /// * `Err(SocketError)` - Polling failed, or the socket has no I/O polling
+    pub fn poll(&self, timeout: Option<Duration>) -> Result<Vec<IoEvent>, SocketError> {
+        let check_io = self.check_io.as_ref().ok_or(SocketError::NotSupported)?;
+        check_io
+            .check(check_io.poll_thread_for(self.as_raw_fd()), timeout, false)
+            .map(Option::unwrap_or_default)
+            .map_err(|e| SocketError::Other(format!("check_io failed: {:?}", e)))
+    }
+    
+    /// Select the socket for `flag` in the I/O poller
+    fn select(&self, flag: NifSelectFlags, pid: ErlangPid, ref_term: ErlangTerm) -> Result<(), SocketError> {
+        let check_io = self.check_io.as_ref().ok_or(SocketError::NotSupported)?;
+        let result = enif_select(check_io, self.as_raw_fd(), flag as u32, ptr::null_mut(), Some(pid), ref_term)
+            .map_err(|e| SocketError::Other(format!("enif_select failed: {:?}", e)))?;
+        if result.has_flag(NifSelectResult::INVALID_EVENT) {
+            return Err(SocketError::InvalidSocket);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TcpSocket {
+    fn drop(&mut self) {
+        // Deselect the descriptor before it is closed and possibly reused
+        if let Some(check_io) = &self.check_io {
+            let _ = enif_select(check_io, self.as_raw_fd(), NifSelectFlags::Stop as u32, ptr::null_mut(), None, 0);
+        }
+    }
+}
+
+/// Whether a failed non-blocking connect is still in progress
+fn connect_in_progress(err: &io::Error) -> bool {
+    #[cfg(unix)]
+    if err.raw_os_error() == Some(libc::EINPROGRESS) {
+        return true;
+    }
+    err.kind() == io::ErrorKind::WouldBlock
 }
 
 impl Read for TcpSocket {
@@ -207,6 +393,7 @@ impl Write for TcpSocket {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use adapters_nif_io::{SelectEventAtom, SelectMessage};
     use std::net::Ipv4Addr;
     
     #[test]
@@ -787,5 +974,117 @@ mod tests {
         let socket = TcpSocket::with_io_polling(AddressFamily::Ipv6, check_io);
         assert!(socket.is_ok());
     }
-}
 
+    type Delivered = std::sync::Arc<std::sync::Mutex<Vec<(ErlangPid, ErlangTerm, SelectEventAtom)>>>;
+
+    fn select_recorder(check_io: &CheckIo) -> Delivered {
+        use std::sync::{Arc, Mutex};
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+        let sink = delivered.clone();
+        check_io.set_select_handler(Arc::new(move |pid, message: SelectMessage| {
+            sink.lock().unwrap().push((pid, message.ref_term, message.event_atom));
+        }));
+        delivered
+    }
+
+    #[test]
+    fn test_tcp_socket_select_accept_and_connect() {
+        let check_io = CheckIo::new();
+        let delivered = select_recorder(&check_io);
+        let listener = TcpSocket::with_io_polling(AddressFamily::Ipv4, check_io.clone()).unwrap();
+        listener.bind(&SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0)).unwrap();
+        listener.listen(16).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // No connection is pending, so the accept waits for a select
+        let outcome = listener.accept_select(1, 100).unwrap();
+        assert!(matches!(
+            outcome,
+            SelectOutcome::Selected(SelectInfo { tag: SelectTag::Accept, ref_term: 100 })
+        ));
+
+        let client = TcpSocket::with_io_polling(AddressFamily::Ipv4, check_io).unwrap();
+        let connected = match client.connect_select(&addr, 2, 200).unwrap() {
+            SelectOutcome::Completed(()) => true,
+            SelectOutcome::Selected(info) => {
+                assert_eq!(info, SelectInfo { tag: SelectTag::Connect, ref_term: 200 });
+                false
+            }
+        };
+
+        // Each selected operation is notified once
+        let expected = if connected { 1 } else { 2 };
+        for _ in 0..50 {
+            if delivered.lock().unwrap().len() >= expected {
+                break;
+            }
+            listener.poll(Some(Duration::from_millis(100))).unwrap();
+        }
+        let messages = delivered.lock().unwrap().clone();
+        assert!(messages.contains(&(1, 100, SelectEventAtom::ReadyInput)));
+        if !connected {
+            assert!(messages.contains(&(2, 200, SelectEventAtom::ReadyOutput)));
+            client.complete_connect().unwrap();
+        }
+        assert_eq!(messages.len(), expected);
+
+        match listener.accept_select(1, 101).unwrap() {
+            SelectOutcome::Completed((accepted, peer)) => {
+                assert_eq!(peer, client.local_addr().unwrap());
+                assert!(accepted.check_io().is_some());
+            }
+            SelectOutcome::Selected(_) => panic!("connection should be pending"),
+        }
+    }
+
+    #[test]
+    fn test_tcp_socket_select_connect_refused() {
+        let check_io = CheckIo::new();
+        let delivered = select_recorder(&check_io);
+        // Bind without listening to get a port that refuses connections
+        let closed = TcpSocket::new(AddressFamily::Ipv4).unwrap();
+        closed.bind(&SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0)).unwrap();
+        let addr = closed.local_addr().unwrap();
+
+        let client = TcpSocket::with_io_polling(AddressFamily::Ipv4, check_io).unwrap();
+        match client.connect_select(&addr, 3, 300) {
+            Err(e) => assert_eq!(e, SocketError::ConnectionRefused),
+            Ok(SelectOutcome::Selected(_)) => {
+                for _ in 0..50 {
+                    if !delivered.lock().unwrap().is_empty() {
+                        break;
+                    }
+                    client.poll(Some(Duration::from_millis(100))).unwrap();
+                }
+                assert_eq!(delivered.lock().unwrap().len(), 1);
+                assert_eq!(client.complete_connect(), Err(SocketError::ConnectionRefused));
+            }
+            Ok(SelectOutcome::Completed(())) => panic!("connect should fail"),
+        }
+    }
+
+    #[test]
+    fn test_tcp_socket_select_requires_io_polling() {
+        let socket = TcpSocket::new(AddressFamily::Ipv4).unwrap();
+        socket.bind(&SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0)).unwrap();
+        socket.listen(1).unwrap();
+        assert!(matches!(socket.accept_select(1, 1), Err(SocketError::NotSupported)));
+        assert_eq!(socket.cancel_select(), Err(SocketError::NotSupported));
+        assert_eq!(socket.poll(Some(Duration::from_millis(1))).unwrap_err(), SocketError::NotSupported);
+    }
+
+    #[test]
+    fn test_tcp_socket_cancel_select() {
+        let check_io = CheckIo::new();
+        let delivered = select_recorder(&check_io);
+        let listener = TcpSocket::with_io_polling(AddressFamily::Ipv4, check_io).unwrap();
+        listener.bind(&SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0)).unwrap();
+        listener.listen(1).unwrap();
+        listener.accept_select(1, 1).unwrap();
+        listener.cancel_select().unwrap();
+
+        let _client = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        assert!(listener.poll(Some(Duration::from_millis(50))).unwrap().is_empty());
+        assert!(delivered.lock().unwrap().is_empty());
+    }
+}
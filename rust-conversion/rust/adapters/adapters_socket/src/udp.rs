@@ -3,8 +3,9 @@
 //! Provides UDP (User Datagram Protocol) socket functionality for datagram-based
 //! communication.
 
+use super::ancillary::ReceivedMessage;
 use super::socket::{Socket, SocketError, AddressFamily};
-use std::net::SocketAddr;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
 use adapters_nif_io::CheckIo;
 
 /// UDP Socket
@@ -215,6 +216,73 @@ impl UdpSocket {
     pub fn inner(&self) -> &Socket {
         &self.socket
     }
+    
+    /// Receive a datagram with its control messages (ancillary data)
+    ///
+    /// See [`Socket::recv_msg`].
+    pub fn recv_msg(&self, buf: &mut [u8]) -> Result<ReceivedMessage, SocketError> {
+        self.socket.recv_msg(buf)
+    }
+    
+    /// Receive packet info control messages
+    pub fn set_recv_pktinfo(&self, enable: bool) -> Result<(), SocketError> {
+        self.socket.set_recv_pktinfo(enable)
+    }
+    
+    /// Receive type of service control messages
+    pub fn set_recv_tos(&self, enable: bool) -> Result<(), SocketError> {
+        self.socket.set_recv_tos(enable)
+    }
+    
+    /// Receive timestamp control messages
+    pub fn set_recv_timestamp(&self, enable: bool) -> Result<(), SocketError> {
+        self.socket.set_recv_timestamp(enable)
+    }
+    
+    /// Join an IPv4 multicast group on the interface with address `interface`
+    pub fn join_multicast_v4(&self, group: &Ipv4Addr, interface: &Ipv4Addr) -> Result<(), SocketError> {
+        self.socket.join_multicast_v4(group, interface)
+    }
+    
+    /// Leave an IPv4 multicast group
+    pub fn leave_multicast_v4(&self, group: &Ipv4Addr, interface: &Ipv4Addr) -> Result<(), SocketError> {
+        self.socket.leave_multicast_v4(group, interface)
+    }
+    
+    /// Join an IPv6 multicast group on the interface with index `interface`
+    pub fn join_multicast_v6(&self, group: &Ipv6Addr, interface: u32) -> Result<(), SocketError> {
+        self.socket.join_multicast_v6(group, interface)
+    }
+    
+    /// Leave an IPv6 multicast group
+    pub fn leave_multicast_v6(&self, group: &Ipv6Addr, interface: u32) -> Result<(), SocketError> {
+        self.socket.leave_multicast_v6(group, interface)
+    }
+    
+    /// Set the multicast TTL (IPv4) or hop limit (IPv6)
+    pub fn set_multicast_ttl(&self, ttl: u32) -> Result<(), SocketError> {
+        self.socket.set_multicast_ttl(ttl)
+    }
+    
+    /// Get the multicast TTL (IPv4) or hop limit (IPv6)
+    pub fn multicast_ttl(&self) -> Result<u32, SocketError> {
+        self.socket.multicast_ttl()
+    }
+    
+    /// Set whether multicast datagrams are looped back to the sending host
+    pub fn set_multicast_loop(&self, multicast_loop: bool) -> Result<(), SocketError> {
+        self.socket.set_multicast_loop(multicast_loop)
+    }
+    
+    /// Set the `SO_BROADCAST` flag
+    pub fn set_broadcast(&self, broadcast: bool) -> Result<(), SocketError> {
+        self.socket.set_broadcast(broadcast)
+    }
+    
+    /// Get the `SO_BROADCAST` flag
+    pub fn broadcast(&self) -> Result<bool, SocketError> {
+        self.socket.broadcast()
+    }
 }
 
 #[cfg(test)]
@@ -780,5 +848,53 @@ mod tests {
         let _ = sender.join();
         assert!(received.is_some());
     }
-}
 
+    #[test]
+    fn test_udp_broadcast_and_multicast_settings() {
+        let socket = UdpSocket::new(AddressFamily::Ipv4).unwrap();
+        assert!(!socket.broadcast().unwrap());
+        socket.set_broadcast(true).unwrap();
+        assert!(socket.broadcast().unwrap());
+        socket.set_multicast_ttl(8).unwrap();
+        assert_eq!(socket.multicast_ttl().unwrap(), 8);
+        socket.set_multicast_loop(true).unwrap();
+        // IPv6 groups cannot be joined on an IPv4 socket
+        let group = "ff02::fb".parse().unwrap();
+        assert_eq!(socket.join_multicast_v6(&group, 0), Err(SocketError::NotSupported));
+    }
+
+    #[test]
+    fn test_udp_multicast_loopback() {
+        use std::thread;
+        use std::time::Duration;
+
+        let group = Ipv4Addr::new(239, 255, 40, 83);
+        let receiver = UdpSocket::new(AddressFamily::Ipv4).unwrap();
+        receiver.inner().set_reuse_address(true).unwrap();
+        receiver.bind(&SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0)).unwrap();
+        // Multicast may be unavailable where the tests run (no route or interface)
+        if receiver.join_multicast_v4(&group, &Ipv4Addr::LOCALHOST).is_err() {
+            return;
+        }
+        let sender = UdpSocket::new(AddressFamily::Ipv4).unwrap();
+        sender.set_multicast_loop(true).unwrap();
+        sender.inner().set_multicast_if_v4(&Ipv4Addr::LOCALHOST).unwrap();
+        let port = receiver.local_addr().unwrap().port();
+        if sender.send_to(b"hello group", &SocketAddr::new(group.into(), port)).is_err() {
+            return;
+        }
+
+        let mut buf = [0u8; 32];
+        for _ in 0..50 {
+            match receiver.recv_msg(&mut buf) {
+                Ok(message) => {
+                    assert_eq!(&buf[..message.len], b"hello group");
+                    receiver.leave_multicast_v4(&group, &Ipv4Addr::LOCALHOST).unwrap();
+                    return;
+                }
+                Err(SocketError::WouldBlock) => thread::sleep(Duration::from_millis(10)),
+                Err(e) => panic!("recv_msg failed: {:?}", e),
+            }
+        }
+    }
+}
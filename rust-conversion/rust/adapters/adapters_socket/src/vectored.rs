@@ -0,0 +1,285 @@
+//! Vectored I/O Module
+//!
+//! Provides scatter-gather I/O for sockets: sending the segments of an iolist
+//! with one `writev` without flattening them, receiving into pre-allocated
+//! buffers with one `readv`, and sending file contents with `sendfile`.
+//!
+//! `sendfile` is used on Linux and Android. Other platforms, and files the
+//! kernel cannot send directly, fall back to reading the file in chunks and
+//! writing them to the socket.
+
+use std::fs::File;
+use std::io::{self, IoSlice, IoSliceMut, Read, Seek, SeekFrom, Write};
+
+use infrastructure_nif_api::{iolist_to_iovec, NifEnv, NifTerm};
+
+use crate::socket::{Socket, SocketError, SocketType};
+
+/// Chunk size of the `sendfile` fallback
+const SENDFILE_CHUNK_SIZE: usize = 64 * 1024;
+
+impl Socket {
+    /// Send the segments of an iolist with a single `writev`
+    ///
+    /// The socket is non-blocking, so fewer bytes than the total may be
+    /// sent; the caller continues with the unsent remainder.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - Number of bytes sent
+    /// * `Err(SocketError)` - Error sending
+    pub fn send_vectored(&self, segments: &[&[u8]]) -> Result<usize, SocketError> {
+        let slices: Vec<IoSlice> = segments.iter().map(|s| IoSlice::new(s)).collect();
+        self.inner().send_vectored(&slices).map_err(SocketError::from)
+    }
+
+    /// Send an iolist term built in a NIF environment with a single `writev`
+    ///
+    /// The iolist is split into segments with `iolist_to_iovec`; as with
+    /// [`Socket::send_vectored`], fewer bytes than the total may be sent.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - Number of bytes sent
+    /// * `Err(SocketError)` - Error sending, or `Other` if `iolist` is not an
+    ///   iolist
+    pub fn send_iolist(&self, env: &NifEnv, iolist: NifTerm) -> Result<usize, SocketError> {
+        let segments = iolist_to_iovec(env, iolist).ok_or_else(|| SocketError::Other("not an iolist".to_string()))?;
+        let slices: Vec<&[u8]> = segments.iter().map(|segment| segment.data()).collect();
+        self.send_vectored(&slices)
+    }
+
+    /// Receive into pre-allocated buffers with a single `readv`
+    ///
+    /// Buffers are filled in order.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - Number of bytes received, 0 if the peer closed
+    /// * `Err(SocketError)` - Error receiving
+    pub fn recv_vectored(&self, buffers: &mut [&mut [u8]]) -> Result<usize, SocketError> {
+        let mut slices: Vec<IoSliceMut> = buffers.iter_mut().map(|b| IoSliceMut::new(b)).collect();
+        let mut inner = self.inner();
+        inner.read_vectored(&mut slices).map_err(SocketError::from)
+    }
+
+    /// Send part of a file to the connected peer (stream sockets only)
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - File to send
+    /// * `offset` - Position in the file to start from
+    /// * `count` - Number of bytes to send
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - Number of bytes sent; less than `count` at end of file
+    ///   or when the socket buffer fills up
+    /// * `Err(SocketError)` - Error sending, or `WouldBlock` if nothing could
+    ///   be sent
+    pub fn sendfile(&self, file: &File, offset: u64, count: usize) -> Result<usize, SocketError> {
+        self.require_socket_type(SocketType::Stream)?;
+        sendfile_impl(self, file, offset, count)
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn sendfile_impl(socket: &Socket, file: &File, offset: u64, count: usize) -> Result<usize, SocketError> {
+    use std::os::unix::io::AsRawFd;
+
+    let mut file_offset = libc::off_t::try_from(offset)
+        .map_err(|_| SocketError::Other(format!("file offset {} out of range", offset)))?;
+    let mut sent = 0;
+    while sent < count {
+        // Safety: both descriptors stay open for the duration of the call and
+        // `file_offset` is a valid, exclusively borrowed local
+        let result = unsafe {
+            libc::sendfile(socket.as_raw_fd(), file.as_raw_fd(), &mut file_offset, count - sent)
+        };
+        if result == 0 {
+            break; // End of file
+        }
+        if result > 0 {
+            sent += result as usize;
+            continue;
+        }
+        let err = io::Error::last_os_error();
+        match err.raw_os_error() {
+            Some(libc::EINTR) => {}
+            // The file cannot be sent directly (e.g. it is not mmap-able)
+            Some(libc::EINVAL) | Some(libc::ENOSYS) if sent == 0 => {
+                return sendfile_fallback(socket, file, offset, count);
+            }
+            _ if sent > 0 && err.kind() == io::ErrorKind::WouldBlock => break,
+            _ => return Err(SocketError::from(err)),
+        }
+    }
+    Ok(sent)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn sendfile_impl(socket: &Socket, file: &File, offset: u64, count: usize) -> Result<usize, SocketError> {
+    sendfile_fallback(socket, file, offset, count)
+}
+
+/// Send part of a file by reading it in chunks and writing them to the socket
+///
+/// Moves the file position.
+fn sendfile_fallback(socket: &Socket, file: &File, offset: u64, count: usize) -> Result<usize, SocketError> {
+    let mut file = file;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut inner = socket.inner();
+    let mut chunk = vec![0u8; SENDFILE_CHUNK_SIZE.min(count)];
+    let mut sent = 0;
+    while sent < count {
+        let len = chunk.len().min(count - sent);
+        let read = match file.read(&mut chunk[..len]) {
+            Ok(0) => break, // End of file
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(SocketError::from(e)),
+        };
+        let mut written = 0;
+        while written < read {
+            match inner.write(&chunk[written..read]) {
+                Ok(n) => written += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock && sent + written > 0 => {
+                    return Ok(sent + written);
+                }
+                Err(e) => return Err(SocketError::from(e)),
+            }
+        }
+        sent += read;
+    }
+    Ok(sent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::socket::{AddressFamily, Protocol};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::thread;
+    use std::time::Duration;
+
+    /// Connected pair of non-blocking TCP sockets
+    fn connected_pair() -> (Socket, Socket) {
+        let listener = Socket::new(AddressFamily::Ipv4, SocketType::Stream, Protocol::Tcp).unwrap();
+        listener.bind(&SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0)).unwrap();
+        listener.listen(1).unwrap();
+        let client = Socket::new(AddressFamily::Ipv4, SocketType::Stream, Protocol::Tcp).unwrap();
+        // A non-blocking connect reports that it is in progress
+        let _ = client.connect(&listener.local_addr().unwrap());
+        for _ in 0..100 {
+            if let Ok((server, _)) = listener.accept() {
+                while client.peer_addr().is_err() {
+                    thread::sleep(Duration::from_millis(5));
+                }
+                return (client, server);
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("connection not accepted");
+    }
+
+    fn recv_exact(socket: &Socket, len: usize) -> Vec<u8> {
+        let mut received = Vec::new();
+        let mut buf = [0u8; 4096];
+        for _ in 0..200 {
+            if received.len() >= len {
+                break;
+            }
+            match socket.recv_vectored(&mut [&mut buf]) {
+                Ok(n) => received.extend_from_slice(&buf[..n]),
+                Err(SocketError::WouldBlock) => thread::sleep(Duration::from_millis(10)),
+                Err(e) => panic!("recv failed: {:?}", e),
+            }
+        }
+        received
+    }
+
+    fn temp_file(name: &str, contents: &[u8]) -> (std::path::PathBuf, File) {
+        let path = std::env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        let file = File::open(&path).unwrap();
+        (path, file)
+    }
+
+    #[test]
+    fn test_send_and_recv_vectored() {
+        let (client, server) = connected_pair();
+        let sent = client.send_vectored(&[b"GET ", b"", b"/index", b" HTTP/1.1"]).unwrap();
+        assert_eq!(sent, 19);
+
+        let mut head = [0u8; 4];
+        let mut rest = [0u8; 32];
+        let mut received = 0;
+        for _ in 0..100 {
+            match server.recv_vectored(&mut [&mut head, &mut rest]) {
+                Ok(n) => {
+                    received = n;
+                    break;
+                }
+                Err(SocketError::WouldBlock) => thread::sleep(Duration::from_millis(10)),
+                Err(e) => panic!("recv failed: {:?}", e),
+            }
+        }
+        assert_eq!(received, 19);
+        assert_eq!(&head, b"GET ");
+        assert_eq!(&rest[..15], b"/index HTTP/1.1");
+    }
+
+    #[test]
+    fn test_send_iolist() {
+        use entities_process::Process;
+        use infrastructure_nif_api::{enif_make_binary, enif_make_int, enif_make_list};
+        use std::sync::Arc;
+
+        let env = NifEnv::from_process(Arc::new(Process::new(41383)));
+        let iolist = enif_make_list(&env, &[
+            enif_make_binary(&env, b"GET".to_vec()),
+            enif_make_int(&env, b' ' as i32),
+            enif_make_list(&env, &[enif_make_binary(&env, b"/".to_vec())]),
+        ]);
+        let (client, server) = connected_pair();
+        assert_eq!(client.send_iolist(&env, iolist).unwrap(), 5);
+        assert_eq!(recv_exact(&server, 5), b"GET /");
+        assert!(matches!(client.send_iolist(&env, enif_make_int(&env, 1)), Err(SocketError::Other(_))));
+    }
+
+    #[test]
+    fn test_sendfile() {
+        let contents: Vec<u8> = (0..200_000u32).map(|i| i as u8).collect();
+        let (path, file) = temp_file("adapters_socket_sendfile", &contents);
+        let (client, server) = connected_pair();
+
+        let reader = thread::spawn(move || recv_exact(&server, 1000));
+        assert_eq!(client.sendfile(&file, 500, 1000).unwrap(), 1000);
+        assert_eq!(reader.join().unwrap(), &contents[500..1500]);
+
+        // Past the end of the file only the remaining bytes are sent
+        let (client, server) = connected_pair();
+        let reader = thread::spawn(move || recv_exact(&server, 100));
+        assert_eq!(client.sendfile(&file, 199_900, 1000).unwrap(), 100);
+        assert_eq!(reader.join().unwrap(), &contents[199_900..]);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_sendfile_fallback() {
+        let (path, file) = temp_file("adapters_socket_sendfile_fallback", b"0123456789");
+        let (client, server) = connected_pair();
+        assert_eq!(sendfile_fallback(&client, &file, 2, 5).unwrap(), 5);
+        assert_eq!(recv_exact(&server, 5), b"23456");
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_sendfile_requires_stream_socket() {
+        let (path, file) = temp_file("adapters_socket_sendfile_udp", b"x");
+        let socket = Socket::new(AddressFamily::Ipv4, SocketType::Datagram, Protocol::Udp).unwrap();
+        assert_eq!(socket.sendfile(&file, 0, 1), Err(SocketError::NotSupported));
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,936 @@
+//! Fd Driver Module (Unix-specific)
+//!
+//! Provides the `fd` port driver behind `open_port({fd, In, Out}, Options)`,
+//! which wraps existing file descriptors, and the standard I/O ports built on
+//! it. Group leaders use these ports for console I/O, so this module also
+//! handles the Erlang I/O protocol (`{io_request, From, ReplyAs, Request}`).
+//! Based on the fd driver in sys_drivers.c
+//!
+//! ## Input
+//!
+//! The input descriptor is switched to non-blocking mode and reads never
+//! wait. A request that needs more input than has been read is queued, and
+//! later requests queue behind it. When the pollset reports the descriptor
+//! readable, [`FdPort::ready_input`] reads what is available and answers the
+//! queued requests it completes. The descriptor is restored to blocking mode
+//! when the port is closed. Descriptors are never closed by the driver.
+//!
+//! ## Driver
+//!
+//! [`FdDriver`] is the `fd` port driver. Its ports are opened with the
+//! command `fd In Out`, where `-` stands for no descriptor, and select their
+//! input descriptor for reading in the global select table; the driver's
+//! `ready_input` callback then reads the input. Replies to I/O requests are
+//! handed to the reply handler installed with [`FdDriver::set_reply_handler`].
+//! [`open_standard_io`] opens the standard I/O port on descriptors 0 and 1.
+//!
+//! ## Encoding
+//!
+//! Character data in requests is translated between the request's encoding
+//! and the port's encoding. Unicode data that cannot be represented in
+//! latin1 is rejected with `{error, no_translation}`.
+
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::os::unix::io::RawFd;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+
+use infrastructure_driver_api::{
+    get_global_driver_registry, get_global_port_table, get_global_select_table, DriverEntry,
+    DriverError, DriverPort, DriverSelectFlags,
+};
+
+use crate::spawn_driver::PortData;
+use crate::sys_drivers::{nbio_stop_fd, FdData};
+
+/// Size of a single read from the input descriptor
+const READ_CHUNK_SIZE: usize = 1024;
+
+/// Name of the fd driver
+pub const FD_DRIVER_NAME: &str = "fd";
+
+/// Character encoding of an I/O device or request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoEncoding {
+    /// One byte per character
+    Latin1,
+    /// UTF-8
+    Unicode,
+}
+
+/// I/O device option (`io:getopts/1`, `io:setopts/2`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoOption {
+    /// `{binary, Bool}`: deliver input as binaries instead of lists
+    Binary(bool),
+    /// `{encoding, Encoding}`
+    Encoding(IoEncoding),
+}
+
+/// Request of the Erlang I/O protocol
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IoRequest {
+    /// `{put_chars, Encoding, Chars}`
+    PutChars {
+        /// Encoding of `chars`
+        encoding: IoEncoding,
+        /// Characters to write
+        chars: Vec<u8>,
+    },
+    /// `{get_line, Encoding, Prompt}`
+    GetLine {
+        /// Encoding of the prompt and the returned data
+        encoding: IoEncoding,
+        /// Prompt written before reading
+        prompt: Vec<u8>,
+    },
+    /// `{get_chars, Encoding, Prompt, N}`
+    GetChars {
+        /// Encoding of the prompt and the returned data
+        encoding: IoEncoding,
+        /// Prompt written before reading
+        prompt: Vec<u8>,
+        /// Number of characters to read
+        count: usize,
+    },
+    /// `getopts`
+    GetOpts,
+    /// `{setopts, Opts}`
+    SetOpts(Vec<IoOption>),
+    /// `{requests, Requests}`: executed in order until one fails
+    Requests(Vec<IoRequest>),
+}
+
+/// Reason of an `{error, Reason}` reply
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoErrorReason {
+    /// `no_translation`: characters cannot be represented in the target encoding
+    NoTranslation,
+    /// `request`: the request is not supported by the device
+    Request,
+    /// POSIX error from the underlying descriptor
+    Io(io::ErrorKind),
+}
+
+/// Reply of the Erlang I/O protocol
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IoReply {
+    /// `ok`
+    Ok,
+    /// Data read from the device
+    Data(PortData),
+    /// `eof`
+    Eof,
+    /// `{error, Reason}`
+    Error(IoErrorReason),
+    /// Current options (reply to `getopts`)
+    Opts(Vec<IoOption>),
+}
+
+/// `{io_request, From, ReplyAs, Request}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IoRequestMessage {
+    /// Requesting process
+    pub from: u64,
+    /// Reference identifying the reply
+    pub reply_as: u64,
+    /// Request
+    pub request: IoRequest,
+}
+
+/// `{io_reply, ReplyAs, Reply}`, sent to `to`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IoReplyMessage {
+    /// Process that made the request
+    pub to: u64,
+    /// Reference identifying the reply
+    pub reply_as: u64,
+    /// Reply
+    pub reply: IoReply,
+}
+
+/// Fd driver errors
+#[derive(Debug)]
+pub enum FdDriverError {
+    /// Operation on the descriptor failed
+    Io(io::Error),
+    /// The port has no descriptor in the required direction
+    NotOpen,
+}
+
+impl std::fmt::Display for FdDriverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FdDriverError::Io(err) => write!(f, "Fd driver I/O error: {}", err),
+            FdDriverError::NotOpen => write!(f, "Port is not open in this direction"),
+        }
+    }
+}
+
+impl std::error::Error for FdDriverError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FdDriverError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FdDriverError {
+    fn from(err: io::Error) -> Self {
+        FdDriverError::Io(err)
+    }
+}
+
+/// Outcome of executing a request
+enum Progress {
+    /// The request completed with a reply
+    Done(IoReply),
+    /// The request needs more input; holds what is left to execute
+    Wait(IoRequest),
+}
+
+/// Port wrapping existing file descriptors
+pub struct FdPort {
+    /// Input descriptor state (`None` if the port is output only)
+    input: Option<FdData>,
+    /// Output descriptor (`None` if the port is input only)
+    out_fd: Option<RawFd>,
+    /// Requests waiting for input, oldest first
+    waiting: VecDeque<IoRequestMessage>,
+    /// Data read but not yet consumed by a request
+    pending: Vec<u8>,
+    /// End of input has been reached
+    eof: bool,
+    /// Deliver data as binaries
+    binary: bool,
+    /// Encoding of the device
+    encoding: IoEncoding,
+}
+
+impl std::fmt::Debug for FdPort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FdPort")
+            .field("in_fd", &self.input.as_ref().map(|input| input.fd))
+            .field("out_fd", &self.out_fd)
+            .field("waiting", &self.waiting.len())
+            .field("pending", &self.pending.len())
+            .field("eof", &self.eof)
+            .field("binary", &self.binary)
+            .field("encoding", &self.encoding)
+            .finish()
+    }
+}
+
+impl FdPort {
+    /// Open a port on the given descriptors (`open_port({fd, In, Out}, [])`)
+    ///
+    /// # Arguments
+    /// * `in_fd` - Descriptor to read from, if any
+    /// * `out_fd` - Descriptor to write to, if any
+    pub fn open(in_fd: Option<RawFd>, out_fd: Option<RawFd>) -> Result<Self, FdDriverError> {
+        let input = match in_fd {
+            Some(fd) => {
+                set_nonblocking(fd)?;
+                Some(FdData::new(fd))
+            }
+            None => None,
+        };
+        Ok(Self {
+            input,
+            out_fd,
+            waiting: VecDeque::new(),
+            pending: Vec::new(),
+            eof: false,
+            binary: false,
+            encoding: IoEncoding::Latin1,
+        })
+    }
+
+    /// Open the port used by the `user` group leader (descriptors 0 and 1)
+    pub fn standard_io() -> Result<Self, FdDriverError> {
+        Self::open(Some(0), Some(1))
+    }
+
+    /// Open an output port on standard error (descriptor 2)
+    pub fn standard_error() -> Result<Self, FdDriverError> {
+        Self::open(None, Some(2))
+    }
+
+    /// Input descriptor, if the port reads
+    pub fn input_fd(&self) -> Option<RawFd> {
+        self.input.as_ref().map(|input| input.fd)
+    }
+
+    /// Check if the end of input has been reached
+    pub fn is_eof(&self) -> bool {
+        self.eof
+    }
+
+    /// Number of requests waiting for input
+    pub fn waiting(&self) -> usize {
+        self.waiting.len()
+    }
+
+    /// Current device options
+    pub fn options(&self) -> Vec<IoOption> {
+        vec![IoOption::Binary(self.binary), IoOption::Encoding(self.encoding)]
+    }
+
+    /// Write raw bytes to the output descriptor (`port_command/2`)
+    pub fn command(&mut self, data: &[u8]) -> Result<(), FdDriverError> {
+        let fd = self.out_fd.ok_or(FdDriverError::NotOpen)?;
+        write_all_fd(fd, data)?;
+        Ok(())
+    }
+
+    /// Handle an `{io_request, From, ReplyAs, Request}` message
+    ///
+    /// # Returns
+    /// * `Some(reply)` - The request completed
+    /// * `None` - The request waits for input; its reply is returned by
+    ///   [`FdPort::ready_input`] once the input has arrived
+    pub fn io_request(&mut self, message: IoRequestMessage) -> Option<IoReplyMessage> {
+        if !self.waiting.is_empty() {
+            self.waiting.push_back(message);
+            return None;
+        }
+        match self.execute(&message.request) {
+            Progress::Done(reply) => Some(reply_message(&message, reply)),
+            Progress::Wait(rest) => {
+                self.waiting.push_back(IoRequestMessage { request: rest, ..message });
+                None
+            }
+        }
+    }
+
+    /// Read the available input and complete the requests waiting for it
+    ///
+    /// Called when the pollset reports the input descriptor readable.
+    ///
+    /// # Returns
+    /// Replies to the completed requests, in request order
+    pub fn ready_input(&mut self) -> Vec<IoReplyMessage> {
+        let mut replies = Vec::new();
+        if let Err(err) = self.fill() {
+            // The descriptor failed; every waiting request fails with it
+            let kind = match err {
+                FdDriverError::Io(err) => err.kind(),
+                FdDriverError::NotOpen => io::ErrorKind::NotConnected,
+            };
+            for message in self.waiting.drain(..) {
+                replies.push(reply_message(&message, IoReply::Error(IoErrorReason::Io(kind))));
+            }
+            return replies;
+        }
+        while let Some(message) = self.waiting.front_mut() {
+            let request = std::mem::replace(&mut message.request, IoRequest::GetOpts);
+            match self.execute(&request) {
+                Progress::Done(reply) => {
+                    let message = self.waiting.pop_front().unwrap();
+                    replies.push(reply_message(&message, reply));
+                }
+                Progress::Wait(rest) => {
+                    self.waiting.front_mut().unwrap().request = rest;
+                    break;
+                }
+            }
+        }
+        replies
+    }
+
+    /// Execute a single I/O protocol request
+    fn execute(&mut self, request: &IoRequest) -> Progress {
+        match request {
+            IoRequest::PutChars { encoding, chars } => {
+                Progress::Done(match translate(chars, *encoding, self.encoding) {
+                    Some(bytes) => match self.command(&bytes) {
+                        Ok(()) => IoReply::Ok,
+                        Err(err) => error_reply(err),
+                    },
+                    None => IoReply::Error(IoErrorReason::NoTranslation),
+                })
+            }
+            IoRequest::GetLine { encoding, prompt } => {
+                match self.get(*encoding, prompt, |pending, _| {
+                    pending.iter().position(|&b| b == b'\n').map(|i| i + 1)
+                }) {
+                    Some(reply) => Progress::Done(reply),
+                    None => Progress::Wait(IoRequest::GetLine { encoding: *encoding, prompt: Vec::new() }),
+                }
+            }
+            IoRequest::GetChars { encoding, prompt, count } => {
+                let count = *count;
+                match self.get(*encoding, prompt, move |pending, device| {
+                    char_boundary(pending, device, count)
+                }) {
+                    Some(reply) => Progress::Done(reply),
+                    None => Progress::Wait(IoRequest::GetChars {
+                        encoding: *encoding,
+                        prompt: Vec::new(),
+                        count,
+                    }),
+                }
+            }
+            IoRequest::GetOpts => Progress::Done(IoReply::Opts(self.options())),
+            IoRequest::SetOpts(options) => {
+                for option in options {
+                    match option {
+                        IoOption::Binary(binary) => self.binary = *binary,
+                        IoOption::Encoding(encoding) => self.encoding = *encoding,
+                    }
+                }
+                Progress::Done(IoReply::Ok)
+            }
+            IoRequest::Requests(requests) => {
+                let mut reply = IoReply::Ok;
+                for (index, request) in requests.iter().enumerate() {
+                    match self.execute(request) {
+                        Progress::Done(done) => reply = done,
+                        Progress::Wait(rest) => {
+                            // Requests already executed are not executed again
+                            let mut remaining = vec![rest];
+                            remaining.extend_from_slice(&requests[index + 1..]);
+                            return Progress::Wait(IoRequest::Requests(remaining));
+                        }
+                    }
+                    if matches!(reply, IoReply::Error(_)) {
+                        break;
+                    }
+                }
+                Progress::Done(reply)
+            }
+        }
+    }
+
+    /// Close the port, restoring blocking mode on the input descriptor
+    pub fn close(mut self) {
+        self.stop_input();
+    }
+
+    /// Write a prompt, then take the data up to the end `complete` finds
+    ///
+    /// # Returns
+    /// The reply, or `None` if the data is not complete and the end of input
+    /// has not been reached
+    fn get<F>(&mut self, encoding: IoEncoding, prompt: &[u8], complete: F) -> Option<IoReply>
+    where
+        F: Fn(&[u8], IoEncoding) -> Option<usize>,
+    {
+        if self.input.is_none() {
+            return Some(IoReply::Error(IoErrorReason::Request));
+        }
+        if !prompt.is_empty() && self.out_fd.is_some() {
+            let Some(prompt) = translate(prompt, encoding, self.encoding) else {
+                return Some(IoReply::Error(IoErrorReason::NoTranslation));
+            };
+            if let Err(err) = self.command(&prompt) {
+                return Some(error_reply(err));
+            }
+        }
+
+        let end = match complete(&self.pending, self.encoding) {
+            Some(end) => end,
+            None if self.eof => self.pending.len(),
+            None => return None,
+        };
+        if end == 0 {
+            return Some(IoReply::Eof);
+        }
+
+        let data: Vec<u8> = self.pending.drain(..end).collect();
+        Some(match translate(&data, self.encoding, encoding) {
+            Some(data) => IoReply::Data(if self.binary {
+                PortData::Binary(data)
+            } else {
+                PortData::List(data)
+            }),
+            None => IoReply::Error(IoErrorReason::NoTranslation),
+        })
+    }
+
+    /// Append the available input to the pending data, without waiting
+    fn fill(&mut self) -> Result<(), FdDriverError> {
+        let fd = self.input.as_ref().ok_or(FdDriverError::NotOpen)?.fd;
+        while !self.eof {
+            match read_fd(fd, READ_CHUNK_SIZE) {
+                Ok(data) if data.is_empty() => self.eof = true,
+                Ok(data) => self.pending.extend_from_slice(&data),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(())
+    }
+
+    fn stop_input(&mut self) {
+        if let Some(mut input) = self.input.take() {
+            nbio_stop_fd(&mut input);
+        }
+    }
+}
+
+impl Drop for FdPort {
+    fn drop(&mut self) {
+        self.stop_input();
+    }
+}
+
+/// Map a driver error to an I/O protocol error reply
+fn error_reply(err: FdDriverError) -> IoReply {
+    match err {
+        FdDriverError::Io(err) => IoReply::Error(IoErrorReason::Io(err.kind())),
+        FdDriverError::NotOpen => IoReply::Error(IoErrorReason::Request),
+    }
+}
+
+/// Reply message answering `message`
+fn reply_message(message: &IoRequestMessage, reply: IoReply) -> IoReplyMessage {
+    IoReplyMessage {
+        to: message.from,
+        reply_as: message.reply_as,
+        reply,
+    }
+}
+
+/// Handler delivering I/O protocol replies to the requesting processes
+///
+/// Installed by the runtime integration layer with
+/// [`FdDriver::set_reply_handler`]. Without a handler replies are dropped.
+pub type IoReplyHandler = Arc<dyn Fn(IoReplyMessage) + Send + Sync>;
+
+/// The `fd` port driver
+///
+/// Keeps an [`FdPort`] for every port opened on the driver, keyed by port
+/// number.
+#[derive(Default)]
+pub struct FdDriver {
+    /// Fd ports by port number
+    ports: Mutex<HashMap<u64, FdPort>>,
+    /// Handler delivering replies
+    reply_handler: RwLock<Option<IoReplyHandler>>,
+}
+
+impl FdDriver {
+    /// Create the driver
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Install the handler delivering replies to I/O requests
+    pub fn set_reply_handler(&self, handler: IoReplyHandler) {
+        *self.reply_handler.write().unwrap() = Some(handler);
+    }
+
+    /// Handle an I/O request sent to a port opened on the driver
+    ///
+    /// The reply is delivered through the reply handler, at once or when the
+    /// input the request waits for has arrived.
+    ///
+    /// # Returns
+    /// * `Ok(())` - The request was handled or queued
+    /// * `Err(DriverError::PortClosed)` - The port is not open on the driver
+    pub fn io_request(&self, port: &DriverPort, message: IoRequestMessage) -> Result<(), DriverError> {
+        let reply = {
+            let mut ports = self.ports.lock().unwrap();
+            let fd_port = ports.get_mut(&port.id()).ok_or(DriverError::PortClosed)?;
+            fd_port.io_request(message)
+        };
+        if let Some(reply) = reply {
+            self.deliver(vec![reply]);
+        }
+        Ok(())
+    }
+
+    fn deliver(&self, replies: Vec<IoReplyMessage>) {
+        if replies.is_empty() {
+            return;
+        }
+        if let Some(handler) = self.reply_handler.read().unwrap().clone() {
+            replies.into_iter().for_each(|reply| handler(reply));
+        }
+    }
+}
+
+impl DriverEntry for FdDriver {
+    fn driver_name(&self) -> &str {
+        FD_DRIVER_NAME
+    }
+
+    fn start(&self, port: &DriverPort, command: &str) -> Result<(), DriverError> {
+        let (in_fd, out_fd) = parse_fd_command(command)
+            .ok_or_else(|| DriverError::Failed(format!("bad fd command: {}", command)))?;
+        let fd_port = FdPort::open(in_fd, out_fd).map_err(|err| DriverError::Failed(err.to_string()))?;
+        if let Some(fd) = in_fd {
+            let mode = DriverSelectFlags::Read as u32 | DriverSelectFlags::Use as u32;
+            get_global_select_table().driver_select(port, fd, mode, true)?;
+        }
+        self.ports.lock().unwrap().insert(port.id(), fd_port);
+        Ok(())
+    }
+
+    fn output(&self, port: &DriverPort, data: &[u8]) -> Result<(), DriverError> {
+        let mut ports = self.ports.lock().unwrap();
+        let fd_port = ports.get_mut(&port.id()).ok_or(DriverError::PortClosed)?;
+        fd_port.command(data).map_err(|err| DriverError::Failed(err.to_string()))
+    }
+
+    fn ready_input(&self, port: &DriverPort, event: i32) {
+        let (replies, eof) = {
+            let mut ports = self.ports.lock().unwrap();
+            let Some(fd_port) = ports.get_mut(&port.id()) else {
+                return;
+            };
+            (fd_port.ready_input(), fd_port.is_eof())
+        };
+        if eof {
+            // Nothing more will be read; the descriptor leaves the pollset
+            let _ = get_global_select_table().driver_select(port, event, DriverSelectFlags::Read as u32, false);
+        }
+        self.deliver(replies);
+    }
+
+    fn stop(&self, port: &DriverPort) {
+        get_global_select_table().port_closed(port);
+        if let Some(fd_port) = self.ports.lock().unwrap().remove(&port.id()) {
+            fd_port.close();
+        }
+    }
+}
+
+/// Parse the descriptors of an `fd In Out` command
+fn parse_fd_command(command: &str) -> Option<(Option<RawFd>, Option<RawFd>)> {
+    let fd = |word: &str| match word {
+        "-" => Some(None),
+        word => word.parse::<RawFd>().ok().filter(|fd| *fd >= 0).map(Some),
+    };
+    match command.split_whitespace().collect::<Vec<_>>()[..] {
+        [FD_DRIVER_NAME, in_fd, out_fd] => Some((fd(in_fd)?, fd(out_fd)?)),
+        _ => None,
+    }
+}
+
+/// Global fd driver instance
+static GLOBAL_FD_DRIVER: OnceLock<Arc<FdDriver>> = OnceLock::new();
+
+/// Get the global fd driver, loading it into the global driver registry
+pub fn get_global_fd_driver() -> &'static Arc<FdDriver> {
+    GLOBAL_FD_DRIVER.get_or_init(|| {
+        let driver = Arc::new(FdDriver::new());
+        // Only this function loads a driver under the name
+        let _ = get_global_driver_registry().add_driver_entry(driver.clone());
+        driver
+    })
+}
+
+/// Open the standard I/O port (`fd 0 1`) in the global port table
+pub fn open_standard_io() -> Result<Arc<DriverPort>, DriverError> {
+    get_global_fd_driver();
+    get_global_port_table().open_port(get_global_driver_registry(), "fd 0 1")
+}
+
+/// Open an output-only port on standard output (`fd - 1`) in the global
+/// port table, leaving standard input to its current reader
+pub fn open_standard_output() -> Result<Arc<DriverPort>, DriverError> {
+    get_global_fd_driver();
+    get_global_port_table().open_port(get_global_driver_registry(), "fd - 1")
+}
+
+/// Translate character data between encodings
+///
+/// Returns `None` if the data cannot be represented in `to`.
+pub fn translate(data: &[u8], from: IoEncoding, to: IoEncoding) -> Option<Vec<u8>> {
+    match (from, to) {
+        (IoEncoding::Latin1, IoEncoding::Latin1) | (IoEncoding::Unicode, IoEncoding::Unicode) => {
+            Some(data.to_vec())
+        }
+        (IoEncoding::Latin1, IoEncoding::Unicode) => {
+            Some(data.iter().map(|&b| b as char).collect::<String>().into_bytes())
+        }
+        (IoEncoding::Unicode, IoEncoding::Latin1) => std::str::from_utf8(data)
+            .ok()?
+            .chars()
+            .map(|c| u8::try_from(u32::from(c)).ok())
+            .collect(),
+    }
+}
+
+/// Byte length of the first `count` characters of `data`, if all are present
+fn char_boundary(data: &[u8], encoding: IoEncoding, count: usize) -> Option<usize> {
+    match encoding {
+        IoEncoding::Latin1 => (data.len() >= count).then_some(count),
+        IoEncoding::Unicode => {
+            let text = match std::str::from_utf8(data) {
+                Ok(text) => text,
+                // Trailing partial character: only count complete ones
+                Err(err) => std::str::from_utf8(&data[..err.valid_up_to()]).ok()?,
+            };
+            if count == 0 {
+                return Some(0);
+            }
+            text.char_indices()
+                .nth(count - 1)
+                .map(|(i, c)| i + c.len_utf8())
+        }
+    }
+}
+
+/// Put a descriptor in non-blocking mode
+fn set_nonblocking(fd: RawFd) -> io::Result<()> {
+    // Safety: fcntl on a caller-provided descriptor; errors are reported
+    unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFL);
+        if flags < 0 || libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Read up to `max` bytes from a descriptor
+fn read_fd(fd: RawFd, max: usize) -> io::Result<Vec<u8>> {
+    let mut buf = vec![0u8; max];
+    // Safety: buf is valid for writes of buf.len() bytes
+    let n = unsafe { libc::read(fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) };
+    if n < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    buf.truncate(n as usize);
+    Ok(buf)
+}
+
+/// Write all bytes to a descriptor, waiting if it is non-blocking and full
+fn write_all_fd(fd: RawFd, mut data: &[u8]) -> io::Result<()> {
+    while !data.is_empty() {
+        // Safety: data is valid for reads of data.len() bytes
+        let n = unsafe { libc::write(fd, data.as_ptr() as *const libc::c_void, data.len()) };
+        if n < 0 {
+            let err = io::Error::last_os_error();
+            match err.kind() {
+                io::ErrorKind::Interrupted => continue,
+                io::ErrorKind::WouldBlock => {
+                    let mut pollfd = libc::pollfd {
+                        fd,
+                        events: libc::POLLOUT,
+                        revents: 0,
+                    };
+                    // Safety: pollfd is a valid single-element array
+                    unsafe { libc::poll(&mut pollfd, 1, -1) };
+                    continue;
+                }
+                _ => return Err(err),
+            }
+        }
+        data = &data[n as usize..];
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use infrastructure_driver_api::{DriverRegistry, PortTable};
+    use std::io::{Read, Write};
+    use std::os::unix::io::AsRawFd;
+
+    fn put_chars(chars: &[u8]) -> IoRequest {
+        IoRequest::PutChars {
+            encoding: IoEncoding::Unicode,
+            chars: chars.to_vec(),
+        }
+    }
+
+    fn get_line() -> IoRequest {
+        IoRequest::GetLine {
+            encoding: IoEncoding::Latin1,
+            prompt: Vec::new(),
+        }
+    }
+
+    fn request(port: &mut FdPort, request: IoRequest) -> Option<IoReply> {
+        port.io_request(IoRequestMessage { from: 1, reply_as: 2, request })
+            .map(|message| message.reply)
+    }
+
+    fn replies(messages: Vec<IoReplyMessage>) -> Vec<IoReply> {
+        messages.into_iter().map(|message| message.reply).collect()
+    }
+
+    #[test]
+    fn test_translate() {
+        assert_eq!(translate(&[0xE5], IoEncoding::Latin1, IoEncoding::Unicode), Some("å".as_bytes().to_vec()));
+        assert_eq!(translate("å".as_bytes(), IoEncoding::Unicode, IoEncoding::Latin1), Some(vec![0xE5]));
+        assert_eq!(translate("€".as_bytes(), IoEncoding::Unicode, IoEncoding::Latin1), None);
+        assert_eq!(translate(b"abc", IoEncoding::Latin1, IoEncoding::Latin1), Some(b"abc".to_vec()));
+    }
+
+    #[test]
+    fn test_char_boundary() {
+        assert_eq!(char_boundary(b"abc", IoEncoding::Latin1, 2), Some(2));
+        assert_eq!(char_boundary(b"a", IoEncoding::Latin1, 2), None);
+        let text = "åäö".as_bytes();
+        assert_eq!(char_boundary(text, IoEncoding::Unicode, 2), Some(4));
+        assert_eq!(char_boundary(&text[..3], IoEncoding::Unicode, 2), None);
+    }
+
+    #[test]
+    fn test_parse_fd_command() {
+        assert_eq!(parse_fd_command("fd 0 1"), Some((Some(0), Some(1))));
+        assert_eq!(parse_fd_command("fd - 2"), Some((None, Some(2))));
+        assert_eq!(parse_fd_command("fd 0"), None);
+        assert_eq!(parse_fd_command("fd -1 1"), None);
+        assert_eq!(parse_fd_command("spawn 0 1"), None);
+    }
+
+    #[test]
+    fn test_put_chars_reaches_output() {
+        let (mut reader, writer) = io::pipe().unwrap();
+        let mut port = FdPort::open(None, Some(writer.as_raw_fd())).unwrap();
+        let reply = port.io_request(IoRequestMessage {
+            from: 10,
+            reply_as: 20,
+            request: put_chars(b"hello\n"),
+        });
+        assert_eq!(reply, Some(IoReplyMessage { to: 10, reply_as: 20, reply: IoReply::Ok }));
+        drop(port);
+        drop(writer);
+        let mut output = String::new();
+        reader.read_to_string(&mut output).unwrap();
+        assert_eq!(output, "hello\n");
+    }
+
+    #[test]
+    fn test_put_chars_no_translation() {
+        let (_reader, writer) = io::pipe().unwrap();
+        let mut port = FdPort::open(None, Some(writer.as_raw_fd())).unwrap();
+        assert_eq!(
+            request(&mut port, put_chars("€".as_bytes())),
+            Some(IoReply::Error(IoErrorReason::NoTranslation))
+        );
+        request(&mut port, IoRequest::SetOpts(vec![IoOption::Encoding(IoEncoding::Unicode)]));
+        assert_eq!(request(&mut port, put_chars("€".as_bytes())), Some(IoReply::Ok));
+    }
+
+    #[test]
+    fn test_get_line_and_eof() {
+        let (reader, mut writer) = io::pipe().unwrap();
+        let mut port = FdPort::open(Some(reader.as_raw_fd()), None).unwrap();
+        writer.write_all(b"first\nsecond").unwrap();
+        drop(writer);
+
+        // Nothing has been read before the descriptor is reported readable
+        assert_eq!(request(&mut port, get_line()), None);
+        assert_eq!(replies(port.ready_input()), vec![IoReply::Data(PortData::List(b"first\n".to_vec()))]);
+        request(&mut port, IoRequest::SetOpts(vec![IoOption::Binary(true)]));
+        assert_eq!(request(&mut port, get_line()), Some(IoReply::Data(PortData::Binary(b"second".to_vec()))));
+        assert_eq!(request(&mut port, get_line()), Some(IoReply::Eof));
+        port.close();
+    }
+
+    #[test]
+    fn test_get_chars_completes_on_ready_input() {
+        let (reader, mut writer) = io::pipe().unwrap();
+        let mut port = FdPort::open(Some(reader.as_raw_fd()), None).unwrap();
+        let get_chars = IoRequest::GetChars {
+            encoding: IoEncoding::Latin1,
+            prompt: Vec::new(),
+            count: 3,
+        };
+        assert_eq!(request(&mut port, get_chars), None);
+        // Later requests wait behind the first one
+        assert_eq!(request(&mut port, IoRequest::GetOpts), None);
+        assert_eq!(port.waiting(), 2);
+
+        writer.write_all(b"ab").unwrap();
+        assert!(port.ready_input().is_empty());
+        writer.write_all(b"cd").unwrap();
+        assert_eq!(
+            replies(port.ready_input()),
+            vec![
+                IoReply::Data(PortData::List(b"abc".to_vec())),
+                IoReply::Opts(vec![IoOption::Binary(false), IoOption::Encoding(IoEncoding::Latin1)]),
+            ]
+        );
+        assert_eq!(port.waiting(), 0);
+    }
+
+    #[test]
+    fn test_waiting_requests_not_executed_twice() {
+        let (reader, mut writer) = io::pipe().unwrap();
+        let (mut output, out_writer) = io::pipe().unwrap();
+        let mut port = FdPort::open(Some(reader.as_raw_fd()), Some(out_writer.as_raw_fd())).unwrap();
+        let requests = IoRequest::Requests(vec![
+            put_chars(b"a"),
+            IoRequest::GetLine { encoding: IoEncoding::Latin1, prompt: b"> ".to_vec() },
+            put_chars(b"b"),
+        ]);
+        assert_eq!(request(&mut port, requests), None);
+        writer.write_all(b"x").unwrap();
+        assert!(port.ready_input().is_empty());
+        writer.write_all(b"\n").unwrap();
+        assert_eq!(replies(port.ready_input()), vec![IoReply::Ok]);
+
+        drop(port);
+        drop(out_writer);
+        let mut written = String::new();
+        output.read_to_string(&mut written).unwrap();
+        assert_eq!(written, "a> b");
+    }
+
+    #[test]
+    fn test_requests_and_getopts() {
+        let (_reader, writer) = io::pipe().unwrap();
+        let mut port = FdPort::open(None, Some(writer.as_raw_fd())).unwrap();
+        let reply = request(&mut port, IoRequest::Requests(vec![
+            IoRequest::SetOpts(vec![IoOption::Binary(true)]),
+            IoRequest::GetOpts,
+        ]));
+        assert_eq!(
+            reply,
+            Some(IoReply::Opts(vec![IoOption::Binary(true), IoOption::Encoding(IoEncoding::Latin1)]))
+        );
+        // Reading from an output-only port is not supported
+        assert_eq!(request(&mut port, get_line()), Some(IoReply::Error(IoErrorReason::Request)));
+    }
+
+    #[test]
+    fn test_fd_driver_port() {
+        let driver = Arc::new(FdDriver::new());
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+        let sink = delivered.clone();
+        driver.set_reply_handler(Arc::new(move |reply| sink.lock().unwrap().push(reply)));
+        let drivers = DriverRegistry::new();
+        drivers.add_driver_entry(driver.clone()).unwrap();
+        let ports = PortTable::new();
+
+        let (reader, mut writer) = io::pipe().unwrap();
+        let (mut output, out_writer) = io::pipe().unwrap();
+        let fd = reader.as_raw_fd();
+        let command = format!("fd {} {}", fd, out_writer.as_raw_fd());
+        let port = ports.open_port(&drivers, &command).unwrap();
+        assert_eq!(get_global_select_table().selected_events(fd), Some(DriverSelectFlags::Read as u32));
+        assert!(ports.open_port(&drivers, "fd x 1").is_err());
+
+        port.output(b"out").unwrap();
+        let message = IoRequestMessage { from: 5, reply_as: 6, request: get_line() };
+        driver.io_request(&port, message).unwrap();
+        assert!(delivered.lock().unwrap().is_empty());
+        writer.write_all(b"in\n").unwrap();
+        assert!(port.ready_input(fd));
+        assert_eq!(
+            *delivered.lock().unwrap(),
+            vec![IoReplyMessage { to: 5, reply_as: 6, reply: IoReply::Data(PortData::List(b"in\n".to_vec())) }]
+        );
+
+        // Closing deselects the descriptor
+        ports.close_port(port.id());
+        assert!(get_global_select_table().is_stop_scheduled(fd));
+        get_global_select_table().pollset_removed(fd);
+        assert_eq!(get_global_select_table().selected_events(fd), None);
+        drop(out_writer);
+        let mut written = String::new();
+        output.read_to_string(&mut written).unwrap();
+        assert_eq!(written, "out");
+    }
+}
@@ -8,7 +8,8 @@
 //! The `adapters_system_integration_unix` crate is part of the adapters layer in
 //! the CLEAN architecture implementation of Erlang/OTP. It provides I/O adapters
 //! for Unix-specific system integration operations, including file descriptor
-//! management, pipe operations, and terminal window size queries.
+//! management, pipe operations, terminal window size queries, and spawning
+//! external programs as ports.
 //!
 //! ## Platform Support
 //!
@@ -22,6 +23,13 @@
 //!   system operations including file descriptor management, pipe operations,
 //!   terminal window size queries, and driver data management
 //!
+//! - **[`spawn_driver`](spawn_driver/index.html)**: Spawn driver for `open_port/2`,
+//!   running external programs with stdin/stdout piping, packet framing and
+//!   exit status reporting
+//!
+//! - **[`fd_driver`](fd_driver/index.html)**: Fd driver for `open_port({fd, In, Out}, _)`
+//!   and the standard I/O ports, handling the Erlang I/O protocol for group leaders
+//!
 //! ## Functions
 //!
 //! - `init_fd_data`: Initialize file descriptor data structure
@@ -38,11 +46,16 @@
 //!
 //! ## See Also
 //!
-//! - [`adapters_nif_io`](../adapters_nif_io/index.html): NIF I/O polling and event management
+//! - [`infrastructure_driver_api`](../infrastructure_driver_api/index.html): Driver API the
+//!   fd driver implements, and the select table its input descriptors are polled through
 //! - [`frameworks_system_integration_unix`](../../frameworks/frameworks_system_integration_unix/index.html): Unix system integration framework
 
 #[cfg(unix)]
 pub mod sys_drivers;
+#[cfg(unix)]
+pub mod spawn_driver;
+#[cfg(unix)]
+pub mod fd_driver;
 
 #[cfg(unix)]
 pub use sys_drivers::{
@@ -51,6 +64,19 @@ pub use sys_drivers::{
     clear_fd_data, nbio_stop_fd, fd_flush,
 };
 
+#[cfg(unix)]
+pub use spawn_driver::{
+    SpawnPort, SpawnOptions, SpawnError, PacketMode, PacketDecoder,
+    PortMessage, PortData, encode_packet,
+};
+
+#[cfg(unix)]
+pub use fd_driver::{
+    FdPort, FdDriver, FdDriverError, IoEncoding, IoOption, IoRequest, IoReply,
+    IoErrorReason, IoRequestMessage, IoReplyMessage, IoReplyHandler, FD_DRIVER_NAME,
+    get_global_fd_driver, open_standard_io, open_standard_output,
+};
+
 #[cfg(not(unix))]
 /// Unix-specific functionality is only available on Unix systems
 pub fn unix_only() {
@@ -0,0 +1,662 @@
+//! Spawn Driver Module (Unix-specific)
+//!
+//! Provides the spawn driver behind `open_port({spawn, Command}, Options)` and
+//! `open_port({spawn_executable, File}, Options)`: an external OS process whose
+//! stdin receives port output and whose stdout is delivered as port messages.
+//! Based on the spawn driver in sys_drivers.c
+//!
+//! ## Framing
+//!
+//! Data read from the program is split according to the packet option:
+//!
+//! - `stream`: every read is delivered as it arrives
+//! - `{packet, N}` (N = 1, 2 or 4): each message is preceded by an N-byte
+//!   big-endian length header, both on input and output
+//! - `{line, L}`: data is delivered line by line as `{eol, Line}`; lines longer
+//!   than `L` bytes are delivered in chunks as `{noeol, Chunk}`
+//!
+//! Partial packets are kept in an [`FdData`] between reads, as in the C driver.
+//!
+//! ## Exit Status
+//!
+//! With the `exit_status` option an `{exit_status, Status}` message is
+//! delivered after the program's output has been read to the end. A program
+//! killed by a signal reports `128 + Signal`.
+
+use std::io::{self, Read, Write};
+use std::os::unix::process::ExitStatusExt;
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::sys_drivers::{clear_fd_data, FdData};
+
+/// Size of the read buffer used for the program's stdout
+const READ_BUF_SIZE: usize = 4096;
+
+/// How long [`SpawnPort::close`] waits for the program to finish its output
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Interval at which the reader thread checks whether the program has
+/// exited after the end of its output
+const REAP_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Packet framing of a spawned port
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketMode {
+    /// No framing
+    Stream,
+    /// N-byte big-endian length header (1, 2 or 4)
+    Packet(u8),
+    /// Line oriented input with the given maximum line length
+    Line(usize),
+}
+
+/// Options for spawning a port program
+///
+/// Mirrors the `open_port/2` options handled by the spawn driver.
+#[derive(Debug, Clone)]
+pub struct SpawnOptions {
+    /// Program arguments (`{args, Args}`)
+    pub args: Vec<String>,
+    /// Extra environment variables (`{env, Env}`)
+    pub env: Vec<(String, String)>,
+    /// Start the program with only the variables in `env` instead of
+    /// inheriting the emulator's environment
+    pub clear_env: bool,
+    /// Working directory (`{cd, Dir}`)
+    pub cd: Option<PathBuf>,
+    /// Packet framing
+    pub packet: PacketMode,
+    /// Deliver data as binaries instead of lists (`binary`)
+    pub binary: bool,
+    /// Deliver `{exit_status, Status}` when the program exits (`exit_status`)
+    pub exit_status: bool,
+    /// Redirect the program's stderr to its stdout (`stderr_to_stdout`)
+    pub stderr_to_stdout: bool,
+}
+
+impl Default for SpawnOptions {
+    fn default() -> Self {
+        Self {
+            args: Vec::new(),
+            env: Vec::new(),
+            clear_env: false,
+            cd: None,
+            packet: PacketMode::Stream,
+            binary: false,
+            exit_status: false,
+            stderr_to_stdout: false,
+        }
+    }
+}
+
+/// Data delivered by a port
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortData {
+    /// Data as a binary (`binary` option)
+    Binary(Vec<u8>),
+    /// Data as a list of bytes (default)
+    List(Vec<u8>),
+}
+
+impl PortData {
+    fn new(data: Vec<u8>, binary: bool) -> Self {
+        if binary {
+            PortData::Binary(data)
+        } else {
+            PortData::List(data)
+        }
+    }
+
+    /// Get the bytes of the data
+    pub fn bytes(&self) -> &[u8] {
+        match self {
+            PortData::Binary(data) | PortData::List(data) => data,
+        }
+    }
+}
+
+/// Message delivered by a spawned port to its owner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortMessage {
+    /// `{Port, {data, Data}}`
+    Data(PortData),
+    /// `{Port, {data, {eol | noeol, Line}}}` in line mode
+    Line {
+        /// `true` for a complete line (`eol`), `false` for a chunk (`noeol`)
+        eol: bool,
+        /// Line contents without the newline
+        data: PortData,
+    },
+    /// `{Port, {exit_status, Status}}`
+    ExitStatus(i32),
+}
+
+/// Spawn driver errors
+#[derive(Debug)]
+pub enum SpawnError {
+    /// The program could not be started or an I/O operation failed
+    Io(io::Error),
+    /// Invalid packet header size (must be 1, 2 or 4)
+    BadPacketSize(u8),
+    /// Data is too large for the packet header
+    PacketTooLarge(usize),
+    /// The port has been closed
+    Closed,
+}
+
+impl std::fmt::Display for SpawnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpawnError::Io(err) => write!(f, "Spawn driver I/O error: {}", err),
+            SpawnError::BadPacketSize(size) => write!(f, "Invalid packet size: {}", size),
+            SpawnError::PacketTooLarge(len) => {
+                write!(f, "Data of {} bytes does not fit the packet header", len)
+            }
+            SpawnError::Closed => write!(f, "Port is closed"),
+        }
+    }
+}
+
+impl std::error::Error for SpawnError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SpawnError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SpawnError {
+    fn from(err: io::Error) -> Self {
+        SpawnError::Io(err)
+    }
+}
+
+/// Splits the program's output into port messages
+///
+/// Equivalent to the input handling of the spawn driver (`ready_input` in
+/// sys_drivers.c).
+#[derive(Debug)]
+pub struct PacketDecoder {
+    mode: PacketMode,
+    binary: bool,
+    /// Partial packet state (header bytes in `pbuf`, body in `buf`)
+    fd_data: FdData,
+    /// Partial line in line mode
+    line: Vec<u8>,
+}
+
+impl PacketDecoder {
+    /// Create a decoder for the given framing
+    pub fn new(mode: PacketMode, binary: bool) -> Result<Self, SpawnError> {
+        if let PacketMode::Packet(size) = mode {
+            if !matches!(size, 1 | 2 | 4) {
+                return Err(SpawnError::BadPacketSize(size));
+            }
+        }
+        Ok(Self {
+            mode,
+            binary,
+            fd_data: FdData::new(-1),
+            line: Vec::new(),
+        })
+    }
+
+    /// Feed bytes read from the program, returning the complete messages
+    pub fn decode(&mut self, mut input: &[u8]) -> Vec<PortMessage> {
+        let mut messages = Vec::new();
+        match self.mode {
+            PacketMode::Stream => {
+                if !input.is_empty() {
+                    messages.push(PortMessage::Data(PortData::new(input.to_vec(), self.binary)));
+                }
+            }
+            PacketMode::Packet(size) => {
+                let size = size as usize;
+                while !input.is_empty() {
+                    let fd_data = &mut self.fd_data;
+                    if fd_data.buf.is_none() {
+                        // Collect the length header
+                        let take = (size - fd_data.psz).min(input.len());
+                        fd_data.pbuf[fd_data.psz..fd_data.psz + take].copy_from_slice(&input[..take]);
+                        fd_data.psz += take;
+                        input = &input[take..];
+                        if fd_data.psz < size {
+                            break;
+                        }
+                        let len = fd_data.pbuf[..size]
+                            .iter()
+                            .fold(0usize, |len, &b| (len << 8) | b as usize);
+                        // The length comes from the program: the buffer
+                        // grows with the data actually received
+                        fd_data.buf = Some(Vec::with_capacity(len.min(READ_BUF_SIZE)));
+                        fd_data.sz = len;
+                        fd_data.remain = len;
+                        fd_data.buf_offset = 0;
+                    }
+                    let take = fd_data.remain.min(input.len());
+                    if let Some(buf) = fd_data.buf.as_mut() {
+                        buf.extend_from_slice(&input[..take]);
+                    }
+                    fd_data.buf_offset += take;
+                    fd_data.remain -= take;
+                    input = &input[take..];
+                    if fd_data.remain == 0 {
+                        let data = fd_data.buf.take().unwrap_or_default();
+                        clear_fd_data(fd_data);
+                        messages.push(PortMessage::Data(PortData::new(data, self.binary)));
+                    }
+                }
+            }
+            PacketMode::Line(max) => {
+                for &byte in input {
+                    if byte == b'\n' {
+                        let line = std::mem::take(&mut self.line);
+                        messages.push(self.line_message(true, line));
+                    } else {
+                        // A full line is only a chunk once more data follows
+                        // it, so a line of exactly `max` bytes is an `eol`
+                        if self.line.len() >= max {
+                            let chunk = std::mem::take(&mut self.line);
+                            messages.push(self.line_message(false, chunk));
+                        }
+                        self.line.push(byte);
+                    }
+                }
+            }
+        }
+        messages
+    }
+
+    /// Flush a partial line at end of input
+    ///
+    /// Only line mode delivers incomplete data at end of input (as `noeol`);
+    /// an incomplete packet is discarded.
+    pub fn finish(&mut self) -> Option<PortMessage> {
+        clear_fd_data(&mut self.fd_data);
+        if self.line.is_empty() {
+            return None;
+        }
+        let line = std::mem::take(&mut self.line);
+        Some(self.line_message(false, line))
+    }
+
+    fn line_message(&self, eol: bool, data: Vec<u8>) -> PortMessage {
+        PortMessage::Line {
+            eol,
+            data: PortData::new(data, self.binary),
+        }
+    }
+}
+
+/// Prefix data with its packet header
+///
+/// Equivalent to the output handling of the spawn driver (`fd_async` /
+/// `output` in sys_drivers.c). Stream and line mode output is not framed.
+pub fn encode_packet(mode: PacketMode, data: &[u8]) -> Result<Vec<u8>, SpawnError> {
+    let PacketMode::Packet(size) = mode else {
+        return Ok(data.to_vec());
+    };
+    let max = match size {
+        1 => u8::MAX as usize,
+        2 => u16::MAX as usize,
+        4 => u32::MAX as usize,
+        _ => return Err(SpawnError::BadPacketSize(size)),
+    };
+    if data.len() > max {
+        return Err(SpawnError::PacketTooLarge(data.len()));
+    }
+    let header = (data.len() as u32).to_be_bytes();
+    let mut packet = Vec::with_capacity(size as usize + data.len());
+    packet.extend_from_slice(&header[4 - size as usize..]);
+    packet.extend_from_slice(data);
+    Ok(packet)
+}
+
+/// Port running an external program
+///
+/// Output written with [`command`](SpawnPort::command) goes to the
+/// program's stdin; the program's stdout is read by a reader thread and
+/// delivered as [`PortMessage`]s.
+///
+/// Dropping the port kills the program if it is still running and reaps it.
+#[derive(Debug)]
+pub struct SpawnPort {
+    os_pid: u32,
+    packet: PacketMode,
+    stdin: Option<ChildStdin>,
+    messages: Receiver<PortMessage>,
+    /// Shared with the reader thread, which reaps the program after the
+    /// end of its output
+    child: Arc<Mutex<Child>>,
+}
+
+impl SpawnPort {
+    /// Spawn a program
+    ///
+    /// # Arguments
+    /// * `program` - Executable to run (looked up in `PATH`)
+    /// * `options` - Spawn options
+    pub fn spawn(program: &str, options: &SpawnOptions) -> Result<Self, SpawnError> {
+        let mut decoder = PacketDecoder::new(options.packet, options.binary)?;
+
+        // The program's stdout (and stderr with `stderr_to_stdout`) is a
+        // pipe read by the reader thread.
+        let (mut output, output_writer) = io::pipe()?;
+        let stderr = if options.stderr_to_stdout {
+            Stdio::from(output_writer.try_clone()?)
+        } else {
+            Stdio::inherit()
+        };
+
+        let mut command = Command::new(program);
+        if options.clear_env {
+            command.env_clear();
+        }
+        command
+            .args(&options.args)
+            .envs(options.env.iter().map(|(k, v)| (k, v)))
+            .stdin(Stdio::piped())
+            .stdout(output_writer)
+            .stderr(stderr);
+        if let Some(cd) = &options.cd {
+            command.current_dir(cd);
+        }
+        let mut child = command.spawn()?;
+        // Drop the parent's copies of the write end so that end of output
+        // is seen when the program exits
+        drop(command);
+
+        let os_pid = child.id();
+        let stdin = child.stdin.take();
+        let exit_status = options.exit_status;
+        let child = Arc::new(Mutex::new(child));
+        let reaped = Arc::clone(&child);
+
+        let (sender, messages) = mpsc::channel();
+        // The reader is never joined: it ends with the program's output
+        thread::spawn(move || {
+            let mut buf = [0u8; READ_BUF_SIZE];
+            loop {
+                match output.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(n) => {
+                        for message in decoder.decode(&buf[..n]) {
+                            if sender.send(message).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(_) => break,
+                }
+            }
+            if let Some(message) = decoder.finish() {
+                let _ = sender.send(message);
+            }
+            let status = wait_exit_status(&reaped);
+            if exit_status {
+                let _ = sender.send(PortMessage::ExitStatus(status));
+            }
+        });
+
+        Ok(Self {
+            os_pid,
+            packet: options.packet,
+            stdin,
+            messages,
+            child,
+        })
+    }
+
+    /// OS process id of the program (`erlang:port_info(Port, os_pid)`)
+    pub fn os_pid(&self) -> u32 {
+        self.os_pid
+    }
+
+    /// Send data to the program (`port_command/2`)
+    pub fn command(&mut self, data: &[u8]) -> Result<(), SpawnError> {
+        let packet = encode_packet(self.packet, data)?;
+        let stdin = self.stdin.as_mut().ok_or(SpawnError::Closed)?;
+        stdin.write_all(&packet)?;
+        stdin.flush()?;
+        Ok(())
+    }
+
+    /// Close the program's stdin, signalling end of input
+    pub fn close_output(&mut self) {
+        self.stdin = None;
+    }
+
+    /// Take the next message if one is available
+    pub fn try_recv(&self) -> Option<PortMessage> {
+        match self.messages.try_recv() {
+            Ok(message) => Some(message),
+            Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => None,
+        }
+    }
+
+    /// Wait up to `timeout` for the next message
+    ///
+    /// Returns `None` on timeout or when the program has exited and all its
+    /// messages have been delivered.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<PortMessage> {
+        match self.messages.recv_timeout(timeout) {
+            Ok(message) => Some(message),
+            Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => None,
+        }
+    }
+
+    /// Close the port and collect the remaining messages
+    ///
+    /// Closes the program's stdin and waits up to five seconds for it to
+    /// finish writing its output. A program still running afterwards is
+    /// killed.
+    pub fn close(self) -> Vec<PortMessage> {
+        self.close_within(CLOSE_TIMEOUT)
+    }
+
+    fn close_within(mut self, timeout: Duration) -> Vec<PortMessage> {
+        self.close_output();
+        let deadline = Instant::now() + timeout;
+        let mut messages = Vec::new();
+        while let Ok(message) = self
+            .messages
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        {
+            messages.push(message);
+        }
+        messages
+    }
+}
+
+impl Drop for SpawnPort {
+    fn drop(&mut self) {
+        self.stdin = None;
+        let mut child = self.child.lock().unwrap_or_else(|err| err.into_inner());
+        if let Ok(None) = child.try_wait() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Wait for the program and convert its status as the spawn driver reports it
+///
+/// The lock is only held while checking, so dropping the port can kill a
+/// program that closed its output but keeps running.
+fn wait_exit_status(child: &Mutex<Child>) -> i32 {
+    loop {
+        let status = child.lock().unwrap_or_else(|err| err.into_inner()).try_wait();
+        match status {
+            Ok(Some(status)) => {
+                return match (status.code(), status.signal()) {
+                    (Some(code), _) => code,
+                    (None, Some(signal)) => 128 + signal,
+                    (None, None) => -1,
+                };
+            }
+            Ok(None) => thread::sleep(REAP_INTERVAL),
+            Err(_) => return -1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(data: &[u8]) -> PortMessage {
+        PortMessage::Data(PortData::List(data.to_vec()))
+    }
+
+    #[test]
+    fn test_decode_stream() {
+        let mut decoder = PacketDecoder::new(PacketMode::Stream, true).unwrap();
+        assert_eq!(
+            decoder.decode(b"abc"),
+            vec![PortMessage::Data(PortData::Binary(b"abc".to_vec()))]
+        );
+        assert!(decoder.decode(b"").is_empty());
+    }
+
+    #[test]
+    fn test_decode_packet_split_across_reads() {
+        let mut decoder = PacketDecoder::new(PacketMode::Packet(2), false).unwrap();
+        assert!(decoder.decode(&[0]).is_empty());
+        assert!(decoder.decode(&[3, b'a']).is_empty());
+        assert_eq!(decoder.decode(b"bc\x00\x01x\x00"), vec![list(b"abc"), list(b"x")]);
+        assert_eq!(decoder.decode(&[0]), vec![list(b"")]);
+    }
+
+    #[test]
+    fn test_decode_packet_4() {
+        let mut decoder = PacketDecoder::new(PacketMode::Packet(4), false).unwrap();
+        let input = encode_packet(PacketMode::Packet(4), b"hello").unwrap();
+        assert_eq!(input[..4], [0, 0, 0, 5]);
+        assert_eq!(decoder.decode(&input), vec![list(b"hello")]);
+    }
+
+    #[test]
+    fn test_decode_line() {
+        let mut decoder = PacketDecoder::new(PacketMode::Line(4), false).unwrap();
+        let messages = decoder.decode(b"ab\nabcdef\nwxyz\ngh");
+        let expected: Vec<(bool, &[u8])> =
+            vec![(true, b"ab"), (false, b"abcd"), (true, b"ef"), (true, b"wxyz")];
+        assert_eq!(messages.len(), expected.len());
+        for (message, (eol, data)) in messages.iter().zip(expected) {
+            assert_eq!(
+                *message,
+                PortMessage::Line { eol, data: PortData::List(data.to_vec()) }
+            );
+        }
+        assert_eq!(
+            decoder.finish(),
+            Some(PortMessage::Line { eol: false, data: PortData::List(b"gh".to_vec()) })
+        );
+        assert_eq!(decoder.finish(), None);
+    }
+
+    #[test]
+    fn test_decode_packet_grows_with_data() {
+        let mut decoder = PacketDecoder::new(PacketMode::Packet(4), false).unwrap();
+        assert!(decoder.decode(&[0xff, 0xff, 0xff, 0xff, b'a']).is_empty());
+        let buf = decoder.fd_data.buf.as_ref().unwrap();
+        assert_eq!(buf.as_slice(), b"a");
+        assert!(buf.capacity() <= READ_BUF_SIZE);
+        assert_eq!(decoder.fd_data.remain, u32::MAX as usize - 1);
+    }
+
+    #[test]
+    fn test_bad_packet_size() {
+        assert!(matches!(
+            PacketDecoder::new(PacketMode::Packet(3), false),
+            Err(SpawnError::BadPacketSize(3))
+        ));
+        assert!(matches!(
+            encode_packet(PacketMode::Packet(1), &[0; 256]),
+            Err(SpawnError::PacketTooLarge(256))
+        ));
+    }
+
+    #[test]
+    fn test_spawn_cat_packet_roundtrip() {
+        let options = SpawnOptions {
+            packet: PacketMode::Packet(2),
+            binary: true,
+            exit_status: true,
+            ..SpawnOptions::default()
+        };
+        let mut port = SpawnPort::spawn("cat", &options).unwrap();
+        assert!(port.os_pid() > 0);
+        port.command(b"ping").unwrap();
+        assert_eq!(
+            port.recv_timeout(Duration::from_secs(5)),
+            Some(PortMessage::Data(PortData::Binary(b"ping".to_vec())))
+        );
+        assert_eq!(port.close(), vec![PortMessage::ExitStatus(0)]);
+    }
+
+    #[test]
+    fn test_spawn_exit_status() {
+        let options = SpawnOptions {
+            args: vec!["-c".to_string(), "echo out; exit 3".to_string()],
+            packet: PacketMode::Line(80),
+            exit_status: true,
+            ..SpawnOptions::default()
+        };
+        let port = SpawnPort::spawn("sh", &options).unwrap();
+        assert_eq!(
+            port.close(),
+            vec![
+                PortMessage::Line { eol: true, data: PortData::List(b"out".to_vec()) },
+                PortMessage::ExitStatus(3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_spawn_clear_env() {
+        std::env::set_var("SPAWN_DRIVER_INHERITED", "inherited");
+        let options = SpawnOptions {
+            args: vec!["-c".to_string(), "echo \"$GIVEN:$SPAWN_DRIVER_INHERITED\"".to_string()],
+            env: vec![("GIVEN".to_string(), "given".to_string())],
+            clear_env: true,
+            binary: true,
+            ..SpawnOptions::default()
+        };
+        let port = SpawnPort::spawn("/bin/sh", &options).unwrap();
+        assert_eq!(
+            port.close(),
+            vec![PortMessage::Data(PortData::Binary(b"given:\n".to_vec()))]
+        );
+    }
+
+    #[test]
+    fn test_spawn_missing_program() {
+        let result = SpawnPort::spawn("/nonexistent/program", &SpawnOptions::default());
+        assert!(matches!(result, Err(SpawnError::Io(_))));
+    }
+
+    #[test]
+    fn test_close_kills_running_program() {
+        let options = SpawnOptions {
+            args: vec!["-c".to_string(), "exec sleep 30 </dev/null >/dev/null".to_string()],
+            exit_status: true,
+            ..SpawnOptions::default()
+        };
+        let port = SpawnPort::spawn("sh", &options).unwrap();
+        let os_pid = port.os_pid() as libc::pid_t;
+        let started = Instant::now();
+        assert_eq!(port.close_within(Duration::from_millis(50)), vec![]);
+        assert!(started.elapsed() < Duration::from_secs(5));
+        // Killed and reaped: no zombie is left behind
+        assert_eq!(unsafe { libc::kill(os_pid, 0) }, -1);
+        assert_eq!(io::Error::last_os_error().raw_os_error(), Some(libc::ESRCH));
+    }
+}
@@ -46,3 +46,68 @@ fn test_unix_only_placeholder() {
     // Should not panic
 }
 
+
+#[test]
+#[cfg(unix)]
+fn test_spawn_port_stderr_to_stdout_stream() {
+    let options = SpawnOptions {
+        args: vec!["-c".to_string(), "echo err 1>&2".to_string()],
+        binary: true,
+        stderr_to_stdout: true,
+        ..SpawnOptions::default()
+    };
+    let port = SpawnPort::spawn("sh", &options).unwrap();
+    let data: Vec<u8> = port
+        .close()
+        .iter()
+        .flat_map(|message| match message {
+            PortMessage::Data(data) => data.bytes().to_vec(),
+            other => panic!("unexpected message {:?}", other),
+        })
+        .collect();
+    assert_eq!(data, b"err\n".to_vec());
+}
+
+#[test]
+#[cfg(unix)]
+fn test_spawn_port_packet_echo_with_env() {
+    let options = SpawnOptions {
+        args: vec!["-c".to_string(), "printf '\\000\\003'; printf \"$GREETING\"".to_string()],
+        env: vec![("GREETING".to_string(), "hey".to_string())],
+        packet: PacketMode::Packet(2),
+        exit_status: true,
+        ..SpawnOptions::default()
+    };
+    let port = SpawnPort::spawn("sh", &options).unwrap();
+    assert_eq!(
+        port.close(),
+        vec![
+            PortMessage::Data(PortData::List(b"hey".to_vec())),
+            PortMessage::ExitStatus(0),
+        ]
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn test_fd_port_io_request_get_line() {
+    use std::io::Write;
+    let (reader, mut writer) = std::io::pipe().unwrap();
+    writer.write_all(b"hello\nworld").unwrap();
+    drop(writer);
+
+    let in_fd = std::os::unix::io::AsRawFd::as_raw_fd(&reader);
+    let mut port = FdPort::open(Some(in_fd), None).unwrap();
+    let request = IoRequestMessage {
+        from: 1,
+        reply_as: 7,
+        request: IoRequest::GetLine { encoding: IoEncoding::Latin1, prompt: Vec::new() },
+    };
+    // The reply is ready once the input has been read
+    assert_eq!(port.io_request(request), None);
+    let reply = port.ready_input().pop().unwrap();
+    assert_eq!(reply.to, 1);
+    assert_eq!(reply.reply_as, 7);
+    assert_eq!(reply.reply, IoReply::Data(PortData::List(b"hello\n".to_vec())));
+    port.close();
+}
@@ -0,0 +1,549 @@
+//! BIF Timer Module
+//!
+//! Provides the timer service behind `erlang:send_after/3,4`,
+//! `erlang:start_timer/3,4`, `erlang:cancel_timer/1,2` and
+//! `erlang:read_timer/1,2`.
+//!
+//! ## Overview
+//!
+//! Timers are kept in a [`TimerWheel`] with millisecond ticks, so millions
+//! of concurrent timers can be created and cancelled cheaply. No thread is
+//! dedicated to the timers: schedulers ask [`BifTimers::next_timeout`] how
+//! long they may sleep, call [`BifTimers::bump`] when they wake up, and
+//! deliver the returned [`TimerEvent`]s. A wakeup callback registered with
+//! [`BifTimers::set_wakeup`] is invoked whenever a new timer expires before
+//! every existing one, so a sleeping scheduler can shorten its sleep.
+//!
+//! The service is generic over the destination `D` (a pid or registered
+//! name) and the message `M`; turning events into Erlang messages is left
+//! to the caller:
+//!
+//! - `send_after` timers deliver `Msg`
+//! - `start_timer` timers deliver `{timeout, TimerRef, Msg}`
+//! - asynchronous cancel/read requests deliver
+//!   `{cancel_timer, TimerRef, Result}` / `{read_timer, TimerRef, Result}`
+//!
+//! ## Cancel/fire races
+//!
+//! A synchronous `cancel_timer` that returns `false` only says the timer is
+//! gone; on its own it does not say whether the timeout message has reached
+//! the mailbox yet. Schedulers that deliver with [`BifTimers::bump_with`]
+//! do so while holding the timer lock, and [`BifTimers::cancel_timer_and_flush`]
+//! flushes under the same lock. A timer is therefore either cancelled before
+//! it fires, and its message never arrives, or its message is already in the
+//! mailbox and can be removed. The caller never sees a message arrive after
+//! the flush.
+//!
+//! ## Examples
+//!
+//! ```rust
+//! use adapters_time_management::bif_timer::{BifTimers, TimerEvent, TimerOptions};
+//!
+//! let timers: BifTimers<u32, &str> = BifTimers::new();
+//! let now = timers.now_ms();
+//! let timer_ref = timers.send_after(now + 5, 1, "hello", TimerOptions { abs: true });
+//!
+//! let events = timers.bump_until(now + 5);
+//! assert!(matches!(&events[0], TimerEvent::Timeout { timer_ref: r, .. } if *r == timer_ref));
+//! ```
+//!
+//! Based on `erl_hl_timer.c`
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::timer_wheel::{TimerId, TimerWheel};
+
+/// Reference identifying a BIF timer
+pub type TimerRef = TimerId;
+
+/// Kind of BIF timer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerKind {
+    /// Created by `erlang:send_after`; delivers the message as is
+    SendAfter,
+    /// Created by `erlang:start_timer`; delivers `{timeout, TimerRef, Msg}`
+    StartTimer,
+}
+
+/// Options for `send_after/4` and `start_timer/4`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimerOptions {
+    /// Time is an absolute time in the service time base ([`BifTimers::now_ms`])
+    /// instead of a relative timeout
+    pub abs: bool,
+}
+
+/// Options for `cancel_timer/2`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CancelTimerOptions {
+    /// Deliver the result as a message instead of returning it
+    pub asynchronous: bool,
+    /// Report the remaining time; otherwise the result is `ok`
+    pub info: bool,
+}
+
+impl Default for CancelTimerOptions {
+    fn default() -> Self {
+        Self {
+            asynchronous: false,
+            info: true,
+        }
+    }
+}
+
+/// Options for `read_timer/2`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReadTimerOptions {
+    /// Deliver the result as a message instead of returning it
+    pub asynchronous: bool,
+}
+
+/// Result of `cancel_timer` and `read_timer`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerReply {
+    /// Milliseconds left until the timer would have expired
+    Remaining(u64),
+    /// The timer was not found (already expired or cancelled): `false`
+    NotFound,
+    /// The request was handled without information, or asynchronously: `ok`
+    Ok,
+}
+
+/// Something a scheduler must deliver after a timer operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimerEvent<D, M> {
+    /// A timer expired
+    Timeout {
+        /// Reference of the expired timer
+        timer_ref: TimerRef,
+        /// Kind of timer, deciding the shape of the delivered message
+        kind: TimerKind,
+        /// Destination of the message
+        dest: D,
+        /// Message payload
+        msg: M,
+    },
+    /// Result of an asynchronous `cancel_timer`
+    CancelResult {
+        /// Process that requested the cancellation
+        to: D,
+        /// Reference of the timer
+        timer_ref: TimerRef,
+        /// Remaining time, or `NotFound`
+        result: TimerReply,
+    },
+    /// Result of an asynchronous `read_timer`
+    ReadResult {
+        /// Process that requested the read
+        to: D,
+        /// Reference of the timer
+        timer_ref: TimerRef,
+        /// Remaining time, or `NotFound`
+        result: TimerReply,
+    },
+}
+
+/// A pending BIF timer
+#[derive(Debug)]
+struct BifTimer<D, M> {
+    kind: TimerKind,
+    dest: D,
+    msg: M,
+}
+
+/// Timer state guarded by the service lock
+struct TimerState<D, M> {
+    wheel: TimerWheel<BifTimer<D, M>>,
+    /// Replies to asynchronous requests waiting to be delivered
+    replies: Vec<TimerEvent<D, M>>,
+}
+
+/// Callback used to wake a sleeping scheduler
+pub type WakeupFn = Box<dyn Fn(Duration) + Send + Sync>;
+
+/// BIF timer service
+pub struct BifTimers<D, M> {
+    start: Instant,
+    state: Mutex<TimerState<D, M>>,
+    wakeup: Mutex<Option<WakeupFn>>,
+}
+
+impl<D, M> Default for BifTimers<D, M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D, M> BifTimers<D, M> {
+    /// Create an empty timer service
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            state: Mutex::new(TimerState {
+                wheel: TimerWheel::new(0),
+                replies: Vec::new(),
+            }),
+            wakeup: Mutex::new(None),
+        }
+    }
+
+    /// Current time of the service in milliseconds since it was created
+    ///
+    /// This is the time base for absolute timer times.
+    pub fn now_ms(&self) -> u64 {
+        self.start.elapsed().as_millis() as u64
+    }
+
+    /// Register the callback invoked when a timer becomes the earliest one
+    ///
+    /// The callback receives the time until that timer expires.
+    pub fn set_wakeup(&self, wakeup: WakeupFn) {
+        *self.wakeup.lock().unwrap() = Some(wakeup);
+    }
+
+    /// Number of active timers
+    pub fn active_timers(&self) -> usize {
+        self.state.lock().unwrap().wheel.len()
+    }
+
+    /// Start a timer that sends `msg` to `dest` (`erlang:send_after/4`)
+    pub fn send_after(&self, time: u64, dest: D, msg: M, options: TimerOptions) -> TimerRef {
+        self.create_timer(TimerKind::SendAfter, time, dest, msg, options)
+    }
+
+    /// Start a timer that sends `{timeout, TimerRef, msg}` to `dest`
+    /// (`erlang:start_timer/4`)
+    pub fn start_timer(&self, time: u64, dest: D, msg: M, options: TimerOptions) -> TimerRef {
+        self.create_timer(TimerKind::StartTimer, time, dest, msg, options)
+    }
+
+    /// Cancel a timer (`erlang:cancel_timer/2`)
+    ///
+    /// With `asynchronous` set, the result is queued as a
+    /// [`TimerEvent::CancelResult`] for `caller` (only when `info` is set)
+    /// and `TimerReply::Ok` is returned.
+    pub fn cancel_timer(
+        &self,
+        timer_ref: TimerRef,
+        caller: D,
+        options: CancelTimerOptions,
+    ) -> TimerReply {
+        self.cancel_timer_and_flush(timer_ref, caller, options, |_| false).0
+    }
+
+    /// Cancel a timer and flush its message if it already fired
+    ///
+    /// Behaves like [`cancel_timer`](Self::cancel_timer). If the timer is no
+    /// longer active, `flush` is called with the timer reference while the
+    /// timer lock is held, so no delivery by [`bump_with`](Self::bump_with)
+    /// can happen concurrently. `flush` should remove the timer's message
+    /// from the caller's mailbox, as the Erlang idiom
+    /// `receive {timeout, TRef, _} -> ok after 0 -> ok end` does.
+    ///
+    /// Returns the cancel reply and whether `flush` removed a message.
+    pub fn cancel_timer_and_flush<F>(
+        &self,
+        timer_ref: TimerRef,
+        caller: D,
+        options: CancelTimerOptions,
+        flush: F,
+    ) -> (TimerReply, bool)
+    where
+        F: FnOnce(TimerRef) -> bool,
+    {
+        let now = self.now_ms();
+        let mut state = self.state.lock().unwrap();
+        let (result, flushed) = match state.wheel.cancel(timer_ref) {
+            Some((expiry, _)) => (TimerReply::Remaining(expiry.saturating_sub(now)), false),
+            None => (TimerReply::NotFound, flush(timer_ref)),
+        };
+        if !options.info {
+            return (TimerReply::Ok, flushed);
+        }
+        if options.asynchronous {
+            state.replies.push(TimerEvent::CancelResult {
+                to: caller,
+                timer_ref,
+                result,
+            });
+            return (TimerReply::Ok, flushed);
+        }
+        (result, flushed)
+    }
+
+    /// Read the time left on a timer (`erlang:read_timer/2`)
+    ///
+    /// With `asynchronous` set, the result is queued as a
+    /// [`TimerEvent::ReadResult`] for `caller` and `TimerReply::Ok` is
+    /// returned.
+    pub fn read_timer(&self, timer_ref: TimerRef, caller: D, options: ReadTimerOptions) -> TimerReply {
+        let now = self.now_ms();
+        let mut state = self.state.lock().unwrap();
+        let result = match state.wheel.expiry(timer_ref) {
+            Some(expiry) => TimerReply::Remaining(expiry.saturating_sub(now)),
+            None => TimerReply::NotFound,
+        };
+        if options.asynchronous {
+            state.replies.push(TimerEvent::ReadResult {
+                to: caller,
+                timer_ref,
+                result,
+            });
+            return TimerReply::Ok;
+        }
+        result
+    }
+
+    /// Time until the next timer needs attention
+    ///
+    /// Schedulers use this as their maximum sleep time. Returns
+    /// `Some(Duration::ZERO)` if work is already pending and `None` if there
+    /// is nothing to wait for.
+    pub fn next_timeout(&self) -> Option<Duration> {
+        let now = self.now_ms();
+        let state = self.state.lock().unwrap();
+        if !state.replies.is_empty() {
+            return Some(Duration::ZERO);
+        }
+        state
+            .wheel
+            .next_expiry()
+            .map(|tick| Duration::from_millis(tick.saturating_sub(now)))
+    }
+
+    /// Expire timers up to the current time (`erts_bump_timers()`)
+    ///
+    /// Returns the events to deliver: expired timers in expiry order,
+    /// followed by pending asynchronous replies.
+    pub fn bump(&self) -> Vec<TimerEvent<D, M>> {
+        self.bump_until(self.now_ms())
+    }
+
+    /// Expire timers up to the current time and deliver them with `deliver`
+    ///
+    /// Unlike [`bump`](Self::bump), events are handed to `deliver` while the
+    /// timer lock is held, which makes firing and delivery atomic with
+    /// respect to [`cancel_timer_and_flush`](Self::cancel_timer_and_flush).
+    /// `deliver` must not call back into this timer service.
+    pub fn bump_with<F>(&self, deliver: F)
+    where
+        F: FnMut(TimerEvent<D, M>),
+    {
+        self.bump_until_with(self.now_ms(), deliver)
+    }
+
+    /// Expire timers up to `now_ms` and deliver them with `deliver`
+    ///
+    /// See [`bump_with`](Self::bump_with).
+    pub fn bump_until_with<F>(&self, now_ms: u64, deliver: F)
+    where
+        F: FnMut(TimerEvent<D, M>),
+    {
+        let mut state = self.state.lock().unwrap();
+        Self::expire(&mut state, now_ms).into_iter().for_each(deliver);
+    }
+
+    /// Expire timers up to `now_ms` in the service time base
+    ///
+    /// Used by schedulers that have already read the clock.
+    pub fn bump_until(&self, now_ms: u64) -> Vec<TimerEvent<D, M>> {
+        let mut state = self.state.lock().unwrap();
+        Self::expire(&mut state, now_ms)
+    }
+
+    /// Advance the wheel and collect expired timers and pending replies
+    fn expire(state: &mut TimerState<D, M>, now_ms: u64) -> Vec<TimerEvent<D, M>> {
+        let mut events: Vec<TimerEvent<D, M>> = state
+            .wheel
+            .advance_to(now_ms)
+            .into_iter()
+            .map(|(timer_ref, timer)| TimerEvent::Timeout {
+                timer_ref,
+                kind: timer.kind,
+                dest: timer.dest,
+                msg: timer.msg,
+            })
+            .collect();
+        events.append(&mut state.replies);
+        events
+    }
+
+    fn create_timer(
+        &self,
+        kind: TimerKind,
+        time: u64,
+        dest: D,
+        msg: M,
+        options: TimerOptions,
+    ) -> TimerRef {
+        let now = self.now_ms();
+        let expiry = if options.abs { time } else { now.saturating_add(time) };
+        let (timer_ref, earliest) = {
+            let mut state = self.state.lock().unwrap();
+            let previous = state.wheel.next_expiry();
+            let timer_ref = state.wheel.insert(expiry, BifTimer { kind, dest, msg });
+            (timer_ref, previous.is_none_or(|p| expiry < p))
+        };
+        if earliest {
+            if let Some(wakeup) = self.wakeup.lock().unwrap().as_ref() {
+                wakeup(Duration::from_millis(expiry.saturating_sub(now)));
+            }
+        }
+        timer_ref
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_send_after_fires() {
+        let timers: BifTimers<u32, &str> = BifTimers::new();
+        let now = timers.now_ms();
+        let r = timers.send_after(now + 100, 7, "msg", TimerOptions { abs: true });
+        assert!(timers.bump_until(now + 99).is_empty());
+        let events = timers.bump_until(now + 100);
+        assert_eq!(
+            events,
+            vec![TimerEvent::Timeout {
+                timer_ref: r,
+                kind: TimerKind::SendAfter,
+                dest: 7,
+                msg: "msg"
+            }]
+        );
+        assert_eq!(timers.active_timers(), 0);
+    }
+
+    #[test]
+    fn test_start_timer_kind() {
+        let timers: BifTimers<u32, u32> = BifTimers::new();
+        let now = timers.now_ms();
+        timers.start_timer(now + 1, 1, 2, TimerOptions { abs: true });
+        match &timers.bump_until(now + 1)[0] {
+            TimerEvent::Timeout { kind, .. } => assert_eq!(*kind, TimerKind::StartTimer),
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_cancel_timer_sync() {
+        let timers: BifTimers<u32, ()> = BifTimers::new();
+        let r = timers.send_after(60_000, 1, (), TimerOptions::default());
+        match timers.cancel_timer(r, 1, CancelTimerOptions::default()) {
+            TimerReply::Remaining(ms) => assert!(ms > 59_000 && ms <= 60_000),
+            other => panic!("unexpected reply {:?}", other),
+        }
+        assert_eq!(
+            timers.cancel_timer(r, 1, CancelTimerOptions::default()),
+            TimerReply::NotFound
+        );
+        let no_info = CancelTimerOptions {
+            asynchronous: false,
+            info: false,
+        };
+        assert_eq!(timers.cancel_timer(r, 1, no_info), TimerReply::Ok);
+    }
+
+    #[test]
+    fn test_cancel_timer_async() {
+        let timers: BifTimers<u32, ()> = BifTimers::new();
+        let r = timers.send_after(60_000, 1, (), TimerOptions::default());
+        let options = CancelTimerOptions {
+            asynchronous: true,
+            info: true,
+        };
+        assert_eq!(timers.cancel_timer(r, 9, options), TimerReply::Ok);
+        assert_eq!(timers.next_timeout(), Some(Duration::ZERO));
+        let events = timers.bump();
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            TimerEvent::CancelResult { to, timer_ref, result } => {
+                assert_eq!(*to, 9);
+                assert_eq!(*timer_ref, r);
+                assert!(matches!(result, TimerReply::Remaining(_)));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_read_timer() {
+        let timers: BifTimers<u32, ()> = BifTimers::new();
+        let now = timers.now_ms();
+        let r = timers.send_after(now + 5_000, 1, (), TimerOptions { abs: true });
+        assert!(matches!(
+            timers.read_timer(r, 1, ReadTimerOptions::default()),
+            TimerReply::Remaining(ms) if ms <= 5_000
+        ));
+        assert_eq!(
+            timers.read_timer(r, 3, ReadTimerOptions { asynchronous: true }),
+            TimerReply::Ok
+        );
+        assert!(matches!(
+            timers.bump_until(now).as_slice(),
+            [TimerEvent::ReadResult { to: 3, .. }]
+        ));
+        timers.bump_until(now + 5_000);
+        assert_eq!(
+            timers.read_timer(r, 1, ReadTimerOptions::default()),
+            TimerReply::NotFound
+        );
+    }
+
+    #[test]
+    fn test_wakeup_on_earlier_timer() {
+        let timers: BifTimers<u32, ()> = BifTimers::new();
+        let wakeups = Arc::new(AtomicUsize::new(0));
+        let counter = wakeups.clone();
+        timers.set_wakeup(Box::new(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }));
+        timers.send_after(10_000, 1, (), TimerOptions::default());
+        timers.send_after(20_000, 1, (), TimerOptions::default());
+        timers.send_after(1_000, 1, (), TimerOptions::default());
+        assert_eq!(wakeups.load(Ordering::SeqCst), 2);
+        assert!(timers.next_timeout().unwrap() <= Duration::from_millis(1_000));
+    }
+
+    #[test]
+    fn test_next_timeout_empty() {
+        let timers: BifTimers<u32, ()> = BifTimers::new();
+        assert_eq!(timers.next_timeout(), None);
+    }
+
+    #[test]
+    fn test_cancel_timer_and_flush() {
+        let timers: BifTimers<u32, u32> = BifTimers::new();
+        let now = timers.now_ms();
+        let r = timers.start_timer(now + 10, 1, 5, TimerOptions { abs: true });
+        let mut mailbox = Vec::new();
+        timers.bump_until_with(now + 10, |event| mailbox.push(event));
+        assert_eq!(mailbox.len(), 1);
+
+        // The timer already fired: cancel returns false and the message is flushed
+        let (reply, flushed) = timers.cancel_timer_and_flush(r, 1, CancelTimerOptions::default(), |tref| {
+            let before = mailbox.len();
+            mailbox.retain(|e| !matches!(e, TimerEvent::Timeout { timer_ref, .. } if *timer_ref == tref));
+            mailbox.len() < before
+        });
+        assert_eq!(reply, TimerReply::NotFound);
+        assert!(flushed);
+        assert!(mailbox.is_empty());
+    }
+
+    #[test]
+    fn test_cancel_active_timer_does_not_flush() {
+        let timers: BifTimers<u32, u32> = BifTimers::new();
+        let r = timers.send_after(60_000, 1, 5, TimerOptions::default());
+        let (reply, flushed) = timers.cancel_timer_and_flush(r, 1, CancelTimerOptions::default(), |_| {
+            panic!("flush must not run for an active timer")
+        });
+        assert!(matches!(reply, TimerReply::Remaining(_)));
+        assert!(!flushed);
+    }
+}
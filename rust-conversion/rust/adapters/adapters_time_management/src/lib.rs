@@ -18,9 +18,16 @@
 //! - **[`timeslice`](timeslice/index.html)**: Time slice management for controlling
 //!   process execution time and scheduling fairness
 //!
+//! - **[`timer_wheel`](timer_wheel/index.html)**: Hierarchical timer wheel for storing
+//!   large numbers of timers with constant-time insertion and cancellation
+//!
+//! - **[`bif_timer`](bif_timer/index.html)**: BIF timer service for `send_after`,
+//!   `start_timer`, `cancel_timer` and `read_timer`, driven by scheduler wakeups
+//!
 //! ## Architecture
 //!
-//! This crate is based on the C implementation in `timer_drv.c` and `consume_timeslice_drv.c`.
+//! This crate is based on the C implementation in `timer_drv.c`, `consume_timeslice_drv.c`
+//! and `erl_hl_timer.c`.
 //! It depends on the Entities layer for fundamental data types.
 //!
 //! ## See Also
@@ -30,7 +37,14 @@
 
 pub mod timer;
 pub mod timeslice;
+pub mod timer_wheel;
+pub mod bif_timer;
 
 pub use timer::Timer;
 pub use timeslice::TimeSlice;
+pub use timer_wheel::{TimerId, TimerWheel};
+pub use bif_timer::{
+    BifTimers, CancelTimerOptions, ReadTimerOptions, TimerEvent, TimerKind, TimerOptions,
+    TimerRef, TimerReply,
+};
 
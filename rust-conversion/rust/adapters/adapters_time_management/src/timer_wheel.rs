@@ -0,0 +1,407 @@
+//! Timer Wheel Module
+//!
+//! Provides a hierarchical timer wheel for storing large numbers of timers
+//! with cheap insertion, cancellation and expiry.
+//!
+//! ## Overview
+//!
+//! Time is measured in integer ticks. The wheel has [`WHEEL_LEVELS`] levels
+//! of [`WHEEL_SLOTS`] slots each; a timer is stored at the lowest level whose
+//! slot range still covers its expiry time. When time reaches the start of a
+//! higher-level slot, its timers are cascaded down to finer levels, and
+//! timers in a level-0 slot fire when time reaches that tick.
+//!
+//! - **Insertion** is O(1).
+//! - **Cancellation** is O(1): the timer is dropped from the entry table
+//!   and its slot entry is discarded lazily when the slot is processed.
+//! - **Advancing** jumps straight to the next slot that holds timers using
+//!   per-level occupancy bitmaps, so idle periods cost nothing.
+//!
+//! Timers further away than the wheel covers are parked in an overflow list
+//! and re-inserted whenever time crosses a top-level boundary.
+//!
+//! ## Examples
+//!
+//! ```rust
+//! use adapters_time_management::timer_wheel::TimerWheel;
+//!
+//! let mut wheel = TimerWheel::new(0);
+//! let id = wheel.insert(10, "ten");
+//! wheel.insert(300, "three hundred");
+//!
+//! assert_eq!(wheel.next_expiry(), Some(10));
+//! let fired = wheel.advance_to(100);
+//! assert_eq!(fired, vec![(id, "ten")]);
+//! assert_eq!(wheel.len(), 1);
+//! ```
+//!
+//! Based on the timer wheel in `erl_time_sup.c` and `erl_hl_timer.c`
+
+use std::collections::HashMap;
+
+/// Bits of the tick consumed by each wheel level
+const SLOT_BITS: u32 = 8;
+
+/// Number of slots per wheel level
+pub const WHEEL_SLOTS: usize = 1 << SLOT_BITS;
+
+/// Number of wheel levels
+pub const WHEEL_LEVELS: usize = 5;
+
+/// Mask selecting a slot index
+const SLOT_MASK: u64 = (WHEEL_SLOTS as u64) - 1;
+
+/// Ticks covered by the whole wheel
+const WHEEL_SPAN_BITS: u32 = SLOT_BITS * WHEEL_LEVELS as u32;
+
+/// Words in a slot occupancy bitmap
+const BITMAP_WORDS: usize = WHEEL_SLOTS / 64;
+
+/// Identifier of a timer in a wheel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TimerId(pub u64);
+
+/// A timer stored in the wheel
+#[derive(Debug)]
+struct Entry<T> {
+    expiry: u64,
+    payload: T,
+}
+
+/// One level of the wheel
+#[derive(Debug)]
+struct Level {
+    slots: Vec<Vec<TimerId>>,
+    occupied: [u64; BITMAP_WORDS],
+}
+
+impl Level {
+    fn new() -> Self {
+        Self {
+            slots: (0..WHEEL_SLOTS).map(|_| Vec::new()).collect(),
+            occupied: [0; BITMAP_WORDS],
+        }
+    }
+
+    fn push(&mut self, slot: usize, id: TimerId) {
+        self.slots[slot].push(id);
+        self.occupied[slot / 64] |= 1 << (slot % 64);
+    }
+
+    fn take(&mut self, slot: usize) -> Vec<TimerId> {
+        self.occupied[slot / 64] &= !(1 << (slot % 64));
+        std::mem::take(&mut self.slots[slot])
+    }
+
+    /// First occupied slot strictly after `slot`
+    fn next_occupied_after(&self, slot: usize) -> Option<usize> {
+        let start = slot + 1;
+        if start >= WHEEL_SLOTS {
+            return None;
+        }
+        let mut word = start / 64;
+        let mut bits = self.occupied[word] & (!0u64 << (start % 64));
+        loop {
+            if bits != 0 {
+                return Some(word * 64 + bits.trailing_zeros() as usize);
+            }
+            word += 1;
+            if word >= BITMAP_WORDS {
+                return None;
+            }
+            bits = self.occupied[word];
+        }
+    }
+}
+
+/// Hierarchical timer wheel
+///
+/// Stores timers with an arbitrary payload `T` keyed by expiry tick.
+#[derive(Debug)]
+pub struct TimerWheel<T> {
+    current: u64,
+    next_id: u64,
+    levels: Vec<Level>,
+    entries: HashMap<TimerId, Entry<T>>,
+    /// Timers that were already due when inserted or cascaded
+    due: Vec<TimerId>,
+    /// Timers beyond the range covered by the wheel levels
+    overflow: Vec<TimerId>,
+}
+
+impl<T> TimerWheel<T> {
+    /// Create an empty wheel whose current time is `now`
+    pub fn new(now: u64) -> Self {
+        Self {
+            current: now,
+            next_id: 1,
+            levels: (0..WHEEL_LEVELS).map(|_| Level::new()).collect(),
+            entries: HashMap::new(),
+            due: Vec::new(),
+            overflow: Vec::new(),
+        }
+    }
+
+    /// Current time of the wheel
+    pub fn now(&self) -> u64 {
+        self.current
+    }
+
+    /// Number of active timers
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check whether the wheel has no active timers
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Insert a timer expiring at tick `expiry`
+    ///
+    /// Timers with an expiry at or before the current time fire on the next
+    /// call to [`Self::advance_to`].
+    pub fn insert(&mut self, expiry: u64, payload: T) -> TimerId {
+        let id = TimerId(self.next_id);
+        self.next_id += 1;
+        self.entries.insert(id, Entry { expiry, payload });
+        self.place(id, expiry);
+        id
+    }
+
+    /// Cancel a timer, returning its expiry and payload if it was active
+    pub fn cancel(&mut self, id: TimerId) -> Option<(u64, T)> {
+        self.entries.remove(&id).map(|e| (e.expiry, e.payload))
+    }
+
+    /// Expiry tick of an active timer
+    pub fn expiry(&self, id: TimerId) -> Option<u64> {
+        self.entries.get(&id).map(|e| e.expiry)
+    }
+
+    /// Payload of an active timer
+    pub fn get(&self, id: TimerId) -> Option<&T> {
+        self.entries.get(&id).map(|e| &e.payload)
+    }
+
+    /// Earliest tick at which [`Self::advance_to`] has work to do
+    ///
+    /// This is exact for timers in level 0 and a lower bound otherwise
+    /// (the tick at which a higher-level slot is cascaded), which makes it
+    /// suitable as a sleep deadline for schedulers. Returns `None` when no
+    /// timers are pending.
+    pub fn next_expiry(&self) -> Option<u64> {
+        if !self.due.is_empty() {
+            return Some(self.current);
+        }
+        // Each level only holds slots of the current block of the level
+        // above it, so the first occupied slot found from the bottom up is
+        // the earliest one
+        for (level_no, level) in self.levels.iter().enumerate() {
+            let shift = SLOT_BITS * level_no as u32;
+            let slot = ((self.current >> shift) & SLOT_MASK) as usize;
+            if let Some(next) = level.next_occupied_after(slot) {
+                let block = (self.current >> (shift + SLOT_BITS)) << (shift + SLOT_BITS);
+                return Some(block | ((next as u64) << shift));
+            }
+        }
+        if self.overflow.is_empty() {
+            None
+        } else {
+            Some(((self.current >> WHEEL_SPAN_BITS) + 1) << WHEEL_SPAN_BITS)
+        }
+    }
+
+    /// Advance time to `now` and return all timers that expired
+    ///
+    /// Timers are returned in expiry order. Time never moves backwards;
+    /// advancing to an earlier tick only fires already-due timers.
+    pub fn advance_to(&mut self, now: u64) -> Vec<(TimerId, T)> {
+        let mut fired = Vec::new();
+        self.fire_due(&mut fired);
+        while let Some(tick) = self.next_expiry() {
+            if tick > now {
+                break;
+            }
+            if tick > self.current {
+                self.current = tick;
+                self.process_tick();
+            }
+            self.fire_due(&mut fired);
+        }
+        self.current = self.current.max(now);
+        fired
+    }
+
+    /// Store a timer in the slot matching its expiry
+    fn place(&mut self, id: TimerId, expiry: u64) {
+        if expiry <= self.current {
+            self.due.push(id);
+            return;
+        }
+        for level_no in 0..WHEEL_LEVELS {
+            let shift = SLOT_BITS * (level_no as u32 + 1);
+            if expiry >> shift == self.current >> shift {
+                let slot = ((expiry >> (SLOT_BITS * level_no as u32)) & SLOT_MASK) as usize;
+                self.levels[level_no].push(slot, id);
+                return;
+            }
+        }
+        self.overflow.push(id);
+    }
+
+    /// Cascade and expire the slots that start at the current tick
+    fn process_tick(&mut self) {
+        let tick = self.current;
+        if tick & ((1 << WHEEL_SPAN_BITS) - 1) == 0 {
+            for id in std::mem::take(&mut self.overflow) {
+                self.replace(id);
+            }
+        }
+        for level_no in (1..WHEEL_LEVELS).rev() {
+            let shift = SLOT_BITS * level_no as u32;
+            if tick & ((1 << shift) - 1) == 0 {
+                let slot = ((tick >> shift) & SLOT_MASK) as usize;
+                for id in self.levels[level_no].take(slot) {
+                    self.replace(id);
+                }
+            }
+        }
+        let slot = (tick & SLOT_MASK) as usize;
+        let expired = self.levels[0].take(slot);
+        self.due.extend(expired);
+    }
+
+    /// Re-place a timer after a cascade, dropping cancelled ones
+    fn replace(&mut self, id: TimerId) {
+        if let Some(expiry) = self.entries.get(&id).map(|e| e.expiry) {
+            self.place(id, expiry);
+        }
+    }
+
+    /// Move all due timers into `fired`, ordered by expiry
+    fn fire_due(&mut self, fired: &mut Vec<(TimerId, T)>) {
+        let mut due: Vec<(u64, TimerId)> = std::mem::take(&mut self.due)
+            .into_iter()
+            .filter_map(|id| self.entries.get(&id).map(|e| (e.expiry, id)))
+            .collect();
+        due.sort_unstable();
+        for (_, id) in due {
+            if let Some(entry) = self.entries.remove(&id) {
+                fired.push((id, entry.payload));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fire_in_order() {
+        let mut wheel = TimerWheel::new(0);
+        wheel.insert(30, 3);
+        wheel.insert(10, 1);
+        wheel.insert(20, 2);
+        let fired: Vec<i32> = wheel.advance_to(25).into_iter().map(|(_, p)| p).collect();
+        assert_eq!(fired, vec![1, 2]);
+        assert_eq!(wheel.now(), 25);
+        let fired: Vec<i32> = wheel.advance_to(30).into_iter().map(|(_, p)| p).collect();
+        assert_eq!(fired, vec![3]);
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn test_does_not_fire_early() {
+        let mut wheel = TimerWheel::new(0);
+        wheel.insert(1000, ());
+        assert!(wheel.advance_to(999).is_empty());
+        assert_eq!(wheel.advance_to(1000).len(), 1);
+    }
+
+    #[test]
+    fn test_cascade_across_levels() {
+        let mut wheel = TimerWheel::new(5);
+        let expiries = [6u64, 255, 256, 257, 65_535, 65_536, 70_000, 16_777_300, 4_294_967_296];
+        for &e in &expiries {
+            wheel.insert(e, e);
+        }
+        let mut fired = Vec::new();
+        for (_, e) in wheel.advance_to(u64::from(u32::MAX) * 2) {
+            fired.push(e);
+        }
+        assert_eq!(fired, expiries.to_vec());
+    }
+
+    #[test]
+    fn test_each_timer_fires_at_its_tick() {
+        let mut wheel = TimerWheel::new(0);
+        let expiries = [1u64, 256, 300, 65_536, 65_537, 131_072 + 5];
+        for &e in &expiries {
+            wheel.insert(e, e);
+        }
+        for &e in &expiries {
+            assert!(wheel.advance_to(e - 1).is_empty(), "{} fired early", e);
+            let fired = wheel.advance_to(e);
+            assert_eq!(fired.len(), 1);
+            assert_eq!(fired[0].1, e);
+        }
+    }
+
+    #[test]
+    fn test_cancel() {
+        let mut wheel = TimerWheel::new(0);
+        let a = wheel.insert(100, "a");
+        let b = wheel.insert(70_000, "b");
+        assert_eq!(wheel.expiry(a), Some(100));
+        assert_eq!(wheel.cancel(a), Some((100, "a")));
+        assert_eq!(wheel.cancel(a), None);
+        assert_eq!(wheel.cancel(b), Some((70_000, "b")));
+        assert!(wheel.advance_to(1_000_000).is_empty());
+    }
+
+    #[test]
+    fn test_already_due() {
+        let mut wheel = TimerWheel::new(50);
+        wheel.insert(10, ());
+        assert_eq!(wheel.next_expiry(), Some(50));
+        assert_eq!(wheel.advance_to(50).len(), 1);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut wheel = TimerWheel::new(0);
+        let far = (1u64 << WHEEL_SPAN_BITS) * 3 + 17;
+        wheel.insert(far, ());
+        assert_eq!(wheel.next_expiry(), Some(1 << WHEEL_SPAN_BITS));
+        assert!(wheel.advance_to(far - 1).is_empty());
+        assert_eq!(wheel.advance_to(far).len(), 1);
+    }
+
+    #[test]
+    fn test_next_expiry() {
+        let mut wheel = TimerWheel::<()>::new(0);
+        assert_eq!(wheel.next_expiry(), None);
+        wheel.insert(42, ());
+        assert_eq!(wheel.next_expiry(), Some(42));
+        wheel.insert(3, ());
+        assert_eq!(wheel.next_expiry(), Some(3));
+    }
+
+    #[test]
+    fn test_many_timers() {
+        let mut wheel = TimerWheel::new(0);
+        let count = 200_000u64;
+        let mut ids = Vec::new();
+        for i in 0..count {
+            ids.push(wheel.insert((i * 7919) % 1_000_000 + 1, i));
+        }
+        for id in ids.iter().step_by(2) {
+            wheel.cancel(*id);
+        }
+        let fired = wheel.advance_to(1_000_000);
+        assert_eq!(fired.len() as u64, count / 2);
+        assert!(wheel.is_empty());
+    }
+}
@@ -75,3 +75,100 @@ fn test_timeslice_operations() {
     // Note: Check if timeslice module has public API
 }
 
+
+#[test]
+fn test_bif_timers_fire_after_sleep() {
+    let timers: BifTimers<u32, &str> = BifTimers::new();
+    let r = timers.send_after(5, 1, "late", TimerOptions::default());
+    timers.start_timer(60_000, 2, "never", TimerOptions::default());
+
+    let sleep = timers.next_timeout().unwrap();
+    assert!(sleep <= Duration::from_millis(5));
+    std::thread::sleep(Duration::from_millis(10));
+
+    let events = timers.bump();
+    assert_eq!(events.len(), 1);
+    assert!(matches!(&events[0], TimerEvent::Timeout { timer_ref, dest: 1, .. } if *timer_ref == r));
+    assert_eq!(timers.active_timers(), 1);
+}
+
+#[test]
+fn test_bif_timers_many_concurrent_timers() {
+    let timers: BifTimers<u32, u32> = BifTimers::new();
+    let now = timers.now_ms();
+    let refs: Vec<TimerRef> = (0..100_000u32)
+        .map(|i| timers.send_after(now + 1 + u64::from(i % 5_000), i, i, TimerOptions { abs: true }))
+        .collect();
+    for r in refs.iter().take(50_000) {
+        assert!(matches!(
+            timers.cancel_timer(*r, 0, CancelTimerOptions::default()),
+            TimerReply::Remaining(_)
+        ));
+    }
+    let events = timers.bump_until(now + 5_000);
+    assert_eq!(events.len(), 50_000);
+    assert_eq!(timers.active_timers(), 0);
+}
+
+#[test]
+fn test_cancel_timer_fire_race_with_process_mailbox() {
+    use entities_process::{Message, Process};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    let timers: Arc<BifTimers<u64, u64>> = Arc::new(BifTimers::new());
+    let process = Arc::new(Process::new(1));
+    let done = Arc::new(AtomicBool::new(false));
+
+    // Scheduler thread: keeps expiring timers and delivering their messages
+    let scheduler = {
+        let timers = timers.clone();
+        let process = process.clone();
+        let done = done.clone();
+        std::thread::spawn(move || {
+            while !done.load(Ordering::SeqCst) {
+                timers.bump_with(|event| {
+                    if let TimerEvent::Timeout { timer_ref, msg, .. } = event {
+                        process.send_message(Message::from_timer(msg, timer_ref.0));
+                    }
+                });
+                std::thread::yield_now();
+            }
+        })
+    };
+
+    let mut cancelled_before_fire = 0;
+    let mut flushed_after_fire = 0;
+    for i in 0..200u64 {
+        let r = timers.start_timer(i % 3, 1, i, TimerOptions::default());
+        std::thread::sleep(Duration::from_micros(i % 7 * 150));
+        let (reply, flushed) =
+            timers.cancel_timer_and_flush(r, 1, CancelTimerOptions::default(), |tref| {
+                process.flush_timer_message(tref.0)
+            });
+        match reply {
+            // Cancelled in time: the message must never show up
+            TimerReply::Remaining(_) => {
+                assert!(!flushed);
+                cancelled_before_fire += 1;
+            }
+            // Already fired: the message was delivered and is now flushed
+            TimerReply::NotFound => {
+                assert!(flushed, "timer {} fired but its message was not in the mailbox", i);
+                flushed_after_fire += 1;
+            }
+            TimerReply::Ok => unreachable!(),
+        }
+        // No message from this timer may remain or arrive later
+        assert!(process
+            .receive_after_0(|m| m.timer_ref == Some(r.0))
+            .is_none());
+    }
+
+    std::thread::sleep(Duration::from_millis(5));
+    done.store(true, Ordering::SeqCst);
+    scheduler.join().unwrap();
+
+    assert_eq!(cancelled_before_fire + flushed_after_fire, 200);
+    assert_eq!(process.message_queue_len(), 0);
+}
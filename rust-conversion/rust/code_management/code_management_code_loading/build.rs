@@ -0,0 +1,47 @@
+//! Build script embedding the preloaded modules
+//!
+//! Every `.beam` file in the directory named by `IRON_BEAM_PRELOADED_DIR` is
+//! embedded into the crate, for `preloaded::load_preloaded()` to load at
+//! startup. Without the variable no modules are embedded.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory holding the compiled preloaded modules
+const PRELOADED_DIR_ENV: &str = "IRON_BEAM_PRELOADED_DIR";
+
+fn main() {
+    println!("cargo:rerun-if-env-changed={}", PRELOADED_DIR_ENV);
+
+    let mut modules: Vec<(String, PathBuf)> = Vec::new();
+    if let Some(dir) = env::var_os(PRELOADED_DIR_ENV).filter(|dir| !dir.is_empty()) {
+        let dir = PathBuf::from(dir);
+        println!("cargo:rerun-if-changed={}", dir.display());
+        let entries = fs::read_dir(&dir)
+            .unwrap_or_else(|e| panic!("{} ({}): {}", PRELOADED_DIR_ENV, dir.display(), e));
+        for entry in entries {
+            let path = entry.expect("readable preloaded directory entry").path();
+            if path.extension().is_some_and(|ext| ext == "beam") {
+                let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+                let path = fs::canonicalize(&path).expect("canonical preloaded module path");
+                println!("cargo:rerun-if-changed={}", path.display());
+                modules.push((name, path));
+            }
+        }
+    }
+    modules.sort();
+
+    let mut table = String::from("&[\n");
+    for (name, path) in &modules {
+        table.push_str(&format!(
+            "    ({:?}, include_bytes!({:?}) as &[u8]),\n",
+            name,
+            path.display().to_string()
+        ));
+    }
+    table.push(']');
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("preloaded_modules.rs");
+    fs::write(out, table).expect("write embedded preloaded module table");
+}
@@ -29,6 +29,9 @@
 
 use crate::module_management::ModuleTableManager;
 use crate::code_index::get_global_code_ix;
+use crate::code_area::{self, get_global_code_area, CodeRegion};
+use entities_system_integration_common::MmapError;
+use crate::fun_table::get_global_fun_table;
 
 /// BEAM file read result
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -90,6 +93,180 @@ pub struct BeamFile {
     pub attributes_data: Option<Vec<u8>>,
     /// Compile info chunk data (raw bytes - will be decoded to ErlangTerm when term decoding supports tuples/lists)
     pub compile_info_data: Option<Vec<u8>>,
+    /// Literal table chunk data (raw bytes, committed to the code area with the code)
+    pub literal_data: Option<Vec<u8>>,
+    /// Lambda table (`FunT` chunk)
+    pub lambdas: Vec<BeamLambda>,
+}
+
+/// Entry of the lambda table of a BEAM file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BeamLambda {
+    /// Function atom (1-based index into the atom table)
+    pub function: u32,
+    /// Arity of the function, including the free variables
+    pub arity: u32,
+    /// Label of the function
+    pub label: i32,
+    /// Index of the fun in the module
+    pub index: u32,
+    /// Number of free variables
+    pub num_free: u32,
+    /// Uniq value of the fun
+    pub old_uniq: u32,
+}
+
+impl BeamFile {
+    /// Get the module name (the first entry of the atom table)
+    ///
+    /// Returns `None` if the atom table was not present.
+    pub fn module_name(&self) -> Option<&str> {
+        self.atoms.first().map(String::as_str)
+    }
+
+    /// Look up an exported function by name and arity
+    ///
+    /// Export entries reference the atom table with 1-based indices.
+    ///
+    /// # Returns
+    /// The entry label of the function, or `None` if it is not exported
+    pub fn find_export(&self, function: &str, arity: u32) -> Option<i32> {
+        self.exports.iter().find_map(|&(func_atom, func_arity, label)| {
+            let name = self.atoms.get((func_atom as usize).checked_sub(1)?)?;
+            (name == function && func_arity == arity).then_some(label)
+        })
+    }
+
+    /// Find the `label` instruction defining a label in the code chunk
+    ///
+    /// Walks the generic instructions of the code chunk, skipping the
+    /// operands of each using the arities of genop.tab, until the label is
+    /// found or `int_code_end` is reached.
+    ///
+    /// # Returns
+    /// The byte offset of the instruction in [`code_data`](Self::code_data),
+    /// or `None` if the label is not defined or the code cannot be decoded
+    pub fn find_label(&self, label: i32) -> Option<usize> {
+        let code = &self.code_data;
+        let header_size = u32::from_be_bytes(code.get(0..4)?.try_into().ok()?) as usize;
+        let mut pos = 4 + header_size;
+        loop {
+            let opcode = *code.get(pos)?;
+            match opcode {
+                GENOP_LABEL => {
+                    let (_, value, _) = compact_term(code, pos + 1)?;
+                    if value == Some(label as u64) {
+                        return Some(pos);
+                    }
+                }
+                GENOP_INT_CODE_END => return None,
+                _ => {}
+            }
+            let arity = *GENOP_ARITIES.get(opcode as usize)?;
+            pos += 1;
+            for _ in 0..arity {
+                pos = skip_compact_term(code, pos)?;
+            }
+        }
+    }
+}
+
+/// Generic `label` instruction
+const GENOP_LABEL: u8 = 1;
+
+/// Generic `int_code_end` instruction
+const GENOP_INT_CODE_END: u8 = 3;
+
+/// Operand counts of the generic instructions, by opcode (genop.tab)
+///
+/// Opcode 0 is not an instruction; it is given a count so that every
+/// opcode up to `debug_line` (184) is an index.
+const GENOP_ARITIES: [u8; 185] = [
+    0, // 0: not an instruction
+    1, 3, 0, 2, 3, 2, 2, 3, 2, 4, // 1-10: label .. bif1
+    5, 2, 3, 2, 3, 2, 1, 1, 0, 0, // 11-20: bif2 .. send
+    0, 0, 2, 1, 1, 2, 4, 4, 4, 4, // 21-30: remove_message .. m_div
+    4, 4, 4, 4, 4, 4, 4, 3, 3, 3, // 31-40: int_div .. is_ge
+    3, 3, 3, 3, 2, 2, 2, 2, 2, 2, // 41-50: is_eq .. is_reference
+    2, 2, 2, 2, 2, 2, 2, 3, 3, 3, // 51-60: is_port .. select_tuple_arity
+    1, 2, 1, 2, 3, 3, 3, 3, 3, 2, // 61-70: jump .. put_tuple
+    1, 1, 0, 1, 1, 3, 2, 2, 2, 5, // 71-80: put .. bs_get_integer
+    5, 5, 4, 2, 1, 1, 2, 2, 5, 5, // 81-90: bs_get_float .. bs_put_binary
+    5, 2, 1, 0, 1, 2, 2, 4, 4, 4, // 91-100: bs_put_float .. fmul
+    4, 3, 1, 2, 1, 1, 1, 2, 6, 3, // 101-110: fdiv .. bs_bits_to_bytes
+    5, 1, 2, 2, 3, 5, 7, 7, 7, 5, // 111-120: bs_add .. bs_skip_bits2
+    3, 2, 2, 5, 6, 2, 2, 2, 2, 1, // 121-130: bs_test_tail2 .. bs_context_to_binary
+    3, 4, 0, 8, 6, 2, 6, 5, 4, 5, // 131-140: bs_test_unit .. bs_get_utf16
+    4, 5, 4, 3, 3, 3, 3, 3, 0, 1, // 141-150: bs_skip_utf16 .. recv_mark
+    1, 7, 1, 5, 5, 2, 3, 3, 4, 0, // 151-160: recv_set .. build_stacktrace
+    0, 2, 2, 2, 3, 4, 3, 2, 2, 4, // 161-170: raw_raise .. bs_start_match4
+    3, 1, 2, 1, 1, 1, 6, 3, 0, 1, // 171-180: make_fun3 .. badrecord
+    5, 3, 2, 4, // 181-184: update_record .. debug_line
+];
+
+/// Decode the tag and value of a compact term
+///
+/// # Returns
+/// The tag, the value if it fits in 64 bits, and the offset after the
+/// encoded value
+fn compact_term(code: &[u8], pos: usize) -> Option<(u8, Option<u64>, usize)> {
+    let first = *code.get(pos)?;
+    let tag = first & 0x07;
+    if first & 0x08 == 0 {
+        return Some((tag, Some(u64::from(first >> 4)), pos + 1));
+    }
+    if first & 0x10 == 0 {
+        let next = *code.get(pos + 1)?;
+        return Some((tag, Some((u64::from(first >> 5) << 8) | u64::from(next)), pos + 2));
+    }
+    let (start, len) = match first >> 5 {
+        7 => {
+            let (_, len, start) = compact_term(code, pos + 1)?;
+            (start, usize::try_from(len?).ok()?.checked_add(9)?)
+        }
+        len => (pos + 1, len as usize + 2),
+    };
+    let bytes = code.get(start..start.checked_add(len)?)?;
+    let value = (len <= 8).then(|| bytes.iter().fold(0, |value, &byte| (value << 8) | u64::from(byte)));
+    Some((tag, value, start + len))
+}
+
+/// Skip a compact term, including the operands of an extended term
+///
+/// # Returns
+/// The offset after the term
+fn skip_compact_term(code: &[u8], pos: usize) -> Option<usize> {
+    const TAG_EXTENDED: u8 = 7;
+
+    let (tag, value, mut pos) = compact_term(code, pos)?;
+    if tag != TAG_EXTENDED {
+        return Some(pos);
+    }
+    let terms = match value? {
+        // Float, stored as 8 raw bytes
+        0 => return Some(pos + 8),
+        // List: length, then the elements
+        1 => {
+            let (_, len, next) = compact_term(code, pos)?;
+            pos = next;
+            len?
+        }
+        // Floating point register and literal
+        2 | 4 => 1,
+        // Allocation list: length, then a kind and a count per entry
+        3 => {
+            let (_, len, next) = compact_term(code, pos)?;
+            pos = next;
+            len?.checked_mul(2)?
+        }
+        // Type-tagged register: the register and its type index
+        5 => 2,
+        _ => return None,
+    };
+    for _ in 0..terms {
+        pos = skip_compact_term(code, pos)?;
+    }
+    Some(pos)
 }
 
 /// BEAM file loader
@@ -138,6 +315,8 @@ impl BeamLoader {
             has_on_load: false,
             attributes_data: None,
             compile_info_data: None,
+            literal_data: None,
+            lambdas: vec![],
         };
         
         // Parse IFF chunks starting after the BEAM form type (byte 12)
@@ -186,6 +365,15 @@ impl BeamLoader {
                 0x43496E66 => { // "CInf" - Compile info chunk
                     beam_file.compile_info_data = Some(chunk_data);
                 }
+                0x4C697454 => { // "LitT" - Literal table chunk
+                    beam_file.literal_data = Some(chunk_data);
+                }
+                0x41745538 | 0x41746F6D => { // "AtU8" / "Atom" - Atom table chunk
+                    beam_file.atoms = Self::read_atom_table(&chunk_data)?;
+                }
+                0x46756E54 => { // "FunT" - Lambda table chunk
+                    beam_file.lambdas = Self::read_lambda_table(&chunk_data)?;
+                }
                 0x436F6465 => { // "Code" - Code chunk
                     beam_file.code_data = chunk_data.clone();
                     beam_file.code_size = chunk_size as u32;
@@ -256,6 +444,82 @@ impl BeamLoader {
         Ok(beam_file)
     }
 
+    /// Parse the lambda table chunk (`FunT`)
+    ///
+    /// The chunk holds a 4-byte count followed by one entry per fun of six
+    /// 4-byte fields: function atom, arity, label, index, number of free
+    /// variables and uniq value.
+    fn read_lambda_table(chunk: &[u8]) -> Result<Vec<BeamLambda>, BeamFileReadResult> {
+        let field = |pos: usize| -> Result<u32, BeamFileReadResult> {
+            chunk
+                .get(pos..pos + 4)
+                .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                .ok_or(BeamFileReadResult::CorruptLambdaTable)
+        };
+        let count = field(0)? as usize;
+        if chunk.len() < 4 + count * 24 {
+            return Err(BeamFileReadResult::CorruptLambdaTable);
+        }
+        (0..count)
+            .map(|entry| {
+                let pos = 4 + entry * 24;
+                Ok(BeamLambda {
+                    function: field(pos)?,
+                    arity: field(pos + 4)?,
+                    label: field(pos + 8)? as i32,
+                    index: field(pos + 12)?,
+                    num_free: field(pos + 16)?,
+                    old_uniq: field(pos + 20)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Parse the atom table chunk (`AtU8` or legacy `Atom`)
+    ///
+    /// The chunk starts with a 4-byte signed count. A non-negative count
+    /// means each atom is prefixed by a single length byte; a negative count
+    /// (OTP 28 long atoms) means each length is a compact-term encoded
+    /// integer.
+    fn read_atom_table(chunk: &[u8]) -> Result<Vec<String>, BeamFileReadResult> {
+        if chunk.len() < 4 {
+            return Err(BeamFileReadResult::CorruptAtomTable);
+        }
+        let raw_count = i32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        let long_lengths = raw_count < 0;
+        let count = raw_count.unsigned_abs() as usize;
+
+        let mut atoms = Vec::with_capacity(count.min(chunk.len()));
+        let mut pos = 4;
+        for _ in 0..count {
+            let len = if long_lengths {
+                let first = *chunk.get(pos).ok_or(BeamFileReadResult::CorruptAtomTable)? as usize;
+                if first & 0x08 == 0 {
+                    pos += 1;
+                    first >> 4
+                } else if first & 0x10 == 0 {
+                    let next = *chunk.get(pos + 1).ok_or(BeamFileReadResult::CorruptAtomTable)? as usize;
+                    pos += 2;
+                    ((first >> 5) << 8) | next
+                } else {
+                    return Err(BeamFileReadResult::CorruptAtomTable);
+                }
+            } else {
+                let len = *chunk.get(pos).ok_or(BeamFileReadResult::CorruptAtomTable)? as usize;
+                pos += 1;
+                len
+            };
+            let bytes = chunk
+                .get(pos..pos + len)
+                .ok_or(BeamFileReadResult::CorruptAtomTable)?;
+            let name = String::from_utf8(bytes.to_vec())
+                .map_err(|_| BeamFileReadResult::CorruptAtomTable)?;
+            atoms.push(name);
+            pos += len;
+        }
+        Ok(atoms)
+    }
+
     /// Prepare loading a module from BEAM file
     ///
     /// This parses the BEAM file and prepares it for loading.
@@ -324,7 +588,17 @@ impl BeamLoader {
         Self::make_current_old(module_manager, module_atom)?;
 
         // Finalize code into module instance
-        Self::finalize_code(beam, &module, staging_ix)?;
+        let region = Self::finalize_code(beam, &module, staging_ix)?;
+        table.update_module(module_atom, |module| {
+            let curr = &mut module.curr;
+            curr.code_hdr = Some(region.code as *const ());
+            curr.code_length = region.code_length as u32;
+            curr.executable_region = Some(region.code as *const ());
+            curr.writable_region = Some(region.code as *mut ());
+            curr.literals = Some(region.literals as *const ());
+            curr.literals_length = region.literals_length as u32;
+        });
+        get_global_fun_table().register_module(module_atom, region.code as usize, beam);
 
         Ok(())
     }
@@ -348,10 +622,16 @@ impl BeamLoader {
         let staging_ix = code_ix.staging_code_ix() as usize;
         let table = module_manager.get_table(staging_ix);
 
-        if table.get_module(module_atom).is_some() {
-            // Check if old code already exists (would need to check old instance)
-            // For now, simplified: just copy curr to old
-            // In full implementation, would check if old is already populated
+        match table.get_module(module_atom) {
+            Some(module) if module.curr.code_hdr.is_some() => {
+                if module.old.code_hdr.is_some() {
+                    return Err(BeamLoadError::OldCodeExists);
+                }
+                table.update_module(module_atom, |module| {
+                    module.old = std::mem::take(&mut module.curr);
+                });
+            }
+            _ => {}
         }
 
         Ok(())
@@ -359,8 +639,9 @@ impl BeamLoader {
 
     /// Finalize code loading into module instance
     ///
-    /// Equivalent to beam_load_finalize_code(). Sets up the module instance with
-    /// the loaded code.
+    /// Equivalent to beam_load_finalize_code(). Commits the code and literals
+    /// to the code area, where they are read-only from then on (see
+    /// [`code_area`](crate::code_area)).
     ///
     /// # Arguments
     /// * `beam` - Parsed BEAM file
@@ -368,25 +649,23 @@ impl BeamLoader {
     /// * `_code_ix` - Code index
     ///
     /// # Returns
-    /// Ok(()) if successful, error otherwise
+    /// The committed code and literals, or an error if the module has no code,
+    /// the code area is exhausted or the code could not be made read-only
     pub fn finalize_code(
         beam: &BeamFile,
         _module: &crate::module_management::Module,
         _code_ix: usize,
-    ) -> Result<(), BeamLoadError> {
-        // In a full implementation, this would:
-        // 1. Allocate code memory
-        // 2. Copy code data from beam
-        // 3. Set up code header
-        // 4. Update module.curr with code information
-        // 5. Set executable_region and writable_region
-        
-        // For now, simplified: just verify beam has code
+    ) -> Result<CodeRegion, BeamLoadError> {
         if beam.code_data.is_empty() {
             return Err(BeamLoadError::InvalidModule);
         }
 
-        Ok(())
+        let area = get_global_code_area().ok_or(BeamLoadError::CodeAreaExhausted)?;
+        let literals = beam.literal_data.as_deref().unwrap_or(&[]);
+        code_area::commit_code(&area, &beam.code_data, literals).map_err(|error| match error {
+            MmapError::ProtectionFailed => BeamLoadError::CodeProtectionFailed,
+            _ => BeamLoadError::CodeAreaExhausted,
+        })
     }
 
     /// Initialize loading subsystem
@@ -532,15 +811,48 @@ impl BeamLoader {
 
     /// Purge auxiliary code
     ///
-    /// Equivalent to beam_load_purge_aux(). Purges old code from a module.
+    /// Equivalent to beam_load_purge_aux(). Releases the code and literals
+    /// committed at `code_hdr`; addresses outside the code area are ignored.
     ///
     /// # Arguments
     /// * `code_hdr` - Code header pointer (simplified as usize)
-    pub fn purge_aux(_code_hdr: usize) {
-        // In a full implementation, this would:
-        // 1. Free code memory
-        // 2. Release resources
-        // 3. Update module state
+    pub fn purge_aux(code_hdr: usize) {
+        if let Some(area) = get_global_code_area() {
+            let _ = code_area::release_code(&area, code_hdr as *const u8);
+        }
+    }
+
+    /// Purge the old code of a module
+    ///
+    /// Clears the old instance of the module in the staging table, releases
+    /// its code and literals and removes the funs only found in it from the
+    /// fun table.
+    ///
+    /// # Arguments
+    /// * `module_manager` - Module table manager
+    /// * `module_atom` - Module atom index
+    ///
+    /// # Returns
+    /// true if old code was purged, false if the module had none
+    pub fn purge_old_code(module_manager: &ModuleTableManager, module_atom: u32) -> bool {
+        let code_ix = get_global_code_ix();
+        let staging_ix = code_ix.staging_code_ix() as usize;
+        let table = module_manager.get_table(staging_ix);
+
+        let mut code_hdr = None;
+        table.update_module(module_atom, |module| {
+            code_hdr = module.old.code_hdr;
+            module.old.init();
+        });
+        match code_hdr {
+            Some(code_hdr) => {
+                let _guard = module_manager.rwlock_old_code(staging_ix);
+                get_global_fun_table().purge(module_atom, code_hdr as usize);
+                Self::purge_aux(code_hdr as usize);
+                true
+            }
+            None => false,
+        }
     }
 
     /// Create new generic operation
@@ -583,6 +895,10 @@ pub enum BeamLoadError {
     OldCodeExists,
     /// Invalid module
     InvalidModule,
+    /// No room in the code area for the code and literals
+    CodeAreaExhausted,
+    /// The committed code and literals could not be made read-only
+    CodeProtectionFailed,
 }
 
 #[cfg(test)]
@@ -698,6 +1014,37 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_loaded_code_read_only_until_purged() {
+        let image = build_beam(&[(b"Code", vec![1, 2, 3, 4]), (b"LitT", vec![5, 6, 7, 8])]);
+        let beam = BeamLoader::read_beam_file(&image).unwrap();
+        let module_manager = ModuleTableManager::new();
+        let staging_ix = get_global_code_ix().staging_code_ix() as usize;
+        let table = module_manager.get_table(staging_ix);
+        let area = get_global_code_area().unwrap();
+
+        BeamLoader::finish_loading(&beam, 7, &module_manager).unwrap();
+        let first = table.get_module(7).unwrap().curr.clone();
+        let code = first.code_hdr.unwrap() as *const u8;
+        let literals = first.literals.unwrap() as *const u8;
+        assert_eq!(first.code_length, 4);
+        assert_eq!(unsafe { *literals.add(1) }, 6);
+        assert!(area.is_protected(code));
+        assert!(area.is_protected(literals));
+
+        // Reloading makes the current code old, a third load needs a purge
+        BeamLoader::finish_loading(&beam, 7, &module_manager).unwrap();
+        assert_eq!(table.get_module(7).unwrap().old.code_hdr, first.code_hdr);
+        assert_eq!(
+            BeamLoader::finish_loading(&beam, 7, &module_manager),
+            Err(BeamLoadError::OldCodeExists)
+        );
+
+        assert!(BeamLoader::purge_old_code(&module_manager, 7));
+        assert!(!BeamLoader::purge_old_code(&module_manager, 7));
+        assert!(table.get_module(7).unwrap().old.code_hdr.is_none());
+    }
+
     #[test]
     fn test_has_code_on_load() {
         let mut data = vec![0u8; 16];
@@ -1211,6 +1558,8 @@ mod tests {
             has_on_load: false,
             attributes_data: None,
             compile_info_data: None,
+            literal_data: None,
+            lambdas: vec![],
         };
         
         let module_manager = ModuleTableManager::new();
@@ -1235,6 +1584,8 @@ mod tests {
             has_on_load: false,
             attributes_data: None,
             compile_info_data: None,
+            literal_data: None,
+            lambdas: vec![],
         };
         
         let result = BeamLoader::prepare_emit(&beam);
@@ -1253,6 +1604,8 @@ mod tests {
             has_on_load: false,
             attributes_data: None,
             compile_info_data: None,
+            literal_data: None,
+            lambdas: vec![],
         };
         
         let result = BeamLoader::finish_emit(&beam);
@@ -1365,6 +1718,8 @@ mod tests {
             has_on_load: true,
             attributes_data: Some(vec![7, 8]),
             compile_info_data: Some(vec![9, 10]),
+            literal_data: None,
+            lambdas: vec![],
         };
         
         let debug_str = format!("{:?}", beam);
@@ -1383,6 +1738,8 @@ mod tests {
             has_on_load: true,
             attributes_data: Some(vec![7, 8]),
             compile_info_data: Some(vec![9, 10]),
+            literal_data: None,
+            lambdas: vec![],
         };
         
         let cloned = beam.clone();
@@ -1401,6 +1758,8 @@ mod tests {
             has_on_load: false,
             attributes_data: None,
             compile_info_data: None,
+            literal_data: None,
+            lambdas: vec![],
         };
         
         let beam2 = BeamFile {
@@ -1413,6 +1772,8 @@ mod tests {
             has_on_load: false,
             attributes_data: None,
             compile_info_data: None,
+            literal_data: None,
+            lambdas: vec![],
         };
         
         let beam3 = BeamFile {
@@ -1425,6 +1786,8 @@ mod tests {
             has_on_load: false,
             attributes_data: None,
             compile_info_data: None,
+            literal_data: None,
+            lambdas: vec![],
         };
         
         assert_eq!(beam1, beam2);
@@ -1470,5 +1833,124 @@ mod tests {
         assert_eq!(beam.code_size, 10);
         assert_eq!(beam.code_data.len(), 10);
     }
+
+    /// Build a BEAM image from (chunk id, chunk data) pairs
+    fn build_beam(chunks: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut body = b"BEAM".to_vec();
+        for (id, data) in chunks {
+            body.extend_from_slice(*id);
+            body.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            body.extend_from_slice(data);
+            while !body.len().is_multiple_of(4) {
+                body.push(0);
+            }
+        }
+        let mut image = b"FOR1".to_vec();
+        image.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        image.extend_from_slice(&body);
+        image
+    }
+
+    #[test]
+    fn test_read_atom_table_and_exports() {
+        let mut atoms = 2i32.to_be_bytes().to_vec();
+        for name in ["init", "boot"] {
+            atoms.push(name.len() as u8);
+            atoms.extend_from_slice(name.as_bytes());
+        }
+        let mut exports = 1u32.to_be_bytes().to_vec();
+        exports.extend_from_slice(&2u32.to_be_bytes());
+        exports.extend_from_slice(&1u32.to_be_bytes());
+        exports.extend_from_slice(&7i32.to_be_bytes());
+
+        let image = build_beam(&[(b"AtU8", atoms), (b"ExpT", exports), (b"Code", vec![0; 4])]);
+        let beam = BeamLoader::read_beam_file(&image).unwrap();
+        assert_eq!(beam.module_name(), Some("init"));
+        assert_eq!(beam.find_export("boot", 1), Some(7));
+        assert_eq!(beam.find_export("boot", 2), None);
+        assert_eq!(beam.find_export("init", 1), None);
+    }
+
+    #[test]
+    fn test_find_label() {
+        let mut code = 16u32.to_be_bytes().to_vec();
+        for field in [0u32, 184, 4, 1] {
+            code.extend_from_slice(&field.to_be_bytes());
+        }
+        let mut labels = Vec::new();
+        let instructions: [&[u8]; 9] = [
+            &[0x01, 0x10],                                     // label 1
+            &[0x99, 0x00],                                     // line 0
+            &[0x02, 0x12, 0x22, 0x10],                         // func_info a1 a2 1
+            &[0x01, 0x20],                                     // label 2
+            &[0x40, 0x69, 0xE8, 0x13],                         // move 1000 x1
+            &[0x3B, 0x03, 0x35, 0x17, 0x20, 0x12, 0x25],       // select_val x0 f3 [a1, f2]
+            &[0x40, 0x47, 0x00, 0x03],                         // move literal0 x0
+            &[0x01, 0x30],                                     // label 3
+            &[0x40, 0x99, 0x01, 0, 0, 0, 0, 0, 0x03, 0x13, 0x03], // move 1 bsl 40 x0; return; int_code_end
+        ];
+        for instruction in instructions {
+            if instruction[0] == 0x01 {
+                labels.push(code.len());
+            }
+            code.extend_from_slice(instruction);
+        }
+
+        let beam = BeamLoader::read_beam_file(&build_beam(&[(b"Code", code.clone())])).unwrap();
+        assert_eq!(beam.find_label(1), Some(labels[0]));
+        assert_eq!(beam.find_label(2), Some(labels[1]));
+        assert_eq!(beam.find_label(3), Some(labels[2]));
+        assert_eq!(beam.find_label(4), None);
+
+        // An unknown opcode stops the walk
+        code.insert(labels[2], 0xFF);
+        let beam = BeamLoader::read_beam_file(&build_beam(&[(b"Code", code)])).unwrap();
+        assert_eq!(beam.find_label(2), Some(labels[1]));
+        assert_eq!(beam.find_label(3), None);
+    }
+
+    #[test]
+    fn test_read_lambda_table() {
+        let mut funt = 1u32.to_be_bytes().to_vec();
+        for field in [2u32, 1, 12, 0, 1, 0x7F00_0001] {
+            funt.extend_from_slice(&field.to_be_bytes());
+        }
+        let image = build_beam(&[(b"FunT", funt.clone()), (b"Code", vec![0; 4])]);
+        let beam = BeamLoader::read_beam_file(&image).unwrap();
+        assert_eq!(
+            beam.lambdas,
+            vec![BeamLambda { function: 2, arity: 1, label: 12, index: 0, num_free: 1, old_uniq: 0x7F00_0001 }]
+        );
+
+        funt.truncate(20);
+        let image = build_beam(&[(b"FunT", funt)]);
+        assert_eq!(
+            BeamLoader::read_beam_file(&image),
+            Err(BeamFileReadResult::CorruptLambdaTable)
+        );
+    }
+
+    #[test]
+    fn test_read_atom_table_long_lengths() {
+        // Negative count: lengths are compact-term encoded (tag u, small value)
+        let mut atoms = (-1i32).to_be_bytes().to_vec();
+        atoms.push(4 << 4);
+        atoms.extend_from_slice(b"prim");
+        let image = build_beam(&[(b"AtU8", atoms)]);
+        let beam = BeamLoader::read_beam_file(&image).unwrap();
+        assert_eq!(beam.atoms, vec!["prim".to_string()]);
+    }
+
+    #[test]
+    fn test_read_atom_table_corrupt() {
+        let mut atoms = 1i32.to_be_bytes().to_vec();
+        atoms.push(10);
+        atoms.extend_from_slice(b"abc");
+        let image = build_beam(&[(b"AtU8", atoms)]);
+        assert_eq!(
+            BeamLoader::read_beam_file(&image),
+            Err(BeamFileReadResult::CorruptAtomTable)
+        );
+    }
 }
 
@@ -0,0 +1,168 @@
+//! Code Area
+//!
+//! Provides the memory loaded code and literals are committed to.
+//! Based on the code and literal area allocation of beam_load.c and the
+//! write protection of erts_seal_module()/erts_unseal_module() in module.c.
+//!
+//! Every loaded module gets one segment of the code area, holding its code
+//! followed by its literals on pages of their own. Once the segment is
+//! filled it is made read-only, so a stray write to code or literals faults
+//! where it happens instead of corrupting a running module. Unsealing a
+//! module makes its code writable again for patching (breakpoints), and
+//! purging the module releases the segment.
+//!
+//! Tests that patch loaded code in place can turn protection off with
+//! [`set_memory_protection`](entities_system_integration_common::set_memory_protection).
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ */
+
+use std::sync::{Arc, OnceLock};
+
+use entities_system_integration_common::{page_size, MmapError, SuperCarrier};
+
+/// Size of the range reserved for the code area
+///
+/// Like the literal area, the reservation is backed by the system allocator
+/// rather than reserved address space, so it is kept moderate.
+pub const DEFAULT_CODE_AREA_SIZE: usize = 64 * 1024 * 1024;
+
+/// Code and literals of a module committed to the code area
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodeRegion {
+    /// Start of the segment and of the code
+    pub code: *const u8,
+    /// Length of the code in bytes
+    pub code_length: usize,
+    /// Start of the literals, on the first page after the code
+    pub literals: *const u8,
+    /// Length of the literals in bytes
+    pub literals_length: usize,
+}
+
+/// Global code area, reserved on first use
+static GLOBAL_CODE_AREA: OnceLock<Option<Arc<SuperCarrier>>> = OnceLock::new();
+
+/// Get the global code area
+///
+/// Returns `None` if it could not be reserved.
+pub fn get_global_code_area() -> Option<Arc<SuperCarrier>> {
+    GLOBAL_CODE_AREA
+        .get_or_init(|| SuperCarrier::reserve(DEFAULT_CODE_AREA_SIZE).ok().map(Arc::new))
+        .clone()
+}
+
+/// Get the global code area without reserving it
+pub(crate) fn reserved_code_area() -> Option<Arc<SuperCarrier>> {
+    GLOBAL_CODE_AREA.get().cloned().flatten()
+}
+
+/// Commit code and literals to a new segment and make it read-only
+///
+/// # Arguments
+/// * `area` - Code area to map the segment from
+/// * `code` - Code to copy
+/// * `literals` - Literals to copy
+///
+/// The code and the literals each start on a page of their own, and the
+/// segment covers whole pages, so sealing it never protects a neighbouring
+/// segment.
+///
+/// # Errors
+/// * Errors of [`SuperCarrier::map`]
+/// * `MmapError::ProtectionFailed` - If the segment could not be made
+///   read-only; it is unmapped again
+pub fn commit_code(area: &SuperCarrier, code: &[u8], literals: &[u8]) -> Result<CodeRegion, MmapError> {
+    let code_pages = code.len().next_multiple_of(page_size());
+    let size = code_pages + literals.len();
+    let segment = area.map(size)?;
+    // The segment was just mapped and is at least `size` bytes
+    unsafe {
+        std::ptr::copy_nonoverlapping(code.as_ptr(), segment, code.len());
+        std::ptr::copy_nonoverlapping(literals.as_ptr(), segment.add(code_pages), literals.len());
+    }
+    if let Err(error) = area.protect(segment, size) {
+        let _ = area.unmap(segment);
+        return Err(error);
+    }
+    Ok(CodeRegion {
+        code: segment,
+        code_length: code.len(),
+        literals: unsafe { segment.add(code_pages) },
+        literals_length: literals.len(),
+    })
+}
+
+/// Make the code of a segment writable or read-only again
+///
+/// # Errors
+/// * Errors of [`SuperCarrier::protect`] and [`SuperCarrier::unprotect`]
+pub fn set_code_writable(
+    area: &SuperCarrier,
+    code: *const u8,
+    code_length: usize,
+    writable: bool,
+) -> Result<(), MmapError> {
+    if writable {
+        area.unprotect(code as *mut u8, code_length)
+    } else {
+        area.protect(code as *mut u8, code_length)
+    }
+}
+
+/// Release the segment starting at `code`
+///
+/// The segment is made writable again and unmapped.
+///
+/// # Errors
+/// * `MmapError::NotMapped` - If `code` is not the start of a segment
+pub fn release_code(area: &SuperCarrier, code: *const u8) -> Result<(), MmapError> {
+    area.unmap(code as *mut u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_and_release_code() {
+        let area = SuperCarrier::reserve(8 * page_size()).unwrap();
+        let region = commit_code(&area, &[1, 2, 3], &[4, 5]).unwrap();
+        assert_eq!(region.code_length, 3);
+        assert_eq!(region.literals as usize - region.code as usize, page_size());
+        assert!(area.is_protected(region.code));
+        assert!(area.is_protected(region.literals));
+        assert_eq!(unsafe { *region.literals.add(1) }, 5);
+
+        set_code_writable(&area, region.code, region.code_length, true).unwrap();
+        assert!(!area.is_protected(region.code));
+        assert!(area.is_protected(region.literals));
+        set_code_writable(&area, region.code, region.code_length, false).unwrap();
+
+        release_code(&area, region.code).unwrap();
+        assert!(!area.is_protected(region.literals));
+        assert_eq!(area.mapped_size(), 0);
+        assert_eq!(release_code(&area, region.code), Err(MmapError::NotMapped));
+    }
+}
@@ -7,6 +7,12 @@
 //! - Code modification permission: For tracing, breakpoints, etc.
 //! - Code staging permission: For code loading and purging
 //! - Code load permission: Both staging and modification (for full code loading)
+//!
+//! On top of the locks, an embedder can install a [`CodeAccessPolicy`] that
+//! decides per module whether it may be loaded, reloaded, purged or have a
+//! NIF library attached, for example to keep tenants of a sandboxed
+//! deployment from replacing each other's code. Without a policy every
+//! operation is allowed.
 
 /*
  * %CopyrightBegin%
@@ -32,7 +38,7 @@
  * %CopyrightEnd%
  */
 
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, RwLock};
 use std::collections::VecDeque;
 
 /// Process ID type (simplified - in full implementation would be Process*)
@@ -49,6 +55,65 @@ pub enum CodePermissionType {
     Load,
 }
 
+/// Code operation checked against the access policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CodeOperation {
+    /// Load a module that has no current code
+    Load,
+    /// Load new code for a module that already has current code
+    Reload,
+    /// Purge the old code of a module
+    Purge,
+    /// Attach a NIF library to a module
+    LoadNif,
+}
+
+impl CodeOperation {
+    /// Name of the operation, as used in error messages
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CodeOperation::Load => "load",
+            CodeOperation::Reload => "reload",
+            CodeOperation::Purge => "purge",
+            CodeOperation::LoadNif => "load_nif",
+        }
+    }
+}
+
+/// Embedder hook deciding which code operations are allowed
+///
+/// Called before the operation takes effect, without any code permission
+/// lock held.
+pub trait CodeAccessPolicy: Send + Sync {
+    /// Decide whether `operation` may be performed on `module`
+    ///
+    /// # Arguments
+    /// * `module` - Module name
+    /// * `operation` - Operation requested
+    /// * `requester` - Process requesting the operation, if known
+    ///
+    /// # Returns
+    /// `true` to allow the operation
+    fn allow(&self, module: &str, operation: CodeOperation, requester: Option<ProcessId>) -> bool;
+}
+
+/// A code operation refused by the access policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeAccessDenied {
+    /// Module name
+    pub module: String,
+    /// Operation refused
+    pub operation: CodeOperation,
+}
+
+impl std::fmt::Display for CodeAccessDenied {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} of module {} not allowed", self.operation.as_str(), self.module)
+    }
+}
+
+impl std::error::Error for CodeAccessDenied {}
+
 /// Code permission queue item
 struct CodePermissionQueueItem {
     /// Process waiting for permission (if process-based)
@@ -75,6 +140,8 @@ pub struct CodePermissionManager {
     mod_permission: Arc<Mutex<CodePermission>>,
     /// Code staging permission
     stage_permission: Arc<Mutex<CodePermission>>,
+    /// Per-module access policy installed by the embedder
+    access_policy: RwLock<Option<Arc<dyn CodeAccessPolicy>>>,
 }
 
 impl CodePermission {
@@ -94,6 +161,7 @@ impl CodePermissionManager {
         Self {
             mod_permission: Arc::new(Mutex::new(CodePermission::new())),
             stage_permission: Arc::new(Mutex::new(CodePermission::new())),
+            access_policy: RwLock::new(None),
         }
     }
 
@@ -198,6 +266,50 @@ impl CodePermissionManager {
         self.has_code_stage_permission(process_id) && self.has_code_mod_permission(process_id)
     }
 
+    /// Install the access policy consulted for loading, reloading, purging
+    /// and attaching NIF libraries
+    ///
+    /// # Returns
+    /// The previously installed policy, if any
+    pub fn set_access_policy(&self, policy: Arc<dyn CodeAccessPolicy>) -> Option<Arc<dyn CodeAccessPolicy>> {
+        self.access_policy.write().unwrap().replace(policy)
+    }
+
+    /// Remove the access policy, allowing every operation again
+    ///
+    /// # Returns
+    /// The removed policy, if any
+    pub fn clear_access_policy(&self) -> Option<Arc<dyn CodeAccessPolicy>> {
+        self.access_policy.write().unwrap().take()
+    }
+
+    /// Check an operation on a module against the access policy
+    ///
+    /// # Arguments
+    /// * `module` - Module name
+    /// * `operation` - Operation requested
+    /// * `requester` - Process requesting the operation, if known
+    ///
+    /// # Returns
+    /// * `Ok(())` - The operation is allowed (or no policy is installed)
+    /// * `Err(CodeAccessDenied)` - The policy refused the operation
+    pub fn check_module_access(
+        &self,
+        module: &str,
+        operation: CodeOperation,
+        requester: Option<ProcessId>,
+    ) -> Result<(), CodeAccessDenied> {
+        // Call the policy without holding the lock so that it may replace itself
+        let policy = self.access_policy.read().unwrap().clone();
+        match policy {
+            Some(policy) if !policy.allow(module, operation, requester) => Err(CodeAccessDenied {
+                module: module.to_string(),
+                operation,
+            }),
+            _ => Ok(()),
+        }
+    }
+
     /// Internal: Try to seize a permission
     fn try_seize_permission(
         &self,
@@ -313,5 +425,32 @@ mod tests {
         manager.release_code_load_permission();
         assert!(!manager.has_code_load_permission(process_id));
     }
+
+    struct TenantPolicy;
+
+    impl CodeAccessPolicy for TenantPolicy {
+        fn allow(&self, module: &str, operation: CodeOperation, requester: Option<ProcessId>) -> bool {
+            module.starts_with("tenant_") && (operation != CodeOperation::LoadNif || requester == Some(1))
+        }
+    }
+
+    #[test]
+    fn test_access_policy() {
+        let manager = CodePermissionManager::new();
+        assert!(manager.check_module_access("kernel", CodeOperation::Reload, None).is_ok());
+
+        assert!(manager.set_access_policy(Arc::new(TenantPolicy)).is_none());
+        assert!(manager.check_module_access("tenant_a", CodeOperation::Load, None).is_ok());
+        assert!(manager.check_module_access("tenant_a", CodeOperation::LoadNif, Some(1)).is_ok());
+        let denied = manager.check_module_access("tenant_a", CodeOperation::LoadNif, Some(2)).unwrap_err();
+        assert_eq!(denied.to_string(), "load_nif of module tenant_a not allowed");
+        assert_eq!(
+            manager.check_module_access("kernel", CodeOperation::Purge, None),
+            Err(CodeAccessDenied { module: "kernel".to_string(), operation: CodeOperation::Purge })
+        );
+
+        assert!(manager.clear_access_policy().is_some());
+        assert!(manager.check_module_access("kernel", CodeOperation::Purge, None).is_ok());
+    }
 }
 
@@ -1,10 +1,19 @@
 //! Code Save/Restore Module
 //!
 //! Provides code save and restore functionality.
+//!
+//! Snapshots store the atom table together with the code, so that atom
+//! indices referenced by saved code resolve to the same atoms after a
+//! restore. A snapshot file holds the serialized atom table (see
+//! `AtomTable::serialize`) followed by the code length as a big-endian
+//! `u64` and the code bytes.
+//!
 //! Based on custom_code_save_restore_yield_state_alt_syntax.c
 
 use std::fs;
+use std::io::{Read, Write};
 use std::path::Path;
+use entities_data_handling::{AtomSnapshotError, AtomTable};
 
 /// Code save/restore manager
 pub struct CodeSaveRestore;
@@ -32,6 +41,52 @@ impl CodeSaveRestore {
     pub fn restore_code<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, RestoreError> {
         fs::read(path).map_err(|_| RestoreError::FileError)
     }
+
+    /// Save code together with the atom table it refers to
+    ///
+    /// # Arguments
+    /// * `code` - Code bytes to save
+    /// * `atoms` - Atom table whose indices the code uses
+    /// * `path` - Path to save file
+    ///
+    /// # Returns
+    /// Result indicating success or error
+    pub fn save_snapshot<P: AsRef<Path>>(
+        code: &[u8],
+        atoms: &AtomTable,
+        path: P,
+    ) -> Result<(), SaveError> {
+        let mut buf = Vec::new();
+        atoms.serialize(&mut buf).map_err(SaveError::AtomTable)?;
+        buf.write_all(&(code.len() as u64).to_be_bytes())
+            .map_err(|_| SaveError::FileError)?;
+        buf.extend_from_slice(code);
+        fs::write(path, buf).map_err(|_| SaveError::FileError)
+    }
+
+    /// Restore code and its atom table from a snapshot
+    ///
+    /// Atoms are restored at their original indices.
+    ///
+    /// # Arguments
+    /// * `path` - Path to snapshot file
+    ///
+    /// # Returns
+    /// Restored atom table and code bytes, or error
+    pub fn restore_snapshot<P: AsRef<Path>>(path: P) -> Result<(AtomTable, Vec<u8>), RestoreError> {
+        let data = fs::read(path).map_err(|_| RestoreError::FileError)?;
+        let mut reader = data.as_slice();
+        let atoms = AtomTable::deserialize(&mut reader).map_err(RestoreError::AtomTable)?;
+        let mut len = [0u8; 8];
+        reader
+            .read_exact(&mut len)
+            .map_err(|_| RestoreError::InvalidSnapshot)?;
+        let len = u64::from_be_bytes(len) as usize;
+        if reader.len() != len {
+            return Err(RestoreError::InvalidSnapshot);
+        }
+        Ok((atoms, reader.to_vec()))
+    }
 }
 
 /// Save operation errors
@@ -39,6 +94,8 @@ impl CodeSaveRestore {
 pub enum SaveError {
     /// File error
     FileError,
+    /// Atom table could not be serialized
+    AtomTable(AtomSnapshotError),
 }
 
 /// Restore operation errors
@@ -46,6 +103,10 @@ pub enum SaveError {
 pub enum RestoreError {
     /// File error
     FileError,
+    /// Atom table in the snapshot is malformed
+    AtomTable(AtomSnapshotError),
+    /// Code section of the snapshot is truncated or has trailing data
+    InvalidSnapshot,
 }
 
 #[cfg(test)]
@@ -65,5 +126,45 @@ mod tests {
         
         let _ = fs::remove_file(&test_file);
     }
-}
 
+    #[test]
+    fn test_snapshot_roundtrip() {
+        use entities_data_handling::AtomEncoding;
+
+        let atoms = AtomTable::new(100);
+        let ok = atoms.put_index(b"ok", AtomEncoding::SevenBitAscii, false).unwrap();
+        let module = atoms.put_index(b"my_module", AtomEncoding::SevenBitAscii, false).unwrap();
+        let code = [ok as u8, module as u8, 0xAA];
+
+        let test_file = std::env::temp_dir().join("test_snapshot_roundtrip.bin");
+        CodeSaveRestore::save_snapshot(&code, &atoms, &test_file).unwrap();
+        let (restored_atoms, restored_code) = CodeSaveRestore::restore_snapshot(&test_file).unwrap();
+        let _ = fs::remove_file(&test_file);
+
+        assert_eq!(restored_code, code);
+        assert_eq!(restored_atoms.get_name(restored_code[0] as usize), Some(b"ok".to_vec()));
+        assert_eq!(restored_atoms.get_name(restored_code[1] as usize), Some(b"my_module".to_vec()));
+    }
+
+    #[test]
+    fn test_restore_snapshot_rejects_plain_code() {
+        let test_file = std::env::temp_dir().join("test_snapshot_plain.bin");
+        CodeSaveRestore::save_code(b"not a snapshot", &test_file).unwrap();
+        let result = CodeSaveRestore::restore_snapshot(&test_file);
+        let _ = fs::remove_file(&test_file);
+        assert!(matches!(result, Err(RestoreError::AtomTable(AtomSnapshotError::BadMagic))));
+    }
+
+    #[test]
+    fn test_restore_snapshot_truncated_code() {
+        let atoms = AtomTable::new(10);
+        let test_file = std::env::temp_dir().join("test_snapshot_truncated.bin");
+        CodeSaveRestore::save_snapshot(b"code", &atoms, &test_file).unwrap();
+        let mut data = fs::read(&test_file).unwrap();
+        data.pop();
+        fs::write(&test_file, data).unwrap();
+        let result = CodeSaveRestore::restore_snapshot(&test_file);
+        let _ = fs::remove_file(&test_file);
+        assert!(matches!(result, Err(RestoreError::InvalidSnapshot)));
+    }
+}
@@ -0,0 +1,372 @@
+//! Fun Table
+//!
+//! Provides the table resolving funs to the loaded code they were created in.
+//! Based on erl_fun.c (erts_put_fun_entry2(), erts_get_fun_entry() and the
+//! fun purge functions).
+//!
+//! A local fun is identified by its module, its index in the module's lambda
+//! table (`FunT` chunk) and the uniq value the compiler derived from the
+//! module's code. A fun received over distribution or decoded from the
+//! external term format carries only that identity, so before it can be
+//! applied it is looked up here to find the code it belongs to.
+//!
+//! Loading a module registers its lambdas. Reloading a module whose funs are
+//! unchanged moves their entries to the new code; entries only found in the
+//! old code keep pointing at it until the old code is purged. A fun of a
+//! module that is not loaded, or of a version of it that is gone, cannot be
+//! resolved (in C the fun is then handed to `error_handler:undefined_lambda/3`).
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 2000-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ */
+
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+use infrastructure_code_loading::ErlangFunType;
+
+use crate::beam_loader::BeamFile;
+
+/// Identity of a local fun
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FunKey {
+    /// Module atom index
+    pub module: u32,
+    /// Index in the module's lambda table
+    pub index: u32,
+    /// Uniq value of the module version the fun was created in
+    pub uniq: u32,
+}
+
+/// Loaded code a fun resolves to
+///
+/// In C: ErlFunEntry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunEntry {
+    /// Identity of the fun
+    pub key: FunKey,
+    /// Name of the function implementing the fun
+    pub function: String,
+    /// Arity of the function, including the free variables
+    pub arity: u32,
+    /// Number of free variables
+    pub num_free: u32,
+    /// Label of the function in the module's code
+    pub label: i32,
+    /// Code header of the module instance holding the function
+    pub code_hdr: usize,
+}
+
+impl FunEntry {
+    /// Get the number of arguments the fun is applied to
+    pub fn fun_arity(&self) -> u32 {
+        self.arity - self.num_free
+    }
+}
+
+/// Fun resolution errors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FunTableError {
+    /// The fun refers to a module that is not loaded
+    ModuleNotLoaded {
+        /// Module name or atom index
+        module: String,
+    },
+    /// The module is loaded, but not the version the fun was created in
+    StaleFun(FunKey),
+    /// The fun is an external fun (`fun M:F/A`), resolved through the export table
+    NotLocalFun,
+}
+
+impl std::fmt::Display for FunTableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FunTableError::ModuleNotLoaded { module } => {
+                write!(f, "fun refers to module {} which is not loaded", module)
+            }
+            FunTableError::StaleFun(key) => write!(
+                f,
+                "fun {}/{} of module {} refers to code that is no longer loaded",
+                key.index, key.uniq, key.module
+            ),
+            FunTableError::NotLocalFun => write!(f, "not a local fun"),
+        }
+    }
+}
+
+impl std::error::Error for FunTableError {}
+
+/// Fun table
+pub struct FunTable {
+    /// Fun entries by identity
+    entries: RwLock<HashMap<FunKey, FunEntry>>,
+    /// Code header of the current code of each loaded module
+    current: RwLock<HashMap<u32, usize>>,
+}
+
+impl FunTable {
+    /// Create an empty fun table
+    pub fn new() -> Self {
+        Self {
+            entries: RwLock::new(HashMap::new()),
+            current: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Register the lambdas of a module loaded at `code_hdr`
+    ///
+    /// Entries of funs the module already had are moved to the new code.
+    ///
+    /// # Arguments
+    /// * `module` - Module atom index
+    /// * `code_hdr` - Code header of the new current code
+    /// * `beam` - Parsed BEAM file of the module
+    pub fn register_module(&self, module: u32, code_hdr: usize, beam: &BeamFile) {
+        let mut entries = self.entries.write().unwrap();
+        for lambda in &beam.lambdas {
+            let key = FunKey {
+                module,
+                index: lambda.index,
+                uniq: lambda.old_uniq,
+            };
+            let function = (lambda.function as usize)
+                .checked_sub(1)
+                .and_then(|atom| beam.atoms.get(atom))
+                .cloned()
+                .unwrap_or_default();
+            entries.insert(
+                key,
+                FunEntry {
+                    key,
+                    function,
+                    arity: lambda.arity,
+                    num_free: lambda.num_free,
+                    label: lambda.label,
+                    code_hdr,
+                },
+            );
+        }
+        self.current.write().unwrap().insert(module, code_hdr);
+    }
+
+    /// Remove the entries of purged code
+    ///
+    /// In C: erts_fun_purge_prepare() and erts_fun_purge_complete()
+    ///
+    /// # Arguments
+    /// * `module` - Module atom index
+    /// * `code_hdr` - Code header of the purged module instance
+    ///
+    /// # Returns
+    /// Number of entries removed
+    pub fn purge(&self, module: u32, code_hdr: usize) -> usize {
+        let mut entries = self.entries.write().unwrap();
+        let before = entries.len();
+        entries.retain(|key, entry| key.module != module || entry.code_hdr != code_hdr);
+        let mut current = self.current.write().unwrap();
+        if current.get(&module) == Some(&code_hdr) {
+            current.remove(&module);
+        }
+        before - entries.len()
+    }
+
+    /// Look up the code of a local fun
+    ///
+    /// In C: erts_get_fun_entry()
+    ///
+    /// # Errors
+    /// * `FunTableError::ModuleNotLoaded` - If no code of the module is loaded
+    /// * `FunTableError::StaleFun` - If the module's loaded code has no such fun
+    pub fn resolve(&self, key: FunKey) -> Result<FunEntry, FunTableError> {
+        if let Some(entry) = self.entries.read().unwrap().get(&key) {
+            return Ok(entry.clone());
+        }
+        if self.current.read().unwrap().contains_key(&key.module) {
+            Err(FunTableError::StaleFun(key))
+        } else {
+            Err(FunTableError::ModuleNotLoaded {
+                module: key.module.to_string(),
+            })
+        }
+    }
+
+    /// Look up the code of a decoded fun
+    ///
+    /// # Arguments
+    /// * `fun` - Fun decoded from the external term format
+    /// * `atom_index` - Looks up the atom index of a module name; `None` if
+    ///   the atom does not exist
+    ///
+    /// # Errors
+    /// * `FunTableError::ModuleNotLoaded` - If no code of the module is loaded
+    /// * `FunTableError::StaleFun` - If the module's loaded code has no such fun
+    /// * `FunTableError::NotLocalFun` - If `fun` is an external fun
+    pub fn resolve_decoded(
+        &self,
+        fun: &ErlangFunType,
+        atom_index: impl Fn(&str) -> Option<u32>,
+    ) -> Result<FunEntry, FunTableError> {
+        let ErlangFunType::Closure {
+            module, index, uniq, ..
+        } = fun
+        else {
+            return Err(FunTableError::NotLocalFun);
+        };
+        let not_loaded = || FunTableError::ModuleNotLoaded {
+            module: module.clone(),
+        };
+        let key = FunKey {
+            module: atom_index(module).ok_or_else(not_loaded)?,
+            index: *index as u32,
+            uniq: *uniq as u32,
+        };
+        self.resolve(key).map_err(|error| match error {
+            FunTableError::ModuleNotLoaded { .. } => not_loaded(),
+            other => other,
+        })
+    }
+
+    /// Number of fun entries
+    pub fn len(&self) -> usize {
+        self.entries.read().unwrap().len()
+    }
+
+    /// Check if the table has no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.read().unwrap().is_empty()
+    }
+}
+
+impl Default for FunTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Global fun table instance
+static GLOBAL_FUN_TABLE: OnceLock<FunTable> = OnceLock::new();
+
+/// Get the global fun table
+pub fn get_global_fun_table() -> &'static FunTable {
+    GLOBAL_FUN_TABLE.get_or_init(FunTable::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::beam_loader::{BeamLambda, BeamLoader};
+    use infrastructure_code_loading::ErlangPid;
+
+    fn beam(lambdas: Vec<BeamLambda>) -> BeamFile {
+        let image = [b"FOR1".as_slice(), &4u32.to_be_bytes(), b"BEAM"].concat();
+        let mut beam = BeamLoader::read_beam_file(&image).unwrap();
+        beam.atoms = vec!["m".to_string(), "-f/0-fun-0-".to_string(), "-g/1-fun-1-".to_string()];
+        beam.lambdas = lambdas;
+        beam
+    }
+
+    fn lambda(function: u32, index: u32, old_uniq: u32) -> BeamLambda {
+        BeamLambda {
+            function,
+            arity: 2,
+            label: 10 + index as i32,
+            index,
+            num_free: 1,
+            old_uniq,
+        }
+    }
+
+    fn closure(module: &str, index: i64, uniq: i64) -> ErlangFunType {
+        ErlangFunType::Closure {
+            arity: 1,
+            module: module.to_string(),
+            index,
+            uniq,
+            old_index: Some(index),
+            md5: Some([0; 16]),
+            n_free_vars: 1,
+            free_vars: vec![],
+            pid: ErlangPid {
+                node: "nonode@nohost".to_string(),
+                num: 0,
+                serial: 0,
+                creation: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_resolve_across_reload_and_purge() {
+        let table = FunTable::new();
+        let key = |index, uniq| FunKey { module: 5, index, uniq };
+        assert_eq!(
+            table.resolve(key(0, 99)),
+            Err(FunTableError::ModuleNotLoaded { module: "5".to_string() })
+        );
+
+        table.register_module(5, 0x1000, &beam(vec![lambda(2, 0, 99), lambda(3, 1, 99)]));
+        let entry = table.resolve(key(0, 99)).unwrap();
+        assert_eq!(entry.function, "-f/0-fun-0-");
+        assert_eq!((entry.label, entry.code_hdr, entry.fun_arity()), (10, 0x1000, 1));
+        assert_eq!(table.resolve(key(0, 7)), Err(FunTableError::StaleFun(key(0, 7))));
+
+        // The new version keeps fun 0, changes fun 1
+        table.register_module(5, 0x2000, &beam(vec![lambda(2, 0, 99), lambda(3, 1, 100)]));
+        assert_eq!(table.resolve(key(0, 99)).unwrap().code_hdr, 0x2000);
+        assert_eq!(table.resolve(key(1, 99)).unwrap().code_hdr, 0x1000);
+        assert_eq!(table.purge(5, 0x1000), 1);
+        assert_eq!(table.resolve(key(1, 99)), Err(FunTableError::StaleFun(key(1, 99))));
+        assert_eq!(table.len(), 2);
+
+        // Deleting and purging the current code unloads the module
+        assert_eq!(table.purge(5, 0x2000), 2);
+        assert!(table.is_empty());
+        assert!(matches!(table.resolve(key(0, 99)), Err(FunTableError::ModuleNotLoaded { .. })));
+    }
+
+    #[test]
+    fn test_resolve_decoded_fun() {
+        let table = FunTable::new();
+        table.register_module(5, 0x1000, &beam(vec![lambda(2, 0, 99)]));
+        let atom_index = |name: &str| match name {
+            "m" => Some(5),
+            "other" => Some(6),
+            _ => None,
+        };
+
+        let entry = table.resolve_decoded(&closure("m", 0, 99), atom_index).unwrap();
+        assert_eq!(entry.key, FunKey { module: 5, index: 0, uniq: 99 });
+        for module in ["other", "no_such_atom"] {
+            assert_eq!(
+                table.resolve_decoded(&closure(module, 0, 99), atom_index),
+                Err(FunTableError::ModuleNotLoaded { module: module.to_string() })
+            );
+        }
+        let export = ErlangFunType::Export {
+            module: "m".to_string(),
+            function: "f".to_string(),
+            arity: 0,
+        };
+        assert_eq!(table.resolve_decoded(&export, atom_index), Err(FunTableError::NotLocalFun));
+    }
+}
@@ -20,8 +20,14 @@
 //! - **[`code_index`](code_index/index.html)**: Code index management for organizing and
 //!   accessing code versions
 //! - **[`beam_loader`](beam_loader/index.html)**: BEAM file loading and parsing
+//! - **[`code_area`](code_area/index.html)**: Memory loaded code and literals are
+//!   committed to, read-only once committed
+//! - **[`fun_table`](fun_table/index.html)**: Resolution of funs, such as those decoded
+//!   from the external term format, to the loaded code they were created in
+//! - **[`preloaded`](preloaded/index.html)**: Preloaded modules embedded at build time
+//!   and loaded before the boot script runs
 //! - **[`code_permissions`](code_permissions/index.html)**: Code permission management for
-//!   controlling code access
+//!   controlling code access, with an embedder policy for per-module load/purge/NIF access
 //! - **[`code_barriers`](code_barriers/index.html)**: Code barriers for safe code loading
 //!   and hot code swapping
 //! - **[`beam_debug`](beam_debug/index.html)**: BEAM debugging and tracing functionality
@@ -43,16 +49,22 @@ pub mod code_save_restore;
 pub mod module_management;
 pub mod code_index;
 pub mod beam_loader;
+pub mod code_area;
+pub mod fun_table;
+pub mod preloaded;
 pub mod code_permissions;
 pub mod code_barriers;
 pub mod beam_debug;
 
 pub use code_loader::CodeLoader;
-pub use unicode::UnicodeHandler;
+pub use unicode::{UnicodeHandler, UnicodeError, Encoding, Endianness, Conversion};
 pub use module_management::{ModuleTableManager, ModuleTable, Module, ModuleInstance, get_global_module_manager};
 pub use code_index::{CodeIndexManager, CodeIndex, get_global_code_ix, NUM_CODE_IX};
-pub use beam_loader::{BeamLoader, BeamFile, BeamFileReadResult, BeamLoadError};
-pub use code_permissions::{CodePermissionManager, ProcessId, get_global_code_permissions};
+pub use beam_loader::{BeamLoader, BeamFile, BeamLambda, BeamFileReadResult, BeamLoadError};
+pub use code_area::{CodeRegion, get_global_code_area};
+pub use fun_table::{FunTable, FunKey, FunEntry, FunTableError, get_global_fun_table};
+pub use preloaded::{load_preloaded, load_preloaded_modules, embedded_preloaded, PreloadedModule, PreloadedError, PRELOAD_ORDER};
+pub use code_permissions::{CodePermissionManager, CodeAccessPolicy, CodeAccessDenied, CodeOperation, ProcessId, get_global_code_permissions};
 pub use code_barriers::{CodeBarrier, CodeBarrierManager, get_global_code_barriers, debug_require_code_barrier, debug_check_code_barrier};
 pub use beam_debug::{BeamDebugTracer, get_global_debug_tracer, dbg_set_traced_mfa, dbg_is_traced_mfa, dbg_vtrace_mfa};
 
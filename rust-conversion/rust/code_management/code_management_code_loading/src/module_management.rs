@@ -31,6 +31,8 @@ use std::collections::HashMap;
 use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 use std::sync::atomic::{AtomicU64, Ordering};
 
+use crate::code_area::{self, reserved_code_area};
+
 /// Module instance - represents a single version of a module's code
 #[derive(Debug, Clone)]
 pub struct ModuleInstance {
@@ -50,6 +52,10 @@ pub struct ModuleInstance {
     pub executable_region: Option<*const ()>,
     /// Writable region (simplified)
     pub writable_region: Option<*mut ()>,
+    /// Literal area
+    pub literals: Option<*const ()>,
+    /// Length of the literal area in bytes
+    pub literals_length: u32,
     /// Metadata (simplified)
     pub metadata: Option<*mut ()>,
     /// Whether module is unsealed (can be modified)
@@ -69,6 +75,8 @@ impl ModuleInstance {
         self.num_traced_exports = 0;
         self.executable_region = None;
         self.writable_region = None;
+        self.literals = None;
+        self.literals_length = 0;
         self.metadata = None;
         self.unsealed = false;
     }
@@ -76,25 +84,40 @@ impl ModuleInstance {
     /// Unseal a module (make it writable for modification)
     ///
     /// Equivalent to erts_unseal_module(). The module must not already be unsealed.
+    /// Code committed to the code area is made writable.
     ///
     /// # Panics
     /// Panics if the module is already unsealed (in debug builds).
     pub fn unseal(&mut self) {
         debug_assert!(!self.unsealed, "Module is already unsealed");
+        self.set_code_writable(true);
         self.unsealed = true;
     }
 
     /// Seal a module (make it read-only after modification)
     ///
     /// Equivalent to erts_seal_module(). The module must be unsealed.
+    /// Code committed to the code area is made read-only again.
     ///
     /// # Panics
     /// Panics if the module is not unsealed (in debug builds).
     pub fn seal(&mut self) {
         debug_assert!(self.unsealed, "Module is not unsealed");
+        self.set_code_writable(false);
         self.unsealed = false;
     }
 
+    /// Change the access of the code if it lies in the code area
+    fn set_code_writable(&self, writable: bool) {
+        let (Some(code), Some(area)) = (self.code_hdr, reserved_code_area()) else {
+            return;
+        };
+        if area.contains(code as *const u8) {
+            // Protection only catches stray writes; the code is usable either way
+            let _ = code_area::set_code_writable(&area, code as *const u8, self.code_length as usize, writable);
+        }
+    }
+
     /// Convert a code pointer to a writable pointer
     ///
     /// Equivalent to erts_writable_code_ptr(). The module must be unsealed.
@@ -145,6 +168,8 @@ impl Default for ModuleInstance {
             num_traced_exports: 0,
             executable_region: None,
             writable_region: None,
+            literals: None,
+            literals_length: 0,
             metadata: None,
             unsealed: false,
         };
@@ -253,6 +278,24 @@ impl ModuleTable {
         }
     }
 
+    /// Update a module in the table
+    ///
+    /// Modules shared with another table are copied before they are updated,
+    /// so the other table keeps its version.
+    ///
+    /// # Arguments
+    /// * `module` - Module atom index
+    /// * `update` - Function applied to the module
+    ///
+    /// # Returns
+    /// The updated module, or None if it is not in the table
+    pub fn update_module<F: FnOnce(&mut Module)>(&self, module: u32, update: F) -> Option<Arc<Module>> {
+        let mut modules = self.modules.write().unwrap();
+        let entry = modules.get_mut(&module)?;
+        update(Arc::make_mut(entry));
+        Some(Arc::clone(entry))
+    }
+
     /// Get the number of modules in the table
     pub fn size(&self) -> usize {
         let modules = self.modules.read().unwrap();
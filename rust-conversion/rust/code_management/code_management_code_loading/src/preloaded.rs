@@ -0,0 +1,261 @@
+//! Preloaded Module Embedding
+//!
+//! Provides the preloaded modules (`erl_prim_loader`, `init`, `prim_file`,
+//! `erlang`, ...) that are built into the emulator and loaded before the boot
+//! script runs, since they are needed to read and interpret it.
+//!
+//! The modules are embedded at build time: the build script includes every
+//! `.beam` file in the directory named by `IRON_BEAM_PRELOADED_DIR`. A build
+//! without the variable embeds no modules. [`load_preloaded`] loads the
+//! embedded modules through the BEAM loader in [`PRELOAD_ORDER`].
+//!
+//! Based on the `preload` table generated by `make_preload` and
+//! `erts_preloaded()`/`erl_first_process_otp()` in erl_init.c.
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ */
+
+use crate::beam_loader::{BeamFile, BeamFileReadResult, BeamLoadError, BeamLoader};
+use crate::module_management::get_global_module_manager;
+
+/// Preloaded modules embedded at build time, sorted by name
+static EMBEDDED_PRELOADED: &[(&str, &[u8])] =
+    include!(concat!(env!("OUT_DIR"), "/preloaded_modules.rs"));
+
+/// Load order of the OTP preloaded modules (`PRE_LOADED_ERL_MODULES`)
+///
+/// Embedded modules not listed here are loaded after these, by name.
+pub const PRELOAD_ORDER: [&str; 20] = [
+    "erl_prim_loader",
+    "init",
+    "prim_buffer",
+    "prim_file",
+    "prim_inet",
+    "zlib",
+    "socket_registry",
+    "prim_socket",
+    "prim_net",
+    "prim_zip",
+    "erl_init",
+    "erts_code_purger",
+    "erlang",
+    "erts_internal",
+    "erl_tracer",
+    "erts_literal_area_collector",
+    "erts_dirty_process_signal_handler",
+    "atomics",
+    "counters",
+    "persistent_term",
+];
+
+/// A preloaded module that has been loaded
+#[derive(Debug, Clone)]
+pub struct PreloadedModule {
+    /// Module name
+    pub name: String,
+    /// Module atom index used in the module table
+    pub atom: u32,
+    /// Parsed BEAM file
+    pub beam: BeamFile,
+}
+
+/// Preloaded module loading errors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreloadedError {
+    /// A preloaded module is not a valid BEAM file
+    InvalidBeam {
+        /// Module name
+        module: String,
+        /// Reason the file was rejected
+        reason: BeamFileReadResult,
+    },
+    /// The BEAM file contains a different module than its name says
+    ModuleNameMismatch {
+        /// Module name the file was embedded as
+        expected: String,
+        /// Module name in the file
+        found: Option<String>,
+    },
+    /// Loading the module into the module table failed
+    Load {
+        /// Module name
+        module: String,
+        /// Loader error
+        error: BeamLoadError,
+    },
+}
+
+impl std::fmt::Display for PreloadedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PreloadedError::InvalidBeam { module, reason } => {
+                write!(f, "Invalid BEAM file for preloaded module {}: {:?}", module, reason)
+            }
+            PreloadedError::ModuleNameMismatch { expected, found } => {
+                write!(f, "Expected preloaded module {}, found {:?}", expected, found)
+            }
+            PreloadedError::Load { module, error } => {
+                write!(f, "Failed to load preloaded module {}: {:?}", module, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PreloadedError {}
+
+/// Get the preloaded modules embedded at build time
+///
+/// # Returns
+/// Module names and BEAM binaries, sorted by name
+pub fn embedded_preloaded() -> &'static [(&'static str, &'static [u8])] {
+    EMBEDDED_PRELOADED
+}
+
+/// Load the embedded preloaded modules
+///
+/// # Arguments
+/// * `module_atom` - Gives the atom index of a module name
+///
+/// # Returns
+/// The loaded modules, in load order
+pub fn load_preloaded(module_atom: impl FnMut(&str) -> u32) -> Result<Vec<PreloadedModule>, PreloadedError> {
+    load_preloaded_modules(embedded_preloaded(), module_atom)
+}
+
+/// Load a set of preloaded modules
+///
+/// The modules are loaded into the global module table in [`PRELOAD_ORDER`],
+/// after checking that each binary holds the module it is named for.
+///
+/// # Arguments
+/// * `modules` - Module names and BEAM binaries
+/// * `module_atom` - Gives the atom index of a module name
+///
+/// # Returns
+/// The loaded modules, in load order
+pub fn load_preloaded_modules(
+    modules: &[(&str, &[u8])],
+    mut module_atom: impl FnMut(&str) -> u32,
+) -> Result<Vec<PreloadedModule>, PreloadedError> {
+    let mut ordered: Vec<&(&str, &[u8])> = modules.iter().collect();
+    ordered.sort_by_key(|(name, _)| {
+        let position = PRELOAD_ORDER.iter().position(|preloaded| preloaded == name);
+        (position.unwrap_or(PRELOAD_ORDER.len()), *name)
+    });
+
+    BeamLoader::init_load();
+    let module_manager = get_global_module_manager();
+    let mut loaded = Vec::with_capacity(ordered.len());
+    for &&(name, code) in &ordered {
+        let beam = BeamLoader::prepare_loading(code, None)
+            .map_err(|reason| PreloadedError::InvalidBeam { module: name.to_string(), reason })?;
+        if beam.module_name() != Some(name) {
+            return Err(PreloadedError::ModuleNameMismatch {
+                expected: name.to_string(),
+                found: beam.module_name().map(str::to_string),
+            });
+        }
+
+        let atom = module_atom(name);
+        BeamLoader::finish_loading(&beam, atom, module_manager)
+            .map_err(|error| PreloadedError::Load { module: name.to_string(), error })?;
+        loaded.push(PreloadedModule { name: name.to_string(), atom, beam });
+    }
+
+    Ok(loaded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal BEAM file holding an atom table and a code chunk
+    fn beam_for(module: &str) -> Vec<u8> {
+        let mut atoms = 1u32.to_be_bytes().to_vec();
+        atoms.push(module.len() as u8);
+        atoms.extend_from_slice(module.as_bytes());
+        while !atoms.len().is_multiple_of(4) {
+            atoms.push(0);
+        }
+
+        let mut body = b"BEAM".to_vec();
+        body.extend_from_slice(b"AtU8");
+        body.extend_from_slice(&(atoms.len() as u32).to_be_bytes());
+        body.extend_from_slice(&atoms);
+        body.extend_from_slice(b"Code");
+        body.extend_from_slice(&4u32.to_be_bytes());
+        body.extend_from_slice(&[0u8; 4]);
+
+        let mut data = b"FOR1".to_vec();
+        data.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        data.extend_from_slice(&body);
+        data
+    }
+
+    #[test]
+    fn test_embedded_preloaded_sorted() {
+        let names: Vec<&str> = embedded_preloaded().iter().map(|(name, _)| *name).collect();
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(names, sorted);
+    }
+
+    #[test]
+    fn test_load_in_preload_order() {
+        let extra = beam_for("preloaded_test_extra");
+        let init = beam_for("init");
+        let loader = beam_for("erl_prim_loader");
+        let modules: [(&str, &[u8]); 3] = [
+            ("preloaded_test_extra", &extra),
+            ("init", &init),
+            ("erl_prim_loader", &loader),
+        ];
+
+        let mut next_atom = 9000;
+        let loaded = load_preloaded_modules(&modules, |_| {
+            next_atom += 1;
+            next_atom
+        })
+        .unwrap();
+        let names: Vec<&str> = loaded.iter().map(|module| module.name.as_str()).collect();
+        assert_eq!(names, ["erl_prim_loader", "init", "preloaded_test_extra"]);
+        assert_eq!(loaded[0].atom, 9001);
+    }
+
+    #[test]
+    fn test_load_rejects_bad_modules() {
+        let result = load_preloaded_modules(&[("broken", b"not a beam")], |_| 9100);
+        assert!(matches!(result, Err(PreloadedError::InvalidBeam { .. })));
+
+        let other = beam_for("other");
+        let result = load_preloaded_modules(&[("expected", &other)], |_| 9101);
+        assert_eq!(
+            result.unwrap_err(),
+            PreloadedError::ModuleNameMismatch {
+                expected: "expected".to_string(),
+                found: Some("other".to_string()),
+            }
+        );
+    }
+}
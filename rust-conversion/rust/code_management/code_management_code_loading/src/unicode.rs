@@ -2,6 +2,13 @@
 //!
 //! Provides Unicode handling functionality.
 //! Based on erl_unicode.c - Unicode conversion and validation.
+//!
+//! The conversion engine behind `unicode:characters_to_binary/3` and
+//! `unicode:characters_to_list/2` converts chardata between latin1,
+//! UTF-8, UTF-16 and UTF-32, reporting invalid and incomplete input the
+//! way the BIFs do.
+
+use entities_data_handling::term_hashing::Term;
 
 /// Unicode handler
 pub struct UnicodeHandler;
@@ -92,6 +99,115 @@ impl UnicodeHandler {
     pub fn analyze_utf8(data: &[u8]) -> Result<usize, UnicodeError> {
         Self::count_utf8_chars(data)
     }
+
+    /// Convert character data to a binary (`unicode:characters_to_binary/3`)
+    ///
+    /// `data` is chardata: a binary, or a possibly deep and possibly
+    /// improper list of code points and binaries, with binaries encoded in
+    /// `input`. A character split across consecutive binaries is decoded
+    /// as one character.
+    ///
+    /// # Arguments
+    /// * `data` - Character data to convert
+    /// * `input` - Encoding of the binaries in `data`
+    /// * `output` - Encoding of the result
+    ///
+    /// # Returns
+    /// * `Ok(Conversion)` - The converted binary, or the part converted
+    ///   before an invalid or incomplete character
+    /// * `Err(UnicodeError::BadArgument)` - If `data` is not chardata
+    ///
+    /// # Examples
+    /// ```
+    /// use code_management_code_loading::unicode::{Conversion, Encoding, Endianness, UnicodeHandler};
+    /// use entities_data_handling::term_hashing::Term;
+    ///
+    /// let data = Term::List {
+    ///     head: Box::new(Term::Small(0x20AC)),
+    ///     tail: Box::new(Term::Nil),
+    /// };
+    /// let utf16 = Encoding::Utf16(Endianness::Big);
+    /// assert_eq!(
+    ///     UnicodeHandler::characters_to_binary(&data, Encoding::Utf8, utf16),
+    ///     Ok(Conversion::Complete(vec![0x20, 0xAC]))
+    /// );
+    /// ```
+    pub fn characters_to_binary(
+        data: &Term,
+        input: Encoding,
+        output: Encoding,
+    ) -> Result<Conversion<Vec<u8>>, UnicodeError> {
+        let mut converted = Vec::new();
+        let outcome = Self::convert(data, input, |ch| encode_char(ch, output, &mut converted))?;
+        Ok(outcome.with(converted))
+    }
+
+    /// Convert character data to a list of code points
+    /// (`unicode:characters_to_list/2`)
+    ///
+    /// # Arguments
+    /// * `data` - Character data to convert
+    /// * `input` - Encoding of the binaries in `data`
+    ///
+    /// # Returns
+    /// * `Ok(Conversion)` - The code points, or those converted before an
+    ///   invalid or incomplete character
+    /// * `Err(UnicodeError::BadArgument)` - If `data` is not chardata
+    pub fn characters_to_list(data: &Term, input: Encoding) -> Result<Conversion<Vec<u32>>, UnicodeError> {
+        let mut converted = Vec::new();
+        let outcome = Self::convert(data, input, |ch| {
+            converted.push(ch);
+            true
+        })?;
+        Ok(outcome.with(converted))
+    }
+
+    /// Decode `data` and pass each character to `emit`, which returns
+    /// false if the character cannot be represented in the output
+    fn convert(data: &Term, input: Encoding, mut emit: impl FnMut(u32) -> bool) -> Result<Outcome, UnicodeError> {
+        let items = flatten_chardata(data)?;
+        // Bytes of an incomplete character at the end of the previous binary
+        let mut pending: Vec<u8> = Vec::new();
+        for (index, item) in items.iter().enumerate() {
+            let bytes = match *item {
+                CharItem::Char(ch) => {
+                    let valid = match input {
+                        Encoding::Latin1 => (0..=0xFF).contains(&ch),
+                        _ => u32::try_from(ch).ok().and_then(char::from_u32).is_some(),
+                    };
+                    if !pending.is_empty() || !valid || !emit(ch as u32) {
+                        return Ok(Outcome::Error(rest_term(data, &pending, &items[index..])));
+                    }
+                    continue;
+                }
+                CharItem::Bytes(bytes) => bytes,
+            };
+            let buffer = if pending.is_empty() {
+                bytes.to_vec()
+            } else {
+                [pending.as_slice(), bytes].concat()
+            };
+            pending.clear();
+            let mut position = 0;
+            while position < buffer.len() {
+                match decode_char(&buffer[position..], input) {
+                    Decoded::Char(ch, length) if emit(ch) => position += length,
+                    Decoded::Incomplete => {
+                        pending = buffer[position..].to_vec();
+                        break;
+                    }
+                    Decoded::Char(..) | Decoded::Invalid => {
+                        return Ok(Outcome::Error(rest_term(data, &buffer[position..], &items[index + 1..])));
+                    }
+                }
+            }
+        }
+        if pending.is_empty() {
+            Ok(Outcome::Complete)
+        } else {
+            Ok(Outcome::Incomplete(pending))
+        }
+    }
 }
 
 /// Unicode operation errors
@@ -99,6 +215,257 @@ impl UnicodeHandler {
 pub enum UnicodeError {
     /// Invalid UTF-8 encoding
     InvalidUtf8,
+    /// Argument is not valid character data
+    BadArgument,
+}
+
+/// Byte order of UTF-16 and UTF-32
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    /// Most significant byte first
+    Big,
+    /// Least significant byte first
+    Little,
+}
+
+/// Character encoding of binaries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// ISO-8859-1, one byte per character
+    Latin1,
+    /// UTF-8 (`unicode` or `utf8`)
+    Utf8,
+    /// UTF-16
+    Utf16(Endianness),
+    /// UTF-32
+    Utf32(Endianness),
+}
+
+impl Encoding {
+    /// Look up an encoding by its atom name
+    ///
+    /// `utf16` and `utf32` are big endian, as in the `unicode` module.
+    ///
+    /// # Arguments
+    /// * `name` - Encoding name such as `"unicode"` or `"utf16"`
+    ///
+    /// # Returns
+    /// The encoding, or `None` if the name is unknown
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "latin1" => Some(Encoding::Latin1),
+            "unicode" | "utf8" => Some(Encoding::Utf8),
+            "utf16" => Some(Encoding::Utf16(Endianness::Big)),
+            "utf32" => Some(Encoding::Utf32(Endianness::Big)),
+            _ => None,
+        }
+    }
+}
+
+/// Result of a chardata conversion
+#[derive(Debug, Clone, PartialEq)]
+pub enum Conversion<T> {
+    /// All data was converted
+    Complete(T),
+    /// An invalid character was found (`{error, Converted, RestData}`)
+    ///
+    /// `rest` starts at the invalid character.
+    Error { converted: T, rest: Term },
+    /// The data ends in the middle of a character
+    /// (`{incomplete, Converted, RestBinary}`)
+    Incomplete { converted: T, rest: Vec<u8> },
+}
+
+/// How a conversion ended, before the converted data is attached
+enum Outcome {
+    Complete,
+    Error(Term),
+    Incomplete(Vec<u8>),
+}
+
+impl Outcome {
+    fn with<T>(self, converted: T) -> Conversion<T> {
+        match self {
+            Outcome::Complete => Conversion::Complete(converted),
+            Outcome::Error(rest) => Conversion::Error { converted, rest },
+            Outcome::Incomplete(rest) => Conversion::Incomplete { converted, rest },
+        }
+    }
+}
+
+/// Element of flattened chardata
+#[derive(Debug, Clone, Copy)]
+enum CharItem<'a> {
+    Char(i64),
+    Bytes(&'a [u8]),
+}
+
+/// Result of decoding one character from a binary
+enum Decoded {
+    /// Code point and its length in bytes
+    Char(u32, usize),
+    /// The bytes are a prefix of a valid encoding
+    Incomplete,
+    Invalid,
+}
+
+/// Flatten chardata into code points and binaries, in order
+///
+/// Uses an explicit stack, since long lists nest through their tails.
+fn flatten_chardata(data: &Term) -> Result<Vec<CharItem<'_>>, UnicodeError> {
+    let mut items = Vec::new();
+    // Terms still to visit, with whether they are in a list tail (or the
+    // top level), where only lists and binaries are allowed
+    let mut stack = vec![(data, true)];
+    while let Some((term, in_tail)) = stack.pop() {
+        match term {
+            Term::Nil => {}
+            Term::List { head, tail } => {
+                stack.push((tail, true));
+                stack.push((head, false));
+            }
+            Term::Binary {
+                data,
+                bit_offset: 0,
+                bit_size,
+            } if bit_size % 8 == 0 && bit_size / 8 <= data.len() => {
+                items.push(CharItem::Bytes(&data[..bit_size / 8]));
+            }
+            Term::Small(ch) if !in_tail => items.push(CharItem::Char(*ch)),
+            _ => return Err(UnicodeError::BadArgument),
+        }
+    }
+    Ok(items)
+}
+
+/// Remaining data after an invalid character
+///
+/// A binary if `data` was a single binary, otherwise a list of the
+/// unconverted bytes of the current binary followed by the remaining items.
+fn rest_term(data: &Term, bytes: &[u8], items: &[CharItem]) -> Term {
+    let binary = |bytes: &[u8]| Term::Binary {
+        data: bytes.to_vec(),
+        bit_offset: 0,
+        bit_size: bytes.len() * 8,
+    };
+    if matches!(data, Term::Binary { .. }) {
+        return binary(bytes);
+    }
+    let leading = (!bytes.is_empty()).then(|| binary(bytes));
+    let terms: Vec<Term> = leading
+        .into_iter()
+        .chain(items.iter().map(|item| match *item {
+            CharItem::Char(ch) => Term::Small(ch),
+            CharItem::Bytes(bytes) => binary(bytes),
+        }))
+        .collect();
+    terms.into_iter().rev().fold(Term::Nil, |tail, head| Term::List {
+        head: Box::new(head),
+        tail: Box::new(tail),
+    })
+}
+
+/// Decode the first character of `bytes`
+fn decode_char(bytes: &[u8], encoding: Encoding) -> Decoded {
+    match encoding {
+        Encoding::Latin1 => Decoded::Char(bytes[0] as u32, 1),
+        Encoding::Utf8 => decode_utf8(bytes),
+        Encoding::Utf16(endianness) => {
+            let unit = |i: usize| -> Option<u32> {
+                let pair = [*bytes.get(i)?, *bytes.get(i + 1)?];
+                Some(match endianness {
+                    Endianness::Big => u16::from_be_bytes(pair),
+                    Endianness::Little => u16::from_le_bytes(pair),
+                } as u32)
+            };
+            match unit(0) {
+                None => Decoded::Incomplete,
+                Some(high @ 0xD800..=0xDBFF) => match unit(2) {
+                    None => Decoded::Incomplete,
+                    Some(low @ 0xDC00..=0xDFFF) => {
+                        Decoded::Char(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00), 4)
+                    }
+                    Some(_) => Decoded::Invalid,
+                },
+                Some(0xDC00..=0xDFFF) => Decoded::Invalid,
+                Some(ch) => Decoded::Char(ch, 2),
+            }
+        }
+        Encoding::Utf32(endianness) => {
+            let Some(quad) = bytes.get(..4) else {
+                return Decoded::Incomplete;
+            };
+            let quad = [quad[0], quad[1], quad[2], quad[3]];
+            let ch = match endianness {
+                Endianness::Big => u32::from_be_bytes(quad),
+                Endianness::Little => u32::from_le_bytes(quad),
+            };
+            match char::from_u32(ch) {
+                Some(_) => Decoded::Char(ch, 4),
+                None => Decoded::Invalid,
+            }
+        }
+    }
+}
+
+/// Decode the first UTF-8 character of `bytes`, rejecting overlong forms,
+/// surrogates and code points above U+10FFFF
+fn decode_utf8(bytes: &[u8]) -> Decoded {
+    let first = bytes[0];
+    // Sequence length and the valid range of the second byte
+    let (length, second) = match first {
+        0x00..=0x7F => return Decoded::Char(first as u32, 1),
+        0xC2..=0xDF => (2, 0x80..=0xBF),
+        0xE0 => (3, 0xA0..=0xBF),
+        0xED => (3, 0x80..=0x9F),
+        0xE1..=0xEF => (3, 0x80..=0xBF),
+        0xF0 => (4, 0x90..=0xBF),
+        0xF1..=0xF3 => (4, 0x80..=0xBF),
+        0xF4 => (4, 0x80..=0x8F),
+        _ => return Decoded::Invalid,
+    };
+    for (i, &byte) in bytes.iter().enumerate().take(length).skip(1) {
+        let valid = if i == 1 { second.contains(&byte) } else { (0x80..=0xBF).contains(&byte) };
+        if !valid {
+            return Decoded::Invalid;
+        }
+    }
+    if bytes.len() < length {
+        return Decoded::Incomplete;
+    }
+    let initial = (first as u32) & (0x7F >> length);
+    let ch = bytes[1..length]
+        .iter()
+        .fold(initial, |ch, &byte| (ch << 6) | (byte & 0x3F) as u32);
+    Decoded::Char(ch, length)
+}
+
+/// Append `ch` to `out` in `encoding`
+///
+/// # Returns
+/// false if `ch` cannot be represented in `encoding`
+fn encode_char(ch: u32, encoding: Encoding, out: &mut Vec<u8>) -> bool {
+    let Some(c) = char::from_u32(ch) else {
+        return false;
+    };
+    match encoding {
+        Encoding::Latin1 => match u8::try_from(ch) {
+            Ok(byte) => out.push(byte),
+            Err(_) => return false,
+        },
+        Encoding::Utf8 => out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        Encoding::Utf16(endianness) => {
+            for unit in c.encode_utf16(&mut [0; 2]) {
+                match endianness {
+                    Endianness::Big => out.extend_from_slice(&unit.to_be_bytes()),
+                    Endianness::Little => out.extend_from_slice(&unit.to_le_bytes()),
+                }
+            }
+        }
+        Encoding::Utf32(Endianness::Big) => out.extend_from_slice(&ch.to_be_bytes()),
+        Encoding::Utf32(Endianness::Little) => out.extend_from_slice(&ch.to_le_bytes()),
+    }
+    true
 }
 
 #[cfg(test)]
@@ -157,5 +524,211 @@ mod tests {
         let invalid = &[0xFF, 0xFE];
         assert!(UnicodeHandler::analyze_utf8(invalid).is_err());
     }
-}
 
+    fn binary(bytes: &[u8]) -> Term {
+        Term::Binary {
+            data: bytes.to_vec(),
+            bit_offset: 0,
+            bit_size: bytes.len() * 8,
+        }
+    }
+
+    fn list(items: Vec<Term>) -> Term {
+        items.into_iter().rev().fold(Term::Nil, |tail, head| Term::List {
+            head: Box::new(head),
+            tail: Box::new(tail),
+        })
+    }
+
+    fn chars(text: &str) -> Vec<Term> {
+        text.chars().map(|c| Term::Small(c as i64)).collect()
+    }
+
+    #[test]
+    fn test_encoding_from_name() {
+        assert_eq!(Encoding::from_name("unicode"), Some(Encoding::Utf8));
+        assert_eq!(Encoding::from_name("utf16"), Some(Encoding::Utf16(Endianness::Big)));
+        assert_eq!(Encoding::from_name("latin1"), Some(Encoding::Latin1));
+        assert_eq!(Encoding::from_name("ascii"), None);
+    }
+
+    #[test]
+    fn test_characters_to_binary_encodings() {
+        let text = "aé€😀";
+        let data = list(chars(text));
+        let convert = |output| UnicodeHandler::characters_to_binary(&data, Encoding::Utf8, output).unwrap();
+        assert_eq!(convert(Encoding::Utf8), Conversion::Complete(text.as_bytes().to_vec()));
+
+        let utf16: Vec<u16> = text.encode_utf16().collect();
+        let big: Vec<u8> = utf16.iter().flat_map(|u| u.to_be_bytes()).collect();
+        let little: Vec<u8> = utf16.iter().flat_map(|u| u.to_le_bytes()).collect();
+        assert_eq!(convert(Encoding::Utf16(Endianness::Big)), Conversion::Complete(big.clone()));
+        assert_eq!(convert(Encoding::Utf16(Endianness::Little)), Conversion::Complete(little.clone()));
+
+        let utf32: Vec<u8> = text.chars().flat_map(|c| (c as u32).to_le_bytes()).collect();
+        assert_eq!(convert(Encoding::Utf32(Endianness::Little)), Conversion::Complete(utf32.clone()));
+
+        // Decoding each encoding gives the code points back
+        let code_points: Vec<u32> = text.chars().map(|c| c as u32).collect();
+        for (bytes, encoding) in [
+            (big, Encoding::Utf16(Endianness::Big)),
+            (little, Encoding::Utf16(Endianness::Little)),
+            (utf32, Encoding::Utf32(Endianness::Little)),
+        ] {
+            assert_eq!(
+                UnicodeHandler::characters_to_list(&binary(&bytes), encoding),
+                Ok(Conversion::Complete(code_points.clone()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_latin1_conversion() {
+        let data = binary(&[b'a', 0xE9, 0xFF]);
+        assert_eq!(
+            UnicodeHandler::characters_to_binary(&data, Encoding::Latin1, Encoding::Utf8),
+            Ok(Conversion::Complete("aéÿ".as_bytes().to_vec()))
+        );
+        // Characters above 255 cannot be written as latin1
+        let data = list(vec![binary("é€x".as_bytes())]);
+        assert_eq!(
+            UnicodeHandler::characters_to_binary(&data, Encoding::Utf8, Encoding::Latin1),
+            Ok(Conversion::Error {
+                converted: vec![0xE9],
+                rest: list(vec![binary("€x".as_bytes())]),
+            })
+        );
+        // Nor read from latin1 lists
+        let data = list(vec![Term::Small(b'a' as i64), Term::Small(256)]);
+        assert_eq!(
+            UnicodeHandler::characters_to_list(&data, Encoding::Latin1),
+            Ok(Conversion::Error {
+                converted: vec![b'a' as u32],
+                rest: list(vec![Term::Small(256)]),
+            })
+        );
+    }
+
+    #[test]
+    fn test_deep_and_improper_chardata() {
+        // [$a, [<<"b">>, [], [$c]] | <<"d">>]
+        let data = Term::List {
+            head: Box::new(Term::Small(b'a' as i64)),
+            tail: Box::new(Term::List {
+                head: Box::new(list(vec![binary(b"b"), Term::Nil, list(chars("c"))])),
+                tail: Box::new(binary(b"d")),
+            }),
+        };
+        assert_eq!(
+            UnicodeHandler::characters_to_binary(&data, Encoding::Utf8, Encoding::Utf8),
+            Ok(Conversion::Complete(b"abcd".to_vec()))
+        );
+
+        let long = list(vec![Term::Small(b'x' as i64); 10_000]);
+        match UnicodeHandler::characters_to_list(&long, Encoding::Utf8) {
+            Ok(Conversion::Complete(converted)) => assert_eq!(converted.len(), 10_000),
+            other => panic!("unexpected conversion {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_bad_arguments() {
+        let bad = [
+            Term::Small(b'a' as i64),
+            Term::Atom(1),
+            list(vec![Term::Tuple(vec![])]),
+            Term::List {
+                head: Box::new(Term::Small(b'a' as i64)),
+                tail: Box::new(Term::Small(b'b' as i64)),
+            },
+            Term::Binary {
+                data: vec![0xFF],
+                bit_offset: 0,
+                bit_size: 7,
+            },
+        ];
+        for data in &bad {
+            assert_eq!(
+                UnicodeHandler::characters_to_list(data, Encoding::Utf8),
+                Err(UnicodeError::BadArgument),
+                "{:?}",
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        // Overlong, surrogate, above U+10FFFF and stray continuation bytes
+        for invalid in [&[0xC0, 0x80][..], &[0xED, 0xA0, 0x80], &[0xF4, 0x90, 0x80, 0x80], &[0x80]] {
+            let data = binary(&[&b"ok"[..], invalid].concat());
+            assert_eq!(
+                UnicodeHandler::characters_to_binary(&data, Encoding::Utf8, Encoding::Utf8),
+                Ok(Conversion::Error {
+                    converted: b"ok".to_vec(),
+                    rest: binary(invalid),
+                }),
+                "{:?}",
+                invalid
+            );
+        }
+
+        // Surrogate code points in lists
+        let data = list(vec![Term::Small(b'a' as i64), Term::Small(0xD800), binary(b"b")]);
+        assert_eq!(
+            UnicodeHandler::characters_to_list(&data, Encoding::Utf8),
+            Ok(Conversion::Error {
+                converted: vec![b'a' as u32],
+                rest: list(vec![Term::Small(0xD800), binary(b"b")]),
+            })
+        );
+    }
+
+    #[test]
+    fn test_incomplete_characters() {
+        let euro = "€".as_bytes();
+        let data = binary(&[b"a", &euro[..2]].concat());
+        assert_eq!(
+            UnicodeHandler::characters_to_binary(&data, Encoding::Utf8, Encoding::Utf8),
+            Ok(Conversion::Incomplete {
+                converted: b"a".to_vec(),
+                rest: euro[..2].to_vec(),
+            })
+        );
+
+        // A character split across binaries is joined
+        let data = list(vec![binary(&euro[..1]), binary(&euro[1..2]), binary(&euro[2..])]);
+        assert_eq!(
+            UnicodeHandler::characters_to_list(&data, Encoding::Utf8),
+            Ok(Conversion::Complete(vec![0x20AC]))
+        );
+
+        // But not across an integer
+        let data = list(vec![binary(&euro[..2]), Term::Small(b'x' as i64)]);
+        assert_eq!(
+            UnicodeHandler::characters_to_list(&data, Encoding::Utf8),
+            Ok(Conversion::Error {
+                converted: vec![],
+                rest: list(vec![binary(&euro[..2]), Term::Small(b'x' as i64)]),
+            })
+        );
+
+        // A lone high surrogate and a short UTF-32 unit
+        let data = binary(&[0x00, 0x61, 0xD8, 0x3D]);
+        assert_eq!(
+            UnicodeHandler::characters_to_list(&data, Encoding::Utf16(Endianness::Big)),
+            Ok(Conversion::Incomplete {
+                converted: vec![0x61],
+                rest: vec![0xD8, 0x3D],
+            })
+        );
+        let data = binary(&[0x61, 0, 0, 0, 0x62]);
+        assert_eq!(
+            UnicodeHandler::characters_to_list(&data, Encoding::Utf32(Endianness::Little)),
+            Ok(Conversion::Incomplete {
+                converted: vec![0x61],
+                rest: vec![0x62],
+            })
+        );
+    }
+}
@@ -308,3 +308,40 @@ fn test_global_singletons_integration() {
     assert!(index.is_some());
 }
 
+
+#[test]
+fn test_unicode_chardata_conversion_roundtrip() {
+    use code_management_code_loading::unicode::{Conversion, Encoding, Endianness, UnicodeHandler};
+    use entities_data_handling::term_hashing::Term;
+
+    let binary = |bytes: &[u8]| Term::Binary {
+        data: bytes.to_vec(),
+        bit_offset: 0,
+        bit_size: bytes.len() * 8,
+    };
+    // ["Grüß ", <<"世"/utf8>> | <<"界"/utf8>>]
+    let data = Term::List {
+        head: Box::new(binary("Grüß ".as_bytes())),
+        tail: Box::new(Term::List {
+            head: Box::new(binary("世".as_bytes())),
+            tail: Box::new(binary("界".as_bytes())),
+        }),
+    };
+    let utf16 = Encoding::Utf16(Endianness::Little);
+    let Ok(Conversion::Complete(encoded)) = UnicodeHandler::characters_to_binary(&data, Encoding::Utf8, utf16) else {
+        panic!("conversion to UTF-16 failed");
+    };
+    assert_eq!(encoded.len(), 7 * 2);
+
+    // Feed the UTF-16 back in two chunks split inside a character
+    let (first, second) = encoded.split_at(5);
+    let Ok(Conversion::Incomplete { converted, rest }) = UnicodeHandler::characters_to_list(&binary(first), utf16) else {
+        panic!("expected an incomplete first chunk");
+    };
+    assert_eq!(converted, vec!['G' as u32, 'r' as u32]);
+    let resumed = [rest.as_slice(), second].concat();
+    let Ok(Conversion::Complete(tail)) = UnicodeHandler::characters_to_binary(&binary(&resumed), utf16, Encoding::Utf8) else {
+        panic!("expected the second chunk to complete");
+    };
+    assert_eq!(String::from_utf8(tail).unwrap(), "üß 世界");
+}
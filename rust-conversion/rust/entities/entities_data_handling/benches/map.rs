@@ -0,0 +1,120 @@
+//! Map Benchmarks
+//!
+//! Compares the flatmap layout of [`Map`] (contiguous key array plus value
+//! array) with a per-entry layout where every key-value pair is a separate
+//! allocation, for `get`, `put` and `merge` on small maps of 1 to 32 keys.
+//!
+//! Run with `cargo bench -p entities_data_handling --bench map`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use entities_data_handling::term_hashing::Term;
+use entities_data_handling::Map;
+
+const SIZES: [i64; 6] = [1, 2, 4, 8, 16, 32];
+
+/// Map of key-value pair entries used as the baseline
+#[derive(Clone)]
+struct PerEntryMap {
+    entries: Vec<(Term, Term)>,
+}
+
+impl PerEntryMap {
+    fn get(&self, key: &Term) -> Option<&Term> {
+        self.entries.iter().find(|e| e.0 == *key).map(|e| &e.1)
+    }
+
+    fn put(&mut self, key: Term, value: Term) {
+        match self.entries.iter_mut().find(|e| e.0 == key) {
+            Some(entry) => entry.1 = value,
+            None => self.entries.push((key, value)),
+        }
+    }
+
+    fn merge(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for entry in &other.entries {
+            result.put(entry.0.clone(), entry.1.clone());
+        }
+        result
+    }
+}
+
+fn pairs(range: std::ops::Range<i64>) -> Vec<(Term, Term)> {
+    range
+        .map(|i| (Term::Atom(i as u32), Term::Small(i)))
+        .collect()
+}
+
+fn flat(range: std::ops::Range<i64>) -> Map {
+    Map::from_list(pairs(range))
+}
+
+fn per_entry(range: std::ops::Range<i64>) -> PerEntryMap {
+    let mut map = PerEntryMap { entries: Vec::new() };
+    for (key, value) in pairs(range) {
+        map.put(key, value);
+    }
+    map
+}
+
+fn bench_get(c: &mut Criterion) {
+    let mut group = c.benchmark_group("map_get");
+    for size in SIZES {
+        // The last key is the worst case for a linear scan
+        let key = Term::Atom((size - 1) as u32);
+        let map = flat(0..size);
+        group.bench_with_input(BenchmarkId::new("flatmap", size), &key, |b, key| {
+            b.iter(|| black_box(map.get(black_box(key))))
+        });
+        let map = per_entry(0..size);
+        group.bench_with_input(BenchmarkId::new("per_entry", size), &key, |b, key| {
+            b.iter(|| black_box(map.get(black_box(key))))
+        });
+    }
+    group.finish();
+}
+
+fn bench_put(c: &mut Criterion) {
+    let mut group = c.benchmark_group("map_put");
+    for size in SIZES {
+        // Updating an existing key of a copy, as `M#{K := V}` does
+        let key = Term::Atom((size - 1) as u32);
+        let map = flat(0..size);
+        group.bench_with_input(BenchmarkId::new("flatmap", size), &key, |b, key| {
+            b.iter(|| {
+                let mut copy = map.clone();
+                copy.put(key.clone(), Term::Small(-1));
+                black_box(copy)
+            })
+        });
+        let map = per_entry(0..size);
+        group.bench_with_input(BenchmarkId::new("per_entry", size), &key, |b, key| {
+            b.iter(|| {
+                let mut copy = map.clone();
+                copy.put(key.clone(), Term::Small(-1));
+                black_box(copy)
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_merge(c: &mut Criterion) {
+    let mut group = c.benchmark_group("map_merge");
+    for size in SIZES {
+        // Second map overlaps the upper half of the first one
+        let half = size / 2;
+        let (left, right) = (flat(0..size), flat(half..half + size));
+        group.bench_function(BenchmarkId::new("flatmap", size), |b| {
+            b.iter(|| black_box(left.merge(black_box(&right))))
+        });
+        let (left, right) = (per_entry(0..size), per_entry(half..half + size));
+        group.bench_function(BenchmarkId::new("per_entry", size), |b| {
+            b.iter(|| black_box(left.merge(black_box(&right))))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_get, bench_put, bench_merge);
+criterion_main!(benches);
@@ -16,6 +16,8 @@
 //! - **Multiple Encoding Support**: Handles 7-bit ASCII, Latin1, and UTF-8 encodings
 //! - **Validation**: Validates atom names according to encoding rules and length limits
 //! - **Encoding Conversion**: Automatically converts Latin1 to UTF-8 for internal storage
+//! - **Snapshots**: Serializes the whole table, preserving atom indices, for code
+//!   snapshots and crash dumps
 //!
 //! ## Encoding Support
 //!
@@ -81,7 +83,9 @@
  */
 
 use std::sync::RwLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::collections::HashMap;
+use std::io::{Read, Write};
 
 /// Atom encoding types
 ///
@@ -137,6 +141,12 @@ pub const MAX_ATOM_CHARACTERS: usize = 255;
 /// Maximum atom size limit in bytes
 pub const MAX_ATOM_SZ_LIMIT: usize = 1024;
 
+/// Magic bytes at the start of a serialized atom table
+pub const ATOM_TABLE_MAGIC: &[u8; 4] = b"ATBL";
+
+/// Version of the serialized atom table format
+pub const ATOM_TABLE_FORMAT_VERSION: u8 = 1;
+
 /// Atom representation
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Atom {
@@ -156,6 +166,8 @@ pub struct AtomTable {
     index_to_name: RwLock<Vec<Option<Vec<u8>>>>,
     /// Current number of atoms
     entries: RwLock<usize>,
+    /// Bytes in atom names
+    name_bytes: AtomicUsize,
     /// Maximum number of atoms
     limit: usize,
 }
@@ -167,6 +179,7 @@ impl AtomTable {
             atoms: RwLock::new(HashMap::new()),
             index_to_name: RwLock::new(Vec::new()),
             entries: RwLock::new(0),
+            name_bytes: AtomicUsize::new(0),
             limit,
         }
     }
@@ -245,6 +258,7 @@ impl AtomTable {
         if index >= index_to_name.len() {
             index_to_name.resize(index + 1, None);
         }
+        self.name_bytes.fetch_add(validated_name.len(), Ordering::Relaxed);
         index_to_name[index] = Some(validated_name);
         *entries += 1;
 
@@ -362,6 +376,142 @@ impl AtomTable {
         *self.entries.read().unwrap()
     }
 
+    /// Get the maximum number of atoms the table can hold
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Get the bytes allocated for the table (`erlang:memory(atom)`)
+    ///
+    /// Counts the capacity of both maps and the atom names, which are kept
+    /// in each of them.
+    pub fn allocated_bytes(&self) -> usize {
+        let map_capacity = self.atoms.read().unwrap().capacity();
+        let index_capacity = self.index_to_name.read().unwrap().capacity();
+        map_capacity * (std::mem::size_of::<Vec<u8>>() + std::mem::size_of::<usize>())
+            + index_capacity * std::mem::size_of::<Option<Vec<u8>>>()
+            + 2 * self.name_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Get the bytes used by the atoms in the table (`erlang:memory(atom_used)`)
+    pub fn used_bytes(&self) -> usize {
+        let entry_size = std::mem::size_of::<Vec<u8>>()
+            + std::mem::size_of::<usize>()
+            + std::mem::size_of::<Option<Vec<u8>>>();
+        self.size() * entry_size + 2 * self.name_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Serialize the full atom table
+    ///
+    /// Writes every atom in index order using a stable binary format, so that
+    /// [`deserialize`](Self::deserialize) recreates each atom at its original
+    /// index. All integers are big-endian:
+    ///
+    /// | Field | Size |
+    /// |-------|------|
+    /// | Magic `ATBL` | 4 bytes |
+    /// | Format version ([`ATOM_TABLE_FORMAT_VERSION`]) | 1 byte |
+    /// | Table limit | 8 bytes |
+    /// | Atom count | 8 bytes |
+    /// | Per atom: name length, then UTF-8 name bytes | 2 bytes + length |
+    ///
+    /// # Arguments
+    /// * `writer` - Destination of the serialized table
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the table was written
+    /// * `Err(AtomSnapshotError::Io)` - If writing failed
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use entities_data_handling::{AtomTable, AtomEncoding};
+    ///
+    /// let table = AtomTable::new(1000);
+    /// let index = table.put_index(b"saved", AtomEncoding::SevenBitAscii, false).unwrap();
+    ///
+    /// let mut buf = Vec::new();
+    /// table.serialize(&mut buf).unwrap();
+    ///
+    /// let restored = AtomTable::deserialize(&mut buf.as_slice()).unwrap();
+    /// assert_eq!(restored.get_name(index), Some(b"saved".to_vec()));
+    /// ```
+    pub fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), AtomSnapshotError> {
+        let index_to_name = self.index_to_name.read().unwrap();
+        let entries = *self.entries.read().unwrap();
+
+        writer.write_all(ATOM_TABLE_MAGIC)?;
+        writer.write_all(&[ATOM_TABLE_FORMAT_VERSION])?;
+        writer.write_all(&(self.limit as u64).to_be_bytes())?;
+        writer.write_all(&(entries as u64).to_be_bytes())?;
+        for name in index_to_name.iter().take(entries) {
+            let name = name.as_deref().unwrap_or_default();
+            writer.write_all(&(name.len() as u16).to_be_bytes())?;
+            writer.write_all(name)?;
+        }
+        Ok(())
+    }
+
+    /// Deserialize an atom table written by [`serialize`](Self::serialize)
+    ///
+    /// Every atom is restored at the index it had when serialized, and the
+    /// table keeps its original limit. The input is validated: names must be
+    /// valid UTF-8 within [`MAX_ATOM_SZ_LIMIT`] bytes and may not repeat.
+    ///
+    /// # Arguments
+    /// * `reader` - Source of the serialized table
+    ///
+    /// # Returns
+    /// * `Ok(AtomTable)` - The restored table
+    /// * `Err(AtomSnapshotError)` - If the input is truncated or malformed
+    pub fn deserialize<R: Read>(reader: &mut R) -> Result<Self, AtomSnapshotError> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != ATOM_TABLE_MAGIC {
+            return Err(AtomSnapshotError::BadMagic);
+        }
+        let mut version = [0u8; 1];
+        reader.read_exact(&mut version)?;
+        if version[0] != ATOM_TABLE_FORMAT_VERSION {
+            return Err(AtomSnapshotError::UnsupportedVersion(version[0]));
+        }
+        let limit = read_u64(reader)? as usize;
+        let count = read_u64(reader)? as usize;
+        if count > limit {
+            return Err(AtomSnapshotError::TooManyAtoms);
+        }
+
+        let mut atoms = HashMap::new();
+        let mut index_to_name = Vec::new();
+        let mut name_bytes = 0;
+        for index in 0..count {
+            let mut len = [0u8; 2];
+            reader.read_exact(&mut len)?;
+            let len = u16::from_be_bytes(len) as usize;
+            if len > MAX_ATOM_SZ_LIMIT {
+                return Err(AtomSnapshotError::InvalidAtom(index));
+            }
+            let mut name = vec![0u8; len];
+            reader.read_exact(&mut name)?;
+            if std::str::from_utf8(&name).is_err() {
+                return Err(AtomSnapshotError::InvalidAtom(index));
+            }
+            if atoms.insert(name.clone(), index).is_some() {
+                return Err(AtomSnapshotError::DuplicateAtom(index));
+            }
+            name_bytes += name.len();
+            index_to_name.push(Some(name));
+        }
+
+        Ok(Self {
+            atoms: RwLock::new(atoms),
+            index_to_name: RwLock::new(index_to_name),
+            entries: RwLock::new(count),
+            name_bytes: AtomicUsize::new(name_bytes),
+            limit,
+        })
+    }
+
     fn validate_atom_name(
         &self,
         name: &[u8],
@@ -450,6 +600,54 @@ pub enum AtomError {
     TableFull,
 }
 
+/// Atom table snapshot errors
+///
+/// Returned by [`AtomTable::serialize`] and [`AtomTable::deserialize`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtomSnapshotError {
+    /// Reading or writing the snapshot failed
+    Io(std::io::ErrorKind),
+    /// The input does not start with [`ATOM_TABLE_MAGIC`]
+    BadMagic,
+    /// The input uses a format version this runtime cannot read
+    UnsupportedVersion(u8),
+    /// The snapshot holds more atoms than its table limit
+    TooManyAtoms,
+    /// The atom at the given index is too long or not valid UTF-8
+    InvalidAtom(usize),
+    /// The atom at the given index repeats an earlier atom
+    DuplicateAtom(usize),
+}
+
+impl From<std::io::Error> for AtomSnapshotError {
+    fn from(e: std::io::Error) -> Self {
+        AtomSnapshotError::Io(e.kind())
+    }
+}
+
+impl std::fmt::Display for AtomSnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AtomSnapshotError::Io(kind) => write!(f, "I/O error: {}", kind),
+            AtomSnapshotError::BadMagic => write!(f, "Not an atom table snapshot"),
+            AtomSnapshotError::UnsupportedVersion(v) => {
+                write!(f, "Unsupported atom table format version: {}", v)
+            }
+            AtomSnapshotError::TooManyAtoms => write!(f, "Atom count exceeds table limit"),
+            AtomSnapshotError::InvalidAtom(i) => write!(f, "Invalid atom at index {}", i),
+            AtomSnapshotError::DuplicateAtom(i) => write!(f, "Duplicate atom at index {}", i),
+        }
+    }
+}
+
+impl std::error::Error for AtomSnapshotError {}
+
+fn read_u64<R: Read>(reader: &mut R) -> Result<u64, AtomSnapshotError> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_be_bytes(buf))
+}
+
 fn latin1_to_utf8(latin1: &[u8]) -> Vec<u8> {
     // Simple Latin1 to UTF-8 conversion
     // Characters 0-127 map directly, 128-255 become 2-byte UTF-8
@@ -642,6 +840,23 @@ mod tests {
         assert_eq!(table.get_name(index), Some(name.to_vec()));
     }
 
+    #[test]
+    fn test_atom_memory() {
+        let table = AtomTable::new(1000);
+        assert_eq!(table.used_bytes(), 0);
+        table.put_index(b"abc", AtomEncoding::SevenBitAscii, false).unwrap();
+        table.put_index(b"abc", AtomEncoding::SevenBitAscii, false).unwrap();
+        let one = table.used_bytes();
+        table.put_index(b"defgh", AtomEncoding::SevenBitAscii, false).unwrap();
+        assert_eq!(table.used_bytes() - one, one + 4);
+        assert!(table.allocated_bytes() >= table.used_bytes());
+
+        let mut buf = Vec::new();
+        table.serialize(&mut buf).unwrap();
+        let restored = AtomTable::deserialize(&mut buf.as_slice()).unwrap();
+        assert_eq!(restored.used_bytes(), table.used_bytes());
+    }
+
     #[test]
     fn test_utf8_validation_valid() {
         let table = AtomTable::new(1000);
@@ -1137,5 +1352,102 @@ mod tests {
             Err(AtomError::InvalidEncoding)
         );
     }
-}
 
+    #[test]
+    fn test_snapshot_roundtrip_preserves_indices() {
+        let table = AtomTable::new(500);
+        let names: [&[u8]; 4] = [b"ok", b"error", "\u{e5}ngstr\u{f6}m".as_bytes(), "\u{4e16}".as_bytes()];
+        let indices: Vec<usize> = names
+            .iter()
+            .map(|n| table.put_index(n, AtomEncoding::Utf8, false).unwrap())
+            .collect();
+
+        let mut buf = Vec::new();
+        table.serialize(&mut buf).unwrap();
+        let restored = AtomTable::deserialize(&mut buf.as_slice()).unwrap();
+
+        assert_eq!(restored.size(), table.size());
+        for (name, index) in names.iter().zip(indices) {
+            assert_eq!(restored.get_name(index), Some(name.to_vec()));
+            assert_eq!(restored.get(name, AtomEncoding::Utf8), Some(index));
+        }
+        // New atoms continue after the restored ones
+        let next = restored.put_index(b"new", AtomEncoding::SevenBitAscii, false).unwrap();
+        assert_eq!(next, names.len());
+    }
+
+    #[test]
+    fn test_snapshot_preserves_limit() {
+        let table = AtomTable::new(1);
+        table.put_index(b"only", AtomEncoding::SevenBitAscii, false).unwrap();
+        let mut buf = Vec::new();
+        table.serialize(&mut buf).unwrap();
+        let restored = AtomTable::deserialize(&mut buf.as_slice()).unwrap();
+        assert_eq!(
+            restored.put_index(b"more", AtomEncoding::SevenBitAscii, false),
+            Err(AtomError::TableFull)
+        );
+    }
+
+    #[test]
+    fn test_snapshot_is_stable() {
+        let table = AtomTable::new(2);
+        table.put_index(b"a", AtomEncoding::SevenBitAscii, false).unwrap();
+        let mut buf = Vec::new();
+        table.serialize(&mut buf).unwrap();
+        let expected: Vec<u8> = [
+            &b"ATBL"[..],
+            &[1],
+            &2u64.to_be_bytes(),
+            &1u64.to_be_bytes(),
+            &[0, 1, b'a'],
+        ]
+        .concat();
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn test_snapshot_rejects_malformed_input() {
+        let table = AtomTable::new(10);
+        table.put_index(b"x", AtomEncoding::SevenBitAscii, false).unwrap();
+        let mut buf = Vec::new();
+        table.serialize(&mut buf).unwrap();
+
+        let mut bad_magic = buf.clone();
+        bad_magic[0] = b'X';
+        assert_eq!(
+            AtomTable::deserialize(&mut bad_magic.as_slice()).err(),
+            Some(AtomSnapshotError::BadMagic)
+        );
+
+        let mut bad_version = buf.clone();
+        bad_version[4] = 99;
+        assert_eq!(
+            AtomTable::deserialize(&mut bad_version.as_slice()).err(),
+            Some(AtomSnapshotError::UnsupportedVersion(99))
+        );
+
+        let truncated = &buf[..buf.len() - 1];
+        assert_eq!(
+            AtomTable::deserialize(&mut &truncated[..]).err(),
+            Some(AtomSnapshotError::Io(std::io::ErrorKind::UnexpectedEof))
+        );
+
+        // Atom count is the last byte of the 8-byte count at offset 13
+        let mut duplicate = buf.clone();
+        duplicate[20] = 2;
+        duplicate.extend_from_slice(&[0, 1, b'x']);
+        assert_eq!(
+            AtomTable::deserialize(&mut duplicate.as_slice()).err(),
+            Some(AtomSnapshotError::DuplicateAtom(1))
+        );
+
+        let mut invalid_utf8 = buf.clone();
+        let last = invalid_utf8.len() - 1;
+        invalid_utf8[last] = 0xFF;
+        assert_eq!(
+            AtomTable::deserialize(&mut invalid_utf8.as_slice()).err(),
+            Some(AtomSnapshotError::InvalidAtom(0))
+        );
+    }
+}
@@ -12,6 +12,11 @@
 //! This module provides a basic binary data structure. For bit-aligned operations
 //! (bitstrings), see the [`bits`](super::bits/index.html) module.
 //!
+//! Binaries larger than [`ERL_ONHEAP_BIN_LIMIT`] bytes are not stored on the
+//! process heap but as reference-counted [`RefcBinary`] values shared by all
+//! terms, processes and sub-binaries referencing them, so passing them around
+//! never copies the data.
+//!
 //! ## Examples
 //!
 //! ```rust
@@ -55,6 +60,12 @@
  * %CopyrightEnd%
  */
 
+use std::sync::Arc;
+
+/// Largest binary, in bytes, stored on the process heap; larger binaries are
+/// reference-counted
+pub const ERL_ONHEAP_BIN_LIMIT: usize = 64;
+
 /// Binary data structure for Erlang binaries
 ///
 /// Represents an immutable sequence of bytes. Binaries are a fundamental data type
@@ -128,6 +139,114 @@ impl Binary {
     }
 }
 
+/// Reference-counted binary
+///
+/// Holds a reference to shared, immutable binary data, the equivalent of a
+/// `ProcBin` (or an `ErlSubBin` pointing into one) referencing a `Binary`
+/// in `erl_binary.h`. Cloning a `RefcBinary` or taking a sub-binary only adds
+/// a reference; the data is freed when the last reference is dropped.
+///
+/// ## Examples
+///
+/// ```rust
+/// use entities_data_handling::binary::RefcBinary;
+///
+/// let binary = RefcBinary::new(b"hello world".to_vec());
+/// let world = binary.sub_binary(6, 5).unwrap();
+/// assert_eq!(world.data(), b"world");
+/// assert!(world.shares_storage(&binary));
+/// assert_eq!(binary.ref_count(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RefcBinary {
+    /// Shared data
+    storage: Arc<Vec<u8>>,
+    /// Offset of this binary in the shared data
+    offset: usize,
+    /// Size of this binary in bytes
+    size: usize,
+}
+
+impl RefcBinary {
+    /// Create a reference-counted binary, taking ownership of the data
+    /// without copying it
+    pub fn new(data: Vec<u8>) -> Self {
+        let size = data.len();
+        Self {
+            storage: Arc::new(data),
+            offset: 0,
+            size,
+        }
+    }
+
+    /// Get the binary data
+    pub fn data(&self) -> &[u8] {
+        &self.storage[self.offset..self.offset + self.size]
+    }
+
+    /// Get the size of the binary in bytes
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Check if the binary is empty
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Get the offset of the binary in its shared data
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Create a sub-binary sharing this binary's data
+    ///
+    /// # Arguments
+    /// * `offset` - Start of the sub-binary, relative to this binary
+    /// * `size` - Size of the sub-binary in bytes
+    ///
+    /// # Returns
+    /// * `Some(RefcBinary)` - The sub-binary
+    /// * `None` - The range is outside this binary
+    pub fn sub_binary(&self, offset: usize, size: usize) -> Option<RefcBinary> {
+        let end = offset.checked_add(size)?;
+        if end > self.size {
+            return None;
+        }
+        Some(Self {
+            storage: Arc::clone(&self.storage),
+            offset: self.offset + offset,
+            size,
+        })
+    }
+
+    /// Get the number of references to the shared data
+    pub fn ref_count(&self) -> usize {
+        Arc::strong_count(&self.storage)
+    }
+
+    /// Check if two binaries reference the same shared data
+    pub fn shares_storage(&self, other: &RefcBinary) -> bool {
+        Arc::ptr_eq(&self.storage, &other.storage)
+    }
+
+    /// Get an identifier of the shared data, the same for all binaries
+    /// sharing it
+    pub fn storage_id(&self) -> usize {
+        Arc::as_ptr(&self.storage) as usize
+    }
+
+    /// Get the size of the shared data in bytes
+    pub fn storage_size(&self) -> usize {
+        self.storage.len()
+    }
+
+    /// Get the address of the binary data, identifying it in heap terms
+    pub fn address(&self) -> usize {
+        self.storage.as_ptr() as usize + self.offset
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -138,5 +257,38 @@ mod tests {
         let binary = Binary::new(data.clone());
         assert_eq!(binary.data(), &data);
     }
+
+    #[test]
+    fn test_refc_binary_shares_data() {
+        let data = vec![7u8; 100];
+        let pointer = data.as_ptr();
+        let binary = RefcBinary::new(data);
+        assert_eq!(binary.data().as_ptr(), pointer);
+        assert_eq!(binary.len(), 100);
+
+        let copy = binary.clone();
+        assert!(copy.shares_storage(&binary));
+        assert_eq!(binary.ref_count(), 2);
+        drop(copy);
+        assert_eq!(binary.ref_count(), 1);
+    }
+
+    #[test]
+    fn test_refc_sub_binary() {
+        let binary = RefcBinary::new(b"0123456789".to_vec());
+        let sub = binary.sub_binary(2, 6).unwrap();
+        assert_eq!(sub.data(), b"234567");
+        assert_eq!(sub.offset(), 2);
+
+        let nested = sub.sub_binary(1, 2).unwrap();
+        assert_eq!(nested.data(), b"34");
+        assert_eq!(nested.offset(), 3);
+        assert_eq!(nested.address(), binary.address() + 3);
+        assert!(nested.shares_storage(&binary));
+
+        assert!(sub.sub_binary(5, 2).is_none());
+        assert!(sub.sub_binary(usize::MAX, 2).is_none());
+        assert!(sub.sub_binary(6, 0).unwrap().is_empty());
+    }
 }
 
@@ -26,7 +26,8 @@
 //!   bit-aligned data in Erlang binaries and bitstrings.
 //!
 //! - **[`binary`](binary/index.html)**: Binary data structure for representing Erlang binaries
-//!   and bitstrings. Provides basic binary data storage and retrieval operations.
+//!   and bitstrings. Provides basic binary data storage and retrieval operations, and
+//!   reference-counted binaries shared between terms and sub-binaries.
 //!
 //! - **[`map`](map/index.html)**: Map data structure for key-value pairs where both keys and
 //!   values are Erlang terms. Provides operations for insertion, lookup, update, removal, and
@@ -102,6 +103,7 @@ pub mod atomics;
 
 // Re-export main types for convenience
 pub use term_hashing::HashValue;
-pub use atom::{AtomTable, AtomEncoding};
+pub use atom::{AtomTable, AtomEncoding, AtomSnapshotError};
 pub use map::{Map, MapError};
+pub use binary::{RefcBinary, ERL_ONHEAP_BIN_LIMIT};
 
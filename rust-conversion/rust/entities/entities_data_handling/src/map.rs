@@ -8,8 +8,10 @@
 //!
 //! This module implements a persistent map data structure for the entities layer.
 //! Maps store key-value pairs where both keys and values are `Term` types, allowing
-//! for flexible data structures. The implementation uses a vector-based storage
-//! with linear search, which is efficient for small maps typical in the entities layer.
+//! for flexible data structures. The implementation follows the BEAM flatmap
+//! layout: keys live in one contiguous key array and values in a parallel value
+//! array, searched linearly, which is efficient for small maps typical in the
+//! entities layer. Benchmarks for `get`, `put` and `merge` are in `benches/map.rs`.
 //!
 //! ## Features
 //!
@@ -74,18 +76,23 @@
  * %CopyrightEnd%
  */
 
+use std::sync::Arc;
+
 use crate::term_hashing::Term;
 
 /// Map data structure
 ///
-/// Internally stores key-value pairs as a vector. For efficient lookup,
-/// we use hash-based indexing. This is a simplified implementation for
-/// the entities layer.
+/// Uses the flatmap layout of the BEAM: all keys are stored contiguously in
+/// a shared key array (the "key tuple") and all values in a parallel value
+/// array, so that `values[i]` belongs to `keys[i]`. Lookups scan the dense
+/// key array only, and maps that differ only in their values (e.g. after
+/// [`update`](Map::update) on a clone) share the same key array.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Map {
-    /// Key-value pairs stored in insertion order
-    /// For efficient lookup, we maintain that keys are unique
-    pairs: Vec<(Term, Term)>,
+    /// Keys in insertion order, shared between maps with the same key set
+    keys: Arc<Vec<Term>>,
+    /// Values, index-aligned with `keys`
+    values: Vec<Term>,
 }
 
 /// Map operation errors
@@ -101,18 +108,27 @@ impl Map {
     /// Create a new empty map
     pub fn new() -> Self {
         Self {
-            pairs: Vec::new(),
+            keys: Arc::new(Vec::new()),
+            values: Vec::new(),
+        }
+    }
+
+    /// Create a new empty map with room for `capacity` entries
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            keys: Arc::new(Vec::with_capacity(capacity)),
+            values: Vec::with_capacity(capacity),
         }
     }
 
     /// Get the size of the map (number of key-value pairs)
     pub fn size(&self) -> usize {
-        self.pairs.len()
+        self.keys.len()
     }
 
     /// Check if the map is empty
     pub fn is_empty(&self) -> bool {
-        self.pairs.is_empty()
+        self.keys.is_empty()
     }
 
     /// Check if a key exists in the map
@@ -122,27 +138,26 @@ impl Map {
 
     /// Get a value by key, returning None if key doesn't exist
     pub fn get(&self, key: &Term) -> Option<&Term> {
-        self.find_index(key).map(|idx| &self.pairs[idx].1)
+        self.find_index(key).map(|idx| &self.values[idx])
     }
 
     /// Find a key-value pair, returning Some((key, value)) if found, None otherwise
     pub fn find(&self, key: &Term) -> Option<(&Term, &Term)> {
-        self.find_index(key).map(|idx| {
-            let (k, v) = &self.pairs[idx];
-            (k, v)
-        })
+        self.find_index(key)
+            .map(|idx| (&self.keys[idx], &self.values[idx]))
     }
 
     /// Put a key-value pair into the map
     ///
-    /// If the key already exists, the value is updated.
-    /// Returns the previous value if the key existed, None otherwise.
+    /// If the key already exists, the value is updated and the key array is
+    /// left untouched. Returns the previous value if the key existed, None
+    /// otherwise.
     pub fn put(&mut self, key: Term, value: Term) -> Option<Term> {
         if let Some(idx) = self.find_index(&key) {
-            let old_value = std::mem::replace(&mut self.pairs[idx].1, value);
-            Some(old_value)
+            Some(std::mem::replace(&mut self.values[idx], value))
         } else {
-            self.pairs.push((key, value));
+            Arc::make_mut(&mut self.keys).push(key);
+            self.values.push(value);
             None
         }
     }
@@ -150,10 +165,10 @@ impl Map {
     /// Update a key-value pair in the map
     ///
     /// Returns Ok(previous_value) if the key exists, Err(MapError::KeyNotFound) otherwise.
+    /// The key array is never copied by an update.
     pub fn update(&mut self, key: &Term, value: Term) -> Result<Term, MapError> {
         if let Some(idx) = self.find_index(key) {
-            let old_value = std::mem::replace(&mut self.pairs[idx].1, value);
-            Ok(old_value)
+            Ok(std::mem::replace(&mut self.values[idx], value))
         } else {
             Err(MapError::KeyNotFound)
         }
@@ -163,44 +178,42 @@ impl Map {
     ///
     /// Returns the value if the key existed, None otherwise.
     pub fn remove(&mut self, key: &Term) -> Option<Term> {
-        if let Some(idx) = self.find_index(key) {
-            Some(self.pairs.remove(idx).1)
-        } else {
-            None
-        }
+        self.take(key).map(|(_, value)| value)
     }
 
     /// Take a key-value pair from the map
     ///
     /// Returns Some((key, value)) if the key existed, None otherwise.
     pub fn take(&mut self, key: &Term) -> Option<(Term, Term)> {
-        if let Some(idx) = self.find_index(key) {
-            Some(self.pairs.remove(idx))
-        } else {
-            None
-        }
+        let idx = self.find_index(key)?;
+        let key = Arc::make_mut(&mut self.keys).remove(idx);
+        Some((key, self.values.remove(idx)))
     }
 
     /// Get all keys in the map
     pub fn keys(&self) -> Vec<&Term> {
-        self.pairs.iter().map(|(k, _)| k).collect()
+        self.keys.iter().collect()
     }
 
     /// Get all values in the map
     pub fn values(&self) -> Vec<&Term> {
-        self.pairs.iter().map(|(_, v)| v).collect()
+        self.values.iter().collect()
     }
 
     /// Convert the map to a list of (key, value) pairs
     pub fn to_list(&self) -> Vec<(Term, Term)> {
-        self.pairs.clone()
+        self.keys
+            .iter()
+            .cloned()
+            .zip(self.values.iter().cloned())
+            .collect()
     }
 
     /// Create a map from a list of (key, value) pairs
     ///
     /// If duplicate keys exist, the last value for each key is kept.
     pub fn from_list(pairs: Vec<(Term, Term)>) -> Self {
-        let mut map = Self::new();
+        let mut map = Self::with_capacity(pairs.len());
         for (key, value) in pairs {
             map.put(key, value);
         }
@@ -211,25 +224,53 @@ impl Map {
     ///
     /// Keys from `other` take precedence over keys in `self`.
     /// Returns a new map containing all key-value pairs.
+    ///
+    /// Keys of `other` are only looked up among the keys of `self`, since
+    /// the keys of a map are unique. If every key of `other` is already in
+    /// `self`, the result shares the key array of `self`.
     pub fn merge(&self, other: &Self) -> Self {
-        let mut result = self.clone();
-        for (key, value) in &other.pairs {
-            result.put(key.clone(), value.clone());
+        let mut values = self.values.clone();
+        let mut new_keys = Vec::new();
+        let mut new_values = Vec::new();
+        for (key, value) in other.keys.iter().zip(&other.values) {
+            match self.find_index(key) {
+                Some(idx) => values[idx] = value.clone(),
+                None => {
+                    new_keys.push(key.clone());
+                    new_values.push(value.clone());
+                }
+            }
+        }
+        if new_keys.is_empty() {
+            return Self {
+                keys: Arc::clone(&self.keys),
+                values,
+            };
+        }
+        let mut keys = Vec::with_capacity(self.keys.len() + new_keys.len());
+        keys.extend(self.keys.iter().cloned());
+        keys.append(&mut new_keys);
+        values.append(&mut new_values);
+        Self {
+            keys: Arc::new(keys),
+            values,
         }
-        result
     }
 
-    /// Find the index of a key in the pairs vector
+    /// Check if two maps share the same key array
     ///
-    /// Uses linear search through the pairs. For small maps (typical in entities layer),
-    /// this is efficient. For larger maps, hash-based optimization could be added.
+    /// Maps derived from one another by updating existing keys only share
+    /// their keys, like flatmaps sharing a key tuple in the BEAM.
+    pub fn shares_keys_with(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.keys, &other.keys)
+    }
+
+    /// Find the index of a key in the key array
+    ///
+    /// Scans the contiguous key array, which is efficient for small maps
+    /// (the BEAM uses flatmaps up to 32 keys).
     fn find_index(&self, key: &Term) -> Option<usize> {
-        for (idx, (k, _)) in self.pairs.iter().enumerate() {
-            if k == key {
-                return Some(idx);
-            }
-        }
-        None
+        self.keys.iter().position(|k| k == key)
     }
 }
 
@@ -498,5 +539,46 @@ mod tests {
         assert!(map.is_empty());
         assert_eq!(map.size(), 0);
     }
-}
 
+    #[test]
+    fn test_map_update_shares_keys() {
+        let map = Map::from_list(vec![
+            (Term::Small(1), Term::Small(10)),
+            (Term::Small(2), Term::Small(20)),
+        ]);
+        let mut updated = map.clone();
+        updated.update(&Term::Small(2), Term::Small(200)).unwrap();
+        updated.put(Term::Small(1), Term::Small(100));
+        assert!(updated.shares_keys_with(&map));
+        assert_eq!(map.get(&Term::Small(2)), Some(&Term::Small(20)));
+
+        updated.put(Term::Small(3), Term::Small(30));
+        assert!(!updated.shares_keys_with(&map));
+        assert_eq!(map.size(), 2);
+    }
+
+    #[test]
+    fn test_map_merge_shares_keys() {
+        let map1 = Map::from_list(vec![
+            (Term::Small(1), Term::Small(10)),
+            (Term::Small(2), Term::Small(20)),
+        ]);
+        let map2 = Map::from_list(vec![(Term::Small(2), Term::Small(200))]);
+        let merged = map1.merge(&map2);
+        assert!(merged.shares_keys_with(&map1));
+        assert_eq!(merged.get(&Term::Small(2)), Some(&Term::Small(200)));
+
+        let merged = map2.merge(&map1);
+        assert_eq!(merged.size(), 2);
+        assert_eq!(merged.get(&Term::Small(2)), Some(&Term::Small(20)));
+    }
+
+    #[test]
+    fn test_map_take_keeps_alignment() {
+        let mut map = Map::from_list((0..5).map(|i| (Term::Small(i), Term::Small(i * 10))).collect());
+        assert_eq!(map.take(&Term::Small(1)), Some((Term::Small(1), Term::Small(10))));
+        for i in [0, 2, 3, 4] {
+            assert_eq!(map.get(&Term::Small(i)), Some(&Term::Small(i * 10)));
+        }
+    }
+}
@@ -521,3 +521,30 @@ fn test_binary_integration() {
     let result = bits::cmp_bits(binary1.data(), 0, binary2.data(), 0, 8);
     assert_ne!(result, 0);
 }
+
+#[test]
+fn test_atom_table_snapshot_keeps_term_atoms_valid() {
+    let table = AtomTable::new(1000);
+    let ok = table.put_index(b"ok", AtomEncoding::SevenBitAscii, false).unwrap();
+    let name = table.put_index(b"worker", AtomEncoding::SevenBitAscii, false).unwrap();
+    let term = Term::Tuple(vec![Term::Atom(ok as u32), Term::Atom(name as u32)]);
+
+    let mut snapshot = Vec::new();
+    table.serialize(&mut snapshot).unwrap();
+    let restored = AtomTable::deserialize(&mut snapshot.as_slice()).unwrap();
+
+    if let Term::Tuple(elements) = term {
+        let names: Vec<Vec<u8>> = elements
+            .iter()
+            .map(|t| match t {
+                Term::Atom(i) => restored.get_name(*i as usize).unwrap(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(names, vec![b"ok".to_vec(), b"worker".to_vec()]);
+    }
+    assert!(matches!(
+        AtomTable::deserialize(&mut &snapshot[..10]),
+        Err(AtomSnapshotError::Io(_))
+    ));
+}
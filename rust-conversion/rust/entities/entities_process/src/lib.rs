@@ -16,6 +16,13 @@
 //! - **Process State**: Enumeration of all possible process states (Active, Running, Suspended, etc.)
 //! - **Safe Heap Management**: Heap implemented using safe Rust `Vec<Eterm>` with index-based access
 //! - **Type Safety**: Process ID and Eterm type aliases for type safety
+//! - **Spawn Metadata**: Parent process, initial call and spawn time recorded at creation
+//! - **Message Queue**: Per-process mailbox with selective `receive ... after 0`
+//! - **Sequential Tracing**: Trace tokens passed on with messages, with per-process serial clocks
+//! - **Process Defaults**: System-wide heap and backtrace defaults, changed by `system_flag/2`
+//! - **Spawn Options**: Priority, message queue placement, heap limits, links and monitors
+//! - **System Tasks**: Work other processes schedule on a process, run at the requester's priority
+//! - **Term Tags**: The erl_term.h tag scheme of `Eterm` words, shared by every crate building terms
 //!
 //! ## Safety
 //!
@@ -47,7 +54,18 @@
 
 pub mod process;
 pub mod process_executor;
+pub mod message_queue;
+pub mod seq_trace;
+pub mod process_defaults;
+pub mod spawn_opts;
+pub mod system_task;
+pub mod term_tags;
 
 // Re-export main types for convenience
-pub use process::{Process, ProcessId, ProcessState, Eterm, ErtsCodePtr};
+pub use process::{Process, ProcessId, ProcessState, Eterm, ErtsCodePtr, InitialCall, SpawnInfo, Monitor, MonitorKind, AliasKey, AliasMode, BusyDestination, BifContinuation};
+pub use message_queue::{Message, MessageQueue, FRAGMENT_ROOT};
+pub use seq_trace::{SeqTraceState, SeqTraceToken};
+pub use process_defaults::{process_defaults, update_process_defaults, ProcessDefaults};
+pub use spawn_opts::{MaxHeapSize, MessageQueueData, ProcessPriority, SpawnOpts};
+pub use system_task::{SystemTask, SystemTaskKind, SystemTaskQueue};
 pub use process_executor::{ProcessExecutor, ProcessExecutionResult, set_process_executor, execute_process};
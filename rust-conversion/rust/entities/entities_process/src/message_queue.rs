@@ -0,0 +1,174 @@
+//! Message Queue Entity
+//!
+//! Provides the process message queue (mailbox) used by `!` and `receive`.
+//! Based on the signal/message queue in erts/emulator/beam/erl_proc_sig_queue.h
+//!
+//! Messages are kept in arrival order. `receive` scans from the oldest
+//! message and removes the first one that matches; if none matches and the
+//! `after` timeout is 0, it returns immediately without blocking.
+//!
+//! Messages produced by BIF timers (`erlang:send_after/3`,
+//! `erlang:start_timer/3`) remember the timer they came from, so that a
+//! timer message that raced with `erlang:cancel_timer/1` can be flushed.
+//!
+//! Messages built outside the receiver's heap, such as those sent by native
+//! threads with `enif_send`, carry their terms in a heap fragment; pointers in
+//! the payload address words of the fragment.
+//!
+//! Messages sent by a sequentially traced process carry the sender's
+//! [`SeqTraceToken`].
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use crate::process::Eterm;
+use crate::seq_trace::SeqTraceToken;
+use crate::term_tags::make_boxed;
+
+/// Payload of a message whose term is the boxed object at word 0 of its
+/// heap fragment
+pub const FRAGMENT_ROOT: Eterm = make_boxed(0);
+
+/// A message in a process message queue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    /// Message term
+    pub payload: Eterm,
+    /// Reference number of the timer that sent this message, if any
+    pub timer_ref: Option<u64>,
+    /// Heap fragment holding the payload, if it was built off the receiver's heap
+    pub heap_fragment: Option<Arc<[Eterm]>>,
+    /// Sequential trace token of the sender, if it was traced
+    pub seq_trace_token: Option<SeqTraceToken>,
+}
+
+impl Message {
+    /// Create an ordinary message
+    pub fn new(payload: Eterm) -> Self {
+        Self {
+            payload,
+            timer_ref: None,
+            heap_fragment: None,
+            seq_trace_token: None,
+        }
+    }
+
+    /// Create a message whose payload lives in a heap fragment
+    pub fn with_heap_fragment(payload: Eterm, heap_fragment: Vec<Eterm>) -> Self {
+        Self {
+            payload,
+            timer_ref: None,
+            heap_fragment: Some(heap_fragment.into()),
+            seq_trace_token: None,
+        }
+    }
+
+    /// Create a message sent by the timer with reference number `timer_ref`
+    pub fn from_timer(payload: Eterm, timer_ref: u64) -> Self {
+        Self {
+            payload,
+            timer_ref: Some(timer_ref),
+            heap_fragment: None,
+            seq_trace_token: None,
+        }
+    }
+
+    /// Attach the sender's sequential trace token
+    pub fn with_seq_trace_token(mut self, token: Option<SeqTraceToken>) -> Self {
+        self.seq_trace_token = token;
+        self
+    }
+}
+
+/// Process message queue
+#[derive(Debug, Default)]
+pub struct MessageQueue {
+    messages: VecDeque<Message>,
+}
+
+impl MessageQueue {
+    /// Create an empty message queue
+    pub fn new() -> Self {
+        Self {
+            messages: VecDeque::new(),
+        }
+    }
+
+    /// Append a message to the end of the queue
+    pub fn push(&mut self, message: Message) {
+        self.messages.push_back(message);
+    }
+
+    /// Number of messages in the queue
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Check if the queue is empty
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Selective receive with `after 0`
+    ///
+    /// Removes and returns the oldest message accepted by `matches`, or
+    /// returns `None` at once if there is none.
+    pub fn receive_after_0<F>(&mut self, matches: F) -> Option<Message>
+    where
+        F: FnMut(&Message) -> bool,
+    {
+        let position = self.messages.iter().position(matches)?;
+        self.messages.remove(position)
+    }
+
+    /// Remove the message sent by the timer with reference number `timer_ref`
+    ///
+    /// Returns `true` if such a message was in the queue.
+    pub fn flush_timer_message(&mut self, timer_ref: u64) -> bool {
+        self.receive_after_0(|m| m.timer_ref == Some(timer_ref)).is_some()
+    }
+
+    /// Iterate over the queued messages, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &Message> {
+        self.messages.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fifo_order() {
+        let mut queue = MessageQueue::new();
+        queue.push(Message::new(1));
+        queue.push(Message::new(2));
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.receive_after_0(|_| true), Some(Message::new(1)));
+        assert_eq!(queue.receive_after_0(|_| true), Some(Message::new(2)));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_selective_receive_after_0() {
+        let mut queue = MessageQueue::new();
+        queue.push(Message::new(1));
+        queue.push(Message::new(2));
+        queue.push(Message::new(3));
+        assert_eq!(queue.receive_after_0(|m| m.payload == 2), Some(Message::new(2)));
+        assert_eq!(queue.receive_after_0(|m| m.payload == 9), None);
+        let left: Vec<Eterm> = queue.iter().map(|m| m.payload).collect();
+        assert_eq!(left, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_flush_timer_message() {
+        let mut queue = MessageQueue::new();
+        queue.push(Message::new(1));
+        queue.push(Message::from_timer(2, 77));
+        assert!(!queue.flush_timer_message(78));
+        assert!(queue.flush_timer_message(77));
+        assert!(!queue.flush_timer_message(77));
+        assert_eq!(queue.len(), 1);
+    }
+}
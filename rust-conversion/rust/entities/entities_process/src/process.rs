@@ -6,8 +6,18 @@
 //! The heap is implemented using safe Rust (`Vec<Eterm>`) with index-based
 //! access instead of raw pointers for maximum safety.
 
+use std::any::Any;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::message_queue::{Message, MessageQueue};
+use crate::process_defaults::{process_defaults, ProcessDefaults};
+use crate::seq_trace::{SeqTraceState, SeqTraceToken};
+use crate::spawn_opts::{MaxHeapSize, MessageQueueData, ProcessPriority, SpawnOpts};
+use crate::system_task::{SystemTask, SystemTaskQueue};
 
 /// Process ID type
 pub type ProcessId = u64;
@@ -22,6 +32,127 @@ pub type Eterm = u64;
 /// but heap/stack use safe Vec-based storage
 pub type ErtsCodePtr = *const u8;
 
+/// Initial call of a process (`{Module, Function, Arity}`)
+///
+/// The function the process was spawned to run, as reported by
+/// `process_info(Pid, initial_call)`. Based on `u.initial` in erl_process.h
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InitialCall {
+    /// Module name
+    pub module: String,
+    /// Function name
+    pub function: String,
+    /// Number of arguments
+    pub arity: u32,
+}
+
+impl InitialCall {
+    /// Create an initial call from module, function and arity
+    pub fn new(module: &str, function: &str, arity: u32) -> Self {
+        Self {
+            module: module.to_string(),
+            function: function.to_string(),
+            arity,
+        }
+    }
+}
+
+/// Metadata recorded when a process is spawned
+///
+/// Based on the `parent` and `u.initial` fields of `struct process` and the
+/// spawn handling in erl_create_process() (erl_process.c)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpawnInfo {
+    /// Process that spawned this process (`None` for processes created by
+    /// the runtime itself, such as `init`)
+    pub parent: Option<ProcessId>,
+    /// Function the process was spawned to run
+    pub initial_call: InitialCall,
+    /// Time of spawn
+    pub spawn_time: SystemTime,
+}
+
+/// Kind of a monitor (based on ERTS_MON_TYPE_* in erl_monitor_link.h)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MonitorKind {
+    /// `erlang:monitor(process, Pid)` or `spawn_opt` with `monitor`
+    Process,
+    /// Held by a process that has suspended the target with
+    /// `erlang:suspend_process/2`, so the target is resumed if the
+    /// suspender exits
+    Suspend,
+}
+
+/// A monitor between two processes
+///
+/// Both the watching process and the monitored process keep a copy, so the
+/// monitor can be found from either side. Based on `ErtsMonitor` in
+/// erl_monitor_link.h
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Monitor {
+    /// Kind of monitor
+    pub kind: MonitorKind,
+    /// Reference number returned to the watcher
+    pub reference: u64,
+    /// Process that receives the `'DOWN'` message
+    pub watcher: ProcessId,
+    /// Process being monitored
+    pub target: ProcessId,
+}
+
+/// How a process alias is deactivated (based on `alias/1` options)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AliasMode {
+    /// Active until `unalias/1` is called (`explicit_unalias`)
+    ExplicitUnalias,
+    /// Deactivated by the first message sent through it (`reply`)
+    Reply,
+}
+
+/// Key of a process alias: the first three numbers of the alias reference
+pub type AliasKey = [u32; 3];
+
+/// Busy destination a sending process is suspended on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BusyDestination {
+    /// Busy port (port number)
+    Port(u64),
+    /// Busy distribution entry (connection identifier)
+    Dist(u64),
+}
+
+/// Work a yielding BIF saved on the process to continue when next scheduled
+///
+/// The state is owned by the BIF that yielded it and is only handed back to
+/// the same `module:function/arity`.
+pub struct BifContinuation {
+    /// Module name (atom)
+    pub module: Eterm,
+    /// Function name (atom)
+    pub function: Eterm,
+    /// Arity
+    pub arity: u32,
+    /// State of the remaining work
+    pub state: Box<dyn Any + Send>,
+}
+
+impl fmt::Debug for BifContinuation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BifContinuation")
+            .field("module", &self.module)
+            .field("function", &self.function)
+            .field("arity", &self.arity)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Process state flag set while a process is exiting (ERTS_PSFLG_EXITING)
+const PSFLG_EXITING: u32 = 0x20;
+/// Process state flag set while a process is suspended (ERTS_PSFLG_SUSPENDED)
+const PSFLG_SUSPENDED: u32 = 0x400;
+/// Process state flag set while system tasks are queued (ERTS_PSFLG_SYS_TASKS)
+const PSFLG_SYS_TASKS: u32 = 0x1000;
+
 /// Process state flags (based on ERTS_PSFLG_* from erl_process.h)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProcessState {
@@ -142,6 +273,22 @@ pub struct Process {
     min_heap_size: usize,
     /// Maximum heap size in words (0 = unlimited)
     max_heap_size: usize,
+    /// Kill the process when it exceeds `max_heap_size`
+    max_heap_kill: bool,
+    /// Log an error report when the process exceeds `max_heap_size`
+    max_heap_error_logger: bool,
+    /// Generational collections before a fullsweep
+    fullsweep_after: usize,
+    /// Generational collections since the last fullsweep
+    gen_gcs: usize,
+    /// Grow the heap at the next collection (F_HEAP_GROW)
+    heap_grow: bool,
+    /// Scheduling priority
+    priority: ProcessPriority,
+    /// Message queue placement
+    message_queue_data: MessageQueueData,
+    /// Preserve sharing within the messages the process sends
+    copy_shared: bool,
     /// Heap data storage (safe Rust Vec, protected by Mutex for concurrent access)
     heap_data: Mutex<Vec<Eterm>>,
     /// Heap start index (usually 0, but can be offset if needed)
@@ -152,7 +299,7 @@ pub struct Process {
     /// In Erlang, stack and heap share the same memory block
     stack_top_index: Option<usize>,
     /// Process flags (ERTS_PSFLG_*)
-    flags: u32,
+    flags: AtomicU32,
     /// Number of reductions for this process
     reds: usize,
     /// Number of reductions left to execute (function calls)
@@ -170,8 +317,12 @@ pub struct Process {
     uniq: i64,
     /// Times left to reschedule a low priority process
     schedule_count: u8,
-    /// Suspend count
-    rcount: u32,
+    /// Suspend count per suspending process
+    suspenders: Mutex<BTreeMap<ProcessId, u32>>,
+    /// Busy destination the process is suspended on while sending
+    busy_wait: Mutex<Option<BusyDestination>>,
+    /// Continuation of a BIF that yielded
+    bif_continuation: Mutex<Option<BifContinuation>>,
     /// NIF function pointers currently used by this process
     /// These pointers are tracked for code purging safety checks
     nif_pointers: Vec<*const u8>,
@@ -179,18 +330,48 @@ pub struct Process {
     /// These are reference counted to prevent libraries from being unloaded
     /// while processes are using them
     nif_libraries: Vec<std::sync::Arc<dyn std::any::Any + Send + Sync>>,
+    /// Message queue (mailbox)
+    msg_queue: Mutex<MessageQueue>,
+    /// Sequential trace token and clock
+    seq_trace: Mutex<SeqTraceState>,
+    /// Parent, initial call and spawn time (None if not created by a spawn)
+    spawn_info: Option<SpawnInfo>,
+    /// Group leader, the process I/O requests are sent to
+    group_leader: AtomicU64,
+    /// Module handling calls to undefined functions (None: `error_handler`)
+    error_handler: Mutex<Option<Eterm>>,
+    /// Active aliases of the process
+    aliases: Mutex<HashMap<AliasKey, AliasMode>>,
+    /// Linked processes
+    links: Mutex<BTreeSet<ProcessId>>,
+    /// Monitors this process is watching or is the target of
+    monitors: Mutex<Vec<Monitor>>,
+    /// System tasks other processes scheduled on this process
+    sys_tasks: Mutex<SystemTaskQueue>,
 }
 
 impl Process {
     /// Create a new process with default values
     ///
+    /// Heap sizes and `fullsweep_after` come from the current
+    /// [`process_defaults`](crate::process_defaults::process_defaults).
+    ///
     /// # Arguments
     /// * `id` - Process identifier
     ///
     /// # Returns
     /// A new Process instance with default values
     pub fn new(id: ProcessId) -> Self {
-        let initial_heap_size = 233; // Default minimum heap size (words)
+        Self::with_defaults(id, &process_defaults())
+    }
+
+    /// Create a new process with the given defaults
+    ///
+    /// # Arguments
+    /// * `id` - Process identifier
+    /// * `defaults` - Heap sizes and `fullsweep_after` for the process
+    pub fn with_defaults(id: ProcessId, defaults: &ProcessDefaults) -> Self {
+        let initial_heap_size = defaults.min_heap_size;
         let mut heap_data = Vec::with_capacity(initial_heap_size);
         heap_data.resize(initial_heap_size, 0);
         
@@ -198,12 +379,20 @@ impl Process {
             id,
             heap_sz: initial_heap_size,
             min_heap_size: initial_heap_size,
-            max_heap_size: 0,    // 0 = unlimited
+            max_heap_size: defaults.max_heap_size,    // 0 = unlimited
+            max_heap_kill: true,
+            max_heap_error_logger: true,
+            fullsweep_after: defaults.fullsweep_after,
+            gen_gcs: 0,
+            heap_grow: false,
+            priority: ProcessPriority::Normal,
+            message_queue_data: MessageQueueData::OnHeap,
+            copy_shared: false,
             heap_data: Mutex::new(heap_data),
             heap_start_index: 0,
             heap_top_index: Mutex::new(0),
             stack_top_index: None,
-            flags: 0,
+            flags: AtomicU32::new(0),
             reds: 0,
             fcalls: 0,
             arity: 0,
@@ -212,10 +401,82 @@ impl Process {
             i: std::ptr::null(),
             uniq: 0,
             schedule_count: 0,
-            rcount: 0,
+            suspenders: Mutex::new(BTreeMap::new()),
+            busy_wait: Mutex::new(None),
+            bif_continuation: Mutex::new(None),
             nif_pointers: Vec::new(),
             nif_libraries: Vec::new(),
+            msg_queue: Mutex::new(MessageQueue::new()),
+            seq_trace: Mutex::new(SeqTraceState::default()),
+            spawn_info: None,
+            group_leader: AtomicU64::new(id),
+            error_handler: Mutex::new(None),
+            aliases: Mutex::new(HashMap::new()),
+            links: Mutex::new(BTreeSet::new()),
+            monitors: Mutex::new(Vec::new()),
+            sys_tasks: Mutex::new(SystemTaskQueue::new()),
+        }
+    }
+
+    /// Create a process for a spawn, recording its spawn metadata
+    ///
+    /// The spawn time is taken when the process is created.
+    ///
+    /// # Arguments
+    /// * `id` - Process identifier
+    /// * `parent` - Spawning process, or `None` if spawned by the runtime
+    /// * `initial_call` - Function the process is spawned to run
+    pub fn spawned(id: ProcessId, parent: Option<ProcessId>, initial_call: InitialCall) -> Self {
+        let mut process = Self::new(id);
+        process.spawn_info = Some(SpawnInfo {
+            parent,
+            initial_call,
+            spawn_time: SystemTime::now(),
+        });
+        process
+    }
+
+    /// Create a process for a `spawn_opt`, applying the spawn options
+    ///
+    /// Heap settings not given in `opts` come from the current process
+    /// defaults. Links and monitors are not set up here; the spawn use case
+    /// adds them on both sides before the process becomes visible.
+    ///
+    /// # Arguments
+    /// * `id` - Process identifier
+    /// * `parent` - Spawning process, or `None` if spawned by the runtime
+    /// * `initial_call` - Function the process is spawned to run
+    /// * `opts` - Spawn options
+    pub fn spawned_with_opts(
+        id: ProcessId,
+        parent: Option<ProcessId>,
+        initial_call: InitialCall,
+        opts: &SpawnOpts,
+    ) -> Self {
+        let mut defaults = process_defaults();
+        if let Some(min_heap_size) = opts.min_heap_size {
+            defaults.min_heap_size = min_heap_size;
+        }
+        if let Some(max_heap_size) = opts.max_heap_size {
+            defaults.max_heap_size = max_heap_size.size;
         }
+        if let Some(fullsweep_after) = opts.fullsweep_after {
+            defaults.fullsweep_after = fullsweep_after;
+        }
+        let mut process = Self::with_defaults(id, &defaults);
+        if let Some(max_heap_size) = opts.max_heap_size {
+            process.max_heap_kill = max_heap_size.kill;
+            process.max_heap_error_logger = max_heap_size.error_logger;
+        }
+        process.priority = opts.priority;
+        process.message_queue_data = opts.message_queue_data;
+        process.copy_shared = opts.copy_shared;
+        process.spawn_info = Some(SpawnInfo {
+            parent,
+            initial_call,
+            spawn_time: SystemTime::now(),
+        });
+        process
     }
 
     /// Get process ID
@@ -230,7 +491,7 @@ impl Process {
 
     /// Get process state from flags
     pub fn get_state(&self) -> ProcessState {
-        ProcessState::from_flags(self.flags)
+        ProcessState::from_flags(self.flags())
     }
 
     /// Get heap size in words
@@ -248,6 +509,235 @@ impl Process {
         self.max_heap_size
     }
 
+    /// Get the maximum heap size together with its kill and log settings
+    pub fn max_heap_size_limit(&self) -> MaxHeapSize {
+        MaxHeapSize {
+            size: self.max_heap_size,
+            kill: self.max_heap_kill,
+            error_logger: self.max_heap_error_logger,
+        }
+    }
+
+    /// Get the number of generational collections before a fullsweep
+    pub fn fullsweep_after(&self) -> usize {
+        self.fullsweep_after
+    }
+
+    /// Get the number of generational collections since the last fullsweep
+    pub fn gen_gcs(&self) -> usize {
+        self.gen_gcs
+    }
+
+    /// Record a garbage collection
+    ///
+    /// A fullsweep resets the generational collection count.
+    ///
+    /// # Arguments
+    /// * `major` - Whether the collection was a fullsweep
+    pub fn record_gc(&mut self, major: bool) {
+        if major {
+            self.gen_gcs = 0;
+        } else {
+            self.gen_gcs += 1;
+        }
+    }
+
+    /// Check if the heap is to grow at the next collection
+    pub fn heap_grow_pending(&self) -> bool {
+        self.heap_grow
+    }
+
+    /// Set or clear growing the heap at the next collection
+    ///
+    /// A fullsweep that leaves the heap over 75% full postpones growing it
+    /// to the next collection.
+    pub fn set_heap_grow(&mut self, grow: bool) {
+        self.heap_grow = grow;
+    }
+
+    /// Resize the heap to `words` words
+    ///
+    /// The heap never shrinks below the words in use or the minimum heap size.
+    ///
+    /// # Returns
+    /// The new heap size in words
+    pub fn resize_heap(&mut self, words: usize) -> usize {
+        let heap_top = *self.heap_top_index.lock().unwrap();
+        let new_size = words.max(heap_top).max(self.min_heap_size);
+        self.heap_data.lock().unwrap().resize(new_size, 0);
+        self.heap_sz = new_size;
+        new_size
+    }
+
+    /// Get the scheduling priority
+    pub fn priority(&self) -> ProcessPriority {
+        self.priority
+    }
+
+    /// Change the scheduling priority, returning the previous one
+    pub fn set_priority(&mut self, priority: ProcessPriority) -> ProcessPriority {
+        std::mem::replace(&mut self.priority, priority)
+    }
+
+    /// Get the message queue placement
+    pub fn message_queue_data(&self) -> MessageQueueData {
+        self.message_queue_data
+    }
+
+    /// Check if the messages the process sends are copied preserving sharing
+    pub fn copy_shared(&self) -> bool {
+        self.copy_shared
+    }
+
+    /// Get the priority the process is scheduled at
+    ///
+    /// The higher of its own priority and the priority of its queued
+    /// system tasks.
+    pub fn effective_priority(&self) -> ProcessPriority {
+        match self.sys_tasks.lock().unwrap().priority() {
+            Some(priority) => priority.min(self.priority),
+            None => self.priority,
+        }
+    }
+
+    /// Queue a system task on the process
+    pub fn schedule_system_task(&self, task: SystemTask) {
+        let mut sys_tasks = self.sys_tasks.lock().unwrap();
+        sys_tasks.push(task);
+        self.flags.fetch_or(PSFLG_SYS_TASKS, Ordering::AcqRel);
+    }
+
+    /// Take the next system task to execute
+    pub fn take_system_task(&self) -> Option<SystemTask> {
+        let mut sys_tasks = self.sys_tasks.lock().unwrap();
+        let task = sys_tasks.pop();
+        if sys_tasks.is_empty() {
+            self.flags.fetch_and(!PSFLG_SYS_TASKS, Ordering::AcqRel);
+        }
+        task
+    }
+
+    /// Check if system tasks are queued on the process
+    pub fn has_system_tasks(&self) -> bool {
+        self.flags() & PSFLG_SYS_TASKS != 0
+    }
+
+    /// Change the message queue placement, returning the previous one
+    pub fn set_message_queue_data(&mut self, mqd: MessageQueueData) -> MessageQueueData {
+        std::mem::replace(&mut self.message_queue_data, mqd)
+    }
+
+    /// Mark the process as exiting
+    pub fn set_exiting(&self) {
+        self.flags.fetch_or(PSFLG_EXITING, Ordering::AcqRel);
+    }
+
+    /// Suspend the process on behalf of `suspender`
+    ///
+    /// Suspensions nest: the process stays suspended until every suspend
+    /// has been matched by a resume.
+    ///
+    /// # Returns
+    /// The number of times `suspender` now has the process suspended
+    pub fn suspend(&self, suspender: ProcessId) -> u32 {
+        let mut suspenders = self.suspenders.lock().unwrap();
+        let count = suspenders.entry(suspender).or_insert(0);
+        *count += 1;
+        self.flags.fetch_or(PSFLG_SUSPENDED, Ordering::AcqRel);
+        *count
+    }
+
+    /// Undo one suspend done by `suspender`
+    ///
+    /// # Returns
+    /// * `Some(true)` - The process is still suspended
+    /// * `Some(false)` - The process is no longer suspended
+    /// * `None` - `suspender` had not suspended the process
+    pub fn resume(&self, suspender: ProcessId) -> Option<bool> {
+        let mut suspenders = self.suspenders.lock().unwrap();
+        let count = suspenders.get_mut(&suspender)?;
+        *count -= 1;
+        if *count == 0 {
+            suspenders.remove(&suspender);
+        }
+        Some(self.update_suspended(&suspenders))
+    }
+
+    /// Undo every suspend done by `suspender`, as when it exits
+    ///
+    /// # Returns
+    /// `true` if the process is still suspended by other processes
+    pub fn resume_all(&self, suspender: ProcessId) -> bool {
+        let mut suspenders = self.suspenders.lock().unwrap();
+        suspenders.remove(&suspender);
+        self.update_suspended(&suspenders)
+    }
+
+    /// Number of times `suspender` has the process suspended
+    pub fn suspend_count(&self, suspender: ProcessId) -> u32 {
+        self.suspenders.lock().unwrap().get(&suspender).copied().unwrap_or(0)
+    }
+
+    /// Check if the process is suspended
+    pub fn is_suspended(&self) -> bool {
+        self.flags() & PSFLG_SUSPENDED != 0
+    }
+
+    /// Suspend the process on a busy port or distribution entry it sends to
+    ///
+    /// The suspend is undone by [`resume_from_busy`](Self::resume_from_busy)
+    /// once the destination is no longer busy.
+    pub fn suspend_on_busy(&self, destination: BusyDestination) {
+        let _suspenders = self.suspenders.lock().unwrap();
+        *self.busy_wait.lock().unwrap() = Some(destination);
+        self.flags.fetch_or(PSFLG_SUSPENDED, Ordering::AcqRel);
+    }
+
+    /// Undo the suspend on a busy destination
+    ///
+    /// # Returns
+    /// * `Some(true)` - The process is still suspended by other processes
+    /// * `Some(false)` - The process is no longer suspended
+    /// * `None` - The process was not suspended on a busy destination
+    pub fn resume_from_busy(&self) -> Option<bool> {
+        let suspenders = self.suspenders.lock().unwrap();
+        self.busy_wait.lock().unwrap().take()?;
+        Some(self.update_suspended(&suspenders))
+    }
+
+    /// Get the busy destination the process is suspended on, if any
+    pub fn busy_destination(&self) -> Option<BusyDestination> {
+        *self.busy_wait.lock().unwrap()
+    }
+
+    /// Save the continuation of a BIF that yielded
+    ///
+    /// # Returns
+    /// The continuation it replaces, if any
+    pub fn save_bif_continuation(&self, continuation: BifContinuation) -> Option<BifContinuation> {
+        self.bif_continuation.lock().unwrap().replace(continuation)
+    }
+
+    /// Take the continuation of a BIF that yielded, if any
+    pub fn take_bif_continuation(&self) -> Option<BifContinuation> {
+        self.bif_continuation.lock().unwrap().take()
+    }
+
+    /// Check if a BIF that yielded has work left on the process
+    pub fn has_bif_continuation(&self) -> bool {
+        self.bif_continuation.lock().unwrap().is_some()
+    }
+
+    /// Clear the suspended flag once no suspends remain
+    fn update_suspended(&self, suspenders: &BTreeMap<ProcessId, u32>) -> bool {
+        if suspenders.is_empty() && self.busy_wait.lock().unwrap().is_none() {
+            self.flags.fetch_and(!PSFLG_SUSPENDED, Ordering::AcqRel);
+            false
+        } else {
+            true
+        }
+    }
+
     /// Get stack top index
     pub fn stack_top_index(&self) -> Option<usize> {
         self.stack_top_index
@@ -319,7 +809,7 @@ impl Process {
 
     /// Get process flags
     pub fn flags(&self) -> u32 {
-        self.flags
+        self.flags.load(Ordering::Acquire)
     }
 
     /// Get reductions
@@ -337,6 +827,17 @@ impl Process {
         self.arity
     }
 
+    /// Set the number of live argument registers
+    ///
+    /// The argument registers are the first words of the heap until the
+    /// process is first scheduled.
+    ///
+    /// # Arguments
+    /// * `arity` - Number of live argument registers
+    pub fn set_arity(&mut self, arity: u8) {
+        self.arity = arity;
+    }
+
     /// Get catches
     pub fn catches(&self) -> i32 {
         self.catches
@@ -370,9 +871,11 @@ impl Process {
         self.schedule_count
     }
 
-    /// Get suspend count
+    /// Get suspend count (suspends by all processes together, plus the
+    /// suspend on a busy destination)
     pub fn rcount(&self) -> u32 {
-        self.rcount
+        let suspends: u32 = self.suspenders.lock().unwrap().values().sum();
+        suspends + u32::from(self.busy_wait.lock().unwrap().is_some())
     }
 
     // Legacy compatibility methods (for backward compatibility during migration)
@@ -493,6 +996,205 @@ impl Process {
     pub fn get_nif_libraries(&self) -> &[std::sync::Arc<dyn std::any::Any + Send + Sync>] {
         &self.nif_libraries
     }
+
+    /// Get the spawn metadata, if the process was created by a spawn
+    pub fn spawn_info(&self) -> Option<&SpawnInfo> {
+        self.spawn_info.as_ref()
+    }
+
+    /// Get the parent process (the process that spawned this one)
+    pub fn parent(&self) -> Option<ProcessId> {
+        self.spawn_info.as_ref().and_then(|info| info.parent)
+    }
+
+    /// Get the initial call of the process
+    pub fn initial_call(&self) -> Option<&InitialCall> {
+        self.spawn_info.as_ref().map(|info| &info.initial_call)
+    }
+
+    /// Get the time the process was spawned
+    pub fn spawn_time(&self) -> Option<SystemTime> {
+        self.spawn_info.as_ref().map(|info| info.spawn_time)
+    }
+
+    /// Deliver a message to the end of the message queue
+    pub fn send_message(&self, message: Message) {
+        self.msg_queue.lock().unwrap().push(message);
+    }
+
+    /// Number of messages in the message queue
+    pub fn message_queue_len(&self) -> usize {
+        self.msg_queue.lock().unwrap().len()
+    }
+
+    /// Selective receive with `after 0`
+    ///
+    /// Removes and returns the oldest message accepted by `matches`, or
+    /// returns `None` immediately if no message matches. The process takes
+    /// over the sequential trace token of the received message.
+    ///
+    /// # Arguments
+    /// * `matches` - Receive pattern as a predicate
+    pub fn receive_after_0<F>(&self, matches: F) -> Option<Message>
+    where
+        F: FnMut(&Message) -> bool,
+    {
+        let message = self.msg_queue.lock().unwrap().receive_after_0(matches)?;
+        self.seq_trace.lock().unwrap().on_receive(message.seq_trace_token);
+        Some(message)
+    }
+
+    /// Remove the message sent by a timer from the message queue
+    ///
+    /// The equivalent of `receive {timeout, TRef, _} -> true after 0 -> false end`
+    /// after a `cancel_timer` that returned `false`.
+    ///
+    /// # Arguments
+    /// * `timer_ref` - Reference number of the timer
+    ///
+    /// # Returns
+    /// `true` if a message from the timer was removed
+    pub fn flush_timer_message(&self, timer_ref: u64) -> bool {
+        self.msg_queue.lock().unwrap().flush_timer_message(timer_ref)
+    }
+
+    /// Number of words held in heap fragments by queued messages
+    pub fn message_fragment_words(&self) -> usize {
+        self.msg_queue
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|m| m.heap_fragment.as_ref())
+            .map(|fragment| fragment.len())
+            .sum()
+    }
+
+    /// Get the group leader
+    ///
+    /// A process is its own group leader until one is set, as for `init`;
+    /// spawned processes inherit the group leader of their parent.
+    pub fn group_leader(&self) -> ProcessId {
+        self.group_leader.load(Ordering::Acquire)
+    }
+
+    /// Set the group leader (`group_leader/2`)
+    pub fn set_group_leader(&self, leader: ProcessId) {
+        self.group_leader.store(leader, Ordering::Release);
+    }
+
+    /// Get the error handler module set with `process_flag(error_handler, Module)`
+    ///
+    /// # Returns
+    /// The module atom, or `None` if the process uses the module `error_handler`
+    pub fn error_handler(&self) -> Option<Eterm> {
+        *self.error_handler.lock().unwrap()
+    }
+
+    /// Set the error handler module (`process_flag(error_handler, Module)`)
+    ///
+    /// # Returns
+    /// The previous error handler module, `None` if it was `error_handler`
+    pub fn set_error_handler(&self, module: Eterm) -> Option<Eterm> {
+        self.error_handler.lock().unwrap().replace(module)
+    }
+
+    /// Activate an alias (`alias/0,1`)
+    pub fn add_alias(&self, key: AliasKey, mode: AliasMode) {
+        self.aliases.lock().unwrap().insert(key, mode);
+    }
+
+    /// Deactivate an alias (`unalias/1`)
+    ///
+    /// # Returns
+    /// `true` if the alias was active
+    pub fn remove_alias(&self, key: AliasKey) -> bool {
+        self.aliases.lock().unwrap().remove(&key).is_some()
+    }
+
+    /// Check whether a message sent through an alias may be delivered
+    ///
+    /// A `reply` alias is deactivated by the message that uses it.
+    ///
+    /// # Returns
+    /// `true` if the alias was active
+    pub fn use_alias(&self, key: AliasKey) -> bool {
+        let mut aliases = self.aliases.lock().unwrap();
+        match aliases.get(&key) {
+            Some(AliasMode::Reply) => {
+                aliases.remove(&key);
+                true
+            }
+            Some(AliasMode::ExplicitUnalias) => true,
+            None => false,
+        }
+    }
+
+    /// Link this process to `other`
+    ///
+    /// # Returns
+    /// `false` if the processes were already linked
+    pub fn add_link(&self, other: ProcessId) -> bool {
+        self.links.lock().unwrap().insert(other)
+    }
+
+    /// Remove the link to `other`
+    ///
+    /// # Returns
+    /// `false` if the processes were not linked
+    pub fn remove_link(&self, other: ProcessId) -> bool {
+        self.links.lock().unwrap().remove(&other)
+    }
+
+    /// Get the linked processes in ascending order
+    pub fn links(&self) -> Vec<ProcessId> {
+        self.links.lock().unwrap().iter().copied().collect()
+    }
+
+    /// Add a monitor this process watches or is the target of
+    pub fn add_monitor(&self, monitor: Monitor) {
+        self.monitors.lock().unwrap().push(monitor);
+    }
+
+    /// Remove the monitor with reference number `reference`
+    pub fn remove_monitor(&self, reference: u64) -> Option<Monitor> {
+        let mut monitors = self.monitors.lock().unwrap();
+        let position = monitors.iter().position(|m| m.reference == reference)?;
+        Some(monitors.remove(position))
+    }
+
+    /// Get the monitors this process watches or is the target of
+    pub fn monitors(&self) -> Vec<Monitor> {
+        self.monitors.lock().unwrap().clone()
+    }
+
+    /// Get the sequential trace token (`seq_trace:get_token/0`)
+    pub fn seq_trace_token(&self) -> Option<SeqTraceToken> {
+        self.seq_trace.lock().unwrap().token
+    }
+
+    /// Replace the sequential trace token, returning the previous one
+    pub fn set_seq_trace_token(&self, token: Option<SeqTraceToken>) -> Option<SeqTraceToken> {
+        std::mem::replace(&mut self.seq_trace.lock().unwrap().token, token)
+    }
+
+    /// Get the sequential trace token together with the clock and last count
+    pub fn seq_trace_state(&self) -> SeqTraceState {
+        *self.seq_trace.lock().unwrap()
+    }
+
+    /// Clear the sequential trace token and clock (`seq_trace:reset_trace/0`)
+    pub fn reset_seq_trace(&self) {
+        *self.seq_trace.lock().unwrap() = SeqTraceState::default();
+    }
+
+    /// Advance the sequential trace serial for a message this process sends
+    ///
+    /// Returns the token to attach to the message with
+    /// [`Message::with_seq_trace_token`], or `None` if the process is not
+    /// sequentially traced.
+    pub fn seq_trace_send(&self) -> Option<SeqTraceToken> {
+        self.seq_trace.lock().unwrap().on_send(self.id)
+    }
 }
 
 // Implement Debug trait
@@ -503,11 +1205,17 @@ impl fmt::Debug for Process {
             .field("heap_sz", &self.heap_sz)
             .field("min_heap_size", &self.min_heap_size)
             .field("max_heap_size", &self.max_heap_size)
+            .field("fullsweep_after", &self.fullsweep_after)
+            .field("gen_gcs", &self.gen_gcs)
+            .field("heap_grow", &self.heap_grow)
+            .field("priority", &self.priority)
+            .field("message_queue_data", &self.message_queue_data)
+            .field("copy_shared", &self.copy_shared)
             .field("heap_data_len", &self.heap_data.lock().unwrap().len())
             .field("heap_start_index", &self.heap_start_index)
             .field("heap_top_index", &*self.heap_top_index.lock().unwrap())
             .field("stack_top_index", &self.stack_top_index)
-            .field("flags", &format!("0x{:x}", self.flags))
+            .field("flags", &format!("0x{:x}", self.flags()))
             .field("reds", &self.reds)
             .field("fcalls", &self.fcalls)
             .field("arity", &self.arity)
@@ -515,11 +1223,20 @@ impl fmt::Debug for Process {
             .field("return_trace_frames", &self.return_trace_frames)
             .field("uniq", &self.uniq)
             .field("schedule_count", &self.schedule_count)
-            .field("rcount", &self.rcount)
+            .field("bif_continuation", &*self.bif_continuation.lock().unwrap())
+            .field("rcount", &self.rcount())
             .field("state", &self.get_state())
             .field("i", &(self.i as usize))
             .field("nif_pointers_count", &self.nif_pointers.len())
             .field("nif_libraries_count", &self.nif_libraries.len())
+            .field("message_queue_len", &self.message_queue_len())
+            .field("spawn_info", &self.spawn_info)
+            .field("seq_trace", &*self.seq_trace.lock().unwrap())
+            .field("group_leader", &self.group_leader())
+            .field("error_handler", &self.error_handler())
+            .field("aliases", &self.aliases.lock().unwrap().len())
+            .field("links", &*self.links.lock().unwrap())
+            .field("monitors", &*self.monitors.lock().unwrap())
             .finish()
     }
 }
@@ -547,6 +1264,146 @@ mod tests {
         assert_eq!(process.heap_slice().len(), 233); // Heap data is initialized
     }
 
+    #[test]
+    fn test_process_with_defaults() {
+        let defaults = ProcessDefaults {
+            min_heap_size: 610,
+            max_heap_size: 10_000,
+            fullsweep_after: 10,
+            ..ProcessDefaults::default()
+        };
+        let process = Process::with_defaults(124, &defaults);
+        assert_eq!(process.heap_sz(), 610);
+        assert_eq!(process.min_heap_size(), 610);
+        assert_eq!(process.max_heap_size(), 10_000);
+        assert_eq!(process.fullsweep_after(), 10);
+        assert_eq!(process.heap_slice().len(), 610);
+    }
+
+    #[test]
+    fn test_process_spawned_with_opts() {
+        let opts = SpawnOpts {
+            priority: ProcessPriority::High,
+            message_queue_data: MessageQueueData::OffHeap,
+            min_heap_size: Some(1000),
+            max_heap_size: Some(MaxHeapSize { size: 5000, kill: false, error_logger: true }),
+            fullsweep_after: Some(0),
+            copy_shared: true,
+            ..SpawnOpts::default()
+        };
+        let process = Process::spawned_with_opts(3, Some(1), InitialCall::new("m", "f", 0), &opts);
+        assert!(process.copy_shared());
+        assert!(!Process::new(4).copy_shared());
+        assert_eq!(process.priority(), ProcessPriority::High);
+        assert_eq!(process.message_queue_data(), MessageQueueData::OffHeap);
+        assert_eq!(process.heap_sz(), 1000);
+        assert_eq!(process.max_heap_size(), 5000);
+        assert!(!process.max_heap_size_limit().kill);
+        assert_eq!(process.fullsweep_after(), 0);
+        assert_eq!(process.parent(), Some(1));
+    }
+
+    #[test]
+    fn test_process_group_leader() {
+        let process = Process::new(6);
+        assert_eq!(process.group_leader(), 6);
+        process.set_group_leader(1);
+        assert_eq!(process.group_leader(), 1);
+    }
+
+    #[test]
+    fn test_process_error_handler() {
+        let process = Process::new(6);
+        assert_eq!(process.error_handler(), None);
+        assert_eq!(process.set_error_handler(0x4B), None);
+        assert_eq!(process.set_error_handler(0x8B), Some(0x4B));
+        assert_eq!(process.error_handler(), Some(0x8B));
+    }
+
+    #[test]
+    fn test_process_aliases() {
+        let process = Process::new(7);
+        process.add_alias([1, 2, 3], AliasMode::ExplicitUnalias);
+        process.add_alias([4, 5, 6], AliasMode::Reply);
+        assert!(process.use_alias([1, 2, 3]));
+        assert!(process.use_alias([1, 2, 3]));
+        assert!(process.use_alias([4, 5, 6]));
+        assert!(!process.use_alias([4, 5, 6]));
+        assert!(process.remove_alias([1, 2, 3]));
+        assert!(!process.use_alias([1, 2, 3]));
+    }
+
+    #[test]
+    fn test_process_links_and_monitors() {
+        let process = Process::new(4);
+        assert!(process.add_link(9));
+        assert!(!process.add_link(9));
+        assert_eq!(process.links(), vec![9]);
+        process.add_monitor(Monitor { kind: MonitorKind::Process, reference: 77, watcher: 4, target: 9 });
+        assert_eq!(process.remove_monitor(77).map(|m| m.target), Some(9));
+        assert!(process.monitors().is_empty());
+        assert!(process.remove_link(9));
+    }
+
+    #[test]
+    fn test_process_suspend_nests() {
+        let process = Process::new(5);
+        assert_eq!(process.suspend(1), 1);
+        assert_eq!(process.suspend(1), 2);
+        assert_eq!(process.suspend(2), 1);
+        assert_eq!(process.get_state(), ProcessState::Suspended);
+        assert_eq!(process.rcount(), 3);
+        assert_eq!(process.resume(3), None);
+        assert_eq!(process.resume(1), Some(true));
+        assert!(process.resume_all(1));
+        assert_eq!(process.resume(2), Some(false));
+        assert!(!process.is_suspended());
+        assert_eq!(process.rcount(), 0);
+    }
+
+    #[test]
+    fn test_process_suspend_on_busy() {
+        let process = Process::new(6);
+        process.suspend_on_busy(BusyDestination::Port(3));
+        process.suspend(1);
+        assert_eq!(process.busy_destination(), Some(BusyDestination::Port(3)));
+        assert_eq!(process.rcount(), 2);
+        assert_eq!(process.resume_from_busy(), Some(true));
+        assert_eq!(process.resume_from_busy(), None);
+        assert_eq!(process.resume(1), Some(false));
+        assert!(!process.is_suspended());
+    }
+
+    #[test]
+    fn test_process_bif_continuation() {
+        let process = Process::new(6);
+        assert!(!process.has_bif_continuation());
+        let continuation = BifContinuation { module: 1, function: 2, arity: 1, state: Box::new(10usize) };
+        assert!(process.save_bif_continuation(continuation).is_none());
+        assert!(process.has_bif_continuation());
+        assert!(format!("{:?}", process).contains("BifContinuation"));
+
+        let continuation = process.take_bif_continuation().unwrap();
+        assert_eq!((continuation.module, continuation.function, continuation.arity), (1, 2, 1));
+        assert_eq!(continuation.state.downcast_ref::<usize>(), Some(&10));
+        assert!(process.take_bif_continuation().is_none());
+    }
+
+    #[test]
+    fn test_process_spawned() {
+        let before = SystemTime::now();
+        let process = Process::spawned(2, Some(1), InitialCall::new("lists", "map", 2));
+        assert_eq!(process.id(), 2);
+        assert_eq!(process.parent(), Some(1));
+        assert_eq!(process.initial_call(), Some(&InitialCall::new("lists", "map", 2)));
+        assert!(process.spawn_time().unwrap() >= before);
+
+        let plain = Process::new(3);
+        assert!(plain.spawn_info().is_none());
+        assert_eq!(plain.parent(), None);
+        assert_eq!(plain.initial_call(), None);
+    }
+
     #[test]
     fn test_process_get_state() {
         let mut process = Process::new(1);
@@ -1076,7 +1933,7 @@ mod tests {
     fn test_process_debug_flags_format() {
         let mut process = Process::new(1);
         // Set some flags
-        process.flags = 0x12345678;
+        process.flags = AtomicU32::new(0x12345678);
         
         let debug_str = format!("{:?}", process);
         // Flags should be formatted as hex
@@ -1196,5 +2053,61 @@ mod tests {
         *process.heap_top_index.lock().unwrap() = 50;
         assert_eq!(process.stack_size_words(), Some(0));
     }
-}
 
+    #[test]
+    fn test_process_message_queue() {
+        let process = Process::new(5);
+        assert_eq!(process.message_queue_len(), 0);
+        process.send_message(Message::new(10));
+        process.send_message(Message::from_timer(20, 3));
+        assert_eq!(process.message_queue_len(), 2);
+        assert!(process.flush_timer_message(3));
+        assert_eq!(process.receive_after_0(|_| true), Some(Message::new(10)));
+        assert_eq!(process.receive_after_0(|_| true), None);
+    }
+
+    #[test]
+    fn test_process_seq_trace_token_follows_messages() {
+        use crate::seq_trace::SeqTraceToken;
+
+        let sender = Process::new(1);
+        let receiver = Process::new(2);
+        assert_eq!(sender.seq_trace_send(), None);
+
+        sender.set_seq_trace_token(Some(SeqTraceToken::new(17, 1)));
+        receiver.send_message(Message::new(10).with_seq_trace_token(sender.seq_trace_send()));
+        receiver.send_message(Message::new(20));
+
+        receiver.receive_after_0(|m| m.payload == 10).unwrap();
+        let token = receiver.seq_trace_token().unwrap();
+        assert_eq!((token.label, token.serial, token.sender), (17, 1, 1));
+        assert_eq!(receiver.seq_trace_state().clock, 1);
+
+        receiver.receive_after_0(|_| true).unwrap();
+        assert_eq!(receiver.seq_trace_token(), None);
+
+        sender.reset_seq_trace();
+        assert_eq!(sender.seq_trace_state(), Default::default());
+    }
+
+    #[test]
+    fn test_process_system_tasks_raise_priority() {
+        use crate::system_task::{SystemTask, SystemTaskKind};
+
+        let process = Process::new(1);
+        assert_eq!(process.effective_priority(), ProcessPriority::Normal);
+        process.schedule_system_task(SystemTask {
+            kind: SystemTaskKind::GarbageCollect,
+            requester: 2,
+            request_id: 7,
+            priority: ProcessPriority::High,
+        });
+        assert!(process.has_system_tasks());
+        assert_eq!(process.get_state(), ProcessState::SysTasks);
+        assert_eq!(process.effective_priority(), ProcessPriority::High);
+
+        assert_eq!(process.take_system_task().map(|task| task.request_id), Some(7));
+        assert!(!process.has_system_tasks());
+        assert_eq!(process.effective_priority(), ProcessPriority::Normal);
+    }
+}
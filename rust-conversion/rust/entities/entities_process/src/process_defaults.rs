@@ -0,0 +1,97 @@
+//! Process Defaults Entity
+//!
+//! Provides the system-wide defaults applied to new processes and to
+//! exception backtraces. They start at the ERTS defaults and are changed at
+//! runtime with `erlang:system_flag/2`.
+//! Based on the `erts_default_*` settings in erts/emulator/beam/erl_process.c
+//!
+//! A process takes the heap defaults when it is created, so changing them
+//! does not affect processes that already exist.
+
+use std::sync::RwLock;
+
+/// Default minimum heap size in words
+pub const DEFAULT_MIN_HEAP_SIZE: usize = 233;
+/// Default number of generational collections before a fullsweep
+pub const DEFAULT_FULLSWEEP_AFTER: usize = 65535;
+/// Default number of frames in an exception backtrace
+pub const DEFAULT_BACKTRACE_DEPTH: usize = 8;
+/// Largest backtrace depth that can be set (`MAX_BACKTRACE_SIZE`)
+pub const MAX_BACKTRACE_DEPTH: usize = 64;
+
+/// System-wide process defaults
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessDefaults {
+    /// Minimum heap size in words for new processes
+    pub min_heap_size: usize,
+    /// Maximum heap size in words for new processes (0 = unlimited)
+    pub max_heap_size: usize,
+    /// Generational collections before a fullsweep for new processes
+    pub fullsweep_after: usize,
+    /// Frames kept in exception backtraces
+    pub backtrace_depth: usize,
+}
+
+impl ProcessDefaults {
+    /// The ERTS defaults
+    pub const fn new() -> Self {
+        Self {
+            min_heap_size: DEFAULT_MIN_HEAP_SIZE,
+            max_heap_size: 0,
+            fullsweep_after: DEFAULT_FULLSWEEP_AFTER,
+            backtrace_depth: DEFAULT_BACKTRACE_DEPTH,
+        }
+    }
+}
+
+impl Default for ProcessDefaults {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Global process defaults
+static PROCESS_DEFAULTS: RwLock<ProcessDefaults> = RwLock::new(ProcessDefaults::new());
+
+/// Get the current process defaults
+pub fn process_defaults() -> ProcessDefaults {
+    *PROCESS_DEFAULTS.read().unwrap()
+}
+
+/// Change the process defaults
+///
+/// # Arguments
+/// * `update` - Function applied to the current defaults
+///
+/// # Returns
+/// The defaults before the change
+pub fn update_process_defaults(update: impl FnOnce(&mut ProcessDefaults)) -> ProcessDefaults {
+    let mut defaults = PROCESS_DEFAULTS.write().unwrap();
+    let old = *defaults;
+    update(&mut defaults);
+    old
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_erts_defaults() {
+        let defaults = ProcessDefaults::default();
+        assert_eq!(defaults.min_heap_size, 233);
+        assert_eq!(defaults.max_heap_size, 0);
+        assert_eq!(defaults.fullsweep_after, 65535);
+        assert_eq!(defaults.backtrace_depth, 8);
+    }
+
+    #[test]
+    fn test_update_returns_old_defaults() {
+        // Only the backtrace depth is changed, so processes created by other
+        // tests keep the default heap sizes
+        let old = update_process_defaults(|d| d.backtrace_depth = 20);
+        assert_eq!(process_defaults().backtrace_depth, 20);
+        let changed = update_process_defaults(|d| d.backtrace_depth = old.backtrace_depth);
+        assert_eq!(changed.backtrace_depth, 20);
+    }
+}
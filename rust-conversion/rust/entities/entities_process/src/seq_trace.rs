@@ -0,0 +1,151 @@
+//! Sequential Trace Token Entity
+//!
+//! Provides the sequential trace token carried by processes and messages.
+//! Based on the seq_trace token handling in erts/emulator/beam/erl_trace.c
+//!
+//! A token is `{Flags, Label, Serial, From, LastCnt}` in Erlang. A process
+//! with a token passes it on with every message it sends, bumping the serial
+//! from its own clock first. A process that receives a message takes over
+//! the message's token, or loses its token if the message has none, and
+//! advances its clock past the message's serial. This keeps serials ordered
+//! along every chain of messages.
+
+use crate::process::{Eterm, ProcessId};
+
+/// Trace `send` events (`seq_trace:set_token(send, true)`)
+pub const SEQ_TRACE_SEND: u32 = 1 << 0;
+/// Trace `'receive'` events
+pub const SEQ_TRACE_RECEIVE: u32 = 1 << 1;
+/// Trace `print` events from `seq_trace:print/1,2`
+pub const SEQ_TRACE_PRINT: u32 = 1 << 2;
+/// Timestamp events with `erlang:timestamp/0`
+pub const SEQ_TRACE_NOW_TIMESTAMP: u32 = 1 << 3;
+/// Timestamp events with a strictly monotonic `{Time, UniqueInteger}`
+pub const SEQ_TRACE_STRICT_MONOTONIC_TIMESTAMP: u32 = 1 << 4;
+/// Timestamp events with monotonic time
+pub const SEQ_TRACE_MONOTONIC_TIMESTAMP: u32 = 1 << 5;
+
+/// Sequential trace token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeqTraceToken {
+    /// Enabled `SEQ_TRACE_*` flags
+    pub flags: u32,
+    /// Label identifying the trace
+    pub label: Eterm,
+    /// Serial of the last message sent with this token
+    pub serial: u64,
+    /// Process that last sent the token
+    pub sender: ProcessId,
+    /// Serial the sender had seen before sending
+    pub last_count: u64,
+}
+
+impl SeqTraceToken {
+    /// Create a token with no flags set, as `seq_trace:set_token/2` does
+    /// for a process without a token
+    pub fn new(label: Eterm, owner: ProcessId) -> Self {
+        Self {
+            flags: 0,
+            label,
+            serial: 0,
+            sender: owner,
+            last_count: 0,
+        }
+    }
+
+    /// Check whether a `SEQ_TRACE_*` flag is set
+    pub fn has_flag(&self, flag: u32) -> bool {
+        self.flags & flag != 0
+    }
+
+    /// Set or clear a `SEQ_TRACE_*` flag, returning whether it was set
+    pub fn set_flag(&mut self, flag: u32, on: bool) -> bool {
+        let was_set = self.has_flag(flag);
+        if on {
+            self.flags |= flag;
+        } else {
+            self.flags &= !flag;
+        }
+        was_set
+    }
+}
+
+/// Sequential trace state of a process
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SeqTraceState {
+    /// Current token, `None` when the process is not sequentially traced
+    pub token: Option<SeqTraceToken>,
+    /// Highest serial seen or sent by the process
+    pub clock: u64,
+    /// Serial of the last traced message received
+    pub last_count: u64,
+}
+
+impl SeqTraceState {
+    /// Update the token for a message send by `sender`
+    ///
+    /// Returns the token to attach to the message, if the process has one.
+    pub fn on_send(&mut self, sender: ProcessId) -> Option<SeqTraceToken> {
+        let token = self.token.as_mut()?;
+        token.last_count = self.last_count;
+        self.clock += 1;
+        token.serial = self.clock;
+        token.sender = sender;
+        Some(*token)
+    }
+
+    /// Take over the token of a received message
+    pub fn on_receive(&mut self, token: Option<SeqTraceToken>) {
+        if let Some(token) = token {
+            self.clock = self.clock.max(token.serial);
+            self.last_count = token.serial;
+        }
+        self.token = token;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_flag_returns_previous() {
+        let mut token = SeqTraceToken::new(7, 1);
+        assert!(!token.set_flag(SEQ_TRACE_SEND, true));
+        assert!(token.set_flag(SEQ_TRACE_SEND, true));
+        assert!(token.has_flag(SEQ_TRACE_SEND));
+        assert!(token.set_flag(SEQ_TRACE_SEND, false));
+        assert_eq!(token.flags, 0);
+    }
+
+    #[test]
+    fn test_send_without_token() {
+        let mut state = SeqTraceState::default();
+        assert_eq!(state.on_send(1), None);
+        assert_eq!(state.clock, 0);
+    }
+
+    #[test]
+    fn test_serials_increase_along_message_chain() {
+        let mut a = SeqTraceState { token: Some(SeqTraceToken::new(7, 1)), ..Default::default() };
+        let mut b = SeqTraceState::default();
+
+        let sent = a.on_send(1).unwrap();
+        assert_eq!((sent.last_count, sent.serial, sent.sender), (0, 1, 1));
+        b.on_receive(Some(sent));
+        let reply = b.on_send(2).unwrap();
+        assert_eq!((reply.last_count, reply.serial, reply.sender), (1, 2, 2));
+        a.on_receive(Some(reply));
+        assert_eq!(a.clock, 2);
+        let next = a.on_send(1).unwrap();
+        assert_eq!((next.last_count, next.serial), (2, 3));
+    }
+
+    #[test]
+    fn test_receive_without_token_clears_token() {
+        let mut state = SeqTraceState { token: Some(SeqTraceToken::new(7, 1)), clock: 5, last_count: 3 };
+        state.on_receive(None);
+        assert_eq!(state.token, None);
+        assert_eq!(state.clock, 5);
+    }
+}
@@ -0,0 +1,175 @@
+//! Spawn Options Entity
+//!
+//! Provides the options accepted by `erlang:spawn_opt/2,3,4,5` and the
+//! per-process settings they control.
+//! Based on `ErlSpawnOpts` in erts/emulator/beam/erl_process.h
+//!
+//! Options that are not given fall back to the current
+//! [`process_defaults`](crate::process_defaults::process_defaults) when the
+//! process is created.
+
+/// Scheduling priority of a process (`process_flag(priority, P)`)
+///
+/// Based on the PRIORITY_* constants in erl_process.h
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum ProcessPriority {
+    /// `max`, reserved for system processes
+    Max,
+    /// `high`
+    High,
+    /// `normal`
+    #[default]
+    Normal,
+    /// `low`
+    Low,
+}
+
+impl ProcessPriority {
+    /// Parse a priority from its atom name
+    pub fn from_atom(name: &str) -> Option<Self> {
+        match name {
+            "max" => Some(ProcessPriority::Max),
+            "high" => Some(ProcessPriority::High),
+            "normal" => Some(ProcessPriority::Normal),
+            "low" => Some(ProcessPriority::Low),
+            _ => None,
+        }
+    }
+
+    /// Atom name of the priority
+    pub fn as_atom(self) -> &'static str {
+        match self {
+            ProcessPriority::Max => "max",
+            ProcessPriority::High => "high",
+            ProcessPriority::Normal => "normal",
+            ProcessPriority::Low => "low",
+        }
+    }
+}
+
+/// Where the messages of a process are stored (`message_queue_data`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MessageQueueData {
+    /// Messages are part of the process heap and are collected with it
+    #[default]
+    OnHeap,
+    /// Messages stay in heap fragments outside the process heap
+    OffHeap,
+}
+
+impl MessageQueueData {
+    /// Parse the setting from its atom name
+    pub fn from_atom(name: &str) -> Option<Self> {
+        match name {
+            "on_heap" => Some(MessageQueueData::OnHeap),
+            "off_heap" => Some(MessageQueueData::OffHeap),
+            _ => None,
+        }
+    }
+
+    /// Atom name of the setting
+    pub fn as_atom(self) -> &'static str {
+        match self {
+            MessageQueueData::OnHeap => "on_heap",
+            MessageQueueData::OffHeap => "off_heap",
+        }
+    }
+}
+
+/// Maximum heap size of a process and what happens when it is reached
+///
+/// Based on the `max_heap_size` map (`#{size, kill, error_logger}`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MaxHeapSize {
+    /// Limit in words (0 = unlimited)
+    pub size: usize,
+    /// Kill the process when the limit is exceeded
+    pub kill: bool,
+    /// Send an error report to the logger when the limit is exceeded
+    pub error_logger: bool,
+}
+
+impl MaxHeapSize {
+    /// A limit of `size` words that kills the process and logs an error
+    pub const fn new(size: usize) -> Self {
+        Self {
+            size,
+            kill: true,
+            error_logger: true,
+        }
+    }
+}
+
+impl Default for MaxHeapSize {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// Options given to `spawn_opt`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpawnOpts {
+    /// Link the new process to its parent (`link`)
+    pub link: bool,
+    /// Monitor the new process from its parent (`monitor`)
+    pub monitor: bool,
+    /// Scheduling priority
+    pub priority: ProcessPriority,
+    /// Message queue placement
+    pub message_queue_data: MessageQueueData,
+    /// Minimum heap size in words (default from the process defaults)
+    pub min_heap_size: Option<usize>,
+    /// Maximum heap size (default from the process defaults)
+    pub max_heap_size: Option<MaxHeapSize>,
+    /// Generational collections before a fullsweep (default from the process defaults)
+    pub fullsweep_after: Option<usize>,
+    /// Preserve sharing within the messages the process sends (`copy_shared`)
+    pub copy_shared: bool,
+}
+
+impl SpawnOpts {
+    /// Check the options for conflicts that make `spawn_opt` fail with `badarg`
+    ///
+    /// The maximum heap size must not be below the minimum heap size.
+    ///
+    /// # Arguments
+    /// * `default_min_heap_size` - Minimum heap size used if none is given
+    pub fn validate(&self, default_min_heap_size: usize) -> Result<(), String> {
+        let min_heap_size = self.min_heap_size.unwrap_or(default_min_heap_size);
+        match self.max_heap_size {
+            Some(max) if max.size != 0 && max.size < min_heap_size => {
+                Err("max_heap_size must not be below min_heap_size".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_atom_names_round_trip() {
+        for prio in [ProcessPriority::Max, ProcessPriority::High, ProcessPriority::Normal, ProcessPriority::Low] {
+            assert_eq!(ProcessPriority::from_atom(prio.as_atom()), Some(prio));
+        }
+        assert_eq!(ProcessPriority::from_atom("urgent"), None);
+        assert_eq!(MessageQueueData::from_atom("off_heap"), Some(MessageQueueData::OffHeap));
+        assert_eq!(MessageQueueData::OnHeap.as_atom(), "on_heap");
+    }
+
+    #[test]
+    fn test_validate_heap_limits() {
+        let mut opts = SpawnOpts {
+            max_heap_size: Some(MaxHeapSize::new(100)),
+            ..SpawnOpts::default()
+        };
+        assert!(opts.validate(233).is_err());
+        opts.min_heap_size = Some(50);
+        assert!(opts.validate(233).is_ok());
+        opts.max_heap_size = Some(MaxHeapSize::new(0));
+        opts.min_heap_size = None;
+        assert!(opts.validate(233).is_ok());
+    }
+}
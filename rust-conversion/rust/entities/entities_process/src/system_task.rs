@@ -0,0 +1,140 @@
+//! System Task Entity
+//!
+//! Provides the system tasks other processes schedule on a process.
+//! Based on ErtsProcSysTask and the system task queues in
+//! erts/emulator/beam/erl_process.c
+//!
+//! A system task is work done on a process on behalf of another process,
+//! such as a garbage collection requested with `erlang:garbage_collect/2`
+//! or the `check_process_code` and `copy_literals` requests made while code
+//! is purged. Tasks are queued on the target process and carry the priority
+//! they were requested at. The target is scheduled at the higher of its own
+//! priority and the priority of its queued tasks, so a task requested by a
+//! high priority process is not held up by a low priority target. When a
+//! task has been executed, `{Kind, RequestId, Result}` is sent to the
+//! requester.
+
+use std::collections::VecDeque;
+
+use crate::process::ProcessId;
+use crate::spawn_opts::ProcessPriority;
+
+/// Work a system task does on its target process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemTaskKind {
+    /// Garbage collect the process
+    GarbageCollect,
+    /// Check if the process executes or refers to the code in a range
+    CheckProcessCode {
+        /// Start address of the module's code
+        code_start: usize,
+        /// Size of the module's code in bytes
+        code_size: u32,
+    },
+    /// Copy literals in a range onto the process heap before it is released
+    CopyLiterals {
+        /// Start address of the literal area
+        area_start: usize,
+        /// Size of the literal area in bytes
+        area_size: usize,
+    },
+}
+
+impl SystemTaskKind {
+    /// Get the atom the reply to the task is tagged with
+    pub fn name(&self) -> &'static str {
+        match self {
+            SystemTaskKind::GarbageCollect => "garbage_collect",
+            SystemTaskKind::CheckProcessCode { .. } => "check_process_code",
+            SystemTaskKind::CopyLiterals { .. } => "copy_literals",
+        }
+    }
+}
+
+/// A system task queued on a process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemTask {
+    /// Work to do
+    pub kind: SystemTaskKind,
+    /// Process the reply is sent to
+    pub requester: ProcessId,
+    /// Request identifier sent back in the reply
+    pub request_id: u64,
+    /// Priority the task was requested at
+    pub priority: ProcessPriority,
+}
+
+/// System tasks queued on a process
+///
+/// Tasks are taken highest priority first, and in request order within a
+/// priority.
+#[derive(Debug, Default)]
+pub struct SystemTaskQueue {
+    tasks: VecDeque<SystemTask>,
+}
+
+impl SystemTaskQueue {
+    /// Create an empty queue
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a task
+    pub fn push(&mut self, task: SystemTask) {
+        self.tasks.push_back(task);
+    }
+
+    /// Take the next task to execute
+    pub fn pop(&mut self) -> Option<SystemTask> {
+        let position = self
+            .tasks
+            .iter()
+            .enumerate()
+            .min_by_key(|(index, task)| (task.priority, *index))
+            .map(|(index, _)| index)?;
+        self.tasks.remove(position)
+    }
+
+    /// Get the highest priority of the queued tasks
+    pub fn priority(&self) -> Option<ProcessPriority> {
+        self.tasks.iter().map(|task| task.priority).min()
+    }
+
+    /// Number of queued tasks
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Check if no tasks are queued
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(request_id: u64, priority: ProcessPriority) -> SystemTask {
+        SystemTask {
+            kind: SystemTaskKind::GarbageCollect,
+            requester: 1,
+            request_id,
+            priority,
+        }
+    }
+
+    #[test]
+    fn test_queue_order_and_priority() {
+        let mut queue = SystemTaskQueue::new();
+        assert_eq!(queue.priority(), None);
+        queue.push(task(1, ProcessPriority::Low));
+        queue.push(task(2, ProcessPriority::High));
+        queue.push(task(3, ProcessPriority::High));
+        assert_eq!(queue.priority(), Some(ProcessPriority::High));
+
+        let order: Vec<u64> = std::iter::from_fn(|| queue.pop()).map(|task| task.request_id).collect();
+        assert_eq!(order, vec![2, 3, 1]);
+        assert!(queue.is_empty());
+    }
+}
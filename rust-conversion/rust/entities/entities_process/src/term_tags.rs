@@ -0,0 +1,177 @@
+//! Term Tags Module
+//!
+//! Provides the tag bits of [`Eterm`] words, shared by every crate that
+//! builds or inspects terms. Based on the 64-bit tag scheme in erl_term.h
+//!
+//! The two low bits of a term are its primary tag:
+//! - [`TAG_PRIMARY_HEADER`] - header word of a boxed object
+//! - [`TAG_PRIMARY_LIST`] - pointer to a cons cell
+//! - [`TAG_PRIMARY_BOXED`] - pointer to a boxed object
+//! - [`TAG_PRIMARY_IMMED1`] - immediate: pid, port, small integer, or an
+//!   immediate-2 term (atom, catch, nil)
+//!
+//! Pointer terms address heap words by index: a list or boxed term is
+//! `(index << TAG_PRIMARY_SIZE) | tag`.
+
+use crate::process::Eterm;
+
+/// Number of primary tag bits
+pub const TAG_PRIMARY_SIZE: u32 = 2;
+/// Mask of the primary tag
+pub const TAG_PRIMARY_MASK: Eterm = 0x3;
+/// Primary tag of header words
+pub const TAG_PRIMARY_HEADER: Eterm = 0x0;
+/// Primary tag of list pointers
+pub const TAG_PRIMARY_LIST: Eterm = 0x1;
+/// Primary tag of boxed pointers
+pub const TAG_PRIMARY_BOXED: Eterm = 0x2;
+/// Primary tag of immediates
+pub const TAG_PRIMARY_IMMED1: Eterm = 0x3;
+
+/// Number of immediate-1 tag bits (`_TAG_IMMED1_SIZE`)
+pub const TAG_IMMED1_SIZE: u32 = 4;
+/// Mask of the immediate-1 tag
+pub const TAG_IMMED1_MASK: Eterm = 0xF;
+/// Immediate-1 tag of local pids
+pub const TAG_IMMED1_PID: Eterm = 0x3;
+/// Immediate-1 tag of local ports
+pub const TAG_IMMED1_PORT: Eterm = 0x7;
+/// Immediate-1 tag of immediate-2 terms
+pub const TAG_IMMED1_IMMED2: Eterm = 0xB;
+/// Immediate-1 tag of small integers
+pub const TAG_IMMED1_SMALL: Eterm = 0xF;
+
+/// Number of immediate-2 tag bits (`_TAG_IMMED2_SIZE`)
+pub const TAG_IMMED2_SIZE: u32 = 6;
+/// Mask of the immediate-2 tag
+pub const TAG_IMMED2_MASK: Eterm = 0x3F;
+/// Immediate-2 tag of atoms
+pub const TAG_IMMED2_ATOM: Eterm = 0x0B;
+/// Immediate-2 tag of catch markers
+pub const TAG_IMMED2_CATCH: Eterm = 0x1B;
+/// Immediate-2 tag of nil
+pub const TAG_IMMED2_NIL: Eterm = 0x3B;
+
+/// Nil (`[]`)
+pub const NIL: Eterm = TAG_IMMED2_NIL;
+
+/// Word returned by a BIF that failed (`THE_NON_VALUE`)
+///
+/// Zero is a tuple header, which no term can be.
+pub const THE_NON_VALUE: Eterm = 0;
+
+/// Mask of the header tag (primary tag and subtag)
+pub const TAG_HEADER_MASK: Eterm = 0x3F;
+/// Offset of the arity in a header word (`_HEADER_ARITY_OFFS`)
+pub const HEADER_ARITY_OFFS: u32 = 6;
+
+/// Header subtag of tuples
+pub const ARITYVAL_SUBTAG: Eterm = 0x0 << TAG_PRIMARY_SIZE;
+/// Header subtag of positive bignums
+pub const POS_BIG_SUBTAG: Eterm = 0x2 << TAG_PRIMARY_SIZE;
+/// Header subtag of negative bignums
+pub const NEG_BIG_SUBTAG: Eterm = 0x3 << TAG_PRIMARY_SIZE;
+/// Header subtag of references
+pub const REF_SUBTAG: Eterm = 0x4 << TAG_PRIMARY_SIZE;
+/// Header subtag of funs
+pub const FUN_SUBTAG: Eterm = 0x5 << TAG_PRIMARY_SIZE;
+/// Header subtag of floats
+pub const FLOAT_SUBTAG: Eterm = 0x6 << TAG_PRIMARY_SIZE;
+/// Header subtag of export funs
+pub const EXPORT_SUBTAG: Eterm = 0x7 << TAG_PRIMARY_SIZE;
+/// Header subtag of reference-counted binaries
+pub const REFC_BINARY_SUBTAG: Eterm = 0x8 << TAG_PRIMARY_SIZE;
+/// Header subtag of heap binaries
+pub const HEAP_BINARY_SUBTAG: Eterm = 0x9 << TAG_PRIMARY_SIZE;
+/// Header subtag of sub binaries
+pub const SUB_BINARY_SUBTAG: Eterm = 0xA << TAG_PRIMARY_SIZE;
+/// Header subtag no object uses
+pub const UNUSED_SUBTAG: Eterm = 0xB << TAG_PRIMARY_SIZE;
+/// Header subtag of external pids
+pub const EXTERNAL_PID_SUBTAG: Eterm = 0xC << TAG_PRIMARY_SIZE;
+/// Header subtag of external ports
+pub const EXTERNAL_PORT_SUBTAG: Eterm = 0xD << TAG_PRIMARY_SIZE;
+/// Header subtag of external references
+pub const EXTERNAL_REF_SUBTAG: Eterm = 0xE << TAG_PRIMARY_SIZE;
+/// Header subtag of maps
+pub const MAP_SUBTAG: Eterm = 0xF << TAG_PRIMARY_SIZE;
+
+/// Make an atom term of an atom index (`make_atom`)
+pub const fn make_atom(index: usize) -> Eterm {
+    ((index as Eterm) << TAG_IMMED2_SIZE) | TAG_IMMED2_ATOM
+}
+
+/// Make a small integer term (`make_small`)
+pub const fn make_small(value: i64) -> Eterm {
+    ((value as Eterm) << TAG_IMMED1_SIZE) | TAG_IMMED1_SMALL
+}
+
+/// Value of a small integer term (`signed_val`)
+pub const fn signed_val(term: Eterm) -> i64 {
+    (term as i64) >> TAG_IMMED1_SIZE
+}
+
+/// Make a local pid term of a process identifier (`make_internal_pid`)
+///
+/// The top [`TAG_IMMED1_SIZE`] bits of the identifier are not kept.
+pub const fn make_internal_pid(id: u64) -> Eterm {
+    (id << TAG_IMMED1_SIZE) | TAG_IMMED1_PID
+}
+
+/// Make a local port term of a port identifier (`make_internal_port`)
+pub const fn make_internal_port(id: u64) -> Eterm {
+    (id << TAG_IMMED1_SIZE) | TAG_IMMED1_PORT
+}
+
+/// Identifier of a local pid or port term
+pub const fn internal_id(term: Eterm) -> u64 {
+    term >> TAG_IMMED1_SIZE
+}
+
+/// Make the header word of a tuple (`make_arityval`)
+pub const fn make_arityval(arity: usize) -> Eterm {
+    ((arity as Eterm) << HEADER_ARITY_OFFS) | ARITYVAL_SUBTAG
+}
+
+/// Make a boxed pointer to a heap word (`make_boxed`)
+pub const fn make_boxed(index: usize) -> Eterm {
+    ((index as Eterm) << TAG_PRIMARY_SIZE) | TAG_PRIMARY_BOXED
+}
+
+/// Make a list pointer to a heap word (`make_list`)
+pub const fn make_list(index: usize) -> Eterm {
+    ((index as Eterm) << TAG_PRIMARY_SIZE) | TAG_PRIMARY_LIST
+}
+
+/// Heap word index a list or boxed pointer addresses
+pub const fn pointer_index(term: Eterm) -> usize {
+    (term >> TAG_PRIMARY_SIZE) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_immediates() {
+        assert_eq!(make_atom(3), 0xCB);
+        assert_eq!(make_atom(3) & TAG_IMMED2_MASK, TAG_IMMED2_ATOM);
+        assert_eq!(make_small(-1) & TAG_IMMED1_MASK, TAG_IMMED1_SMALL);
+        assert_eq!((make_small(-1) as i64) >> TAG_IMMED1_SIZE, -1);
+        assert_eq!(NIL & TAG_IMMED1_MASK, TAG_IMMED1_IMMED2);
+        assert_ne!(NIL, make_small(3));
+        assert_eq!(signed_val(make_small(-7)), -7);
+        assert_eq!(make_internal_pid(9) & TAG_IMMED1_MASK, TAG_IMMED1_PID);
+        assert_eq!(make_internal_port(9) & TAG_IMMED1_MASK, TAG_IMMED1_PORT);
+        assert_eq!(internal_id(make_internal_pid(9)), 9);
+    }
+
+    #[test]
+    fn test_pointers() {
+        assert_eq!(make_boxed(5) & TAG_PRIMARY_MASK, TAG_PRIMARY_BOXED);
+        assert_eq!(make_list(5) & TAG_PRIMARY_MASK, TAG_PRIMARY_LIST);
+        assert_eq!(pointer_index(make_boxed(5)), 5);
+        assert_eq!(make_arityval(2) & TAG_HEADER_MASK, ARITYVAL_SUBTAG);
+        assert_eq!(make_arityval(2) >> HEADER_ARITY_OFFS, 2);
+    }
+}
@@ -15,7 +15,7 @@
 //!
 //! - **[`mmap`](mmap/index.html)**: Memory mapping operations for allocating and
 //!   managing memory-mapped regions. Provides a platform-independent interface
-//!   for memory mapping operations.
+//!   for memory mapping operations, and the super carrier that carriers are mapped from.
 //!
 //! ## Usage
 //!
@@ -60,5 +60,8 @@
 
 pub mod mmap;
 
-pub use mmap::MemoryMap;
+pub use mmap::{
+    get_global_super_carrier, memory_protection_enabled, page_size, reserve_global_super_carrier, set_memory_protection,
+    MemoryMap, MmapError, SuperCarrier,
+};
 
@@ -15,6 +15,11 @@
 //! - **File Mapping**: Map files into memory for efficient access
 //! - **Memory Access**: Direct access to mapped memory as byte slices
 //! - **Platform Independent**: Works across all platforms using Rust standard library
+//! - **Super Carrier**: A virtual range reserved up front (`+MMscs`) that carriers
+//!   are mapped from, so callers can tell by address whether memory came from it
+//! - **Protection**: Committed pages can be made read-only so stray writes to
+//!   code and literals fault where they happen; [`set_memory_protection`]
+//!   turns this off for debugging
 //!
 //! ## Examples
 //!
@@ -58,9 +63,13 @@
  * %CopyrightEnd%
  */
 
+use std::alloc::Layout;
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 
 /// Memory map representation for file-backed memory mapping
 ///
@@ -162,6 +171,437 @@ impl MemoryMap {
     }
 }
 
+/// Page size assumed where the OS does not report one
+const FALLBACK_PAGE_SIZE: usize = 4096;
+
+/// Get the OS page size, the granularity of super-carrier mappings, commits
+/// and protection
+///
+/// Read once with `sysconf(_SC_PAGESIZE)`: 4 KiB on most systems, 16 KiB on
+/// Apple Silicon and some aarch64 Linux kernels, 64 KiB on others.
+pub fn page_size() -> usize {
+    static PAGE_SIZE: OnceLock<usize> = OnceLock::new();
+    *PAGE_SIZE.get_or_init(os_page_size)
+}
+
+#[cfg(unix)]
+fn os_page_size() -> usize {
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if size > 0 && (size as usize).is_power_of_two() {
+        size as usize
+    } else {
+        FALLBACK_PAGE_SIZE
+    }
+}
+
+#[cfg(not(unix))]
+fn os_page_size() -> usize {
+    FALLBACK_PAGE_SIZE
+}
+
+/// Errors of super-carrier operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MmapError {
+    /// Size is zero or too large
+    InvalidSize,
+    /// The virtual range could not be reserved
+    ReservationFailed,
+    /// No free range in the super carrier is large enough
+    Exhausted,
+    /// Range is not inside the super carrier
+    OutOfRange,
+    /// Address is not the start of a mapped segment
+    NotMapped,
+    /// The global super carrier is already reserved
+    AlreadyReserved,
+    /// The access of a range could not be changed
+    ProtectionFailed,
+}
+
+impl std::fmt::Display for MmapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MmapError::InvalidSize => write!(f, "invalid size"),
+            MmapError::ReservationFailed => write!(f, "could not reserve super carrier"),
+            MmapError::Exhausted => write!(f, "super carrier exhausted"),
+            MmapError::OutOfRange => write!(f, "range outside super carrier"),
+            MmapError::NotMapped => write!(f, "address is not a mapped segment"),
+            MmapError::AlreadyReserved => write!(f, "super carrier already reserved"),
+            MmapError::ProtectionFailed => write!(f, "could not change memory protection"),
+        }
+    }
+}
+
+impl std::error::Error for MmapError {}
+
+/// Bookkeeping of a super carrier, in offsets from its base
+struct SuperCarrierState {
+    /// Free ranges, offset to length, in address order
+    free: BTreeMap<usize, usize>,
+    /// Mapped segments, offset to length
+    mapped: BTreeMap<usize, usize>,
+    /// Committed flag per page
+    committed: Vec<bool>,
+    /// Read-only flag per page
+    protected: Vec<bool>,
+}
+
+/// Virtual range reserved up front that carriers are mapped from
+///
+/// Based on the super carrier of erl_mmap.c (`+MMscs`). The whole range is
+/// reserved when the super carrier is created; segments are mapped from it
+/// address order first fit and their pages committed, and unmapping a
+/// segment decommits its pages and coalesces the range with its free
+/// neighbours. Decommitted pages read back as zeros.
+///
+/// Since every segment lies inside the reserved range, [`SuperCarrier::contains`]
+/// tells by address alone whether memory came from the super carrier.
+///
+/// Committed pages can be made read-only with [`SuperCarrier::protect`].
+/// Decommitting and unmapping make pages writable again first.
+pub struct SuperCarrier {
+    /// Start of the reserved range
+    base: usize,
+    /// Layout of the reservation
+    layout: Layout,
+    /// Free, mapped and committed ranges
+    state: Mutex<SuperCarrierState>,
+}
+
+impl SuperCarrier {
+    /// Reserve a super carrier of the given size, rounded up to whole pages
+    ///
+    /// # Errors
+    /// * `MmapError::InvalidSize` - If `size` is zero or too large
+    /// * `MmapError::ReservationFailed` - If the range could not be reserved
+    pub fn reserve(size: usize) -> Result<Self, MmapError> {
+        let size = round_to_pages(size).ok_or(MmapError::InvalidSize)?;
+        if size == 0 {
+            return Err(MmapError::InvalidSize);
+        }
+        let layout =
+            Layout::from_size_align(size, page_size()).map_err(|_| MmapError::InvalidSize)?;
+        // Zeroed memory is only backed once touched, which stands in for a
+        // reservation of address space without committing it
+        let base = unsafe { std::alloc::alloc_zeroed(layout) };
+        if base.is_null() {
+            return Err(MmapError::ReservationFailed);
+        }
+        let mut free = BTreeMap::new();
+        free.insert(0, size);
+        Ok(Self {
+            base: base as usize,
+            layout,
+            state: Mutex::new(SuperCarrierState {
+                free,
+                mapped: BTreeMap::new(),
+                committed: vec![false; size / page_size()],
+                protected: vec![false; size / page_size()],
+            }),
+        })
+    }
+
+    /// Get the start of the reserved range
+    pub fn base(&self) -> *mut u8 {
+        self.base as *mut u8
+    }
+
+    /// Get the size of the reserved range
+    pub fn size(&self) -> usize {
+        self.layout.size()
+    }
+
+    /// Check if an address lies inside the reserved range
+    pub fn contains(&self, ptr: *const u8) -> bool {
+        let addr = ptr as usize;
+        addr >= self.base && addr < self.base + self.size()
+    }
+
+    /// Map a segment of at least `size` bytes and commit its pages
+    ///
+    /// # Errors
+    /// * `MmapError::InvalidSize` - If `size` is zero
+    /// * `MmapError::Exhausted` - If no free range is large enough
+    pub fn map(&self, size: usize) -> Result<*mut u8, MmapError> {
+        let size = round_to_pages(size).ok_or(MmapError::InvalidSize)?;
+        if size == 0 {
+            return Err(MmapError::InvalidSize);
+        }
+        let mut state = self.state.lock().unwrap();
+        let (offset, free_len) = state
+            .free
+            .iter()
+            .find(|(_, len)| **len >= size)
+            .map(|(offset, len)| (*offset, *len))
+            .ok_or(MmapError::Exhausted)?;
+        state.free.remove(&offset);
+        if free_len > size {
+            state.free.insert(offset + size, free_len - size);
+        }
+        state.mapped.insert(offset, size);
+        set_pages(&mut state.committed, offset, size, true);
+        Ok((self.base + offset) as *mut u8)
+    }
+
+    /// Unmap a segment returned by [`SuperCarrier::map`]
+    ///
+    /// The segment's pages are decommitted and its range merged with
+    /// adjacent free ranges.
+    ///
+    /// # Errors
+    /// * `MmapError::NotMapped` - If `ptr` is not the start of a mapped segment
+    pub fn unmap(&self, ptr: *mut u8) -> Result<(), MmapError> {
+        let offset = self.offset_of(ptr).ok_or(MmapError::NotMapped)?;
+        let mut state = self.state.lock().unwrap();
+        let size = state.mapped.remove(&offset).ok_or(MmapError::NotMapped)?;
+        self.decommit(&mut state, offset, size);
+
+        let mut start = offset;
+        let mut len = size;
+        if let Some((&prev, &prev_len)) = state.free.range(..offset).next_back() {
+            if prev + prev_len == offset {
+                state.free.remove(&prev);
+                start = prev;
+                len += prev_len;
+            }
+        }
+        if let Some(next_len) = state.free.remove(&(offset + size)) {
+            len += next_len;
+        }
+        state.free.insert(start, len);
+        Ok(())
+    }
+
+    /// Commit the pages covering a range
+    ///
+    /// # Errors
+    /// * `MmapError::OutOfRange` - If the range is not inside the super carrier
+    pub fn commit(&self, ptr: *mut u8, size: usize) -> Result<(), MmapError> {
+        let (offset, len) = self.page_range(ptr, size)?;
+        set_pages(&mut self.state.lock().unwrap().committed, offset, len, true);
+        Ok(())
+    }
+
+    /// Decommit the pages covering a range; they read back as zeros
+    ///
+    /// # Errors
+    /// * `MmapError::OutOfRange` - If the range is not inside the super carrier
+    pub fn uncommit(&self, ptr: *mut u8, size: usize) -> Result<(), MmapError> {
+        let (offset, len) = self.page_range(ptr, size)?;
+        self.decommit(&mut self.state.lock().unwrap(), offset, len);
+        Ok(())
+    }
+
+    /// Make the pages covering a range read-only
+    ///
+    /// Does nothing while memory protection is disabled
+    /// ([`set_memory_protection`]).
+    ///
+    /// # Errors
+    /// * `MmapError::OutOfRange` - If the range is not inside the super carrier
+    /// * `MmapError::ProtectionFailed` - If the pages could not be protected
+    pub fn protect(&self, ptr: *mut u8, size: usize) -> Result<(), MmapError> {
+        let (offset, len) = self.page_range(ptr, size)?;
+        if !memory_protection_enabled() {
+            return Ok(());
+        }
+        let mut state = self.state.lock().unwrap();
+        set_access(self.base + offset, len, true)?;
+        set_pages(&mut state.protected, offset, len, true);
+        Ok(())
+    }
+
+    /// Make the pages covering a range writable again
+    ///
+    /// # Errors
+    /// * `MmapError::OutOfRange` - If the range is not inside the super carrier
+    /// * `MmapError::ProtectionFailed` - If the pages could not be unprotected
+    pub fn unprotect(&self, ptr: *mut u8, size: usize) -> Result<(), MmapError> {
+        let (offset, len) = self.page_range(ptr, size)?;
+        self.release_protection(&mut self.state.lock().unwrap(), offset, len)
+    }
+
+    /// Check if the page holding an address is read-only
+    pub fn is_protected(&self, ptr: *const u8) -> bool {
+        if !self.contains(ptr) {
+            return false;
+        }
+        let page = (ptr as usize - self.base) / page_size();
+        self.state.lock().unwrap().protected[page]
+    }
+
+    /// Check if the page holding an address is committed
+    pub fn is_committed(&self, ptr: *const u8) -> bool {
+        if !self.contains(ptr) {
+            return false;
+        }
+        let page = (ptr as usize - self.base) / page_size();
+        self.state.lock().unwrap().committed[page]
+    }
+
+    /// Get the number of bytes in mapped segments
+    pub fn mapped_size(&self) -> usize {
+        self.state.lock().unwrap().mapped.values().sum()
+    }
+
+    /// Get the number of committed bytes
+    pub fn committed_size(&self) -> usize {
+        let state = self.state.lock().unwrap();
+        state.committed.iter().filter(|committed| **committed).count() * page_size()
+    }
+
+    /// Get the size of the largest free range
+    pub fn largest_free_size(&self) -> usize {
+        self.state.lock().unwrap().free.values().copied().max().unwrap_or(0)
+    }
+
+    fn offset_of(&self, ptr: *const u8) -> Option<usize> {
+        self.contains(ptr).then(|| ptr as usize - self.base)
+    }
+
+    /// Get the page-aligned offset and length covering a range
+    fn page_range(&self, ptr: *mut u8, size: usize) -> Result<(usize, usize), MmapError> {
+        let offset = self.offset_of(ptr).ok_or(MmapError::OutOfRange)?;
+        let start = offset - offset % page_size();
+        let end = offset.checked_add(size).and_then(round_to_pages).ok_or(MmapError::OutOfRange)?;
+        if end > self.size() {
+            return Err(MmapError::OutOfRange);
+        }
+        Ok((start, end - start))
+    }
+
+    /// Make the read-only pages of a page-aligned range writable
+    fn release_protection(
+        &self,
+        state: &mut SuperCarrierState,
+        offset: usize,
+        len: usize,
+    ) -> Result<(), MmapError> {
+        let first = offset / page_size();
+        if state.protected[first..first + len / page_size()].contains(&true) {
+            set_access(self.base + offset, len, false)?;
+            set_pages(&mut state.protected, offset, len, false);
+        }
+        Ok(())
+    }
+
+    /// Zero the committed pages of a range and mark them decommitted
+    fn decommit(&self, state: &mut SuperCarrierState, offset: usize, len: usize) {
+        // Pages that cannot be made writable again are left as they are
+        if self.release_protection(state, offset, len).is_err() {
+            return;
+        }
+        let first = offset / page_size();
+        let pages = &mut state.committed[first..first + len / page_size()];
+        for (page, is_committed) in (first..).zip(pages.iter_mut()) {
+            if *is_committed {
+                // The page lies inside the reservation owned by self
+                unsafe {
+                    std::ptr::write_bytes(
+                        (self.base + page * page_size()) as *mut u8,
+                        0,
+                        page_size(),
+                    );
+                }
+                *is_committed = false;
+            }
+        }
+    }
+}
+
+impl Drop for SuperCarrier {
+    fn drop(&mut self) {
+        // The system allocator must be able to write to the range it gets back
+        let state = self.state.get_mut().unwrap();
+        if state.protected.contains(&true) && set_access(self.base, self.size(), false).is_err() {
+            return;
+        }
+        unsafe { std::alloc::dealloc(self.base as *mut u8, self.layout) };
+    }
+}
+
+impl std::fmt::Debug for SuperCarrier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SuperCarrier")
+            .field("base", &(self.base as *const u8))
+            .field("size", &self.size())
+            .finish()
+    }
+}
+
+/// Round a size up to whole pages
+fn round_to_pages(size: usize) -> Option<usize> {
+    size.checked_next_multiple_of(page_size())
+}
+
+/// Mark the pages of a page-aligned range
+fn set_pages(pages: &mut [bool], offset: usize, len: usize, value: bool) {
+    let first = offset / page_size();
+    pages[first..first + len / page_size()].fill(value);
+}
+
+/// Make a page-aligned range read-only or writable
+#[cfg(unix)]
+fn set_access(addr: usize, len: usize, read_only: bool) -> Result<(), MmapError> {
+    let prot = if read_only {
+        libc::PROT_READ
+    } else {
+        libc::PROT_READ | libc::PROT_WRITE
+    };
+    // The range lies inside a reservation owned by the caller
+    if unsafe { libc::mprotect(addr as *mut libc::c_void, len, prot) } == 0 {
+        Ok(())
+    } else {
+        Err(MmapError::ProtectionFailed)
+    }
+}
+
+/// Make a page-aligned range read-only or writable
+///
+/// Pages are only marked; the access is not enforced on this platform.
+#[cfg(not(unix))]
+fn set_access(_addr: usize, _len: usize, _read_only: bool) -> Result<(), MmapError> {
+    Ok(())
+}
+
+/// Whether [`SuperCarrier::protect`] makes pages read-only
+static MEMORY_PROTECTION: AtomicBool = AtomicBool::new(true);
+
+/// Enable or disable memory protection of code and literal areas
+///
+/// A debugging switch for tests that patch loaded code or literals in
+/// place. Disabling it does not unprotect pages that are read-only already.
+pub fn set_memory_protection(enabled: bool) {
+    MEMORY_PROTECTION.store(enabled, Ordering::Relaxed);
+}
+
+/// Check if memory protection is enabled
+pub fn memory_protection_enabled() -> bool {
+    MEMORY_PROTECTION.load(Ordering::Relaxed)
+}
+
+/// Global super carrier instance
+static GLOBAL_SUPER_CARRIER: OnceLock<Arc<SuperCarrier>> = OnceLock::new();
+
+/// Reserve the global super carrier (`+MMscs`)
+///
+/// # Errors
+/// * `MmapError::AlreadyReserved` - If the global super carrier exists
+/// * Errors of [`SuperCarrier::reserve`]
+pub fn reserve_global_super_carrier(size: usize) -> Result<Arc<SuperCarrier>, MmapError> {
+    if GLOBAL_SUPER_CARRIER.get().is_some() {
+        return Err(MmapError::AlreadyReserved);
+    }
+    let carrier = Arc::new(SuperCarrier::reserve(size)?);
+    GLOBAL_SUPER_CARRIER.set(Arc::clone(&carrier)).map_err(|_| MmapError::AlreadyReserved)?;
+    Ok(carrier)
+}
+
+/// Get the global super carrier, if one was reserved
+pub fn get_global_super_carrier() -> Option<Arc<SuperCarrier>> {
+    GLOBAL_SUPER_CARRIER.get().cloned()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -184,5 +624,75 @@ mod tests {
         // Cleanup
         let _ = fs::remove_file(&test_file);
     }
-}
 
+    #[test]
+    fn test_super_carrier_map_and_coalesce() {
+        let carrier = SuperCarrier::reserve(4 * page_size()).unwrap();
+        assert_eq!(carrier.size(), 4 * page_size());
+        let a = carrier.map(100).unwrap();
+        let b = carrier.map(page_size() + 1).unwrap();
+        assert_eq!(a, carrier.base());
+        assert!(carrier.contains(b));
+        assert_eq!(carrier.mapped_size(), 3 * page_size());
+        assert_eq!(carrier.map(2 * page_size()), Err(MmapError::Exhausted));
+
+        carrier.unmap(a).unwrap();
+        assert_eq!(carrier.unmap(a), Err(MmapError::NotMapped));
+        assert_eq!(carrier.largest_free_size(), page_size());
+        carrier.unmap(b).unwrap();
+        assert_eq!(carrier.largest_free_size(), carrier.size());
+        assert_eq!(carrier.mapped_size(), 0);
+    }
+
+    #[test]
+    fn test_super_carrier_commit() {
+        let carrier = SuperCarrier::reserve(2 * page_size()).unwrap();
+        let segment = carrier.map(2 * page_size()).unwrap();
+        assert_eq!(carrier.committed_size(), 2 * page_size());
+        unsafe { *segment.add(page_size()) = 7 };
+
+        let second = unsafe { segment.add(page_size()) };
+        carrier.uncommit(second, 1).unwrap();
+        assert!(!carrier.is_committed(second));
+        assert!(carrier.is_committed(segment));
+        assert_eq!(unsafe { *second }, 0);
+        carrier.commit(second, page_size()).unwrap();
+        assert_eq!(carrier.committed_size(), 2 * page_size());
+        assert_eq!(carrier.commit(second, 2 * page_size()), Err(MmapError::OutOfRange));
+        assert!(!carrier.contains(unsafe { segment.add(2 * page_size()) }));
+    }
+
+    #[test]
+    fn test_page_size_matches_os() {
+        assert!(page_size().is_power_of_two());
+        #[cfg(unix)]
+        assert_eq!(page_size() as libc::c_long, unsafe { libc::sysconf(libc::_SC_PAGESIZE) });
+    }
+
+    #[test]
+    fn test_super_carrier_protect() {
+        let carrier = SuperCarrier::reserve(4 * page_size()).unwrap();
+        let segment = carrier.map(2 * page_size()).unwrap();
+        unsafe { *segment = 7 };
+        carrier.protect(segment, 2 * page_size()).unwrap();
+        assert!(carrier.is_protected(segment));
+        assert_eq!(unsafe { *segment }, 7);
+
+        carrier.unprotect(segment, page_size()).unwrap();
+        assert!(!carrier.is_protected(segment));
+        unsafe { *segment = 8 };
+        // Unmapping makes the pages still protected writable to zero them
+        let second = unsafe { segment.add(page_size()) };
+        assert!(carrier.is_protected(second));
+        carrier.unmap(segment).unwrap();
+        assert!(!carrier.is_protected(second));
+
+        set_memory_protection(false);
+        let segment = carrier.map(page_size()).unwrap();
+        carrier.protect(segment, page_size()).unwrap();
+        set_memory_protection(true);
+        assert!(!carrier.is_protected(segment));
+        unsafe { *segment = 9 };
+        assert_eq!(carrier.protect(segment, 8 * page_size()), Err(MmapError::OutOfRange));
+    }
+}
@@ -35,6 +35,10 @@
 //!   arbitrary base conversions. This is essential for displaying big numbers
 //!   to users and for serialization purposes.
 //!
+//! - **Heavy-Integer Generators**: `factorial`, `binomial`, and `fibonacci`
+//!   produce canonical large integers using binary splitting and fast
+//!   doubling. They serve as workloads for the bignum arithmetic paths.
+//!
 //! # Implementation Details
 //!
 //! This module uses the `malachite` crate for high-performance arbitrary-precision
@@ -116,8 +120,6 @@
  */
 
 use malachite::Integer;
-use malachite::base::num::conversion::traits::RoundingFrom;
-use malachite::base::rounding_modes::RoundingMode;
 
 /// Big number representation using malachite's Integer.
 ///
@@ -553,8 +555,8 @@ impl BigNumber {
     ///
     /// This function enables conversion from big integers to floating-point numbers,
     /// which is essential for Erlang's type system where numbers can be represented
-    /// as either floats or integers. The conversion uses malachite's `RoundingFrom`
-    /// trait with `Exact` rounding mode to ensure precision when possible.
+    /// as either floats or integers. Values a float cannot hold exactly are
+    /// rounded to the nearest float, ties to even, as in `erlang:float/1`.
     ///
     /// # Returns
     ///
@@ -595,16 +597,7 @@ impl BigNumber {
     /// Returns -1 (error) if
     /// the result is not finite.
     pub fn to_f64(&self) -> Option<f64> {
-        // Use malachite's RoundingFrom trait for efficient conversion
-        // This is more efficient than string conversion and handles overflow correctly
-        let (result, _ordering) = f64::rounding_from(&self.value, RoundingMode::Exact);
-        
-        // Check if result is finite (returns -1 if not finite)
-        if result.is_finite() {
-            Some(result)
-        } else {
-            None  // Overflow case - value too large for f64
-        }
+        crate::BigRational::from_rational(malachite::Rational::from(&self.value)).to_f64_checked()
     }
 
     /// Convert a big number to a 32-bit unsigned integer.
@@ -1861,6 +1854,44 @@ impl BigNumber {
         }
     }
 
+    /// Parse a number written in a given base (2-36).
+    //
+    /// The inverse of [`to_string_base`](Self::to_string_base): an optional
+    /// `+` or `-` sign followed by at least one digit. Letters are digits
+    /// 10-35 in either case. No whitespace is accepted.
+    //
+    /// # Returns
+    //
+    /// The number, or `None` if `base` is out of range or `text` is not a
+    /// number in `base`
+    //
+    /// # Examples
+    //
+    /// ```rust
+    /// use entities_utilities::BigNumber;
+    //
+    /// let num = BigNumber::from_string_base("-Ff", 16).unwrap();
+    /// assert_eq!(num.to_i64(), Some(-255));
+    /// assert!(BigNumber::from_string_base("12", 2).is_none());
+    /// ```
+    pub fn from_string_base(text: &str, base: u32) -> Option<Self> {
+        use malachite::base::num::conversion::traits::FromStringBase;
+
+        if !(2..=36).contains(&base) {
+            return None;
+        }
+        let (negative, digits) = match text.as_bytes().first() {
+            Some(b'-') => (true, &text[1..]),
+            Some(b'+') => (false, &text[1..]),
+            _ => (false, text),
+        };
+        if digits.is_empty() || !digits.chars().all(|c| c.is_digit(base)) {
+            return None;
+        }
+        let magnitude = malachite::Natural::from_string_base(base as u8, digits)?;
+        Some(Self { value: Integer::from_sign_and_abs(!negative, magnitude) })
+    }
+
     /// Get a reference to the internal `Integer` value.
     //
     /// This function provides access to the underlying `malachite::Integer`
@@ -1978,6 +2009,136 @@ impl BigNumber {
     pub fn from_integer(value: Integer) -> Self {
         Self { value }
     }
+
+    /// Compute the factorial `n!`.
+    ///
+    /// The product `1 * 2 * ... * n` is evaluated by binary splitting: the
+    /// range is halved recursively and the two partial products are
+    /// multiplied, so the large multiplications happen between operands of
+    /// similar size where malachite's fast multiplication pays off.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number whose factorial to compute
+    ///
+    /// # Returns
+    ///
+    /// A new `BigNumber` containing `n!` (with `0! = 1`).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use entities_utilities::BigNumber;
+    ///
+    /// assert_eq!(BigNumber::factorial(0).to_i64(), Some(1));
+    /// assert_eq!(BigNumber::factorial(20).to_i64(), Some(2432902008176640000));
+    /// assert_eq!(BigNumber::factorial(25).to_string_base(10), "15511210043330985984000000");
+    /// ```
+    pub fn factorial(n: u64) -> Self {
+        Self {
+            value: Self::range_product(1, n),
+        }
+    }
+
+    /// Compute the binomial coefficient `C(n, k)`.
+    ///
+    /// Uses the symmetry `C(n, k) = C(n, n - k)` to keep `k` small, then
+    /// divides the falling product `(n - k + 1) * ... * n` by `k!`. Both
+    /// products are computed by binary splitting and the division is exact.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - Size of the set
+    /// * `k` - Number of elements chosen
+    ///
+    /// # Returns
+    ///
+    /// A new `BigNumber` containing `C(n, k)`, or zero when `k > n`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use entities_utilities::BigNumber;
+    ///
+    /// assert_eq!(BigNumber::binomial(5, 2).to_i64(), Some(10));
+    /// assert_eq!(BigNumber::binomial(3, 5).to_i64(), Some(0));
+    /// assert_eq!(BigNumber::binomial(100, 50).to_string_base(10),
+    ///            "100891344545564193334812497256");
+    /// ```
+    pub fn binomial(n: u64, k: u64) -> Self {
+        if k > n {
+            return Self::from_i64(0);
+        }
+        let k = k.min(n - k);
+        let numerator = Self::range_product(n - k + 1, n);
+        let denominator = Self::range_product(1, k);
+        Self {
+            value: numerator / denominator,
+        }
+    }
+
+    /// Compute the Fibonacci number `F(n)`.
+    ///
+    /// Uses the fast doubling identities
+    /// `F(2m) = F(m) * (2F(m+1) - F(m))` and `F(2m+1) = F(m)^2 + F(m+1)^2`,
+    /// walking the bits of `n` from the most significant end. This needs
+    /// `O(log n)` big multiplications instead of `n` additions.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - Index of the Fibonacci number (`F(0) = 0`, `F(1) = 1`)
+    ///
+    /// # Returns
+    ///
+    /// A new `BigNumber` containing `F(n)`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use entities_utilities::BigNumber;
+    ///
+    /// assert_eq!(BigNumber::fibonacci(0).to_i64(), Some(0));
+    /// assert_eq!(BigNumber::fibonacci(10).to_i64(), Some(55));
+    /// assert_eq!(BigNumber::fibonacci(100).to_string_base(10), "354224848179261915075");
+    /// ```
+    pub fn fibonacci(n: u64) -> Self {
+        // (a, b) = (F(m), F(m + 1)), starting from m = 0
+        let mut a = Integer::from(0);
+        let mut b = Integer::from(1);
+        for bit in (0..u64::BITS - n.leading_zeros()).rev() {
+            let two_b = &b << 1u32;
+            let c = &a * (two_b - &a);
+            let d = &a * &a + &b * &b;
+            if (n >> bit) & 1 == 0 {
+                a = c;
+                b = d;
+            } else {
+                b = &c + &d;
+                a = d;
+            }
+        }
+        Self { value: a }
+    }
+
+    /// Multiply all integers in `lo..=hi` by binary splitting.
+    ///
+    /// Returns 1 for an empty range.
+    fn range_product(lo: u64, hi: u64) -> Integer {
+        if lo > hi {
+            return Integer::from(1);
+        }
+        // Small ranges are multiplied directly; the partial products still
+        // fit comfortably in a few limbs.
+        if hi - lo < 16 {
+            let mut product = Integer::from(lo);
+            for i in lo + 1..=hi {
+                product *= Integer::from(i);
+            }
+            return product;
+        }
+        let mid = lo + (hi - lo) / 2;
+        Self::range_product(lo, mid) * Self::range_product(mid + 1, hi)
+    }
 }
 
 impl From<i64> for BigNumber {
@@ -2219,6 +2380,12 @@ mod tests {
         assert!(large_f64.is_some());
         // Verify it's approximately correct (within f64 precision)
         assert!((large_f64.unwrap() - 1e20).abs() < 1e10); // Allow some precision loss
+
+        // Integers between floats round to the nearest one instead of failing
+        let between = BigNumber::from_u64(u64::MAX);
+        assert_eq!(between.to_f64(), Some(18446744073709551616.0));
+        let too_large = BigNumber::from_f64(f64::MAX).unwrap().lshift(1);
+        assert_eq!(too_large.to_f64(), None);
     }
 
     #[test]
@@ -2279,6 +2446,23 @@ mod tests {
         let _ = big.to_string_base(37);
     }
 
+    #[test]
+    fn test_string_base_round_trip() {
+        let big = BigNumber::from_string_base("123456789012345678901234567890", 10).unwrap();
+        for base in 2..=36 {
+            let text = big.to_string_base(base);
+            assert_eq!(BigNumber::from_string_base(&text, base), Some(big.clone()));
+            assert_eq!(BigNumber::from_string_base(&text.to_uppercase(), base), Some(big.clone()));
+        }
+        assert_eq!(BigNumber::from_string_base("+7", 8), Some(BigNumber::from_i64(7)));
+        assert_eq!(BigNumber::from_string_base("-0", 2), Some(BigNumber::from_i64(0)));
+        for text in ["", "-", "+", "8", " 1", "1 ", "--1", "+-1", "1_0"] {
+            assert_eq!(BigNumber::from_string_base(text, 8), None, "{:?}", text);
+        }
+        assert_eq!(BigNumber::from_string_base("1", 1), None);
+        assert_eq!(BigNumber::from_string_base("1", 37), None);
+    }
+
     #[test]
     fn test_large_numbers() {
         // Test with numbers larger than i64
@@ -2347,4 +2531,64 @@ mod tests {
         map.insert(a.clone(), "test");
         assert_eq!(map.get(&b), Some(&"test"));
     }
+
+    #[test]
+    fn test_factorial() {
+        assert_eq!(BigNumber::factorial(0).to_i64(), Some(1));
+        assert_eq!(BigNumber::factorial(1).to_i64(), Some(1));
+        assert_eq!(BigNumber::factorial(5).to_i64(), Some(120));
+        assert_eq!(BigNumber::factorial(20).to_i64(), Some(2432902008176640000));
+        assert!(BigNumber::factorial(21).to_i64().is_none());
+
+        // Compare the binary splitting result with a naive running product
+        let mut naive = BigNumber::from_i64(1);
+        for n in 1..=300u64 {
+            naive = naive.times(&BigNumber::from_u64(n));
+            assert_eq!(BigNumber::factorial(n), naive, "{}!", n);
+        }
+
+        // 1000! has 2568 decimal digits
+        assert_eq!(BigNumber::factorial(1000).to_string_base(10).len(), 2568);
+    }
+
+    #[test]
+    fn test_binomial() {
+        assert_eq!(BigNumber::binomial(0, 0).to_i64(), Some(1));
+        assert_eq!(BigNumber::binomial(10, 0).to_i64(), Some(1));
+        assert_eq!(BigNumber::binomial(10, 10).to_i64(), Some(1));
+        assert_eq!(BigNumber::binomial(10, 3).to_i64(), Some(120));
+        assert_eq!(BigNumber::binomial(4, 7).to_i64(), Some(0));
+
+        // Pascal's rule: C(n, k) = C(n-1, k-1) + C(n-1, k)
+        for n in 1..80u64 {
+            for k in 1..n {
+                let expected = BigNumber::binomial(n - 1, k - 1).plus(&BigNumber::binomial(n - 1, k));
+                assert_eq!(BigNumber::binomial(n, k), expected, "C({}, {})", n, k);
+            }
+        }
+
+        // Agrees with the factorial definition
+        let n = 200;
+        let k = 73;
+        let expected = BigNumber::factorial(n)
+            .div(&BigNumber::factorial(k).times(&BigNumber::factorial(n - k)))
+            .unwrap();
+        assert_eq!(BigNumber::binomial(n, k), expected);
+    }
+
+    #[test]
+    fn test_fibonacci() {
+        let mut a = BigNumber::from_i64(0);
+        let mut b = BigNumber::from_i64(1);
+        for n in 0..500u64 {
+            assert_eq!(BigNumber::fibonacci(n), a, "F({})", n);
+            let next = a.plus(&b);
+            a = b;
+            b = next;
+        }
+
+        assert_eq!(BigNumber::fibonacci(93).to_string_base(10), "12200160415121876738");
+        // F(1000) has 209 decimal digits
+        assert_eq!(BigNumber::fibonacci(1000).to_string_base(10).len(), 209);
+    }
 }
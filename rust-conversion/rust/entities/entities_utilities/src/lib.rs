@@ -21,7 +21,8 @@
 //!   `register/2` and `whereis/1` functionality, allowing processes to be found
 //!   by name. The register table maintains bidirectional mappings between names
 //!   and IDs, ensuring that each name maps to exactly one ID and each ID maps to
-//!   at most one name.
+//!   at most one name. Groups of register/unregister operations can be applied
+//!   atomically as a transaction.
 //!
 //! # Architecture
 //!
@@ -115,4 +116,6 @@ pub mod register;
 
 pub use big::BigNumber;
 pub use rational::BigRational;
-pub use register::{Register, RegisterResult};
+pub use register::{
+    Register, RegisterOp, RegisterResult, RegisterTransaction, RegisterTransactionError,
+};
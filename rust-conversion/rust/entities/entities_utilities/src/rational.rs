@@ -24,7 +24,12 @@
 //!   that respect mathematical ordering.
 //!
 //! - **String Conversion**: Convert rational numbers to string representations in
-//!   various formats (decimal, fraction, scientific notation).
+//!   various formats (decimal, fraction, scientific notation), and parse decimal
+//!   strings exactly.
+//!
+//! - **Approximation**: Continued fractions and the best rational approximation
+//!   with a bounded denominator, as used to reduce conversion ratios such as
+//!   those between time units.
 //!
 //! # Implementation Details
 //!
@@ -90,11 +95,19 @@
  * %CopyrightEnd%
  */
 
-use malachite::Rational;
+use std::str::FromStr;
+
+use malachite::{Integer, Natural, Rational};
 use malachite::base::num::conversion::traits::{IsInteger, RoundingFrom};
-use malachite::base::num::arithmetic::traits::Abs;
+use malachite::base::num::arithmetic::traits::{Abs, Pow, PowerOf2, UnsignedAbs};
 use malachite::base::num::basic::traits::Zero;
 use malachite::base::rounding_modes::RoundingMode;
+use malachite::rational::arithmetic::traits::Approximate;
+use malachite::rational::conversion::traits::ContinuedFraction;
+
+/// Largest power of ten accepted by [`BigRational::from_decimal_str`], which
+/// keeps a short string such as `"1e999999999"` from building a huge number
+const MAX_DECIMAL_EXPONENT: u64 = 100_000;
 
 /// Big rational number representation using malachite's Rational.
 ///
@@ -354,6 +367,35 @@ impl BigRational {
         result
     }
 
+    /// Convert a rational number to the nearest 64-bit floating-point number.
+    ///
+    /// Unlike [`BigRational::to_f64`], which saturates at the largest finite
+    /// float, values that round beyond the finite range are refused. Ties are
+    /// rounded to the float with an even mantissa, as IEEE 754 does.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(f64)` - The correctly rounded float
+    /// * `None` - The value rounds to an infinity
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use entities_utilities::BigRational;
+    ///
+    /// let r = BigRational::from_fraction(1, 3).unwrap();
+    /// assert_eq!(r.to_f64_checked(), Some(1.0 / 3.0));
+    /// ```
+    pub fn to_f64_checked(&self) -> Option<f64> {
+        // Halfway between the largest finite float and 2^1024, which ties to
+        // the even 2^1024 and so overflows
+        let overflow = Rational::try_from(f64::MAX).ok()? + Rational::power_of_2(970u64);
+        if (&self.value).abs() >= overflow {
+            return None;
+        }
+        Some(self.to_f64())
+    }
+
     /// Convert a rational number to a 64-bit signed integer, if possible.
     ///
     /// This conversion only succeeds if the rational number represents an exact
@@ -443,6 +485,175 @@ impl BigRational {
         Self { value }
     }
 
+    /// Parse a decimal string exactly.
+    ///
+    /// Accepts an optional sign, digits, an optional fraction and an optional
+    /// exponent, as in `"-12.5"`, `"3"` or `"1.25e-3"`. The value is exact, so
+    /// `"0.1"` is one tenth rather than the float nearest to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The decimal string
+    ///
+    /// # Returns
+    ///
+    /// * `Some(BigRational)` - The parsed value
+    /// * `None` - The string is not a decimal number, or its power of ten is
+    ///   beyond 10^100000
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use entities_utilities::BigRational;
+    ///
+    /// let r = BigRational::from_decimal_str("-1.25e-1").unwrap();
+    /// assert_eq!(r.to_string(), "-1/8");
+    /// ```
+    pub fn from_decimal_str(text: &str) -> Option<Self> {
+        let (mantissa, exponent) = match text.find(['e', 'E']) {
+            Some(pos) => (&text[..pos], parse_exponent(&text[pos + 1..])?),
+            None => (text, 0),
+        };
+        let (negative, mantissa) = match mantissa.as_bytes().first() {
+            Some(b'-') => (true, &mantissa[1..]),
+            Some(b'+') => (false, &mantissa[1..]),
+            _ => (false, mantissa),
+        };
+        let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let is_digits = |digits: &str| digits.bytes().all(|c| c.is_ascii_digit());
+        if whole.is_empty() || !is_digits(whole) || !is_digits(fraction) || mantissa.ends_with('.') {
+            return None;
+        }
+        let digits = Natural::from_str(&format!("{}{}", whole, fraction)).ok()?;
+        let exponent = exponent - i64::try_from(fraction.len()).ok()?;
+        if exponent.unsigned_abs() > MAX_DECIMAL_EXPONENT {
+            return None;
+        }
+        let scale = Natural::from(10u32).pow(exponent.unsigned_abs());
+        let (numerator, denominator) = if exponent >= 0 {
+            (digits * scale, Natural::from(1u32))
+        } else {
+            (digits, scale)
+        };
+        Some(Self {
+            value: Rational::from_sign_and_naturals(!negative, numerator, denominator),
+        })
+    }
+
+    /// Format the rational number as a decimal string.
+    ///
+    /// The value is rounded to `precision` decimals, ties to the even last
+    /// digit. A precision of zero writes an integer without a decimal point.
+    ///
+    /// # Arguments
+    ///
+    /// * `precision` - Number of decimals to write
+    ///
+    /// # Returns
+    ///
+    /// The decimal string.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use entities_utilities::BigRational;
+    ///
+    /// let r = BigRational::from_fraction(-2, 3).unwrap();
+    /// assert_eq!(r.to_decimal_string(4), "-0.6667");
+    /// ```
+    pub fn to_decimal_string(&self, precision: usize) -> String {
+        let scale = Natural::from(10u32).pow(precision as u64);
+        let scaled = &self.value * Rational::from(scale);
+        let (rounded, _) = Integer::rounding_from(scaled, RoundingMode::Nearest);
+        let sign = if rounded < 0 { "-" } else { "" };
+        let digits = format!("{:0>width$}", rounded.unsigned_abs().to_string(), width = precision + 1);
+        if precision == 0 {
+            return format!("{}{}", sign, digits);
+        }
+        let (whole, fraction) = digits.split_at(digits.len() - precision);
+        format!("{}{}.{}", sign, whole, fraction)
+    }
+
+    /// Expand the rational number into a continued fraction.
+    ///
+    /// The first term is the floor of the value and may be negative; the
+    /// others are positive, and the last of them is greater than one.
+    ///
+    /// # Returns
+    ///
+    /// The terms `[a0; a1, a2, ...]`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use entities_utilities::BigRational;
+    ///
+    /// let r = BigRational::from_fraction(355, 113).unwrap();
+    /// let terms: Vec<String> = r.to_continued_fraction().iter().map(|t| t.to_string()).collect();
+    /// assert_eq!(terms, ["3", "7", "16"]);
+    /// ```
+    pub fn to_continued_fraction(&self) -> Vec<Integer> {
+        let (floor, rest) = self.value.clone().continued_fraction();
+        std::iter::once(floor).chain(rest.map(Integer::from)).collect()
+    }
+
+    /// Create a rational number from a continued fraction.
+    ///
+    /// Both expansions of a rational number are accepted, the canonical one
+    /// and the one ending in 1.
+    ///
+    /// # Arguments
+    ///
+    /// * `terms` - The terms `[a0; a1, a2, ...]`
+    ///
+    /// # Returns
+    ///
+    /// * `Some(BigRational)` - The value of the continued fraction
+    /// * `None` - There are no terms, or a term after the first is not positive
+    pub fn from_continued_fraction(terms: &[Integer]) -> Option<Self> {
+        let (floor, rest) = terms.split_first()?;
+        let rest = rest
+            .iter()
+            .map(|term| (*term > 0).then(|| term.unsigned_abs()))
+            .collect::<Option<Vec<Natural>>>()?;
+        Some(Self {
+            value: Rational::from_continued_fraction(floor.clone(), rest.into_iter()),
+        })
+    }
+
+    /// Find the closest rational number with a bounded denominator.
+    ///
+    /// The approximation is found from the convergents and semiconvergents of
+    /// the continued fraction. Of two equally close candidates the one with
+    /// the smaller denominator is chosen.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_denominator` - Largest denominator allowed (must not be zero)
+    ///
+    /// # Returns
+    ///
+    /// * `Some(BigRational)` - The best approximation; the value itself if its
+    ///   denominator is small enough
+    /// * `None` - `max_denominator` is zero
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use entities_utilities::BigRational;
+    ///
+    /// let pi = BigRational::from_f64(std::f64::consts::PI).unwrap();
+    /// assert_eq!(pi.best_approximation(1000).unwrap().to_string(), "355/113");
+    /// ```
+    pub fn best_approximation(&self, max_denominator: u64) -> Option<Self> {
+        if max_denominator == 0 {
+            return None;
+        }
+        Some(Self {
+            value: (&self.value).approximate(&Natural::from(max_denominator)),
+        })
+    }
+
     /// Get the numerator of the rational number.
     ///
     /// Returns the numerator as an Integer (converted from Natural).
@@ -468,6 +679,16 @@ impl BigRational {
     }
 }
 
+/// Parse the exponent of a decimal string: an optional sign and digits
+fn parse_exponent(text: &str) -> Option<i64> {
+    let digits = text.strip_prefix(['+', '-']).unwrap_or(text);
+    if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    // Exponents too large for i64 are far beyond the accepted range anyway
+    text.parse::<i64>().ok().filter(|exponent| exponent.unsigned_abs() <= MAX_DECIMAL_EXPONENT)
+}
+
 impl std::fmt::Display for BigRational {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.value)
@@ -739,5 +960,73 @@ mod tests {
         let r3 = BigRational::from_i64(0);
         assert_eq!(format!("{}", r3), "0");
     }
-}
 
+    #[test]
+    fn test_decimal_string_round_trip() {
+        let r = BigRational::from_decimal_str("0.1").unwrap();
+        assert_eq!(r, BigRational::from_fraction(1, 10).unwrap());
+        assert_eq!(BigRational::from_decimal_str("+42").unwrap().to_i64(), Some(42));
+        assert_eq!(BigRational::from_decimal_str("1.5E3").unwrap().to_i64(), Some(1500));
+        assert_eq!(BigRational::from_decimal_str("-2.50e-2").unwrap().to_string(), "-1/40");
+        for text in ["", "-", ".5", "1.", "1.2.3", "1e", "1e+", "0x10", " 1", "1e100001", "1e99999999999999999999"] {
+            assert!(BigRational::from_decimal_str(text).is_none(), "{:?}", text);
+        }
+
+        let third = BigRational::from_fraction(1, 3).unwrap();
+        assert_eq!(third.to_decimal_string(5), "0.33333");
+        assert_eq!(third.neg().to_decimal_string(0), "0");
+        assert_eq!(BigRational::from_fraction(5, 2).unwrap().to_decimal_string(0), "2");
+        assert_eq!(BigRational::from_fraction(7, 2).unwrap().to_decimal_string(0), "4");
+        assert_eq!(BigRational::from_fraction(-1, 8).unwrap().to_decimal_string(3), "-0.125");
+        assert_eq!(BigRational::from_fraction(-1, 1000).unwrap().to_decimal_string(2), "0.00");
+        assert_eq!(BigRational::from_i64(-12).to_decimal_string(2), "-12.00");
+
+        let text = "-123456789012345678901234567890.000000000000000000000000000001";
+        assert_eq!(BigRational::from_decimal_str(text).unwrap().to_decimal_string(30), text);
+    }
+
+    #[test]
+    fn test_to_f64_checked() {
+        assert_eq!(BigRational::from_decimal_str("0.1").unwrap().to_f64_checked(), Some(0.1));
+        // 2^53 + 1 is halfway between two floats and rounds to the even one
+        let halfway = BigRational::from_u64((1 << 53) + 1);
+        assert_eq!(halfway.to_f64_checked(), Some(9007199254740992.0));
+        let max = BigRational::from_f64(f64::MAX).unwrap();
+        assert_eq!(max.to_f64_checked(), Some(f64::MAX));
+        assert_eq!(max.neg().to_f64_checked(), Some(-f64::MAX));
+        assert_eq!(BigRational::from_decimal_str("1e309").unwrap().to_f64_checked(), None);
+        assert_eq!(BigRational::from_decimal_str("-1e309").unwrap().to_f64_checked(), None);
+        assert_eq!(BigRational::from_decimal_str("1e-400").unwrap().to_f64_checked(), Some(0.0));
+        assert_eq!(BigRational::from_decimal_str("4.9406564584124654e-324").unwrap().to_f64_checked(), Some(5.0e-324));
+    }
+
+    #[test]
+    fn test_continued_fraction() {
+        let terms = |r: &BigRational| r.to_continued_fraction().iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        let r = BigRational::from_fraction(-7, 3).unwrap();
+        assert_eq!(terms(&r), ["-3", "1", "2"]);
+        assert_eq!(BigRational::from_continued_fraction(&r.to_continued_fraction()), Some(r));
+        assert_eq!(terms(&BigRational::from_i64(5)), ["5"]);
+
+        // The expansion ending in 1 has the same value
+        let ones = [Integer::from(0), Integer::from(1), Integer::from(1)];
+        assert_eq!(BigRational::from_continued_fraction(&ones), BigRational::from_fraction(1, 2));
+        assert_eq!(BigRational::from_continued_fraction(&[]), None);
+        assert_eq!(BigRational::from_continued_fraction(&[Integer::from(1), Integer::from(0)]), None);
+    }
+
+    #[test]
+    fn test_best_approximation() {
+        let pi = BigRational::from_f64(std::f64::consts::PI).unwrap();
+        assert_eq!(pi.best_approximation(7).unwrap().to_string(), "22/7");
+        assert_eq!(pi.best_approximation(1).unwrap().to_string(), "3");
+        assert_eq!(pi.neg().best_approximation(120).unwrap().to_string(), "-355/113");
+        // A semiconvergent beats the previous convergent: 0.3 is closer to 2/7 than to 1/3
+        let r = BigRational::from_decimal_str("0.3").unwrap();
+        assert_eq!(r.best_approximation(7).unwrap().to_string(), "2/7");
+        // The ratio of two time units needs no approximation when it fits
+        let ratio = BigRational::from_fraction(1_000, 1_000_000_000).unwrap();
+        assert_eq!(ratio.best_approximation(1_000_000), Some(ratio.clone()));
+        assert_eq!(ratio.best_approximation(0), None);
+    }
+}
@@ -225,7 +225,7 @@ pub struct Register {
 /// let result = reg.register_name("", 300);
 /// assert_eq!(result, RegisterResult::InvalidName);
 /// ```
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RegisterResult {
     /// Successfully registered
     Success,
@@ -239,6 +239,128 @@ pub enum RegisterResult {
     NotAlive,
 }
 
+/// A single operation in a [`RegisterTransaction`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegisterOp {
+    /// Register `name` with `id`
+    Register {
+        /// Name to register
+        name: String,
+        /// Process/port ID to associate with the name
+        id: u64,
+    },
+    /// Remove the registration of a name
+    UnregisterName(String),
+    /// Remove the registration of a process/port ID
+    UnregisterId(u64),
+}
+
+/// A group of register operations applied atomically.
+///
+/// Operations are applied in order by [`Register::apply_transaction`]: either
+/// all of them take effect, or none do. Because later operations see the
+/// effect of earlier ones, a group of processes can be renamed or swapped
+/// without any observer seeing an intermediate state.
+///
+/// # Examples
+///
+/// ```rust
+/// use entities_utilities::{Register, RegisterTransaction};
+///
+/// let mut reg = Register::new();
+/// reg.register_name("old_worker", 100);
+///
+/// // Rename old_worker to new_worker in one step
+/// let mut tx = RegisterTransaction::new();
+/// tx.unregister_name("old_worker").register("new_worker", 100);
+/// reg.apply_transaction(&tx).unwrap();
+///
+/// assert_eq!(reg.whereis_name("old_worker"), None);
+/// assert_eq!(reg.whereis_name("new_worker"), Some(100));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegisterTransaction {
+    ops: Vec<RegisterOp>,
+}
+
+impl RegisterTransaction {
+    /// Create an empty transaction.
+    pub fn new() -> Self {
+        Self { ops: Vec::new() }
+    }
+
+    /// Add a registration of `name` with `id`.
+    pub fn register(&mut self, name: &str, id: u64) -> &mut Self {
+        self.ops.push(RegisterOp::Register {
+            name: name.to_string(),
+            id,
+        });
+        self
+    }
+
+    /// Add the removal of a registered name.
+    pub fn unregister_name(&mut self, name: &str) -> &mut Self {
+        self.ops.push(RegisterOp::UnregisterName(name.to_string()));
+        self
+    }
+
+    /// Add the removal of a registered process/port ID.
+    pub fn unregister_id(&mut self, id: u64) -> &mut Self {
+        self.ops.push(RegisterOp::UnregisterId(id));
+        self
+    }
+
+    /// Operations in the order they will be applied.
+    pub fn ops(&self) -> &[RegisterOp] {
+        &self.ops
+    }
+
+    /// Number of operations in the transaction.
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Check if the transaction has no operations.
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+}
+
+/// Reason a [`RegisterTransaction`] was rejected.
+///
+/// `index` is the position of the first failing operation. When a
+/// transaction is rejected the register table is left unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegisterTransactionError {
+    /// A registration failed with the given result
+    Register {
+        /// Position of the failing operation
+        index: usize,
+        /// Why the registration failed
+        result: RegisterResult,
+    },
+    /// An unregistration named a name or ID that is not registered
+    NotRegistered {
+        /// Position of the failing operation
+        index: usize,
+    },
+}
+
+impl std::fmt::Display for RegisterTransactionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegisterTransactionError::Register { index, result } => {
+                write!(f, "Operation {} failed to register: {:?}", index, result)
+            }
+            RegisterTransactionError::NotRegistered { index } => {
+                write!(f, "Operation {} failed to unregister: not registered", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RegisterTransactionError {}
+
 impl Register {
     /// Create a new empty register table.
     //
@@ -1028,6 +1150,70 @@ impl Register {
     pub fn get_all_ids(&self) -> Vec<u64> {
         self.table.values().copied().collect()
     }
+
+    /// Apply a group of register operations atomically.
+    ///
+    /// The operations are first applied in order to a staged copy of the
+    /// table. If every operation succeeds the staged table replaces the
+    /// current one; otherwise the table is left untouched and the first
+    /// failure is returned. Unregistering a name or ID that is not
+    /// registered (at that point in the transaction) is a failure, matching
+    /// `unregister/1` raising `badarg`.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The operations to apply
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if all operations were applied
+    /// * `Err(RegisterTransactionError)` describing the first failing operation
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use entities_utilities::{Register, RegisterResult, RegisterTransaction, RegisterTransactionError};
+    ///
+    /// let mut reg = Register::new();
+    /// reg.register_name("a", 1);
+    /// reg.register_name("b", 2);
+    ///
+    /// // Second operation fails, so the first one is not applied either
+    /// let mut tx = RegisterTransaction::new();
+    /// tx.unregister_name("a").register("b", 1);
+    /// assert_eq!(
+    ///     reg.apply_transaction(&tx),
+    ///     Err(RegisterTransactionError::Register { index: 1, result: RegisterResult::AlreadyRegistered })
+    /// );
+    /// assert_eq!(reg.whereis_name("a"), Some(1));
+    /// ```
+    pub fn apply_transaction(&mut self, tx: &RegisterTransaction) -> Result<(), RegisterTransactionError> {
+        let mut staged = Register {
+            table: self.table.clone(),
+        };
+        for (index, op) in tx.ops().iter().enumerate() {
+            match op {
+                RegisterOp::Register { name, id } => {
+                    let result = staged.register_name(name, *id);
+                    if result != RegisterResult::Success {
+                        return Err(RegisterTransactionError::Register { index, result });
+                    }
+                }
+                RegisterOp::UnregisterName(name) => {
+                    if !staged.unregister_name(name) {
+                        return Err(RegisterTransactionError::NotRegistered { index });
+                    }
+                }
+                RegisterOp::UnregisterId(id) => {
+                    if staged.unregister_id(*id).is_none() {
+                        return Err(RegisterTransactionError::NotRegistered { index });
+                    }
+                }
+            }
+        }
+        self.table = staged.table;
+        Ok(())
+    }
 }
 
 impl Default for Register {
@@ -1225,4 +1411,77 @@ mod tests {
         let reg = Register::default();
         assert!(reg.is_empty());
     }
+
+    #[test]
+    fn test_transaction_swap_names() {
+        let mut reg = Register::new();
+        reg.register_name("primary", 1);
+        reg.register_name("backup", 2);
+
+        let mut tx = RegisterTransaction::new();
+        tx.unregister_id(1)
+            .unregister_id(2)
+            .register("primary", 2)
+            .register("backup", 1);
+        assert_eq!(tx.len(), 4);
+        assert_eq!(reg.apply_transaction(&tx), Ok(()));
+        assert_eq!(reg.whereis_name("primary"), Some(2));
+        assert_eq!(reg.whereis_name("backup"), Some(1));
+        assert_eq!(reg.size(), 2);
+    }
+
+    #[test]
+    fn test_transaction_rolls_back_on_failure() {
+        let mut reg = Register::new();
+        reg.register_name("a", 1);
+
+        let mut tx = RegisterTransaction::new();
+        tx.register("b", 2).register("c", 3).register("", 4);
+        assert_eq!(
+            reg.apply_transaction(&tx),
+            Err(RegisterTransactionError::Register {
+                index: 2,
+                result: RegisterResult::InvalidName
+            })
+        );
+        assert_eq!(reg.size(), 1);
+        assert!(!reg.is_registered("b"));
+    }
+
+    #[test]
+    fn test_transaction_unregister_missing() {
+        let mut reg = Register::new();
+        reg.register_name("a", 1);
+
+        let mut tx = RegisterTransaction::new();
+        tx.unregister_name("a").unregister_name("a");
+        assert_eq!(
+            reg.apply_transaction(&tx),
+            Err(RegisterTransactionError::NotRegistered { index: 1 })
+        );
+        assert_eq!(reg.whereis_name("a"), Some(1));
+
+        let mut tx = RegisterTransaction::new();
+        tx.unregister_id(42);
+        assert_eq!(
+            reg.apply_transaction(&tx),
+            Err(RegisterTransactionError::NotRegistered { index: 0 })
+        );
+    }
+
+    #[test]
+    fn test_transaction_empty() {
+        let mut reg = Register::new();
+        reg.register_name("a", 1);
+        let tx = RegisterTransaction::new();
+        assert!(tx.is_empty());
+        assert_eq!(reg.apply_transaction(&tx), Ok(()));
+        assert_eq!(reg.size(), 1);
+    }
+
+    #[test]
+    fn test_transaction_error_display() {
+        let err = RegisterTransactionError::NotRegistered { index: 3 };
+        assert!(err.to_string().contains("Operation 3"));
+    }
 }
@@ -287,3 +287,34 @@ fn test_big_number_comparison_operations() {
     assert!(neg_b.comp(&neg_a) > 0);
 }
 
+
+#[test]
+fn test_register_transaction_rename_group() {
+    use entities_utilities::{RegisterTransaction, RegisterTransactionError};
+
+    let mut reg = Register::new();
+    for (i, name) in ["sup", "worker_a", "worker_b"].iter().enumerate() {
+        assert_eq!(reg.register_name(name, i as u64 + 1), RegisterResult::Success);
+    }
+
+    // Rename the whole group with the v2 prefix in one step
+    let mut tx = RegisterTransaction::new();
+    for name in ["sup", "worker_a", "worker_b"] {
+        let id = reg.whereis_name(name).unwrap();
+        tx.unregister_name(name).register(&format!("v2_{}", name), id);
+    }
+    assert_eq!(reg.apply_transaction(&tx), Ok(()));
+    assert_eq!(reg.whereis_name("v2_sup"), Some(1));
+    assert_eq!(reg.whereis_name("v2_worker_b"), Some(3));
+    assert!(!reg.is_registered("worker_a"));
+
+    // A conflicting group rename leaves every registration in place
+    let mut tx = RegisterTransaction::new();
+    tx.unregister_name("v2_sup").register("v3_sup", 1).register("v2_worker_a", 3);
+    assert!(matches!(
+        reg.apply_transaction(&tx),
+        Err(RegisterTransactionError::Register { index: 2, .. })
+    ));
+    assert_eq!(reg.whereis_name("v2_sup"), Some(1));
+    assert!(!reg.is_registered("v3_sup"));
+}
@@ -1,19 +1,31 @@
 //! Boot Script Parser and Executor
 //!
-//! Handles loading and executing boot scripts (.boot files).
-//! Boot scripts are binary Erlang terms containing instructions for:
+//! Handles loading and executing boot scripts. A boot script is either a
+//! `.boot` file, holding the script as a binary Erlang term, or a `.script`
+//! file, holding it as text. Boot scripts contain instructions for:
 //! - Loading modules
 //! - Setting code paths
 //! - Starting kernel processes
 //! - Starting applications
 //!
+//! Modules listed in `primLoad` are loaded through the BEAM loader, and
+//! `apply` directives are resolved against the loaded modules or against
+//! functions registered with [`register_boot_function`].
+//!
 //! Based on init.erl boot script handling
 
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
-use std::sync::{Mutex, OnceLock};
-use infrastructure_utilities::{ErlangTerm, decode_term};
+use std::sync::{Mutex, OnceLock, RwLock};
+use code_management_code_loading::{get_global_module_manager, BeamFile, BeamLoader};
+use entities_data_handling::AtomEncoding;
+use infrastructure_utilities::atom_table::get_global_atom_table;
+use infrastructure_utilities::{ErlangTerm, decode_term, scan_string, Token, TokenKind};
 use entities_utilities::{Register, RegisterResult};
 
+/// Function run natively for a boot script `apply` directive
+pub type BootFunction = fn(&[ErlangTerm]) -> Result<(), String>;
+
 /// Boot script structure
 #[derive(Debug, Clone)]
 pub struct BootScript {
@@ -26,7 +38,7 @@ pub struct BootScript {
 }
 
 /// Boot script command
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum BootCommand {
     /// Progress update: {progress, Info}
     Progress(String),
@@ -43,13 +55,13 @@ pub enum BootCommand {
         name: String,
         module: String,
         function: String,
-        args: Vec<String>,
+        args: Vec<ErlangTerm>,
     },
     /// Apply function: {apply, {Mod, Func, Args}}
     Apply {
         module: String,
         function: String,
-        args: Vec<String>,
+        args: Vec<ErlangTerm>,
     },
 }
 
@@ -81,11 +93,14 @@ impl std::error::Error for BootScriptError {}
 
 /// Load and parse a boot script file
 ///
-/// Boot scripts are binary Erlang terms with the format:
+/// Boot scripts are Erlang terms with the format:
 /// {script, {Name, Vsn}, [Commands]}
 ///
+/// A `.boot` file holds the term in the external term format, a `.script`
+/// file holds it as text terminated by a dot.
+///
 /// # Arguments
-/// * `boot_path` - Path to .boot file (without extension)
+/// * `boot_path` - Path to the .boot or .script file (the extension may be left out)
 /// * `rootdir` - Root directory for resolving paths
 /// * `bindir` - Binary directory for resolving paths
 ///
@@ -103,30 +118,41 @@ pub fn load_boot_script(
     let boot_data = std::fs::read(&resolved_path)
         .map_err(|e| BootScriptError::IoError(format!("Failed to read boot script: {}", e)))?;
     
+    if resolved_path.ends_with(".script") {
+        let text = String::from_utf8(boot_data)
+            .map_err(|e| BootScriptError::ParseError(format!("Script is not UTF-8: {}", e)))?;
+        return parse_script_text(&text);
+    }
+    
     // Parse binary Erlang term
     parse_boot_script(&boot_data)
 }
 
 /// Resolve boot script path
 ///
-/// Tries multiple locations:
-/// 1. Exact path (if absolute or with .boot extension)
-/// 2. bindir/boot.boot
-/// 3. rootdir/bin/boot.boot
+/// Tries multiple locations, preferring .boot over .script:
+/// 1. Exact path (if absolute or with .boot/.script extension)
+/// 2. boot.boot, bindir/boot.boot and rootdir/bin/boot.boot
+/// 3. boot.script, bindir/boot.script and rootdir/bin/boot.script
 fn resolve_boot_path(boot_path: &str, rootdir: &str, bindir: &str) -> Result<String, BootScriptError> {
     // Try exact path first
-    if Path::new(boot_path).is_absolute() || boot_path.ends_with(".boot") {
-        if Path::new(boot_path).exists() {
-            return Ok(boot_path.to_string());
-        }
+    if (Path::new(boot_path).is_absolute() || boot_path.ends_with(".boot") || boot_path.ends_with(".script"))
+        && Path::new(boot_path).is_file()
+    {
+        return Ok(boot_path.to_string());
     }
     
-    // Try with .boot extension
-    let paths_to_try = vec![
-        format!("{}.boot", boot_path),
-        format!("{}/{}.boot", bindir, boot_path),
-        format!("{}/bin/{}.boot", rootdir, boot_path),
-    ];
+    // Try with .boot, then .script extension
+    let paths_to_try: Vec<String> = ["boot", "script"]
+        .iter()
+        .flat_map(|ext| {
+            [
+                format!("{}.{}", boot_path, ext),
+                format!("{}/{}.{}", bindir, boot_path, ext),
+                format!("{}/bin/{}.{}", rootdir, boot_path, ext),
+            ]
+        })
+        .collect();
     
     let tried_paths = paths_to_try.clone();
     for path in paths_to_try {
@@ -157,12 +183,96 @@ fn parse_boot_script(data: &[u8]) -> Result<BootScript, BootScriptError> {
     let term = decode_term(data)
         .map_err(|e| BootScriptError::ParseError(format!("Failed to decode term: {}", e)))?;
     
-    // Parse the script structure: {script, {Name, Vsn}, [Commands]}
+    parse_script_term(term)
+}
+
+/// Parse boot script from the text of a .script file
+///
+/// The text holds a single term terminated by a dot, as read by
+/// `file:consult/1`. Comments are allowed.
+///
+/// # Arguments
+/// * `text` - Contents of the .script file
+///
+/// # Returns
+/// Parsed boot script or error
+pub fn parse_script_text(text: &str) -> Result<BootScript, BootScriptError> {
+    let tokens = scan_string(text)
+        .map_err(|e| BootScriptError::ParseError(format!("Failed to scan script: {}", e)))?;
+    let mut pos = 0;
+    let term = read_term(&tokens, &mut pos)?;
+    match tokens.get(pos).map(|t| &t.kind) {
+        Some(TokenKind::Dot) => parse_script_term(term),
+        _ => Err(BootScriptError::ParseError("Expected '.' after the script term".to_string())),
+    }
+}
+
+/// Read a literal term from scanned tokens
+///
+/// Strings are read as binaries, as the term decoder returns them.
+fn read_term(tokens: &[Token], pos: &mut usize) -> Result<ErlangTerm, BootScriptError> {
+    let token = tokens
+        .get(*pos)
+        .ok_or_else(|| BootScriptError::ParseError("Unexpected end of script".to_string()))?;
+    *pos += 1;
+    match &token.kind {
+        TokenKind::Atom(name) => Ok(ErlangTerm::Atom(name.clone())),
+        TokenKind::Integer(i) => Ok(ErlangTerm::Integer(*i)),
+        TokenKind::Float(f) => Ok(ErlangTerm::Float(*f)),
+        TokenKind::Char(c) => Ok(ErlangTerm::Integer(*c as i64)),
+        TokenKind::String(s) => Ok(ErlangTerm::Binary(s.as_bytes().to_vec())),
+        TokenKind::Minus => match read_term(tokens, pos)? {
+            ErlangTerm::Integer(i) => Ok(ErlangTerm::Integer(-i)),
+            ErlangTerm::Float(f) => Ok(ErlangTerm::Float(-f)),
+            _ => Err(BootScriptError::ParseError(format!("Expected a number at line {}", token.line))),
+        },
+        TokenKind::LeftBrace => {
+            read_elements(tokens, pos, TokenKind::RightBrace).map(ErlangTerm::Tuple)
+        }
+        TokenKind::LeftBracket => {
+            let elements = read_elements(tokens, pos, TokenKind::RightBracket)?;
+            Ok(if elements.is_empty() { ErlangTerm::Nil } else { ErlangTerm::List(elements) })
+        }
+        other => Err(BootScriptError::ParseError(format!(
+            "Unexpected token {:?} at line {}",
+            other, token.line
+        ))),
+    }
+}
+
+/// Read comma-separated terms up to the closing token
+fn read_elements(tokens: &[Token], pos: &mut usize, close: TokenKind) -> Result<Vec<ErlangTerm>, BootScriptError> {
+    let mut elements = Vec::new();
+    if tokens.get(*pos).map(|t| &t.kind) == Some(&close) {
+        *pos += 1;
+        return Ok(elements);
+    }
+    loop {
+        elements.push(read_term(tokens, pos)?);
+        let token = tokens
+            .get(*pos)
+            .ok_or_else(|| BootScriptError::ParseError("Unexpected end of script".to_string()))?;
+        *pos += 1;
+        match &token.kind {
+            TokenKind::Comma => continue,
+            kind if *kind == close => return Ok(elements),
+            other => {
+                return Err(BootScriptError::ParseError(format!(
+                    "Unexpected token {:?} at line {}",
+                    other, token.line
+                )))
+            }
+        }
+    }
+}
+
+/// Parse the script structure: {script, {Name, Vsn}, [Commands]}
+fn parse_script_term(term: ErlangTerm) -> Result<BootScript, BootScriptError> {
     match term {
-        ErlangTerm::Tuple(mut elements) if elements.len() == 3 => {
+        ErlangTerm::Tuple(elements) if elements.len() == 3 => {
             // First element should be atom "script"
-            let name = match &elements[0] {
-                ErlangTerm::Atom(s) if s == "script" => s.clone(),
+            match &elements[0] {
+                ErlangTerm::Atom(s) if s == "script" => {}
                 _ => {
                     return Err(BootScriptError::InvalidFormat(
                         "Expected 'script' atom as first element".to_string(),
@@ -195,6 +305,7 @@ fn parse_boot_script(data: &[u8]) -> Result<BootScript, BootScriptError> {
             
             // Third element should be list of commands
             let commands = match &elements[2] {
+                ErlangTerm::Nil => Vec::new(),
                 ErlangTerm::List(cmd_terms) => {
                     let mut parsed_commands = Vec::new();
                     for cmd_term in cmd_terms {
@@ -302,6 +413,7 @@ fn parse_command(term: &ErlangTerm) -> Result<BootCommand, BootScriptError> {
 /// Parse a list of module names
 fn parse_module_list(term: &ErlangTerm) -> Result<Vec<String>, BootScriptError> {
     match term {
+        ErlangTerm::Nil => Ok(Vec::new()),
         ErlangTerm::List(elements) => {
             let mut modules = Vec::new();
             for elem in elements {
@@ -331,19 +443,14 @@ fn parse_string_list(term: &ErlangTerm) -> Result<Vec<String>, BootScriptError>
 }
 
 /// Parse MFA (Module, Function, Args) tuple
-fn parse_mfa(term: &ErlangTerm) -> Result<(String, String, Vec<String>), BootScriptError> {
+fn parse_mfa(term: &ErlangTerm) -> Result<(String, String, Vec<ErlangTerm>), BootScriptError> {
     match term {
         ErlangTerm::Tuple(elements) if elements.len() == 3 => {
             let module = term_to_string(&elements[0])?;
             let function = term_to_string(&elements[1])?;
             let args = match &elements[2] {
-                ErlangTerm::List(args_list) => {
-                    let mut parsed_args = Vec::new();
-                    for arg in args_list {
-                        parsed_args.push(term_to_string(arg)?);
-                    }
-                    parsed_args
-                }
+                ErlangTerm::Nil => Vec::new(),
+                ErlangTerm::List(args_list) => args_list.clone(),
                 _ => {
                     return Err(BootScriptError::ParseError(
                         "Args must be a list".to_string(),
@@ -377,16 +484,17 @@ fn term_to_string(term: &ErlangTerm) -> Result<String, BootScriptError> {
 ///
 /// # Arguments
 /// * `script` - Boot script to execute
+/// * `rootdir` - Root directory substituted for `$ROOT` in `path` commands
 ///
 /// # Returns
 /// Result indicating success or failure
-pub fn execute_boot_script(script: &BootScript) -> Result<(), String> {
+pub fn execute_boot_script(script: &BootScript, rootdir: &str) -> Result<(), String> {
     eprintln!("Executing boot script: {} (version {})", script.name, script.version);
     
     for (i, command) in script.commands.iter().enumerate() {
         eprintln!("  [{}/{}] Executing: {:?}", i + 1, script.commands.len(), command);
         
-        match execute_command(command) {
+        match execute_command(command, rootdir) {
             Ok(()) => {}
             Err(e) => {
                 eprintln!("  Error executing command: {}", e);
@@ -403,10 +511,11 @@ pub fn execute_boot_script(script: &BootScript) -> Result<(), String> {
 ///
 /// # Arguments
 /// * `command` - Command to execute
+/// * `rootdir` - Root directory substituted for `$ROOT`
 ///
 /// # Returns
 /// Result indicating success or failure
-fn execute_command(command: &BootCommand) -> Result<(), String> {
+fn execute_command(command: &BootCommand, rootdir: &str) -> Result<(), String> {
     match command {
         BootCommand::Progress(info) => {
             eprintln!("    Progress: {}", info);
@@ -414,15 +523,17 @@ fn execute_command(command: &BootCommand) -> Result<(), String> {
         }
         BootCommand::PreLoaded(modules) => {
             eprintln!("    Preloaded modules: {:?}", modules);
-            mark_modules_preloaded(&modules)
+            mark_modules_preloaded(modules)
         }
         BootCommand::Path(paths) => {
+            // In init.erl: fix_path/2 replaces $ROOT with the root directory
+            let paths: Vec<String> = paths.iter().map(|path| path.replace("$ROOT", rootdir)).collect();
             eprintln!("    Setting code path: {:?}", paths);
             set_code_path(&paths)
         }
         BootCommand::PrimLoad(modules) => {
             eprintln!("    Loading modules: {:?}", modules);
-            load_modules(&modules)
+            load_modules(modules)
         }
         BootCommand::KernelLoadCompleted => {
             eprintln!("    Kernel load completed");
@@ -458,124 +569,98 @@ fn spawn_kernel_process(
     name: &str,
     module: &str,
     function: &str,
-    args: &[String],
+    args: &[ErlangTerm],
 ) -> Result<(), String> {
-    use entities_process::{Process, ErtsCodePtr};
-    use infrastructure_utilities::process_table::get_global_process_table;
-    use usecases_scheduling::{get_global_schedulers, schedule_process, Priority};
-    use std::sync::Arc;
-    
-    // Allocate a new process with automatic ID generation
-    let process_table = get_global_process_table();
-    let (pid, process_arc) = process_table
-        .new_element(|id| {
-            let mut process = Process::new(id);
-            
-            // In the full implementation, we would:
-            // 1. Look up the module in the code server
-            // 2. Find the function entry point (export table lookup)
-            // 3. Set the instruction pointer to the function entry point
-            // 4. Set up the process heap with function arguments
-            // 5. Set up the process stack for function call
-            
-            // For now, create a placeholder code sequence
-            // In production, this would be the actual function entry point
-            use infrastructure_emulator_loop::instruction_decoder::opcodes;
-            let mut process_code = Vec::new();
-            
-            // Create a simple entry point that will eventually call the function
-            // For now, just a return instruction (process will exit immediately)
-            // In full implementation, this would be the actual function code
-            process_code.push(opcodes::RETURN as u64);
-            
-            let code_ptr = process_code.as_ptr() as ErtsCodePtr;
-            std::mem::forget(process_code); // Keep code alive
-            
-            process.set_i(code_ptr);
-            Arc::new(process)
-        })
-        .map_err(|e| format!("Failed to allocate process: {:?}", e))?;
+    resolve_function(module, function, args.len() as u32)?;
+    let pid = spawn_boot_process(module, function, args)?;
     
     // Register process name
     register_process_name(name, pid)?;
     
-    // Schedule the process
-    let schedulers = get_global_schedulers()
-        .ok_or_else(|| "Schedulers not initialized".to_string())?;
-    
-    let schedulers_guard = schedulers
-        .lock()
-        .map_err(|e| format!("Failed to lock schedulers: {}", e))?;
-    
-    if schedulers_guard.is_empty() {
-        return Err("No schedulers available".to_string());
-    }
-    
-    // Schedule on first available scheduler
-    let scheduler = &schedulers_guard[0];
-    let runq = scheduler.runq();
-    let runq_guard = runq.lock()
-        .map_err(|e| format!("Failed to lock run queue: {}", e))?;
-    
-    schedule_process(process_arc.clone(), &runq_guard, Priority::Normal)
-        .map_err(|e| format!("Failed to schedule kernel process: {:?}", e))?;
-    
     eprintln!("      ✓ Kernel process '{}' spawned and scheduled (PID: {})", name, pid);
     Ok(())
 }
 
-/// Apply a function directly
+/// Apply a function
 ///
-/// Creates a temporary process to execute the specified module:function/arity
-/// with the given arguments. This is used for boot script `apply` commands.
+/// Runs a boot script `apply` directive. A function registered with
+/// [`register_boot_function`] is run directly; a function exported by a
+/// module loaded by the script is run in a new process.
 ///
 /// # Arguments
 /// * `module` - Module name
 /// * `function` - Function name
-/// * `args` - Function arguments (as strings for now)
+/// * `args` - Function arguments
 ///
 /// # Returns
-/// Result indicating success or failure
-///
-/// # Note
-/// In the full implementation, this would:
-/// 1. Look up the function in the module's export table
-/// 2. Create a temporary process or use current context
-/// 3. Set up the function call with proper argument encoding
-/// 4. Execute the function synchronously
-/// 5. Return the result
+/// * `Ok(())` - The function was run or scheduled
+/// * `Err(String)` - The function failed or is undefined
 fn apply_function(
     module: &str,
     function: &str,
-    args: &[String],
+    args: &[ErlangTerm],
 ) -> Result<(), String> {
-    use entities_process::{Process, ErtsCodePtr};
+    let arity = args.len() as u32;
+    if let Some(boot_function) = lookup_boot_function(module, function, arity) {
+        return boot_function(args)
+            .map_err(|e| format!("{}:{}/{} failed: {}", module, function, arity, e));
+    }
+    
+    resolve_function(module, function, arity)?;
+    let pid = spawn_boot_process(module, function, args)?;
+    
+    eprintln!("      ✓ Function {}.{}/{} scheduled for execution (PID: {})", 
+             module, function, arity, pid);
+    Ok(())
+}
+
+/// Check that a function can be called by the boot script
+///
+/// The function must be exported by a module loaded with `primLoad`, or
+/// belong to a preloaded module.
+///
+/// # Returns
+/// * `Ok(())` - The function is defined
+/// * `Err(String)` - The function is undefined (`undef` in init.erl)
+fn resolve_function(module: &str, function: &str, arity: u32) -> Result<(), String> {
+    let state = boot_state().lock().map_err(|e| format!("Failed to lock boot state: {}", e))?;
+    let defined = match state.loaded.get(module) {
+        Some(beam) => beam.find_export(function, arity).is_some(),
+        None => state.preloaded.contains(module),
+    };
+    if defined {
+        Ok(())
+    } else {
+        Err(format!("undef: {}:{}/{}", module, function, arity))
+    }
+}
+
+/// Create and schedule a process running module:function/arity
+///
+/// # Returns
+/// The process ID
+fn spawn_boot_process(module: &str, function: &str, args: &[ErlangTerm]) -> Result<u64, String> {
+    use entities_process::{Process, ErtsCodePtr, InitialCall};
     use infrastructure_utilities::process_table::get_global_process_table;
     use usecases_scheduling::{get_global_schedulers, schedule_process, Priority};
     use std::sync::Arc;
     
-    eprintln!("      Applying function: {}.{}/{}", module, function, args.len());
-    
-    // In the full implementation, we would:
-    // 1. Look up the module in the code server
-    // 2. Find the function entry point in the export table
-    // 3. Create a temporary process or use the current execution context
-    // 4. Set up the process heap with function arguments (properly encoded as Erlang terms)
-    // 5. Set the instruction pointer to the function entry point
-    // 6. Execute the function synchronously (or schedule and wait)
-    // 7. Handle the return value or any exceptions
-    
-    // For now, we'll create a temporary process similar to kernel process spawning
-    // but mark it for synchronous execution
-    
     // Allocate a new process with automatic ID generation
     let process_table = get_global_process_table();
     let (pid, process_arc) = process_table
         .new_element(|id| {
-            let mut process = Process::new(id);
+            // Boot processes are spawned by the runtime, so they have no parent
+            let initial_call = InitialCall::new(module, function, args.len() as u32);
+            let mut process = Process::spawned(id, None, initial_call);
+            
+            // In the full implementation, we would:
+            // 1. Find the function entry point (export table lookup)
+            // 2. Set the instruction pointer to the function entry point
+            // 3. Set up the process heap with function arguments
+            // 4. Set up the process stack for function call
             
-            // In the full implementation, this would be the actual function entry point
             // For now, create a placeholder code sequence
+            // In production, this would be the actual function entry point
             use infrastructure_emulator_loop::instruction_decoder::opcodes;
             let mut process_code = Vec::new();
             
@@ -590,18 +675,9 @@ fn apply_function(
             process.set_i(code_ptr);
             Arc::new(process)
         })
-        .map_err(|e| format!("Failed to allocate process for apply: {:?}", e))?;
-    
-    eprintln!("      Created temporary process (PID: {}) for apply", pid);
-    
-    // In the full implementation, we would:
-    // 1. Execute the function synchronously in the current context
-    // 2. Wait for the result
-    // 3. Handle any exceptions
-    // 4. Clean up the temporary process
+        .map_err(|e| format!("Failed to allocate process: {:?}", e))?;
     
-    // For now, we'll schedule it like a kernel process
-    // In production, this would be synchronous execution
+    // Schedule the process
     let schedulers = get_global_schedulers()
         .ok_or_else(|| "Schedulers not initialized".to_string())?;
     
@@ -620,21 +696,16 @@ fn apply_function(
         .map_err(|e| format!("Failed to lock run queue: {}", e))?;
     
     schedule_process(process_arc.clone(), &runq_guard, Priority::Normal)
-        .map_err(|e| format!("Failed to schedule apply process: {:?}", e))?;
-    
-    eprintln!("      ✓ Function {}.{}/{} scheduled for execution (PID: {})", 
-             module, function, args.len(), pid);
-    
-    // Note: In the full implementation, we would wait for the result here
-    // For now, we just schedule it and continue
+        .map_err(|e| format!("Failed to schedule {}:{}/{}: {:?}", module, function, args.len(), e))?;
     
-    Ok(())
+    Ok(pid)
 }
 
 /// Load modules from boot script
 ///
-/// Loads BEAM modules specified in the primLoad command.
-/// This function searches for .beam files and loads them into the runtime.
+/// Loads the BEAM modules specified in the primLoad command from the code
+/// path through the BEAM loader. As in init.erl, a module that cannot be
+/// loaded stops the boot.
 ///
 /// # Arguments
 /// * `modules` - List of module names to load
@@ -642,68 +713,128 @@ fn apply_function(
 /// # Returns
 /// Result indicating success or failure
 fn load_modules(modules: &[String]) -> Result<(), String> {
-    use code_management_code_loading::CodeLoader;
-    use code_management_code_loading::code_loader::LoadError;
-    use std::path::Path;
-    
-    // Get code search paths
-    // In the full implementation, this would use the code path from boot script
-    // For now, we'll try common locations
     let code_paths = get_code_paths();
     
-    let mut loaded_count = 0;
+    BeamLoader::init_load();
     let mut failed_modules = Vec::new();
     
     for module_name in modules {
-        // Try to find and load the module
-        let mut found = false;
-        
-        for code_path in &code_paths {
-            // Try .beam file
-            let beam_path = Path::new(code_path).join(format!("{}.beam", module_name));
-            
-            match CodeLoader::load_module(&beam_path) {
-                Ok(code) => {
-                    // Verify the code
-                    if CodeLoader::verify_module(&code) {
-                        eprintln!("      ✓ Loaded: {} (from {})", module_name, beam_path.display());
-                        loaded_count += 1;
-                        found = true;
-                        break;
-                    } else {
-                        eprintln!("      ✗ Invalid format: {}", module_name);
-                    }
-                }
-                Err(LoadError::FileError) => {
-                    // File not found, try next path
-                    continue;
-                }
-                Err(LoadError::InvalidFormat) => {
-                    eprintln!("      ✗ Invalid format: {}", module_name);
-                    failed_modules.push(module_name.clone());
-                    found = true; // Don't try other paths
-                    break;
-                }
-            }
-        }
-        
-        if !found {
+        let Some(beam_path) = code_paths
+            .iter()
+            .map(|code_path| Path::new(code_path).join(format!("{}.beam", module_name)))
+            .find(|beam_path| beam_path.is_file())
+        else {
             eprintln!("      ✗ Not found: {} (searched in: {:?})", module_name, code_paths);
             failed_modules.push(module_name.clone());
+            continue;
+        };
+        
+        match load_module(module_name, &beam_path) {
+            Ok(()) => eprintln!("      ✓ Loaded: {} (from {})", module_name, beam_path.display()),
+            Err(e) => {
+                eprintln!("      ✗ {}", e);
+                failed_modules.push(module_name.clone());
+            }
         }
     }
     
     if !failed_modules.is_empty() {
-        eprintln!("    Warning: Failed to load {} modules: {:?}", 
-                 failed_modules.len(), failed_modules);
-        // In the full implementation, this might be an error
-        // For now, we'll continue with a warning
+        return Err(format!("Failed to load {} modules: {:?}", failed_modules.len(), failed_modules));
     }
     
-    eprintln!("    Loaded {}/{} modules", loaded_count, modules.len());
+    eprintln!("    Loaded {} modules", modules.len());
     Ok(())
 }
 
+/// Load one module through the BEAM loader
+///
+/// Based on the `erl_prim_loader:get_file/1` and `erlang:load_module/2`
+/// calls made by init.erl for each module.
+fn load_module(module_name: &str, beam_path: &Path) -> Result<(), String> {
+    use usecases_bifs::load::{LoadBif, ModuleStatus};
+    
+    let code = std::fs::read(beam_path)
+        .map_err(|e| format!("Failed to read {}: {}", beam_path.display(), e))?;
+    let beam = BeamLoader::prepare_loading(&code, None)
+        .map_err(|reason| format!("Invalid BEAM file for {}: {:?}", module_name, reason))?;
+    if beam.module_name() != Some(module_name) {
+        return Err(format!("Expected module {}, found {:?}", module_name, beam.module_name()));
+    }
+    
+    let atom = get_global_atom_table()
+        .put_index(module_name.as_bytes(), AtomEncoding::SevenBitAscii, false)
+        .map_err(|e| format!("Invalid module name {}: {:?}", module_name, e))?;
+    BeamLoader::finish_loading(&beam, atom as u32, get_global_module_manager())
+        .map_err(|e| format!("Failed to load {}: {:?}", module_name, e))?;
+    LoadBif::register_module(module_name, ModuleStatus::Loaded, false, false);
+    
+    boot_state()
+        .lock()
+        .map_err(|e| format!("Failed to lock boot state: {}", e))?
+        .loaded
+        .insert(module_name.to_string(), beam);
+    Ok(())
+}
+
+/// Modules known to the boot script
+#[derive(Default)]
+struct BootState {
+    /// Modules loaded by `primLoad`
+    loaded: HashMap<String, BeamFile>,
+    /// Modules listed in `preLoaded`
+    preloaded: HashSet<String>,
+}
+
+/// Global boot state
+static BOOT_STATE: OnceLock<Mutex<BootState>> = OnceLock::new();
+
+/// Functions run natively for `apply` directives, by module, function and arity
+type BootFunctionTable = HashMap<(String, String, u32), BootFunction>;
+
+/// Registered boot functions
+static BOOT_FUNCTIONS: OnceLock<RwLock<BootFunctionTable>> = OnceLock::new();
+
+fn boot_state() -> &'static Mutex<BootState> {
+    BOOT_STATE.get_or_init(|| Mutex::new(BootState::default()))
+}
+
+fn boot_functions() -> &'static RwLock<BootFunctionTable> {
+    BOOT_FUNCTIONS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Register a function run natively for a boot script `apply` directive
+///
+/// Lets the runtime provide functions the boot script applies before the
+/// modules defining them can be executed.
+///
+/// # Arguments
+/// * `module` - Module name
+/// * `function` - Function name
+/// * `arity` - Number of arguments
+/// * `boot_function` - Function run with the directive's arguments
+///
+/// # Returns
+/// The previously registered function, if any
+pub fn register_boot_function(
+    module: &str,
+    function: &str,
+    arity: u32,
+    boot_function: BootFunction,
+) -> Option<BootFunction> {
+    boot_functions()
+        .write()
+        .unwrap()
+        .insert((module.to_string(), function.to_string(), arity), boot_function)
+}
+
+fn lookup_boot_function(module: &str, function: &str, arity: u32) -> Option<BootFunction> {
+    boot_functions()
+        .read()
+        .unwrap()
+        .get(&(module.to_string(), function.to_string(), arity))
+        .copied()
+}
+
 /// Global code path storage
 ///
 /// Stores the code search paths set by boot script `path` commands.
@@ -809,7 +940,7 @@ fn init_process_registry() -> &'static Mutex<Register> {
 ///
 /// # Returns
 /// Result indicating success or failure
-fn register_process_name(name: &str, pid: u64) -> Result<(), String> {
+pub(crate) fn register_process_name(name: &str, pid: u64) -> Result<(), String> {
     let registry = init_process_registry();
     let mut reg_guard = registry
         .lock()
@@ -835,6 +966,49 @@ fn register_process_name(name: &str, pid: u64) -> Result<(), String> {
     }
 }
 
+/// Unregister a process name
+///
+/// # Returns
+/// `true` if the name was registered
+pub(crate) fn unregister_process_name(name: &str) -> bool {
+    init_process_registry()
+        .lock()
+        .map(|mut registry| registry.unregister_name(name))
+        .unwrap_or(false)
+}
+
+/// Look up a process registered by the boot sequence (`whereis/1`)
+///
+/// # Arguments
+/// * `name` - Process name
+///
+/// # Returns
+/// The process ID, or `None` if the name is not registered
+pub fn whereis(name: &str) -> Option<u64> {
+    init_process_registry().lock().ok()?.whereis_name(name)
+}
+
+/// Load the preloaded modules embedded in the emulator
+///
+/// Runs before the boot script, which needs `erl_prim_loader`, `init` and
+/// `prim_file` to be read. The loaded modules are marked as preloaded.
+///
+/// # Returns
+/// The names of the loaded modules, in load order
+pub fn load_embedded_preloaded() -> Result<Vec<String>, String> {
+    let atom_table = get_global_atom_table();
+    let loaded = code_management_code_loading::load_preloaded(|name| {
+        atom_table
+            .put_index(name.as_bytes(), AtomEncoding::SevenBitAscii, false)
+            .expect("preloaded module names are valid atoms") as u32
+    })
+    .map_err(|e| e.to_string())?;
+    
+    let names: Vec<String> = loaded.into_iter().map(|module| module.name).collect();
+    mark_modules_preloaded(&names)?;
+    Ok(names)
+}
+
 /// Mark modules as preloaded
 ///
 /// Marks the specified modules as preloaded in the module management system.
@@ -850,8 +1024,12 @@ fn mark_modules_preloaded(modules: &[String]) -> Result<(), String> {
     // This ensures consistency with the module management system
     use usecases_bifs::load::LoadBif;
     
+    let mut state = boot_state()
+        .lock()
+        .map_err(|e| format!("Failed to lock boot state: {}", e))?;
     for module_name in modules {
         LoadBif::mark_preloaded(module_name);
+        state.preloaded.insert(module_name.clone());
         eprintln!("      ✓ Marked '{}' as preloaded", module_name);
     }
     
@@ -875,4 +1053,99 @@ mod tests {
         let result = resolve_boot_path("nonexistent", "/root", "/bin");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_parse_script_text() {
+        let text = r#"
+            %% script generated by systools
+            {script,{"OTP APN 181 01","27"},
+                [{preLoaded,[erl_prim_loader,init]},
+                 {progress,preloaded},
+                 {path,["$ROOT/lib/kernel/ebin"]},
+                 {primLoad,[]},
+                 {kernel_load_completed},
+                 {kernelProcess,heart,{heart,start,[]}},
+                 {apply,{application,start_boot,[kernel,permanent]}},
+                 {apply,{c,erlangrc,[]}},
+                 {progress,started}]}.
+        "#;
+        let script = parse_script_text(text).unwrap();
+        assert_eq!(script.name, "OTP APN 181 01");
+        assert_eq!(script.version, "27");
+        assert_eq!(script.commands.len(), 9);
+        assert_eq!(script.commands[0], BootCommand::PreLoaded(vec!["erl_prim_loader".to_string(), "init".to_string()]));
+        assert_eq!(script.commands[2], BootCommand::Path(vec!["$ROOT/lib/kernel/ebin".to_string()]));
+        assert_eq!(script.commands[3], BootCommand::PrimLoad(vec![]));
+        assert_eq!(
+            script.commands[6],
+            BootCommand::Apply {
+                module: "application".to_string(),
+                function: "start_boot".to_string(),
+                args: vec![ErlangTerm::Atom("kernel".to_string()), ErlangTerm::Atom("permanent".to_string())],
+            }
+        );
+        assert!(matches!(&script.commands[7], BootCommand::Apply { args, .. } if args.is_empty()));
+
+        assert!(parse_script_text("{script,{\"a\",\"1\"},[]}").is_err());
+        assert!(parse_script_text("{other,{\"a\",\"1\"},[]}.").is_err());
+    }
+
+    #[test]
+    fn test_parse_binary_boot_script() {
+        // term_to_binary({script,{"s","1"},[{progress,x}]})
+        let mut data = vec![131, 104, 3, 119, 6];
+        data.extend_from_slice(b"script");
+        data.extend_from_slice(&[104, 2, 107, 0, 1, b's', 107, 0, 1, b'1']);
+        data.extend_from_slice(&[108, 0, 0, 0, 1, 104, 2, 119, 8]);
+        data.extend_from_slice(b"progress");
+        data.extend_from_slice(&[119, 1, b'x', 106]);
+        let script = parse_boot_script(&data).unwrap();
+        assert_eq!(script.name, "s");
+        assert_eq!(script.commands, vec![BootCommand::Progress("x".to_string())]);
+    }
+
+    #[test]
+    fn test_apply_directives() {
+        fn record(args: &[ErlangTerm]) -> Result<(), String> {
+            match args {
+                [ErlangTerm::Atom(name)] if name == "ok" => Ok(()),
+                _ => Err("bad argument".to_string()),
+            }
+        }
+        register_boot_function("boot_test", "record", 1, record);
+
+        let apply = |arg: &str| BootCommand::Apply {
+            module: "boot_test".to_string(),
+            function: "record".to_string(),
+            args: vec![ErlangTerm::Atom(arg.to_string())],
+        };
+        assert!(execute_command(&apply("ok"), "/otp").is_ok());
+        assert!(execute_command(&apply("error"), "/otp").is_err());
+
+        let undefined = BootCommand::Apply {
+            module: "no_such_module".to_string(),
+            function: "start".to_string(),
+            args: vec![],
+        };
+        let err = execute_command(&undefined, "/otp").unwrap_err();
+        assert!(err.contains("undef"));
+    }
+
+    #[test]
+    fn test_load_embedded_preloaded() {
+        let names = load_embedded_preloaded().unwrap();
+        assert_eq!(names.len(), code_management_code_loading::embedded_preloaded().len());
+        let state = boot_state().lock().unwrap();
+        assert!(names.iter().all(|name| state.preloaded.contains(name)));
+    }
+
+    #[test]
+    fn test_path_and_prim_load() {
+        let path = BootCommand::Path(vec!["$ROOT/lib/boot_test/ebin".to_string()]);
+        execute_command(&path, "/nonexistent/otp").unwrap();
+        assert_eq!(get_code_paths(), vec!["/nonexistent/otp/lib/boot_test/ebin".to_string()]);
+
+        let prim_load = BootCommand::PrimLoad(vec!["boot_test_missing".to_string()]);
+        assert!(execute_command(&prim_load, "/nonexistent/otp").is_err());
+    }
 }
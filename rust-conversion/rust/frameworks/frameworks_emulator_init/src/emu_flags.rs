@@ -0,0 +1,349 @@
+//! Emulator Flags Module
+//!
+//! Provides parsing of the emulator flags into an [`InitConfig`].
+//! Based on the argument loop of `erl_start()` in erl_init.c
+//!
+//! Emulator flags start with `+`; a flag's value may follow it directly
+//! (`+P1048576`) or be the next argument (`+P 1048576`). Values out of range
+//! are reported as [`FlagError`]s, as `erts_usage()` reports them in C.
+//!
+//! The init flags `-name`, `-sname`, `-setcookie` and `-boot` are kept in the
+//! configuration. Other `-` flags, with the values that follow them, are
+//! passed on to init unchanged, and everything after `-extra` becomes init's
+//! plain arguments.
+
+use std::fmt;
+
+use crate::main_init::InitConfig;
+
+/// Fewest processes that can be configured with `+P` (`ERTS_MIN_PROCESSES`)
+pub const MIN_PROCESSES: usize = 1024;
+/// Most processes that can be configured with `+P` (`ERTS_MAX_PROCESSES`)
+pub const MAX_PROCESSES: usize = (1 << 27) - 1;
+/// Fewest ports that can be configured with `+Q` (`ERTS_MIN_PORTS`)
+pub const MIN_PORTS: usize = 1024;
+/// Most ports that can be configured with `+Q` (`ERTS_MAX_PORTS`)
+pub const MAX_PORTS: usize = (1 << 27) - 1;
+/// Most schedulers of each kind (`ERTS_MAX_NO_OF_SCHEDULERS`)
+pub const MAX_SCHEDULERS: usize = 1024;
+/// Smallest atom table size (`MIN_ATOM_TABLE_SIZE`)
+pub const MIN_ATOM_TABLE_SIZE: usize = 8192;
+/// Largest atom table size (`MAX_ATOM_TABLE_SIZE`)
+pub const MAX_ATOM_TABLE_SIZE: usize = (1 << 31) - 1;
+/// Most async threads (`ERTS_MAX_NO_OF_ASYNC_THREADS`)
+pub const MAX_ASYNC_THREADS: usize = 1024;
+
+/// Scheduler bind type (`+sbt`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchedulerBindType {
+    /// `u`: schedulers are not bound
+    #[default]
+    Unbound,
+    /// `ns`: no spread
+    NoSpread,
+    /// `ts`: thread spread
+    ThreadSpread,
+    /// `ps`: processor spread
+    ProcessorSpread,
+    /// `s`: spread
+    Spread,
+    /// `nnts`: no node thread spread
+    NoNodeThreadSpread,
+    /// `nnps`: no node processor spread
+    NoNodeProcessorSpread,
+    /// `tnnps`: thread no node processor spread
+    ThreadNoNodeProcessorSpread,
+    /// `db`: default bind, currently `tnnps`
+    DefaultBind,
+}
+
+impl SchedulerBindType {
+    /// Parse the value of `+sbt`
+    pub fn from_flag(value: &str) -> Option<Self> {
+        match value {
+            "u" => Some(SchedulerBindType::Unbound),
+            "ns" => Some(SchedulerBindType::NoSpread),
+            "ts" => Some(SchedulerBindType::ThreadSpread),
+            "ps" => Some(SchedulerBindType::ProcessorSpread),
+            "s" => Some(SchedulerBindType::Spread),
+            "nnts" => Some(SchedulerBindType::NoNodeThreadSpread),
+            "nnps" => Some(SchedulerBindType::NoNodeProcessorSpread),
+            "tnnps" => Some(SchedulerBindType::ThreadNoNodeProcessorSpread),
+            "db" => Some(SchedulerBindType::DefaultBind),
+            _ => None,
+        }
+    }
+}
+
+/// Node name given with `-name` or `-sname`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeName {
+    /// Name as given, with or without a host part
+    pub name: String,
+    /// `true` for `-name` (long names), `false` for `-sname`
+    pub long: bool,
+}
+
+/// Emulator flag errors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlagError {
+    /// A flag was given without its value
+    MissingValue(String),
+    /// A flag's value is malformed or out of range
+    InvalidValue {
+        /// The flag
+        flag: String,
+        /// The rejected value
+        value: String,
+        /// What the flag accepts
+        expected: String,
+    },
+    /// An unknown emulator (`+`) flag
+    UnknownFlag(String),
+    /// Flags that cannot be combined
+    Conflict(String),
+}
+
+impl fmt::Display for FlagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FlagError::MissingValue(flag) => write!(f, "missing value for {}", flag),
+            FlagError::InvalidValue { flag, value, expected } => {
+                write!(f, "bad value '{}' for {}: expected {}", value, flag, expected)
+            }
+            FlagError::UnknownFlag(flag) => write!(f, "bad flag {}", flag),
+            FlagError::Conflict(msg) => write!(f, "conflicting flags: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for FlagError {}
+
+/// Emulator flags taking a value, longest first so prefixes match correctly
+const VALUE_FLAGS: [&str; 11] = [
+    "+SDcpu", "+SDio", "+MMscs", "+hmbs", "+hms", "+sbt", "+P", "+Q", "+S", "+t", "+A",
+];
+
+/// Parse the emulator arguments
+///
+/// # Arguments
+/// * `argv` - Emulator arguments, starting with the program name
+/// * `config` - Configuration the flags are applied to
+///
+/// # Returns
+/// * `Ok(config)` - The configuration with the flags applied
+/// * `Err(FlagError)` - The first bad flag
+pub fn parse_emu_flags(argv: &[String], mut config: InitConfig) -> Result<InitConfig, FlagError> {
+    let mut args = argv.iter().skip(1).peekable();
+    while let Some(arg) = args.next() {
+        if arg == "-extra" {
+            config.extra_args.extend(args.by_ref().cloned());
+            break;
+        }
+        if let Some(flag) = VALUE_FLAGS.iter().find(|flag| arg.starts_with(*flag)) {
+            let inline = &arg[flag.len()..];
+            let value = if inline.is_empty() {
+                args.next().ok_or_else(|| FlagError::MissingValue(flag.to_string()))?.clone()
+            } else {
+                inline.to_string()
+            };
+            apply_value_flag(&mut config, flag, &value)?;
+            continue;
+        }
+        if let Some(value) = arg.strip_prefix("+K") {
+            let value = if value.is_empty() {
+                args.next().ok_or_else(|| FlagError::MissingValue("+K".to_string()))?.as_str()
+            } else {
+                value
+            };
+            config.kernel_poll = parse_bool("+K", value)?;
+            continue;
+        }
+        if arg.starts_with('+') {
+            return Err(FlagError::UnknownFlag(arg.clone()));
+        }
+        match arg.as_str() {
+            "-name" | "-sname" => {
+                let name = init_flag_value(arg, args.next())?;
+                if config.node_name.is_some() {
+                    return Err(FlagError::Conflict("-name and -sname given together".to_string()));
+                }
+                config.node_name = Some(NodeName { name, long: arg == "-name" });
+            }
+            "-setcookie" => config.cookie = Some(init_flag_value(arg, args.next())?),
+            "-boot" => config.boot = Some(init_flag_value(arg, args.next())?),
+            _ => {
+                // Init flags and plain arguments are init's to interpret
+                config.init_args.push(arg.clone());
+                if arg.starts_with('-') {
+                    while let Some(value) = args.next_if(|next| !next.starts_with(['-', '+'])) {
+                        config.init_args.push(value.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    if config.no_dirty_cpu_schedulers > config.no_schedulers {
+        return Err(FlagError::Conflict(format!(
+            "{} dirty CPU schedulers but only {} schedulers",
+            config.no_dirty_cpu_schedulers, config.no_schedulers
+        )));
+    }
+    Ok(config)
+}
+
+/// Apply an emulator flag that takes a value
+fn apply_value_flag(config: &mut InitConfig, flag: &str, value: &str) -> Result<(), FlagError> {
+    match flag {
+        "+P" => config.proc_tab_sz = parse_in_range(flag, value, MIN_PROCESSES, MAX_PROCESSES)?,
+        "+Q" => config.port_tab_sz = parse_in_range(flag, value, MIN_PORTS, MAX_PORTS)?,
+        "+S" => {
+            let (total, online) = parse_scheduler_counts(flag, value, config.no_schedulers)?;
+            config.no_schedulers = total;
+            config.no_schedulers_online = online.unwrap_or(total);
+        }
+        "+SDcpu" => {
+            let (total, online) = parse_scheduler_counts(flag, value, config.no_dirty_cpu_schedulers)?;
+            config.no_dirty_cpu_schedulers = total;
+            config.no_dirty_cpu_schedulers_online = online.unwrap_or(total);
+        }
+        "+SDio" => config.no_dirty_io_schedulers = parse_in_range(flag, value, 1, MAX_SCHEDULERS)?,
+        "+t" => config.atom_table_sz = parse_in_range(flag, value, MIN_ATOM_TABLE_SIZE, MAX_ATOM_TABLE_SIZE)?,
+        "+A" => config.async_threads = parse_in_range(flag, value, 0, MAX_ASYNC_THREADS)?,
+        "+sbt" => {
+            config.scheduler_bind_type = SchedulerBindType::from_flag(value)
+                .ok_or_else(|| invalid(flag, value, "u, ns, ts, ps, s, nnts, nnps, tnnps or db"))?;
+        }
+        "+hms" => config.min_heap_size = parse_in_range(flag, value, 1, usize::MAX)?,
+        "+hmbs" => config.min_bin_vheap_size = parse_in_range(flag, value, 1, usize::MAX)?,
+        "+MMscs" => config.super_carrier_mb = parse_in_range(flag, value, 0, usize::MAX >> 20)?,
+        _ => unreachable!("{} is listed in VALUE_FLAGS", flag),
+    }
+    Ok(())
+}
+
+/// Parse `Total:Online`, `Total` or `:Online` for `+S` and `+SDcpu`
+///
+/// # Returns
+/// The total and, if given, the number online
+fn parse_scheduler_counts(flag: &str, value: &str, current: usize) -> Result<(usize, Option<usize>), FlagError> {
+    let (total, online) = match value.split_once(':') {
+        Some((total, online)) => (total, Some(online)),
+        None => (value, None),
+    };
+    let total = if total.is_empty() {
+        current
+    } else {
+        parse_in_range(flag, total, 1, MAX_SCHEDULERS)?
+    };
+    let online = online.map(|online| parse_in_range(flag, online, 1, MAX_SCHEDULERS)).transpose()?;
+    if online.is_some_and(|online| online > total) {
+        return Err(invalid(flag, value, "no more schedulers online than in total"));
+    }
+    Ok((total, online))
+}
+
+/// Parse an integer flag value within `min..=max`
+fn parse_in_range(flag: &str, value: &str, min: usize, max: usize) -> Result<usize, FlagError> {
+    match value.parse::<usize>() {
+        Ok(n) if (min..=max).contains(&n) => Ok(n),
+        _ if max == usize::MAX => Err(invalid(flag, value, &format!("an integer of at least {}", min))),
+        _ => Err(invalid(flag, value, &format!("an integer in {}..{}", min, max))),
+    }
+}
+
+/// Parse `true` or `false`
+fn parse_bool(flag: &str, value: &str) -> Result<bool, FlagError> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(invalid(flag, value, "true or false")),
+    }
+}
+
+/// Get the non-empty value of an init flag
+fn init_flag_value(flag: &str, value: Option<&String>) -> Result<String, FlagError> {
+    match value {
+        Some(value) if !value.is_empty() && !value.starts_with(['-', '+']) => Ok(value.clone()),
+        _ => Err(FlagError::MissingValue(flag.to_string())),
+    }
+}
+
+fn invalid(flag: &str, value: &str, expected: &str) -> FlagError {
+    FlagError::InvalidValue {
+        flag: flag.to_string(),
+        value: value.to_string(),
+        expected: expected.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<InitConfig, FlagError> {
+        let argv: Vec<String> = std::iter::once("beam").chain(args.iter().copied()).map(String::from).collect();
+        let config = InitConfig {
+            no_schedulers: 4,
+            no_schedulers_online: 4,
+            ..InitConfig::default()
+        };
+        parse_emu_flags(&argv, config)
+    }
+
+    #[test]
+    fn test_emulator_flags() {
+        let config = parse(&[
+            "+P", "2048", "+Q4096", "+S", "8:2", "+SDcpu", "4:1", "+SDio", "5", "+t", "10000",
+            "+A", "0", "+K", "true", "+sbt", "tnnps", "+hms", "1000", "+hmbs", "2000", "+MMscs", "64",
+        ])
+        .unwrap();
+        assert_eq!(config.proc_tab_sz, 2048);
+        assert_eq!(config.port_tab_sz, 4096);
+        assert_eq!((config.no_schedulers, config.no_schedulers_online), (8, 2));
+        assert_eq!((config.no_dirty_cpu_schedulers, config.no_dirty_cpu_schedulers_online), (4, 1));
+        assert_eq!(config.no_dirty_io_schedulers, 5);
+        assert_eq!(config.atom_table_sz, 10000);
+        assert_eq!(config.async_threads, 0);
+        assert!(config.kernel_poll);
+        assert_eq!(config.scheduler_bind_type, SchedulerBindType::ThreadNoNodeProcessorSpread);
+        assert_eq!((config.min_heap_size, config.min_bin_vheap_size), (1000, 2000));
+        assert_eq!(config.super_carrier_mb, 64);
+
+        let config = parse(&["+S", ":3"]).unwrap();
+        assert_eq!((config.no_schedulers, config.no_schedulers_online), (4, 3));
+        let config = parse(&["+S", "2"]).unwrap();
+        assert_eq!((config.no_schedulers, config.no_schedulers_online), (2, 2));
+    }
+
+    #[test]
+    fn test_init_flags_and_extra() {
+        let config = parse(&[
+            "-root", "/otp", "-sname", "node", "-setcookie", "secret", "-boot", "start_clean",
+            "-mode", "interactive", "-extra", "+P", "-x", "plain",
+        ])
+        .unwrap();
+        assert_eq!(config.node_name, Some(NodeName { name: "node".to_string(), long: false }));
+        assert_eq!(config.cookie.as_deref(), Some("secret"));
+        assert_eq!(config.boot.as_deref(), Some("start_clean"));
+        assert_eq!(config.init_args, vec!["-root", "/otp", "-mode", "interactive"]);
+        assert_eq!(config.extra_args, vec!["+P", "-x", "plain"]);
+    }
+
+    #[test]
+    fn test_bad_flags() {
+        assert!(matches!(parse(&["+P", "10"]), Err(FlagError::InvalidValue { .. })));
+        assert!(matches!(parse(&["+P"]), Err(FlagError::MissingValue(_))));
+        assert!(matches!(parse(&["+S", "2:3"]), Err(FlagError::InvalidValue { .. })));
+        assert!(matches!(parse(&["+SDcpu", "8"]), Err(FlagError::Conflict(_))));
+        assert!(matches!(parse(&["+K", "yes"]), Err(FlagError::InvalidValue { .. })));
+        assert!(matches!(parse(&["+sbt", "xx"]), Err(FlagError::InvalidValue { .. })));
+        assert!(matches!(parse(&["+t", "100"]), Err(FlagError::InvalidValue { .. })));
+        assert!(matches!(parse(&["+Zz"]), Err(FlagError::UnknownFlag(_))));
+        assert!(matches!(parse(&["-name", "a", "-sname", "b"]), Err(FlagError::Conflict(_))));
+        assert!(matches!(parse(&["-setcookie"]), Err(FlagError::MissingValue(_))));
+        let err = parse(&["+A", "5000"]).unwrap_err();
+        assert_eq!(err.to_string(), "bad value '5000' for +A: expected an integer in 0..1024");
+    }
+}
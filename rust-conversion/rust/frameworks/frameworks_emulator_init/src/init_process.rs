@@ -0,0 +1,230 @@
+//! Init Process Module
+//!
+//! Provides the primitive init process: the first process of the system,
+//! registered as `init`, which holds the processed command line and under
+//! which the boot script is run.
+//!
+//! The command line is kept in the form init.erl gives it out:
+//! - init flags (`-Flag Value...`) are returned by [`InitProcess::get_argument`]
+//! - plain arguments, including everything after `-extra`, are returned by
+//!   [`InitProcess::get_plain_arguments`]
+//!
+//! Based on `erl_first_process_otp()` in erl_init.c and the argument
+//! handling of init.erl
+
+use std::sync::{Arc, RwLock};
+
+use entities_process::{ErtsCodePtr, InitialCall, Process, ProcessId};
+use infrastructure_emulator_loop::instruction_decoder::opcodes;
+use infrastructure_utilities::process_table::get_global_process_table;
+use usecases_scheduling::{get_global_schedulers, schedule_process, Priority};
+
+use crate::boot_script::{register_process_name, unregister_process_name};
+use crate::main_init::InitConfig;
+
+/// Registered name of the init process
+pub const INIT_NAME: &str = "init";
+
+/// The running init process
+static INIT_PROCESS: RwLock<Option<Arc<InitProcess>>> = RwLock::new(None);
+
+/// The init process and its command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InitProcess {
+    /// Process ID
+    pid: ProcessId,
+    /// Init flags in command line order, with their values
+    flags: Vec<(String, Vec<String>)>,
+    /// Plain arguments
+    plain_args: Vec<String>,
+}
+
+impl InitProcess {
+    /// Build the init command line from the initialization configuration
+    ///
+    /// `-name`/`-sname`, `-setcookie` and `-boot`, which the emulator consumes,
+    /// are given to init as flags as well.
+    ///
+    /// # Arguments
+    /// * `pid` - Process ID of the init process
+    /// * `config` - Initialization configuration with the parsed command line
+    pub fn new(pid: ProcessId, config: &InitConfig) -> Self {
+        let mut flags: Vec<(String, Vec<String>)> = Vec::new();
+        let mut plain_args = Vec::new();
+
+        if let Some(node_name) = &config.node_name {
+            let flag = if node_name.long { "name" } else { "sname" };
+            flags.push((flag.to_string(), vec![node_name.name.clone()]));
+        }
+        if let Some(cookie) = &config.cookie {
+            flags.push(("setcookie".to_string(), vec![cookie.clone()]));
+        }
+        if let Some(boot) = &config.boot {
+            flags.push(("boot".to_string(), vec![boot.clone()]));
+        }
+        // Values before the first init flag are plain arguments
+        let mut in_flag = false;
+        for arg in &config.init_args {
+            match (arg.strip_prefix('-'), flags.last_mut()) {
+                (Some(flag), _) => {
+                    flags.push((flag.to_string(), Vec::new()));
+                    in_flag = true;
+                }
+                (None, Some((_, values))) if in_flag => values.push(arg.clone()),
+                (None, _) => plain_args.push(arg.clone()),
+            }
+        }
+        plain_args.extend(config.extra_args.iter().cloned());
+
+        Self { pid, flags, plain_args }
+    }
+
+    /// Get the process ID
+    pub fn pid(&self) -> ProcessId {
+        self.pid
+    }
+
+    /// Get the values of an init flag (`init:get_argument/1`)
+    ///
+    /// # Arguments
+    /// * `flag` - Flag name without the leading `-`
+    ///
+    /// # Returns
+    /// One list of values per occurrence of the flag, or `None` if it was not given
+    pub fn get_argument(&self, flag: &str) -> Option<Vec<Vec<String>>> {
+        let values: Vec<Vec<String>> = self
+            .flags
+            .iter()
+            .filter(|(name, _)| name == flag)
+            .map(|(_, values)| values.clone())
+            .collect();
+        (!values.is_empty()).then_some(values)
+    }
+
+    /// Get all init flags in command line order (`init:get_arguments/0`)
+    pub fn get_arguments(&self) -> &[(String, Vec<String>)] {
+        &self.flags
+    }
+
+    /// Get the plain arguments (`init:get_plain_arguments/0`)
+    pub fn get_plain_arguments(&self) -> &[String] {
+        &self.plain_args
+    }
+}
+
+/// Start the init process
+///
+/// Creates the first process, registers it as `init` and schedules it if the
+/// schedulers are running. A previously started init process is replaced.
+///
+/// # Arguments
+/// * `config` - Initialization configuration with the parsed command line
+///
+/// # Returns
+/// The init process
+pub fn start_init_process(config: &InitConfig) -> Result<Arc<InitProcess>, String> {
+    let (pid, process) = get_global_process_table()
+        .new_element(|id| {
+            // init is created by the runtime itself, so it has no parent
+            let mut process = Process::spawned(id, None, InitialCall::new(INIT_NAME, "boot", 1));
+
+            // In the full implementation, the instruction pointer would be the
+            // entry of init:boot/1, called with the command line. Until then
+            // init runs a placeholder sequence: move x(0) x(1); return
+            let code: Vec<u64> = vec![opcodes::MOVE as u64, 0, 1, opcodes::RETURN as u64];
+            let code_ptr = code.as_ptr() as ErtsCodePtr;
+            std::mem::forget(code); // Keep code alive
+
+            process.set_i(code_ptr);
+            Arc::new(process)
+        })
+        .map_err(|e| format!("Failed to allocate init process: {:?}", e))?;
+
+    let init = Arc::new(InitProcess::new(pid, config));
+    let mut current = INIT_PROCESS.write().unwrap();
+    if current.take().is_some() {
+        unregister_process_name(INIT_NAME);
+    }
+    register_process_name(INIT_NAME, pid)?;
+    *current = Some(Arc::clone(&init));
+    drop(current);
+
+    schedule_init(process)?;
+
+    Ok(init)
+}
+
+/// Schedule the init process at maximum priority on the first scheduler
+///
+/// # Returns
+/// Whether the process was scheduled; it is not before the schedulers are
+/// initialized
+pub(crate) fn schedule_init(process: Arc<Process>) -> Result<bool, String> {
+    let Some(schedulers) = get_global_schedulers() else {
+        return Ok(false);
+    };
+    let schedulers_guard = schedulers.lock().unwrap();
+    let Some(scheduler) = schedulers_guard.first() else {
+        return Ok(false);
+    };
+    let runq = scheduler.runq();
+    let runq_guard = runq.lock().unwrap();
+    schedule_process(process, &runq_guard, Priority::Max)
+        .map_err(|e| format!("Failed to schedule init process: {:?}", e))?;
+    Ok(true)
+}
+
+/// Get the running init process
+pub fn get_init_process() -> Option<Arc<InitProcess>> {
+    INIT_PROCESS.read().unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emu_flags::NodeName;
+
+    #[test]
+    fn test_init_command_line() {
+        let config = InitConfig {
+            node_name: Some(NodeName { name: "a@host".to_string(), long: true }),
+            boot: Some("start_clean".to_string()),
+            init_args: ["first", "-mode", "embedded", "-pa", "/x", "/y", "-noshell", "-pa", "/z"]
+                .map(String::from)
+                .to_vec(),
+            extra_args: vec!["x".to_string(), "-y".to_string()],
+            ..InitConfig::default()
+        };
+        let init = InitProcess::new(7, &config);
+
+        assert_eq!(init.pid(), 7);
+        assert_eq!(init.get_argument("name"), Some(vec![vec!["a@host".to_string()]]));
+        assert_eq!(init.get_argument("boot"), Some(vec![vec!["start_clean".to_string()]]));
+        assert_eq!(init.get_argument("mode"), Some(vec![vec!["embedded".to_string()]]));
+        assert_eq!(
+            init.get_argument("pa"),
+            Some(vec![vec!["/x".to_string(), "/y".to_string()], vec!["/z".to_string()]])
+        );
+        assert_eq!(init.get_argument("noshell"), Some(vec![vec![]]));
+        assert_eq!(init.get_argument("sname"), None);
+        assert_eq!(init.get_plain_arguments(), ["first", "x", "-y"]);
+        assert_eq!(init.get_arguments().len(), 6);
+    }
+
+    #[test]
+    fn test_start_init_process() {
+        let config = InitConfig {
+            extra_args: vec!["plain".to_string()],
+            ..InitConfig::default()
+        };
+        let init = start_init_process(&config).unwrap();
+        assert_eq!(crate::boot_script::whereis(INIT_NAME), Some(init.pid()));
+        assert_eq!(get_init_process().unwrap().get_plain_arguments(), ["plain"]);
+
+        let process = get_global_process_table().lookup(init.pid()).unwrap();
+        assert_eq!(process.initial_call().map(|call| call.function.as_str()), Some("boot"));
+
+        let restarted = start_init_process(&config).unwrap();
+        assert_eq!(crate::boot_script::whereis(INIT_NAME), Some(restarted.pid()));
+    }
+}
@@ -18,8 +18,20 @@
 //! - **[`main_init`](main_init/index.html)**: Main initialization phase
 //!   (coordinates all component initialization)
 //!
+//! - **[`emu_flags`](emu_flags/index.html)**: Emulator flag parsing
+//!   (`+P`, `+S`, `-name`, `-extra`, ...) into the initialization configuration
+//!
 //! - **[`initialization`](initialization/index.html)**: Initialization state management
 //!
+//! - **[`boot_script`](boot_script/index.html)**: Boot script (`.boot`/`.script`)
+//!   loading and interpretation
+//!
+//! - **[`init_process`](init_process/index.html)**: The primitive init process
+//!   holding the processed command line
+//!
+//! - **[`self_hosted_boot`](self_hosted_boot/index.html)**: Boot from the preloaded OTP
+//!   modules by running `init:boot/1` (requires the `self_hosted_boot` feature)
+//!
 //! ## Architecture
 //!
 //! This crate is based on the C implementation in `erl_init.c`. It coordinates
@@ -52,11 +64,18 @@
 
 pub mod early_init;
 pub mod main_init;
+pub mod emu_flags;
 pub mod initialization;
 pub mod boot_script;
+pub mod init_process;
 pub mod env;
+#[cfg(feature = "self_hosted_boot")]
+pub mod self_hosted_boot;
 
 pub use early_init::{early_init, EarlyInitResult};
 pub use main_init::{erl_init, erl_start, InitConfig, TimeWarpMode};
+pub use emu_flags::{parse_emu_flags, FlagError, NodeName, SchedulerBindType};
+pub use boot_script::{execute_boot_script, load_boot_script, load_embedded_preloaded, register_boot_function, BootCommand, BootScript, BootScriptError};
+pub use init_process::{get_init_process, start_init_process, InitProcess};
 pub use initialization::{InitializationState, is_initialized, set_initialized};
 
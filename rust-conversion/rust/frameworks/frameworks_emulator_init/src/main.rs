@@ -100,7 +100,11 @@ fn main() {
     // 2. Load boot script
     // 3. Create init process
     // 4. Enter main execution loop (blocks until shutdown)
-    match frameworks_emulator_init::main_init::erl_start(&mut argc, &mut emulator_args) {
+    // A panic escaping the runtime is an unrecoverable error: leave a crash dump
+    let started = infrastructure_debugging::dump_on_panic(|| {
+        frameworks_emulator_init::main_init::erl_start(&mut argc, &mut emulator_args)
+    });
+    match started {
         Ok(()) => {
             // erl_start() returns after shutdown is complete
             process::exit(0);
@@ -3,6 +3,7 @@
 //! Provides main initialization phase functions.
 //! Based on `erl_init()` and `erl_start()` from erl_init.c
 
+use crate::emu_flags::{NodeName, SchedulerBindType};
 use crate::initialization::set_initialized;
 
 /// Initialization configuration
@@ -30,18 +31,34 @@ pub struct InitConfig {
     pub time_correction: i32,
     /// Time warp mode
     pub time_warp_mode: TimeWarpMode,
+    /// Atom table size
+    pub atom_table_sz: usize,
+    /// Number of async threads
+    pub async_threads: usize,
+    /// Kernel poll enabled
+    pub kernel_poll: bool,
+    /// Scheduler bind type
+    pub scheduler_bind_type: SchedulerBindType,
+    /// Default minimum heap size of processes, in words
+    pub min_heap_size: usize,
+    /// Default minimum binary virtual heap size of processes, in words
+    pub min_bin_vheap_size: usize,
+    /// Super carrier size in megabytes, 0 for none
+    pub super_carrier_mb: usize,
+    /// Node name (`-name` or `-sname`)
+    pub node_name: Option<NodeName>,
+    /// Magic cookie (`-setcookie`)
+    pub cookie: Option<String>,
+    /// Boot script (`-boot`)
+    pub boot: Option<String>,
+    /// Init flags passed on to init
+    pub init_args: Vec<String>,
+    /// Plain arguments after `-extra`
+    pub extra_args: Vec<String>,
 }
 
 /// Time warp mode
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum TimeWarpMode {
-    /// No time warp
-    NoTimeWarp,
-    /// Multi-time warp
-    MultiTimeWarp,
-    /// Single time warp
-    SingleTimeWarp,
-}
+pub use infrastructure_time_management::TimeWarpMode;
 
 impl Default for InitConfig {
     fn default() -> Self {
@@ -57,6 +74,18 @@ impl Default for InitConfig {
             no_dirty_io_schedulers: 0,
             time_correction: 0,
             time_warp_mode: TimeWarpMode::NoTimeWarp,
+            atom_table_sz: 1_048_576, // ATOM_LIMIT
+            async_threads: 1,
+            kernel_poll: false,
+            scheduler_bind_type: SchedulerBindType::Unbound,
+            min_heap_size: 233,        // H_DEFAULT_SIZE
+            min_bin_vheap_size: 46422, // VH_DEFAULT_SIZE
+            super_carrier_mb: 0,
+            node_name: None,
+            cookie: None,
+            boot: None,
+            init_args: Vec::new(),
+            extra_args: Vec::new(),
         }
     }
 }
@@ -74,6 +103,10 @@ impl Default for InitConfig {
 /// * `Ok(())` - Initialization successful
 /// * `Err(String)` - Initialization error
 pub fn erl_init(config: InitConfig) -> Result<(), String> {
+    // Initialize the clock; the first initialization fixes the time warp mode
+    // In C: erts_init_time_sup(time_correction, time_warp_mode);
+    infrastructure_time_management::init_time(config.time_warp_mode);
+
     // Initialize global literals
     // In C: init_global_literals();
     infrastructure_utilities::init_global_literals()
@@ -88,6 +121,17 @@ pub fn erl_init(config: InitConfig) -> Result<(), String> {
     )
     .map_err(|e| format!("Failed to initialize process management: {}", e))?;
     
+    // Apply the default minimum heap size of new processes (+hms)
+    // In C: H_MIN_SIZE = erts_next_heap_size(H_MIN_SIZE, 0);
+    entities_process::update_process_defaults(|defaults| defaults.min_heap_size = config.min_heap_size);
+    
+    // Reserve the super carrier (+MMscs)
+    // In C: erts_mmap_init(&erts_dflt_mmapper, &init);
+    if config.super_carrier_mb > 0 {
+        entities_system_integration_common::mmap::reserve_global_super_carrier(config.super_carrier_mb << 20)
+            .map_err(|e| format!("Failed to reserve super carrier: {}", e))?;
+    }
+    
     // Initialize scheduling
     // In C: erts_init_scheduling(no_schedulers, no_schedulers_online, no_poll_threads, 
     //                            no_dirty_cpu_schedulers, no_dirty_cpu_schedulers_online, no_dirty_io_schedulers)
@@ -158,7 +202,7 @@ pub fn erl_start(argc: &mut usize, argv: &mut Vec<String>) -> Result<(), String>
         .map_err(|e| format!("Early initialization failed: {}", e))?;
     
     // Build initialization configuration
-    let config = InitConfig {
+    let defaults = InitConfig {
         ncpu: early_result.ncpu,
         no_schedulers: early_result.no_schedulers,
         no_schedulers_online: early_result.no_schedulers_online,
@@ -169,56 +213,119 @@ pub fn erl_start(argc: &mut usize, argv: &mut Vec<String>) -> Result<(), String>
         ..Default::default()
     };
     
-    // Parse command line arguments for configuration overrides
-    // Extract boot script path from arguments
-    let boot_script = extract_boot_script(argv);
+    // Apply the emulator flags from the command line
+    let config = crate::emu_flags::parse_emu_flags(argv, defaults)
+        .map_err(|e| format!("Invalid command line: {}", e))?;
     
     // Perform main initialization
-    erl_init(config)
+    erl_init(config.clone())
         .map_err(|e| format!("Main initialization failed: {}", e))?;
     
-    // Step 1: Start scheduler threads
-    // In C: erts_start_schedulers()
+    // Step 1: Start the poll threads and scheduler threads
+    // In C: erts_init_check_io() and erts_start_schedulers()
+    let check_io = start_check_io(&config)
+        .map_err(|e| format!("Failed to start poll threads: {}", e))?;
     let scheduler_handles = usecases_scheduling::erts_start_schedulers()
         .map_err(|e| format!("Failed to start scheduler threads: {}", e))?;
     
-    // Step 2: Load boot script (if specified)
-    // The boot script is loaded and executed here, before the init process starts
-    // In the full implementation, the init process would execute the boot script
+    // Step 2: Create init process and run the boot script
+    // In C: This is done by erl_first_process() which creates the init process
+    // The init process then loads the boot script and starts the shell
     use crate::env;
     let (rootdir, bindir) = env::determine_paths().unwrap_or_else(|_| (String::new(), String::new()));
-    if let Some(boot_path) = boot_script {
-        if let Err(e) = load_boot_script(&boot_path, &rootdir, &bindir) {
-            eprintln!("Warning: {}", e);
-            eprintln!("Continuing without boot script (some features may not work)");
+    #[cfg(feature = "self_hosted_boot")]
+    match crate::self_hosted_boot::self_hosted_boot(&rootdir, &bindir, &config) {
+        Ok(outcome) => register_standard_io(outcome.init_pid),
+        Err(e) => {
+            eprintln!("Warning: self-hosted boot failed: {}", e);
+            eprintln!("Falling back to the placeholder init process");
+            placeholder_boot(&config, &rootdir, &bindir)?;
         }
     }
+    #[cfg(not(feature = "self_hosted_boot"))]
+    placeholder_boot(&config, &rootdir, &bindir)?;
     
-    // Step 3: Create init process and start Erlang shell
-    // In C: This is done by erl_first_process() which creates the init process
-    // The init process then loads the boot script and starts the shell
-    create_init_process()
-        .map_err(|e| format!("Failed to create init process: {}", e))?;
-    
-    // Step 4: Enter main execution loop (block until shutdown)
+    // Step 3: Enter main execution loop (block until shutdown)
     // In C: erts_sys_main_thread() - the main thread enters a loop or waits
     // The scheduler threads are already running, so we just need to wait
     // For now, we'll wait for a shutdown signal or until schedulers stop
     wait_for_shutdown(scheduler_handles);
+    check_io.stop_poll_threads();
     
     Ok(())
 }
 
-/// Extract boot script path from command line arguments
-fn extract_boot_script(argv: &[String]) -> Option<String> {
-    for (i, arg) in argv.iter().enumerate() {
-        if arg == "--boot" || arg == "-boot" {
-            if i + 1 < argv.len() {
-                return Some(argv[i + 1].clone());
-            }
+/// Boot with the placeholder init process
+///
+/// Creates the init process, loads the embedded preloaded modules and runs
+/// the boot script given with `-boot`. This is the boot path without the
+/// `self_hosted_boot` feature and its fallback when the self-hosted boot
+/// fails.
+fn placeholder_boot(config: &InitConfig, rootdir: &str, bindir: &str) -> Result<(), String> {
+    let init = crate::init_process::start_init_process(config)
+        .map_err(|e| format!("Failed to create init process: {}", e))?;
+    eprintln!("Init process created and scheduled (PID: {})", init.pid());
+    register_standard_io(init.pid());
+    
+    // The preloaded modules are needed to read and interpret the boot script
+    match crate::boot_script::load_embedded_preloaded() {
+        Ok(modules) => eprintln!("Loaded {} preloaded modules", modules.len()),
+        Err(e) => eprintln!("Warning: failed to load preloaded modules: {}", e),
+    }
+    
+    // init interprets the boot script given with -boot
+    if let Some(boot_path) = &config.boot {
+        if let Err(e) = load_boot_script(boot_path, rootdir, bindir) {
+            eprintln!("Warning: {}", e);
+            eprintln!("Continuing without boot script (some features may not work)");
         }
     }
-    None
+    Ok(())
+}
+
+/// Create the check I/O manager and start its poll threads
+///
+/// The poll threads apply the `driver_select` requests of port drivers.
+/// A readable fd becomes an input task of its port, executed by the
+/// scheduler the port is bound to; before the schedulers run, the driver's
+/// `ready_input` is called directly.
+fn start_check_io(config: &InitConfig) -> Result<adapters_nif_io::CheckIo, String> {
+    use adapters_nif_io::{CheckIo, CheckIoConfig};
+    use infrastructure_driver_api::{get_global_port_table, get_global_select_table};
+    use usecases_scheduling::{erts_schedule_port_task, PortTask, PortTaskType};
+    
+    let check_io = CheckIo::with_config(CheckIoConfig {
+        num_pollsets: config.no_poll_threads,
+        num_poll_threads: config.no_poll_threads,
+        ..Default::default()
+    });
+    check_io.set_driver_select(
+        get_global_select_table(),
+        std::sync::Arc::new(|port, event| {
+            let task = PortTask::with_event(PortTaskType::Input, event);
+            if erts_schedule_port_task(port, task).is_err() {
+                if let Some(port) = get_global_port_table().lookup(port) {
+                    port.ready_input(event);
+                }
+            }
+        }),
+    );
+    check_io.start_poll_threads().map_err(|e| format!("{:?}", e))?;
+    Ok(check_io)
+}
+
+/// Make the standard output port the I/O device of init, the group leader
+/// of the processes it starts
+///
+/// The simple REPL reads standard input itself, so the port is opened for
+/// output only.
+fn register_standard_io(init_pid: u64) {
+    #[cfg(unix)]
+    if let Err(e) = usecases_bifs::io::IoBif::register_standard_output(init_pid) {
+        eprintln!("Warning: failed to open the standard I/O port: {}", e);
+    }
+    #[cfg(not(unix))]
+    let _ = init_pid;
 }
 
 /// Load boot script
@@ -237,89 +344,12 @@ fn load_boot_script(boot_path: &str, rootdir: &str, bindir: &str) -> Result<(),
         .map_err(|e| format!("Failed to load boot script: {}", e))?;
     
     // Execute boot script commands
-    boot_script::execute_boot_script(&script)
+    boot_script::execute_boot_script(&script, rootdir)
         .map_err(|e| format!("Failed to execute boot script: {}", e))?;
     
     Ok(())
 }
 
-/// Create init process
-///
-/// Based on erl_first_process() from erl_init.c
-///
-/// Creates the first Erlang process (init process) which will:
-/// 1. Load the boot script
-/// 2. Start kernel processes
-/// 3. Start the Erlang shell
-fn create_init_process() -> Result<(), String> {
-    use entities_process::Process;
-    use infrastructure_utilities::process_table::get_global_process_table;
-    use std::sync::Arc;
-    
-    // In the full implementation, this would:
-    // 1. Create a new process with special init process code
-    // 2. Set up the process with init:boot/1 function
-    // 3. Add the process to the process table
-    // 4. Schedule the process on a scheduler
-    
-    let process_table = get_global_process_table();
-    
-    // Create a placeholder init process
-    // In the real implementation, this would be a special system process
-    // with the init module's boot function
-    let mut init_process = Process::new(1); // PID 1 is typically the init process
-    
-    // For testing, create a simple test code sequence
-    // In the full implementation, this would load actual BEAM code
-    use entities_process::ErtsCodePtr;
-    use infrastructure_emulator_loop::instruction_decoder::opcodes;
-    let mut test_code = Vec::new();
-    
-    // move x(0) x(1)
-    test_code.push(opcodes::MOVE as u64);
-    test_code.push(0); // src = x(0)
-    test_code.push(1); // dst = x(1)
-    
-    // return
-    test_code.push(opcodes::RETURN as u64);
-    
-    // Set instruction pointer to point to our test code
-    // Note: In production, code would be in a code area that persists
-    // For now, we'll leak the memory (not ideal, but works for testing)
-    let code_ptr = test_code.as_ptr() as ErtsCodePtr;
-    std::mem::forget(test_code); // Keep code alive
-    
-    init_process.set_i(code_ptr);
-    
-    let init_process = Arc::new(init_process);
-    
-    // Insert into process table
-    // insert() returns Option<Arc<Process>> (the old process if replaced)
-    // We don't care about the old value, just that it succeeded
-    let _old_process = process_table.insert(1, init_process.clone());
-    
-    // Schedule the init process
-    // In the full implementation, we would:
-    // 1. Get a scheduler
-    // 2. Enqueue the init process to the scheduler's run queue
-    // 3. Wake the scheduler if it's sleeping
-    use usecases_scheduling::{get_global_schedulers, schedule_process, Priority};
-    
-    if let Some(schedulers) = get_global_schedulers() {
-        let schedulers_guard = schedulers.lock().unwrap();
-        if let Some(scheduler) = schedulers_guard.first() {
-            let runq = scheduler.runq();
-            let runq_guard = runq.lock().unwrap();
-            schedule_process(init_process.clone(), &runq_guard, Priority::Max)
-                .map_err(|e| format!("Failed to schedule init process: {:?}", e))?;
-        }
-    }
-    
-    eprintln!("Init process created and scheduled");
-    
-    Ok(())
-}
-
 /// Wait for shutdown signal
 ///
 /// Blocks the main thread until the emulator is shut down.
@@ -570,6 +600,9 @@ mod tests {
         let config = InitConfig::default();
         assert_eq!(config.ncpu, 1);
         assert_eq!(config.proc_tab_sz, 1_048_576);
+        assert_eq!(config.min_heap_size, 233);
+        assert_eq!(config.scheduler_bind_type, SchedulerBindType::Unbound);
+        assert!(config.node_name.is_none());
     }
     
     #[test]
@@ -0,0 +1,547 @@
+//! Self-Hosted Boot Module
+//!
+//! Boots the emulator from the real preloaded OTP modules instead of the
+//! placeholder init process created by `main_init`. Booting happens in two
+//! phases:
+//!
+//! 1. **Load**: read `erl_prim_loader`, `init`, `prim_file` and `prim_inet`
+//!    from the preloaded directory and load them through [`BeamLoader`].
+//! 2. **Run**: spawn init at the exported `init:boot/1` label with the boot
+//!    arguments in x(0), schedule it through the run queue and wait until it
+//!    registers itself as `init`, the first thing `init:boot/1` does.
+//!
+//! This module is only compiled with the `self_hosted_boot` feature.
+//!
+//! **Scope**: the loader copies the compact-encoded Code chunk as is and the
+//! emulator cannot execute it yet, so init never gets to register itself and
+//! the boot ends in [`SelfHostedBootError::BootTimeout`]. `main_init` then
+//! falls back to the placeholder init process and the embedded boot script.
+//! The boot succeeds once the loader transforms the code into instructions
+//! the emulator runs.
+//!
+//! Based on `erl_first_process_otp()` from erl_init.c and the preloaded
+//! module handling in `erl_prim_loader`.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use code_management_code_loading::{
+    get_global_module_manager, BeamFile, BeamFileReadResult, BeamLoadError, BeamLoader,
+};
+use entities_data_handling::AtomEncoding;
+use entities_process::term_tags::{make_boxed, make_list, HEADER_ARITY_OFFS, HEAP_BINARY_SUBTAG, NIL};
+use entities_process::{ErtsCodePtr, Eterm, InitialCall, Process, ProcessId};
+use infrastructure_utilities::atom_table::get_global_atom_table;
+use infrastructure_utilities::process_table::get_global_process_table;
+use usecases_scheduling::erts_schedulers_running;
+
+use crate::boot_script::whereis;
+use crate::init_process::{schedule_init, INIT_NAME};
+use crate::main_init::InitConfig;
+
+/// Preloaded modules required to run `init:boot/1`, in load order
+pub const PRELOADED_MODULES: [&str; 4] = ["erl_prim_loader", "init", "prim_file", "prim_inet"];
+
+/// Environment variable overriding the preloaded module directory
+pub const PRELOADED_DIR_ENV: &str = "ERL_PRELOADED_DIR";
+
+/// Time init is given to register itself before the boot fails
+pub const DEFAULT_BOOT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A preloaded module that has been read and loaded
+#[derive(Debug, Clone)]
+pub struct PreloadedModule {
+    /// Module name
+    pub name: String,
+    /// Module atom index used in the module table
+    pub atom: u32,
+    /// Parsed BEAM file
+    pub beam: BeamFile,
+}
+
+/// Result of a self-hosted boot
+#[derive(Debug, Clone)]
+pub struct BootOutcome {
+    /// Process ID of the init process
+    pub init_pid: ProcessId,
+    /// Time from scheduling init until it registered itself
+    pub elapsed: Duration,
+}
+
+/// Self-hosted boot errors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelfHostedBootError {
+    /// Preloaded module directory does not exist
+    PreloadedDirNotFound(String),
+    /// A preloaded module file could not be read
+    Io { module: String, message: String },
+    /// A preloaded module file is not a valid BEAM file
+    InvalidBeam { module: String, reason: BeamFileReadResult },
+    /// The BEAM file contains a different module than expected
+    ModuleNameMismatch { expected: String, found: Option<String> },
+    /// Loading the module into the module table failed
+    Load { module: String, error: BeamLoadError },
+    /// The boot entry point is not exported
+    MissingEntryPoint { module: String, function: String, arity: u32 },
+    /// The loader has not produced executable code for the entry point
+    EntryNotExecutable { module: String, function: String, arity: u32 },
+    /// The init process could not be created or scheduled
+    Spawn(String),
+    /// init terminated before registering itself
+    InitExited,
+    /// init did not register itself in time
+    BootTimeout(Duration),
+}
+
+impl std::fmt::Display for SelfHostedBootError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SelfHostedBootError::PreloadedDirNotFound(dir) => {
+                write!(f, "Preloaded module directory not found: {}", dir)
+            }
+            SelfHostedBootError::Io { module, message } => {
+                write!(f, "Failed to read preloaded module {}: {}", module, message)
+            }
+            SelfHostedBootError::InvalidBeam { module, reason } => {
+                write!(f, "Invalid BEAM file for {}: {:?}", module, reason)
+            }
+            SelfHostedBootError::ModuleNameMismatch { expected, found } => {
+                write!(f, "Expected module {}, found {:?}", expected, found)
+            }
+            SelfHostedBootError::Load { module, error } => {
+                write!(f, "Failed to load {}: {:?}", module, error)
+            }
+            SelfHostedBootError::MissingEntryPoint { module, function, arity } => {
+                write!(f, "{}:{}/{} is not exported", module, function, arity)
+            }
+            SelfHostedBootError::EntryNotExecutable { module, function, arity } => {
+                write!(f, "No executable code for {}:{}/{}", module, function, arity)
+            }
+            SelfHostedBootError::Spawn(msg) => write!(f, "Failed to spawn init: {}", msg),
+            SelfHostedBootError::InitExited => write!(f, "init exited before registering itself"),
+            SelfHostedBootError::BootTimeout(timeout) => {
+                write!(f, "init did not register itself within {:?}", timeout)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SelfHostedBootError {}
+
+/// Determine the directory holding the preloaded BEAM files
+///
+/// Uses `ERL_PRELOADED_DIR` if set, otherwise `<rootdir>/erts/preloaded/ebin`.
+pub fn preloaded_dir(rootdir: &str) -> PathBuf {
+    match std::env::var(PRELOADED_DIR_ENV) {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => Path::new(rootdir).join("erts").join("preloaded").join("ebin"),
+    }
+}
+
+/// Phase 1: load the preloaded modules
+///
+/// Reads each module in [`PRELOADED_MODULES`] from `dir`, checks that the
+/// file contains the expected module, and loads it into the global module
+/// table under the atom of its name.
+///
+/// # Arguments
+/// * `dir` - Directory containing `<module>.beam` files
+///
+/// # Returns
+/// The loaded modules, in load order
+pub fn load_preloaded_modules(dir: &Path) -> Result<Vec<PreloadedModule>, SelfHostedBootError> {
+    if !dir.is_dir() {
+        return Err(SelfHostedBootError::PreloadedDirNotFound(dir.display().to_string()));
+    }
+
+    BeamLoader::init_load();
+    let module_manager = get_global_module_manager();
+    let atom_table = get_global_atom_table();
+
+    let mut loaded = Vec::with_capacity(PRELOADED_MODULES.len());
+    for name in PRELOADED_MODULES {
+        let path = dir.join(format!("{}.beam", name));
+        let code = std::fs::read(&path).map_err(|e| SelfHostedBootError::Io {
+            module: name.to_string(),
+            message: e.to_string(),
+        })?;
+
+        let beam = BeamLoader::prepare_loading(&code, None).map_err(|reason| {
+            SelfHostedBootError::InvalidBeam { module: name.to_string(), reason }
+        })?;
+        if beam.module_name() != Some(name) {
+            return Err(SelfHostedBootError::ModuleNameMismatch {
+                expected: name.to_string(),
+                found: beam.module_name().map(str::to_string),
+            });
+        }
+
+        let atom = atom_table
+            .put_index(name.as_bytes(), AtomEncoding::SevenBitAscii, false)
+            .expect("preloaded module names are valid atoms") as u32;
+        BeamLoader::finish_loading(&beam, atom, module_manager).map_err(|error| {
+            SelfHostedBootError::Load { module: name.to_string(), error }
+        })?;
+
+        loaded.push(PreloadedModule { name: name.to_string(), atom, beam });
+    }
+
+    Ok(loaded)
+}
+
+/// Resolve the executable entry point of `init:boot/1`
+///
+/// The entry is the `label` instruction of the exported label, located in
+/// the code the loader committed for `init`.
+///
+/// # Returns
+/// Code pointer of the entry, or an error if `init` is missing, does not
+/// export `boot/1`, or has no executable code for it
+pub fn resolve_init_entry(modules: &[PreloadedModule]) -> Result<ErtsCodePtr, SelfHostedBootError> {
+    let missing = || SelfHostedBootError::MissingEntryPoint {
+        module: INIT_NAME.to_string(),
+        function: "boot".to_string(),
+        arity: 1,
+    };
+    let not_executable = || SelfHostedBootError::EntryNotExecutable {
+        module: INIT_NAME.to_string(),
+        function: "boot".to_string(),
+        arity: 1,
+    };
+    let init = modules.iter().find(|m| m.name == INIT_NAME).ok_or_else(missing)?;
+    let label = init.beam.find_export("boot", 1).ok_or_else(missing)?;
+    let offset = init.beam.find_label(label).ok_or_else(not_executable)?;
+
+    let module_manager = get_global_module_manager();
+    let code_ix = code_management_code_loading::get_global_code_ix().active_code_ix() as usize;
+    let module = module_manager.get_table(code_ix).get_module(init.atom).ok_or_else(not_executable)?;
+    match module.curr.executable_region {
+        Some(region) if offset < module.curr.code_length as usize => {
+            // The offset lies within the committed code
+            Ok(unsafe { (region as ErtsCodePtr).add(offset) })
+        }
+        _ => Err(not_executable()),
+    }
+}
+
+/// Build the arguments `init:boot/1` is called with
+///
+/// Like erlexec, the root and bin directories and the program name come
+/// first, followed by the init flags the emulator consumed, the remaining
+/// init flags and, after `-extra`, the extra arguments.
+///
+/// # Arguments
+/// * `rootdir` - OTP root directory
+/// * `bindir` - Directory of the emulator binaries
+/// * `config` - Initialization configuration with the parsed command line
+pub fn boot_args(rootdir: &str, bindir: &str, config: &InitConfig) -> Vec<String> {
+    let mut args: Vec<String> =
+        ["-root", rootdir, "-bindir", bindir, "-progname", "erl"].map(String::from).to_vec();
+    if let Some(node_name) = &config.node_name {
+        args.push(if node_name.long { "-name" } else { "-sname" }.to_string());
+        args.push(node_name.name.clone());
+    }
+    if let Some(cookie) = &config.cookie {
+        args.extend(["-setcookie".to_string(), cookie.clone()]);
+    }
+    if let Some(boot) = &config.boot {
+        args.extend(["-boot".to_string(), boot.clone()]);
+    }
+    args.extend(config.init_args.iter().cloned());
+    if !config.extra_args.is_empty() {
+        args.push("-extra".to_string());
+        args.extend(config.extra_args.iter().cloned());
+    }
+    args
+}
+
+/// Create the init process at `entry` with the boot arguments in x(0)
+///
+/// The arguments are a list of binaries, as built by
+/// `erl_first_process_otp()`. The process is inserted in the process table
+/// but not scheduled.
+///
+/// # Arguments
+/// * `entry` - Entry point of `init:boot/1`
+/// * `boot_args` - Arguments of `init:boot/1`
+pub fn spawn_init(
+    entry: ErtsCodePtr,
+    boot_args: &[String],
+) -> Result<(ProcessId, Arc<Process>), SelfHostedBootError> {
+    if entry.is_null() {
+        return Err(SelfHostedBootError::Spawn("null entry point".to_string()));
+    }
+
+    let process_table = get_global_process_table();
+    let (pid, process) = process_table
+        .new_element(|id| {
+            // init is created by the runtime itself, so it has no parent
+            let mut process = Process::spawned(id, None, InitialCall::new(INIT_NAME, "boot", 1));
+            process.set_i(entry);
+            process.set_arity(1);
+            Arc::new(process)
+        })
+        .map_err(|e| SelfHostedBootError::Spawn(format!("{:?}", e)))?;
+
+    if put_boot_args(&process, boot_args).is_none() {
+        process_table.remove(pid);
+        return Err(SelfHostedBootError::Spawn(
+            "boot arguments do not fit on the heap".to_string(),
+        ));
+    }
+    Ok((pid, process))
+}
+
+/// Put the boot arguments in x(0)
+///
+/// Until the process is first scheduled, its argument registers are the
+/// first words of its heap, so x(0) is allocated before the list.
+fn put_boot_args(process: &Process, args: &[String]) -> Option<()> {
+    let x0 = process.allocate_heap_words(1)?;
+    if x0 != process.heap_start_index() {
+        return None;
+    }
+    let mut list = NIL;
+    for arg in args.iter().rev() {
+        let binary = heap_binary(process, arg.as_bytes())?;
+        let cell = process.allocate_heap_words(2)?;
+        let mut heap = process.heap_slice_mut();
+        heap[cell] = binary;
+        heap[cell + 1] = list;
+        list = make_list(cell);
+    }
+    process.heap_slice_mut()[x0] = list;
+    Some(())
+}
+
+/// Build a heap binary: its header, its size in bytes and the bytes,
+/// packed in words
+fn heap_binary(process: &Process, bytes: &[u8]) -> Option<Eterm> {
+    let data_words = bytes.len().div_ceil(8);
+    let index = process.allocate_heap_words(2 + data_words)?;
+    let mut heap = process.heap_slice_mut();
+    heap[index] = ((1 + data_words as Eterm) << HEADER_ARITY_OFFS) | HEAP_BINARY_SUBTAG;
+    heap[index + 1] = bytes.len() as Eterm;
+    for (word, chunk) in heap[index + 2..].iter_mut().zip(bytes.chunks(8)) {
+        let mut buf = [0u8; 8];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        *word = Eterm::from_ne_bytes(buf);
+    }
+    Some(make_boxed(index))
+}
+
+/// Wait until init has registered itself as `init`
+///
+/// # Arguments
+/// * `pid` - Process ID of the init process
+/// * `timeout` - Time init is given to register itself
+///
+/// # Returns
+/// The time init took to register itself, or an error if it exited first
+/// or did not register in time
+pub fn wait_for_start(pid: ProcessId, timeout: Duration) -> Result<Duration, SelfHostedBootError> {
+    let start = Instant::now();
+    loop {
+        if whereis(INIT_NAME) == Some(pid) {
+            return Ok(start.elapsed());
+        }
+        if get_global_process_table().lookup(pid).is_none() {
+            return Err(SelfHostedBootError::InitExited);
+        }
+        if start.elapsed() >= timeout {
+            return Err(SelfHostedBootError::BootTimeout(timeout));
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+/// Phase 2: run init from its entry point
+///
+/// Spawns init with `boot_args`, schedules it through the run queue of the
+/// first scheduler and waits until it has registered itself. An init that
+/// does not register in time is removed from the process table.
+///
+/// # Arguments
+/// * `entry` - Entry point of `init:boot/1`
+/// * `boot_args` - Arguments of `init:boot/1`
+/// * `timeout` - Time init is given to register itself
+pub fn run_init(
+    entry: ErtsCodePtr,
+    boot_args: &[String],
+    timeout: Duration,
+) -> Result<BootOutcome, SelfHostedBootError> {
+    if !erts_schedulers_running() {
+        return Err(SelfHostedBootError::Spawn("the schedulers are not running".to_string()));
+    }
+
+    let (pid, process) = spawn_init(entry, boot_args)?;
+    match schedule_init(process) {
+        Ok(true) => {}
+        Ok(false) => {
+            get_global_process_table().remove(pid);
+            return Err(SelfHostedBootError::Spawn("no scheduler to run init".to_string()));
+        }
+        Err(e) => {
+            get_global_process_table().remove(pid);
+            return Err(SelfHostedBootError::Spawn(e));
+        }
+    }
+
+    match wait_for_start(pid, timeout) {
+        Ok(elapsed) => Ok(BootOutcome { init_pid: pid, elapsed }),
+        Err(e) => {
+            get_global_process_table().remove(pid);
+            Err(e)
+        }
+    }
+}
+
+/// Boot the system from the preloaded OTP modules
+///
+/// Runs both phases: loads the preloaded modules from [`preloaded_dir`] and
+/// runs `init:boot/1` with the command line until init has registered
+/// itself, waiting at most [`DEFAULT_BOOT_TIMEOUT`].
+///
+/// # Arguments
+/// * `rootdir` - OTP root directory
+/// * `bindir` - Directory of the emulator binaries
+/// * `config` - Initialization configuration with the parsed command line
+pub fn self_hosted_boot(
+    rootdir: &str,
+    bindir: &str,
+    config: &InitConfig,
+) -> Result<BootOutcome, SelfHostedBootError> {
+    let modules = load_preloaded_modules(&preloaded_dir(rootdir))?;
+    let entry = resolve_init_entry(&modules)?;
+    run_init(entry, &boot_args(rootdir, bindir, config), DEFAULT_BOOT_TIMEOUT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entities_process::term_tags::pointer_index;
+
+    /// Read back a list of heap binaries
+    fn heap_strings(process: &Process, mut list: Eterm) -> Vec<String> {
+        let heap = process.heap_slice();
+        let mut strings = Vec::new();
+        while list != NIL {
+            let cell = pointer_index(list);
+            let binary = pointer_index(heap[cell]);
+            assert_eq!(heap[binary] & 0x3F, HEAP_BINARY_SUBTAG);
+            let len = heap[binary + 1] as usize;
+            let words = (heap[binary] >> HEADER_ARITY_OFFS) as usize - 1;
+            let bytes: Vec<u8> = heap[binary + 2..binary + 2 + words]
+                .iter()
+                .flat_map(|word| word.to_ne_bytes())
+                .take(len)
+                .collect();
+            strings.push(String::from_utf8(bytes).unwrap());
+            list = heap[cell + 1];
+        }
+        strings
+    }
+
+    #[test]
+    fn test_preloaded_dir_default() {
+        if std::env::var(PRELOADED_DIR_ENV).is_err() {
+            assert_eq!(
+                preloaded_dir("/otp"),
+                PathBuf::from("/otp/erts/preloaded/ebin")
+            );
+        }
+    }
+
+    #[test]
+    fn test_load_missing_dir() {
+        let result = load_preloaded_modules(Path::new("/nonexistent/preloaded"));
+        assert!(matches!(result, Err(SelfHostedBootError::PreloadedDirNotFound(_))));
+    }
+
+    #[test]
+    fn test_resolve_entry_without_init() {
+        let result = resolve_init_entry(&[]);
+        assert!(matches!(result, Err(SelfHostedBootError::MissingEntryPoint { .. })));
+    }
+
+    #[test]
+    fn test_boot_args() {
+        let config = InitConfig {
+            boot: Some("start_clean".to_string()),
+            init_args: vec!["-noshell".to_string()],
+            extra_args: vec!["x".to_string()],
+            ..InitConfig::default()
+        };
+        assert_eq!(
+            boot_args("/otp", "/otp/bin", &config),
+            ["-root", "/otp", "-bindir", "/otp/bin", "-progname", "erl", "-boot", "start_clean", "-noshell", "-extra", "x"]
+        );
+    }
+
+    #[test]
+    fn test_spawn_init_puts_boot_args_in_x0() {
+        let code: &'static [u8] = Box::leak(vec![0u8; 8].into_boxed_slice());
+        let args = ["-root".to_string(), "/usr/lib/erlang".to_string(), String::new()];
+        let (pid, process) = spawn_init(code.as_ptr(), &args).unwrap();
+
+        assert_eq!(process.i(), code.as_ptr());
+        assert_eq!(process.arity(), 1);
+        let x0 = process.heap_slice()[process.heap_start_index()];
+        assert_eq!(heap_strings(&process, x0), args);
+        assert_eq!(
+            process.initial_call().map(|call| call.function.as_str()),
+            Some("boot")
+        );
+        get_global_process_table().remove(pid);
+    }
+
+    #[test]
+    fn test_spawn_init_null_entry() {
+        let result = spawn_init(std::ptr::null(), &[]);
+        assert!(matches!(result, Err(SelfHostedBootError::Spawn(_))));
+    }
+
+    #[test]
+    fn test_wait_for_start() {
+        let process_table = get_global_process_table();
+        let (pid, _) = process_table.new_element(|id| Arc::new(Process::new(id))).unwrap();
+        assert_eq!(
+            wait_for_start(pid, Duration::from_millis(10)),
+            Err(SelfHostedBootError::BootTimeout(Duration::from_millis(10)))
+        );
+
+        process_table.remove(pid);
+        assert_eq!(
+            wait_for_start(pid, Duration::from_millis(10)),
+            Err(SelfHostedBootError::InitExited)
+        );
+    }
+
+    /// Smoke test against a real OTP installation
+    ///
+    /// Run with `cargo test --features self_hosted_boot -- --ignored` and
+    /// `ERL_PRELOADED_DIR` set to a directory holding the compiled preloaded
+    /// modules. Boot must reach init registering itself, which it cannot do
+    /// until the emulator executes loaded code (see the module docs).
+    #[test]
+    #[ignore = "needs the compiled preloaded modules in ERL_PRELOADED_DIR"]
+    fn test_self_hosted_boot_smoke() {
+        use usecases_scheduling::{erts_init_scheduling, erts_start_schedulers, erts_stop_schedulers};
+
+        let dir = std::env::var(PRELOADED_DIR_ENV)
+            .unwrap_or_else(|_| panic!("{} must be set", PRELOADED_DIR_ENV));
+        let modules = load_preloaded_modules(Path::new(&dir)).expect("preloaded modules load");
+        assert_eq!(modules.len(), PRELOADED_MODULES.len());
+        let entry = resolve_init_entry(&modules).expect("executable code for init:boot/1");
+
+        let _ = erts_init_scheduling(1, 1, 0, 0, 0, 0);
+        let handles = erts_start_schedulers().expect("schedulers start");
+        let args = boot_args("/otp", "/otp/bin", &InitConfig::default());
+        let outcome = run_init(entry, &args, DEFAULT_BOOT_TIMEOUT);
+        erts_stop_schedulers(handles);
+
+        let outcome = outcome.expect("init registers itself");
+        assert_eq!(whereis(INIT_NAME), Some(outcome.init_pid));
+    }
+}
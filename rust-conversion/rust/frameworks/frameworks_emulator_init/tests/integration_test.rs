@@ -46,6 +46,7 @@ fn test_init_config_custom() {
         no_dirty_io_schedulers: 1,
         time_correction: 1,
         time_warp_mode: TimeWarpMode::MultiTimeWarp,
+        ..Default::default()
     };
     
     assert_eq!(config.ncpu, 4);
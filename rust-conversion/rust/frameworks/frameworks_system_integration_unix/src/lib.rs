@@ -19,6 +19,10 @@
 //! - **[`sys_integration`](sys_integration/index.html)**: Unix system integration framework
 //!   providing environment variable handling and other Unix-specific operations
 //!
+//! - **[`signal_dispatcher`](signal_dispatcher/index.html)**: Installs the signal dispositions
+//!   set with `os:set_signal/2` and delivers handled signals as `{notify, Signal}` messages
+//!   to the registered signal server process
+//!
 //! ## Architecture
 //!
 //! This crate is based on the C implementation in `sys_env.c` and related Unix system files.
@@ -32,9 +36,17 @@
 #[cfg(unix)]
 pub mod sys_integration;
 
+#[cfg(unix)]
+pub mod signal_dispatcher;
+
 #[cfg(unix)]
 pub use sys_integration::{SysIntegration, SysError};
 
+#[cfg(unix)]
+pub use signal_dispatcher::{
+    SignalDispatcher, start_signal_dispatcher, get_global_signal_dispatcher, notify_message,
+};
+
 #[cfg(not(unix))]
 /// Unix-specific functionality is only available on Unix systems
 pub fn unix_only() {
@@ -0,0 +1,317 @@
+//! Signal Dispatcher Module (Unix-specific)
+//!
+//! Provides the OS side of the signal service in `infrastructure_utilities`:
+//! installing the dispositions set with `os:set_signal/2`, and a dedicated
+//! thread that turns handled signals into `{notify, Signal}` messages for the
+//! registered receiver, the process running `erl_signal_server`. Based on the
+//! signal handling and the signal dispatcher thread in sys.c.
+//!
+//! ## Delivery
+//!
+//! The handler of a handled signal only records it in the signal service and
+//! writes a byte to a pipe; both are async-signal-safe. The dispatcher thread
+//! blocks reading the pipe, takes the pending signals and sends one message
+//! per signal to the receiver. Signals arriving while no receiver is
+//! registered are dropped.
+//!
+//! The handlers are installed with `sigaction` rather than waiting for the
+//! signals with `sigwait` or `signalfd`: those need the signals blocked in
+//! every thread, which cannot be arranged for threads that already exist
+//! when `os:set_signal/2` is called.
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ *
+ * Creation productivity increased for code in this file by using AALang and GAB.
+ * See https://github.com/yenrab/AALang-Gab
+ */
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::os::fd::FromRawFd;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+
+use entities_data_handling::AtomEncoding;
+use entities_process::term_tags::{make_arityval, make_atom};
+use entities_process::{Eterm, Message, ProcessId, FRAGMENT_ROOT};
+use infrastructure_utilities::atom_table::get_global_atom_table;
+use infrastructure_utilities::process_table::get_global_process_table;
+use infrastructure_utilities::signals::{get_global_signal_service, Signal, SignalAction};
+
+/// Header of a 2-tuple
+const ARITYVAL_2: Eterm = make_arityval(2);
+
+/// Write end of the notification pipe, or -1 before the dispatcher starts
+static NOTIFY_FD: AtomicI32 = AtomicI32::new(-1);
+
+/// Serializes starting the dispatcher
+static START_LOCK: Mutex<()> = Mutex::new(());
+
+/// Global signal dispatcher, set once the dispatcher thread runs
+static GLOBAL_SIGNAL_DISPATCHER: OnceLock<SignalDispatcher> = OnceLock::new();
+
+/// Delivers handled signals to the registered receiver
+pub struct SignalDispatcher {
+    /// Process receiving `{notify, Signal}` messages
+    receiver: Mutex<Option<ProcessId>>,
+}
+
+impl SignalDispatcher {
+    /// Register the process receiving signal notifications
+    ///
+    /// # Returns
+    /// The previously registered receiver
+    pub fn register_receiver(&self, pid: ProcessId) -> Option<ProcessId> {
+        self.receiver.lock().unwrap().replace(pid)
+    }
+
+    /// Unregister the receiver; later signals are dropped
+    pub fn unregister_receiver(&self) -> Option<ProcessId> {
+        self.receiver.lock().unwrap().take()
+    }
+
+    /// Get the registered receiver
+    pub fn receiver(&self) -> Option<ProcessId> {
+        *self.receiver.lock().unwrap()
+    }
+
+    /// Deliver the pending handled signals
+    ///
+    /// # Returns
+    /// The number of notifications sent
+    pub fn dispatch(&self) -> usize {
+        get_global_signal_service()
+            .take_pending()
+            .into_iter()
+            .filter(|&signal| self.deliver(signal))
+            .count()
+    }
+
+    /// Send `{notify, Signal}` to the receiver, if it is alive
+    fn deliver(&self, signal: Signal) -> bool {
+        let Some(pid) = self.receiver() else {
+            return false;
+        };
+        match get_global_process_table().lookup(pid) {
+            Some(process) => {
+                process.send_message(notify_message(signal));
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Start the signal dispatcher
+///
+/// Creates the notification pipe, starts the dispatcher thread and makes
+/// the signal service apply dispositions through this module. Dispositions
+/// set before the dispatcher started are installed now. Calling it again
+/// returns the running dispatcher.
+pub fn start_signal_dispatcher() -> io::Result<&'static SignalDispatcher> {
+    let _guard = START_LOCK.lock().unwrap();
+    if let Some(dispatcher) = GLOBAL_SIGNAL_DISPATCHER.get() {
+        return Ok(dispatcher);
+    }
+
+    let (read_fd, write_fd) = notify_pipe()?;
+    // SAFETY: `read_fd` is a freshly created descriptor owned by nothing else
+    let mut input = unsafe { File::from_raw_fd(read_fd) };
+    let dispatcher = GLOBAL_SIGNAL_DISPATCHER.get_or_init(|| SignalDispatcher {
+        receiver: Mutex::new(None),
+    });
+    NOTIFY_FD.store(write_fd, Ordering::SeqCst);
+
+    thread::Builder::new()
+        .name("sys_sig_dispatcher".to_string())
+        .spawn(move || {
+            let mut buf = [0u8; 64];
+            loop {
+                match input.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(_) => {
+                        dispatcher.dispatch();
+                    }
+                    Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(_) => break,
+                }
+            }
+        })?;
+
+    get_global_signal_service().set_installer(install_disposition)?;
+    Ok(dispatcher)
+}
+
+/// Get the global signal dispatcher, if it has been started
+pub fn get_global_signal_dispatcher() -> Option<&'static SignalDispatcher> {
+    GLOBAL_SIGNAL_DISPATCHER.get()
+}
+
+/// Build the `{notify, Signal}` message sent for a handled signal
+///
+/// The tuple is laid out in a heap fragment: the arity header followed by
+/// the two atoms.
+pub fn notify_message(signal: Signal) -> Message {
+    let words = vec![ARITYVAL_2, atom_term("notify"), atom_term(signal.name())];
+    Message::with_heap_fragment(FRAGMENT_ROOT, words)
+}
+
+/// Atom term of a signal notification atom (`make_atom`)
+fn atom_term(name: &str) -> Eterm {
+    let index = get_global_atom_table()
+        .put_index(name.as_bytes(), AtomEncoding::SevenBitAscii, false)
+        .expect("signal atoms are valid atoms");
+    make_atom(index)
+}
+
+/// Create the notification pipe
+///
+/// Both ends are close-on-exec; the write end is non-blocking, so a signal
+/// handler never blocks on a full pipe, which already has a wake-up pending.
+fn notify_pipe() -> io::Result<(libc::c_int, libc::c_int)> {
+    let mut fds = [0 as libc::c_int; 2];
+    // SAFETY: `fds` has room for the two descriptors `pipe` returns
+    unsafe {
+        if libc::pipe(fds.as_mut_ptr()) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        for fd in fds {
+            libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+        }
+        let flags = libc::fcntl(fds[1], libc::F_GETFL);
+        libc::fcntl(fds[1], libc::F_SETFL, flags | libc::O_NONBLOCK);
+    }
+    Ok((fds[0], fds[1]))
+}
+
+/// OS signal number of a signal
+fn signal_number(signal: Signal) -> libc::c_int {
+    match signal {
+        Signal::Sighup => libc::SIGHUP,
+        Signal::Sigint => libc::SIGINT,
+        Signal::Sigquit => libc::SIGQUIT,
+        Signal::Sigabrt => libc::SIGABRT,
+        Signal::Sigalrm => libc::SIGALRM,
+        Signal::Sigterm => libc::SIGTERM,
+        Signal::Sigusr1 => libc::SIGUSR1,
+        Signal::Sigusr2 => libc::SIGUSR2,
+        Signal::Sigchld => libc::SIGCHLD,
+        Signal::Sigstop => libc::SIGSTOP,
+        Signal::Sigtstp => libc::SIGTSTP,
+    }
+}
+
+/// Handler of handled signals
+///
+/// Only async-signal-safe operations: atomics and `write`.
+extern "C" fn signal_handler(signum: libc::c_int) {
+    let Some(signal) = Signal::ALL.into_iter().find(|&signal| signal_number(signal) == signum) else {
+        return;
+    };
+    if get_global_signal_service().notify(signal) != SignalAction::Handle {
+        return;
+    }
+    let fd = NOTIFY_FD.load(Ordering::SeqCst);
+    if fd >= 0 {
+        let byte = 0u8;
+        // SAFETY: `byte` outlives the call; a failed write means a wake-up is already pending
+        unsafe {
+            libc::write(fd, &byte as *const u8 as *const libc::c_void, 1);
+        }
+    }
+}
+
+/// Apply a disposition with `sigaction`
+fn install_disposition(signal: Signal, action: SignalAction) -> io::Result<()> {
+    let handler = match action {
+        SignalAction::Default => libc::SIG_DFL,
+        SignalAction::Ignore => libc::SIG_IGN,
+        SignalAction::Handle => signal_handler as extern "C" fn(libc::c_int) as libc::sighandler_t,
+    };
+    // SAFETY: the sigaction structure is fully initialized before use
+    unsafe {
+        let mut sa: libc::sigaction = std::mem::zeroed();
+        sa.sa_sigaction = handler;
+        sa.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut sa.sa_mask);
+        if libc::sigaction(signal_number(signal), &sa, std::ptr::null_mut()) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entities_process::Process;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_notify_message_layout() {
+        let message = notify_message(Signal::Sigterm);
+        assert_eq!(message.payload, FRAGMENT_ROOT);
+        let words = message.heap_fragment.unwrap();
+        assert_eq!(words.len(), 3);
+        assert_eq!(words[0], ARITYVAL_2);
+        assert_eq!(words[1], atom_term("notify"));
+        assert_eq!(words[2], atom_term("sigterm"));
+    }
+
+    #[test]
+    fn test_handled_signal_delivered() {
+        let dispatcher = start_signal_dispatcher().unwrap();
+        assert!(std::ptr::eq(dispatcher, start_signal_dispatcher().unwrap()));
+        let receiver = Arc::new(Process::new(41231));
+        get_global_process_table().insert(41231, Arc::clone(&receiver));
+        dispatcher.register_receiver(41231);
+
+        let service = get_global_signal_service();
+        service.set_signal(Signal::Sigusr1, SignalAction::Handle).unwrap();
+        // SAFETY: SIGUSR1 is handled, so raising it only notifies the dispatcher
+        unsafe {
+            libc::raise(libc::SIGUSR1);
+        }
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while receiver.message_queue_len() == 0 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        let message = receiver.receive_after_0(|_| true).unwrap();
+        assert_eq!(message.heap_fragment.unwrap()[2], atom_term("sigusr1"));
+
+        service.set_signal(Signal::Sigusr1, SignalAction::Ignore).unwrap();
+        // SAFETY: SIGUSR1 is ignored
+        unsafe {
+            libc::raise(libc::SIGUSR1);
+        }
+        assert!(service.take_pending().is_empty());
+        assert!(service.set_signal(Signal::Sigstop, SignalAction::Handle).is_err());
+        assert_eq!(service.action(Signal::Sigstop), SignalAction::Default);
+
+        service.set_signal(Signal::Sigusr1, SignalAction::Default).unwrap();
+        get_global_process_table().remove(41231);
+    }
+}
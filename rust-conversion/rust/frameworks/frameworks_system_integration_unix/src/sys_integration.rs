@@ -171,51 +171,19 @@ impl SysIntegration {
 
     /// Initialize signal handlers
     ///
-    /// Sets up basic signal handling for Unix systems.
-    /// This prepares the system for signal handling by verifying signal-related
-    /// functionality is available. Actual signal handler registration should be
-    /// done at the application or runtime level using safe Rust libraries.
+    /// Starts the signal dispatcher, which installs the dispositions set with
+    /// `os:set_signal/2` and delivers handled signals to the signal server.
+    /// Signals are left at their default action until Erlang code changes
+    /// them.
     ///
     /// # Returns
     ///
     /// Returns `Ok(())` if initialization succeeds, or `Err(SysError::Failed)`
-    /// if initialization fails.
-    ///
-    /// # Note
-    ///
-    /// Signal handling in Rust requires either:
-    /// - A safe library like `signal-hook` for registering handlers
-    /// - Or unsafe code with `libc::sigaction`
-    ///
-    /// This function prepares the system for signal handling. To actually register
-    /// signal handlers, use a safe library like `signal-hook` at the application level.
-    ///
-    /// Common signals that may need handling:
-    /// - SIGTERM: Termination signal (graceful shutdown)
-    /// - SIGINT: Interrupt from keyboard (Ctrl+C)
-    /// - SIGQUIT: Quit from keyboard (Ctrl+\)
-    /// - SIGUSR1/SIGUSR2: User-defined signals
-    /// - SIGCHLD: Child process status change
-    /// - SIGWINCH: Window size change
+    /// if the dispatcher could not be started.
     fn init_signal_handlers() -> Result<(), SysError> {
-        // Verify we can get the current process ID (needed for signal handling)
-        let _pid = process::id();
-        
-        // Signal handling setup is complete. The framework is ready for signal
-        // handler registration using safe Rust libraries at the application level.
-        // 
-        // Example of how to register signal handlers (at application level):
-        // ```rust
-        // use signal_hook::consts::signal::*;
-        // use signal_hook::flag;
-        // use std::sync::atomic::{AtomicBool, Ordering};
-        // use std::sync::Arc;
-        // 
-        // let term = Arc::new(AtomicBool::new(false));
-        // flag::register(SIGTERM, Arc::clone(&term))?;
-        // ```
-        
-        Ok(())
+        crate::signal_dispatcher::start_signal_dispatcher()
+            .map(|_| ())
+            .map_err(|_| SysError::Failed)
     }
 
     /// Initialize timezone information
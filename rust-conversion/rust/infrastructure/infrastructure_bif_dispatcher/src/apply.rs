@@ -0,0 +1,579 @@
+//! Apply
+//!
+//! Resolves `apply(Module, Function, Args)` and the application of funs to
+//! the code that runs the call. Based on apply(), fixed_apply(), apply_fun()
+//! and call_error_handler() from beam_common.c
+//!
+//! A call of `Module:Function/Arity` resolves, in order, to:
+//! 1. the BIF registered for it;
+//! 2. its export in loaded code;
+//! 3. `undefined_function(Module, Function, Args)` of the process's error
+//!    handler, the module `error_handler` unless set with `process_flag/2`.
+//!
+//! A local fun resolves through the fun table, or to
+//! `undefined_lambda(Module, Fun, Args)` of the error handler if its code is
+//! not loaded. An external fun (`fun M:F/A`) resolves like `apply(M, F, Args)`.
+//! If the error handler does not define the function either, the call fails
+//! with `undef`.
+//!
+//! Terms use the tagging scheme of erl_term.h, with list and boxed pointers
+//! addressing process heap words by index (`(index << 2) | tag`). A fun is a
+//! boxed object with a `FUN_SUBTAG` header holding, for a local fun, its
+//! module atom, index, uniq and free variables, and for an external fun its
+//! module, function and arity.
+
+use code_management_code_loading::{get_global_fun_table, FunEntry, FunKey, FunTable};
+use entities_data_handling::AtomEncoding;
+use entities_io_operations::{get_global_export_table, Export, ExportTable};
+use entities_process::term_tags::{
+    make_atom, FUN_SUBTAG, HEADER_ARITY_OFFS, NIL, TAG_HEADER_MASK, TAG_IMMED1_MASK,
+    TAG_IMMED1_SMALL, TAG_IMMED2_ATOM, TAG_IMMED2_MASK, TAG_IMMED2_SIZE, TAG_PRIMARY_BOXED,
+    TAG_PRIMARY_HEADER, TAG_PRIMARY_LIST, TAG_PRIMARY_MASK,
+};
+use entities_process::{Eterm, Process};
+use infrastructure_utilities::atom_table::get_global_atom_table;
+use crate::registry::{get_global_registry, BifKey, BifRegistry};
+
+/// Code a call resolved to, with the arguments to call it with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApplyTarget {
+    /// Registered BIF
+    Bif {
+        /// Module, function and arity of the BIF
+        key: BifKey,
+        /// Arguments
+        args: Vec<Eterm>,
+    },
+    /// Exported function of loaded code
+    Code {
+        /// Export entry, with the code pointer to call
+        export: Export,
+        /// Arguments
+        args: Vec<Eterm>,
+    },
+    /// Local fun
+    Fun {
+        /// Fun entry of the loaded code
+        entry: FunEntry,
+        /// Arguments followed by the free variables of the fun
+        args: Vec<Eterm>,
+    },
+}
+
+/// Apply errors, raised as exceptions by the caller
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApplyError {
+    /// Module or function is not an atom, or the arguments are not a proper
+    /// list (`badarg`)
+    Badarg,
+    /// Neither the function nor the error handler's function is defined (`undef`)
+    Undef {
+        /// Module atom of the call
+        module: Eterm,
+        /// Function atom of the call
+        function: Eterm,
+        /// Arity of the call
+        arity: u32,
+    },
+    /// The applied term is not a fun (`{badfun, Term}`)
+    Badfun(Eterm),
+    /// The fun was applied to the wrong number of arguments (`{badarity, {Fun, Args}}`)
+    Badarity {
+        /// Fun applied
+        fun: Eterm,
+        /// Number of arguments it was applied to
+        arity: u32,
+    },
+    /// The process heap has no room for the argument list of the error handler
+    HeapFull,
+}
+
+impl std::fmt::Display for ApplyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApplyError::Badarg => write!(f, "badarg"),
+            ApplyError::Undef { module, function, arity } => {
+                write!(f, "undef: {}:{}/{}", module, function, arity)
+            }
+            ApplyError::Badfun(term) => write!(f, "badfun: {}", term),
+            ApplyError::Badarity { fun, arity } => {
+                write!(f, "badarity: fun {} applied to {} arguments", fun, arity)
+            }
+            ApplyError::HeapFull => write!(f, "heap full"),
+        }
+    }
+}
+
+impl std::error::Error for ApplyError {}
+
+/// Tables a call is resolved through
+#[derive(Clone, Copy)]
+pub struct ApplyTables<'a> {
+    /// Registered BIFs
+    pub registry: &'a BifRegistry,
+    /// Exports of loaded code
+    pub exports: &'a ExportTable,
+    /// Funs of loaded code
+    pub funs: &'a FunTable,
+}
+
+impl<'a> ApplyTables<'a> {
+    /// Resolve calls through the given tables
+    pub fn new(registry: &'a BifRegistry, exports: &'a ExportTable, funs: &'a FunTable) -> Self {
+        Self { registry, exports, funs }
+    }
+}
+
+impl ApplyTables<'static> {
+    /// Resolve calls through the global BIF registry, export table and fun table
+    pub fn global() -> Self {
+        Self::new(get_global_registry(), get_global_export_table(), get_global_fun_table())
+    }
+}
+
+/// Function a call of `Module:Function/Arity` resolved to
+enum Callee {
+    Bif(BifKey),
+    Code(Export),
+}
+
+impl Callee {
+    fn with_args(self, args: Vec<Eterm>) -> ApplyTarget {
+        match self {
+            Callee::Bif(key) => ApplyTarget::Bif { key, args },
+            Callee::Code(export) => ApplyTarget::Code { export, args },
+        }
+    }
+}
+
+/// Get the atom index of an atom term
+fn atom_index(term: Eterm) -> Option<u32> {
+    (term & TAG_IMMED2_MASK == TAG_IMMED2_ATOM).then_some((term >> TAG_IMMED2_SIZE) as u32)
+}
+
+/// Get the atom term of a name in the global atom table
+pub(crate) fn atom(name: &str) -> Option<Eterm> {
+    get_global_atom_table()
+        .put_index(name.as_bytes(), AtomEncoding::SevenBitAscii, false)
+        .ok()
+        .map(make_atom)
+}
+
+/// Get the value of a non-negative small integer term
+fn small(term: Eterm) -> Option<u32> {
+    if term & TAG_IMMED1_MASK != TAG_IMMED1_SMALL {
+        return None;
+    }
+    u32::try_from((term as i64) >> 4).ok()
+}
+
+/// Check if a call is `erlang:apply/2` or `erlang:apply/3`
+pub fn is_apply(module: Eterm, function: Eterm, arity: u32) -> bool {
+    (arity == 2 || arity == 3) && Some(module) == atom("erlang") && Some(function) == atom("apply")
+}
+
+/// Get the elements of a proper list on the process heap
+///
+/// # Returns
+/// The elements, or `None` if `list` is not a proper list
+pub fn list_elements(process: &Process, list: Eterm) -> Option<Vec<Eterm>> {
+    let heap = process.heap_slice();
+    let mut elements = Vec::new();
+    let mut term = list;
+    while term != NIL {
+        if term & TAG_PRIMARY_MASK != TAG_PRIMARY_LIST || elements.len() > heap.len() / 2 {
+            return None;
+        }
+        let cell = (term >> 2) as usize;
+        elements.push(*heap.get(cell)?);
+        term = *heap.get(cell + 1)?;
+    }
+    Some(elements)
+}
+
+/// Build a proper list on the process heap
+///
+/// # Returns
+/// The list, or `None` if the heap is full
+pub(crate) fn make_list(process: &Process, elements: &[Eterm]) -> Option<Eterm> {
+    if elements.is_empty() {
+        return Some(NIL);
+    }
+    let start = process.allocate_heap_words(2 * elements.len())?;
+    let mut heap = process.heap_slice_mut();
+    for (i, &element) in elements.iter().enumerate() {
+        let cell = start + 2 * i;
+        heap[cell] = element;
+        heap[cell + 1] = if i + 1 == elements.len() {
+            NIL
+        } else {
+            (((cell + 2) as Eterm) << 2) | TAG_PRIMARY_LIST
+        };
+    }
+    Some(((start as Eterm) << 2) | TAG_PRIMARY_LIST)
+}
+
+/// Find the function a call of `Module:Function/Arity` runs, without the
+/// error handler
+fn find(tables: &ApplyTables<'_>, module: Eterm, function: Eterm, arity: u32) -> Option<Callee> {
+    if is_apply(module, function, arity) || tables.registry.lookup_entry(module, function, arity).is_some() {
+        return Some(Callee::Bif(BifKey::new(module, function, arity)));
+    }
+    let export = tables.exports.get(atom_index(module)?, atom_index(function)?, arity)?;
+    (export.code_ptr.is_some() && !export.is_stub_entry()).then_some(Callee::Code(export))
+}
+
+/// Find `Function/3` of the process's error handler
+fn find_error_handler(tables: &ApplyTables<'_>, process: &Process, function: &str) -> Option<Callee> {
+    let handler = process.error_handler().or_else(|| atom("error_handler"))?;
+    find(tables, handler, atom(function)?, 3)
+}
+
+/// Resolve a call of `Module:Function` with its arguments, and with their
+/// list term if the caller has one
+fn resolve_mfa(
+    tables: &ApplyTables<'_>,
+    process: &Process,
+    module: Eterm,
+    function: Eterm,
+    args: &[Eterm],
+    arg_list: Option<Eterm>,
+) -> Result<ApplyTarget, ApplyError> {
+    if atom_index(module).is_none() || atom_index(function).is_none() {
+        return Err(ApplyError::Badarg);
+    }
+    let arity = args.len() as u32;
+    if let Some(callee) = find(tables, module, function, arity) {
+        return Ok(callee.with_args(args.to_vec()));
+    }
+
+    let handler = find_error_handler(tables, process, "undefined_function")
+        .ok_or(ApplyError::Undef { module, function, arity })?;
+    let arg_list = match arg_list {
+        Some(list) => list,
+        None => make_list(process, args).ok_or(ApplyError::HeapFull)?,
+    };
+    Ok(handler.with_args(vec![module, function, arg_list]))
+}
+
+/// Resolve the application of a fun to its arguments, and to their list
+/// term if the caller has one
+fn resolve_fun(
+    tables: &ApplyTables<'_>,
+    process: &Process,
+    fun: Eterm,
+    args: &[Eterm],
+    arg_list: Option<Eterm>,
+) -> Result<ApplyTarget, ApplyError> {
+    let words = fun_words(process, fun).ok_or(ApplyError::Badfun(fun))?;
+    let arity = args.len() as u32;
+
+    if let [module, function, fun_arity] = words[..] {
+        if atom_index(function).is_some() {
+            if small(fun_arity) != Some(arity) {
+                return Err(ApplyError::Badarity { fun, arity });
+            }
+            return resolve_mfa(tables, process, module, function, args, arg_list);
+        }
+    }
+
+    let (module, free) = words.split_first().ok_or(ApplyError::Badfun(fun))?;
+    let key = match free {
+        [index, uniq, ..] => FunKey {
+            module: atom_index(*module).ok_or(ApplyError::Badfun(fun))?,
+            index: small(*index).ok_or(ApplyError::Badfun(fun))?,
+            uniq: small(*uniq).ok_or(ApplyError::Badfun(fun))?,
+        },
+        _ => return Err(ApplyError::Badfun(fun)),
+    };
+    let free = &free[2..];
+    if let Ok(entry) = tables.funs.resolve(key) {
+        if entry.num_free as usize != free.len() {
+            return Err(ApplyError::Badfun(fun));
+        }
+        if entry.fun_arity() != arity {
+            return Err(ApplyError::Badarity { fun, arity });
+        }
+        let args = args.iter().chain(free).copied().collect();
+        return Ok(ApplyTarget::Fun { entry, args });
+    }
+
+    let handler = find_error_handler(tables, process, "undefined_lambda")
+        .ok_or(ApplyError::Undef { module: *module, function: fun, arity })?;
+    let arg_list = match arg_list {
+        Some(list) => list,
+        None => make_list(process, args).ok_or(ApplyError::HeapFull)?,
+    };
+    Ok(handler.with_args(vec![*module, fun, arg_list]))
+}
+
+/// Get the words of a fun object after its header
+fn fun_words(process: &Process, fun: Eterm) -> Option<Vec<Eterm>> {
+    if fun & TAG_PRIMARY_MASK != TAG_PRIMARY_BOXED {
+        return None;
+    }
+    let heap = process.heap_slice();
+    let start = (fun >> 2) as usize;
+    let header = *heap.get(start)?;
+    if header & TAG_PRIMARY_MASK != TAG_PRIMARY_HEADER || header & TAG_HEADER_MASK != FUN_SUBTAG {
+        return None;
+    }
+    let size = (header >> HEADER_ARITY_OFFS) as usize;
+    heap.get(start + 1..start + 1 + size).map(<[Eterm]>::to_vec)
+}
+
+/// Resolve `apply(Module, Function, Args)` with the arguments in registers,
+/// as the `apply` instruction has them
+///
+/// # Arguments
+/// * `tables` - Tables to resolve the call through
+/// * `process` - Process making the call
+/// * `module` - Module atom
+/// * `function` - Function atom
+/// * `args` - Arguments
+///
+/// # Returns
+/// * `Ok(ApplyTarget)` - Code to run, possibly the error handler's
+/// * `Err(ApplyError)` - Error to raise
+pub fn resolve_apply(
+    tables: &ApplyTables<'_>,
+    process: &Process,
+    module: Eterm,
+    function: Eterm,
+    args: &[Eterm],
+) -> Result<ApplyTarget, ApplyError> {
+    resolve_mfa(tables, process, module, function, args, None)
+}
+
+/// Resolve `apply(Module, Function, Args)` with the arguments in a list
+/// (`erlang:apply/3`)
+///
+/// # Errors
+/// * `ApplyError::Badarg` - If `arg_list` is not a proper list, or module or
+///   function is not an atom
+/// * `ApplyError::Undef` - If neither the function nor the error handler is defined
+pub fn resolve_apply_list(
+    tables: &ApplyTables<'_>,
+    process: &Process,
+    module: Eterm,
+    function: Eterm,
+    arg_list: Eterm,
+) -> Result<ApplyTarget, ApplyError> {
+    let args = list_elements(process, arg_list).ok_or(ApplyError::Badarg)?;
+    resolve_mfa(tables, process, module, function, &args, Some(arg_list))
+}
+
+/// Resolve the application of a fun to arguments in registers, as the
+/// `call_fun` instruction has them
+///
+/// # Errors
+/// * `ApplyError::Badfun` - If `fun` is not a fun
+/// * `ApplyError::Badarity` - If the fun takes another number of arguments
+/// * `ApplyError::Undef` - If the fun's code is not loaded and the error
+///   handler is not defined
+pub fn resolve_apply_fun(
+    tables: &ApplyTables<'_>,
+    process: &Process,
+    fun: Eterm,
+    args: &[Eterm],
+) -> Result<ApplyTarget, ApplyError> {
+    resolve_fun(tables, process, fun, args, None)
+}
+
+/// Resolve the application of a fun to arguments in a list (`erlang:apply/2`)
+///
+/// # Errors
+/// As [`resolve_apply_fun`], and `ApplyError::Badarg` if `arg_list` is not
+/// a proper list
+pub fn resolve_apply_fun_list(
+    tables: &ApplyTables<'_>,
+    process: &Process,
+    fun: Eterm,
+    arg_list: Eterm,
+) -> Result<ApplyTarget, ApplyError> {
+    let args = list_elements(process, arg_list).ok_or(ApplyError::Badarg)?;
+    resolve_fun(tables, process, fun, &args, Some(arg_list))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::initialization::BifFunction;
+    use code_management_code_loading::{BeamLambda, BeamLoader};
+    use entities_process::ErtsCodePtr;
+    use std::sync::Arc;
+
+    struct FirstArg;
+
+    impl BifFunction for FirstArg {
+        fn call(&self, _process: &Process, args: &[Eterm], _ip: ErtsCodePtr) -> Eterm {
+            args[0]
+        }
+    }
+
+    fn global_atom(name: &str) -> Eterm {
+        atom(name).unwrap()
+    }
+
+    fn int(value: u32) -> Eterm {
+        ((value as Eterm) << 4) | TAG_IMMED1_SMALL
+    }
+
+    /// Put a fun object with the given words on the process heap
+    fn make_fun(process: &Process, words: &[Eterm]) -> Eterm {
+        let start = process.allocate_heap_words(words.len() + 1).unwrap();
+        let mut heap = process.heap_slice_mut();
+        heap[start] = ((words.len() as Eterm) << HEADER_ARITY_OFFS) | FUN_SUBTAG;
+        heap[start + 1..start + 1 + words.len()].copy_from_slice(words);
+        ((start as Eterm) << 2) | TAG_PRIMARY_BOXED
+    }
+
+    fn export(tables: &ApplyTables<'_>, module: Eterm, function: Eterm, arity: u32) -> Export {
+        let (module, function) = (atom_index(module).unwrap(), atom_index(function).unwrap());
+        tables.exports.put(module, function, arity);
+        tables.exports.update_export_code_ptr(module, function, arity, 0x1000 as ErtsCodePtr);
+        tables.exports.get(module, function, arity).unwrap()
+    }
+
+    fn with_tables(test: impl FnOnce(&ApplyTables<'_>)) {
+        let (registry, exports, funs) = (BifRegistry::new(), ExportTable::new(), FunTable::new());
+        test(&ApplyTables::new(&registry, &exports, &funs));
+    }
+
+    #[test]
+    fn test_apply_bif_and_export() {
+        with_tables(|tables| {
+            let process = Process::new(1);
+            let (m, f, g) = (global_atom("apply_m"), global_atom("apply_f"), global_atom("apply_g"));
+            tables.registry.register(m, f, 1, Arc::new(FirstArg)).unwrap();
+            let exported = export(tables, m, g, 2);
+
+            let target = resolve_apply(tables, &process, m, f, &[int(7)]).unwrap();
+            assert_eq!(target, ApplyTarget::Bif { key: BifKey::new(m, f, 1), args: vec![int(7)] });
+
+            let list = make_list(&process, &[int(1), int(2)]).unwrap();
+            let target = resolve_apply_list(tables, &process, m, g, list).unwrap();
+            assert_eq!(target, ApplyTarget::Code { export: exported, args: vec![int(1), int(2)] });
+        });
+    }
+
+    #[test]
+    fn test_apply_badarg() {
+        with_tables(|tables| {
+            let process = Process::new(1);
+            let m = global_atom("apply_m");
+            assert_eq!(resolve_apply(tables, &process, int(1), m, &[]), Err(ApplyError::Badarg));
+            assert_eq!(resolve_apply(tables, &process, m, int(1), &[]), Err(ApplyError::Badarg));
+
+            let improper = make_list(&process, &[int(1)]).unwrap();
+            process.heap_slice_mut()[(improper >> 2) as usize + 1] = int(2);
+            assert_eq!(resolve_apply_list(tables, &process, m, m, improper), Err(ApplyError::Badarg));
+            assert_eq!(resolve_apply_list(tables, &process, m, m, int(2)), Err(ApplyError::Badarg));
+        });
+    }
+
+    #[test]
+    fn test_apply_undefined_function_goes_to_error_handler() {
+        with_tables(|tables| {
+            let process = Process::new(1);
+            let (m, f) = (global_atom("apply_m"), global_atom("apply_missing"));
+            let undef = ApplyError::Undef { module: m, function: f, arity: 1 };
+            assert_eq!(resolve_apply(tables, &process, m, f, &[int(3)]), Err(undef));
+
+            let handler = export(tables, global_atom("error_handler"), global_atom("undefined_function"), 3);
+            let Ok(ApplyTarget::Code { export, args }) = resolve_apply(tables, &process, m, f, &[int(3)]) else {
+                panic!("expected the error handler");
+            };
+            assert_eq!(export, handler);
+            assert_eq!(&args[..2], &[m, f]);
+            assert_eq!(list_elements(&process, args[2]), Some(vec![int(3)]));
+
+            let list = make_list(&process, &[int(4)]).unwrap();
+            let target = resolve_apply_list(tables, &process, m, f, list).unwrap();
+            assert!(matches!(target, ApplyTarget::Code { args, .. } if args == vec![m, f, list]));
+        });
+    }
+
+    #[test]
+    fn test_apply_process_error_handler() {
+        with_tables(|tables| {
+            let process = Process::new(1);
+            let (m, f, own) = (global_atom("apply_m"), global_atom("apply_missing"), global_atom("own_handler"));
+            export(tables, global_atom("error_handler"), global_atom("undefined_function"), 3);
+            process.set_error_handler(own);
+            let undef = ApplyError::Undef { module: m, function: f, arity: 0 };
+            assert_eq!(resolve_apply(tables, &process, m, f, &[]), Err(undef));
+
+            let handler = export(tables, own, global_atom("undefined_function"), 3);
+            let target = resolve_apply(tables, &process, m, f, &[]).unwrap();
+            assert_eq!(target, ApplyTarget::Code { export: handler, args: vec![m, f, NIL] });
+        });
+    }
+
+    #[test]
+    fn test_apply_external_fun() {
+        with_tables(|tables| {
+            let process = Process::new(1);
+            let (m, f) = (global_atom("apply_m"), global_atom("apply_f"));
+            tables.registry.register(m, f, 1, Arc::new(FirstArg)).unwrap();
+            let fun = make_fun(&process, &[m, f, int(1)]);
+
+            let target = resolve_apply_fun(tables, &process, fun, &[int(5)]).unwrap();
+            assert_eq!(target, ApplyTarget::Bif { key: BifKey::new(m, f, 1), args: vec![int(5)] });
+            let badarity = resolve_apply_fun(tables, &process, fun, &[]);
+            assert_eq!(badarity, Err(ApplyError::Badarity { fun, arity: 0 }));
+            assert_eq!(resolve_apply_fun(tables, &process, int(5), &[]), Err(ApplyError::Badfun(int(5))));
+        });
+    }
+
+    #[test]
+    fn test_apply_local_fun() {
+        with_tables(|tables| {
+            let process = Process::new(1);
+            let m = global_atom("apply_fun_m");
+            let image = [b"FOR1".as_slice(), &4u32.to_be_bytes(), b"BEAM"].concat();
+            let mut beam = BeamLoader::read_beam_file(&image).unwrap();
+            beam.atoms = vec!["apply_fun_m".to_string(), "-f/1-fun-0-".to_string()];
+            beam.lambdas = vec![BeamLambda { function: 2, arity: 2, label: 4, index: 0, num_free: 1, old_uniq: 9 }];
+            let fun = make_fun(&process, &[m, int(0), int(9), int(42)]);
+
+            let result = resolve_apply_fun(tables, &process, fun, &[int(1)]);
+            assert!(matches!(result, Err(ApplyError::Undef { .. })));
+
+            tables.funs.register_module(atom_index(m).unwrap(), 0x2000, &beam);
+            let Ok(ApplyTarget::Fun { entry, args }) = resolve_apply_fun(tables, &process, fun, &[int(1)]) else {
+                panic!("expected the fun's code");
+            };
+            assert_eq!(entry.function, "-f/1-fun-0-");
+            assert_eq!(args, vec![int(1), int(42)]);
+            assert_eq!(
+                resolve_apply_fun(tables, &process, fun, &[]),
+                Err(ApplyError::Badarity { fun, arity: 0 })
+            );
+        });
+    }
+
+    #[test]
+    fn test_unloaded_local_fun_goes_to_error_handler() {
+        with_tables(|tables| {
+            let process = Process::new(1);
+            let m = global_atom("apply_unloaded_m");
+            let fun = make_fun(&process, &[m, int(0), int(1)]);
+            let undef = ApplyError::Undef { module: m, function: fun, arity: 0 };
+            assert_eq!(resolve_apply_fun(tables, &process, fun, &[]), Err(undef));
+
+            let handler = export(tables, global_atom("error_handler"), global_atom("undefined_lambda"), 3);
+            let target = resolve_apply_fun(tables, &process, fun, &[]).unwrap();
+            assert_eq!(target, ApplyTarget::Code { export: handler, args: vec![m, fun, NIL] });
+        });
+    }
+
+    #[test]
+    fn test_is_apply() {
+        let (erlang, apply) = (global_atom("erlang"), global_atom("apply"));
+        assert!(is_apply(erlang, apply, 2));
+        assert!(is_apply(erlang, apply, 3));
+        assert!(!is_apply(erlang, apply, 1));
+        assert!(!is_apply(apply, erlang, 3));
+    }
+}
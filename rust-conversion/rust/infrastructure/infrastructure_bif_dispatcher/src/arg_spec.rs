@@ -0,0 +1,244 @@
+//! BIF Argument Specifications
+//!
+//! Provides declarative argument specifications for BIFs. A specification
+//! lists the expected type of each argument; the dispatcher checks the
+//! arguments against it before invoking the BIF, so BIF implementations do
+//! not need to repeat the same type checks.
+//!
+//! A failed check produces a `badarg` error carrying `error_info` for every
+//! offending argument, keyed by its 1-based position, as reported by
+//! `erl_erts_errors` for BIFs raising `badarg`.
+//!
+//! Type checks are made on the tag bits of the argument terms. Based on the
+//! term tagging scheme in erl_term.h
+
+use entities_process::term_tags::{
+    TAG_IMMED1_MASK, TAG_IMMED1_PID, TAG_IMMED1_PORT, TAG_IMMED1_SMALL, TAG_IMMED2_ATOM,
+    TAG_IMMED2_MASK, TAG_IMMED2_NIL, TAG_PRIMARY_BOXED, TAG_PRIMARY_LIST, TAG_PRIMARY_MASK,
+};
+use entities_process::Eterm;
+
+/// Expected type of a BIF argument
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArgType {
+    /// Any term
+    Any,
+    /// Atom
+    Atom,
+    /// Small integer
+    Integer,
+    /// Small integer greater than or equal to zero
+    NonNegInteger,
+    /// Empty list
+    Nil,
+    /// Proper or improper list, including the empty list
+    List,
+    /// Boxed term (tuple, binary, map, fun, bignum, float, ...)
+    Boxed,
+    /// Local pid
+    Pid,
+    /// Local port
+    Port,
+    /// Local pid or port
+    PidOrPort,
+}
+
+impl ArgType {
+    /// Check whether a term matches this type
+    pub fn matches(self, term: Eterm) -> bool {
+        match self {
+            ArgType::Any => true,
+            ArgType::Atom => term & TAG_IMMED2_MASK == TAG_IMMED2_ATOM,
+            ArgType::Integer => term & TAG_IMMED1_MASK == TAG_IMMED1_SMALL,
+            ArgType::NonNegInteger => {
+                term & TAG_IMMED1_MASK == TAG_IMMED1_SMALL && (term as i64) >= 0
+            }
+            ArgType::Nil => term == TAG_IMMED2_NIL,
+            ArgType::List => {
+                term & TAG_PRIMARY_MASK == TAG_PRIMARY_LIST || term == TAG_IMMED2_NIL
+            }
+            ArgType::Boxed => term & TAG_PRIMARY_MASK == TAG_PRIMARY_BOXED,
+            ArgType::Pid => term & TAG_IMMED1_MASK == TAG_IMMED1_PID,
+            ArgType::Port => term & TAG_IMMED1_MASK == TAG_IMMED1_PORT,
+            ArgType::PidOrPort => ArgType::Pid.matches(term) || ArgType::Port.matches(term),
+        }
+    }
+
+    /// Description of a mismatching argument, as used in `error_info`
+    pub fn description(self) -> &'static str {
+        match self {
+            ArgType::Any => "bad argument",
+            ArgType::Atom => "not an atom",
+            ArgType::Integer => "not an integer",
+            ArgType::NonNegInteger => "not a non-negative integer",
+            ArgType::Nil => "not an empty list",
+            ArgType::List => "not a list",
+            ArgType::Boxed => "not a compound term",
+            ArgType::Pid => "not a pid",
+            ArgType::Port => "not a port",
+            ArgType::PidOrPort => "not a pid or port",
+        }
+    }
+}
+
+/// Argument specification of a BIF
+///
+/// Holds one [`ArgType`] per argument; its length is the BIF arity.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ArgSpec {
+    /// Expected type of each argument
+    args: Vec<ArgType>,
+}
+
+impl ArgSpec {
+    /// Create a specification from the expected argument types
+    pub fn new(args: Vec<ArgType>) -> Self {
+        Self { args }
+    }
+
+    /// Specification accepting any `arity` arguments
+    pub fn any(arity: u32) -> Self {
+        Self {
+            args: vec![ArgType::Any; arity as usize],
+        }
+    }
+
+    /// Get the arity of the specification
+    pub fn arity(&self) -> u32 {
+        self.args.len() as u32
+    }
+
+    /// Get the expected argument types
+    pub fn args(&self) -> &[ArgType] {
+        &self.args
+    }
+
+    /// Check arguments against the specification
+    ///
+    /// # Arguments
+    /// * `args` - BIF arguments; at least `arity()` terms
+    ///
+    /// # Returns
+    /// * `Ok(())` - All arguments match
+    /// * `Err(BadargInfo)` - One entry per mismatching argument
+    pub fn check(&self, args: &[Eterm]) -> Result<(), BadargInfo> {
+        let arguments: Vec<ArgumentError> = self
+            .args
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &expected)| {
+                let value = args.get(index).copied();
+                match value {
+                    Some(term) if expected.matches(term) => None,
+                    _ => Some(ArgumentError {
+                        position: index as u32 + 1,
+                        expected,
+                        value,
+                    }),
+                }
+            })
+            .collect();
+        if arguments.is_empty() {
+            Ok(())
+        } else {
+            Err(BadargInfo { arguments })
+        }
+    }
+}
+
+/// Mismatching argument of a BIF call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgumentError {
+    /// 1-based argument position, the key in the `error_info` map
+    pub position: u32,
+    /// Expected type
+    pub expected: ArgType,
+    /// Argument passed, or `None` if it was missing
+    pub value: Option<Eterm>,
+}
+
+/// `error_info` of a `badarg` raised by argument validation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BadargInfo {
+    /// Mismatching arguments, in position order
+    pub arguments: Vec<ArgumentError>,
+}
+
+impl BadargInfo {
+    /// Get the `error_info` entries as (position, description) pairs
+    pub fn error_info(&self) -> Vec<(u32, &'static str)> {
+        self.arguments
+            .iter()
+            .map(|arg| (arg.position, arg.expected.description()))
+            .collect()
+    }
+}
+
+impl std::fmt::Display for BadargInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "bad argument")?;
+        for (i, (position, description)) in self.error_info().into_iter().enumerate() {
+            let sep = if i == 0 { ": " } else { ", " };
+            write!(f, "{}argument {} ({})", sep, position, description)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entities_process::term_tags::{make_atom, make_small as small, NIL};
+
+    const ATOM_OK: Eterm = make_atom(5);
+
+    #[test]
+    fn test_arg_type_matches() {
+        assert!(ArgType::Atom.matches(ATOM_OK));
+        assert!(!ArgType::Atom.matches(NIL));
+        assert!(!ArgType::Atom.matches(small(1)));
+        assert!(ArgType::Integer.matches(small(-3)));
+        assert!(ArgType::NonNegInteger.matches(small(0)));
+        assert!(!ArgType::NonNegInteger.matches(small(-1)));
+        assert!(ArgType::Nil.matches(NIL));
+        assert!(ArgType::List.matches(NIL));
+        assert!(ArgType::List.matches(0x1000 | TAG_PRIMARY_LIST));
+        assert!(!ArgType::List.matches(0x1000 | TAG_PRIMARY_BOXED));
+        assert!(ArgType::Boxed.matches(0x1000 | TAG_PRIMARY_BOXED));
+        assert!(ArgType::Pid.matches((7 << 4) | TAG_IMMED1_PID));
+        assert!(ArgType::PidOrPort.matches((7 << 4) | TAG_IMMED1_PORT));
+        assert!(!ArgType::Port.matches((7 << 4) | TAG_IMMED1_PID));
+        assert!(ArgType::Any.matches(0));
+    }
+
+    #[test]
+    fn test_arg_spec_check() {
+        let spec = ArgSpec::new(vec![ArgType::Atom, ArgType::Integer]);
+        assert_eq!(spec.arity(), 2);
+        assert!(spec.check(&[ATOM_OK, small(1)]).is_ok());
+
+        let err = spec.check(&[small(1), ATOM_OK]).unwrap_err();
+        assert_eq!(err.error_info(), vec![(1, "not an atom"), (2, "not an integer")]);
+        assert_eq!(err.arguments[0].value, Some(small(1)));
+    }
+
+    #[test]
+    fn test_arg_spec_missing_argument() {
+        let spec = ArgSpec::new(vec![ArgType::Any, ArgType::List]);
+        let err = spec.check(&[ATOM_OK]).unwrap_err();
+        assert_eq!(err.arguments.len(), 1);
+        assert_eq!(err.arguments[0].position, 2);
+        assert_eq!(err.arguments[0].value, None);
+    }
+
+    #[test]
+    fn test_badarg_info_display() {
+        let err = ArgSpec::new(vec![ArgType::Pid, ArgType::Atom])
+            .check(&[NIL, NIL])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "bad argument: argument 1 (not a pid), argument 2 (not an atom)"
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! BIF Table
+//!
+//! Registers the BIFs of `usecases_bifs` that the emulator calls by
+//! `{Module, Function, Arity}`. Based on the BIF table generated from
+//! bif.tab
+//!
+//! Every BIF is registered with an [`ArgSpec`], so the dispatcher rejects
+//! arguments of the wrong type with `badarg` before the BIF runs. The
+//! functions here only decode their arguments into the types the
+//! `usecases_bifs` implementations take, and encode the results. A BIF that
+//! fails returns [`THE_NON_VALUE`], which the dispatcher raises as `badarg`.
+
+use std::sync::{Arc, OnceLock};
+
+use entities_process::term_tags::{
+    internal_id, make_boxed, make_internal_pid, make_internal_port, make_small, pointer_index, signed_val,
+    HEADER_ARITY_OFFS, REF_SUBTAG, TAG_HEADER_MASK, THE_NON_VALUE,
+};
+use entities_process::{ErtsCodePtr, Eterm, Process};
+use usecases_bifs::io::IoBif;
+use usecases_bifs::op::ErlangTerm;
+use usecases_bifs::timer::TimerBif;
+
+use crate::apply::atom;
+use crate::arg_spec::{ArgSpec, ArgType};
+use crate::initialization::BifFunction;
+use crate::registry::BifRegistry;
+
+/// Implementation of a table BIF
+type TableBifFn = fn(&Process, &[Eterm]) -> Eterm;
+
+/// BIF of the table: its argument types and implementation
+#[derive(Clone, Copy)]
+struct TableBif {
+    module: &'static str,
+    function: &'static str,
+    args: &'static [ArgType],
+    func: TableBifFn,
+}
+
+/// BIFs registered by [`erts_register_bifs`]
+const BIF_TABLE: &[TableBif] = &[
+    TableBif {
+        module: "erlang",
+        function: "send_after",
+        args: &[ArgType::NonNegInteger, ArgType::Pid, ArgType::Any],
+        func: send_after_3,
+    },
+    TableBif {
+        module: "erlang",
+        function: "start_timer",
+        args: &[ArgType::NonNegInteger, ArgType::Pid, ArgType::Any],
+        func: start_timer_3,
+    },
+    TableBif {
+        module: "erlang",
+        function: "cancel_timer",
+        args: &[ArgType::Boxed],
+        func: cancel_timer_1,
+    },
+    TableBif {
+        module: "erlang",
+        function: "read_timer",
+        args: &[ArgType::Boxed],
+        func: read_timer_1,
+    },
+    TableBif {
+        module: "erlang",
+        function: "group_leader",
+        args: &[],
+        func: group_leader_0,
+    },
+    TableBif {
+        module: "erlang",
+        function: "group_leader",
+        args: &[ArgType::Pid, ArgType::Pid],
+        func: group_leader_2,
+    },
+];
+
+impl BifFunction for TableBif {
+    fn call(&self, process: &Process, args: &[Eterm], _instruction_ptr: ErtsCodePtr) -> Eterm {
+        (self.func)(process, args)
+    }
+}
+
+/// Register the BIF table in a registry
+///
+/// # Returns
+/// * `Ok(())` - All BIFs were registered
+/// * `Err(String)` - A BIF was already registered, or its name is not a valid atom
+pub fn erts_register_bifs(registry: &BifRegistry) -> Result<(), String> {
+    for bif in BIF_TABLE {
+        let (Some(module), Some(function)) = (atom(bif.module), atom(bif.function)) else {
+            return Err(format!("invalid BIF name {}:{}", bif.module, bif.function));
+        };
+        registry.register_with_spec(module, function, ArgSpec::new(bif.args.to_vec()), Arc::new(*bif))?;
+    }
+    Ok(())
+}
+
+/// Register the BIF table in the global registry, once
+pub(crate) fn register_global_bifs() -> Result<(), String> {
+    static REGISTERED: OnceLock<Result<(), String>> = OnceLock::new();
+    REGISTERED
+        .get_or_init(|| erts_register_bifs(crate::registry::get_global_registry()))
+        .clone()
+}
+
+fn send_after_3(process: &Process, args: &[Eterm]) -> Eterm {
+    let timer = TimerBif::send_after_3(signed_val(args[0]) as u64, internal_id(args[1]), args[2]);
+    result_term(process, timer)
+}
+
+fn start_timer_3(process: &Process, args: &[Eterm]) -> Eterm {
+    let timer = TimerBif::start_timer_3(signed_val(args[0]) as u64, internal_id(args[1]), args[2]);
+    result_term(process, timer)
+}
+
+fn cancel_timer_1(process: &Process, args: &[Eterm]) -> Eterm {
+    match reference_number(process, args[0]) {
+        Some(timer_ref) => result_term(process, TimerBif::cancel_timer_1(process, timer_ref)),
+        None => THE_NON_VALUE,
+    }
+}
+
+fn read_timer_1(process: &Process, args: &[Eterm]) -> Eterm {
+    match reference_number(process, args[0]) {
+        Some(timer_ref) => result_term(process, TimerBif::read_timer_1(process, timer_ref)),
+        None => THE_NON_VALUE,
+    }
+}
+
+fn group_leader_0(process: &Process, _args: &[Eterm]) -> Eterm {
+    result_term(process, IoBif::group_leader_0(process))
+}
+
+fn group_leader_2(process: &Process, args: &[Eterm]) -> Eterm {
+    match IoBif::group_leader_2(internal_id(args[0]), internal_id(args[1])) {
+        Ok(result) => result_term(process, result),
+        Err(_) => THE_NON_VALUE,
+    }
+}
+
+/// Reference number of a local reference term on the process heap
+fn reference_number(process: &Process, term: Eterm) -> Option<u64> {
+    let heap = process.heap_slice_mut();
+    let index = pointer_index(term);
+    let header = *heap.get(index)?;
+    if header & TAG_HEADER_MASK != REF_SUBTAG || header >> HEADER_ARITY_OFFS != 1 {
+        return None;
+    }
+    heap.get(index + 1).copied()
+}
+
+/// Encode the result of a BIF implementation
+///
+/// References are built on the process heap. Results that cannot be
+/// encoded, including a reference that does not fit on the heap, fail the
+/// BIF.
+fn result_term(process: &Process, result: ErlangTerm) -> Eterm {
+    match result {
+        ErlangTerm::Integer(value) => make_small(value),
+        ErlangTerm::Atom(name) => atom(&name).unwrap_or(THE_NON_VALUE),
+        ErlangTerm::Pid(id) => make_internal_pid(id),
+        ErlangTerm::Port(id) => make_internal_port(id),
+        ErlangTerm::Reference(number) => match process.allocate_heap_words(2) {
+            Some(index) => {
+                let mut heap = process.heap_slice_mut();
+                heap[index] = (1 << HEADER_ARITY_OFFS) | REF_SUBTAG;
+                heap[index + 1] = number;
+                make_boxed(index)
+            }
+            None => THE_NON_VALUE,
+        },
+        _ => THE_NON_VALUE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dispatcher::{dispatch_bif, BifDispatcherError};
+    use entities_process::term_tags::make_arityval;
+
+    fn call(registry: &BifRegistry, process: &Process, function: &str, args: &[Eterm]) -> Result<Eterm, BifDispatcherError> {
+        let (module, function) = (atom("erlang").unwrap(), atom(function).unwrap());
+        dispatch_bif(registry, process, module, function, args, std::ptr::null())
+    }
+
+    fn error_info(result: Result<Eterm, BifDispatcherError>) -> Vec<(u32, &'static str)> {
+        match result {
+            Err(BifDispatcherError::Badarg(info)) => info.error_info(),
+            other => panic!("Expected Badarg, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_timer_bifs() {
+        let registry = BifRegistry::new();
+        erts_register_bifs(&registry).unwrap();
+        let process = Process::new(40671);
+
+        let timer = call(&registry, &process, "send_after", &[make_small(60_000), make_internal_pid(1), make_small(1)]).unwrap();
+        assert!(reference_number(&process, timer).is_some());
+        let left = call(&registry, &process, "read_timer", &[timer]).unwrap();
+        assert!(signed_val(left) > 59_000 && signed_val(left) <= 60_000);
+        assert!(call(&registry, &process, "cancel_timer", &[timer]).is_ok());
+        assert_eq!(call(&registry, &process, "read_timer", &[timer]), Ok(atom("false").unwrap()));
+    }
+
+    #[test]
+    fn test_argument_types_checked_by_spec() {
+        let registry = BifRegistry::new();
+        erts_register_bifs(&registry).unwrap();
+        let process = Process::new(40672);
+        let pid = make_internal_pid(1);
+
+        let result = call(&registry, &process, "send_after", &[make_small(-1), pid, make_small(1)]);
+        assert_eq!(error_info(result), vec![(1, "not a non-negative integer")]);
+        let result = call(&registry, &process, "start_timer", &[make_small(1), atom("name").unwrap(), make_small(1)]);
+        assert_eq!(error_info(result), vec![(2, "not a pid")]);
+        let result = call(&registry, &process, "read_timer", &[make_small(1)]);
+        assert_eq!(error_info(result), vec![(1, "not a compound term")]);
+        let result = call(&registry, &process, "group_leader", &[make_small(1), pid]);
+        assert_eq!(error_info(result), vec![(1, "not a pid")]);
+    }
+
+    #[test]
+    fn test_failed_bif_raises_badarg() {
+        let registry = BifRegistry::new();
+        erts_register_bifs(&registry).unwrap();
+        let process = Process::new(40673);
+
+        // A boxed term that is not a reference
+        let index = process.allocate_heap_words(1).unwrap();
+        process.heap_slice_mut()[index] = make_arityval(0);
+        let result = call(&registry, &process, "cancel_timer", &[make_boxed(index)]);
+        assert_eq!(error_info(result), vec![]);
+
+        // group_leader/2 of a process that is not alive
+        let result = call(&registry, &process, "group_leader", &[make_internal_pid(1), make_internal_pid(40679)]);
+        assert_eq!(error_info(result), vec![]);
+    }
+
+    #[test]
+    fn test_register_twice_fails() {
+        let registry = BifRegistry::new();
+        erts_register_bifs(&registry).unwrap();
+        assert!(erts_register_bifs(&registry).is_err());
+    }
+}
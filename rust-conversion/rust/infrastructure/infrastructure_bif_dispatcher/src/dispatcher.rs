@@ -5,6 +5,13 @@
 //! erts_call_dirty_bif() from bif.c
 
 use entities_process::{Process, ErtsCodePtr, Eterm};
+use entities_process::term_tags::THE_NON_VALUE;
+use crate::apply::{is_apply, resolve_apply_fun_list, resolve_apply_list, ApplyError, ApplyTables, ApplyTarget};
+use crate::arg_spec::BadargInfo;
+use crate::registry::{BifKey, BifRegistry};
+use code_management_code_loading::get_global_fun_table;
+use entities_io_operations::get_global_export_table;
+use crate::scheduling::{call_yielding_bif, CONTEXT_REDS};
 
 /// BIF dispatcher
 ///
@@ -102,6 +109,107 @@ pub fn call_bif(
     Err(BifDispatcherError::NotImplemented("call_bif requires native function structure access".to_string()))
 }
 
+/// Dispatch a call to a registered BIF
+///
+/// Looks up the BIF in the registry and, if it was registered with an
+/// argument specification, checks the arguments against it before calling
+/// the BIF. BIF implementations can therefore rely on their arguments having
+/// the declared types. A BIF returning `THE_NON_VALUE` failed, and the call
+/// raises `badarg`.
+///
+/// BIFs registered as yielding run for one time slice of [`CONTEXT_REDS`]
+/// reductions at a time. When one yields, its remaining work is saved on the
+/// process and the call is to be repeated, through the BIF's
+/// [`trap_export`](BifRegistry::trap_export), when the process is next
+/// scheduled.
+///
+/// Calls of `erlang:apply/2,3` go to [`dispatch_apply`], with the global
+/// export and fun tables.
+///
+/// # Arguments
+/// * `registry` - Registry to look the BIF up in
+/// * `process` - Process calling the BIF
+/// * `module` - Module atom
+/// * `function` - Function atom
+/// * `args` - BIF arguments; the call arity is `args.len()`
+/// * `instruction_ptr` - Instruction pointer
+///
+/// # Returns
+/// * `Ok(result)` - BIF result term
+/// * `Err(BifDispatcherError::BifNotFound)` - No BIF with this arity is registered
+/// * `Err(BifDispatcherError::Badarg)` - Arguments do not match the specification, or the BIF failed
+/// * `Err(BifDispatcherError::Yielded)` - A yielding BIF used up its time slice
+/// * `Err(BifDispatcherError::Apply)` - An apply failed
+/// * `Err(BifDispatcherError::Trap)` - An apply continues in Erlang code
+pub fn dispatch_bif(
+    registry: &BifRegistry,
+    process: &Process,
+    module: Eterm,
+    function: Eterm,
+    args: &[Eterm],
+    instruction_ptr: ErtsCodePtr,
+) -> Result<Eterm, BifDispatcherError> {
+    let arity = args.len() as u32;
+    if is_apply(module, function, arity) {
+        let tables = ApplyTables::new(registry, get_global_export_table(), get_global_fun_table());
+        return dispatch_apply(&tables, process, args, instruction_ptr);
+    }
+    let entry = registry
+        .lookup_entry(module, function, arity)
+        .ok_or_else(|| BifDispatcherError::BifNotFound(format!("{}:{}/{}", module, function, arity)))?;
+
+    if let Some(spec) = &entry.spec {
+        spec.check(args).map_err(BifDispatcherError::Badarg)?;
+    }
+
+    let result = if entry.yields {
+        let key = BifKey::new(module, function, arity);
+        call_yielding_bif(process, &key, &*entry.func, args, CONTEXT_REDS).ok_or(BifDispatcherError::Yielded)?
+    } else {
+        entry.func.call(process, args, instruction_ptr)
+    };
+    if result == THE_NON_VALUE {
+        return Err(BifDispatcherError::Badarg(BadargInfo { arguments: Vec::new() }));
+    }
+    Ok(result)
+}
+
+/// Dispatch a call of `erlang:apply/3` or `erlang:apply/2`
+///
+/// Resolves the call and runs it if it is a BIF. Erlang code cannot run
+/// inside a BIF, so a call resolved to an export or a fun is returned for
+/// the caller to continue in, as the C BIFs do by trapping to it.
+///
+/// # Arguments
+/// * `tables` - Tables to resolve the call through
+/// * `process` - Process calling apply
+/// * `args` - `[Module, Function, Args]` or `[Fun, Args]`
+/// * `instruction_ptr` - Instruction pointer
+///
+/// # Returns
+/// * `Ok(result)` - Result of the BIF the call resolved to
+/// * `Err(BifDispatcherError::Trap)` - The call continues in Erlang code
+/// * `Err(BifDispatcherError::Apply)` - The call failed (`badarg`, `undef`, ...)
+pub fn dispatch_apply(
+    tables: &ApplyTables<'_>,
+    process: &Process,
+    args: &[Eterm],
+    instruction_ptr: ErtsCodePtr,
+) -> Result<Eterm, BifDispatcherError> {
+    let target = match *args {
+        [module, function, arg_list] => resolve_apply_list(tables, process, module, function, arg_list),
+        [fun, arg_list] => resolve_apply_fun_list(tables, process, fun, arg_list),
+        _ => return Err(BifDispatcherError::InvalidArguments(format!("apply/{}", args.len()))),
+    }
+    .map_err(BifDispatcherError::Apply)?;
+    match target {
+        ApplyTarget::Bif { key, args } => {
+            dispatch_bif(tables.registry, process, key.module, key.function, &args, instruction_ptr)
+        }
+        target => Err(BifDispatcherError::Trap(target)),
+    }
+}
+
 /// Call a dirty BIF function
 ///
 /// Based on erts_call_dirty_bif() from bif.c
@@ -161,6 +269,15 @@ pub enum BifDispatcherError {
     BifNotFound(String),
     /// Invalid arguments
     InvalidArguments(String),
+    /// Arguments do not match the BIF argument specification
+    Badarg(BadargInfo),
+    /// A yielding BIF used up its time slice; its continuation is saved on
+    /// the process and the call is to be repeated when it is next scheduled
+    Yielded,
+    /// An apply failed
+    Apply(ApplyError),
+    /// An apply resolved to Erlang code, which the caller is to continue in
+    Trap(ApplyTarget),
     /// Process error
     ProcessError(String),
     /// Not implemented (for stubbed functions)
@@ -174,6 +291,10 @@ impl std::fmt::Display for BifDispatcherError {
             BifDispatcherError::AlreadyInitialized => write!(f, "BIF dispatcher already initialized"),
             BifDispatcherError::BifNotFound(name) => write!(f, "BIF not found: {}", name),
             BifDispatcherError::InvalidArguments(msg) => write!(f, "Invalid arguments: {}", msg),
+            BifDispatcherError::Badarg(info) => write!(f, "{}", info),
+            BifDispatcherError::Yielded => write!(f, "BIF yielded"),
+            BifDispatcherError::Apply(err) => write!(f, "{}", err),
+            BifDispatcherError::Trap(target) => write!(f, "Trap to {:?}", target),
             BifDispatcherError::ProcessError(msg) => write!(f, "Process error: {}", msg),
             BifDispatcherError::NotImplemented(msg) => write!(f, "Not implemented: {}", msg),
         }
@@ -235,6 +356,134 @@ mod tests {
         assert!(matches!(result.unwrap_err(), BifDispatcherError::NotImplemented(_)));
     }
 
+    struct FirstArgBif;
+
+    impl crate::initialization::BifFunction for FirstArgBif {
+        fn call(&self, _process: &Process, args: &[Eterm], _instruction_ptr: ErtsCodePtr) -> Eterm {
+            args[0]
+        }
+    }
+
+    #[test]
+    fn test_dispatch_bif_checks_spec() {
+        use crate::arg_spec::{ArgSpec, ArgType};
+        use std::sync::Arc;
+
+        let registry = BifRegistry::new();
+        let spec = ArgSpec::new(vec![ArgType::Atom, ArgType::NonNegInteger]);
+        registry.register_with_spec(1, 2, spec, Arc::new(FirstArgBif)).unwrap();
+        let process = Process::new(1);
+        let atom = (3 << 6) | 0x0B;
+        let small = (4 << 4) | 0xF;
+
+        let result = dispatch_bif(&registry, &process, 1, 2, &[atom, small], std::ptr::null());
+        assert_eq!(result, Ok(atom));
+
+        let result = dispatch_bif(&registry, &process, 1, 2, &[small, small], std::ptr::null());
+        match result {
+            Err(BifDispatcherError::Badarg(info)) => {
+                assert_eq!(info.error_info(), vec![(1, "not an atom")]);
+            }
+            other => panic!("Expected Badarg, got {:?}", other),
+        }
+
+        let result = dispatch_bif(&registry, &process, 1, 2, &[atom], std::ptr::null());
+        assert!(matches!(result, Err(BifDispatcherError::BifNotFound(_))));
+    }
+
+    #[test]
+    fn test_dispatch_bif_without_spec() {
+        use std::sync::Arc;
+
+        let registry = BifRegistry::new();
+        registry.register(1, 2, 1, Arc::new(FirstArgBif)).unwrap();
+        let process = Process::new(1);
+
+        let result = dispatch_bif(&registry, &process, 1, 2, &[0x3B], std::ptr::null());
+        assert_eq!(result, Ok(0x3B));
+    }
+
+    /// Counts down from `args[0]`, one reduction per step
+    struct CountDown;
+
+    impl crate::initialization::BifFunction for CountDown {
+        fn call(&self, process: &Process, args: &[Eterm], _instruction_ptr: ErtsCodePtr) -> Eterm {
+            crate::scheduling::run_to_completion(self, process, args)
+        }
+
+        fn call_yielding(
+            &self,
+            _process: &Process,
+            args: &[Eterm],
+            state: Option<Box<dyn std::any::Any + Send>>,
+            reds: usize,
+        ) -> crate::scheduling::BifYield {
+            let left = state.map_or(args[0], |s| *s.downcast::<Eterm>().unwrap());
+            if left as usize <= reds {
+                crate::scheduling::BifYield::Done(args[0])
+            } else {
+                crate::scheduling::BifYield::Yield(Box::new(left - reds as Eterm))
+            }
+        }
+    }
+
+    #[test]
+    fn test_dispatch_bif_yields() {
+        use std::sync::Arc;
+
+        let registry = BifRegistry::new();
+        registry.register_yielding(1, 2, 1, Arc::new(CountDown)).unwrap();
+        let process = Process::new(1);
+        let work = (CONTEXT_REDS * 2 + 1) as Eterm;
+
+        for _ in 0..2 {
+            let result = dispatch_bif(&registry, &process, 1, 2, &[work], std::ptr::null());
+            assert_eq!(result, Err(BifDispatcherError::Yielded));
+            assert!(process.has_bif_continuation());
+        }
+        let result = dispatch_bif(&registry, &process, 1, 2, &[work], std::ptr::null());
+        assert_eq!(result, Ok(work));
+        assert!(!process.has_bif_continuation());
+
+        registry.register(1, 3, 1, Arc::new(CountDown)).unwrap();
+        let result = dispatch_bif(&registry, &process, 1, 3, &[work], std::ptr::null());
+        assert_eq!(result, Ok(work));
+        assert!(!process.has_bif_continuation());
+    }
+
+    #[test]
+    fn test_dispatch_bif_apply() {
+        use crate::apply::{atom, make_list};
+        use entities_process::term_tags::{make_small, NIL};
+        use std::sync::Arc;
+
+        let registry = BifRegistry::new();
+        let process = Process::new(1);
+        let (erlang, apply) = (atom("erlang").unwrap(), atom("apply").unwrap());
+        let (m, f, g) = (atom("dispatch_m").unwrap(), atom("dispatch_f").unwrap(), atom("dispatch_g").unwrap());
+        registry.register(m, f, 1, Arc::new(FirstArgBif)).unwrap();
+
+        let args = make_list(&process, &[NIL]).unwrap();
+        let result = dispatch_bif(&registry, &process, erlang, apply, &[m, f, args], std::ptr::null());
+        assert_eq!(result, Ok(NIL));
+
+        let nested = make_list(&process, &[m, f, args]).unwrap();
+        let result = dispatch_bif(&registry, &process, erlang, apply, &[erlang, apply, nested], std::ptr::null());
+        assert_eq!(result, Ok(NIL));
+
+        let exports = get_global_export_table();
+        let (g_index, m_index) = ((g >> 6) as u32, (m >> 6) as u32);
+        exports.put(m_index, g_index, 1);
+        exports.update_export_code_ptr(m_index, g_index, 1, 0x1000 as ErtsCodePtr);
+        let result = dispatch_bif(&registry, &process, erlang, apply, &[m, g, args], std::ptr::null());
+        assert!(matches!(result, Err(BifDispatcherError::Trap(ApplyTarget::Code { args, .. })) if args == vec![NIL]));
+
+        let result = dispatch_bif(&registry, &process, erlang, apply, &[m, f, make_small(3)], std::ptr::null());
+        assert_eq!(result, Err(BifDispatcherError::Apply(ApplyError::Badarg)));
+        let result = dispatch_bif(&registry, &process, erlang, apply, &[NIL, args], std::ptr::null());
+        assert_eq!(result, Err(BifDispatcherError::Apply(ApplyError::Badfun(NIL))));
+    }
+
     #[test]
     fn test_bif_dispatcher_error_display() {
         let error1 = BifDispatcherError::NotInitialized;
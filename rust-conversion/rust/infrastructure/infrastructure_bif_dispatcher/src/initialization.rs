@@ -4,8 +4,10 @@
 //! trap export setup. Based on erts_init_bif() and erts_init_trap_export()
 //! from bif.c
 
+use std::any::Any;
 use std::sync::{Arc, Mutex};
 use entities_process::Eterm;
+use crate::scheduling::BifYield;
 
 /// Trap export structure
 ///
@@ -40,6 +42,29 @@ pub trait BifFunction {
     /// # Returns
     /// Result term or error indicator
     fn call(&self, process: &entities_process::Process, args: &[Eterm], instruction_ptr: entities_process::ErtsCodePtr) -> Eterm;
+
+    /// Call the BIF for one time slice of at most `reds` reductions
+    ///
+    /// Long-running BIFs override this to yield their remaining work, which
+    /// is handed back as `state` when they are called again (see
+    /// [`call_yielding_bif`](crate::scheduling::call_yielding_bif)). The
+    /// default runs [`call`](Self::call) to completion.
+    ///
+    /// # Arguments
+    /// * `process` - Process calling the BIF
+    /// * `args` - BIF arguments
+    /// * `state` - State the BIF last yielded, or `None` on the first call
+    /// * `reds` - Reductions the slice may use
+    fn call_yielding(
+        &self,
+        process: &entities_process::Process,
+        args: &[Eterm],
+        state: Option<Box<dyn Any + Send>>,
+        reds: usize,
+    ) -> BifYield {
+        let _ = (state, reds);
+        BifYield::Done(self.call(process, args, std::ptr::null()))
+    }
 }
 
 impl TrapExport {
@@ -144,6 +169,8 @@ static AWAIT_EXIT_TRAP_EXPORT: Mutex<Option<TrapExport>> = Mutex::new(None);
 /// - bif_handle_signals_return/2 - Signal return handler
 /// - await_exit_trap/0 - Await exit trap handler
 ///
+/// and registers the BIFs of the BIF table in the global registry.
+///
 /// # Returns
 /// * `Ok(())` - Success
 /// * `Err(BifInitError)` - Initialization error
@@ -190,6 +217,9 @@ pub fn erts_init_bif() -> Result<(), BifInitError> {
         ));
     }
 
+    // Register the BIFs of the BIF table
+    crate::bif_table::register_global_bifs().map_err(BifInitError::InitFailed)?;
+
     // In the C implementation, this would also:
     // - Set up dsend_continue_trap_export
     // - Set up various other trap exports
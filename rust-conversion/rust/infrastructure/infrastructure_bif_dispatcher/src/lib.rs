@@ -23,10 +23,23 @@
 //!   and trap export setup
 //!
 //! - **[`registry`](registry/index.html)**: BIF registry for storing and
-//!   looking up BIF functions by module, function name, and arity
+//!   looking up BIF functions by module, function name, and arity, with
+//!   runtime replacement of implementations and trap exports for yielding BIFs
+//!
+//! - **[`arg_spec`](arg_spec/index.html)**: Declarative argument specifications
+//!   checked by the dispatcher before calling a BIF, raising `badarg` with
+//!   per-argument error information
+//!
+//! - **[`bif_table`](bif_table/index.html)**: The BIFs of `usecases_bifs`
+//!   registered at initialization, each with its argument specification
+//!
+//! - **[`apply`](apply/index.html)**: Resolution of `erlang:apply/2,3` and
+//!   of fun applications to BIFs, exported code, funs or the process's
+//!   error handler
 //!
 //! - **[`scheduling`](scheduling/index.html)**: Helper functions for scheduling
-//!   BIFs, trap preparation, and yield handling
+//!   BIFs, trap preparation, and yield handling, with yielding BIFs saving
+//!   their remaining work on the process between time slices
 //!
 //! ## Architecture
 //!
@@ -34,6 +47,8 @@
 //! - `infrastructure_bifs` for BIF infrastructure framework
 //! - `usecases_bifs` for actual BIF implementations
 //! - `entities_process` for Process structures
+//! - `entities_io_operations` and `code_management_code_loading` for the
+//!   export and fun tables calls are applied through
 //!
 //! The dispatcher routes calls from the emulator to BIF implementations in
 //! the usecases layer, maintaining separation of concerns.
@@ -48,12 +63,16 @@ pub mod dispatcher;
 pub mod trap_handlers;
 pub mod initialization;
 pub mod registry;
+pub mod arg_spec;
 pub mod scheduling;
+pub mod apply;
+pub mod bif_table;
 
-pub use dispatcher::{call_bif, erts_call_dirty_bif, BifDispatcher, BifDispatcherError};
+pub use dispatcher::{call_bif, dispatch_apply, dispatch_bif, erts_call_dirty_bif, BifDispatcher, BifDispatcherError};
 pub use trap_handlers::{bif_return_trap, bif_handle_signals_return, erts_internal_await_exit_trap};
 pub use initialization::{erts_init_bif, erts_init_trap_export, TrapExport, BifInitError};
-pub use registry::{BifRegistry, BifKey, get_global_registry};
-pub use scheduling::{SchedType, prepare_trap, prepare_trap_with_args, prepare_yield_return, is_proc_out_of_reds, reds_left};
-
-
+pub use registry::{BifRegistry, BifKey, BifEntry, get_global_registry};
+pub use arg_spec::{ArgType, ArgSpec, ArgumentError, BadargInfo};
+pub use scheduling::{SchedType, BifYield, CONTEXT_REDS, call_yielding_bif, run_to_completion, prepare_trap, prepare_trap_with_args, prepare_yield_return, is_proc_out_of_reds, reds_left};
+pub use bif_table::erts_register_bifs;
+pub use apply::{ApplyTarget, ApplyError, ApplyTables, resolve_apply, resolve_apply_list, resolve_apply_fun, resolve_apply_fun_list};
@@ -3,14 +3,25 @@
 //! Provides a registry for storing and looking up BIF functions by module,
 //! function name, and arity. This registry is used by the dispatcher to
 //! route BIF calls to their implementations.
+//!
+//! A BIF may be registered with an [`ArgSpec`] describing the expected type
+//! of each argument, which the dispatcher checks before invoking it.
+//!
+//! Each BIF is numbered in registration order, as in the BIF table of
+//! `bif.tab`. BIFs registered as yielding get a trap export, through which
+//! they are re-entered to continue their work. Implementations can be
+//! replaced while the system runs, e.g. to instrument a BIF, and
+//! [`BifRegistry::snapshot`] lists all BIFs in the manner of
+//! `erlang:system_info(snifs)`.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, RwLock};
 use entities_process::Eterm;
-use crate::initialization::BifFunction;
+use crate::initialization::{BifFunction, TrapExport};
+use crate::arg_spec::ArgSpec;
 
 /// BIF registry key (module, function, arity)
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct BifKey {
     /// Module atom
     pub module: Eterm,
@@ -31,20 +42,41 @@ impl BifKey {
     }
 }
 
+/// Registered BIF
+#[derive(Clone)]
+pub struct BifEntry {
+    /// BIF function implementation
+    pub func: Arc<dyn BifFunction + Send + Sync>,
+    /// Argument specification checked before calling `func`
+    pub spec: Option<ArgSpec>,
+    /// BIF number, in registration order
+    pub number: i32,
+    /// Whether the BIF may yield, and so has a trap export
+    pub yields: bool,
+}
+
+/// Registered BIFs, grouped by name
+#[derive(Default)]
+struct Entries {
+    /// Map from (module, function) to the BIFs of each arity
+    by_name: HashMap<(Eterm, Eterm), BTreeMap<u32, BifEntry>>,
+    /// Number of the next BIF registered
+    next_number: i32,
+}
+
 /// BIF registry
 ///
 /// Thread-safe registry for storing and looking up BIF functions.
 /// BIFs are registered by module, function name, and arity.
 pub struct BifRegistry {
-    /// Map from (module, function, arity) to BIF function
-    registry: RwLock<HashMap<BifKey, Arc<dyn BifFunction + Send + Sync>>>,
+    registry: RwLock<Entries>,
 }
 
 impl BifRegistry {
     /// Create a new BIF registry
     pub fn new() -> Self {
         Self {
-            registry: RwLock::new(HashMap::new()),
+            registry: RwLock::new(Entries::default()),
         }
     }
 
@@ -65,18 +97,111 @@ impl BifRegistry {
         function: Eterm,
         arity: u32,
         bif_func: Arc<dyn BifFunction + Send + Sync>,
+    ) -> Result<(), String> {
+        self.insert(module, function, arity, bif_func, None, false)
+    }
+
+    /// Register a BIF function with an argument specification
+    ///
+    /// The arity is taken from the specification.
+    ///
+    /// # Arguments
+    /// * `module` - Module atom
+    /// * `function` - Function atom
+    /// * `spec` - Expected type of each argument
+    /// * `bif_func` - BIF function implementation
+    ///
+    /// # Returns
+    /// * `Ok(())` - Success
+    /// * `Err(String)` - Error (e.g., BIF already registered)
+    pub fn register_with_spec(
+        &self,
+        module: Eterm,
+        function: Eterm,
+        spec: ArgSpec,
+        bif_func: Arc<dyn BifFunction + Send + Sync>,
+    ) -> Result<(), String> {
+        let arity = spec.arity();
+        self.insert(module, function, arity, bif_func, Some(spec), false)
+    }
+
+    /// Register a BIF function that may yield
+    ///
+    /// A trap export is generated for the BIF, see [`BifRegistry::trap_export`].
+    ///
+    /// # Arguments
+    /// * `module` - Module atom
+    /// * `function` - Function atom
+    /// * `arity` - Function arity
+    /// * `bif_func` - BIF function implementation
+    ///
+    /// # Returns
+    /// * `Ok(())` - Success
+    /// * `Err(String)` - Error (e.g., BIF already registered)
+    pub fn register_yielding(
+        &self,
+        module: Eterm,
+        function: Eterm,
+        arity: u32,
+        bif_func: Arc<dyn BifFunction + Send + Sync>,
+    ) -> Result<(), String> {
+        self.insert(module, function, arity, bif_func, None, true)
+    }
+
+    fn insert(
+        &self,
+        module: Eterm,
+        function: Eterm,
+        arity: u32,
+        func: Arc<dyn BifFunction + Send + Sync>,
+        spec: Option<ArgSpec>,
+        yields: bool,
     ) -> Result<(), String> {
         let mut registry = self.registry.write().unwrap();
-        let key = BifKey::new(module, function, arity);
-        
-        if registry.contains_key(&key) {
+        let number = registry.next_number;
+        let arities = registry.by_name.entry((module, function)).or_default();
+
+        if arities.contains_key(&arity) {
             return Err(format!("BIF {}/{} already registered", function, arity));
         }
-        
-        registry.insert(key, bif_func);
+
+        arities.insert(arity, BifEntry { func, spec, number, yields });
+        registry.next_number += 1;
         Ok(())
     }
 
+    /// Replace the implementation of a registered BIF
+    ///
+    /// The swap is atomic: every call dispatched after it returns uses the
+    /// new implementation, while calls already running finish on the old
+    /// one. The argument specification, number and trap export of the BIF
+    /// are kept.
+    ///
+    /// # Arguments
+    /// * `module` - Module atom
+    /// * `function` - Function atom
+    /// * `arity` - Function arity
+    /// * `bif_func` - New BIF function implementation
+    ///
+    /// # Returns
+    /// * `Ok(previous)` - The replaced implementation, e.g. for a wrapper to delegate to
+    /// * `Err(String)` - Error (BIF not registered)
+    pub fn replace(
+        &self,
+        module: Eterm,
+        function: Eterm,
+        arity: u32,
+        bif_func: Arc<dyn BifFunction + Send + Sync>,
+    ) -> Result<Arc<dyn BifFunction + Send + Sync>, String> {
+        let mut registry = self.registry.write().unwrap();
+        let entry = registry
+            .by_name
+            .get_mut(&(module, function))
+            .and_then(|arities| arities.get_mut(&arity))
+            .ok_or_else(|| format!("BIF {}/{} not registered", function, arity))?;
+        Ok(std::mem::replace(&mut entry.func, bif_func))
+    }
+
     /// Look up a BIF function
     ///
     /// # Arguments
@@ -93,9 +218,81 @@ impl BifRegistry {
         function: Eterm,
         arity: u32,
     ) -> Option<Arc<dyn BifFunction + Send + Sync>> {
+        self.lookup_entry(module, function, arity).map(|entry| entry.func)
+    }
+
+    /// Look up a BIF function together with its argument specification
+    ///
+    /// # Arguments
+    /// * `module` - Module atom
+    /// * `function` - Function atom
+    /// * `arity` - Function arity
+    ///
+    /// # Returns
+    /// * `Some(entry)` - BIF entry if found
+    /// * `None` - BIF not found
+    pub fn lookup_entry(&self, module: Eterm, function: Eterm, arity: u32) -> Option<BifEntry> {
+        let registry = self.registry.read().unwrap();
+        registry.by_name.get(&(module, function))?.get(&arity).cloned()
+    }
+
+    /// Look up the BIFs of every arity registered under a name
+    ///
+    /// # Arguments
+    /// * `module` - Module atom
+    /// * `function` - Function atom
+    ///
+    /// # Returns
+    /// Arity and entry of each BIF, in order of arity; empty if there are none
+    pub fn lookup_overloads(&self, module: Eterm, function: Eterm) -> Vec<(u32, BifEntry)> {
         let registry = self.registry.read().unwrap();
-        let key = BifKey::new(module, function, arity);
-        registry.get(&key).cloned()
+        registry
+            .by_name
+            .get(&(module, function))
+            .map(|arities| arities.iter().map(|(arity, entry)| (*arity, entry.clone())).collect())
+            .unwrap_or_default()
+    }
+
+    /// Get the trap export of a yielding BIF
+    ///
+    /// The export is generated from the current implementation, so a BIF
+    /// that yields after being replaced is re-entered in the new one.
+    ///
+    /// # Arguments
+    /// * `module` - Module atom
+    /// * `function` - Function atom
+    /// * `arity` - Function arity
+    ///
+    /// # Returns
+    /// * `Some(export)` - Trap export, carrying the BIF number
+    /// * `None` - BIF not found, or not registered as yielding
+    pub fn trap_export(&self, module: Eterm, function: Eterm, arity: u32) -> Option<TrapExport> {
+        let entry = self.lookup_entry(module, function, arity).filter(|entry| entry.yields)?;
+        let mut export = TrapExport::new(module, function, arity, Some(entry.func));
+        export.set_bif_number(entry.number);
+        Some(export)
+    }
+
+    /// List all registered BIFs
+    ///
+    /// The list is taken in one go, so it is consistent even while BIFs are
+    /// registered or replaced concurrently.
+    ///
+    /// # Returns
+    /// Key and entry of each BIF, ordered by module, function and arity
+    pub fn snapshot(&self) -> Vec<(BifKey, BifEntry)> {
+        let registry = self.registry.read().unwrap();
+        let mut bifs: Vec<_> = registry
+            .by_name
+            .iter()
+            .flat_map(|(&(module, function), arities)| {
+                arities
+                    .iter()
+                    .map(move |(arity, entry)| (BifKey::new(module, function, *arity), entry.clone()))
+            })
+            .collect();
+        bifs.sort_by(|(a, _), (b, _)| a.cmp(b));
+        bifs
     }
 
     /// Unregister a BIF function
@@ -110,20 +307,26 @@ impl BifRegistry {
     /// * `false` - BIF was not found
     pub fn unregister(&self, module: Eterm, function: Eterm, arity: u32) -> bool {
         let mut registry = self.registry.write().unwrap();
-        let key = BifKey::new(module, function, arity);
-        registry.remove(&key).is_some()
+        let Some(arities) = registry.by_name.get_mut(&(module, function)) else {
+            return false;
+        };
+        let removed = arities.remove(&arity).is_some();
+        if arities.is_empty() {
+            registry.by_name.remove(&(module, function));
+        }
+        removed
     }
 
     /// Get the number of registered BIFs
     pub fn len(&self) -> usize {
         let registry = self.registry.read().unwrap();
-        registry.len()
+        registry.by_name.values().map(BTreeMap::len).sum()
     }
 
     /// Check if registry is empty
     pub fn is_empty(&self) -> bool {
         let registry = self.registry.read().unwrap();
-        registry.is_empty()
+        registry.by_name.is_empty()
     }
 }
 
@@ -222,9 +425,143 @@ mod tests {
         assert!(!not_removed);
     }
 
+    #[test]
+    fn test_bif_registry_register_with_spec() {
+        use crate::arg_spec::ArgType;
+
+        let registry = BifRegistry::new();
+        let spec = ArgSpec::new(vec![ArgType::Atom, ArgType::Integer]);
+        registry.register_with_spec(1, 2, spec.clone(), Arc::new(TestBif)).unwrap();
+
+        assert!(registry.lookup(1, 2, 2).is_some());
+        assert_eq!(registry.lookup_entry(1, 2, 2).unwrap().spec, Some(spec));
+        assert!(registry.register(1, 2, 2, Arc::new(TestBif)).is_err());
+
+        registry.register(1, 3, 1, Arc::new(TestBif)).unwrap();
+        assert_eq!(registry.lookup_entry(1, 3, 1).unwrap().spec, None);
+    }
+
     #[test]
     fn test_global_registry() {
         let registry = get_global_registry();
         assert!(registry.is_empty() || registry.len() >= 0); // May have been initialized
     }
+
+    struct ConstBif(Eterm);
+
+    impl BifFunction for ConstBif {
+        fn call(
+            &self,
+            _process: &entities_process::Process,
+            _args: &[Eterm],
+            _instruction_ptr: entities_process::ErtsCodePtr,
+        ) -> Eterm {
+            self.0
+        }
+    }
+
+    fn call(func: &Arc<dyn BifFunction + Send + Sync>) -> Eterm {
+        func.call(&entities_process::Process::new(1), &[], std::ptr::null())
+    }
+
+    #[test]
+    fn test_bif_registry_overloads() {
+        let registry = BifRegistry::new();
+        registry.register(1, 2, 3, Arc::new(ConstBif(3))).unwrap();
+        registry.register(1, 2, 1, Arc::new(ConstBif(1))).unwrap();
+        registry.register(1, 5, 2, Arc::new(ConstBif(2))).unwrap();
+
+        let overloads = registry.lookup_overloads(1, 2);
+        let arities: Vec<_> = overloads.iter().map(|(arity, _)| *arity).collect();
+        assert_eq!(arities, vec![1, 3]);
+        assert_eq!(call(&overloads[0].1.func), 1);
+        assert_eq!(call(&registry.lookup(1, 2, 3).unwrap()), 3);
+        assert!(registry.lookup_overloads(1, 3).is_empty());
+
+        registry.unregister(1, 2, 1);
+        registry.unregister(1, 2, 3);
+        assert!(registry.lookup_overloads(1, 2).is_empty());
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn test_bif_registry_replace() {
+        let registry = BifRegistry::new();
+        let spec = ArgSpec::new(vec![]);
+        registry.register_with_spec(1, 2, spec.clone(), Arc::new(ConstBif(1))).unwrap();
+        let running = registry.lookup(1, 2, 0).unwrap();
+
+        let previous = registry.replace(1, 2, 0, Arc::new(ConstBif(2))).unwrap();
+        assert_eq!(call(&previous), 1);
+        assert_eq!(call(&running), 1);
+        let entry = registry.lookup_entry(1, 2, 0).unwrap();
+        assert_eq!(call(&entry.func), 2);
+        assert_eq!(entry.spec, Some(spec));
+        assert_eq!(entry.number, 0);
+
+        assert!(registry.replace(1, 2, 1, Arc::new(ConstBif(3))).is_err());
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn test_bif_registry_replace_concurrently() {
+        let registry = Arc::new(BifRegistry::new());
+        registry.register(1, 2, 0, Arc::new(ConstBif(0))).unwrap();
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let registry = Arc::clone(&registry);
+                std::thread::spawn(move || {
+                    let mut last = 0;
+                    for _ in 0..1000 {
+                        let value = call(&registry.lookup(1, 2, 0).unwrap());
+                        assert!(value >= last);
+                        last = value;
+                    }
+                })
+            })
+            .collect();
+        for value in 1..=100 {
+            registry.replace(1, 2, 0, Arc::new(ConstBif(value))).unwrap();
+        }
+        for reader in readers {
+            reader.join().unwrap();
+        }
+        assert_eq!(call(&registry.lookup(1, 2, 0).unwrap()), 100);
+    }
+
+    #[test]
+    fn test_bif_registry_trap_export() {
+        let registry = BifRegistry::new();
+        registry.register(1, 2, 1, Arc::new(ConstBif(1))).unwrap();
+        registry.register_yielding(1, 3, 2, Arc::new(ConstBif(2))).unwrap();
+
+        assert!(registry.trap_export(1, 2, 1).is_none());
+        assert!(registry.trap_export(1, 3, 1).is_none());
+
+        let export = registry.trap_export(1, 3, 2).unwrap();
+        assert_eq!((export.module(), export.function(), export.arity()), (1, 3, 2));
+        assert_eq!(export.bif_number(), 1);
+        assert_eq!(call(export.bif_func().unwrap()), 2);
+
+        registry.replace(1, 3, 2, Arc::new(ConstBif(3))).unwrap();
+        let export = registry.trap_export(1, 3, 2).unwrap();
+        assert_eq!(call(export.bif_func().unwrap()), 3);
+        assert_eq!(export.bif_number(), 1);
+    }
+
+    #[test]
+    fn test_bif_registry_snapshot() {
+        let registry = BifRegistry::new();
+        registry.register(2, 1, 0, Arc::new(ConstBif(0))).unwrap();
+        registry.register_yielding(1, 2, 2, Arc::new(ConstBif(1))).unwrap();
+        registry.register(1, 2, 1, Arc::new(ConstBif(2))).unwrap();
+
+        let snapshot = registry.snapshot();
+        let keys: Vec<_> = snapshot.iter().map(|(key, _)| key.clone()).collect();
+        assert_eq!(keys, vec![BifKey::new(1, 2, 1), BifKey::new(1, 2, 2), BifKey::new(2, 1, 0)]);
+        let numbers: Vec<_> = snapshot.iter().map(|(_, entry)| (entry.number, entry.yields)).collect();
+        assert_eq!(numbers, vec![(2, false), (1, true), (0, false)]);
+        assert!(BifRegistry::new().snapshot().is_empty());
+    }
 }
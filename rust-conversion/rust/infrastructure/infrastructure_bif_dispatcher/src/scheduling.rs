@@ -2,9 +2,38 @@
 //!
 //! Provides helper functions for scheduling BIFs, including trap preparation
 //! and yield handling. Based on scheduling functions from bif.c
+//!
+//! BIFs that can run for long (`lists:reverse/2`, `term_to_binary/1`, ETS
+//! `select`) do their work in slices through
+//! [`BifFunction::call_yielding`]: when a slice uses up its reductions the
+//! BIF returns [`BifYield::Yield`] with the remaining work, which
+//! [`call_yielding_bif`] saves on the process and hands back to the BIF when
+//! it is called again.
+
+use std::any::Any;
+use entities_process::{BifContinuation, Process, Eterm};
+use crate::initialization::{BifFunction, TrapExport};
+use crate::registry::BifKey;
+
+/// Reductions a yielding BIF may use per time slice (CONTEXT_REDS)
+pub const CONTEXT_REDS: usize = 4000;
+
+/// Outcome of one time slice of a yielding BIF
+pub enum BifYield {
+    /// The BIF is done and returned this term
+    Done(Eterm),
+    /// The BIF used up its reductions; the state holds the remaining work
+    Yield(Box<dyn Any + Send>),
+}
 
-use entities_process::{Process, Eterm};
-use crate::initialization::TrapExport;
+impl std::fmt::Debug for BifYield {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BifYield::Done(result) => f.debug_tuple("Done").field(result).finish(),
+            BifYield::Yield(_) => f.debug_tuple("Yield").finish_non_exhaustive(),
+        }
+    }
+}
 
 /// Scheduler type for BIF execution
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -216,10 +245,145 @@ pub fn reds_left(process: &Process) -> i32 {
     fcalls.max(0)
 }
 
+/// Run one time slice of a yielding BIF
+///
+/// Resumes the BIF from the continuation saved on the process if it is the
+/// BIF's own, and starts it afresh otherwise, dropping a continuation left
+/// by another BIF. If the BIF yields, its state is saved on the process for
+/// the next call.
+///
+/// # Arguments
+/// * `process` - Process calling the BIF
+/// * `key` - Module, function and arity of the BIF
+/// * `bif` - BIF implementation
+/// * `args` - BIF arguments
+/// * `reds` - Reductions the slice may use
+///
+/// # Returns
+/// * `Some(result)` - The BIF is done
+/// * `None` - The BIF yielded; call it again when the process is next scheduled
+pub fn call_yielding_bif(
+    process: &Process,
+    key: &BifKey,
+    bif: &(dyn BifFunction + Send + Sync),
+    args: &[Eterm],
+    reds: usize,
+) -> Option<Eterm> {
+    let state = process
+        .take_bif_continuation()
+        .filter(|c| c.module == key.module && c.function == key.function && c.arity == key.arity)
+        .map(|c| c.state);
+    match bif.call_yielding(process, args, state, reds) {
+        BifYield::Done(result) => Some(result),
+        BifYield::Yield(state) => {
+            process.save_bif_continuation(BifContinuation {
+                module: key.module,
+                function: key.function,
+                arity: key.arity,
+                state,
+            });
+            None
+        }
+    }
+}
+
+/// Run a yielding BIF to completion without giving up the scheduler
+///
+/// Lets a yielding BIF implement [`BifFunction::call`] in terms of
+/// [`BifFunction::call_yielding`], for callers that cannot yield.
+pub fn run_to_completion(bif: &dyn BifFunction, process: &Process, args: &[Eterm]) -> Eterm {
+    let mut state = None;
+    loop {
+        match bif.call_yielding(process, args, state, CONTEXT_REDS) {
+            BifYield::Done(result) => return result,
+            BifYield::Yield(next) => state = Some(next),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::initialization::TrapExport;
+    use entities_process::ErtsCodePtr;
+
+    /// Sums `1..=args[0]`, one reduction per element
+    struct SumTo;
+
+    impl BifFunction for SumTo {
+        fn call(&self, process: &Process, args: &[Eterm], _ip: ErtsCodePtr) -> Eterm {
+            run_to_completion(self, process, args)
+        }
+
+        fn call_yielding(
+            &self,
+            _process: &Process,
+            args: &[Eterm],
+            state: Option<Box<dyn Any + Send>>,
+            reds: usize,
+        ) -> BifYield {
+            let (mut next, mut sum) = state
+                .map(|s| *s.downcast::<(Eterm, Eterm)>().unwrap())
+                .unwrap_or((1, 0));
+            for _ in 0..reds {
+                if next > args[0] {
+                    return BifYield::Done(sum);
+                }
+                sum += next;
+                next += 1;
+            }
+            BifYield::Yield(Box::new((next, sum)))
+        }
+    }
+
+    struct Constant;
+
+    impl BifFunction for Constant {
+        fn call(&self, _process: &Process, _args: &[Eterm], _ip: ErtsCodePtr) -> Eterm {
+            42
+        }
+    }
+
+    #[test]
+    fn test_call_yielding_bif_resumes() {
+        let process = Process::new(1);
+        let key = BifKey::new(10, 20, 1);
+        let mut slices = 1;
+        let result = loop {
+            match call_yielding_bif(&process, &key, &SumTo, &[10_000], CONTEXT_REDS) {
+                Some(result) => break result,
+                None => {
+                    assert!(process.has_bif_continuation());
+                    slices += 1;
+                }
+            }
+        };
+        assert_eq!(result, 10_000 * 10_001 / 2);
+        assert_eq!(slices, 3);
+        assert!(!process.has_bif_continuation());
+    }
+
+    #[test]
+    fn test_call_yielding_bif_drops_foreign_continuation() {
+        let process = Process::new(1);
+        assert_eq!(call_yielding_bif(&process, &BifKey::new(10, 20, 1), &SumTo, &[10], 4), None);
+        assert_eq!(call_yielding_bif(&process, &BifKey::new(10, 21, 1), &SumTo, &[3], 4), Some(6));
+        assert!(!process.has_bif_continuation());
+    }
+
+    #[test]
+    fn test_call_yielding_bif_default_runs_call() {
+        let process = Process::new(1);
+        assert_eq!(call_yielding_bif(&process, &BifKey::new(1, 2, 0), &Constant, &[], 1), Some(42));
+        assert!(!process.has_bif_continuation());
+    }
+
+    #[test]
+    fn test_run_to_completion() {
+        let process = Process::new(1);
+        assert_eq!(SumTo.call(&process, &[20_000], std::ptr::null()), 20_000 * 20_001 / 2);
+        assert!(!process.has_bif_continuation());
+    }
 
     // SchedType tests
     #[test]
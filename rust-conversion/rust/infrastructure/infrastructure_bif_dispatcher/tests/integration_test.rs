@@ -229,3 +229,29 @@ fn test_bif_dispatcher_default() {
     let dispatcher = BifDispatcher::default();
     assert!(!dispatcher.is_initialized());
 }
+
+struct ArityBif;
+
+impl infrastructure_bif_dispatcher::initialization::BifFunction for ArityBif {
+    fn call(&self, _process: &Process, args: &[Eterm], _instruction_ptr: ErtsCodePtr) -> Eterm {
+        args.len() as Eterm
+    }
+}
+
+#[test]
+fn test_dispatch_bif_with_arg_spec() {
+    let registry = BifRegistry::new();
+    let spec = ArgSpec::new(vec![ArgType::PidOrPort, ArgType::List]);
+    registry.register_with_spec(10, 20, spec, Arc::new(ArityBif)).unwrap();
+    let process = Process::new(10);
+    let pid: Eterm = (42 << 4) | 0x3;
+    let nil: Eterm = 0x3B;
+
+    assert_eq!(dispatch_bif(&registry, &process, 10, 20, &[pid, nil], std::ptr::null()), Ok(2));
+
+    let err = dispatch_bif(&registry, &process, 10, 20, &[nil, pid], std::ptr::null()).unwrap_err();
+    let BifDispatcherError::Badarg(info) = err else {
+        panic!("Expected Badarg error");
+    };
+    assert_eq!(info.error_info(), vec![(1, "not a pid or port"), (2, "not a list")]);
+}
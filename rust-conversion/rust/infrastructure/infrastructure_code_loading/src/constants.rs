@@ -133,6 +133,9 @@ pub const ERL_V4_PORT_EXT: u8 = 120;
 /// New Port (32-bit id and creation)
 pub const ERL_NEW_PORT_EXT: u8 = 89;
 
+/// Compressed term (zlib)
+pub const ERL_COMPRESSED_EXT: u8 = 80;
+
 /// Maximum value for ERL_INTEGER_EXT (2^31 - 1)
 pub const ERL_MAX: i64 = 2_147_483_647;
 
@@ -40,6 +40,9 @@ use crate::constants::{ERL_FLOAT_EXT, NEW_FLOAT_EXT};
 
 /// Decode a double-precision floating-point number from EI format
 ///
+/// Accepts both `NEW_FLOAT_EXT` and the legacy `ERL_FLOAT_EXT`, whatever
+/// minor version the peer encoded with.
+///
 /// # Arguments
 /// * `buf` - Buffer containing EI-encoded data
 /// * `index` - Current index in buffer
@@ -80,7 +83,9 @@ pub fn decode_double(buf: &[u8], index: &mut usize) -> Result<f64, DecodeError>
             }
             let float_str = std::str::from_utf8(&buf[*index..*index + 31])
                 .map_err(|_| DecodeError::InvalidFormat("Invalid UTF-8 in float".to_string()))?;
+            // The string is zero-padded; older peers may also pad with spaces
             let value = float_str.trim_end_matches('\0')
+                .trim()
                 .parse::<f64>()
                 .map_err(|_| DecodeError::InvalidFormat("Invalid float format".to_string()))?;
             *index += 31;
@@ -357,5 +362,31 @@ mod tests {
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), DecodeError::BufferTooShort);
     }
-}
 
+    #[test]
+    fn test_decode_legacy_roundtrip_is_exact() {
+        use crate::encode_double::encode_double_with_minor_version;
+
+        for original in [0.1, -2.5e-300, 1.7976931348623157e308, std::f64::consts::E] {
+            for minor_version in [0, 1] {
+                let mut buf = vec![0u8; 32];
+                let mut encode_index = 0;
+                encode_double_with_minor_version(&mut Some(&mut buf), &mut encode_index, original, minor_version)
+                    .unwrap();
+                let mut decode_index = 0;
+                let decoded = decode_double(&buf, &mut decode_index).unwrap();
+                assert_eq!(decoded.to_bits(), original.to_bits());
+                assert_eq!(decode_index, encode_index);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_old_float_space_padded() {
+        let mut buf = vec![ERL_FLOAT_EXT];
+        buf.extend_from_slice(format!("{:<31}", "1.5e+01").as_bytes());
+        let mut index = 0;
+        assert_eq!(decode_double(&buf, &mut index).unwrap(), 15.0);
+        assert_eq!(index, 32);
+    }
+}
@@ -235,11 +235,10 @@ pub fn decode_fun(buf: &[u8], index: &mut usize) -> Result<ErlangFunType, Decode
             // Calculate remaining bytes for free variables
             // C code: n = n - (s - s0) + 1;
             // where n = total_size, s = current position, s0 = tag position
-            // This gives: remaining = total_size - (bytes_consumed_after_tag) + 1
-            // The +1 accounts for the fact that total_size includes the tag
-            let bytes_consumed_after_tag = *index - s0 - 1; // Subtract 1 for tag
-            let remaining_bytes = if total_size + 1 >= bytes_consumed_after_tag {
-                total_size + 1 - bytes_consumed_after_tag
+            // The size counts itself and all that follows it, but not the tag
+            let bytes_consumed = *index - s0;
+            let remaining_bytes = if total_size + 1 >= bytes_consumed {
+                total_size + 1 - bytes_consumed
             } else {
                 return Err(DecodeError::InvalidFormat("Invalid total_size in closure".to_string()));
             };
@@ -487,6 +486,7 @@ mod tests {
         let mut decode_index = 0;
         let result = decode_fun(&buf, &mut decode_index);
         assert!(result.is_ok());
+        assert_eq!(decode_index, encode_index);
         match result.unwrap() {
             ErlangFunType::Closure { arity, index: idx, uniq, old_index, md5: decoded_md5, n_free_vars, .. } => {
                 assert_eq!(arity, 2);
@@ -131,7 +131,7 @@ pub fn decode_longlong(buf: &[u8], index: &mut usize) -> Result<i64, DecodeError
                 if value > 0x8000000000000000 {
                     return Err(DecodeError::ValueTooLarge);
                 }
-                -(value as i64)
+                (value as i64).wrapping_neg()
             } else {
                 if value > 0x7FFFFFFFFFFFFFFF {
                     return Err(DecodeError::ValueTooLarge);
@@ -378,6 +378,18 @@ mod tests {
         assert_eq!(decoded, value);
     }
 
+    #[test]
+    fn test_decode_longlong_min() {
+        let buf = [ERL_SMALL_BIG_EXT, 8, 1, 0, 0, 0, 0, 0, 0, 0, 0x80];
+        let mut index = 0;
+        assert_eq!(decode_longlong(&buf, &mut index).unwrap(), i64::MIN);
+        assert_eq!(index, buf.len());
+
+        let buf = [ERL_SMALL_BIG_EXT, 8, 1, 1, 0, 0, 0, 0, 0, 0, 0x80];
+        let mut index = 0;
+        assert_eq!(decode_longlong(&buf, &mut index), Err(DecodeError::ValueTooLarge));
+    }
+
     #[test]
     fn test_decode_longlong_small_big_buffer_too_short_for_arity() {
         let buf = vec![ERL_SMALL_BIG_EXT];
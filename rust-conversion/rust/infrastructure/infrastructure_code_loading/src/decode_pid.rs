@@ -38,7 +38,7 @@
 //! Based on `lib/erl_interface/src/decode/decode_pid.c`
 
 use crate::constants::{ERL_NEW_PID_EXT, ERL_PID_EXT};
-use infrastructure_data_handling::decode_atom::decode_atom;
+use infrastructure_data_handling::decode_atom::decode_atom_name;
 use super::encode_pid::ErlangPid;
 
 /// Decode a PID from EI format
@@ -63,7 +63,7 @@ pub fn decode_pid(buf: &[u8], index: &mut usize) -> Result<ErlangPid, DecodeErro
     }
 
     // Decode node atom
-    let (node, new_pos) = decode_atom(buf, *index)
+    let (node, new_pos) = decode_atom_name(buf, *index)
         .map_err(|e| DecodeError::AtomDecodeError(format!("{:?}", e)))?;
     *index = new_pos;
 
@@ -146,11 +146,7 @@ mod tests {
         
         let mut decode_index = 0;
         let decoded = decode_pid(&buf, &mut decode_index).unwrap();
-        // Note: decode_atom returns a placeholder, so we can't compare node names
-        // But we can verify num, serial, and creation match
-        assert_eq!(decoded.num, pid.num);
-        assert_eq!(decoded.serial, pid.serial);
-        assert_eq!(decoded.creation, pid.creation);
+        assert_eq!(decoded, pid);
     }
 
     #[test]
@@ -37,7 +37,7 @@
 //! Based on `lib/erl_interface/src/decode/decode_port.c`
 
 use crate::constants::{ERL_V4_PORT_EXT, ERL_NEW_PORT_EXT, ERL_PORT_EXT};
-use infrastructure_data_handling::decode_atom::decode_atom;
+use infrastructure_data_handling::decode_atom::decode_atom_name;
 use super::encode_port::ErlangPort;
 
 /// Decode a port from EI format
@@ -63,7 +63,7 @@ pub fn decode_port(buf: &[u8], index: &mut usize) -> Result<ErlangPort, DecodeEr
     }
 
     // Decode node atom
-    let (node, new_pos) = decode_atom(buf, *index)
+    let (node, new_pos) = decode_atom_name(buf, *index)
         .map_err(|e| DecodeError::AtomDecodeError(format!("{:?}", e)))?;
     *index = new_pos;
 
@@ -147,10 +147,7 @@ mod tests {
         let mut decode_index = 0;
         let decoded = decode_port(&buf, &mut decode_index).unwrap();
         
-        // Note: decode_atom returns a placeholder, so we can't compare node names
-        // But we can verify id and creation match
-        assert_eq!(decoded.id, port.id);
-        assert_eq!(decoded.creation, port.creation);
+        assert_eq!(decoded, port);
     }
 
     #[test]
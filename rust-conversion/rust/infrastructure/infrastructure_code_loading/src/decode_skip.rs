@@ -8,26 +8,40 @@
 //!
 //! The `skip_term` function reads the tag byte of a term and advances the buffer index
 //! past the entire term without decoding its contents. This is more efficient than
-//! decoding and discarding the result.
+//! decoding and discarding the result. `skip_term_span` also returns the bytes the
+//! term spans, so a consumer can match on part of a message and keep the rest for
+//! later.
+//!
+//! Nested terms are skipped iteratively, keeping a count of the terms still to be
+//! skipped instead of recursing, so arbitrarily deep terms are safe to skip.
 //!
 //! ## Supported Term Types
 //!
 //! All EI format term types are supported:
 //! - Immediate values: integers, atoms, nil
-//! - Boxed values: floats, binaries, bitstrings
-//! - Compound types: tuples, lists, maps
+//! - Boxed values: floats, big integers, binaries, bitstrings, strings
+//! - Compound types: tuples, lists (including improper lists), maps
 //! - Process types: PIDs, ports, references
-//! - Special types: functions, exports, traces
+//! - Special types: functions, exports
+//! - Compressed terms, whose zlib stream is inflated to find where it ends
 //!
 //! ## Examples
 //!
 //! ```rust
+//! use infrastructure_code_loading::constants::*;
 //! use infrastructure_code_loading::decode_skip;
 //!
-//! // Skip over a term without decoding it
+//! // {1, [2]} followed by the atom ok
+//! let buf = [
+//!     ERL_SMALL_TUPLE_EXT, 2, ERL_SMALL_INTEGER_EXT, 1,
+//!     ERL_LIST_EXT, 0, 0, 0, 1, ERL_SMALL_INTEGER_EXT, 2, ERL_NIL_EXT,
+//!     ERL_SMALL_ATOM_EXT, 2, b'o', b'k',
+//! ];
 //! let mut index = 0;
-//! decode_skip::skip_term(&buf, &mut index)?;
+//! let span = decode_skip::skip_term_span(&buf, &mut index).unwrap();
+//! assert_eq!(span, 0..12);
 //! // index now points past the skipped term
+//! assert_eq!(buf[index], ERL_SMALL_ATOM_EXT);
 //! ```
 //!
 //! ## See Also
@@ -37,12 +51,12 @@
 //!
 //! Based on `lib/erl_interface/src/decode/decode_skip.c`
 
+use std::ops::Range;
+use flate2::{Decompress, FlushDecompress, Status};
 use crate::constants::*;
-use super::decode_double::decode_double;
 use super::decode_pid::decode_pid;
 use super::decode_port::decode_port;
 use super::decode_ref::decode_ref;
-use super::decode_fun::decode_fun;
 
 /// Error type for term skipping operations
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -59,339 +73,236 @@ pub enum SkipError {
 ///
 /// # Arguments
 /// * `buf` - Buffer containing EI-encoded data
-/// * `index` - Current index in buffer (will be updated)
+/// * `index` - Current index in buffer (updated only on success)
 ///
 /// # Returns
 /// * `Ok(())` - Successfully skipped the term
 /// * `Err(SkipError)` - Error skipping the term
 ///
 /// # Note
-/// This function recursively skips compound types (lists, tuples, maps).
+/// Compound types (lists, tuples, maps, funs) are skipped iteratively, so
+/// deeply nested terms cannot overflow the stack.
 pub fn skip_term(buf: &[u8], index: &mut usize) -> Result<(), SkipError> {
-    if *index >= buf.len() {
-        return Err(SkipError::BufferTooShort);
-    }
+    skip_term_span(buf, index).map(|_| ())
+}
 
-    let tag = buf[*index];
-    *index += 1;
+/// Skip over a term and return the bytes it spans
+///
+/// Lets a caller find a term, e.g. the control message of a distribution
+/// packet, and decode or forward it later without decoding it now.
+///
+/// # Arguments
+/// * `buf` - Buffer containing EI-encoded data
+/// * `index` - Current index in buffer (updated only on success)
+///
+/// # Returns
+/// * `Ok(Range<usize>)` - Range of `buf` holding the term, ending at the new index
+/// * `Err(SkipError)` - Error skipping the term
+pub fn skip_term_span(buf: &[u8], index: &mut usize) -> Result<Range<usize>, SkipError> {
+    let start = *index;
+    let mut pos = start;
+    // Terms still to be skipped, including the elements of enclosing
+    // compound terms
+    let mut pending: usize = 1;
 
-    match tag {
-        // Atoms
-        ERL_SMALL_ATOM_EXT => {
-            // Small atom: length (1 byte) + data
-            if *index >= buf.len() {
-                return Err(SkipError::BufferTooShort);
-            }
-            let length = buf[*index] as usize;
-            *index += 1;
-            if *index + length > buf.len() {
-                return Err(SkipError::BufferTooShort);
-            }
-            *index += length;
-        }
-        ERL_SMALL_ATOM_UTF8_EXT => {
-            // Small atom UTF-8: length (1 byte) + data
-            if *index >= buf.len() {
-                return Err(SkipError::BufferTooShort);
-            }
-            let length = buf[*index] as usize;
-            *index += 1;
-            if *index + length > buf.len() {
-                return Err(SkipError::BufferTooShort);
-            }
-            *index += length;
-        }
-        ERL_ATOM_EXT => {
-            // Atom: length (2 bytes) + data
-            if *index + 2 > buf.len() {
-                return Err(SkipError::BufferTooShort);
-            }
-            let length = u16::from_be_bytes([buf[*index], buf[*index + 1]]) as usize;
-            *index += 2;
-            if *index + length > buf.len() {
-                return Err(SkipError::BufferTooShort);
-            }
-            *index += length;
-        }
-        ERL_ATOM_UTF8_EXT => {
-            // Atom UTF-8: length (2 bytes) + data
-            if *index + 2 > buf.len() {
-                return Err(SkipError::BufferTooShort);
-            }
-            let length = u16::from_be_bytes([buf[*index], buf[*index + 1]]) as usize;
-            *index += 2;
-            if *index + length > buf.len() {
-                return Err(SkipError::BufferTooShort);
-            }
-            *index += length;
-        }
-        // PIDs
-        ERL_PID_EXT | ERL_NEW_PID_EXT => {
-            let _ = decode_pid(buf, index)
-                .map_err(|e| SkipError::DecodeError(format!("PID decode error: {:?}", e)))?;
-        }
-        // Ports
-        ERL_PORT_EXT | ERL_NEW_PORT_EXT | ERL_V4_PORT_EXT => {
-            let _ = decode_port(buf, index)
-                .map_err(|e| SkipError::DecodeError(format!("Port decode error: {:?}", e)))?;
-        }
-        // References
-        ERL_REFERENCE_EXT | ERL_NEW_REFERENCE_EXT | ERL_NEWER_REFERENCE_EXT => {
-            let _ = decode_ref(buf, index)
-                .map_err(|e| SkipError::DecodeError(format!("Ref decode error: {:?}", e)))?;
-        }
-        // Nil (empty list)
-        ERL_NIL_EXT => {
-            // Already consumed the tag, nothing more to skip
-        }
-        // Lists
-        ERL_LIST_EXT => {
-            // List: tag already consumed, now read length (4 bytes)
-            if *index + 4 > buf.len() {
-                return Err(SkipError::BufferTooShort);
-            }
-            let length = u32::from_be_bytes([
-                buf[*index],
-                buf[*index + 1],
-                buf[*index + 2],
-                buf[*index + 3],
-            ]) as usize;
-            *index += 4;
-            
-            // Skip all elements
-            for _ in 0..length {
-                skip_term(buf, index)?;
+    while pending > 0 {
+        pending -= 1;
+        let tag = read_u8(buf, &mut pos)?;
+
+        let elements = match tag {
+            // Atoms and strings
+            ERL_SMALL_ATOM_EXT | ERL_SMALL_ATOM_UTF8_EXT => {
+                let length = read_u8(buf, &mut pos)? as usize;
+                advance(buf, &mut pos, length)?;
+                0
             }
-            
-            // Check for tail (improper list)
-            if *index < buf.len() {
-                let tail_tag = buf[*index];
-                if tail_tag != ERL_NIL_EXT {
-                    skip_term(buf, index)?;
-                } else {
-                    *index += 1; // Skip ERL_NIL_EXT tag
-                }
+            ERL_ATOM_EXT | ERL_ATOM_UTF8_EXT | ERL_STRING_EXT => {
+                let length = read_u16(buf, &mut pos)?;
+                advance(buf, &mut pos, length)?;
+                0
             }
-        }
-        // Strings (ERL_STRING_EXT)
-        ERL_STRING_EXT => {
-            if *index + 2 > buf.len() {
-                return Err(SkipError::BufferTooShort);
+            // Integers and floats
+            ERL_SMALL_INTEGER_EXT => {
+                advance(buf, &mut pos, 1)?;
+                0
             }
-            let length = u16::from_be_bytes([buf[*index], buf[*index + 1]]) as usize;
-            *index += 2;
-            if *index + length > buf.len() {
-                return Err(SkipError::BufferTooShort);
+            ERL_INTEGER_EXT => {
+                advance(buf, &mut pos, 4)?;
+                0
             }
-            *index += length;
-        }
-        // Tuples
-        ERL_SMALL_TUPLE_EXT => {
-            // Small tuple: tag already consumed, now read arity (1 byte)
-            if *index >= buf.len() {
-                return Err(SkipError::BufferTooShort);
+            ERL_SMALL_BIG_EXT => {
+                // Digit count, sign byte, digits
+                let digits = read_u8(buf, &mut pos)? as usize;
+                advance(buf, &mut pos, 1 + digits)?;
+                0
             }
-            let arity = buf[*index] as usize;
-            *index += 1;
-            
-            // Skip all elements
-            for _ in 0..arity {
-                skip_term(buf, index)?;
+            ERL_LARGE_BIG_EXT => {
+                let digits = read_u32(buf, &mut pos)?;
+                advance(buf, &mut pos, 1)?;
+                advance(buf, &mut pos, digits)?;
+                0
             }
-        }
-        ERL_LARGE_TUPLE_EXT => {
-            // Large tuple: tag already consumed, now read arity (4 bytes)
-            if *index + 4 > buf.len() {
-                return Err(SkipError::BufferTooShort);
+            NEW_FLOAT_EXT => {
+                advance(buf, &mut pos, 8)?;
+                0
             }
-            let arity = u32::from_be_bytes([
-                buf[*index],
-                buf[*index + 1],
-                buf[*index + 2],
-                buf[*index + 3],
-            ]) as usize;
-            *index += 4;
-            
-            // Skip all elements
-            for _ in 0..arity {
-                skip_term(buf, index)?;
+            ERL_FLOAT_EXT => {
+                advance(buf, &mut pos, 31)?;
+                0
             }
-        }
-        // Maps
-        ERL_MAP_EXT => {
-            // Map: tag already consumed, now read arity (4 bytes)
-            if *index + 4 > buf.len() {
-                return Err(SkipError::BufferTooShort);
+            // Binaries
+            ERL_BINARY_EXT => {
+                let length = read_u32(buf, &mut pos)?;
+                advance(buf, &mut pos, length)?;
+                0
             }
-            let arity = u32::from_be_bytes([
-                buf[*index],
-                buf[*index + 1],
-                buf[*index + 2],
-                buf[*index + 3],
-            ]) as usize;
-            *index += 4;
-            
-            // Skip all key-value pairs (arity * 2 terms)
-            for _ in 0..(arity * 2) {
-                skip_term(buf, index)?;
+            ERL_BIT_BINARY_EXT => {
+                // Byte count, bits used in the last byte, data
+                let bytes = read_u32(buf, &mut pos)?;
+                advance(buf, &mut pos, 1)?;
+                advance(buf, &mut pos, bytes)?;
+                0
             }
-        }
-        // Binaries
-        ERL_BINARY_EXT => {
-            // Binary: length (4 bytes) + data
-            if *index + 4 > buf.len() {
-                return Err(SkipError::BufferTooShort);
+            // PIDs, ports and references
+            ERL_PID_EXT | ERL_NEW_PID_EXT => {
+                // The decoder reads the tag itself
+                pos -= 1;
+                decode_pid(buf, &mut pos)
+                    .map_err(|e| SkipError::DecodeError(format!("PID decode error: {:?}", e)))?;
+                0
             }
-            let length = u32::from_be_bytes([
-                buf[*index],
-                buf[*index + 1],
-                buf[*index + 2],
-                buf[*index + 3],
-            ]) as usize;
-            *index += 4;
-            if *index + length > buf.len() {
-                return Err(SkipError::BufferTooShort);
+            ERL_PORT_EXT | ERL_NEW_PORT_EXT | ERL_V4_PORT_EXT => {
+                // The decoder reads the tag itself
+                pos -= 1;
+                decode_port(buf, &mut pos)
+                    .map_err(|e| SkipError::DecodeError(format!("Port decode error: {:?}", e)))?;
+                0
             }
-            *index += length;
-        }
-        // Bit binaries
-        ERL_BIT_BINARY_EXT => {
-            if *index + 5 > buf.len() {
-                return Err(SkipError::BufferTooShort);
+            ERL_REFERENCE_EXT | ERL_NEW_REFERENCE_EXT | ERL_NEWER_REFERENCE_EXT => {
+                // The decoder reads the tag itself
+                pos -= 1;
+                decode_ref(buf, &mut pos)
+                    .map_err(|e| SkipError::DecodeError(format!("Ref decode error: {:?}", e)))?;
+                0
             }
-            let bytes = u32::from_be_bytes([
-                buf[*index],
-                buf[*index + 1],
-                buf[*index + 2],
-                buf[*index + 3],
-            ]) as usize;
-            *index += 4;
-            
-            // Read last_bits byte
-            if *index >= buf.len() {
-                return Err(SkipError::BufferTooShort);
+            // Tuples, lists and maps
+            ERL_NIL_EXT => 0,
+            ERL_SMALL_TUPLE_EXT => read_u8(buf, &mut pos)? as usize,
+            ERL_LARGE_TUPLE_EXT => read_u32(buf, &mut pos)?,
+            ERL_LIST_EXT => {
+                // The elements, then the tail
+                let length = read_u32(buf, &mut pos)?;
+                length.checked_add(1).ok_or_else(too_many_elements)?
             }
-            let _last_bits = buf[*index];
-            *index += 1;
-            
-            // Skip the binary data
-            if *index + bytes > buf.len() {
-                return Err(SkipError::BufferTooShort);
+            ERL_MAP_EXT => {
+                // A key and a value per pair
+                let pairs = read_u32(buf, &mut pos)?;
+                pairs.checked_mul(2).ok_or_else(too_many_elements)?
             }
-            *index += bytes;
-        }
-        // Small integer
-        ERL_SMALL_INTEGER_EXT => {
-            if *index >= buf.len() {
-                return Err(SkipError::BufferTooShort);
+            // Funs
+            ERL_EXPORT_EXT => {
+                // Module, function and arity
+                3
             }
-            *index += 1;
-        }
-        // Integer
-        ERL_INTEGER_EXT => {
-            if *index + 4 > buf.len() {
-                return Err(SkipError::BufferTooShort);
+            ERL_FUN_EXT => {
+                // Pid, module, index and uniq, then the free variables
+                let free_vars = read_u32(buf, &mut pos)?;
+                free_vars.checked_add(4).ok_or_else(too_many_elements)?
             }
-            *index += 4;
-        }
-        // Big integers
-        ERL_SMALL_BIG_EXT | ERL_LARGE_BIG_EXT => {
-            // Skip big integer: arity + sign + data
-            let arity = if tag == ERL_SMALL_BIG_EXT {
-                if *index >= buf.len() {
-                    return Err(SkipError::BufferTooShort);
+            ERL_NEW_FUN_EXT => {
+                // The size counts itself and all that follows it
+                let size = read_u32(buf, &mut pos)?;
+                if size < 4 {
+                    return Err(SkipError::InvalidFormat(format!("Invalid fun size: {}", size)));
                 }
-                let a = buf[*index] as usize;
-                *index += 1;
-                a
-            } else {
-                if *index + 4 > buf.len() {
-                    return Err(SkipError::BufferTooShort);
-                }
-                let a = u32::from_be_bytes([
-                    buf[*index],
-                    buf[*index + 1],
-                    buf[*index + 2],
-                    buf[*index + 3],
-                ]) as usize;
-                *index += 4;
-                a
-            };
-            
-            // Skip sign byte
-            if *index >= buf.len() {
-                return Err(SkipError::BufferTooShort);
+                advance(buf, &mut pos, size - 4)?;
+                0
             }
-            *index += 1;
-            
-            // Skip data bytes
-            if *index + arity > buf.len() {
-                return Err(SkipError::BufferTooShort);
+            // Compressed terms
+            ERL_COMPRESSED_EXT => {
+                // Uncompressed size, then a zlib stream of unknown length
+                let size = read_u32(buf, &mut pos)?;
+                pos += compressed_length(&buf[pos..], size)?;
+                0
             }
-            *index += arity;
-        }
-        // Floats
-        ERL_FLOAT_EXT | NEW_FLOAT_EXT => {
-            let _ = decode_double(buf, index)
-                .map_err(|e| SkipError::DecodeError(format!("Double decode error: {:?}", e)))?;
-        }
-        // Functions
-        ERL_FUN_EXT | ERL_NEW_FUN_EXT | ERL_EXPORT_EXT => {
-            let _ = decode_fun(buf, index)
-                .map_err(|e| SkipError::DecodeError(format!("Fun decode error: {:?}", e)))?;
-        }
-        // Trace (note: ERL_TRACE_EXT and ERL_V4_PORT_EXT both have value 120,
-        // but trace is handled as a tuple, so this case is actually unreachable)
-        // We'll handle it in the default case if needed
-        #[allow(unreachable_patterns)]
-        ERL_TRACE_EXT => {
-            // Trace is a tuple with 5 elements: flags, label, serial, from_pid, prev
-            // We can skip it by treating it as a tuple - but we need to check the next byte
-            // to see if it's a small or large tuple
-            if *index >= buf.len() {
-                return Err(SkipError::BufferTooShort);
+            _ => {
+                return Err(SkipError::InvalidFormat(format!("Unexpected tag: {}", tag)));
             }
-            let tuple_tag = buf[*index];
-            let arity = match tuple_tag {
-                ERL_SMALL_TUPLE_EXT => {
-                    *index += 1;
-                    if *index >= buf.len() {
-                        return Err(SkipError::BufferTooShort);
-                    }
-                    let a = buf[*index] as usize;
-                    *index += 1;
-                    a
-                }
-                ERL_LARGE_TUPLE_EXT => {
-                    *index += 1;
-                    if *index + 4 > buf.len() {
-                        return Err(SkipError::BufferTooShort);
-                    }
-                    let a = u32::from_be_bytes([
-                        buf[*index],
-                        buf[*index + 1],
-                        buf[*index + 2],
-                        buf[*index + 3],
-                    ]) as usize;
-                    *index += 4;
-                    a
-                }
-                _ => return Err(SkipError::InvalidFormat(format!("Trace should start with tuple, got tag: {}", tuple_tag))),
-            };
-            
-            // Skip all elements (should be 5, but we'll skip whatever arity we got)
-            for _ in 0..arity {
-                skip_term(buf, index)?;
-            }
-        }
-        _ => {
-            return Err(SkipError::InvalidFormat(format!("Unexpected tag: {}", tag)));
-        }
+        };
+
+        pending = pending.checked_add(elements).ok_or_else(too_many_elements)?;
     }
 
+    *index = pos;
+    Ok(start..pos)
+}
+
+fn too_many_elements() -> SkipError {
+    SkipError::InvalidFormat("Too many elements".to_string())
+}
+
+fn advance(buf: &[u8], pos: &mut usize, count: usize) -> Result<(), SkipError> {
+    if count > buf.len().saturating_sub(*pos) {
+        return Err(SkipError::BufferTooShort);
+    }
+    *pos += count;
     Ok(())
 }
 
+fn read_u8(buf: &[u8], pos: &mut usize) -> Result<u8, SkipError> {
+    let value = *buf.get(*pos).ok_or(SkipError::BufferTooShort)?;
+    *pos += 1;
+    Ok(value)
+}
+
+fn read_u16(buf: &[u8], pos: &mut usize) -> Result<usize, SkipError> {
+    let bytes = buf.get(*pos..*pos + 2).ok_or(SkipError::BufferTooShort)?;
+    *pos += 2;
+    Ok(u16::from_be_bytes([bytes[0], bytes[1]]) as usize)
+}
+
+fn read_u32(buf: &[u8], pos: &mut usize) -> Result<usize, SkipError> {
+    let bytes = buf.get(*pos..*pos + 4).ok_or(SkipError::BufferTooShort)?;
+    *pos += 4;
+    Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+}
+
+/// Find the length of the zlib stream at the start of `data`, which must
+/// inflate to exactly `size` bytes
+///
+/// The output is inflated into scratch space and dropped, so a bogus `size`
+/// costs no memory.
+fn compressed_length(data: &[u8], size: usize) -> Result<usize, SkipError> {
+    let mut inflater = Decompress::new(true);
+    let mut scratch = [0u8; 4096];
+    loop {
+        let (read, written) = (inflater.total_in(), inflater.total_out());
+        let status = inflater
+            .decompress(&data[read as usize..], &mut scratch, FlushDecompress::None)
+            .map_err(|e| SkipError::DecodeError(format!("Inflate error: {}", e)))?;
+        if inflater.total_out() as usize > size {
+            return Err(SkipError::InvalidFormat(format!(
+                "Compressed term inflates to more than {} bytes",
+                size
+            )));
+        }
+        match status {
+            Status::StreamEnd => break,
+            Status::Ok if (inflater.total_in(), inflater.total_out()) != (read, written) => {}
+            // No progress: the stream is cut short
+            _ => return Err(SkipError::BufferTooShort),
+        }
+    }
+    if inflater.total_out() as usize != size {
+        return Err(SkipError::InvalidFormat(format!(
+            "Compressed term inflates to {} bytes, expected {}",
+            inflater.total_out(),
+            size
+        )));
+    }
+    Ok(inflater.total_in() as usize)
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -641,47 +552,27 @@ mod tests {
     #[test]
     fn test_skip_new_float() {
         // New float: tag + 8 bytes (IEEE 754)
-        // decode_double expects NEW_FLOAT_EXT tag at current index
-        // But skip_term already consumed the tag, so we need to put it back
-        // Actually, looking at the code, skip_term calls decode_double after consuming tag
-        // So decode_double will read the first byte of float data as the tag
-        // This will fail, but we're testing the code path
         let value: f64 = 3.14;
         let mut buf = vec![NEW_FLOAT_EXT];
         buf.extend_from_slice(&value.to_bits().to_be_bytes());
         let mut index = 0;
-        // decode_double will see the first byte of float data, not the tag
-        let result = skip_term(&buf, &mut index);
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            SkipError::DecodeError(_) => {}
-            _ => {}
-        }
+        skip_term(&buf, &mut index).unwrap();
+        assert_eq!(index, 9);
     }
     
     #[test]
     fn test_skip_float_ext() {
         // Old float: tag + 31 bytes
-        // Similar issue - decode_double expects tag at current index
         let mut buf = vec![ERL_FLOAT_EXT];
-        // Create valid old float format: "3.14159..." padded to 31 bytes
         let float_str = format!("{:31}", "3.14159");
         buf.extend_from_slice(float_str.as_bytes());
         let mut index = 0;
-        // decode_double will see the first byte of float string, not the tag
-        let result = skip_term(&buf, &mut index);
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            SkipError::DecodeError(_) => {}
-            _ => {}
-        }
+        skip_term(&buf, &mut index).unwrap();
+        assert_eq!(index, 32);
     }
     
     #[test]
     fn test_skip_pid_ext() {
-        // PID_EXT format - decode_pid expects the tag to be at current index
-        // But skip_term already consumed the tag, so decode_pid will see the next byte
-        // (which should be the atom tag, not a PID tag)
         let mut buf = vec![ERL_PID_EXT];
         buf.push(ERL_SMALL_ATOM_EXT);
         buf.push(4);
@@ -690,19 +581,12 @@ mod tests {
         buf.extend_from_slice(&[0, 0, 0, 0]); // serial
         buf.push(0); // creation
         let mut index = 0;
-        // decode_pid will see ERL_SMALL_ATOM_EXT instead of PID tag, so it will error
-        let result = skip_term(&buf, &mut index);
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            SkipError::DecodeError(_) => {}
-            _ => {}
-        }
+        skip_term(&buf, &mut index).unwrap();
+        assert_eq!(index, buf.len());
     }
     
     #[test]
     fn test_skip_new_pid_ext() {
-        // Test NEW_PID_EXT - similar issue, decode_pid expects tag at current index
-        // Let's just verify the code path is called
         let mut buf = vec![ERL_NEW_PID_EXT];
         buf.push(ERL_SMALL_ATOM_EXT);
         buf.push(4);
@@ -711,18 +595,12 @@ mod tests {
         buf.extend_from_slice(&[0, 0, 0, 0]); // serial
         buf.extend_from_slice(&[0, 0, 0, 0]); // creation
         let mut index = 0;
-        // decode_pid will see ERL_SMALL_ATOM_EXT instead of PID tag, so it will error
-        let result = skip_term(&buf, &mut index);
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            SkipError::DecodeError(_) => {}
-            _ => {}
-        }
+        skip_term(&buf, &mut index).unwrap();
+        assert_eq!(index, buf.len());
     }
     
     #[test]
     fn test_skip_port_ext() {
-        // Similar to PID - decode_port expects tag at current index
         let mut buf = vec![ERL_PORT_EXT];
         buf.push(ERL_SMALL_ATOM_EXT);
         buf.push(4);
@@ -730,12 +608,8 @@ mod tests {
         buf.extend_from_slice(&[0, 0, 0, 1]); // id
         buf.push(0); // creation
         let mut index = 0;
-        let result = skip_term(&buf, &mut index);
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            SkipError::DecodeError(_) => {}
-            _ => {}
-        }
+        skip_term(&buf, &mut index).unwrap();
+        assert_eq!(index, buf.len());
     }
     
     #[test]
@@ -747,12 +621,8 @@ mod tests {
         buf.extend_from_slice(&[0, 0, 0, 1]); // id
         buf.extend_from_slice(&[0, 0, 0, 0]); // creation
         let mut index = 0;
-        let result = skip_term(&buf, &mut index);
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            SkipError::DecodeError(_) => {}
-            _ => {}
-        }
+        skip_term(&buf, &mut index).unwrap();
+        assert_eq!(index, buf.len());
     }
     
     #[test]
@@ -765,12 +635,8 @@ mod tests {
         buf.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]); // id (8 bytes)
         buf.extend_from_slice(&[0, 0, 0, 0]); // creation
         let mut index = 0;
-        let result = skip_term(&buf, &mut index);
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            SkipError::DecodeError(_) => {}
-            _ => {}
-        }
+        skip_term(&buf, &mut index).unwrap();
+        assert_eq!(index, buf.len());
     }
     
     #[test]
@@ -782,12 +648,8 @@ mod tests {
         buf.extend_from_slice(&[0, 0, 0, 1]); // id
         buf.push(0); // creation
         let mut index = 0;
-        let result = skip_term(&buf, &mut index);
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            SkipError::DecodeError(_) => {}
-            _ => {}
-        }
+        skip_term(&buf, &mut index).unwrap();
+        assert_eq!(index, buf.len());
     }
     
     #[test]
@@ -797,15 +659,11 @@ mod tests {
         buf.push(ERL_SMALL_ATOM_EXT);
         buf.push(4);
         buf.extend_from_slice(b"node");
-        buf.extend_from_slice(&[0, 0, 0, 0]); // creation
+        buf.push(0); // creation
         buf.extend_from_slice(&[0, 0, 0, 1]); // id
         let mut index = 0;
-        let result = skip_term(&buf, &mut index);
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            SkipError::DecodeError(_) => {}
-            _ => {}
-        }
+        skip_term(&buf, &mut index).unwrap();
+        assert_eq!(index, buf.len());
     }
     
     #[test]
@@ -818,53 +676,102 @@ mod tests {
         buf.extend_from_slice(&[0, 0, 0, 0]); // creation
         buf.extend_from_slice(&[0, 0, 0, 1]); // id
         let mut index = 0;
-        let result = skip_term(&buf, &mut index);
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            SkipError::DecodeError(_) => {}
-            _ => {}
-        }
+        skip_term(&buf, &mut index).unwrap();
+        assert_eq!(index, buf.len());
     }
     
     #[test]
     fn test_skip_fun_ext() {
-        // FUN_EXT format (uses decode_fun)
-        let mut buf = vec![ERL_FUN_EXT];
-        buf.push(0); // At least one byte
+        use crate::encode_fun::{encode_fun, ErlangFunType};
+        use crate::encode_pid::ErlangPid;
+
+        // Old format closure with two free variables, followed by nil
+        let fun = ErlangFunType::Closure {
+            arity: -1,
+            module: "test".to_string(),
+            index: 1,
+            uniq: 2,
+            old_index: None,
+            md5: None,
+            n_free_vars: 2,
+            free_vars: vec![ERL_SMALL_INTEGER_EXT, 7, ERL_NIL_EXT],
+            pid: ErlangPid { node: "node@host".to_string(), num: 1, serial: 0, creation: 1 },
+        };
+        let mut buf = vec![0u8; 100];
+        let mut end = 0;
+        encode_fun(&mut Some(&mut buf[..]), &mut end, &fun).unwrap();
+        buf.truncate(end);
+        buf.push(ERL_NIL_EXT);
         let mut index = 0;
-        let result = skip_term(&buf, &mut index);
-        // decode_fun will likely fail, but we're testing the code path
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            SkipError::DecodeError(_) => {}
-            _ => {}
-        }
+        skip_term(&buf, &mut index).unwrap();
+        assert_eq!(index, end);
+    }
+
+    #[test]
+    fn test_skip_fun_ext_truncated() {
+        let buf = vec![ERL_FUN_EXT, 0];
+        let mut index = 0;
+        assert_eq!(skip_term(&buf, &mut index), Err(SkipError::BufferTooShort));
+        assert_eq!(index, 0);
     }
     
     #[test]
     fn test_skip_new_fun_ext() {
-        let mut buf = vec![ERL_NEW_FUN_EXT];
-        buf.push(0);
+        use crate::encode_fun::{encode_fun, ErlangFunType};
+        use crate::encode_pid::ErlangPid;
+
+        let fun = ErlangFunType::Closure {
+            arity: 1,
+            module: "test".to_string(),
+            index: 1,
+            uniq: 2,
+            old_index: Some(3),
+            md5: Some([9; 16]),
+            n_free_vars: 1,
+            free_vars: vec![ERL_SMALL_INTEGER_EXT, 7],
+            pid: ErlangPid { node: "node@host".to_string(), num: 1, serial: 0, creation: 1 },
+        };
+        let mut buf = vec![0u8; 100];
+        let mut end = 0;
+        encode_fun(&mut Some(&mut buf[..]), &mut end, &fun).unwrap();
+        buf.truncate(end);
+        buf.push(ERL_NIL_EXT);
         let mut index = 0;
-        let result = skip_term(&buf, &mut index);
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            SkipError::DecodeError(_) => {}
-            _ => {}
-        }
+        skip_term(&buf, &mut index).unwrap();
+        assert_eq!(index, end);
+    }
+
+    #[test]
+    fn test_skip_new_fun_ext_invalid_size() {
+        let buf = vec![ERL_NEW_FUN_EXT, 0, 0, 0, 2];
+        let mut index = 0;
+        assert!(matches!(skip_term(&buf, &mut index), Err(SkipError::InvalidFormat(_))));
+
+        let buf = vec![ERL_NEW_FUN_EXT, 0, 0, 0, 100, 0];
+        assert_eq!(skip_term(&buf, &mut index), Err(SkipError::BufferTooShort));
     }
     
     #[test]
     fn test_skip_export_ext() {
-        let mut buf = vec![ERL_EXPORT_EXT];
-        buf.push(0);
+        use crate::encode_fun::{encode_fun, ErlangFunType};
+
+        let fun = ErlangFunType::Export {
+            module: "lists".to_string(),
+            function: "map".to_string(),
+            arity: 2,
+        };
+        let mut buf = vec![0u8; 100];
+        let mut end = 0;
+        encode_fun(&mut Some(&mut buf[..]), &mut end, &fun).unwrap();
+        buf.truncate(end);
+        buf.push(ERL_NIL_EXT);
         let mut index = 0;
-        let result = skip_term(&buf, &mut index);
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            SkipError::DecodeError(_) => {}
-            _ => {}
-        }
+        skip_term(&buf, &mut index).unwrap();
+        assert_eq!(index, end);
+
+        let buf = vec![ERL_EXPORT_EXT, 0];
+        let mut index = 0;
+        assert!(matches!(skip_term(&buf, &mut index), Err(SkipError::InvalidFormat(_))));
     }
     
     #[test]
@@ -883,14 +790,8 @@ mod tests {
         buf.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]); // id (8 bytes)
         buf.extend_from_slice(&[0, 0, 0, 0]); // creation
         let mut index = 0;
-        // This will try to decode as V4_PORT_EXT, which will fail
-        // but we're testing the code path
-        let result = skip_term(&buf, &mut index);
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            SkipError::DecodeError(_) => {}
-            _ => {}
-        }
+        skip_term(&buf, &mut index).unwrap();
+        assert_eq!(index, buf.len());
     }
     
     #[test]
@@ -906,8 +807,8 @@ mod tests {
         buf.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]);
         buf.extend_from_slice(&[0, 0, 0, 0]);
         let mut index = 0;
-        let result = skip_term(&buf, &mut index);
-        assert!(result.is_err());
+        skip_term(&buf, &mut index).unwrap();
+        assert_eq!(index, buf.len());
     }
     
     #[test]
@@ -1159,5 +1060,107 @@ mod tests {
         skip_term(&buf, &mut index).unwrap();
         assert!(index > 0);
     }
-}
 
+    #[test]
+    fn test_skip_term_span() {
+        // ok followed by {1, 2}
+        let buf = vec![
+            ERL_SMALL_ATOM_EXT, 2, b'o', b'k',
+            ERL_SMALL_TUPLE_EXT, 2, ERL_SMALL_INTEGER_EXT, 1, ERL_SMALL_INTEGER_EXT, 2,
+        ];
+        let mut index = 0;
+        assert_eq!(skip_term_span(&buf, &mut index), Ok(0..4));
+        assert_eq!(skip_term_span(&buf, &mut index), Ok(4..10));
+        assert_eq!(index, buf.len());
+    }
+
+    #[test]
+    fn test_skip_error_keeps_index() {
+        // List whose tail is missing
+        let buf = vec![ERL_LIST_EXT, 0, 0, 0, 1, ERL_SMALL_INTEGER_EXT, 1];
+        let mut index = 0;
+        assert_eq!(skip_term(&buf, &mut index), Err(SkipError::BufferTooShort));
+        assert_eq!(index, 0);
+    }
+
+    #[test]
+    fn test_skip_map_of_compound_terms() {
+        // #{[1] => {a}, <<2>> => 3}
+        let buf = vec![
+            ERL_MAP_EXT, 0, 0, 0, 2,
+            ERL_LIST_EXT, 0, 0, 0, 1, ERL_SMALL_INTEGER_EXT, 1, ERL_NIL_EXT,
+            ERL_SMALL_TUPLE_EXT, 1, ERL_SMALL_ATOM_EXT, 1, b'a',
+            ERL_BINARY_EXT, 0, 0, 0, 1, 2,
+            ERL_SMALL_INTEGER_EXT, 3,
+        ];
+        let mut index = 0;
+        skip_term(&buf, &mut index).unwrap();
+        assert_eq!(index, buf.len());
+    }
+
+    #[test]
+    fn test_skip_deeply_nested_without_recursion() {
+        // 1,000,000 nested one-element tuples around nil
+        let depth = 1_000_000;
+        let mut buf = Vec::with_capacity(depth * 2 + 1);
+        for _ in 0..depth {
+            buf.extend_from_slice(&[ERL_SMALL_TUPLE_EXT, 1]);
+        }
+        buf.push(ERL_NIL_EXT);
+        let mut index = 0;
+        skip_term(&buf, &mut index).unwrap();
+        assert_eq!(index, buf.len());
+    }
+
+    #[test]
+    fn test_skip_huge_arity() {
+        let buf = vec![ERL_MAP_EXT, 0xff, 0xff, 0xff, 0xff, ERL_NIL_EXT];
+        let mut index = 0;
+        assert_eq!(skip_term(&buf, &mut index), Err(SkipError::BufferTooShort));
+    }
+
+    fn compress(term: &[u8]) -> Vec<u8> {
+        use flate2::{Compress, Compression, FlushCompress};
+
+        let mut compressor = Compress::new(Compression::default(), true);
+        let mut data = Vec::with_capacity(term.len() + 64);
+        compressor.compress_vec(term, &mut data, FlushCompress::Finish).unwrap();
+        let mut buf = vec![ERL_COMPRESSED_EXT];
+        buf.extend_from_slice(&(term.len() as u32).to_be_bytes());
+        buf.extend_from_slice(&data);
+        buf
+    }
+
+    #[test]
+    fn test_skip_compressed() {
+        let mut term = vec![ERL_BINARY_EXT, 0, 0, 0x27, 0x10];
+        term.extend_from_slice(&[0u8; 10_000]);
+        let mut buf = compress(&term);
+        let end = buf.len();
+        buf.push(ERL_NIL_EXT);
+        let mut index = 0;
+        assert_eq!(skip_term_span(&buf, &mut index), Ok(0..end));
+    }
+
+    #[test]
+    fn test_skip_compressed_errors() {
+        let term = vec![ERL_SMALL_INTEGER_EXT, 42];
+
+        // Cut short
+        let buf = compress(&term);
+        let mut index = 0;
+        assert_eq!(skip_term(&buf[..buf.len() - 2], &mut index), Err(SkipError::BufferTooShort));
+
+        // Wrong uncompressed size, either way
+        for size in [1u32, 3] {
+            let mut buf = compress(&term);
+            buf[1..5].copy_from_slice(&size.to_be_bytes());
+            assert!(matches!(skip_term(&buf, &mut index), Err(SkipError::InvalidFormat(_))));
+        }
+
+        // Not zlib
+        let buf = vec![ERL_COMPRESSED_EXT, 0, 0, 0, 2, 0xff, 0xff];
+        assert!(matches!(skip_term(&buf, &mut index), Err(SkipError::DecodeError(_))));
+        assert_eq!(index, 0);
+    }
+}
@@ -138,15 +138,11 @@ mod tests {
         
         let mut decode_index = 0;
         let decoded = decode_trace(&buf, &mut decode_index).unwrap();
-        // Note: decode_atom returns a placeholder, so we can't compare node names in from PID
-        // But we can verify other fields match
         assert_eq!(decoded.flags, trace.flags);
         assert_eq!(decoded.label, trace.label);
         assert_eq!(decoded.serial, trace.serial);
         assert_eq!(decoded.prev, trace.prev);
-        assert_eq!(decoded.from.num, trace.from.num);
-        assert_eq!(decoded.from.serial, trace.from.serial);
-        assert_eq!(decoded.from.creation, trace.from.creation);
+        assert_eq!(decoded.from, trace.from);
     }
 
     #[test]
@@ -0,0 +1,561 @@
+//! Growable EI Buffer Module
+//!
+//! Provides [`EiBuffer`], the equivalent of the `ei_x_buff` dynamic buffer of
+//! erl_interface, and [`EiTermIterator`] for walking the structure of an
+//! encoded term without decoding it.
+//!
+//! ## Overview
+//!
+//! The encode functions of this crate write into a caller-provided slice, or
+//! only compute the size when given `None`. An `EiBuffer` does both: each
+//! `encode_*` method sizes the term, grows the buffer to fit and writes it at
+//! the buffer's index, as the `ei_x_encode_*` functions do. The same index is
+//! the cursor of the `decode_*` methods, which advance it past each term they
+//! decode and leave it unchanged on error.
+//!
+//! ## Examples
+//!
+//! ```rust
+//! use infrastructure_code_loading::ei_buffer::EiBuffer;
+//!
+//! let mut x = EiBuffer::new_with_version();
+//! x.encode_tuple_header(2).unwrap();
+//! x.encode_atom("ok").unwrap();
+//! x.encode_long(42).unwrap();
+//!
+//! let mut reader = EiBuffer::from_bytes(x.into_bytes());
+//! reader.decode_version().unwrap();
+//! assert_eq!(reader.decode_tuple_header().unwrap(), 2);
+//! assert_eq!(reader.decode_atom().unwrap(), "ok");
+//! assert_eq!(reader.decode_long().unwrap(), 42);
+//! ```
+//!
+//! ## See Also
+//!
+//! - [`decode_skip`](super::decode_skip/index.html): Skipping terms, used by the iterator
+//! - [`infrastructure_data_handling`](../infrastructure_data_handling/index.html): Atom and binary encoding
+//!
+//! Based on `lib/erl_interface/src/misc/ei_x_encode.c` and `ei_decode_term.c`
+
+use crate::constants::*;
+use crate::decode_skip::skip_term;
+use crate::encode_pid::ErlangPid;
+use crate::encode_port::ErlangPort;
+use crate::encode_ref::ErlangRef;
+use entities_data_handling::atom::AtomEncoding;
+
+/// Error type for EI buffer operations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EiBufferError {
+    /// The buffer does not start with the version byte (131)
+    BadVersion(u8),
+    /// Encoding failed
+    EncodeError(String),
+    /// Decoding failed
+    DecodeError(String),
+}
+
+/// Growable buffer of EI-encoded terms (`ei_x_buff`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EiBuffer {
+    buf: Vec<u8>,
+    index: usize,
+}
+
+impl EiBuffer {
+    /// Create an empty buffer (`ei_x_new`)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a buffer starting with the version byte (`ei_x_new_with_version`)
+    pub fn new_with_version() -> Self {
+        Self { buf: vec![ERL_VERSION], index: 1 }
+    }
+
+    /// Create a buffer to decode encoded bytes, with the index at the start
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self { buf: bytes, index: 0 }
+    }
+
+    /// Get the contents of the buffer
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Take the contents of the buffer
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+
+    /// Get the size of the contents in bytes
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Check if the buffer is empty
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// Get the index: where the next term is encoded to or decoded from
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Move the index, at most to the end of the contents
+    pub fn set_index(&mut self, index: usize) {
+        self.index = index.min(self.buf.len());
+    }
+
+    /// Append the contents of another buffer (`ei_x_append`)
+    pub fn append(&mut self, other: &EiBuffer) {
+        self.buf.truncate(self.index);
+        self.buf.extend_from_slice(&other.buf);
+        self.index = self.buf.len();
+    }
+
+    /// Size, grow and write a term with one of the encode functions
+    ///
+    /// Anything after the index is overwritten, as encoding always ends the
+    /// contents.
+    fn encode_with<E: std::fmt::Debug>(
+        &mut self,
+        encode: impl Fn(&mut Option<&mut [u8]>, &mut usize) -> Result<(), E>,
+    ) -> Result<(), EiBufferError> {
+        let error = |e: E| EiBufferError::EncodeError(format!("{:?}", e));
+        let mut end = self.index;
+        encode(&mut None, &mut end).map_err(error)?;
+        self.buf.resize(end, 0);
+        let mut index = self.index;
+        encode(&mut Some(&mut self.buf[..]), &mut index).map_err(error)?;
+        self.index = index;
+        Ok(())
+    }
+
+    /// Append a term with one of the encode functions writing to a `Vec`
+    fn encode_appending<E: std::fmt::Debug>(
+        &mut self,
+        encode: impl FnOnce(&mut Vec<u8>) -> Result<usize, E>,
+    ) -> Result<(), EiBufferError> {
+        self.buf.truncate(self.index);
+        let result = encode(&mut self.buf);
+        if let Err(e) = result {
+            self.buf.truncate(self.index);
+            return Err(EiBufferError::EncodeError(format!("{:?}", e)));
+        }
+        self.index = self.buf.len();
+        Ok(())
+    }
+
+    /// Encode the version byte (`ei_x_encode_version`)
+    pub fn encode_version(&mut self) -> Result<(), EiBufferError> {
+        self.encode_with(|buf, index| {
+            if let Some(b) = buf.as_mut() {
+                b[*index] = ERL_VERSION;
+            }
+            *index += 1;
+            Ok::<(), ()>(())
+        })
+    }
+
+    /// Encode a signed integer (`ei_x_encode_longlong`)
+    pub fn encode_long(&mut self, value: i64) -> Result<(), EiBufferError> {
+        self.encode_with(|buf, index| crate::encode_integers::encode_longlong(buf, index, value))
+    }
+
+    /// Encode an unsigned integer (`ei_x_encode_ulonglong`)
+    pub fn encode_ulong(&mut self, value: u64) -> Result<(), EiBufferError> {
+        self.encode_with(|buf, index| crate::encode_integers::encode_ulonglong(buf, index, value))
+    }
+
+    /// Encode a float (`ei_x_encode_double`)
+    pub fn encode_double(&mut self, value: f64) -> Result<(), EiBufferError> {
+        self.encode_with(|buf, index| crate::encode_double::encode_double(buf, index, value))
+    }
+
+    /// Encode a character, as a small integer (`ei_x_encode_char`)
+    pub fn encode_char(&mut self, value: u8) -> Result<(), EiBufferError> {
+        self.encode_with(|buf, index| crate::encode_char::encode_char(buf, index, value))
+    }
+
+    /// Encode an atom, as UTF-8 unless it is ASCII (`ei_x_encode_atom`)
+    pub fn encode_atom(&mut self, name: &str) -> Result<(), EiBufferError> {
+        let encoding = if name.is_ascii() { AtomEncoding::SevenBitAscii } else { AtomEncoding::Utf8 };
+        self.encode_appending(|buf| infrastructure_data_handling::encode_atom(buf, name, encoding))
+    }
+
+    /// Encode a binary (`ei_x_encode_binary`)
+    pub fn encode_binary(&mut self, data: &[u8]) -> Result<(), EiBufferError> {
+        self.encode_appending(|buf| infrastructure_data_handling::encode_binary(buf, data))
+    }
+
+    /// Encode a tuple header; the elements follow (`ei_x_encode_tuple_header`)
+    pub fn encode_tuple_header(&mut self, arity: usize) -> Result<(), EiBufferError> {
+        self.encode_with(|buf, index| crate::encode_headers::encode_tuple_header(buf, index, arity))
+    }
+
+    /// Encode a list header (`ei_x_encode_list_header`)
+    ///
+    /// The elements and the tail follow, except for a length of 0, which
+    /// encodes the empty list.
+    pub fn encode_list_header(&mut self, length: usize) -> Result<(), EiBufferError> {
+        if length == 0 {
+            return self.encode_empty_list();
+        }
+        self.encode_with(|buf, index| crate::encode_headers::encode_list_header(buf, index, length))
+    }
+
+    /// Encode the empty list (`ei_x_encode_empty_list`)
+    pub fn encode_empty_list(&mut self) -> Result<(), EiBufferError> {
+        self.encode_with(|buf, index| {
+            if let Some(b) = buf.as_mut() {
+                b[*index] = ERL_NIL_EXT;
+            }
+            *index += 1;
+            Ok::<(), ()>(())
+        })
+    }
+
+    /// Encode a map header; the keys and values follow (`ei_x_encode_map_header`)
+    pub fn encode_map_header(&mut self, arity: usize) -> Result<(), EiBufferError> {
+        self.encode_with(|buf, index| crate::encode_headers::encode_map_header(buf, index, arity))
+    }
+
+    /// Encode a process identifier (`ei_x_encode_pid`)
+    pub fn encode_pid(&mut self, pid: &ErlangPid) -> Result<(), EiBufferError> {
+        self.encode_with(|buf, index| crate::encode_pid::encode_pid(buf, index, pid))
+    }
+
+    /// Encode a port identifier (`ei_x_encode_port`)
+    pub fn encode_port(&mut self, port: &ErlangPort) -> Result<(), EiBufferError> {
+        self.encode_with(|buf, index| crate::encode_port::encode_port(buf, index, port))
+    }
+
+    /// Encode a reference (`ei_x_encode_ref`)
+    pub fn encode_ref(&mut self, r#ref: &ErlangRef) -> Result<(), EiBufferError> {
+        self.encode_with(|buf, index| crate::encode_ref::encode_ref(buf, index, r#ref))
+    }
+
+    /// Decode a term with one of the decode functions, advancing the index
+    /// only on success
+    fn decode_with<T, E: std::fmt::Debug>(
+        &mut self,
+        decode: impl FnOnce(&[u8], &mut usize) -> Result<T, E>,
+    ) -> Result<T, EiBufferError> {
+        let mut index = self.index;
+        let value = decode(&self.buf, &mut index).map_err(|e| EiBufferError::DecodeError(format!("{:?}", e)))?;
+        self.index = index;
+        Ok(value)
+    }
+
+    /// Decode the version byte (`ei_decode_version`)
+    pub fn decode_version(&mut self) -> Result<u8, EiBufferError> {
+        match self.buf.get(self.index) {
+            Some(&ERL_VERSION) => {
+                self.index += 1;
+                Ok(ERL_VERSION)
+            }
+            Some(&other) => Err(EiBufferError::BadVersion(other)),
+            None => Err(EiBufferError::DecodeError("BufferTooShort".to_string())),
+        }
+    }
+
+    /// Get the type of the next term without decoding it (`ei_get_type`)
+    ///
+    /// # Returns
+    /// * `Ok((tag, size))` - The tag byte and the size of the term: the
+    ///   arity of tuples and maps, the length of lists, strings, atoms and
+    ///   binaries, the number of digit bytes of bignums, and 0 otherwise
+    /// * `Err(EiBufferError)` - The buffer ends
+    pub fn get_type(&self) -> Result<(u8, usize), EiBufferError> {
+        term_type(&self.buf, self.index)
+    }
+
+    /// Decode a signed integer (`ei_decode_longlong`)
+    pub fn decode_long(&mut self) -> Result<i64, EiBufferError> {
+        self.decode_with(crate::decode_integers::decode_longlong)
+    }
+
+    /// Decode an unsigned integer (`ei_decode_ulonglong`)
+    pub fn decode_ulong(&mut self) -> Result<u64, EiBufferError> {
+        self.decode_with(crate::decode_integers::decode_ulonglong)
+    }
+
+    /// Decode a float (`ei_decode_double`)
+    pub fn decode_double(&mut self) -> Result<f64, EiBufferError> {
+        self.decode_with(crate::decode_double::decode_double)
+    }
+
+    /// Decode a character (`ei_decode_char`)
+    pub fn decode_char(&mut self) -> Result<u8, EiBufferError> {
+        self.decode_with(crate::decode_char::decode_char)
+    }
+
+    /// Decode an atom (`ei_decode_atom`)
+    pub fn decode_atom(&mut self) -> Result<String, EiBufferError> {
+        self.decode_with(|buf, index| {
+            infrastructure_data_handling::decode_atom_name(buf, *index).map(|(name, end)| {
+                *index = end;
+                name
+            })
+        })
+    }
+
+    /// Decode a binary (`ei_decode_binary`)
+    pub fn decode_binary(&mut self) -> Result<Vec<u8>, EiBufferError> {
+        self.decode_with(|buf, index| {
+            infrastructure_data_handling::decode_binary(buf, *index).map(|(data, end)| {
+                *index = end;
+                data
+            })
+        })
+    }
+
+    /// Decode a tuple header (`ei_decode_tuple_header`)
+    pub fn decode_tuple_header(&mut self) -> Result<usize, EiBufferError> {
+        self.decode_with(crate::decode_headers::decode_tuple_header)
+    }
+
+    /// Decode a list header (`ei_decode_list_header`)
+    ///
+    /// The empty list decodes as a header of length 0.
+    pub fn decode_list_header(&mut self) -> Result<usize, EiBufferError> {
+        if self.buf.get(self.index) == Some(&ERL_NIL_EXT) {
+            self.index += 1;
+            return Ok(0);
+        }
+        self.decode_with(crate::decode_headers::decode_list_header)
+    }
+
+    /// Decode a map header (`ei_decode_map_header`)
+    pub fn decode_map_header(&mut self) -> Result<usize, EiBufferError> {
+        self.decode_with(crate::decode_headers::decode_map_header)
+    }
+
+    /// Decode a process identifier (`ei_decode_pid`)
+    pub fn decode_pid(&mut self) -> Result<ErlangPid, EiBufferError> {
+        self.decode_with(crate::decode_pid::decode_pid)
+    }
+
+    /// Decode a port identifier (`ei_decode_port`)
+    pub fn decode_port(&mut self) -> Result<ErlangPort, EiBufferError> {
+        self.decode_with(crate::decode_port::decode_port)
+    }
+
+    /// Decode a reference (`ei_decode_ref`)
+    pub fn decode_ref(&mut self) -> Result<ErlangRef, EiBufferError> {
+        self.decode_with(crate::decode_ref::decode_ref)
+    }
+
+    /// Skip the next term (`ei_skip_term`)
+    pub fn skip_term(&mut self) -> Result<(), EiBufferError> {
+        self.decode_with(skip_term)
+    }
+
+    /// Walk the structure of the next term, without moving the index
+    pub fn term_iter(&self) -> EiTermIterator<'_> {
+        EiTermIterator::new(&self.buf, self.index)
+    }
+}
+
+/// A term met by an [`EiTermIterator`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EiTerm {
+    /// Tag byte of the term
+    pub tag: u8,
+    /// Size of the term, as returned by [`EiBuffer::get_type`]
+    pub size: usize,
+    /// Index of the tag byte in the buffer
+    pub index: usize,
+    /// Nesting depth: 0 for the term iterated over, 1 for its elements, ...
+    pub depth: usize,
+}
+
+/// Iterator over the skeleton of an encoded term
+///
+/// Yields the term and each of its subterms, depth first, in encoding
+/// order. Tuples, lists and maps are entered: their headers are yielded and
+/// then their elements (for lists, followed by the tail; for maps,
+/// alternating keys and values). Every other term is yielded and skipped
+/// without decoding. Iteration stops after the first error.
+#[derive(Debug, Clone)]
+pub struct EiTermIterator<'a> {
+    buf: &'a [u8],
+    index: usize,
+    /// Number of subterms still to yield, per entered term
+    pending: Vec<usize>,
+}
+
+impl<'a> EiTermIterator<'a> {
+    /// Iterate over the term encoded at `index` in `buf`
+    pub fn new(buf: &'a [u8], index: usize) -> Self {
+        Self { buf, index, pending: vec![1] }
+    }
+
+    /// Get the index of the next term; after the last, the end of the term
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    fn step(&mut self) -> Result<EiTerm, EiBufferError> {
+        let start = self.index;
+        let depth = self.pending.len() - 1;
+        let (tag, size) = term_type(self.buf, start)?;
+        let children = match tag {
+            ERL_SMALL_TUPLE_EXT => Some((2, size)),
+            ERL_LARGE_TUPLE_EXT => Some((5, size)),
+            ERL_LIST_EXT => Some((5, size + 1)),
+            ERL_MAP_EXT => Some((5, 2 * size)),
+            _ => None,
+        };
+        match children {
+            Some((header, children)) => {
+                self.index += header;
+                if children > 0 {
+                    self.pending.push(children);
+                }
+            }
+            None => skip_term(self.buf, &mut self.index)
+                .map_err(|e| EiBufferError::DecodeError(format!("{:?}", e)))?,
+        }
+        Ok(EiTerm { tag, size, index: start, depth })
+    }
+}
+
+impl Iterator for EiTermIterator<'_> {
+    type Item = Result<EiTerm, EiBufferError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.last() == Some(&0) {
+            self.pending.pop();
+        }
+        *self.pending.last_mut()? -= 1;
+        let result = self.step();
+        if result.is_err() {
+            self.pending.clear();
+        }
+        Some(result)
+    }
+}
+
+/// Read the tag and size of the term at `index`
+fn term_type(buf: &[u8], index: usize) -> Result<(u8, usize), EiBufferError> {
+    let too_short = || EiBufferError::DecodeError("BufferTooShort".to_string());
+    let tag = *buf.get(index).ok_or_else(too_short)?;
+    let byte = |offset: usize| buf.get(index + offset).copied().ok_or_else(too_short);
+    let u16_at = |offset: usize| Ok::<usize, EiBufferError>(u16::from_be_bytes([byte(offset)?, byte(offset + 1)?]) as usize);
+    let u32_at = |offset: usize| {
+        Ok::<usize, EiBufferError>(u32::from_be_bytes([byte(offset)?, byte(offset + 1)?, byte(offset + 2)?, byte(offset + 3)?]) as usize)
+    };
+    let size = match tag {
+        ERL_SMALL_ATOM_EXT | ERL_SMALL_ATOM_UTF8_EXT | ERL_SMALL_TUPLE_EXT | ERL_SMALL_BIG_EXT => byte(1)? as usize,
+        ERL_ATOM_EXT | ERL_ATOM_UTF8_EXT | ERL_STRING_EXT => u16_at(1)?,
+        ERL_LARGE_TUPLE_EXT | ERL_LIST_EXT | ERL_MAP_EXT | ERL_BINARY_EXT | ERL_LARGE_BIG_EXT => u32_at(1)?,
+        _ => 0,
+    };
+    Ok((tag, size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_grows_and_decodes_back() {
+        let mut x = EiBuffer::new_with_version();
+        x.encode_map_header(1).unwrap();
+        x.encode_atom("k\u{e9}y").unwrap();
+        x.encode_list_header(3).unwrap();
+        x.encode_long(i64::MIN).unwrap();
+        x.encode_ulong(u64::MAX).unwrap();
+        x.encode_binary(&[1, 2, 3]).unwrap();
+        x.encode_empty_list().unwrap();
+        assert_eq!(x.index(), x.len());
+
+        let mut reader = EiBuffer::from_bytes(x.into_bytes());
+        assert_eq!(reader.decode_version(), Ok(ERL_VERSION));
+        assert_eq!(reader.decode_map_header(), Ok(1));
+        assert_eq!(reader.decode_atom().unwrap(), "k\u{e9}y");
+        assert_eq!(reader.decode_list_header(), Ok(3));
+        assert_eq!(reader.decode_long(), Ok(i64::MIN));
+        assert_eq!(reader.decode_ulong(), Ok(u64::MAX));
+        assert_eq!(reader.decode_binary().unwrap(), vec![1, 2, 3]);
+        assert_eq!(reader.decode_list_header(), Ok(0));
+        assert_eq!(reader.index(), reader.len());
+    }
+
+    #[test]
+    fn test_decode_errors_leave_index() {
+        let mut x = EiBuffer::new();
+        x.encode_atom("ok").unwrap();
+        assert_eq!(x.encode_tuple_header(1).map(|_| x.len()), Ok(6));
+
+        let mut reader = EiBuffer::from_bytes(x.into_bytes());
+        assert_eq!(reader.decode_version(), Err(EiBufferError::BadVersion(ERL_SMALL_ATOM_EXT)));
+        assert!(reader.decode_long().is_err());
+        assert!(reader.decode_tuple_header().is_err());
+        assert_eq!(reader.index(), 0);
+        assert_eq!(reader.get_type(), Ok((ERL_SMALL_ATOM_EXT, 2)));
+        reader.skip_term().unwrap();
+        assert_eq!(reader.get_type(), Ok((ERL_SMALL_TUPLE_EXT, 1)));
+        assert_eq!(reader.decode_tuple_header(), Ok(1));
+        assert!(reader.get_type().is_err());
+    }
+
+    #[test]
+    fn test_encoding_overwrites_after_index() {
+        let mut x = EiBuffer::new();
+        x.encode_long(1).unwrap();
+        x.encode_long(1 << 40).unwrap();
+        x.set_index(2);
+        x.encode_char(7).unwrap();
+        assert_eq!(x.as_bytes(), &[ERL_SMALL_INTEGER_EXT, 1, ERL_SMALL_INTEGER_EXT, 7]);
+
+        let mut y = EiBuffer::new_with_version();
+        y.append(&x);
+        assert_eq!(y.len(), 5);
+        assert_eq!(y.index(), 5);
+    }
+
+    #[test]
+    fn test_term_iterator_walks_skeleton() {
+        // {ok, [1, <<2>> | tail], #{a => {}}}
+        let mut x = EiBuffer::new();
+        x.encode_tuple_header(3).unwrap();
+        x.encode_atom("ok").unwrap();
+        x.encode_list_header(2).unwrap();
+        x.encode_long(1).unwrap();
+        x.encode_binary(&[2]).unwrap();
+        x.encode_atom("tail").unwrap();
+        x.encode_map_header(1).unwrap();
+        x.encode_atom("a").unwrap();
+        x.encode_tuple_header(0).unwrap();
+        x.encode_long(5).unwrap();
+
+        x.set_index(0);
+        let mut iter = x.term_iter();
+        let terms: Vec<(u8, usize, usize)> =
+            iter.by_ref().map(|term| term.map(|t| (t.tag, t.size, t.depth)).unwrap()).collect();
+        assert_eq!(
+            terms,
+            vec![
+                (ERL_SMALL_TUPLE_EXT, 3, 0),
+                (ERL_SMALL_ATOM_EXT, 2, 1),
+                (ERL_LIST_EXT, 2, 1),
+                (ERL_SMALL_INTEGER_EXT, 0, 2),
+                (ERL_BINARY_EXT, 1, 2),
+                (ERL_SMALL_ATOM_EXT, 4, 2),
+                (ERL_MAP_EXT, 1, 1),
+                (ERL_SMALL_ATOM_EXT, 1, 2),
+                (ERL_SMALL_TUPLE_EXT, 0, 2),
+            ]
+        );
+        // The iterator stops at the end of the term, before the next one
+        assert_eq!(iter.index(), x.len() - 2);
+
+        let truncated = &x.as_bytes()[..x.len() - 5];
+        let results: Vec<_> = EiTermIterator::new(truncated, 0).collect();
+        assert!(results.last().unwrap().is_err());
+    }
+}
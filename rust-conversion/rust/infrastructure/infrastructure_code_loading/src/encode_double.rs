@@ -7,8 +7,11 @@
 //! ## Overview
 //!
 //! Floating-point numbers in EI format use the `NEW_FLOAT_EXT` tag followed by
-//! an 8-byte IEEE 754 double-precision value in big-endian format. The old
-//! `ERL_FLOAT_EXT` format (31-byte string representation) is not supported.
+//! an 8-byte IEEE 754 double-precision value in big-endian format. For peers
+//! that only understand the old format, [`encode_double_with_minor_version`]
+//! with minor version 0 emits the legacy `ERL_FLOAT_EXT` form instead: a
+//! 31-byte, zero-padded `"%.20e"` decimal string, as `term_to_binary/2` does
+//! with `{minor_version, 0}`.
 //!
 //! ## Limitations
 //!
@@ -30,6 +33,12 @@
 //! let mut size_index = 0;
 //! encode_double(&mut None, &mut size_index, 2.71828).unwrap();
 //! assert_eq!(size_index, 9); // 1 byte tag + 8 bytes value
+//!
+//! // Legacy format for old peers
+//! use infrastructure_code_loading::encode_double::encode_double_with_minor_version;
+//! let mut legacy_index = 0;
+//! encode_double_with_minor_version(&mut None, &mut legacy_index, 2.71828, 0).unwrap();
+//! assert_eq!(legacy_index, 32); // 1 byte tag + 31 bytes string
 //! ```
 //!
 //! ## See Also
@@ -39,10 +48,16 @@
 //!
 //! Based on `lib/erl_interface/src/encode/encode_double.c`
 
-use crate::constants::NEW_FLOAT_EXT;
+use crate::constants::{ERL_FLOAT_EXT, NEW_FLOAT_EXT};
+
+/// Size of the decimal string in the legacy `ERL_FLOAT_EXT` format
+pub const FLOAT_EXT_STRING_SIZE: usize = 31;
 
 /// Encode a double-precision floating-point number to EI format
 ///
+/// Always uses `NEW_FLOAT_EXT` (minor version 1, the default of
+/// `term_to_binary/1`).
+///
 /// # Arguments
 /// * `buf` - Optional buffer to write to (None for size calculation)
 /// * `index` - Current index in buffer
@@ -52,11 +67,50 @@ use crate::constants::NEW_FLOAT_EXT;
 /// * `Ok(())` - Success
 /// * `Err(EncodeError)` - Encoding error (e.g., NaN or Infinity)
 pub fn encode_double(buf: &mut Option<&mut [u8]>, index: &mut usize, value: f64) -> Result<(), EncodeError> {
+    encode_double_with_minor_version(buf, index, value, 1)
+}
+
+/// Encode a double-precision floating-point number for a given minor version
+///
+/// Minor version 0 selects the legacy `ERL_FLOAT_EXT` format (tag followed by
+/// a 31-byte decimal string); any later minor version selects
+/// `NEW_FLOAT_EXT`.
+///
+/// # Arguments
+/// * `buf` - Optional buffer to write to (None for size calculation)
+/// * `index` - Current index in buffer
+/// * `value` - The floating-point value to encode
+/// * `minor_version` - External format minor version of the peer
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(EncodeError)` - Encoding error (e.g., NaN or Infinity)
+pub fn encode_double_with_minor_version(
+    buf: &mut Option<&mut [u8]>,
+    index: &mut usize,
+    value: f64,
+    minor_version: u8,
+) -> Result<(), EncodeError> {
     // Erlang does not handle Inf and NaN
     if !value.is_finite() {
         return Err(EncodeError::InvalidValue);
     }
 
+    if minor_version == 0 {
+        if let Some(b) = buf.as_mut() {
+            if *index + 1 + FLOAT_EXT_STRING_SIZE > b.len() {
+                return Err(EncodeError::BufferTooSmall);
+            }
+            let digits = format_float_ext(value);
+            let field = &mut b[*index + 1..*index + 1 + FLOAT_EXT_STRING_SIZE];
+            field.fill(0);
+            field[..digits.len()].copy_from_slice(digits.as_bytes());
+            b[*index] = ERL_FLOAT_EXT;
+        }
+        *index += 1 + FLOAT_EXT_STRING_SIZE;
+        return Ok(());
+    }
+
     if let Some(b) = buf.as_mut() {
         if *index + 9 > b.len() {
             return Err(EncodeError::BufferTooSmall);
@@ -70,6 +124,20 @@ pub fn encode_double(buf: &mut Option<&mut [u8]>, index: &mut usize, value: f64)
     Ok(())
 }
 
+/// Format a float as C's `"%.20e"` does (e.g. `3.14000000000000012434e+00`)
+///
+/// The result is at most 27 bytes for finite values, so it always fits the
+/// 31-byte `ERL_FLOAT_EXT` field.
+fn format_float_ext(value: f64) -> String {
+    let formatted = format!("{:.20e}", value);
+    let (mantissa, exponent) = formatted
+        .split_once('e')
+        .unwrap_or((formatted.as_str(), "0"));
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    let sign = if exponent < 0 { '-' } else { '+' };
+    format!("{}e{}{:02}", mantissa, sign, exponent.abs())
+}
+
 /// Encoding errors
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EncodeError {
@@ -113,5 +181,45 @@ mod tests {
         encode_double(&mut None, &mut index, 3.14).unwrap();
         assert_eq!(index, 9);
     }
-}
 
+    #[test]
+    fn test_encode_double_legacy_format() {
+        let mut buf = vec![0xFFu8; 40];
+        let mut index = 0;
+        encode_double_with_minor_version(&mut Some(&mut buf), &mut index, 1.25, 0).unwrap();
+        assert_eq!(index, 32);
+        assert_eq!(buf[0], ERL_FLOAT_EXT);
+        let expected = b"1.25000000000000000000e+00";
+        assert_eq!(&buf[1..1 + expected.len()], expected);
+        assert!(buf[1 + expected.len()..32].iter().all(|&b| b == 0));
+        assert_eq!(buf[32], 0xFF);
+    }
+
+    #[test]
+    fn test_format_float_ext() {
+        assert_eq!(format_float_ext(0.0), "0.00000000000000000000e+00");
+        assert_eq!(format_float_ext(-0.375), "-3.75000000000000000000e-01");
+        assert_eq!(format_float_ext(1e100).len(), 27);
+        assert!(format_float_ext(f64::MIN).len() <= FLOAT_EXT_STRING_SIZE);
+    }
+
+    #[test]
+    fn test_encode_double_minor_version_selects_tag() {
+        let mut buf = vec![0u8; 40];
+        let mut index = 0;
+        encode_double_with_minor_version(&mut Some(&mut buf), &mut index, 1.0, 2).unwrap();
+        assert_eq!(index, 9);
+        assert_eq!(buf[0], NEW_FLOAT_EXT);
+
+        let mut small = vec![0u8; 20];
+        let mut index = 0;
+        assert_eq!(
+            encode_double_with_minor_version(&mut Some(&mut small), &mut index, 1.0, 0),
+            Err(EncodeError::BufferTooSmall)
+        );
+        assert_eq!(
+            encode_double_with_minor_version(&mut None, &mut index, f64::NAN, 0),
+            Err(EncodeError::InvalidValue)
+        );
+    }
+}
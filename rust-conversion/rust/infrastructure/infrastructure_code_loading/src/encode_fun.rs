@@ -198,9 +198,9 @@ pub fn encode_fun(buf: &mut Option<&mut [u8]>, index: &mut usize, fun: &ErlangFu
                 }
                 *index += free_vars.len();
 
-                // Update size field
+                // Update size field, which counts itself and all that follows it
                 if let Some(b) = buf.as_mut() {
-                    let size = (*index - size_pos) as u32;
+                    let size = (*index - size_pos - 1) as u32;
                     b[size_pos + 1..size_pos + 5].copy_from_slice(&size.to_be_bytes());
                 }
             }
@@ -54,7 +54,7 @@ use crate::constants::*;
 /// * `Ok(new_index)` - New index after encoding
 /// * `Err(EncodeError)` - Encoding error
 pub fn encode_longlong(buf: &mut Option<&mut [u8]>, index: &mut usize, value: i64) -> Result<(), EncodeError> {
-    let abs_value = value.abs() as u64;
+    let abs_value = value.unsigned_abs();
     let is_negative = value < 0;
 
     if value >= 0 && value < 256 {
@@ -100,9 +100,8 @@ pub fn encode_longlong(buf: &mut Option<&mut [u8]>, index: &mut usize, value: i6
             b[*index + 1] = arity as u8;
             b[*index + 2] = if is_negative { 1 } else { 0 };
             b[*index + 3..*index + 3 + arity].copy_from_slice(&bytes);
-        } else {
-            *index += 3 + arity;
         }
+        *index += 3 + arity;
     }
 
     Ok(())
@@ -162,9 +161,8 @@ pub fn encode_ulonglong(buf: &mut Option<&mut [u8]>, index: &mut usize, value: u
             b[*index + 1] = arity as u8;
             b[*index + 2] = 0; // unsigned, always positive
             b[*index + 3..*index + 3 + arity].copy_from_slice(&bytes);
-        } else {
-            *index += 3 + arity;
         }
+        *index += 3 + arity;
     }
 
     Ok(())
@@ -234,6 +232,7 @@ mod tests {
         assert_eq!(buf[0], ERL_SMALL_BIG_EXT);
         assert_eq!(buf[1], 5); // 5 bytes for this value
         assert_eq!(buf[2], 0); // positive
+        assert_eq!(index, 3 + 5);
     }
 
     #[test]
@@ -319,6 +318,12 @@ mod tests {
         assert_eq!(buf[0], ERL_SMALL_BIG_EXT);
         assert_eq!(buf[1], 5); // 5 bytes for this value
         assert_eq!(buf[2], 1); // negative
+        assert_eq!(index, 3 + 5);
+
+        let mut index = 0;
+        encode_longlong(&mut Some(&mut buf), &mut index, i64::MIN).unwrap();
+        assert_eq!(&buf[..11], &[ERL_SMALL_BIG_EXT, 8, 1, 0, 0, 0, 0, 0, 0, 0, 0x80]);
+        assert_eq!(index, 11);
     }
 
     #[test]
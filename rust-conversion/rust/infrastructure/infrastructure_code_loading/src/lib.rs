@@ -32,6 +32,7 @@
 //! - **[`decode_fun`](decode_fun/index.html)**: Function decoding
 //! - **[`encode_trace`](encode_trace/index.html)**: Trace encoding
 //! - **[`decode_trace`](decode_trace/index.html)**: Trace decoding
+//! - **[`ei_buffer`](ei_buffer/index.html)**: Growable `ei_x_buff` style buffer and term skeleton iteration
 //!
 //! ## Architecture
 //!
@@ -65,11 +66,12 @@ pub mod decode_fun;
 pub mod encode_trace;
 pub mod decode_trace;
 pub mod decode_skip;
+pub mod ei_buffer;
 
 pub use code_loader::CodeLoader;
 pub use encode_integers::{encode_long, encode_ulong, encode_longlong, encode_ulonglong, EncodeError as IntegerEncodeError};
 pub use decode_integers::{decode_long, decode_ulong, decode_longlong, decode_ulonglong, DecodeError as IntegerDecodeError};
-pub use encode_double::{encode_double, EncodeError as DoubleEncodeError};
+pub use encode_double::{encode_double, encode_double_with_minor_version, EncodeError as DoubleEncodeError};
 pub use decode_double::{decode_double, DecodeError as DoubleDecodeError};
 pub use encode_char::{encode_char, EncodeError as CharEncodeError};
 pub use decode_char::{decode_char, DecodeError as CharDecodeError};
@@ -85,4 +87,5 @@ pub use encode_fun::{encode_fun, ErlangFunType, EncodeError as FunEncodeError};
 pub use decode_fun::{decode_fun, DecodeError as FunDecodeError};
 pub use encode_trace::{encode_trace, ErlangTrace, EncodeError as TraceEncodeError};
 pub use decode_trace::{decode_trace, DecodeError as TraceDecodeError};
+pub use ei_buffer::{EiBuffer, EiBufferError, EiTerm, EiTermIterator};
 
@@ -336,9 +336,7 @@ fn test_encode_decode_ref_roundtrip() {
     let mut decode_index = 0;
     let decoded = decode_ref(&buf, &mut decode_index).unwrap();
     
-    // Note: decode_atom returns a placeholder, so we can't compare node names directly
-    // But we can verify the node field is populated (non-empty)
-    assert!(!decoded.node.is_empty());
+    assert_eq!(decoded, r#ref);
     assert_eq!(decoded.len, r#ref.len);
     assert_eq!(decoded.ids, r#ref.ids);
     assert_eq!(decoded.creation, r#ref.creation);
@@ -63,6 +63,41 @@ pub fn decode_atom(buf: &[u8], index: usize) -> Result<(String, usize), DecodeAt
         })
 }
 
+/// Decode an atom from EI-encoded bytes, returning its actual name
+///
+/// Unlike [`decode_atom`], which yields an atom table placeholder, this
+/// returns the text of the atom. Latin1 atoms are converted to UTF-8.
+/// It is used where the name itself must survive a round-trip, such as
+/// the node name of a pid, port, or reference.
+///
+/// # Arguments
+/// * `buf` - Buffer containing EI-encoded data
+/// * `index` - Starting index in the buffer
+///
+/// # Returns
+/// * `Ok((atom_name, new_index))` - Decoded atom name and new index position
+/// * `Err(DecodeAtomError)` - Decoding error
+pub fn decode_atom_name(buf: &[u8], index: usize) -> Result<(String, usize), DecodeAtomError> {
+    if index >= buf.len() {
+        return Err(DecodeAtomError::BufferTooShort);
+    }
+
+    let tag = buf[index];
+    let (_, new_pos) = decode_atom_internal(buf, index + 1, tag)?;
+    let header_len = match tag {
+        100 | 118 => 2,
+        _ => 1,
+    };
+    let data = &buf[index + 1 + header_len..new_pos];
+    let name = match tag {
+        // Latin1 atoms: every byte maps directly to a code point
+        100 | 115 => data.iter().map(|&b| b as char).collect(),
+        // UTF-8 atoms were validated by decode_atom_internal
+        _ => String::from_utf8_lossy(data).into_owned(),
+    };
+    Ok((name, new_pos))
+}
+
 /// Internal atom decoder (used by decode_term)
 pub(crate) fn decode_atom_internal(
     buf: &[u8],
@@ -604,5 +639,27 @@ mod tests {
         let result4 = decode_atom_internal(&buf2, 0, 118);
         assert!(result4.is_ok());
     }
+
+    #[test]
+    fn test_decode_atom_name() {
+        let buf = vec![119, 9, b'n', b'o', b'd', b'e', b'@', b'h', b'o', b's', b't'];
+        let (name, pos) = decode_atom_name(&buf, 0).unwrap();
+        assert_eq!(name, "node@host");
+        assert_eq!(pos, buf.len());
+
+        let buf = vec![100, 0, 2, b'o', b'k'];
+        assert_eq!(decode_atom_name(&buf, 0).unwrap(), ("ok".to_string(), 5));
+
+        // Latin1 byte 0xE5 is 'å'
+        let buf = vec![115, 1, 0xE5];
+        assert_eq!(decode_atom_name(&buf, 0).unwrap().0, "\u{e5}");
+    }
+
+    #[test]
+    fn test_decode_atom_name_errors() {
+        assert!(matches!(decode_atom_name(&[], 0), Err(DecodeAtomError::BufferTooShort)));
+        assert!(matches!(decode_atom_name(&[118, 0, 5, b'a'], 0), Err(DecodeAtomError::BufferTooShort)));
+        assert!(matches!(decode_atom_name(&[1, 0], 0), Err(DecodeAtomError::InvalidTag(1))));
+    }
 }
 
@@ -18,7 +18,8 @@
 //! - **[`decode_binary`](decode_binary/index.html)**: Decode binaries from EI format
 //! - **[`encode_atom`](encode_atom/index.html)**: Encode atoms to EI format
 //! - **[`encode_binary`](encode_binary/index.html)**: Encode binaries to EI format
-//! - **[`print_term`](print_term/index.html)**: Print terms in human-readable format
+//! - **[`print_term`](print_term/index.html)**: Print terms in human-readable format, optionally depth-limited
+//! - **[`pretty_term`](pretty_term/index.html)**: Pretty-print terms with line wrapping (`~p`)
 //!
 //! ## Architecture
 //!
@@ -60,11 +61,13 @@ pub mod decode_binary;
 pub mod encode_atom;
 pub mod encode_binary;
 pub mod print_term;
+pub mod pretty_term;
 
 // Re-export main types
 pub use decode_term::{decode_ei_term, DecodeError};
-pub use decode_atom::{decode_atom, DecodeAtomError};
+pub use decode_atom::{decode_atom, decode_atom_name, DecodeAtomError};
 pub use decode_binary::{decode_binary, DecodeBinaryError};
 pub use encode_atom::{encode_atom, encode_atom_len, EncodeAtomError};
 pub use encode_binary::{encode_binary, EncodeBinaryError};
-pub use print_term::{print_term, s_print_term, PrintError};
+pub use print_term::{print_term, s_print_term, s_print_term_with, write_term, PrintError, PrintOptions};
+pub use pretty_term::{pretty_print_term, PrettyEncoding, PrettyOptions};
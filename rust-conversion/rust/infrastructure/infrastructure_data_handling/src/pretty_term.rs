@@ -0,0 +1,453 @@
+//! Pretty Term Module
+//!
+//! Provides `~p`-style pretty-printing of Erlang terms, as done by `io_lib_pretty`.
+//! A term that fits in the remaining line width prints on one line; otherwise
+//! lists, tuples and maps break with one element per line, aligned after the
+//! opening bracket.
+//!
+//! ## Overview
+//!
+//! Compared to [`print_term`](super::print_term), the pretty-printer:
+//! - **Wraps lines**: Breaks compound terms to keep within the line length
+//! - **Prints strings**: Lists and binaries of printable characters print as
+//!   `"abc"` and `<<"abc">>`, with `\n`-style escapes. Which characters are
+//!   printable depends on the [`PrettyEncoding`]
+//! - **Limits depth**: Cuts terms off below a depth as `~P` does, including map
+//!   entries and binary bytes
+//! - **Spaces map associations**: Prints `#{a => 1}`
+//!
+//! Tuples are never printed as records; there is no record definition lookup.
+//! Atoms, numbers, pids, ports, refs and funs print as in `print_term`.
+//!
+//! ## Examples
+//!
+//! ```rust
+//! use infrastructure_data_handling::pretty_term::{pretty_print_term, PrettyOptions};
+//! use entities_data_handling::term_hashing::Term;
+//!
+//! let tuple = Term::Tuple((1..=3).map(|n| Term::Small(n * 1_000_000)).collect());
+//! let options = PrettyOptions { line_length: 12, ..Default::default() };
+//! assert_eq!(pretty_print_term(&tuple, &options).unwrap(), "{1000000,\n 2000000,\n 3000000}");
+//! ```
+//!
+//! Based on `lib/stdlib/src/io_lib_pretty.erl`
+
+use entities_data_handling::term_hashing::Term;
+use entities_data_handling::AtomTable;
+
+use crate::print_term::{s_print_term_with, PrintError, PrintOptions};
+
+/// Character range considered printable in strings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrettyEncoding {
+    /// Latin-1 printable characters only (`~p`)
+    Latin1,
+    /// Any printable Unicode character (`~tp`); binaries must be UTF-8
+    Unicode,
+}
+
+/// Options for pretty-printing a term
+#[derive(Clone, Copy)]
+pub struct PrettyOptions<'a> {
+    /// Maximum line length to fit terms within
+    pub line_length: usize,
+    /// Column the term starts at, for terms printed after other text
+    pub column: usize,
+    /// Depth limit with the semantics of `~P`. `None` prints the whole term.
+    pub depth: Option<usize>,
+    /// Which characters print as strings
+    pub encoding: PrettyEncoding,
+    /// Atom table to look atom names up in
+    pub atoms: Option<&'a AtomTable>,
+}
+
+impl Default for PrettyOptions<'_> {
+    fn default() -> Self {
+        Self {
+            line_length: 80,
+            column: 1,
+            depth: None,
+            encoding: PrettyEncoding::Latin1,
+            atoms: None,
+        }
+    }
+}
+
+/// Pretty-print a term to a string
+///
+/// # Arguments
+/// * `term` - Term to print
+/// * `options` - Line length, start column, depth limit, encoding and atom table
+///
+/// # Returns
+/// * `Ok(string)` - Pretty-printed term, possibly over several lines
+/// * `Err(PrintError)` - Invalid term
+pub fn pretty_print_term(term: &Term, options: &PrettyOptions) -> Result<String, PrintError> {
+    let doc = Builder { options }.term(term, options.depth)?;
+    let mut out = String::new();
+    let mut layout = Layout { out: &mut out, column: options.column.saturating_sub(1), width: options.line_length };
+    layout.doc(&doc);
+    Ok(out)
+}
+
+/// A term prepared for layout, with its one-line form
+struct Doc {
+    flat: String,
+    kind: DocKind,
+}
+
+enum DocKind {
+    /// Printed as is
+    Leaf,
+    /// Elements between brackets, breakable after each comma
+    Seq {
+        open: &'static str,
+        items: Vec<Doc>,
+        tail: Option<Box<Doc>>,
+        close: &'static str,
+    },
+    /// A map association `Key => Value`
+    Assoc(Box<Doc>, Box<Doc>),
+}
+
+impl Doc {
+    fn leaf(flat: String) -> Self {
+        Doc { flat, kind: DocKind::Leaf }
+    }
+
+    fn seq(open: &'static str, items: Vec<Doc>, tail: Option<Doc>, close: &'static str) -> Self {
+        let mut flat = String::from(open);
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                flat.push(',');
+            }
+            flat.push_str(&item.flat);
+        }
+        if let Some(tail) = &tail {
+            flat.push('|');
+            flat.push_str(&tail.flat);
+        }
+        flat.push_str(close);
+        Doc { flat, kind: DocKind::Seq { open, items, tail: tail.map(Box::new), close } }
+    }
+
+    fn assoc(key: Doc, value: Doc) -> Self {
+        let flat = format!("{} => {}", key.flat, value.flat);
+        Doc { flat, kind: DocKind::Assoc(Box::new(key), Box::new(value)) }
+    }
+}
+
+/// Builds the layout tree of a term, applying the depth limit
+struct Builder<'o, 'a> {
+    options: &'o PrettyOptions<'a>,
+}
+
+impl Builder<'_, '_> {
+    fn term(&self, term: &Term, depth: Option<usize>) -> Result<Doc, PrintError> {
+        if depth == Some(0) {
+            return Ok(Doc::leaf("...".to_string()));
+        }
+        match term {
+            Term::List { .. } => {
+                if let Some(string) = self.printable_list(term) {
+                    return Ok(Doc::leaf(quote(&string)));
+                }
+                if depth == Some(1) {
+                    return Ok(Doc::leaf("[...]".to_string()));
+                }
+                self.list(term, depth.map(|d| d - 1))
+            }
+            Term::Tuple(elements) => {
+                let items = self.elements(elements.iter(), depth, |builder, elem, depth| builder.term(elem, depth))?;
+                Ok(Doc::seq("{", items, None, "}"))
+            }
+            Term::Map(pairs) => {
+                let items = self.elements(pairs.iter(), depth, |builder, (key, value), depth| {
+                    Ok(Doc::assoc(builder.term(key, depth)?, builder.term(value, depth)?))
+                })?;
+                Ok(Doc::seq("#{", items, None, "}"))
+            }
+            Term::Binary { data, bit_offset: 0, bit_size }
+                if bit_size % 8 == 0 && *bit_size > 0 && *bit_size <= data.len() * 8 =>
+            {
+                match self.printable_binary(&data[..bit_size / 8], depth) {
+                    Some(string) => Ok(Doc::leaf(string)),
+                    None => self.leaf(term, depth),
+                }
+            }
+            _ => self.leaf(term, depth),
+        }
+    }
+
+    fn leaf(&self, term: &Term, depth: Option<usize>) -> Result<Doc, PrintError> {
+        let options = PrintOptions { depth, atoms: self.options.atoms };
+        Ok(Doc::leaf(s_print_term_with(term, &options)?))
+    }
+
+    /// Build the elements of a tuple or map, replacing the elements beyond
+    /// the depth limit with a single `...`
+    fn elements<T>(
+        &self,
+        elements: impl ExactSizeIterator<Item = T>,
+        depth: Option<usize>,
+        element: impl Fn(&Self, T, Option<usize>) -> Result<Doc, PrintError>,
+    ) -> Result<Vec<Doc>, PrintError> {
+        if elements.len() > 0 && depth == Some(1) {
+            return Ok(vec![Doc::leaf("...".to_string())]);
+        }
+        let mut items = Vec::with_capacity(elements.len());
+        let mut depth = depth.map(|d| d - 1);
+        for (i, elem) in elements.enumerate() {
+            if i > 0 {
+                if depth == Some(1) {
+                    items.push(Doc::leaf("...".to_string()));
+                    break;
+                }
+                depth = depth.map(|d| d - 1);
+            }
+            items.push(element(self, elem, depth)?);
+        }
+        Ok(items)
+    }
+
+    /// Build a non-string list, looping over the tails
+    fn list(&self, list: &Term, depth: Option<usize>) -> Result<Doc, PrintError> {
+        let mut items = Vec::new();
+        let mut tail = None;
+        let mut depth = depth;
+        let mut current = list;
+        loop {
+            match current {
+                Term::Nil => break,
+                Term::List { head, tail: rest } => {
+                    if !items.is_empty() {
+                        if depth == Some(1) {
+                            tail = Some(Doc::leaf("...".to_string()));
+                            break;
+                        }
+                        depth = depth.map(|d| d - 1);
+                    }
+                    items.push(self.term(head, depth)?);
+                    current = rest;
+                }
+                improper => {
+                    tail = Some(self.term(improper, depth.map(|d| d.saturating_sub(1)))?);
+                    break;
+                }
+            }
+        }
+        Ok(Doc::seq("[", items, tail, "]"))
+    }
+
+    /// The characters of a proper list, if all of them are printable
+    fn printable_list(&self, list: &Term) -> Option<String> {
+        let mut string = String::new();
+        let mut current = list;
+        loop {
+            match current {
+                Term::Nil => return (!string.is_empty()).then_some(string),
+                Term::List { head, tail } => {
+                    let Term::Small(c) = head.as_ref() else { return None };
+                    let c = u32::try_from(*c).ok().and_then(char::from_u32)?;
+                    if !self.printable(c) {
+                        return None;
+                    }
+                    string.push(c);
+                    current = tail;
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    /// A binary as `<<"abc">>`, if its bytes within the depth limit are
+    /// printable characters
+    fn printable_binary(&self, bytes: &[u8], depth: Option<usize>) -> Option<String> {
+        let (string, suffix) = match self.options.encoding {
+            PrettyEncoding::Latin1 => (bytes.iter().map(|&b| char::from(b)).collect(), ""),
+            PrettyEncoding::Unicode => (std::str::from_utf8(bytes).ok()?.to_string(), "/utf8"),
+        };
+        let total = string.chars().count();
+        let limit = depth.map_or(total, |d| total.min(d - 1));
+        let shown: String = string.chars().take(limit).collect();
+        if shown.is_empty() || !shown.chars().all(|c| self.printable(c)) {
+            return None;
+        }
+        let more = if limit < total { "..." } else { "" };
+        Some(format!("<<{}{}{}>>", quote(&shown), suffix, more))
+    }
+
+    fn printable(&self, c: char) -> bool {
+        match c {
+            '\n' | '\r' | '\t' | '\u{b}' | '\u{8}' | '\u{c}' | '\u{1b}' => true,
+            ' '..='~' | '\u{a0}'..='\u{ff}' => true,
+            _ => self.options.encoding == PrettyEncoding::Unicode && c > '\u{ff}' && !c.is_control(),
+        }
+    }
+}
+
+/// Quote a string as `io_lib:write_string/1` does
+fn quote(string: &str) -> String {
+    let mut quoted = String::with_capacity(string.len() + 2);
+    quoted.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '\u{b}' => quoted.push_str("\\v"),
+            '\u{8}' => quoted.push_str("\\b"),
+            '\u{c}' => quoted.push_str("\\f"),
+            '\u{1b}' => quoted.push_str("\\e"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Writes a layout tree, breaking what does not fit in the line
+struct Layout<'s> {
+    out: &'s mut String,
+    column: usize,
+    width: usize,
+}
+
+impl Layout<'_> {
+    fn text(&mut self, text: &str) {
+        self.out.push_str(text);
+        self.column += text.chars().count();
+    }
+
+    fn newline(&mut self, indent: usize) {
+        self.out.push('\n');
+        self.out.extend(std::iter::repeat_n(' ', indent));
+        self.column = indent;
+    }
+
+    fn doc(&mut self, doc: &Doc) {
+        if self.column + doc.flat.chars().count() <= self.width {
+            self.text(&doc.flat);
+            return;
+        }
+        match &doc.kind {
+            DocKind::Leaf => self.text(&doc.flat),
+            DocKind::Seq { open, items, tail, close } => {
+                self.text(open);
+                let indent = self.column;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        self.text(",");
+                        self.newline(indent);
+                    }
+                    self.doc(item);
+                }
+                if let Some(tail) = tail {
+                    self.text("|");
+                    self.doc(tail);
+                }
+                self.text(close);
+            }
+            DocKind::Assoc(key, value) => {
+                let indent = self.column;
+                self.doc(key);
+                self.text(" =>");
+                // A value that does not fit after the key goes on its own line
+                if self.column + 1 + value.flat.chars().count() > self.width && !matches!(value.kind, DocKind::Leaf) {
+                    self.newline(indent + 4);
+                } else {
+                    self.text(" ");
+                }
+                self.doc(value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(elements: Vec<Term>) -> Term {
+        elements.into_iter().rev().fold(Term::Nil, |tail, head| Term::List {
+            head: Box::new(head),
+            tail: Box::new(tail),
+        })
+    }
+
+    fn chars(text: &str) -> Term {
+        list(text.chars().map(|c| Term::Small(c as i64)).collect())
+    }
+
+    fn binary(bytes: &[u8]) -> Term {
+        Term::Binary { data: bytes.to_vec(), bit_offset: 0, bit_size: bytes.len() * 8 }
+    }
+
+    fn pretty(term: &Term, line_length: usize) -> String {
+        let options = PrettyOptions { line_length, ..Default::default() };
+        pretty_print_term(term, &options).unwrap()
+    }
+
+    #[test]
+    fn test_fits_on_one_line() {
+        let term = Term::Tuple(vec![Term::Small(1), list(vec![Term::Small(2), Term::Small(300)]), Term::Nil]);
+        assert_eq!(pretty(&term, 80), "{1,[2,300],[]}");
+    }
+
+    #[test]
+    fn test_breaks_nested_terms() {
+        let inner = list((1..=4).map(|n| Term::Small(n * 10_000)).collect());
+        let term = Term::Tuple(vec![Term::Small(1), inner]);
+        assert_eq!(pretty(&term, 20), "{1,\n [10000,\n  20000,\n  30000,\n  40000]}");
+    }
+
+    #[test]
+    fn test_improper_list_and_column() {
+        let term = Term::List { head: Box::new(Term::Small(1)), tail: Box::new(Term::Small(2)) };
+        assert_eq!(pretty(&term, 80), "[1|2]");
+        let options = PrettyOptions { line_length: 10, column: 6, ..Default::default() };
+        let tuple = Term::Tuple(vec![Term::Small(100), Term::Small(200)]);
+        assert_eq!(pretty_print_term(&tuple, &options).unwrap(), "{100,\n      200}");
+    }
+
+    #[test]
+    fn test_strings_with_escapes() {
+        assert_eq!(pretty(&chars("say \"hi\"\n"), 80), "\"say \\\"hi\\\"\\n\"");
+        assert_eq!(pretty(&binary(b"abc"), 80), "<<\"abc\">>");
+        assert_eq!(pretty(&binary(&[1, 2]), 80), "<<1,2>>");
+    }
+
+    #[test]
+    fn test_latin1_and_unicode_encodings() {
+        let latin1 = PrettyOptions::default();
+        let unicode = PrettyOptions { encoding: PrettyEncoding::Unicode, ..Default::default() };
+        let greek = chars("αβ");
+        assert_eq!(pretty_print_term(&greek, &latin1).unwrap(), "[945,946]");
+        assert_eq!(pretty_print_term(&greek, &unicode).unwrap(), "\"αβ\"");
+        assert_eq!(pretty_print_term(&chars("é"), &latin1).unwrap(), "\"é\"");
+        let utf8 = binary("αβ".as_bytes());
+        assert_eq!(pretty_print_term(&utf8, &unicode).unwrap(), "<<\"αβ\"/utf8>>");
+        // As in Erlang, UTF-8 bytes print as their Latin-1 characters in ~p
+        assert_eq!(pretty_print_term(&utf8, &latin1).unwrap(), "<<\"Î±Î²\">>");
+        assert_eq!(pretty_print_term(&binary(&[0xff, 0xfe]), &unicode).unwrap(), "<<255,254>>");
+    }
+
+    #[test]
+    fn test_maps_and_truncation() {
+        let map = Term::Map(vec![(Term::Small(1), binary(b"one")), (Term::Small(2), binary(b"two"))]);
+        assert_eq!(pretty(&map, 80), "#{1 => <<\"one\">>,2 => <<\"two\">>}");
+        let options = PrettyOptions { depth: Some(2), ..Default::default() };
+        assert_eq!(pretty_print_term(&map, &options).unwrap(), "#{1 => <<...>>,...}");
+        let options = PrettyOptions { depth: Some(3), ..Default::default() };
+        assert_eq!(pretty_print_term(&binary(b"abcdef"), &options).unwrap(), "<<\"ab\"...>>");
+    }
+
+    #[test]
+    fn test_map_value_on_next_line() {
+        let value = Term::Tuple((1..=3).map(|n| Term::Small(n * 1000)).collect());
+        let map = Term::Map(vec![(Term::Small(1), value)]);
+        assert_eq!(pretty(&map, 16), "#{1 =>\n      {1000,\n       2000,\n       3000}}");
+    }
+}
@@ -8,11 +8,12 @@
 //!
 //! The print term module converts Erlang terms to their string representations,
 //! following Erlang's standard formatting conventions:
-//! - **Atoms**: Printed as atom names (or atom indices in simplified implementation)
+//! - **Atoms**: Printed as atom names when an atom table is given, otherwise as `atom_N`
 //! - **Integers**: Printed as decimal numbers
 //! - **Floats**: Printed in standard floating-point notation
 //! - **Binaries**: Printed as `<<...>>` with byte values
-//! - **Lists**: Printed as `[...]` with elements
+//! - **Lists**: Printed as `[...]` with elements, improper tails as `|Tail`, printable lists as strings
+//! - **Pids, ports, refs and funs**: Printed as `<0.1.2>`, `#Port<0.1>`, `#Ref<0.3.2.1>` and `fun m:f/1`
 //! - **Tuples**: Printed as `{...}` with elements
 //! - **Maps**: Printed as `#{...}` with key-value pairs
 //!
@@ -20,11 +21,13 @@
 //!
 //! - **`print_term`**: Prints a term to stdout
 //! - **`s_print_term`**: Converts a term to a string representation
+//! - **`s_print_term_with`**: Converts a term to a string with a `~P`-style depth limit
+//! - **`write_term`**: Prints a term to any writer, such as stderr
 //!
 //! ## Examples
 //!
 //! ```rust
-//! use infrastructure_data_handling::print_term;
+//! use infrastructure_data_handling::print_term::{print_term, s_print_term};
 //! use entities_data_handling::term_hashing::Term;
 //!
 //! // Print a term to stdout
@@ -43,9 +46,24 @@
 //! - [`decode_term`](super::decode_term/index.html): Term decoding functions
 //! - [`entities_data_handling::term_hashing`](../../entities/entities_data_handling/term_hashing/index.html): Term type definitions
 //!
-//! Based on `lib/erl_interface/src/misc/ei_printterm.c`
+//! Based on `lib/erl_interface/src/misc/ei_printterm.c` and `erts/emulator/beam/erl_printf_term.c`
+
+use std::io::Write;
 
 use entities_data_handling::term_hashing::Term;
+use entities_data_handling::AtomTable;
+
+/// Options for printing a term
+#[derive(Clone, Copy, Default)]
+pub struct PrintOptions<'a> {
+    /// Depth limit with the semantics of `~P`: each nesting level and each
+    /// list, tuple, map or binary element uses one level, and cut-off parts
+    /// print as `...`. `None` prints the whole term.
+    pub depth: Option<usize>,
+    /// Atom table to look atom names up in. Without one, or for an atom
+    /// not in the table, atoms print as `atom_<index>`.
+    pub atoms: Option<&'a AtomTable>,
+}
 
 /// Print a term to stdout
 ///
@@ -56,11 +74,7 @@ use entities_data_handling::term_hashing::Term;
 /// * `Ok(())` - Success
 /// * `Err(PrintError)` - Print error
 pub fn print_term(term: &Term) -> Result<(), PrintError> {
-    let mut buf = Vec::new();
-    print_term_internal(term, &mut buf, 0)?;
-    let s = String::from_utf8(buf).map_err(|_| PrintError::EncodingError)?;
-    print!("{}", s);
-    Ok(())
+    write_term(&mut std::io::stdout().lock(), term, &PrintOptions::default())
 }
 
 /// Print a term to a string
@@ -72,149 +86,303 @@ pub fn print_term(term: &Term) -> Result<(), PrintError> {
 /// * `Ok(string)` - String representation
 /// * `Err(PrintError)` - Print error
 pub fn s_print_term(term: &Term) -> Result<String, PrintError> {
+    s_print_term_with(term, &PrintOptions::default())
+}
+
+/// Print a term to a string with options
+///
+/// # Arguments
+/// * `term` - Term to print
+/// * `options` - Depth limit and atom table
+///
+/// # Returns
+/// * `Ok(string)` - String representation
+/// * `Err(PrintError)` - Print error
+///
+/// # Examples
+/// ```
+/// use infrastructure_data_handling::print_term::{s_print_term_with, PrintOptions};
+/// use entities_data_handling::term_hashing::Term;
+///
+/// let list = (1..=5).rev().fold(Term::Nil, |tail, n| Term::List {
+///     head: Box::new(Term::Small(n)),
+///     tail: Box::new(tail),
+/// });
+/// let options = PrintOptions { depth: Some(3), ..Default::default() };
+/// assert_eq!(s_print_term_with(&list, &options).unwrap(), "[1,2|...]");
+/// ```
+pub fn s_print_term_with(term: &Term, options: &PrintOptions) -> Result<String, PrintError> {
     let mut buf = Vec::new();
-    print_term_internal(term, &mut buf, 0)?;
+    write_term(&mut buf, term, options)?;
     String::from_utf8(buf).map_err(|_| PrintError::EncodingError)
 }
 
-/// Internal printing function
-fn print_term_internal(
-    term: &Term,
-    buf: &mut Vec<u8>,
-    _depth: usize,
-) -> Result<(), PrintError> {
-    match term {
-        Term::Nil => {
-            buf.extend_from_slice(b"[]");
+/// Print a term to a writer, such as stdout, stderr or a buffer
+///
+/// # Arguments
+/// * `out` - Writer to print to
+/// * `term` - Term to print
+/// * `options` - Depth limit and atom table
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(PrintError)` - Invalid term or write error
+pub fn write_term<W: Write>(out: &mut W, term: &Term, options: &PrintOptions) -> Result<(), PrintError> {
+    let mut buf = Vec::new();
+    Printer { buf: &mut buf, atoms: options.atoms }.term(term, options.depth)?;
+    out.write_all(&buf).map_err(|e| PrintError::IoError(e.to_string()))
+}
+
+/// Term printer following the `%T` format of erl_printf_term.c
+struct Printer<'a, 'b> {
+    buf: &'b mut Vec<u8>,
+    atoms: Option<&'a AtomTable>,
+}
+
+impl Printer<'_, '_> {
+    fn text(&mut self, text: &str) {
+        self.buf.extend_from_slice(text.as_bytes());
+    }
+
+    /// Print `term` within `depth` levels (`None` for unlimited)
+    fn term(&mut self, term: &Term, depth: Option<usize>) -> Result<(), PrintError> {
+        if depth == Some(0) {
+            self.text("...");
+            return Ok(());
         }
-        Term::Small(n) => {
-            let s = n.to_string();
-            buf.extend_from_slice(s.as_bytes());
+        let inner = depth.map(|d| d - 1);
+        match term {
+            Term::Nil => self.text("[]"),
+            Term::Small(n) => self.text(&n.to_string()),
+            Term::Big(n) => self.text(&n.to_string_base(10)),
+            Term::Rational(rational) => {
+                self.text(&format!("{}/{}", rational.numerator(), rational.denominator()))
+            }
+            Term::Float(f) => self.float(*f),
+            Term::Atom(index) => self.atom(*index),
+            Term::Binary { data, bit_offset, bit_size } => self.bits(data, *bit_offset, *bit_size, depth)?,
+            Term::List { .. } => {
+                if let Some(string) = printable_string(term) {
+                    self.string(&string);
+                } else if depth == Some(1) {
+                    self.text("[...]");
+                } else {
+                    self.list(term, inner)?;
+                }
+            }
+            Term::Tuple(elements) => {
+                self.text("{");
+                self.elements(elements.iter(), depth, |printer, elem, depth| printer.term(elem, depth))?;
+                self.text("}");
+            }
+            Term::Map(pairs) => {
+                self.text("#{");
+                self.elements(pairs.iter(), depth, |printer, (key, value), depth| {
+                    printer.term(key, depth)?;
+                    printer.text("=>");
+                    printer.term(value, depth)
+                })?;
+                self.text("}");
+            }
+            Term::Pid { node, id, serial, .. } => self.text(&format!("<{}.{}.{}>", node, id, serial)),
+            Term::Port { node, id, .. } => self.text(&format!("#Port<{}.{}>", node, id)),
+            Term::Ref { node, ids, .. } => {
+                // Reference numbers print most significant first
+                self.text(&format!("#Ref<{}", node));
+                for id in ids.iter().rev() {
+                    self.text(&format!(".{}", id));
+                }
+                self.text(">");
+            }
+            Term::Fun { is_local: true, module, function, old_uniq, .. } => {
+                self.text("#Fun<");
+                self.atom(*module);
+                self.text(&format!(".{}.{}>", function, old_uniq.unwrap_or(0)));
+            }
+            Term::Fun { is_local: false, module, function, arity, .. } => {
+                self.text("fun ");
+                self.atom(*module);
+                self.text(":");
+                self.atom(*function);
+                self.text(&format!("/{}", arity));
+            }
         }
-        Term::Atom(index) => {
-            // For now, print atom index
-            // In full implementation, would look up atom name
-            buf.extend_from_slice(b"atom_");
-            buf.extend_from_slice(index.to_string().as_bytes());
+        Ok(())
+    }
+
+    /// Print the elements of a tuple or map within `depth`, cutting off
+    /// the remaining elements with `...` when the depth runs out
+    fn elements<T>(
+        &mut self,
+        elements: impl ExactSizeIterator<Item = T>,
+        depth: Option<usize>,
+        mut element: impl FnMut(&mut Self, T, Option<usize>) -> Result<(), PrintError>,
+    ) -> Result<(), PrintError> {
+        if elements.len() == 0 {
+            return Ok(());
+        }
+        if depth == Some(1) {
+            self.text("...");
+            return Ok(());
         }
-        Term::Float(f) => {
-            let s = f.to_string();
-            buf.extend_from_slice(s.as_bytes());
+        let mut depth = depth.map(|d| d - 1);
+        for (i, elem) in elements.enumerate() {
+            if i > 0 {
+                self.text(",");
+                if depth == Some(1) {
+                    self.text("...");
+                    break;
+                }
+                depth = depth.map(|d| d - 1);
+            }
+            element(self, elem, depth)?;
         }
-        Term::Binary { data, bit_offset, bit_size } => {
-            if *bit_offset == 0 && *bit_size == data.len() * 8 {
-                // Full byte binary
-                buf.extend_from_slice(b"<<");
-                for (i, byte) in data.iter().enumerate() {
-                    if i > 0 {
-                        buf.extend_from_slice(b",");
+        Ok(())
+    }
+
+    /// Print a non-string list, looping over the tails
+    fn list(&mut self, list: &Term, depth: Option<usize>) -> Result<(), PrintError> {
+        self.text("[");
+        let mut depth = depth;
+        let mut current = list;
+        let mut first = true;
+        loop {
+            match current {
+                Term::Nil => break,
+                Term::List { head, tail } => {
+                    if !first {
+                        if depth == Some(1) {
+                            self.text("|...");
+                            break;
+                        }
+                        self.text(",");
+                        depth = depth.map(|d| d - 1);
                     }
-                    buf.extend_from_slice(byte.to_string().as_bytes());
+                    self.term(head, depth)?;
+                    current = tail;
+                    first = false;
+                }
+                improper => {
+                    self.text("|");
+                    self.term(improper, depth.map(|d| d.saturating_sub(1)))?;
+                    break;
                 }
-                buf.extend_from_slice(b">>");
-            } else {
-                // Bitstring
-                buf.extend_from_slice(b"<<");
-                // Simplified bitstring representation
-                buf.extend_from_slice(bit_size.to_string().as_bytes());
-                buf.extend_from_slice(b":");
-                buf.extend_from_slice(bit_offset.to_string().as_bytes());
-                buf.extend_from_slice(b">>");
             }
         }
-        Term::List { head, tail } => {
-            buf.extend_from_slice(b"[");
-            print_term_internal(head, buf, 0)?;
-            if let Term::Nil = **tail {
-                // End of list
-            } else {
-                buf.extend_from_slice(b",");
-                print_term_internal(tail, buf, 0)?;
+        self.text("]");
+        Ok(())
+    }
+
+    fn string(&mut self, string: &[u8]) {
+        self.buf.push(b'"');
+        for &c in string {
+            if c == b'"' || c == b'\\' {
+                self.buf.push(b'\\');
             }
-            buf.extend_from_slice(b"]");
+            self.buf.push(c);
         }
-        Term::Tuple(elements) => {
-            buf.extend_from_slice(b"{");
-            for (i, elem) in elements.iter().enumerate() {
-                if i > 0 {
-                    buf.extend_from_slice(b",");
-                }
-                print_term_internal(elem, buf, 0)?;
+        self.buf.push(b'"');
+    }
+
+    /// Print a float so it always reads back as a float (`1.0`, `1.0e100`)
+    fn float(&mut self, f: f64) {
+        let text = format!("{:?}", f);
+        match text.find('e') {
+            Some(exponent) if !text[..exponent].contains('.') => {
+                self.text(&format!("{}.0{}", &text[..exponent], &text[exponent..]))
             }
-            buf.extend_from_slice(b"}");
+            _ => self.text(&text),
         }
-        Term::Map(pairs) => {
-            buf.extend_from_slice(b"#");
-            buf.extend_from_slice(b"{");
-            for (i, (key, value)) in pairs.iter().enumerate() {
-                if i > 0 {
-                    buf.extend_from_slice(b",");
+    }
+
+    /// Print an atom by name, quoted where needed
+    fn atom(&mut self, index: u32) {
+        let name = self.atoms.and_then(|atoms| atoms.get_name(index as usize));
+        let Some(name) = name else {
+            self.text(&format!("atom_{}", index));
+            return;
+        };
+        let name = String::from_utf8_lossy(&name).into_owned();
+        if atom_needs_quotes(&name) {
+            self.buf.push(b'\'');
+            for c in name.chars() {
+                if c == '\'' || c == '\\' {
+                    self.buf.push(b'\\');
                 }
-                print_term_internal(key, buf, 0)?;
-                buf.extend_from_slice(b"=>");
-                print_term_internal(value, buf, 0)?;
+                self.text(c.encode_utf8(&mut [0; 4]));
             }
-            buf.extend_from_slice(b"}");
-        }
-        Term::Pid { node, id, serial, creation } => {
-            buf.extend_from_slice(b"<");
-            buf.extend_from_slice(node.to_string().as_bytes());
-            buf.extend_from_slice(b".");
-            buf.extend_from_slice(id.to_string().as_bytes());
-            buf.extend_from_slice(b".");
-            buf.extend_from_slice(serial.to_string().as_bytes());
-            buf.extend_from_slice(b".");
-            buf.extend_from_slice(creation.to_string().as_bytes());
-            buf.extend_from_slice(b">");
+            self.buf.push(b'\'');
+        } else {
+            self.text(&name);
         }
-        Term::Port { node, id, creation } => {
-            buf.extend_from_slice(b"#Port<");
-            buf.extend_from_slice(node.to_string().as_bytes());
-            buf.extend_from_slice(b".");
-            buf.extend_from_slice(id.to_string().as_bytes());
-            buf.extend_from_slice(b".");
-            buf.extend_from_slice(creation.to_string().as_bytes());
-            buf.extend_from_slice(b">");
+    }
+
+    /// Print a binary or bitstring, with trailing bits as `Value:Size`
+    fn bits(&mut self, data: &[u8], bit_offset: usize, bit_size: usize, depth: Option<usize>) -> Result<(), PrintError> {
+        if bit_offset > 7 || bit_offset + bit_size > data.len() * 8 {
+            return Err(PrintError::InvalidBinary);
         }
-        Term::Ref { node, ids, creation } => {
-            buf.extend_from_slice(b"#Ref<");
-            buf.extend_from_slice(node.to_string().as_bytes());
-            for id in ids {
-                buf.extend_from_slice(b".");
-                buf.extend_from_slice(id.to_string().as_bytes());
+        let bit = |i: usize| (data[(bit_offset + i) / 8] >> (7 - (bit_offset + i) % 8)) & 1;
+        let value = |start: usize, len: usize| (start..start + len).fold(0u8, |v, i| (v << 1) | bit(i));
+        let bytes = bit_size / 8;
+        let rest = bit_size % 8;
+        let segments = bytes + usize::from(rest > 0);
+
+        self.text("<<");
+        for segment in 0..segments {
+            if segment > 0 {
+                self.text(",");
             }
-            buf.extend_from_slice(b".");
-            buf.extend_from_slice(creation.to_string().as_bytes());
-            buf.extend_from_slice(b">");
-        }
-        Term::Fun { is_local, module, function, arity, .. } => {
-            if *is_local {
-                buf.extend_from_slice(b"fun ");
+            // Each segment uses one level of depth
+            if depth.is_some_and(|d| segment + 1 >= d) {
+                self.text("...");
+                break;
+            }
+            if segment < bytes {
+                self.text(&value(segment * 8, 8).to_string());
             } else {
-                buf.extend_from_slice(b"fun ");
+                self.text(&format!("{}:{}", value(bytes * 8, rest), rest));
             }
-            buf.extend_from_slice(b"module:");
-            buf.extend_from_slice(module.to_string().as_bytes());
-            buf.extend_from_slice(b"/function:");
-            buf.extend_from_slice(function.to_string().as_bytes());
-            buf.extend_from_slice(b"/arity:");
-            buf.extend_from_slice(arity.to_string().as_bytes());
         }
-        Term::Big(..) => {
-            buf.extend_from_slice(b"<bignum>");
-        }
-        Term::Rational(rational) => {
-            // Print rational as numerator/denominator
-            let numerator = rational.numerator();
-            let denominator = rational.denominator();
-            let num_str = numerator.to_string();
-            let den_str = denominator.to_string();
-            buf.extend_from_slice(num_str.as_bytes());
-            buf.extend_from_slice(b"/");
-            buf.extend_from_slice(den_str.as_bytes());
+        self.text(">>");
+        Ok(())
+    }
+}
+
+/// The characters of a list printed as a string, if it is a non-empty
+/// proper list of printable ASCII characters
+fn printable_string(list: &Term) -> Option<Vec<u8>> {
+    let mut string = Vec::new();
+    let mut current = list;
+    loop {
+        match current {
+            Term::Nil => return (!string.is_empty()).then_some(string),
+            Term::List { head, tail } => match head.as_ref() {
+                Term::Small(c @ 32..=126) => {
+                    string.push(*c as u8);
+                    current = tail;
+                }
+                _ => return None,
+            },
+            _ => return None,
         }
     }
-    Ok(())
+}
+
+/// Whether an atom name must be quoted to read back as the same atom
+fn atom_needs_quotes(name: &str) -> bool {
+    const RESERVED: [&str; 27] = [
+        "after", "and", "andalso", "band", "begin", "bnot", "bor", "bsl", "bsr", "bxor", "case",
+        "catch", "cond", "div", "else", "end", "fun", "if", "let", "maybe", "not", "of", "or",
+        "orelse", "receive", "rem", "try",
+    ];
+    let mut chars = name.chars();
+    let starts_lower = chars.next().is_some_and(|c| c.is_ascii_lowercase());
+    !starts_lower
+        || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '@')
+        || RESERVED.contains(&name)
+        || name == "when"
+        || name == "xor"
 }
 
 /// Print errors
@@ -222,8 +390,24 @@ fn print_term_internal(
 pub enum PrintError {
     /// Encoding error
     EncodingError,
+    /// Binary whose bit offset or size does not fit its data
+    InvalidBinary,
+    /// Writing the output failed
+    IoError(String),
 }
 
+impl std::fmt::Display for PrintError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrintError::EncodingError => write!(f, "Encoding error"),
+            PrintError::InvalidBinary => write!(f, "Invalid binary"),
+            PrintError::IoError(msg) => write!(f, "I/O error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for PrintError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -317,9 +501,8 @@ mod tests {
         };
         let result = s_print_term(&term);
         assert!(result.is_ok());
-        let output = result.unwrap();
-        assert!(output.starts_with("<<"));
-        assert!(output.contains("12:4")); // bit_size:bit_offset
+        // Bits 4..16 of 0x0102: one full byte and four trailing bits
+        assert_eq!(result.unwrap(), "<<16,2:4>>");
     }
 
     #[test]
@@ -327,13 +510,11 @@ mod tests {
         let term = Term::Binary {
             data: vec![1, 2, 3],
             bit_offset: 2,
-            bit_size: 24,
+            bit_size: 20,
         };
         let result = s_print_term(&term);
         assert!(result.is_ok());
-        let output = result.unwrap();
-        assert!(output.starts_with("<<"));
-        assert!(output.contains("24:2"));
+        assert_eq!(result.unwrap(), "<<4,8,0:4>>");
     }
 
     #[test]
@@ -402,12 +583,12 @@ mod tests {
     #[test]
     fn test_print_list_single_element() {
         let term = Term::List {
-            head: Box::new(Term::Small(42)),
+            head: Box::new(Term::Small(420)),
             tail: Box::new(Term::Nil),
         };
         let result = s_print_term(&term);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "[42]");
+        assert_eq!(result.unwrap(), "[420]");
     }
 
     #[test]
@@ -457,9 +638,7 @@ mod tests {
         };
         let result = s_print_term(&term);
         assert!(result.is_ok());
-        let output = result.unwrap();
-        assert!(output.starts_with("[1,"));
-        assert!(output.ends_with("2]"));
+        assert_eq!(result.unwrap(), "[1|2]");
     }
 
     #[test]
@@ -550,7 +729,7 @@ mod tests {
         };
         let result = s_print_term(&term);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "<1.2.3.4>");
+        assert_eq!(result.unwrap(), "<1.2.3>");
     }
 
     #[test]
@@ -579,7 +758,7 @@ mod tests {
         };
         let result = s_print_term(&term);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "#Port<1.2.3>");
+        assert_eq!(result.unwrap(), "#Port<1.2>");
     }
 
     #[test]
@@ -607,11 +786,7 @@ mod tests {
         };
         let result = s_print_term(&term);
         assert!(result.is_ok());
-        let output = result.unwrap();
-        assert!(output.starts_with("#Ref<"));
-        assert!(output.contains("1"));
-        assert!(output.contains("2"));
-        assert!(output.ends_with(">"));
+        assert_eq!(result.unwrap(), "#Ref<1>");
     }
 
     #[test]
@@ -662,11 +837,7 @@ mod tests {
         };
         let result = s_print_term(&term);
         assert!(result.is_ok());
-        let output = result.unwrap();
-        assert!(output.starts_with("fun "));
-        assert!(output.contains("module:1"));
-        assert!(output.contains("function:2"));
-        assert!(output.contains("arity:3"));
+        assert_eq!(result.unwrap(), "#Fun<atom_1.2.0>");
     }
 
     #[test]
@@ -681,11 +852,7 @@ mod tests {
         };
         let result = s_print_term(&term);
         assert!(result.is_ok());
-        let output = result.unwrap();
-        assert!(output.starts_with("fun "));
-        assert!(output.contains("module:1"));
-        assert!(output.contains("function:2"));
-        assert!(output.contains("arity:3"));
+        assert_eq!(result.unwrap(), "#Fun<atom_1.2.100>");
     }
 
     #[test]
@@ -700,11 +867,7 @@ mod tests {
         };
         let result = s_print_term(&term);
         assert!(result.is_ok());
-        let output = result.unwrap();
-        assert!(output.starts_with("fun "));
-        assert!(output.contains("module:10"));
-        assert!(output.contains("function:20"));
-        assert!(output.contains("arity:30"));
+        assert_eq!(result.unwrap(), "fun atom_10:atom_20/30");
     }
 
     #[test]
@@ -855,5 +1018,93 @@ mod tests {
         assert!(output.contains("/"));
         assert!(output.contains("3"));
     }
-}
 
+    fn list(elements: Vec<Term>, tail: Term) -> Term {
+        elements.into_iter().rev().fold(tail, |tail, head| Term::List {
+            head: Box::new(head),
+            tail: Box::new(tail),
+        })
+    }
+
+    fn with_depth(term: &Term, depth: usize) -> String {
+        let options = PrintOptions { depth: Some(depth), ..Default::default() };
+        s_print_term_with(term, &options).unwrap()
+    }
+
+    #[test]
+    fn test_print_proper_and_improper_lists() {
+        let proper = list(vec![Term::Small(1), Term::Small(2), Term::Small(300)], Term::Nil);
+        assert_eq!(s_print_term(&proper).unwrap(), "[1,2,300]");
+        let improper = list(vec![Term::Small(1), Term::Small(2)], Term::Small(3));
+        assert_eq!(s_print_term(&improper).unwrap(), "[1,2|3]");
+    }
+
+    #[test]
+    fn test_print_printable_list_as_string() {
+        let string = list(b"say \"hi\"".iter().map(|&c| Term::Small(c as i64)).collect(), Term::Nil);
+        assert_eq!(s_print_term(&string).unwrap(), "\"say \\\"hi\\\"\"");
+        let not_string = list(vec![Term::Small(104), Term::Small(10)], Term::Nil);
+        assert_eq!(s_print_term(&not_string).unwrap(), "[104,10]");
+    }
+
+    #[test]
+    fn test_print_depth_limited() {
+        let numbers = list((1..=5).map(Term::Small).collect(), Term::Nil);
+        assert_eq!(with_depth(&numbers, 0), "...");
+        assert_eq!(with_depth(&numbers, 1), "[...]");
+        assert_eq!(with_depth(&numbers, 3), "[1,2|...]");
+        assert_eq!(with_depth(&numbers, 10), "[1,2,3,4,5]");
+
+        let inner = Term::Tuple(vec![Term::Small(2), Term::Small(3)]);
+        let tuple = Term::Tuple(vec![Term::Small(1), inner, Term::Small(4)]);
+        assert_eq!(with_depth(&tuple, 1), "{...}");
+        assert_eq!(with_depth(&tuple, 3), "{1,{...},...}");
+        assert_eq!(with_depth(&tuple, 4), "{1,{2,...},4}");
+
+        let map = Term::Map(vec![(Term::Small(1), Term::Small(2)), (Term::Small(3), Term::Small(4))]);
+        assert_eq!(with_depth(&map, 1), "#{...}");
+        assert_eq!(with_depth(&map, 2), "#{1=>2,...}");
+
+        let binary = Term::Binary { data: vec![1, 2, 3, 4], bit_offset: 0, bit_size: 32 };
+        assert_eq!(with_depth(&binary, 1), "<<...>>");
+        assert_eq!(with_depth(&binary, 3), "<<1,2,...>>");
+        assert_eq!(with_depth(&binary, 5), "<<1,2,3,4>>");
+    }
+
+    #[test]
+    fn test_print_atoms_with_table() {
+        use entities_data_handling::AtomEncoding;
+        let atoms = AtomTable::new(100);
+        let index = |name: &[u8]| atoms.put_index(name, AtomEncoding::Latin1, false).unwrap() as u32;
+        let term = Term::Tuple(vec![
+            Term::Atom(index(b"ok")),
+            Term::Atom(index(b"Hello")),
+            Term::Atom(index(b"receive")),
+            Term::Atom(index(b"it's")),
+            Term::Atom(99),
+        ]);
+        let options = PrintOptions { atoms: Some(&atoms), ..Default::default() };
+        assert_eq!(s_print_term_with(&term, &options).unwrap(), "{ok,'Hello','receive','it\\'s',atom_99}");
+    }
+
+    #[test]
+    fn test_print_floats_read_back_as_floats() {
+        assert_eq!(s_print_term(&Term::Float(1.0)).unwrap(), "1.0");
+        assert_eq!(s_print_term(&Term::Float(1e100)).unwrap(), "1.0e100");
+        assert_eq!(s_print_term(&Term::Float(-0.5)).unwrap(), "-0.5");
+    }
+
+    #[test]
+    fn test_print_invalid_binary() {
+        let term = Term::Binary { data: vec![1], bit_offset: 0, bit_size: 16 };
+        assert_eq!(s_print_term(&term), Err(PrintError::InvalidBinary));
+    }
+
+    #[test]
+    fn test_write_term_to_writer() {
+        let pid = Term::Pid { node: 0, id: 80, serial: 0, creation: 1 };
+        let mut out = Vec::new();
+        write_term(&mut out, &pid, &PrintOptions::default()).unwrap();
+        assert_eq!(out, b"<0.80.0>");
+    }
+}
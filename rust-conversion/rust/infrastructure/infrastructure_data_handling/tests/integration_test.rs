@@ -285,3 +285,32 @@ fn test_print_term_various_types() {
     }
 }
 
+
+#[test]
+fn test_pretty_print_term_wraps_crash_report() {
+    use entities_data_handling::atom::AtomTable;
+
+    let atoms = AtomTable::new(100);
+    let atom = |name: &str| Term::Atom(atoms.put_index(name.as_bytes(), AtomEncoding::Latin1, false).unwrap() as u32);
+    let chars = |text: &str| {
+        text.bytes().rev().fold(Term::Nil, |tail, c| Term::List {
+            head: Box::new(Term::Small(c as i64)),
+            tail: Box::new(tail),
+        })
+    };
+    let stack = Term::List {
+        head: Box::new(Term::Tuple(vec![atom("lists"), atom("map"), Term::Small(2), chars("lists.erl")])),
+        tail: Box::new(Term::Nil),
+    };
+    let report = Term::Tuple(vec![atom("badarg"), stack]);
+
+    let options = PrettyOptions { line_length: 30, atoms: Some(&atoms), ..Default::default() };
+    assert_eq!(
+        pretty_print_term(&report, &options).unwrap(),
+        "{badarg,\n [{lists,map,2,\"lists.erl\"}]}"
+    );
+    let flat = PrettyOptions { atoms: Some(&atoms), ..Default::default() };
+    assert_eq!(pretty_print_term(&report, &flat).unwrap(), "{badarg,[{lists,map,2,\"lists.erl\"}]}");
+    let shallow = PrettyOptions { depth: Some(3), atoms: Some(&atoms), ..Default::default() };
+    assert_eq!(pretty_print_term(&report, &shallow).unwrap(), "{badarg,[...]}");
+}
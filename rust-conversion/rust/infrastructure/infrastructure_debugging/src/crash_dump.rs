@@ -0,0 +1,560 @@
+//! Crash Dump Module
+//!
+//! Provides the crash dump writer used when the runtime system goes down on
+//! an unrecoverable error or `erlang:halt/1` with a slogan.
+//! Based on `erl_crash_dump()` in break.c
+//!
+//! The dump is a text file in the `erl_crash_dump` 0.5 format, made of
+//! sections that start with a `=tag` line:
+//! - the preamble, with the slogan and system version
+//! - one `=proc` section per process in the process table
+//! - sections registered with [`register_crash_dump_section`], such as the
+//!   ETS tables (`=ets`) and timers (`=timer`), whose owners live in outer
+//!   layers
+//! - the loaded modules (`=loaded_modules` and `=mod`)
+//! - the atom table (`=atoms`), newest atom first
+//! - `=end`
+//!
+//! The writer honors the environment variables of the C implementation:
+//! - `ERL_CRASH_DUMP` - file to write, `erl_crash.dump` by default
+//! - `ERL_CRASH_DUMP_SECONDS` - time limit; `0` disables the dump and `-1`
+//!   or unset means no limit
+//! - `ERL_CRASH_DUMP_BYTES` - size limit; `0` disables the dump. A dump
+//!   cut short by the limit ends with `=abort:file is full`
+
+use std::any::Any;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use code_management_code_loading::code_index::get_global_code_ix;
+use code_management_code_loading::module_management::get_global_module_manager;
+use entities_process::Process;
+use infrastructure_utilities::atom_table::get_global_atom_table;
+use infrastructure_utilities::process_table::get_global_process_table;
+
+/// Crash dump format version
+pub const CRASH_DUMP_VERSION: &str = "0.5";
+
+/// Crash dump file used when `ERL_CRASH_DUMP` is not set
+pub const DEFAULT_CRASH_DUMP: &str = "erl_crash.dump";
+
+/// Maximum number of characters of a slogan
+pub const MAX_SLOGAN_LENGTH: usize = 200;
+
+/// Writer of a crash dump section registered by another subsystem
+///
+/// The writer emits its own `=tag` lines.
+pub type CrashDumpSection = fn(&mut dyn Write) -> io::Result<()>;
+
+/// Sections registered by other subsystems, in registration order
+static SECTIONS: RwLock<Vec<(&'static str, CrashDumpSection)>> = RwLock::new(Vec::new());
+
+/// Set while a crash dump is being written, so a fatal error raised while
+/// dumping does not start a second dump
+static DUMPING: AtomicBool = AtomicBool::new(false);
+
+/// Crash dump errors
+#[derive(Debug)]
+pub enum CrashDumpError {
+    /// Crash dumps are disabled (`ERL_CRASH_DUMP_SECONDS=0` or `ERL_CRASH_DUMP_BYTES=0`)
+    Disabled,
+    /// Another crash dump is already being written
+    InProgress,
+    /// The time limit passed before the dump was complete
+    TimedOut,
+    /// Creating or writing the dump file failed
+    Io(io::Error),
+}
+
+impl std::fmt::Display for CrashDumpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CrashDumpError::Disabled => write!(f, "Crash dumps are disabled"),
+            CrashDumpError::InProgress => write!(f, "A crash dump is already being written"),
+            CrashDumpError::TimedOut => write!(f, "Crash dump time limit reached"),
+            CrashDumpError::Io(err) => write!(f, "Crash dump I/O error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for CrashDumpError {}
+
+impl From<io::Error> for CrashDumpError {
+    fn from(err: io::Error) -> Self {
+        CrashDumpError::Io(err)
+    }
+}
+
+/// Where and how much to dump
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrashDumpConfig {
+    /// File to write
+    pub path: PathBuf,
+    /// Time limit, `None` for no limit
+    pub time_limit: Option<Duration>,
+    /// Size limit in bytes, `None` for no limit
+    pub byte_limit: Option<u64>,
+    /// Crash dumps are enabled
+    pub enabled: bool,
+}
+
+impl Default for CrashDumpConfig {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from(DEFAULT_CRASH_DUMP),
+            time_limit: None,
+            byte_limit: None,
+            enabled: true,
+        }
+    }
+}
+
+impl CrashDumpConfig {
+    /// Read the configuration from `ERL_CRASH_DUMP`, `ERL_CRASH_DUMP_SECONDS`
+    /// and `ERL_CRASH_DUMP_BYTES`
+    ///
+    /// Values that are not integers are ignored.
+    pub fn from_env() -> Self {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    /// Build the configuration from variable lookups
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let mut config = Self::default();
+        if let Some(path) = var("ERL_CRASH_DUMP").filter(|path| !path.is_empty()) {
+            config.path = PathBuf::from(path);
+        }
+        match var("ERL_CRASH_DUMP_SECONDS").and_then(|s| s.trim().parse::<i64>().ok()) {
+            Some(0) => config.enabled = false,
+            Some(seconds) if seconds > 0 => config.time_limit = Some(Duration::from_secs(seconds as u64)),
+            _ => {}
+        }
+        match var("ERL_CRASH_DUMP_BYTES").and_then(|s| s.trim().parse::<u64>().ok()) {
+            Some(0) => config.enabled = false,
+            Some(bytes) => config.byte_limit = Some(bytes),
+            None => {}
+        }
+        config
+    }
+}
+
+/// Register a section to include in crash dumps
+///
+/// Used by subsystems this crate cannot reach, such as the ETS tables and
+/// BIF timers. Registering a name again replaces its writer.
+///
+/// # Arguments
+/// * `name` - Name of the section, e.g. `ets`
+/// * `section` - Writer of the section
+pub fn register_crash_dump_section(name: &'static str, section: CrashDumpSection) {
+    let mut sections = SECTIONS.write().unwrap();
+    match sections.iter_mut().find(|(registered, _)| *registered == name) {
+        Some(entry) => entry.1 = section,
+        None => sections.push((name, section)),
+    }
+}
+
+/// Remove a registered crash dump section
+///
+/// # Returns
+/// `true` if the section was registered
+pub fn unregister_crash_dump_section(name: &str) -> bool {
+    let mut sections = SECTIONS.write().unwrap();
+    let before = sections.len();
+    sections.retain(|(registered, _)| *registered != name);
+    sections.len() != before
+}
+
+/// Write a crash dump configured from the environment
+///
+/// Reports progress on stderr as the C implementation does.
+///
+/// # Arguments
+/// * `slogan` - Reason for the crash
+///
+/// # Returns
+/// * `Ok(path)` - The dump was written to `path`
+/// * `Err(CrashDumpError)` - The dump is disabled, already in progress, or failed
+pub fn write_crash_dump(slogan: &str) -> Result<PathBuf, CrashDumpError> {
+    let config = CrashDumpConfig::from_env();
+    if config.enabled {
+        eprintln!("\nCrash dump is being written to: {}...", config.path.display());
+    }
+    let result = write_crash_dump_with(&config, slogan);
+    match &result {
+        Ok(_) => eprintln!("done"),
+        Err(CrashDumpError::Disabled) | Err(CrashDumpError::InProgress) => {}
+        Err(err) => eprintln!("{}", err),
+    }
+    result.map(|()| config.path)
+}
+
+/// Write a crash dump
+///
+/// # Arguments
+/// * `config` - Where and how much to dump
+/// * `slogan` - Reason for the crash
+pub fn write_crash_dump_with(config: &CrashDumpConfig, slogan: &str) -> Result<(), CrashDumpError> {
+    if !config.enabled {
+        return Err(CrashDumpError::Disabled);
+    }
+    if DUMPING.swap(true, Ordering::AcqRel) {
+        return Err(CrashDumpError::InProgress);
+    }
+
+    let result = File::create(&config.path).map_err(CrashDumpError::from).and_then(|file| {
+        let mut out = LimitedWriter::new(BufWriter::new(file), config);
+        let result = write_crash_dump_to(&mut out, slogan);
+        finish_limited(out, result)
+    });
+
+    DUMPING.store(false, Ordering::Release);
+    result
+}
+
+/// Write a crash dump to a writer, without limits
+///
+/// # Arguments
+/// * `out` - Destination
+/// * `slogan` - Reason for the crash, truncated to [`MAX_SLOGAN_LENGTH`] characters
+pub fn write_crash_dump_to(out: &mut dyn Write, slogan: &str) -> io::Result<()> {
+    write_preamble(out, slogan)?;
+    write_processes(out)?;
+    let sections = SECTIONS.read().unwrap().clone();
+    for (_, section) in sections {
+        section(out)?;
+    }
+    write_loaded_modules(out)?;
+    write_atoms(out)?;
+    writeln!(out, "=end")?;
+    out.flush()
+}
+
+/// Write a crash dump and exit the OS process with status 1
+///
+/// Used for unrecoverable errors (`erts_exit(ERTS_DUMP_EXIT, ...)`).
+///
+/// # Arguments
+/// * `slogan` - Reason for the crash, also printed on stderr
+pub fn erts_fatal_error(slogan: &str) -> ! {
+    eprintln!("{}", slogan);
+    let _ = write_crash_dump(slogan);
+    std::process::exit(1)
+}
+
+/// Run `f`, turning a panic escaping it into a crash dump and an exit
+///
+/// Used around the top level of the emulator and the bodies of runtime
+/// threads, so only panics nobody catches are fatal; panics caught further
+/// down, such as in guard evaluation, are left alone. The panic hook has
+/// already printed the message when the dump is written. The OS process
+/// exits with status 1 afterwards.
+pub fn dump_on_panic<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(payload) => {
+            let _ = write_crash_dump(&panic_slogan(payload.as_ref()));
+            std::process::exit(1)
+        }
+    }
+}
+
+/// Slogan of a panic, from its `&str` or `String` payload
+fn panic_slogan(payload: &(dyn Any + Send)) -> String {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => match payload.downcast_ref::<String>() {
+            Some(message) => message.clone(),
+            None => "panic".to_string(),
+        },
+    }
+}
+
+/// Write the preamble (`=erl_crash_dump`)
+fn write_preamble(out: &mut dyn Write, slogan: &str) -> io::Result<()> {
+    let slogan: String = slogan.chars().take(MAX_SLOGAN_LENGTH).collect();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or(0);
+    let thread = std::thread::current();
+
+    writeln!(out, "=erl_crash_dump:{}", CRASH_DUMP_VERSION)?;
+    writeln!(out, "{}", format_ctime(now))?;
+    writeln!(out, "Slogan: {}", slogan)?;
+    writeln!(out, "System version: iron-beam {}", env!("CARGO_PKG_VERSION"))?;
+    writeln!(out, "Taints: ")?;
+    writeln!(out, "Atoms: {}", get_global_atom_table().size())?;
+    writeln!(out, "Calling Thread: {}", thread.name().unwrap_or("unnamed"))
+}
+
+/// Write one `=proc` section per process
+fn write_processes(out: &mut dyn Write) -> io::Result<()> {
+    let table = get_global_process_table();
+    let mut ids = table.get_all_ids();
+    ids.sort_unstable();
+    for id in ids {
+        if let Some(process) = table.lookup(id) {
+            write_process(out, &process)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the `=proc` section of a process
+fn write_process(out: &mut dyn Write, process: &Process) -> io::Result<()> {
+    writeln!(out, "=proc:<0.{}.0>", process.id())?;
+    writeln!(out, "State: {:?}", process.get_state())?;
+    if let Some(call) = process.initial_call() {
+        writeln!(out, "Spawned as: {}:{}/{}", call.module, call.function, call.arity)?;
+    }
+    if let Some(parent) = process.parent() {
+        writeln!(out, "Spawned by: <0.{}.0>", parent)?;
+    }
+    writeln!(out, "Message queue length: {}", process.message_queue_len())?;
+    writeln!(out, "Reductions: {}", process.reds())?;
+    writeln!(out, "Stack+heap: {}", process.heap_sz())
+}
+
+/// Write the `=loaded_modules` and `=mod` sections of the active code index
+fn write_loaded_modules(out: &mut dyn Write) -> io::Result<()> {
+    let manager = get_global_module_manager();
+    let code_ix = get_global_code_ix().active_code_ix() as usize;
+    let modules: Vec<_> = (0..manager.module_code_size(code_ix))
+        .filter_map(|index| manager.module_code(index, code_ix))
+        .collect();
+
+    let current: u64 = modules.iter().map(|module| module.curr.code_length as u64).sum();
+    let old: u64 = modules.iter().map(|module| module.old.code_length as u64).sum();
+    writeln!(out, "=loaded_modules")?;
+    writeln!(out, "Current code: {}", current)?;
+    writeln!(out, "Old code: {}", old)?;
+
+    let atoms = get_global_atom_table();
+    for module in modules {
+        let name = atoms
+            .get_name(module.module as usize)
+            .map(|name| String::from_utf8_lossy(&name).into_owned())
+            .unwrap_or_else(|| format!("module_{}", module.module));
+        writeln!(out, "=mod:{}", name)?;
+        writeln!(out, "Current size: {}", module.curr.code_length)?;
+        if module.old.code_length > 0 {
+            writeln!(out, "Old size: {}", module.old.code_length)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the `=atoms` section, newest atom first
+fn write_atoms(out: &mut dyn Write) -> io::Result<()> {
+    let atoms = get_global_atom_table();
+    writeln!(out, "=atoms")?;
+    for index in (0..atoms.size()).rev() {
+        if let Some(name) = atoms.get_name(index) {
+            out.write_all(&name)?;
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+/// Format a time as `ctime()` does, in UTC (`Sat Oct 17 09:30:00 2026`)
+fn format_ctime(secs: u64) -> String {
+    const DAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let days = secs / 86_400;
+    let rem = secs % 86_400;
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{} {} {:2} {:02}:{:02}:{:02} {}",
+        DAYS[(days % 7) as usize],
+        MONTHS[(month - 1) as usize],
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60,
+        year
+    )
+}
+
+/// Writer enforcing the size and time limits of a crash dump
+struct LimitedWriter<W: Write> {
+    /// Destination
+    inner: W,
+    /// Bytes that may still be written, `None` for no limit
+    remaining: Option<u64>,
+    /// Time at which writing stops, `None` for no limit
+    deadline: Option<Instant>,
+    /// The time limit was reached
+    timed_out: bool,
+    /// The size limit cut the dump short
+    full: bool,
+}
+
+impl<W: Write> LimitedWriter<W> {
+    fn new(inner: W, config: &CrashDumpConfig) -> Self {
+        Self {
+            inner,
+            remaining: config.byte_limit,
+            deadline: config.time_limit.map(|limit| Instant::now() + limit),
+            timed_out: false,
+            full: false,
+        }
+    }
+}
+
+impl<W: Write> Write for LimitedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            self.timed_out = true;
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "crash dump time limit reached"));
+        }
+        let len = match self.remaining {
+            Some(0) if !buf.is_empty() => {
+                self.full = true;
+                return Err(io::Error::new(io::ErrorKind::WriteZero, "crash dump size limit reached"));
+            }
+            Some(remaining) => buf.len().min(remaining as usize),
+            None => buf.len(),
+        };
+        let written = self.inner.write(&buf[..len])?;
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining -= written as u64;
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Complete a limited dump, marking one cut short by the size limit
+fn finish_limited<W: Write>(mut out: LimitedWriter<W>, result: io::Result<()>) -> Result<(), CrashDumpError> {
+    if out.timed_out {
+        let _ = out.inner.flush();
+        return Err(CrashDumpError::TimedOut);
+    }
+    if out.full {
+        writeln!(out.inner, "\n=abort:file is full")?;
+        out.inner.flush()?;
+        return Ok(());
+    }
+    result?;
+    out.inner.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entities_data_handling::AtomEncoding;
+
+    fn dump_section(out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "=ets:<0.1.0>")?;
+        writeln!(out, "Name: crash_dump_test")
+    }
+
+    #[test]
+    fn test_config_from_vars() {
+        let config = CrashDumpConfig::from_vars(|_| None);
+        assert_eq!(config, CrashDumpConfig::default());
+
+        let config = CrashDumpConfig::from_vars(|name| match name {
+            "ERL_CRASH_DUMP" => Some("/tmp/x.dump".to_string()),
+            "ERL_CRASH_DUMP_SECONDS" => Some("5".to_string()),
+            "ERL_CRASH_DUMP_BYTES" => Some("1024".to_string()),
+            _ => None,
+        });
+        assert_eq!(config.path, PathBuf::from("/tmp/x.dump"));
+        assert_eq!(config.time_limit, Some(Duration::from_secs(5)));
+        assert_eq!(config.byte_limit, Some(1024));
+        assert!(config.enabled);
+
+        let unlimited = CrashDumpConfig::from_vars(|name| (name == "ERL_CRASH_DUMP_SECONDS").then(|| "-1".to_string()));
+        assert_eq!(unlimited.time_limit, None);
+        assert!(!CrashDumpConfig::from_vars(|name| (name == "ERL_CRASH_DUMP_SECONDS").then(|| "0".to_string())).enabled);
+        assert!(!CrashDumpConfig::from_vars(|name| (name == "ERL_CRASH_DUMP_BYTES").then(|| "0".to_string())).enabled);
+    }
+
+    #[test]
+    fn test_dump_contents() {
+        get_global_atom_table()
+            .put_index(b"crash_dump_test_atom", AtomEncoding::SevenBitAscii, false)
+            .unwrap();
+        register_crash_dump_section("crash_dump_test", dump_section);
+
+        let mut out = Vec::new();
+        write_crash_dump_to(&mut out, &"s".repeat(MAX_SLOGAN_LENGTH + 5)).unwrap();
+        let dump = String::from_utf8(out).unwrap();
+
+        assert!(dump.starts_with("=erl_crash_dump:0.5\n"));
+        assert!(dump.contains(&format!("Slogan: {}\n", "s".repeat(MAX_SLOGAN_LENGTH))));
+        assert!(dump.contains("=ets:<0.1.0>\nName: crash_dump_test\n"));
+        assert!(dump.contains("=loaded_modules\n"));
+        assert!(dump.contains("\ncrash_dump_test_atom\n"));
+        assert!(dump.ends_with("=end\n"));
+        assert!(dump.find("=ets").unwrap() < dump.find("=loaded_modules").unwrap());
+        assert!(unregister_crash_dump_section("crash_dump_test"));
+    }
+
+    #[test]
+    fn test_size_limit_and_disabled() {
+        let dir = std::env::temp_dir().join(format!("crash_dump_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("limited.dump");
+
+        let config = CrashDumpConfig {
+            path: path.clone(),
+            byte_limit: Some(40),
+            ..CrashDumpConfig::default()
+        };
+        write_crash_dump_with(&config, "too big").unwrap();
+        let dump = std::fs::read_to_string(&path).unwrap();
+        assert!(dump.starts_with("=erl_crash_dump:0.5\n"));
+        assert!(dump.ends_with("\n=abort:file is full\n"));
+        assert_eq!(dump.len(), 40 + "\n=abort:file is full\n".len());
+
+        let disabled = CrashDumpConfig { enabled: false, ..config };
+        assert!(matches!(write_crash_dump_with(&disabled, "x"), Err(CrashDumpError::Disabled)));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_format_ctime() {
+        assert_eq!(format_ctime(0), "Thu Jan  1 00:00:00 1970");
+        assert_eq!(format_ctime(1_792_229_400), "Sat Oct 17 09:30:00 2026");
+    }
+
+    #[test]
+    fn test_dump_on_panic_ignores_caught_panics() {
+        let caught = dump_on_panic(|| panic::catch_unwind(|| panic!("caught")).is_err());
+        assert!(caught);
+        assert_eq!(dump_on_panic(|| 7), 7);
+
+        assert_eq!(panic_slogan(&"static"), "static");
+        assert_eq!(panic_slogan(&"owned".to_string()), "owned");
+        assert_eq!(panic_slogan(&7), "panic");
+    }
+}
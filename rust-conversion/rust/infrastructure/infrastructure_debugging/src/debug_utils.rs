@@ -8,10 +8,18 @@
 //! - Term display and formatting
 //! - Debug state management
 //! - Integration with debugging adapters
+//! - Heap consistency checking (similar to `erts_check_heap()` in C)
 
 use std::sync::atomic::{AtomicBool, Ordering};
-use infrastructure_data_handling::print_term::{print_term, s_print_term};
+use std::io::Write;
+use infrastructure_data_handling::print_term::{s_print_term, write_term, PrintError, PrintOptions};
 use entities_data_handling::term_hashing::Term;
+use entities_process::term_tags::{
+    ARITYVAL_SUBTAG, EXTERNAL_PID_SUBTAG, EXTERNAL_PORT_SUBTAG, EXTERNAL_REF_SUBTAG, FUN_SUBTAG,
+    HEADER_ARITY_OFFS, MAP_SUBTAG, REFC_BINARY_SUBTAG, REF_SUBTAG, TAG_HEADER_MASK,
+    TAG_PRIMARY_BOXED, TAG_PRIMARY_HEADER, TAG_PRIMARY_LIST, TAG_PRIMARY_MASK, UNUSED_SUBTAG,
+};
+use entities_process::{Eterm, Process};
 
 /// Global debug state
 static DEBUG_ENABLED: AtomicBool = AtomicBool::new(false);
@@ -152,7 +160,8 @@ impl DebugUtils {
     /// Display a term (print term display)
     ///
     /// This function displays a term in readable format, similar to `ptd()` in the C code.
-    /// It uses the print_term functionality from infrastructure_data_handling.
+    /// It uses the print_term functionality from infrastructure_data_handling,
+    /// writing the term and its newline to stderr together.
     ///
     /// # Arguments
     ///
@@ -174,8 +183,10 @@ impl DebugUtils {
     /// ```
     pub fn display_term(term: &Term) -> Result<(), DebugError> {
         if Self::is_enabled() {
-            print_term(term).map_err(|e| DebugError::PrintError(format!("{:?}", e)))?;
-            eprintln!(); // Newline after term
+            let mut stderr = std::io::stderr().lock();
+            write_term(&mut stderr, term, &PrintOptions::default())
+                .and_then(|()| writeln!(stderr).map_err(|e| PrintError::IoError(e.to_string())))
+                .map_err(|e| DebugError::PrintError(e.to_string()))?;
         }
         Ok(())
     }
@@ -205,7 +216,7 @@ impl DebugUtils {
     /// assert_eq!(s, "42");
     /// ```
     pub fn term_to_string(term: &Term) -> Result<String, DebugError> {
-        s_print_term(term).map_err(|e| DebugError::PrintError(format!("{:?}", e)))
+        s_print_term(term).map_err(|e| DebugError::PrintError(e.to_string()))
     }
 
     /// Paranoid display of a term
@@ -272,6 +283,257 @@ impl std::fmt::Display for DebugError {
 
 impl std::error::Error for DebugError {}
 
+/// Heap to be checked by [`check_heap`]
+///
+/// Pointer terms address heap words by index: a list or boxed term is
+/// `(index << 2) | tag`, with the primary tags of erl_term.h.
+#[derive(Debug, Clone, Copy)]
+pub struct HeapView<'a> {
+    /// Heap words; the stack occupies the words from `stack_top` to the end
+    pub heap: &'a [Eterm],
+    /// First heap word in use
+    pub heap_start: usize,
+    /// First free heap word (`htop`)
+    pub heap_top: usize,
+    /// First stack word (`stop`), if the process has a stack
+    pub stack_top: Option<usize>,
+    /// Heap indices of the objects on the off-heap list
+    pub off_heap: &'a [usize],
+}
+
+/// Heap consistency errors found by [`check_heap`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeapCheckError {
+    /// `htop` or `stop` lies outside the heap, or the heap overlaps the stack
+    BadBounds {
+        /// Description of the violated bound
+        reason: String,
+    },
+    /// A list or boxed term points outside the live heap
+    PointerOutOfBounds {
+        /// Heap index of the word holding the term
+        location: usize,
+        /// The term
+        term: Eterm,
+    },
+    /// A list term points to a cons cell containing a header word
+    BadConsCell {
+        /// Heap index of the word holding the term
+        location: usize,
+        /// The term
+        term: Eterm,
+    },
+    /// A boxed term does not point to a valid header
+    BadBoxed {
+        /// Heap index of the word holding the term
+        location: usize,
+        /// The term
+        term: Eterm,
+    },
+    /// A header word has an unused subtag, or its object runs past `htop`
+    BadHeader {
+        /// Heap index of the header
+        location: usize,
+        /// The header word
+        header: Eterm,
+    },
+    /// A header word appears where a term is expected
+    HeaderInTermPosition {
+        /// Heap index of the word
+        location: usize,
+        /// The word
+        word: Eterm,
+    },
+    /// An off-heap list entry is not a live off-heap object, or is listed twice
+    BadOffHeap {
+        /// Heap index in the off-heap entry
+        location: usize,
+    },
+}
+
+impl std::fmt::Display for HeapCheckError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HeapCheckError::BadBounds { reason } => write!(f, "Bad heap bounds: {}", reason),
+            HeapCheckError::PointerOutOfBounds { location, term } => {
+                write!(f, "Pointer out of heap at {}: {:#x}", location, term)
+            }
+            HeapCheckError::BadConsCell { location, term } => {
+                write!(f, "List term at {} points to a bad cons cell: {:#x}", location, term)
+            }
+            HeapCheckError::BadBoxed { location, term } => {
+                write!(f, "Boxed term at {} does not point to a header: {:#x}", location, term)
+            }
+            HeapCheckError::BadHeader { location, header } => {
+                write!(f, "Bad header at {}: {:#x}", location, header)
+            }
+            HeapCheckError::HeaderInTermPosition { location, word } => {
+                write!(f, "Header word in term position at {}: {:#x}", location, word)
+            }
+            HeapCheckError::BadOffHeap { location } => write!(f, "Bad off-heap entry: {}", location),
+        }
+    }
+}
+
+impl std::error::Error for HeapCheckError {}
+
+/// Check a heap for corruption (`erts_check_heap`)
+///
+/// Walks the heap objects from `heap_start` to `heap_top` and the stack,
+/// checking that:
+/// - every list and boxed term points into the live heap
+/// - list terms point to cons cells and boxed terms point to valid headers
+/// - headers have a used subtag and their objects end before `heap_top`
+/// - off-heap list entries are distinct off-heap objects in the live heap
+///
+/// Tuple and map payloads are checked as terms; the payloads of other
+/// boxed objects hold raw data and are skipped. Stack words with the
+/// header tag are continuation pointers and are skipped.
+///
+/// # Arguments
+///
+/// * `view` - The heap to check
+///
+/// # Returns
+///
+/// * `Ok(())` - The heap is consistent
+/// * `Err(errors)` - All inconsistencies found
+pub fn check_heap(view: &HeapView<'_>) -> Result<(), Vec<HeapCheckError>> {
+    let mut errors = Vec::new();
+    let stack_top = view.stack_top.unwrap_or(view.heap.len());
+    if view.heap_start > view.heap_top || view.heap_top > stack_top || stack_top > view.heap.len() {
+        errors.push(HeapCheckError::BadBounds {
+            reason: format!(
+                "heap_start {} heap_top {} stack_top {} size {}",
+                view.heap_start,
+                view.heap_top,
+                stack_top,
+                view.heap.len()
+            ),
+        });
+        return Err(errors);
+    }
+
+    let mut index = view.heap_start;
+    while index < view.heap_top {
+        let word = view.heap[index];
+        if word & TAG_PRIMARY_MASK != TAG_PRIMARY_HEADER {
+            check_term(view, index, word, &mut errors);
+            index += 1;
+            continue;
+        }
+        let arity = (word >> HEADER_ARITY_OFFS) as usize;
+        let subtag = word & TAG_HEADER_MASK;
+        if subtag == UNUSED_SUBTAG || arity >= view.heap_top - index {
+            errors.push(HeapCheckError::BadHeader { location: index, header: word });
+            index += 1;
+            continue;
+        }
+        if subtag == ARITYVAL_SUBTAG || subtag == MAP_SUBTAG {
+            for element in index + 1..=index + arity {
+                let term = view.heap[element];
+                if term & TAG_PRIMARY_MASK == TAG_PRIMARY_HEADER {
+                    errors.push(HeapCheckError::HeaderInTermPosition { location: element, word: term });
+                } else {
+                    check_term(view, element, term, &mut errors);
+                }
+            }
+        }
+        index += arity + 1;
+    }
+
+    for (location, &word) in view.heap.iter().enumerate().skip(stack_top) {
+        if word & TAG_PRIMARY_MASK != TAG_PRIMARY_HEADER {
+            check_term(view, location, word, &mut errors);
+        }
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    for &object in view.off_heap {
+        let valid = object >= view.heap_start
+            && object < view.heap_top
+            && is_off_heap_header(view.heap[object])
+            && seen.insert(object);
+        if !valid {
+            errors.push(HeapCheckError::BadOffHeap { location: object });
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Check a term held at `location`
+fn check_term(view: &HeapView<'_>, location: usize, term: Eterm, errors: &mut Vec<HeapCheckError>) {
+    let tag = term & TAG_PRIMARY_MASK;
+    if tag != TAG_PRIMARY_LIST && tag != TAG_PRIMARY_BOXED {
+        return;
+    }
+    let target = (term >> 2) as usize;
+    let words = if tag == TAG_PRIMARY_LIST { 2 } else { 1 };
+    if target < view.heap_start || target + words > view.heap_top {
+        errors.push(HeapCheckError::PointerOutOfBounds { location, term });
+        return;
+    }
+    if tag == TAG_PRIMARY_LIST {
+        let cell = &view.heap[target..target + 2];
+        if cell.iter().any(|word| word & TAG_PRIMARY_MASK == TAG_PRIMARY_HEADER && *word != 0) {
+            errors.push(HeapCheckError::BadConsCell { location, term });
+        }
+    } else {
+        let header = view.heap[target];
+        if header & TAG_PRIMARY_MASK != TAG_PRIMARY_HEADER || header & TAG_HEADER_MASK == UNUSED_SUBTAG {
+            errors.push(HeapCheckError::BadBoxed { location, term });
+        }
+    }
+}
+
+/// Check if a header starts an object kept on the off-heap list
+fn is_off_heap_header(header: Eterm) -> bool {
+    header & TAG_PRIMARY_MASK == TAG_PRIMARY_HEADER
+        && matches!(
+            header & TAG_HEADER_MASK,
+            REF_SUBTAG
+                | FUN_SUBTAG
+                | REFC_BINARY_SUBTAG
+                | EXTERNAL_PID_SUBTAG
+                | EXTERNAL_PORT_SUBTAG
+                | EXTERNAL_REF_SUBTAG
+        )
+}
+
+/// Check the heap and stack of a process
+///
+/// The process does not track an off-heap list, so only the heap and stack
+/// are checked.
+pub fn check_process_heap(process: &Process) -> Result<(), Vec<HeapCheckError>> {
+    let heap = process.heap_slice();
+    check_heap(&HeapView {
+        heap: &heap,
+        heap_start: process.heap_start_index(),
+        heap_top: process.heap_top_index(),
+        stack_top: process.stack_top_index(),
+        off_heap: &[],
+    })
+}
+
+/// Check the heap of a process in debug builds, panicking on corruption
+///
+/// Intended to be called after garbage collection and code loading; does
+/// nothing in release builds.
+pub fn debug_check_process_heap(process: &Process) {
+    #[cfg(debug_assertions)]
+    if let Err(errors) = check_process_heap(process) {
+        let report: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        panic!("heap of process {} is corrupt: {}", process.id(), report.join("; "));
+    }
+    #[cfg(not(debug_assertions))]
+    let _ = process;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -755,7 +1017,7 @@ mod tests {
         let result = DebugUtils::term_to_string(&term);
         assert!(result.is_ok());
         let s = result.unwrap();
-        assert!(s.contains("fun"));
+        assert_eq!(s, "#Fun<atom_1.2.0>");
     }
 
     #[test]
@@ -765,7 +1027,7 @@ mod tests {
         let result = DebugUtils::term_to_string(&term);
         assert!(result.is_ok());
         let s = result.unwrap();
-        assert_eq!(s, "<bignum>");
+        assert_eq!(s, "1234567890");
     }
 
     #[test]
@@ -850,5 +1112,119 @@ mod tests {
         DebugUtils::disable_verbose();
         DebugUtils::verbose_output("should not appear");
     }
+
+    use entities_process::term_tags::{make_small, NIL};
+
+    fn small(value: u64) -> Eterm {
+        make_small(value as i64)
+    }
+
+    fn list(index: usize) -> Eterm {
+        ((index as Eterm) << 2) | TAG_PRIMARY_LIST
+    }
+
+    fn boxed(index: usize) -> Eterm {
+        ((index as Eterm) << 2) | TAG_PRIMARY_BOXED
+    }
+
+    fn header(arity: usize, subtag: Eterm) -> Eterm {
+        ((arity as Eterm) << HEADER_ARITY_OFFS) | subtag
+    }
+
+    fn view<'a>(heap: &'a [Eterm], heap_top: usize, stack_top: Option<usize>, off_heap: &'a [usize]) -> HeapView<'a> {
+        HeapView {
+            heap,
+            heap_start: 0,
+            heap_top,
+            stack_top,
+            off_heap,
+        }
+    }
+
+    /// Heap holding [1, {2, [1]}] with the list on the stack
+    fn valid_heap() -> Vec<Eterm> {
+        vec![
+            small(1),
+            boxed(2),
+            header(2, ARITYVAL_SUBTAG),
+            small(2),
+            list(5),
+            small(1),
+            NIL,
+            header(1, REFC_BINARY_SUBTAG),
+            42,
+            0,
+            list(0),
+            0x1000, // continuation pointer
+        ]
+    }
+
+    #[test]
+    fn test_check_heap_valid() {
+        let heap = valid_heap();
+        assert_eq!(check_heap(&view(&heap, 9, Some(10), &[7])), Ok(()));
+    }
+
+    #[test]
+    fn test_check_heap_pointer_out_of_bounds() {
+        let mut heap = valid_heap();
+        heap[10] = list(9);
+        heap[4] = boxed(100);
+        let errors = check_heap(&view(&heap, 9, Some(10), &[])).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                HeapCheckError::PointerOutOfBounds { location: 4, term: boxed(100) },
+                HeapCheckError::PointerOutOfBounds { location: 10, term: list(9) },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_heap_bad_tags() {
+        let mut heap = valid_heap();
+        heap[1] = boxed(3);
+        heap[4] = list(1);
+        let errors = check_heap(&view(&heap, 9, Some(10), &[])).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                HeapCheckError::BadBoxed { location: 1, term: boxed(3) },
+                HeapCheckError::BadConsCell { location: 4, term: list(1) },
+            ]
+        );
+
+        let mut heap = valid_heap();
+        heap[3] = header(0, REF_SUBTAG);
+        let errors = check_heap(&view(&heap, 9, Some(10), &[])).unwrap_err();
+        assert!(errors.contains(&HeapCheckError::HeaderInTermPosition { location: 3, word: heap[3] }));
+    }
+
+    #[test]
+    fn test_check_heap_bad_header_and_off_heap() {
+        let mut heap = valid_heap();
+        heap[7] = header(5, REFC_BINARY_SUBTAG);
+        let errors = check_heap(&view(&heap, 9, Some(10), &[2, 7, 7])).unwrap_err();
+        assert!(errors.contains(&HeapCheckError::BadHeader { location: 7, header: heap[7] }));
+        assert!(errors.contains(&HeapCheckError::BadOffHeap { location: 2 }));
+
+        let heap = valid_heap();
+        let errors = check_heap(&view(&heap, 9, Some(10), &[7, 7])).unwrap_err();
+        assert_eq!(errors, vec![HeapCheckError::BadOffHeap { location: 7 }]);
+    }
+
+    #[test]
+    fn test_check_heap_bad_bounds() {
+        let heap = valid_heap();
+        let errors = check_heap(&view(&heap, 11, Some(10), &[])).unwrap_err();
+        assert!(matches!(errors[0], HeapCheckError::BadBounds { .. }));
+    }
+
+    #[test]
+    fn test_check_process_heap() {
+        let process = Process::new(40702);
+        assert_eq!(check_process_heap(&process), Ok(()));
+        debug_check_process_heap(&process);
+    }
 }
 
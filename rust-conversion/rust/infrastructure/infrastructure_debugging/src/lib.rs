@@ -17,7 +17,11 @@
 //!   - Term display and formatting (similar to `ptd()` in C)
 //!   - Paranoid display for corrupted data structures
 //!   - Debug state management
+//!   - Heap consistency checking (similar to `erts_check_heap()` in C)
 //!   - Integration with debugging adapters
+//! - **[`crash_dump`](crash_dump/index.html)**: Crash dump writer for fatal errors and
+//!   `erlang:halt/1` slogans, honoring `ERL_CRASH_DUMP`, `ERL_CRASH_DUMP_SECONDS` and
+//!   `ERL_CRASH_DUMP_BYTES`
 //!
 //! ## Architecture
 //!
@@ -30,6 +34,14 @@
 //! - [`usecases_bifs`](../../usecases/usecases_bifs/index.html): Trace BIF implementations
 
 pub mod debug_utils;
+pub mod crash_dump;
 
-pub use debug_utils::{DebugUtils, DebugError};
-
+pub use debug_utils::{
+    DebugUtils, DebugError, HeapCheckError, HeapView, check_heap, check_process_heap,
+    debug_check_process_heap,
+};
+pub use crash_dump::{
+    CrashDumpConfig, CrashDumpError, CrashDumpSection, dump_on_panic, erts_fatal_error,
+    register_crash_dump_section, unregister_crash_dump_section, write_crash_dump,
+    write_crash_dump_to, write_crash_dump_with,
+};
@@ -272,3 +272,27 @@ fn test_display_term_various_types() {
     }
 }
 
+
+#[test]
+fn test_check_heap_finds_corruption() {
+    use infrastructure_debugging::{check_heap, HeapCheckError, HeapView};
+    // [7] stored as a cons cell at 0, referenced from the stack at 3
+    let mut heap: Vec<u64> = vec![(7 << 4) | 0xF, 0x3B, 0, 0x1];
+    let view = |heap: &[u64]| {
+        check_heap(&HeapView {
+            heap,
+            heap_start: 0,
+            heap_top: 2,
+            stack_top: Some(3),
+            off_heap: &[],
+        })
+    };
+    assert_eq!(view(&heap), Ok(()));
+
+    // Point the stack root past the heap top
+    heap[3] = (2 << 2) | 0x1;
+    assert_eq!(
+        view(&heap),
+        Err(vec![HeapCheckError::PointerOutOfBounds { location: 3, term: heap[3] }])
+    );
+}
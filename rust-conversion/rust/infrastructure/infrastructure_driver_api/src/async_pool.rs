@@ -0,0 +1,383 @@
+//! Driver Async Thread Pool
+//!
+//! Provides the async thread pool behind `driver_async`, used by drivers to
+//! run blocking operations (typically file I/O) off the scheduler threads.
+//!
+//! Each pool thread has its own work queue. Jobs started with the same key
+//! go to the same queue and therefore run, and complete, in the order they
+//! were started; drivers use the port key (see [`driver_async_port_key`]) or
+//! a per-fd key so that operations on one file stay ordered. Jobs without a
+//! key are distributed over the queues round-robin.
+//!
+//! Completed jobs are not handed to the driver on the pool thread. They are
+//! queued until a scheduler thread calls [`AsyncPool::deliver_ready`], which
+//! it does on every pass of its scheduling loop, and which invokes the
+//! driver's `ready_async` callback for each of them.
+//!
+//! A pool with no threads (`+A 0`) runs jobs synchronously in
+//! `driver_async` and calls `ready_async` immediately.
+//!
+//! A job that panics has no result, so `ready_async` is not called for it;
+//! the pool thread carries on with the next job in its queue.
+//! Based on erl_async.c
+
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::driver_entry::DriverError;
+use crate::port::DriverPort;
+use crate::port_table::PortTable;
+
+/// Default number of async threads (`+A`)
+pub const DEFAULT_ASYNC_THREADS: usize = 1;
+
+/// Maximum number of async threads (`+A`)
+pub const MAX_ASYNC_THREADS: usize = 1024;
+
+/// Job run on an async thread
+type AsyncInvoke = Box<dyn FnOnce() -> Box<dyn Any + Send> + Send>;
+
+/// Job queued for an async thread
+struct AsyncJob {
+    /// Id returned by `driver_async`
+    id: i64,
+    /// Port that started the job
+    port: u64,
+    /// Work to run
+    invoke: AsyncInvoke,
+}
+
+/// Completed job waiting to be delivered to its port
+pub struct AsyncReady {
+    /// Id returned by `driver_async`
+    pub id: i64,
+    /// Port that started the job
+    pub port: u64,
+    /// Value returned by the job
+    pub data: Box<dyn Any + Send>,
+}
+
+impl std::fmt::Debug for AsyncReady {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AsyncReady")
+            .field("id", &self.id)
+            .field("port", &self.port)
+            .finish()
+    }
+}
+
+/// Completion queue shared with the pool threads
+struct ReadyQueue {
+    /// Completed jobs in completion order
+    jobs: Mutex<VecDeque<AsyncReady>>,
+    /// Signalled when a job completes
+    cond: Condvar,
+}
+
+/// Async thread pool
+pub struct AsyncPool {
+    /// Work queue of each thread
+    queues: Vec<Sender<AsyncJob>>,
+    /// Pool threads
+    threads: Vec<JoinHandle<()>>,
+    /// Completed jobs
+    ready: Arc<ReadyQueue>,
+    /// Next job id
+    next_id: AtomicI64,
+    /// Next queue for jobs without a key
+    next_queue: AtomicUsize,
+}
+
+impl AsyncPool {
+    /// Create a pool with the given number of threads (`+A`)
+    ///
+    /// # Returns
+    /// * `Ok(pool)` - The pool
+    /// * `Err(DriverError::Failed)` - More than [`MAX_ASYNC_THREADS`] threads
+    pub fn new(threads: usize) -> Result<Self, DriverError> {
+        if threads > MAX_ASYNC_THREADS {
+            return Err(DriverError::Failed(format!(
+                "invalid number of async threads: {} (max {})",
+                threads, MAX_ASYNC_THREADS
+            )));
+        }
+        let ready = Arc::new(ReadyQueue {
+            jobs: Mutex::new(VecDeque::new()),
+            cond: Condvar::new(),
+        });
+        let mut queues = Vec::with_capacity(threads);
+        let mut handles = Vec::with_capacity(threads);
+        for index in 0..threads {
+            let (sender, receiver) = mpsc::channel::<AsyncJob>();
+            let ready = ready.clone();
+            let handle = std::thread::Builder::new()
+                .name(format!("async_{}", index + 1))
+                .spawn(move || {
+                    for job in receiver {
+                        let Ok(data) = panic::catch_unwind(AssertUnwindSafe(job.invoke)) else {
+                            continue;
+                        };
+                        ready.jobs.lock().unwrap().push_back(AsyncReady {
+                            id: job.id,
+                            port: job.port,
+                            data,
+                        });
+                        ready.cond.notify_all();
+                    }
+                })
+                .map_err(|err| DriverError::Failed(err.to_string()))?;
+            queues.push(sender);
+            handles.push(handle);
+        }
+        Ok(Self {
+            queues,
+            threads: handles,
+            ready,
+            next_id: AtomicI64::new(1),
+            next_queue: AtomicUsize::new(0),
+        })
+    }
+
+    /// Get the number of threads in the pool
+    pub fn size(&self) -> usize {
+        self.queues.len()
+    }
+
+    /// Run a job on an async thread (`driver_async`)
+    ///
+    /// # Arguments
+    /// * `port` - Port starting the job; its driver's `ready_async` receives the result
+    /// * `key` - Jobs with the same key run in order on the same thread
+    /// * `invoke` - Work to run
+    ///
+    /// # Returns
+    /// * `Ok(id)` - Id of the job, passed to `ready_async`; 0 if the job
+    ///   already completed synchronously because the pool has no threads
+    /// * `Err(DriverError::Failed)` - The job panicked while running
+    ///   synchronously, or its pool thread is gone
+    pub fn driver_async<F, R>(&self, port: &DriverPort, key: Option<u32>, invoke: F) -> Result<i64, DriverError>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Any + Send,
+    {
+        if self.queues.is_empty() {
+            let data = panic::catch_unwind(AssertUnwindSafe(invoke))
+                .map_err(|_| DriverError::Failed("async job panicked".to_string()))?;
+            port.ready_async(0, Box::new(data));
+            return Ok(0);
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let index = match key {
+            Some(key) => key as usize % self.queues.len(),
+            None => self.next_queue.fetch_add(1, Ordering::Relaxed) % self.queues.len(),
+        };
+        let job = AsyncJob {
+            id,
+            port: port.id(),
+            invoke: Box::new(move || Box::new(invoke()) as Box<dyn Any + Send>),
+        };
+        self.queues[index]
+            .send(job)
+            .map_err(|_| DriverError::Failed(format!("async thread {} exited", index + 1)))?;
+        Ok(id)
+    }
+
+    /// Get the number of completed jobs waiting to be delivered
+    pub fn ready_count(&self) -> usize {
+        self.ready.jobs.lock().unwrap().len()
+    }
+
+    /// Wait until at least `count` completed jobs are waiting to be delivered
+    ///
+    /// # Returns
+    /// `true` if `count` jobs are waiting, `false` on timeout
+    pub fn wait_ready(&self, count: usize, timeout: Duration) -> bool {
+        let jobs = self.ready.jobs.lock().unwrap();
+        let (jobs, _) = self
+            .ready
+            .cond
+            .wait_timeout_while(jobs, timeout, |jobs| jobs.len() < count)
+            .unwrap();
+        jobs.len() >= count
+    }
+
+    /// Take the completed jobs, in completion order
+    pub fn take_ready(&self) -> Vec<AsyncReady> {
+        self.ready.jobs.lock().unwrap().drain(..).collect()
+    }
+
+    /// Deliver completed jobs to their drivers' `ready_async` callbacks
+    ///
+    /// Called from the scheduler threads. Results for ports that have been
+    /// closed are dropped.
+    ///
+    /// # Returns
+    /// Number of results delivered
+    pub fn deliver_ready(&self, ports: &PortTable) -> usize {
+        let mut delivered = 0;
+        for ready in self.take_ready() {
+            if let Some(port) = ports.lookup(ready.port) {
+                if port.ready_async(ready.id, ready.data) {
+                    delivered += 1;
+                }
+            }
+        }
+        delivered
+    }
+}
+
+impl Drop for AsyncPool {
+    fn drop(&mut self) {
+        self.queues.clear();
+        for handle in self.threads.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Key keeping the async jobs of a port ordered (`driver_async_port_key`)
+pub fn driver_async_port_key(port: &DriverPort) -> u32 {
+    port.id() as u32
+}
+
+/// Global async pool instance
+static GLOBAL_ASYNC_POOL: OnceLock<AsyncPool> = OnceLock::new();
+
+/// Initialize the global async pool with the given number of threads (`+A`)
+///
+/// # Returns
+/// * `Ok(pool)` - The global pool
+/// * `Err(DriverError::Failed)` - Invalid size, or the pool is already initialized
+pub fn init_global_async_pool(threads: usize) -> Result<&'static AsyncPool, DriverError> {
+    let pool = AsyncPool::new(threads)?;
+    GLOBAL_ASYNC_POOL
+        .set(pool)
+        .map_err(|_| DriverError::Failed("async pool already initialized".to_string()))?;
+    Ok(get_global_async_pool())
+}
+
+/// Get the global async pool, creating it with [`DEFAULT_ASYNC_THREADS`] if needed
+pub fn get_global_async_pool() -> &'static AsyncPool {
+    GLOBAL_ASYNC_POOL.get_or_init(|| AsyncPool::new(DEFAULT_ASYNC_THREADS).expect("default async pool"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver_entry::DriverEntry;
+    use crate::port_table::DriverRegistry;
+
+    /// Driver recording `ready_async` results
+    struct AsyncDriver {
+        results: Mutex<Vec<(i64, u32)>>,
+    }
+
+    impl DriverEntry for AsyncDriver {
+        fn driver_name(&self) -> &str {
+            "async_drv"
+        }
+
+        fn output(&self, _port: &DriverPort, _data: &[u8]) -> Result<(), DriverError> {
+            Ok(())
+        }
+
+        fn ready_async(&self, _port: &DriverPort, async_id: i64, data: Box<dyn Any + Send>) {
+            let value = *data.downcast::<u32>().unwrap();
+            self.results.lock().unwrap().push((async_id, value));
+        }
+    }
+
+    fn open_port() -> (Arc<AsyncDriver>, PortTable, Arc<DriverPort>) {
+        let driver = Arc::new(AsyncDriver {
+            results: Mutex::new(Vec::new()),
+        });
+        let drivers = DriverRegistry::new();
+        drivers.add_driver_entry(driver.clone()).unwrap();
+        let ports = PortTable::new();
+        let port = ports.open_port(&drivers, "async_drv").unwrap();
+        (driver, ports, port)
+    }
+
+    #[test]
+    fn test_pool_size_limits() {
+        assert_eq!(AsyncPool::new(4).unwrap().size(), 4);
+        assert_eq!(AsyncPool::new(0).unwrap().size(), 0);
+        assert!(AsyncPool::new(MAX_ASYNC_THREADS + 1).is_err());
+    }
+
+    #[test]
+    fn test_keyed_jobs_complete_in_order() {
+        let (_driver, _ports, port) = open_port();
+        let pool = AsyncPool::new(4).unwrap();
+        let key = driver_async_port_key(&port);
+        let ids: Vec<i64> = (0..20u32)
+            .map(|n| {
+                pool.driver_async(&port, Some(key), move || {
+                    std::thread::sleep(Duration::from_millis(((20 - n) % 3) as u64));
+                    n
+                })
+                .unwrap()
+            })
+            .collect();
+        assert!(pool.wait_ready(20, Duration::from_secs(5)));
+        let ready = pool.take_ready();
+        assert_eq!(ready.iter().map(|r| r.id).collect::<Vec<_>>(), ids);
+        assert!(ready.iter().all(|r| r.port == port.id()));
+    }
+
+    #[test]
+    fn test_deliver_ready_calls_driver() {
+        let (driver, ports, port) = open_port();
+        let pool = AsyncPool::new(2).unwrap();
+        let id = pool.driver_async(&port, None, || 7u32).unwrap();
+        assert!(id > 0);
+        assert!(pool.wait_ready(1, Duration::from_secs(5)));
+        assert!(driver.results.lock().unwrap().is_empty());
+
+        assert_eq!(pool.deliver_ready(&ports), 1);
+        assert_eq!(*driver.results.lock().unwrap(), vec![(id, 7)]);
+        assert_eq!(pool.ready_count(), 0);
+    }
+
+    #[test]
+    fn test_results_for_closed_port_dropped() {
+        let (driver, ports, port) = open_port();
+        let pool = AsyncPool::new(1).unwrap();
+        pool.driver_async(&port, None, || 1u32).unwrap();
+        assert!(pool.wait_ready(1, Duration::from_secs(5)));
+        ports.close_port(port.id());
+
+        assert_eq!(pool.deliver_ready(&ports), 0);
+        assert!(driver.results.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_no_threads_runs_synchronously() {
+        let (driver, _ports, port) = open_port();
+        let pool = AsyncPool::new(0).unwrap();
+        assert_eq!(pool.driver_async(&port, Some(3), || 42u32), Ok(0));
+        assert_eq!(*driver.results.lock().unwrap(), vec![(0, 42)]);
+        assert_eq!(pool.ready_count(), 0);
+    }
+
+    #[test]
+    fn test_panicking_job_keeps_thread() {
+        let (driver, ports, port) = open_port();
+        let pool = AsyncPool::new(1).unwrap();
+        pool.driver_async(&port, None, || -> u32 { panic!("job failed") }).unwrap();
+        let id = pool.driver_async(&port, None, || 5u32).unwrap();
+        assert!(pool.wait_ready(1, Duration::from_secs(5)));
+
+        assert_eq!(pool.deliver_ready(&ports), 1);
+        assert_eq!(*driver.results.lock().unwrap(), vec![(id, 5)]);
+
+        let sync = AsyncPool::new(0).unwrap();
+        assert!(sync.driver_async(&port, None, || -> u32 { panic!("job failed") }).is_err());
+    }
+}
@@ -0,0 +1,132 @@
+//! Driver Entry
+//!
+//! Provides the driver entry interface implemented by port drivers, the
+//! Rust equivalent of `ErlDrvEntry`. The runtime calls a driver through its
+//! entry when a port is opened, sent data, controlled, called or closed.
+//! Based on erl_driver.h
+
+use std::any::Any;
+
+use crate::port::DriverPort;
+
+/// Port driver
+///
+/// Callbacks not implemented by a driver default to reporting
+/// [`DriverError::NotSupported`], which `erlang:port_control/3` and
+/// `erlang:port_call/3` turn into `badarg`, as for a NULL callback in
+/// `ErlDrvEntry`.
+pub trait DriverEntry: Send + Sync {
+    /// Name of the driver, as used in `open_port({spawn_driver, Name}, _)`
+    fn driver_name(&self) -> &str;
+
+    /// Called when a port is opened on the driver (`start`)
+    ///
+    /// # Arguments
+    /// * `port` - The new port
+    /// * `command` - Command string passed to `open_port/2`
+    fn start(&self, _port: &DriverPort, _command: &str) -> Result<(), DriverError> {
+        Ok(())
+    }
+
+    /// Called with data sent to the port by `port_command` (`output`)
+    fn output(&self, port: &DriverPort, data: &[u8]) -> Result<(), DriverError>;
+
+    /// Called with the segments of an iolist sent to the port (`outputv`)
+    ///
+    /// The default joins the segments and calls [`DriverEntry::output`].
+    fn outputv(&self, port: &DriverPort, segments: &[&[u8]]) -> Result<(), DriverError> {
+        self.output(port, &segments.concat())
+    }
+
+    /// Called by `erlang:port_control/3` (`control`)
+    ///
+    /// # Returns
+    /// * `Ok(reply)` - Reply data returned to the caller
+    fn control(&self, _port: &DriverPort, _command: u32, _data: &[u8]) -> Result<Vec<u8>, DriverError> {
+        Err(DriverError::NotSupported)
+    }
+
+    /// Called by `erlang:port_call/3` (`call`)
+    ///
+    /// # Returns
+    /// * `Ok(reply)` - Reply data returned to the caller
+    fn call(&self, _port: &DriverPort, _command: u32, _data: &[u8]) -> Result<Vec<u8>, DriverError> {
+        Err(DriverError::NotSupported)
+    }
+
+    /// Called when the port is closed (`stop`)
+    fn stop(&self, _port: &DriverPort) {}
+
+    /// Called when an fd selected with [`DriverSelectFlags::Read`] is
+    /// readable (`ready_input`)
+    ///
+    /// # Arguments
+    /// * `port` - Port that selected the fd
+    /// * `event` - The readable fd
+    ///
+    /// [`DriverSelectFlags::Read`]: crate::driver_select::DriverSelectFlags::Read
+    fn ready_input(&self, _port: &DriverPort, _event: i32) {}
+
+    /// Called on the port's scheduler thread when a job started with
+    /// `driver_async` completes (`ready_async`)
+    ///
+    /// # Arguments
+    /// * `port` - Port that started the job
+    /// * `async_id` - Id returned by `driver_async`
+    /// * `data` - Value returned by the job
+    fn ready_async(&self, _port: &DriverPort, _async_id: i64, _data: Box<dyn Any + Send>) {}
+
+    /// Called when an fd deselected with `ERL_DRV_USE` is no longer in any
+    /// pollset and may be closed (`stop_select`)
+    ///
+    /// The driver closes the fd here rather than when deselecting it, so the
+    /// fd number cannot be reused while a poll thread still watches it.
+    ///
+    /// # Arguments
+    /// * `event` - The deselected fd
+    fn stop_select(&self, _event: i32) {}
+}
+
+/// Driver API errors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DriverError {
+    /// The driver does not implement the callback
+    NotSupported,
+    /// No driver with this name is loaded
+    DriverNotFound(String),
+    /// A driver with this name is already loaded
+    AlreadyLoaded(String),
+    /// The port does not exist or has been closed
+    PortClosed,
+    /// The driver rejected the request
+    Failed(String),
+    /// The port table is full
+    SystemLimit,
+}
+
+impl std::fmt::Display for DriverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DriverError::NotSupported => write!(f, "Operation not supported by driver"),
+            DriverError::DriverNotFound(name) => write!(f, "Driver not found: {}", name),
+            DriverError::AlreadyLoaded(name) => write!(f, "Driver already loaded: {}", name),
+            DriverError::PortClosed => write!(f, "Port closed"),
+            DriverError::Failed(msg) => write!(f, "Driver failed: {}", msg),
+            DriverError::SystemLimit => write!(f, "Port limit reached"),
+        }
+    }
+}
+
+impl std::error::Error for DriverError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_driver_error_display() {
+        assert!(DriverError::NotSupported.to_string().contains("not supported"));
+        assert!(DriverError::DriverNotFound("efile".to_string()).to_string().contains("efile"));
+        assert!(DriverError::Failed("bad".to_string()).to_string().contains("bad"));
+    }
+}
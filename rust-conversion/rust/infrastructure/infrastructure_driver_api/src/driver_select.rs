@@ -0,0 +1,494 @@
+//! Driver Select
+//!
+//! Provides `driver_select`, through which drivers ask to be notified when an
+//! fd becomes readable or writable, and the `stop_select` protocol for
+//! closing such fds safely.
+//!
+//! A driver must not close an fd it has selected while the fd may still be in
+//! a pollset: the fd number could be reused by a newly opened file and the
+//! poll thread would then report events for the wrong file. Instead the
+//! driver deselects the fd with [`DriverSelectFlags::Use`] and closes it in
+//! its `stop_select` callback. If the fd is not in a pollset, `stop_select`
+//! is called immediately. Otherwise the fd is queued for removal from the
+//! pollset and `stop_select` is called once the poll backend confirms the
+//! removal through [`SelectTable::pollset_removed`].
+//!
+//! The poll backend registers a notifier with
+//! [`SelectTable::set_pollset_notifier`] and applies the changes returned by
+//! [`SelectTable::take_pollset_updates`] whenever it is notified. When a
+//! selected fd becomes readable, [`SelectTable::input_ready`] names the port
+//! to run `ready_input` for; the fd is not polled for input again until
+//! [`SelectTable::input_handled`] re-arms it after the callback has run.
+//! Based on erl_check_io.c
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+
+use crate::driver_entry::{DriverEntry, DriverError};
+use crate::port::DriverPort;
+
+/// Select mode flags for `driver_select`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum DriverSelectFlags {
+    /// Notify when the fd is readable (`ERL_DRV_READ`)
+    Read = 1 << 0,
+    /// Notify when the fd is writable (`ERL_DRV_WRITE`)
+    Write = 1 << 1,
+    /// The driver uses the fd; deselecting it schedules `stop_select` (`ERL_DRV_USE`)
+    Use = 1 << 2,
+}
+
+impl DriverSelectFlags {
+    /// Check if a mode contains this flag
+    pub fn is_set(self, mode: u32) -> bool {
+        mode & self as u32 != 0
+    }
+}
+
+/// Outcome of a `driver_select` call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriverSelectResult {
+    /// The selected events were updated
+    Updated,
+    /// The fd was not in a pollset and `stop_select` has been called
+    StopCalled,
+    /// `stop_select` will be called once the fd has left the pollset
+    StopScheduled,
+}
+
+/// Change the poll backend must apply to its pollset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollsetUpdate {
+    /// The fd
+    pub fd: i32,
+    /// Events to poll for; 0 means remove the fd from the pollset
+    pub events: u32,
+}
+
+/// Select state of an fd
+struct SelectState {
+    /// Port that selected the fd
+    port: u64,
+    /// Driver called on `stop_select`
+    driver: Arc<dyn DriverEntry>,
+    /// Selected `Read`/`Write` events
+    events: u32,
+    /// The driver has set `Use` on the fd
+    used: bool,
+    /// The fd may be in the pollset, pending confirmation of its removal
+    in_pollset: bool,
+    /// `stop_select` is due once the fd leaves the pollset
+    stop_scheduled: bool,
+    /// `ready_input` is pending; input is not polled until it has run
+    input_pending: bool,
+}
+
+impl SelectState {
+    /// Events the pollset must report: input is masked while `ready_input` is pending
+    fn polled_events(&self) -> u32 {
+        if self.input_pending {
+            self.events & !(DriverSelectFlags::Read as u32)
+        } else {
+            self.events
+        }
+    }
+}
+
+/// Mutable table state
+#[derive(Default)]
+struct SelectInner {
+    /// Select state by fd
+    fds: HashMap<i32, SelectState>,
+    /// Pollset changes not yet taken by the poll backend
+    updates: Vec<PollsetUpdate>,
+}
+
+/// Callback telling the poll backend that pollset updates are pending
+pub type PollsetNotifier = Arc<dyn Fn() + Send + Sync>;
+
+/// Table of fds selected by drivers
+pub struct SelectTable {
+    inner: Mutex<SelectInner>,
+    notifier: RwLock<Option<PollsetNotifier>>,
+}
+
+impl SelectTable {
+    /// Create an empty select table
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(SelectInner::default()),
+            notifier: RwLock::new(None),
+        }
+    }
+
+    /// Register the poll backend's notifier, called whenever pollset
+    /// updates are queued
+    pub fn set_pollset_notifier(&self, notifier: PollsetNotifier) {
+        *self.notifier.write().unwrap() = Some(notifier);
+    }
+
+    /// Select or deselect events on an fd for a port (`driver_select`)
+    ///
+    /// # Arguments
+    /// * `port` - Port selecting the fd
+    /// * `event` - The fd
+    /// * `mode` - Combination of [`DriverSelectFlags`]
+    /// * `on` - Select (`true`) or deselect (`false`) the events in `mode`
+    ///
+    /// # Returns
+    /// * `Ok(result)` - How the request was handled
+    /// * `Err(DriverError::Failed)` - Invalid fd, or the fd is selected by another port
+    pub fn driver_select(
+        &self,
+        port: &DriverPort,
+        event: i32,
+        mode: u32,
+        on: bool,
+    ) -> Result<DriverSelectResult, DriverError> {
+        let result = self.select(port, event, mode, on);
+        self.notify_pollset();
+        result
+    }
+
+    fn select(
+        &self,
+        port: &DriverPort,
+        event: i32,
+        mode: u32,
+        on: bool,
+    ) -> Result<DriverSelectResult, DriverError> {
+        if event < 0 {
+            return Err(DriverError::Failed(format!("invalid fd: {}", event)));
+        }
+        let io_events = mode & (DriverSelectFlags::Read as u32 | DriverSelectFlags::Write as u32);
+        let mut inner = self.inner.lock().unwrap();
+
+        if on {
+            let state = inner.fds.entry(event).or_insert_with(|| SelectState {
+                port: port.id(),
+                driver: port.driver().clone(),
+                events: 0,
+                used: false,
+                in_pollset: false,
+                stop_scheduled: false,
+                input_pending: false,
+            });
+            if state.port != port.id() {
+                return Err(DriverError::Failed(format!(
+                    "fd {} is selected by port {}",
+                    event, state.port
+                )));
+            }
+            if state.stop_scheduled {
+                return Err(DriverError::Failed(format!("fd {} is being stopped", event)));
+            }
+            let events = state.events | io_events;
+            state.used |= DriverSelectFlags::Use.is_set(mode);
+            let changed = events != state.events;
+            state.events = events;
+            if events != 0 {
+                state.in_pollset = true;
+            }
+            let polled = state.polled_events();
+            if changed {
+                inner.updates.push(PollsetUpdate { fd: event, events: polled });
+            }
+            return Ok(DriverSelectResult::Updated);
+        }
+
+        let Some(state) = inner.fds.get_mut(&event) else {
+            return Ok(DriverSelectResult::Updated);
+        };
+        if state.port != port.id() {
+            return Err(DriverError::Failed(format!(
+                "fd {} is selected by port {}",
+                event, state.port
+            )));
+        }
+        let stop = DriverSelectFlags::Use.is_set(mode) && state.used;
+        let events = if stop { 0 } else { state.events & !io_events };
+        let changed = events != state.events;
+        state.events = events;
+        if stop {
+            state.used = false;
+        }
+        let polled = state.polled_events();
+        if changed {
+            inner.updates.push(PollsetUpdate { fd: event, events: polled });
+        }
+        if !stop {
+            return Ok(DriverSelectResult::Updated);
+        }
+        let state = inner.fds.get_mut(&event).unwrap();
+        if state.in_pollset {
+            state.stop_scheduled = true;
+            return Ok(DriverSelectResult::StopScheduled);
+        }
+        let state = inner.fds.remove(&event).unwrap();
+        drop(inner);
+        state.driver.stop_select(event);
+        Ok(DriverSelectResult::StopCalled)
+    }
+
+    /// Take the pollset changes the poll backend must apply, in order
+    pub fn take_pollset_updates(&self) -> Vec<PollsetUpdate> {
+        std::mem::take(&mut self.inner.lock().unwrap().updates)
+    }
+
+    /// Confirm that the poll backend has removed an fd from its pollset
+    ///
+    /// Calls the driver's `stop_select` if it was scheduled for the fd.
+    ///
+    /// # Returns
+    /// `true` if `stop_select` was called
+    pub fn pollset_removed(&self, event: i32) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(state) = inner.fds.get_mut(&event) else {
+            return false;
+        };
+        if state.events != 0 {
+            // Selected again before the removal was confirmed
+            return false;
+        }
+        state.in_pollset = false;
+        if !state.stop_scheduled {
+            if !state.used {
+                inner.fds.remove(&event);
+            }
+            return false;
+        }
+        let state = inner.fds.remove(&event).unwrap();
+        drop(inner);
+        state.driver.stop_select(event);
+        true
+    }
+
+    /// Report that a selected fd is readable
+    ///
+    /// # Returns
+    /// * `Some(port)` - Port whose `ready_input` must run; input on the fd is
+    ///   not reported again until [`input_handled`](Self::input_handled)
+    /// * `None` - The fd is not selected for input, is being stopped, or
+    ///   `ready_input` is already pending
+    pub fn input_ready(&self, event: i32) -> Option<u64> {
+        let mut inner = self.inner.lock().unwrap();
+        let state = inner.fds.get_mut(&event)?;
+        if !DriverSelectFlags::Read.is_set(state.events) || state.stop_scheduled || state.input_pending {
+            return None;
+        }
+        state.input_pending = true;
+        Some(state.port)
+    }
+
+    /// Re-arm input polling of an fd once its `ready_input` has run
+    pub fn input_handled(&self, event: i32) {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            let Some(state) = inner.fds.get_mut(&event) else {
+                return;
+            };
+            if !state.input_pending {
+                return;
+            }
+            state.input_pending = false;
+            let events = state.events;
+            if events == 0 {
+                return;
+            }
+            inner.updates.push(PollsetUpdate { fd: event, events });
+        }
+        self.notify_pollset();
+    }
+
+    /// Call the poll backend's notifier if updates are pending
+    fn notify_pollset(&self) {
+        if self.inner.lock().unwrap().updates.is_empty() {
+            return;
+        }
+        if let Some(notifier) = self.notifier.read().unwrap().clone() {
+            notifier();
+        }
+    }
+
+    /// Deselect all fds of a closed port, as if each had been deselected
+    /// with all flags
+    ///
+    /// # Returns
+    /// Number of fds whose `stop_select` was called immediately
+    pub fn port_closed(&self, port: &DriverPort) -> usize {
+        let fds: Vec<i32> = {
+            let inner = self.inner.lock().unwrap();
+            inner
+                .fds
+                .iter()
+                .filter(|(_, state)| state.port == port.id() && !state.stop_scheduled)
+                .map(|(fd, _)| *fd)
+                .collect()
+        };
+        let all = DriverSelectFlags::Read as u32 | DriverSelectFlags::Write as u32 | DriverSelectFlags::Use as u32;
+        fds.into_iter()
+            .filter(|fd| self.driver_select(port, *fd, all, false) == Ok(DriverSelectResult::StopCalled))
+            .count()
+    }
+
+    /// Get the selected events of an fd
+    pub fn selected_events(&self, event: i32) -> Option<u32> {
+        self.inner.lock().unwrap().fds.get(&event).map(|state| state.events)
+    }
+
+    /// Check if `stop_select` is pending for an fd
+    pub fn is_stop_scheduled(&self, event: i32) -> bool {
+        self.inner
+            .lock()
+            .unwrap()
+            .fds
+            .get(&event)
+            .is_some_and(|state| state.stop_scheduled)
+    }
+}
+
+impl Default for SelectTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Global select table instance
+static GLOBAL_SELECT_TABLE: OnceLock<SelectTable> = OnceLock::new();
+
+/// Get the global select table
+pub fn get_global_select_table() -> &'static SelectTable {
+    GLOBAL_SELECT_TABLE.get_or_init(SelectTable::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::port_table::{DriverRegistry, PortTable};
+
+    /// Driver recording `stop_select` calls
+    struct SelectDriver {
+        stopped: Mutex<Vec<i32>>,
+    }
+
+    impl DriverEntry for SelectDriver {
+        fn driver_name(&self) -> &str {
+            "select_drv"
+        }
+
+        fn output(&self, _port: &DriverPort, _data: &[u8]) -> Result<(), DriverError> {
+            Ok(())
+        }
+
+        fn stop_select(&self, event: i32) {
+            self.stopped.lock().unwrap().push(event);
+        }
+    }
+
+    fn open_port(ports: &PortTable) -> (Arc<SelectDriver>, Arc<DriverPort>) {
+        let driver = Arc::new(SelectDriver {
+            stopped: Mutex::new(Vec::new()),
+        });
+        let drivers = DriverRegistry::new();
+        drivers.add_driver_entry(driver.clone()).unwrap();
+        let port = ports.open_port(&drivers, "select_drv").unwrap();
+        (driver, port)
+    }
+
+    const READ_USE: u32 = DriverSelectFlags::Read as u32 | DriverSelectFlags::Use as u32;
+
+    #[test]
+    fn test_stop_deferred_until_pollset_removal() {
+        let ports = PortTable::new();
+        let (driver, port) = open_port(&ports);
+        let table = SelectTable::new();
+
+        assert_eq!(table.driver_select(&port, 7, READ_USE, true), Ok(DriverSelectResult::Updated));
+        assert_eq!(table.take_pollset_updates(), vec![PollsetUpdate { fd: 7, events: 1 }]);
+
+        assert_eq!(
+            table.driver_select(&port, 7, READ_USE, false),
+            Ok(DriverSelectResult::StopScheduled)
+        );
+        assert!(table.is_stop_scheduled(7));
+        assert!(driver.stopped.lock().unwrap().is_empty());
+        assert_eq!(table.take_pollset_updates(), vec![PollsetUpdate { fd: 7, events: 0 }]);
+
+        assert!(table.pollset_removed(7));
+        assert_eq!(*driver.stopped.lock().unwrap(), vec![7]);
+        assert_eq!(table.selected_events(7), None);
+    }
+
+    #[test]
+    fn test_input_masked_until_handled() {
+        let ports = PortTable::new();
+        let (_driver, port) = open_port(&ports);
+        let table = SelectTable::new();
+        let notified = Arc::new(Mutex::new(0));
+        let counter = notified.clone();
+        table.set_pollset_notifier(Arc::new(move || *counter.lock().unwrap() += 1));
+
+        table.driver_select(&port, 9, READ_USE, true).unwrap();
+        assert_eq!(*notified.lock().unwrap(), 1);
+        table.take_pollset_updates();
+
+        assert_eq!(table.input_ready(9), Some(port.id()));
+        assert_eq!(table.input_ready(9), None);
+        table.driver_select(&port, 9, DriverSelectFlags::Write as u32, true).unwrap();
+        assert_eq!(table.take_pollset_updates(), vec![PollsetUpdate { fd: 9, events: 2 }]);
+
+        table.input_handled(9);
+        assert_eq!(*notified.lock().unwrap(), 3);
+        assert_eq!(table.take_pollset_updates(), vec![PollsetUpdate { fd: 9, events: 3 }]);
+        assert_eq!(table.input_ready(9), Some(port.id()));
+        assert_eq!(table.input_ready(10), None);
+    }
+
+    #[test]
+    fn test_stop_called_when_not_in_pollset() {
+        let ports = PortTable::new();
+        let (driver, port) = open_port(&ports);
+        let table = SelectTable::new();
+
+        table.driver_select(&port, 8, READ_USE, true).unwrap();
+        table.driver_select(&port, 8, DriverSelectFlags::Read as u32, false).unwrap();
+        assert!(!table.pollset_removed(8));
+        assert_eq!(table.selected_events(8), Some(0));
+
+        assert_eq!(
+            table.driver_select(&port, 8, DriverSelectFlags::Use as u32, false),
+            Ok(DriverSelectResult::StopCalled)
+        );
+        assert_eq!(*driver.stopped.lock().unwrap(), vec![8]);
+    }
+
+    #[test]
+    fn test_fd_owned_by_one_port() {
+        let ports = PortTable::new();
+        let (_driver, port_a) = open_port(&ports);
+        let (_driver, port_b) = open_port(&ports);
+        let table = SelectTable::new();
+
+        table.driver_select(&port_a, 9, READ_USE, true).unwrap();
+        assert!(table.driver_select(&port_b, 9, READ_USE, true).is_err());
+        table.driver_select(&port_a, 9, READ_USE, false).unwrap();
+        assert!(table.driver_select(&port_a, 9, READ_USE, true).is_err());
+        assert!(table.driver_select(&port_a, -1, READ_USE, true).is_err());
+    }
+
+    #[test]
+    fn test_port_closed_stops_fds() {
+        let ports = PortTable::new();
+        let (driver, port) = open_port(&ports);
+        let table = SelectTable::new();
+
+        table.driver_select(&port, 10, READ_USE, true).unwrap();
+        table.driver_select(&port, 11, DriverSelectFlags::Use as u32, true).unwrap();
+        assert_eq!(table.port_closed(&port), 1);
+        assert_eq!(*driver.stopped.lock().unwrap(), vec![11]);
+        assert!(table.is_stop_scheduled(10));
+        assert!(table.pollset_removed(10));
+        assert_eq!(*driver.stopped.lock().unwrap(), vec![11, 10]);
+    }
+}
@@ -0,0 +1,61 @@
+//! Infrastructure Driver API
+//!
+//! Provides the Rust driver API - equivalent to the C `erl_driver.h` API but
+//! implemented in pure Rust.
+//!
+//! ## Overview
+//!
+//! Port drivers implement [`DriverEntry`] and are loaded into a
+//! [`DriverRegistry`]. Ports opened on a driver are kept in a [`PortTable`]
+//! and reached by the port BIFs (`port_command`, `port_control`, `port_call`)
+//! through their port number.
+//!
+//! Blocking driver work is run on the [`AsyncPool`] with `driver_async`;
+//! results are delivered back to the driver's `ready_async` callback on the
+//! port scheduler thread.
+//!
+//! ## Modules
+//!
+//! - **[`async_pool`](async_pool/index.html)**: Async thread pool
+//!   (`driver_async`) with keyed work queues
+//!
+//! - **[`driver_entry`](driver_entry/index.html)**: Driver entry callbacks
+//!   (`ErlDrvEntry`) and driver API errors
+//!
+//! - **[`driver_select`](driver_select/index.html)**: fd selection
+//!   (`driver_select`) and deferred closing through `stop_select`
+//!
+//! - **[`port`](port/index.html)**: Driver ports with the driver queue and
+//!   busy port state used for sender backpressure
+//!
+//! - **[`port_table`](port_table/index.html)**: Tables of loaded drivers and
+//!   open ports, with port number allocation and slot reuse
+//!
+//! ## See Also
+//!
+//! - [`usecases_bifs`](../../usecases/usecases_bifs/index.html): Port BIFs
+//! - [`infrastructure_nif_api`](../infrastructure_nif_api/index.html): NIF API
+//! - `erts/emulator/beam/erl_driver.h` - C header
+//! - `erts/emulator/beam/io.c` - C reference implementation
+//! - `erts/emulator/beam/erl_async.c` - C async thread pool
+//! - `erts/emulator/sys/common/erl_check_io.c` - C driver_select implementation
+
+pub mod async_pool;
+pub mod driver_entry;
+pub mod driver_select;
+pub mod port;
+pub mod port_table;
+
+pub use async_pool::{
+    AsyncPool, AsyncReady, DEFAULT_ASYNC_THREADS, MAX_ASYNC_THREADS, driver_async_port_key,
+    get_global_async_pool, init_global_async_pool,
+};
+pub use driver_entry::{DriverEntry, DriverError};
+pub use driver_select::{
+    DriverSelectFlags, DriverSelectResult, PollsetNotifier, PollsetUpdate, SelectTable, get_global_select_table,
+};
+pub use port::{DriverPort, DEFAULT_QUEUE_HIGH_WATERMARK, DEFAULT_QUEUE_LOW_WATERMARK};
+pub use port_table::{
+    make_port_id, port_serial, port_slot, DriverRegistry, PortTable, DEFAULT_PORT_LIMIT, PORT_SLOT_BITS,
+    get_global_driver_registry, get_global_port_table,
+};
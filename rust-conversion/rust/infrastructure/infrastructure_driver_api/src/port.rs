@@ -0,0 +1,373 @@
+//! Driver Ports
+//!
+//! Provides the port side of the driver API: the driver queue
+//! (`driver_enq`, `driver_deq`, `driver_sizeq`, `driver_peekq`) and busy
+//! port state (`set_busy_port`).
+//!
+//! A port becomes busy when its driver queue reaches the high watermark, or
+//! when the driver marks it busy. Processes sending to a busy port are
+//! suspended on the port and resumed once the port stops being busy, which
+//! happens when `driver_deq` drains the queue to the low watermark or the
+//! driver clears the busy state.
+//! Based on erl_driver.h and io.c
+
+use std::any::Any;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use crate::driver_entry::{DriverEntry, DriverError};
+use crate::driver_select::get_global_select_table;
+
+/// Driver queue size at which the port becomes busy
+pub const DEFAULT_QUEUE_HIGH_WATERMARK: usize = 8 * 1024;
+
+/// Driver queue size at which a busy port stops being busy
+pub const DEFAULT_QUEUE_LOW_WATERMARK: usize = 4 * 1024;
+
+/// Port opened on a driver
+pub struct DriverPort {
+    /// Port number
+    id: u64,
+    /// Command string the port was opened with
+    name: String,
+    /// Driver handling the port
+    driver: Arc<dyn DriverEntry>,
+    /// Queue and busy state
+    state: Mutex<PortState>,
+}
+
+/// Mutable port state
+struct PortState {
+    /// Driver queue
+    queue: VecDeque<u8>,
+    /// Port is busy
+    busy: bool,
+    /// Queue size at which the port becomes busy
+    high_watermark: usize,
+    /// Queue size at which the port stops being busy
+    low_watermark: usize,
+    /// Processes suspended on the busy port
+    suspended: Vec<u64>,
+    /// Processes to resume since the port stopped being busy
+    resumed: Vec<u64>,
+    /// Port has been closed
+    closed: bool,
+}
+
+impl PortState {
+    /// Clear the busy state, moving suspended processes to the resume list
+    fn clear_busy(&mut self) {
+        self.busy = false;
+        let suspended = std::mem::take(&mut self.suspended);
+        self.resumed.extend(suspended);
+    }
+}
+
+impl DriverPort {
+    /// Create a port on a driver
+    pub(crate) fn new(id: u64, name: &str, driver: Arc<dyn DriverEntry>) -> Self {
+        Self {
+            id,
+            name: name.to_string(),
+            driver,
+            state: Mutex::new(PortState {
+                queue: VecDeque::new(),
+                busy: false,
+                high_watermark: DEFAULT_QUEUE_HIGH_WATERMARK,
+                low_watermark: DEFAULT_QUEUE_LOW_WATERMARK,
+                suspended: Vec::new(),
+                resumed: Vec::new(),
+                closed: false,
+            }),
+        }
+    }
+
+    /// Get the port number
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Get the command string the port was opened with
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the name of the driver handling the port
+    pub fn driver_name(&self) -> &str {
+        self.driver.driver_name()
+    }
+
+    /// Get the driver handling the port
+    pub(crate) fn driver(&self) -> &Arc<dyn DriverEntry> {
+        &self.driver
+    }
+
+    /// Check if the port has been closed
+    pub fn is_closed(&self) -> bool {
+        self.state.lock().unwrap().closed
+    }
+
+    /// Send data to the driver (`output` callback)
+    pub fn output(&self, data: &[u8]) -> Result<(), DriverError> {
+        self.ensure_open()?;
+        self.driver.output(self, data)
+    }
+
+    /// Send the segments of an iolist to the driver (`outputv` callback)
+    pub fn outputv(&self, segments: &[&[u8]]) -> Result<(), DriverError> {
+        self.ensure_open()?;
+        self.driver.outputv(self, segments)
+    }
+
+    /// Perform a synchronous control operation (`control` callback)
+    pub fn control(&self, command: u32, data: &[u8]) -> Result<Vec<u8>, DriverError> {
+        self.ensure_open()?;
+        self.driver.control(self, command, data)
+    }
+
+    /// Perform a synchronous call (`call` callback)
+    pub fn call(&self, command: u32, data: &[u8]) -> Result<Vec<u8>, DriverError> {
+        self.ensure_open()?;
+        self.driver.call(self, command, data)
+    }
+
+    /// Hand a completed async job to the driver (`ready_async` callback)
+    ///
+    /// # Returns
+    /// `false` if the port has been closed and the result was dropped
+    pub(crate) fn ready_async(&self, async_id: i64, data: Box<dyn Any + Send>) -> bool {
+        if self.is_closed() {
+            return false;
+        }
+        self.driver.ready_async(self, async_id, data);
+        true
+    }
+
+    /// Tell the driver that a selected fd is readable (`ready_input` callback)
+    ///
+    /// Input polling of the fd is re-armed in the global select table once
+    /// the callback has run.
+    ///
+    /// # Returns
+    /// `false` if the port has been closed and the event was dropped
+    pub fn ready_input(&self, event: i32) -> bool {
+        if self.is_closed() {
+            return false;
+        }
+        self.driver.ready_input(self, event);
+        get_global_select_table().input_handled(event);
+        true
+    }
+
+    /// Append data to the driver queue (`driver_enq`)
+    ///
+    /// Marks the port busy when the queue reaches the high watermark.
+    pub fn driver_enq(&self, data: &[u8]) {
+        let mut state = self.state.lock().unwrap();
+        state.queue.extend(data);
+        if state.queue.len() >= state.high_watermark {
+            state.busy = true;
+        }
+    }
+
+    /// Remove `size` bytes from the head of the driver queue (`driver_deq`)
+    ///
+    /// Clears the busy state, resuming suspended processes, when the queue
+    /// drains to the low watermark.
+    ///
+    /// # Returns
+    /// Number of bytes remaining in the queue
+    pub fn driver_deq(&self, size: usize) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let size = size.min(state.queue.len());
+        state.queue.drain(..size);
+        if state.busy && state.queue.len() <= state.low_watermark {
+            state.clear_busy();
+        }
+        state.queue.len()
+    }
+
+    /// Get the number of bytes in the driver queue (`driver_sizeq`)
+    pub fn driver_sizeq(&self) -> usize {
+        self.state.lock().unwrap().queue.len()
+    }
+
+    /// Get a copy of the driver queue contents (`driver_peekq`)
+    pub fn driver_peekq(&self) -> Vec<u8> {
+        self.state.lock().unwrap().queue.iter().copied().collect()
+    }
+
+    /// Set the busy state of the port (`set_busy_port`)
+    ///
+    /// Clearing the busy state resumes processes suspended on the port.
+    pub fn set_busy_port(&self, on: bool) {
+        let mut state = self.state.lock().unwrap();
+        if on {
+            state.busy = true;
+        } else if state.busy {
+            state.clear_busy();
+        }
+    }
+
+    /// Check if the port is busy
+    pub fn is_busy(&self) -> bool {
+        self.state.lock().unwrap().busy
+    }
+
+    /// Set the queue watermarks controlling the busy state
+    ///
+    /// # Arguments
+    /// * `high` - Queue size at which the port becomes busy
+    /// * `low` - Queue size at which the port stops being busy; capped at `high`
+    pub fn set_busy_limits(&self, high: usize, low: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.high_watermark = high;
+        state.low_watermark = low.min(high);
+    }
+
+    /// Suspend a sending process on the port
+    ///
+    /// # Returns
+    /// * `true` - The port is busy and the process was suspended
+    /// * `false` - The port is not busy; the process may send
+    pub fn suspend_sender(&self, pid: u64) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if !state.busy {
+            return false;
+        }
+        if !state.suspended.contains(&pid) {
+            state.suspended.push(pid);
+        }
+        true
+    }
+
+    /// Get the processes currently suspended on the port
+    pub fn suspended_senders(&self) -> Vec<u64> {
+        self.state.lock().unwrap().suspended.clone()
+    }
+
+    /// Take the processes to resume since the port stopped being busy
+    pub fn take_resumed(&self) -> Vec<u64> {
+        std::mem::take(&mut self.state.lock().unwrap().resumed)
+    }
+
+    /// Close the port, calling the driver's `stop` callback
+    ///
+    /// # Returns
+    /// Processes to resume, including those still suspended on the port
+    pub(crate) fn close(&self) -> Vec<u64> {
+        {
+            let mut state = self.state.lock().unwrap();
+            if state.closed {
+                return Vec::new();
+            }
+            state.closed = true;
+        }
+        self.driver.stop(self);
+        let mut state = self.state.lock().unwrap();
+        state.queue.clear();
+        state.clear_busy();
+        std::mem::take(&mut state.resumed)
+    }
+
+    fn ensure_open(&self) -> Result<(), DriverError> {
+        if self.is_closed() {
+            Err(DriverError::PortClosed)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl std::fmt::Debug for DriverPort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DriverPort")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .field("driver", &self.driver_name())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct QueueDriver;
+
+    impl DriverEntry for QueueDriver {
+        fn driver_name(&self) -> &str {
+            "queue_drv"
+        }
+
+        fn output(&self, port: &DriverPort, data: &[u8]) -> Result<(), DriverError> {
+            port.driver_enq(data);
+            Ok(())
+        }
+    }
+
+    fn port() -> DriverPort {
+        DriverPort::new(1, "queue_drv", Arc::new(QueueDriver))
+    }
+
+    #[test]
+    fn test_driver_queue() {
+        let port = port();
+        port.output(b"hello").unwrap();
+        port.outputv(&[b" ", b"", b"world"]).unwrap();
+        assert_eq!(port.driver_sizeq(), 11);
+        assert_eq!(port.driver_deq(6), 5);
+        assert_eq!(port.driver_peekq(), b"world".to_vec());
+        assert_eq!(port.driver_deq(100), 0);
+    }
+
+    #[test]
+    fn test_busy_watermarks_resume_senders() {
+        let port = port();
+        port.set_busy_limits(8, 2);
+        assert!(!port.suspend_sender(10));
+
+        port.output(b"12345678").unwrap();
+        assert!(port.is_busy());
+        assert!(port.suspend_sender(10));
+        assert!(port.suspend_sender(11));
+        assert!(port.suspend_sender(10));
+        assert_eq!(port.suspended_senders(), vec![10, 11]);
+
+        port.driver_deq(4);
+        assert!(port.is_busy());
+        assert!(port.take_resumed().is_empty());
+
+        port.driver_deq(2);
+        assert!(!port.is_busy());
+        assert_eq!(port.take_resumed(), vec![10, 11]);
+        assert!(port.take_resumed().is_empty());
+    }
+
+    #[test]
+    fn test_set_busy_port() {
+        let port = port();
+        port.set_busy_port(true);
+        assert!(port.suspend_sender(3));
+        port.set_busy_port(false);
+        assert_eq!(port.take_resumed(), vec![3]);
+    }
+
+    #[test]
+    fn test_close() {
+        let port = port();
+        port.set_busy_port(true);
+        port.suspend_sender(5);
+        assert_eq!(port.close(), vec![5]);
+        assert!(port.is_closed());
+        assert_eq!(port.output(b"x"), Err(DriverError::PortClosed));
+        assert!(port.close().is_empty());
+    }
+
+    #[test]
+    fn test_control_not_supported() {
+        let port = port();
+        assert_eq!(port.control(1, b""), Err(DriverError::NotSupported));
+        assert_eq!(port.call(1, b""), Err(DriverError::NotSupported));
+    }
+}
@@ -0,0 +1,371 @@
+//! Driver and Port Tables
+//!
+//! Provides the table of loaded drivers, looked up by name when a port is
+//! opened, and the table of open driver ports, looked up by port number when
+//! a port BIF is called.
+//! Based on io.c and erl_ptab.c
+//!
+//! A port number is made of a slot (the low 28 bits) and a serial (the bits
+//! above). Closed ports free their slot, which is reused oldest first with
+//! the serial incremented, so a reused slot yields a new port number and a
+//! lookup of a closed port finds nothing. Port numbers of a slot's first
+//! use fit the 32 bits of `NEW_PORT_EXT`; later ones need `V4_PORT_EXT`.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+
+use crate::driver_entry::{DriverEntry, DriverError};
+use crate::port::DriverPort;
+
+/// Default maximum number of open ports, as ERTS without `+Q`
+pub const DEFAULT_PORT_LIMIT: usize = 65536;
+
+/// Bits of a port number holding the slot
+pub const PORT_SLOT_BITS: u32 = 28;
+
+/// Mask of the slot in a port number
+const PORT_SLOT_MASK: u64 = (1 << PORT_SLOT_BITS) - 1;
+
+/// Build a port number from a slot and serial
+pub fn make_port_id(slot: u64, serial: u64) -> u64 {
+    (serial << PORT_SLOT_BITS) | (slot & PORT_SLOT_MASK)
+}
+
+/// Get the slot of a port number
+pub fn port_slot(id: u64) -> u64 {
+    id & PORT_SLOT_MASK
+}
+
+/// Get the serial of a port number
+pub fn port_serial(id: u64) -> u64 {
+    id >> PORT_SLOT_BITS
+}
+
+/// Slot allocation state of the port table
+struct PortSlots {
+    /// Current serial of each slot used so far
+    serials: HashMap<u64, u64>,
+    /// Freed slots, oldest first
+    free: VecDeque<u64>,
+    /// Next slot never used; slot 0 is reserved
+    next_slot: u64,
+}
+
+impl PortSlots {
+    fn new() -> Self {
+        Self {
+            serials: HashMap::new(),
+            free: VecDeque::new(),
+            next_slot: 1,
+        }
+    }
+
+    /// Take a slot and make its current port number
+    fn allocate(&mut self) -> Option<u64> {
+        let slot = match self.free.pop_front() {
+            Some(slot) => slot,
+            None if self.next_slot <= PORT_SLOT_MASK => {
+                self.next_slot += 1;
+                self.next_slot - 1
+            }
+            None => return None,
+        };
+        let serial = *self.serials.entry(slot).or_insert(0);
+        Some(make_port_id(slot, serial))
+    }
+
+    /// Free the slot of a port number, moving it to the next serial
+    fn release(&mut self, id: u64) {
+        let slot = port_slot(id);
+        if let Some(serial) = self.serials.get_mut(&slot) {
+            if *serial == port_serial(id) {
+                *serial = (*serial + 1) & (u64::MAX >> PORT_SLOT_BITS);
+                self.free.push_back(slot);
+            }
+        }
+    }
+}
+
+/// Table of loaded drivers
+pub struct DriverRegistry {
+    /// Map from driver name to driver entry
+    drivers: RwLock<HashMap<String, Arc<dyn DriverEntry>>>,
+}
+
+impl DriverRegistry {
+    /// Create an empty driver registry
+    pub fn new() -> Self {
+        Self {
+            drivers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Load a driver (`add_driver_entry`)
+    ///
+    /// # Returns
+    /// * `Ok(())` - Success
+    /// * `Err(DriverError::AlreadyLoaded)` - A driver with the same name is loaded
+    pub fn add_driver_entry(&self, driver: Arc<dyn DriverEntry>) -> Result<(), DriverError> {
+        let mut drivers = self.drivers.write().unwrap();
+        let name = driver.driver_name().to_string();
+        if drivers.contains_key(&name) {
+            return Err(DriverError::AlreadyLoaded(name));
+        }
+        drivers.insert(name, driver);
+        Ok(())
+    }
+
+    /// Unload a driver (`remove_driver_entry`)
+    ///
+    /// # Returns
+    /// `true` if the driver was loaded
+    pub fn remove_driver_entry(&self, name: &str) -> bool {
+        self.drivers.write().unwrap().remove(name).is_some()
+    }
+
+    /// Look up a driver by name
+    pub fn lookup(&self, name: &str) -> Option<Arc<dyn DriverEntry>> {
+        self.drivers.read().unwrap().get(name).cloned()
+    }
+}
+
+impl Default for DriverRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Table of open driver ports
+pub struct PortTable {
+    /// Map from port number to port
+    ports: RwLock<HashMap<u64, Arc<DriverPort>>>,
+    /// Slots and serials of port numbers
+    slots: Mutex<PortSlots>,
+    /// Maximum number of open ports
+    max_ports: usize,
+}
+
+impl PortTable {
+    /// Create an empty port table with the default port limit
+    pub fn new() -> Self {
+        Self::with_max_ports(DEFAULT_PORT_LIMIT)
+    }
+
+    /// Create an empty port table holding at most `max_ports` open ports
+    pub fn with_max_ports(max_ports: usize) -> Self {
+        Self {
+            ports: RwLock::new(HashMap::new()),
+            slots: Mutex::new(PortSlots::new()),
+            max_ports,
+        }
+    }
+
+    /// Get the maximum number of open ports
+    pub fn max_ports(&self) -> usize {
+        self.max_ports
+    }
+
+    /// Open a port on a loaded driver
+    ///
+    /// The driver is selected by the first word of `command`, as for
+    /// `open_port({spawn_driver, Command}, _)`, and its `start` callback is
+    /// called with the full command.
+    ///
+    /// # Returns
+    /// * `Ok(port)` - The new port
+    /// * `Err(DriverError::SystemLimit)` - The port limit has been reached
+    /// * `Err(DriverError)` - No such driver, or the driver failed to start
+    pub fn open_port(&self, drivers: &DriverRegistry, command: &str) -> Result<Arc<DriverPort>, DriverError> {
+        if self.len() >= self.max_ports {
+            return Err(DriverError::SystemLimit);
+        }
+        let driver_name = command.split_whitespace().next().unwrap_or("");
+        let driver = drivers
+            .lookup(driver_name)
+            .ok_or_else(|| DriverError::DriverNotFound(driver_name.to_string()))?;
+        let id = self.slots.lock().unwrap().allocate().ok_or(DriverError::SystemLimit)?;
+        let port = Arc::new(DriverPort::new(id, command, driver.clone()));
+        if let Err(error) = driver.start(&port, command) {
+            self.slots.lock().unwrap().release(id);
+            return Err(error);
+        }
+        self.ports.write().unwrap().insert(id, port.clone());
+        Ok(port)
+    }
+
+    /// Look up an open port by number
+    pub fn lookup(&self, id: u64) -> Option<Arc<DriverPort>> {
+        self.ports.read().unwrap().get(&id).cloned()
+    }
+
+    /// Close a port, calling the driver's `stop` callback
+    ///
+    /// # Returns
+    /// * `Some(resumed)` - Processes to resume that were suspended on the port
+    /// * `None` - No port with this number is open
+    pub fn close_port(&self, id: u64) -> Option<Vec<u64>> {
+        let port = self.ports.write().unwrap().remove(&id)?;
+        self.slots.lock().unwrap().release(id);
+        Some(port.close())
+    }
+
+    /// Take the processes to resume from every port that stopped being busy
+    ///
+    /// Schedulers call this on each pass of their loop, so senders suspended
+    /// on a busy port are resumed however the port's queue was drained.
+    pub fn take_resumed(&self) -> Vec<u64> {
+        self.ports
+            .read()
+            .unwrap()
+            .values()
+            .flat_map(|port| port.take_resumed())
+            .collect()
+    }
+
+    /// List the numbers of the open ports, in table order
+    ///
+    /// The list is a snapshot; ports may be opened and closed meanwhile.
+    /// Equivalent to `erts_ptab_list()`, which implements `erlang:ports/0`.
+    pub fn list(&self) -> Vec<u64> {
+        let mut ids: Vec<u64> = self.ports.read().unwrap().keys().copied().collect();
+        ids.sort_by_key(|id| (port_slot(*id), port_serial(*id)));
+        ids
+    }
+
+    /// Get the number of open ports
+    pub fn len(&self) -> usize {
+        self.ports.read().unwrap().len()
+    }
+
+    /// Check if no ports are open
+    pub fn is_empty(&self) -> bool {
+        self.ports.read().unwrap().is_empty()
+    }
+}
+
+impl Default for PortTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Global driver registry instance
+static GLOBAL_DRIVER_REGISTRY: OnceLock<DriverRegistry> = OnceLock::new();
+
+/// Global port table instance
+static GLOBAL_PORT_TABLE: OnceLock<PortTable> = OnceLock::new();
+
+/// Get the global driver registry
+pub fn get_global_driver_registry() -> &'static DriverRegistry {
+    GLOBAL_DRIVER_REGISTRY.get_or_init(DriverRegistry::new)
+}
+
+/// Get the global port table
+pub fn get_global_port_table() -> &'static PortTable {
+    GLOBAL_PORT_TABLE.get_or_init(PortTable::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NullDriver;
+
+    impl DriverEntry for NullDriver {
+        fn driver_name(&self) -> &str {
+            "null_drv"
+        }
+
+        fn output(&self, _port: &DriverPort, _data: &[u8]) -> Result<(), DriverError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_driver_registry() {
+        let drivers = DriverRegistry::new();
+        drivers.add_driver_entry(Arc::new(NullDriver)).unwrap();
+        assert_eq!(
+            drivers.add_driver_entry(Arc::new(NullDriver)),
+            Err(DriverError::AlreadyLoaded("null_drv".to_string()))
+        );
+        assert!(drivers.lookup("null_drv").is_some());
+        assert!(drivers.remove_driver_entry("null_drv"));
+        assert!(drivers.lookup("null_drv").is_none());
+    }
+
+    #[test]
+    fn test_open_and_close_port() {
+        let drivers = DriverRegistry::new();
+        drivers.add_driver_entry(Arc::new(NullDriver)).unwrap();
+        let ports = PortTable::new();
+
+        let port = ports.open_port(&drivers, "null_drv arg").unwrap();
+        assert_eq!(port.name(), "null_drv arg");
+        assert_eq!(port.driver_name(), "null_drv");
+        assert_eq!(ports.len(), 1);
+        assert!(ports.lookup(port.id()).is_some());
+
+        assert_eq!(ports.close_port(port.id()), Some(Vec::new()));
+        assert!(port.is_closed());
+        assert!(ports.is_empty());
+        assert_eq!(ports.close_port(port.id()), None);
+    }
+
+    #[test]
+    fn test_take_resumed_across_ports() {
+        let drivers = DriverRegistry::new();
+        drivers.add_driver_entry(Arc::new(NullDriver)).unwrap();
+        let ports = PortTable::new();
+        let first = ports.open_port(&drivers, "null_drv").unwrap();
+        let second = ports.open_port(&drivers, "null_drv").unwrap();
+        first.set_busy_port(true);
+        assert!(first.suspend_sender(5));
+        second.set_busy_port(true);
+        assert!(second.suspend_sender(6));
+
+        first.set_busy_port(false);
+        assert_eq!(ports.take_resumed(), vec![5]);
+        assert!(ports.take_resumed().is_empty());
+        assert_eq!(second.suspended_senders(), vec![6]);
+    }
+
+    #[test]
+    fn test_port_slot_reuse() {
+        let drivers = DriverRegistry::new();
+        drivers.add_driver_entry(Arc::new(NullDriver)).unwrap();
+        let ports = PortTable::new();
+
+        let first = ports.open_port(&drivers, "null_drv").unwrap().id();
+        let second = ports.open_port(&drivers, "null_drv").unwrap().id();
+        assert_eq!((port_slot(first), port_serial(first)), (1, 0));
+        assert_eq!(ports.list(), vec![first, second]);
+
+        ports.close_port(first);
+        let reused = ports.open_port(&drivers, "null_drv").unwrap().id();
+        assert_eq!(reused, make_port_id(1, 1));
+        assert!(reused > 0x0FFF_FFFF);
+        assert!(ports.lookup(first).is_none());
+        assert_eq!(ports.list(), vec![reused, second]);
+    }
+
+    #[test]
+    fn test_open_port_unknown_driver() {
+        let ports = PortTable::new();
+        let result = ports.open_port(&DriverRegistry::new(), "missing_drv");
+        assert_eq!(result.unwrap_err(), DriverError::DriverNotFound("missing_drv".to_string()));
+    }
+
+    #[test]
+    fn test_open_port_limit() {
+        let drivers = DriverRegistry::new();
+        drivers.add_driver_entry(Arc::new(NullDriver)).unwrap();
+        let ports = PortTable::with_max_ports(1);
+        assert_eq!(ports.max_ports(), 1);
+
+        let port = ports.open_port(&drivers, "null_drv").unwrap();
+        assert_eq!(ports.open_port(&drivers, "null_drv").unwrap_err(), DriverError::SystemLimit);
+        ports.close_port(port.id());
+        assert!(ports.open_port(&drivers, "null_drv").is_ok());
+    }
+}
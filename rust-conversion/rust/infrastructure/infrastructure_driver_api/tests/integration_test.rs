@@ -0,0 +1,130 @@
+//! Integration tests for infrastructure_driver_api crate
+//!
+//! These tests verify driver loading, port opening, driver callbacks and
+//! busy port backpressure through the public API.
+
+use infrastructure_driver_api::*;
+use std::sync::Arc;
+
+struct EchoDriver;
+
+impl DriverEntry for EchoDriver {
+    fn driver_name(&self) -> &str {
+        "it_echo_drv"
+    }
+
+    fn output(&self, port: &DriverPort, data: &[u8]) -> Result<(), DriverError> {
+        port.driver_enq(data);
+        Ok(())
+    }
+
+    fn control(&self, port: &DriverPort, command: u32, _data: &[u8]) -> Result<Vec<u8>, DriverError> {
+        match command {
+            0 => Ok(port.driver_peekq()),
+            _ => Err(DriverError::Failed("unknown command".to_string())),
+        }
+    }
+}
+
+#[test]
+fn test_port_lifecycle_with_backpressure() {
+    let drivers = DriverRegistry::new();
+    drivers.add_driver_entry(Arc::new(EchoDriver)).unwrap();
+    let ports = PortTable::new();
+    let port = ports.open_port(&drivers, "it_echo_drv").unwrap();
+    port.set_busy_limits(4, 0);
+
+    port.output(b"abcd").unwrap();
+    assert_eq!(port.control(0, b"").unwrap(), b"abcd".to_vec());
+    assert!(port.suspend_sender(100));
+
+    assert_eq!(port.driver_deq(4), 0);
+    assert_eq!(port.take_resumed(), vec![100]);
+    assert!(ports.close_port(port.id()).unwrap().is_empty());
+}
+
+#[test]
+fn test_global_tables() {
+    assert!(std::ptr::eq(get_global_driver_registry(), get_global_driver_registry()));
+    assert!(std::ptr::eq(get_global_port_table(), get_global_port_table()));
+}
+
+struct SumDriver {
+    total: std::sync::Mutex<u64>,
+}
+
+impl DriverEntry for SumDriver {
+    fn driver_name(&self) -> &str {
+        "it_sum_drv"
+    }
+
+    fn output(&self, _port: &DriverPort, _data: &[u8]) -> Result<(), DriverError> {
+        Ok(())
+    }
+
+    fn ready_async(&self, _port: &DriverPort, _async_id: i64, data: Box<dyn std::any::Any + Send>) {
+        *self.total.lock().unwrap() += *data.downcast::<u64>().unwrap();
+    }
+}
+
+#[test]
+fn test_driver_async_delivers_to_ready_async() {
+    let driver = Arc::new(SumDriver {
+        total: std::sync::Mutex::new(0),
+    });
+    let drivers = DriverRegistry::new();
+    drivers.add_driver_entry(driver.clone()).unwrap();
+    let ports = PortTable::new();
+    let port = ports.open_port(&drivers, "it_sum_drv").unwrap();
+
+    let pool = AsyncPool::new(3).unwrap();
+    let key = driver_async_port_key(&port);
+    for n in 1..=10u64 {
+        pool.driver_async(&port, Some(key), move || n).unwrap();
+    }
+    assert!(pool.wait_ready(10, std::time::Duration::from_secs(5)));
+    assert_eq!(pool.deliver_ready(&ports), 10);
+    assert_eq!(*driver.total.lock().unwrap(), 55);
+}
+
+struct FdDriver {
+    closed: std::sync::Mutex<Vec<i32>>,
+}
+
+impl DriverEntry for FdDriver {
+    fn driver_name(&self) -> &str {
+        "it_fd_drv"
+    }
+
+    fn output(&self, _port: &DriverPort, _data: &[u8]) -> Result<(), DriverError> {
+        Ok(())
+    }
+
+    fn stop_select(&self, event: i32) {
+        self.closed.lock().unwrap().push(event);
+    }
+}
+
+#[test]
+fn test_stop_select_waits_for_poll_backend() {
+    let driver = Arc::new(FdDriver {
+        closed: std::sync::Mutex::new(Vec::new()),
+    });
+    let drivers = DriverRegistry::new();
+    drivers.add_driver_entry(driver.clone()).unwrap();
+    let ports = PortTable::new();
+    let port = ports.open_port(&drivers, "it_fd_drv").unwrap();
+    let table = SelectTable::new();
+    let mode = DriverSelectFlags::Read as u32 | DriverSelectFlags::Write as u32 | DriverSelectFlags::Use as u32;
+
+    table.driver_select(&port, 20, mode, true).unwrap();
+    assert_eq!(table.selected_events(20), Some(3));
+    assert_eq!(table.driver_select(&port, 20, mode, false), Ok(DriverSelectResult::StopScheduled));
+    assert!(driver.closed.lock().unwrap().is_empty());
+
+    // The poll backend applies the updates, then confirms the removal
+    let updates = table.take_pollset_updates();
+    assert_eq!(updates.last(), Some(&PollsetUpdate { fd: 20, events: 0 }));
+    assert!(table.pollset_removed(20));
+    assert_eq!(*driver.closed.lock().unwrap(), vec![20]);
+}
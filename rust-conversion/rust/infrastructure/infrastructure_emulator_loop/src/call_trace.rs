@@ -0,0 +1,512 @@
+//! Call Tracing
+//!
+//! Provides call tracing for the emulator loop: the `call` and `return_to`
+//! trace events emitted from call and return instructions.
+//!
+//! A call is traced when the calling process has the `call` trace flag set
+//! (`erlang:trace/3`) and the called function matches an installed trace
+//! pattern (`erlang:trace_pattern/3`) whose match specification, if any,
+//! accepts the arguments. When the process also has the `return_to` flag,
+//! returning from a traced call emits a `return_to` event naming the
+//! function execution resumes in.
+//!
+//! Trace patterns play the role of the breakpoints installed on traced
+//! functions, match specifications are evaluated through the [`MatchSpec`]
+//! trait, and events are delivered to a [`CallTracer`]. A [`GuardMatchSpec`]
+//! evaluates its guard in a [`GuardSandbox`], so a guard that fails or runs
+//! out of reductions only makes the call untraced.
+//!
+//! Based on beam_bp.c and erl_bif_trace.c
+
+use std::sync::{Arc, OnceLock, RwLock};
+
+use entities_process::term_tags::{
+    NIL, TAG_IMMED1_MASK, TAG_IMMED1_SIZE, TAG_IMMED1_SMALL, TAG_IMMED2_ATOM, TAG_IMMED2_MASK,
+    TAG_IMMED2_SIZE,
+};
+use entities_process::{ErtsCodePtr, Eterm, Process, ProcessId};
+use infrastructure_utilities::atom_table::get_global_atom_table;
+use usecases_bifs::guard::{GuardFailure, GuardSandbox};
+use usecases_bifs::op::ErlangTerm;
+use usecases_bifs::trace::{TraceBif, TraceFlags, TraceInfo, TraceTarget};
+
+use crate::instruction_decoder::{decode_instruction, opcodes};
+
+/// Number of code slots taken by the `func_info` preceding a function entry
+const FUNC_INFO_SIZE: usize = 4;
+
+/// Module, function and arity of a function
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CodeMfa {
+    /// Module atom
+    pub module: Eterm,
+    /// Function atom
+    pub function: Eterm,
+    /// Arity
+    pub arity: u32,
+}
+
+impl CodeMfa {
+    /// Create a new MFA
+    pub fn new(module: Eterm, function: Eterm, arity: u32) -> Self {
+        Self {
+            module,
+            function,
+            arity,
+        }
+    }
+}
+
+/// Look up the MFA of the function whose entry is at `entry`
+///
+/// Reads the `func_info` instruction preceding the function entry, as
+/// `erts_code_to_codemfa` does.
+///
+/// # Safety
+/// `entry` must point into loaded code, at least `FUNC_INFO_SIZE` slots
+/// from its start.
+///
+/// # Returns
+/// * `Some(mfa)` - `entry` is preceded by `func_info`
+/// * `None` - `entry` is not a function entry
+pub unsafe fn code_to_mfa(entry: ErtsCodePtr) -> Option<CodeMfa> {
+    if entry.is_null() {
+        return None;
+    }
+    let func_info = decode_instruction(entry.sub(FUNC_INFO_SIZE)).ok()?;
+    if func_info.opcode != opcodes::FUNC_INFO {
+        return None;
+    }
+    Some(CodeMfa::new(
+        func_info.operands[0],
+        func_info.operands[1],
+        func_info.operands[2] as u32,
+    ))
+}
+
+/// Match specification evaluated on the arguments of a traced call
+pub trait MatchSpec: Send + Sync {
+    /// Check whether the call should be traced
+    fn matches(&self, process: &Process, args: &[Eterm]) -> bool;
+}
+
+impl<F> MatchSpec for F
+where
+    F: Fn(&Process, &[Eterm]) -> bool + Send + Sync,
+{
+    fn matches(&self, process: &Process, args: &[Eterm]) -> bool {
+        self(process, args)
+    }
+}
+
+/// Match specification with a guard on the call arguments
+///
+/// The arguments are decoded to terms and the guard is evaluated on them in
+/// a [`GuardSandbox`]. A call is traced only if the guard is `true`; it is
+/// not traced if an argument cannot be decoded or the guard fails.
+pub struct GuardMatchSpec<G> {
+    /// Guard on the decoded arguments
+    guard: G,
+    /// Reduction budget of each evaluation
+    reductions: usize,
+}
+
+impl<G> GuardMatchSpec<G>
+where
+    G: Fn(&mut GuardSandbox, &[ErlangTerm]) -> Result<ErlangTerm, GuardFailure> + Send + Sync,
+{
+    /// Create a match specification with the default reduction budget
+    pub fn new(guard: G) -> Self {
+        Self {
+            guard,
+            reductions: GuardSandbox::DEFAULT_REDUCTIONS,
+        }
+    }
+
+    /// Set the reduction budget of each evaluation
+    pub fn with_reductions(mut self, reductions: usize) -> Self {
+        self.reductions = reductions;
+        self
+    }
+}
+
+impl<G> MatchSpec for GuardMatchSpec<G>
+where
+    G: Fn(&mut GuardSandbox, &[ErlangTerm]) -> Result<ErlangTerm, GuardFailure> + Send + Sync,
+{
+    fn matches(&self, process: &Process, args: &[Eterm]) -> bool {
+        let Some(terms) = args.iter().map(|&arg| guard_term(arg)).collect::<Option<Vec<_>>>() else {
+            return false;
+        };
+        GuardSandbox::new(self.reductions)
+            .with_self(process.id())
+            .eval(|sandbox| (self.guard)(sandbox, &terms))
+    }
+}
+
+/// Decode an immediate argument for a guard
+///
+/// # Returns
+/// The small integer, atom or nil, or `None` for any other term
+pub(crate) fn guard_term(term: Eterm) -> Option<ErlangTerm> {
+    if term == NIL {
+        Some(ErlangTerm::Nil)
+    } else if term & TAG_IMMED1_MASK == TAG_IMMED1_SMALL {
+        Some(ErlangTerm::Integer((term as i64) >> TAG_IMMED1_SIZE))
+    } else if term & TAG_IMMED2_MASK == TAG_IMMED2_ATOM {
+        let name = get_global_atom_table().get_name((term >> TAG_IMMED2_SIZE) as usize)?;
+        Some(ErlangTerm::Atom(String::from_utf8(name).ok()?))
+    } else {
+        None
+    }
+}
+
+/// Trace pattern selecting the functions to call trace
+///
+/// `None` for the function or arity matches any function or arity, as `'_'`
+/// does in `erlang:trace_pattern/3`.
+#[derive(Clone)]
+pub struct TracePattern {
+    /// Module atom
+    pub module: Eterm,
+    /// Function atom, or any function
+    pub function: Option<Eterm>,
+    /// Arity, or any arity
+    pub arity: Option<u32>,
+    /// Match specification, or trace all calls
+    pub match_spec: Option<Arc<dyn MatchSpec>>,
+}
+
+impl TracePattern {
+    /// Create a pattern tracing every call to the matching functions
+    pub fn new(module: Eterm, function: Option<Eterm>, arity: Option<u32>) -> Self {
+        Self {
+            module,
+            function,
+            arity,
+            match_spec: None,
+        }
+    }
+
+    /// Set the match specification of the pattern
+    pub fn with_match_spec(mut self, match_spec: Arc<dyn MatchSpec>) -> Self {
+        self.match_spec = Some(match_spec);
+        self
+    }
+
+    /// Check whether the pattern selects a function
+    pub fn matches_mfa(&self, mfa: &CodeMfa) -> bool {
+        self.module == mfa.module
+            && self.function.is_none_or(|function| function == mfa.function)
+            && self.arity.is_none_or(|arity| arity == mfa.arity)
+    }
+
+    fn same_selection(&self, other: &TracePattern) -> bool {
+        self.module == other.module && self.function == other.function && self.arity == other.arity
+    }
+}
+
+impl std::fmt::Debug for TracePattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TracePattern")
+            .field("module", &self.module)
+            .field("function", &self.function)
+            .field("arity", &self.arity)
+            .field("match_spec", &self.match_spec.is_some())
+            .finish()
+    }
+}
+
+/// Call trace event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceEvent {
+    /// `{trace, Pid, call, {M, F, Args}}`
+    Call {
+        /// Traced process
+        pid: ProcessId,
+        /// Called function
+        mfa: CodeMfa,
+        /// Call arguments
+        args: Vec<Eterm>,
+    },
+    /// `{trace, Pid, return_to, {M, F, A}}`
+    ReturnTo {
+        /// Traced process
+        pid: ProcessId,
+        /// Function execution returns to, or `None` for `undefined`
+        mfa: Option<CodeMfa>,
+    },
+}
+
+/// Receiver of call trace events
+pub trait CallTracer: Send + Sync {
+    /// Deliver a trace event
+    fn trace(&self, event: TraceEvent);
+}
+
+/// Installed trace patterns and the tracer receiving call trace events
+pub struct CallTrace {
+    /// Trace patterns, most recently set last
+    patterns: RwLock<Vec<TracePattern>>,
+    /// Tracer receiving events
+    tracer: RwLock<Option<Arc<dyn CallTracer>>>,
+}
+
+impl CallTrace {
+    /// Create an empty call trace table
+    pub fn new() -> Self {
+        Self {
+            patterns: RwLock::new(Vec::new()),
+            tracer: RwLock::new(None),
+        }
+    }
+
+    /// Install a trace pattern, replacing one with the same selection
+    pub fn set_trace_pattern(&self, pattern: TracePattern) {
+        let mut patterns = self.patterns.write().unwrap();
+        patterns.retain(|installed| !installed.same_selection(&pattern));
+        patterns.push(pattern);
+    }
+
+    /// Remove the trace pattern with the given selection
+    ///
+    /// # Returns
+    /// `true` if a pattern was removed
+    pub fn clear_trace_pattern(&self, module: Eterm, function: Option<Eterm>, arity: Option<u32>) -> bool {
+        let selection = TracePattern::new(module, function, arity);
+        let mut patterns = self.patterns.write().unwrap();
+        let before = patterns.len();
+        patterns.retain(|installed| !installed.same_selection(&selection));
+        patterns.len() != before
+    }
+
+    /// Remove all trace patterns
+    pub fn clear_all(&self) {
+        self.patterns.write().unwrap().clear();
+    }
+
+    /// Find the trace pattern applying to a function
+    ///
+    /// The most recently set matching pattern applies.
+    pub fn lookup(&self, mfa: &CodeMfa) -> Option<TracePattern> {
+        let patterns = self.patterns.read().unwrap();
+        patterns.iter().rev().find(|pattern| pattern.matches_mfa(mfa)).cloned()
+    }
+
+    /// Set the tracer receiving call trace events
+    pub fn set_tracer(&self, tracer: Option<Arc<dyn CallTracer>>) {
+        *self.tracer.write().unwrap() = tracer;
+    }
+
+    /// Trace a call if the process and function are traced
+    ///
+    /// # Returns
+    /// `true` if a `call` event was emitted
+    pub fn trace_call(&self, process: &Process, mfa: &CodeMfa, args: &[Eterm]) -> bool {
+        let Some(tracer) = self.tracer.read().unwrap().clone() else {
+            return false;
+        };
+        if !process_trace_flags(process.id()).is_some_and(|flags| flags.call) {
+            return false;
+        }
+        let Some(pattern) = self.lookup(mfa) else {
+            return false;
+        };
+        if let Some(match_spec) = &pattern.match_spec {
+            if !match_spec.matches(process, args) {
+                return false;
+            }
+        }
+        tracer.trace(TraceEvent::Call {
+            pid: process.id(),
+            mfa: *mfa,
+            args: args.to_vec(),
+        });
+        true
+    }
+
+    /// Trace the return from a traced call
+    ///
+    /// # Arguments
+    /// * `process` - Returning process
+    /// * `mfa` - Function execution returns to
+    pub fn trace_return_to(&self, process: &Process, mfa: Option<CodeMfa>) {
+        let Some(tracer) = self.tracer.read().unwrap().clone() else {
+            return;
+        };
+        if process_trace_flags(process.id()).is_some_and(|flags| flags.call && flags.return_to) {
+            tracer.trace(TraceEvent::ReturnTo {
+                pid: process.id(),
+                mfa,
+            });
+        }
+    }
+}
+
+impl Default for CallTrace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Get the trace flags set on a process
+fn process_trace_flags(pid: ProcessId) -> Option<TraceFlags> {
+    match TraceBif::trace_info(None, TraceTarget::Process(pid)) {
+        Ok(TraceInfo::Process { flags, .. }) => Some(flags),
+        _ => None,
+    }
+}
+
+/// Global call trace instance
+static GLOBAL_CALL_TRACE: OnceLock<Arc<CallTrace>> = OnceLock::new();
+
+/// Get the global call trace table
+pub fn get_global_call_trace() -> Arc<CallTrace> {
+    GLOBAL_CALL_TRACE.get_or_init(|| Arc::new(CallTrace::new())).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct CollectingTracer(Mutex<Vec<TraceEvent>>);
+
+    impl CallTracer for CollectingTracer {
+        fn trace(&self, event: TraceEvent) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    fn trace_process(pid: ProcessId, return_to: bool) {
+        let flags = TraceFlags {
+            call: true,
+            return_to,
+            ..Default::default()
+        };
+        TraceBif::trace(None, TraceTarget::Process(pid), true, flags).unwrap();
+    }
+
+    #[test]
+    fn test_code_to_mfa() {
+        let code: Vec<u8> = vec![opcodes::FUNC_INFO, 10, 20, 2, opcodes::RETURN];
+        let entry = unsafe { code.as_ptr().add(FUNC_INFO_SIZE) };
+        assert_eq!(unsafe { code_to_mfa(entry) }, Some(CodeMfa::new(10, 20, 2)));
+
+        let code: Vec<u8> = vec![opcodes::LABEL, 1, 0, 0, opcodes::RETURN];
+        let entry = unsafe { code.as_ptr().add(FUNC_INFO_SIZE) };
+        assert_eq!(unsafe { code_to_mfa(entry) }, None);
+    }
+
+    #[test]
+    fn test_trace_pattern_selection() {
+        let table = CallTrace::new();
+        table.set_trace_pattern(TracePattern::new(1, None, None));
+        table.set_trace_pattern(TracePattern::new(1, Some(2), Some(1)));
+        table.set_trace_pattern(TracePattern::new(1, Some(2), Some(1)));
+
+        assert!(table.lookup(&CodeMfa::new(1, 5, 0)).is_some());
+        assert_eq!(table.lookup(&CodeMfa::new(1, 2, 1)).unwrap().function, Some(2));
+        assert!(table.lookup(&CodeMfa::new(2, 2, 1)).is_none());
+
+        assert!(table.clear_trace_pattern(1, None, None));
+        assert!(!table.clear_trace_pattern(1, None, None));
+        assert!(table.lookup(&CodeMfa::new(1, 5, 0)).is_none());
+        table.clear_all();
+        assert!(table.lookup(&CodeMfa::new(1, 2, 1)).is_none());
+    }
+
+    #[test]
+    fn test_trace_call_requires_flag_and_pattern() {
+        let table = CallTrace::new();
+        let tracer = Arc::new(CollectingTracer::default());
+        table.set_tracer(Some(tracer.clone()));
+        table.set_trace_pattern(TracePattern::new(1, Some(2), None));
+        let mfa = CodeMfa::new(1, 2, 1);
+
+        let untraced = Process::new(40680);
+        assert!(!table.trace_call(&untraced, &mfa, &[7]));
+
+        let process = Process::new(40681);
+        trace_process(40681, true);
+        assert!(!table.trace_call(&process, &CodeMfa::new(1, 3, 1), &[7]));
+        assert!(table.trace_call(&process, &mfa, &[7]));
+        table.trace_return_to(&process, None);
+
+        assert_eq!(
+            *tracer.0.lock().unwrap(),
+            vec![
+                TraceEvent::Call { pid: 40681, mfa, args: vec![7] },
+                TraceEvent::ReturnTo { pid: 40681, mfa: None },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trace_call_match_spec() {
+        let table = CallTrace::new();
+        let tracer = Arc::new(CollectingTracer::default());
+        table.set_tracer(Some(tracer.clone()));
+        let first_arg_is_one = |_: &Process, args: &[Eterm]| args.first() == Some(&1);
+        table.set_trace_pattern(TracePattern::new(1, None, None).with_match_spec(Arc::new(first_arg_is_one)));
+        let process = Process::new(40682);
+        trace_process(40682, false);
+
+        assert!(!table.trace_call(&process, &CodeMfa::new(1, 2, 1), &[2]));
+        assert!(table.trace_call(&process, &CodeMfa::new(1, 2, 1), &[1]));
+        table.trace_return_to(&process, None);
+        assert_eq!(tracer.0.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_trace_call_guard_match_spec() {
+        let table = CallTrace::new();
+        let tracer = Arc::new(CollectingTracer::default());
+        table.set_tracer(Some(tracer.clone()));
+        // [{[X], [{'>', X, 10}], []}]
+        let greater_than_ten = GuardMatchSpec::new(|guard: &mut GuardSandbox, args: &[ErlangTerm]| {
+            guard.call(">", &[args[0].clone(), ErlangTerm::Integer(10)])
+        });
+        table.set_trace_pattern(TracePattern::new(1, None, None).with_match_spec(Arc::new(greater_than_ten)));
+        let process = Process::new(40683);
+        trace_process(40683, false);
+
+        let small = entities_process::term_tags::make_small;
+        assert!(table.trace_call(&process, &CodeMfa::new(1, 2, 1), &[small(11)]));
+        assert!(!table.trace_call(&process, &CodeMfa::new(1, 2, 1), &[small(-3)]));
+        // Arguments a guard cannot see are not traced
+        assert!(!table.trace_call(&process, &CodeMfa::new(1, 2, 1), &[0x2]));
+        assert_eq!(tracer.0.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_guard_match_spec_failures() {
+        let process = Process::new(40684);
+        let atom = |name: &str| {
+            let index = get_global_atom_table()
+                .put_index(name.as_bytes(), entities_data_handling::AtomEncoding::SevenBitAscii, false)
+                .unwrap();
+            entities_process::term_tags::make_atom(index)
+        };
+
+        // [{[A], [{is_atom, A}], []}]
+        let is_atom = GuardMatchSpec::new(|guard: &mut GuardSandbox, args: &[ErlangTerm]| guard.call("is_atom", args));
+        assert!(is_atom.matches(&process, &[atom("guard_match_spec")]));
+        assert!(!is_atom.matches(&process, &[NIL]));
+
+        // Exceptions, calls that are not guard BIFs and exhausted reductions
+        // make the guard false
+        let badarg = GuardMatchSpec::new(|guard: &mut GuardSandbox, args: &[ErlangTerm]| guard.call("hd", args));
+        assert!(!badarg.matches(&process, &[NIL]));
+        let not_guard = GuardMatchSpec::new(|guard: &mut GuardSandbox, args: &[ErlangTerm]| {
+            guard.call("put", &[args[0].clone(), ErlangTerm::Nil])
+        });
+        assert!(!not_guard.matches(&process, &[NIL]));
+        let looping = GuardMatchSpec::new(|guard: &mut GuardSandbox, args: &[ErlangTerm]| loop {
+            guard.call("is_list", args)?;
+        })
+        .with_reductions(50);
+        assert!(!looping.matches(&process, &[NIL]));
+    }
+}
@@ -6,13 +6,18 @@
 //!
 //! Based on `process_main()` and `init_emulator()` from `beam_emu.c`.
 
-use entities_process::{Process, ProcessId, ErtsCodePtr, Eterm};
+use entities_process::{process_defaults, Process, ErtsCodePtr, Eterm};
 use usecases_scheduling::{Scheduler, ScheduleError, RunQueue, Priority, dequeue_process};
 use std::sync::Mutex;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+use infrastructure_utilities::statistics::get_global_statistics;
 
 use super::registers::RegisterManager;
+use super::call_trace::{code_to_mfa, get_global_call_trace, CallTrace, CodeMfa};
+use super::instruction_decoder::{decode_instruction, opcodes};
+use super::instruction_execution::next_instruction;
 
 /// Emulator loop error types
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -35,6 +40,17 @@ impl From<ScheduleError> for EmulatorLoopError {
     }
 }
 
+/// Call stack frame pushed by a non-tail call
+#[derive(Debug, Clone, Copy)]
+struct CallFrame {
+    /// Instruction to continue at on return
+    return_ip: ErtsCodePtr,
+    /// Function containing `return_ip`
+    mfa: Option<CodeMfa>,
+    /// The called function was call traced
+    traced: bool,
+}
+
 /// Emulator loop state
 ///
 /// Manages the state of the emulator loop for a scheduler thread.
@@ -58,6 +74,14 @@ pub struct EmulatorLoop {
     fcalls: i32,
     /// Reductions at start of execution (REDS_IN in C code)
     reds_in: i32,
+    /// Return frames of the current process
+    call_stack: Vec<CallFrame>,
+    /// Function currently executing, if known
+    current_mfa: Option<CodeMfa>,
+    /// Trace patterns and tracer for call tracing
+    call_trace: Arc<CallTrace>,
+    /// Index of the scheduler running this loop, for statistics
+    scheduler_index: usize,
 }
 
 impl EmulatorLoop {
@@ -71,6 +95,10 @@ impl EmulatorLoop {
             instruction_ptr: std::ptr::null(),
             fcalls: 0,
             reds_in: 0,
+            call_stack: Vec::new(),
+            current_mfa: None,
+            call_trace: get_global_call_trace(),
+            scheduler_index: 0,
         }
     }
     
@@ -90,10 +118,69 @@ impl EmulatorLoop {
     }
     
     /// Set the current process
+    ///
+    /// Switching to a different process discards the call frames and
+    /// current function of the previous one.
     pub fn set_current_process(&mut self, process: Option<Arc<Process>>) {
+        let same = match (&self.current_process, &process) {
+            (Some(current), Some(new)) => current.id() == new.id(),
+            _ => false,
+        };
+        if !same {
+            self.call_stack.clear();
+            self.current_mfa = None;
+        }
         self.current_process = process;
     }
+
+    /// Get the function currently executing, if known
+    pub fn current_mfa(&self) -> Option<CodeMfa> {
+        self.current_mfa
+    }
+
+    /// Set the function currently executing
+    pub fn set_current_mfa(&mut self, mfa: Option<CodeMfa>) {
+        self.current_mfa = mfa;
+    }
+
+    /// Get the number of return frames on the call stack
+    pub fn call_depth(&self) -> usize {
+        self.call_stack.len()
+    }
+
+    /// Get the backtrace of the current process, innermost function first
+    ///
+    /// Holds at most the system `backtrace_depth` functions, as set with
+    /// `erlang:system_flag(backtrace_depth, Depth)`.
+    pub fn backtrace(&self) -> Vec<CodeMfa> {
+        let depth = process_defaults().backtrace_depth;
+        self.current_mfa
+            .into_iter()
+            .chain(self.call_stack.iter().rev().filter_map(|frame| frame.mfa))
+            .take(depth)
+            .collect()
+    }
+
+    /// Get the call trace table used for `call` and `return_to` events
+    pub fn call_trace(&self) -> &Arc<CallTrace> {
+        &self.call_trace
+    }
+
+    /// Set the call trace table (defaults to the global one)
+    pub fn set_call_trace(&mut self, call_trace: Arc<CallTrace>) {
+        self.call_trace = call_trace;
+    }
     
+    /// Get the index of the scheduler running this loop
+    pub fn scheduler_index(&self) -> usize {
+        self.scheduler_index
+    }
+
+    /// Set the index of the scheduler running this loop
+    pub fn set_scheduler_index(&mut self, index: usize) {
+        self.scheduler_index = index;
+    }
+
     /// Get current instruction pointer
     pub fn instruction_ptr(&self) -> ErtsCodePtr {
         self.instruction_ptr
@@ -224,9 +311,32 @@ fn init_emulator_finish() -> Result<(), EmulatorLoopError> {
 /// * `Ok(Some(Arc<Process>))` - Process yielded, should be rescheduled
 /// * `Ok(None)` - Process exited normally
 /// * `Err(EmulatorLoopError)` - Error during execution
+///
+/// The reductions used and the time spent are added to the statistics of
+/// the loop's scheduler however the process stops.
 pub fn process_main(
     emulator_loop: &mut EmulatorLoop,
     init_done: Arc<AtomicBool>,
+) -> Result<Option<Arc<Process>>, EmulatorLoopError> {
+    // No reductions are used until the process is set up to run
+    emulator_loop.set_reds_in(0);
+    emulator_loop.set_fcalls(0);
+    let started = Instant::now();
+
+    let result = run_process(emulator_loop, init_done);
+
+    emulator_loop.calculate_reds_used(false);
+    let statistics = get_global_statistics();
+    let scheduler = emulator_loop.scheduler_index;
+    statistics.add_reductions(scheduler, emulator_loop.reds_used().max(0) as u64);
+    statistics.add_runtime(scheduler, started.elapsed());
+    result
+}
+
+/// Run the current process until it yields or exits (body of [`process_main`])
+fn run_process(
+    emulator_loop: &mut EmulatorLoop,
+    init_done: Arc<AtomicBool>,
 ) -> Result<Option<Arc<Process>>, EmulatorLoopError> {
     // Check if initialization is needed
     if !init_done.load(Ordering::Acquire) {
@@ -266,7 +376,7 @@ pub fn process_main(
     emulator_loop.set_fcalls(1000);  // Remaining reductions
     
     // Execute instructions in a loop until process yields or exits
-    use super::instruction_execution::{InstructionExecutor, DefaultInstructionExecutor, InstructionResult, next_instruction};
+    use super::instruction_execution::{InstructionExecutor, DefaultInstructionExecutor, InstructionResult};
     let executor = DefaultInstructionExecutor;
     
     let mut max_iterations = 1000; // Limit iterations to prevent infinite loops
@@ -289,6 +399,8 @@ pub fn process_main(
             return Ok(None);
         }
         
+        let opcode = decode_instruction(current_ip).ok().map(|decoded| (decoded.opcode, decoded.operands));
+
         // Execute the instruction
         let result = executor.execute_instruction(
             &process,
@@ -312,6 +424,9 @@ pub fn process_main(
             }
             InstructionResult::Jump(target_ip) => {
                 // Jump to new instruction pointer (call/return)
+                if let Some((op @ (opcodes::CALL | opcodes::CALL_LAST | opcodes::CALL_ONLY | opcodes::APPLY | opcodes::APPLY_LAST), operands)) = &opcode {
+                    enter_function(emulator_loop, &process, *op, operands, current_ip, target_ip, &x_regs);
+                }
                 emulator_loop.set_instruction_ptr(target_ip);
                 // Decrement reductions
                 emulator_loop.set_fcalls(emulator_loop.fcalls() - 1);
@@ -323,8 +438,19 @@ pub fn process_main(
                 return Ok(Some(process));
             }
             InstructionResult::NormalExit => {
-                // Process exited normally
-                return Ok(None);
+                // Return to the caller if there is one, otherwise the process exited normally
+                let returning = matches!(opcode, Some((opcodes::RETURN | opcodes::APPLY_LAST, _)));
+                match emulator_loop.call_stack.pop().filter(|_| returning) {
+                    Some(frame) => {
+                        if frame.traced {
+                            emulator_loop.call_trace.trace_return_to(&process, frame.mfa);
+                        }
+                        emulator_loop.current_mfa = frame.mfa;
+                        emulator_loop.set_instruction_ptr(frame.return_ip);
+                        emulator_loop.set_fcalls(emulator_loop.fcalls() - 1);
+                    }
+                    None => return Ok(None),
+                }
             }
             InstructionResult::ErrorExit => {
                 // Process exited with error
@@ -351,9 +477,52 @@ pub fn process_main(
     Ok(Some(process))
 }
 
+/// Enter the function called by a call or apply instruction
+///
+/// Emits the `call` trace event if the call is traced, and pushes a return
+/// frame for non-tail calls. A traced tail call marks the frame it will
+/// return through, so `return_to` is still reported.
+///
+/// # Arguments
+/// * `opcode` - Call or apply instruction opcode
+/// * `operands` - Call instruction operands (arity first)
+/// * `call_ip` - Address of the call instruction
+/// * `target_ip` - Entry of the called function
+/// * `x_regs` - X registers holding the arguments
+fn enter_function(
+    emulator_loop: &mut EmulatorLoop,
+    process: &Process,
+    opcode: u8,
+    operands: &[u64],
+    call_ip: ErtsCodePtr,
+    target_ip: ErtsCodePtr,
+    x_regs: &[Eterm],
+) {
+    // SAFETY: call targets are function entries in loaded code
+    let mfa = unsafe { code_to_mfa(target_ip) };
+    let arity = operands.first().map_or(0, |&arity| arity as usize).min(x_regs.len());
+    let traced = mfa.is_some_and(|mfa| emulator_loop.call_trace.trace_call(process, &mfa, &x_regs[..arity]));
+
+    if opcode == opcodes::CALL || opcode == opcodes::APPLY {
+        if let Some(return_ip) = next_instruction(call_ip) {
+            emulator_loop.call_stack.push(CallFrame {
+                return_ip,
+                mfa: emulator_loop.current_mfa,
+                traced,
+            });
+        }
+    } else if traced {
+        if let Some(frame) = emulator_loop.call_stack.last_mut() {
+            frame.traced = true;
+        }
+    }
+    emulator_loop.current_mfa = mfa;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use entities_process::ProcessId;
     
     #[test]
     fn test_emulator_loop_creation() {
@@ -426,6 +595,111 @@ mod tests {
         assert!(result2.is_ok());
     }
     
+    #[derive(Default)]
+    struct CollectingTracer(Mutex<Vec<crate::call_trace::TraceEvent>>);
+
+    impl crate::call_trace::CallTracer for CollectingTracer {
+        fn trace(&self, event: crate::call_trace::TraceEvent) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    /// `caller/0` calling `callee/1`, both in module 1
+    fn call_return_code() -> Vec<u8> {
+        vec![
+            opcodes::FUNC_INFO, 1, 10, 0,
+            opcodes::CALL, 1, 8,
+            opcodes::RETURN,
+            opcodes::FUNC_INFO, 1, 20, 1,
+            opcodes::MOVE, 0, 1,
+            opcodes::RETURN,
+        ]
+    }
+
+    fn run_call_return(pid: ProcessId, call_trace: Arc<CallTrace>) -> EmulatorLoop {
+        let code = call_return_code();
+        let mut emulator_loop = EmulatorLoop::new();
+        emulator_loop.set_call_trace(call_trace);
+        emulator_loop.set_current_process(Some(Arc::new(Process::new(pid))));
+        emulator_loop.set_current_mfa(Some(CodeMfa::new(1, 10, 0)));
+        emulator_loop.set_instruction_ptr(unsafe { code.as_ptr().add(4) });
+
+        let result = process_main(&mut emulator_loop, Arc::new(AtomicBool::new(true)));
+        assert!(matches!(result, Ok(None)));
+        emulator_loop
+    }
+
+    #[test]
+    fn test_process_main_call_and_return_to_trace() {
+        use crate::call_trace::{TraceEvent, TracePattern};
+        use usecases_bifs::trace::{TraceBif, TraceFlags, TraceTarget};
+
+        let flags = TraceFlags { call: true, return_to: true, ..Default::default() };
+        TraceBif::trace(None, TraceTarget::Process(40683), true, flags).unwrap();
+        let call_trace = Arc::new(CallTrace::new());
+        let tracer = Arc::new(CollectingTracer::default());
+        call_trace.set_tracer(Some(tracer.clone()));
+        call_trace.set_trace_pattern(TracePattern::new(1, Some(20), None));
+
+        let emulator_loop = run_call_return(40683, call_trace);
+        assert_eq!(emulator_loop.call_depth(), 0);
+        assert_eq!(emulator_loop.current_mfa(), Some(CodeMfa::new(1, 10, 0)));
+
+        let events = tracer.0.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            &events[0],
+            TraceEvent::Call { pid: 40683, mfa, args } if *mfa == CodeMfa::new(1, 20, 1) && args.len() == 1
+        ));
+        assert_eq!(events[1], TraceEvent::ReturnTo { pid: 40683, mfa: Some(CodeMfa::new(1, 10, 0)) });
+    }
+
+    #[test]
+    fn test_process_main_untraced_call() {
+        use crate::call_trace::TracePattern;
+
+        let call_trace = Arc::new(CallTrace::new());
+        let tracer = Arc::new(CollectingTracer::default());
+        call_trace.set_tracer(Some(tracer.clone()));
+        call_trace.set_trace_pattern(TracePattern::new(1, None, None));
+
+        run_call_return(40684, call_trace);
+        assert!(tracer.0.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_backtrace_innermost_first_and_limited() {
+        let mut emulator_loop = EmulatorLoop::new();
+        for function in 0..10 {
+            emulator_loop.call_stack.push(CallFrame {
+                return_ip: std::ptr::null(),
+                mfa: Some(CodeMfa::new(1, function, 0)),
+                traced: false,
+            });
+        }
+        emulator_loop.set_current_mfa(Some(CodeMfa::new(1, 99, 0)));
+
+        let backtrace = emulator_loop.backtrace();
+        assert_eq!(backtrace.len(), process_defaults().backtrace_depth);
+        assert_eq!(backtrace[0], CodeMfa::new(1, 99, 0));
+        assert_eq!(backtrace[1], CodeMfa::new(1, 9, 0));
+    }
+
+    #[test]
+    fn test_process_main_counts_scheduler_reductions() {
+        let code = call_return_code();
+        let mut emulator_loop = EmulatorLoop::new();
+        emulator_loop.set_scheduler_index(9);
+        emulator_loop.set_call_trace(Arc::new(CallTrace::new()));
+        emulator_loop.set_current_process(Some(Arc::new(Process::new(40687))));
+        emulator_loop.set_instruction_ptr(unsafe { code.as_ptr().add(4) });
+        process_main(&mut emulator_loop, Arc::new(AtomicBool::new(true))).unwrap();
+
+        assert!(emulator_loop.reds_used() > 0);
+        let reductions = get_global_statistics().scheduler_reductions();
+        assert_eq!(reductions[9], emulator_loop.reds_used() as u64);
+    }
+
     #[test]
     fn test_process_main_initialization() {
         let mut emulator_loop = EmulatorLoop::new();
@@ -17,8 +17,12 @@ pub mod opcodes {
     pub const CALL_LAST: u8 = 5;
     pub const CALL_ONLY: u8 = 6;
     pub const CALL_EXT: u8 = 7;
+    pub const BIF1: u8 = 10;
+    pub const BIF2: u8 = 11;
     // ... more opcodes ...
     pub const MOVE: u8 = 64;
+    pub const APPLY: u8 = 112;
+    pub const APPLY_LAST: u8 = 113;
     pub const RETURN: u8 = 75; // Approximate - return is a specific instruction
 }
 
@@ -75,6 +79,10 @@ pub fn decode_instruction(instruction_ptr: ErtsCodePtr) -> Result<DecodedInstruc
             opcodes::CALL_LAST => (3, 4),
             opcodes::CALL_ONLY => (2, 3),
             opcodes::CALL_EXT => (2, 3),
+            opcodes::BIF1 => (4, 5),
+            opcodes::BIF2 => (5, 6),
+            opcodes::APPLY => (1, 2),
+            opcodes::APPLY_LAST => (2, 3),
             opcodes::RETURN => (0, 1),
             opcodes::LABEL => (1, 2),
             opcodes::FUNC_INFO => (3, 4),
@@ -5,7 +5,14 @@
 //!
 //! Based on the instruction execution framework in beam_emu.c
 
+use entities_data_handling::AtomEncoding;
+use entities_process::term_tags::{make_atom, make_small, TAG_IMMED1_SIZE, NIL};
 use entities_process::{Process, ErtsCodePtr, Eterm};
+use infrastructure_bif_dispatcher::{dispatch_bif, resolve_apply, ApplyTables, ApplyTarget, BifDispatcherError};
+use infrastructure_utilities::atom_table::get_global_atom_table;
+use usecases_bifs::guard::{GuardSandbox, GUARD_BIFS};
+use usecases_bifs::op::ErlangTerm;
+use crate::call_trace::guard_term;
 use crate::instruction_decoder::{decode_instruction, opcodes};
 
 /// Instruction execution result
@@ -62,7 +69,7 @@ pub struct DefaultInstructionExecutor;
 impl InstructionExecutor for DefaultInstructionExecutor {
     fn execute_instruction(
         &self,
-        _process: &Process,
+        process: &Process,
         instruction_ptr: ErtsCodePtr,
         registers: &mut [Eterm],
         _heap: &mut [Eterm],
@@ -132,6 +139,26 @@ impl InstructionExecutor for DefaultInstructionExecutor {
                 }
                 Ok(InstructionResult::Continue)
             }
+            opcodes::APPLY | opcodes::APPLY_LAST => {
+                // apply Arity / apply_last Arity Deallocate
+                // x0..x(Arity-1) hold the arguments, x(Arity) the module
+                // and x(Arity+1) the function
+                let arity = decoded.operands.first().map_or(0, |&arity| arity as usize);
+                if arity + 2 > registers.len() {
+                    return Ok(InstructionResult::ErrorExit);
+                }
+                let (module, function) = (registers[arity], registers[arity + 1]);
+                let tables = ApplyTables::global();
+                let target = match resolve_apply(&tables, process, module, function, &registers[..arity]) {
+                    Ok(target) => target,
+                    Err(_) => return Ok(InstructionResult::ErrorExit),
+                };
+                Ok(enter_apply_target(&tables, process, decoded.opcode, target, instruction_ptr, registers))
+            }
+            opcodes::BIF1 | opcodes::BIF2 => {
+                // bif1 Fail Bif Arg Dst / bif2 Fail Bif Arg1 Arg2 Dst
+                Ok(execute_guard_bif(process, &decoded.operands, instruction_ptr, registers))
+            }
             opcodes::RETURN => {
                 // return - exit function normally
                 Ok(InstructionResult::NormalExit)
@@ -145,6 +172,116 @@ impl InstructionExecutor for DefaultInstructionExecutor {
     }
 }
 
+/// Continue an apply in the code it resolved to
+///
+/// Exported code is jumped to with the arguments in the X registers. A BIF
+/// is called in place, its result in x0; after `apply_last` the function
+/// then returns. A BIF that yields is called again when the process is next
+/// scheduled, by executing the apply again. Local funs have no code pointer
+/// to jump to, so applying one through `erlang:apply/2` fails.
+fn enter_apply_target(
+    tables: &ApplyTables<'_>,
+    process: &Process,
+    opcode: u8,
+    target: ApplyTarget,
+    instruction_ptr: ErtsCodePtr,
+    registers: &mut [Eterm],
+) -> InstructionResult {
+    let target = match target {
+        ApplyTarget::Bif { key, args } => {
+            match dispatch_bif(tables.registry, process, key.module, key.function, &args, instruction_ptr) {
+                Ok(result) => {
+                    registers[0] = result;
+                    return if opcode == opcodes::APPLY_LAST {
+                        InstructionResult::NormalExit
+                    } else {
+                        InstructionResult::Continue
+                    };
+                }
+                Err(BifDispatcherError::Yielded) => return InstructionResult::Yield,
+                Err(BifDispatcherError::Trap(target)) => target,
+                Err(_) => return InstructionResult::ErrorExit,
+            }
+        }
+        target => target,
+    };
+    match target {
+        ApplyTarget::Code { export, args } if args.len() <= registers.len() => match export.code_ptr {
+            Some(code_ptr) => {
+                registers[..args.len()].copy_from_slice(&args);
+                InstructionResult::Jump(code_ptr)
+            }
+            None => InstructionResult::ErrorExit,
+        },
+        _ => InstructionResult::ErrorExit,
+    }
+}
+
+/// Call a guard BIF in a [`GuardSandbox`]
+///
+/// `Bif` is the index of the BIF in [`GUARD_BIFS`]; the arguments are read
+/// from and the result stored in X registers. A call that fails, runs out of
+/// reductions or has an argument or result that is not an immediate jumps
+/// to the fail label, or without one (`Fail` 0) exits the process with an
+/// error.
+///
+/// Based on the `bif1` and `bif2` instructions in bif_instrs.tab
+fn execute_guard_bif(
+    process: &Process,
+    operands: &[u64],
+    instruction_ptr: ErtsCodePtr,
+    registers: &mut [Eterm],
+) -> InstructionResult {
+    let [fail, bif, args @ .., dst] = operands else {
+        return InstructionResult::ErrorExit;
+    };
+    let dst = *dst as usize;
+    let Some(&(name, arity)) = GUARD_BIFS.get(*bif as usize) else {
+        return InstructionResult::ErrorExit;
+    };
+    if arity != args.len() || dst >= registers.len() {
+        return InstructionResult::ErrorExit;
+    }
+
+    let result = args
+        .iter()
+        .map(|&arg| registers.get(arg as usize).copied().and_then(guard_term))
+        .collect::<Option<Vec<_>>>()
+        .and_then(|terms| {
+            GuardSandbox::new(GuardSandbox::DEFAULT_REDUCTIONS)
+                .with_self(process.id())
+                .call(name, &terms)
+                .ok()
+        })
+        .and_then(|result| immediate_term(&result));
+    match result {
+        Some(result) => {
+            registers[dst] = result;
+            InstructionResult::Continue
+        }
+        None if *fail == 0 => InstructionResult::ErrorExit,
+        None => InstructionResult::Jump(unsafe { instruction_ptr.offset(*fail as isize) }),
+    }
+}
+
+/// Encode a guard BIF result as an immediate
+///
+/// # Returns
+/// The small integer, atom or nil, or `None` for any other term
+fn immediate_term(term: &ErlangTerm) -> Option<Eterm> {
+    match term {
+        ErlangTerm::Nil => Some(NIL),
+        ErlangTerm::Integer(value) if (value << TAG_IMMED1_SIZE) >> TAG_IMMED1_SIZE == *value => {
+            Some(make_small(*value))
+        }
+        ErlangTerm::Atom(name) => get_global_atom_table()
+            .put_index(name.as_bytes(), AtomEncoding::SevenBitAscii, false)
+            .ok()
+            .map(make_atom),
+        _ => None,
+    }
+}
+
 /// Check if instruction pointer is valid
 ///
 /// Based on VALID_INSTR macro from beam_emu.c
@@ -223,4 +360,106 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), InstructionResult::Continue);
     }
+
+    struct SecondArg;
+
+    impl infrastructure_bif_dispatcher::initialization::BifFunction for SecondArg {
+        fn call(&self, _process: &Process, args: &[Eterm], _ip: ErtsCodePtr) -> Eterm {
+            args[1]
+        }
+    }
+
+    fn atom(name: &str) -> Eterm {
+        use entities_data_handling::AtomEncoding;
+        use infrastructure_utilities::atom_table::get_global_atom_table;
+
+        let index = get_global_atom_table().put_index(name.as_bytes(), AtomEncoding::SevenBitAscii, false).unwrap();
+        entities_process::term_tags::make_atom(index)
+    }
+
+    fn execute(code: &[u8], registers: &mut [Eterm]) -> InstructionResult {
+        let process = Process::new(1);
+        DefaultInstructionExecutor
+            .execute_instruction(&process, code.as_ptr(), registers, &mut [])
+            .unwrap()
+    }
+
+    #[test]
+    fn test_apply_bif() {
+        use infrastructure_bif_dispatcher::get_global_registry;
+        use std::sync::Arc;
+
+        let (m, f) = (atom("exec_apply_m"), atom("exec_apply_bif"));
+        get_global_registry().register(m, f, 2, Arc::new(SecondArg)).unwrap();
+
+        let mut registers = vec![0x3B, 0x4F, m, f];
+        assert_eq!(execute(&[opcodes::APPLY, 2], &mut registers), InstructionResult::Continue);
+        assert_eq!(registers[0], 0x4F);
+
+        let mut registers = vec![0x3B, 0x5F, m, f];
+        assert_eq!(execute(&[opcodes::APPLY_LAST, 2, 0], &mut registers), InstructionResult::NormalExit);
+        assert_eq!(registers[0], 0x5F);
+    }
+
+    #[test]
+    fn test_apply_export() {
+        use entities_io_operations::get_global_export_table;
+
+        let code = [opcodes::RETURN];
+        let (m, f) = (atom("exec_apply_m"), atom("exec_apply_code"));
+        let exports = get_global_export_table();
+        exports.put((m >> 6) as u32, (f >> 6) as u32, 1);
+        exports.update_export_code_ptr((m >> 6) as u32, (f >> 6) as u32, 1, code.as_ptr());
+
+        let mut registers = vec![0x2F, m, f];
+        assert_eq!(execute(&[opcodes::APPLY, 1], &mut registers), InstructionResult::Jump(code.as_ptr()));
+        assert_eq!(registers[0], 0x2F);
+    }
+
+    #[test]
+    fn test_apply_errors() {
+        let (m, f) = (atom("exec_apply_m"), atom("exec_apply_missing"));
+        assert_eq!(execute(&[opcodes::APPLY, 0], &mut [m, f]), InstructionResult::ErrorExit);
+        assert_eq!(execute(&[opcodes::APPLY, 0], &mut [0x2F, f]), InstructionResult::ErrorExit);
+        assert_eq!(execute(&[opcodes::APPLY, 5], &mut [m, f]), InstructionResult::ErrorExit);
+    }
+
+    fn guard_bif(name: &str, arity: usize) -> u8 {
+        GUARD_BIFS.iter().position(|&bif| bif == (name, arity)).unwrap() as u8
+    }
+
+    #[test]
+    fn test_guard_bif() {
+        let (ok, error) = (atom("ok"), atom("error"));
+        let mut registers = vec![make_small(3), make_small(4), 0];
+        let code = [opcodes::BIF2, 0, guard_bif("+", 2), 0, 1, 2];
+        assert_eq!(execute(&code, &mut registers), InstructionResult::Continue);
+        assert_eq!(registers[2], make_small(7));
+
+        let mut registers = vec![ok, 0];
+        let code = [opcodes::BIF1, 0, guard_bif("is_atom", 1), 0, 1];
+        assert_eq!(execute(&code, &mut registers), InstructionResult::Continue);
+        assert_eq!(registers[1], atom("true"));
+
+        // A failing guard BIF jumps to the fail label
+        let mut registers = vec![error, make_small(1), 0];
+        let code = [opcodes::BIF2, 6, guard_bif("+", 2), 0, 1, 2, opcodes::RETURN];
+        assert_eq!(execute(&code, &mut registers), InstructionResult::Jump(code[6..].as_ptr()));
+        assert_eq!(registers[2], 0);
+    }
+
+    #[test]
+    fn test_guard_bif_errors() {
+        let mut registers = vec![atom("error"), make_small(1), 0];
+        // Without a fail label the process exits
+        let code = [opcodes::BIF2, 0, guard_bif("+", 2), 0, 1, 2];
+        assert_eq!(execute(&code, &mut registers), InstructionResult::ErrorExit);
+        // Unknown BIF, wrong arity and a destination out of range
+        let code = [opcodes::BIF1, 0, u8::MAX, 0, 1];
+        assert_eq!(execute(&code, &mut registers), InstructionResult::ErrorExit);
+        let code = [opcodes::BIF1, 0, guard_bif("+", 2), 0, 1];
+        assert_eq!(execute(&code, &mut registers), InstructionResult::ErrorExit);
+        let code = [opcodes::BIF1, 0, guard_bif("is_atom", 1), 0, 9];
+        assert_eq!(execute(&code, &mut registers), InstructionResult::ErrorExit);
+    }
 }
@@ -22,6 +22,9 @@
 //! - **[`registers`](registers/index.html)**: Register management functions
 //!   (copy_in_registers, copy_out_registers)
 //!
+//! - **[`call_trace`](call_trace/index.html)**: `call` and `return_to` trace
+//!   events for traced processes calling functions with trace patterns
+//!
 //! ## Architecture
 //!
 //! This crate is based on the C implementation in `beam_emu.c`. It depends on:
@@ -43,6 +46,7 @@ pub mod registers;
 pub mod instruction_execution;
 pub mod instruction_decoder;
 pub mod process_executor_impl;
+pub mod call_trace;
 
 #[cfg(test)]
 mod test_code;
@@ -52,5 +56,6 @@ pub use registers::{copy_in_registers, copy_out_registers, RegisterManager};
 pub use instruction_execution::{InstructionResult, InstructionExecutor, DefaultInstructionExecutor, is_valid_instruction, next_instruction};
 pub use instruction_decoder::{decode_instruction, get_instruction_size, opcodes};
 pub use process_executor_impl::EmulatorLoopExecutor;
+pub use call_trace::{code_to_mfa, get_global_call_trace, CallTrace, CallTracer, CodeMfa, GuardMatchSpec, MatchSpec, TraceEvent, TracePattern};
 
 
@@ -32,28 +32,92 @@
 
 use std::collections::HashMap;
 
+use infrastructure_utilities::memory::{get_global_memory_counters, MemoryCounters, MemoryKind};
+use usecases_bifs::guard::{GuardFailure, GuardSandbox};
+use usecases_bifs::op::ErlangTerm;
+
+/// Bytes an object takes in a table
+const OBJECT_BYTES: usize = std::mem::size_of::<(u64, u64)>();
+
 /// ETS table
+///
+/// The table and its objects are counted as ETS memory for
+/// `erlang:memory(ets)` until the table is dropped.
 pub struct EtsTable {
     data: HashMap<u64, u64>, // Placeholder - actual implementation needs proper term types
+    /// Counters the table's memory is counted on
+    memory: &'static MemoryCounters,
 }
 
 impl EtsTable {
     /// Create a new ETS table
     pub fn new() -> Self {
+        Self::with_memory_counters(get_global_memory_counters())
+    }
+
+    /// Create a table counting its memory on `counters`
+    fn with_memory_counters(memory: &'static MemoryCounters) -> Self {
+        memory.add(MemoryKind::Ets, std::mem::size_of::<Self>());
         Self {
             data: HashMap::new(),
+            memory,
         }
     }
 
+    /// Get the bytes the table and its objects take
+    pub fn memory_bytes(&self) -> usize {
+        std::mem::size_of::<Self>() + self.data.len() * OBJECT_BYTES
+    }
+
     /// Insert a key-value pair
     pub fn insert(&mut self, key: u64, value: u64) -> Option<u64> {
-        self.data.insert(key, value)
+        let previous = self.data.insert(key, value);
+        if previous.is_none() {
+            self.memory.add(MemoryKind::Ets, OBJECT_BYTES);
+        }
+        previous
     }
 
     /// Lookup a value
     pub fn lookup(&self, key: u64) -> Option<u64> {
         self.data.get(&key).copied()
     }
+
+    /// Select the objects a match specification guard accepts
+    ///
+    /// Each object is bound as `Key` and `Value` and the guard is evaluated
+    /// on them in a [`GuardSandbox`], as `ets:select/2` evaluates the guards
+    /// of `[{{'$1', '$2'}, Guards, ['$_']}]`. An object is selected only if
+    /// the guard is `true`; one whose guard fails or runs out of reductions
+    /// is skipped, as is one that does not fit in small integers.
+    ///
+    /// Based on `db_prog_match()` from erl_db_util.c
+    ///
+    /// # Arguments
+    /// * `guard` - Guard on the key and value of an object
+    /// * `reductions` - Reduction budget of the guard for each object
+    pub fn select<G>(&self, guard: G, reductions: usize) -> Vec<(u64, u64)>
+    where
+        G: Fn(&mut GuardSandbox, &ErlangTerm, &ErlangTerm) -> Result<ErlangTerm, GuardFailure>,
+    {
+        self.data
+            .iter()
+            .filter(|(&key, &value)| {
+                let (Ok(key), Ok(value)) = (i64::try_from(key), i64::try_from(value)) else {
+                    return false;
+                };
+                let (key, value) = (ErlangTerm::Integer(key), ErlangTerm::Integer(value));
+                GuardSandbox::new(reductions).eval(|sandbox| guard(sandbox, &key, &value))
+            })
+            .map(|(&key, &value)| (key, value))
+            .collect()
+    }
+}
+
+impl Drop for EtsTable {
+    fn drop(&mut self) {
+        self.memory.sub(MemoryKind::Ets, self.memory_bytes());
+    }
 }
 
 #[cfg(test)]
@@ -66,5 +130,41 @@ mod tests {
         table.insert(1, 100);
         assert_eq!(table.lookup(1), Some(100));
     }
+
+    #[test]
+    fn test_ets_memory_accounting() {
+        let counters: &'static MemoryCounters = Box::leak(Box::new(MemoryCounters::new()));
+        let mut table = EtsTable::with_memory_counters(counters);
+        table.insert(1, 100);
+        table.insert(2, 200);
+        // Replacing an object takes no more memory
+        table.insert(1, 101);
+        assert_eq!(table.memory_bytes(), std::mem::size_of::<EtsTable>() + 2 * OBJECT_BYTES);
+        assert_eq!(counters.get(MemoryKind::Ets), table.memory_bytes());
+
+        drop(table);
+        assert_eq!(counters.get(MemoryKind::Ets), 0);
+    }
+
+    #[test]
+    fn test_ets_select() {
+        let mut table = EtsTable::new();
+        for key in 1..=4 {
+            table.insert(key, key * 10);
+        }
+        table.insert(5, u64::MAX);
+
+        // [{{'$1', '$2'}, [{'>', '$2', 20}], ['$_']}]
+        let mut selected = table.select(|guard, _, value| guard.call(">", &[value.clone(), ErlangTerm::Integer(20)]), 10);
+        selected.sort();
+        assert_eq!(selected, vec![(3, 30), (4, 40)]);
+
+        // Guards that fail or run out of reductions select nothing
+        assert!(table.select(|guard, key, _| guard.call("hd", std::slice::from_ref(key)), 10).is_empty());
+        let looping = |guard: &mut GuardSandbox, key: &ErlangTerm, _: &ErlangTerm| loop {
+            guard.call("is_integer", std::slice::from_ref(key))?;
+        };
+        assert!(table.select(looping, 10).is_empty());
+    }
 }
 
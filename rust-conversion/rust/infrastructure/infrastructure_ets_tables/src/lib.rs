@@ -14,11 +14,13 @@
 //!
 //! - **[`ets_table`](ets_table/index.html)**: ETS table implementation providing
 //!   key-value storage with various table types (set, ordered_set, bag, duplicate_bag)
+//!   and `select` with match specification guards evaluated in a guard sandbox
 //!
 //! ## Architecture
 //!
 //! This crate is based on the C implementation in `cgi_echo.c` and related ETS files.
-//! It depends on the Entities and Adapters layers.
+//! It depends on the Entities and Adapters layers, and on the guard BIFs of the
+//! Use Cases layer.
 //!
 //! ## See Also
 //!
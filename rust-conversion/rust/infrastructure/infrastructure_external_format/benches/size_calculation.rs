@@ -0,0 +1,49 @@
+//! Size Calculation Benchmarks
+//!
+//! Measures [`erts_encode_ext_size`] and [`erts_encode_ext_size_shared`] on a
+//! nested term of about 100 MB: a tuple of lists of `{Index, Binary}`
+//! tuples holding 1 KB binaries, half of which repeat.
+//!
+//! Run with `cargo bench -p infrastructure_external_format --bench size_calculation`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use entities_data_handling::term_hashing::Term;
+use infrastructure_external_format::{erts_encode_ext_size, erts_encode_ext_size_shared};
+
+const LISTS: usize = 100;
+const LIST_LENGTH: usize = 1000;
+const BINARY_SIZE: usize = 1024;
+
+fn nested_term() -> Term {
+    let lists = (0..LISTS)
+        .map(|i| {
+            (0..LIST_LENGTH).rev().fold(Term::Nil, |tail, j| {
+                // Odd elements carry the same binary in every list
+                let fill = if j % 2 == 0 { (i * LIST_LENGTH + j) as u8 } else { 0 };
+                let data = vec![fill; BINARY_SIZE];
+                let element = Term::Tuple(vec![
+                    Term::Small(j as i64),
+                    Term::Binary { data, bit_offset: 0, bit_size: BINARY_SIZE * 8 },
+                ]);
+                Term::List { head: Box::new(element), tail: Box::new(tail) }
+            })
+        })
+        .collect();
+    Term::Tuple(lists)
+}
+
+fn bench_size(c: &mut Criterion) {
+    let term = nested_term();
+    let mut group = c.benchmark_group("encode_ext_size_100mb");
+    group.sample_size(10);
+    group.bench_function("size", |b| {
+        b.iter(|| erts_encode_ext_size(black_box(&term), None).unwrap())
+    });
+    group.bench_function("size_shared", |b| {
+        b.iter(|| erts_encode_ext_size_shared(black_box(&term), None).unwrap().shared.len())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_size);
+criterion_main!(benches);
@@ -17,6 +17,7 @@ use infrastructure_code_loading::encode_fun::{encode_fun, ErlangFunType};
 use infrastructure_bignum_encoding::BignumCodec;
 use std::collections::HashSet;
 use super::VERSION_MAGIC;
+use super::iovec::BinaryRefs;
 
 /// Encoding error types
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -49,7 +50,7 @@ pub fn enc_term(term: &Term, atom_table: Option<&AtomTable>) -> Result<Vec<u8>,
     let mut buf = vec![VERSION_MAGIC];
     
     // Encode the term using internal helper
-    enc_term_int(&mut buf, term, atom_table)?;
+    enc_term_int(&mut buf, term, atom_table, None)?;
     
     Ok(buf)
 }
@@ -58,7 +59,16 @@ pub fn enc_term(term: &Term, atom_table: Option<&AtomTable>) -> Result<Vec<u8>,
 ///
 /// Based on `enc_term_int()` from external.c. This function encodes a term
 /// without the version magic byte (used internally).
-fn enc_term_int(buf: &mut Vec<u8>, term: &Term, atom_table: Option<&AtomTable>) -> Result<(), EncodeError> {
+///
+/// When `refs` is given, binaries at or above its threshold are not copied:
+/// only their `BINARY_EXT` header is written and the data is recorded in
+/// `refs` at the current end of `buf`.
+pub(crate) fn enc_term_int<'a>(
+    buf: &mut Vec<u8>,
+    term: &'a Term,
+    atom_table: Option<&AtomTable>,
+    mut refs: Option<&mut BinaryRefs<'a>>,
+) -> Result<(), EncodeError> {
     match term {
         Term::Nil => {
             // NIL_EXT = 106
@@ -95,7 +105,7 @@ fn enc_term_int(buf: &mut Vec<u8>, term: &Term, atom_table: Option<&AtomTable>)
             
             // Encode each element
             for element in elements {
-                enc_term_int(buf, element, atom_table)?;
+                enc_term_int(buf, element, atom_table, refs.as_deref_mut())?;
             }
             Ok(())
         }
@@ -165,11 +175,11 @@ fn enc_term_int(buf: &mut Vec<u8>, term: &Term, atom_table: Option<&AtomTable>)
                 match current {
                     Term::Nil => break,
                     Term::List { head: h, tail: t } => {
-                        enc_term_int(buf, h, atom_table)?;
+                        enc_term_int(buf, h, atom_table, refs.as_deref_mut())?;
                         current = t.as_ref();
                     }
                     _ => {
-                        enc_term_int(buf, current, atom_table)?;
+                        enc_term_int(buf, current, atom_table, refs.as_deref_mut())?;
                         break;
                     }
                 }
@@ -180,6 +190,15 @@ fn enc_term_int(buf: &mut Vec<u8>, term: &Term, atom_table: Option<&AtomTable>)
             Ok(())
         }
         Term::Binary { data, bit_offset: _, bit_size: _ } => {
+            if let Some(refs) = refs.filter(|refs| data.len() >= refs.threshold) {
+                let len = u32::try_from(data.len())
+                    .map_err(|_| EncodeError::InvalidTerm("Binary too large".to_string()))?;
+                // BINARY_EXT = 109, followed by the 4-byte length
+                buf.push(109);
+                buf.extend_from_slice(&len.to_be_bytes());
+                refs.refs.push((buf.len(), data));
+                return Ok(());
+            }
             // Encode binary
             // encode_binary only takes buf and data, bit_size is not used in the current implementation
             encode_binary(buf, data)
@@ -199,8 +218,8 @@ fn enc_term_int(buf: &mut Vec<u8>, term: &Term, atom_table: Option<&AtomTable>)
             
             // Encode each key-value pair
             for (key, value) in entries {
-                enc_term_int(buf, key, atom_table)?;
-                enc_term_int(buf, value, atom_table)?;
+                enc_term_int(buf, key, atom_table, refs.as_deref_mut())?;
+                enc_term_int(buf, value, atom_table, refs.as_deref_mut())?;
             }
             Ok(())
         }
@@ -0,0 +1,186 @@
+//! I/O Vector Encoding Module
+//!
+//! Provides encoding of terms to external format as an I/O vector, for
+//! `erlang:term_to_iovec/1` and for vectored writes in the distribution
+//! layer. Based on the iovec output of `erts_term_to_binary_int()` in
+//! external.c.
+//!
+//! The encoding is the same as [`enc_term`](crate::encoding::enc_term), but
+//! binaries large enough to be off-heap (more than `ERL_ONHEAP_BIN_LIMIT`
+//! bytes) are not copied into the output buffer: the vector references
+//! their data, between the encoded bytes that precede and follow them.
+
+use entities_data_handling::atom::AtomTable;
+use entities_data_handling::binary::ERL_ONHEAP_BIN_LIMIT;
+use entities_data_handling::term_hashing::Term;
+use super::encoding::{enc_term_int, EncodeError};
+use super::VERSION_MAGIC;
+
+/// Smallest binary referenced rather than copied by [`erts_encode_ext_iovec`]
+pub const IOVEC_BINARY_THRESHOLD: usize = ERL_ONHEAP_BIN_LIMIT + 1;
+
+/// Binaries referenced by an encoding in progress
+pub(crate) struct BinaryRefs<'a> {
+    /// Smallest binary to reference
+    pub(crate) threshold: usize,
+    /// Referenced binaries, with the offset in the output buffer they follow
+    pub(crate) refs: Vec<(usize, &'a [u8])>,
+}
+
+/// A term encoded to external format as an I/O vector
+///
+/// Borrows the data of the large binaries of the encoded term.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TermIovec<'a> {
+    buffer: Vec<u8>,
+    refs: Vec<(usize, &'a [u8])>,
+}
+
+impl<'a> TermIovec<'a> {
+    /// Get the segments of the vector, in order
+    ///
+    /// Encoded bytes and referenced binaries alternate; no segment is empty.
+    pub fn segments(&self) -> Vec<&[u8]> {
+        let mut segments = Vec::with_capacity(2 * self.refs.len() + 1);
+        let mut start = 0;
+        for &(offset, data) in &self.refs {
+            if offset > start {
+                segments.push(&self.buffer[start..offset]);
+            }
+            if !data.is_empty() {
+                segments.push(data);
+            }
+            start = offset;
+        }
+        if self.buffer.len() > start {
+            segments.push(&self.buffer[start..]);
+        }
+        segments
+    }
+
+    /// Get the total size of the encoding in bytes
+    pub fn len(&self) -> usize {
+        self.buffer.len() + self.refs.iter().map(|(_, data)| data.len()).sum::<usize>()
+    }
+
+    /// Check if the vector is empty (never true for an encoded term)
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the number of bytes copied into the output buffer
+    pub fn copied_len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Flatten the vector into one buffer, as `term_to_binary/1` returns
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.segments().concat()
+    }
+}
+
+/// Encode a term to external format as an I/O vector
+///
+/// # Arguments
+/// * `term` - The term to encode
+/// * `atom_table` - Optional atom table for looking up atom names
+/// * `threshold` - Smallest binary to reference rather than copy
+///
+/// # Returns
+/// * `Ok(TermIovec)` - The encoding, borrowing binaries from `term`
+/// * `Err(EncodeError)` - Encoding error
+pub fn enc_term_iovec<'a>(
+    term: &'a Term,
+    atom_table: Option<&AtomTable>,
+    threshold: usize,
+) -> Result<TermIovec<'a>, EncodeError> {
+    let mut buffer = vec![VERSION_MAGIC];
+    let mut refs = BinaryRefs { threshold, refs: Vec::new() };
+    enc_term_int(&mut buffer, term, atom_table, Some(&mut refs))?;
+    Ok(TermIovec { buffer, refs: refs.refs })
+}
+
+/// Encode a term to external format as an I/O vector (`term_to_iovec/1`)
+///
+/// Binaries of at least [`IOVEC_BINARY_THRESHOLD`] bytes are referenced.
+///
+/// # Arguments
+/// * `term` - The term to encode
+/// * `atom_table` - Optional atom table for looking up atom names
+///
+/// # Returns
+/// * `Ok(TermIovec)` - The encoding, borrowing binaries from `term`
+/// * `Err(EncodeError)` - Encoding error
+pub fn erts_encode_ext_iovec<'a>(
+    term: &'a Term,
+    atom_table: Option<&AtomTable>,
+) -> Result<TermIovec<'a>, EncodeError> {
+    enc_term_iovec(term, atom_table, IOVEC_BINARY_THRESHOLD)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoding::erts_decode_ext;
+    use crate::encoding::enc_term;
+
+    fn binary(data: Vec<u8>) -> Term {
+        let bit_size = data.len() * 8;
+        Term::Binary { data, bit_offset: 0, bit_size }
+    }
+
+    #[test]
+    fn test_large_binaries_are_referenced() {
+        let term = Term::Tuple(vec![
+            binary(vec![7; IOVEC_BINARY_THRESHOLD]),
+            Term::Small(1),
+            binary(vec![1, 2, 3]),
+            binary(vec![9; 1000]),
+        ]);
+        let iovec = erts_encode_ext_iovec(&term, None).unwrap();
+        let Term::Tuple(elements) = &term else { unreachable!() };
+        let Term::Binary { data: first, .. } = &elements[0] else { unreachable!() };
+        let Term::Binary { data: last, .. } = &elements[3] else { unreachable!() };
+
+        let segments = iovec.segments();
+        assert_eq!(segments.len(), 4);
+        assert_eq!(segments[1].as_ptr(), first.as_ptr());
+        assert_eq!(segments[3].as_ptr(), last.as_ptr());
+        // The small binary is copied, before the header of the large one
+        assert!(segments[2].ends_with(&[1, 2, 3, 109, 0, 0, 3, 232]));
+
+        assert_eq!(iovec.to_vec(), enc_term(&term, None).unwrap());
+        assert_eq!(iovec.len(), iovec.to_vec().len());
+        assert_eq!(iovec.copied_len(), iovec.len() - IOVEC_BINARY_THRESHOLD - 1000);
+        assert_eq!(erts_decode_ext(&iovec.to_vec()).unwrap(), term);
+    }
+
+    #[test]
+    fn test_threshold() {
+        let small = binary(vec![5; ERL_ONHEAP_BIN_LIMIT]);
+        let iovec = erts_encode_ext_iovec(&small, None).unwrap();
+        assert_eq!(iovec.segments().len(), 1);
+        assert_eq!(iovec.copied_len(), iovec.len());
+
+        // A binary at the end of the encoding ends the vector
+        let iovec = enc_term_iovec(&small, None, 1).unwrap();
+        let segments = iovec.segments();
+        assert_eq!(segments, vec![&[131, 109, 0, 0, 0, 64][..], &[5; 64][..]]);
+
+        // An empty binary adds no segment
+        let empty = binary(Vec::new());
+        let iovec = enc_term_iovec(&empty, None, 0).unwrap();
+        assert_eq!(iovec.segments(), vec![&[131, 109, 0, 0, 0, 0][..]]);
+    }
+
+    #[test]
+    fn test_terms_without_binaries() {
+        let term = Term::List {
+            head: Box::new(Term::Small(1)),
+            tail: Box::new(Term::Nil),
+        };
+        let iovec = erts_encode_ext_iovec(&term, None).unwrap();
+        assert_eq!(iovec.segments(), vec![&enc_term(&term, None).unwrap()[..]]);
+        assert!(!iovec.is_empty());
+    }
+}
@@ -22,8 +22,11 @@
 //! - **[`decoding`](decoding/index.html)**: Core decoding functions
 //!   (dec_term, dec_atom, dec_pid, erts_decode_ext)
 //!
+//! - **[`iovec`](iovec/index.html)**: Encoding to an I/O vector referencing
+//!   large binaries (erts_encode_ext_iovec, for term_to_iovec)
+//!
 //! - **[`size_calculation`](size_calculation/index.html)**: Size calculation functions
-//!   (erts_encode_ext_size, encode_size_struct_int)
+//!   (erts_encode_ext_size, encode_size_struct_int, erts_encode_ext_size_shared)
 //!
 //! ## Architecture
 //!
@@ -44,11 +47,16 @@
 
 pub mod encoding;
 pub mod decoding;
+pub mod iovec;
 pub mod size_calculation;
 
 pub use encoding::{enc_term, enc_atom, enc_pid, erts_encode_ext, EncodeError};
 pub use decoding::{dec_term, dec_atom, dec_pid, erts_decode_ext, DecodeError};
-pub use size_calculation::{erts_encode_ext_size, encode_size_struct_int, SizeCalculationError};
+pub use iovec::{enc_term_iovec, erts_encode_ext_iovec, TermIovec, IOVEC_BINARY_THRESHOLD};
+pub use size_calculation::{
+    erts_encode_ext_size, encode_size_struct_int, erts_encode_ext_size_shared,
+    SharedSubterm, SizeCalculationError, SizeWithSharing,
+};
 
 /// External term format version magic byte
 /// This is the first byte in ETF-encoded data (value 131)
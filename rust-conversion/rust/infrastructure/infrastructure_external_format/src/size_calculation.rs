@@ -5,7 +5,9 @@
 
 use entities_data_handling::term_hashing::Term;
 use entities_data_handling::atom::AtomTable;
-use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 
 /// Size calculation error types
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -45,6 +47,10 @@ pub fn erts_encode_ext_size(term: &Term, atom_table: Option<&AtomTable>) -> Resu
 /// Based on `encode_size_struct_int()` from external.c. This function calculates
 /// the size needed for the term data itself, excluding the version magic byte.
 ///
+/// Like the C implementation, the term is walked with an explicit stack
+/// rather than by recursion, so deeply nested terms cannot overflow the
+/// native stack.
+///
 /// # Arguments
 /// * `term` - The term to calculate size for
 /// * `atom_table` - Optional atom table for looking up atom names
@@ -53,6 +59,120 @@ pub fn erts_encode_ext_size(term: &Term, atom_table: Option<&AtomTable>) -> Resu
 /// * `Ok(usize)` - Size in bytes needed for term encoding
 /// * `Err(SizeCalculationError)` - Calculation error
 pub fn encode_size_struct_int(term: &Term, atom_table: Option<&AtomTable>) -> Result<usize, SizeCalculationError> {
+    let mut size = 0usize;
+    let mut stack = vec![term];
+    while let Some(term) = stack.pop() {
+        let node_size = node_size(term, atom_table, &mut stack)?;
+        size = size.checked_add(node_size).ok_or(SizeCalculationError::TermTooLarge)?;
+    }
+    Ok(size)
+}
+
+/// A compound subterm occurring more than once in a term
+#[derive(Debug, Clone, PartialEq)]
+pub struct SharedSubterm<'a> {
+    /// The first occurrence of the subterm
+    pub term: &'a Term,
+    /// Number of occurrences, at least 2
+    pub occurrences: usize,
+    /// Size in bytes needed to encode one occurrence
+    pub size: usize,
+}
+
+/// Encoding size of a term with its repeated subterms
+#[derive(Debug, Clone, PartialEq)]
+pub struct SizeWithSharing<'a> {
+    /// Size in bytes needed for encoding, as [`erts_encode_ext_size`]
+    pub size: usize,
+    /// Repeated tuples, lists, maps, binaries and bignums, in the order their
+    /// first occurrence is completed by a depth-first walk (inner before outer)
+    pub shared: Vec<SharedSubterm<'a>>,
+}
+
+/// Calculate the size needed to encode a term, detecting repeated subterms
+///
+/// Returns the same size as [`erts_encode_ext_size`], together with the
+/// compound subterms that occur more than once. Subterms are compared by
+/// value: a fingerprint is computed bottom-up for every subterm and
+/// subterms with equal fingerprints are compared in full. This is the basis
+/// for deterministic and sharing-preserving encodings.
+///
+/// # Arguments
+/// * `term` - The term to calculate size for
+/// * `atom_table` - Optional atom table for looking up atom names
+///
+/// # Returns
+/// * `Ok(SizeWithSharing)` - Size in bytes and repeated subterms
+/// * `Err(SizeCalculationError)` - Calculation error
+pub fn erts_encode_ext_size_shared<'a>(
+    term: &'a Term,
+    atom_table: Option<&AtomTable>,
+) -> Result<SizeWithSharing<'a>, SizeCalculationError> {
+    enum Frame<'a> {
+        Enter(&'a Term),
+        Exit { term: &'a Term, node_size: usize, children: usize },
+    }
+
+    let mut stack = vec![Frame::Enter(term)];
+    let mut children = Vec::new();
+    // Fingerprint and encoded size of each completed subterm not yet
+    // consumed by its parent
+    let mut results: Vec<(u64, usize)> = Vec::new();
+    let mut candidates: Vec<SharedSubterm<'a>> = Vec::new();
+    let mut by_fingerprint: HashMap<u64, Vec<usize>> = HashMap::new();
+
+    while let Some(frame) = stack.pop() {
+        match frame {
+            Frame::Enter(term) => {
+                children.clear();
+                let node_size = node_size(term, atom_table, &mut children)?;
+                stack.push(Frame::Exit { term, node_size, children: children.len() });
+                stack.extend(children.iter().rev().map(|&child| Frame::Enter(child)));
+            }
+            Frame::Exit { term, node_size, children } => {
+                let mut hasher = DefaultHasher::new();
+                hash_node(term, &mut hasher);
+                let mut size = node_size;
+                for (fingerprint, child_size) in results.drain(results.len() - children..) {
+                    fingerprint.hash(&mut hasher);
+                    size = size.checked_add(child_size).ok_or(SizeCalculationError::TermTooLarge)?;
+                }
+                let fingerprint = hasher.finish();
+                results.push((fingerprint, size));
+
+                if is_compound(term) {
+                    let group = by_fingerprint.entry(fingerprint).or_default();
+                    match group.iter().find(|&&index| candidates[index].term == term) {
+                        Some(&index) => candidates[index].occurrences += 1,
+                        None => {
+                            group.push(candidates.len());
+                            candidates.push(SharedSubterm { term, occurrences: 1, size });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let size = results.pop().map_or(0, |(_, size)| size);
+    Ok(SizeWithSharing {
+        size: size.checked_add(1).ok_or(SizeCalculationError::TermTooLarge)?,
+        shared: candidates.into_iter().filter(|c| c.occurrences > 1).collect(),
+    })
+}
+
+/// Most elements of a list accepted for encoding
+const MAX_LIST_LENGTH: usize = 10000;
+
+/// Calculate the size of the encoding of a term excluding its subterms
+///
+/// The subterms, whose encodings follow, are appended to `children` in
+/// encoding order.
+fn node_size<'a>(
+    term: &'a Term,
+    atom_table: Option<&AtomTable>,
+    children: &mut Vec<&'a Term>,
+) -> Result<usize, SizeCalculationError> {
     match term {
         Term::Nil => {
             // NIL_EXT = 1 byte
@@ -71,7 +191,7 @@ pub fn encode_size_struct_int(term: &Term, atom_table: Option<&AtomTable>) -> Re
                 // SMALL_BIG_EXT = 1 byte tag + 1 byte arity + 1 byte sign + n bytes
                 // LARGE_BIG_EXT = 1 byte tag + 4 bytes arity + 1 byte sign + n bytes
                 // Estimate: calculate bytes needed for the value
-                let abs_value = value.abs();
+                let abs_value = value.unsigned_abs();
                 let bytes_needed = if abs_value == 0 {
                     1
                 } else {
@@ -109,112 +229,45 @@ pub fn encode_size_struct_int(term: &Term, atom_table: Option<&AtomTable>) -> Re
         Term::Tuple(elements) => {
             // Tuple header: SMALL_TUPLE_EXT (1 byte) + 1 byte arity, or
             // LARGE_TUPLE_EXT (1 byte) + 4 bytes arity
-            let arity = elements.len();
-            let header_size = if arity <= 255 {
-                1 + 1 // SMALL_TUPLE_EXT
+            children.extend(elements);
+            if elements.len() <= 255 {
+                Ok(1 + 1) // SMALL_TUPLE_EXT
             } else {
-                1 + 4 // LARGE_TUPLE_EXT
-            };
-            
-            // Calculate size for each element
-            let mut elements_size = 0;
-            for element in elements {
-                elements_size += encode_size_struct_int(element, atom_table)?;
+                Ok(1 + 4) // LARGE_TUPLE_EXT
             }
-            
-            Ok(header_size + elements_size)
         }
-        Term::List { head, tail } => {
-            // Count list length by traversing with cycle detection
+        Term::List { .. } => {
+            // The elements, and an improper tail, follow the header
             let mut length = 0;
-            let mut current = head.as_ref();
-            let mut visited = std::collections::HashSet::new();
-            let mut max_depth = 10000; // Prevent infinite loops
-            
-            loop {
-                if max_depth == 0 {
-                    return Err(SizeCalculationError::InvalidTerm("List too deep or circular".to_string()));
-                }
-                max_depth -= 1;
-                
-                // Use pointer address to detect cycles
-                let ptr = current as *const Term as usize;
-                if visited.contains(&ptr) {
-                    // Circular list detected
-                    return Err(SizeCalculationError::InvalidTerm("Circular list detected".to_string()));
-                }
-                visited.insert(ptr);
-                
-                match current {
-                    Term::Nil => break,
-                    Term::List { head: _, tail: next } => {
-                        length += 1;
-                        current = next.as_ref();
-                    }
-                    _ => {
-                        length += 1;
-                        break;
-                    }
+            let mut current = term;
+            while let Term::List { head, tail } = current {
+                length += 1;
+                if length > MAX_LIST_LENGTH {
+                    return Err(SizeCalculationError::InvalidTerm("List too long".to_string()));
                 }
+                children.push(head);
+                current = tail;
             }
-            
-            // List header: LIST_EXT = 1 byte tag + 4 bytes length + 4 bytes tail
-            let header_size = 1 + 4 + 4; // tag + length + tail
-            
-            // Calculate size for each element with cycle detection
-            let mut elements_size = 0;
-            let mut current = head.as_ref();
-            let mut visited = std::collections::HashSet::new();
-            let mut max_depth = 10000; // Prevent infinite loops
-            
-            loop {
-                if max_depth == 0 {
-                    return Err(SizeCalculationError::InvalidTerm("List too deep or circular".to_string()));
-                }
-                max_depth -= 1;
-                
-                // Use pointer address to detect cycles
-                let ptr = current as *const Term as usize;
-                if visited.contains(&ptr) {
-                    // Circular list detected
-                    return Err(SizeCalculationError::InvalidTerm("Circular list detected".to_string()));
-                }
-                visited.insert(ptr);
-                
-                match current {
-                    Term::Nil => break,
-                    Term::List { head: h, tail: t } => {
-                        elements_size += encode_size_struct_int(h, atom_table)?;
-                        current = t.as_ref();
-                    }
-                    _ => {
-                        elements_size += encode_size_struct_int(current, atom_table)?;
-                        break;
-                    }
-                }
+            if !matches!(current, Term::Nil) {
+                children.push(current);
             }
             
-            // Add NIL_EXT for tail (1 byte)
-            Ok(header_size + elements_size + 1)
+            // List header: LIST_EXT = 1 byte tag + 4 bytes length + 4 bytes tail,
+            // and NIL_EXT for the tail (1 byte)
+            Ok(1 + 4 + 4 + 1)
         }
-        Term::Binary { data, bit_offset: _, bit_size } => {
+        Term::Binary { data: _, bit_offset: _, bit_size } => {
             // BINARY_EXT = 1 byte tag + 4 bytes length + n bytes
-            let byte_size = (bit_size + 7) / 8; // Round up to bytes
+            let byte_size = bit_size.div_ceil(8); // Round up to bytes
             Ok(1 + 4 + byte_size)
         }
         Term::Map(entries) => {
             // MAP_EXT = 1 byte tag + 4 bytes size
-            let map_size = entries.len();
-            let header_size = 1 + 4;
-            
-            // Calculate size for each key-value pair
-            let mut entries_size = 0;
             for (key, value) in entries {
-                entries_size += encode_size_struct_int(key, atom_table)?;
-                entries_size += encode_size_struct_int(value, atom_table)?;
+                children.push(key);
+                children.push(value);
             }
-            
-            Ok(header_size + entries_size)
+            Ok(1 + 4)
         }
         Term::Big(_) => {
             // Big integer encoding (estimate)
@@ -229,6 +282,33 @@ pub fn encode_size_struct_int(term: &Term, atom_table: Option<&AtomTable>) -> Re
     }
 }
 
+/// Check if a term is worth sharing: one that is not an immediate
+fn is_compound(term: &Term) -> bool {
+    matches!(
+        term,
+        Term::Tuple(_) | Term::List { .. } | Term::Map(_) | Term::Binary { .. } | Term::Big(_)
+    )
+}
+
+/// Hash a term excluding its subterms, whose fingerprints are added after
+fn hash_node(term: &Term, hasher: &mut DefaultHasher) {
+    std::mem::discriminant(term).hash(hasher);
+    match term {
+        Term::Small(value) => value.hash(hasher),
+        Term::Atom(index) => index.hash(hasher),
+        Term::Big(value) => value.hash(hasher),
+        Term::Float(value) => value.to_bits().hash(hasher),
+        Term::Binary { data, bit_offset, bit_size } => {
+            data.hash(hasher);
+            bit_offset.hash(hasher);
+            bit_size.hash(hasher);
+        }
+        Term::Tuple(elements) => elements.len().hash(hasher),
+        Term::Map(entries) => entries.len().hash(hasher),
+        _ => {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -739,5 +819,79 @@ mod tests {
         let result = erts_encode_ext_size(&term, None);
         assert!(result.is_err());
     }
-}
 
+    fn binary(data: Vec<u8>) -> Term {
+        let bit_size = data.len() * 8;
+        Term::Binary { data, bit_offset: 0, bit_size }
+    }
+
+    fn list(elements: Vec<Term>) -> Term {
+        elements.into_iter().rev().fold(Term::Nil, |tail, head| Term::List {
+            head: Box::new(head),
+            tail: Box::new(tail),
+        })
+    }
+
+    #[test]
+    fn test_encode_size_deeply_nested() {
+        let depth = 200_000;
+        let mut term = Term::Nil;
+        for _ in 0..depth {
+            term = Term::Tuple(vec![term]);
+        }
+        let size = erts_encode_ext_size(&term, None).unwrap();
+        assert_eq!(size, 1 + depth * 2 + 1);
+
+        // Dismantle iteratively; dropping recursively would overflow the stack
+        while let Term::Tuple(mut elements) = term {
+            term = elements.pop().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_encode_size_list_length_limit() {
+        let term = list(vec![Term::Nil; MAX_LIST_LENGTH]);
+        assert_eq!(encode_size_struct_int(&term, None).unwrap(), 10 + MAX_LIST_LENGTH);
+        let term = list(vec![Term::Nil; MAX_LIST_LENGTH + 1]);
+        assert!(matches!(encode_size_struct_int(&term, None), Err(SizeCalculationError::InvalidTerm(_))));
+    }
+
+    #[test]
+    fn test_encode_size_shared_detects_repeated_subterms() {
+        let payload = binary(vec![7; 100]);
+        let pair = Term::Tuple(vec![Term::Small(1), payload.clone()]);
+        let term = Term::Map(vec![
+            (Term::Atom(1), pair.clone()),
+            (Term::Atom(2), list(vec![pair.clone(), Term::Float(1.5)])),
+            (Term::Atom(3), Term::Tuple(vec![Term::Small(2), payload.clone()])),
+        ]);
+        let result = erts_encode_ext_size_shared(&term, None).unwrap();
+        assert_eq!(result.size, erts_encode_ext_size(&term, None).unwrap());
+
+        // Inner subterms complete before the subterms containing them
+        assert_eq!(result.shared.len(), 2);
+        assert_eq!(*result.shared[0].term, payload);
+        assert_eq!(result.shared[0].occurrences, 3);
+        assert_eq!(result.shared[0].size, 105);
+        assert_eq!(*result.shared[1].term, pair);
+        assert_eq!(result.shared[1].occurrences, 2);
+        assert_eq!(result.shared[1].size, 2 + 2 + 105);
+    }
+
+    #[test]
+    fn test_encode_size_shared_distinguishes_similar_subterms() {
+        // Same shape and leaves in a different order, and immediates, are not shared
+        let term = Term::Tuple(vec![
+            Term::Tuple(vec![Term::Small(1), Term::Small(2)]),
+            Term::Tuple(vec![Term::Small(2), Term::Small(1)]),
+            Term::Small(1),
+            Term::Float(0.0),
+            Term::Float(-0.0),
+        ]);
+        let result = erts_encode_ext_size_shared(&term, None).unwrap();
+        assert!(result.shared.is_empty());
+        assert_eq!(result.size, erts_encode_ext_size(&term, None).unwrap());
+
+        assert!(erts_encode_ext_size_shared(&Term::Pid { node: 0, id: 1, serial: 0, creation: 0 }, None).is_err());
+    }
+}
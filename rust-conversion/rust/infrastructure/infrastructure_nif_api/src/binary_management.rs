@@ -0,0 +1,567 @@
+//! Binary Management Functions
+//!
+//! Provides the binary part of the NIF API: allocating binaries
+//! (`enif_alloc_binary`, `enif_realloc_binary`, `enif_release_binary`),
+//! inspecting binary terms (`enif_inspect_binary`,
+//! `enif_inspect_iolist_as_binary`) and making sub-binaries
+//! (`enif_make_sub_binary`). Binary terms are made with `enif_make_binary`.
+//!
+//! ## Binary Storage
+//!
+//! Binaries of up to [`ERL_ONHEAP_BIN_LIMIT`] bytes are copied onto the
+//! process heap. Larger binaries, and binaries that already share data with
+//! another binary, are reference-counted [`RefcBinary`] values: the heap only
+//! holds a small term referencing the data, and the reference is kept on the
+//! process off-heap list until the garbage collector finds the term dead or
+//! the process exits. Making a term from an allocated binary, inspecting a
+//! reference-counted binary term and making a sub-binary of one never copy
+//! the data. The shared data is counted as binary memory
+//! (`erlang:memory(binary)`) while terms reference it.
+//!
+//! Based on erl_nif.c and erl_binary.h
+
+use super::{NifEnv, NifTerm};
+use crate::term_creation::enif_make_binary;
+use crate::term_decoding::enif_get_binary;
+use entities_data_handling::binary::{RefcBinary, ERL_ONHEAP_BIN_LIMIT};
+use entities_process::term_tags::{make_boxed, NIL, TAG_PRIMARY_BOXED, TAG_PRIMARY_MASK};
+use entities_process::ProcessId;
+use infrastructure_utilities::memory::{get_global_memory_counters, MemoryCounters, MemoryKind};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+/// NIF binary structure
+///
+/// Represents a binary that can be passed between NIF functions.
+/// Uses safe Rust types instead of raw pointers.
+///
+/// ## Design
+///
+/// This is a safe Rust alternative to the C `ErlNifBinary` structure.
+/// A binary allocated by the NIF owns its data, which the NIF may write to
+/// until the binary is made into a term. A binary returned by
+/// `enif_inspect_binary` is a read-only view sharing the term's data.
+pub struct ErlNifBinary {
+    /// Binary data
+    data: BinaryData,
+}
+
+/// Data of an [`ErlNifBinary`]
+enum BinaryData {
+    /// Data owned by the NIF
+    Owned(Vec<u8>),
+    /// Data shared with binary terms
+    Shared(RefcBinary),
+}
+
+impl ErlNifBinary {
+    /// Create a new binary from a byte vector
+    ///
+    /// # Arguments
+    /// * `data` - Binary data
+    ///
+    /// # Returns
+    /// A new `ErlNifBinary` instance
+    pub fn new(data: Vec<u8>) -> Self {
+        Self {
+            data: BinaryData::Owned(data),
+        }
+    }
+
+    /// Create a read-only binary sharing the data of a reference-counted binary
+    pub fn shared(binary: RefcBinary) -> Self {
+        Self {
+            data: BinaryData::Shared(binary),
+        }
+    }
+
+    /// Get a reference to the binary data
+    ///
+    /// # Returns
+    /// A slice reference to the binary data
+    pub fn data(&self) -> &[u8] {
+        match &self.data {
+            BinaryData::Owned(data) => data,
+            BinaryData::Shared(binary) => binary.data(),
+        }
+    }
+
+    /// Get mutable access to the binary data
+    ///
+    /// # Returns
+    /// * `Some(data)` - The binary was allocated by the NIF
+    /// * `None` - The binary is a read-only view of a term
+    pub fn data_mut(&mut self) -> Option<&mut [u8]> {
+        match &mut self.data {
+            BinaryData::Owned(data) => Some(data),
+            BinaryData::Shared(_) => None,
+        }
+    }
+
+    /// Get the size of the binary in bytes
+    ///
+    /// # Returns
+    /// The size of the binary
+    pub fn size(&self) -> usize {
+        self.data().len()
+    }
+
+    /// Check if the binary shares its data with binary terms
+    pub fn is_shared(&self) -> bool {
+        matches!(self.data, BinaryData::Shared(_))
+    }
+
+    /// Convert into the underlying byte vector
+    ///
+    /// # Returns
+    /// The binary data as a `Vec<u8>`; shared data is copied
+    pub fn into_vec(self) -> Vec<u8> {
+        match self.data {
+            BinaryData::Owned(data) => data,
+            BinaryData::Shared(binary) => binary.data().to_vec(),
+        }
+    }
+
+    /// Convert into a reference-counted binary, without copying the data
+    pub fn into_refc(self) -> RefcBinary {
+        match self.data {
+            BinaryData::Owned(data) => RefcBinary::new(data),
+            BinaryData::Shared(binary) => binary,
+        }
+    }
+}
+
+impl std::fmt::Debug for ErlNifBinary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ErlNifBinary")
+            .field("size", &self.size())
+            .field("shared", &self.is_shared())
+            .finish()
+    }
+}
+
+impl From<Vec<u8>> for ErlNifBinary {
+    fn from(data: Vec<u8>) -> Self {
+        Self::new(data)
+    }
+}
+
+impl From<&Vec<u8>> for ErlNifBinary {
+    fn from(data: &Vec<u8>) -> Self {
+        Self::new(data.clone())
+    }
+}
+
+impl From<&[u8]> for ErlNifBinary {
+    fn from(data: &[u8]) -> Self {
+        Self::new(data.to_vec())
+    }
+}
+
+impl<const N: usize> From<&[u8; N]> for ErlNifBinary {
+    fn from(data: &[u8; N]) -> Self {
+        Self::new(data.to_vec())
+    }
+}
+
+impl From<RefcBinary> for ErlNifBinary {
+    fn from(binary: RefcBinary) -> Self {
+        Self::shared(binary)
+    }
+}
+
+/// Allocate a binary of `size` bytes (`enif_alloc_binary`)
+///
+/// The binary is zero-filled and writable through
+/// [`ErlNifBinary::data_mut`]. It is handed over to the runtime by
+/// `enif_make_binary` or freed by [`enif_release_binary`].
+///
+/// # Returns
+///
+/// * `Some(binary)` - The allocated binary
+/// * `None` - The allocation failed
+pub fn enif_alloc_binary(size: usize) -> Option<ErlNifBinary> {
+    let mut data = Vec::new();
+    data.try_reserve_exact(size).ok()?;
+    data.resize(size, 0);
+    Some(ErlNifBinary::new(data))
+}
+
+/// Change the size of an allocated binary (`enif_realloc_binary`)
+///
+/// Data up to the smaller of the old and new size is kept; new bytes are
+/// zero-filled.
+///
+/// # Returns
+///
+/// * `true` - The binary was resized
+/// * `false` - The binary is a read-only view of a term, or the allocation failed
+pub fn enif_realloc_binary(binary: &mut ErlNifBinary, size: usize) -> bool {
+    let BinaryData::Owned(data) = &mut binary.data else {
+        return false;
+    };
+    if size > data.len() && data.try_reserve_exact(size - data.len()).is_err() {
+        return false;
+    }
+    data.resize(size, 0);
+    true
+}
+
+/// Free a binary not made into a term (`enif_release_binary`)
+pub fn enif_release_binary(binary: ErlNifBinary) {
+    drop(binary);
+}
+
+/// Reference-counted binaries referenced from terms on each process heap
+///
+/// This is the binary part of the process off-heap list.
+struct OffHeapBinaries {
+    /// Binary terms and the references they hold, by process
+    terms: HashMap<ProcessId, Vec<(NifTerm, RefcBinary)>>,
+    /// Number of terms referencing each shared data, by storage identifier
+    references: HashMap<usize, usize>,
+    /// Counters the referenced data is counted on
+    memory: &'static MemoryCounters,
+}
+
+impl OffHeapBinaries {
+    fn new(memory: &'static MemoryCounters) -> Self {
+        Self {
+            terms: HashMap::new(),
+            references: HashMap::new(),
+            memory,
+        }
+    }
+
+    /// Record a term referencing a binary, counting its data on the first
+    /// reference
+    fn register(&mut self, process_id: ProcessId, term: NifTerm, binary: &RefcBinary) {
+        let count = self.references.entry(binary.storage_id()).or_insert(0);
+        if *count == 0 {
+            self.memory.add(MemoryKind::Binary, binary.storage_size());
+        }
+        *count += 1;
+        self.terms.entry(process_id).or_default().push((term, binary.clone()));
+    }
+
+    /// Drop a term reference to a binary, releasing its data on the last one
+    fn unreference(references: &mut HashMap<usize, usize>, memory: &MemoryCounters, binary: &RefcBinary) {
+        let Some(count) = references.get_mut(&binary.storage_id()) else {
+            return;
+        };
+        *count -= 1;
+        if *count == 0 {
+            references.remove(&binary.storage_id());
+            memory.sub(MemoryKind::Binary, binary.storage_size());
+        }
+    }
+
+    /// Drop the terms of a process that are not live
+    fn sweep(&mut self, process_id: ProcessId, is_live: impl Fn(NifTerm) -> bool) -> usize {
+        let Some(terms) = self.terms.get_mut(&process_id) else {
+            return 0;
+        };
+        let before = terms.len();
+        let (references, memory) = (&mut self.references, self.memory);
+        terms.retain(|(term, binary)| {
+            let live = is_live(*term);
+            if !live {
+                Self::unreference(references, memory, binary);
+            }
+            live
+        });
+        let released = before - terms.len();
+        if terms.is_empty() {
+            self.terms.remove(&process_id);
+        }
+        released
+    }
+
+    /// Drop all terms of a process
+    fn release_process(&mut self, process_id: ProcessId) -> usize {
+        let Some(terms) = self.terms.remove(&process_id) else {
+            return 0;
+        };
+        for (_, binary) in &terms {
+            Self::unreference(&mut self.references, self.memory, binary);
+        }
+        terms.len()
+    }
+}
+
+/// Global off-heap binary list
+fn get_off_heap_binaries() -> &'static Mutex<OffHeapBinaries> {
+    static OFF_HEAP: OnceLock<Mutex<OffHeapBinaries>> = OnceLock::new();
+    OFF_HEAP.get_or_init(|| Mutex::new(OffHeapBinaries::new(get_global_memory_counters())))
+}
+
+/// Make a term referencing a reference-counted binary
+///
+/// The term is a boxed header of size 0 followed by the address of the
+/// data, the same shape as a resource term; the off-heap list tells them
+/// apart.
+///
+/// # Returns
+///
+/// The binary term, or nil if the heap is full
+pub(crate) fn make_refc_binary_term(env: &NifEnv, binary: RefcBinary) -> NifTerm {
+    let Some(heap_index) = env.allocate_heap(2) else {
+        return NIL;
+    };
+    let process = env.process();
+    let mut heap_data = process.heap_slice_mut();
+    heap_data[heap_index] = TAG_PRIMARY_BOXED;
+    heap_data[heap_index + 1] = binary.address() as u64;
+    drop(heap_data);
+
+    let term = make_boxed(heap_index);
+    register_binary_term(env.process_id(), term, &binary);
+    term
+}
+
+/// Record that a binary term held by a process references a reference-counted binary
+pub(crate) fn register_binary_term(process_id: ProcessId, term: NifTerm, binary: &RefcBinary) {
+    get_off_heap_binaries().lock().unwrap().register(process_id, term, binary);
+}
+
+/// Get the reference-counted binary referenced by a binary term held by a process
+pub(crate) fn binary_for_term(process_id: ProcessId, term: NifTerm) -> Option<RefcBinary> {
+    let off_heap = get_off_heap_binaries().lock().unwrap();
+    off_heap
+        .terms
+        .get(&process_id)?
+        .iter()
+        .find(|(t, _)| *t == term)
+        .map(|(_, binary)| binary.clone())
+}
+
+/// Get the contents of a binary term (`enif_inspect_binary`)
+///
+/// # Arguments
+///
+/// * `env` - NIF environment of the process holding the term
+/// * `term` - Binary term
+///
+/// # Returns
+///
+/// * `Some(binary)` - Read-only view of the binary; for reference-counted
+///   binaries it shares the term's data
+/// * `None` - The term is not a binary
+pub fn enif_inspect_binary(env: &NifEnv, term: NifTerm) -> Option<ErlNifBinary> {
+    if let Some(binary) = binary_for_term(env.process_id(), term) {
+        return Some(ErlNifBinary::shared(binary));
+    }
+    // Heap binaries are small, so they are copied rather than referenced
+    enif_get_binary(env, term).map(|data| ErlNifBinary::shared(RefcBinary::new(data)))
+}
+
+/// Get the contents of an iolist as one binary (`enif_inspect_iolist_as_binary`)
+///
+/// An iolist is a binary, or a possibly nested list of bytes (integers
+/// 0..=255) and binaries whose tail is nil or a binary.
+///
+/// # Returns
+///
+/// * `Some(binary)` - The concatenated contents; a binary term is not copied
+/// * `None` - The term is not an iolist
+pub fn enif_inspect_iolist_as_binary(env: &NifEnv, term: NifTerm) -> Option<ErlNifBinary> {
+    if term & TAG_PRIMARY_MASK == TAG_PRIMARY_BOXED {
+        return enif_inspect_binary(env, term);
+    }
+    let segments = crate::iolist::iolist_to_iovec(env, term)?;
+    Some(ErlNifBinary::new(segments.iter().flat_map(|segment| segment.data()).copied().collect()))
+}
+
+/// Make a sub-binary of a binary term (`enif_make_sub_binary`)
+///
+/// A sub-binary of a reference-counted binary shares its data; a sub-binary
+/// of a heap binary, which is at most [`ERL_ONHEAP_BIN_LIMIT`] bytes, is a
+/// copy.
+///
+/// # Arguments
+///
+/// * `env` - NIF environment
+/// * `bin_term` - Binary term
+/// * `pos` - Start of the sub-binary in bytes
+/// * `size` - Size of the sub-binary in bytes
+///
+/// # Returns
+///
+/// * `Some(term)` - The sub-binary
+/// * `None` - `bin_term` is not a binary or the range is outside it
+pub fn enif_make_sub_binary(env: &NifEnv, bin_term: NifTerm, pos: usize, size: usize) -> Option<NifTerm> {
+    if let Some(binary) = binary_for_term(env.process_id(), bin_term) {
+        return Some(make_refc_binary_term(env, binary.sub_binary(pos, size)?));
+    }
+    let data = enif_get_binary(env, bin_term)?;
+    let end = pos.checked_add(size)?;
+    Some(enif_make_binary(env, data.get(pos..end)?))
+}
+
+/// Release the references held by binary terms that are no longer live
+///
+/// Called by the garbage collector after marking a process heap, with
+/// [`gc_sweep_resources`](crate::resource_management::gc_sweep_resources).
+///
+/// # Returns
+///
+/// Number of term references released
+pub fn gc_sweep_binaries(process_id: ProcessId, is_live: impl Fn(NifTerm) -> bool) -> usize {
+    get_off_heap_binaries().lock().unwrap().sweep(process_id, is_live)
+}
+
+/// Release the references held by all binary terms of an exiting process
+///
+/// # Returns
+///
+/// Number of term references released
+pub fn release_process_binaries(process_id: ProcessId) -> usize {
+    get_off_heap_binaries().lock().unwrap().release_process(process_id)
+}
+
+/// Check if a binary of `size` bytes is stored on the process heap
+pub(crate) fn is_heap_binary_size(size: usize) -> bool {
+    size <= ERL_ONHEAP_BIN_LIMIT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::term_creation::{enif_make_int, enif_make_list};
+    use entities_process::Process;
+    use std::sync::Arc;
+
+    fn test_env(id: ProcessId) -> NifEnv {
+        NifEnv::from_process(Arc::new(Process::new(id)))
+    }
+
+    #[test]
+    fn test_erl_nif_binary_new() {
+        let data = vec![1, 2, 3, 4, 5];
+        let binary = ErlNifBinary::new(data.clone());
+        assert_eq!(binary.data(), &data);
+        assert_eq!(binary.size(), 5);
+    }
+
+    #[test]
+    fn test_erl_nif_binary_into_vec() {
+        let data = vec![10, 20, 30];
+        let binary = ErlNifBinary::new(data.clone());
+        let retrieved = binary.into_vec();
+        assert_eq!(retrieved, data);
+    }
+
+    #[test]
+    fn test_alloc_and_realloc_binary() {
+        let mut binary = enif_alloc_binary(4).unwrap();
+        assert_eq!(binary.data(), &[0, 0, 0, 0]);
+        binary.data_mut().unwrap().copy_from_slice(b"abcd");
+
+        assert!(enif_realloc_binary(&mut binary, 6));
+        assert_eq!(binary.data(), b"abcd\0\0");
+        assert!(enif_realloc_binary(&mut binary, 2));
+        assert_eq!(binary.data(), b"ab");
+        enif_release_binary(binary);
+
+        let mut shared = ErlNifBinary::shared(RefcBinary::new(b"xy".to_vec()));
+        assert!(shared.data_mut().is_none());
+        assert!(!enif_realloc_binary(&mut shared, 4));
+    }
+
+    #[test]
+    fn test_make_binary_is_zero_copy() {
+        let env = test_env(40720);
+        let mut binary = enif_alloc_binary(1000).unwrap();
+        binary.data_mut().unwrap()[999] = 9;
+        let pointer = binary.data().as_ptr();
+        let term = enif_make_binary(&env, binary);
+
+        let inspected = enif_inspect_binary(&env, term).unwrap();
+        assert!(inspected.is_shared());
+        assert_eq!(inspected.data().as_ptr(), pointer);
+        assert_eq!(inspected.data()[999], 9);
+        assert_eq!(enif_get_binary(&env, term).unwrap().len(), 1000);
+        release_process_binaries(40720);
+    }
+
+    #[test]
+    fn test_small_binary_on_heap() {
+        let env = test_env(40721);
+        let term = enif_make_binary(&env, b"small");
+        assert!(binary_for_term(40721, term).is_none());
+        assert_eq!(enif_inspect_binary(&env, term).unwrap().data(), b"small");
+        assert!(enif_inspect_binary(&env, enif_make_int(&env, 1)).is_none());
+    }
+
+    #[test]
+    fn test_sub_binary_shares_data() {
+        let env = test_env(40722);
+        let data: Vec<u8> = (0..200u8).collect();
+        let term = enif_make_binary(&env, data);
+        let whole = enif_inspect_binary(&env, term).unwrap().into_refc();
+
+        let sub = enif_make_sub_binary(&env, term, 10, 100).unwrap();
+        let inspected = enif_inspect_binary(&env, sub).unwrap();
+        assert_eq!(inspected.data()[0], 10);
+        assert_eq!(inspected.size(), 100);
+        assert!(inspected.into_refc().shares_storage(&whole));
+
+        assert!(enif_make_sub_binary(&env, term, 150, 51).is_none());
+        let heap = enif_make_binary(&env, b"hello world");
+        let world = enif_make_sub_binary(&env, heap, 6, 5).unwrap();
+        assert_eq!(enif_get_binary(&env, world).unwrap(), b"world");
+        assert_eq!(release_process_binaries(40722), 2);
+    }
+
+    #[test]
+    fn test_inspect_iolist_as_binary() {
+        let env = test_env(40723);
+        let inner = enif_make_list(&env, &[enif_make_int(&env, 98), enif_make_binary(&env, b"cd")]);
+        let iolist = enif_make_list(&env, &[enif_make_int(&env, 97), inner, NIL, enif_make_binary(&env, b"e")]);
+        assert_eq!(enif_inspect_iolist_as_binary(&env, iolist).unwrap().data(), b"abcde");
+
+        let binary = enif_make_binary(&env, b"plain");
+        assert_eq!(enif_inspect_iolist_as_binary(&env, binary).unwrap().data(), b"plain");
+        assert_eq!(enif_inspect_iolist_as_binary(&env, NIL).unwrap().size(), 0);
+
+        let bad = enif_make_list(&env, &[enif_make_int(&env, 256)]);
+        assert!(enif_inspect_iolist_as_binary(&env, bad).is_none());
+        assert!(enif_inspect_iolist_as_binary(&env, enif_make_int(&env, 1)).is_none());
+    }
+
+    #[test]
+    fn test_gc_sweep_binaries() {
+        let env = test_env(40724);
+        let live = enif_make_binary(&env, vec![1u8; 100]);
+        let dead = enif_make_binary(&env, vec![2u8; 100]);
+        let binary = binary_for_term(40724, dead).unwrap();
+        assert_eq!(binary.ref_count(), 2);
+
+        assert_eq!(gc_sweep_binaries(40724, |term| term == live), 1);
+        assert_eq!(binary.ref_count(), 1);
+        assert_eq!(release_process_binaries(40724), 1);
+        assert_eq!(gc_sweep_binaries(40724, |_| true), 0);
+    }
+
+    #[test]
+    fn test_off_heap_binary_memory() {
+        let counters: &'static MemoryCounters = Box::leak(Box::new(MemoryCounters::new()));
+        let mut off_heap = OffHeapBinaries::new(counters);
+        let whole = RefcBinary::new(vec![0u8; 1000]);
+        let part = whole.sub_binary(10, 20).unwrap();
+        let other = RefcBinary::new(vec![0u8; 500]);
+
+        // Shared data is counted once however many terms reference it
+        off_heap.register(1, 0x12, &whole);
+        off_heap.register(1, 0x22, &part);
+        off_heap.register(2, 0x12, &whole);
+        off_heap.register(2, 0x32, &other);
+        assert_eq!(counters.get(MemoryKind::Binary), 1500);
+
+        assert_eq!(off_heap.sweep(1, |term| term == 0x22), 1);
+        assert_eq!(off_heap.release_process(2), 2);
+        assert_eq!(counters.get(MemoryKind::Binary), 1000);
+        assert_eq!(off_heap.sweep(1, |_| false), 1);
+        assert_eq!(counters.get(MemoryKind::Binary), 0);
+    }
+}
@@ -12,6 +12,7 @@
 
 use super::{NifEnv, NifTerm};
 use crate::term_creation::enif_make_atom;
+use entities_process::term_tags::NIL;
 
 /// Create a badarg exception
 ///
@@ -32,8 +33,8 @@ use crate::term_creation::enif_make_atom;
 /// - First element: `badarg` atom
 /// - Second element: empty list `[]` (nil)
 ///
-/// Since full tuple creation is not yet implemented, this uses a placeholder
-/// that will be replaced when `enif_make_tuple` is fully implemented.
+/// If the heap has no room for the tuple, the `badarg` atom is returned
+/// instead, which `enif_is_exception` also recognizes.
 ///
 /// # See Also
 ///
@@ -43,13 +44,11 @@ pub fn enif_make_badarg(env: &NifEnv) -> NifTerm {
     let badarg_atom = enif_make_atom(env, "badarg");
     
     // Create the empty list (nil)
-    // Nil is encoded as 0x3F (TAG_IMMED2_NIL)
-    let empty_list = 0x3F;
+    let empty_list = NIL;
     
     // Create the exception tuple {badarg, []}
-    // Use enif_make_tuple to create the tuple
-    // Note: This currently uses a placeholder encoding until heap allocation is integrated
-    crate::term_creation::enif_make_tuple(env, &[badarg_atom, empty_list])
+    // Not via enif_make_tuple, which itself falls back to enif_make_badarg
+    crate::tuple::allocate_tuple(env, &[badarg_atom, empty_list]).unwrap_or(badarg_atom)
 }
 
 /// Create a badarg atom
@@ -87,10 +86,8 @@ pub fn enif_make_badarg_atom(env: &NifEnv) -> NifTerm {
 /// # Implementation Note
 ///
 /// This checks if the term is:
-/// 1. A tuple (when tuple decoding is implemented)
-/// 2. Has a first element that is an error atom
-///
-/// Currently uses a placeholder check until tuple decoding is fully implemented.
+/// 1. An exception atom, or
+/// 2. A tuple whose first element is an exception atom
 ///
 /// # See Also
 ///
@@ -101,16 +98,6 @@ pub fn enif_is_exception(env: &NifEnv, term: NifTerm) -> bool {
         return true;
     }
     
-    // Check if it's a tuple placeholder (can't decode elements, but might be exception)
-    if is_tuple_placeholder(term) {
-        // Placeholder tuples can't be decoded, but if it was created by enif_make_badarg,
-        // it should be detected. For now, we'll conservatively assume placeholder tuples
-        // could be exceptions if they were created by exception-creating functions.
-        // In practice, enif_make_badarg creates tuples that should be heap-allocated,
-        // but if heap allocation fails, it falls back to placeholder.
-        return true; // Conservative: assume placeholder tuples could be exceptions
-    }
-    
     // Check if it's a tuple using enif_get_tuple
     use crate::term_decoding::enif_get_tuple;
     if let Some(elements) = enif_get_tuple(env, term) {
@@ -157,15 +144,6 @@ fn make_exception_tuple_placeholder(atom: NifTerm, list: NifTerm) -> NifTerm {
     EXCEPTION_PLACEHOLDER_TAG | ((atom ^ list) & !EXCEPTION_PLACEHOLDER_MASK)
 }
 
-/// Check if a term is a tuple placeholder
-///
-/// This checks if the term is a placeholder tuple created by enif_make_tuple.
-/// This is a temporary solution until tuple decoding is fully implemented.
-fn is_tuple_placeholder(term: NifTerm) -> bool {
-    const TUPLE_PLACEHOLDER_TAG: u64 = 0xE0E0E0E0E0E0E0E0;
-    (term & TUPLE_PLACEHOLDER_TAG) == TUPLE_PLACEHOLDER_TAG
-}
-
 /// Check if a term is a placeholder exception tuple
 ///
 /// This is a temporary solution until tuple decoding is fully implemented.
@@ -200,6 +178,7 @@ fn is_exception_atom(env: &NifEnv, term: NifTerm) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use entities_process::term_tags::{TAG_IMMED2_ATOM, TAG_IMMED2_MASK};
 
     #[test]
     fn test_enif_make_badarg_atom() {
@@ -209,7 +188,7 @@ mod tests {
         let atom = enif_make_badarg_atom(&env);
         // Should be a valid atom term (check tag bits)
         assert_ne!(atom, 0);
-        assert_eq!(atom & 0x3F, 0x0B); // Atom tag check
+        assert_eq!(atom & TAG_IMMED2_MASK, TAG_IMMED2_ATOM);
     }
 
     #[test]
@@ -218,7 +197,6 @@ mod tests {
         use entities_process::Process;
         let env = crate::nif_env::NifEnv::from_process(Arc::new(Process::new(1)));
         let exception = enif_make_badarg(&env);
-        // Note: term 0 is valid (heap_index 0). Nil is 0x3F, not 0.
         // Check that it's detected as an exception (this validates it's a valid term)
         assert!(enif_is_exception(&env, exception));
     }
@@ -262,12 +240,13 @@ mod tests {
     }
 
     #[test]
-    fn test_is_exception_tuple_placeholder() {
+    fn test_enif_make_badarg_is_tuple() {
         use std::sync::Arc;
         use entities_process::Process;
         let env = crate::nif_env::NifEnv::from_process(Arc::new(Process::new(1)));
         let exception = enif_make_badarg(&env);
-        // The placeholder should be detected
+        let elements = crate::term_decoding::enif_get_tuple(&env, exception).unwrap();
+        assert_eq!(elements, vec![enif_make_badarg_atom(&env), NIL]);
         assert!(enif_is_exception(&env, exception));
     }
 
@@ -279,7 +258,7 @@ mod tests {
         
         // Create a tuple with exception atom as first element
         let badarg_atom = enif_make_atom(&env, "badarg");
-        let empty_list = 0x3F; // nil
+        let empty_list = NIL;
         let tuple = crate::term_creation::enif_make_tuple(&env, &[badarg_atom, empty_list]);
         
         // Should be detected as exception
@@ -297,16 +276,8 @@ mod tests {
         let value = crate::term_creation::enif_make_int(&env, 42);
         let tuple = crate::term_creation::enif_make_tuple(&env, &[ok_atom, value]);
         
-        // Check if it's a placeholder or heap-allocated
-        const TUPLE_PLACEHOLDER_TAG: u64 = 0xE0E0E0E0E0E0E0E0;
-        if (tuple & TUPLE_PLACEHOLDER_TAG) == TUPLE_PLACEHOLDER_TAG {
-            // Placeholder tuples are conservatively treated as potential exceptions
-            // (since we can't decode them to check the first element)
-            assert!(enif_is_exception(&env, tuple));
-        } else {
-            // Heap-allocated tuple - should not be detected as exception
-            assert!(!enif_is_exception(&env, tuple));
-        }
+        // Should not be detected as exception
+        assert!(!enif_is_exception(&env, tuple));
     }
 
     #[test]
@@ -318,17 +289,8 @@ mod tests {
         // Create an empty tuple
         let empty_tuple = crate::term_creation::enif_make_tuple(&env, &[]);
         
-        // Empty tuple returns placeholder, which is conservatively treated as potential exception
-        // But the implementation checks if elements.is_empty() and returns false
-        // However, placeholder tuples can't be decoded, so they're treated as potential exceptions
-        const TUPLE_PLACEHOLDER_TAG: u64 = 0xE0E0E0E0E0E0E0E0;
-        if (empty_tuple & TUPLE_PLACEHOLDER_TAG) == TUPLE_PLACEHOLDER_TAG {
-            // Placeholder - conservatively treated as potential exception
-            assert!(enif_is_exception(&env, empty_tuple));
-        } else {
-            // Heap-allocated empty tuple - should not be exception (no first element)
-            assert!(!enif_is_exception(&env, empty_tuple));
-        }
+        // No first element, so not an exception
+        assert!(!enif_is_exception(&env, empty_tuple));
     }
 
     #[test]
@@ -342,7 +304,7 @@ mod tests {
         assert!(!enif_is_exception(&env, int_term));
         
         // Test with nil
-        let nil_term = 0x3F;
+        let nil_term = NIL;
         assert!(!enif_is_exception(&env, nil_term));
     }
 
@@ -390,28 +352,10 @@ mod tests {
         let int_term = crate::term_creation::enif_make_int(&env, 42);
         assert!(!enif_is_exception(&env, int_term));
         
-        let nil_term = 0x3F;
+        let nil_term = NIL;
         assert!(!enif_is_exception(&env, nil_term));
     }
 
-    #[test]
-    fn test_is_tuple_placeholder() {
-        // Test tuple placeholder detection
-        const TUPLE_PLACEHOLDER_TAG: u64 = 0xE0E0E0E0E0E0E0E0;
-        
-        // Should detect placeholder
-        assert!(is_tuple_placeholder(TUPLE_PLACEHOLDER_TAG));
-        assert!(is_tuple_placeholder(TUPLE_PLACEHOLDER_TAG | 0x1234));
-        
-        // Should not detect non-placeholders
-        use std::sync::Arc;
-        use entities_process::Process;
-        let env = crate::nif_env::NifEnv::from_process(Arc::new(Process::new(1)));
-        let int_term = crate::term_creation::enif_make_int(&env, 42);
-        assert!(!is_tuple_placeholder(int_term));
-        assert!(!is_tuple_placeholder(0));
-    }
-
     #[test]
     fn test_is_exception_tuple_placeholder_helper() {
         // Test old-style exception placeholder detection
@@ -452,7 +396,6 @@ mod tests {
         // Create badarg exception
         let exception = enif_make_badarg(&env);
         
-        // Note: term 0 is valid (heap_index 0). Nil is 0x3F, not 0.
         // Should be detected as exception (this validates it's a valid term)
         assert!(enif_is_exception(&env, exception));
     }
@@ -543,25 +486,24 @@ mod tests {
     }
 
     #[test]
-    fn test_enif_is_exception_with_tuple_placeholder() {
+    fn test_enif_is_exception_with_full_heap() {
         use std::sync::Arc;
         use entities_process::Process;
         let env = crate::nif_env::NifEnv::from_process(Arc::new(Process::new(1)));
         
-        // Create a tuple (may be placeholder if heap is full)
+        // Fill the heap so no tuple fits
+        env.allocate_heap(env.available_heap_space());
+        assert_eq!(enif_make_badarg(&env), enif_make_badarg_atom(&env));
+        
         let elements = vec![
             enif_make_atom(&env, "test"),
             crate::term_creation::enif_make_int(&env, 42),
         ];
         let tuple = crate::term_creation::enif_make_tuple(&env, &elements);
         
-        // Check if it's a placeholder
-        const TUPLE_PLACEHOLDER_TAG: u64 = 0xE0E0E0E0E0E0E0E0;
-        if (tuple & TUPLE_PLACEHOLDER_TAG) == TUPLE_PLACEHOLDER_TAG {
-            // Placeholder tuples are conservatively treated as potential exceptions
-            // (since we can't decode them to check the first element)
-            assert!(enif_is_exception(&env, tuple));
-        }
+        // enif_make_tuple returns badarg when the tuple does not fit
+        assert_eq!(tuple, enif_make_badarg_atom(&env));
+        assert!(enif_is_exception(&env, tuple));
     }
 }
 
@@ -0,0 +1,135 @@
+//! Float Terms
+//!
+//! Provides the heap layout of floats and the float functions of the NIF
+//! API: `enif_make_double`, `enif_get_double` and the `is_float` type test.
+//! Based on the float definitions of erl_term.h and on enif_make_double and
+//! enif_get_double in erl_nif.c.
+//!
+//! A float is a boxed term: a pointer tagged with `TAG_PRIMARY_BOXED`
+//! (`(heap_index << 2) | 0x2`) to the header `HEADER_FLONUM` followed by one
+//! word holding the bits of the IEEE 754 double. The header carries
+//! `FLOAT_SUBTAG`, which tells a float apart from tuples, whose headers have
+//! no subtag, and from bignums and binaries, whose headers carry
+//! `TAG_PRIMARY_BOXED`.
+//!
+//! Erlang has no infinities or NaN: `enif_make_double` refuses them with a
+//! badarg exception.
+
+use entities_process::term_tags::{
+    make_boxed, FLOAT_SUBTAG, HEADER_ARITY_OFFS, TAG_HEADER_MASK, TAG_PRIMARY_BOXED,
+    TAG_PRIMARY_MASK,
+};
+
+use crate::{NifEnv, NifTerm};
+
+/// Words of float data after the header (`FLOAT_SIZE_OBJECT - 1`)
+const FLOAT_DATA_WORDS: usize = 1;
+
+/// Header of a float (`HEADER_FLONUM`)
+pub const HEADER_FLONUM: NifTerm = ((FLOAT_DATA_WORDS as NifTerm) << HEADER_ARITY_OFFS) | FLOAT_SUBTAG;
+
+/// Check if a header word is a float header
+pub fn is_float_header(header: NifTerm) -> bool {
+    header & TAG_HEADER_MASK == FLOAT_SUBTAG
+}
+
+/// Make a boxed pointer to a float at a heap index (`make_float`)
+pub fn make_float_term(heap_index: usize) -> NifTerm {
+    make_boxed(heap_index)
+}
+
+/// Read a float off a heap
+///
+/// # Returns
+/// The value, or `None` if `term` is not a float on `heap`
+pub(crate) fn float_on_heap(heap: &[NifTerm], term: NifTerm) -> Option<f64> {
+    if term & TAG_PRIMARY_MASK != TAG_PRIMARY_BOXED {
+        return None;
+    }
+    let index = (term >> 2) as usize;
+    if !is_float_header(*heap.get(index)?) {
+        return None;
+    }
+    heap.get(index + 1).map(|&bits| f64::from_bits(bits))
+}
+
+/// Allocate a float on the heap of an environment
+///
+/// # Returns
+/// The float, or `None` if the heap is full
+pub(crate) fn allocate_float(env: &NifEnv, value: f64) -> Option<NifTerm> {
+    let heap_index = env.allocate_heap(1 + FLOAT_DATA_WORDS)?;
+    let process = env.process();
+    let mut heap_data = process.heap_slice_mut();
+    heap_data[heap_index] = HEADER_FLONUM;
+    heap_data[heap_index + 1] = value.to_bits();
+    Some(make_float_term(heap_index))
+}
+
+/// Create a float (`enif_make_double`)
+///
+/// # Returns
+/// The float, or a badarg exception if `value` is infinite or NaN or the
+/// heap is full
+pub fn enif_make_double(env: &NifEnv, value: f64) -> NifTerm {
+    if !value.is_finite() {
+        return crate::error_handling::enif_make_badarg(env);
+    }
+    allocate_float(env, value).unwrap_or_else(|| crate::error_handling::enif_make_badarg(env))
+}
+
+/// Get the value of a float (`enif_get_double`)
+///
+/// Integers are not converted.
+///
+/// # Returns
+/// The value, or `None` if `term` is not a float
+pub fn enif_get_double(env: &NifEnv, term: NifTerm) -> Option<f64> {
+    float_on_heap(&env.process().heap_slice(), term)
+}
+
+/// Check if a term is a float (`is_float/1`)
+pub fn enif_is_float(env: &NifEnv, term: NifTerm) -> bool {
+    enif_get_double(env, term).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary_management::enif_inspect_binary;
+    use crate::error_handling::enif_is_exception;
+    use crate::term_creation::{enif_make_binary, enif_make_int, enif_make_tuple};
+    use crate::tuple::is_arity_value;
+    use entities_process::Process;
+    use std::sync::Arc;
+
+    fn test_env() -> NifEnv {
+        NifEnv::from_process(Arc::new(Process::new(41390)))
+    }
+
+    #[test]
+    fn test_make_and_get_double() {
+        let env = test_env();
+        for value in [0.0, -0.0, 1.5, -2.25e-300, f64::MAX, f64::MIN_POSITIVE] {
+            let term = enif_make_double(&env, value);
+            assert!(enif_is_float(&env, term));
+            assert_eq!(enif_get_double(&env, term).map(f64::to_bits), Some(value.to_bits()));
+        }
+        for value in [f64::INFINITY, f64::NEG_INFINITY, f64::NAN] {
+            assert!(enif_is_exception(&env, enif_make_double(&env, value)));
+        }
+    }
+
+    #[test]
+    fn test_float_is_not_other_boxed_terms() {
+        let env = test_env();
+        assert!(!is_arity_value(HEADER_FLONUM));
+        assert_eq!(HEADER_FLONUM & TAG_PRIMARY_MASK, 0);
+
+        let float = enif_make_double(&env, 1.0);
+        assert!(enif_inspect_binary(&env, float).is_none());
+        assert_eq!(enif_get_double(&env, enif_make_binary(&env, vec![1, 2])), None);
+        assert_eq!(enif_get_double(&env, enif_make_tuple(&env, &[float])), None);
+        assert_eq!(enif_get_double(&env, enif_make_int(&env, 1)), None);
+    }
+}
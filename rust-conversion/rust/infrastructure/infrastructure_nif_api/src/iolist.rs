@@ -0,0 +1,154 @@
+//! IO Lists
+//!
+//! Provides the iolist traversal shared by everything that writes iodata:
+//! port commands, socket sends and the file NIF. An iolist is a binary, or
+//! a possibly nested list of bytes (integers 0..=255) and binaries whose
+//! tail is nil or a binary.
+//!
+//! [`iolist_to_iovec`] turns an iolist into segments for vectored I/O
+//! (`writev`) without flattening it: binaries become segments of their own,
+//! sharing the data of reference-counted binaries, and runs of bytes are
+//! gathered into one segment.
+//!
+//! Based on erts_iolist_size and the iovec functions of erl_nif.c
+//! (enif_inspect_iovec)
+
+use crate::binary_management::{enif_inspect_binary, ErlNifBinary};
+use crate::list::cons_on_heap;
+use crate::term_decoding::{decode_small_integer, is_small_integer};
+use crate::{NifEnv, NifTerm};
+use entities_process::term_tags::{NIL, TAG_PRIMARY_BOXED, TAG_PRIMARY_MASK};
+
+/// Element of an iolist met by the traversal
+enum IoListItem {
+    Byte(u8),
+    Binary(ErlNifBinary),
+}
+
+/// Get the number of bytes in an iolist (`erlang:iolist_size/1`)
+///
+/// # Returns
+/// The size, or `None` if `term` is not an iolist
+pub fn iolist_size(env: &NifEnv, term: NifTerm) -> Option<usize> {
+    let mut size = 0;
+    walk_iolist(env, term, &mut |item| {
+        size += match item {
+            IoListItem::Byte(_) => 1,
+            IoListItem::Binary(binary) => binary.size(),
+        };
+    })?;
+    Some(size)
+}
+
+/// Split an iolist into segments for vectored I/O
+///
+/// Empty binaries are left out, so every segment holds data.
+///
+/// # Returns
+/// The segments in order, or `None` if `term` is not an iolist
+pub fn iolist_to_iovec(env: &NifEnv, term: NifTerm) -> Option<Vec<ErlNifBinary>> {
+    let mut segments = Vec::new();
+    let mut bytes = Vec::new();
+    walk_iolist(env, term, &mut |item| match item {
+        IoListItem::Byte(byte) => bytes.push(byte),
+        IoListItem::Binary(binary) => {
+            if !bytes.is_empty() {
+                segments.push(ErlNifBinary::new(std::mem::take(&mut bytes)));
+            }
+            if binary.size() > 0 {
+                segments.push(binary);
+            }
+        }
+    })?;
+    if !bytes.is_empty() {
+        segments.push(ErlNifBinary::new(bytes));
+    }
+    Some(segments)
+}
+
+/// Visit the bytes and binaries of an iolist in order
+fn walk_iolist(env: &NifEnv, term: NifTerm, visit: &mut impl FnMut(IoListItem)) -> Option<()> {
+    if term & TAG_PRIMARY_MASK == TAG_PRIMARY_BOXED {
+        visit(IoListItem::Binary(enif_inspect_binary(env, term)?));
+        return Some(());
+    }
+    let heap = env.process().heap_slice();
+    walk_list(env, &heap, term, visit)
+}
+
+/// Visit a list, iterating over the tails and recursing into nested lists
+fn walk_list(env: &NifEnv, heap: &[NifTerm], term: NifTerm, visit: &mut impl FnMut(IoListItem)) -> Option<()> {
+    let mut term = term;
+    let mut cells = 0;
+    loop {
+        if term == NIL {
+            return Some(());
+        }
+        if term & TAG_PRIMARY_MASK == TAG_PRIMARY_BOXED {
+            visit(IoListItem::Binary(enif_inspect_binary(env, term)?));
+            return Some(());
+        }
+        let (head, tail) = cons_on_heap(heap, term)?;
+        // A longer chain than the heap has cells is circular
+        cells += 1;
+        if cells > heap.len() / 2 {
+            return None;
+        }
+        if is_small_integer(head) && head != NIL {
+            visit(IoListItem::Byte(u8::try_from(decode_small_integer(head)).ok()?));
+        } else if head & TAG_PRIMARY_MASK == TAG_PRIMARY_BOXED {
+            visit(IoListItem::Binary(enif_inspect_binary(env, head)?));
+        } else {
+            walk_list(env, heap, head, visit)?;
+        }
+        term = tail;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary_management::enif_alloc_binary;
+    use crate::term_creation::{enif_make_binary, enif_make_int, enif_make_list, enif_make_list_cell};
+    use entities_data_handling::binary::ERL_ONHEAP_BIN_LIMIT;
+    use entities_process::Process;
+    use std::sync::Arc;
+
+    fn test_env() -> NifEnv {
+        NifEnv::from_process(Arc::new(Process::new(41381)))
+    }
+
+    #[test]
+    fn test_iolist_segments() {
+        let env = test_env();
+        let large = enif_make_binary(&env, enif_alloc_binary(ERL_ONHEAP_BIN_LIMIT + 1).unwrap());
+        let nested = enif_make_list(&env, &[enif_make_int(&env, 2), enif_make_binary(&env, vec![])]);
+        // [1, [2, <<>>], Large, 4 | <<5>>]
+        let tail = enif_make_binary(&env, vec![5]);
+        let cell = enif_make_list_cell(&env, enif_make_int(&env, 4), tail);
+        let cell = enif_make_list_cell(&env, large, cell);
+        let cell = enif_make_list_cell(&env, nested, cell);
+        let iolist = enif_make_list_cell(&env, enif_make_int(&env, 1), cell);
+
+        assert_eq!(iolist_size(&env, iolist), Some(ERL_ONHEAP_BIN_LIMIT + 5));
+        let segments = iolist_to_iovec(&env, iolist).unwrap();
+        let sizes: Vec<usize> = segments.iter().map(ErlNifBinary::size).collect();
+        assert_eq!(sizes, vec![2, ERL_ONHEAP_BIN_LIMIT + 1, 1, 1]);
+        assert_eq!(segments[0].data(), &[1, 2]);
+        // The large binary is shared, not copied
+        assert!(segments[1].is_shared());
+        assert_eq!(segments[3].data(), &[5]);
+
+        assert_eq!(iolist_size(&env, NIL), Some(0));
+        assert_eq!(iolist_size(&env, tail), Some(1));
+    }
+
+    #[test]
+    fn test_not_iolist() {
+        let env = test_env();
+        assert_eq!(iolist_size(&env, enif_make_int(&env, 1)), None);
+        assert!(iolist_to_iovec(&env, enif_make_list(&env, &[enif_make_int(&env, 256)])).is_none());
+        let improper = enif_make_list_cell(&env, enif_make_int(&env, 1), enif_make_int(&env, 2));
+        assert_eq!(iolist_size(&env, improper), None);
+    }
+}
@@ -15,13 +15,38 @@
 //! - **Term Decoding**: Functions to decode Erlang terms (`enif_get_*`)
 //! - **Error Handling**: Functions for exception handling
 //! - **Resource Management**: Functions for managing NIF resources
+//! - **Binaries**: Allocation, inspection and sub-binaries of heap and
+//!   reference-counted binaries (`enif_alloc_binary`, `enif_inspect_binary`,
+//!   `enif_make_sub_binary`, `enif_inspect_iolist_as_binary`)
+//! - **Scheduling**: Rescheduling of long-running NIFs on normal and dirty
+//!   schedulers (`enif_schedule_nif`, `enif_consume_timeslice`)
+//! - **Tuples**: Boxed tuples with arity headers, element access and
+//!   copy-on-write `setelement` (`enif_is_tuple`, `erts_element`,
+//!   `erts_setelement`)
+//! - **Floats**: Boxed floats holding IEEE 754 doubles (`enif_make_double`,
+//!   `enif_get_double`)
+//! - **Lists**: Cons cells, list construction, cell access, length and
+//!   reversal (`enif_make_list_from_array`, `enif_get_list_cell`,
+//!   `enif_get_list_length`, `enif_make_reverse_list`)
+//! - **IO Lists**: Size and segments of iolists without flattening them
+//!   (`iolist_size`, `iolist_to_iovec`), shared by ports, sockets and the
+//!   file NIF
+//! - **Term Comparison**: Term order, identity and hashing of heap terms
+//!   (`enif_compare`, `enif_is_identical`, `enif_hash`)
+//! - **Term Copying**: Deep copies of terms between heaps, optionally
+//!   preserving sharing (`copy_struct`, `copy_shared`, `size_object`)
+//! - **Messaging**: Process-independent environments, term copying and
+//!   sending from native threads (`enif_alloc_env`, `enif_make_copy`, `enif_send`)
 //!
 //! ## Term Representation
 //!
 //! Erlang terms are represented as `u64` values (Eterm). Terms use a tagged pointer scheme
 //! where the lower bits indicate the term type:
 //! - Immediate values (small integers, atoms, nil) are encoded directly
-//! - Boxed values (tuples, lists, binaries) are pointers to heap structures
+//! - Boxed values (tuples, floats, bignums, binaries) are pointers to a header
+//!   word on the heap; the header tells them apart, tuples having an arity
+//!   header and floats `HEADER_FLONUM`
+//! - Lists are pointers to cons cells
 //!
 //! ## NIF Environment
 //!
@@ -39,13 +64,31 @@ pub mod term_creation;
 pub mod term_decoding;
 pub mod error_handling;
 pub mod resource_management;
+pub mod binary_management;
 pub mod nif_env;
+pub mod nif_scheduling;
+pub mod term_copy;
+pub mod msg_environment;
+pub mod tuple;
+pub mod float;
+pub mod term_compare;
+pub mod list;
+pub mod iolist;
 
 pub use term_creation::*;
 pub use term_decoding::*;
 pub use error_handling::*;
 pub use resource_management::*;
+pub use binary_management::*;
 pub use nif_env::*;
+pub use nif_scheduling::*;
+pub use term_copy::*;
+pub use msg_environment::*;
+pub use tuple::*;
+pub use float::*;
+pub use term_compare::*;
+pub use list::*;
+pub use iolist::*;
 
 /// NIF term type (Eterm)
 ///
@@ -0,0 +1,184 @@
+//! List Terms
+//!
+//! Provides the heap layout of lists and the operations on list terms:
+//! construction from arrays, cell access, length and reversal.
+//! Based on the list definitions of erl_term.h and on enif_make_list_cell,
+//! enif_get_list_cell, enif_get_list_length and enif_make_reverse_list in
+//! erl_nif.c.
+//!
+//! A non-empty list is a chain of cons cells: a pointer tagged with
+//! `TAG_PRIMARY_LIST` (`(heap_index << 2) | 0x1`) to two words, the head and
+//! the tail. The empty list is the immediate nil. A list whose last tail is
+//! not nil is improper.
+
+use entities_process::term_tags::{make_list, NIL, TAG_PRIMARY_LIST, TAG_PRIMARY_MASK};
+
+use crate::{NifEnv, NifTerm};
+
+/// Make a pointer to a cons cell at a heap index (`make_list`)
+pub fn make_list_term(heap_index: usize) -> NifTerm {
+    make_list(heap_index)
+}
+
+/// Check if a term is a cons cell pointer (`is_list`)
+pub fn is_cons(term: NifTerm) -> bool {
+    term & TAG_PRIMARY_MASK == TAG_PRIMARY_LIST
+}
+
+/// Locate a cons cell on a heap
+///
+/// # Returns
+/// The head and the tail, or `None` if `term` is not a cons cell on `heap`
+pub(crate) fn cons_on_heap(heap: &[NifTerm], term: NifTerm) -> Option<(NifTerm, NifTerm)> {
+    if !is_cons(term) {
+        return None;
+    }
+    let index = (term >> 2) as usize;
+    Some((*heap.get(index)?, *heap.get(index + 1)?))
+}
+
+/// Allocate a list on the heap of an environment
+///
+/// The cells are allocated together, so either the whole list is built or
+/// nothing is.
+///
+/// # Returns
+/// The list ending in `tail`, or `None` if the heap is full
+pub(crate) fn allocate_list(env: &NifEnv, elements: &[NifTerm], tail: NifTerm) -> Option<NifTerm> {
+    if elements.is_empty() {
+        return Some(tail);
+    }
+    let heap_index = env.allocate_heap(2 * elements.len())?;
+    let process = env.process();
+    let mut heap_data = process.heap_slice_mut();
+    for (i, &element) in elements.iter().enumerate() {
+        let cell = heap_index + 2 * i;
+        heap_data[cell] = element;
+        heap_data[cell + 1] = if i + 1 == elements.len() { tail } else { make_list_term(cell + 2) };
+    }
+    Some(make_list_term(heap_index))
+}
+
+/// Check if a term is a list, empty or not (`enif_is_list`)
+///
+/// Only the first cell is checked, so an improper list is a list.
+pub fn enif_is_list(env: &NifEnv, term: NifTerm) -> bool {
+    term == NIL || cons_on_heap(&env.process().heap_slice(), term).is_some()
+}
+
+/// Get the head and tail of a list (`enif_get_list_cell`)
+///
+/// # Returns
+/// The head and the tail, or `None` if `term` is not a non-empty list
+pub fn enif_get_list_cell(env: &NifEnv, term: NifTerm) -> Option<(NifTerm, NifTerm)> {
+    cons_on_heap(&env.process().heap_slice(), term)
+}
+
+/// Get the length of a proper list (`enif_get_list_length`)
+///
+/// # Returns
+/// The length, or `None` if `term` is not a proper list
+pub fn enif_get_list_length(env: &NifEnv, term: NifTerm) -> Option<usize> {
+    let heap = env.process().heap_slice();
+    let mut length = 0;
+    let mut cell = term;
+    while cell != NIL {
+        let (_, tail) = cons_on_heap(&heap, cell)?;
+        length += 1;
+        // A longer chain than the heap has cells is circular
+        if length > heap.len() / 2 {
+            return None;
+        }
+        cell = tail;
+    }
+    Some(length)
+}
+
+/// Create a list from an array of terms (`enif_make_list_from_array`)
+///
+/// # Arguments
+/// * `env` - NIF environment
+/// * `arr` - Array of term elements
+/// * `cnt` - Number of elements of `arr` to use
+///
+/// # Returns
+/// The list, or a badarg exception if `cnt` exceeds the array or the heap is
+/// full
+pub fn enif_make_list_from_array(env: &NifEnv, arr: &[NifTerm], cnt: usize) -> NifTerm {
+    arr.get(..cnt)
+        .and_then(|elements| allocate_list(env, elements, NIL))
+        .unwrap_or_else(|| crate::error_handling::enif_make_badarg(env))
+}
+
+/// Reverse a proper list (`enif_make_reverse_list`)
+///
+/// # Returns
+/// The reversed list, or `None` if `term` is not a proper list or the heap
+/// is full
+pub fn enif_make_reverse_list(env: &NifEnv, term: NifTerm) -> Option<NifTerm> {
+    let mut elements = {
+        let heap = env.process().heap_slice();
+        let mut elements = Vec::new();
+        let mut cell = term;
+        while cell != NIL {
+            let (head, tail) = cons_on_heap(&heap, cell)?;
+            elements.push(head);
+            if elements.len() > heap.len() / 2 {
+                return None;
+            }
+            cell = tail;
+        }
+        elements
+    };
+    elements.reverse();
+    allocate_list(env, &elements, NIL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::term_creation::{enif_make_int, enif_make_list, enif_make_list_cell};
+    use entities_process::Process;
+    use std::sync::Arc;
+
+    fn test_env() -> NifEnv {
+        NifEnv::from_process(Arc::new(Process::new(41380)))
+    }
+
+    #[test]
+    fn test_list_cells_and_length() {
+        let env = test_env();
+        let int = |n| enif_make_int(&env, n);
+        let list = enif_make_list_from_array(&env, &[int(1), int(2), int(4)], 3);
+
+        let (head, tail) = enif_get_list_cell(&env, list).unwrap();
+        assert_eq!(head, int(1));
+        assert_eq!(enif_get_list_cell(&env, tail).unwrap().0, int(2));
+        assert_eq!(enif_get_list_length(&env, list), Some(3));
+        assert_eq!(enif_get_list_length(&env, NIL), Some(0));
+        assert_eq!(enif_get_list_cell(&env, NIL), None);
+        assert!(enif_is_list(&env, list));
+        assert!(enif_is_list(&env, NIL));
+        assert!(!enif_is_list(&env, int(1)));
+
+        // [1 | 2] is a list, but not a proper one
+        let improper = enif_make_list_cell(&env, int(1), int(2));
+        assert!(enif_is_list(&env, improper));
+        assert_eq!(enif_get_list_length(&env, improper), None);
+        assert_eq!(enif_make_reverse_list(&env, improper), None);
+
+        // Count beyond the array is badarg
+        let term = enif_make_list_from_array(&env, &[int(1)], 2);
+        assert!(crate::error_handling::enif_is_exception(&env, term));
+    }
+
+    #[test]
+    fn test_reverse_list() {
+        let env = test_env();
+        let int = |n| enif_make_int(&env, n);
+        let list = enif_make_list(&env, &[int(1), int(2), int(4)]);
+        let reversed = enif_make_reverse_list(&env, list).unwrap();
+        assert_eq!(crate::term_decoding::enif_get_list(&env, reversed), Some(vec![int(4), int(2), int(1)]));
+        assert_eq!(enif_make_reverse_list(&env, NIL), Some(NIL));
+    }
+}
@@ -0,0 +1,257 @@
+//! Process-Independent Environments and Sending
+//!
+//! Provides process-independent environments (`enif_alloc_env`,
+//! `enif_free_env`, `enif_clear_env`), term copying between environments
+//! (`enif_make_copy`) and sending messages from NIFs and native threads
+//! (`enif_send`).
+//!
+//! A process-independent environment owns a private heap, held by a phony
+//! process that is never registered in the process table. A native thread
+//! created by a NIF builds terms in such an environment and sends them to
+//! any process with `enif_send`. Terms are heap-relative, so a term can only
+//! be used with the environment it was built in; `enif_make_copy` copies it
+//! into another environment, and `enif_send` copies the message into a heap
+//! fragment attached to the message, so the receiver never references the
+//! sender's heap. Both copy with [`copy_struct`], except that `enif_send`
+//! preserves sharing with [`copy_shared`] when the calling process was
+//! spawned with the `copy_shared` option.
+//! Based on erl_nif.c
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use entities_process::{Process, ProcessId};
+use infrastructure_utilities::process_table::get_global_process_table;
+
+use crate::binary_management::release_process_binaries;
+use crate::resource_management::release_process_resources;
+use crate::term_copy::{copy_shared, copy_struct};
+use crate::{NifEnv, NifTerm};
+
+/// Next id for the phony processes of process-independent environments;
+/// ids start in the upper half of the id space to stay clear of real pids
+static NEXT_PHONY_PROCESS_ID: AtomicU64 = AtomicU64::new(1 << 63);
+
+/// Allocate a process-independent environment (`enif_alloc_env`)
+///
+/// The environment can be used from any thread to build terms that are
+/// later sent with [`enif_send`] or copied with [`enif_make_copy`]. It must
+/// be released with [`enif_free_env`].
+pub fn enif_alloc_env() -> NifEnv {
+    NifEnv::process_independent(Arc::new(Process::new(new_phony_process_id())))
+}
+
+/// Free a process-independent environment (`enif_free_env`)
+///
+/// Releases the references held by resource terms built in the environment.
+pub fn enif_free_env(env: NifEnv) {
+    if env.is_process_independent() {
+        release_process_resources(env.process_id());
+        release_process_binaries(env.process_id());
+    }
+}
+
+/// Discard all terms in a process-independent environment so it can be
+/// reused (`enif_clear_env`)
+///
+/// Has no effect on an environment bound to a process.
+pub fn enif_clear_env(env: &mut NifEnv) {
+    if !env.is_process_independent() {
+        return;
+    }
+    release_process_resources(env.process_id());
+    release_process_binaries(env.process_id());
+    env.replace_process(Arc::new(Process::new(new_phony_process_id())));
+}
+
+/// Copy a term from one environment into another (`enif_make_copy`)
+///
+/// # Arguments
+/// * `dst_env` - Environment to copy the term into
+/// * `src_env` - Environment the term was built in
+/// * `src_term` - Term to copy
+///
+/// # Returns
+/// * `Some(term)` - The copy, valid in `dst_env`
+/// * `None` - The heap of `dst_env` is full
+pub fn enif_make_copy(dst_env: &NifEnv, src_env: &NifEnv, src_term: NifTerm) -> Option<NifTerm> {
+    copy_struct(src_env, src_term).copy_to_heap(dst_env)
+}
+
+/// Send a message to a process (`enif_send`)
+///
+/// The message is copied into a heap fragment attached to the message, so
+/// it may be sent from any thread while the receiver runs. When `msg_env`
+/// is given the message was built in it and the environment is cleared, as
+/// by [`enif_clear_env`], whether or not the send succeeds. Sharing within
+/// the message is preserved if the process of `caller_env` was spawned with
+/// the `copy_shared` option.
+///
+/// # Arguments
+/// * `caller_env` - Environment of the calling NIF, or `None` on a thread
+///   that is not a scheduler thread
+/// * `to_pid` - Receiving process
+/// * `msg_env` - Process-independent environment the message was built in,
+///   or `None` if it was built in `caller_env`
+/// * `msg` - Message term
+///
+/// # Returns
+/// * `true` - The message was sent
+/// * `false` - The receiver is not alive, or no environment holds the message
+pub fn enif_send(
+    caller_env: Option<&NifEnv>,
+    to_pid: ProcessId,
+    msg_env: Option<&mut NifEnv>,
+    msg: NifTerm,
+) -> bool {
+    let shared = caller_env.is_some_and(|env| env.process().copy_shared());
+    let fragment = match (msg_env.as_deref(), caller_env) {
+        (Some(env), _) | (None, Some(env)) if shared => copy_shared(env, msg),
+        (Some(env), _) | (None, Some(env)) => copy_struct(env, msg),
+        (None, None) => return false,
+    };
+    if let Some(env) = msg_env {
+        enif_clear_env(env);
+    }
+
+    let Some(receiver) = get_global_process_table().lookup(to_pid) else {
+        return false;
+    };
+    receiver.send_message(fragment.into_message(to_pid));
+    true
+}
+
+fn new_phony_process_id() -> ProcessId {
+    NEXT_PHONY_PROCESS_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary_management::enif_inspect_binary;
+    use crate::resource_management::{enif_alloc_resource, enif_make_resource, ErlNifResourceType};
+    use crate::term_creation::{enif_make_binary, enif_make_int, enif_make_list, enif_make_tuple};
+    use crate::term_decoding::{enif_get_binary, enif_get_int, enif_get_list, enif_get_tuple};
+    use entities_process::{InitialCall, SpawnOpts};
+
+    #[test]
+    fn test_alloc_env_is_process_independent() {
+        let env = enif_alloc_env();
+        let other = enif_alloc_env();
+        assert!(env.is_process_independent());
+        assert_ne!(env.process_id(), other.process_id());
+        assert!(get_global_process_table().lookup(env.process_id()).is_none());
+        enif_free_env(env);
+        enif_free_env(other);
+    }
+
+    #[test]
+    fn test_clear_env_discards_terms() {
+        let mut env = enif_alloc_env();
+        let pid = env.process_id();
+        enif_make_tuple(&env, &[enif_make_int(&env, 1)]);
+        assert!(env.heap_top_index() > 0);
+
+        enif_clear_env(&mut env);
+        assert_eq!(env.heap_top_index(), 0);
+        assert_ne!(env.process_id(), pid);
+        enif_free_env(env);
+    }
+
+    #[test]
+    fn test_make_copy_tuple_and_list() {
+        let src = enif_alloc_env();
+        let dst = enif_alloc_env();
+        // Shift the destination heap so the copy must be relocated
+        dst.allocate_heap(7).unwrap();
+
+        let list = enif_make_list(&src, &[enif_make_int(&src, 1), enif_make_int(&src, 2)]);
+        let tuple = enif_make_tuple(&src, &[enif_make_int(&src, 30), list]);
+        let copy = enif_make_copy(&dst, &src, tuple).unwrap();
+        enif_free_env(src);
+
+        let elements = enif_get_tuple(&dst, copy).unwrap();
+        assert_eq!(enif_get_int(&dst, elements[0]), Some(30));
+        let list = enif_get_list(&dst, elements[1]).unwrap();
+        assert_eq!(enif_get_int(&dst, list[0]), Some(1));
+        assert_eq!(enif_get_int(&dst, list[1]), Some(2));
+        enif_free_env(dst);
+    }
+
+    #[test]
+    fn test_make_copy_immediate_and_full_heap() {
+        let src = enif_alloc_env();
+        let dst = enif_alloc_env();
+        let small = enif_make_int(&src, 5);
+        assert_eq!(enif_make_copy(&dst, &src, small), Some(small));
+
+        let binary = enif_make_binary(&src, &[0u8; 64]);
+        dst.allocate_heap(dst.available_heap_space()).unwrap();
+        assert_eq!(enif_make_copy(&dst, &src, binary), None);
+        enif_free_env(src);
+        enif_free_env(dst);
+    }
+
+    #[test]
+    fn test_make_copy_keeps_resource_alive() {
+        let resource_type = ErlNifResourceType::new("msg_env_res".to_string(), "msg_env".to_string());
+        let src = enif_alloc_env();
+        let dst = enif_alloc_env();
+        let resource = enif_alloc_resource(&resource_type, 8).unwrap();
+        let term = enif_make_resource(&src, &resource);
+        enif_make_copy(&dst, &src, term).unwrap();
+        assert_eq!(resource.ref_count(), 3);
+
+        enif_free_env(src);
+        assert_eq!(resource.ref_count(), 2);
+        enif_free_env(dst);
+        assert_eq!(resource.ref_count(), 1);
+    }
+
+    #[test]
+    fn test_make_copy_shares_refc_binary() {
+        let src = enif_alloc_env();
+        let dst = enif_alloc_env();
+        let empty = enif_make_binary(&src, b"");
+        let large = enif_make_binary(&src, vec![5u8; 500]);
+        let tuple = enif_make_tuple(&src, &[empty, large]);
+        let copy = enif_make_copy(&dst, &src, tuple).unwrap();
+        let original = enif_inspect_binary(&src, large).unwrap().into_refc();
+        enif_free_env(src);
+
+        let elements = enif_get_tuple(&dst, copy).unwrap();
+        assert_eq!(enif_get_binary(&dst, elements[0]), Some(Vec::new()));
+        let copied = enif_inspect_binary(&dst, elements[1]).unwrap().into_refc();
+        assert!(copied.shares_storage(&original));
+        enif_free_env(dst);
+        assert_eq!(original.ref_count(), 2);
+    }
+
+    #[test]
+    fn test_send_without_env_fails() {
+        assert!(!enif_send(None, 40710, None, enif_make_int(&enif_alloc_env(), 1)));
+    }
+
+    #[test]
+    fn test_send_preserves_sharing_with_copy_shared() {
+        let receiver = Arc::new(Process::new(40720));
+        get_global_process_table().insert(40720, Arc::clone(&receiver));
+        let opts = SpawnOpts {
+            copy_shared: true,
+            ..SpawnOpts::default()
+        };
+        let sharing_process =
+            Process::spawned_with_opts(40721, None, InitialCall::new("m", "f", 0), &opts);
+        let sharing = NifEnv::from_process(Arc::new(sharing_process));
+        let flat = NifEnv::from_process(Arc::new(Process::new(40722)));
+
+        for (env, words) in [(&sharing, 2 + 3), (&flat, 2 * 2 + 3)] {
+            let inner = enif_make_tuple(env, &[enif_make_int(env, 1)]);
+            let middle = enif_make_tuple(env, &[inner, inner]);
+            let before = receiver.message_fragment_words();
+            assert!(enif_send(Some(env), 40720, None, middle));
+            assert_eq!(receiver.message_fragment_words() - before, words);
+        }
+        get_global_process_table().remove(40720);
+    }
+}
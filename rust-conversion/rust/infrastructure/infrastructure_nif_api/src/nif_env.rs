@@ -4,10 +4,12 @@
 //! Since all NIFs are in Rust, this provides safe access to the Process structure
 //! and its heap for term allocation.
 
-use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use entities_process::Process;
 use entities_process::ProcessId;
 use infrastructure_utilities::process_table::get_global_process_table;
+use crate::nif_scheduling::ScheduledNif;
 
 /// NIF Environment
 ///
@@ -19,6 +21,14 @@ use infrastructure_utilities::process_table::get_global_process_table;
 pub struct NifEnv {
     /// Process reference - provides access to the heap
     process: Arc<Process>,
+    /// Reductions consumed by NIFs running in this environment
+    reductions: AtomicUsize,
+    /// Reductions consumed in the current timeslice
+    timeslice: AtomicUsize,
+    /// Call scheduled with `enif_schedule_nif` by the running NIF
+    scheduled: Mutex<Option<ScheduledNif>>,
+    /// Environment was created by `enif_alloc_env` and owns its heap
+    independent: bool,
 }
 
 impl NifEnv {
@@ -34,9 +44,7 @@ impl NifEnv {
     /// * `None` - If process is not found
     pub fn from_process_id(process_id: ProcessId) -> Option<Self> {
         let table = get_global_process_table();
-        table.lookup(process_id).map(|process| Self {
-            process,
-        })
+        table.lookup(process_id).map(Self::from_process)
     }
 
     /// Create a new NIF environment from a Process reference
@@ -47,7 +55,33 @@ impl NifEnv {
     /// # Returns
     /// * `NifEnv` - New NIF environment
     pub fn from_process(process: Arc<Process>) -> Self {
-        Self { process }
+        Self {
+            process,
+            reductions: AtomicUsize::new(0),
+            timeslice: AtomicUsize::new(0),
+            scheduled: Mutex::new(None),
+            independent: false,
+        }
+    }
+
+    /// Create a process-independent environment around a phony process
+    /// owning the environment's heap
+    pub(crate) fn process_independent(process: Arc<Process>) -> Self {
+        Self {
+            independent: true,
+            ..Self::from_process(process)
+        }
+    }
+
+    /// Check if the environment was created by `enif_alloc_env`
+    pub fn is_process_independent(&self) -> bool {
+        self.independent
+    }
+
+    /// Replace the phony process of a process-independent environment,
+    /// discarding all terms built in it
+    pub(crate) fn replace_process(&mut self, process: Arc<Process>) {
+        self.process = process;
     }
 
     /// Get the process ID
@@ -105,6 +139,35 @@ impl NifEnv {
     pub fn heap_size(&self) -> usize {
         self.process.heap_sz()
     }
+
+    /// Get the reductions consumed by NIFs running in this environment
+    pub fn reductions(&self) -> usize {
+        self.reductions.load(Ordering::Relaxed)
+    }
+
+    /// Add consumed reductions
+    ///
+    /// # Returns
+    /// Reductions consumed in the current timeslice
+    pub(crate) fn add_reductions(&self, reductions: usize) -> usize {
+        self.reductions.fetch_add(reductions, Ordering::Relaxed);
+        self.timeslice.fetch_add(reductions, Ordering::Relaxed) + reductions
+    }
+
+    /// Start a new timeslice
+    pub(crate) fn reset_timeslice(&self) {
+        self.timeslice.store(0, Ordering::Relaxed);
+    }
+
+    /// Record the call scheduled by the running NIF
+    pub(crate) fn set_scheduled_nif(&self, call: ScheduledNif) {
+        *self.scheduled.lock().unwrap() = Some(call);
+    }
+
+    /// Take the call scheduled by the last NIF to run
+    pub(crate) fn take_scheduled_nif(&self) -> Option<ScheduledNif> {
+        self.scheduled.lock().unwrap().take()
+    }
 }
 
 /// Helper function to get Process from NifEnv
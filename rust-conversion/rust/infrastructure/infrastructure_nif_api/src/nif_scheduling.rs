@@ -0,0 +1,561 @@
+//! NIF Scheduling Functions
+//!
+//! Provides `enif_schedule_nif`, `enif_consume_timeslice` and
+//! `enif_thread_type`, and the dirty CPU and dirty I/O scheduler pools that
+//! run NIFs scheduled as dirty.
+//!
+//! A long-running NIF splits its work into steps. Each step does a slice of
+//! work, reports it with `enif_consume_timeslice`, and when the timeslice is
+//! exhausted calls `enif_schedule_nif` with the function and arguments for the
+//! next step, returning its result. [`DirtySchedulers::execute_nif`] runs the
+//! resulting chain, each step on the scheduler selected by its flags, until a
+//! step returns without rescheduling.
+//!
+//! Blocking work that is not a NIF call, such as file I/O, is offloaded with
+//! [`DirtySchedulers::spawn`], which returns a [`DirtyTask`] immediately so
+//! that the normal scheduler keeps running while the work is in progress.
+//!
+//! ## Design Principles
+//!
+//! - **Safe Rust Only**: NIFs are Rust closures and arguments are copied
+//!   into the scheduled call
+//! - **Blocking Callers**: `execute_nif` blocks the calling scheduler until a
+//!   dirty step completes, standing in for suspending the process
+//!
+//! Based on erl_nif.c and erl_dirty_bif.c
+
+use super::{NifEnv, NifTerm};
+use std::cell::Cell;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::JoinHandle;
+
+/// Value returned by a NIF that has called `enif_schedule_nif`
+///
+/// The NIF must return this value unchanged; it is never seen by Erlang code.
+pub use entities_process::term_tags::THE_NON_VALUE;
+
+/// Reductions in a full timeslice (`CONTEXT_REDS`)
+pub const CONTEXT_REDS: usize = 4000;
+
+/// Default number of dirty I/O schedulers
+pub const DEFAULT_DIRTY_IO_SCHEDULERS: usize = 10;
+
+/// NIF function
+pub type ErlNifFunction = Arc<dyn Fn(&NifEnv, &[NifTerm]) -> NifTerm + Send + Sync>;
+
+/// Scheduler a NIF is scheduled on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum ErlNifDirtyFlags {
+    /// Normal scheduler
+    Normal = 0,
+    /// Dirty CPU scheduler (`ERL_NIF_DIRTY_JOB_CPU_BOUND`)
+    DirtyCpu = 1,
+    /// Dirty I/O scheduler (`ERL_NIF_DIRTY_JOB_IO_BOUND`)
+    DirtyIo = 2,
+}
+
+/// Type of the current thread, as returned by `enif_thread_type`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErlNifThreadType {
+    /// Not a scheduler thread (`ERL_NIF_THR_UNDEFINED`)
+    Undefined,
+    /// Normal scheduler thread (`ERL_NIF_THR_NORMAL_SCHEDULER`)
+    NormalScheduler,
+    /// Dirty CPU scheduler thread (`ERL_NIF_THR_DIRTY_CPU_SCHEDULER`)
+    DirtyCpuScheduler,
+    /// Dirty I/O scheduler thread (`ERL_NIF_THR_DIRTY_IO_SCHEDULER`)
+    DirtyIoScheduler,
+}
+
+thread_local! {
+    /// Scheduler type of the current thread
+    static THREAD_TYPE: Cell<ErlNifThreadType> = const { Cell::new(ErlNifThreadType::Undefined) };
+}
+
+/// Get the scheduler type of the current thread (`enif_thread_type`)
+pub fn enif_thread_type() -> ErlNifThreadType {
+    THREAD_TYPE.with(|thread_type| thread_type.get())
+}
+
+/// Mark the current thread as a normal scheduler thread
+///
+/// Called by scheduler threads when they start.
+pub fn set_normal_scheduler_thread() {
+    THREAD_TYPE.with(|thread_type| thread_type.set(ErlNifThreadType::NormalScheduler));
+}
+
+/// NIF call scheduled with `enif_schedule_nif`
+#[derive(Clone)]
+pub struct ScheduledNif {
+    /// Function name, reported as the current function while the call runs
+    pub name: String,
+    /// Scheduler to run the call on
+    pub flags: ErlNifDirtyFlags,
+    /// Function to call
+    pub function: ErlNifFunction,
+    /// Arguments to call it with
+    pub args: Vec<NifTerm>,
+}
+
+impl std::fmt::Debug for ScheduledNif {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScheduledNif")
+            .field("name", &self.name)
+            .field("flags", &self.flags)
+            .field("args", &self.args)
+            .finish()
+    }
+}
+
+/// Schedule a NIF call to run after the current NIF returns
+///
+/// # Arguments
+///
+/// * `env` - NIF environment of the calling NIF
+/// * `name` - Name of the scheduled function
+/// * `flags` - Scheduler to run the call on
+/// * `function` - Function to call
+/// * `args` - Arguments, copied into the scheduled call
+///
+/// # Returns
+///
+/// [`THE_NON_VALUE`], which the calling NIF must return
+///
+/// # See Also
+///
+/// - `erts/emulator/beam/erl_nif.c:enif_schedule_nif()` - C implementation
+pub fn enif_schedule_nif(
+    env: &NifEnv,
+    name: &str,
+    flags: ErlNifDirtyFlags,
+    function: ErlNifFunction,
+    args: &[NifTerm],
+) -> NifTerm {
+    env.set_scheduled_nif(ScheduledNif {
+        name: name.to_string(),
+        flags,
+        function,
+        args: args.to_vec(),
+    });
+    THE_NON_VALUE
+}
+
+/// Report the share of a timeslice consumed by the calling NIF
+///
+/// Bumps the process reductions by `percent` of [`CONTEXT_REDS`].
+///
+/// # Arguments
+///
+/// * `env` - NIF environment of the calling NIF
+/// * `percent` - Percentage of a full timeslice consumed since the last call, clamped to 1..=100
+///
+/// # Returns
+///
+/// `true` if the timeslice is exhausted and the NIF should schedule its
+/// remaining work with `enif_schedule_nif`
+///
+/// # See Also
+///
+/// - `erts/emulator/beam/erl_nif.c:enif_consume_timeslice()` - C implementation
+pub fn enif_consume_timeslice(env: &NifEnv, percent: i32) -> bool {
+    let percent = percent.clamp(1, 100) as usize;
+    env.add_reductions(CONTEXT_REDS * percent / 100) >= CONTEXT_REDS
+}
+
+/// NIF scheduling errors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NifScheduleError {
+    /// The pool for the requested dirty scheduler type has no threads
+    NoDirtySchedulers(ErlNifDirtyFlags),
+    /// A dirty scheduler exited before completing the call
+    SchedulerStopped,
+}
+
+impl std::fmt::Display for NifScheduleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NifScheduleError::NoDirtySchedulers(flags) => write!(f, "No dirty schedulers for {:?}", flags),
+            NifScheduleError::SchedulerStopped => write!(f, "Dirty scheduler stopped"),
+        }
+    }
+}
+
+impl std::error::Error for NifScheduleError {}
+
+/// Job run on a dirty scheduler
+type DirtyJob = Box<dyn FnOnce() + Send>;
+
+/// Pool of dirty scheduler threads of one type
+///
+/// The threads share one run queue, like the dirty run queues of the
+/// emulator, so a job blocked on one thread does not hold up jobs that an
+/// idle thread could run.
+struct DirtyPool {
+    /// Run queue, `None` once the pool is shutting down
+    queue: Option<Sender<DirtyJob>>,
+    /// Scheduler threads
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl DirtyPool {
+    fn new(threads: usize, thread_type: ErlNifThreadType, prefix: &str) -> Self {
+        let (sender, receiver) = mpsc::channel::<DirtyJob>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let handles = (0..threads)
+            .map(|index| {
+                let receiver = Arc::clone(&receiver);
+                std::thread::Builder::new()
+                    .name(format!("{}_{}", prefix, index + 1))
+                    .spawn(move || {
+                        THREAD_TYPE.with(|current| current.set(thread_type));
+                        loop {
+                            // The lock is released before the job runs
+                            let job = receiver.lock().unwrap().recv();
+                            match job {
+                                Ok(job) => job(),
+                                Err(_) => break,
+                            }
+                        }
+                    })
+                    .expect("failed to spawn dirty scheduler")
+            })
+            .collect();
+        Self {
+            queue: Some(sender),
+            threads: handles,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.threads.len()
+    }
+
+    fn submit(&self, job: DirtyJob) -> bool {
+        self.queue.as_ref().is_some_and(|queue| queue.send(job).is_ok())
+    }
+}
+
+impl Drop for DirtyPool {
+    fn drop(&mut self) {
+        self.queue = None;
+        for handle in self.threads.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Dirty CPU and dirty I/O scheduler pools
+pub struct DirtySchedulers {
+    /// Dirty CPU schedulers
+    cpu: DirtyPool,
+    /// Dirty I/O schedulers
+    io: DirtyPool,
+}
+
+impl DirtySchedulers {
+    /// Create the dirty scheduler pools
+    ///
+    /// # Arguments
+    /// * `cpu_schedulers` - Number of dirty CPU schedulers (`+SDcpu`)
+    /// * `io_schedulers` - Number of dirty I/O schedulers (`+SDio`)
+    pub fn new(cpu_schedulers: usize, io_schedulers: usize) -> Self {
+        Self {
+            cpu: DirtyPool::new(cpu_schedulers, ErlNifThreadType::DirtyCpuScheduler, "dirty_cpu_sched"),
+            io: DirtyPool::new(io_schedulers, ErlNifThreadType::DirtyIoScheduler, "dirty_io_sched"),
+        }
+    }
+
+    /// Get the number of dirty CPU schedulers
+    pub fn cpu_schedulers(&self) -> usize {
+        self.cpu.len()
+    }
+
+    /// Get the number of dirty I/O schedulers
+    pub fn io_schedulers(&self) -> usize {
+        self.io.len()
+    }
+
+    /// Execute a NIF and every call it schedules with `enif_schedule_nif`
+    ///
+    /// Normal steps run on the calling thread; dirty steps run on the
+    /// matching pool with an environment for the same process. The
+    /// timeslice is reset before each step.
+    ///
+    /// # Arguments
+    /// * `env` - NIF environment of the calling process
+    /// * `call` - First call to make
+    ///
+    /// # Returns
+    /// * `Ok(term)` - Result of the last step
+    /// * `Err(NifScheduleError)` - A dirty step could not be run
+    pub fn execute_nif(&self, env: &NifEnv, call: ScheduledNif) -> Result<NifTerm, NifScheduleError> {
+        let mut call = call;
+        loop {
+            let (result, next) = match call.flags {
+                ErlNifDirtyFlags::Normal => {
+                    env.reset_timeslice();
+                    let result = (call.function)(env, &call.args);
+                    (result, env.take_scheduled_nif())
+                }
+                ErlNifDirtyFlags::DirtyCpu => self.run_dirty(&self.cpu, env, call)?,
+                ErlNifDirtyFlags::DirtyIo => self.run_dirty(&self.io, env, call)?,
+            };
+            match next {
+                Some(next) => call = next,
+                None => return Ok(result),
+            }
+        }
+    }
+
+    /// Run blocking work on a dirty scheduler without waiting for it
+    ///
+    /// Unlike [`DirtySchedulers::execute_nif`], the calling scheduler is not
+    /// blocked: the work runs on the pool selected by `flags` and its result
+    /// is collected later from the returned task. Work scheduled as
+    /// [`ErlNifDirtyFlags::Normal`] runs on the calling thread.
+    ///
+    /// # Arguments
+    /// * `flags` - Scheduler to run the work on
+    /// * `work` - Work to run
+    ///
+    /// # Returns
+    /// * `Ok(task)` - Task completing with the result of `work`
+    /// * `Err(NifScheduleError)` - The work could not be scheduled
+    pub fn spawn<T, F>(&self, flags: ErlNifDirtyFlags, work: F) -> Result<DirtyTask<T>, NifScheduleError>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let pool = match flags {
+            ErlNifDirtyFlags::Normal => return Ok(DirtyTask::ready(work())),
+            ErlNifDirtyFlags::DirtyCpu => &self.cpu,
+            ErlNifDirtyFlags::DirtyIo => &self.io,
+        };
+        if pool.len() == 0 {
+            return Err(NifScheduleError::NoDirtySchedulers(flags));
+        }
+        let (reply, result) = mpsc::channel();
+        let job: DirtyJob = Box::new(move || {
+            let _ = reply.send(work());
+        });
+        if !pool.submit(job) {
+            return Err(NifScheduleError::SchedulerStopped);
+        }
+        Ok(DirtyTask { result })
+    }
+
+    fn run_dirty(
+        &self,
+        pool: &DirtyPool,
+        env: &NifEnv,
+        call: ScheduledNif,
+    ) -> Result<(NifTerm, Option<ScheduledNif>), NifScheduleError> {
+        if pool.len() == 0 {
+            return Err(NifScheduleError::NoDirtySchedulers(call.flags));
+        }
+        let process = env.process().clone();
+        let (reply, result) = mpsc::channel();
+        let job: DirtyJob = Box::new(move || {
+            let dirty_env = NifEnv::from_process(process);
+            let term = (call.function)(&dirty_env, &call.args);
+            let _ = reply.send((term, dirty_env.take_scheduled_nif(), dirty_env.reductions()));
+        });
+        if !pool.submit(job) {
+            return Err(NifScheduleError::SchedulerStopped);
+        }
+        let (term, next, reductions) = result.recv().map_err(|_| NifScheduleError::SchedulerStopped)?;
+        env.add_reductions(reductions);
+        Ok((term, next))
+    }
+}
+
+/// Work offloaded to a dirty scheduler with [`DirtySchedulers::spawn`]
+///
+/// The result can be taken once, with either [`DirtyTask::wait`] or
+/// [`DirtyTask::try_wait`].
+pub struct DirtyTask<T> {
+    /// Receives the result when the work completes
+    result: Receiver<T>,
+}
+
+impl<T> DirtyTask<T> {
+    /// Create a task that has already completed with `value`
+    pub fn ready(value: T) -> Self {
+        let (sender, result) = mpsc::channel();
+        let _ = sender.send(value);
+        Self { result }
+    }
+
+    /// Block until the work completes
+    ///
+    /// # Returns
+    /// * `Ok(value)` - Result of the work
+    /// * `Err(NifScheduleError::SchedulerStopped)` - The work never completed
+    pub fn wait(self) -> Result<T, NifScheduleError> {
+        self.result.recv().map_err(|_| NifScheduleError::SchedulerStopped)
+    }
+
+    /// Take the result if the work has completed, without blocking
+    ///
+    /// # Returns
+    /// * `None` - The work is still in progress
+    /// * `Some(Ok(value))` - Result of the work
+    /// * `Some(Err(NifScheduleError::SchedulerStopped))` - The work never
+    ///   completed, or its result was already taken
+    pub fn try_wait(&self) -> Option<Result<T, NifScheduleError>> {
+        match self.result.try_recv() {
+            Ok(value) => Some(Ok(value)),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(NifScheduleError::SchedulerStopped)),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for DirtyTask<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DirtyTask").finish_non_exhaustive()
+    }
+}
+
+/// Global dirty schedulers instance
+static GLOBAL_DIRTY_SCHEDULERS: OnceLock<DirtySchedulers> = OnceLock::new();
+
+/// Initialize the global dirty schedulers
+///
+/// # Returns
+/// `false` if the dirty schedulers were already initialized
+pub fn init_global_dirty_schedulers(cpu_schedulers: usize, io_schedulers: usize) -> bool {
+    let mut created = false;
+    GLOBAL_DIRTY_SCHEDULERS.get_or_init(|| {
+        created = true;
+        DirtySchedulers::new(cpu_schedulers, io_schedulers)
+    });
+    created
+}
+
+/// Get the global dirty schedulers, creating them with one dirty CPU
+/// scheduler per available CPU and [`DEFAULT_DIRTY_IO_SCHEDULERS`] if needed
+pub fn get_global_dirty_schedulers() -> &'static DirtySchedulers {
+    GLOBAL_DIRTY_SCHEDULERS.get_or_init(|| {
+        let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+        DirtySchedulers::new(cpus, DEFAULT_DIRTY_IO_SCHEDULERS)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entities_process::Process;
+
+    fn env() -> NifEnv {
+        NifEnv::from_process(Arc::new(Process::new(40700)))
+    }
+
+    fn call(name: &str, flags: ErlNifDirtyFlags, function: ErlNifFunction, args: &[NifTerm]) -> ScheduledNif {
+        ScheduledNif {
+            name: name.to_string(),
+            flags,
+            function,
+            args: args.to_vec(),
+        }
+    }
+
+    /// Sums 1..=n a few numbers per step, rescheduling itself until done
+    fn sum_step() -> ErlNifFunction {
+        Arc::new(|env: &NifEnv, args: &[NifTerm]| {
+            let (mut next, n, mut acc) = (args[0], args[1], args[2]);
+            while next <= n {
+                acc += next;
+                next += 1;
+                if enif_consume_timeslice(env, 40) && next <= n {
+                    return enif_schedule_nif(env, "sum_step", ErlNifDirtyFlags::Normal, sum_step(), &[next, n, acc]);
+                }
+            }
+            acc
+        })
+    }
+
+    #[test]
+    fn test_consume_timeslice() {
+        let env = env();
+        assert!(!enif_consume_timeslice(&env, 50));
+        assert!(!enif_consume_timeslice(&env, 0));
+        assert!(enif_consume_timeslice(&env, 200));
+        assert_eq!(env.reductions(), CONTEXT_REDS * 151 / 100);
+    }
+
+    #[test]
+    fn test_rescheduled_nif_runs_to_completion() {
+        let schedulers = DirtySchedulers::new(0, 0);
+        let env = env();
+        let result = schedulers
+            .execute_nif(&env, call("sum", ErlNifDirtyFlags::Normal, sum_step(), &[1, 10, 0]))
+            .unwrap();
+        assert_eq!(result, 55);
+        assert!(env.take_scheduled_nif().is_none());
+    }
+
+    #[test]
+    fn test_dirty_nif_runs_on_dirty_scheduler() {
+        let schedulers = DirtySchedulers::new(1, 1);
+        let env = env();
+        let finish: ErlNifFunction = Arc::new(|_env: &NifEnv, args: &[NifTerm]| {
+            assert_eq!(enif_thread_type(), ErlNifThreadType::NormalScheduler);
+            args[0] + 1
+        });
+        let io_step: ErlNifFunction = Arc::new(move |env: &NifEnv, args: &[NifTerm]| {
+            assert_eq!(enif_thread_type(), ErlNifThreadType::DirtyIoScheduler);
+            enif_schedule_nif(env, "finish", ErlNifDirtyFlags::Normal, finish.clone(), &[args[0] * 2])
+        });
+        let cpu_step: ErlNifFunction = Arc::new(move |env: &NifEnv, args: &[NifTerm]| {
+            assert_eq!(enif_thread_type(), ErlNifThreadType::DirtyCpuScheduler);
+            enif_schedule_nif(env, "io_step", ErlNifDirtyFlags::DirtyIo, io_step.clone(), args)
+        });
+
+        set_normal_scheduler_thread();
+        let result = schedulers
+            .execute_nif(&env, call("cpu_step", ErlNifDirtyFlags::DirtyCpu, cpu_step, &[20]))
+            .unwrap();
+        assert_eq!(result, 41);
+    }
+
+    #[test]
+    fn test_spawn_does_not_block_caller() {
+        let schedulers = DirtySchedulers::new(0, 2);
+        let (release, blocked) = mpsc::channel::<()>();
+        let task = schedulers
+            .spawn(ErlNifDirtyFlags::DirtyIo, move || {
+                blocked.recv().unwrap();
+                enif_thread_type()
+            })
+            .unwrap();
+        assert!(task.try_wait().is_none());
+        // The blocked job holds one scheduler; the other runs later jobs
+        for n in 0..4 {
+            assert_eq!(schedulers.spawn(ErlNifDirtyFlags::DirtyIo, move || n * 2).unwrap().wait(), Ok(n * 2));
+        }
+
+        release.send(()).unwrap();
+        assert_eq!(task.wait(), Ok(ErlNifThreadType::DirtyIoScheduler));
+
+        let task = schedulers.spawn(ErlNifDirtyFlags::Normal, || 7).unwrap();
+        assert_eq!(task.try_wait(), Some(Ok(7)));
+        assert_eq!(task.try_wait(), Some(Err(NifScheduleError::SchedulerStopped)));
+        assert!(matches!(
+            schedulers.spawn(ErlNifDirtyFlags::DirtyCpu, || 1),
+            Err(NifScheduleError::NoDirtySchedulers(ErlNifDirtyFlags::DirtyCpu))
+        ));
+    }
+
+    #[test]
+    fn test_no_dirty_schedulers() {
+        let schedulers = DirtySchedulers::new(0, 0);
+        let function: ErlNifFunction = Arc::new(|_env: &NifEnv, _args: &[NifTerm]| 1);
+        assert_eq!(
+            schedulers.execute_nif(&env(), call("f", ErlNifDirtyFlags::DirtyIo, function, &[])),
+            Err(NifScheduleError::NoDirtySchedulers(ErlNifDirtyFlags::DirtyIo))
+        );
+    }
+}
@@ -4,6 +4,17 @@
 //! These functions correspond to resource management functions in the C NIF API,
 //! but are implemented using safe Rust patterns.
 //!
+//! ## Resource Lifecycle
+//!
+//! - Resource types are opened with `enif_open_resource_type`, optionally with
+//!   a destructor, a select `stop` callback and a monitor `down` callback
+//! - Resources are reference counted; references are held by native code,
+//!   by resource terms on process heaps until they are garbage collected,
+//!   and by `enif_select` while an fd is selected
+//! - The destructor runs when the last reference is released
+//! - Resources can monitor processes with `enif_monitor_process`; the `down`
+//!   callback runs when the monitored process exits
+//!
 //! ## Design Principles
 //!
 //! - **Safe Rust Only**: All functions use safe Rust types and operations
@@ -11,74 +22,78 @@
 //! - **No C FFI**: Since NIFs are always written in Rust, no C compatibility needed
 
 use super::{NifEnv, NifTerm};
-use std::sync::Arc;
+use entities_process::term_tags::{make_boxed, TAG_PRIMARY_BOXED};
+use entities_process::ProcessId;
+use infrastructure_utilities::process_table::get_global_process_table;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock, Weak};
+
+/// Resource destructor, called with the resource data when the last
+/// reference to a resource is released
+pub type ResourceDtor = Arc<dyn Fn(&mut [u8]) + Send + Sync>;
 
-/// NIF binary structure
+/// Resource stop callback, called when a resource's fd selected with
+/// `enif_select` has been stopped
 ///
-/// Represents a binary that can be passed between NIF functions.
-/// Uses safe Rust types instead of raw pointers.
+/// Arguments are the resource data, the fd, and whether the call was made
+/// directly from `enif_select` (`true`) or later by the poll backend (`false`).
+pub type ResourceStop = Arc<dyn Fn(&mut [u8], i32, bool) + Send + Sync>;
+
+/// Resource down callback, called when a process monitored by a resource exits
 ///
-/// ## Design
+/// Arguments are the resource data, the exited process and the monitor.
+pub type ResourceDown = Arc<dyn Fn(&mut [u8], ProcessId, &ErlNifMonitor) + Send + Sync>;
+
+/// Resource type callbacks
 ///
-/// This is a safe Rust alternative to the C `ErlNifBinary` structure.
-/// It uses `Vec<u8>` for the binary data, which provides automatic
-/// memory management and bounds checking.
-pub struct ErlNifBinary {
-    /// Binary data
-    data: Vec<u8>,
+/// Rust equivalent of `ErlNifResourceTypeInit`.
+#[derive(Clone, Default)]
+pub struct ErlNifResourceTypeInit {
+    /// Destructor (`dtor`)
+    pub dtor: Option<ResourceDtor>,
+    /// Select stop callback (`stop`)
+    pub stop: Option<ResourceStop>,
+    /// Process monitor down callback (`down`)
+    pub down: Option<ResourceDown>,
 }
 
-impl ErlNifBinary {
-    /// Create a new binary from a byte vector
-    ///
-    /// # Arguments
-    /// * `data` - Binary data
-    ///
-    /// # Returns
-    /// A new `ErlNifBinary` instance
-    pub fn new(data: Vec<u8>) -> Self {
-        Self { data }
-    }
-
-    /// Get a reference to the binary data
-    ///
-    /// # Returns
-    /// A slice reference to the binary data
-    pub fn data(&self) -> &[u8] {
-        &self.data
-    }
-
-    /// Get the size of the binary in bytes
-    ///
-    /// # Returns
-    /// The size of the binary
-    pub fn size(&self) -> usize {
-        self.data.len()
-    }
+/// Flags for `enif_open_resource_type`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum ErlNifResourceFlags {
+    /// Create the resource type if it does not exist (`ERL_NIF_RT_CREATE`)
+    Create = 1 << 0,
+    /// Take over an existing resource type (`ERL_NIF_RT_TAKEOVER`)
+    Takeover = 1 << 1,
+}
 
-    /// Convert into the underlying byte vector
-    ///
-    /// # Returns
-    /// The binary data as a `Vec<u8>`
-    pub fn into_vec(self) -> Vec<u8> {
-        self.data
+impl ErlNifResourceFlags {
+    /// Check if a flag set contains this flag
+    pub fn is_set(self, flags: u32) -> bool {
+        flags & self as u32 != 0
     }
 }
 
 /// Resource type identifier
 ///
 /// Represents a resource type for type-safe resource management.
-/// Uses a string identifier instead of a raw pointer.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Uses a string identifier instead of a raw pointer. Clones share the
+/// callbacks, so a takeover by a new module instance applies to resources
+/// already allocated with the type.
+#[derive(Clone)]
 pub struct ErlNifResourceType {
     /// Resource type name
     name: String,
     /// Module name that owns this resource type
     module: String,
+    /// Callbacks, replaced on takeover
+    callbacks: Arc<RwLock<ErlNifResourceTypeInit>>,
 }
 
 impl ErlNifResourceType {
-    /// Create a new resource type
+    /// Create a new resource type without callbacks
     ///
     /// # Arguments
     /// * `name` - Resource type name
@@ -87,7 +102,21 @@ impl ErlNifResourceType {
     /// # Returns
     /// A new `ErlNifResourceType` instance
     pub fn new(name: String, module: String) -> Self {
-        Self { name, module }
+        Self::with_init(name, module, ErlNifResourceTypeInit::default())
+    }
+
+    /// Create a new resource type with callbacks
+    ///
+    /// # Arguments
+    /// * `name` - Resource type name
+    /// * `module` - Module name
+    /// * `init` - Resource type callbacks
+    pub fn with_init(name: String, module: String, init: ErlNifResourceTypeInit) -> Self {
+        Self {
+            name,
+            module,
+            callbacks: Arc::new(RwLock::new(init)),
+        }
     }
 
     /// Get the resource type name
@@ -99,11 +128,206 @@ impl ErlNifResourceType {
     pub fn module(&self) -> &str {
         &self.module
     }
+
+    /// Get the current callbacks of the type
+    pub fn callbacks(&self) -> ErlNifResourceTypeInit {
+        self.callbacks.read().unwrap().clone()
+    }
+}
+
+impl std::fmt::Debug for ErlNifResourceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ErlNifResourceType")
+            .field("name", &self.name)
+            .field("module", &self.module)
+            .finish()
+    }
+}
+
+impl PartialEq for ErlNifResourceType {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.module == other.module
+    }
+}
+
+impl Eq for ErlNifResourceType {}
+
+impl Hash for ErlNifResourceType {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.module.hash(state);
+    }
+}
+
+/// Table of resource types opened by NIF modules
+pub struct ResourceTypeRegistry {
+    /// Map from (module, name) to resource type
+    types: RwLock<HashMap<(String, String), ErlNifResourceType>>,
+}
+
+impl ResourceTypeRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self {
+            types: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Create or take over a resource type (`enif_open_resource_type`)
+    ///
+    /// # Arguments
+    /// * `module` - Module opening the type
+    /// * `name` - Resource type name
+    /// * `init` - Resource type callbacks
+    /// * `flags` - Combination of [`ErlNifResourceFlags`]
+    ///
+    /// # Returns
+    /// * `Ok(resource_type)` - The created or taken over type
+    /// * `Err(ResourceError::TypeExists)` - The type exists and `Takeover` was not given
+    /// * `Err(ResourceError::InvalidResourceType)` - The type does not exist and `Create` was not given
+    pub fn open_resource_type(
+        &self,
+        module: &str,
+        name: &str,
+        init: ErlNifResourceTypeInit,
+        flags: u32,
+    ) -> Result<ErlNifResourceType, ResourceError> {
+        let key = (module.to_string(), name.to_string());
+        let mut types = self.types.write().unwrap();
+        if let Some(existing) = types.get(&key) {
+            if !ErlNifResourceFlags::Takeover.is_set(flags) {
+                return Err(ResourceError::TypeExists);
+            }
+            *existing.callbacks.write().unwrap() = init;
+            return Ok(existing.clone());
+        }
+        if !ErlNifResourceFlags::Create.is_set(flags) {
+            return Err(ResourceError::InvalidResourceType);
+        }
+        let resource_type = ErlNifResourceType::with_init(name.to_string(), module.to_string(), init);
+        types.insert(key, resource_type.clone());
+        Ok(resource_type)
+    }
+
+    /// Look up an open resource type
+    pub fn lookup(&self, module: &str, name: &str) -> Option<ErlNifResourceType> {
+        self.types
+            .read()
+            .unwrap()
+            .get(&(module.to_string(), name.to_string()))
+            .cloned()
+    }
+}
+
+impl Default for ResourceTypeRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Global resource type registry instance
+static GLOBAL_RESOURCE_TYPES: OnceLock<ResourceTypeRegistry> = OnceLock::new();
+
+/// Get the global resource type registry
+pub fn get_global_resource_types() -> &'static ResourceTypeRegistry {
+    GLOBAL_RESOURCE_TYPES.get_or_init(ResourceTypeRegistry::new)
+}
+
+/// Open a resource type in the global registry
+///
+/// # See Also
+///
+/// - [`ResourceTypeRegistry::open_resource_type`]
+/// - `erts/emulator/beam/erl_nif.c:enif_open_resource_type()` - C implementation
+pub fn enif_open_resource_type(
+    module: &str,
+    name: &str,
+    init: ErlNifResourceTypeInit,
+    flags: u32,
+) -> Result<ErlNifResourceType, ResourceError> {
+    get_global_resource_types().open_resource_type(module, name, init, flags)
+}
+
+/// Resource object shared by all references to a resource
+struct ResourceObject {
+    /// Resource type
+    resource_type: ErlNifResourceType,
+    /// Resource data
+    data: Mutex<Box<[u8]>>,
+}
+
+impl Drop for ResourceObject {
+    fn drop(&mut self) {
+        let address = self as *const ResourceObject as usize;
+        get_monitor_table().remove_resource(address);
+        if let Some(dtor) = self.resource_type.callbacks().dtor {
+            dtor(self.data.get_mut().unwrap());
+        }
+    }
+}
+
+/// Reference to a NIF resource
+///
+/// Resources are reference counted. Cloning a reference keeps the resource
+/// (`enif_keep_resource`) and dropping one releases it
+/// (`enif_release_resource`); the type's destructor runs when the last
+/// reference is released, whether held by native code, a term on a process
+/// heap, a select or a monitor callback in progress.
+#[derive(Clone)]
+pub struct ErlNifResource {
+    object: Arc<ResourceObject>,
+}
+
+impl ErlNifResource {
+    /// Get the resource type
+    pub fn resource_type(&self) -> &ErlNifResourceType {
+        &self.object.resource_type
+    }
+
+    /// Get the size of the resource data in bytes
+    pub fn len(&self) -> usize {
+        self.object.data.lock().unwrap().len()
+    }
+
+    /// Check if the resource has no data
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Access the resource data
+    pub fn with_data<R>(&self, f: impl FnOnce(&mut [u8]) -> R) -> R {
+        f(&mut self.object.data.lock().unwrap())
+    }
+
+    /// Get the number of references to the resource
+    pub fn ref_count(&self) -> usize {
+        Arc::strong_count(&self.object)
+    }
+
+    /// Check if two references are to the same resource
+    pub fn ptr_eq(&self, other: &ErlNifResource) -> bool {
+        Arc::ptr_eq(&self.object, &other.object)
+    }
+
+    /// Address identifying the resource
+    fn address(&self) -> usize {
+        Arc::as_ptr(&self.object) as usize
+    }
+}
+
+impl std::fmt::Debug for ErlNifResource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ErlNifResource")
+            .field("resource_type", &self.object.resource_type)
+            .field("len", &self.len())
+            .finish()
+    }
 }
 
 /// Allocate a resource
 ///
-/// Allocates memory for a NIF resource using safe Rust allocation.
+/// Allocates a zeroed resource of `size` bytes with one reference, owned by
+/// the caller.
 ///
 /// # Arguments
 ///
@@ -112,7 +336,7 @@ impl ErlNifResourceType {
 ///
 /// # Returns
 ///
-/// * `Result<Box<[u8]>, ResourceError>` - Allocated resource data, or error on failure
+/// * `Result<ErlNifResource, ResourceError>` - Allocated resource, or error on failure
 ///
 /// # Errors
 ///
@@ -122,44 +346,57 @@ impl ErlNifResourceType {
 ///
 /// - `erts/emulator/beam/erl_nif.c:enif_alloc_resource()` - C implementation
 pub fn enif_alloc_resource(
-    _resource_type: &ErlNifResourceType,
+    resource_type: &ErlNifResourceType,
     size: usize,
-) -> Result<Box<[u8]>, ResourceError> {
-    // Use safe Rust allocation
-    // In a full implementation, this would:
-    // 1. Register the resource with the resource type's allocator
-    // 2. Initialize resource header/metadata
-    // 3. Return resource data with proper reference counting
-    
-    // Safe allocation using Vec and Box
-    // This will panic on OOM, but in a full implementation we'd handle that gracefully
-    Ok(vec![0u8; size].into_boxed_slice())
+) -> Result<ErlNifResource, ResourceError> {
+    let mut data = Vec::new();
+    data.try_reserve_exact(size)
+        .map_err(|_| ResourceError::AllocationFailed)?;
+    data.resize(size, 0u8);
+    Ok(ErlNifResource {
+        object: Arc::new(ResourceObject {
+            resource_type: resource_type.clone(),
+            data: Mutex::new(data.into_boxed_slice()),
+        }),
+    })
+}
+
+/// Keep a resource, adding a reference
+///
+/// # See Also
+///
+/// - `erts/emulator/beam/erl_nif.c:enif_keep_resource()` - C implementation
+pub fn enif_keep_resource(resource: &ErlNifResource) -> ErlNifResource {
+    resource.clone()
 }
 
 /// Release a resource
 ///
-/// Releases a resource, decrementing its reference count.
-/// The resource is freed when the reference count reaches zero.
+/// Releases a reference to a resource. The resource's destructor is called
+/// and its memory freed when the last reference is released.
 ///
 /// # Arguments
 ///
-/// * `resource` - Resource data to release
+/// * `resource` - Resource reference to release
 ///
-/// # Note
+/// # See Also
 ///
-/// In a full implementation, this would:
-/// 1. Decrement resource reference count
-/// 2. If count reaches zero, call destructor and free memory
-/// 3. Handle resource finalization
+/// - `erts/emulator/beam/erl_nif.c:enif_release_resource()` - C implementation
+pub fn enif_release_resource(resource: ErlNifResource) {
+    drop(resource);
+}
+
+/// Resource terms and the references they hold, by process
+type OffHeapResources = Mutex<HashMap<ProcessId, Vec<(NifTerm, ErlNifResource)>>>;
+
+/// Resources referenced from terms on each process heap
 ///
-/// Currently, the resource is automatically dropped when `Box` goes out of scope.
-/// For full reference counting, we would use `Arc` with a custom drop implementation.
-pub fn enif_release_resource(_resource: Box<[u8]>) {
-    // Resource is automatically dropped when Box goes out of scope
-    // In a full implementation with reference counting, we'd use Arc:
-    // - Wrap resources in Arc<ResourceData>
-    // - Track reference counts
-    // - Call destructor when count reaches zero
+/// This is the resource part of the process off-heap list: each term made by
+/// `enif_make_resource` holds a reference until the garbage collector finds
+/// the term dead or the process exits.
+fn get_off_heap_resources() -> &'static OffHeapResources {
+    static OFF_HEAP: OnceLock<OffHeapResources> = OnceLock::new();
+    OFF_HEAP.get_or_init(|| Mutex::new(HashMap::new()))
 }
 
 /// Create a resource term
@@ -170,7 +407,7 @@ pub fn enif_release_resource(_resource: Box<[u8]>) {
 /// # Arguments
 ///
 /// * `env` - NIF environment
-/// * `resource` - Resource data (wrapped in Arc for reference counting)
+/// * `resource` - Resource reference
 ///
 /// # Returns
 ///
@@ -180,58 +417,358 @@ pub fn enif_release_resource(_resource: Box<[u8]>) {
 ///
 /// Resources are allocated on the heap as boxed terms with:
 /// - Header word containing resource metadata
-/// - Data word containing pointer/reference to the resource object
+/// - Data word containing the address of the resource object
 ///
-/// The resource term uses TAG_PRIMARY_BOXED (0x1) with a resource subtag.
-/// The Arc reference counting ensures the resource stays alive as long as
-/// the term exists.
+/// The resource term uses TAG_PRIMARY_BOXED (0x2) with a resource subtag.
+/// The term keeps a reference to the resource on the process off-heap list,
+/// so the resource stays alive until [`gc_sweep_resources`] finds the term
+/// dead or [`release_process_resources`] is called when the process exits.
 ///
 /// # See Also
 ///
 /// - `erts/emulator/beam/erl_nif.c:enif_make_resource()` - C implementation
 pub fn enif_make_resource(
     env: &NifEnv,
-    resource: &Arc<Box<[u8]>>,
+    resource: &ErlNifResource,
 ) -> NifTerm {
     // Resources are heap-allocated boxed terms
     // We need 2 words: 1 for header, 1 for resource pointer/reference
     let words_needed = 2;
-    
-    // Allocate heap space
-    if let Some(heap_index) = env.allocate_heap(words_needed) {
+    let address = resource.address() as u64;
+
+    let term = if let Some(heap_index) = env.allocate_heap(words_needed) {
         let process = env.process();
         let mut heap_data = process.heap_slice_mut();
-        
+
         // Write resource header
         // Format: (size << 2) | TAG_PRIMARY_BOXED | RESOURCE_SUBTAG
-        // TAG_PRIMARY_BOXED = 0x1
         // For simplicity, we'll use size 0 and encode resource info in the data word
-        // In a full implementation, the header would contain resource type info
-        let header = (0u64 << 2) | 0x1; // Boxed term with size 0
+        let header = TAG_PRIMARY_BOXED; // Boxed term with size 0
         heap_data[heap_index] = header;
-        
-        // Store resource pointer as the data word
-        // Convert Arc pointer to usize for storage
-        // Note: This is safe because we're just storing the pointer value, not dereferencing
-        let resource_ptr = Arc::as_ptr(resource) as usize as u64;
-        heap_data[heap_index + 1] = resource_ptr;
-        
+
+        // Store the resource address as the data word; it is never dereferenced,
+        // the off-heap list holds the actual reference
+        heap_data[heap_index + 1] = address;
+
         drop(heap_data);
-        
+
         // Return resource pointer: (heap_index << 2) | TAG_PRIMARY_BOXED
-        let resource_term = (heap_index as u64) << 2 | 0x1;
+        let resource_term = make_boxed(heap_index);
         if resource_term == 0 {
             // Heap index 0 would result in term 0, which is ambiguous
             // Fall back to placeholder
-            Arc::as_ptr(resource) as usize as u64
+            address
         } else {
             resource_term
         }
     } else {
         // Heap allocation failed, fall back to placeholder
         // In a full implementation, this would return an error or raise an exception
-        Arc::as_ptr(resource) as usize as u64
+        address
+    };
+
+    register_resource_term(env.process_id(), term, resource);
+    term
+}
+
+/// Record that a resource term held by a process references a resource
+pub(crate) fn register_resource_term(process_id: ProcessId, term: NifTerm, resource: &ErlNifResource) {
+    get_off_heap_resources()
+        .lock()
+        .unwrap()
+        .entry(process_id)
+        .or_default()
+        .push((term, resource.clone()));
+}
+
+/// Get the resource referenced by a resource term held by a process
+pub(crate) fn resource_for_term(process_id: ProcessId, term: NifTerm) -> Option<ErlNifResource> {
+    let off_heap = get_off_heap_resources().lock().unwrap();
+    off_heap
+        .get(&process_id)?
+        .iter()
+        .find(|(t, _)| *t == term)
+        .map(|(_, resource)| resource.clone())
+}
+
+/// Get the resource referenced by a resource term
+///
+/// # Arguments
+///
+/// * `env` - NIF environment of the process holding the term
+/// * `term` - Term made by `enif_make_resource`
+/// * `resource_type` - Expected resource type
+///
+/// # Returns
+///
+/// * `Some(resource)` - A new reference to the resource
+/// * `None` - The term is not a resource of this type
+///
+/// # See Also
+///
+/// - `erts/emulator/beam/erl_nif.c:enif_get_resource()` - C implementation
+pub fn enif_get_resource(
+    env: &NifEnv,
+    term: NifTerm,
+    resource_type: &ErlNifResourceType,
+) -> Option<ErlNifResource> {
+    let off_heap = get_off_heap_resources().lock().unwrap();
+    off_heap
+        .get(&env.process_id())?
+        .iter()
+        .find(|(t, resource)| *t == term && resource.resource_type() == resource_type)
+        .map(|(_, resource)| resource.clone())
+}
+
+/// Release the references held by resource terms that are no longer live
+///
+/// Called by the garbage collector after marking a process heap; resources
+/// whose last reference was held by a dead term are destructed.
+///
+/// # Arguments
+///
+/// * `process_id` - Process that was garbage collected
+/// * `is_live` - Returns whether a resource term survived the collection
+///
+/// # Returns
+///
+/// Number of term references released
+pub fn gc_sweep_resources(process_id: ProcessId, is_live: impl Fn(NifTerm) -> bool) -> usize {
+    let released: Vec<ErlNifResource> = {
+        let mut off_heap = get_off_heap_resources().lock().unwrap();
+        let Some(terms) = off_heap.get_mut(&process_id) else {
+            return 0;
+        };
+        let (live, dead): (Vec<_>, Vec<_>) = std::mem::take(terms).into_iter().partition(|(term, _)| is_live(*term));
+        *terms = live;
+        if terms.is_empty() {
+            off_heap.remove(&process_id);
+        }
+        dead.into_iter().map(|(_, resource)| resource).collect()
+    };
+    // Destructors run outside the off-heap lock
+    released.len()
+}
+
+/// Release the references held by all resource terms of an exiting process
+///
+/// # Returns
+///
+/// Number of term references released
+pub fn release_process_resources(process_id: ProcessId) -> usize {
+    let released = get_off_heap_resources().lock().unwrap().remove(&process_id);
+    released.map_or(0, |terms| terms.len())
+}
+
+/// Process monitor held by a resource
+///
+/// Rust equivalent of `ErlNifMonitor`. Monitors are ordered by creation, as
+/// compared by `enif_compare_monitors`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ErlNifMonitor(u64);
+
+/// Monitor held by a resource on a process
+struct MonitorEntry {
+    /// Monitored process
+    target: ProcessId,
+    /// Address of the monitoring resource
+    resource_address: usize,
+    /// Monitoring resource; the monitor does not keep it alive
+    resource: Weak<ResourceObject>,
+}
+
+/// Table of process monitors held by resources
+struct MonitorTable {
+    /// Active monitors
+    monitors: Mutex<HashMap<ErlNifMonitor, MonitorEntry>>,
+    /// Next monitor id
+    next_id: AtomicU64,
+}
+
+impl MonitorTable {
+    /// Remove all monitors of a resource being destructed
+    fn remove_resource(&self, address: usize) {
+        self.monitors
+            .lock()
+            .unwrap()
+            .retain(|_, entry| entry.resource_address != address);
+    }
+}
+
+/// Get the monitor table
+fn get_monitor_table() -> &'static MonitorTable {
+    static MONITORS: OnceLock<MonitorTable> = OnceLock::new();
+    MONITORS.get_or_init(|| MonitorTable {
+        monitors: Mutex::new(HashMap::new()),
+        next_id: AtomicU64::new(1),
+    })
+}
+
+/// Monitor a process from a resource
+///
+/// When the target process exits, the resource type's `down` callback is
+/// called with the resource, the process and the monitor. The monitor does
+/// not keep the resource alive; destructing the resource removes its monitors.
+///
+/// # Arguments
+///
+/// * `resource` - Monitoring resource
+/// * `target` - Process to monitor
+///
+/// # Returns
+///
+/// * `Ok(monitor)` - The monitor
+/// * `Err(ResourceError::NoDownCallback)` - The resource type has no `down` callback
+/// * `Err(ResourceError::ProcessNotAlive)` - The target process does not exist
+///
+/// # See Also
+///
+/// - `erts/emulator/beam/erl_nif.c:enif_monitor_process()` - C implementation
+pub fn enif_monitor_process(
+    resource: &ErlNifResource,
+    target: ProcessId,
+) -> Result<ErlNifMonitor, ResourceError> {
+    if resource.resource_type().callbacks().down.is_none() {
+        return Err(ResourceError::NoDownCallback);
+    }
+    if get_global_process_table().lookup(target).is_none() {
+        return Err(ResourceError::ProcessNotAlive);
     }
+    let table = get_monitor_table();
+    let monitor = ErlNifMonitor(table.next_id.fetch_add(1, Ordering::Relaxed));
+    table.monitors.lock().unwrap().insert(
+        monitor,
+        MonitorEntry {
+            target,
+            resource_address: resource.address(),
+            resource: Arc::downgrade(&resource.object),
+        },
+    );
+    Ok(monitor)
+}
+
+/// Remove a process monitor held by a resource
+///
+/// # Returns
+///
+/// * `Ok(())` - The monitor was removed
+/// * `Err(ResourceError::MonitorNotFound)` - The monitor is not active or not held by the resource
+///
+/// # See Also
+///
+/// - `erts/emulator/beam/erl_nif.c:enif_demonitor_process()` - C implementation
+pub fn enif_demonitor_process(
+    resource: &ErlNifResource,
+    monitor: &ErlNifMonitor,
+) -> Result<(), ResourceError> {
+    let mut monitors = get_monitor_table().monitors.lock().unwrap();
+    match monitors.get(monitor) {
+        Some(entry) if entry.resource_address == resource.address() => {
+            monitors.remove(monitor);
+            Ok(())
+        }
+        _ => Err(ResourceError::MonitorNotFound),
+    }
+}
+
+/// Compare two monitors (`enif_compare_monitors`)
+pub fn enif_compare_monitors(a: &ErlNifMonitor, b: &ErlNifMonitor) -> std::cmp::Ordering {
+    a.cmp(b)
+}
+
+/// Fire the monitors on an exited process
+///
+/// Removes each resource monitor on the process and calls the resource
+/// type's `down` callback, keeping the resource alive for the duration of
+/// the call. Monitors whose resource has already been destructed are
+/// dropped silently.
+///
+/// # Returns
+///
+/// Number of `down` callbacks called
+pub fn notify_process_down(process_id: ProcessId) -> usize {
+    let fired: Vec<(ErlNifMonitor, ErlNifResource)> = {
+        let mut monitors = get_monitor_table().monitors.lock().unwrap();
+        let ids: Vec<ErlNifMonitor> = monitors
+            .iter()
+            .filter(|(_, entry)| entry.target == process_id)
+            .map(|(monitor, _)| *monitor)
+            .collect();
+        let mut fired: Vec<_> = ids
+            .into_iter()
+            .filter_map(|monitor| {
+                let entry = monitors.remove(&monitor)?;
+                entry.resource.upgrade().map(|object| (monitor, ErlNifResource { object }))
+            })
+            .collect();
+        fired.sort_by_key(|(monitor, _)| *monitor);
+        fired
+    };
+    let count = fired.len();
+    for (monitor, resource) in fired {
+        if let Some(down) = resource.resource_type().callbacks().down {
+            resource.with_data(|data| down(data, process_id, &monitor));
+        }
+    }
+    count
+}
+
+/// Resources owning fds selected with `enif_select`
+fn get_select_owners() -> &'static Mutex<HashMap<i32, ErlNifResource>> {
+    static OWNERS: OnceLock<Mutex<HashMap<i32, ErlNifResource>>> = OnceLock::new();
+    OWNERS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Transfer a reference to a resource to the select on an fd
+///
+/// Called by `enif_select` when an fd is selected. The select keeps the
+/// resource alive until [`enif_select_stop`] is called for the fd. Selecting
+/// an fd again with the same resource keeps the existing reference.
+///
+/// # Returns
+///
+/// * `Ok(())` - The resource owns the fd select
+/// * `Err(ResourceError::SelectOwnedByOther)` - The fd is selected with a different resource
+pub fn enif_select_take_resource(event: i32, resource: &ErlNifResource) -> Result<(), ResourceError> {
+    let mut owners = get_select_owners().lock().unwrap();
+    match owners.get(&event) {
+        Some(owner) if !owner.ptr_eq(resource) => Err(ResourceError::SelectOwnedByOther),
+        Some(_) => Ok(()),
+        None => {
+            owners.insert(event, resource.clone());
+            Ok(())
+        }
+    }
+}
+
+/// Get the resource owning the select on an fd
+pub fn enif_select_owner(event: i32) -> Option<ErlNifResource> {
+    get_select_owners().lock().unwrap().get(&event).cloned()
+}
+
+/// Stop the select on an fd, returning ownership of the resource
+///
+/// Calls the resource type's `stop` callback and releases the select's
+/// reference to the resource, which may destruct it.
+///
+/// # Arguments
+///
+/// * `event` - The fd
+/// * `is_direct_call` - `true` when called from `enif_select` itself because
+///   the fd was not in a pollset; `false` when called by the poll backend
+///   after removing the fd
+///
+/// # Returns
+///
+/// `true` if the fd was owned by a resource
+pub fn enif_select_stop(event: i32, is_direct_call: bool) -> bool {
+    let Some(resource) = get_select_owners().lock().unwrap().remove(&event) else {
+        return false;
+    };
+    if let Some(stop) = resource.resource_type().callbacks().stop {
+        resource.with_data(|data| stop(data, event, is_direct_call));
+    }
+    true
 }
 
 /// Resource management errors
@@ -243,29 +780,40 @@ pub enum ResourceError {
     InvalidResourceType,
     /// Resource not found
     ResourceNotFound,
+    /// Resource type already exists and takeover was not requested
+    TypeExists,
+    /// Resource type has no `down` callback
+    NoDownCallback,
+    /// Monitored process does not exist
+    ProcessNotAlive,
+    /// Monitor is not active
+    MonitorNotFound,
+    /// fd is selected with a different resource
+    SelectOwnedByOther,
 }
 
+impl std::fmt::Display for ResourceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResourceError::AllocationFailed => write!(f, "Resource allocation failed"),
+            ResourceError::InvalidResourceType => write!(f, "Invalid resource type"),
+            ResourceError::ResourceNotFound => write!(f, "Resource not found"),
+            ResourceError::TypeExists => write!(f, "Resource type already exists"),
+            ResourceError::NoDownCallback => write!(f, "Resource type has no down callback"),
+            ResourceError::ProcessNotAlive => write!(f, "Process not alive"),
+            ResourceError::MonitorNotFound => write!(f, "Monitor not found"),
+            ResourceError::SelectOwnedByOther => write!(f, "fd selected with another resource"),
+        }
+    }
+}
+
+impl std::error::Error for ResourceError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::sync::Arc;
 
-    #[test]
-    fn test_erl_nif_binary_new() {
-        let data = vec![1, 2, 3, 4, 5];
-        let binary = ErlNifBinary::new(data.clone());
-        assert_eq!(binary.data(), &data);
-        assert_eq!(binary.size(), 5);
-    }
-
-    #[test]
-    fn test_erl_nif_binary_into_vec() {
-        let data = vec![10, 20, 30];
-        let binary = ErlNifBinary::new(data.clone());
-        let retrieved = binary.into_vec();
-        assert_eq!(retrieved, data);
-    }
-
     #[test]
     fn test_erl_nif_resource_type_new() {
         let resource_type = ErlNifResourceType::new(
@@ -332,8 +880,7 @@ mod tests {
             "module".to_string(),
         );
         let resource = enif_alloc_resource(&resource_type, 10).unwrap();
-        let resource_arc = Arc::new(resource);
-        let term = enif_make_resource(&env, &resource_arc);
+        let term = enif_make_resource(&env, &resource);
         // Should return a non-zero value (pointer address)
         assert_ne!(term, 0);
     }
@@ -346,5 +893,165 @@ mod tests {
         let err2 = ResourceError::InvalidResourceType;
         assert_ne!(err, err2);
     }
+
+    fn counting_type(name: &str) -> (ErlNifResourceType, Arc<AtomicU64>) {
+        let destructed = Arc::new(AtomicU64::new(0));
+        let counter = destructed.clone();
+        let init = ErlNifResourceTypeInit {
+            dtor: Some(Arc::new(move |_data: &mut [u8]| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })),
+            ..Default::default()
+        };
+        let resource_type = ErlNifResourceType::with_init(name.to_string(), "module".to_string(), init);
+        (resource_type, destructed)
+    }
+
+    #[test]
+    fn test_open_resource_type_flags() {
+        let registry = ResourceTypeRegistry::new();
+        let create = ErlNifResourceFlags::Create as u32;
+        let takeover = ErlNifResourceFlags::Takeover as u32;
+        assert_eq!(
+            registry.open_resource_type("m", "t", ErlNifResourceTypeInit::default(), takeover),
+            Err(ResourceError::InvalidResourceType)
+        );
+        let created = registry
+            .open_resource_type("m", "t", ErlNifResourceTypeInit::default(), create)
+            .unwrap();
+        assert_eq!(
+            registry.open_resource_type("m", "t", ErlNifResourceTypeInit::default(), create),
+            Err(ResourceError::TypeExists)
+        );
+
+        let init = ErlNifResourceTypeInit {
+            dtor: Some(Arc::new(|_data: &mut [u8]| {})),
+            ..Default::default()
+        };
+        let taken = registry.open_resource_type("m", "t", init, create | takeover).unwrap();
+        assert_eq!(taken, created);
+        assert!(created.callbacks().dtor.is_some());
+        assert_eq!(registry.lookup("m", "t"), Some(created));
+    }
+
+    #[test]
+    fn test_destructor_runs_on_last_release() {
+        let (resource_type, destructed) = counting_type("dtor");
+        let resource = enif_alloc_resource(&resource_type, 8).unwrap();
+        resource.with_data(|data| data[0] = 42);
+        let kept = enif_keep_resource(&resource);
+        assert_eq!(resource.ref_count(), 2);
+
+        enif_release_resource(resource);
+        assert_eq!(destructed.load(Ordering::SeqCst), 0);
+        assert_eq!(kept.with_data(|data| data[0]), 42);
+        enif_release_resource(kept);
+        assert_eq!(destructed.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_gc_sweep_releases_dead_terms() {
+        use entities_process::Process;
+        let env = crate::nif_env::NifEnv::from_process(Arc::new(Process::new(40690)));
+        let (resource_type, destructed) = counting_type("gc");
+        let resource = enif_alloc_resource(&resource_type, 4).unwrap();
+        let term = enif_make_resource(&env, &resource);
+        enif_release_resource(resource);
+
+        let found = enif_get_resource(&env, term, &resource_type).unwrap();
+        assert_eq!(found.len(), 4);
+        drop(found);
+        let other_type = ErlNifResourceType::new("other".to_string(), "module".to_string());
+        assert!(enif_get_resource(&env, term, &other_type).is_none());
+
+        assert_eq!(gc_sweep_resources(40690, |t| t == term), 0);
+        assert_eq!(destructed.load(Ordering::SeqCst), 0);
+        assert_eq!(gc_sweep_resources(40690, |_| false), 1);
+        assert_eq!(destructed.load(Ordering::SeqCst), 1);
+        assert!(enif_get_resource(&env, term, &resource_type).is_none());
+    }
+
+    #[test]
+    fn test_monitor_down_callback() {
+        use entities_process::Process;
+        let target = 40691;
+        get_global_process_table().insert(target, Arc::new(Process::new(target)));
+        let downs = Arc::new(Mutex::new(Vec::new()));
+        let recorded = downs.clone();
+        let init = ErlNifResourceTypeInit {
+            down: Some(Arc::new(move |data: &mut [u8], pid: ProcessId, monitor: &ErlNifMonitor| {
+                recorded.lock().unwrap().push((data[0], pid, *monitor));
+            })),
+            ..Default::default()
+        };
+        let resource_type = ErlNifResourceType::with_init("down".to_string(), "module".to_string(), init);
+        let resource = enif_alloc_resource(&resource_type, 1).unwrap();
+        resource.with_data(|data| data[0] = 7);
+
+        let first = enif_monitor_process(&resource, target).unwrap();
+        let second = enif_monitor_process(&resource, target).unwrap();
+        assert_eq!(enif_compare_monitors(&first, &second), std::cmp::Ordering::Less);
+        enif_demonitor_process(&resource, &second).unwrap();
+        assert_eq!(enif_demonitor_process(&resource, &second), Err(ResourceError::MonitorNotFound));
+
+        get_global_process_table().remove(target);
+        assert_eq!(notify_process_down(target), 1);
+        assert_eq!(*downs.lock().unwrap(), vec![(7, target, first)]);
+        assert_eq!(notify_process_down(target), 0);
+        assert_eq!(enif_monitor_process(&resource, target), Err(ResourceError::ProcessNotAlive));
+
+        let plain = ErlNifResourceType::new("plain".to_string(), "module".to_string());
+        let plain = enif_alloc_resource(&plain, 1).unwrap();
+        assert_eq!(enif_monitor_process(&plain, target), Err(ResourceError::NoDownCallback));
+    }
+
+    #[test]
+    fn test_monitor_removed_with_resource() {
+        use entities_process::Process;
+        let target = 40692;
+        get_global_process_table().insert(target, Arc::new(Process::new(target)));
+        let init = ErlNifResourceTypeInit {
+            down: Some(Arc::new(|_: &mut [u8], _: ProcessId, _: &ErlNifMonitor| panic!("down after destruct"))),
+            ..Default::default()
+        };
+        let resource_type = ErlNifResourceType::with_init("gone".to_string(), "module".to_string(), init);
+        let resource = enif_alloc_resource(&resource_type, 1).unwrap();
+        enif_monitor_process(&resource, target).unwrap();
+        enif_release_resource(resource);
+        get_global_process_table().remove(target);
+        assert_eq!(notify_process_down(target), 0);
+    }
+
+    #[test]
+    fn test_select_ownership_and_stop() {
+        let stops = Arc::new(Mutex::new(Vec::new()));
+        let recorded = stops.clone();
+        let init = ErlNifResourceTypeInit {
+            stop: Some(Arc::new(move |_data: &mut [u8], event: i32, direct: bool| {
+                recorded.lock().unwrap().push((event, direct));
+            })),
+            ..Default::default()
+        };
+        let resource_type = ErlNifResourceType::with_init("select".to_string(), "module".to_string(), init);
+        let resource = enif_alloc_resource(&resource_type, 1).unwrap();
+        let other = enif_alloc_resource(&resource_type, 1).unwrap();
+
+        enif_select_take_resource(40690, &resource).unwrap();
+        enif_select_take_resource(40690, &resource).unwrap();
+        assert_eq!(resource.ref_count(), 2);
+        assert_eq!(enif_select_take_resource(40690, &other), Err(ResourceError::SelectOwnedByOther));
+        assert!(enif_select_owner(40690).unwrap().ptr_eq(&resource));
+
+        assert!(enif_select_stop(40690, false));
+        assert_eq!(*stops.lock().unwrap(), vec![(40690, false)]);
+        assert_eq!(resource.ref_count(), 1);
+        assert!(!enif_select_stop(40690, true));
+    }
+
+    #[test]
+    fn test_resource_error_display() {
+        assert!(ResourceError::TypeExists.to_string().contains("exists"));
+        assert!(ResourceError::ProcessNotAlive.to_string().contains("alive"));
+    }
 }
 
@@ -0,0 +1,223 @@
+//! Term Comparison and Hashing
+//!
+//! Provides term order, identity and hashing of terms built in a NIF
+//! environment: `enif_compare`, `enif_is_identical` and `enif_hash`.
+//!
+//! Terms are read off the heap into the [`Term`] representation used by
+//! `erts_cmp` and the hash functions, so heap terms order and hash exactly
+//! like the terms the runtime builds itself, tuples included: a tuple sorts
+//! by arity first and then element by element.
+//!
+//! The heap headers of bignums and binaries are alike, so a boxed term that
+//! is neither a tuple nor a float is read as a binary.
+//!
+//! Based on enif_compare, enif_is_identical and enif_hash in erl_nif.c
+
+use std::cmp::Ordering;
+
+use entities_data_handling::term_hashing::{erts_internal_salted_hash, make_hash2, Term};
+use infrastructure_runtime_utils::{eq, erts_cmp};
+
+use crate::term_decoding::{decode_small_integer, enif_get_binary, enif_get_map, is_small_integer};
+use crate::float::{float_on_heap, is_float_header};
+use crate::tuple::tuple_on_heap;
+use entities_process::term_tags::{
+    NIL, TAG_IMMED2_ATOM, TAG_IMMED2_MASK, TAG_PRIMARY_BOXED, TAG_PRIMARY_HEADER,
+    TAG_PRIMARY_LIST, TAG_PRIMARY_MASK,
+};
+
+use crate::{NifEnv, NifTerm};
+
+/// Range of `erlang:phash2/1`
+const PHASH2_RANGE: u64 = 1 << 27;
+
+/// Hash function of `enif_hash` (`ErlNifHash`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NifHash {
+    /// Internal hash of the running runtime, salted; not portable
+    InternalHash,
+    /// Portable hash of `erlang:phash2/1`; the salt is ignored
+    Phash2,
+}
+
+/// Compare two terms in Erlang term order (`enif_compare`)
+///
+/// # Returns
+/// A negative value, zero or a positive value as `lhs` is less than, equal
+/// to or greater than `rhs`
+pub fn enif_compare(env: &NifEnv, lhs: NifTerm, rhs: NifTerm) -> i32 {
+    match (heap_term(env, lhs), heap_term(env, rhs)) {
+        (Some(a), Some(b)) => erts_cmp(&a, &b, 0).unwrap_or_else(|_| ordering_value(lhs.cmp(&rhs))),
+        _ => ordering_value(lhs.cmp(&rhs)),
+    }
+}
+
+/// Check if two terms are identical (`enif_is_identical`)
+///
+/// Identical terms match, so `1` and `1.0` are not identical.
+pub fn enif_is_identical(env: &NifEnv, lhs: NifTerm, rhs: NifTerm) -> bool {
+    if lhs == rhs {
+        return true;
+    }
+    match (heap_term(env, lhs), heap_term(env, rhs)) {
+        (Some(a), Some(b)) => eq(&a, &b).unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Hash a term (`enif_hash`)
+///
+/// # Arguments
+/// * `env` - Environment the term was built in
+/// * `hash` - Hash function
+/// * `term` - Term to hash
+/// * `salt` - Salt of [`NifHash::InternalHash`]
+///
+/// # Returns
+/// The hash, or 0 if `term` is not a term built in `env`
+pub fn enif_hash(env: &NifEnv, hash: NifHash, term: NifTerm, salt: u64) -> u64 {
+    let Some(term) = heap_term(env, term) else {
+        return 0;
+    };
+    match hash {
+        NifHash::InternalHash => erts_internal_salted_hash(term, salt),
+        NifHash::Phash2 => make_hash2(term) as u64 % PHASH2_RANGE,
+    }
+}
+
+fn ordering_value(ordering: Ordering) -> i32 {
+    match ordering {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    }
+}
+
+/// Read a term built in an environment
+///
+/// # Returns
+/// The term, or `None` if `term` or one of its subterms is not a term built
+/// in `env`
+pub(crate) fn heap_term(env: &NifEnv, term: NifTerm) -> Option<Term> {
+    let heap = env.process().heap_slice();
+    read_term(env, &heap, term)
+}
+
+fn read_term(env: &NifEnv, heap: &[NifTerm], term: NifTerm) -> Option<Term> {
+    if term == NIL {
+        return Some(Term::Nil);
+    }
+    if is_small_integer(term) {
+        return Some(Term::Small(decode_small_integer(term)));
+    }
+    if term & TAG_IMMED2_MASK == TAG_IMMED2_ATOM {
+        return Some(Term::Atom((term >> 6) as u32));
+    }
+    match term & TAG_PRIMARY_MASK {
+        TAG_PRIMARY_BOXED if heap.get((term >> 2) as usize).is_some_and(|&header| is_float_header(header)) => {
+            float_on_heap(heap, term).map(Term::Float)
+        }
+        TAG_PRIMARY_BOXED => match tuple_on_heap(heap, term) {
+            Some((index, arity)) => heap[index + 1..index + 1 + arity]
+                .iter()
+                .map(|&element| read_term(env, heap, element))
+                .collect::<Option<Vec<_>>>()
+                .map(Term::Tuple),
+            None => enif_get_binary(env, term).map(|data| Term::Binary {
+                bit_size: data.len() * 8,
+                data,
+                bit_offset: 0,
+            }),
+        },
+        TAG_PRIMARY_LIST => read_list(env, heap, term),
+        TAG_PRIMARY_HEADER => enif_get_map(env, term)?
+            .into_iter()
+            .map(|(key, value)| Some((read_term(env, heap, key)?, read_term(env, heap, value)?)))
+            .collect::<Option<Vec<_>>>()
+            .map(Term::Map),
+        _ => None,
+    }
+}
+
+/// Read a list, iterating over the tails so long lists do not recurse
+fn read_list(env: &NifEnv, heap: &[NifTerm], term: NifTerm) -> Option<Term> {
+    let mut heads = Vec::new();
+    let mut cell = term;
+    while cell & TAG_PRIMARY_MASK == TAG_PRIMARY_LIST {
+        let index = (cell >> 2) as usize;
+        if index + 1 >= heap.len() || heads.len() >= heap.len() {
+            return None;
+        }
+        heads.push(read_term(env, heap, heap[index])?);
+        cell = heap[index + 1];
+    }
+    let tail = read_term(env, heap, cell)?;
+    Some(heads.into_iter().rev().fold(tail, |tail, head| Term::List {
+        head: Box::new(head),
+        tail: Box::new(tail),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::term_creation::{enif_make_atom, enif_make_binary, enif_make_int, enif_make_tuple};
+    use entities_process::Process;
+    use std::sync::Arc;
+
+    fn test_env() -> NifEnv {
+        NifEnv::from_process(Arc::new(Process::new(41371)))
+    }
+
+    #[test]
+    fn test_compare_tuples() {
+        let env = test_env();
+        let int = |n| enif_make_int(&env, n);
+        let small = enif_make_tuple(&env, &[int(9), int(9)]);
+        let large = enif_make_tuple(&env, &[int(1), int(1), int(1)]);
+        let low = enif_make_tuple(&env, &[int(1), int(2)]);
+        let high = enif_make_tuple(&env, &[int(1), int(4)]);
+        let copy = enif_make_tuple(&env, &[int(1), int(2)]);
+
+        // Arity first, then element by element
+        assert!(enif_compare(&env, small, large) < 0);
+        assert!(enif_compare(&env, low, high) < 0);
+        assert!(enif_compare(&env, high, low) > 0);
+        assert_eq!(enif_compare(&env, low, copy), 0);
+        assert!(enif_is_identical(&env, low, copy));
+        assert!(!enif_is_identical(&env, low, high));
+
+        // Floats compare by value
+        let half = crate::float::enif_make_double(&env, 0.5);
+        let quarter = crate::float::enif_make_double(&env, 0.25);
+        assert!(enif_compare(&env, quarter, half) < 0);
+        assert!(enif_is_identical(&env, half, crate::float::enif_make_double(&env, 0.5)));
+
+        // Tuples sort after atoms
+        let atom = enif_make_atom(&env, "ok");
+        assert!(enif_compare(&env, atom, low) < 0);
+        assert!(enif_compare(&env, low, atom) > 0);
+    }
+
+    #[test]
+    fn test_hash_matches_runtime_terms() {
+        let env = test_env();
+        let binary = enif_make_binary(&env, vec![1, 2]);
+        let float = crate::float::enif_make_double(&env, 2.5);
+        let tuple = enif_make_tuple(&env, &[enif_make_int(&env, 1), binary, float]);
+        let expected = Term::Tuple(vec![
+            Term::Small(1),
+            Term::Binary { data: vec![1, 2], bit_offset: 0, bit_size: 16 },
+            Term::Float(2.5),
+        ]);
+
+        assert_eq!(enif_hash(&env, NifHash::Phash2, tuple, 0), make_hash2(expected.clone()) as u64 % PHASH2_RANGE);
+        assert_eq!(enif_hash(&env, NifHash::InternalHash, tuple, 7), erts_internal_salted_hash(expected, 7));
+        let copy = enif_make_tuple(&env, &[
+            enif_make_int(&env, 1),
+            enif_make_binary(&env, vec![1, 2]),
+            crate::float::enif_make_double(&env, 2.5),
+        ]);
+        assert_eq!(enif_hash(&env, NifHash::Phash2, tuple, 0), enif_hash(&env, NifHash::Phash2, copy, 5));
+    }
+}
@@ -0,0 +1,546 @@
+//! Term Copying
+//!
+//! Provides the deep term copy used whenever a term leaves the heap it was
+//! built on: `enif_make_copy`, message passing (`enif_send`) and storing
+//! terms outside a process, such as in ETS tables. This is the Rust
+//! equivalent of `size_object` and `copy_struct`.
+//!
+//! A term is copied into a [`TermFragment`], laid out from word 0 as if it
+//! started a heap. All pointers in the fragment point into the fragment, so
+//! it can be used as a message heap fragment as is, or moved onto a process
+//! heap with [`TermFragment::copy_to_heap`], which rewrites the pointers for
+//! the destination heap.
+//!
+//! Literals are not copied; the copy references the same literal.
+//! Reference-counted binaries and resources are not copied either; the copy
+//! holds a new reference to the same data.
+//!
+//! [`copy_struct`] copies a subterm each time it is referenced, as copy.c
+//! does, so a subterm referenced several times within the term ends up as
+//! several copies. [`copy_shared`] is the opt-in mode preserving sharing
+//! (`copy_shared_calculate` and `copy_shared_perform`): it tracks the
+//! subterms it has copied in a hash table and copies each of them once, so a
+//! heavily shared term, such as a DAG whose flat copy grows exponentially,
+//! copies in space linear in its size on the source heap. Messages sent by a
+//! process spawned with the `copy_shared` option are copied in this mode.
+//!
+//! Based on copy.c
+
+use std::collections::HashMap;
+
+use entities_data_handling::binary::RefcBinary;
+use entities_process::term_tags::{
+    TAG_PRIMARY_BOXED, TAG_PRIMARY_HEADER, TAG_PRIMARY_IMMED1, TAG_PRIMARY_LIST, TAG_PRIMARY_MASK,
+};
+use entities_process::{Message, ProcessId};
+
+use crate::binary_management::{binary_for_term, register_binary_term};
+use crate::resource_management::{register_resource_term, resource_for_term, ErlNifResource};
+use crate::float::is_float_header;
+use crate::tuple::{arityval, is_arity_value};
+use crate::{NifEnv, NifTerm};
+
+/// Term copied off the heap it was built on
+///
+/// Holds the copied words, laid out from word 0, and the off-heap objects
+/// (reference-counted binaries and resources) the copy references. The
+/// references are handed to the process that ends up holding the copy.
+pub struct TermFragment {
+    /// Copied root term, relative to word 0
+    root: Copied,
+    /// Copied words
+    words: Vec<NifTerm>,
+    /// Positions in `words` holding pointers into `words`
+    pointers: Vec<usize>,
+    /// Copied resource terms and the resources they reference
+    resources: Vec<(NifTerm, ErlNifResource)>,
+    /// Copied reference-counted binary terms and the binaries they reference
+    binaries: Vec<(NifTerm, RefcBinary)>,
+}
+
+impl TermFragment {
+    /// Get the copied term, as a pointer into [`words`](Self::words)
+    pub fn root(&self) -> NifTerm {
+        self.root.term
+    }
+
+    /// Get the copied words
+    pub fn words(&self) -> &[NifTerm] {
+        &self.words
+    }
+
+    /// Get the size of the copy in words
+    pub fn size(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Move the copy onto the heap of an environment
+    ///
+    /// # Returns
+    /// * `Some(term)` - The copy, valid in `env`
+    /// * `None` - The heap of `env` is full
+    pub fn copy_to_heap(mut self, env: &NifEnv) -> Option<NifTerm> {
+        if self.words.is_empty() {
+            return Some(self.root.term);
+        }
+        let base = env.allocate_heap(self.words.len())?;
+        self.relocate(base);
+        let process = env.process();
+        let mut heap_data = process.heap_slice_mut();
+        heap_data[base..base + self.words.len()].copy_from_slice(&self.words);
+        drop(heap_data);
+        self.register_off_heap(env.process_id());
+        Some(self.root.term)
+    }
+
+    /// Turn the copy into a heap fragment owned by a process
+    ///
+    /// The process holds the references to the off-heap objects in the copy
+    /// until it exits, as the fragment is not on its heap.
+    ///
+    /// # Returns
+    /// The root term and the fragment words it points into
+    pub fn into_heap_fragment(self, owner: ProcessId) -> (NifTerm, Vec<NifTerm>) {
+        self.register_off_heap(owner);
+        (self.root.term, self.words)
+    }
+
+    /// Turn the copy into a message for a process
+    pub fn into_message(self, receiver: ProcessId) -> Message {
+        let (root, words) = self.into_heap_fragment(receiver);
+        Message::with_heap_fragment(root, words)
+    }
+
+    /// Move the copy to start at heap index `base`
+    fn relocate(&mut self, base: usize) {
+        let offset = (base as u64) << 2;
+        for &position in &self.pointers {
+            self.words[position] += offset;
+        }
+        for (term, _) in &mut self.resources {
+            *term += offset;
+        }
+        for (term, _) in &mut self.binaries {
+            *term += offset;
+        }
+        if self.root.internal {
+            self.root.term += offset;
+        }
+    }
+
+    fn register_off_heap(&self, owner: ProcessId) {
+        for (term, resource) in &self.resources {
+            register_resource_term(owner, *term, resource);
+        }
+        for (term, binary) in &self.binaries {
+            register_binary_term(owner, *term, binary);
+        }
+    }
+}
+
+impl std::fmt::Debug for TermFragment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TermFragment")
+            .field("root", &self.root.term)
+            .field("size", &self.words.len())
+            .field("resources", &self.resources.len())
+            .field("binaries", &self.binaries.len())
+            .finish()
+    }
+}
+
+/// Copy a term built in an environment (`copy_struct`)
+pub fn copy_struct(env: &NifEnv, term: NifTerm) -> TermFragment {
+    copy_struct_with_literals(env, term, |_| false)
+}
+
+/// Copy a term built in an environment, leaving literals uncopied
+///
+/// # Arguments
+/// * `env` - Environment the term was built in
+/// * `term` - Term to copy
+/// * `is_literal` - Returns whether a tuple, boxed or list term points into
+///   a literal area (`erts_is_literal`); such terms are referenced by the
+///   copy rather than copied
+pub fn copy_struct_with_literals(env: &NifEnv, term: NifTerm, is_literal: impl Fn(NifTerm) -> bool) -> TermFragment {
+    copy_term(env, term, &is_literal, false)
+}
+
+/// Copy a term built in an environment, preserving sharing (`copy_shared_perform`)
+///
+/// A subterm referenced several times within the term is copied once, so
+/// the copy is never larger than the term on the source heap.
+pub fn copy_shared(env: &NifEnv, term: NifTerm) -> TermFragment {
+    copy_term(env, term, &|_| false, true)
+}
+
+/// Get the number of words a copy of a term takes (`size_object`)
+pub fn size_object(env: &NifEnv, term: NifTerm) -> usize {
+    copy_struct(env, term).size()
+}
+
+/// Get the number of words a sharing-preserving copy of a term takes (`copy_shared_calculate`)
+pub fn size_shared(env: &NifEnv, term: NifTerm) -> usize {
+    copy_shared(env, term).size()
+}
+
+fn copy_term(env: &NifEnv, term: NifTerm, is_literal: &dyn Fn(NifTerm) -> bool, shared: bool) -> TermFragment {
+    let mut copy = TermCopy {
+        src: env.process().heap_slice(),
+        src_pid: env.process_id(),
+        is_literal,
+        shared,
+        copied: HashMap::new(),
+        words: Vec::new(),
+        pointers: Vec::new(),
+        resources: Vec::new(),
+        binaries: Vec::new(),
+    };
+    let root = copy.copy(term);
+    TermFragment {
+        root,
+        words: copy.words,
+        pointers: copy.pointers,
+        resources: copy.resources,
+        binaries: copy.binaries,
+    }
+}
+
+/// Term produced by a copy
+#[derive(Debug, Clone, Copy)]
+struct Copied {
+    term: NifTerm,
+    /// The term points into the copy and moves with it
+    internal: bool,
+}
+
+impl Copied {
+    fn external(term: NifTerm) -> Self {
+        Self { term, internal: false }
+    }
+
+    fn internal(position: usize, tag: u64) -> Self {
+        Self {
+            term: ((position as u64) << 2) | tag,
+            internal: true,
+        }
+    }
+}
+
+/// State of a copy in progress
+struct TermCopy<'a> {
+    /// Heap of the environment the term was built in
+    src: Vec<NifTerm>,
+    /// Process owning the source heap
+    src_pid: ProcessId,
+    /// Literal check
+    is_literal: &'a dyn Fn(NifTerm) -> bool,
+    /// Copy each shared subterm once
+    shared: bool,
+    /// Copies of the subterms copied so far, by source term; only kept when
+    /// preserving sharing
+    copied: HashMap<NifTerm, Copied>,
+    /// Copied words
+    words: Vec<NifTerm>,
+    /// Positions in `words` holding pointers into `words`
+    pointers: Vec<usize>,
+    /// Copied resource terms and the resources they reference
+    resources: Vec<(NifTerm, ErlNifResource)>,
+    /// Copied reference-counted binary terms and the binaries they reference
+    binaries: Vec<(NifTerm, RefcBinary)>,
+}
+
+impl TermCopy<'_> {
+    /// Copy a term, laying it out after the words already copied
+    ///
+    /// Words that do not point into the source heap are not terms built in
+    /// it and are copied unchanged.
+    fn copy(&mut self, term: NifTerm) -> Copied {
+        if term & TAG_PRIMARY_MASK == TAG_PRIMARY_IMMED1 || (self.is_literal)(term) {
+            return Copied::external(term);
+        }
+        if let Some(copied) = self.lookup(term) {
+            return copied;
+        }
+        let index = (term >> 2) as usize;
+        let copied = match term & TAG_PRIMARY_MASK {
+            TAG_PRIMARY_HEADER => self.copy_map(term, index),
+            TAG_PRIMARY_BOXED if self.src.get(index).is_some_and(|&header| is_arity_value(header)) => {
+                self.copy_tuple(term, index)
+            }
+            TAG_PRIMARY_BOXED if self.src.get(index).is_some_and(|&header| is_float_header(header)) => {
+                self.copy_float(term, index)
+            }
+            TAG_PRIMARY_BOXED => self.copy_boxed(term, index),
+            _ => self.copy_list(term, index),
+        };
+        self.remember(term, copied);
+        copied
+    }
+
+    /// Get the copy of a subterm already copied, when preserving sharing
+    fn lookup(&self, term: NifTerm) -> Option<Copied> {
+        if self.shared {
+            self.copied.get(&term).copied()
+        } else {
+            None
+        }
+    }
+
+    fn remember(&mut self, term: NifTerm, copied: Copied) {
+        if self.shared {
+            self.copied.insert(term, copied);
+        }
+    }
+
+    fn copy_tuple(&mut self, term: NifTerm, index: usize) -> Copied {
+        let header = self.src[index];
+        let arity = arityval(header);
+        if index + arity >= self.src.len() {
+            return Copied::external(term);
+        }
+        self.copy_elements(index, arity, TAG_PRIMARY_BOXED)
+    }
+
+    /// Copy a float; the word after the header holds bits, not a term
+    fn copy_float(&mut self, term: NifTerm, index: usize) -> Copied {
+        if index + 1 >= self.src.len() {
+            return Copied::external(term);
+        }
+        let position = self.words.len();
+        self.words.extend_from_slice(&self.src[index..index + 2]);
+        Copied::internal(position, TAG_PRIMARY_BOXED)
+    }
+
+    fn copy_map(&mut self, term: NifTerm, index: usize) -> Copied {
+        let Some(&header) = self.src.get(index) else {
+            return Copied::external(term);
+        };
+        let size = (header >> 2) as usize;
+        if header & TAG_PRIMARY_MASK != TAG_PRIMARY_HEADER || index + 2 * size >= self.src.len() {
+            return Copied::external(term);
+        }
+        self.copy_elements(index, 2 * size, TAG_PRIMARY_HEADER)
+    }
+
+    /// Copy a header and the `count` terms following it
+    fn copy_elements(&mut self, index: usize, count: usize, tag: u64) -> Copied {
+        let position = self.words.len();
+        self.words.push(self.src[index]);
+        self.words.resize(position + 1 + count, 0);
+        for i in 0..count {
+            let element = self.copy(self.src[index + 1 + i]);
+            self.store(position + 1 + i, element);
+        }
+        Copied::internal(position, tag)
+    }
+
+    fn copy_boxed(&mut self, term: NifTerm, index: usize) -> Copied {
+        let Some(&header) = self.src.get(index) else {
+            return Copied::external(term);
+        };
+        let bytes = (header >> 2) as usize;
+        // Resources and reference-counted binaries have an empty header
+        // followed by the address of the off-heap object
+        let resource = (bytes == 0).then(|| resource_for_term(self.src_pid, term)).flatten();
+        let binary = (bytes == 0).then(|| binary_for_term(self.src_pid, term)).flatten();
+        let size = if resource.is_some() || binary.is_some() { 2 } else { 1 + bytes.div_ceil(8) };
+        // Bignum headers also carry their sign in the low bit
+        if header & TAG_PRIMARY_BOXED == 0 || index + size > self.src.len() {
+            return Copied::external(term);
+        }
+        let position = self.words.len();
+        self.words.extend_from_slice(&self.src[index..index + size]);
+        let copy = Copied::internal(position, TAG_PRIMARY_BOXED);
+        if let Some(resource) = resource {
+            self.resources.push((copy.term, resource));
+        }
+        if let Some(binary) = binary {
+            self.binaries.push((copy.term, binary));
+        }
+        copy
+    }
+
+    /// Copy a list, iterating over the tails so long lists do not recurse
+    fn copy_list(&mut self, term: NifTerm, index: usize) -> Copied {
+        if index + 1 >= self.src.len() {
+            return Copied::external(term);
+        }
+        let first = self.words.len();
+        self.words.extend([0, 0]);
+        let mut cell = first;
+        let mut src_cell = index;
+        loop {
+            let head = self.copy(self.src[src_cell]);
+            self.store(cell, head);
+            let tail = self.src[src_cell + 1];
+            let tail_index = (tail >> 2) as usize;
+            let shared_tail = self.lookup(tail);
+            if tail & TAG_PRIMARY_MASK == TAG_PRIMARY_LIST
+                && shared_tail.is_none()
+                && !(self.is_literal)(tail)
+                && tail_index + 1 < self.src.len()
+            {
+                let next = Copied::internal(self.words.len(), TAG_PRIMARY_LIST);
+                self.words.extend([0, 0]);
+                self.store(cell + 1, next);
+                self.remember(tail, next);
+                cell = (next.term >> 2) as usize;
+                src_cell = tail_index;
+            } else {
+                let tail = shared_tail.unwrap_or_else(|| self.copy(tail));
+                self.store(cell + 1, tail);
+                break;
+            }
+        }
+        Copied::internal(first, TAG_PRIMARY_LIST)
+    }
+
+    /// Store a copied term, remembering it if it points into the copy
+    fn store(&mut self, position: usize, copied: Copied) {
+        self.words[position] = copied.term;
+        if copied.internal {
+            self.pointers.push(position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary_management::{enif_inspect_binary, release_process_binaries};
+    use crate::term_creation::{enif_make_binary, enif_make_int, enif_make_list, enif_make_list_cell, enif_make_tuple};
+    use crate::term_decoding::{enif_get_int, enif_get_list, enif_get_tuple};
+    use entities_process::Process;
+    use std::sync::Arc;
+
+    fn test_env(id: ProcessId) -> NifEnv {
+        NifEnv::from_process(Arc::new(Process::new(id)))
+    }
+
+    #[test]
+    fn test_copy_preserves_shared_subterms() {
+        let src = test_env(40730);
+        let shared = enif_make_tuple(&src, &[enif_make_int(&src, 1), enif_make_int(&src, 2)]);
+        let term = enif_make_tuple(&src, &[shared, shared, shared]);
+        // 3 words for the shared tuple, 4 for the outer one
+        assert_eq!(size_shared(&src, term), 7);
+        assert_eq!(size_object(&src, term), 4 + 3 * 3);
+
+        let dst = test_env(40731);
+        dst.allocate_heap(5).unwrap();
+        let copy = copy_shared(&src, term).copy_to_heap(&dst).unwrap();
+        let elements = enif_get_tuple(&dst, copy).unwrap();
+        assert_eq!(elements[0], elements[1]);
+        assert_eq!(elements[1], elements[2]);
+        let inner = enif_get_tuple(&dst, elements[0]).unwrap();
+        assert_eq!(enif_get_int(&dst, inner[1]), Some(2));
+    }
+
+    #[test]
+    fn test_copy_of_dag_is_linear() {
+        let src = test_env(40740);
+        // {T, T} nested 40 deep: 2^40 leaves when unshared
+        let mut term = enif_make_int(&src, 1);
+        for _ in 0..40 {
+            term = enif_make_tuple(&src, &[term, term]);
+        }
+        assert_eq!(size_shared(&src, term), 40 * 3);
+
+        let dst = test_env(40741);
+        let mut copy = copy_shared(&src, term).copy_to_heap(&dst).unwrap();
+        for _ in 0..40 {
+            let elements = enif_get_tuple(&dst, copy).unwrap();
+            assert_eq!(elements[0], elements[1]);
+            copy = elements[0];
+        }
+        assert_eq!(enif_get_int(&dst, copy), Some(1));
+    }
+
+    #[test]
+    fn test_flat_copy_expands_dag() {
+        let src = test_env(40742);
+        let mut term = enif_make_int(&src, 1);
+        for _ in 0..5 {
+            term = enif_make_tuple(&src, &[term, term]);
+        }
+        // 2^5 - 1 tuples of 3 words each
+        assert_eq!(size_object(&src, term), 93);
+        assert_eq!(size_shared(&src, term), 15);
+
+        let dst = test_env(40743);
+        let copy = copy_struct(&src, term).copy_to_heap(&dst).unwrap();
+        let elements = enif_get_tuple(&dst, copy).unwrap();
+        assert_ne!(elements[0], elements[1]);
+        assert_eq!(enif_get_tuple(&dst, elements[0]).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_copy_preserves_shared_list_tails() {
+        let src = test_env(40732);
+        let tail = enif_make_list(&src, &[enif_make_int(&src, 20), enif_make_int(&src, 30)]);
+        let a = enif_make_list_cell(&src, enif_make_int(&src, 10), tail);
+        let b = enif_make_list_cell(&src, enif_make_int(&src, 11), tail);
+        let term = enif_make_tuple(&src, &[a, b]);
+        // Tuple, two heads and one shared two-cell tail
+        assert_eq!(size_shared(&src, term), 3 + 2 * 2 + 2 * 2);
+        assert_eq!(size_object(&src, term), 3 + 2 * (2 + 2 * 2));
+
+        let dst = test_env(40733);
+        let copy = copy_shared(&src, term).copy_to_heap(&dst).unwrap();
+        let elements = enif_get_tuple(&dst, copy).unwrap();
+        let list = enif_get_list(&dst, elements[1]).unwrap();
+        assert_eq!(list.iter().map(|&t| enif_get_int(&dst, t).unwrap()).collect::<Vec<_>>(), vec![11, 20, 30]);
+    }
+
+    #[test]
+    fn test_copy_floats() {
+        let src = test_env(40738);
+        // The bits of the float are not read as a term, whatever they look like
+        let float = crate::float::enif_make_double(&src, f64::from_bits(0x4000_0000_0000_0006));
+        let term = enif_make_tuple(&src, &[float, float]);
+        assert_eq!(size_object(&src, term), 3 + 2 + 2);
+        assert_eq!(size_shared(&src, term), 3 + 2);
+
+        let dst = test_env(40739);
+        dst.allocate_heap(3).unwrap();
+        let copy = copy_struct(&src, term).copy_to_heap(&dst).unwrap();
+        let elements = enif_get_tuple(&dst, copy).unwrap();
+        assert_ne!(elements[0], elements[1]);
+        for element in elements {
+            assert_eq!(crate::float::enif_get_double(&dst, element).map(f64::to_bits), Some(0x4000_0000_0000_0006));
+        }
+    }
+
+    #[test]
+    fn test_literals_are_not_copied() {
+        let src = test_env(40734);
+        let literal = enif_make_tuple(&src, &[enif_make_int(&src, 7)]);
+        let term = enif_make_tuple(&src, &[literal, enif_make_int(&src, 8)]);
+        let fragment = copy_struct_with_literals(&src, term, |t| t == literal);
+        assert_eq!(fragment.size(), 3);
+        assert_eq!(fragment.words()[1], literal);
+        assert_eq!(fragment.root() >> 2, 0);
+    }
+
+    #[test]
+    fn test_refc_binaries_are_shared() {
+        let src = test_env(40735);
+        let binary = enif_make_binary(&src, vec![1u8; 300]);
+        let data = enif_inspect_binary(&src, binary).unwrap().into_refc();
+        let (root, words) = copy_struct(&src, binary).into_heap_fragment(40736);
+        assert_eq!(words.len(), 2);
+        assert!(binary_for_term(40736, root).unwrap().shares_storage(&data));
+        assert_eq!(data.ref_count(), 3);
+        release_process_binaries(40735);
+        release_process_binaries(40736);
+    }
+
+    #[test]
+    fn test_immediate_copy_is_empty() {
+        let src = test_env(40737);
+        let fragment = copy_struct(&src, enif_make_int(&src, 5));
+        assert_eq!(fragment.size(), 0);
+        assert_eq!(fragment.copy_to_heap(&src), Some(enif_make_int(&src, 5)));
+    }
+}
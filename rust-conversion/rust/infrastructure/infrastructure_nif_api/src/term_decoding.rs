@@ -4,6 +4,12 @@
 //! These functions correspond to the `enif_get_*` functions in the C NIF API.
 
 use super::{NifEnv, NifTerm, NifCharEncoding};
+use crate::binary_management::binary_for_term;
+use crate::term_creation::BIGNUM_SIGN_BIT;
+use entities_process::term_tags::{
+    NIL, TAG_IMMED1_MASK, TAG_IMMED1_SIZE, TAG_IMMED1_SMALL, TAG_IMMED2_ATOM, TAG_IMMED2_MASK,
+    TAG_IMMED2_SIZE, TAG_PRIMARY_BOXED, TAG_PRIMARY_HEADER, TAG_PRIMARY_LIST, TAG_PRIMARY_MASK,
+};
 
 /// Decode an atom term
 ///
@@ -26,16 +32,11 @@ pub fn enif_get_atom(
     _env: &NifEnv,
     term: NifTerm,
 ) -> Option<(String, NifCharEncoding)> {
-    // Check for nil (0x3F) first - nil is not an atom
-    if term == 0x3F {
-        return None;
-    }
-    
     // Check if term is an atom (decode tag)
     // Format: (atom_index << 6) + 0x0B, so check if (term & 0x3F) == 0x0B
-    if (term & 0x3F) == 0x0B {
-        // Extract atom index: (term - 0x0B) >> 6
-        let atom_index = ((term - 0x0B) >> 6) as usize;
+    if (term & TAG_IMMED2_MASK) == TAG_IMMED2_ATOM {
+        // Extract atom index
+        let atom_index = (term >> TAG_IMMED2_SIZE) as usize;
         
         // Look up atom name from the global atom table
         let atom_table = infrastructure_utilities::atom_table::get_global_atom_table();
@@ -80,7 +81,7 @@ pub fn enif_get_int(env: &NifEnv, term: NifTerm) -> Option<i32> {
         
         // First, check if the term has any bits set beyond the 27-bit value range
         // Extract the unsigned value before masking
-        let unsigned = (term - 0xF) >> 4;
+        let unsigned = term >> TAG_IMMED1_SIZE;
         
         // Mask to 27 bits to get the actual value bits
         let value_27bit = unsigned & 0x7FFFFFF;
@@ -109,17 +110,7 @@ pub fn enif_get_int(env: &NifEnv, term: NifTerm) -> Option<i32> {
         // Check if the decoded value is within valid small integer range (27 bits)
         // This filters out invalid terms that aren't valid integers of the right size
         // Note: decode_small_integer already handles sign extension correctly
-        // Validate that the term is actually a valid integer term, not just any term with the right tag
-        // For terms like nil (0x3F), they may have the small integer tag but aren't valid integers
         if value >= MIN_SMALL_INT_VALUE && value <= MAX_SMALL_INT_VALUE {
-            // Validate that this is a valid integer term, not an invalid immediate value
-            // Check if the term is nil (0x3F) - nil is not a valid integer despite having the right tag
-            // The check is for anything that isn't an int of the right size, not just nil
-            if term == 0x3F {
-                // This is nil, not a valid integer term
-                return None;
-            }
-            
             // Check if value fits in i32 range (the "right size" for enif_get_int)
             if value >= (i32::MIN as i64) && value <= (i32::MAX as i64) {
                 return Some(value as i32);
@@ -200,12 +191,17 @@ pub fn enif_get_binary(
     term: NifTerm,
 ) -> Option<Vec<u8>> {
     // Check if term is a binary pointer
-    // Binaries are heap-allocated with TAG_PRIMARY_BOXED (0x1) in lower 2 bits
+    // Binaries are heap-allocated with TAG_PRIMARY_BOXED (0x2) in lower 2 bits
     // and BINARY_SUBTAG in the header
     if !is_boxed_term(term) {
         return None;
     }
     
+    // Reference-counted binaries are kept off the heap
+    if let Some(binary) = binary_for_term(env.process_id(), term) {
+        return Some(binary.data().to_vec());
+    }
+    
     // Extract heap index from term pointer
     // Format: (heap_index << 2) | TAG_PRIMARY_BOXED
     let heap_index = (term >> 2) as usize;
@@ -224,8 +220,10 @@ pub fn enif_get_binary(
     // BINARY_SUBTAG is typically 0x0 for binaries
     let header = heap_data[heap_index];
     
-    // Check if this is actually a binary (would need to check subtag in full implementation)
-    // For now, we'll try to decode it as a binary
+    // Tuples and floats share the boxed tag; their headers are not binary headers
+    if crate::tuple::is_arity_value(header) || crate::float::is_float_header(header) {
+        return None;
+    }
     
     // Extract size from header (upper bits)
     let size = (header >> 2) as usize;
@@ -327,49 +325,11 @@ pub fn enif_get_tuple(
     env: &NifEnv,
     term: NifTerm,
 ) -> Option<Vec<NifTerm>> {
-    // Check if term is a tuple placeholder (from term_creation)
-    if is_tuple_placeholder(term) {
-        return decode_tuple_placeholder(term);
-    }
-    
-    // Check if term is a heap-allocated tuple
-    // Tuples are heap-allocated with TAG_PRIMARY_HEADER (0x0) in lower 2 bits
-    if !is_header_term(term) {
-        return None;
-    }
-    
-    // Extract heap index from term pointer
-    // Format: (heap_index << 2) | TAG_PRIMARY_HEADER
-    let heap_index = (term >> 2) as usize;
-    
-    // Get heap data
+    // Tuples are boxed: a pointer to an arity header followed by the elements
     let process = env.process();
     let heap_data = process.heap_slice();
-    
-    // Check bounds
-    if heap_index >= heap_data.len() {
-        return None;
-    }
-    
-    // Read tuple header
-    // Tuple header format: (arity << 2) | TAG_PRIMARY_HEADER
-    let header = heap_data[heap_index];
-    
-    // Extract arity from header (upper bits)
-    let arity = (header >> 2) as usize;
-    
-    // Check if we have enough heap space for the tuple elements
-    if heap_index + 1 + arity > heap_data.len() {
-        return None;
-    }
-    
-    // Read tuple elements
-    let mut elements = Vec::with_capacity(arity);
-    for i in 0..arity {
-        elements.push(heap_data[heap_index + 1 + i]);
-    }
-    
-    Some(elements)
+    let (heap_index, arity) = crate::tuple::tuple_on_heap(&heap_data, term)?;
+    Some(heap_data[heap_index + 1..heap_index + 1 + arity].to_vec())
 }
 
 /// Decode a list term
@@ -395,7 +355,7 @@ pub fn enif_get_list(
     }
     
     // Check if term is a cons cell (heap-allocated)
-    // Cons cells are heap-allocated with TAG_PRIMARY_LIST (0x2) in lower 2 bits
+    // Cons cells are heap-allocated with TAG_PRIMARY_LIST (0x1) in lower 2 bits
     if !is_list_term(term) {
         return None;
     }
@@ -518,8 +478,7 @@ pub fn enif_get_map(
 ///
 /// This is a public function for testing purposes.
 pub fn is_small_integer(term: NifTerm) -> bool {
-    // Check tag: lower 4 bits should be 0xF (_TAG_IMMED1_SMALL)
-    (term & 0xF) == 0xF
+    (term & TAG_IMMED1_MASK) == TAG_IMMED1_SMALL
 }
 
 /// Decode a small integer from an Eterm
@@ -529,8 +488,8 @@ pub fn is_small_integer(term: NifTerm) -> bool {
 pub fn decode_small_integer(term: NifTerm) -> i64 {
     // Extract value: The value is stored in bits [4..], with tag 0xF in bits [0..3]
     // To decode: subtract the tag, then shift right by 4
-    // Format: (value << 4) + 0xF, so value = (term - 0xF) >> 4
-    let unsigned = (term - 0xF) >> 4;
+    // Format: (value << 4) + 0xF, so value = term >> 4
+    let unsigned = term >> TAG_IMMED1_SIZE;
     // The value uses 27 bits (bits 0-26 after shifting)
     // The encoding preserves two's complement, so we can use arithmetic right shift
     // to sign extend. First, extract the 27-bit value, then sign extend.
@@ -551,47 +510,22 @@ pub fn decode_small_integer(term: NifTerm) -> i64 {
 
 /// Check if a term is nil (empty list)
 fn is_nil(term: NifTerm) -> bool {
-    // Nil is encoded as 0x3F (TAG_IMMED2_NIL)
-    term == 0x3F
+    term == NIL
 }
 
 /// Check if a term is a boxed term (heap-allocated)
 fn is_boxed_term(term: NifTerm) -> bool {
-    // Boxed terms have TAG_PRIMARY_BOXED (0x1) in lower 2 bits
-    (term & 0x3) == 0x1
+    (term & TAG_PRIMARY_MASK) == TAG_PRIMARY_BOXED
 }
 
 /// Check if a term is a header term (tuple, map, etc.)
 fn is_header_term(term: NifTerm) -> bool {
-    // Header terms have TAG_PRIMARY_HEADER (0x0) in lower 2 bits
-    // Note: term 0 is valid (heap_index 0). Nil is 0x3F, not 0.
-    (term & 0x3) == 0x0
+    (term & TAG_PRIMARY_MASK) == TAG_PRIMARY_HEADER
 }
 
 /// Check if a term is a list term (cons cell)
 fn is_list_term(term: NifTerm) -> bool {
-    // List terms have TAG_PRIMARY_LIST (0x2) in lower 2 bits
-    (term & 0x3) == 0x2
-}
-
-/// Check if a term is a tuple placeholder
-fn is_tuple_placeholder(term: NifTerm) -> bool {
-    const TUPLE_PLACEHOLDER_TAG: u64 = 0xE0E0E0E0E0E0E0E0;
-    (term & TUPLE_PLACEHOLDER_TAG) == TUPLE_PLACEHOLDER_TAG
-}
-
-/// Decode a tuple placeholder
-/// This should only be used as a fallback when heap allocation fails
-fn decode_tuple_placeholder(term: NifTerm) -> Option<Vec<NifTerm>> {
-    // Placeholder tuples don't store actual elements
-    // They encode arity in the lower bits
-    const TUPLE_PLACEHOLDER_MASK: u64 = 0xE0E0E0E0E0E0E0E0;
-    let arity_encoded = (term & !TUPLE_PLACEHOLDER_MASK) >> 2;
-    let arity = (arity_encoded & 0x3FFFFFF) as usize;
-    
-    // Return empty vector with correct capacity
-    // This is a fallback when heap allocation is not available
-    Some(Vec::with_capacity(arity))
+    (term & TAG_PRIMARY_MASK) == TAG_PRIMARY_LIST
 }
 
 /// Decode a bignum from a term
@@ -619,12 +553,15 @@ fn decode_bignum(env: &NifEnv, term: NifTerm) -> Option<entities_utilities::BigN
     // Read bignum header
     // Bignum header format: (arity << 2) | TAG_PRIMARY_BOXED | sign
     let header = heap_data[heap_index];
+    if crate::tuple::is_arity_value(header) || crate::float::is_float_header(header) {
+        return None;
+    }
     
     // Extract arity (number of bytes) from header
     let arity = (header >> 2) as usize;
     
     // Determine sign from header (lowest bit after tag)
-    let is_negative = (header & 0x1) != 0;
+    let is_negative = (header & BIGNUM_SIGN_BIT) != 0;
     
     // Calculate number of words needed for the data
     let data_words = (arity + 7) / 8; // Round up
@@ -1111,7 +1048,6 @@ mod tests {
         
         // Decode it back
         // Note: This may return None if bignum decoding isn't fully implemented yet
-        // (placeholder tuples don't store actual bignum data)
         let result = enif_get_rational(&env, term);
         // If decoding works, verify it matches
         if let Some(decoded) = result {
@@ -1219,7 +1155,7 @@ mod tests {
     #[test]
     fn test_is_nil() {
         // Test nil term
-        assert!(is_nil(0x3F));
+        assert!(is_nil(NIL));
         
         // Test non-nil terms
         let env = test_env();
@@ -1231,7 +1167,7 @@ mod tests {
     #[test]
     fn test_is_boxed_term() {
         let env = test_env();
-        // Boxed terms have TAG_PRIMARY_BOXED (0x1) in lower 2 bits
+        // Boxed terms have TAG_PRIMARY_BOXED (0x2) in lower 2 bits
         // Most terms we create are not boxed (they're immediate values)
         // This test verifies the function works correctly
         let int_term = term_creation::enif_make_int(&env, 42);
@@ -1239,7 +1175,7 @@ mod tests {
         assert!(!is_boxed_term(int_term));
         
         // Test with a term that has boxed tag
-        let boxed_term = 0x5; // (1 << 2) | 0x1 = boxed term at heap index 1
+        let boxed_term = 0x6; // (1 << 2) | 0x2 = boxed term at heap index 1
         assert!(is_boxed_term(boxed_term));
     }
 
@@ -1247,8 +1183,7 @@ mod tests {
     fn test_is_header_term() {
         let env = test_env();
         // Header terms have TAG_PRIMARY_HEADER (0x0) in lower 2 bits
-        // Note: term 0 is valid (heap_index 0). Nil is 0x3F, not 0.
-        // Test with zero (should return true - it's a valid header term)
+        // Only the tag bits are checked, so THE_NON_VALUE (0) has the header tag too
         assert!(is_header_term(0));
         
         // Test with a term that has header tag
@@ -1263,9 +1198,9 @@ mod tests {
     #[test]
     fn test_is_list_term() {
         let env = test_env();
-        // List terms have TAG_PRIMARY_LIST (0x2) in lower 2 bits
+        // List terms have TAG_PRIMARY_LIST (0x1) in lower 2 bits
         // Test with a term that has list tag
-        let list_term = 0x6; // (1 << 2) | 0x2 = list term at heap index 1
+        let list_term = 0x5; // (1 << 2) | 0x1 = list term at heap index 1
         assert!(is_list_term(list_term));
         
         // Test with non-list terms
@@ -1273,50 +1208,14 @@ mod tests {
         assert!(!is_list_term(int_term));
         
         // Test nil (empty list) - nil is not a list term (it's immediate)
-        assert!(!is_list_term(0x3F));
+        assert!(!is_list_term(NIL));
     }
 
     #[test]
-    fn test_is_tuple_placeholder() {
-        const TUPLE_PLACEHOLDER_TAG: u64 = 0xE0E0E0E0E0E0E0E0;
-        
-        // Test with placeholder
-        assert!(is_tuple_placeholder(TUPLE_PLACEHOLDER_TAG));
-        assert!(is_tuple_placeholder(TUPLE_PLACEHOLDER_TAG | 0x1234));
-        
-        // Test with non-placeholder
+    fn test_enif_get_tuple_pair() {
         let env = test_env();
-        let int_term = term_creation::enif_make_int(&env, 42);
-        assert!(!is_tuple_placeholder(int_term));
-        assert!(!is_tuple_placeholder(0));
-    }
-
-    #[test]
-    fn test_decode_tuple_placeholder() {
-        const TUPLE_PLACEHOLDER_TAG: u64 = 0xE0E0E0E0E0E0E0E0;
         
-        // Test with placeholder for tuple of arity 3
-        // Arity is encoded in lower bits: (arity << 2)
-        let placeholder = TUPLE_PLACEHOLDER_TAG | (3 << 2);
-        let result = decode_tuple_placeholder(placeholder);
-        assert!(result.is_some());
-        let elements = result.unwrap();
-        // Placeholder tuples return empty vector (can't decode actual elements)
-        assert_eq!(elements.capacity(), 3);
-        
-        // Test with placeholder for empty tuple
-        let empty_placeholder = TUPLE_PLACEHOLDER_TAG | (0 << 2);
-        let result = decode_tuple_placeholder(empty_placeholder);
-        assert!(result.is_some());
-        let elements = result.unwrap();
-        assert_eq!(elements.capacity(), 0);
-    }
-
-    #[test]
-    fn test_enif_get_tuple_with_placeholder() {
-        let env = test_env();
-        
-        // Create a tuple (which may return a placeholder)
+        // Create a tuple
         let elements = vec![
             term_creation::enif_make_int(&env, 1),
             term_creation::enif_make_int(&env, 2),
@@ -1326,15 +1225,14 @@ mod tests {
         // Try to decode it
         let result = enif_get_tuple(&env, tuple_term);
         
-        // Should return Some (even if placeholder, it returns empty vector)
-        assert!(result.is_some());
+        assert_eq!(result, Some(elements));
     }
 
     #[test]
     fn test_enif_get_tuple_with_heap_tuple() {
         let env = test_env();
         
-        // Create a tuple that should be heap-allocated (if heap has space)
+        // Create a tuple on the heap
         let elements = vec![
             term_creation::enif_make_int(&env, 10),
             term_creation::enif_make_int(&env, 20),
@@ -1342,24 +1240,9 @@ mod tests {
         ];
         let tuple_term = term_creation::enif_make_tuple(&env, &elements);
         
-        // Try to decode it (works for both heap-allocated and placeholder)
+        // Decode it back
         let result = enif_get_tuple(&env, tuple_term);
-        assert!(result.is_some());
-        let decoded_elements = result.unwrap();
-        
-        // Check if it's a heap-allocated tuple (not placeholder)
-        const TUPLE_PLACEHOLDER_TAG: u64 = 0xE0E0E0E0E0E0E0E0;
-        if (tuple_term & TUPLE_PLACEHOLDER_TAG) != TUPLE_PLACEHOLDER_TAG {
-            // It's a heap-allocated tuple - verify elements
-            if decoded_elements.len() == 3 {
-                // Elements should match
-                // Note: We can't directly compare terms, but we can verify they exist
-                assert_eq!(decoded_elements.len(), 3);
-            }
-        } else {
-            // Placeholder tuple - returns empty vector with capacity
-            assert_eq!(decoded_elements.capacity(), 3);
-        }
+        assert_eq!(result, Some(elements));
     }
 
     #[test]
@@ -1374,13 +1257,22 @@ mod tests {
         assert!(enif_get_tuple(&env, atom_term).is_none());
     }
 
+    #[test]
+    fn test_tuple_is_not_binary() {
+        let env = test_env();
+        let tuple_term = term_creation::enif_make_tuple(&env, &[
+            term_creation::enif_make_int(&env, 1),
+        ]);
+        assert!(enif_get_binary(&env, tuple_term).is_none());
+        assert!(enif_get_int(&env, tuple_term).is_none());
+    }
+
     #[test]
     fn test_enif_get_list_nil() {
         let env = test_env();
         
         // Test with nil (empty list)
-        // Nil is encoded as 0x3F (TAG_IMMED2_NIL)
-        let nil_term = 0x3F;
+        let nil_term = NIL;
         let result = enif_get_list(&env, nil_term);
         assert!(result.is_some());
         let elements = result.unwrap();
@@ -1519,7 +1411,7 @@ mod tests {
         
         // Test with a term that points beyond heap bounds
         let large_heap_index = 999999;
-        let invalid_binary_term = (large_heap_index << 2) | 0x1; // Boxed term
+        let invalid_binary_term = (large_heap_index << 2) | TAG_PRIMARY_BOXED;
         let result = enif_get_binary(&env, invalid_binary_term);
         // Should return None due to bounds check
         assert!(result.is_none());
@@ -1531,7 +1423,7 @@ mod tests {
         
         // Test with a term that points beyond heap bounds
         let large_heap_index = 999999;
-        let invalid_list_term = (large_heap_index << 2) | 0x2; // List term
+        let invalid_list_term = (large_heap_index << 2) | TAG_PRIMARY_LIST;
         let result = enif_get_list(&env, invalid_list_term);
         // Should return None or handle gracefully
         assert!(result.is_none() || result.is_some());
@@ -0,0 +1,182 @@
+//! Tuple Terms
+//!
+//! Provides the heap layout of tuples and the operations on tuple terms:
+//! type test, element access and `setelement`.
+//! Based on the tuple definitions of erl_term.h and on element/2 and
+//! setelement/3 in bif.c.
+//!
+//! A tuple is a boxed term: a pointer tagged with `TAG_PRIMARY_BOXED`
+//! (`(heap_index << 2) | 0x2`) to an arity header followed by the elements.
+//! The header is `make_arityval(arity)`, `arity << 6` with the
+//! `ARITYVAL_SUBTAG` and `TAG_PRIMARY_HEADER` tag bits zero, which tells a
+//! tuple apart from the other boxed terms on the heap (bignums and binaries),
+//! whose headers carry `TAG_PRIMARY_BOXED`. The empty tuple is a header
+//! alone.
+//!
+//! Tuples are immutable: [`erts_setelement`] builds a new tuple and leaves
+//! the original, which other terms may share, untouched.
+
+use entities_process::term_tags::{
+    make_boxed, ARITYVAL_SUBTAG, HEADER_ARITY_OFFS, TAG_HEADER_MASK, TAG_PRIMARY_BOXED,
+    TAG_PRIMARY_MASK,
+};
+
+pub use entities_process::term_tags::make_arityval;
+
+use crate::{NifEnv, NifTerm};
+
+/// Largest arity of a tuple (`MAX_ARITYVAL`)
+pub const MAX_TUPLE_ARITY: usize = infrastructure_runtime_utils::MAX_ARITYVAL;
+
+/// Check if a header word is a tuple header (`is_arity_value`)
+pub fn is_arity_value(header: NifTerm) -> bool {
+    header & TAG_HEADER_MASK == ARITYVAL_SUBTAG
+}
+
+/// Get the arity of a tuple header (`arityval`)
+pub fn arityval(header: NifTerm) -> usize {
+    (header >> HEADER_ARITY_OFFS) as usize
+}
+
+/// Make a boxed pointer to a tuple at a heap index (`make_tuple`)
+pub fn make_tuple_term(heap_index: usize) -> NifTerm {
+    make_boxed(heap_index)
+}
+
+/// Locate a tuple on a heap
+///
+/// # Returns
+/// The heap index of the header and the arity, or `None` if `term` is not a
+/// tuple on `heap`
+pub(crate) fn tuple_on_heap(heap: &[NifTerm], term: NifTerm) -> Option<(usize, usize)> {
+    if term & TAG_PRIMARY_MASK != TAG_PRIMARY_BOXED {
+        return None;
+    }
+    let index = (term >> 2) as usize;
+    let header = *heap.get(index)?;
+    if !is_arity_value(header) {
+        return None;
+    }
+    let arity = arityval(header);
+    (index + arity < heap.len()).then_some((index, arity))
+}
+
+/// Allocate a tuple on the heap of an environment
+///
+/// # Returns
+/// The tuple, or `None` if the heap is full or the arity too large
+pub(crate) fn allocate_tuple(env: &NifEnv, elements: &[NifTerm]) -> Option<NifTerm> {
+    if elements.len() > MAX_TUPLE_ARITY {
+        return None;
+    }
+    let heap_index = env.allocate_heap(elements.len() + 1)?;
+    let process = env.process();
+    let mut heap_data = process.heap_slice_mut();
+    heap_data[heap_index] = make_arityval(elements.len());
+    heap_data[heap_index + 1..heap_index + 1 + elements.len()].copy_from_slice(elements);
+    Some(make_tuple_term(heap_index))
+}
+
+/// Check if a term is a tuple (`enif_is_tuple`)
+pub fn enif_is_tuple(env: &NifEnv, term: NifTerm) -> bool {
+    tuple_on_heap(&env.process().heap_slice(), term).is_some()
+}
+
+/// Get the arity of a tuple (`tuple_size/1`)
+///
+/// # Returns
+/// The arity, or `None` if `term` is not a tuple
+pub fn erts_tuple_size(env: &NifEnv, term: NifTerm) -> Option<usize> {
+    tuple_on_heap(&env.process().heap_slice(), term).map(|(_, arity)| arity)
+}
+
+/// Get an element of a tuple (`element/2`)
+///
+/// # Arguments
+/// * `env` - Environment the tuple was built in
+/// * `index` - 1-based position of the element
+/// * `tuple` - Tuple term
+///
+/// # Returns
+/// The element, or `None` if `tuple` is not a tuple or `index` is out of range
+pub fn erts_element(env: &NifEnv, index: usize, tuple: NifTerm) -> Option<NifTerm> {
+    let heap = env.process().heap_slice();
+    let (start, arity) = tuple_on_heap(&heap, tuple)?;
+    (1..=arity).contains(&index).then(|| heap[start + index])
+}
+
+/// Replace an element of a tuple (`setelement/3`)
+///
+/// Builds a copy of the tuple with the element replaced; the original tuple
+/// is not modified.
+///
+/// # Arguments
+/// * `env` - Environment the tuple was built in
+/// * `index` - 1-based position of the element
+/// * `tuple` - Tuple term
+/// * `value` - New element
+///
+/// # Returns
+/// The new tuple, or `None` if `tuple` is not a tuple, `index` is out of
+/// range or the heap is full
+pub fn erts_setelement(env: &NifEnv, index: usize, tuple: NifTerm, value: NifTerm) -> Option<NifTerm> {
+    let mut elements = {
+        let heap = env.process().heap_slice();
+        let (start, arity) = tuple_on_heap(&heap, tuple)?;
+        if !(1..=arity).contains(&index) {
+            return None;
+        }
+        heap[start + 1..start + 1 + arity].to_vec()
+    };
+    elements[index - 1] = value;
+    allocate_tuple(env, &elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::term_creation::{enif_make_binary, enif_make_int, enif_make_tuple};
+    use entities_process::Process;
+    use std::sync::Arc;
+
+    fn test_env() -> NifEnv {
+        NifEnv::from_process(Arc::new(Process::new(41370)))
+    }
+
+    #[test]
+    fn test_tuple_layout_and_element_access() {
+        let env = test_env();
+        let one = enif_make_int(&env, 1);
+        let two = enif_make_int(&env, 2);
+        let tuple = enif_make_tuple(&env, &[one, two]);
+        assert_eq!(tuple & TAG_PRIMARY_MASK, TAG_PRIMARY_BOXED);
+        assert_eq!(env.process().heap_slice()[(tuple >> 2) as usize], make_arityval(2));
+
+        assert!(enif_is_tuple(&env, tuple));
+        assert_eq!(erts_tuple_size(&env, tuple), Some(2));
+        assert_eq!(erts_element(&env, 2, tuple), Some(two));
+        assert_eq!(erts_element(&env, 0, tuple), None);
+        assert_eq!(erts_element(&env, 3, tuple), None);
+
+        let empty = enif_make_tuple(&env, &[]);
+        assert_eq!(erts_tuple_size(&env, empty), Some(0));
+        assert!(!enif_is_tuple(&env, one));
+        assert!(!enif_is_tuple(&env, enif_make_binary(&env, vec![1, 2, 3])));
+    }
+
+    #[test]
+    fn test_setelement_copies() {
+        let env = test_env();
+        let inner = enif_make_tuple(&env, &[enif_make_int(&env, 7)]);
+        let tuple = enif_make_tuple(&env, &[inner, enif_make_int(&env, 8)]);
+
+        let updated = erts_setelement(&env, 2, tuple, enif_make_int(&env, 9)).unwrap();
+        assert_ne!(updated, tuple);
+        assert_eq!(erts_element(&env, 2, tuple), Some(enif_make_int(&env, 8)));
+        assert_eq!(erts_element(&env, 2, updated), Some(enif_make_int(&env, 9)));
+        // Untouched elements are shared, not copied
+        assert_eq!(erts_element(&env, 1, updated), Some(inner));
+        assert_eq!(erts_setelement(&env, 3, tuple, inner), None);
+        assert_eq!(erts_setelement(&env, 1, enif_make_int(&env, 1), inner), None);
+    }
+}
@@ -5,6 +5,7 @@
 
 use infrastructure_nif_api::*;
 use entities_process::{Process, ProcessId};
+use entities_process::term_tags::{pointer_index, NIL};
 use std::sync::Arc;
 use infrastructure_utilities::process_table::get_global_process_table;
 use infrastructure_nif_api::resource_management::ErlNifResourceType;
@@ -182,7 +183,6 @@ fn test_enif_make_tuple() {
         enif_make_int(&env, 3),
     ]);
     
-    // Note: term 0 is valid (heap_index 0). Nil is 0x3F, not 0.
     // Verify tuples can be decoded (this validates they're valid terms)
     assert!(enif_get_tuple(&env, tuple_term1).is_some());
     assert!(enif_get_tuple(&env, tuple_term2).is_some());
@@ -374,7 +374,6 @@ fn test_enif_make_badarg() {
     
     // Create badarg exception
     let badarg_term = enif_make_badarg(&env);
-    // Note: term 0 is valid (heap_index 0). Nil is 0x3F, not 0.
     // Verify it's an exception (this validates it's a valid term)
     let is_exception = enif_is_exception(&env, badarg_term);
     assert!(is_exception);
@@ -434,7 +433,7 @@ fn test_enif_make_resource() {
     let resource_type = ErlNifResourceType::new("test_resource".to_string(), "test_module".to_string());
     let resource = enif_alloc_resource(&resource_type, 100);
     assert!(resource.is_ok());
-    let resource = Arc::new(resource.unwrap());
+    let resource = resource.unwrap();
     let resource_term = enif_make_resource(&env, &resource);
     
     assert!(resource_term != 0);
@@ -598,9 +597,9 @@ fn test_error_cases() {
     let env = NifEnv::from_process(process);
     
     // Test decoding terms that are not ints or atoms
-    // Use nil (0x3F) - it's a valid term but not an int or atom,
+    // Use nil (NIL) - it's a valid term but not an int or atom,
     // so enif_get_int and enif_get_atom should return None
-    let invalid_term = 0x3Fu64; // Nil term
+    let invalid_term = NIL;
     let decoded_int = enif_get_int(&env, invalid_term);
     assert!(decoded_int.is_none());
     
@@ -635,3 +634,114 @@ fn test_multiple_terms_creation() {
     assert_ne!(binary, string);
 }
 
+
+#[test]
+fn test_resource_destructed_when_process_exits() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    let destructed = Arc::new(AtomicUsize::new(0));
+    let counter = destructed.clone();
+    let init = ErlNifResourceTypeInit {
+        dtor: Some(Arc::new(move |_data: &mut [u8]| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })),
+        ..Default::default()
+    };
+    let resource_type = enif_open_resource_type(
+        "it_module",
+        "it_exit_resource",
+        init,
+        ErlNifResourceFlags::Create as u32,
+    )
+    .unwrap();
+
+    let process = Arc::new(Process::new(40693));
+    let env = NifEnv::from_process(process);
+    let resource = enif_alloc_resource(&resource_type, 16).unwrap();
+    let term = enif_make_resource(&env, &resource);
+    enif_release_resource(resource);
+    assert!(enif_get_resource(&env, term, &resource_type).is_some());
+    assert_eq!(destructed.load(Ordering::SeqCst), 0);
+
+    assert_eq!(release_process_resources(40693), 1);
+    assert_eq!(destructed.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_schedule_nif_on_dirty_cpu_scheduler() {
+    let schedulers = DirtySchedulers::new(2, 0);
+    let env = NifEnv::from_process(Arc::new(Process::new(40701)));
+
+    // Yields to a dirty CPU scheduler, which counts down in slices
+    fn countdown() -> ErlNifFunction {
+        Arc::new(|env: &NifEnv, args: &[NifTerm]| {
+            let mut remaining = args[0];
+            while remaining > 0 {
+                remaining -= 1;
+                if enif_consume_timeslice(env, 25) && remaining > 0 {
+                    return enif_schedule_nif(env, "countdown", ErlNifDirtyFlags::DirtyCpu, countdown(), &[remaining]);
+                }
+            }
+            assert_eq!(enif_thread_type(), ErlNifThreadType::DirtyCpuScheduler);
+            1
+        })
+    }
+    let start: ErlNifFunction = Arc::new(|env: &NifEnv, args: &[NifTerm]| {
+        enif_schedule_nif(env, "countdown", ErlNifDirtyFlags::DirtyCpu, countdown(), args)
+    });
+    let call = ScheduledNif {
+        name: "start".to_string(),
+        flags: ErlNifDirtyFlags::Normal,
+        function: start,
+        args: vec![10],
+    };
+
+    assert_eq!(schedulers.execute_nif(&env, call), Ok(1));
+    assert_eq!(env.reductions(), 10 * CONTEXT_REDS / 4);
+}
+
+#[test]
+fn test_enif_send_from_native_thread() {
+    let receiver = Arc::new(Process::new(40703));
+    get_global_process_table().insert(40703, Arc::clone(&receiver));
+
+    let sender = std::thread::spawn(|| {
+        let mut msg_env = enif_alloc_env();
+        let list = enif_make_list(&msg_env, &[enif_make_int(&msg_env, 10), enif_make_int(&msg_env, 20)]);
+        let msg = enif_make_tuple(&msg_env, &[enif_make_atom(&msg_env, "done"), list]);
+        let sent = enif_send(None, 40703, Some(&mut msg_env), msg);
+        assert_eq!(msg_env.heap_top_index(), 0);
+        enif_free_env(msg_env);
+        sent
+    });
+    assert!(sender.join().unwrap());
+
+    let message = receiver.receive_after_0(|_| true).unwrap();
+    let fragment = message.heap_fragment.unwrap();
+    // The fragment holds the tuple followed by the list cells
+    let tuple = pointer_index(message.payload);
+    assert_eq!(fragment[tuple], make_arityval(2));
+    let cell = pointer_index(fragment[tuple + 2]);
+    assert_eq!(fragment[cell], enif_make_int(&enif_alloc_env(), 10));
+    get_global_process_table().remove(40703);
+
+    assert!(!enif_send(None, 40703, Some(&mut enif_alloc_env()), NIL));
+}
+
+#[test]
+fn test_binary_api_round_trip() {
+    let env = NifEnv::from_process(Arc::new(Process::new(40725)));
+
+    let mut binary = enif_alloc_binary(256).unwrap();
+    for (i, byte) in binary.data_mut().unwrap().iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    let term = enif_make_binary(&env, binary);
+    let sub = enif_make_sub_binary(&env, term, 100, 10).unwrap();
+    assert_eq!(enif_get_binary(&env, sub).unwrap(), (100..110).collect::<Vec<u8>>());
+
+    let iolist = enif_make_list(&env, &[sub, enif_make_int(&env, 0)]);
+    let flat = enif_inspect_iolist_as_binary(&env, iolist).unwrap();
+    assert_eq!(flat.size(), 11);
+    enif_release_binary(flat);
+    assert_eq!(release_process_binaries(40725), 2);
+}
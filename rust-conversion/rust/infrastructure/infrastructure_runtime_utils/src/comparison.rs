@@ -300,6 +300,19 @@ pub fn erts_cmp(a: &Term, b: &Term, _order: i32) -> Result<i32, ComparisonError>
             let b_cross = b_num * a_den;
             Ok(if a_cross < b_cross { -1 } else { 1 })
         }
+        (Term::Tuple(a_elements), Term::Tuple(b_elements)) => {
+            // Smaller arity first, then element by element
+            if a_elements.len() != b_elements.len() {
+                return Ok(if a_elements.len() < b_elements.len() { -1 } else { 1 });
+            }
+            for (a_elem, b_elem) in a_elements.iter().zip(b_elements.iter()) {
+                let result = erts_cmp(a_elem, b_elem, _order)?;
+                if result != 0 {
+                    return Ok(result);
+                }
+            }
+            Ok(0)
+        }
         // For complex types, we'd need more sophisticated comparison
         _ => Err(ComparisonError::ComparisonFailed("Complex type comparison not fully implemented".to_string())),
     }
@@ -681,10 +694,26 @@ mod tests {
     }
     
     #[test]
-    fn test_erts_cmp_complex_types() {
-        // Complex types should return an error
+    fn test_erts_cmp_tuple() {
+        // Tuples compare by arity first, then element by element
         let a = Term::Tuple(vec![Term::Small(1)]);
         let b = Term::Tuple(vec![Term::Small(2)]);
+        assert_eq!(erts_cmp(&a, &b, 0).unwrap(), -1);
+        assert_eq!(erts_cmp(&b, &a, 0).unwrap(), 1);
+        
+        let larger = Term::Tuple(vec![Term::Small(0), Term::Small(0)]);
+        assert_eq!(erts_cmp(&b, &larger, 0).unwrap(), -1);
+        
+        let nested_a = Term::Tuple(vec![Term::Atom(1), a]);
+        let nested_b = Term::Tuple(vec![Term::Atom(1), b]);
+        assert_eq!(erts_cmp(&nested_a, &nested_b, 0).unwrap(), -1);
+    }
+    
+    #[test]
+    fn test_erts_cmp_complex_types() {
+        // Complex types should return an error
+        let a = Term::Tuple(vec![Term::Map(vec![(Term::Small(1), Term::Small(2))])]);
+        let b = Term::Tuple(vec![Term::Map(vec![(Term::Small(1), Term::Small(3))])]);
         let result = erts_cmp(&a, &b, 0);
         assert!(result.is_err());
         match result.unwrap_err() {
@@ -17,7 +17,8 @@
 //! ## Modules
 //!
 //! - **[`term_building`](term_building/index.html)**: Term building functions
-//!   (erts_bld_atom, erts_bld_uint, erts_bld_tuple, etc.)
+//!   (erts_bld_atom, erts_bld_uint, erts_bld_tuple, etc.) and list
+//!   deconstruction (erts_list_length, erts_list_to_vec)
 //!
 //! - **[`comparison`](comparison/index.html)**: Term comparison functions
 //!   (eq, erts_cmp)
@@ -44,8 +45,9 @@ pub mod initialization;
 pub use term_building::{
     erts_bld_atom, erts_bld_uint, erts_bld_uword, erts_bld_uint64, erts_bld_sint64,
     erts_bld_cons, erts_bld_tuple, erts_bld_tuplev, erts_bld_string_n, erts_bld_list,
+    erts_list_length, erts_list_to_vec,
     erts_bld_2tup_list, erts_bld_atom_uword_2tup_list, erts_bld_atom_2uint_3tup_list,
-    TermBuildingError, HeapBuilder,
+    TermBuildingError, HeapBuilder, MAX_ARITYVAL,
 };
 pub use comparison::{eq, erts_cmp, ComparisonError};
 pub use initialization::{erts_init_utils, erts_init_utils_mem, erts_utils_sched_spec_data_init};
@@ -7,6 +7,10 @@ use entities_data_handling::term_hashing::Term;
 use entities_data_handling::atom::{AtomTable, AtomEncoding};
 use entities_utilities::BigNumber;
 
+/// Largest arity of a tuple (`MAX_ARITYVAL`), bounded by the 26 bits the
+/// arity takes in a tuple header
+pub const MAX_ARITYVAL: usize = (1 << 26) - 1;
+
 /// Term building error types
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TermBuildingError {
@@ -245,12 +249,18 @@ pub fn erts_bld_cons(
 ///
 /// # Returns
 /// * `Ok(Term)` - Built tuple
-/// * `Err(TermBuildingError)` - Building error
+/// * `Err(TermBuildingError::InvalidArgument)` - Arity exceeds [`MAX_ARITYVAL`]
 pub fn erts_bld_tuple(
     builder: &mut HeapBuilder,
     elements: Vec<Term>,
 ) -> Result<Term, TermBuildingError> {
     let arity = elements.len();
+    if arity > MAX_ARITYVAL {
+        return Err(TermBuildingError::InvalidArgument(format!(
+            "tuple arity {} exceeds {}",
+            arity, MAX_ARITYVAL
+        )));
+    }
     
     if arity == 0 {
         // Empty tuple - special case, no heap allocation
@@ -326,13 +336,21 @@ pub fn erts_bld_string_n(
 /// * `terms` - Array of terms
 ///
 /// # Returns
-/// * `Ok(Term)` - Built list
-/// * `Err(TermBuildingError)` - Building error
+/// * `Ok(Term)` - Built list of the first `length` terms
+/// * `Err(TermBuildingError::InvalidArgument)` - `length` exceeds the terms
 pub fn erts_bld_list(
     builder: &mut HeapBuilder,
     length: usize,
     terms: &[Term],
 ) -> Result<Term, TermBuildingError> {
+    let Some(terms) = terms.get(..length) else {
+        return Err(TermBuildingError::InvalidArgument(format!(
+            "list length {} exceeds {} terms",
+            length,
+            terms.len()
+        )));
+    };
+
     // Each cons cell needs 2 words
     let words_needed = length * 2;
     builder.add_size(words_needed);
@@ -352,6 +370,50 @@ pub fn erts_bld_list(
     }
 }
 
+/// Get the length of a proper list (`erts_list_length`)
+///
+/// The tails are followed iteratively, so long lists do not recurse.
+///
+/// # Returns
+/// * `Some(usize)` - Number of elements
+/// * `None` - `list` is not a proper list
+pub fn erts_list_length(list: &Term) -> Option<usize> {
+    let mut length = 0;
+    let mut cell = list;
+    loop {
+        match cell {
+            Term::Nil => return Some(length),
+            Term::List { tail, .. } => {
+                length += 1;
+                cell = tail;
+            }
+            _ => return None,
+        }
+    }
+}
+
+/// Split a proper list into its elements
+///
+/// The inverse of [`erts_bld_list`].
+///
+/// # Returns
+/// * `Some(Vec<Term>)` - Elements in order
+/// * `None` - `list` is not a proper list
+pub fn erts_list_to_vec(list: &Term) -> Option<Vec<Term>> {
+    let mut elements = Vec::new();
+    let mut cell = list;
+    loop {
+        match cell {
+            Term::Nil => return Some(elements),
+            Term::List { head, tail } => {
+                elements.push((**head).clone());
+                cell = tail;
+            }
+            _ => return None,
+        }
+    }
+}
+
 /// Build a list of 2-tuples
 ///
 /// Based on `erts_bld_2tup_list()` from utils.c
@@ -794,6 +856,34 @@ mod tests {
         assert_eq!(builder.size(), 0);
     }
     
+    #[test]
+    fn test_erts_bld_list_length_exceeds_terms() {
+        let terms = vec![Term::Small(1), Term::Small(2)];
+        let mut builder = HeapBuilder::new_build(100);
+        let list = erts_bld_list(&mut builder, 1, &terms).unwrap();
+        assert_eq!(erts_list_to_vec(&list), Some(vec![Term::Small(1)]));
+        assert!(matches!(
+            erts_bld_list(&mut builder, 3, &terms),
+            Err(TermBuildingError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_erts_list_deconstruction() {
+        let terms = vec![Term::Small(1), Term::Atom(2), Term::Nil];
+        let mut builder = HeapBuilder::new_build(100);
+        let list = erts_bld_list(&mut builder, terms.len(), &terms).unwrap();
+        assert_eq!(erts_list_length(&list), Some(3));
+        assert_eq!(erts_list_to_vec(&list), Some(terms));
+        assert_eq!(erts_list_length(&Term::Nil), Some(0));
+
+        // [1 | 2] is improper
+        let improper = erts_bld_cons(&mut builder, Term::Small(1), Term::Small(2)).unwrap();
+        assert_eq!(erts_list_length(&improper), None);
+        assert_eq!(erts_list_to_vec(&improper), None);
+        assert_eq!(erts_list_length(&Term::Small(1)), None);
+    }
+
     #[test]
     fn test_erts_bld_2tup_list() {
         let mut builder = HeapBuilder::new_size_calc();
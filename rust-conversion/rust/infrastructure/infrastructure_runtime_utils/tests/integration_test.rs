@@ -252,8 +252,14 @@ fn test_erts_cmp_complex_terms() {
     let term1 = Term::Tuple(vec![Term::Small(1)]);
     let term2 = Term::Tuple(vec![Term::Small(2)]);
     
+    // Tuples compare element by element
+    assert_eq!(erts_cmp(&term1, &term2, 0), Ok(-1));
+    
+    let term1 = Term::Map(vec![(Term::Small(1), Term::Small(1))]);
+    let term2 = Term::Map(vec![(Term::Small(1), Term::Small(2))]);
+    
     let result = erts_cmp(&term1, &term2, 0);
-    // Complex type comparison (tuples) is not yet fully implemented
+    // Complex type comparison (maps) is not yet fully implemented
     // The function returns an error indicating this
     assert!(result.is_err());
     match result.unwrap_err() {
@@ -0,0 +1,542 @@
+//! Clock Module
+//!
+//! Provides the Erlang clock subsystem: monotonic time, corrected Erlang
+//! system time and the time offset between them, following the time warp
+//! mode selected with `+C` at emulator start.
+//!
+//! ## Overview
+//!
+//! Erlang time is built from two parts:
+//!
+//! - **Erlang monotonic time**: a strictly non-decreasing clock in native
+//!   units that starts at [`MONOTONIC_BEGIN`], an arbitrary large negative
+//!   value, just like the C runtime.
+//! - **Time offset**: the difference between Erlang system time and
+//!   monotonic time. Erlang system time is always `monotonic + offset`.
+//!
+//! How the offset is allowed to change depends on the [`TimeWarpMode`]:
+//!
+//! - **`no_time_warp`**: the offset is fixed at start. OS system time
+//!   changes are not reflected in Erlang system time.
+//! - **`single_time_warp`**: the offset is *preliminary* and fixed until
+//!   [`ErtsClock::finalize_time_offset`] is called. Finalizing performs a
+//!   single warp to the current OS system time and fixes the offset again.
+//! - **`multi_time_warp`**: the offset is *volatile*. It follows the OS
+//!   system time whenever they drift apart by more than
+//!   [`TIME_OFFSET_CHANGE_THRESHOLD`], and each change is delivered as a
+//!   [`TimeOffsetEvent`] to subscribers.
+//!
+//! ## Examples
+//!
+//! ```rust
+//! use infrastructure_time_management::clock::{ErtsClock, TimeWarpMode};
+//! use infrastructure_time_management::time_unit::TimeUnit;
+//!
+//! let clock = ErtsClock::new(TimeWarpMode::NoTimeWarp);
+//! let t1 = clock.monotonic_time(TimeUnit::Native);
+//! let t2 = clock.monotonic_time(TimeUnit::Native);
+//! assert!(t2 >= t1);
+//!
+//! let system = clock.system_time(TimeUnit::Native);
+//! assert!(system - clock.time_offset(TimeUnit::Native) >= t2);
+//! ```
+//!
+//! Based on `erl_time_sup.c`
+
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::time_unit::{convert_time_unit, TimeUnit};
+
+/// Value of Erlang monotonic time (native units) when the clock starts
+///
+/// Matches the default `ERTS_MONOTONIC_BEGIN` of 64-bit builds, which keeps
+/// monotonic time far away from zero so that code cannot rely on its value.
+pub const MONOTONIC_BEGIN: i64 = -576_460_752_303_423_488;
+
+/// Minimum drift (native units) before a volatile time offset is updated
+pub const TIME_OFFSET_CHANGE_THRESHOLD: i64 = 1_000_000;
+
+/// Time warp mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeWarpMode {
+    /// No time warp
+    NoTimeWarp,
+    /// Multi-time warp
+    MultiTimeWarp,
+    /// Single time warp
+    SingleTimeWarp,
+}
+
+impl TimeWarpMode {
+    /// Parse a time warp mode from its `+C` argument name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "no_time_warp" => Some(TimeWarpMode::NoTimeWarp),
+            "multi_time_warp" => Some(TimeWarpMode::MultiTimeWarp),
+            "single_time_warp" => Some(TimeWarpMode::SingleTimeWarp),
+            _ => None,
+        }
+    }
+
+    /// Name of the mode as reported by `erlang:system_info(time_warp_mode)`
+    pub fn name(&self) -> &'static str {
+        match self {
+            TimeWarpMode::NoTimeWarp => "no_time_warp",
+            TimeWarpMode::MultiTimeWarp => "multi_time_warp",
+            TimeWarpMode::SingleTimeWarp => "single_time_warp",
+        }
+    }
+}
+
+/// State of the time offset
+///
+/// Reported by `erlang:system_info(time_offset)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeOffsetState {
+    /// Offset is fixed for now but will be finalized later (single time warp)
+    Preliminary,
+    /// Offset will never change again
+    Final,
+    /// Offset may change at any time (multi time warp)
+    Volatile,
+}
+
+/// Notification that the time offset changed
+///
+/// Both offsets are in native time units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeOffsetEvent {
+    /// Offset before the change
+    pub old_offset: i64,
+    /// Offset after the change
+    pub new_offset: i64,
+}
+
+/// Source of raw clock readings
+///
+/// Separates the clock logic from the OS so that time warps can be tested
+/// deterministically.
+pub trait ClockSource: Send + Sync {
+    /// Elapsed time since an arbitrary fixed point, in native units
+    ///
+    /// Must never decrease.
+    fn monotonic_nanos(&self) -> i64;
+
+    /// OS system time since the Unix epoch, in native units
+    fn os_system_time_nanos(&self) -> i64;
+}
+
+impl<T: ClockSource + ?Sized> ClockSource for std::sync::Arc<T> {
+    fn monotonic_nanos(&self) -> i64 {
+        (**self).monotonic_nanos()
+    }
+
+    fn os_system_time_nanos(&self) -> i64 {
+        (**self).os_system_time_nanos()
+    }
+}
+
+/// Clock source backed by the operating system clocks
+#[derive(Debug)]
+pub struct OsClockSource {
+    start: Instant,
+}
+
+impl OsClockSource {
+    /// Create a source whose monotonic reading starts at zero now
+    pub fn new() -> Self {
+        Self { start: Instant::now() }
+    }
+}
+
+impl Default for OsClockSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClockSource for OsClockSource {
+    fn monotonic_nanos(&self) -> i64 {
+        self.start.elapsed().as_nanos() as i64
+    }
+
+    fn os_system_time_nanos(&self) -> i64 {
+        match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_nanos() as i64,
+            Err(e) => -(e.duration().as_nanos() as i64),
+        }
+    }
+}
+
+/// Manually driven clock source
+///
+/// Both readings only change when told to, which makes it possible to
+/// simulate OS time changes. Share it with the clock through an `Arc` to
+/// keep a handle for driving it.
+#[derive(Debug, Default)]
+pub struct ManualClockSource {
+    monotonic: AtomicI64,
+    system: AtomicI64,
+}
+
+impl ManualClockSource {
+    /// Create a manual source with the given OS system time (native units)
+    pub fn new(os_system_time: i64) -> Self {
+        Self {
+            monotonic: AtomicI64::new(0),
+            system: AtomicI64::new(os_system_time),
+        }
+    }
+
+    /// Let `nanos` pass on both the monotonic and the OS system clock
+    pub fn advance(&self, nanos: i64) {
+        self.monotonic.fetch_add(nanos, Ordering::SeqCst);
+        self.system.fetch_add(nanos, Ordering::SeqCst);
+    }
+
+    /// Step the OS system clock by `nanos` without moving monotonic time
+    pub fn warp_os_time(&self, nanos: i64) {
+        self.system.fetch_add(nanos, Ordering::SeqCst);
+    }
+}
+
+impl ClockSource for ManualClockSource {
+    fn monotonic_nanos(&self) -> i64 {
+        self.monotonic.load(Ordering::SeqCst)
+    }
+
+    fn os_system_time_nanos(&self) -> i64 {
+        self.system.load(Ordering::SeqCst)
+    }
+}
+
+/// Mutable part of the clock guarded by a lock
+struct OffsetState {
+    offset: i64,
+    state: TimeOffsetState,
+    subscribers: Vec<Sender<TimeOffsetEvent>>,
+}
+
+/// Erlang clock
+///
+/// Combines a [`ClockSource`] with the time warp mode to provide the
+/// values returned by `erlang:monotonic_time/0,1`, `erlang:system_time/0,1`
+/// and `erlang:time_offset/0,1`.
+pub struct ErtsClock {
+    mode: TimeWarpMode,
+    source: Box<dyn ClockSource>,
+    /// Last monotonic value handed out, used to keep readings non-decreasing
+    last_monotonic: AtomicI64,
+    offset: Mutex<OffsetState>,
+}
+
+impl ErtsClock {
+    /// Create a clock backed by the OS clocks
+    pub fn new(mode: TimeWarpMode) -> Self {
+        Self::with_source(mode, Box::new(OsClockSource::new()))
+    }
+
+    /// Create a clock backed by the given source
+    ///
+    /// The initial time offset makes Erlang system time equal to the OS
+    /// system time reported by the source.
+    pub fn with_source(mode: TimeWarpMode, source: Box<dyn ClockSource>) -> Self {
+        let monotonic = MONOTONIC_BEGIN.saturating_add(source.monotonic_nanos());
+        let offset = source.os_system_time_nanos().saturating_sub(monotonic);
+        let state = match mode {
+            TimeWarpMode::NoTimeWarp => TimeOffsetState::Final,
+            TimeWarpMode::SingleTimeWarp => TimeOffsetState::Preliminary,
+            TimeWarpMode::MultiTimeWarp => TimeOffsetState::Volatile,
+        };
+        Self {
+            mode,
+            source,
+            last_monotonic: AtomicI64::new(monotonic),
+            offset: Mutex::new(OffsetState {
+                offset,
+                state,
+                subscribers: Vec::new(),
+            }),
+        }
+    }
+
+    /// Time warp mode of this clock
+    pub fn time_warp_mode(&self) -> TimeWarpMode {
+        self.mode
+    }
+
+    /// Current state of the time offset
+    pub fn time_offset_state(&self) -> TimeOffsetState {
+        self.offset.lock().unwrap().state
+    }
+
+    /// Erlang monotonic time in native units
+    fn monotonic_native(&self) -> i64 {
+        let now = MONOTONIC_BEGIN.saturating_add(self.source.monotonic_nanos());
+        let previous = self.last_monotonic.fetch_max(now, Ordering::SeqCst);
+        now.max(previous)
+    }
+
+    /// Erlang monotonic time (`erlang:monotonic_time/1`)
+    pub fn monotonic_time(&self, unit: TimeUnit) -> i64 {
+        convert_time_unit(self.monotonic_native(), TimeUnit::Native, unit)
+    }
+
+    /// Erlang system time (`erlang:system_time/1`)
+    ///
+    /// In multi time warp mode this also picks up OS system time changes.
+    pub fn system_time(&self, unit: TimeUnit) -> i64 {
+        let monotonic = self.monotonic_native();
+        let offset = self.current_offset(monotonic);
+        convert_time_unit(monotonic.saturating_add(offset), TimeUnit::Native, unit)
+    }
+
+    /// Current time offset (`erlang:time_offset/1`)
+    pub fn time_offset(&self, unit: TimeUnit) -> i64 {
+        let monotonic = self.monotonic_native();
+        convert_time_unit(self.current_offset(monotonic), TimeUnit::Native, unit)
+    }
+
+    /// OS system time (`os:system_time/1`), unaffected by the offset
+    pub fn os_system_time(&self, unit: TimeUnit) -> i64 {
+        convert_time_unit(self.source.os_system_time_nanos(), TimeUnit::Native, unit)
+    }
+
+    /// Erlang system time as `{MegaSecs, Secs, MicroSecs}` (`erlang:timestamp/0`)
+    pub fn timestamp(&self) -> (i64, i64, i64) {
+        let micros = self.system_time(TimeUnit::Microsecond);
+        let secs = micros.div_euclid(1_000_000);
+        (
+            secs.div_euclid(1_000_000),
+            secs.rem_euclid(1_000_000),
+            micros.rem_euclid(1_000_000),
+        )
+    }
+
+    /// Finalize the time offset (`erlang:system_flag(time_offset, finalize)`)
+    ///
+    /// Only has an effect in single time warp mode while the offset is
+    /// preliminary: the offset warps once to match the current OS system
+    /// time and becomes final. Returns the state before the call.
+    pub fn finalize_time_offset(&self) -> TimeOffsetState {
+        let monotonic = self.monotonic_native();
+        let mut guard = self.offset.lock().unwrap();
+        let previous = guard.state;
+        if previous == TimeOffsetState::Preliminary {
+            let new_offset = self.source.os_system_time_nanos().saturating_sub(monotonic);
+            Self::change_offset(&mut guard, new_offset);
+            guard.state = TimeOffsetState::Final;
+        }
+        previous
+    }
+
+    /// Subscribe to time offset changes
+    ///
+    /// The equivalent of `erlang:monitor(time_offset, clock_service)`. Events
+    /// are only produced in multi time warp mode, and once when a
+    /// preliminary offset is finalized with a different value.
+    pub fn subscribe(&self) -> Receiver<TimeOffsetEvent> {
+        let (tx, rx) = channel();
+        self.offset.lock().unwrap().subscribers.push(tx);
+        rx
+    }
+
+    /// Re-check the OS system time and update a volatile offset
+    ///
+    /// Returns the change, if any. Called implicitly by [`Self::system_time`]
+    /// and [`Self::time_offset`].
+    pub fn check_time_offset(&self) -> Option<TimeOffsetEvent> {
+        let monotonic = self.monotonic_native();
+        self.recheck_offset(monotonic)
+    }
+
+    /// Update a volatile offset from the OS system time at `monotonic`
+    fn recheck_offset(&self, monotonic: i64) -> Option<TimeOffsetEvent> {
+        let mut guard = self.offset.lock().unwrap();
+        if guard.state != TimeOffsetState::Volatile {
+            return None;
+        }
+        let new_offset = self.source.os_system_time_nanos().saturating_sub(monotonic);
+        if (new_offset - guard.offset).abs() <= TIME_OFFSET_CHANGE_THRESHOLD {
+            return None;
+        }
+        Self::change_offset(&mut guard, new_offset)
+    }
+
+    /// Offset to use for a reading taken at `monotonic`
+    fn current_offset(&self, monotonic: i64) -> i64 {
+        self.recheck_offset(monotonic);
+        self.offset.lock().unwrap().offset
+    }
+
+    /// Store a new offset and notify subscribers if it differs
+    fn change_offset(state: &mut OffsetState, new_offset: i64) -> Option<TimeOffsetEvent> {
+        if state.offset == new_offset {
+            return None;
+        }
+        let event = TimeOffsetEvent {
+            old_offset: state.offset,
+            new_offset,
+        };
+        state.offset = new_offset;
+        state.subscribers.retain(|tx| tx.send(event).is_ok());
+        Some(event)
+    }
+}
+
+impl std::fmt::Debug for ErtsClock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ErtsClock")
+            .field("mode", &self.mode)
+            .field("time_offset_state", &self.time_offset_state())
+            .finish()
+    }
+}
+
+/// Runtime-wide clock
+static CLOCK: OnceLock<ErtsClock> = OnceLock::new();
+
+/// Initialize the runtime clock (`erts_init_time_sup()`)
+///
+/// The first call fixes the time warp mode for the lifetime of the process;
+/// later calls return the already initialized clock unchanged.
+pub fn init_time(mode: TimeWarpMode) -> &'static ErtsClock {
+    CLOCK.get_or_init(|| ErtsClock::new(mode))
+}
+
+/// Runtime clock, initialized in `no_time_warp` mode if [`init_time`] was not called
+pub fn get_clock() -> &'static ErtsClock {
+    CLOCK.get_or_init(|| ErtsClock::new(TimeWarpMode::NoTimeWarp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    const START: i64 = 1_700_000_000_000_000_000;
+
+    fn manual_clock(mode: TimeWarpMode) -> (ErtsClock, Arc<ManualClockSource>) {
+        let source = Arc::new(ManualClockSource::new(START));
+        (ErtsClock::with_source(mode, Box::new(source.clone())), source)
+    }
+
+    #[test]
+    fn test_time_warp_mode_names() {
+        for mode in [
+            TimeWarpMode::NoTimeWarp,
+            TimeWarpMode::SingleTimeWarp,
+            TimeWarpMode::MultiTimeWarp,
+        ] {
+            assert_eq!(TimeWarpMode::from_name(mode.name()), Some(mode));
+        }
+        assert_eq!(TimeWarpMode::from_name("warp_speed"), None);
+    }
+
+    #[test]
+    fn test_monotonic_starts_at_begin() {
+        let (clock, source) = manual_clock(TimeWarpMode::NoTimeWarp);
+        assert_eq!(clock.monotonic_time(TimeUnit::Native), MONOTONIC_BEGIN);
+        source.advance(5_000);
+        assert_eq!(clock.monotonic_time(TimeUnit::Native), MONOTONIC_BEGIN + 5_000);
+    }
+
+    #[test]
+    fn test_system_time_is_monotonic_plus_offset() {
+        let (clock, source) = manual_clock(TimeWarpMode::NoTimeWarp);
+        assert_eq!(clock.system_time(TimeUnit::Native), START);
+        source.advance(1_000_000_000);
+        assert_eq!(clock.system_time(TimeUnit::Second), START / 1_000_000_000 + 1);
+        assert_eq!(
+            clock.system_time(TimeUnit::Native),
+            clock.monotonic_time(TimeUnit::Native) + clock.time_offset(TimeUnit::Native)
+        );
+    }
+
+    #[test]
+    fn test_no_time_warp_ignores_os_time_changes() {
+        let (clock, source) = manual_clock(TimeWarpMode::NoTimeWarp);
+        assert_eq!(clock.time_offset_state(), TimeOffsetState::Final);
+        let offset = clock.time_offset(TimeUnit::Native);
+        source.warp_os_time(3_600_000_000_000);
+        assert_eq!(clock.time_offset(TimeUnit::Native), offset);
+        assert_eq!(clock.system_time(TimeUnit::Native), START);
+        assert_eq!(clock.os_system_time(TimeUnit::Native), START + 3_600_000_000_000);
+        assert_eq!(clock.finalize_time_offset(), TimeOffsetState::Final);
+    }
+
+    #[test]
+    fn test_single_time_warp_finalize() {
+        let (clock, source) = manual_clock(TimeWarpMode::SingleTimeWarp);
+        let events = clock.subscribe();
+        assert_eq!(clock.time_offset_state(), TimeOffsetState::Preliminary);
+
+        source.warp_os_time(60_000_000_000);
+        assert_eq!(clock.system_time(TimeUnit::Native), START);
+
+        assert_eq!(clock.finalize_time_offset(), TimeOffsetState::Preliminary);
+        assert_eq!(clock.time_offset_state(), TimeOffsetState::Final);
+        assert_eq!(clock.system_time(TimeUnit::Native), START + 60_000_000_000);
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.new_offset - event.old_offset, 60_000_000_000);
+
+        // A second warp is not picked up any more
+        source.warp_os_time(60_000_000_000);
+        assert_eq!(clock.finalize_time_offset(), TimeOffsetState::Final);
+        assert_eq!(clock.system_time(TimeUnit::Native), START + 60_000_000_000);
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_multi_time_warp_follows_os_time() {
+        let (clock, source) = manual_clock(TimeWarpMode::MultiTimeWarp);
+        let events = clock.subscribe();
+        assert_eq!(clock.time_offset_state(), TimeOffsetState::Volatile);
+
+        source.warp_os_time(-30_000_000_000);
+        let monotonic = clock.monotonic_time(TimeUnit::Native);
+        assert_eq!(clock.system_time(TimeUnit::Native), START - 30_000_000_000);
+        assert_eq!(clock.monotonic_time(TimeUnit::Native), monotonic);
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.new_offset - event.old_offset, -30_000_000_000);
+        assert_eq!(clock.finalize_time_offset(), TimeOffsetState::Volatile);
+    }
+
+    #[test]
+    fn test_multi_time_warp_threshold() {
+        let (clock, source) = manual_clock(TimeWarpMode::MultiTimeWarp);
+        source.warp_os_time(TIME_OFFSET_CHANGE_THRESHOLD);
+        assert_eq!(clock.check_time_offset(), None);
+        source.warp_os_time(1);
+        let event = clock.check_time_offset().unwrap();
+        assert_eq!(event.new_offset - event.old_offset, TIME_OFFSET_CHANGE_THRESHOLD + 1);
+    }
+
+    #[test]
+    fn test_timestamp() {
+        let (clock, source) = manual_clock(TimeWarpMode::NoTimeWarp);
+        source.advance(123_456_000);
+        let (mega, secs, micro) = clock.timestamp();
+        let total_secs = (START + 123_456_000) / 1_000_000_000;
+        assert_eq!(mega, total_secs / 1_000_000);
+        assert_eq!(secs, total_secs % 1_000_000);
+        assert_eq!(micro, 123_456);
+    }
+
+    #[test]
+    fn test_os_clock_is_close_to_system_time() {
+        let clock = ErtsClock::new(TimeWarpMode::NoTimeWarp);
+        let diff = clock.system_time(TimeUnit::Millisecond) - clock.os_system_time(TimeUnit::Millisecond);
+        assert!(diff.abs() < 1_000);
+    }
+
+    #[test]
+    fn test_get_clock_is_shared() {
+        let clock = get_clock();
+        assert!(std::ptr::eq(clock, get_clock()));
+        assert!(std::ptr::eq(clock, init_time(TimeWarpMode::MultiTimeWarp)));
+    }
+}
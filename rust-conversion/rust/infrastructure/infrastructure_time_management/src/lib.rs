@@ -14,6 +14,9 @@
 //!
 //! - **[`time_sup`](time_sup/index.html)**: Time supervision functionality for managing
 //!   time-related operations and ensuring time consistency across the runtime
+//! - **[`clock`](clock/index.html)**: Erlang monotonic time, system time and time offset
+//!   handling for the `no_time_warp`, `single_time_warp` and `multi_time_warp` modes
+//! - **[`time_unit`](time_unit/index.html)**: Erlang time units and conversion between them
 //!
 //! ## Architecture
 //!
@@ -26,6 +29,13 @@
 //! - [`entities_data_handling`](../../entities/entities_data_handling/index.html): Term types for time operations
 
 pub mod time_sup;
+pub mod clock;
+pub mod time_unit;
 
 pub use time_sup::TimeSup;
+pub use clock::{
+    get_clock, init_time, ClockSource, ErtsClock, ManualClockSource, OsClockSource,
+    TimeOffsetEvent, TimeOffsetState, TimeWarpMode,
+};
+pub use time_unit::{convert_time_unit, TimeUnit};
 
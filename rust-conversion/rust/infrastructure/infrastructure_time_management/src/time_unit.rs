@@ -0,0 +1,167 @@
+//! Time Unit Module
+//!
+//! Provides the Erlang time units and conversion between them, as used by
+//! `erlang:convert_time_unit/3` and by every time BIF that takes a unit
+//! argument.
+//!
+//! ## Overview
+//!
+//! A time unit is described by the number of parts it divides one second
+//! into. The named units map to:
+//!
+//! - `second` → 1
+//! - `millisecond` → 1 000
+//! - `microsecond` → 1 000 000
+//! - `nanosecond` → 1 000 000 000
+//! - `native` → [`NATIVE_PARTS_PER_SECOND`] (nanoseconds in this runtime)
+//! - `perf_counter` → same resolution as `native`
+//!
+//! Any positive integer is also accepted as a unit (parts per second).
+//!
+//! ## Examples
+//!
+//! ```rust
+//! use infrastructure_time_management::time_unit::{convert_time_unit, TimeUnit};
+//!
+//! assert_eq!(convert_time_unit(1500, TimeUnit::Millisecond, TimeUnit::Second), 1);
+//! assert_eq!(convert_time_unit(-1, TimeUnit::Millisecond, TimeUnit::Second), -1);
+//! ```
+//!
+//! Based on `erts_napi_convert_time_unit()` and time unit handling in `erl_time_sup.c`
+
+/// Resolution of the native time unit (parts per second)
+pub const NATIVE_PARTS_PER_SECOND: u64 = 1_000_000_000;
+
+/// Erlang time unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeUnit {
+    /// Seconds
+    Second,
+    /// Milliseconds
+    Millisecond,
+    /// Microseconds
+    Microsecond,
+    /// Nanoseconds
+    Nanosecond,
+    /// Native time unit of the runtime
+    Native,
+    /// Performance counter unit
+    PerfCounter,
+    /// Arbitrary resolution in parts per second (must be positive)
+    PartsPerSecond(u64),
+}
+
+impl TimeUnit {
+    /// Number of parts one second is divided into
+    pub fn parts_per_second(&self) -> u64 {
+        match self {
+            TimeUnit::Second => 1,
+            TimeUnit::Millisecond => 1_000,
+            TimeUnit::Microsecond => 1_000_000,
+            TimeUnit::Nanosecond => 1_000_000_000,
+            TimeUnit::Native | TimeUnit::PerfCounter => NATIVE_PARTS_PER_SECOND,
+            TimeUnit::PartsPerSecond(parts) => *parts,
+        }
+    }
+
+    /// Parse a time unit from its atom name
+    ///
+    /// Accepts the current names as well as the deprecated plural forms
+    /// (`seconds`, `milli_seconds`, `micro_seconds`, `nano_seconds`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "second" | "seconds" => Some(TimeUnit::Second),
+            "millisecond" | "milli_seconds" => Some(TimeUnit::Millisecond),
+            "microsecond" | "micro_seconds" => Some(TimeUnit::Microsecond),
+            "nanosecond" | "nano_seconds" => Some(TimeUnit::Nanosecond),
+            "native" => Some(TimeUnit::Native),
+            "perf_counter" => Some(TimeUnit::PerfCounter),
+            _ => None,
+        }
+    }
+
+    /// Create a unit from an integer parts-per-second value
+    ///
+    /// Returns `None` for zero or negative values, which are `badarg` in Erlang.
+    pub fn from_parts_per_second(parts: i64) -> Option<Self> {
+        if parts > 0 {
+            Some(TimeUnit::PartsPerSecond(parts as u64))
+        } else {
+            None
+        }
+    }
+}
+
+/// Convert a time value between units
+///
+/// Equivalent to `erlang:convert_time_unit/3`. The result is rounded
+/// towards negative infinity, so converting a negative value to a coarser
+/// unit never rounds towards zero. Results outside the `i64` range saturate.
+///
+/// # Arguments
+/// * `time` - Time value in `from` units
+/// * `from` - Unit of `time`
+/// * `to` - Unit to convert to
+pub fn convert_time_unit(time: i64, from: TimeUnit, to: TimeUnit) -> i64 {
+    let from_parts = from.parts_per_second() as i128;
+    let to_parts = to.parts_per_second() as i128;
+    if from_parts == to_parts {
+        return time;
+    }
+    let scaled = time as i128 * to_parts;
+    let result = scaled.div_euclid(from_parts);
+    result.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parts_per_second() {
+        assert_eq!(TimeUnit::Second.parts_per_second(), 1);
+        assert_eq!(TimeUnit::Millisecond.parts_per_second(), 1_000);
+        assert_eq!(TimeUnit::Native.parts_per_second(), NATIVE_PARTS_PER_SECOND);
+        assert_eq!(TimeUnit::PartsPerSecond(60).parts_per_second(), 60);
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(TimeUnit::from_name("millisecond"), Some(TimeUnit::Millisecond));
+        assert_eq!(TimeUnit::from_name("milli_seconds"), Some(TimeUnit::Millisecond));
+        assert_eq!(TimeUnit::from_name("native"), Some(TimeUnit::Native));
+        assert_eq!(TimeUnit::from_name("fortnight"), None);
+        assert_eq!(TimeUnit::from_parts_per_second(0), None);
+        assert_eq!(TimeUnit::from_parts_per_second(10), Some(TimeUnit::PartsPerSecond(10)));
+    }
+
+    #[test]
+    fn test_convert_time_unit() {
+        assert_eq!(convert_time_unit(2, TimeUnit::Second, TimeUnit::Millisecond), 2000);
+        assert_eq!(convert_time_unit(1999, TimeUnit::Millisecond, TimeUnit::Second), 1);
+        assert_eq!(convert_time_unit(5, TimeUnit::Native, TimeUnit::Native), 5);
+        assert_eq!(
+            convert_time_unit(1_500_000, TimeUnit::Nanosecond, TimeUnit::Millisecond),
+            1
+        );
+    }
+
+    #[test]
+    fn test_convert_time_unit_negative_floors() {
+        assert_eq!(convert_time_unit(-1, TimeUnit::Millisecond, TimeUnit::Second), -1);
+        assert_eq!(convert_time_unit(-1000, TimeUnit::Millisecond, TimeUnit::Second), -1);
+        assert_eq!(convert_time_unit(-1001, TimeUnit::Millisecond, TimeUnit::Second), -2);
+    }
+
+    #[test]
+    fn test_convert_time_unit_saturates() {
+        assert_eq!(
+            convert_time_unit(i64::MAX, TimeUnit::Second, TimeUnit::Nanosecond),
+            i64::MAX
+        );
+        assert_eq!(
+            convert_time_unit(i64::MIN, TimeUnit::Second, TimeUnit::Nanosecond),
+            i64::MIN
+        );
+    }
+}
@@ -114,3 +114,46 @@ fn test_time_sup_concurrent_access() {
     }
 }
 
+
+#[test]
+fn test_clock_multi_time_warp_integration() {
+    use infrastructure_time_management::{
+        ErtsClock, ManualClockSource, TimeOffsetState, TimeUnit, TimeWarpMode,
+    };
+    use std::sync::Arc;
+
+    let source = Arc::new(ManualClockSource::new(1_000_000_000_000));
+    let clock = ErtsClock::with_source(TimeWarpMode::MultiTimeWarp, Box::new(source.clone()));
+    let events = clock.subscribe();
+    assert_eq!(clock.time_offset_state(), TimeOffsetState::Volatile);
+
+    source.advance(2_000_000_000);
+    assert_eq!(clock.system_time(TimeUnit::Second), 1_002);
+
+    source.warp_os_time(-10_000_000_000);
+    assert_eq!(clock.system_time(TimeUnit::Second), 992);
+    let event = events.try_recv().unwrap();
+    assert_eq!(event.old_offset - event.new_offset, 10_000_000_000);
+}
+
+#[test]
+fn test_clock_matches_time_sup_integration() {
+    use infrastructure_time_management::{get_clock, TimeUnit};
+
+    let clock = get_clock();
+    let erlang_millis = clock.system_time(TimeUnit::Millisecond);
+    let os_millis = TimeSup::now_millis() as i64;
+    assert!((os_millis - erlang_millis).abs() < 1_000);
+}
+
+#[test]
+fn test_convert_time_unit_integration() {
+    use infrastructure_time_management::{convert_time_unit, TimeUnit};
+
+    let native = convert_time_unit(3, TimeUnit::Second, TimeUnit::Native);
+    assert_eq!(convert_time_unit(native, TimeUnit::Native, TimeUnit::Millisecond), 3_000);
+    assert_eq!(
+        convert_time_unit(1, TimeUnit::Second, TimeUnit::PartsPerSecond(50)),
+        50
+    );
+}
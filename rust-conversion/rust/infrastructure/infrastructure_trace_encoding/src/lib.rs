@@ -13,7 +13,8 @@
 //! ## Modules
 //!
 //! - **[`trace_codec`](trace_codec/index.html)**: High-level trace codec interface that
-//!   wraps the lower-level encoding/decoding functions from `infrastructure_code_loading`
+//!   wraps the lower-level encoding/decoding functions from `infrastructure_code_loading`,
+//!   including sequential trace tokens with term labels and unsigned serials
 //!
 //! ## Architecture
 //!
@@ -28,6 +29,6 @@
 
 pub mod trace_codec;
 
-pub use trace_codec::{TraceCodec, EncodeError, DecodeError};
+pub use trace_codec::{TraceCodec, EncodeError, DecodeError, SeqTraceToken, TraceLabel, TraceTimestamp, SEQ_TRACE_FLAGS};
 pub use infrastructure_code_loading::ErlangTrace;
 
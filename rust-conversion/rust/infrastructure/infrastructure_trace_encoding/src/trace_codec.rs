@@ -19,6 +19,16 @@
 //! - **From PID**: Process ID that generated the trace
 //! - **Previous**: Reference to previous trace in the chain
 //!
+//! ## Sequential Trace Tokens
+//!
+//! [`SeqTraceToken`] is the same tuple as the runtime sends it along with traced
+//! messages, checked on the way in and out:
+//! - **Flags** must be among [`SEQ_TRACE_FLAGS`]; [`SeqTraceToken::timestamp`] picks
+//!   the timestamp when more than one timestamp flag is set
+//! - **Label** may be any term, as allowed since OTP 21
+//! - **Serial** and **Previous** are unsigned, so serials keep their value past
+//!   `i64::MAX` and through the clock wrapping to zero
+//!
 //! ## Examples
 //!
 //! ```rust
@@ -26,11 +36,19 @@
 //! use infrastructure_code_loading::encode_pid::ErlangPid;
 //!
 //! // Encode a trace
-//! let trace = ErlangTrace { /* ... */ };
+//! let trace = ErlangTrace {
+//!     flags: 1,
+//!     label: 2,
+//!     serial: 3,
+//!     from: ErlangPid { node: "node@host".to_string(), num: 1, serial: 0, creation: 1 },
+//!     prev: 0,
+//! };
 //! let encoded = TraceCodec::encode(&trace).unwrap();
 //!
-//! // Decode a trace
+//! // Decode a trace, or the same bytes as a token
 //! let decoded = TraceCodec::decode(&encoded).unwrap();
+//! let token = TraceCodec::decode_token(&encoded).unwrap();
+//! assert_eq!(token.serial, 3);
 //! ```
 //!
 //! ## See Also
@@ -41,7 +59,26 @@
 //!
 //! Based on `encode_trace.c` and `decode_trace.c`
 
-use infrastructure_code_loading::{encode_trace, decode_trace, ErlangTrace, TraceEncodeError, TraceDecodeError};
+use entities_process::seq_trace::{
+    SEQ_TRACE_MONOTONIC_TIMESTAMP, SEQ_TRACE_NOW_TIMESTAMP, SEQ_TRACE_PRINT, SEQ_TRACE_RECEIVE,
+    SEQ_TRACE_SEND, SEQ_TRACE_STRICT_MONOTONIC_TIMESTAMP,
+};
+use infrastructure_code_loading::{encode_trace, decode_trace, ErlangTrace, ErlangPid, TraceEncodeError, TraceDecodeError};
+use infrastructure_code_loading::decode_headers::decode_tuple_header;
+use infrastructure_code_loading::decode_integers::{decode_longlong, decode_ulonglong};
+use infrastructure_code_loading::decode_pid::decode_pid;
+use infrastructure_code_loading::decode_skip::skip_term_span;
+use infrastructure_code_loading::encode_headers::encode_tuple_header;
+use infrastructure_code_loading::encode_integers::{encode_longlong, encode_ulonglong};
+use infrastructure_code_loading::encode_pid::encode_pid;
+
+/// All flags a sequential trace token may carry
+pub const SEQ_TRACE_FLAGS: u32 = SEQ_TRACE_SEND
+    | SEQ_TRACE_RECEIVE
+    | SEQ_TRACE_PRINT
+    | SEQ_TRACE_NOW_TIMESTAMP
+    | SEQ_TRACE_STRICT_MONOTONIC_TIMESTAMP
+    | SEQ_TRACE_MONOTONIC_TIMESTAMP;
 
 /// Trace codec for encoding/decoding ErlangTrace values
 pub struct TraceCodec;
@@ -146,6 +183,195 @@ impl TraceCodec {
         decode_trace(data, &mut index)
             .map_err(|e| DecodeError::from(e))
     }
+
+    /// Encode a sequential trace token to bytes using EI format
+    ///
+    /// The token is encoded as the tuple `{Flags, Label, Serial, From, Prev}`
+    /// that the runtime sends along with traced messages. Serials are
+    /// unsigned, so a serial beyond `i64::MAX` is encoded as a bignum rather
+    /// than wrapping to a negative number.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The token to encode
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(bytes)` - Encoded bytes in EI format
+    /// * `Err(EncodeError)` - Unknown flags, a label that is not one term, or an encoding error
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use infrastructure_trace_encoding::{SeqTraceToken, TraceCodec, TraceLabel};
+    /// use infrastructure_code_loading::ErlangPid;
+    ///
+    /// let token = SeqTraceToken {
+    ///     flags: 1,
+    ///     label: TraceLabel::Integer(17),
+    ///     serial: u64::MAX,
+    ///     from: ErlangPid { node: "node@host".to_string(), num: 1, serial: 0, creation: 1 },
+    ///     prev: u64::MAX - 1,
+    /// };
+    /// let encoded = TraceCodec::encode_token(&token).unwrap();
+    /// assert_eq!(TraceCodec::decode_token(&encoded).unwrap(), token);
+    /// ```
+    pub fn encode_token(token: &SeqTraceToken) -> Result<Vec<u8>, EncodeError> {
+        if token.flags & !SEQ_TRACE_FLAGS != 0 {
+            return Err(EncodeError::InvalidFlags(token.flags as i64));
+        }
+        if let TraceLabel::Term(term) = &token.label {
+            let mut end = 0;
+            if skip_term_span(term, &mut end).is_err() || end != term.len() {
+                return Err(EncodeError::InvalidLabel);
+            }
+        }
+
+        // First, calculate the size needed
+        let mut index = 0;
+        encode_token_into(&mut None, &mut index, token)?;
+
+        let mut buf = vec![0u8; index];
+        let mut index = 0;
+        encode_token_into(&mut Some(&mut buf[..]), &mut index, token)?;
+        buf.truncate(index);
+        Ok(buf)
+    }
+
+    /// Decode a sequential trace token from bytes in EI format
+    ///
+    /// An integer label is always decoded as [`TraceLabel::Integer`], and
+    /// any other label as the bytes of the term.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The encoded bytes to decode
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(token)` - Decoded token
+    /// * `Err(DecodeError)` - Unknown flags, negative serials, or a decoding error
+    pub fn decode_token(data: &[u8]) -> Result<SeqTraceToken, DecodeError> {
+        let integer = |e| DecodeError::IntegerDecodeError(format!("{:?}", e));
+        let mut index = 0;
+
+        let arity = decode_tuple_header(data, &mut index)
+            .map_err(|e| DecodeError::HeaderDecodeError(format!("{:?}", e)))?;
+        if arity != 5 {
+            return Err(DecodeError::InvalidFormat(format!("Expected arity 5, got {}", arity)));
+        }
+
+        let flags = decode_longlong(data, &mut index).map_err(integer)?;
+        if flags < 0 || flags as u64 & !(SEQ_TRACE_FLAGS as u64) != 0 {
+            return Err(DecodeError::InvalidFlags(flags));
+        }
+
+        let mut label_end = index;
+        let label = match decode_longlong(data, &mut label_end) {
+            Ok(value) => TraceLabel::Integer(value),
+            Err(_) => {
+                label_end = index;
+                let span = skip_term_span(data, &mut label_end)
+                    .map_err(|e| DecodeError::InvalidFormat(format!("Label: {:?}", e)))?;
+                TraceLabel::Term(data[span].to_vec())
+            }
+        };
+        index = label_end;
+
+        let serial = decode_ulonglong(data, &mut index).map_err(integer)?;
+        let from = decode_pid(data, &mut index)
+            .map_err(|e| DecodeError::PidDecodeError(format!("{:?}", e)))?;
+        let prev = decode_ulonglong(data, &mut index).map_err(integer)?;
+
+        Ok(SeqTraceToken {
+            flags: flags as u32,
+            label,
+            serial,
+            from,
+            prev,
+        })
+    }
+}
+
+/// Label of a sequential trace token
+///
+/// Labels were integers until OTP 21, since when they may be any term.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceLabel {
+    /// Integer label
+    Integer(i64),
+    /// Any other label, as the EI encoding of the term (without version byte)
+    Term(Vec<u8>),
+}
+
+/// Which timestamp trace messages of a token carry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceTimestamp {
+    /// No timestamp
+    None,
+    /// `erlang:now/0` timestamp
+    Now,
+    /// Strict monotonic time and unique integer
+    StrictMonotonic,
+    /// Monotonic time
+    Monotonic,
+}
+
+/// Sequential trace token, as sent along with traced messages
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeqTraceToken {
+    /// Trace flags, a combination of the `SEQ_TRACE_*` flags
+    pub flags: u32,
+    /// Label
+    pub label: TraceLabel,
+    /// Serial of the message the token was sent with
+    pub serial: u64,
+    /// Sender of the message
+    pub from: ErlangPid,
+    /// Serial of the last message the sender received
+    pub prev: u64,
+}
+
+impl SeqTraceToken {
+    /// Get the timestamp the token asks for
+    ///
+    /// When several timestamp flags are set, `now` takes precedence over
+    /// strict monotonic, which takes precedence over monotonic time.
+    pub fn timestamp(&self) -> TraceTimestamp {
+        if self.flags & SEQ_TRACE_NOW_TIMESTAMP != 0 {
+            TraceTimestamp::Now
+        } else if self.flags & SEQ_TRACE_STRICT_MONOTONIC_TIMESTAMP != 0 {
+            TraceTimestamp::StrictMonotonic
+        } else if self.flags & SEQ_TRACE_MONOTONIC_TIMESTAMP != 0 {
+            TraceTimestamp::Monotonic
+        } else {
+            TraceTimestamp::None
+        }
+    }
+}
+
+fn encode_token_into(
+    buf: &mut Option<&mut [u8]>,
+    index: &mut usize,
+    token: &SeqTraceToken,
+) -> Result<(), EncodeError> {
+    encode_tuple_header(buf, index, 5).map_err(|_| EncodeError::HeaderEncodeError)?;
+    encode_longlong(buf, index, token.flags as i64).map_err(|_| EncodeError::IntegerEncodeError)?;
+    match &token.label {
+        TraceLabel::Integer(value) => {
+            encode_longlong(buf, index, *value).map_err(|_| EncodeError::IntegerEncodeError)?
+        }
+        TraceLabel::Term(term) => {
+            if let Some(b) = buf.as_mut() {
+                b[*index..*index + term.len()].copy_from_slice(term);
+            }
+            *index += term.len();
+        }
+    }
+    encode_ulonglong(buf, index, token.serial).map_err(|_| EncodeError::IntegerEncodeError)?;
+    encode_pid(buf, index, &token.from).map_err(|e| EncodeError::PidEncodeError(format!("{:?}", e)))?;
+    encode_ulonglong(buf, index, token.prev).map_err(|_| EncodeError::IntegerEncodeError)?;
+    Ok(())
 }
 
 /// Encoding errors
@@ -157,6 +383,10 @@ pub enum EncodeError {
     IntegerEncodeError,
     /// PID encoding error
     PidEncodeError(String),
+    /// Flags outside `SEQ_TRACE_FLAGS`
+    InvalidFlags(i64),
+    /// Label that is not exactly one encoded term
+    InvalidLabel,
 }
 
 impl From<TraceEncodeError> for EncodeError {
@@ -180,6 +410,8 @@ pub enum DecodeError {
     PidDecodeError(String),
     /// Invalid format
     InvalidFormat(String),
+    /// Flags outside `SEQ_TRACE_FLAGS`
+    InvalidFlags(i64),
 }
 
 impl From<TraceDecodeError> for DecodeError {
@@ -196,7 +428,7 @@ impl From<TraceDecodeError> for DecodeError {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use infrastructure_code_loading::ErlangPid;
+    use quickcheck::{Arbitrary, Gen, QuickCheck};
 
     #[test]
     fn test_encode_decode_roundtrip() {
@@ -437,5 +669,177 @@ mod tests {
         assert!(debug_str3.contains("PidDecodeError"));
         assert!(debug_str4.contains("InvalidFormat"));
     }
-}
 
+    fn token(flags: u32, label: TraceLabel, serial: u64, prev: u64) -> SeqTraceToken {
+        SeqTraceToken {
+            flags,
+            label,
+            serial,
+            from: ErlangPid {
+                node: "node@host".to_string(),
+                num: 123,
+                serial: 456,
+                creation: 1,
+            },
+            prev,
+        }
+    }
+
+    #[test]
+    fn test_token_serial_wraparound() {
+        // Serials past i64::MAX, and just after the clock wraps
+        for (serial, prev) in [(u64::MAX, u64::MAX - 1), (0, u64::MAX), (1 << 63, i64::MAX as u64)] {
+            let token = token(SEQ_TRACE_SEND, TraceLabel::Integer(1), serial, prev);
+            let encoded = TraceCodec::encode_token(&token).unwrap();
+            assert_eq!(TraceCodec::decode_token(&encoded).unwrap(), token);
+        }
+    }
+
+    #[test]
+    fn test_token_negative_serial() {
+        let trace = ErlangTrace {
+            flags: 0,
+            label: 0,
+            serial: -1,
+            from: token(0, TraceLabel::Integer(0), 0, 0).from,
+            prev: 0,
+        };
+        let encoded = TraceCodec::encode(&trace).unwrap();
+        assert!(matches!(
+            TraceCodec::decode_token(&encoded),
+            Err(DecodeError::IntegerDecodeError(_))
+        ));
+    }
+
+    #[test]
+    fn test_token_term_label() {
+        // {label, <<1, 2>>}
+        let label = vec![104, 2, 119, 5, b'l', b'a', b'b', b'e', b'l', 109, 0, 0, 0, 2, 1, 2];
+        let token = token(SEQ_TRACE_PRINT, TraceLabel::Term(label), 7, 6);
+        let encoded = TraceCodec::encode_token(&token).unwrap();
+        assert_eq!(TraceCodec::decode_token(&encoded).unwrap(), token);
+    }
+
+    #[test]
+    fn test_token_invalid_label() {
+        // Truncated term, and two terms
+        for label in [vec![104, 2, 97, 1], vec![97, 1, 97, 2]] {
+            let token = token(0, TraceLabel::Term(label), 1, 0);
+            assert_eq!(TraceCodec::encode_token(&token), Err(EncodeError::InvalidLabel));
+        }
+    }
+
+    #[test]
+    fn test_token_flag_validation() {
+        let bad = token(SEQ_TRACE_SEND | 1 << 6, TraceLabel::Integer(0), 1, 0);
+        assert_eq!(TraceCodec::encode_token(&bad), Err(EncodeError::InvalidFlags(0x41)));
+
+        for flags in [64, -1] {
+            let trace = ErlangTrace {
+                flags,
+                label: 0,
+                serial: 0,
+                from: bad.from.clone(),
+                prev: 0,
+            };
+            let encoded = TraceCodec::encode(&trace).unwrap();
+            assert_eq!(TraceCodec::decode_token(&encoded), Err(DecodeError::InvalidFlags(flags)));
+        }
+    }
+
+    #[test]
+    fn test_token_from_erlang_trace() {
+        // Tokens with integer labels and non-negative fields are plain traces
+        let trace = ErlangTrace {
+            flags: (SEQ_TRACE_SEND | SEQ_TRACE_RECEIVE) as i64,
+            label: -5,
+            serial: 3,
+            from: token(0, TraceLabel::Integer(0), 0, 0).from,
+            prev: 2,
+        };
+        let encoded = TraceCodec::encode(&trace).unwrap();
+        let decoded = TraceCodec::decode_token(&encoded).unwrap();
+        assert_eq!(decoded, token(3, TraceLabel::Integer(-5), 3, 2));
+        assert_eq!(TraceCodec::encode_token(&decoded).unwrap(), encoded);
+    }
+
+    #[test]
+    fn test_token_timestamp_precedence() {
+        let cases = [
+            (0, TraceTimestamp::None),
+            (SEQ_TRACE_MONOTONIC_TIMESTAMP, TraceTimestamp::Monotonic),
+            (SEQ_TRACE_MONOTONIC_TIMESTAMP | SEQ_TRACE_STRICT_MONOTONIC_TIMESTAMP, TraceTimestamp::StrictMonotonic),
+            (SEQ_TRACE_FLAGS, TraceTimestamp::Now),
+        ];
+        for (flags, timestamp) in cases {
+            assert_eq!(token(flags, TraceLabel::Integer(0), 0, 0).timestamp(), timestamp);
+        }
+    }
+
+    /// Encode a random term that is not an integer, nesting up to `depth`
+    fn arbitrary_term(g: &mut Gen, depth: usize, out: &mut Vec<u8>) {
+        let kinds: &[u8] = if depth == 0 { &[0, 1, 2] } else { &[0, 1, 2, 3, 4] };
+        match g.choose(kinds).unwrap() {
+            0 => {
+                let name: Vec<u8> = (0..u8::arbitrary(g) % 8).map(|i| b'a' + i).collect();
+                out.extend_from_slice(&[119, name.len() as u8]);
+                out.extend_from_slice(&name);
+            }
+            1 => {
+                let data = Vec::<u8>::arbitrary(g);
+                out.push(109);
+                out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+                out.extend_from_slice(&data);
+            }
+            2 => out.push(106),
+            3 => {
+                let arity = u8::arbitrary(g) % 4;
+                out.extend_from_slice(&[104, arity]);
+                for _ in 0..arity {
+                    arbitrary_term(g, depth - 1, out);
+                }
+            }
+            _ => {
+                let length = u8::arbitrary(g) % 4 + 1;
+                out.push(108);
+                out.extend_from_slice(&(length as u32).to_be_bytes());
+                for _ in 0..=length {
+                    arbitrary_term(g, depth - 1, out);
+                }
+            }
+        }
+    }
+
+    impl Arbitrary for SeqTraceToken {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let label = if bool::arbitrary(g) {
+                TraceLabel::Integer(i64::arbitrary(g))
+            } else {
+                let mut term = Vec::new();
+                arbitrary_term(g, 3, &mut term);
+                TraceLabel::Term(term)
+            };
+            SeqTraceToken {
+                flags: u32::arbitrary(g) & SEQ_TRACE_FLAGS,
+                label,
+                serial: u64::arbitrary(g),
+                from: ErlangPid {
+                    node: "node@host".to_string(),
+                    num: u32::arbitrary(g),
+                    serial: u32::arbitrary(g),
+                    creation: u32::arbitrary(g),
+                },
+                prev: u64::arbitrary(g),
+            }
+        }
+    }
+
+    #[test]
+    fn prop_token_roundtrip() {
+        fn roundtrip(token: SeqTraceToken) -> bool {
+            let encoded = TraceCodec::encode_token(&token).unwrap();
+            TraceCodec::decode_token(&encoded) == Ok(token)
+        }
+        QuickCheck::new().tests(1000).quickcheck(roundtrip as fn(SeqTraceToken) -> bool);
+    }
+}
@@ -7,10 +7,14 @@
 //! ## Overview
 //!
 //! The compression module provides:
-//! - **Zlib compression**: Using the `flate2` crate with `miniz_oxide` backend (pure safe Rust, equivalent to C zlib)
+//! - **Zlib compression**: Using the `flate2` crate with the `zlib-rs` backend (a pure Rust port of C zlib)
 //! - **Zstd compression**: Using the `zstd` crate (provides both compression and decompression)
 //! - **Chunked interfaces**: For streaming compression/decompression (used by term_to_binary)
 //! - **One-shot interfaces**: For simple compress/uncompress operations
+//! - **zlib streams**: [`ZlibDeflater`] and [`ZlibInflater`] back the `zlib` module, with
+//!   flush modes, preset dictionaries, zlib/raw/gzip formats selected by window bits, and
+//!   output in bounded chunks
+//! - **gzip files**: [`GzipFile`] reads and writes files opened with the `compressed` mode
 //!
 //! ## Usage
 //!
@@ -58,10 +62,12 @@
 //!
 //! Based on `erts/emulator/beam/erl_zlib.c`
 
-use flate2::Compression;
-use flate2::write::DeflateEncoder;
-use flate2::read::DeflateDecoder;
-use std::io::{Write, Read};
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
+use flate2::write::{DeflateEncoder, GzEncoder};
+use flate2::read::{DeflateDecoder, MultiGzDecoder};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write, Read};
+use std::path::Path;
 
 /// Compression level enumeration matching zlib levels
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -112,6 +118,8 @@ pub enum CompressionError {
     MemoryError,
     /// Stream error
     StreamError,
+    /// Inflating needs the preset dictionary with this Adler-32 checksum
+    NeedDictionary(u32),
     /// Other error
     Other(String),
 }
@@ -123,6 +131,7 @@ impl std::fmt::Display for CompressionError {
             CompressionError::DataError => write!(f, "Data error"),
             CompressionError::MemoryError => write!(f, "Memory allocation error"),
             CompressionError::StreamError => write!(f, "Stream error"),
+            CompressionError::NeedDictionary(adler) => write!(f, "Need dictionary {}", adler),
             CompressionError::Other(msg) => write!(f, "Other error: {}", msg),
         }
     }
@@ -371,6 +380,422 @@ pub fn uncompress(
     Ok(())
 }
 
+/// Flush mode of a zlib stream operation (`zlib:deflate/3`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZlibFlush {
+    /// Buffer output as the compressor sees fit (`none`)
+    None,
+    /// Emit all pending output, aligned to a byte boundary (`sync`)
+    Sync,
+    /// Like `Sync`, and reset the compression state so that decompression
+    /// can restart from this point (`full`)
+    Full,
+    /// Emit all pending output and end the stream (`finish`)
+    Finish,
+}
+
+impl From<ZlibFlush> for FlushCompress {
+    fn from(flush: ZlibFlush) -> Self {
+        match flush {
+            ZlibFlush::None => FlushCompress::None,
+            ZlibFlush::Sync => FlushCompress::Sync,
+            ZlibFlush::Full => FlushCompress::Full,
+            ZlibFlush::Finish => FlushCompress::Finish,
+        }
+    }
+}
+
+/// Header and trailer format of a zlib stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZlibFormat {
+    /// zlib header and Adler-32 trailer (window bits 8..15)
+    Zlib,
+    /// Raw deflate data (window bits -8..-15)
+    Raw,
+    /// gzip header and CRC-32 trailer (window bits 24..31)
+    Gzip,
+    /// zlib or gzip, detected from the header when inflating (window bits
+    /// 40..47)
+    Auto,
+}
+
+/// Format and window size of a zlib stream
+///
+/// Corresponds to the `WindowBits` argument of `zlib:deflateInit/6` and
+/// `zlib:inflateInit/2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZlibWindow {
+    /// Header and trailer format
+    pub format: ZlibFormat,
+    /// Base two logarithm of the window size (8..15)
+    pub bits: u8,
+}
+
+impl Default for ZlibWindow {
+    fn default() -> Self {
+        Self {
+            format: ZlibFormat::Zlib,
+            bits: 15,
+        }
+    }
+}
+
+impl ZlibWindow {
+    /// Decode a zlib `windowBits` value
+    ///
+    /// `8..=15` selects the zlib format, `-15..=-8` raw deflate, `24..=31`
+    /// gzip and `40..=47` automatic detection.
+    pub fn from_window_bits(window_bits: i32) -> CompressionResult<Self> {
+        let (format, bits) = match window_bits {
+            8..=15 => (ZlibFormat::Zlib, window_bits),
+            -15..=-8 => (ZlibFormat::Raw, -window_bits),
+            24..=31 => (ZlibFormat::Gzip, window_bits - 16),
+            40..=47 => (ZlibFormat::Auto, window_bits - 32),
+            _ => return Err(CompressionError::StreamError),
+        };
+        Ok(Self { format, bits: bits as u8 })
+    }
+
+    /// Window bits accepted by the zlib backend, which does not support a
+    /// 256 byte window and uses 512 bytes instead, as zlib itself does
+    fn backend_bits(&self) -> u8 {
+        self.bits.clamp(9, 15)
+    }
+}
+
+/// Streaming zlib compressor (`zlib:deflateInit/6`, `zlib:deflate/3`)
+///
+/// Input that does not fit in the output of one call stays buffered, so
+/// output can be produced in bounded chunks.
+pub struct ZlibDeflater {
+    compress: Compress,
+    /// Input not yet consumed by the compressor
+    pending: Vec<u8>,
+    /// Flush mode of the operation in progress
+    flush: ZlibFlush,
+    /// Whether the stream has ended
+    finished: bool,
+}
+
+impl ZlibDeflater {
+    /// Create a compressor
+    ///
+    /// # Arguments
+    /// * `level` - Compression level, 0 (none) to 9 (best)
+    /// * `window` - Format and window size; `Auto` is only valid for inflating
+    pub fn new(level: u32, window: ZlibWindow) -> CompressionResult<Self> {
+        if level > 9 {
+            return Err(CompressionError::StreamError);
+        }
+        let level = Compression::new(level);
+        let compress = match window.format {
+            ZlibFormat::Zlib => Compress::new_with_window_bits(level, true, window.backend_bits()),
+            ZlibFormat::Raw => Compress::new_with_window_bits(level, false, window.backend_bits()),
+            ZlibFormat::Gzip => Compress::new_gzip(level, window.backend_bits()),
+            ZlibFormat::Auto => return Err(CompressionError::StreamError),
+        };
+        Ok(Self {
+            compress,
+            pending: Vec::new(),
+            flush: ZlibFlush::None,
+            finished: false,
+        })
+    }
+
+    /// Set a preset dictionary (`zlib:deflateSetDictionary/2`)
+    ///
+    /// Must be called before any data is compressed. Not supported for gzip.
+    ///
+    /// # Returns
+    /// Adler-32 checksum of the dictionary, which the inflating side
+    /// receives when it needs the dictionary
+    pub fn set_dictionary(&mut self, dictionary: &[u8]) -> CompressionResult<u32> {
+        self.compress.set_dictionary(dictionary).map_err(|_| CompressionError::StreamError)
+    }
+
+    /// Compress `input`, returning all output produced by `flush`
+    pub fn deflate(&mut self, input: &[u8], flush: ZlibFlush) -> CompressionResult<Vec<u8>> {
+        let mut output = Vec::new();
+        let mut input = input;
+        loop {
+            let (result, chunk) = self.deflate_chunk(input, flush, DEFAULT_CHUNK_SIZE)?;
+            output.extend_from_slice(&chunk);
+            if result == ChunkResult::Done {
+                return Ok(output);
+            }
+            input = &[];
+        }
+    }
+
+    /// Compress `input`, returning at most `max_output` bytes
+    ///
+    /// # Returns
+    /// * `(ChunkResult::More, output)` - More output is pending; call again
+    ///   with empty input and the same flush mode to get it
+    /// * `(ChunkResult::Done, output)` - The flush is complete
+    pub fn deflate_chunk(
+        &mut self,
+        input: &[u8],
+        flush: ZlibFlush,
+        max_output: usize,
+    ) -> CompressionResult<(ChunkResult, Vec<u8>)> {
+        if self.finished {
+            return if input.is_empty() { Ok((ChunkResult::Done, Vec::new())) } else { Err(CompressionError::StreamError) };
+        }
+        self.pending.extend_from_slice(input);
+        self.flush = flush;
+        let mut output = Vec::with_capacity(max_output);
+        loop {
+            let (before_in, before_out) = (self.compress.total_in(), self.compress.total_out());
+            let status = self
+                .compress
+                .compress_vec(&self.pending, &mut output, self.flush.into())
+                .map_err(|_| CompressionError::StreamError)?;
+            let consumed = (self.compress.total_in() - before_in) as usize;
+            let produced = self.compress.total_out() - before_out;
+            self.pending.drain(..consumed);
+
+            if status == Status::StreamEnd {
+                self.finished = true;
+                return Ok((ChunkResult::Done, output));
+            }
+            if output.len() == max_output {
+                return Ok((ChunkResult::More, output));
+            }
+            // With room left in the output, a non-final flush is complete
+            // once all input is consumed
+            let flushed = self.pending.is_empty() && self.flush != ZlibFlush::Finish;
+            if flushed || (consumed == 0 && produced == 0) {
+                return Ok((ChunkResult::Done, output));
+            }
+        }
+    }
+
+    /// Reset the compressor for a new stream (`zlib:deflateReset/1`)
+    pub fn reset(&mut self) {
+        self.compress.reset();
+        self.pending.clear();
+        self.finished = false;
+    }
+
+    /// Whether the stream has ended
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+/// Streaming zlib decompressor (`zlib:inflateInit/2`, `zlib:inflate/2`,
+/// `zlib:safeInflate/2`)
+///
+/// Input that cannot be decompressed into the output of one call stays
+/// buffered, so output can be produced in bounded chunks.
+pub struct ZlibInflater {
+    /// Decompressor, created once the format is known
+    decompress: Option<Decompress>,
+    window: ZlibWindow,
+    /// Input not yet consumed by the decompressor
+    pending: Vec<u8>,
+    /// Whether the stream has ended
+    finished: bool,
+}
+
+impl ZlibInflater {
+    /// Create a decompressor
+    pub fn new(window: ZlibWindow) -> Self {
+        let mut inflater = Self {
+            decompress: None,
+            window,
+            pending: Vec::new(),
+            finished: false,
+        };
+        inflater.decompress = inflater.create(window.format);
+        inflater
+    }
+
+    fn create(&self, format: ZlibFormat) -> Option<Decompress> {
+        let bits = self.window.backend_bits();
+        match format {
+            ZlibFormat::Zlib => Some(Decompress::new_with_window_bits(true, bits)),
+            ZlibFormat::Raw => Some(Decompress::new_with_window_bits(false, bits)),
+            ZlibFormat::Gzip => Some(Decompress::new_gzip(bits)),
+            ZlibFormat::Auto => None,
+        }
+    }
+
+    /// Set a preset dictionary (`zlib:inflateSetDictionary/2`)
+    ///
+    /// For the zlib format, call this when inflating fails with
+    /// [`CompressionError::NeedDictionary`], then continue with empty input.
+    /// For raw streams, call it before inflating.
+    pub fn set_dictionary(&mut self, dictionary: &[u8]) -> CompressionResult<u32> {
+        let decompress = self.decompress.as_mut().ok_or(CompressionError::StreamError)?;
+        decompress.set_dictionary(dictionary).map_err(|_| CompressionError::DataError)
+    }
+
+    /// Decompress `input`, returning all output
+    pub fn inflate(&mut self, input: &[u8]) -> CompressionResult<Vec<u8>> {
+        let mut output = Vec::new();
+        let mut input = input;
+        loop {
+            let (result, chunk) = self.inflate_chunk(input, DEFAULT_CHUNK_SIZE)?;
+            output.extend_from_slice(&chunk);
+            if result == ChunkResult::Done {
+                return Ok(output);
+            }
+            input = &[];
+        }
+    }
+
+    /// Decompress `input`, returning at most `max_output` bytes
+    ///
+    /// # Returns
+    /// * `(ChunkResult::More, output)` - More output may be pending; call
+    ///   again with empty input to get it
+    /// * `(ChunkResult::Done, output)` - All input is consumed, or the
+    ///   stream has ended
+    /// * `Err(CompressionError::NeedDictionary(adler))` - A preset
+    ///   dictionary with the given checksum is needed
+    pub fn inflate_chunk(&mut self, input: &[u8], max_output: usize) -> CompressionResult<(ChunkResult, Vec<u8>)> {
+        self.pending.extend_from_slice(input);
+        let mut output = Vec::with_capacity(max_output);
+        if self.decompress.is_none() {
+            match self.pending.first() {
+                // A zlib header never starts with the first gzip magic byte
+                Some(0x1f) => self.decompress = self.create(ZlibFormat::Gzip),
+                Some(_) => self.decompress = self.create(ZlibFormat::Zlib),
+                None => return Ok((ChunkResult::Done, output)),
+            }
+        }
+        let decompress = self.decompress.as_mut().ok_or(CompressionError::StreamError)?;
+        loop {
+            if self.finished {
+                return Ok((ChunkResult::Done, output));
+            }
+            let (before_in, before_out) = (decompress.total_in(), decompress.total_out());
+            let result = decompress.decompress_vec(&self.pending, &mut output, FlushDecompress::None);
+            let consumed = (decompress.total_in() - before_in) as usize;
+            let produced = decompress.total_out() - before_out;
+            self.pending.drain(..consumed);
+
+            let status = match result {
+                Ok(status) => status,
+                Err(error) => {
+                    return Err(match error.needs_dictionary() {
+                        Some(adler) => CompressionError::NeedDictionary(adler),
+                        None => CompressionError::DataError,
+                    })
+                }
+            };
+            if status == Status::StreamEnd {
+                self.finished = true;
+                return Ok((ChunkResult::Done, output));
+            }
+            if output.len() == max_output {
+                return Ok((ChunkResult::More, output));
+            }
+            if consumed == 0 && produced == 0 {
+                return Ok((ChunkResult::Done, output));
+            }
+        }
+    }
+
+    /// Reset the decompressor for a new stream (`zlib:inflateReset/1`)
+    pub fn reset(&mut self) {
+        self.decompress = self.create(self.window.format);
+        self.pending.clear();
+        self.finished = false;
+    }
+
+    /// Whether the end of the stream has been reached
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+/// Output chunk size of the unbounded stream operations
+const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Compress data in the gzip format (`zlib:gzip/1`)
+pub fn gzip(data: &[u8], level: CompressionLevel) -> CompressionResult<Vec<u8>> {
+    let mut deflater = ZlibDeflater::new(level as u32, ZlibWindow { format: ZlibFormat::Gzip, bits: 15 })?;
+    deflater.deflate(data, ZlibFlush::Finish)
+}
+
+/// Decompress gzip data (`zlib:gunzip/1`)
+///
+/// Concatenated gzip members are decompressed one after another.
+pub fn gunzip(data: &[u8]) -> CompressionResult<Vec<u8>> {
+    let mut output = Vec::new();
+    MultiGzDecoder::new(data)
+        .read_to_end(&mut output)
+        .map_err(|_| CompressionError::DataError)?;
+    Ok(output)
+}
+
+/// File opened with the `compressed` mode of `file:open/2`
+///
+/// Reading decompresses gzip files and reads other files unchanged.
+/// Writing produces a gzip file, which is complete once
+/// [`GzipFile::finish`] has been called.
+pub enum GzipFile {
+    /// File read through a gzip decoder
+    GzipReader(MultiGzDecoder<BufReader<File>>),
+    /// File that is not gzip compressed
+    PlainReader(BufReader<File>),
+    /// File written through a gzip encoder
+    Writer(GzEncoder<File>),
+}
+
+impl GzipFile {
+    /// Open a file for reading
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let gzip = reader.fill_buf()?.starts_with(&[0x1f, 0x8b]);
+        Ok(if gzip { GzipFile::GzipReader(MultiGzDecoder::new(reader)) } else { GzipFile::PlainReader(reader) })
+    }
+
+    /// Create or truncate a file for writing
+    pub fn create<P: AsRef<Path>>(path: P, level: CompressionLevel) -> io::Result<Self> {
+        Ok(GzipFile::Writer(GzEncoder::new(File::create(path)?, level.into())))
+    }
+
+    /// Write the gzip trailer and flush the file
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            GzipFile::Writer(encoder) => encoder.finish()?.sync_all(),
+            GzipFile::GzipReader(_) | GzipFile::PlainReader(_) => Ok(()),
+        }
+    }
+}
+
+impl Read for GzipFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            GzipFile::GzipReader(decoder) => decoder.read(buf),
+            GzipFile::PlainReader(reader) => reader.read(buf),
+            GzipFile::Writer(_) => Err(io::Error::new(io::ErrorKind::Unsupported, "file not opened for reading")),
+        }
+    }
+}
+
+impl Write for GzipFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            GzipFile::Writer(encoder) => encoder.write(buf),
+            GzipFile::GzipReader(_) | GzipFile::PlainReader(_) => {
+                Err(io::Error::new(io::ErrorKind::Unsupported, "file not opened for writing"))
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            GzipFile::Writer(encoder) => encoder.flush(),
+            GzipFile::GzipReader(_) | GzipFile::PlainReader(_) => Ok(()),
+        }
+    }
+}
+
 /// Compress data using zstd (via zstd crate)
 ///
 /// # Arguments
@@ -1151,5 +1576,178 @@ mod tests {
             assert_eq!(&decompressed, data);
         }
     }
-}
 
+    fn sample_data() -> Vec<u8> {
+        (0..20_000u32).flat_map(|i| format!("line {} of {}\n", i % 97, i % 13).into_bytes()).collect()
+    }
+
+    #[test]
+    fn test_zlib_window_bits() {
+        let window = |bits| ZlibWindow::from_window_bits(bits).unwrap();
+        assert_eq!(window(15), ZlibWindow::default());
+        assert_eq!(window(-9), ZlibWindow { format: ZlibFormat::Raw, bits: 9 });
+        assert_eq!(window(31), ZlibWindow { format: ZlibFormat::Gzip, bits: 15 });
+        assert_eq!(window(47), ZlibWindow { format: ZlibFormat::Auto, bits: 15 });
+        assert_eq!(ZlibWindow::from_window_bits(16), Err(CompressionError::StreamError));
+        assert_eq!(ZlibWindow::from_window_bits(7), Err(CompressionError::StreamError));
+        assert!(ZlibDeflater::new(6, window(47)).is_err());
+        assert!(ZlibDeflater::new(10, window(15)).is_err());
+    }
+
+    #[test]
+    fn test_zlib_stream_formats() {
+        let data = sample_data();
+        for (bits, header) in [(15, &[0x78][..]), (8, &[][..]), (-15, &[][..]), (31, &[0x1f, 0x8b][..])] {
+            let window = ZlibWindow::from_window_bits(bits).unwrap();
+            let mut deflater = ZlibDeflater::new(6, window).unwrap();
+            let compressed = deflater.deflate(&data, ZlibFlush::Finish).unwrap();
+            assert!(deflater.is_finished());
+            assert!(compressed.starts_with(header), "window bits {}", bits);
+            assert!(compressed.len() < data.len() / 4);
+
+            let mut inflater = ZlibInflater::new(window);
+            assert_eq!(inflater.inflate(&compressed).unwrap(), data);
+            assert!(inflater.is_finished());
+            if bits > 0 {
+                // zlib and gzip headers are detected automatically
+                let mut inflater = ZlibInflater::new(ZlibWindow::from_window_bits(47).unwrap());
+                assert_eq!(inflater.inflate(&compressed).unwrap(), data);
+            }
+        }
+    }
+
+    #[test]
+    fn test_zlib_flush_modes() {
+        let raw = || ZlibWindow::from_window_bits(-15).unwrap();
+        let mut deflater = ZlibDeflater::new(6, raw()).unwrap();
+        let first = deflater.deflate(b"first part, ", ZlibFlush::Sync).unwrap();
+        assert!(first.ends_with(&[0x00, 0x00, 0xff, 0xff]));
+        let mut inflater = ZlibInflater::new(raw());
+        assert_eq!(inflater.inflate(&first).unwrap(), b"first part, ");
+
+        // After a full flush, decompression can restart at the flush point
+        let second = deflater.deflate(b"second part, ", ZlibFlush::Full).unwrap();
+        let third = deflater.deflate(b"third part", ZlibFlush::Finish).unwrap();
+        let mut restart = ZlibInflater::new(raw());
+        assert_eq!(restart.inflate(&third).unwrap(), b"third part");
+        assert_eq!(inflater.inflate(&[second, third].concat()).unwrap(), b"second part, third part");
+
+        // Without a flush, output may be held back until the stream ends
+        let mut deflater = ZlibDeflater::new(6, ZlibWindow::default()).unwrap();
+        let held = deflater.deflate(b"buffered", ZlibFlush::None).unwrap();
+        let rest = deflater.deflate(b"", ZlibFlush::Finish).unwrap();
+        let mut inflater = ZlibInflater::new(ZlibWindow::default());
+        assert_eq!(inflater.inflate(&[held, rest].concat()).unwrap(), b"buffered");
+        assert_eq!(deflater.deflate(b"more", ZlibFlush::None), Err(CompressionError::StreamError));
+        deflater.reset();
+        assert!(!deflater.deflate(b"more", ZlibFlush::Finish).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_zlib_preset_dictionary() {
+        let dictionary = b"a dictionary of common words: hello world stream";
+        let mut deflater = ZlibDeflater::new(9, ZlibWindow::default()).unwrap();
+        let adler = deflater.set_dictionary(dictionary).unwrap();
+        let compressed = deflater.deflate(b"hello world, hello stream", ZlibFlush::Finish).unwrap();
+
+        let mut inflater = ZlibInflater::new(ZlibWindow::default());
+        assert_eq!(inflater.inflate(&compressed), Err(CompressionError::NeedDictionary(adler)));
+        inflater.set_dictionary(dictionary).unwrap();
+        assert_eq!(inflater.inflate(&[]).unwrap(), b"hello world, hello stream");
+
+        // Raw streams take the dictionary before inflating
+        let raw = ZlibWindow::from_window_bits(-15).unwrap();
+        let mut deflater = ZlibDeflater::new(9, raw).unwrap();
+        deflater.set_dictionary(dictionary).unwrap();
+        let compressed = deflater.deflate(b"hello world", ZlibFlush::Finish).unwrap();
+        let mut inflater = ZlibInflater::new(raw);
+        inflater.set_dictionary(dictionary).unwrap();
+        assert_eq!(inflater.inflate(&compressed).unwrap(), b"hello world");
+    }
+
+    #[test]
+    fn test_zlib_bounded_chunks() {
+        let data = sample_data();
+        let mut deflater = ZlibDeflater::new(6, ZlibWindow::default()).unwrap();
+        let mut compressed = Vec::new();
+        let mut input = &data[..];
+        loop {
+            let (result, chunk) = deflater.deflate_chunk(input, ZlibFlush::Finish, 256).unwrap();
+            assert!(chunk.len() <= 256);
+            compressed.extend_from_slice(&chunk);
+            input = &[];
+            if result == ChunkResult::Done {
+                break;
+            }
+        }
+
+        // Like zlib:safeInflate/2, output comes in chunks of bounded size
+        let mut inflater = ZlibInflater::new(ZlibWindow::default());
+        let mut output = Vec::new();
+        let mut chunks = 0;
+        let mut input = &compressed[..];
+        loop {
+            let (result, chunk) = inflater.inflate_chunk(input, 1000).unwrap();
+            assert!(chunk.len() <= 1000);
+            output.extend_from_slice(&chunk);
+            chunks += 1;
+            input = &[];
+            if result == ChunkResult::Done {
+                break;
+            }
+        }
+        assert_eq!(output, data);
+        assert!(chunks > data.len() / 1000);
+        assert_eq!(inflater.inflate_chunk(&[], 1000).unwrap(), (ChunkResult::Done, Vec::new()));
+    }
+
+    #[test]
+    fn test_zlib_inflate_errors() {
+        let mut inflater = ZlibInflater::new(ZlibWindow::default());
+        assert_eq!(inflater.inflate(b"not compressed data"), Err(CompressionError::DataError));
+        let mut inflater = ZlibInflater::new(ZlibWindow::from_window_bits(47).unwrap());
+        assert_eq!(inflater.inflate(&[]).unwrap(), Vec::<u8>::new());
+        assert_eq!(inflater.set_dictionary(b"dict"), Err(CompressionError::StreamError));
+    }
+
+    #[test]
+    fn test_gzip_gunzip() {
+        let data = sample_data();
+        let first = gzip(&data, CompressionLevel::BestCompression).unwrap();
+        let second = gzip(b"second member", CompressionLevel::BestSpeed).unwrap();
+        assert_eq!(&first[..2], &[0x1f, 0x8b]);
+        assert_eq!(gunzip(&first).unwrap(), data);
+        assert_eq!(gunzip(&[first, second].concat()).unwrap(), [&data[..], b"second member"].concat());
+        assert_eq!(gunzip(b"\x1f\x8bgarbage"), Err(CompressionError::DataError));
+    }
+
+    #[test]
+    fn test_gzip_file() {
+        let dir = std::env::temp_dir();
+        let compressed = dir.join(format!("test_gzip_file_{}.gz", std::process::id()));
+        let plain = dir.join(format!("test_gzip_file_{}.txt", std::process::id()));
+        let data = sample_data();
+
+        let mut file = GzipFile::create(&compressed, CompressionLevel::Default).unwrap();
+        file.write_all(&data[..1000]).unwrap();
+        file.write_all(&data[1000..]).unwrap();
+        assert!(file.read(&mut [0u8; 4]).is_err());
+        file.finish().unwrap();
+        assert_eq!(gunzip(&std::fs::read(&compressed).unwrap()).unwrap(), data);
+
+        let mut output = Vec::new();
+        GzipFile::open(&compressed).unwrap().read_to_end(&mut output).unwrap();
+        assert_eq!(output, data);
+
+        // Files that are not compressed are read unchanged
+        std::fs::write(&plain, b"plain text").unwrap();
+        let mut file = GzipFile::open(&plain).unwrap();
+        let mut output = Vec::new();
+        file.read_to_end(&mut output).unwrap();
+        assert_eq!(output, b"plain text");
+        assert!(file.write(b"x").is_err());
+
+        let _ = std::fs::remove_file(&compressed);
+        let _ = std::fs::remove_file(&plain);
+    }
+}
@@ -0,0 +1,384 @@
+//! Distribution Entry Table Module
+//!
+//! Provides the distribution entries of the nodes this node talks to and
+//! their output queues. Based on erl_node_tables.c and the output queue
+//! handling in dist.c.
+//!
+//! Data sent to another node is appended to the output queue of its
+//! distribution entry, from which the distribution controller takes it.
+//! An entry becomes busy when the queued bytes reach the busy limit
+//! (`dist_buf_busy_limit`). Processes sending to a busy entry are suspended
+//! on it and resumed once the controller drains the queue below the limit,
+//! or when the connection goes down.
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ *
+ * Creation productivity increased for code in this file by using AALang and GAB.
+ * See https://github.com/yenrab/AALang-Gab
+ */
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+
+use entities_process::ProcessId;
+
+/// Default output queue size at which a distribution entry becomes busy
+///
+/// Based on the default of the `+zdbbl` emulator flag (1024 kilobytes)
+pub const DEFAULT_DIST_BUF_BUSY_LIMIT: usize = 1024 * 1024;
+
+/// Connection state of a distribution entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistConnectionState {
+    /// Not connected (ERTS_DE_STATE_IDLE)
+    Idle,
+    /// Connection set up in progress; sends are queued (ERTS_DE_STATE_PENDING)
+    Pending,
+    /// Connected (ERTS_DE_STATE_CONNECTED)
+    Connected,
+}
+
+/// Mutable distribution entry state
+struct DistEntryState {
+    /// Connection state
+    connection: DistConnectionState,
+    /// Output queue of encoded distribution messages
+    queue: VecDeque<Vec<u8>>,
+    /// Bytes in the output queue
+    queue_size: usize,
+    /// Queue size at which the entry becomes busy
+    busy_limit: usize,
+    /// Processes suspended on the busy entry
+    suspended: Vec<ProcessId>,
+    /// Processes to resume since the entry stopped being busy
+    resumed: Vec<ProcessId>,
+}
+
+impl DistEntryState {
+    fn is_busy(&self) -> bool {
+        self.queue_size >= self.busy_limit
+    }
+
+    /// Move suspended processes to the resume list once the entry is not busy
+    fn release_if_drained(&mut self) {
+        if !self.is_busy() {
+            let suspended = std::mem::take(&mut self.suspended);
+            self.resumed.extend(suspended);
+        }
+    }
+}
+
+/// Distribution entry of a remote node
+///
+/// Based on DistEntry in erl_node_tables.h
+pub struct DistEntry {
+    /// Connection identifier
+    id: u64,
+    /// Node name
+    node: String,
+    /// Queue, connection and busy state
+    state: Mutex<DistEntryState>,
+}
+
+impl DistEntry {
+    fn new(id: u64, node: &str, busy_limit: usize) -> Self {
+        Self {
+            id,
+            node: node.to_string(),
+            state: Mutex::new(DistEntryState {
+                connection: DistConnectionState::Idle,
+                queue: VecDeque::new(),
+                queue_size: 0,
+                busy_limit,
+                suspended: Vec::new(),
+                resumed: Vec::new(),
+            }),
+        }
+    }
+
+    /// Get the connection identifier
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Get the node name
+    pub fn node(&self) -> &str {
+        &self.node
+    }
+
+    /// Get the connection state
+    pub fn connection_state(&self) -> DistConnectionState {
+        self.state.lock().unwrap().connection
+    }
+
+    /// Check if the node is connected
+    pub fn is_connected(&self) -> bool {
+        self.connection_state() == DistConnectionState::Connected
+    }
+
+    /// Start setting up a connection; sends are queued until it is up
+    ///
+    /// # Returns
+    /// `true` if the entry was idle and a connection should be requested
+    pub fn set_pending(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.connection != DistConnectionState::Idle {
+            return false;
+        }
+        state.connection = DistConnectionState::Pending;
+        true
+    }
+
+    /// Mark the connection as up
+    pub fn set_connected(&self) {
+        self.state.lock().unwrap().connection = DistConnectionState::Connected;
+    }
+
+    /// Take the connection down
+    ///
+    /// Queued data is dropped and processes suspended on the entry are
+    /// moved to the resume list.
+    pub fn disconnect(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.connection = DistConnectionState::Idle;
+        state.queue.clear();
+        state.queue_size = 0;
+        state.release_if_drained();
+    }
+
+    /// Append encoded data to the output queue
+    pub fn enqueue(&self, data: Vec<u8>) {
+        let mut state = self.state.lock().unwrap();
+        state.queue_size += data.len();
+        state.queue.push_back(data);
+    }
+
+    /// Take the oldest data from the output queue, as the distribution
+    /// controller does when it writes to the connection
+    ///
+    /// Processes suspended on the entry are moved to the resume list once
+    /// the queue drains below the busy limit.
+    pub fn dequeue(&self) -> Option<Vec<u8>> {
+        let mut state = self.state.lock().unwrap();
+        let data = state.queue.pop_front()?;
+        state.queue_size -= data.len();
+        state.release_if_drained();
+        Some(data)
+    }
+
+    /// Get the number of bytes in the output queue
+    pub fn queue_size(&self) -> usize {
+        self.state.lock().unwrap().queue_size
+    }
+
+    /// Check if the entry is busy
+    pub fn is_busy(&self) -> bool {
+        self.state.lock().unwrap().is_busy()
+    }
+
+    /// Set the output queue size at which the entry becomes busy
+    pub fn set_busy_limit(&self, limit: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.busy_limit = limit;
+        state.release_if_drained();
+    }
+
+    /// Suspend a sending process on the entry
+    ///
+    /// # Returns
+    /// * `true` - The entry is busy and the process was suspended
+    /// * `false` - The entry is not busy; the process may send
+    pub fn suspend_sender(&self, pid: ProcessId) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if !state.is_busy() {
+            return false;
+        }
+        if !state.suspended.contains(&pid) {
+            state.suspended.push(pid);
+        }
+        true
+    }
+
+    /// Get the processes currently suspended on the entry
+    pub fn suspended_senders(&self) -> Vec<ProcessId> {
+        self.state.lock().unwrap().suspended.clone()
+    }
+
+    /// Take the processes to resume since the entry stopped being busy
+    pub fn take_resumed(&self) -> Vec<ProcessId> {
+        std::mem::take(&mut self.state.lock().unwrap().resumed)
+    }
+}
+
+impl std::fmt::Debug for DistEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.lock().unwrap();
+        f.debug_struct("DistEntry")
+            .field("id", &self.id)
+            .field("node", &self.node)
+            .field("connection", &state.connection)
+            .field("queue_size", &state.queue_size)
+            .field("busy_limit", &state.busy_limit)
+            .finish()
+    }
+}
+
+/// Table of distribution entries, keyed by node name
+///
+/// Based on the dist table in erl_node_tables.c
+pub struct DistTable {
+    /// Map from node name to entry
+    entries: RwLock<HashMap<String, Arc<DistEntry>>>,
+    /// Next connection identifier
+    next_id: AtomicU64,
+    /// Busy limit given to new entries
+    busy_limit: Mutex<usize>,
+}
+
+impl DistTable {
+    /// Create an empty table
+    pub fn new() -> Self {
+        Self {
+            entries: RwLock::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+            busy_limit: Mutex::new(DEFAULT_DIST_BUF_BUSY_LIMIT),
+        }
+    }
+
+    /// Look up the entry of a node
+    pub fn lookup(&self, node: &str) -> Option<Arc<DistEntry>> {
+        self.entries.read().unwrap().get(node).cloned()
+    }
+
+    /// Look up a node's entry by connection identifier
+    pub fn lookup_id(&self, id: u64) -> Option<Arc<DistEntry>> {
+        self.entries.read().unwrap().values().find(|entry| entry.id == id).cloned()
+    }
+
+    /// Look up the entry of a node, creating an idle entry if there is none
+    pub fn find_or_insert(&self, node: &str) -> Arc<DistEntry> {
+        if let Some(entry) = self.lookup(node) {
+            return entry;
+        }
+        let busy_limit = self.busy_limit();
+        let mut entries = self.entries.write().unwrap();
+        Arc::clone(entries.entry(node.to_string()).or_insert_with(|| {
+            let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+            Arc::new(DistEntry::new(id, node, busy_limit))
+        }))
+    }
+
+    /// Remove the entry of a node, taking its connection down
+    ///
+    /// # Returns
+    /// Processes to resume that were suspended on the entry
+    pub fn remove(&self, node: &str) -> Vec<ProcessId> {
+        let Some(entry) = self.entries.write().unwrap().remove(node) else {
+            return Vec::new();
+        };
+        entry.disconnect();
+        entry.take_resumed()
+    }
+
+    /// Get the busy limit given to new entries (`dist_buf_busy_limit`)
+    pub fn busy_limit(&self) -> usize {
+        *self.busy_limit.lock().unwrap()
+    }
+
+    /// Set the busy limit given to new entries
+    pub fn set_busy_limit(&self, limit: usize) {
+        *self.busy_limit.lock().unwrap() = limit;
+    }
+
+    /// Get the number of entries
+    pub fn len(&self) -> usize {
+        self.entries.read().unwrap().len()
+    }
+
+    /// Check if the table has no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.read().unwrap().is_empty()
+    }
+}
+
+impl Default for DistTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Global distribution entry table instance
+static GLOBAL_DIST_TABLE: OnceLock<DistTable> = OnceLock::new();
+
+/// Get the global distribution entry table
+pub fn get_global_dist_table() -> &'static DistTable {
+    GLOBAL_DIST_TABLE.get_or_init(DistTable::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_busy_until_drained() {
+        let table = DistTable::new();
+        table.set_busy_limit(8);
+        let entry = table.find_or_insert("a@host");
+        entry.set_connected();
+        entry.enqueue(vec![0; 5]);
+        assert!(!entry.suspend_sender(1));
+        entry.enqueue(vec![0; 5]);
+        assert!(entry.is_busy());
+        assert!(entry.suspend_sender(1));
+        assert!(entry.suspend_sender(2));
+        assert_eq!(entry.suspended_senders(), vec![1, 2]);
+
+        assert_eq!(entry.dequeue().map(|data| data.len()), Some(5));
+        assert_eq!(entry.queue_size(), 5);
+        assert_eq!(entry.take_resumed(), vec![1, 2]);
+        assert!(entry.take_resumed().is_empty());
+    }
+
+    #[test]
+    fn test_connection_states() {
+        let table = DistTable::new();
+        let entry = table.find_or_insert("b@host");
+        assert!(Arc::ptr_eq(&entry, &table.find_or_insert("b@host")));
+        assert_eq!(table.lookup_id(entry.id()).map(|e| e.node().to_string()), Some("b@host".to_string()));
+        assert_eq!(entry.connection_state(), DistConnectionState::Idle);
+        assert!(entry.set_pending());
+        assert!(!entry.set_pending());
+        entry.set_connected();
+        assert!(entry.is_connected());
+
+        entry.set_busy_limit(1);
+        entry.enqueue(vec![1, 2]);
+        assert!(entry.suspend_sender(9));
+        assert_eq!(table.remove("b@host"), vec![9]);
+        assert_eq!(entry.connection_state(), DistConnectionState::Idle);
+        assert_eq!(entry.queue_size(), 0);
+        assert!(table.is_empty());
+    }
+}
@@ -6,6 +6,10 @@
 
 use super::erl_parse::{Expr, BinOp, UnOp};
 use entities_process::{Eterm, Process, ProcessId};
+use entities_process::term_tags::{
+    make_atom, make_small, NIL, TAG_IMMED1_MASK, TAG_IMMED1_SIZE, TAG_IMMED1_SMALL, TAG_IMMED2_ATOM,
+    TAG_IMMED2_MASK, TAG_IMMED2_SIZE,
+};
 use entities_data_handling::term_hashing::Term;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -756,25 +760,14 @@ fn list_length(term: &Term) -> Result<i64, EvalError> {
 
 /// Convert Term to Eterm
 ///
-/// This is a simplified conversion. In a full implementation, Eterm would use
-/// proper tagging for different types. For now, we use a simple encoding.
+/// Immediates use the shared tag scheme. Floats have no heap to live on
+/// here, so they are passed as their raw bits.
 fn term_to_eterm(term: &Term) -> Result<Eterm, EvalError> {
     match term {
-        Term::Small(i) => {
-            // Simple encoding: small integers as-is (would need proper tagging in full impl)
-            Ok(*i as u64)
-        }
-        Term::Float(f) => {
-            // Float encoding (would need proper boxed representation in full impl)
-            Ok(f.to_bits())
-        }
-        Term::Atom(index) => {
-            // Atom encoding (would need proper tagging in full impl)
-            Ok((*index as u64) << 32 | 0x0B) // Simplified: atom tag + index
-        }
-        Term::Nil => {
-            Ok(0x3F) // Nil tag (simplified)
-        }
+        Term::Small(i) => Ok(make_small(*i)),
+        Term::Float(f) => Ok(f.to_bits()),
+        Term::Atom(index) => Ok(make_atom(*index as usize)),
+        Term::Nil => Ok(NIL),
         _ => {
             Err(EvalError::TypeError(format!("Cannot convert term to Eterm: {:?}", term)))
         }
@@ -783,34 +776,19 @@ fn term_to_eterm(term: &Term) -> Result<Eterm, EvalError> {
 
 /// Convert Eterm to Term
 ///
-/// This is a simplified conversion. In a full implementation, we would decode
-/// proper Eterm tags. For now, we use a simple decoding.
+/// Decodes the immediates of the shared tag scheme; any other word is read
+/// back as the raw bits of a float.
 fn eterm_to_term(eterm: Eterm) -> Result<Term, EvalError> {
-    // Check for nil
-    if eterm == 0x3F {
+    if eterm == NIL {
         return Ok(Term::Nil);
     }
-    
-    // Check for atom (simplified tag check)
-    if (eterm & 0x3F) == 0x0B {
-        let atom_index = ((eterm >> 32) & 0xFFFFFFFF) as u32;
-        return Ok(Term::Atom(atom_index));
+    if eterm & TAG_IMMED2_MASK == TAG_IMMED2_ATOM {
+        return Ok(Term::Atom((eterm >> TAG_IMMED2_SIZE) as u32));
     }
-    
-    // Check if it's a small integer (simplified - would need proper tag check)
-    // For now, assume values < 2^31 are small integers
-    if eterm < (1u64 << 31) {
-        return Ok(Term::Small(eterm as i64));
-    }
-    
-    // Try to decode as float
-    let f = f64::from_bits(eterm);
-    if f.is_finite() {
-        return Ok(Term::Float(f));
+    if eterm & TAG_IMMED1_MASK == TAG_IMMED1_SMALL {
+        return Ok(Term::Small((eterm as i64) >> TAG_IMMED1_SIZE));
     }
-    
-    // Default: treat as small integer
-    Ok(Term::Small(eterm as i64))
+    Ok(Term::Float(f64::from_bits(eterm)))
 }
 
 /// Match a pattern against a value
@@ -1030,33 +1008,33 @@ mod tests {
     
     #[test]
     fn test_eval_integer() {
-        let expr = Expr::Integer(42);
+        let e = Expr::Integer(42);
         let bindings = new_bindings();
-        let (result, _) = expr(&expr, &bindings).unwrap();
+        let (result, _) = expr(&e, &bindings).unwrap();
         assert_eq!(result, Term::Small(42));
     }
     
     #[test]
     fn test_eval_add() {
-        let expr = Expr::BinOp {
+        let e = Expr::BinOp {
             op: BinOp::Add,
             left: Box::new(Expr::Integer(2)),
             right: Box::new(Expr::Integer(3)),
         };
         let bindings = new_bindings();
-        let (result, _) = expr(&expr, &bindings).unwrap();
+        let (result, _) = expr(&e, &bindings).unwrap();
         assert_eq!(result, Term::Small(5));
     }
     
     #[test]
     fn test_eval_mul() {
-        let expr = Expr::BinOp {
+        let e = Expr::BinOp {
             op: BinOp::Mul,
             left: Box::new(Expr::Integer(2)),
             right: Box::new(Expr::Integer(3)),
         };
         let bindings = new_bindings();
-        let (result, _) = expr(&expr, &bindings).unwrap();
+        let (result, _) = expr(&e, &bindings).unwrap();
         assert_eq!(result, Term::Small(6));
     }
 }
@@ -117,6 +117,18 @@ impl<'a> TermDecoder<'a> {
                 let atom = String::from_utf8_lossy(&buf).to_string();
                 Ok(ErlangTerm::Atom(atom))
             }
+            // Small atom (115), UTF-8 atom (118) and small UTF-8 atom (119)
+            115 | 118 | 119 => {
+                let len = if tag == 118 {
+                    self.read_u16_be()? as usize
+                } else {
+                    self.read_u8()? as usize
+                };
+                let mut buf = vec![0u8; len];
+                self.cursor.read_exact(&mut buf)?;
+                let atom = String::from_utf8_lossy(&buf).to_string();
+                Ok(ErlangTerm::Atom(atom))
+            }
             // Small tuple (arity < 256)
             104 => {
                 let arity = self.read_u8()? as usize;
@@ -261,6 +273,16 @@ mod tests {
         assert_eq!(term, ErlangTerm::Atom("test".to_string()));
     }
 
+    #[test]
+    fn test_decode_utf8_atoms() {
+        // SMALL_ATOM_UTF8_EXT, as written by term_to_binary/1 since OTP 26
+        let data = vec![131, 119, 4, b'i', b'n', b'i', b't'];
+        assert_eq!(decode_term(&data).unwrap(), ErlangTerm::Atom("init".to_string()));
+        // ATOM_UTF8_EXT with a two-byte character
+        let data = vec![131, 118, 0, 2, 0xc3, 0xa5];
+        assert_eq!(decode_term(&data).unwrap(), ErlangTerm::Atom("å".to_string()));
+    }
+
     #[test]
     fn test_decode_small_integer() {
         // Encode small integer 42: [131, 97, 42]
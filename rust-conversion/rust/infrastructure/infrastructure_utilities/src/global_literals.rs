@@ -4,125 +4,393 @@
 //! Based on erl_global_literals.c
 //!
 //! Global literals are used to store Erlang terms that are never modified or
-//! deleted. They are commonly-used constants at compile or run-time.
+//! deleted. They are commonly-used constants at compile or run-time, such as
+//! the empty tuple, the empty map, the table of common atoms and the error
+//! tuples returned by the BIFs.
+//!
+//! The literals are built once by [`init_global_literals`], called from
+//! `erl_init`, and live in chunks of a super carrier of their own. A chunk is
+//! only writable while a literal is copied into it and is read-only
+//! otherwise, so no process can modify a literal another process holds.
+//! Allocation takes the area lock; looking up a predefined literal with
+//! [`erts_get_global_literal`] takes no lock, so all schedulers can read the
+//! literals concurrently once they are initialized.
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 2024-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ */
+
+use std::sync::{Mutex, OnceLock};
 
-use std::sync::Mutex;
+use entities_data_handling::AtomEncoding;
+use entities_process::term_tags::{
+    make_arityval, make_atom, MAP_SUBTAG, TAG_PRIMARY_BOXED, TAG_PRIMARY_MASK,
+};
+use entities_process::Eterm;
+use entities_system_integration_common::{MmapError, SuperCarrier};
 
-/// Global literal area
+use crate::atom_table::get_global_atom_table;
+
+/// Size of a global literal chunk in bytes
 ///
-/// Stores a chunk of global literals. Based on ErtsLiteralArea in erl_global_literals.c
-struct GlobalLiteralArea {
-    /// Literal area data (using Vec for safety)
-    data: Vec<u8>,
-    /// Size of the literal area in bytes
+/// In C: GLOBAL_LITERAL_INITIAL_SIZE
+pub const GLOBAL_LITERAL_CHUNK_SIZE: usize = 1 << 16;
+
+/// Size of the range reserved for global literals
+pub const GLOBAL_LITERAL_AREA_SIZE: usize = 16 * GLOBAL_LITERAL_CHUNK_SIZE;
+
+/// Atoms of the common atom table, in the order of its elements
+pub const COMMON_ATOMS: [&str; 10] = [
+    "true",
+    "false",
+    "ok",
+    "error",
+    "undefined",
+    "badarg",
+    "nil",
+    "infinity",
+    "normal",
+    "timeout",
+];
+
+/// Predefined global literals
+///
+/// In C: ErtsLiteralTag (ERTS_LIT_*)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GlobalLiteral {
+    /// `{}`
+    EmptyTuple,
+    /// `#{}`
+    EmptyMap,
+    /// Tuple of the [`COMMON_ATOMS`]
+    CommonAtoms,
+    /// `{error, badarg}`
+    ErrorBadarg,
+    /// `{error, enomem}`
+    ErrorEnomem,
+    /// `{error, notsup}`
+    ErrorNotsup,
+    /// `{error, timeout}`
+    ErrorTimeout,
+}
+
+impl GlobalLiteral {
+    /// All predefined literals
+    pub const ALL: [GlobalLiteral; 7] = [
+        GlobalLiteral::EmptyTuple,
+        GlobalLiteral::EmptyMap,
+        GlobalLiteral::CommonAtoms,
+        GlobalLiteral::ErrorBadarg,
+        GlobalLiteral::ErrorEnomem,
+        GlobalLiteral::ErrorNotsup,
+        GlobalLiteral::ErrorTimeout,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Global literal errors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlobalLiteralError {
+    /// The literal has no words
+    Empty,
+    /// No memory left in the global literal area
+    Exhausted,
+    /// The global literal area could not be reserved or protected
+    Mmap(MmapError),
+}
+
+impl std::fmt::Display for GlobalLiteralError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GlobalLiteralError::Empty => write!(f, "global literal has no words"),
+            GlobalLiteralError::Exhausted => write!(f, "global literal area exhausted"),
+            GlobalLiteralError::Mmap(e) => write!(f, "global literal area: {:?}", e),
+        }
+    }
+}
+
+impl std::error::Error for GlobalLiteralError {}
+
+impl From<MmapError> for GlobalLiteralError {
+    fn from(error: MmapError) -> Self {
+        match error {
+            MmapError::Exhausted => GlobalLiteralError::Exhausted,
+            other => GlobalLiteralError::Mmap(other),
+        }
+    }
+}
+
+/// Chunk literals are currently allocated from
+///
+/// Based on ErtsLiteralArea in erl_global_literals.c
+struct GlobalLiteralChunk {
+    /// Start of the chunk
+    start: usize,
+    /// Size of the chunk in bytes
     size: usize,
+    /// Bytes allocated from the chunk
+    used: usize,
 }
 
 /// Global literals manager
 ///
-/// Manages global literal areas and provides allocation/registration functions.
+/// Manages global literal chunks and provides allocation of global literals.
 /// Based on the global literal system in erl_global_literals.c
 pub struct GlobalLiterals {
-    /// Lock for thread-safe access
-    lock: Mutex<()>,
-    /// Literal areas
-    areas: Mutex<Vec<GlobalLiteralArea>>,
-    /// Current allocation offset
-    current_offset: Mutex<usize>,
-    /// Current area size remaining
-    current_size: Mutex<usize>,
+    /// Range the chunks are mapped from
+    area: SuperCarrier,
+    /// Chunk being allocated from; the lock serializes allocation
+    current: Mutex<Option<GlobalLiteralChunk>>,
+    /// Predefined literals, set once by [`init_global_literals`]
+    predefined: OnceLock<[Eterm; GlobalLiteral::ALL.len()]>,
 }
 
 impl GlobalLiterals {
-    /// Create a new global literals manager
-    fn new() -> Self {
-        Self {
-            lock: Mutex::new(()),
-            areas: Mutex::new(Vec::new()),
-            current_offset: Mutex::new(0),
-            current_size: Mutex::new(0),
-        }
+    /// Create a global literals manager reserving `size` bytes
+    ///
+    /// # Errors
+    /// * `GlobalLiteralError::Mmap` - If the range could not be reserved
+    pub fn new(size: usize) -> Result<Self, GlobalLiteralError> {
+        Ok(Self {
+            area: SuperCarrier::reserve(size)?,
+            current: Mutex::new(None),
+            predefined: OnceLock::new(),
+        })
     }
 
-    /// Expand the global literal area
+    /// Allocate a global literal holding a copy of `words`
     ///
-    /// Allocates a new chunk for global literals.
-    fn expand_area(&self, size: usize) -> Result<(), String> {
-        let _guard = self.lock.lock().unwrap();
-        
-        // Allocate new area using Vec for safety
-        let area = GlobalLiteralArea {
-            data: vec![0u8; size],
-            size,
-        };
-        
-        let mut areas = self.areas.lock().unwrap();
-        areas.push(area);
-        
-        let mut current_offset = self.current_offset.lock().unwrap();
-        let mut current_size = self.current_size.lock().unwrap();
-        *current_offset = 0;
-        *current_size = size;
-        
-        Ok(())
+    /// The words are copied into the current chunk, which is read-only again
+    /// before this returns. A literal larger than a chunk gets a chunk of its
+    /// own.
+    ///
+    /// # Returns
+    /// The boxed term pointing at the first word
+    ///
+    /// # Errors
+    /// * `GlobalLiteralError::Empty` - If `words` is empty
+    /// * `GlobalLiteralError::Exhausted` - If no chunk can be mapped
+    /// * `GlobalLiteralError::Mmap` - If the chunk could not be protected
+    pub fn allocate(&self, words: &[Eterm]) -> Result<Eterm, GlobalLiteralError> {
+        if words.is_empty() {
+            return Err(GlobalLiteralError::Empty);
+        }
+        let bytes = std::mem::size_of_val(words);
+        let mut current = self.current.lock().unwrap();
+        if current.as_ref().is_none_or(|chunk| chunk.size - chunk.used < bytes) {
+            // In C: expand_shared_global_literal_area(size)
+            let size = bytes.max(GLOBAL_LITERAL_CHUNK_SIZE);
+            let start = self.area.map(size)?;
+            self.area.protect(start, size)?;
+            *current = Some(GlobalLiteralChunk {
+                start: start as usize,
+                size,
+                used: 0,
+            });
+        }
+        let chunk = current.as_mut().expect("a chunk was just mapped");
+        let chunk_start = chunk.start as *mut u8;
+        let literal = chunk.start + chunk.used;
+
+        self.area.unprotect(chunk_start, chunk.size)?;
+        // The chunk is writable and has at least `bytes` left at `literal`
+        unsafe {
+            std::ptr::copy_nonoverlapping(words.as_ptr(), literal as *mut Eterm, words.len());
+        }
+        self.area.protect(chunk_start, chunk.size)?;
+        chunk.used += bytes;
+        Ok(literal as Eterm | TAG_PRIMARY_BOXED)
     }
 
-    /// Initialize empty tuple
+    /// Get a predefined literal
     ///
-    /// Creates the empty tuple global literal.
-    fn init_empty_tuple(&self) -> Result<(), String> {
-        // In a full implementation, this would:
-        // 1. Allocate space for the tuple
-        // 2. Create the tuple term
-        // 3. Register it as a global literal
-        // For now, this is a placeholder
+    /// # Returns
+    /// The literal, or `None` before the literals are initialized
+    pub fn get(&self, literal: GlobalLiteral) -> Option<Eterm> {
+        self.predefined.get().map(|terms| terms[literal.index()])
+    }
+
+    /// Check if a term points into the global literal area
+    pub fn contains(&self, term: Eterm) -> bool {
+        term & TAG_PRIMARY_MASK == TAG_PRIMARY_BOXED
+            && self.area.contains((term & !TAG_PRIMARY_MASK) as *const u8)
+    }
+
+    /// Build the predefined literals; does nothing if they already are
+    fn init_predefined(&self) -> Result<(), GlobalLiteralError> {
+        if self.predefined.get().is_some() {
+            return Ok(());
+        }
+        let mut terms = [0; GlobalLiteral::ALL.len()];
+        for literal in GlobalLiteral::ALL {
+            let words = match literal {
+                GlobalLiteral::EmptyTuple => vec![make_arityval(0)],
+                // Flatmap header, size and keys
+                GlobalLiteral::EmptyMap => vec![MAP_SUBTAG, 0, terms[GlobalLiteral::EmptyTuple.index()]],
+                GlobalLiteral::CommonAtoms => std::iter::once(make_arityval(COMMON_ATOMS.len()))
+                    .chain(COMMON_ATOMS.iter().map(|name| atom_term(name)))
+                    .collect(),
+                GlobalLiteral::ErrorBadarg => error_tuple("badarg"),
+                GlobalLiteral::ErrorEnomem => error_tuple("enomem"),
+                GlobalLiteral::ErrorNotsup => error_tuple("notsup"),
+                GlobalLiteral::ErrorTimeout => error_tuple("timeout"),
+            };
+            terms[literal.index()] = self.allocate(&words)?;
+        }
+        // A concurrent initialization may have won; its literals are used
+        let _ = self.predefined.set(terms);
         Ok(())
     }
 }
 
+/// Atom term of a literal atom (`make_atom`)
+fn atom_term(name: &str) -> Eterm {
+    let index = get_global_atom_table()
+        .put_index(name.as_bytes(), AtomEncoding::SevenBitAscii, false)
+        .expect("literal atoms are valid atoms");
+    make_atom(index)
+}
+
+/// Words of `{error, Reason}`
+fn error_tuple(reason: &str) -> Vec<Eterm> {
+    vec![make_arityval(2), atom_term("error"), atom_term(reason)]
+}
+
 /// Global literals instance (singleton)
-static GLOBAL_LITERALS: std::sync::OnceLock<GlobalLiterals> = std::sync::OnceLock::new();
+static GLOBAL_LITERALS: OnceLock<Option<GlobalLiterals>> = OnceLock::new();
 
 /// Get the global literals instance
-fn get_global_literals() -> &'static GlobalLiterals {
-    GLOBAL_LITERALS.get_or_init(GlobalLiterals::new)
+///
+/// Returns `None` if the global literal area could not be reserved.
+pub fn get_global_literals() -> Option<&'static GlobalLiterals> {
+    GLOBAL_LITERALS
+        .get_or_init(|| GlobalLiterals::new(GLOBAL_LITERAL_AREA_SIZE).ok())
+        .as_ref()
 }
 
 /// Initialize global literals
 ///
 /// Based on `init_global_literals()` from erl_global_literals.c
 ///
-/// Initializes the global literals system, including:
-/// - Setting up the global literal lock
-/// - Expanding the shared global literal area
-/// - Initializing the empty tuple
+/// Reserves the global literal area and builds the predefined literals.
+/// Calling it again after a successful initialization does nothing.
 ///
 /// # Returns
 /// * `Ok(())` - Initialization successful
 /// * `Err(String)` - Initialization error
 pub fn init_global_literals() -> Result<(), String> {
-    let literals = get_global_literals();
-    
-    // Expand shared global literal area
-    // In C: expand_shared_global_literal_area(GLOBAL_LITERAL_INITIAL_SIZE)
-    // GLOBAL_LITERAL_INITIAL_SIZE is typically 1<<16 (65536 bytes)
-    const GLOBAL_LITERAL_INITIAL_SIZE: usize = 1 << 16;
-    literals.expand_area(GLOBAL_LITERAL_INITIAL_SIZE)?;
-    
-    // Initialize empty tuple
-    literals.init_empty_tuple()?;
-    
-    Ok(())
+    let literals = get_global_literals()
+        .ok_or_else(|| "could not reserve the global literal area".to_string())?;
+    literals.init_predefined().map_err(|e| e.to_string())
+}
+
+/// Get a predefined global literal
+///
+/// # Returns
+/// The literal, or `None` before [`init_global_literals`] has run
+pub fn erts_get_global_literal(literal: GlobalLiteral) -> Option<Eterm> {
+    get_global_literals()?.get(literal)
+}
+
+/// Allocate a global literal holding a copy of `words`
+///
+/// In C: erts_global_literal_allocate() and erts_global_literal_register()
+///
+/// # Errors
+/// See [`GlobalLiterals::allocate`]
+pub fn erts_global_literal_allocate(words: &[Eterm]) -> Result<Eterm, GlobalLiteralError> {
+    get_global_literals()
+        .ok_or(GlobalLiteralError::Exhausted)?
+        .allocate(words)
+}
+
+/// Check if a term is a global literal
+pub fn is_global_literal(term: Eterm) -> bool {
+    get_global_literals().is_some_and(|literals| literals.contains(term))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use entities_process::term_tags::NIL;
+
+    /// Words of the literal a boxed term points at
+    fn words_of(term: Eterm, len: usize) -> Vec<Eterm> {
+        let ptr = (term & !TAG_PRIMARY_MASK) as *const Eterm;
+        unsafe { std::slice::from_raw_parts(ptr, len) }.to_vec()
+    }
 
     #[test]
     fn test_init_global_literals() {
         let result = init_global_literals();
         assert!(result.is_ok());
+        assert!(init_global_literals().is_ok());
+
+        let empty_tuple = erts_get_global_literal(GlobalLiteral::EmptyTuple).unwrap();
+        assert!(is_global_literal(empty_tuple));
+        assert_eq!(words_of(empty_tuple, 1), vec![make_arityval(0)]);
+        let empty_map = erts_get_global_literal(GlobalLiteral::EmptyMap).unwrap();
+        assert_eq!(words_of(empty_map, 3), vec![MAP_SUBTAG, 0, empty_tuple]);
+        let badarg = erts_get_global_literal(GlobalLiteral::ErrorBadarg).unwrap();
+        assert_eq!(
+            words_of(badarg, 3),
+            vec![make_arityval(2), atom_term("error"), atom_term("badarg")]
+        );
+        let atoms = erts_get_global_literal(GlobalLiteral::CommonAtoms).unwrap();
+        assert_eq!(words_of(atoms, 1)[0], make_arityval(COMMON_ATOMS.len()));
+        assert!(!is_global_literal(atom_term("ok")));
     }
-}
 
+    #[test]
+    fn test_literals_are_read_only_and_shared() {
+        let literals = GlobalLiterals::new(4 * GLOBAL_LITERAL_CHUNK_SIZE).unwrap();
+        assert_eq!(literals.get(GlobalLiteral::EmptyTuple), None);
+        assert_eq!(literals.allocate(&[]), Err(GlobalLiteralError::Empty));
+
+        let small = literals.allocate(&[make_arityval(1), NIL]).unwrap();
+        let large = literals.allocate(&vec![0; GLOBAL_LITERAL_CHUNK_SIZE / 8 + 1]).unwrap();
+        assert!(literals.contains(small) && literals.contains(large));
+        assert!(literals.area.is_protected((small & !TAG_PRIMARY_MASK) as *const u8));
+        assert_eq!(words_of(small, 2), vec![make_arityval(1), NIL]);
+        assert_eq!(
+            literals.allocate(&vec![0; GLOBAL_LITERAL_CHUNK_SIZE / 4]),
+            Err(GlobalLiteralError::Exhausted)
+        );
+
+        // Every scheduler reads the same literals
+        literals.init_predefined().unwrap();
+        let literals = std::sync::Arc::new(literals);
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let literals = std::sync::Arc::clone(&literals);
+                std::thread::spawn(move || literals.get(GlobalLiteral::ErrorEnomem).unwrap())
+            })
+            .collect();
+        let terms: Vec<Eterm> = threads.into_iter().map(|thread| thread.join().unwrap()).collect();
+        assert!(terms.iter().all(|term| *term == terms[0]));
+        assert_eq!(words_of(terms[0], 3)[2], atom_term("enomem"));
+    }
+}
@@ -31,6 +31,23 @@
 //!   (based on `erl_ptab.c`). Note: This is NOT pure data storage; it includes process
 //!   management operations.
 //!
+//! - **[`statistics`](statistics/index.html)**: Runtime counters behind `erlang:statistics/1`
+//! - **[`memory`](memory/index.html)**: Sharded counters behind the `erlang:memory/0,1` categories
+//!   (reductions, run queue lengths, port I/O, garbage collection and run time)
+//!
+//! - **[`dist_table`](dist_table/index.html)**: Distribution entries of remote nodes with
+//!   their output queues and busy state (based on `erl_node_tables.c`)
+//!
+//! - **[`signals`](signals/index.html)**: Dispositions of the signals controlled by
+//!   `os:set_signal/2` and the handled signals pending for `erl_signal_server`
+//!
+//! - **[`thr_progress`](thr_progress/index.html)**: Thread progress tracking, deferred
+//!   operations and scheduler blocking for safe memory reclamation (based on `erl_thr_progress.c`)
+//!
+//! - **[`global_literals`](global_literals/index.html)**: Immutable literals shared by all
+//!   processes (empty tuple, empty map, common atoms, error tuples) in read-only memory
+//!   (based on `erl_global_literals.c`)
+//!
 //! ## Architecture
 //!
 //! This crate is a large module with many utility functions. It depends only on the Entities
@@ -45,6 +62,11 @@ pub mod common;
 pub mod helpers;
 pub mod compression;
 pub mod process_table;
+pub mod statistics;
+pub mod memory;
+pub mod dist_table;
+pub mod thr_progress;
+pub mod signals;
 pub mod atom_table;
 pub mod global_literals;
 pub mod erlang_term_decoder;
@@ -54,10 +76,15 @@ pub mod erl_eval;
 
 pub use common::{CommonUtils, FormatUtils, MathUtils, RationalUtils, MiscUtils, HashUtils, ArrayUtils, ThreadingUtils, TimeUtils, PathUtils, UtilityError};
 pub use helpers::HelperFunctions;
-pub use compression::{CompressionLevel, CompressionError, CompressionResult, ChunkResult, DeflateStream, InflateStream, compress2, uncompress, zstd_compress, zstd_decompress};
-pub use process_table::{ProcessTable, get_global_process_table, ProcessTableError};
+pub use compression::{CompressionLevel, CompressionError, CompressionResult, ChunkResult, DeflateStream, InflateStream, compress2, uncompress, zstd_compress, zstd_decompress, ZlibDeflater, ZlibInflater, ZlibFlush, ZlibFormat, ZlibWindow, GzipFile, gzip, gunzip};
+pub use process_table::{ProcessTable, ProcessTableSnapshot, get_global_process_table, ProcessTableError};
+pub use statistics::{Statistics, get_global_statistics};
+pub use memory::{MemoryCounters, MemoryKind, get_global_memory_counters};
+pub use dist_table::{DistTable, DistEntry, DistConnectionState, get_global_dist_table};
+pub use signals::{Signal, SignalAction, SignalInstaller, SignalService, get_global_signal_service};
+pub use thr_progress::{ThrProgress, ThrProgressValue, LaterOp, get_global_thr_progress};
 pub use atom_table::get_global_atom_table;
-pub use global_literals::init_global_literals;
+pub use global_literals::{init_global_literals, erts_get_global_literal, erts_global_literal_allocate, is_global_literal, GlobalLiteral, GlobalLiteralError, GlobalLiterals};
 pub use erlang_term_decoder::{decode_term, ErlangTerm, DecoderError};
 pub use erl_scan::{scan_string, Token, TokenKind, ScanError};
 pub use erl_parse::{parse_exprs, parse_expr, Expr, BinOp, UnOp, ParseError};
@@ -0,0 +1,239 @@
+//! Memory Counters Module
+//!
+//! Provides the counters behind the `erlang:memory/0,1` categories for
+//! memory that is not allocated through the alloc_util allocators.
+//! Based on the `erts_memory()` accounting in erl_alloc.c.
+//!
+//! The counters are sharded: every thread updates the slot it was given on
+//! its first update, so schedulers counting concurrently do not contend on
+//! the same cache line. A read sums the slots. A single slot may go negative
+//! when memory is released on another thread than the one it was counted
+//! on; only the sum is meaningful.
+//!
+//! The process table counts the processes it holds, remembering the footprint
+//! it counted for each so a heap resized by the garbage collector is released
+//! exactly; other owners (binaries, ETS tables, loaded code) count their
+//! memory with [`MemoryCounters::add`] and [`MemoryCounters::sub`].
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ */
+
+use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+use entities_process::Process;
+
+/// Number of counter slots threads are spread over
+pub const MEMORY_COUNTER_SLOTS: usize = 64;
+
+/// Memory categories counted outside the allocators
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemoryKind {
+    /// Memory allocated for processes
+    Processes,
+    /// Memory used by processes
+    ProcessesUsed,
+    /// Memory allocated for atoms
+    Atom,
+    /// Memory used by atoms
+    AtomUsed,
+    /// Memory allocated for binaries
+    Binary,
+    /// Memory allocated for Erlang code
+    Code,
+    /// Memory allocated for ETS tables
+    Ets,
+    /// Other memory of the runtime system
+    System,
+}
+
+impl MemoryKind {
+    /// All counted categories
+    pub const ALL: [MemoryKind; 8] = [
+        MemoryKind::Processes,
+        MemoryKind::ProcessesUsed,
+        MemoryKind::Atom,
+        MemoryKind::AtomUsed,
+        MemoryKind::Binary,
+        MemoryKind::Code,
+        MemoryKind::Ets,
+        MemoryKind::System,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Counters of one slot, kept on a cache line of their own
+#[repr(align(64))]
+#[derive(Default)]
+struct CounterSlot {
+    bytes: [AtomicI64; MemoryKind::ALL.len()],
+}
+
+/// Sharded memory counters
+pub struct MemoryCounters {
+    slots: Vec<CounterSlot>,
+}
+
+/// Next slot handed out to a thread
+static NEXT_SLOT: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// Slot this thread updates
+    static THREAD_SLOT: usize = NEXT_SLOT.fetch_add(1, Ordering::Relaxed) % MEMORY_COUNTER_SLOTS;
+}
+
+impl MemoryCounters {
+    /// Create counters with all categories at zero
+    pub fn new() -> Self {
+        Self {
+            slots: (0..MEMORY_COUNTER_SLOTS).map(|_| CounterSlot::default()).collect(),
+        }
+    }
+
+    /// Count memory allocated for a category
+    pub fn add(&self, kind: MemoryKind, bytes: usize) {
+        self.update(kind, bytes as i64);
+    }
+
+    /// Count memory released from a category
+    pub fn sub(&self, kind: MemoryKind, bytes: usize) {
+        self.update(kind, -(bytes as i64));
+    }
+
+    /// Get the bytes counted for a category
+    pub fn get(&self, kind: MemoryKind) -> usize {
+        let total: i64 = self
+            .slots
+            .iter()
+            .map(|slot| slot.bytes[kind.index()].load(Ordering::Relaxed))
+            .sum();
+        total.max(0) as usize
+    }
+
+    /// Count a process added to the process table
+    ///
+    /// # Returns
+    /// The footprint counted, to be released with [`sub_process`](Self::sub_process)
+    pub fn add_process(&self, process: &Process) -> usize {
+        let bytes = process_footprint(process);
+        self.add(MemoryKind::Processes, bytes);
+        self.add(MemoryKind::ProcessesUsed, bytes);
+        bytes
+    }
+
+    /// Count a process removed from the process table
+    ///
+    /// # Arguments
+    /// * `footprint` - Footprint counted for the process
+    pub fn sub_process(&self, footprint: usize) {
+        self.sub(MemoryKind::Processes, footprint);
+        self.sub(MemoryKind::ProcessesUsed, footprint);
+    }
+
+    /// Count the change in footprint of a process whose heap was resized
+    ///
+    /// # Arguments
+    /// * `footprint` - Footprint counted for the process so far
+    /// * `process` - The resized process
+    ///
+    /// # Returns
+    /// The footprint now counted
+    pub fn resize_process(&self, footprint: usize, process: &Process) -> usize {
+        self.sub_process(footprint);
+        self.add_process(process)
+    }
+
+    fn update(&self, kind: MemoryKind, delta: i64) {
+        let slot = THREAD_SLOT.with(|slot| *slot);
+        self.slots[slot].bytes[kind.index()].fetch_add(delta, Ordering::Relaxed);
+    }
+}
+
+impl Default for MemoryCounters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Get the bytes a process takes: its process structure and heap
+pub fn process_footprint(process: &Process) -> usize {
+    std::mem::size_of::<Process>() + process.heap_sz() * std::mem::size_of::<usize>()
+}
+
+/// Global memory counters instance
+static GLOBAL_MEMORY_COUNTERS: OnceLock<MemoryCounters> = OnceLock::new();
+
+/// Get the global memory counters
+pub fn get_global_memory_counters() -> &'static MemoryCounters {
+    GLOBAL_MEMORY_COUNTERS.get_or_init(MemoryCounters::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters_sum_over_threads() {
+        let counters = std::sync::Arc::new(MemoryCounters::new());
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let counters = std::sync::Arc::clone(&counters);
+                std::thread::spawn(move || {
+                    for _ in 0..100 {
+                        counters.add(MemoryKind::Ets, 10);
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(counters.get(MemoryKind::Ets), 4_000);
+
+        // Memory released on another thread than it was counted on
+        counters.sub(MemoryKind::Ets, 1_000);
+        assert_eq!(counters.get(MemoryKind::Ets), 3_000);
+        assert_eq!(counters.get(MemoryKind::Binary), 0);
+    }
+
+    #[test]
+    fn test_process_accounting() {
+        let counters = MemoryCounters::new();
+        let mut process = Process::new(1);
+        let footprint = counters.add_process(&process);
+        assert_eq!(counters.get(MemoryKind::Processes), footprint);
+        assert!(footprint >= std::mem::size_of::<Process>());
+
+        // A resized heap is counted with its new size
+        let words = process.resize_heap(process.heap_sz() * 2);
+        let resized = counters.resize_process(footprint, &process);
+        assert_eq!(resized, std::mem::size_of::<Process>() + words * std::mem::size_of::<usize>());
+        assert_eq!(counters.get(MemoryKind::Processes), resized);
+        counters.sub_process(resized);
+        assert_eq!(counters.get(MemoryKind::ProcessesUsed), 0);
+    }
+}
@@ -8,6 +8,24 @@
 //! process, it looks up the process structure in the process table using
 //! the process identifier.
 //!
+//! ## Identifier Reuse
+//!
+//! Identifiers handed out by [`ProcessTable::new_element`] are made of a
+//! slot number (the low 32 bits, the process number of the external pid
+//! format) and a serial (the high bits). Freed slots are reused in the
+//! order they were freed, and each reuse increments the slot's serial, so
+//! a reused slot yields a pid distinct from the ones it had before. A
+//! lookup of a stale pid therefore finds nothing rather than the slot's new
+//! occupant.
+//!
+//! ## Iteration
+//!
+//! [`ProcessTable::snapshot`] takes the processes in the table at one point
+//! in time, in table order, and iterating the snapshot does not hold the
+//! table lock. Processes may spawn and exit while a snapshot is walked,
+//! which is what `erlang:processes/0`, crash dumps and the introspection
+//! BIFs need. Based on erts_ptab_list() and the ptab foreach machinery.
+//!
 //! ## Performance Optimization
 //!
 //! The current implementation uses a HashMap-based design which is sufficient
@@ -43,9 +61,53 @@
  */
 
 use std::collections::{HashMap, VecDeque};
-use std::sync::{Arc, RwLock};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use entities_process::{Process, ProcessId};
+use crate::memory::{get_global_memory_counters, MemoryCounters};
+
+/// Bits of a process identifier holding the slot number
+pub const PID_SLOT_BITS: u32 = 32;
+
+/// Mask of the slot number in a process identifier
+const PID_SLOT_MASK: u64 = (1 << PID_SLOT_BITS) - 1;
+
+/// Build a process identifier from a slot number and serial
+pub fn make_pid(slot: u32, serial: u32) -> ProcessId {
+    ((serial as u64) << PID_SLOT_BITS) | slot as u64
+}
+
+/// Get the slot number (process number) of a process identifier
+pub fn pid_slot(id: ProcessId) -> u32 {
+    (id & PID_SLOT_MASK) as u32
+}
+
+/// Get the serial of a process identifier
+pub fn pid_serial(id: ProcessId) -> u32 {
+    (id >> PID_SLOT_BITS) as u32
+}
+
+/// Slot allocation state
+///
+/// Based on the free list and serial data of ErtsPTab in erl_ptab.c
+struct SlotState {
+    /// Current serial of each slot handed out so far
+    serials: HashMap<u32, u32>,
+    /// Slots freed and not yet reused, oldest first
+    free: VecDeque<u32>,
+    /// Next slot never used; slot 0 is reserved
+    next_slot: u64,
+}
+
+impl SlotState {
+    fn new() -> Self {
+        Self {
+            serials: HashMap::new(),
+            free: VecDeque::new(),
+            next_slot: 1,
+        }
+    }
+}
 
 /// Process table/registry
 ///
@@ -57,12 +119,14 @@ pub struct ProcessTable {
     /// Internal hash map storing processes by ID
     /// Uses Arc for shared ownership and RwLock for thread safety
     table: Arc<RwLock<HashMap<ProcessId, Arc<Process>>>>,
-    /// Next ID to use for new processes (atomic counter)
-    next_id: AtomicU64,
-    /// Queue of free IDs available for reuse
-    free_ids: Arc<RwLock<VecDeque<ProcessId>>>,
+    /// Slot numbers and serials of identifiers made by `new_element()`
+    slots: Mutex<SlotState>,
     /// Maximum number of processes in the table (0 = unlimited)
-    max_size: usize,
+    max_size: AtomicUsize,
+    /// Counters the processes in the table are counted on, if any
+    memory: Option<&'static MemoryCounters>,
+    /// Footprint counted for each process, released when it is removed
+    footprints: Mutex<HashMap<ProcessId, usize>>,
 }
 
 impl ProcessTable {
@@ -100,12 +164,27 @@ impl ProcessTable {
     pub fn with_max_size(max_size: usize) -> Self {
         Self {
             table: Arc::new(RwLock::new(HashMap::new())),
-            next_id: AtomicU64::new(1), // Start from 1, 0 is reserved
-            free_ids: Arc::new(RwLock::new(VecDeque::new())),
-            max_size,
+            slots: Mutex::new(SlotState::new()),
+            max_size: AtomicUsize::new(max_size),
+            memory: None,
+            footprints: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Count the memory of the processes in the table on `counters`
+    fn with_memory_counters(mut self, counters: &'static MemoryCounters) -> Self {
+        self.memory = Some(counters);
+        self
+    }
+
+    /// Set the maximum number of processes (`+P`)
+    ///
+    /// # Arguments
+    /// * `max_size` - Maximum number of processes (0 = unlimited)
+    pub fn set_max_size(&self, max_size: usize) {
+        self.max_size.store(max_size, Ordering::Relaxed);
+    }
+
     /// Get the maximum size of the table
     ///
     /// # Returns
@@ -123,10 +202,9 @@ impl ProcessTable {
     /// assert_eq!(limited_table.max_size(), Some(100));
     /// ```
     pub fn max_size(&self) -> Option<usize> {
-        if self.max_size == 0 {
-            None
-        } else {
-            Some(self.max_size)
+        match self.max_size.load(Ordering::Relaxed) {
+            0 => None,
+            max_size => Some(max_size),
         }
     }
 
@@ -184,13 +262,19 @@ impl ProcessTable {
     /// ```
     pub fn insert(&self, id: ProcessId, process: Arc<Process>) -> Option<Arc<Process>> {
         let mut table = self.table.write().unwrap();
-        table.insert(id, process).map(|p| Arc::clone(&p))
+        if let Some(memory) = self.memory {
+            let footprint = memory.add_process(&process);
+            if let Some(previous) = self.footprints.lock().unwrap().insert(id, footprint) {
+                memory.sub_process(previous);
+            }
+        }
+        table.insert(id, process)
     }
 
     /// Remove a process from the table
     ///
-    /// When a process is removed, its ID is added to the free ID pool
-    /// for reuse by `new_element()`.
+    /// When a process made by `new_element()` is removed, its slot is
+    /// freed for reuse with the next serial.
     ///
     /// # Arguments
     /// * `id` - Process ID to remove
@@ -216,13 +300,24 @@ impl ProcessTable {
     pub fn remove(&self, id: ProcessId) -> Option<Arc<Process>> {
         let mut table = self.table.write().unwrap();
         let removed = table.remove(&id);
-        
-        // Add ID to free pool for reuse (if it was actually removed)
-        if removed.is_some() && id > 0 {
-            let mut free_ids = self.free_ids.write().unwrap();
-            free_ids.push_back(id);
+        if let Some(memory) = self.memory {
+            if let Some(footprint) = self.footprints.lock().unwrap().remove(&id) {
+                memory.sub_process(footprint);
+            }
         }
-        
+
+        // Free the slot if the identifier is its current one
+        if removed.is_some() {
+            let mut slots = self.slots.lock().unwrap();
+            let slot = pid_slot(id);
+            if let Some(serial) = slots.serials.get_mut(&slot) {
+                if *serial == pid_serial(id) {
+                    *serial = serial.wrapping_add(1);
+                    slots.free.push_back(slot);
+                }
+            }
+        }
+
         removed
     }
 
@@ -291,6 +386,71 @@ impl ProcessTable {
         table.keys().copied().collect()
     }
 
+    /// Take a snapshot of the processes in the table
+    ///
+    /// The snapshot holds the processes that were in the table when it was
+    /// taken, ordered by slot and serial, whatever is spawned or removed
+    /// afterwards.
+    ///
+    /// # Examples
+    /// ```
+    /// use infrastructure_utilities::process_table::ProcessTable;
+    /// use entities_process::Process;
+    /// use std::sync::Arc;
+    ///
+    /// let table = ProcessTable::new();
+    /// let (id, _) = table.new_element(|id| Arc::new(Process::new(id))).unwrap();
+    /// let snapshot = table.snapshot();
+    /// table.remove(id);
+    /// assert_eq!(snapshot.ids(), vec![id]);
+    /// ```
+    pub fn snapshot(&self) -> ProcessTableSnapshot {
+        let mut processes: Vec<(ProcessId, Arc<Process>)> = {
+            let table = self.table.read().unwrap();
+            table.iter().map(|(id, process)| (*id, Arc::clone(process))).collect()
+        };
+        processes.sort_by_key(|(id, _)| (pid_slot(*id), pid_serial(*id)));
+        ProcessTableSnapshot { processes }
+    }
+
+    /// List the identifiers of all processes in the table, in table order
+    ///
+    /// Equivalent to `erts_ptab_list()`, which implements `erlang:processes/0`.
+    pub fn list(&self) -> Vec<ProcessId> {
+        self.snapshot().ids()
+    }
+
+    /// Call a function for each process in a snapshot of the table
+    ///
+    /// The table is not locked while the function runs, so it may spawn
+    /// or remove processes.
+    pub fn foreach<F>(&self, mut f: F)
+    where
+        F: FnMut(ProcessId, &Arc<Process>),
+    {
+        for (id, process) in self.snapshot().iter() {
+            f(id, process);
+        }
+    }
+
+    /// Count the new footprint of a process whose heap was resized
+    ///
+    /// Called by the garbage collector after resizing the heap, so the
+    /// footprint released when the process is removed matches the one
+    /// counted.
+    ///
+    /// # Arguments
+    /// * `id` - Process ID
+    /// * `process` - The resized process
+    pub fn update_footprint(&self, id: ProcessId, process: &Process) {
+        let Some(memory) = self.memory else {
+            return;
+        };
+        if let Some(footprint) = self.footprints.lock().unwrap().get_mut(&id) {
+            *footprint = memory.resize_process(*footprint, process);
+        }
+    }
+
     /// Clear all processes from the table
     ///
     /// # Examples
@@ -309,16 +469,19 @@ impl ProcessTable {
     /// ```
     pub fn clear(&self) {
         let mut table = self.table.write().unwrap();
+        if let Some(memory) = self.memory {
+            self.footprints.lock().unwrap().drain().for_each(|(_, footprint)| memory.sub_process(footprint));
+        }
         table.clear();
-        let mut free_ids = self.free_ids.write().unwrap();
-        free_ids.clear();
+        *self.slots.lock().unwrap() = SlotState::new();
     }
 
     /// Create a new process element with automatically generated ID
     ///
     /// This is equivalent to `erts_ptab_new_element()` in the C code.
     /// It automatically generates a unique process ID and inserts the process
-    /// into the table. IDs are reused from a free pool when available.
+    /// into the table. Freed slots are reused, oldest first, with their
+    /// serial incremented so the new ID differs from earlier ones.
     ///
     /// # Arguments
     /// * `init_fn` - Function to initialize the process with the generated ID
@@ -343,63 +506,71 @@ impl ProcessTable {
     where
         F: Fn(ProcessId) -> Arc<Process>,
     {
-        // Check capacity
-        if self.max_size > 0 {
-            let table = self.table.read().unwrap();
-            if table.len() >= self.max_size {
-                return Err(ProcessTableError::TableFull);
-            }
+        let mut table = self.table.write().unwrap();
+        let max_size = self.max_size.load(Ordering::Relaxed);
+        if max_size > 0 && table.len() >= max_size {
+            return Err(ProcessTableError::TableFull);
         }
 
-        // Try to get a free ID first (for reuse)
+        let mut slots = self.slots.lock().unwrap();
         loop {
-            let id = {
-                let mut free_ids = self.free_ids.write().unwrap();
-                if let Some(free_id) = free_ids.pop_front() {
-                    free_id
-                } else {
-                    // Generate new ID
-                    let new_id = self.next_id.fetch_add(1, Ordering::Relaxed);
-                    // Skip 0 as it's reserved
-                    if new_id == 0 {
-                        self.next_id.fetch_add(1, Ordering::Relaxed);
-                        1
-                    } else {
-                        new_id
-                    }
+            let slot = match slots.free.pop_front() {
+                Some(slot) => slot,
+                None if slots.next_slot <= PID_SLOT_MASK => {
+                    let slot = slots.next_slot as u32;
+                    slots.next_slot += 1;
+                    slot
                 }
+                None => return Err(ProcessTableError::TableFull),
             };
+            let serial = *slots.serials.entry(slot).or_insert(0);
+            let id = make_pid(slot, serial);
 
-            // Create process with the generated ID
-            let process = init_fn(id);
-
-            // Insert into table
-            let mut table = self.table.write().unwrap();
-            
-            // Check capacity again (another thread might have filled it)
-            if self.max_size > 0 && table.len() >= self.max_size {
-                // Put ID back in free pool
-                drop(table);
-                let mut free_ids = self.free_ids.write().unwrap();
-                free_ids.push_front(id);
-                return Err(ProcessTableError::TableFull);
+            // An identifier inserted directly may be in use; skip to the
+            // slot's next serial
+            if table.contains_key(&id) {
+                slots.serials.insert(slot, serial.wrapping_add(1));
+                slots.free.push_back(slot);
+                continue;
             }
 
-            // Verify ID doesn't already exist (shouldn't happen, but be safe)
-            if table.contains_key(&id) {
-                // Put ID back in free pool and try next ID
-                drop(table);
-                let mut free_ids = self.free_ids.write().unwrap();
-                free_ids.push_front(id);
-                continue; // Try again with next ID
-            } else {
-                table.insert(id, Arc::clone(&process));
-                return Ok((id, process));
+            let process = init_fn(id);
+            if let Some(memory) = self.memory {
+                self.footprints.lock().unwrap().insert(id, memory.add_process(&process));
             }
+            table.insert(id, Arc::clone(&process));
+            return Ok((id, process));
         }
     }
 }
 
+/// Processes in a process table at one point in time, in table order
+pub struct ProcessTableSnapshot {
+    processes: Vec<(ProcessId, Arc<Process>)>,
+}
+
+impl ProcessTableSnapshot {
+    /// Get the identifiers of the processes
+    pub fn ids(&self) -> Vec<ProcessId> {
+        self.processes.iter().map(|(id, _)| *id).collect()
+    }
+
+    /// Iterate over the identifiers and processes
+    pub fn iter(&self) -> impl Iterator<Item = (ProcessId, &Arc<Process>)> {
+        self.processes.iter().map(|(id, process)| (*id, process))
+    }
+
+    /// Get the number of processes
+    pub fn len(&self) -> usize {
+        self.processes.len()
+    }
+
+    /// Check if the snapshot holds no processes
+    pub fn is_empty(&self) -> bool {
+        self.processes.is_empty()
+    }
+}
+
 /// Errors that can occur when operating on the process table
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProcessTableError {
@@ -449,7 +620,7 @@ static GLOBAL_PROCESS_TABLE: std::sync::OnceLock<ProcessTable> = std::sync::Once
 /// table.insert(123, process);
 /// ```
 pub fn get_global_process_table() -> &'static ProcessTable {
-    GLOBAL_PROCESS_TABLE.get_or_init(ProcessTable::new)
+    GLOBAL_PROCESS_TABLE.get_or_init(|| ProcessTable::new().with_memory_counters(get_global_memory_counters()))
 }
 
 #[cfg(test)]
@@ -611,9 +782,40 @@ mod tests {
         let (id1, _) = table.new_element(|id| Arc::new(Process::new(id))).unwrap();
         table.remove(id1).unwrap();
         
-        // Create another - should reuse the ID
+        // Create another - should reuse the slot with the next serial
         let (id2, _) = table.new_element(|id| Arc::new(Process::new(id))).unwrap();
-        assert_eq!(id2, id1); // ID should be reused
+        assert_eq!(pid_slot(id2), pid_slot(id1));
+        assert_eq!(pid_serial(id2), pid_serial(id1) + 1);
+        assert_ne!(id2, id1);
+
+        // A lookup of the stale pid does not find the new occupant
+        assert!(table.lookup(id1).is_none());
+        assert!(table.remove(id1).is_none());
+        assert_eq!(table.lookup(id2).map(|p| p.get_id()), Some(id2));
+    }
+
+    #[test]
+    fn test_new_element_slot_reuse_order() {
+        let table = ProcessTable::new();
+        let ids: Vec<_> = (0..3)
+            .map(|_| table.new_element(|id| Arc::new(Process::new(id))).unwrap().0)
+            .collect();
+        table.remove(ids[2]).unwrap();
+        table.remove(ids[0]).unwrap();
+
+        // Oldest freed slot first, then the next one never used
+        let reused: Vec<_> = (0..3)
+            .map(|_| table.new_element(|id| Arc::new(Process::new(id))).unwrap().0)
+            .collect();
+        assert_eq!(reused.iter().map(|id| pid_slot(*id)).collect::<Vec<_>>(), vec![pid_slot(ids[2]), pid_slot(ids[0]), 4]);
+        assert_eq!(pid_serial(reused[2]), 0);
+
+        // A directly inserted pid holding the next identifier is skipped
+        table.remove(reused[1]).unwrap();
+        let taken = make_pid(pid_slot(ids[0]), 2);
+        table.insert(taken, Arc::new(Process::new(taken)));
+        let (id, _) = table.new_element(|id| Arc::new(Process::new(id))).unwrap();
+        assert_eq!(id, make_pid(pid_slot(ids[0]), 3));
     }
 
     #[test]
@@ -632,6 +834,35 @@ mod tests {
         table.remove(id1).unwrap();
         let (id3, _) = table.new_element(|id| Arc::new(Process::new(id))).unwrap();
         assert_eq!(table.size(), 2);
+
+        // Lowering the limit below the process count refuses new processes
+        table.set_max_size(1);
+        assert_eq!(table.new_element(|id| Arc::new(Process::new(id))).unwrap_err(), ProcessTableError::TableFull);
+        table.remove(id2).unwrap();
+        table.remove(id3).unwrap();
+        assert!(table.new_element(|id| Arc::new(Process::new(id))).is_ok());
+    }
+
+    #[test]
+    fn test_snapshot_is_point_in_time() {
+        let table = ProcessTable::new();
+        let (first, _) = table.new_element(|id| Arc::new(Process::new(id))).unwrap();
+        let (second, _) = table.new_element(|id| Arc::new(Process::new(id))).unwrap();
+        table.remove(first).unwrap();
+        let (reused, _) = table.new_element(|id| Arc::new(Process::new(id))).unwrap();
+        assert_eq!(table.list(), vec![reused, second]);
+
+        // Spawning and removing while walking a snapshot
+        let mut seen = Vec::new();
+        table.foreach(|id, process| {
+            assert_eq!(process.get_id(), id);
+            table.remove(id);
+            table.new_element(|id| Arc::new(Process::new(id))).unwrap();
+            seen.push(id);
+        });
+        assert_eq!(seen, vec![reused, second]);
+        assert_eq!(table.size(), 2);
+        assert!(table.list().iter().all(|id| !seen.contains(id)));
     }
 
     #[test]
@@ -642,5 +873,36 @@ mod tests {
         let table2 = ProcessTable::with_max_size(100);
         assert_eq!(table2.max_size(), Some(100));
     }
-}
 
+    #[test]
+    fn test_memory_accounting() {
+        use crate::memory::{process_footprint, MemoryKind};
+
+        let counters: &'static MemoryCounters = Box::leak(Box::new(MemoryCounters::new()));
+        let table = ProcessTable::new().with_memory_counters(counters);
+        let (first, process) = table.new_element(|id| Arc::new(Process::new(id))).unwrap();
+        let footprint = process_footprint(&process);
+        table.insert(7, Arc::new(Process::new(7)));
+        assert_eq!(counters.get(MemoryKind::Processes), 2 * footprint);
+
+        // Replacing a process counts only the new one
+        table.insert(7, Arc::new(Process::new(7)));
+        assert_eq!(counters.get(MemoryKind::Processes), 2 * footprint);
+
+        table.remove(first);
+        assert_eq!(counters.get(MemoryKind::ProcessesUsed), footprint);
+
+        // The footprint counted after a heap resize is the one released
+        let mut grown = Process::new(7);
+        grown.resize_heap(grown.heap_sz() * 2);
+        table.update_footprint(7, &grown);
+        assert!(process_footprint(&grown) > footprint);
+        assert_eq!(counters.get(MemoryKind::Processes), process_footprint(&grown));
+        table.remove(7);
+        assert_eq!(counters.get(MemoryKind::Processes), 0);
+
+        table.insert(8, Arc::new(Process::new(8)));
+        table.clear();
+        assert_eq!(counters.get(MemoryKind::Processes), 0);
+    }
+}
@@ -0,0 +1,321 @@
+//! Signal Service Module
+//!
+//! Provides the dispositions of the OS signals the runtime lets Erlang code
+//! control with `os:set_signal/2`, and the set of handled signals waiting to
+//! be delivered to `erl_signal_server`. Based on the signal handling in
+//! sys.c and erl_signal_server.
+//!
+//! A signal is either left to its default action, ignored, or handled. A
+//! handled signal is recorded as pending by [`SignalService::notify`], which
+//! the OS signal handler calls; the emulator takes the pending signals with
+//! [`SignalService::take_pending`] and sends `{notify, Signal}` to the
+//! signal server. Both the dispositions and the pending set are atomics, so
+//! `notify` is safe to call from a signal handler.
+//!
+//! The service itself does not touch the OS. The layer that owns the signal
+//! handlers installs a [`SignalInstaller`] with
+//! [`SignalService::set_installer`], and every disposition change is applied
+//! through it before it takes effect.
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ *
+ * Creation productivity increased for code in this file by using AALang and GAB.
+ * See https://github.com/yenrab/AALang-Gab
+ */
+
+use std::io;
+use std::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+/// Applies a signal disposition to the OS
+pub type SignalInstaller = fn(Signal, SignalAction) -> io::Result<()>;
+
+/// Signals that can be controlled with `os:set_signal/2`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Signal {
+    /// Hangup
+    Sighup,
+    /// Interrupt
+    Sigint,
+    /// Quit
+    Sigquit,
+    /// Abort
+    Sigabrt,
+    /// Alarm clock
+    Sigalrm,
+    /// Termination request
+    Sigterm,
+    /// User-defined signal 1
+    Sigusr1,
+    /// User-defined signal 2
+    Sigusr2,
+    /// Child process stopped or terminated
+    Sigchld,
+    /// Stop
+    Sigstop,
+    /// Terminal stop
+    Sigtstp,
+}
+
+impl Signal {
+    /// All controllable signals
+    pub const ALL: [Signal; 11] = [
+        Signal::Sighup,
+        Signal::Sigint,
+        Signal::Sigquit,
+        Signal::Sigabrt,
+        Signal::Sigalrm,
+        Signal::Sigterm,
+        Signal::Sigusr1,
+        Signal::Sigusr2,
+        Signal::Sigchld,
+        Signal::Sigstop,
+        Signal::Sigtstp,
+    ];
+
+    /// Atom naming the signal (e.g. `sigterm`)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Signal::Sighup => "sighup",
+            Signal::Sigint => "sigint",
+            Signal::Sigquit => "sigquit",
+            Signal::Sigabrt => "sigabrt",
+            Signal::Sigalrm => "sigalrm",
+            Signal::Sigterm => "sigterm",
+            Signal::Sigusr1 => "sigusr1",
+            Signal::Sigusr2 => "sigusr2",
+            Signal::Sigchld => "sigchld",
+            Signal::Sigstop => "sigstop",
+            Signal::Sigtstp => "sigtstp",
+        }
+    }
+
+    /// Look up a signal by name, ignoring case (`sigterm` or `SIGTERM`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|signal| signal.name().eq_ignore_ascii_case(name))
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// How a signal is dealt with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalAction {
+    /// The OS default action
+    Default,
+    /// The signal is ignored
+    Ignore,
+    /// The signal is delivered to `erl_signal_server`
+    Handle,
+}
+
+impl SignalAction {
+    /// Parse the action argument of `os:set_signal/2`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(SignalAction::Default),
+            "ignore" => Some(SignalAction::Ignore),
+            "handle" => Some(SignalAction::Handle),
+            _ => None,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => SignalAction::Ignore,
+            2 => SignalAction::Handle,
+            _ => SignalAction::Default,
+        }
+    }
+
+    fn as_u8(self) -> u8 {
+        match self {
+            SignalAction::Default => 0,
+            SignalAction::Ignore => 1,
+            SignalAction::Handle => 2,
+        }
+    }
+}
+
+/// Signal dispositions and pending handled signals
+pub struct SignalService {
+    /// Disposition per signal, indexed by [`Signal`]
+    actions: [AtomicU8; Signal::ALL.len()],
+    /// Bit set of handled signals not yet delivered
+    pending: AtomicU32,
+    /// Applies dispositions to the OS; serializes disposition changes
+    installer: Mutex<Option<SignalInstaller>>,
+}
+
+impl SignalService {
+    /// Create a service with every signal at its default action
+    pub fn new() -> Self {
+        Self {
+            actions: std::array::from_fn(|_| AtomicU8::new(SignalAction::Default.as_u8())),
+            pending: AtomicU32::new(0),
+            installer: Mutex::new(None),
+        }
+    }
+
+    /// Set the function that applies dispositions to the OS
+    ///
+    /// Dispositions already set are applied with it right away.
+    pub fn set_installer(&self, installer: SignalInstaller) -> io::Result<()> {
+        let mut current = self.installer.lock().unwrap();
+        for signal in Signal::ALL {
+            let action = self.action(signal);
+            if action != SignalAction::Default {
+                installer(signal, action)?;
+            }
+        }
+        *current = Some(installer);
+        Ok(())
+    }
+
+    /// Set how a signal is dealt with (`os:set_signal/2`)
+    ///
+    /// The disposition is applied to the OS through the installer, if one
+    /// is set. A pending signal is dropped when it stops being handled.
+    ///
+    /// # Returns
+    /// * `Ok(previous)` - The previous action
+    /// * `Err(error)` - The OS rejected the disposition; it is left unchanged
+    pub fn set_signal(&self, signal: Signal, action: SignalAction) -> io::Result<SignalAction> {
+        let installer = self.installer.lock().unwrap();
+        // Record a handled signal before the handler can see it arrive
+        let previous = self.actions[signal.index()].swap(action.as_u8(), Ordering::SeqCst);
+        if let Some(install) = *installer {
+            if let Err(err) = install(signal, action) {
+                self.actions[signal.index()].store(previous, Ordering::SeqCst);
+                return Err(err);
+            }
+        }
+        if action != SignalAction::Handle {
+            self.pending.fetch_and(!(1 << signal.index()), Ordering::SeqCst);
+        }
+        Ok(SignalAction::from_u8(previous))
+    }
+
+    /// Get how a signal is dealt with
+    pub fn action(&self, signal: Signal) -> SignalAction {
+        SignalAction::from_u8(self.actions[signal.index()].load(Ordering::SeqCst))
+    }
+
+    /// Record that a signal arrived; safe to call from a signal handler
+    ///
+    /// # Returns
+    /// The action in effect; the signal is only recorded when it is handled
+    pub fn notify(&self, signal: Signal) -> SignalAction {
+        let action = self.action(signal);
+        if action == SignalAction::Handle {
+            self.pending.fetch_or(1 << signal.index(), Ordering::SeqCst);
+        }
+        action
+    }
+
+    /// Take the handled signals to deliver to `erl_signal_server`
+    pub fn take_pending(&self) -> Vec<Signal> {
+        let pending = self.pending.swap(0, Ordering::SeqCst);
+        Signal::ALL
+            .into_iter()
+            .filter(|signal| pending & (1 << signal.index()) != 0)
+            .collect()
+    }
+}
+
+impl Default for SignalService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Global signal service instance
+static GLOBAL_SIGNAL_SERVICE: OnceLock<SignalService> = OnceLock::new();
+
+/// Get the global signal service
+pub fn get_global_signal_service() -> &'static SignalService {
+    GLOBAL_SIGNAL_SERVICE.get_or_init(SignalService::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signal_names() {
+        assert_eq!(Signal::from_name("sigusr1"), Some(Signal::Sigusr1));
+        assert_eq!(Signal::from_name("SIGTERM"), Some(Signal::Sigterm));
+        assert_eq!(Signal::from_name("sigkill"), None);
+        for signal in Signal::ALL {
+            assert_eq!(Signal::from_name(signal.name()), Some(signal));
+        }
+        assert_eq!(SignalAction::from_name("handle"), Some(SignalAction::Handle));
+        assert_eq!(SignalAction::from_name("catch"), None);
+    }
+
+    #[test]
+    fn test_handled_signals_pending() {
+        let service = SignalService::new();
+        assert_eq!(service.notify(Signal::Sigusr1), SignalAction::Default);
+        assert!(service.take_pending().is_empty());
+
+        assert_eq!(service.set_signal(Signal::Sigusr1, SignalAction::Handle).unwrap(), SignalAction::Default);
+        service.set_signal(Signal::Sighup, SignalAction::Handle).unwrap();
+        assert_eq!(service.notify(Signal::Sigusr1), SignalAction::Handle);
+        service.notify(Signal::Sigusr1);
+        service.notify(Signal::Sighup);
+        assert_eq!(service.take_pending(), vec![Signal::Sighup, Signal::Sigusr1]);
+        assert!(service.take_pending().is_empty());
+
+        service.notify(Signal::Sighup);
+        service.set_signal(Signal::Sighup, SignalAction::Ignore).unwrap();
+        assert_eq!(service.action(Signal::Sighup), SignalAction::Ignore);
+        assert!(service.take_pending().is_empty());
+    }
+
+    #[test]
+    fn test_installer() {
+        fn reject_sigstop(signal: Signal, _action: SignalAction) -> io::Result<()> {
+            if signal == Signal::Sigstop {
+                return Err(io::Error::from(io::ErrorKind::InvalidInput));
+            }
+            Ok(())
+        }
+
+        let service = SignalService::new();
+        service.set_signal(Signal::Sigstop, SignalAction::Ignore).unwrap();
+        assert!(service.set_installer(reject_sigstop).is_err());
+
+        service.set_signal(Signal::Sigstop, SignalAction::Default).unwrap();
+        service.set_installer(reject_sigstop).unwrap();
+        assert!(service.set_signal(Signal::Sigstop, SignalAction::Handle).is_err());
+        assert_eq!(service.action(Signal::Sigstop), SignalAction::Default);
+        service.set_signal(Signal::Sigterm, SignalAction::Handle).unwrap();
+        assert_eq!(service.action(Signal::Sigterm), SignalAction::Handle);
+    }
+}
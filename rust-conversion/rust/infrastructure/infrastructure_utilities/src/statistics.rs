@@ -0,0 +1,303 @@
+//! Statistics Module
+//!
+//! Provides the runtime counters behind `erlang:statistics/1`.
+//! Based on the statistics counters in erl_process.c, io.c and erl_gc.c.
+//!
+//! Counters are updated by the parts of the runtime that do the work:
+//! schedulers add the reductions and run time of the processes they execute
+//! and publish their run queue lengths, ports count the bytes passing
+//! through them, and the garbage collector counts collections. Queries that
+//! return a "since last call" value (`reductions`, `exact_reductions`,
+//! `wall_clock` and `runtime`) each remember their own last call, as in ERTS.
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ *
+ * Creation productivity increased for code in this file by using AALang and GAB.
+ * See https://github.com/yenrab/AALang-Gab
+ */
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+/// Counters kept per scheduler
+#[derive(Default)]
+struct SchedulerCounters {
+    /// Reductions executed on the scheduler
+    reductions: AtomicU64,
+    /// Time spent executing processes, in nanoseconds
+    runtime_nanos: AtomicU64,
+    /// Run queue length at the last snapshot
+    run_queue_length: AtomicUsize,
+}
+
+/// Runtime statistics counters
+///
+/// Thread-safe; all counters can be updated concurrently from scheduler
+/// and port threads.
+pub struct Statistics {
+    /// Time the counters were created (system start for the global instance)
+    start: Instant,
+    /// Per-scheduler counters, indexed by scheduler index
+    schedulers: RwLock<Vec<SchedulerCounters>>,
+    /// Total reductions at the last `reductions` query
+    last_reductions: Mutex<u64>,
+    /// Total reductions at the last `exact_reductions` query
+    last_exact_reductions: Mutex<u64>,
+    /// Elapsed time at the last `wall_clock` query
+    last_wall_clock: Mutex<Duration>,
+    /// Total run time at the last `runtime` query
+    last_runtime: Mutex<Duration>,
+    /// Bytes received from ports
+    bytes_in: AtomicU64,
+    /// Bytes sent to ports
+    bytes_out: AtomicU64,
+    /// Number of garbage collections
+    gc_count: AtomicU64,
+    /// Heap words reclaimed by garbage collections
+    gc_words_reclaimed: AtomicU64,
+}
+
+impl Statistics {
+    /// Create counters starting at zero
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            schedulers: RwLock::new(Vec::new()),
+            last_reductions: Mutex::new(0),
+            last_exact_reductions: Mutex::new(0),
+            last_wall_clock: Mutex::new(Duration::ZERO),
+            last_runtime: Mutex::new(Duration::ZERO),
+            bytes_in: AtomicU64::new(0),
+            bytes_out: AtomicU64::new(0),
+            gc_count: AtomicU64::new(0),
+            gc_words_reclaimed: AtomicU64::new(0),
+        }
+    }
+
+    /// Run `f` on the counters of a scheduler, adding counters for
+    /// schedulers not seen before
+    fn with_scheduler<R>(&self, scheduler: usize, f: impl FnOnce(&SchedulerCounters) -> R) -> R {
+        {
+            let schedulers = self.schedulers.read().unwrap();
+            if let Some(counters) = schedulers.get(scheduler) {
+                return f(counters);
+            }
+        }
+        let mut schedulers = self.schedulers.write().unwrap();
+        if schedulers.len() <= scheduler {
+            schedulers.resize_with(scheduler + 1, SchedulerCounters::default);
+        }
+        f(&schedulers[scheduler])
+    }
+
+    /// Add reductions executed on a scheduler
+    pub fn add_reductions(&self, scheduler: usize, reductions: u64) {
+        self.with_scheduler(scheduler, |c| c.reductions.fetch_add(reductions, Ordering::Relaxed));
+    }
+
+    /// Add time a scheduler spent executing processes
+    pub fn add_runtime(&self, scheduler: usize, time: Duration) {
+        let nanos = u64::try_from(time.as_nanos()).unwrap_or(u64::MAX);
+        self.with_scheduler(scheduler, |c| c.runtime_nanos.fetch_add(nanos, Ordering::Relaxed));
+    }
+
+    /// Publish the current run queue length of a scheduler
+    pub fn set_run_queue_length(&self, scheduler: usize, length: usize) {
+        self.with_scheduler(scheduler, |c| c.run_queue_length.store(length, Ordering::Relaxed));
+    }
+
+    /// Count bytes received from a port
+    pub fn add_bytes_in(&self, bytes: u64) {
+        self.bytes_in.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Count bytes sent to a port
+    pub fn add_bytes_out(&self, bytes: u64) {
+        self.bytes_out.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Count a garbage collection and the heap words it reclaimed
+    pub fn record_garbage_collection(&self, words_reclaimed: u64) {
+        self.gc_count.fetch_add(1, Ordering::Relaxed);
+        self.gc_words_reclaimed.fetch_add(words_reclaimed, Ordering::Relaxed);
+    }
+
+    /// Get the reductions executed on each scheduler, by scheduler index
+    pub fn scheduler_reductions(&self) -> Vec<u64> {
+        let schedulers = self.schedulers.read().unwrap();
+        schedulers.iter().map(|c| c.reductions.load(Ordering::Relaxed)).collect()
+    }
+
+    /// Get the total reductions executed on all schedulers
+    pub fn total_reductions(&self) -> u64 {
+        self.scheduler_reductions().iter().sum()
+    }
+
+    /// `statistics(reductions)`: total reductions and reductions since the
+    /// last call
+    pub fn reductions(&self) -> (u64, u64) {
+        Self::since_last(&self.last_reductions, self.total_reductions())
+    }
+
+    /// `statistics(exact_reductions)`: as [`Statistics::reductions`], with
+    /// its own last call
+    pub fn exact_reductions(&self) -> (u64, u64) {
+        Self::since_last(&self.last_exact_reductions, self.total_reductions())
+    }
+
+    /// Get the run queue length of each scheduler at its last snapshot
+    pub fn run_queue_lengths(&self) -> Vec<usize> {
+        let schedulers = self.schedulers.read().unwrap();
+        schedulers.iter().map(|c| c.run_queue_length.load(Ordering::Relaxed)).collect()
+    }
+
+    /// `statistics(run_queue)`: total length of all run queues
+    pub fn total_run_queue_length(&self) -> usize {
+        self.run_queue_lengths().iter().sum()
+    }
+
+    /// `statistics(io)`: bytes received from and sent to ports
+    pub fn io(&self) -> (u64, u64) {
+        (self.bytes_in.load(Ordering::Relaxed), self.bytes_out.load(Ordering::Relaxed))
+    }
+
+    /// `statistics(garbage_collection)`: number of collections and words
+    /// reclaimed
+    pub fn garbage_collection(&self) -> (u64, u64) {
+        (self.gc_count.load(Ordering::Relaxed), self.gc_words_reclaimed.load(Ordering::Relaxed))
+    }
+
+    /// `statistics(wall_clock)`: milliseconds since start and since the
+    /// last call
+    pub fn wall_clock(&self) -> (u64, u64) {
+        Self::duration_since_last(&self.last_wall_clock, self.start.elapsed())
+    }
+
+    /// `statistics(runtime)`: milliseconds spent executing processes in
+    /// total and since the last call
+    ///
+    /// This is the time measured by the schedulers around process
+    /// execution, not the CPU time of the whole OS process.
+    pub fn runtime(&self) -> (u64, u64) {
+        let schedulers = self.schedulers.read().unwrap();
+        let nanos: u64 = schedulers.iter().map(|c| c.runtime_nanos.load(Ordering::Relaxed)).sum();
+        drop(schedulers);
+        Self::duration_since_last(&self.last_runtime, Duration::from_nanos(nanos))
+    }
+
+    fn since_last(last: &Mutex<u64>, total: u64) -> (u64, u64) {
+        let mut last = last.lock().unwrap();
+        let since = total.saturating_sub(*last);
+        *last = total;
+        (total, since)
+    }
+
+    fn duration_since_last(last: &Mutex<Duration>, total: Duration) -> (u64, u64) {
+        let mut last = last.lock().unwrap();
+        let since = total.saturating_sub(*last);
+        *last = total;
+        (total.as_millis() as u64, since.as_millis() as u64)
+    }
+}
+
+impl Default for Statistics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Global statistics instance
+static GLOBAL_STATISTICS: OnceLock<Statistics> = OnceLock::new();
+
+/// Get the global statistics counters
+///
+/// # Examples
+/// ```
+/// use infrastructure_utilities::statistics::get_global_statistics;
+///
+/// let stats = get_global_statistics();
+/// stats.add_bytes_out(10);
+/// assert!(stats.io().1 >= 10);
+/// ```
+pub fn get_global_statistics() -> &'static Statistics {
+    GLOBAL_STATISTICS.get_or_init(Statistics::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reductions_since_last_call() {
+        let stats = Statistics::new();
+        stats.add_reductions(0, 100);
+        stats.add_reductions(2, 50);
+        assert_eq!(stats.scheduler_reductions(), vec![100, 0, 50]);
+        assert_eq!(stats.reductions(), (150, 150));
+        stats.add_reductions(1, 7);
+        assert_eq!(stats.reductions(), (157, 7));
+        // exact_reductions keeps its own last call
+        assert_eq!(stats.exact_reductions(), (157, 157));
+        assert_eq!(stats.exact_reductions(), (157, 0));
+    }
+
+    #[test]
+    fn test_run_queue_lengths_snapshot() {
+        let stats = Statistics::new();
+        stats.set_run_queue_length(1, 3);
+        stats.set_run_queue_length(0, 2);
+        stats.set_run_queue_length(1, 1);
+        assert_eq!(stats.run_queue_lengths(), vec![2, 1]);
+        assert_eq!(stats.total_run_queue_length(), 3);
+    }
+
+    #[test]
+    fn test_io_and_garbage_collection() {
+        let stats = Statistics::new();
+        stats.add_bytes_in(5);
+        stats.add_bytes_out(8);
+        stats.add_bytes_out(2);
+        assert_eq!(stats.io(), (5, 10));
+        stats.record_garbage_collection(120);
+        stats.record_garbage_collection(30);
+        assert_eq!(stats.garbage_collection(), (2, 150));
+    }
+
+    #[test]
+    fn test_runtime_and_wall_clock() {
+        let stats = Statistics::new();
+        stats.add_runtime(0, Duration::from_millis(40));
+        stats.add_runtime(1, Duration::from_millis(5));
+        assert_eq!(stats.runtime(), (45, 45));
+        stats.add_runtime(0, Duration::from_millis(3));
+        assert_eq!(stats.runtime(), (48, 3));
+
+        std::thread::sleep(Duration::from_millis(2));
+        let (total, since) = stats.wall_clock();
+        assert!(total >= 2);
+        assert_eq!(total, since);
+        assert!(stats.wall_clock().0 >= total);
+    }
+}
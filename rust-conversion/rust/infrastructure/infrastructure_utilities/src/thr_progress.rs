@@ -0,0 +1,396 @@
+//! Thread Progress Module
+//!
+//! Provides thread progress tracking for safe deferred memory reclamation.
+//! Based on erl_thr_progress.c.
+//!
+//! Managed threads (the schedulers) regularly call
+//! [`ThrProgress::update`] at points where they hold no references to shared
+//! data structures that may be unlinked concurrently. The global progress
+//! value advances each time every active managed thread has confirmed the
+//! current value. Memory that has been unlinked can be reclaimed once the
+//! value returned by [`ThrProgress::later`] at unlink time has been
+//! reached: by then every managed thread has passed a progress point after
+//! the unlink and can no longer see the memory.
+//!
+//! Deferred work is scheduled with [`ThrProgress::schedule_later_op`] and
+//! run by a managed thread's `update` once its progress value is reached.
+//!
+//! A managed thread going to sleep calls [`ThrProgress::prepare_wait`] so
+//! that it does not hold progress back, and [`ThrProgress::finalize_wait`]
+//! when it wakes up.
+//!
+//! [`ThrProgress::block`] stops all managed threads at their next progress
+//! point until [`ThrProgress::unblock`] is called, for operations that need
+//! exclusive access to data the schedulers read without locks.
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 2011-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ *
+ * Creation productivity increased for code in this file by using AALang and GAB.
+ * See https://github.com/yenrab/AALang-Gab
+ */
+
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex, OnceLock};
+
+/// Thread progress value (ErtsThrPrgrVal)
+pub type ThrProgressValue = u64;
+
+/// Deferred operation run once a progress value has been reached
+pub type LaterOp = Box<dyn FnOnce() + Send>;
+
+/// Progress state of a managed thread
+#[derive(Debug, Clone, Copy)]
+struct ManagedThread {
+    /// Last progress value the thread confirmed
+    confirmed: ThrProgressValue,
+    /// Thread takes part in progress (not sleeping)
+    active: bool,
+    /// Thread is parked at a progress point because of a block
+    parked: bool,
+}
+
+/// Progress state protected by the lock
+struct ProgressState {
+    /// Current global progress value
+    current: ThrProgressValue,
+    /// Managed threads, indexed by their registration index
+    threads: Vec<ManagedThread>,
+    /// Number of outstanding blocks
+    block_count: usize,
+    /// Deferred operations with the value they wait for, in scheduling order
+    later_ops: VecDeque<(ThrProgressValue, LaterOp)>,
+}
+
+impl ProgressState {
+    /// Advance the progress value as far as the managed threads allow
+    fn try_advance(&mut self) {
+        if self.threads.iter().all(|thread| !thread.active) {
+            // No thread can hold a reference; every pending value is reached
+            let target = self.later_ops.iter().map(|(value, _)| *value).max().unwrap_or(self.current);
+            self.current = self.current.max(target);
+            return;
+        }
+        if self
+            .threads
+            .iter()
+            .filter(|thread| thread.active)
+            .all(|thread| thread.confirmed >= self.current)
+        {
+            self.current += 1;
+        }
+    }
+
+    /// Take the deferred operations whose value has been reached
+    fn take_due_ops(&mut self) -> Vec<LaterOp> {
+        let current = self.current;
+        let (due, pending): (VecDeque<_>, VecDeque<_>) =
+            self.later_ops.drain(..).partition(|(value, _)| *value <= current);
+        self.later_ops = pending;
+        due.into_iter().map(|(_, op)| op).collect()
+    }
+
+    /// Check if every active managed thread other than `caller` is parked
+    fn all_parked(&self, caller: Option<usize>) -> bool {
+        self.threads
+            .iter()
+            .enumerate()
+            .filter(|(index, thread)| Some(*index) != caller && thread.active)
+            .all(|(_, thread)| thread.parked)
+    }
+}
+
+/// Thread progress tracker (erts_thr_progress)
+pub struct ThrProgress {
+    /// Progress state
+    state: Mutex<ProgressState>,
+    /// Signalled when threads park or a block is released
+    changed: Condvar,
+}
+
+impl ThrProgress {
+    /// Create a tracker without managed threads
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(ProgressState {
+                current: 0,
+                threads: Vec::new(),
+                block_count: 0,
+                later_ops: VecDeque::new(),
+            }),
+            changed: Condvar::new(),
+        }
+    }
+
+    /// Register a managed thread (erts_thr_progress_register_managed_thread)
+    ///
+    /// The thread starts active, having confirmed the current value.
+    ///
+    /// # Returns
+    /// Index identifying the thread in the other calls
+    pub fn register_managed_thread(&self) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let current = state.current;
+        state.threads.push(ManagedThread { confirmed: current, active: true, parked: false });
+        state.threads.len() - 1
+    }
+
+    /// Get the number of registered managed threads
+    pub fn managed_threads(&self) -> usize {
+        self.state.lock().unwrap().threads.len()
+    }
+
+    /// Get the current progress value (erts_thr_progress_current)
+    pub fn current(&self) -> ThrProgressValue {
+        self.state.lock().unwrap().current
+    }
+
+    /// Get a progress value that is reached only after every managed
+    /// thread has passed a progress point after this call
+    /// (erts_thr_progress_later)
+    pub fn later(&self) -> ThrProgressValue {
+        // A thread may have confirmed the current value just before the
+        // caller unlinked its data, so it must confirm one more
+        self.state.lock().unwrap().current + 2
+    }
+
+    /// Check if a progress value has been reached (erts_thr_progress_has_reached)
+    pub fn has_reached(&self, value: ThrProgressValue) -> bool {
+        self.state.lock().unwrap().current >= value
+    }
+
+    /// Schedule an operation to run once every managed thread has passed a
+    /// progress point (erts_schedule_thr_prgr_later_op)
+    ///
+    /// # Returns
+    /// The progress value the operation waits for
+    pub fn schedule_later_op(&self, op: LaterOp) -> ThrProgressValue {
+        let (value, due) = {
+            let mut state = self.state.lock().unwrap();
+            let value = state.current + 2;
+            state.later_ops.push_back((value, op));
+            if state.threads.iter().all(|thread| !thread.active) {
+                state.try_advance();
+            }
+            (value, state.take_due_ops())
+        };
+        due.into_iter().for_each(|op| op());
+        value
+    }
+
+    /// Get the number of deferred operations waiting for progress
+    pub fn pending_later_ops(&self) -> usize {
+        self.state.lock().unwrap().later_ops.len()
+    }
+
+    /// Confirm progress from a managed thread (erts_thr_progress_update)
+    ///
+    /// Call at a point where the thread holds no references to memory that
+    /// others may unlink. Parks the thread while a block is in progress and
+    /// runs deferred operations that have become due.
+    ///
+    /// # Returns
+    /// Number of deferred operations run
+    pub fn update(&self, thread: usize) -> usize {
+        let due = {
+            let mut state = self.state.lock().unwrap();
+            if state.block_count > 0 {
+                state.threads[thread].parked = true;
+                self.changed.notify_all();
+                while state.block_count > 0 {
+                    state = self.changed.wait(state).unwrap();
+                }
+                state.threads[thread].parked = false;
+            }
+            state.threads[thread].confirmed = state.current;
+            state.try_advance();
+            state.take_due_ops()
+        };
+        let count = due.len();
+        due.into_iter().for_each(|op| op());
+        count
+    }
+
+    /// Stop taking part in progress before going to sleep
+    /// (erts_thr_progress_prepare_wait)
+    pub fn prepare_wait(&self, thread: usize) {
+        let due = {
+            let mut state = self.state.lock().unwrap();
+            state.threads[thread].active = false;
+            state.try_advance();
+            self.changed.notify_all();
+            state.take_due_ops()
+        };
+        due.into_iter().for_each(|op| op());
+    }
+
+    /// Take part in progress again after waking up
+    /// (erts_thr_progress_finalize_wait)
+    ///
+    /// Waits while a block is in progress.
+    pub fn finalize_wait(&self, thread: usize) {
+        let mut state = self.state.lock().unwrap();
+        while state.block_count > 0 {
+            state = self.changed.wait(state).unwrap();
+        }
+        let current = state.current;
+        state.threads[thread].active = true;
+        state.threads[thread].confirmed = current;
+    }
+
+    /// Block all managed threads at their next progress point
+    /// (erts_thr_progress_block)
+    ///
+    /// Returns once every active managed thread is parked. Blocks nest.
+    ///
+    /// # Arguments
+    /// * `caller` - Index of the calling thread if it is a managed thread;
+    ///   it is not waited for
+    pub fn block(&self, caller: Option<usize>) {
+        let mut state = self.state.lock().unwrap();
+        state.block_count += 1;
+        while !state.all_parked(caller) {
+            state = self.changed.wait(state).unwrap();
+        }
+    }
+
+    /// Release one block (erts_thr_progress_unblock)
+    ///
+    /// The managed threads continue when the last block is released.
+    pub fn unblock(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.block_count = state.block_count.saturating_sub(1);
+        if state.block_count == 0 {
+            self.changed.notify_all();
+        }
+    }
+
+    /// Check if a block is in progress (erts_thr_progress_is_blocking)
+    pub fn is_blocking(&self) -> bool {
+        self.state.lock().unwrap().block_count > 0
+    }
+}
+
+impl Default for ThrProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Global thread progress instance
+static GLOBAL_THR_PROGRESS: OnceLock<ThrProgress> = OnceLock::new();
+
+/// Get the global thread progress tracker
+pub fn get_global_thr_progress() -> &'static ThrProgress {
+    GLOBAL_THR_PROGRESS.get_or_init(ThrProgress::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_later_op_waits_for_all_threads() {
+        let progress = ThrProgress::new();
+        let a = progress.register_managed_thread();
+        let b = progress.register_managed_thread();
+        let freed = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&freed);
+        let value = progress.schedule_later_op(Box::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }));
+        assert_eq!(value, progress.current() + 2);
+
+        // Thread a alone cannot advance progress past thread b
+        for _ in 0..5 {
+            assert_eq!(progress.update(a), 0);
+        }
+        assert!(!progress.has_reached(value));
+
+        progress.update(b);
+        progress.update(a);
+        progress.update(b);
+        assert!(progress.has_reached(value));
+        progress.update(a);
+        assert_eq!(freed.load(Ordering::SeqCst), 1);
+        assert_eq!(progress.pending_later_ops(), 0);
+    }
+
+    #[test]
+    fn test_sleeping_thread_does_not_hold_progress() {
+        let progress = ThrProgress::new();
+        let a = progress.register_managed_thread();
+        let b = progress.register_managed_thread();
+        let value = progress.later();
+        progress.prepare_wait(b);
+        progress.update(a);
+        progress.update(a);
+        assert!(progress.has_reached(value));
+
+        progress.finalize_wait(b);
+        let value = progress.later();
+        progress.update(a);
+        progress.update(a);
+        assert!(!progress.has_reached(value));
+    }
+
+    #[test]
+    fn test_later_op_without_active_threads_runs_at_once() {
+        let progress = ThrProgress::new();
+        let ran = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&ran);
+        progress.schedule_later_op(Box::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }));
+        assert_eq!(ran.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_block_parks_managed_threads() {
+        let progress = Arc::new(ThrProgress::new());
+        let index = progress.register_managed_thread();
+        let stop = Arc::new(AtomicUsize::new(0));
+        let worker = {
+            let progress = Arc::clone(&progress);
+            let stop = Arc::clone(&stop);
+            std::thread::spawn(move || {
+                while stop.load(Ordering::SeqCst) == 0 {
+                    progress.update(index);
+                    std::thread::yield_now();
+                }
+            })
+        };
+
+        progress.block(None);
+        assert!(progress.is_blocking());
+        let value = progress.current();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert_eq!(progress.current(), value);
+        stop.store(1, Ordering::SeqCst);
+        progress.unblock();
+        worker.join().unwrap();
+        assert!(!progress.is_blocking());
+    }
+}
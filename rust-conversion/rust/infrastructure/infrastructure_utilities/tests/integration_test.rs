@@ -743,3 +743,43 @@ fn test_rational_utils_comparison_workflow() {
     assert!((min.to_f64() - 0.25).abs() < 1e-10);
 }
 
+
+#[test]
+fn test_zlib_stream_with_dictionary_and_bounded_output() {
+    let dictionary = b"iron beam zlib stream dictionary";
+    let data: Vec<u8> = b"iron beam stream ".iter().cycle().take(10_000).copied().collect();
+
+    let mut deflater = ZlibDeflater::new(6, ZlibWindow::default()).unwrap();
+    let adler = deflater.set_dictionary(dictionary).unwrap();
+    let mut compressed = deflater.deflate(&data[..5_000], ZlibFlush::Sync).unwrap();
+    compressed.extend(deflater.deflate(&data[5_000..], ZlibFlush::Finish).unwrap());
+
+    let mut inflater = ZlibInflater::new(ZlibWindow::from_window_bits(47).unwrap());
+    assert_eq!(inflater.inflate_chunk(&compressed, 512), Err(CompressionError::NeedDictionary(adler)));
+    inflater.set_dictionary(dictionary).unwrap();
+    let mut output = Vec::new();
+    loop {
+        let (result, chunk) = inflater.inflate_chunk(&[], 512).unwrap();
+        assert!(chunk.len() <= 512);
+        output.extend(chunk);
+        if result == ChunkResult::Done {
+            break;
+        }
+    }
+    assert_eq!(output, data);
+}
+
+#[test]
+fn test_gzip_file_roundtrip() {
+    let path = std::env::temp_dir().join(format!("integration_gzip_file_{}.gz", std::process::id()));
+    let mut file = GzipFile::create(&path, CompressionLevel::BestSpeed).unwrap();
+    std::io::Write::write_all(&mut file, b"compressed file contents").unwrap();
+    file.finish().unwrap();
+
+    let contents = std::fs::read(&path).unwrap();
+    assert_eq!(gunzip(&contents).unwrap(), b"compressed file contents");
+    let mut output = String::new();
+    std::io::Read::read_to_string(&mut GzipFile::open(&path).unwrap(), &mut output).unwrap();
+    assert_eq!(output, "compressed file contents");
+    let _ = std::fs::remove_file(&path);
+}
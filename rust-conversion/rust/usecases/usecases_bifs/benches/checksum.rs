@@ -0,0 +1,38 @@
+//! Checksum Benchmarks
+//!
+//! Compares CRC32C, hardware accelerated where the CPU allows, and the
+//! 64-bit xxHash with the existing CRC32 path, for payloads from a small
+//! distribution fragment header up to a full 64 KiB fragment.
+//!
+//! Run with `cargo bench -p usecases_bifs --bench checksum`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use usecases_bifs::checksum::ChecksumBif;
+
+const SIZES: [usize; 5] = [16, 256, 4096, 16384, 65536];
+
+fn payload(size: usize) -> Vec<u8> {
+    (0..size).map(|i| (i * 31 % 251) as u8).collect()
+}
+
+fn bench_checksums(c: &mut Criterion) {
+    let mut group = c.benchmark_group("checksum");
+    for size in SIZES {
+        let data = payload(size);
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("crc32", size), &data, |b, data| {
+            b.iter(|| ChecksumBif::crc32(black_box(data)))
+        });
+        let crc32c = if ChecksumBif::crc32c_accelerated() { "crc32c_hw" } else { "crc32c_table" };
+        group.bench_with_input(BenchmarkId::new(crc32c, size), &data, |b, data| {
+            b.iter(|| ChecksumBif::crc32c(black_box(data)))
+        });
+        group.bench_with_input(BenchmarkId::new("xxhash64", size), &data, |b, data| {
+            b.iter(|| ChecksumBif::xxhash64(black_box(data), 0))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_checksums);
+criterion_main!(benches);
@@ -0,0 +1,303 @@
+//! Process Alias Built-in Functions
+//!
+//! Provides process aliases:
+//! - `erlang:alias/0,1`
+//! - `erlang:unalias/1`
+//! - Sending to an alias, locally and through the distribution
+//!
+//! An alias is a pid reference: a reference that also carries the pid of
+//! the process that created it. A message sent to an alias is delivered to
+//! that process only while the alias is active in its alias table; once the
+//! alias is deactivated with `unalias/1`, or after the first message for a
+//! `reply` alias, messages sent to it are silently dropped.
+//!
+//! On another node a send to an alias becomes the `ALIAS_SEND` control
+//! message `{33, FromPid, Alias}`, or `ALIAS_SEND_TT` with a trace token.
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ *
+ * Creation productivity increased for code in this file by using AALang and GAB.
+ * See https://github.com/yenrab/AALang-Gab
+ */
+
+use entities_data_handling::atom::AtomEncoding;
+use entities_data_handling::term_hashing::Term;
+use entities_process::{AliasMode, Eterm, Message, Process};
+use infrastructure_code_loading::encode_ref::ErlangRef;
+use infrastructure_utilities::atom_table::get_global_atom_table;
+use infrastructure_utilities::process_table::get_global_process_table;
+
+use crate::op::ErlangTerm;
+use crate::seq_trace::local_pid;
+use crate::unique::{Reference, UniqueBif};
+
+/// Distribution operation `ALIAS_SEND`: `{33, FromPid, Alias}`
+pub const DOP_ALIAS_SEND: i64 = 33;
+/// Distribution operation `ALIAS_SEND_TT`: `{34, FromPid, Alias, Token}`
+pub const DOP_ALIAS_SEND_TT: i64 = 34;
+
+/// Error type for alias operations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AliasError {
+    /// Bad argument (invalid option, or a control message that is not an alias send)
+    BadArgument(String),
+}
+
+impl std::fmt::Display for AliasError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AliasError::BadArgument(msg) => write!(f, "Bad argument: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AliasError {}
+
+/// Process alias BIF operations
+pub struct AliasBif;
+
+impl AliasBif {
+    /// Create an alias for the caller (alias/0)
+    ///
+    /// The alias stays active until `unalias/1` is called.
+    pub fn alias_0(process: &Process) -> Reference {
+        Self::create(process, AliasMode::ExplicitUnalias)
+    }
+
+    /// Create an alias for the caller with options (alias/1)
+    ///
+    /// # Arguments
+    /// * `process` - Calling process
+    /// * `options` - List of at most one of `explicit_unalias` and `reply`
+    ///
+    /// # Returns
+    /// * `Ok(Reference)` - The new alias
+    /// * `Err(AliasError::BadArgument)` - Invalid options
+    pub fn alias_1(process: &Process, options: &ErlangTerm) -> Result<Reference, AliasError> {
+        let items: &[ErlangTerm] = match options {
+            ErlangTerm::Nil => &[],
+            ErlangTerm::List(items) => items,
+            _ => return Err(AliasError::BadArgument("options must be a list".to_string())),
+        };
+        let mut mode = None;
+        for item in items {
+            let parsed = match item {
+                ErlangTerm::Atom(name) if name == "explicit_unalias" => AliasMode::ExplicitUnalias,
+                ErlangTerm::Atom(name) if name == "reply" => AliasMode::Reply,
+                _ => return Err(AliasError::BadArgument(format!("invalid alias option: {:?}", item))),
+            };
+            if mode.is_some_and(|mode| mode != parsed) {
+                return Err(AliasError::BadArgument("conflicting alias options".to_string()));
+            }
+            mode = Some(parsed);
+        }
+        Ok(Self::create(process, mode.unwrap_or(AliasMode::ExplicitUnalias)))
+    }
+
+    /// Deactivate an alias of the caller (unalias/1)
+    ///
+    /// # Returns
+    /// `true` if the alias was active
+    pub fn unalias_1(process: &Process, alias: &Reference) -> bool {
+        alias.pid() == Some(process.id()) && process.remove_alias(alias.alias_key())
+    }
+
+    /// Send a message to an alias (`Alias ! Message`)
+    ///
+    /// The message is dropped if the alias is not a pid reference of a live
+    /// local process or is no longer active.
+    ///
+    /// # Returns
+    /// `true` if the message was delivered
+    pub fn send(alias: &Reference, payload: Eterm) -> bool {
+        Self::deliver(alias, Message::new(payload))
+    }
+
+    /// Build the distribution control message for sending to an alias on
+    /// another node
+    ///
+    /// # Arguments
+    /// * `sender` - Sending process; its trace token goes with the message
+    /// * `alias` - Alias on the other node
+    pub fn dist_send_control(sender: &Process, alias: &Reference) -> Term {
+        let token = sender.seq_trace_token();
+        let op = if token.is_some() { DOP_ALIAS_SEND_TT } else { DOP_ALIAS_SEND };
+        let mut control = vec![Term::Small(op), local_pid(sender.id()), reference_term(alias)];
+        if let Some(token) = token {
+            control.push(Term::Tuple(vec![
+                Term::Small(token.flags as i64),
+                Term::Small(token.label as i64),
+                Term::Small(token.serial as i64),
+                local_pid(token.sender),
+                Term::Small(token.last_count as i64),
+            ]));
+        }
+        Term::Tuple(control)
+    }
+
+    /// Deliver a message that arrived from another node with an alias send
+    /// control message
+    ///
+    /// # Returns
+    /// * `Ok(true)` - The message was delivered
+    /// * `Ok(false)` - The alias was not active and the message was dropped
+    /// * `Err(AliasError::BadArgument)` - `control` is not an alias send
+    pub fn dist_deliver(control: &Term, payload: Eterm) -> Result<bool, AliasError> {
+        let alias = match control {
+            Term::Tuple(elements) => match (elements.first(), elements.len()) {
+                (Some(Term::Small(DOP_ALIAS_SEND)), 3) | (Some(Term::Small(DOP_ALIAS_SEND_TT)), 4) => {
+                    reference_from_term(&elements[2])?
+                }
+                _ => return Err(AliasError::BadArgument("not an alias send".to_string())),
+            },
+            _ => return Err(AliasError::BadArgument("not an alias send".to_string())),
+        };
+        Ok(Self::deliver(&alias, Message::new(payload)))
+    }
+
+    /// Create and activate an alias for `process`
+    fn create(process: &Process, mode: AliasMode) -> Reference {
+        let alias = UniqueBif::make_pid_ref(process.id());
+        process.add_alias(alias.alias_key(), mode);
+        alias
+    }
+
+    /// Deliver `message` through `alias` if it is active
+    fn deliver(alias: &Reference, message: Message) -> bool {
+        let Some(process) = alias.pid().and_then(|pid| get_global_process_table().lookup(pid)) else {
+            return false;
+        };
+        if !process.use_alias(alias.alias_key()) {
+            return false;
+        }
+        process.send_message(message);
+        true
+    }
+}
+
+/// The reference as a `Term::Ref` for the external term format
+fn reference_term(reference: &Reference) -> Term {
+    let node = get_global_atom_table()
+        .put_index(reference.node().as_bytes(), AtomEncoding::Utf8, false)
+        .unwrap_or(0);
+    Term::Ref {
+        node: node as u32,
+        ids: reference.numbers().to_vec(),
+        creation: reference.creation(),
+    }
+}
+
+fn reference_from_term(term: &Term) -> Result<Reference, AliasError> {
+    let Term::Ref { node, ids, creation } = term else {
+        return Err(AliasError::BadArgument("alias must be a reference".to_string()));
+    };
+    let node = get_global_atom_table()
+        .get_name(*node as usize)
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| AliasError::BadArgument("unknown node atom".to_string()))?;
+    let external = ErlangRef {
+        node,
+        len: ids.len() as u16,
+        creation: *creation,
+        ids: ids.clone(),
+    };
+    Reference::from_external(&external).map_err(|e| AliasError::BadArgument(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn live(id: u64) -> Arc<Process> {
+        let process = Arc::new(Process::new(id));
+        get_global_process_table().insert(id, Arc::clone(&process));
+        process
+    }
+
+    #[test]
+    fn test_send_until_unalias() {
+        let process = live(41111);
+        let alias = AliasBif::alias_0(&process);
+        assert_eq!(alias.pid(), Some(41111));
+        assert!(AliasBif::send(&alias, 1));
+        assert!(AliasBif::send(&alias, 2));
+        assert!(AliasBif::unalias_1(&process, &alias));
+        assert!(!AliasBif::unalias_1(&process, &alias));
+        assert!(!AliasBif::send(&alias, 3));
+        assert_eq!(process.message_queue_len(), 2);
+        get_global_process_table().remove(41111);
+    }
+
+    #[test]
+    fn test_reply_alias_delivers_once() {
+        let process = live(41112);
+        let options = ErlangTerm::List(vec![ErlangTerm::Atom("reply".to_string())]);
+        let alias = AliasBif::alias_1(&process, &options).unwrap();
+        assert!(AliasBif::send(&alias, 1));
+        assert!(!AliasBif::send(&alias, 2));
+        assert_eq!(process.message_queue_len(), 1);
+        get_global_process_table().remove(41112);
+    }
+
+    #[test]
+    fn test_alias_1_bad_options() {
+        let process = Process::new(41113);
+        let conflicting = ErlangTerm::List(vec![
+            ErlangTerm::Atom("reply".to_string()),
+            ErlangTerm::Atom("explicit_unalias".to_string()),
+        ]);
+        assert!(AliasBif::alias_1(&process, &conflicting).is_err());
+        assert!(AliasBif::alias_1(&process, &ErlangTerm::Atom("reply".to_string())).is_err());
+        assert!(AliasBif::alias_1(&process, &ErlangTerm::Nil).is_ok());
+    }
+
+    #[test]
+    fn test_plain_reference_is_not_an_alias() {
+        let process = live(41114);
+        let reference = UniqueBif::make_ref();
+        process.add_alias(reference.alias_key(), AliasMode::ExplicitUnalias);
+        assert!(!AliasBif::send(&reference, 1));
+        get_global_process_table().remove(41114);
+    }
+
+    #[test]
+    fn test_dist_alias_send() {
+        let sender = Process::new(41115);
+        let receiver = live(41116);
+        let alias = AliasBif::alias_0(&receiver);
+
+        let control = AliasBif::dist_send_control(&sender, &alias);
+        let Term::Tuple(elements) = &control else { panic!("control must be a tuple") };
+        assert_eq!(elements[0], Term::Small(DOP_ALIAS_SEND));
+        assert!(matches!(&elements[2], Term::Ref { ids, .. } if ids.len() == 5));
+
+        assert_eq!(AliasBif::dist_deliver(&control, 9), Ok(true));
+        AliasBif::unalias_1(&receiver, &alias);
+        assert_eq!(AliasBif::dist_deliver(&control, 9), Ok(false));
+        assert!(AliasBif::dist_deliver(&Term::Tuple(vec![Term::Small(2)]), 9).is_err());
+        assert_eq!(receiver.message_queue_len(), 1);
+        get_global_process_table().remove(41116);
+    }
+}
@@ -0,0 +1,467 @@
+//! Arithmetic Built-in Functions
+//!
+//! Provides the arithmetic operators over integers and floats:
+//! - `+`, `-` and `*`, binary and unary
+//! - `/` (float division), `div` and `rem` (integer division)
+//! - `abs/1`
+//! - `band`, `bor`, `bxor`, `bnot`, `bsl` and `bsr` over integers
+//!
+//! Integers are kept small (`ErlangTerm::Integer`) while they fit in 64 bits.
+//! A result that overflows is promoted to a bignum (`ErlangTerm::BigInteger`),
+//! and a bignum result that fits is demoted again, so every integer has one
+//! representation. An operation mixing an integer and a float is done in
+//! floats.
+//!
+//! As in erl_arith.c, operands that are not numbers, division by zero, and
+//! float results or integer-to-float conversions that leave the finite range
+//! raise `badarith`. `abs/1` is a BIF rather than an operator, so it raises
+//! `badarg` instead.
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ *
+ * Creation productivity increased for code in this file by using AALang and GAB.
+ * See https://github.com/yenrab/AALang-Gab
+ */
+
+use crate::op::ErlangTerm;
+use entities_utilities::BigNumber;
+
+/// Error type for arithmetic operations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArithError {
+    /// Bad arithmetic (`badarith`): an operand is not a number of the right
+    /// kind, division by zero, or a float out of range
+    BadArith(String),
+    /// Bad argument (`badarg`) to an arithmetic BIF
+    BadArgument(String),
+}
+
+impl std::fmt::Display for ArithError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArithError::BadArith(msg) => write!(f, "Bad arithmetic: {}", msg),
+            ArithError::BadArgument(msg) => write!(f, "Bad argument: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ArithError {}
+
+/// Operand of an arithmetic operation
+enum Number {
+    Small(i64),
+    Big(BigNumber),
+    Float(f64),
+}
+
+impl Number {
+    fn from_term(term: &ErlangTerm) -> Option<Self> {
+        match term {
+            ErlangTerm::Integer(n) => Some(Number::Small(*n)),
+            ErlangTerm::BigInteger(bn) => Some(Number::Big(bn.clone())),
+            ErlangTerm::Float(f) => Some(Number::Float(*f)),
+            _ => None,
+        }
+    }
+
+    fn to_big(&self) -> Option<BigNumber> {
+        match self {
+            Number::Small(n) => Some(BigNumber::from_i64(*n)),
+            Number::Big(bn) => Some(bn.clone()),
+            Number::Float(_) => None,
+        }
+    }
+
+    fn to_float(&self) -> Result<f64, ArithError> {
+        match self {
+            Number::Small(n) => Ok(*n as f64),
+            Number::Big(bn) => bn
+                .to_f64()
+                .ok_or_else(|| ArithError::BadArith("integer too large for a float".to_string())),
+            Number::Float(f) => Ok(*f),
+        }
+    }
+}
+
+/// Arithmetic BIF operations
+pub struct ArithBif;
+
+impl ArithBif {
+    /// Add two numbers (`+`)
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::arith::ArithBif;
+    /// use usecases_bifs::op::ErlangTerm;
+    ///
+    /// let sum = ArithBif::plus(&ErlangTerm::Integer(i64::MAX), &ErlangTerm::Integer(1)).unwrap();
+    /// assert!(matches!(sum, ErlangTerm::BigInteger(_)));
+    /// ```
+    pub fn plus(lhs: &ErlangTerm, rhs: &ErlangTerm) -> Result<ErlangTerm, ArithError> {
+        Self::mixed(lhs, rhs, "+", i64::checked_add, BigNumber::plus, |a, b| a + b)
+    }
+
+    /// Subtract a number from another (`-`)
+    pub fn minus(lhs: &ErlangTerm, rhs: &ErlangTerm) -> Result<ErlangTerm, ArithError> {
+        Self::mixed(lhs, rhs, "-", i64::checked_sub, BigNumber::minus, |a, b| a - b)
+    }
+
+    /// Multiply two numbers (`*`)
+    pub fn times(lhs: &ErlangTerm, rhs: &ErlangTerm) -> Result<ErlangTerm, ArithError> {
+        Self::mixed(lhs, rhs, "*", i64::checked_mul, BigNumber::times, |a, b| a * b)
+    }
+
+    /// Divide two numbers as floats (`/`)
+    ///
+    /// # Returns
+    /// The quotient, always a float, or `badarith` if `rhs` is zero
+    pub fn divide(lhs: &ErlangTerm, rhs: &ErlangTerm) -> Result<ErlangTerm, ArithError> {
+        let (a, b) = Self::operands(lhs, rhs, "/")?;
+        let divisor = b.to_float()?;
+        if divisor == 0.0 {
+            return Err(ArithError::BadArith("division by zero".to_string()));
+        }
+        float_result(a.to_float()? / divisor)
+    }
+
+    /// Divide two integers, truncating towards zero (`div`)
+    ///
+    /// # Returns
+    /// The quotient, or `badarith` if an operand is not an integer or `rhs`
+    /// is zero
+    pub fn int_div(lhs: &ErlangTerm, rhs: &ErlangTerm) -> Result<ErlangTerm, ArithError> {
+        Self::integer_division(lhs, rhs, "div", i64::checked_div, BigNumber::div)
+    }
+
+    /// Remainder of integer division, with the sign of `lhs` (`rem`)
+    ///
+    /// # Returns
+    /// The remainder, or `badarith` if an operand is not an integer or `rhs`
+    /// is zero
+    pub fn rem(lhs: &ErlangTerm, rhs: &ErlangTerm) -> Result<ErlangTerm, ArithError> {
+        Self::integer_division(lhs, rhs, "rem", i64::checked_rem, BigNumber::rem)
+    }
+
+    /// Negate a number (unary `-`)
+    pub fn negate(arg: &ErlangTerm) -> Result<ErlangTerm, ArithError> {
+        Self::minus(&ErlangTerm::Integer(0), arg).map(|result| match (arg, result) {
+            // 0 - 0.0 is 0.0, but -0.0 is expected
+            (ErlangTerm::Float(f), _) => ErlangTerm::Float(-f),
+            (_, result) => result,
+        })
+    }
+
+    /// Check a number for unary `+`
+    ///
+    /// # Returns
+    /// The number itself, or `badarith` if it is not a number
+    pub fn unary_plus(arg: &ErlangTerm) -> Result<ErlangTerm, ArithError> {
+        match Number::from_term(arg) {
+            Some(_) => Ok(arg.clone()),
+            None => Err(ArithError::BadArith(format!("+{:?}", arg))),
+        }
+    }
+
+    /// Absolute value (`abs/1`)
+    ///
+    /// # Returns
+    /// The absolute value, or `badarg` if `arg` is not a number
+    pub fn abs(arg: &ErlangTerm) -> Result<ErlangTerm, ArithError> {
+        match Number::from_term(arg) {
+            Some(Number::Float(f)) => Ok(ErlangTerm::Float(f.abs())),
+            Some(Number::Small(n)) if n >= 0 => Ok(ErlangTerm::Integer(n)),
+            Some(_) if Self::is_negative(arg) => Self::negate(arg),
+            Some(_) => Ok(arg.clone()),
+            None => Err(ArithError::BadArgument(format!("abs({:?})", arg))),
+        }
+    }
+
+    /// Bitwise and of two integers (`band`)
+    pub fn band(lhs: &ErlangTerm, rhs: &ErlangTerm) -> Result<ErlangTerm, ArithError> {
+        Self::bitwise(lhs, rhs, "band", |a, b| a & b, BigNumber::bitand)
+    }
+
+    /// Bitwise or of two integers (`bor`)
+    pub fn bor(lhs: &ErlangTerm, rhs: &ErlangTerm) -> Result<ErlangTerm, ArithError> {
+        Self::bitwise(lhs, rhs, "bor", |a, b| a | b, BigNumber::bitor)
+    }
+
+    /// Bitwise exclusive or of two integers (`bxor`)
+    pub fn bxor(lhs: &ErlangTerm, rhs: &ErlangTerm) -> Result<ErlangTerm, ArithError> {
+        Self::bitwise(lhs, rhs, "bxor", |a, b| a ^ b, BigNumber::bitxor)
+    }
+
+    /// Bitwise complement of an integer (`bnot`)
+    pub fn bnot(arg: &ErlangTerm) -> Result<ErlangTerm, ArithError> {
+        match Number::from_term(arg) {
+            Some(Number::Small(n)) => Ok(ErlangTerm::Integer(!n)),
+            Some(Number::Big(bn)) => Ok(integer_result(bn.bitnot())),
+            _ => Err(ArithError::BadArith(format!("bnot {:?}", arg))),
+        }
+    }
+
+    /// Shift an integer left (`bsl`), or right for a negative shift
+    ///
+    /// # Returns
+    /// The shifted integer, or `badarith` if an operand is not an integer or
+    /// the result would exceed [`MAX_SHIFT_BITS`] bits
+    pub fn bsl(lhs: &ErlangTerm, rhs: &ErlangTerm) -> Result<ErlangTerm, ArithError> {
+        Self::shift(lhs, rhs, "bsl", false)
+    }
+
+    /// Shift an integer right (`bsr`), rounding towards negative infinity,
+    /// or left for a negative shift
+    pub fn bsr(lhs: &ErlangTerm, rhs: &ErlangTerm) -> Result<ErlangTerm, ArithError> {
+        Self::shift(lhs, rhs, "bsr", true)
+    }
+
+    fn is_negative(arg: &ErlangTerm) -> bool {
+        match arg {
+            ErlangTerm::Integer(n) => *n < 0,
+            ErlangTerm::BigInteger(bn) => !bn.is_positive() && !bn.is_zero(),
+            _ => false,
+        }
+    }
+
+    fn operands(lhs: &ErlangTerm, rhs: &ErlangTerm, op: &str) -> Result<(Number, Number), ArithError> {
+        match (Number::from_term(lhs), Number::from_term(rhs)) {
+            (Some(a), Some(b)) => Ok((a, b)),
+            _ => Err(ArithError::BadArith(format!("{:?} {} {:?}", lhs, op, rhs))),
+        }
+    }
+
+    /// Apply an operator: in 64 bits while it does not overflow, then as
+    /// bignums, and as floats if either operand is a float
+    fn mixed(
+        lhs: &ErlangTerm,
+        rhs: &ErlangTerm,
+        op: &str,
+        small: fn(i64, i64) -> Option<i64>,
+        big: fn(&BigNumber, &BigNumber) -> BigNumber,
+        float: fn(f64, f64) -> f64,
+    ) -> Result<ErlangTerm, ArithError> {
+        let (a, b) = Self::operands(lhs, rhs, op)?;
+        if let (Number::Small(x), Number::Small(y)) = (&a, &b) {
+            if let Some(result) = small(*x, *y) {
+                return Ok(ErlangTerm::Integer(result));
+            }
+        }
+        match (a.to_big(), b.to_big()) {
+            (Some(x), Some(y)) => Ok(integer_result(big(&x, &y))),
+            _ => float_result(float(a.to_float()?, b.to_float()?)),
+        }
+    }
+
+    /// Apply a bitwise operator, which never overflows 64 bits for small
+    /// operands
+    fn bitwise(
+        lhs: &ErlangTerm,
+        rhs: &ErlangTerm,
+        op: &str,
+        small: fn(i64, i64) -> i64,
+        big: fn(&BigNumber, &BigNumber) -> BigNumber,
+    ) -> Result<ErlangTerm, ArithError> {
+        let bad = || ArithError::BadArith(format!("{:?} {} {:?}", lhs, op, rhs));
+        let (a, b) = Self::operands(lhs, rhs, op)?;
+        if let (Number::Small(x), Number::Small(y)) = (&a, &b) {
+            return Ok(ErlangTerm::Integer(small(*x, *y)));
+        }
+        match (a.to_big(), b.to_big()) {
+            (Some(x), Some(y)) => Ok(integer_result(big(&x, &y))),
+            _ => Err(bad()),
+        }
+    }
+
+    fn shift(lhs: &ErlangTerm, rhs: &ErlangTerm, op: &str, right: bool) -> Result<ErlangTerm, ArithError> {
+        let bad = || ArithError::BadArith(format!("{:?} {} {:?}", lhs, op, rhs));
+        let (a, b) = Self::operands(lhs, rhs, op)?;
+        let (Some(value), Some(count)) = (a.to_big(), b.to_big()) else {
+            return Err(bad());
+        };
+        // A shift count beyond i64 only ever shifts everything out, or
+        // exceeds the size limit
+        let count = match count.to_i64() {
+            Some(count) => count,
+            None if count.is_positive() => i64::MAX,
+            None => i64::MIN + 1,
+        };
+        let left = if right { count.saturating_neg() } else { count };
+        if value.is_zero() {
+            return Ok(ErlangTerm::Integer(0));
+        }
+        if left > MAX_SHIFT_BITS {
+            return Err(bad());
+        }
+        if left < -MAX_SHIFT_BITS {
+            // Every bit is shifted out, leaving the sign
+            let negative = Self::is_negative(lhs);
+            return Ok(ErlangTerm::Integer(if negative { -1 } else { 0 }));
+        }
+        Ok(integer_result(value.lshift(left as i32)))
+    }
+
+    fn integer_division(
+        lhs: &ErlangTerm,
+        rhs: &ErlangTerm,
+        op: &str,
+        small: fn(i64, i64) -> Option<i64>,
+        big: fn(&BigNumber, &BigNumber) -> Option<BigNumber>,
+    ) -> Result<ErlangTerm, ArithError> {
+        let (a, b) = Self::operands(lhs, rhs, op)?;
+        let bad = || ArithError::BadArith(format!("{:?} {} {:?}", lhs, op, rhs));
+        let (Some(x), Some(y)) = (a.to_big(), b.to_big()) else {
+            return Err(bad());
+        };
+        if y.is_zero() {
+            return Err(bad());
+        }
+        // Only i64::MIN div -1 and i64::MIN rem -1 overflow in 64 bits
+        if let (Number::Small(x), Number::Small(y)) = (&a, &b) {
+            if let Some(result) = small(*x, *y) {
+                return Ok(ErlangTerm::Integer(result));
+            }
+        }
+        big(&x, &y).map(integer_result).ok_or_else(bad)
+    }
+}
+
+/// Largest number of bits an integer is shifted by
+///
+/// Shifting further left raises `badarith` rather than building an
+/// unbounded bignum, as the bignum size limit does in the emulator.
+pub const MAX_SHIFT_BITS: i64 = 1 << 24;
+
+/// Demote a bignum result to a small integer when it fits
+pub(crate) fn integer_result(value: BigNumber) -> ErlangTerm {
+    match value.to_i64() {
+        Some(n) => ErlangTerm::Integer(n),
+        None => ErlangTerm::BigInteger(value),
+    }
+}
+
+/// Check that a float result is finite
+fn float_result(value: f64) -> Result<ErlangTerm, ArithError> {
+    if value.is_finite() {
+        Ok(ErlangTerm::Float(value))
+    } else {
+        Err(ArithError::BadArith("float result out of range".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(n: i64) -> ErlangTerm {
+        ErlangTerm::Integer(n)
+    }
+
+    fn big(text: &str) -> ErlangTerm {
+        ErlangTerm::BigInteger(BigNumber::from_integer(text.parse().unwrap()))
+    }
+
+    #[test]
+    fn test_small_overflow_promotes_to_bignum() {
+        assert_eq!(ArithBif::plus(&int(2), &int(5)), Ok(int(7)));
+        let sum = ArithBif::plus(&int(i64::MAX), &int(1)).unwrap();
+        assert!(matches!(sum, ErlangTerm::BigInteger(_)));
+        assert_eq!(sum, big("9223372036854775808"));
+        assert_eq!(ArithBif::minus(&int(i64::MIN), &int(1)), Ok(big("-9223372036854775809")));
+        assert_eq!(ArithBif::times(&int(1 << 40), &int(1 << 40)), Ok(big("1208925819614629174706176")));
+        assert_eq!(ArithBif::negate(&int(i64::MIN)), Ok(big("9223372036854775808")));
+        assert_eq!(ArithBif::abs(&int(i64::MIN)), Ok(big("9223372036854775808")));
+        assert_eq!(ArithBif::int_div(&int(i64::MIN), &int(-1)), Ok(big("9223372036854775808")));
+        assert_eq!(ArithBif::rem(&int(i64::MIN), &int(-1)), Ok(int(0)));
+    }
+
+    #[test]
+    fn test_bignum_results_demote_to_small() {
+        let result = ArithBif::minus(&big("9223372036854775808"), &int(1)).unwrap();
+        assert!(matches!(result, ErlangTerm::Integer(i64::MAX)));
+        let result = ArithBif::int_div(&big("18446744073709551616"), &big("4294967296")).unwrap();
+        assert!(matches!(result, ErlangTerm::Integer(4294967296)));
+        assert_eq!(ArithBif::abs(&big("-9223372036854775808")), Ok(big("9223372036854775808")));
+        assert_eq!(ArithBif::negate(&big("9223372036854775808")), Ok(int(i64::MIN)));
+    }
+
+    #[test]
+    fn test_integer_division() {
+        assert_eq!(ArithBif::int_div(&int(-7), &int(2)), Ok(int(-3)));
+        assert_eq!(ArithBif::rem(&int(-7), &int(2)), Ok(int(-1)));
+        assert_eq!(ArithBif::rem(&big("-100000000000000000001"), &int(10)), Ok(int(-1)));
+        assert!(matches!(ArithBif::int_div(&int(1), &int(0)), Err(ArithError::BadArith(_))));
+        assert!(matches!(ArithBif::rem(&big("100000000000000000000"), &int(0)), Err(ArithError::BadArith(_))));
+        assert!(matches!(ArithBif::int_div(&ErlangTerm::Float(4.0), &int(2)), Err(ArithError::BadArith(_))));
+    }
+
+    #[test]
+    fn test_mixed_float_arithmetic() {
+        assert_eq!(ArithBif::plus(&int(1), &ErlangTerm::Float(0.5)), Ok(ErlangTerm::Float(1.5)));
+        assert_eq!(ArithBif::times(&big("100000000000000000000"), &ErlangTerm::Float(0.5)), Ok(ErlangTerm::Float(5.0e19)));
+        assert_eq!(ArithBif::divide(&int(1), &int(4)), Ok(ErlangTerm::Float(0.25)));
+        assert_eq!(ArithBif::negate(&ErlangTerm::Float(0.0)), Ok(ErlangTerm::Float(-0.0)));
+        assert_eq!(ArithBif::abs(&ErlangTerm::Float(-2.5)), Ok(ErlangTerm::Float(2.5)));
+
+        // Float results and conversions must stay finite
+        assert!(matches!(ArithBif::times(&ErlangTerm::Float(1.0e308), &int(10)), Err(ArithError::BadArith(_))));
+        assert!(matches!(ArithBif::divide(&int(1), &ErlangTerm::Float(0.0)), Err(ArithError::BadArith(_))));
+        let huge = ArithBif::times(&big("100000000000000000000"), &big(&format!("1{}", "0".repeat(300)))).unwrap();
+        assert!(matches!(ArithBif::plus(&huge, &ErlangTerm::Float(1.0)), Err(ArithError::BadArith(_))));
+    }
+
+    #[test]
+    fn test_non_numbers() {
+        let atom = ErlangTerm::Atom("a".to_string());
+        assert!(matches!(ArithBif::plus(&atom, &int(1)), Err(ArithError::BadArith(_))));
+        assert!(matches!(ArithBif::unary_plus(&atom), Err(ArithError::BadArith(_))));
+        assert!(matches!(ArithBif::negate(&ErlangTerm::Nil), Err(ArithError::BadArith(_))));
+        assert!(matches!(ArithBif::abs(&atom), Err(ArithError::BadArgument(_))));
+        assert_eq!(ArithBif::unary_plus(&int(3)), Ok(int(3)));
+    }
+
+    #[test]
+    fn test_bitwise() {
+        assert_eq!(ArithBif::band(&int(0b1100), &int(0b1010)), Ok(int(0b1000)));
+        assert_eq!(ArithBif::bor(&int(0b1100), &int(0b1010)), Ok(int(0b1110)));
+        assert_eq!(ArithBif::bxor(&int(0b1100), &int(0b1010)), Ok(int(0b0110)));
+        assert_eq!(ArithBif::bnot(&int(5)), Ok(int(-6)));
+        assert_eq!(ArithBif::band(&big("340282366920938463463374607431768211455"), &int(0xFF)), Ok(int(0xFF)));
+        assert!(matches!(ArithBif::band(&ErlangTerm::Float(1.0), &int(1)), Err(ArithError::BadArith(_))));
+        assert!(matches!(ArithBif::bnot(&ErlangTerm::Float(1.0)), Err(ArithError::BadArith(_))));
+    }
+
+    #[test]
+    fn test_shifts() {
+        assert_eq!(ArithBif::bsl(&int(1), &int(4)), Ok(int(16)));
+        assert_eq!(ArithBif::bsl(&int(1), &int(64)), Ok(big("18446744073709551616")));
+        assert_eq!(ArithBif::bsr(&int(-9), &int(1)), Ok(int(-5)));
+        assert_eq!(ArithBif::bsr(&int(16), &int(-2)), Ok(int(64)));
+        assert_eq!(ArithBif::bsr(&int(-1), &big("100000000000000000000")), Ok(int(-1)));
+        assert!(matches!(ArithBif::bsr(&int(7), &int(i64::MIN)), Err(ArithError::BadArith(_))));
+        assert_eq!(ArithBif::bsl(&int(0), &int(i64::MAX)), Ok(int(0)));
+        // Results beyond the size limit are badarith
+        assert!(matches!(ArithBif::bsl(&int(1), &int(MAX_SHIFT_BITS + 1)), Err(ArithError::BadArith(_))));
+        assert!(matches!(ArithBif::bsl(&int(1), &ErlangTerm::Float(1.0)), Err(ArithError::BadArith(_))));
+    }
+}
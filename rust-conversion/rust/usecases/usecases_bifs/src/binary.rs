@@ -0,0 +1,742 @@
+//! Binary Built-in Functions
+//!
+//! Provides the searching and slicing functions of the `binary` module:
+//! - `binary:compile_pattern/1`
+//! - `binary:match/3` and `binary:matches/3`
+//! - `binary:split/3` and `binary:replace/4`
+//! - `binary:part/2` and `binary:copy/2`
+//! - `binary:longest_common_prefix/1` and `binary:longest_common_suffix/1`
+//!
+//! A single search pattern is compiled to a Boyer–Moore matcher and
+//! several patterns to an Aho–Corasick automaton, as in erl_bif_binary.c.
+//! Where several patterns match, the match starting first wins, and of
+//! those starting at the same position the longest.
+//!
+//! Parts returned by [`BinaryBif::split`] and [`BinaryBif::part`] are
+//! sub-binaries sharing the subject's data.
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ *
+ * Creation productivity increased for code in this file by using AALang and GAB.
+ * See https://github.com/yenrab/AALang-Gab
+ */
+
+use entities_data_handling::binary::RefcBinary;
+use std::ops::Range;
+
+/// Error type for binary BIF operations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BinaryError {
+    /// Bad argument (e.g., empty pattern or a part outside the subject)
+    BadArgument(String),
+}
+
+impl std::fmt::Display for BinaryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BinaryError::BadArgument(msg) => write!(f, "Bad argument: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for BinaryError {}
+
+/// Part of a binary (`{Start, Length}`)
+///
+/// A negative length selects the bytes before `start`, so `{Size, -N}`
+/// selects the last `N` bytes of a binary of size `Size`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Part {
+    /// Start offset in bytes
+    pub start: usize,
+    /// Length in bytes, negative to extend backwards from `start`
+    pub length: isize,
+}
+
+impl Part {
+    /// Create a part
+    pub fn new(start: usize, length: isize) -> Self {
+        Self { start, length }
+    }
+
+    /// Byte range of the part in a binary of `size` bytes
+    ///
+    /// # Returns
+    /// `None` if the part is not inside the binary
+    pub fn range(&self, size: usize) -> Option<Range<usize>> {
+        let range = if self.length >= 0 {
+            self.start..self.start.checked_add(self.length as usize)?
+        } else {
+            self.start.checked_sub(self.length.unsigned_abs())?..self.start
+        };
+        (range.end <= size).then_some(range)
+    }
+}
+
+/// Option of `binary:match/3` and `binary:matches/3`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchOption {
+    /// Only search this part of the subject (`{scope, Part}`)
+    Scope(Part),
+}
+
+/// Option of `binary:split/3`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitOption {
+    /// Split at every match instead of only the first (`global`)
+    Global,
+    /// Remove trailing empty parts (`trim`)
+    Trim,
+    /// Remove all empty parts (`trim_all`)
+    TrimAll,
+    /// Only split at matches in this part of the subject (`{scope, Part}`)
+    Scope(Part),
+}
+
+/// Option of `binary:replace/4`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplaceOption {
+    /// Replace every match instead of only the first (`global`)
+    Global,
+    /// Only replace matches in this part of the subject (`{scope, Part}`)
+    Scope(Part),
+    /// Insert the matched bytes at these positions of the replacement
+    /// (`{insert_replaced, InsPos}`)
+    InsertReplaced(Vec<usize>),
+}
+
+/// Compiled search pattern (`binary:compile_pattern/1`)
+#[derive(Debug, Clone)]
+pub struct CompiledPattern {
+    matcher: Matcher,
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    BoyerMoore(BoyerMoore),
+    AhoCorasick(AhoCorasick),
+}
+
+impl CompiledPattern {
+    /// Find the first match starting at or after `from` in `haystack`
+    ///
+    /// # Returns
+    /// Start and length of the match
+    fn find(&self, haystack: &[u8], from: usize) -> Option<(usize, usize)> {
+        match &self.matcher {
+            Matcher::BoyerMoore(bm) => bm.find(haystack, from).map(|start| (start, bm.pattern.len())),
+            Matcher::AhoCorasick(ac) => ac.find(haystack, from),
+        }
+    }
+
+    /// Non-overlapping matches in `range` of `subject`, from left to right
+    fn find_all(&self, subject: &[u8], range: Range<usize>, global: bool) -> Vec<(usize, usize)> {
+        let haystack = &subject[range.clone()];
+        let mut matches = Vec::new();
+        let mut from = 0;
+        while let Some((start, length)) = self.find(haystack, from) {
+            matches.push((range.start + start, length));
+            if !global {
+                break;
+            }
+            from = start + length;
+        }
+        matches
+    }
+}
+
+/// Boyer–Moore matcher for a single pattern
+#[derive(Debug, Clone)]
+struct BoyerMoore {
+    pattern: Vec<u8>,
+    /// Last position of each byte in the pattern, -1 if absent
+    bad_character: Box<[isize; 256]>,
+    /// Shift after a mismatch at each position, given the matched suffix
+    good_suffix: Vec<usize>,
+}
+
+impl BoyerMoore {
+    fn new(pattern: &[u8]) -> Self {
+        let m = pattern.len() as isize;
+        let mut bad_character = Box::new([-1isize; 256]);
+        for (i, &byte) in pattern.iter().enumerate() {
+            bad_character[byte as usize] = i as isize;
+        }
+
+        // suffixes[i]: length of the longest suffix of the pattern ending
+        // at position i
+        let at = |i: isize| pattern[i as usize];
+        let mut suffixes = vec![0isize; pattern.len()];
+        suffixes[(m - 1) as usize] = m;
+        let (mut f, mut g) = (0isize, m - 1);
+        for i in (0..m - 1).rev() {
+            if i > g && suffixes[(i + m - 1 - f) as usize] < i - g {
+                suffixes[i as usize] = suffixes[(i + m - 1 - f) as usize];
+            } else {
+                g = g.min(i);
+                f = i;
+                while g >= 0 && at(g) == at(g + m - 1 - f) {
+                    g -= 1;
+                }
+                suffixes[i as usize] = f - g;
+            }
+        }
+
+        let mut good_suffix = vec![m as usize; pattern.len()];
+        let mut j = 0;
+        for i in (-1..m - 1).rev() {
+            if i == -1 || suffixes[i as usize] == i + 1 {
+                while j < m - 1 - i {
+                    if good_suffix[j as usize] == m as usize {
+                        good_suffix[j as usize] = (m - 1 - i) as usize;
+                    }
+                    j += 1;
+                }
+            }
+        }
+        for i in 0..m - 1 {
+            good_suffix[(m - 1 - suffixes[i as usize]) as usize] = (m - 1 - i) as usize;
+        }
+
+        Self {
+            pattern: pattern.to_vec(),
+            bad_character,
+            good_suffix,
+        }
+    }
+
+    fn find(&self, haystack: &[u8], from: usize) -> Option<usize> {
+        let m = self.pattern.len();
+        let mut j = from;
+        while j + m <= haystack.len() {
+            let mut i = m as isize - 1;
+            while i >= 0 && self.pattern[i as usize] == haystack[j + i as usize] {
+                i -= 1;
+            }
+            if i < 0 {
+                return Some(j);
+            }
+            let bad = i - self.bad_character[haystack[j + i as usize] as usize];
+            j += self.good_suffix[i as usize].max(bad.max(1) as usize);
+        }
+        None
+    }
+}
+
+/// Aho–Corasick automaton for several patterns
+#[derive(Debug, Clone)]
+struct AhoCorasick {
+    /// Transition of each state on each byte
+    delta: Vec<[u32; 256]>,
+    /// Length of the pattern ending in each state, 0 if none
+    output: Vec<usize>,
+    /// Nearest state on the failure path with an output, 0 if none
+    dictionary: Vec<usize>,
+    /// Length of the longest pattern
+    longest: usize,
+}
+
+impl AhoCorasick {
+    fn new(patterns: &[&[u8]]) -> Self {
+        const ABSENT: u32 = u32::MAX;
+        let mut delta = vec![[ABSENT; 256]];
+        let mut output = vec![0];
+        for pattern in patterns {
+            let mut state = 0;
+            for &byte in pattern.iter() {
+                if delta[state][byte as usize] == ABSENT {
+                    delta[state][byte as usize] = delta.len() as u32;
+                    delta.push([ABSENT; 256]);
+                    output.push(0);
+                }
+                state = delta[state][byte as usize] as usize;
+            }
+            output[state] = pattern.len();
+        }
+
+        // Breadth-first, complete the transitions through failure links
+        let mut failure = vec![0usize; delta.len()];
+        let mut dictionary = vec![0usize; delta.len()];
+        let mut queue = std::collections::VecDeque::new();
+        for next in delta[0].iter_mut() {
+            match *next {
+                ABSENT => *next = 0,
+                child => queue.push_back(child as usize),
+            }
+        }
+        while let Some(state) = queue.pop_front() {
+            // The failure state is shallower, so its transitions are complete
+            let fallbacks = delta[failure[state]];
+            for (next, fallback) in delta[state].iter_mut().zip(fallbacks) {
+                match *next {
+                    ABSENT => *next = fallback,
+                    child => {
+                        let child = child as usize;
+                        failure[child] = fallback as usize;
+                        let fail = failure[child];
+                        dictionary[child] = if output[fail] > 0 { fail } else { dictionary[fail] };
+                        queue.push_back(child);
+                    }
+                }
+            }
+        }
+
+        Self {
+            delta,
+            output,
+            dictionary,
+            longest: patterns.iter().map(|p| p.len()).max().unwrap_or(0),
+        }
+    }
+
+    fn find(&self, haystack: &[u8], from: usize) -> Option<(usize, usize)> {
+        let mut best: Option<(usize, usize)> = None;
+        let mut state = 0;
+        for (i, &byte) in haystack.iter().enumerate().skip(from) {
+            state = self.delta[state][byte as usize] as usize;
+            let mut found = if self.output[state] > 0 { state } else { self.dictionary[state] };
+            while found != 0 {
+                let length = self.output[found];
+                let start = i + 1 - length;
+                // Matches of patterns longer than the scanned input are
+                // not in the haystack from `from`
+                if start >= from && best.is_none_or(|(s, l)| start < s || (start == s && length > l)) {
+                    best = Some((start, length));
+                }
+                found = self.dictionary[found];
+            }
+            // No later match can start at or before the best one
+            if best.is_some_and(|(s, _)| i + 1 >= s + self.longest) {
+                break;
+            }
+        }
+        best
+    }
+}
+
+/// Binary Built-in Functions
+pub struct BinaryBif;
+
+impl BinaryBif {
+    /// Compile search patterns (`binary:compile_pattern/1`)
+    ///
+    /// # Arguments
+    /// * `patterns` - One or more non-empty patterns
+    ///
+    /// # Returns
+    /// * `Ok(CompiledPattern)` - Boyer–Moore for one pattern, Aho–Corasick
+    ///   for several
+    /// * `Err(BinaryError)` - If there are no patterns or one is empty
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::binary::BinaryBif;
+    ///
+    /// let pattern = BinaryBif::compile_pattern(&[b"bcde", b"cd"]).unwrap();
+    /// assert_eq!(BinaryBif::match_(b"abcde", &pattern, &[]).unwrap(), Some((1, 4)));
+    /// assert!(BinaryBif::compile_pattern(&[b""]).is_err());
+    /// ```
+    pub fn compile_pattern(patterns: &[&[u8]]) -> Result<CompiledPattern, BinaryError> {
+        if patterns.is_empty() || patterns.iter().any(|p| p.is_empty()) {
+            return Err(BinaryError::BadArgument("patterns must be non-empty".to_string()));
+        }
+        let matcher = match patterns {
+            [pattern] => Matcher::BoyerMoore(BoyerMoore::new(pattern)),
+            _ => Matcher::AhoCorasick(AhoCorasick::new(patterns)),
+        };
+        Ok(CompiledPattern { matcher })
+    }
+
+    /// Find the first match (`binary:match/3`)
+    ///
+    /// # Returns
+    /// * `Ok(Some((position, length)))` - The match
+    /// * `Ok(None)` - No match (`nomatch`)
+    /// * `Err(BinaryError)` - If the scope is outside the subject
+    pub fn match_(
+        subject: &[u8],
+        pattern: &CompiledPattern,
+        options: &[MatchOption],
+    ) -> Result<Option<(usize, usize)>, BinaryError> {
+        let range = Self::scope(subject, options.iter().map(|MatchOption::Scope(part)| *part).next_back())?;
+        Ok(pattern.find_all(subject, range, false).pop())
+    }
+
+    /// Find all non-overlapping matches (`binary:matches/3`)
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::binary::BinaryBif;
+    ///
+    /// let pattern = BinaryBif::compile_pattern(&[b"bcde", b"bc", b"de"]).unwrap();
+    /// assert_eq!(BinaryBif::matches(b"abcde", &pattern, &[]).unwrap(), vec![(1, 4)]);
+    /// ```
+    pub fn matches(
+        subject: &[u8],
+        pattern: &CompiledPattern,
+        options: &[MatchOption],
+    ) -> Result<Vec<(usize, usize)>, BinaryError> {
+        let range = Self::scope(subject, options.iter().map(|MatchOption::Scope(part)| *part).next_back())?;
+        Ok(pattern.find_all(subject, range, true))
+    }
+
+    /// Split a binary at matches (`binary:split/3`)
+    ///
+    /// # Returns
+    /// Sub-binaries of `subject` between the matches
+    ///
+    /// # Examples
+    /// ```
+    /// use entities_data_handling::binary::RefcBinary;
+    /// use usecases_bifs::binary::{BinaryBif, SplitOption};
+    ///
+    /// let subject = RefcBinary::new(vec![0, 1, 0, 0, 4, 255, 255, 9]);
+    /// let pattern = BinaryBif::compile_pattern(&[&[0, 0], &[255, 255]]).unwrap();
+    /// let parts = BinaryBif::split(&subject, &pattern, &[SplitOption::Global]).unwrap();
+    /// let parts: Vec<&[u8]> = parts.iter().map(|p| p.data()).collect();
+    /// assert_eq!(parts, vec![&[0, 1][..], &[4], &[9]]);
+    /// ```
+    pub fn split(
+        subject: &RefcBinary,
+        pattern: &CompiledPattern,
+        options: &[SplitOption],
+    ) -> Result<Vec<RefcBinary>, BinaryError> {
+        let (mut global, mut trim, mut trim_all, mut scope) = (false, false, false, None);
+        for option in options {
+            match option {
+                SplitOption::Global => global = true,
+                SplitOption::Trim => trim = true,
+                SplitOption::TrimAll => trim_all = true,
+                SplitOption::Scope(part) => scope = Some(*part),
+            }
+        }
+        let range = Self::scope(subject.data(), scope)?;
+        let mut parts = Vec::new();
+        let mut last = 0;
+        for (start, length) in pattern.find_all(subject.data(), range, global) {
+            parts.push(Self::sub_binary(subject, last..start));
+            last = start + length;
+        }
+        parts.push(Self::sub_binary(subject, last..subject.len()));
+        if trim_all {
+            parts.retain(|part| !part.is_empty());
+        } else if trim {
+            while parts.last().is_some_and(RefcBinary::is_empty) {
+                parts.pop();
+            }
+        }
+        Ok(parts)
+    }
+
+    /// Replace matches (`binary:replace/4`)
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::binary::{BinaryBif, ReplaceOption};
+    ///
+    /// let pattern = BinaryBif::compile_pattern(&[b"b", b"d"]).unwrap();
+    /// let options = [ReplaceOption::Global, ReplaceOption::InsertReplaced(vec![1])];
+    /// assert_eq!(BinaryBif::replace(b"abcde", &pattern, b"[]", &options).unwrap(), b"a[b]c[d]e");
+    /// ```
+    pub fn replace(
+        subject: &[u8],
+        pattern: &CompiledPattern,
+        replacement: &[u8],
+        options: &[ReplaceOption],
+    ) -> Result<Vec<u8>, BinaryError> {
+        let (mut global, mut scope, mut insert) = (false, None, Vec::new());
+        for option in options {
+            match option {
+                ReplaceOption::Global => global = true,
+                ReplaceOption::Scope(part) => scope = Some(*part),
+                ReplaceOption::InsertReplaced(positions) => {
+                    if positions.iter().any(|&position| position > replacement.len()) {
+                        return Err(BinaryError::BadArgument("insert position outside replacement".to_string()));
+                    }
+                    insert = positions.clone();
+                    insert.sort_unstable();
+                }
+            }
+        }
+        let range = Self::scope(subject, scope)?;
+        let mut result = Vec::with_capacity(subject.len());
+        let mut last = 0;
+        for (start, length) in pattern.find_all(subject, range, global) {
+            result.extend_from_slice(&subject[last..start]);
+            let mut copied = 0;
+            for &position in &insert {
+                result.extend_from_slice(&replacement[copied..position]);
+                result.extend_from_slice(&subject[start..start + length]);
+                copied = position;
+            }
+            result.extend_from_slice(&replacement[copied..]);
+            last = start + length;
+        }
+        result.extend_from_slice(&subject[last..]);
+        Ok(result)
+    }
+
+    /// Extract a part of a binary (`binary:part/2`)
+    ///
+    /// # Returns
+    /// * `Ok(RefcBinary)` - Sub-binary sharing the subject's data
+    /// * `Err(BinaryError)` - If the part is outside the subject
+    ///
+    /// # Examples
+    /// ```
+    /// use entities_data_handling::binary::RefcBinary;
+    /// use usecases_bifs::binary::{BinaryBif, Part};
+    ///
+    /// let subject = RefcBinary::new((1..=10).collect());
+    /// let tail = BinaryBif::part(&subject, Part::new(10, -5)).unwrap();
+    /// assert_eq!(tail.data(), &[6, 7, 8, 9, 10]);
+    /// ```
+    pub fn part(subject: &RefcBinary, part: Part) -> Result<RefcBinary, BinaryError> {
+        let range = Self::scope(subject.data(), Some(part))?;
+        Ok(Self::sub_binary(subject, range))
+    }
+
+    /// Copy a binary, repeated `times` times (`binary:copy/2`)
+    ///
+    /// The result never shares data with the subject, so copying a small
+    /// part of a large binary lets the large binary be freed.
+    pub fn copy(subject: &[u8], times: usize) -> RefcBinary {
+        RefcBinary::new(subject.repeat(times))
+    }
+
+    /// Length of the longest common prefix (`binary:longest_common_prefix/1`)
+    ///
+    /// # Returns
+    /// * `Ok(length)` - The length in bytes
+    /// * `Err(BinaryError)` - If `binaries` is empty
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::binary::BinaryBif;
+    ///
+    /// assert_eq!(BinaryBif::longest_common_prefix(&[b"erlang", b"ergonomy"]).unwrap(), 2);
+    /// assert_eq!(BinaryBif::longest_common_suffix(&[b"erlang", b"fang"]).unwrap(), 3);
+    /// ```
+    pub fn longest_common_prefix(binaries: &[&[u8]]) -> Result<usize, BinaryError> {
+        Self::longest_common(binaries, |binary, i| binary[i])
+    }
+
+    /// Length of the longest common suffix (`binary:longest_common_suffix/1`)
+    pub fn longest_common_suffix(binaries: &[&[u8]]) -> Result<usize, BinaryError> {
+        Self::longest_common(binaries, |binary, i| binary[binary.len() - 1 - i])
+    }
+
+    fn longest_common(binaries: &[&[u8]], byte: impl Fn(&[u8], usize) -> u8) -> Result<usize, BinaryError> {
+        let (first, rest) = binaries
+            .split_first()
+            .ok_or_else(|| BinaryError::BadArgument("empty list of binaries".to_string()))?;
+        let shortest = binaries.iter().map(|binary| binary.len()).min().unwrap_or(0);
+        Ok((0..shortest)
+            .take_while(|&i| rest.iter().all(|binary| byte(binary, i) == byte(first, i)))
+            .count())
+    }
+
+    /// Byte range of the scope, the whole subject by default
+    fn scope(subject: &[u8], scope: Option<Part>) -> Result<Range<usize>, BinaryError> {
+        match scope {
+            Some(part) => part
+                .range(subject.len())
+                .ok_or_else(|| BinaryError::BadArgument("part outside binary".to_string())),
+            None => Ok(0..subject.len()),
+        }
+    }
+
+    fn sub_binary(subject: &RefcBinary, range: Range<usize>) -> RefcBinary {
+        subject
+            .sub_binary(range.start, range.len())
+            .expect("range is inside the subject")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn datas(parts: &[RefcBinary]) -> Vec<&[u8]> {
+        parts.iter().map(|part| part.data()).collect()
+    }
+
+    /// Leftmost-longest match by brute force
+    fn naive_find(haystack: &[u8], patterns: &[&[u8]], from: usize) -> Option<(usize, usize)> {
+        (from..haystack.len()).find_map(|start| {
+            patterns
+                .iter()
+                .filter(|p| haystack[start..].starts_with(p))
+                .map(|p| (start, p.len()))
+                .max_by_key(|&(_, length)| length)
+        })
+    }
+
+    #[test]
+    fn test_part_ranges() {
+        assert_eq!(Part::new(2, 3).range(10), Some(2..5));
+        assert_eq!(Part::new(10, -5).range(10), Some(5..10));
+        assert_eq!(Part::new(10, 0).range(10), Some(10..10));
+        assert_eq!(Part::new(8, 3).range(10), None);
+        assert_eq!(Part::new(2, -3).range(10), None);
+        assert_eq!(Part::new(usize::MAX, 1).range(10), None);
+    }
+
+    #[test]
+    fn test_boyer_moore_against_naive_search() {
+        // Small alphabets give many partial matches and repeated suffixes
+        let mut seed = 12345u32;
+        let mut next = move |bound: u32| {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (seed >> 16) % bound
+        };
+        for _ in 0..500 {
+            let haystack: Vec<u8> = (0..next(60)).map(|_| b'a' + next(3) as u8).collect();
+            let pattern: Vec<u8> = (0..1 + next(6)).map(|_| b'a' + next(3) as u8).collect();
+            let compiled = BinaryBif::compile_pattern(&[&pattern]).unwrap();
+            for from in 0..=haystack.len() {
+                assert_eq!(
+                    compiled.find(&haystack, from),
+                    naive_find(&haystack, &[&pattern], from),
+                    "{:?} in {:?} from {}",
+                    pattern,
+                    haystack,
+                    from
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_aho_corasick_against_naive_search() {
+        let mut seed = 999u32;
+        let mut next = move |bound: u32| {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (seed >> 16) % bound
+        };
+        for _ in 0..500 {
+            let haystack: Vec<u8> = (0..next(60)).map(|_| b'a' + next(3) as u8).collect();
+            let patterns: Vec<Vec<u8>> = (0..2 + next(4))
+                .map(|_| (0..1 + next(5)).map(|_| b'a' + next(3) as u8).collect())
+                .collect();
+            let patterns: Vec<&[u8]> = patterns.iter().map(Vec::as_slice).collect();
+            let compiled = BinaryBif::compile_pattern(&patterns).unwrap();
+            for from in 0..=haystack.len() {
+                assert_eq!(
+                    compiled.find(&haystack, from),
+                    naive_find(&haystack, &patterns, from),
+                    "{:?} in {:?} from {}",
+                    patterns,
+                    haystack,
+                    from
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_match_and_matches() {
+        let pattern = BinaryBif::compile_pattern(&[b"bcde", b"cd"]).unwrap();
+        assert_eq!(BinaryBif::match_(b"abcde", &pattern, &[]), Ok(Some((1, 4))));
+        // The longer pattern does not fit in the scope
+        let scope = [MatchOption::Scope(Part::new(1, 3))];
+        assert_eq!(BinaryBif::match_(b"abcde", &pattern, &scope), Ok(Some((2, 2))));
+        let scope = [MatchOption::Scope(Part::new(5, -2))];
+        assert_eq!(BinaryBif::match_(b"abcde", &pattern, &scope), Ok(None));
+        let scope = [MatchOption::Scope(Part::new(4, 2))];
+        assert!(BinaryBif::match_(b"abcde", &pattern, &scope).is_err());
+
+        let pattern = BinaryBif::compile_pattern(&[b"aa"]).unwrap();
+        assert_eq!(BinaryBif::matches(b"aaaaa", &pattern, &[]), Ok(vec![(0, 2), (2, 2)]));
+        assert_eq!(BinaryBif::matches(b"b", &pattern, &[]), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_split() {
+        let subject = RefcBinary::new(vec![1, 255, 4, 0, 0, 0, 2, 3]);
+        let pattern = BinaryBif::compile_pattern(&[&[0, 0, 0], &[2]]).unwrap();
+        let parts = BinaryBif::split(&subject, &pattern, &[]).unwrap();
+        assert_eq!(datas(&parts), vec![&[1, 255, 4][..], &[2, 3]]);
+        assert!(parts.iter().all(|part| part.shares_storage(&subject)));
+
+        let subject = RefcBinary::new(vec![0, 1, 0, 0, 4, 255, 255, 9]);
+        let pattern = BinaryBif::compile_pattern(&[&[0, 0], &[255, 255]]).unwrap();
+        let options = [SplitOption::Global, SplitOption::Scope(Part::new(3, 4))];
+        let parts = BinaryBif::split(&subject, &pattern, &options).unwrap();
+        assert_eq!(datas(&parts), vec![&[0, 1, 0, 0, 4][..], &[9]]);
+
+        let subject = RefcBinary::new(b",a,,b,,".to_vec());
+        let comma = BinaryBif::compile_pattern(&[b","]).unwrap();
+        let split = |options: &[SplitOption]| {
+            let parts = BinaryBif::split(&subject, &comma, options).unwrap();
+            datas(&parts).iter().map(|part| part.to_vec()).collect::<Vec<_>>()
+        };
+        let global = split(&[SplitOption::Global]);
+        assert_eq!(global, vec![b"".to_vec(), b"a".to_vec(), b"".to_vec(), b"b".to_vec(), b"".to_vec(), b"".to_vec()]);
+        assert_eq!(split(&[SplitOption::Global, SplitOption::Trim]), global[..4].to_vec());
+        assert_eq!(split(&[SplitOption::Global, SplitOption::TrimAll]), vec![b"a".to_vec(), b"b".to_vec()]);
+        assert_eq!(split(&[]), vec![b"".to_vec(), b"a,,b,,".to_vec()]);
+    }
+
+    #[test]
+    fn test_replace() {
+        let b = BinaryBif::compile_pattern(&[b"b"]).unwrap();
+        let options = [ReplaceOption::InsertReplaced(vec![1])];
+        assert_eq!(BinaryBif::replace(b"abcde", &b, b"[]", &options).unwrap(), b"a[b]cde");
+
+        let bd = BinaryBif::compile_pattern(&[b"b", b"d"]).unwrap();
+        let options = [ReplaceOption::Global, ReplaceOption::InsertReplaced(vec![2, 1])];
+        assert_eq!(BinaryBif::replace(b"abcde", &bd, b"[-]", &options).unwrap(), b"a[b-b]c[d-d]e");
+        assert_eq!(BinaryBif::replace(b"abcde", &bd, b"", &[ReplaceOption::Global]).unwrap(), b"ace");
+        let scope = [ReplaceOption::Global, ReplaceOption::Scope(Part::new(2, 3))];
+        assert_eq!(BinaryBif::replace(b"abcde", &bd, b"x", &scope).unwrap(), b"abcxe");
+
+        let invalid = [ReplaceOption::InsertReplaced(vec![3])];
+        assert!(BinaryBif::replace(b"abcde", &b, b"[]", &invalid).is_err());
+    }
+
+    #[test]
+    fn test_part_and_copy() {
+        let subject = RefcBinary::new((1..=10).collect());
+        let part = BinaryBif::part(&subject, Part::new(2, 3)).unwrap();
+        assert_eq!(part.data(), &[3, 4, 5]);
+        assert!(part.shares_storage(&subject));
+        assert!(BinaryBif::part(&subject, Part::new(9, 2)).is_err());
+
+        let copy = BinaryBif::copy(part.data(), 2);
+        assert_eq!(copy.data(), &[3, 4, 5, 3, 4, 5]);
+        assert!(!copy.shares_storage(&subject));
+        assert!(BinaryBif::copy(b"abc", 0).is_empty());
+    }
+
+    #[test]
+    fn test_longest_common_prefix_and_suffix() {
+        assert_eq!(BinaryBif::longest_common_prefix(&[b"erlang", b"ergonomy"]), Ok(2));
+        assert_eq!(BinaryBif::longest_common_prefix(&[b"erlang", b"perl"]), Ok(0));
+        assert_eq!(BinaryBif::longest_common_prefix(&[b"erlang"]), Ok(6));
+        assert_eq!(BinaryBif::longest_common_suffix(&[b"erlang", b"fang"]), Ok(3));
+        assert_eq!(BinaryBif::longest_common_suffix(&[b"erlang", b"", b"ng"]), Ok(0));
+        assert!(BinaryBif::longest_common_prefix(&[]).is_err());
+    }
+}
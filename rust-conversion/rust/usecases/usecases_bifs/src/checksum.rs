@@ -1,6 +1,7 @@
 //! Checksum BIF Module
 //!
-//! Provides checksum built-in functions for CRC32, Adler32, and MD5.
+//! Provides checksum built-in functions for CRC32, CRC32C, Adler32, MD5
+//! and the 64-bit xxHash.
 //!
 //! This module implements checksum algorithms used by Erlang BIFs.
 //! Functions support incremental computation for large data streams.
@@ -406,6 +407,126 @@ impl ChecksumBif {
     pub fn md5_new() -> Md5Context {
         Md5Context::new()
     }
+
+    /// Calculate CRC32C (Castagnoli) checksum for data
+    ///
+    /// Uses the SSE4.2 or ARMv8 CRC instructions when the CPU has them,
+    /// and a table-driven implementation otherwise.
+    ///
+    /// # Arguments
+    /// * `data` - Input data to checksum
+    ///
+    /// # Returns
+    /// CRC32C checksum value
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::checksum::ChecksumBif;
+    ///
+    /// // Standard check value
+    /// assert_eq!(ChecksumBif::crc32c(b"123456789"), 0xe3069283);
+    ///
+    /// // CRC32C for empty data
+    /// assert_eq!(ChecksumBif::crc32c(b""), 0);
+    /// ```
+    pub fn crc32c(data: &[u8]) -> u32 {
+        Self::crc32c_with_initial(0, data)
+    }
+
+    /// Calculate CRC32C checksum starting from a previous value
+    ///
+    /// # Arguments
+    /// * `initial` - CRC32C of the preceding data
+    /// * `data` - Additional data to checksum
+    ///
+    /// # Returns
+    /// CRC32C checksum of the preceding data followed by `data`
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::checksum::ChecksumBif;
+    ///
+    /// let crc1 = ChecksumBif::crc32c(b"Hello");
+    /// let crc2 = ChecksumBif::crc32c_with_initial(crc1, b", world!");
+    /// assert_eq!(crc2, ChecksumBif::crc32c(b"Hello, world!"));
+    /// ```
+    pub fn crc32c_with_initial(initial: u32, data: &[u8]) -> u32 {
+        !crc32c_update(!initial, data)
+    }
+
+    /// Whether CRC32C is computed with CPU instructions on this machine
+    ///
+    /// # Returns
+    /// `true` if SSE4.2 (x86_64) or the ARMv8 CRC extension (aarch64) is
+    /// available
+    pub fn crc32c_accelerated() -> bool {
+        crc32c_hardware().is_some()
+    }
+
+    /// Calculate CRC32C checksum incrementally
+    ///
+    /// # Returns
+    /// A new `Crc32cContext` for incremental CRC32C computation
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::checksum::ChecksumBif;
+    ///
+    /// let mut ctx = ChecksumBif::crc32c_new();
+    /// ctx.update(b"1234");
+    /// ctx.update(b"56789");
+    /// assert_eq!(ctx.finalize(), 0xe3069283);
+    /// ```
+    pub fn crc32c_new() -> Crc32cContext {
+        Crc32cContext::new()
+    }
+
+    /// Calculate the 64-bit xxHash (XXH64) of data
+    ///
+    /// A fast non-cryptographic hash, used for instance to check
+    /// distribution fragments for corruption. It detects accidental
+    /// changes but gives no protection against deliberate ones.
+    ///
+    /// # Arguments
+    /// * `data` - Input data to hash
+    /// * `seed` - Seed of the hash
+    ///
+    /// # Returns
+    /// XXH64 hash value
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::checksum::ChecksumBif;
+    ///
+    /// assert_eq!(ChecksumBif::xxhash64(b"", 0), 0xef46db3751d8e999);
+    /// assert_ne!(ChecksumBif::xxhash64(b"data", 0), ChecksumBif::xxhash64(b"data", 1));
+    /// ```
+    pub fn xxhash64(data: &[u8], seed: u64) -> u64 {
+        let mut ctx = XxHash64Context::new(seed);
+        ctx.update(data);
+        ctx.finalize()
+    }
+
+    /// Calculate the 64-bit xxHash incrementally
+    ///
+    /// # Arguments
+    /// * `seed` - Seed of the hash
+    ///
+    /// # Returns
+    /// A new `XxHash64Context` for incremental XXH64 computation
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::checksum::ChecksumBif;
+    ///
+    /// let mut ctx = ChecksumBif::xxhash64_new(7);
+    /// ctx.update(b"fragment ");
+    /// ctx.update(b"payload");
+    /// assert_eq!(ctx.finalize(), ChecksumBif::xxhash64(b"fragment payload", 7));
+    /// ```
+    pub fn xxhash64_new(seed: u64) -> XxHash64Context {
+        XxHash64Context::new(seed)
+    }
 }
 
 /// Context for incremental MD5 computation
@@ -511,6 +632,284 @@ impl Md5Context {
     }
 }
 
+/// Context for incremental CRC32C computation
+#[derive(Debug, Clone)]
+pub struct Crc32cContext {
+    /// CRC register, inverted as during the computation
+    crc: u32,
+}
+
+impl Crc32cContext {
+    /// Create a new CRC32C context
+    pub fn new() -> Self {
+        Self::with_initial(0)
+    }
+
+    /// Create a context continuing from the CRC32C of preceding data
+    pub fn with_initial(initial: u32) -> Self {
+        Self { crc: !initial }
+    }
+
+    /// Update the context with additional data
+    pub fn update(&mut self, data: &[u8]) {
+        self.crc = crc32c_update(self.crc, data);
+    }
+
+    /// Finalize and return the CRC32C checksum
+    pub fn finalize(self) -> u32 {
+        !self.crc
+    }
+}
+
+impl Default for Crc32cContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// CRC32C polynomial, reflected
+const CRC32C_POLY: u32 = 0x82f63b78;
+
+/// Slicing-by-8 tables: `table[0]` advances the CRC by one byte, and
+/// `table[k]` by one byte followed by `k` zero bytes
+static CRC32C_TABLE: [[u32; 256]; 8] = crc32c_tables();
+
+const fn crc32c_tables() -> [[u32; 256]; 8] {
+    let mut table = [[0u32; 256]; 8];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ CRC32C_POLY } else { crc >> 1 };
+            bit += 1;
+        }
+        table[0][i] = crc;
+        i += 1;
+    }
+    let mut i = 0;
+    while i < 256 {
+        let mut k = 1;
+        while k < 8 {
+            let prev = table[k - 1][i];
+            table[k][i] = (prev >> 8) ^ table[0][(prev & 0xff) as usize];
+            k += 1;
+        }
+        i += 1;
+    }
+    table
+}
+
+/// CRC32C implementation using CPU instructions
+type Crc32cFn = unsafe fn(u32, &[u8]) -> u32;
+
+/// The CRC32C implementation using CPU instructions, if the CPU has them
+fn crc32c_hardware() -> Option<Crc32cFn> {
+    #[cfg(target_arch = "x86_64")]
+    if std::arch::is_x86_feature_detected!("sse4.2") {
+        return Some(crc32c_sse42);
+    }
+    #[cfg(target_arch = "aarch64")]
+    if std::arch::is_aarch64_feature_detected!("crc") {
+        return Some(crc32c_armv8);
+    }
+    None
+}
+
+/// Advance the (inverted) CRC register over `data`
+fn crc32c_update(crc: u32, data: &[u8]) -> u32 {
+    match crc32c_hardware() {
+        // Safety: the CPU supports the instructions used
+        Some(hardware) => unsafe { hardware(crc, data) },
+        None => crc32c_table(crc, data),
+    }
+}
+
+/// Table-driven CRC32C, processing eight bytes per step
+fn crc32c_table(mut crc: u32, data: &[u8]) -> u32 {
+    let table = &CRC32C_TABLE;
+    let mut chunks = data.chunks_exact(8);
+    for chunk in &mut chunks {
+        let low = crc ^ u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        let high = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+        crc = table[7][(low & 0xff) as usize]
+            ^ table[6][((low >> 8) & 0xff) as usize]
+            ^ table[5][((low >> 16) & 0xff) as usize]
+            ^ table[4][(low >> 24) as usize]
+            ^ table[3][(high & 0xff) as usize]
+            ^ table[2][((high >> 8) & 0xff) as usize]
+            ^ table[1][((high >> 16) & 0xff) as usize]
+            ^ table[0][(high >> 24) as usize];
+    }
+    for &byte in chunks.remainder() {
+        crc = table[0][((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    crc
+}
+
+/// CRC32C using the SSE4.2 `crc32` instruction
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.2")]
+unsafe fn crc32c_sse42(crc: u32, data: &[u8]) -> u32 {
+    use std::arch::x86_64::{_mm_crc32_u64, _mm_crc32_u8};
+
+    let mut crc = crc as u64;
+    let mut chunks = data.chunks_exact(8);
+    for chunk in &mut chunks {
+        let word = u64::from_le_bytes(chunk.try_into().unwrap());
+        crc = _mm_crc32_u64(crc, word);
+    }
+    let mut crc = crc as u32;
+    for &byte in chunks.remainder() {
+        crc = _mm_crc32_u8(crc, byte);
+    }
+    crc
+}
+
+/// CRC32C using the ARMv8 `crc32c` instructions
+#[cfg(target_arch = "aarch64")]
+#[target_feature(enable = "crc")]
+unsafe fn crc32c_armv8(mut crc: u32, data: &[u8]) -> u32 {
+    use std::arch::aarch64::{__crc32cb, __crc32cd};
+
+    let mut chunks = data.chunks_exact(8);
+    for chunk in &mut chunks {
+        let word = u64::from_le_bytes(chunk.try_into().unwrap());
+        crc = __crc32cd(crc, word);
+    }
+    for &byte in chunks.remainder() {
+        crc = __crc32cb(crc, byte);
+    }
+    crc
+}
+
+const XXH_PRIME64_1: u64 = 0x9e3779b185ebca87;
+const XXH_PRIME64_2: u64 = 0xc2b2ae3d27d4eb4f;
+const XXH_PRIME64_3: u64 = 0x165667b19e3779f9;
+const XXH_PRIME64_4: u64 = 0x85ebca77c2b2ae63;
+const XXH_PRIME64_5: u64 = 0x27d4eb2f165667c5;
+
+/// Context for incremental XXH64 computation
+#[derive(Debug, Clone)]
+pub struct XxHash64Context {
+    seed: u64,
+    /// Accumulators of the four lanes
+    acc: [u64; 4],
+    /// Total number of bytes hashed
+    total_len: u64,
+    /// Input not yet forming a full 32-byte stripe
+    buffer: [u8; 32],
+    buffered: usize,
+}
+
+impl XxHash64Context {
+    /// Create a new XXH64 context
+    ///
+    /// # Arguments
+    /// * `seed` - Seed of the hash
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            acc: [
+                seed.wrapping_add(XXH_PRIME64_1).wrapping_add(XXH_PRIME64_2),
+                seed.wrapping_add(XXH_PRIME64_2),
+                seed,
+                seed.wrapping_sub(XXH_PRIME64_1),
+            ],
+            total_len: 0,
+            buffer: [0; 32],
+            buffered: 0,
+        }
+    }
+
+    /// Update the context with additional data
+    pub fn update(&mut self, data: &[u8]) {
+        self.total_len += data.len() as u64;
+        let mut data = data;
+        if self.buffered > 0 {
+            let take = data.len().min(32 - self.buffered);
+            self.buffer[self.buffered..self.buffered + take].copy_from_slice(&data[..take]);
+            self.buffered += take;
+            data = &data[take..];
+            if self.buffered < 32 {
+                return;
+            }
+            let stripe = self.buffer;
+            self.stripe(&stripe);
+            self.buffered = 0;
+        }
+        let mut stripes = data.chunks_exact(32);
+        for stripe in &mut stripes {
+            self.stripe(stripe);
+        }
+        let rest = stripes.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buffered = rest.len();
+    }
+
+    /// Finalize and return the XXH64 hash
+    pub fn finalize(self) -> u64 {
+        let mut hash = if self.total_len >= 32 {
+            let [v1, v2, v3, v4] = self.acc;
+            let mut hash = v1
+                .rotate_left(1)
+                .wrapping_add(v2.rotate_left(7))
+                .wrapping_add(v3.rotate_left(12))
+                .wrapping_add(v4.rotate_left(18));
+            for v in self.acc {
+                hash = xxh64_merge_round(hash, v);
+            }
+            hash
+        } else {
+            self.seed.wrapping_add(XXH_PRIME64_5)
+        };
+        hash = hash.wrapping_add(self.total_len);
+
+        let mut rest = &self.buffer[..self.buffered];
+        while rest.len() >= 8 {
+            let lane = u64::from_le_bytes(rest[..8].try_into().unwrap());
+            hash ^= xxh64_round(0, lane);
+            hash = hash.rotate_left(27).wrapping_mul(XXH_PRIME64_1).wrapping_add(XXH_PRIME64_4);
+            rest = &rest[8..];
+        }
+        if rest.len() >= 4 {
+            let lane = u32::from_le_bytes(rest[..4].try_into().unwrap()) as u64;
+            hash ^= lane.wrapping_mul(XXH_PRIME64_1);
+            hash = hash.rotate_left(23).wrapping_mul(XXH_PRIME64_2).wrapping_add(XXH_PRIME64_3);
+            rest = &rest[4..];
+        }
+        for &byte in rest {
+            hash ^= (byte as u64).wrapping_mul(XXH_PRIME64_5);
+            hash = hash.rotate_left(11).wrapping_mul(XXH_PRIME64_1);
+        }
+
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(XXH_PRIME64_2);
+        hash ^= hash >> 29;
+        hash = hash.wrapping_mul(XXH_PRIME64_3);
+        hash ^ (hash >> 32)
+    }
+
+    /// Consume a full 32-byte stripe, eight bytes per lane
+    fn stripe(&mut self, stripe: &[u8]) {
+        for (acc, lane) in self.acc.iter_mut().zip(stripe.chunks_exact(8)) {
+            *acc = xxh64_round(*acc, u64::from_le_bytes(lane.try_into().unwrap()));
+        }
+    }
+}
+
+fn xxh64_round(acc: u64, lane: u64) -> u64 {
+    acc.wrapping_add(lane.wrapping_mul(XXH_PRIME64_2))
+        .rotate_left(31)
+        .wrapping_mul(XXH_PRIME64_1)
+}
+
+fn xxh64_merge_round(hash: u64, acc: u64) -> u64 {
+    (hash ^ xxh64_round(0, acc))
+        .wrapping_mul(XXH_PRIME64_1)
+        .wrapping_add(XXH_PRIME64_4)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -863,5 +1262,78 @@ mod tests {
         let hash2 = ChecksumBif::md5(b"test2");
         assert_ne!(hash1, hash2);
     }
-}
 
+    #[test]
+    fn test_crc32c_check_values() {
+        // RFC 3720 (iSCSI) test vectors
+        let incrementing: Vec<u8> = (0..32).collect();
+        let decrementing: Vec<u8> = (0..32).rev().collect();
+        assert_eq!(ChecksumBif::crc32c(b"123456789"), 0xe3069283);
+        assert_eq!(ChecksumBif::crc32c(&[0u8; 32]), 0x8a9136aa);
+        assert_eq!(ChecksumBif::crc32c(&[0xffu8; 32]), 0x62a8ab43);
+        assert_eq!(ChecksumBif::crc32c(&incrementing), 0x46dd794e);
+        assert_eq!(ChecksumBif::crc32c(&decrementing), 0x113fdb5c);
+    }
+
+    #[test]
+    fn test_crc32c_hardware_matches_table() {
+        let data: Vec<u8> = (0..1000u32).map(|i| (i * 31 % 251) as u8).collect();
+        if let Some(hardware) = crc32c_hardware() {
+            assert!(ChecksumBif::crc32c_accelerated());
+            // Every alignment and remainder length
+            for start in 0..8 {
+                for end in data.len() - 8..data.len() {
+                    let slice = &data[start..end];
+                    assert_eq!(unsafe { hardware(!0, slice) }, crc32c_table(!0, slice));
+                }
+            }
+        }
+        assert_eq!(!crc32c_table(!0, b"123456789"), 0xe3069283);
+    }
+
+    #[test]
+    fn test_crc32c_incremental() {
+        let data: Vec<u8> = (0..100u8).collect();
+        let expected = ChecksumBif::crc32c(&data);
+        for split in [0, 1, 7, 8, 9, 50, 100] {
+            let crc = ChecksumBif::crc32c(&data[..split]);
+            assert_eq!(ChecksumBif::crc32c_with_initial(crc, &data[split..]), expected);
+
+            let mut ctx = ChecksumBif::crc32c_new();
+            ctx.update(&data[..split]);
+            ctx.update(&data[split..]);
+            assert_eq!(ctx.finalize(), expected);
+        }
+        let mut ctx = Crc32cContext::with_initial(ChecksumBif::crc32c(b"1234"));
+        ctx.update(b"56789");
+        assert_eq!(ctx.finalize(), 0xe3069283);
+        assert_eq!(Crc32cContext::default().finalize(), 0);
+    }
+
+    #[test]
+    fn test_xxhash64_reference_values() {
+        assert_eq!(ChecksumBif::xxhash64(b"", 0), 0xef46db3751d8e999);
+        assert_eq!(ChecksumBif::xxhash64(b"a", 0), 0xd24ec4f1a98c6e5b);
+        assert_eq!(ChecksumBif::xxhash64(b"abc", 0), 0x44bc2cf5ad770999);
+        assert_eq!(
+            ChecksumBif::xxhash64(b"Nobody inspects the spammish repetition", 0),
+            0xfbcea83c8a378bf1
+        );
+        assert_ne!(ChecksumBif::xxhash64(b"abc", 1), ChecksumBif::xxhash64(b"abc", 0));
+    }
+
+    #[test]
+    fn test_xxhash64_incremental() {
+        let data: Vec<u8> = (0..200u32).map(|i| (i * 7) as u8).collect();
+        for len in [0, 3, 4, 8, 31, 32, 33, 64, 100, 200] {
+            let expected = ChecksumBif::xxhash64(&data[..len], 42);
+            for piece in [1, 5, 13, 32, 40] {
+                let mut ctx = ChecksumBif::xxhash64_new(42);
+                for chunk in data[..len].chunks(piece) {
+                    ctx.update(chunk);
+                }
+                assert_eq!(ctx.finalize(), expected, "length {} in pieces of {}", len, piece);
+            }
+        }
+    }
+}
@@ -0,0 +1,520 @@
+//! Crypto Built-in Functions
+//!
+//! Provides the digest and random number functions of the `crypto` module
+//! that the runtime itself needs, for instance for the challenge digests of
+//! the distribution handshake:
+//! - `crypto:hash/2`, `crypto:hash_init/1`, `crypto:hash_update/2` and
+//!   `crypto:hash_final/1`
+//! - `crypto:mac/4`, `crypto:mac_init/3`, `crypto:mac_update/2` and
+//!   `crypto:mac_final/1` for HMAC
+//! - `crypto:strong_rand_bytes/1`
+//! - `crypto:hash_equals/2`
+//!
+//! MD5, SHA-1, SHA-2 and SHA-3 are supported. The algorithms come from the
+//! RustCrypto crates and random bytes from the operating system's secure
+//! random number generator.
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ *
+ * Creation productivity increased for code in this file by using AALang and GAB.
+ * See https://github.com/yenrab/AALang-Gab
+ */
+
+use digest::core_api::BlockSizeUser;
+use digest::{Digest, FixedOutput, KeyInit, Update};
+use hmac::SimpleHmac;
+
+/// Hash algorithm, named by its `crypto` atom
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HashAlgorithm {
+    /// MD5 (`md5`)
+    Md5,
+    /// SHA-1 (`sha`)
+    Sha,
+    /// SHA-224 (`sha224`)
+    Sha224,
+    /// SHA-256 (`sha256`)
+    Sha256,
+    /// SHA-384 (`sha384`)
+    Sha384,
+    /// SHA-512 (`sha512`)
+    Sha512,
+    /// SHA3-224 (`sha3_224`)
+    Sha3_224,
+    /// SHA3-256 (`sha3_256`)
+    Sha3_256,
+    /// SHA3-384 (`sha3_384`)
+    Sha3_384,
+    /// SHA3-512 (`sha3_512`)
+    Sha3_512,
+}
+
+impl HashAlgorithm {
+    /// All supported algorithms, as returned by `crypto:supports(hashs)`
+    pub const ALL: [HashAlgorithm; 10] = [
+        HashAlgorithm::Md5,
+        HashAlgorithm::Sha,
+        HashAlgorithm::Sha224,
+        HashAlgorithm::Sha256,
+        HashAlgorithm::Sha384,
+        HashAlgorithm::Sha512,
+        HashAlgorithm::Sha3_224,
+        HashAlgorithm::Sha3_256,
+        HashAlgorithm::Sha3_384,
+        HashAlgorithm::Sha3_512,
+    ];
+
+    /// Look up an algorithm by its atom name
+    ///
+    /// # Returns
+    /// * `Ok(algorithm)` - The algorithm
+    /// * `Err(CryptoError::NotSupported)` - Unknown or unsupported algorithm
+    pub fn from_name(name: &str) -> Result<Self, CryptoError> {
+        Self::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.name() == name)
+            .ok_or_else(|| CryptoError::NotSupported(format!("hash algorithm {}", name)))
+    }
+
+    /// Atom name of the algorithm
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Md5 => "md5",
+            HashAlgorithm::Sha => "sha",
+            HashAlgorithm::Sha224 => "sha224",
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha384 => "sha384",
+            HashAlgorithm::Sha512 => "sha512",
+            HashAlgorithm::Sha3_224 => "sha3_224",
+            HashAlgorithm::Sha3_256 => "sha3_256",
+            HashAlgorithm::Sha3_384 => "sha3_384",
+            HashAlgorithm::Sha3_512 => "sha3_512",
+        }
+    }
+
+    /// Size of the digest in bytes
+    pub fn digest_size(&self) -> usize {
+        match self {
+            HashAlgorithm::Md5 => 16,
+            HashAlgorithm::Sha => 20,
+            HashAlgorithm::Sha224 | HashAlgorithm::Sha3_224 => 28,
+            HashAlgorithm::Sha256 | HashAlgorithm::Sha3_256 => 32,
+            HashAlgorithm::Sha384 | HashAlgorithm::Sha3_384 => 48,
+            HashAlgorithm::Sha512 | HashAlgorithm::Sha3_512 => 64,
+        }
+    }
+
+    /// Create the hash function
+    fn hasher(&self) -> Box<dyn HashFunction> {
+        match self {
+            HashAlgorithm::Md5 => Box::new(md5_digest::Md5::new()),
+            HashAlgorithm::Sha => Box::new(sha1::Sha1::new()),
+            HashAlgorithm::Sha224 => Box::new(sha2::Sha224::new()),
+            HashAlgorithm::Sha256 => Box::new(sha2::Sha256::new()),
+            HashAlgorithm::Sha384 => Box::new(sha2::Sha384::new()),
+            HashAlgorithm::Sha512 => Box::new(sha2::Sha512::new()),
+            HashAlgorithm::Sha3_224 => Box::new(sha3::Sha3_224::new()),
+            HashAlgorithm::Sha3_256 => Box::new(sha3::Sha3_256::new()),
+            HashAlgorithm::Sha3_384 => Box::new(sha3::Sha3_384::new()),
+            HashAlgorithm::Sha3_512 => Box::new(sha3::Sha3_512::new()),
+        }
+    }
+
+    /// Create the HMAC function keyed with `key`
+    fn hmac(&self, key: &[u8]) -> Box<dyn HashFunction> {
+        fn keyed<D>(key: &[u8]) -> Box<dyn HashFunction>
+        where
+            D: Digest + BlockSizeUser + Clone + Send + Sync + 'static,
+        {
+            // HMAC hashes keys longer than a block and pads shorter ones,
+            // so every key length is valid
+            Box::new(SimpleHmac::<D>::new_from_slice(key).expect("HMAC accepts keys of any length"))
+        }
+        match self {
+            HashAlgorithm::Md5 => keyed::<md5_digest::Md5>(key),
+            HashAlgorithm::Sha => keyed::<sha1::Sha1>(key),
+            HashAlgorithm::Sha224 => keyed::<sha2::Sha224>(key),
+            HashAlgorithm::Sha256 => keyed::<sha2::Sha256>(key),
+            HashAlgorithm::Sha384 => keyed::<sha2::Sha384>(key),
+            HashAlgorithm::Sha512 => keyed::<sha2::Sha512>(key),
+            HashAlgorithm::Sha3_224 => keyed::<sha3::Sha3_224>(key),
+            HashAlgorithm::Sha3_256 => keyed::<sha3::Sha3_256>(key),
+            HashAlgorithm::Sha3_384 => keyed::<sha3::Sha3_384>(key),
+            HashAlgorithm::Sha3_512 => keyed::<sha3::Sha3_512>(key),
+        }
+    }
+}
+
+/// Hash or HMAC function behind a [`HashState`] or [`MacState`]
+trait HashFunction: Send + Sync {
+    fn update(&mut self, data: &[u8]);
+    fn finalize(self: Box<Self>) -> Vec<u8>;
+    fn box_clone(&self) -> Box<dyn HashFunction>;
+}
+
+impl<T> HashFunction for T
+where
+    T: Update + FixedOutput + Clone + Send + Sync + 'static,
+{
+    fn update(&mut self, data: &[u8]) {
+        Update::update(self, data);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        FixedOutput::finalize_fixed(*self).to_vec()
+    }
+
+    fn box_clone(&self) -> Box<dyn HashFunction> {
+        Box::new(self.clone())
+    }
+}
+
+/// State of an incremental hash (`crypto:hash_init/1`)
+pub struct HashState {
+    algorithm: HashAlgorithm,
+    function: Box<dyn HashFunction>,
+}
+
+impl HashState {
+    /// Algorithm of the hash
+    pub fn algorithm(&self) -> HashAlgorithm {
+        self.algorithm
+    }
+
+    /// Add data to the hash (`crypto:hash_update/2`)
+    pub fn update(&mut self, data: &[u8]) {
+        self.function.update(data);
+    }
+
+    /// Finish the hash and return the digest (`crypto:hash_final/1`)
+    pub fn finalize(self) -> Vec<u8> {
+        self.function.finalize()
+    }
+}
+
+impl Clone for HashState {
+    fn clone(&self) -> Self {
+        Self {
+            algorithm: self.algorithm,
+            function: self.function.box_clone(),
+        }
+    }
+}
+
+impl std::fmt::Debug for HashState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HashState").field("algorithm", &self.algorithm).finish_non_exhaustive()
+    }
+}
+
+/// State of an incremental HMAC (`crypto:mac_init/3`)
+pub struct MacState {
+    algorithm: HashAlgorithm,
+    function: Box<dyn HashFunction>,
+}
+
+impl MacState {
+    /// Hash algorithm of the HMAC
+    pub fn algorithm(&self) -> HashAlgorithm {
+        self.algorithm
+    }
+
+    /// Add data to the HMAC (`crypto:mac_update/2`)
+    pub fn update(&mut self, data: &[u8]) {
+        self.function.update(data);
+    }
+
+    /// Finish the HMAC and return the tag (`crypto:mac_final/1`)
+    pub fn finalize(self) -> Vec<u8> {
+        self.function.finalize()
+    }
+}
+
+impl Clone for MacState {
+    fn clone(&self) -> Self {
+        Self {
+            algorithm: self.algorithm,
+            function: self.function.box_clone(),
+        }
+    }
+}
+
+impl std::fmt::Debug for MacState {
+    // The key stays out of debug output
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MacState").field("algorithm", &self.algorithm).finish_non_exhaustive()
+    }
+}
+
+/// Crypto built-in functions
+pub struct CryptoBif;
+
+impl CryptoBif {
+    /// Compute the digest of data (`crypto:hash/2`)
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::crypto::{CryptoBif, HashAlgorithm};
+    ///
+    /// let digest = CryptoBif::hash(HashAlgorithm::Sha256, b"abc");
+    /// assert_eq!(digest.len(), 32);
+    /// assert_eq!(&digest[..4], &[0xba, 0x78, 0x16, 0xbf]);
+    /// ```
+    pub fn hash(algorithm: HashAlgorithm, data: &[u8]) -> Vec<u8> {
+        let mut state = Self::hash_init(algorithm);
+        state.update(data);
+        state.finalize()
+    }
+
+    /// Start an incremental hash (`crypto:hash_init/1`)
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::crypto::{CryptoBif, HashAlgorithm};
+    ///
+    /// let mut state = CryptoBif::hash_init(HashAlgorithm::Sha3_256);
+    /// state.update(b"a");
+    /// state.update(b"bc");
+    /// assert_eq!(state.finalize(), CryptoBif::hash(HashAlgorithm::Sha3_256, b"abc"));
+    /// ```
+    pub fn hash_init(algorithm: HashAlgorithm) -> HashState {
+        HashState {
+            algorithm,
+            function: algorithm.hasher(),
+        }
+    }
+
+    /// Compute the HMAC of data (`crypto:mac(hmac, Algorithm, Key, Data)`)
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::crypto::{CryptoBif, HashAlgorithm};
+    ///
+    /// let tag = CryptoBif::mac(HashAlgorithm::Sha, b"key", b"message");
+    /// assert_eq!(tag.len(), 20);
+    /// assert_ne!(tag, CryptoBif::mac(HashAlgorithm::Sha, b"other key", b"message"));
+    /// ```
+    pub fn mac(algorithm: HashAlgorithm, key: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut state = Self::mac_init(algorithm, key);
+        state.update(data);
+        state.finalize()
+    }
+
+    /// Start an incremental HMAC (`crypto:mac_init(hmac, Algorithm, Key)`)
+    pub fn mac_init(algorithm: HashAlgorithm, key: &[u8]) -> MacState {
+        MacState {
+            algorithm,
+            function: algorithm.hmac(key),
+        }
+    }
+
+    /// Generate cryptographically strong random bytes
+    /// (`crypto:strong_rand_bytes/1`)
+    ///
+    /// # Returns
+    /// * `Ok(bytes)` - `size` random bytes
+    /// * `Err(CryptoError::LowEntropy)` - The operating system could not
+    ///   provide secure random bytes
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::crypto::CryptoBif;
+    ///
+    /// let bytes = CryptoBif::strong_rand_bytes(16).unwrap();
+    /// assert_eq!(bytes.len(), 16);
+    /// ```
+    pub fn strong_rand_bytes(size: usize) -> Result<Vec<u8>, CryptoError> {
+        let mut bytes = vec![0u8; size];
+        getrandom::fill(&mut bytes).map_err(|_| CryptoError::LowEntropy)?;
+        Ok(bytes)
+    }
+
+    /// Compare two digests in constant time (`crypto:hash_equals/2`)
+    ///
+    /// The time taken depends only on the length of the digests, not on
+    /// where they differ, so comparing a received MAC does not leak how
+    /// much of it is correct.
+    ///
+    /// # Returns
+    /// * `Ok(equal)` - Whether the digests are equal
+    /// * `Err(CryptoError::InvalidArgument)` - The digests differ in length
+    pub fn hash_equals(a: &[u8], b: &[u8]) -> Result<bool, CryptoError> {
+        if a.len() != b.len() {
+            return Err(CryptoError::InvalidArgument("digests of different sizes".to_string()));
+        }
+        let difference = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+        Ok(std::hint::black_box(difference) == 0)
+    }
+
+    /// Supported hash algorithms (`crypto:supports(hashs)`)
+    pub fn supported_hashes() -> &'static [HashAlgorithm] {
+        &HashAlgorithm::ALL
+    }
+}
+
+/// Error type for crypto BIF operations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CryptoError {
+    /// Invalid argument provided
+    InvalidArgument(String),
+    /// Algorithm not supported
+    NotSupported(String),
+    /// No secure random bytes available
+    LowEntropy,
+}
+
+impl std::fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CryptoError::InvalidArgument(msg) => write!(f, "Invalid argument: {}", msg),
+            CryptoError::NotSupported(msg) => write!(f, "Not supported: {}", msg),
+            CryptoError::LowEntropy => write!(f, "Low entropy"),
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_hash_reference_digests() {
+        let expected = [
+            (HashAlgorithm::Md5, "900150983cd24fb0d6963f7d28e17f72"),
+            (HashAlgorithm::Sha, "a9993e364706816aba3e25717850c26c9cd0d89d"),
+            (HashAlgorithm::Sha224, "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7"),
+            (HashAlgorithm::Sha256, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+            (
+                HashAlgorithm::Sha384,
+                "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed\
+                 8086072ba1e7cc2358baeca134c825a7",
+            ),
+            (
+                HashAlgorithm::Sha512,
+                "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+                 2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+            ),
+            (HashAlgorithm::Sha3_224, "e642824c3f8cf24ad09234ee7d3c766fc9a3a5168d0c94ad73b46fdf"),
+            (HashAlgorithm::Sha3_256, "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"),
+            (
+                HashAlgorithm::Sha3_384,
+                "ec01498288516fc926459f58e2c6ad8df9b473cb0fc08c2596da7cf0e49be4b2\
+                 98d88cea927ac7f539f1edf228376d25",
+            ),
+            (
+                HashAlgorithm::Sha3_512,
+                "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e\
+                 10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0",
+            ),
+        ];
+        for (algorithm, digest) in expected {
+            let computed = CryptoBif::hash(algorithm, b"abc");
+            assert_eq!(hex(&computed), digest, "{}", algorithm.name());
+            assert_eq!(computed.len(), algorithm.digest_size());
+        }
+    }
+
+    #[test]
+    fn test_hash_incremental() {
+        let data: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+        for &algorithm in CryptoBif::supported_hashes() {
+            let mut state = CryptoBif::hash_init(algorithm);
+            state.update(&data[..300]);
+            // A copy continues independently, as the state term in Erlang
+            let copy = state.clone();
+            state.update(&data[300..]);
+            assert_eq!(state.algorithm(), algorithm);
+            assert_eq!(state.finalize(), CryptoBif::hash(algorithm, &data));
+            assert_eq!(copy.finalize(), CryptoBif::hash(algorithm, &data[..300]));
+        }
+    }
+
+    #[test]
+    fn test_hash_algorithm_names() {
+        for &algorithm in CryptoBif::supported_hashes() {
+            assert_eq!(HashAlgorithm::from_name(algorithm.name()), Ok(algorithm));
+        }
+        assert_eq!(HashAlgorithm::from_name("sha"), Ok(HashAlgorithm::Sha));
+        assert!(matches!(HashAlgorithm::from_name("md4"), Err(CryptoError::NotSupported(_))));
+    }
+
+    #[test]
+    fn test_hmac_reference_tags() {
+        // RFC 2202 and RFC 4231 test case 2
+        let (key, data) = (b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(hex(&CryptoBif::mac(HashAlgorithm::Md5, key, data)), "750c783e6ab0b503eaa86e310a5db738");
+        assert_eq!(
+            hex(&CryptoBif::mac(HashAlgorithm::Sha, key, data)),
+            "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79"
+        );
+        assert_eq!(
+            hex(&CryptoBif::mac(HashAlgorithm::Sha256, key, data)),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+
+        // RFC 4231 test case 6: a key longer than the block size
+        let key = [0xaau8; 131];
+        let data = b"Test Using Larger Than Block-Size Key - Hash Key First";
+        assert_eq!(
+            hex(&CryptoBif::mac(HashAlgorithm::Sha256, &key, data)),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn test_hmac_incremental() {
+        for &algorithm in CryptoBif::supported_hashes() {
+            let mut state = CryptoBif::mac_init(algorithm, b"secret");
+            state.update(b"challenge ");
+            state.update(b"response");
+            let tag = state.finalize();
+            assert_eq!(tag, CryptoBif::mac(algorithm, b"secret", b"challenge response"));
+            assert_eq!(tag.len(), algorithm.digest_size());
+        }
+        let state = CryptoBif::mac_init(HashAlgorithm::Sha256, b"secret");
+        assert!(!format!("{:?}", state).contains("secret"));
+    }
+
+    #[test]
+    fn test_strong_rand_bytes() {
+        assert_eq!(CryptoBif::strong_rand_bytes(0), Ok(Vec::new()));
+        let first = CryptoBif::strong_rand_bytes(32).unwrap();
+        let second = CryptoBif::strong_rand_bytes(32).unwrap();
+        assert_eq!(first.len(), 32);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_hash_equals() {
+        assert_eq!(CryptoBif::hash_equals(b"digest", b"digest"), Ok(true));
+        assert_eq!(CryptoBif::hash_equals(b"digest", b"digesT"), Ok(false));
+        assert!(matches!(CryptoBif::hash_equals(b"a", b"ab"), Err(CryptoError::InvalidArgument(_))));
+    }
+}
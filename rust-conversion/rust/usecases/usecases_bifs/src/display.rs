@@ -0,0 +1,292 @@
+//! Display Built-in Functions
+//!
+//! Provides the low-level term output BIFs:
+//! - erlang:display/1 (print a term and a newline to stdout)
+//! - erlang:display_string/2 (write a string to stdout or stderr)
+//! - erts_debug:display/1 (print a term to stderr and return the text)
+//!
+//! Terms print through [`infrastructure_data_handling::print_term`] in the
+//! `%T` format of the emulator, with atom names from the global atom table.
+//! Each BIF has a `_to` variant taking the writer, so output can be captured.
+//!
+//! Based on display_1, display_string_2 and erts_debug_display_1 in bif.c
+//! and erl_bif_info.c
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ *
+ * Creation productivity increased for code in this file by using AALang and GAB.
+ * See https://github.com/yenrab/AALang-Gab
+ */
+
+use std::io::Write;
+
+use code_management_code_loading::{Conversion, Encoding, UnicodeHandler};
+use entities_data_handling::term_hashing::Term;
+use infrastructure_data_handling::print_term::{write_term, PrintError, PrintOptions};
+use infrastructure_utilities::atom_table::get_global_atom_table;
+
+/// Error type for display BIF operations
+#[derive(Debug, Clone, PartialEq)]
+pub enum DisplayError {
+    /// Argument is not a valid string or device
+    BadArgument,
+    /// The term could not be printed or written
+    Print(PrintError),
+}
+
+impl std::fmt::Display for DisplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DisplayError::BadArgument => write!(f, "Bad argument"),
+            DisplayError::Print(e) => write!(f, "Print error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DisplayError {}
+
+impl From<PrintError> for DisplayError {
+    fn from(e: PrintError) -> Self {
+        DisplayError::Print(e)
+    }
+}
+
+/// Output device of erlang:display_string/2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayDevice {
+    /// Standard output (`stdout`)
+    Stdout,
+    /// Standard error (`stderr`)
+    Stderr,
+}
+
+impl DisplayDevice {
+    /// Parse a device from its atom name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "stdout" => Some(DisplayDevice::Stdout),
+            "stderr" => Some(DisplayDevice::Stderr),
+            _ => None,
+        }
+    }
+}
+
+/// Display Built-in Functions
+pub struct DisplayBif;
+
+impl DisplayBif {
+    /// Print a term followed by a newline to stdout (`erlang:display/1`)
+    ///
+    /// # Returns
+    /// * `Ok(true)` - The term was printed
+    /// * `Err(DisplayError)` - Invalid term or write error
+    pub fn display(term: &Term) -> Result<bool, DisplayError> {
+        Self::display_to(&mut std::io::stdout().lock(), term)
+    }
+
+    /// Print a term followed by a newline to `out`
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::display::DisplayBif;
+    /// use entities_data_handling::term_hashing::Term;
+    ///
+    /// let mut out = Vec::new();
+    /// let tuple = Term::Tuple(vec![Term::Small(1), Term::Float(2.0)]);
+    /// assert!(DisplayBif::display_to(&mut out, &tuple).unwrap());
+    /// assert_eq!(out, b"{1,2.0}\n");
+    /// ```
+    pub fn display_to<W: Write>(out: &mut W, term: &Term) -> Result<bool, DisplayError> {
+        let text = Self::format(term, None)?;
+        Self::write_all(out, text.as_bytes())?;
+        Ok(true)
+    }
+
+    /// Write a string to stdout or stderr (`erlang:display_string/2`)
+    ///
+    /// # Arguments
+    /// * `device` - Device to write to
+    /// * `string` - A list of characters or a UTF-8 binary, possibly nested
+    ///
+    /// # Returns
+    /// * `Ok(true)` - The string was written
+    /// * `Err(DisplayError::BadArgument)` - `string` is not valid character data
+    pub fn display_string(device: DisplayDevice, string: &Term) -> Result<bool, DisplayError> {
+        match device {
+            DisplayDevice::Stdout => Self::display_string_to(&mut std::io::stdout().lock(), string),
+            DisplayDevice::Stderr => Self::display_string_to(&mut std::io::stderr().lock(), string),
+        }
+    }
+
+    /// Write a string to `out` as UTF-8
+    pub fn display_string_to<W: Write>(out: &mut W, string: &Term) -> Result<bool, DisplayError> {
+        match UnicodeHandler::characters_to_binary(string, Encoding::Utf8, Encoding::Utf8) {
+            Ok(Conversion::Complete(bytes)) => {
+                Self::write_all(out, &bytes)?;
+                Ok(true)
+            }
+            _ => Err(DisplayError::BadArgument),
+        }
+    }
+
+    /// Print a term followed by a newline to stderr and return the printed
+    /// text (`erts_debug:display/1`)
+    pub fn debug_display(term: &Term) -> Result<String, DisplayError> {
+        Self::debug_display_to(&mut std::io::stderr().lock(), term)
+    }
+
+    /// Print a term followed by a newline to `out` and return the printed text
+    pub fn debug_display_to<W: Write>(out: &mut W, term: &Term) -> Result<String, DisplayError> {
+        let text = Self::format(term, None)?;
+        Self::write_all(out, text.as_bytes())?;
+        Ok(text)
+    }
+
+    /// Format a term as `~P` does, cutting it off below `depth` levels
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::display::DisplayBif;
+    /// use entities_data_handling::term_hashing::Term;
+    ///
+    /// let tuple = Term::Tuple((1..=4).map(Term::Small).collect());
+    /// assert_eq!(DisplayBif::format_depth(&tuple, 3).unwrap(), "{1,2,...}");
+    /// ```
+    pub fn format_depth(term: &Term, depth: usize) -> Result<String, DisplayError> {
+        let mut text = Self::format(term, Some(depth))?;
+        text.pop();
+        Ok(text)
+    }
+
+    /// Print a term with atom names from the global atom table, plus a newline
+    fn format(term: &Term, depth: Option<usize>) -> Result<String, DisplayError> {
+        let options = PrintOptions { depth, atoms: Some(get_global_atom_table()) };
+        let mut buf = Vec::new();
+        write_term(&mut buf, term, &options)?;
+        buf.push(b'\n');
+        String::from_utf8(buf).map_err(|_| DisplayError::Print(PrintError::EncodingError))
+    }
+
+    fn write_all<W: Write>(out: &mut W, bytes: &[u8]) -> Result<(), DisplayError> {
+        out.write_all(bytes)
+            .and_then(|()| out.flush())
+            .map_err(|e| DisplayError::Print(PrintError::IoError(e.to_string())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entities_data_handling::AtomEncoding;
+
+    fn string(text: &str) -> Term {
+        text.chars().rev().fold(Term::Nil, |tail, c| Term::List {
+            head: Box::new(Term::Small(c as i64)),
+            tail: Box::new(tail),
+        })
+    }
+
+    fn atom(name: &str) -> Term {
+        let index = get_global_atom_table()
+            .put_index(name.as_bytes(), AtomEncoding::Utf8, false)
+            .unwrap();
+        Term::Atom(index as u32)
+    }
+
+    #[test]
+    fn test_display_prints_term_and_newline() {
+        let term = Term::Tuple(vec![
+            atom("ok"),
+            Term::Pid { node: 0, id: 42, serial: 0, creation: 1 },
+            Term::Port { node: 0, id: 7, creation: 1 },
+            Term::Ref { node: 0, ids: vec![1, 2, 3], creation: 1 },
+        ]);
+        let mut out = Vec::new();
+        assert_eq!(DisplayBif::display_to(&mut out, &term), Ok(true));
+        assert_eq!(String::from_utf8(out).unwrap(), "{ok,<0.42.0>,#Port<0.7>,#Ref<0.3.2.1>}\n");
+    }
+
+    #[test]
+    fn test_display_funs_and_bitstrings() {
+        let fun = Term::Fun {
+            is_local: false,
+            module: match atom("lists") { Term::Atom(i) => i, _ => unreachable!() },
+            function: match atom("map") { Term::Atom(i) => i, _ => unreachable!() },
+            arity: 2,
+            old_uniq: None,
+            env: vec![],
+        };
+        let bits = Term::Binary { data: vec![1, 2, 0b1010_0000], bit_offset: 0, bit_size: 19 };
+        let mut out = Vec::new();
+        DisplayBif::display_to(&mut out, &Term::List { head: Box::new(fun), tail: Box::new(bits) }).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "[fun lists:map/2|<<1,2,5:3>>]\n");
+    }
+
+    #[test]
+    fn test_display_string() {
+        let mut out = Vec::new();
+        let chardata = Term::List {
+            head: Box::new(string("héllo ")),
+            tail: Box::new(Term::List {
+                head: Box::new(Term::Binary { data: b"world\n".to_vec(), bit_offset: 0, bit_size: 48 }),
+                tail: Box::new(Term::Nil),
+            }),
+        };
+        assert_eq!(DisplayBif::display_string_to(&mut out, &chardata), Ok(true));
+        assert_eq!(String::from_utf8(out).unwrap(), "héllo world\n");
+    }
+
+    #[test]
+    fn test_display_string_bad_argument() {
+        let mut out = Vec::new();
+        assert_eq!(DisplayBif::display_string_to(&mut out, &Term::Small(1)), Err(DisplayError::BadArgument));
+        let invalid = Term::List { head: Box::new(Term::Small(-1)), tail: Box::new(Term::Nil) };
+        assert_eq!(DisplayBif::display_string_to(&mut out, &invalid), Err(DisplayError::BadArgument));
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_debug_display_returns_text() {
+        let mut out = Vec::new();
+        let text = DisplayBif::debug_display_to(&mut out, &string("abc")).unwrap();
+        assert_eq!(text, "\"abc\"\n");
+        assert_eq!(out, text.as_bytes());
+    }
+
+    #[test]
+    fn test_format_depth() {
+        let list = Term::List {
+            head: Box::new(Term::Tuple(vec![Term::Small(1), Term::Small(2)])),
+            tail: Box::new(Term::List { head: Box::new(Term::Small(3)), tail: Box::new(Term::Nil) }),
+        };
+        assert_eq!(DisplayBif::format_depth(&list, 2).unwrap(), "[{...}|...]");
+        assert_eq!(DisplayBif::format_depth(&list, 3).unwrap(), "[{1,...},3]");
+    }
+
+    #[test]
+    fn test_device_from_name() {
+        assert_eq!(DisplayDevice::from_name("stdout"), Some(DisplayDevice::Stdout));
+        assert_eq!(DisplayDevice::from_name("stderr"), Some(DisplayDevice::Stderr));
+        assert_eq!(DisplayDevice::from_name("standard_io"), None);
+    }
+}
@@ -570,6 +570,7 @@ impl DynamicLibraryLoader {
             release: false, // Use debug builds by default for faster compilation
             cargo_flags: Vec::new(),
             output_dir: None, // Compile to temporary location, we'll use the result
+            offline: None,
         };
 
         // Compile the source file
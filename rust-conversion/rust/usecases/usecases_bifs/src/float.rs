@@ -0,0 +1,373 @@
+//! Float Built-in Functions
+//!
+//! Provides the conversions between floats and text:
+//! - `erlang:float_to_list/1,2` and `erlang:float_to_binary/1,2`
+//! - `erlang:list_to_float/1` and `erlang:binary_to_float/1`
+//!
+//! Without options a float is written as `"%.20e"` in C, which reads back
+//! exactly. The `short` option writes the fewest digits that read back
+//! exactly (the shortest round-trip digits of Ryū and Grisu, produced here by
+//! the formatter of the standard library), placed in decimal or scientific
+//! notation, whichever is shorter, as in io_lib_format.erl. Decimal notation
+//! wins ties, so `100.0` stays `"100.0"` but `1000.0` becomes `"1.0e3"`.
+//!
+//! Parsing accepts only the forms the runtime writes: an optional sign,
+//! digits, a decimal point, digits and an optional exponent. `"1"`, `"1."`
+//! and `"1e5"` are not floats, and values that overflow are refused.
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ *
+ * Creation productivity increased for code in this file by using AALang and GAB.
+ * See https://github.com/yenrab/AALang-Gab
+ */
+
+/// Largest number of decimals of `{decimals, N}`
+pub const MAX_DECIMALS: usize = 253;
+/// Largest number of decimals of `{scientific, N}`
+pub const MAX_SCIENTIFIC_DECIMALS: usize = 249;
+/// Decimals written without options (`{scientific, 20}`)
+pub const DEFAULT_SCIENTIFIC_DECIMALS: usize = 20;
+
+/// Error type for float BIF operations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FloatError {
+    /// Bad argument (e.g., an option out of range or text that is not a float)
+    BadArgument(String),
+}
+
+impl std::fmt::Display for FloatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FloatError::BadArgument(msg) => write!(f, "Bad argument: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for FloatError {}
+
+/// Option of `float_to_list/2` and `float_to_binary/2`
+///
+/// Of `Decimals`, `Scientific` and `Short` the last one given applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FloatOption {
+    /// Decimal notation with `N` decimals (`{decimals, N}`), `N` at most
+    /// [`MAX_DECIMALS`]
+    Decimals(usize),
+    /// Scientific notation with `N` decimals (`{scientific, N}`), `N` at most
+    /// [`MAX_SCIENTIFIC_DECIMALS`]
+    Scientific(usize),
+    /// Drop trailing zeros of `Decimals`, keeping one decimal (`compact`)
+    Compact,
+    /// Fewest digits that read back exactly (`short`)
+    Short,
+}
+
+/// Notation selected by the options
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Notation {
+    Decimals(usize),
+    Scientific(usize),
+    Short,
+}
+
+/// Float BIF operations
+pub struct FloatBif;
+
+impl FloatBif {
+    /// Write a float as text (`float_to_list/1,2`)
+    ///
+    /// # Arguments
+    /// * `value` - Float to write
+    /// * `options` - Format options; none writes `{scientific, 20}`
+    ///
+    /// # Returns
+    /// * `Ok(String)` - The text
+    /// * `Err(FloatError::BadArgument)` - `value` is not finite or an option
+    ///   is out of range
+    pub fn float_to_list(value: f64, options: &[FloatOption]) -> Result<String, FloatError> {
+        if !value.is_finite() {
+            return Err(FloatError::BadArgument(format!("{} is not a float", value)));
+        }
+        let mut notation = Notation::Scientific(DEFAULT_SCIENTIFIC_DECIMALS);
+        let mut compact = false;
+        for option in options {
+            match *option {
+                FloatOption::Decimals(n) if n <= MAX_DECIMALS => notation = Notation::Decimals(n),
+                FloatOption::Scientific(n) if n <= MAX_SCIENTIFIC_DECIMALS => notation = Notation::Scientific(n),
+                FloatOption::Compact => compact = true,
+                FloatOption::Short => notation = Notation::Short,
+                _ => return Err(FloatError::BadArgument(format!("invalid option {:?}", option))),
+            }
+        }
+        Ok(match notation {
+            Notation::Decimals(n) => format_decimals(value, n, compact),
+            Notation::Scientific(n) => format_scientific(value, n),
+            Notation::Short => format_short(value),
+        })
+    }
+
+    /// Write a float as a binary (`float_to_binary/1,2`)
+    ///
+    /// See [`FloatBif::float_to_list`].
+    pub fn float_to_binary(value: f64, options: &[FloatOption]) -> Result<Vec<u8>, FloatError> {
+        Self::float_to_list(value, options).map(String::into_bytes)
+    }
+
+    /// Read a float from text (`list_to_float/1`)
+    ///
+    /// # Returns
+    /// * `Ok(f64)` - The float, correctly rounded
+    /// * `Err(FloatError::BadArgument)` - The text is not a float or the
+    ///   value overflows
+    pub fn list_to_float(text: &str) -> Result<f64, FloatError> {
+        if !is_float_syntax(text.as_bytes()) {
+            return Err(FloatError::BadArgument(format!("{:?} is not a float", text)));
+        }
+        text.parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .ok_or_else(|| FloatError::BadArgument(format!("{:?} is out of range", text)))
+    }
+
+    /// Read a float from a binary (`binary_to_float/1`)
+    ///
+    /// See [`FloatBif::list_to_float`].
+    pub fn binary_to_float(binary: &[u8]) -> Result<f64, FloatError> {
+        let text = std::str::from_utf8(binary)
+            .map_err(|_| FloatError::BadArgument("binary is not a float".to_string()))?;
+        Self::list_to_float(text)
+    }
+}
+
+/// Write a float with the fewest digits that read back exactly (`short`)
+///
+/// The digits are placed in decimal notation unless scientific notation is
+/// shorter.
+pub fn format_short(value: f64) -> String {
+    let sign = if value.is_sign_negative() { "-" } else { "" };
+    // `{:e}` gives the shortest round-trip digits, as in "1.2345e-7"
+    let scientific = format!("{:e}", value.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let digits: String = mantissa.chars().filter(|&c| c != '.').collect();
+    let place = exponent.parse::<i32>().unwrap_or(0) + 1;
+    format!("{}{}", sign, insert_decimal(place, &digits))
+}
+
+/// Write a float in scientific notation with `decimals` decimals
+/// (`"%.*e"` in C, with a signed exponent of at least two digits)
+pub fn format_scientific(value: f64, decimals: usize) -> String {
+    let formatted = format!("{:.*e}", decimals, value);
+    let (mantissa, exponent) = formatted.split_once('e').unwrap_or((&formatted, "0"));
+    let (sign, digits) = match exponent.strip_prefix('-') {
+        Some(digits) => ('-', digits),
+        None => ('+', exponent),
+    };
+    format!("{}e{}{:0>2}", mantissa, sign, digits)
+}
+
+/// Write a float in decimal notation with `decimals` decimals (`"%.*f"` in
+/// C), dropping trailing zeros but one if `compact`
+pub fn format_decimals(value: f64, decimals: usize, compact: bool) -> String {
+    let mut formatted = format!("{:.*}", decimals, value);
+    if compact && decimals > 0 {
+        let kept = formatted.trim_end_matches('0').len();
+        let kept = if formatted.as_bytes()[kept - 1] == b'.' { kept + 1 } else { kept };
+        formatted.truncate(kept);
+    }
+    formatted
+}
+
+/// Place the decimal point in `digits`, the value being `0.digits * 10^place`
+///
+/// Scientific notation is used where it is shorter than padding with zeros.
+fn insert_decimal(place: i32, digits: &str) -> String {
+    let length = digits.len() as i32;
+    if place == 0 {
+        return format!("0.{}", digits);
+    }
+    if place > 0 && place < length {
+        let (whole, fraction) = digits.split_at(place as usize);
+        return format!("{}.{}", whole, fraction);
+    }
+    let exponent = (place - 1).to_string();
+    let exponent_dot = if length == 1 { 2 } else { 1 };
+    let exponent_cost = exponent.len() as i32 + 1 + exponent_dot;
+    if place < 0 {
+        if 2 - place <= exponent_cost {
+            return format!("0.{}{}", "0".repeat(-place as usize), digits);
+        }
+    } else if place - length + 2 <= exponent_cost {
+        return format!("{}{}.0", digits, "0".repeat((place - length) as usize));
+    }
+    match digits.split_at(1) {
+        (first, "") => format!("{}.0e{}", first, exponent),
+        (first, rest) => format!("{}.{}e{}", first, rest, exponent),
+    }
+}
+
+/// Check the text of a float: `[+-]?[0-9]+\.[0-9]+([eE][+-]?[0-9]+)?`
+fn is_float_syntax(text: &[u8]) -> bool {
+    fn digits(text: &[u8]) -> usize {
+        text.iter().take_while(|c| c.is_ascii_digit()).count()
+    }
+    let mut pos = usize::from(matches!(text.first(), Some(b'+' | b'-')));
+    let whole = digits(&text[pos..]);
+    if whole == 0 || text.get(pos + whole) != Some(&b'.') {
+        return false;
+    }
+    pos += whole + 1;
+    let fraction = digits(&text[pos..]);
+    if fraction == 0 {
+        return false;
+    }
+    pos += fraction;
+    if matches!(text.get(pos), Some(b'e' | b'E')) {
+        pos += 1;
+        if matches!(text.get(pos), Some(b'+' | b'-')) {
+            pos += 1;
+        }
+        let exponent = digits(&text[pos..]);
+        if exponent == 0 {
+            return false;
+        }
+        pos += exponent;
+    }
+    pos == text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entities_data_handling::term_hashing::Term;
+    use infrastructure_external_format::{dec_term, erts_encode_ext};
+
+    fn short(value: f64) -> String {
+        FloatBif::float_to_list(value, &[FloatOption::Short]).unwrap()
+    }
+
+    #[test]
+    fn test_float_to_list_short() {
+        assert_eq!(short(0.0), "0.0");
+        assert_eq!(short(-0.0), "-0.0");
+        assert_eq!(short(1.0), "1.0");
+        assert_eq!(short(0.1), "0.1");
+        assert_eq!(short(123.456), "123.456");
+        assert_eq!(short(-2.5), "-2.5");
+        assert_eq!(short(100.0), "100.0");
+        assert_eq!(short(1000.0), "1.0e3");
+        assert_eq!(short(1234000.0), "1.234e6");
+        assert_eq!(short(1230.0), "1230.0");
+        assert_eq!(short(0.05), "0.05");
+        assert_eq!(short(0.0001), "0.0001");
+        assert_eq!(short(1.0e-10), "1.0e-10");
+        assert_eq!(short(1.5e-10), "1.5e-10");
+        assert_eq!(short(1.0e15), "1.0e15");
+        assert_eq!(short(0.1 + 0.2), "0.30000000000000004");
+        assert_eq!(short(123456789012345680.0), "123456789012345680.0");
+        assert_eq!(short(1.2345678901234567e19), "1.2345678901234567e19");
+        assert_eq!(short(f64::MAX), "1.7976931348623157e308");
+        assert_eq!(short(5.0e-324), "5.0e-324");
+    }
+
+    #[test]
+    fn test_float_to_list_scientific() {
+        assert_eq!(FloatBif::float_to_list(1.0, &[]).unwrap(), "1.00000000000000000000e+00");
+        assert_eq!(FloatBif::float_to_list(0.1, &[]).unwrap(), "1.00000000000000005551e-01");
+        assert_eq!(FloatBif::float_to_list(-1.25e100, &[FloatOption::Scientific(3)]).unwrap(), "-1.250e+100");
+        assert_eq!(FloatBif::float_to_list(7.0e-5, &[FloatOption::Scientific(0)]).unwrap(), "7e-05");
+        assert!(FloatBif::float_to_list(1.0, &[FloatOption::Scientific(MAX_SCIENTIFIC_DECIMALS + 1)]).is_err());
+    }
+
+    #[test]
+    fn test_float_to_list_decimals() {
+        let decimals = |value, n, compact: bool| {
+            let options = if compact {
+                vec![FloatOption::Decimals(n), FloatOption::Compact]
+            } else {
+                vec![FloatOption::Decimals(n)]
+            };
+            FloatBif::float_to_list(value, &options).unwrap()
+        };
+        assert_eq!(decimals(1.23456, 2, false), "1.23");
+        assert_eq!(decimals(1.1, 4, false), "1.1000");
+        assert_eq!(decimals(1.1, 4, true), "1.1");
+        assert_eq!(decimals(1.0, 4, true), "1.0");
+        assert_eq!(decimals(2.75, 0, true), "3");
+        assert_eq!(decimals(-0.001, 2, false), "-0.00");
+        assert!(FloatBif::float_to_list(1.0, &[FloatOption::Decimals(MAX_DECIMALS + 1)]).is_err());
+
+        // The last notation given applies
+        let options = [FloatOption::Short, FloatOption::Decimals(1)];
+        assert_eq!(FloatBif::float_to_binary(2.25, &options).unwrap(), b"2.2".to_vec());
+        assert!(FloatBif::float_to_list(f64::NAN, &[]).is_err());
+    }
+
+    #[test]
+    fn test_list_to_float() {
+        assert_eq!(FloatBif::list_to_float("1.5"), Ok(1.5));
+        assert_eq!(FloatBif::list_to_float("+1.0e3"), Ok(1000.0));
+        assert_eq!(FloatBif::list_to_float("-2.5E-2"), Ok(-0.025));
+        assert_eq!(FloatBif::binary_to_float(b"0.30000000000000004"), Ok(0.1 + 0.2));
+        for text in ["1", "1.", ".5", "1e5", "1.0e", "1.0e+", " 1.0", "1.0 ", "inf", "nan", "1.0e400", ""] {
+            assert!(FloatBif::list_to_float(text).is_err(), "{:?}", text);
+        }
+        assert!(FloatBif::binary_to_float(&[0xFF, b'.', b'0']).is_err());
+    }
+
+    #[test]
+    fn test_formats_read_back_exactly() {
+        for value in [0.1, -1.0 / 3.0, 6.02214076e23, 2.2250738585072014e-308, 5.0e-324, 1.0e22, 9007199254740993.0] {
+            assert_eq!(FloatBif::list_to_float(&short(value)).map(f64::to_bits), Ok(value.to_bits()));
+            let scientific = FloatBif::float_to_list(value, &[]).unwrap();
+            assert_eq!(FloatBif::list_to_float(&scientific).map(f64::to_bits), Ok(value.to_bits()));
+        }
+    }
+
+    #[test]
+    fn test_new_float_ext_interop() {
+        for value in [0.0, -0.0, 1.5, -123.456, 1.0e-300, f64::MAX] {
+            let encoded = erts_encode_ext(&Term::Float(value), None).unwrap();
+            // VERSION_MAGIC, NEW_FLOAT_EXT and the big-endian IEEE 754 bits
+            assert_eq!(encoded[..2], [131, 70]);
+            assert_eq!(encoded[2..], value.to_be_bytes());
+
+            let Ok(Term::Float(decoded)) = dec_term(&encoded) else {
+                panic!("NEW_FLOAT_EXT did not decode to a float");
+            };
+            assert_eq!(decoded.to_bits(), value.to_bits());
+            assert_eq!(FloatBif::list_to_float(&short(decoded)).map(f64::to_bits), Ok(value.to_bits()));
+        }
+    }
+
+    #[test]
+    fn test_float_ext_interop() {
+        // FLOAT_EXT holds the "%.20e" text, zero-padded to 31 bytes
+        let value = -6.02214076e23;
+        let text = FloatBif::float_to_binary(value, &[]).unwrap();
+        let mut encoded = vec![131, 99];
+        encoded.extend_from_slice(&text);
+        encoded.resize(2 + 31, 0);
+        assert_eq!(dec_term(&encoded), Ok(Term::Float(value)));
+    }
+}
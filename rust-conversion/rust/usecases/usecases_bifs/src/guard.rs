@@ -7,7 +7,10 @@
 //! - Type checking (is_integer_3)
 //! - Binary operations (binary_part_2, binary_part_3)
 //!
-//! This module implements safe Rust equivalents of Erlang guard BIFs.
+//! This module implements safe Rust equivalents of Erlang guard BIFs, and
+//! [`GuardSandbox`], the execution mode guards are evaluated in: only guard
+//! BIFs are callable, reductions are bounded and failures make the guard
+//! false.
 
 /*
  * %CopyrightBegin%
@@ -36,8 +39,11 @@
  * See https://github.com/yenrab/AALang-Gab
  */
 
-use crate::op::ErlangTerm;
+use crate::arith::{ArithBif, ArithError};
+use crate::op::{ErlangTerm, OpBif, OpError};
+use crate::unique::UniqueBif;
 use entities_utilities::BigNumber;
+use std::panic::{self, AssertUnwindSafe};
 
 /// Guard BIF operations
 pub struct GuardBif;
@@ -1014,6 +1020,362 @@ impl std::fmt::Display for GuardError {
 
 impl std::error::Error for GuardError {}
 
+/// Guard BIFs callable in a [`GuardSandbox`], by name and arity
+///
+/// The BIFs and operators `erl_lint` allows in guards: type tests, guard
+/// arithmetic and sizes, term access, the arithmetic, bitwise, comparison
+/// and boolean operators, and `self/0` and `node/0,1`.
+pub const GUARD_BIFS: &[(&str, usize)] = &[
+    ("abs", 1),
+    ("float", 1),
+    ("trunc", 1),
+    ("floor", 1),
+    ("ceil", 1),
+    ("round", 1),
+    ("length", 1),
+    ("size", 1),
+    ("bit_size", 1),
+    ("byte_size", 1),
+    ("tuple_size", 1),
+    ("map_size", 1),
+    ("hd", 1),
+    ("tl", 1),
+    ("element", 2),
+    ("is_map_key", 2),
+    ("min", 2),
+    ("max", 2),
+    ("binary_part", 2),
+    ("binary_part", 3),
+    ("is_atom", 1),
+    ("is_binary", 1),
+    ("is_bitstring", 1),
+    ("is_boolean", 1),
+    ("is_float", 1),
+    ("is_function", 1),
+    ("is_function", 2),
+    ("is_integer", 1),
+    ("is_integer", 3),
+    ("is_list", 1),
+    ("is_map", 1),
+    ("is_number", 1),
+    ("is_pid", 1),
+    ("is_port", 1),
+    ("is_record", 2),
+    ("is_record", 3),
+    ("is_reference", 1),
+    ("is_tuple", 1),
+    ("self", 0),
+    ("node", 0),
+    ("node", 1),
+    ("+", 1),
+    ("-", 1),
+    ("+", 2),
+    ("-", 2),
+    ("*", 2),
+    ("/", 2),
+    ("div", 2),
+    ("rem", 2),
+    ("bnot", 1),
+    ("band", 2),
+    ("bor", 2),
+    ("bxor", 2),
+    ("bsl", 2),
+    ("bsr", 2),
+    ("not", 1),
+    ("and", 2),
+    ("or", 2),
+    ("xor", 2),
+    ("==", 2),
+    ("/=", 2),
+    ("=:=", 2),
+    ("=/=", 2),
+    ("<", 2),
+    ("=<", 2),
+    (">", 2),
+    (">=", 2),
+];
+
+/// Check if a BIF may be called in a guard
+pub fn is_guard_bif(name: &str, arity: usize) -> bool {
+    GUARD_BIFS.contains(&(name, arity))
+}
+
+/// Number of terms in a term, counting a binary as one term per word
+fn term_count(term: &ErlangTerm) -> usize {
+    match term {
+        ErlangTerm::Tuple(items) | ErlangTerm::List(items) => 1 + items.iter().map(term_count).sum::<usize>(),
+        ErlangTerm::Map(map) => 1 + map.iter().map(|(key, value)| term_count(key) + term_count(value)).sum::<usize>(),
+        ErlangTerm::Binary(bytes) | ErlangTerm::Bitstring(bytes, _) => 1 + bytes.len() / 8,
+        _ => 1,
+    }
+}
+
+/// Guard execution mode
+///
+/// Evaluates guards with guard semantics: only the [`GUARD_BIFS`] are
+/// callable, each call costs reductions out of a fixed budget in proportion
+/// to the size of its input (see [`GuardSandbox::cost`]), and any failure, including an exception raised inside a BIF, makes the guard
+/// false instead of escaping. The emulator evaluates guard BIF instructions
+/// and the guards of trace match specifications in a sandbox, and ETS
+/// tables the guards of `select` match specifications.
+///
+/// # Examples
+/// ```
+/// use usecases_bifs::guard::GuardSandbox;
+/// use usecases_bifs::op::ErlangTerm;
+///
+/// let list = ErlangTerm::List(vec![ErlangTerm::Integer(1)]);
+/// let mut sandbox = GuardSandbox::new(GuardSandbox::DEFAULT_REDUCTIONS);
+/// assert!(sandbox.eval(|guard| {
+///     let length = guard.call("length", &[list.clone()])?;
+///     guard.call("==", &[length, ErlangTerm::Integer(1)])
+/// }));
+///
+/// // length(1) fails, so the guard is false
+/// assert!(!sandbox.eval(|guard| guard.call("length", &[ErlangTerm::Integer(1)])));
+/// ```
+#[derive(Debug, Clone)]
+pub struct GuardSandbox {
+    /// Reductions left for guard BIF calls
+    reductions: usize,
+    /// Pid of the process the guard runs in, returned by `self/0`
+    self_pid: Option<u64>,
+}
+
+impl GuardSandbox {
+    /// Default reduction budget, a full time slice (`CONTEXT_REDS`)
+    pub const DEFAULT_REDUCTIONS: usize = 4000;
+
+    /// Terms a traversing guard BIF visits per reduction, as `length/1`
+    /// does in `erts_trapping_length_1()`
+    pub const TERMS_PER_REDUCTION: usize = 16;
+
+    /// Create a sandbox with a reduction budget
+    pub fn new(reductions: usize) -> Self {
+        Self {
+            reductions,
+            self_pid: None,
+        }
+    }
+
+    /// Set the process the guard runs in
+    ///
+    /// Without a process, `self/0` fails.
+    pub fn with_self(mut self, pid: u64) -> Self {
+        self.self_pid = Some(pid);
+        self
+    }
+
+    /// Get the number of reductions left
+    pub fn reductions_left(&self) -> usize {
+        self.reductions
+    }
+
+    /// Call a guard BIF
+    ///
+    /// # Arguments
+    /// * `name` - BIF or operator name, e.g. `"length"` or `"=:="`
+    /// * `args` - Arguments
+    ///
+    /// # Returns
+    /// The result, or the reason the call failed
+    pub fn call(&mut self, name: &str, args: &[ErlangTerm]) -> Result<ErlangTerm, GuardFailure> {
+        if !is_guard_bif(name, args.len()) {
+            return Err(GuardFailure::NotGuardBif {
+                name: name.to_string(),
+                arity: args.len(),
+            });
+        }
+        let cost = Self::cost(name, args);
+        if cost > self.reductions {
+            self.reductions = 0;
+            return Err(GuardFailure::OutOfReductions);
+        }
+        self.reductions -= cost;
+        self.apply(name, args)
+    }
+
+    /// Reductions a guard BIF call costs
+    ///
+    /// Every call costs one reduction. BIFs that traverse their arguments,
+    /// `length/1`, the term comparisons and `min/2` and `max/2`, cost one
+    /// more per [`TERMS_PER_REDUCTION`](Self::TERMS_PER_REDUCTION) terms they
+    /// visit, like the `BUMP_REDS` of the C implementation.
+    ///
+    /// # Arguments
+    /// * `name` - BIF or operator name
+    /// * `args` - Arguments
+    pub fn cost(name: &str, args: &[ErlangTerm]) -> usize {
+        let terms = match (name, args) {
+            ("length", [ErlangTerm::List(items)]) => items.len(),
+            ("==" | "/=" | "=:=" | "=/=" | "<" | "=<" | ">" | ">=" | "min" | "max", [arg1, arg2]) => {
+                term_count(arg1).min(term_count(arg2))
+            }
+            _ => 0,
+        };
+        1 + terms / Self::TERMS_PER_REDUCTION
+    }
+
+    /// Evaluate a guard
+    ///
+    /// # Arguments
+    /// * `guard` - Guard expression, calling guard BIFs through the sandbox
+    ///
+    /// # Returns
+    /// `true` only if the guard evaluates to the atom `true`; a failure or a
+    /// panic in the guard makes it `false`
+    pub fn eval<F>(&mut self, guard: F) -> bool
+    where
+        F: FnOnce(&mut GuardSandbox) -> Result<ErlangTerm, GuardFailure>,
+    {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| guard(self)));
+        matches!(result, Ok(Ok(ErlangTerm::Atom(ref atom))) if atom == "true")
+    }
+
+    fn apply(&self, name: &str, args: &[ErlangTerm]) -> Result<ErlangTerm, GuardFailure> {
+        let boolean = |value: bool| ErlangTerm::Atom(if value { "true" } else { "false" }.to_string());
+        let result = match (name, args) {
+            ("abs", [arg]) => GuardBif::abs(arg)?,
+            ("float", [arg]) => GuardBif::float(arg)?,
+            ("trunc", [arg]) => GuardBif::trunc(arg)?,
+            ("floor", [arg]) => GuardBif::floor(arg)?,
+            ("ceil", [arg]) => GuardBif::ceil(arg)?,
+            ("round", [arg]) => GuardBif::round(arg)?,
+            ("length", [arg]) => GuardBif::length(arg)?,
+            ("size", [arg]) => GuardBif::size(arg)?,
+            ("bit_size", [arg]) => GuardBif::bit_size(arg)?,
+            ("byte_size", [arg]) => GuardBif::byte_size(arg)?,
+            ("tuple_size", [ErlangTerm::Tuple(items)]) => GuardBif::usize_to_term(items.len()),
+            ("map_size", [ErlangTerm::Map(map)]) => GuardBif::usize_to_term(map.len()),
+            ("hd", [ErlangTerm::List(items)]) if !items.is_empty() => items[0].clone(),
+            ("tl", [ErlangTerm::List(items)]) if !items.is_empty() => match &items[1..] {
+                [] => ErlangTerm::Nil,
+                tail => ErlangTerm::List(tail.to_vec()),
+            },
+            ("element", [ErlangTerm::Integer(index), ErlangTerm::Tuple(items)])
+                if *index >= 1 && (*index as u64) <= items.len() as u64 =>
+            {
+                items[*index as usize - 1].clone()
+            }
+            ("is_map_key", [key, ErlangTerm::Map(map)]) => boolean(map.contains_key(key)),
+            ("min", [arg1, arg2]) => GuardBif::min(arg1, arg2)?,
+            ("max", [arg1, arg2]) => GuardBif::max(arg1, arg2)?,
+            ("binary_part", [binary, pos_len]) => GuardBif::binary_part_2(binary, pos_len)?,
+            ("binary_part", [binary, start, length]) => GuardBif::binary_part_3(binary, start, length)?,
+            ("is_atom", [arg]) => OpBif::is_atom(arg),
+            ("is_binary", [arg]) => OpBif::is_binary(arg),
+            ("is_bitstring", [arg]) => OpBif::is_bitstring(arg),
+            ("is_boolean", [arg]) => OpBif::is_boolean(arg),
+            ("is_float", [arg]) => OpBif::is_float(arg),
+            ("is_function", [arg]) => OpBif::is_function(arg),
+            ("is_function", [arg, arity]) => OpBif::is_function_with_arity(arg, arity)?,
+            ("is_integer", [arg]) => OpBif::is_integer(arg),
+            ("is_integer", [value, min, max]) => GuardBif::is_integer_3(value, min, max)?,
+            ("is_list", [arg]) => OpBif::is_list(arg),
+            ("is_map", [arg]) => OpBif::is_map(arg),
+            ("is_number", [arg]) => OpBif::is_number(arg),
+            ("is_pid", [arg]) => OpBif::is_pid(arg),
+            ("is_port", [arg]) => OpBif::is_port(arg),
+            ("is_record", [term, tag]) => OpBif::is_record(term, tag)?,
+            ("is_record", [term, tag, size]) => OpBif::is_record_with_size(term, tag, size)?,
+            ("is_reference", [arg]) => OpBif::is_reference(arg),
+            ("is_tuple", [arg]) => OpBif::is_tuple(arg),
+            ("self", []) => ErlangTerm::Pid(self.self_pid.ok_or_else(|| {
+                GuardFailure::BadArgument("self/0 outside a process".to_string())
+            })?),
+            // Pids, ports and references carry no node, so they are local
+            ("node", [])
+            | ("node", [ErlangTerm::Pid(_) | ErlangTerm::Port(_) | ErlangTerm::Reference(_)]) => {
+                ErlangTerm::Atom(UniqueBif::local_node().0)
+            }
+            ("+", [arg]) => ArithBif::unary_plus(arg)?,
+            ("-", [arg]) => ArithBif::negate(arg)?,
+            ("+", [arg1, arg2]) => ArithBif::plus(arg1, arg2)?,
+            ("-", [arg1, arg2]) => ArithBif::minus(arg1, arg2)?,
+            ("*", [arg1, arg2]) => ArithBif::times(arg1, arg2)?,
+            ("/", [arg1, arg2]) => ArithBif::divide(arg1, arg2)?,
+            ("div", [arg1, arg2]) => ArithBif::int_div(arg1, arg2)?,
+            ("rem", [arg1, arg2]) => ArithBif::rem(arg1, arg2)?,
+            ("bnot", [arg]) => ArithBif::bnot(arg)?,
+            ("band", [arg1, arg2]) => ArithBif::band(arg1, arg2)?,
+            ("bor", [arg1, arg2]) => ArithBif::bor(arg1, arg2)?,
+            ("bxor", [arg1, arg2]) => ArithBif::bxor(arg1, arg2)?,
+            ("bsl", [arg1, arg2]) => ArithBif::bsl(arg1, arg2)?,
+            ("bsr", [arg1, arg2]) => ArithBif::bsr(arg1, arg2)?,
+            ("not", [arg]) => OpBif::not(arg)?,
+            ("and", [arg1, arg2]) => OpBif::and(arg1, arg2)?,
+            ("or", [arg1, arg2]) => OpBif::or(arg1, arg2)?,
+            ("xor", [arg1, arg2]) => OpBif::xor(arg1, arg2)?,
+            ("==", [arg1, arg2]) => OpBif::seq(arg1, arg2),
+            ("/=", [arg1, arg2]) => OpBif::sneq(arg1, arg2),
+            ("=:=", [arg1, arg2]) => OpBif::seqeq(arg1, arg2),
+            ("=/=", [arg1, arg2]) => OpBif::sneqeq(arg1, arg2),
+            ("<", [arg1, arg2]) => OpBif::slt(arg1, arg2),
+            ("=<", [arg1, arg2]) => OpBif::sle(arg1, arg2),
+            (">", [arg1, arg2]) => OpBif::sgt(arg1, arg2),
+            (">=", [arg1, arg2]) => OpBif::sge(arg1, arg2),
+            _ => return Err(GuardFailure::BadArgument(format!("{}/{}", name, args.len()))),
+        };
+        Ok(result)
+    }
+}
+
+/// Reason a guard failed in a [`GuardSandbox`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuardFailure {
+    /// The function is not a guard BIF
+    NotGuardBif {
+        /// Function name
+        name: String,
+        /// Arity called with
+        arity: usize,
+    },
+    /// A guard BIF raised badarg
+    BadArgument(String),
+    /// An arithmetic operator raised badarith
+    BadArith(String),
+    /// The reduction budget ran out
+    OutOfReductions,
+}
+
+impl std::fmt::Display for GuardFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GuardFailure::NotGuardBif { name, arity } => write!(f, "{}/{} is not a guard BIF", name, arity),
+            GuardFailure::BadArgument(msg) => write!(f, "Bad argument: {}", msg),
+            GuardFailure::BadArith(msg) => write!(f, "Bad arithmetic: {}", msg),
+            GuardFailure::OutOfReductions => write!(f, "Guard ran out of reductions"),
+        }
+    }
+}
+
+impl std::error::Error for GuardFailure {}
+
+impl From<GuardError> for GuardFailure {
+    fn from(error: GuardError) -> Self {
+        match error {
+            GuardError::BadArgument(msg) => GuardFailure::BadArgument(msg),
+        }
+    }
+}
+
+impl From<ArithError> for GuardFailure {
+    fn from(error: ArithError) -> Self {
+        match error {
+            ArithError::BadArith(msg) => GuardFailure::BadArith(msg),
+            ArithError::BadArgument(msg) => GuardFailure::BadArgument(msg),
+        }
+    }
+}
+
+impl From<OpError> for GuardFailure {
+    fn from(error: OpError) -> Self {
+        match error {
+            OpError::BadArgument(msg) => GuardFailure::BadArgument(msg),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2133,5 +2495,133 @@ mod tests {
             _ => panic!("Expected Float"),
         }
     }
-}
 
+    #[test]
+    fn test_guard_sandbox_eval() {
+        let atom = |name: &str| ErlangTerm::Atom(name.to_string());
+        let tuple = ErlangTerm::Tuple(vec![atom("point"), ErlangTerm::Integer(3), ErlangTerm::Integer(4)]);
+        let mut sandbox = GuardSandbox::new(GuardSandbox::DEFAULT_REDUCTIONS);
+
+        // is_record(T, point, 3) andalso element(2, T) < element(3, T)
+        assert!(sandbox.eval(|guard| {
+            let is_point = guard.call("is_record", &[tuple.clone(), atom("point"), ErlangTerm::Integer(3)])?;
+            let x = guard.call("element", &[ErlangTerm::Integer(2), tuple.clone()])?;
+            let y = guard.call("element", &[ErlangTerm::Integer(3), tuple.clone()])?;
+            let less = guard.call("<", &[x, y])?;
+            guard.call("and", &[is_point, less])
+        }));
+        assert_eq!(sandbox.reductions_left(), GuardSandbox::DEFAULT_REDUCTIONS - 5);
+
+        // A non-boolean result is not true
+        assert!(!sandbox.eval(|guard| guard.call("tuple_size", std::slice::from_ref(&tuple))));
+        assert!(!sandbox.eval(|guard| guard.call("is_atom", &[ErlangTerm::Integer(1)])));
+    }
+
+    #[test]
+    fn test_guard_sandbox_failures_are_false() {
+        let mut sandbox = GuardSandbox::new(GuardSandbox::DEFAULT_REDUCTIONS);
+
+        // badarg in a guard BIF
+        assert_eq!(
+            sandbox.call("element", &[ErlangTerm::Integer(4), ErlangTerm::Tuple(vec![])]),
+            Err(GuardFailure::BadArgument("element/2".to_string()))
+        );
+        assert!(matches!(
+            sandbox.call("length", &[ErlangTerm::Integer(1)]),
+            Err(GuardFailure::BadArgument(_))
+        ));
+        assert!(!sandbox.eval(|guard| guard.call("hd", &[ErlangTerm::Nil])));
+        assert!(!sandbox.eval(|guard| guard.call("not", &[ErlangTerm::Integer(1)])));
+
+        // Exceptions raised inside the guard do not escape
+        assert!(!sandbox.eval(|_| panic!("guard raised")));
+    }
+
+    #[test]
+    fn test_guard_sandbox_whitelist() {
+        let mut sandbox = GuardSandbox::new(10);
+        assert!(is_guard_bif("is_integer", 3));
+        assert!(!is_guard_bif("is_integer", 2));
+        assert!(!is_guard_bif("send", 2));
+
+        assert_eq!(
+            sandbox.call("send", &[ErlangTerm::Pid(1), ErlangTerm::Nil]),
+            Err(GuardFailure::NotGuardBif { name: "send".to_string(), arity: 2 })
+        );
+        assert!(!sandbox.eval(|guard| guard.call("atom_to_list", &[ErlangTerm::Atom("a".to_string())])));
+        // Calls that are not allowed cost no reductions
+        assert_eq!(sandbox.reductions_left(), 10);
+    }
+
+    #[test]
+    fn test_guard_sandbox_reductions() {
+        let mut sandbox = GuardSandbox::new(2);
+        let list = ErlangTerm::List(vec![ErlangTerm::Integer(1), ErlangTerm::Integer(2)]);
+        assert_eq!(sandbox.call("tl", std::slice::from_ref(&list)), Ok(ErlangTerm::List(vec![ErlangTerm::Integer(2)])));
+        assert_eq!(sandbox.call("hd", std::slice::from_ref(&list)), Ok(ErlangTerm::Integer(1)));
+        assert_eq!(sandbox.call("hd", std::slice::from_ref(&list)), Err(GuardFailure::OutOfReductions));
+
+        // A guard looping on guard BIFs stops when the budget runs out
+        let mut sandbox = GuardSandbox::new(100);
+        assert!(!sandbox.eval(|guard| loop {
+            guard.call("is_list", std::slice::from_ref(&list))?;
+        }));
+        assert_eq!(sandbox.reductions_left(), 0);
+    }
+
+    #[test]
+    fn test_guard_sandbox_reductions_scale_with_input() {
+        let long = ErlangTerm::List((0..160).map(ErlangTerm::Integer).collect());
+        assert_eq!(GuardSandbox::cost("length", std::slice::from_ref(&long)), 11);
+        assert_eq!(GuardSandbox::cost("hd", std::slice::from_ref(&long)), 1);
+        // A comparison stops at the end of the smaller term
+        assert_eq!(GuardSandbox::cost("=:=", &[long.clone(), long.clone()]), 11);
+        assert_eq!(GuardSandbox::cost("<", &[long.clone(), ErlangTerm::Integer(1)]), 1);
+
+        let mut sandbox = GuardSandbox::new(20);
+        assert_eq!(sandbox.call("length", std::slice::from_ref(&long)), Ok(ErlangTerm::Integer(160)));
+        assert_eq!(sandbox.reductions_left(), 9);
+        // A call that costs more than is left fails and uses up the budget
+        assert_eq!(sandbox.call("length", std::slice::from_ref(&long)), Err(GuardFailure::OutOfReductions));
+        assert_eq!(sandbox.reductions_left(), 0);
+    }
+
+    #[test]
+    fn test_guard_sandbox_arithmetic() {
+        let mut sandbox = GuardSandbox::new(GuardSandbox::DEFAULT_REDUCTIONS);
+        let int = ErlangTerm::Integer;
+
+        // X + 1 > 10
+        let guard = |x: i64| {
+            move |guard: &mut GuardSandbox| {
+                let sum = guard.call("+", &[int(x), int(1)])?;
+                guard.call(">", &[sum, int(10)])
+            }
+        };
+        assert!(sandbox.eval(guard(10)));
+        assert!(!sandbox.eval(guard(9)));
+
+        assert_eq!(sandbox.call("-", &[int(3)]), Ok(int(-3)));
+        assert_eq!(sandbox.call("rem", &[int(7), int(3)]), Ok(int(1)));
+        assert_eq!(sandbox.call("/", &[int(1), int(4)]), Ok(ErlangTerm::Float(0.25)));
+        assert_eq!(sandbox.call("band", &[int(6), int(3)]), Ok(int(2)));
+        assert_eq!(sandbox.call("bsl", &[int(1), int(3)]), Ok(int(8)));
+        assert_eq!(sandbox.call("bnot", &[int(0)]), Ok(int(-1)));
+        assert!(matches!(sandbox.call("div", &[int(1), int(0)]), Err(GuardFailure::BadArith(_))));
+        assert!(!sandbox.eval(|guard| guard.call("+", &[ErlangTerm::Atom("a".to_string()), int(1)])));
+    }
+
+    #[test]
+    fn test_guard_sandbox_self_and_node() {
+        let mut sandbox = GuardSandbox::new(10).with_self(42);
+        assert_eq!(sandbox.call("self", &[]), Ok(ErlangTerm::Pid(42)));
+        let _lock = crate::unique::LOCAL_NODE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let node = ErlangTerm::Atom(UniqueBif::local_node().0);
+        assert_eq!(sandbox.call("node", &[]), Ok(node.clone()));
+        assert_eq!(sandbox.call("node", &[ErlangTerm::Pid(42)]), Ok(node));
+        assert!(matches!(sandbox.call("node", &[ErlangTerm::Integer(1)]), Err(GuardFailure::BadArgument(_))));
+
+        // self() is only defined inside a process
+        assert!(matches!(GuardSandbox::new(10).call("self", &[]), Err(GuardFailure::BadArgument(_))));
+    }
+}
@@ -0,0 +1,353 @@
+//! Halt Built-in Functions
+//!
+//! Provides the controlled shutdown of the runtime system:
+//! - `erlang:halt/0,1,2`
+//!
+//! The status given to `halt` selects how the runtime goes down:
+//! - a non-negative integer exits the OS process with that status, after
+//!   flushing the ports and delivering completed async jobs when the `flush`
+//!   option is `true` (the default)
+//! - the atom `abort` aborts the OS process, producing a core dump if the
+//!   OS is set up for it
+//! - a string writes a crash dump with the string as slogan and exits with
+//!   status 1
+//!
+//! In all cases the scheduler threads are stopped through
+//! `usecases_scheduling::threads` before the OS process exits.
+//!
+//! ## Flushing
+//!
+//! Flushing delivers completed async jobs to their ports and waits for the
+//! driver queues to drain, then closes every port. When `flush_timeout`
+//! passes first, the runtime halts as if `{flush, false}` had been given.
+//!
+//! ## Crash dumps
+//!
+//! Crash dumps are written by `infrastructure_debugging::crash_dump`, which
+//! honors `ERL_CRASH_DUMP`, `ERL_CRASH_DUMP_SECONDS` and
+//! `ERL_CRASH_DUMP_BYTES`. Slogans are truncated to [`MAX_SLOGAN_LENGTH`]
+//! characters.
+//!
+//! Based on `halt_2()` in bif.c and `erts_exit_flush_async()` in erl_init.c
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ */
+
+use crate::op::ErlangTerm;
+use infrastructure_debugging::crash_dump::write_crash_dump;
+use infrastructure_driver_api::{get_global_async_pool, get_global_port_table};
+use std::convert::Infallible;
+use std::io::Write;
+use std::time::{Duration, Instant};
+use usecases_scheduling::erts_halt_schedulers;
+
+pub use infrastructure_debugging::crash_dump::MAX_SLOGAN_LENGTH;
+
+/// Time given to the scheduler threads to stop when not flushing
+const SCHEDULER_STOP_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Error type for halt BIF operations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HaltError {
+    /// Bad status or options
+    BadArgument(String),
+}
+
+impl std::fmt::Display for HaltError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HaltError::BadArgument(msg) => write!(f, "Bad argument: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for HaltError {}
+
+/// How the runtime system halts, selected by the status given to `halt`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HaltAction {
+    /// Exit the OS process with a status
+    Exit(i32),
+    /// Abort the OS process, producing a core dump
+    Abort,
+    /// Write a crash dump with a slogan and exit with status 1
+    CrashDump(String),
+}
+
+/// Options of `halt/2`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HaltOptions {
+    /// Flush ports and async jobs before exiting (`{flush, boolean()}`)
+    pub flush: bool,
+    /// Maximum time to flush, `None` for `infinity` (`{flush_timeout, Timeout}`)
+    pub flush_timeout: Option<Duration>,
+}
+
+impl Default for HaltOptions {
+    fn default() -> Self {
+        Self {
+            flush: true,
+            flush_timeout: None,
+        }
+    }
+}
+
+/// Halt BIF operations
+pub struct HaltBif;
+
+impl HaltBif {
+    /// Halt the runtime system with status 0 (halt/0)
+    pub fn halt_0() -> Result<Infallible, HaltError> {
+        Self::halt_1(&ErlangTerm::Integer(0))
+    }
+
+    /// Halt the runtime system (halt/1)
+    ///
+    /// Equivalent to `halt/2` with no options.
+    ///
+    /// # Arguments
+    /// * `status` - Non-negative integer, `abort`, or a string slogan
+    ///
+    /// # Returns
+    /// Only returns on a bad status
+    pub fn halt_1(status: &ErlangTerm) -> Result<Infallible, HaltError> {
+        Self::halt_2(status, &ErlangTerm::Nil)
+    }
+
+    /// Halt the runtime system (halt/2)
+    ///
+    /// # Arguments
+    /// * `status` - Non-negative integer, `abort`, or a string slogan
+    /// * `options` - List of `{flush, boolean()}` and `{flush_timeout, Timeout}`
+    ///
+    /// # Returns
+    /// Only returns on a bad status or bad options
+    pub fn halt_2(status: &ErlangTerm, options: &ErlangTerm) -> Result<Infallible, HaltError> {
+        let action = Self::parse_status(status)?;
+        let options = Self::parse_options(options)?;
+        Self::perform(action, options)
+    }
+
+    /// Parse the status given to `halt`
+    ///
+    /// # Returns
+    /// * `Ok(HaltAction)` - How to halt
+    /// * `Err(HaltError::BadArgument)` - Not a non-negative integer, `abort` or a string
+    pub fn parse_status(status: &ErlangTerm) -> Result<HaltAction, HaltError> {
+        match status {
+            ErlangTerm::Integer(code) => i32::try_from(*code)
+                .ok()
+                .filter(|code| *code >= 0)
+                .map(HaltAction::Exit)
+                .ok_or_else(|| HaltError::BadArgument(format!("invalid halt status: {}", code))),
+            ErlangTerm::Atom(atom) if atom == "abort" => Ok(HaltAction::Abort),
+            ErlangTerm::Nil => Ok(HaltAction::CrashDump(String::new())),
+            ErlangTerm::List(chars) => chars
+                .iter()
+                .map(|c| match c {
+                    ErlangTerm::Integer(c) => u32::try_from(*c).ok().and_then(char::from_u32),
+                    _ => None,
+                })
+                .collect::<Option<String>>()
+                .map(|slogan| HaltAction::CrashDump(slogan.chars().take(MAX_SLOGAN_LENGTH).collect()))
+                .ok_or_else(|| HaltError::BadArgument("halt slogan is not a string".to_string())),
+            _ => Err(HaltError::BadArgument(format!("invalid halt status: {:?}", status))),
+        }
+    }
+
+    /// Parse the options given to `halt/2`
+    ///
+    /// # Returns
+    /// * `Ok(HaltOptions)` - The options, defaulting to flushing without timeout
+    /// * `Err(HaltError::BadArgument)` - Not a proper list of known options
+    pub fn parse_options(options: &ErlangTerm) -> Result<HaltOptions, HaltError> {
+        let items = match options {
+            ErlangTerm::Nil => return Ok(HaltOptions::default()),
+            ErlangTerm::List(items) => items,
+            _ => return Err(HaltError::BadArgument("halt options must be a list".to_string())),
+        };
+
+        let mut parsed = HaltOptions::default();
+        for item in items {
+            match item {
+                ErlangTerm::Tuple(pair) if pair.len() == 2 => match (&pair[0], &pair[1]) {
+                    (ErlangTerm::Atom(key), ErlangTerm::Atom(value)) if key == "flush" => {
+                        parsed.flush = match value.as_str() {
+                            "true" => true,
+                            "false" => false,
+                            _ => return Err(bad_option(item)),
+                        };
+                    }
+                    (ErlangTerm::Atom(key), ErlangTerm::Atom(value)) if key == "flush_timeout" && value == "infinity" => {
+                        parsed.flush_timeout = None;
+                    }
+                    (ErlangTerm::Atom(key), ErlangTerm::Integer(ms)) if key == "flush_timeout" && *ms >= 0 => {
+                        parsed.flush_timeout = Some(Duration::from_millis(*ms as u64));
+                    }
+                    _ => return Err(bad_option(item)),
+                },
+                _ => return Err(bad_option(item)),
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Halt the runtime system
+    ///
+    /// Stops the scheduler threads and exits the OS process as selected by
+    /// `action`. Flushing only applies to [`HaltAction::Exit`].
+    pub fn perform(action: HaltAction, options: HaltOptions) -> ! {
+        match action {
+            HaltAction::Exit(status) => {
+                // After a timed out flush, halt as if `{flush, false}` was given
+                if options.flush && Self::flush(options.flush_timeout) {
+                    erts_halt_schedulers(options.flush_timeout);
+                } else {
+                    erts_halt_schedulers(Some(SCHEDULER_STOP_TIMEOUT));
+                }
+                let _ = std::io::stdout().flush();
+                std::process::exit(status)
+            }
+            HaltAction::Abort => std::process::abort(),
+            HaltAction::CrashDump(slogan) => {
+                erts_halt_schedulers(Some(SCHEDULER_STOP_TIMEOUT));
+                let _ = write_crash_dump(&slogan);
+                std::process::exit(1)
+            }
+        }
+    }
+
+    /// Flush ports and async jobs before exiting
+    ///
+    /// Delivers completed async jobs to their ports and waits for the driver
+    /// queues of all ports to drain, then closes the ports.
+    ///
+    /// # Arguments
+    /// * `timeout` - Maximum time to wait, or `None` to wait until done
+    ///
+    /// # Returns
+    /// `true` if everything was flushed, `false` on timeout (the ports are
+    /// left open)
+    pub fn flush(timeout: Option<Duration>) -> bool {
+        let ports = get_global_port_table();
+        let pool = get_global_async_pool();
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        loop {
+            pool.deliver_ready(ports);
+            let pending = ports
+                .list()
+                .into_iter()
+                .filter_map(|id| ports.lookup(id))
+                .any(|port| !port.is_closed() && port.driver_sizeq() > 0);
+            if !pending && pool.ready_count() == 0 {
+                break;
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return false;
+            }
+            pool.wait_ready(1, Duration::from_millis(1));
+        }
+
+        for id in ports.list() {
+            ports.close_port(id);
+        }
+        true
+    }
+}
+
+/// Bad argument error for a `halt/2` option
+fn bad_option(option: &ErlangTerm) -> HaltError {
+    HaltError::BadArgument(format!("invalid halt option: {:?}", option))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(s: &str) -> ErlangTerm {
+        ErlangTerm::List(s.chars().map(|c| ErlangTerm::Integer(c as i64)).collect())
+    }
+
+    fn atom(name: &str) -> ErlangTerm {
+        ErlangTerm::Atom(name.to_string())
+    }
+
+    #[test]
+    fn test_parse_status() {
+        assert_eq!(HaltBif::parse_status(&ErlangTerm::Integer(3)), Ok(HaltAction::Exit(3)));
+        assert_eq!(HaltBif::parse_status(&atom("abort")), Ok(HaltAction::Abort));
+        assert_eq!(
+            HaltBif::parse_status(&string("out of memory")),
+            Ok(HaltAction::CrashDump("out of memory".to_string()))
+        );
+        assert_eq!(HaltBif::parse_status(&ErlangTerm::Nil), Ok(HaltAction::CrashDump(String::new())));
+
+        let long = "x".repeat(MAX_SLOGAN_LENGTH + 10);
+        match HaltBif::parse_status(&string(&long)).unwrap() {
+            HaltAction::CrashDump(slogan) => assert_eq!(slogan.len(), MAX_SLOGAN_LENGTH),
+            other => panic!("unexpected action {:?}", other),
+        }
+
+        assert!(HaltBif::parse_status(&ErlangTerm::Integer(-1)).is_err());
+        assert!(HaltBif::parse_status(&atom("normal")).is_err());
+        assert!(HaltBif::parse_status(&ErlangTerm::List(vec![atom("a")])).is_err());
+        assert!(HaltBif::parse_status(&ErlangTerm::Float(1.0)).is_err());
+    }
+
+    #[test]
+    fn test_parse_options() {
+        assert_eq!(HaltBif::parse_options(&ErlangTerm::Nil), Ok(HaltOptions::default()));
+
+        let options = ErlangTerm::List(vec![
+            ErlangTerm::Tuple(vec![atom("flush"), atom("false")]),
+            ErlangTerm::Tuple(vec![atom("flush_timeout"), ErlangTerm::Integer(500)]),
+        ]);
+        assert_eq!(
+            HaltBif::parse_options(&options),
+            Ok(HaltOptions {
+                flush: false,
+                flush_timeout: Some(Duration::from_millis(500)),
+            })
+        );
+
+        let infinity = ErlangTerm::List(vec![ErlangTerm::Tuple(vec![atom("flush_timeout"), atom("infinity")])]);
+        assert_eq!(HaltBif::parse_options(&infinity).unwrap().flush_timeout, None);
+
+        for bad in [
+            ErlangTerm::List(vec![ErlangTerm::Tuple(vec![atom("flush"), atom("maybe")])]),
+            ErlangTerm::List(vec![ErlangTerm::Tuple(vec![atom("flush_timeout"), ErlangTerm::Integer(-1)])]),
+            ErlangTerm::List(vec![atom("flush")]),
+            atom("flush"),
+        ] {
+            assert!(HaltBif::parse_options(&bad).is_err());
+        }
+    }
+
+    #[test]
+    fn test_bad_arguments_return() {
+        assert!(HaltBif::halt_1(&atom("bad")).is_err());
+        assert!(HaltBif::halt_2(&ErlangTerm::Integer(0), &atom("bad")).is_err());
+    }
+}
@@ -2,7 +2,10 @@
 //!
 //! Provides system information, process information, and module information BIFs:
 //! - System information queries (system_info/1)
-//! - Process information (process_info/1, process_info/2)
+//! - Runtime statistics (statistics/1)
+//! - Memory usage (memory/0, memory/1)
+//! - Runtime tunables (system_flag/2)
+//! - Process information (processes/0, process_info/1, process_info/2)
 //! - Module information (get_module_info/1, get_module_info/2)
 //! - Function information (fun_info/2)
 //!
@@ -36,8 +39,16 @@
  */
 
 use crate::op::ErlangTerm;
-use entities_process::{ProcessId, ProcessState};
+use entities_process::process_defaults::MAX_BACKTRACE_DEPTH;
+use entities_process::{process_defaults, update_process_defaults, Process, ProcessId, ProcessState};
 use infrastructure_utilities::process_table::get_global_process_table;
+use infrastructure_utilities::statistics::get_global_statistics;
+use infrastructure_utilities::atom_table::get_global_atom_table;
+use infrastructure_driver_api::get_global_port_table;
+use usecases_memory_management::{allocator_info, erts_memory, AllocatorKind, AllocatorType, CarrierStats};
+use usecases_scheduling::{
+    get_global_dirty_schedulers, get_global_schedulers, set_dirty_cpu_schedulers_online, set_schedulers_online,
+};
 
 /// Error type for information operations
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -61,7 +72,7 @@ impl InfoBif {
     /// Returns information about the current system based on the requested item.
     ///
     /// # Arguments
-    /// * `item` - Information item to retrieve (atom)
+    /// * `item` - Information item to retrieve (atom, or `{allocator, Alloc}`)
     ///
     /// # Returns
     /// * `Ok(ErlangTerm)` - System information value
@@ -87,6 +98,16 @@ impl InfoBif {
     pub fn system_info_1(item: &ErlangTerm) -> Result<ErlangTerm, InfoError> {
         let item_str = match item {
             ErlangTerm::Atom(name) => name.clone(),
+            ErlangTerm::Tuple(parts) => match parts.as_slice() {
+                [ErlangTerm::Atom(tag), ErlangTerm::Atom(name)] if tag == "allocator" => {
+                    return Ok(Self::allocator_info_term(name));
+                }
+                _ => {
+                    return Err(InfoError::BadArgument(
+                        "System info item must be an atom or {allocator, Alloc}".to_string(),
+                    ));
+                }
+            },
             _ => {
                 return Err(InfoError::BadArgument(
                     "System info item must be an atom".to_string(),
@@ -130,9 +151,106 @@ impl InfoBif {
                 // Whether time correction is enabled
                 Ok(ErlangTerm::Atom("true".to_string()))
             }
+            "process_count" => {
+                // Number of processes in the process table
+                Ok(ErlangTerm::Integer(get_global_process_table().size() as i64))
+            }
             "process_limit" => {
                 // Maximum number of processes
-                Ok(ErlangTerm::Integer(134217727)) // Default Erlang limit
+                let limit = get_global_process_table().max_size().unwrap_or(134217727); // Default Erlang limit
+                Ok(ErlangTerm::Integer(limit as i64))
+            }
+            "port_count" => {
+                // Number of open ports
+                Ok(ErlangTerm::Integer(get_global_port_table().len() as i64))
+            }
+            "port_limit" => {
+                // Maximum number of open ports
+                Ok(ErlangTerm::Integer(get_global_port_table().max_ports() as i64))
+            }
+            "atom_count" => {
+                // Number of atoms in the atom table
+                Ok(ErlangTerm::Integer(get_global_atom_table().size() as i64))
+            }
+            "atom_limit" => {
+                // Maximum number of atoms
+                Ok(ErlangTerm::Integer(get_global_atom_table().limit() as i64))
+            }
+            "schedulers" => {
+                // Number of scheduler threads
+                Ok(ErlangTerm::Integer(scheduler_counts().0 as i64))
+            }
+            "schedulers_online" => {
+                // Number of schedulers online
+                Ok(ErlangTerm::Integer(scheduler_counts().1 as i64))
+            }
+            "dirty_cpu_schedulers" | "dirty_cpu_schedulers_online" | "dirty_io_schedulers" => {
+                // Dirty scheduler counts; without initialization these are
+                // the ERTS defaults (one dirty CPU scheduler per scheduler,
+                // ten dirty IO schedulers)
+                let count = match (get_global_dirty_schedulers(), item_str.as_str()) {
+                    (Some(dirty), "dirty_cpu_schedulers") => dirty.cpu,
+                    (Some(dirty), "dirty_cpu_schedulers_online") => dirty.cpu_online,
+                    (Some(dirty), _) => dirty.io,
+                    (None, "dirty_cpu_schedulers") => scheduler_counts().0,
+                    (None, "dirty_cpu_schedulers_online") => scheduler_counts().1,
+                    (None, _) => 10,
+                };
+                Ok(ErlangTerm::Integer(count as i64))
+            }
+            "logical_processors" | "logical_processors_available" | "logical_processors_online" => {
+                // Logical processors detected, or `unknown`
+                Ok(match std::thread::available_parallelism() {
+                    Ok(count) => ErlangTerm::Integer(count.get() as i64),
+                    Err(_) => ErlangTerm::Atom("unknown".to_string()),
+                })
+            }
+            "min_heap_size" | "fullsweep_after" => {
+                // {Item, Value} defaults for new processes
+                let defaults = process_defaults();
+                let value = if item_str == "min_heap_size" { defaults.min_heap_size } else { defaults.fullsweep_after };
+                Ok(ErlangTerm::Tuple(vec![ErlangTerm::Atom(item_str.clone()), ErlangTerm::Integer(value as i64)]))
+            }
+            "max_heap_size" => {
+                // {max_heap_size, #{size => Words}} default for new processes
+                Ok(ErlangTerm::Tuple(vec![
+                    ErlangTerm::Atom("max_heap_size".to_string()),
+                    max_heap_size_map(process_defaults().max_heap_size),
+                ]))
+            }
+            "backtrace_depth" => {
+                // Frames kept in exception backtraces
+                Ok(ErlangTerm::Integer(process_defaults().backtrace_depth as i64))
+            }
+            "ets_count" => {
+                // There is no ETS table registry to count tables in
+                Err(InfoError::NotSupported(
+                    "ets_count: ETS tables are not tracked".to_string(),
+                ))
+            }
+            "allocator" => {
+                // {Allocator, Version, Features, Settings}: memory comes from
+                // the Rust global allocator; the features are the alloc_util
+                // strategies (good fit, best fit, a fit, first fit)
+                let features = ["gf", "bf", "af", "aoff"]
+                    .iter()
+                    .map(|strategy| ErlangTerm::Atom(strategy.to_string()))
+                    .collect();
+                Ok(ErlangTerm::Tuple(vec![
+                    ErlangTerm::Atom("rust_std".to_string()),
+                    ErlangTerm::List(Vec::new()),
+                    ErlangTerm::List(features),
+                    ErlangTerm::List(Vec::new()),
+                ]))
+            }
+            "alloc_util_allocators" => {
+                // Names of the instrumented allocator types
+                Ok(ErlangTerm::List(
+                    AllocatorKind::ALL
+                        .iter()
+                        .map(|kind| ErlangTerm::Atom(kind.name().to_string()))
+                        .collect(),
+                ))
             }
             "system_version" => {
                 // System version string
@@ -170,7 +288,7 @@ impl InfoBif {
             }
             "wordsize" => {
                 // Word size in bytes
-                Ok(ErlangTerm::Integer(8)) // 64-bit system
+                Ok(ErlangTerm::Integer(std::mem::size_of::<usize>() as i64))
             }
             "otp_release" => {
                 // OTP release version
@@ -186,6 +304,296 @@ impl InfoBif {
         }
     }
 
+    /// Build the `{allocator, Alloc}` report of an allocator type
+    ///
+    /// Returns `false` for names that are not allocator types, as
+    /// erts_alloc_info does for unknown allocators.
+    fn allocator_info_term(name: &str) -> ErlangTerm {
+        let Some(kind) = AllocatorKind::from_name(name) else {
+            return ErlangTerm::Atom("false".to_string());
+        };
+        let info = allocator_info(kind);
+        let strategy = match info.strategy {
+            AllocatorType::GoodFit => "gf",
+            AllocatorType::BestFit => "bf",
+            AllocatorType::AFit => "af",
+            AllocatorType::FirstFit => "aoff",
+        };
+        let pair = |key: &str, value: ErlangTerm| ErlangTerm::Tuple(vec![ErlangTerm::Atom(key.to_string()), value]);
+        let carriers = |stats: CarrierStats| {
+            ErlangTerm::List(vec![
+                ErlangTerm::Tuple(vec![
+                    ErlangTerm::Atom("blocks".to_string()),
+                    ErlangTerm::Integer(stats.blocks as i64),
+                    ErlangTerm::Integer(stats.max_blocks as i64),
+                ]),
+                ErlangTerm::Tuple(vec![
+                    ErlangTerm::Atom("blocks_size".to_string()),
+                    ErlangTerm::Integer(stats.blocks_size as i64),
+                    ErlangTerm::Integer(stats.max_blocks_size as i64),
+                ]),
+                pair("carriers", ErlangTerm::Integer(stats.carriers as i64)),
+                pair("carriers_size", ErlangTerm::Integer(stats.carriers_size as i64)),
+            ])
+        };
+        let report = vec![
+            pair(
+                "options",
+                ErlangTerm::List(vec![
+                    pair("as", ErlangTerm::Atom(strategy.to_string())),
+                    pair("sbct", ErlangTerm::Integer(info.sbc_threshold as i64)),
+                    pair("lmbcs", ErlangTerm::Integer(info.mbc_size as i64)),
+                ]),
+            ),
+            pair("mbcs", carriers(info.mbcs)),
+            pair("sbcs", carriers(info.sbcs)),
+            pair(
+                "calls",
+                ErlangTerm::List(vec![
+                    pair("alloc", ErlangTerm::Integer(info.calls.alloc as i64)),
+                    pair("free", ErlangTerm::Integer(info.calls.free as i64)),
+                    pair("realloc", ErlangTerm::Integer(info.calls.realloc as i64)),
+                ]),
+            ),
+        ];
+        // A single instance per allocator type
+        ErlangTerm::List(vec![ErlangTerm::Tuple(vec![
+            ErlangTerm::Atom("instance".to_string()),
+            ErlangTerm::Integer(0),
+            ErlangTerm::List(report),
+        ])])
+    }
+
+    /// Set a runtime tunable (system_flag/2)
+    ///
+    /// Scheduler flags take effect immediately. The heap flags change the
+    /// defaults given to processes spawned afterwards; existing processes
+    /// keep their settings.
+    ///
+    /// # Arguments
+    /// * `flag` - Flag (atom): `schedulers_online`, `dirty_cpu_schedulers_online`,
+    ///   `fullsweep_after`, `min_heap_size`, `max_heap_size` or `backtrace_depth`
+    /// * `value` - New value: a non-negative integer, or for `max_heap_size`
+    ///   also a map with a `size` key
+    ///
+    /// # Returns
+    /// * `Ok(ErlangTerm)` - The old value
+    /// * `Err(InfoError)` - If the flag or value is invalid, or the
+    ///   schedulers are not initialized
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::info::InfoBif;
+    /// use usecases_bifs::op::ErlangTerm;
+    ///
+    /// let flag = ErlangTerm::Atom("backtrace_depth".to_string());
+    /// let old = InfoBif::system_flag_2(&flag, &ErlangTerm::Integer(16)).unwrap();
+    /// assert_eq!(old, ErlangTerm::Integer(8));
+    /// ```
+    pub fn system_flag_2(flag: &ErlangTerm, value: &ErlangTerm) -> Result<ErlangTerm, InfoError> {
+        let flag_str = match flag {
+            ErlangTerm::Atom(name) => name.as_str(),
+            _ => return Err(InfoError::BadArgument("System flag must be an atom".to_string())),
+        };
+
+        match flag_str {
+            "schedulers_online" => {
+                let old = set_schedulers_online(non_negative(flag_str, value)?)
+                    .map_err(InfoError::BadArgument)?;
+                Ok(ErlangTerm::Integer(old as i64))
+            }
+            "dirty_cpu_schedulers_online" => {
+                let old = set_dirty_cpu_schedulers_online(non_negative(flag_str, value)?)
+                    .map_err(InfoError::BadArgument)?;
+                Ok(ErlangTerm::Integer(old as i64))
+            }
+            "fullsweep_after" => {
+                let words = non_negative(flag_str, value)?;
+                let old = update_process_defaults(|defaults| defaults.fullsweep_after = words);
+                Ok(ErlangTerm::Integer(old.fullsweep_after as i64))
+            }
+            "min_heap_size" => {
+                let words = non_negative(flag_str, value)?;
+                let old = update_process_defaults(|defaults| defaults.min_heap_size = words);
+                Ok(ErlangTerm::Integer(old.min_heap_size as i64))
+            }
+            "max_heap_size" => {
+                let words = match value {
+                    ErlangTerm::Map(map) => match map.get(&ErlangTerm::Atom("size".to_string())) {
+                        Some(size) => non_negative(flag_str, size)?,
+                        None => process_defaults().max_heap_size,
+                    },
+                    _ => non_negative(flag_str, value)?,
+                };
+                if words != 0 && words < process_defaults().min_heap_size {
+                    return Err(InfoError::BadArgument(
+                        "max_heap_size must not be below min_heap_size".to_string(),
+                    ));
+                }
+                let old = update_process_defaults(|defaults| defaults.max_heap_size = words);
+                Ok(max_heap_size_map(old.max_heap_size))
+            }
+            "backtrace_depth" => {
+                let depth = non_negative(flag_str, value)?.min(MAX_BACKTRACE_DEPTH);
+                let old = update_process_defaults(|defaults| defaults.backtrace_depth = depth);
+                Ok(ErlangTerm::Integer(old.backtrace_depth as i64))
+            }
+            _ => Err(InfoError::BadArgument(format!("Unknown system flag: {}", flag_str))),
+        }
+    }
+
+    /// Get runtime statistics (statistics/1)
+    ///
+    /// Reads the global statistics counters. Items that return
+    /// `{Total, SinceLastCall}` remember their own last call.
+    ///
+    /// # Arguments
+    /// * `item` - Statistics item (atom): `reductions`, `exact_reductions`,
+    ///   `run_queue`, `run_queue_lengths`, `total_run_queue_lengths`, `io`,
+    ///   `garbage_collection`, `wall_clock` or `runtime`
+    ///
+    /// # Returns
+    /// * `Ok(ErlangTerm)` - Statistics value, shaped as in Erlang
+    /// * `Err(InfoError)` - If the item is not an atom or not supported
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::info::InfoBif;
+    /// use usecases_bifs::op::ErlangTerm;
+    ///
+    /// // {{input, In}, {output, Out}}
+    /// let result = InfoBif::statistics_1(&ErlangTerm::Atom("io".to_string()));
+    /// assert!(matches!(result, Ok(ErlangTerm::Tuple(_))));
+    ///
+    /// // Invalid: unknown item
+    /// let result = InfoBif::statistics_1(&ErlangTerm::Atom("bogus".to_string()));
+    /// assert!(result.is_err());
+    /// ```
+    pub fn statistics_1(item: &ErlangTerm) -> Result<ErlangTerm, InfoError> {
+        let item_str = match item {
+            ErlangTerm::Atom(name) => name.as_str(),
+            _ => {
+                return Err(InfoError::BadArgument(
+                    "Statistics item must be an atom".to_string(),
+                ));
+            }
+        };
+
+        let statistics = get_global_statistics();
+        let pair = |(total, since): (u64, u64)| {
+            ErlangTerm::Tuple(vec![ErlangTerm::Integer(total as i64), ErlangTerm::Integer(since as i64)])
+        };
+        match item_str {
+            "reductions" => Ok(pair(statistics.reductions())),
+            "exact_reductions" => Ok(pair(statistics.exact_reductions())),
+            "wall_clock" => Ok(pair(statistics.wall_clock())),
+            "runtime" => Ok(pair(statistics.runtime())),
+            "run_queue" | "total_run_queue_lengths" => {
+                Ok(ErlangTerm::Integer(statistics.total_run_queue_length() as i64))
+            }
+            "run_queue_lengths" => Ok(ErlangTerm::List(
+                statistics
+                    .run_queue_lengths()
+                    .into_iter()
+                    .map(|len| ErlangTerm::Integer(len as i64))
+                    .collect(),
+            )),
+            "io" => {
+                let (input, output) = statistics.io();
+                Ok(ErlangTerm::Tuple(vec![
+                    ErlangTerm::Tuple(vec![ErlangTerm::Atom("input".to_string()), ErlangTerm::Integer(input as i64)]),
+                    ErlangTerm::Tuple(vec![ErlangTerm::Atom("output".to_string()), ErlangTerm::Integer(output as i64)]),
+                ]))
+            }
+            "garbage_collection" => {
+                let (count, words) = statistics.garbage_collection();
+                Ok(ErlangTerm::Tuple(vec![
+                    ErlangTerm::Integer(count as i64),
+                    ErlangTerm::Integer(words as i64),
+                    ErlangTerm::Integer(0),
+                ]))
+            }
+            _ => Err(InfoError::BadArgument(format!(
+                "Unknown statistics item: {}",
+                item_str
+            ))),
+        }
+    }
+
+    /// Get the memory of the runtime system (memory/0)
+    ///
+    /// # Returns
+    /// List of `{Type, Size}` for every memory type, sizes in bytes
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::info::InfoBif;
+    /// use usecases_bifs::op::ErlangTerm;
+    ///
+    /// let ErlangTerm::List(items) = InfoBif::memory_0() else { unreachable!() };
+    /// assert_eq!(items.len(), 9);
+    /// ```
+    pub fn memory_0() -> ErlangTerm {
+        ErlangTerm::List(
+            erts_memory()
+                .items()
+                .into_iter()
+                .map(|(memory_type, size)| {
+                    ErlangTerm::Tuple(vec![ErlangTerm::Atom(memory_type.to_string()), ErlangTerm::Integer(size as i64)])
+                })
+                .collect(),
+        )
+    }
+
+    /// Get the memory of the runtime system by type (memory/1)
+    ///
+    /// # Arguments
+    /// * `types` - A memory type (atom), or a list of memory types
+    ///
+    /// # Returns
+    /// * `Ok(ErlangTerm::Integer)` - Size in bytes of a single type
+    /// * `Ok(ErlangTerm::List)` - `{Type, Size}` for each type of a list, in list order
+    /// * `Err(InfoError::BadArgument)` - Unknown memory type
+    pub fn memory_1(types: &ErlangTerm) -> Result<ErlangTerm, InfoError> {
+        let memory = erts_memory();
+        let size = |memory_type: &ErlangTerm| match memory_type {
+            ErlangTerm::Atom(name) => memory
+                .get(name)
+                .map(|size| ErlangTerm::Integer(size as i64))
+                .ok_or_else(|| InfoError::BadArgument(format!("Unknown memory type: {}", name))),
+            _ => Err(InfoError::BadArgument("Memory type must be an atom".to_string())),
+        };
+        match types {
+            ErlangTerm::Atom(_) => size(types),
+            ErlangTerm::Nil => Ok(ErlangTerm::Nil),
+            ErlangTerm::List(items) => items
+                .iter()
+                .map(|item| Ok(ErlangTerm::Tuple(vec![item.clone(), size(item)?])))
+                .collect::<Result<Vec<_>, _>>()
+                .map(ErlangTerm::List),
+            _ => Err(InfoError::BadArgument(
+                "Memory type must be an atom or a list of atoms".to_string(),
+            )),
+        }
+    }
+
+    /// List all existing processes (processes/0)
+    ///
+    /// Exiting processes exist until they are removed from the process
+    /// table, so they are included.
+    ///
+    /// # Returns
+    /// List of the pids in a snapshot of the process table, in table order
+    pub fn processes_0() -> ErlangTerm {
+        ErlangTerm::List(
+            get_global_process_table()
+                .list()
+                .into_iter()
+                .map(ErlangTerm::Pid)
+                .collect(),
+        )
+    }
+
     /// Get process information (process_info/1)
     ///
     /// Returns information about a process. Returns a list of all process information.
@@ -234,6 +642,12 @@ impl InfoBif {
         // Build process information list
         let mut info = Vec::new();
 
+        // Initial call
+        info.push(ErlangTerm::Tuple(vec![
+            ErlangTerm::Atom("initial_call".to_string()),
+            Self::initial_call_term(&process),
+        ]));
+
         // Status
         let status_str = match process.get_state() {
             ProcessState::Free => "free",
@@ -328,10 +742,11 @@ impl InfoBif {
     ///
     /// # Arguments
     /// * `pid` - Process ID
-    /// * `item` - Information item to retrieve (atom)
+    /// * `item` - Information item to retrieve (atom), or a list of items
     ///
     /// # Returns
-    /// * `Ok(ErlangTerm)` - Process information value
+    /// * `Ok(ErlangTerm)` - Process information value, or a list of
+    ///   `{Item, Value}` tuples when `item` is a list
     /// * `Err(InfoError)` - If operation fails
     ///
     /// # Examples
@@ -370,15 +785,6 @@ impl InfoBif {
             }
         };
 
-        let item_str = match item {
-            ErlangTerm::Atom(name) => name.clone(),
-            _ => {
-                return Err(InfoError::BadArgument(
-                    "Process info item must be an atom".to_string(),
-                ));
-            }
-        };
-
         // Look up the process in the process table
         let table = get_global_process_table();
         let process = table.lookup(pid_value as ProcessId)
@@ -386,9 +792,33 @@ impl InfoBif {
                 format!("Process with PID {} not found", pid_value)
             ))?;
 
-        // Return the specific requested information item
-        // Return the specific requested information item from the actual process
-        match item_str.as_str() {
+        match item {
+            ErlangTerm::Atom(name) => Self::process_info_item(&process, name),
+            // A list of items returns a list of {Item, Value} tuples
+            ErlangTerm::List(items) => {
+                let mut info = Vec::with_capacity(items.len());
+                for item in items {
+                    let ErlangTerm::Atom(name) = item else {
+                        return Err(InfoError::BadArgument(
+                            "Process info item must be an atom".to_string(),
+                        ));
+                    };
+                    info.push(ErlangTerm::Tuple(vec![
+                        ErlangTerm::Atom(name.clone()),
+                        Self::process_info_item(&process, name)?,
+                    ]));
+                }
+                Ok(ErlangTerm::List(info))
+            }
+            _ => Err(InfoError::BadArgument(
+                "Process info item must be an atom or a list of atoms".to_string(),
+            )),
+        }
+    }
+
+    /// Get a single process information item
+    fn process_info_item(process: &Process, item_str: &str) -> Result<ErlangTerm, InfoError> {
+        match item_str {
             "status" => {
                 let status_str = match process.get_state() {
                     ProcessState::Free => "free",
@@ -444,14 +874,12 @@ impl InfoBif {
                 ErlangTerm::Integer(2),
                 ]))
             },
-            "initial_call" => {
-                // Initial call not yet available in Process struct
-                Ok(ErlangTerm::Tuple(vec![
-                ErlangTerm::Atom("erlang".to_string()),
-                ErlangTerm::Atom("apply".to_string()),
-                ErlangTerm::Integer(2),
-                ]))
-            },
+            "initial_call" => Ok(Self::initial_call_term(process)),
+            "group_leader" => Ok(ErlangTerm::Pid(process.group_leader())),
+            "parent" => Ok(match process.parent() {
+                Some(parent) => ErlangTerm::Pid(parent),
+                None => ErlangTerm::Atom("undefined".to_string()),
+            }),
             "dictionary" => {
                 // Process dictionary not yet integrated, return empty list
                 Ok(ErlangTerm::List(vec![]))
@@ -467,6 +895,24 @@ impl InfoBif {
         }
     }
 
+    /// Build the `{Module, Function, Arity}` term for a process's initial call
+    ///
+    /// Processes not created by a spawn report `{erlang, apply, 2}`.
+    fn initial_call_term(process: &Process) -> ErlangTerm {
+        match process.initial_call() {
+            Some(call) => ErlangTerm::Tuple(vec![
+                ErlangTerm::Atom(call.module.clone()),
+                ErlangTerm::Atom(call.function.clone()),
+                ErlangTerm::Integer(call.arity as i64),
+            ]),
+            None => ErlangTerm::Tuple(vec![
+                ErlangTerm::Atom("erlang".to_string()),
+                ErlangTerm::Atom("apply".to_string()),
+                ErlangTerm::Integer(2),
+            ]),
+        }
+    }
+
     /// Get module information (get_module_info/1)
     ///
     /// Returns all information about a module.
@@ -711,10 +1157,116 @@ impl InfoBif {
     }
 }
 
+/// Value of a system flag that must be a non-negative integer
+fn non_negative(flag: &str, value: &ErlangTerm) -> Result<usize, InfoError> {
+    match value {
+        ErlangTerm::Integer(n) if *n >= 0 => Ok(*n as usize),
+        _ => Err(InfoError::BadArgument(format!(
+            "{} must be a non-negative integer",
+            flag
+        ))),
+    }
+}
+
+/// `max_heap_size` setting as the map returned by system_flag/2 and system_info/1
+fn max_heap_size_map(words: usize) -> ErlangTerm {
+    let mut map = std::collections::HashMap::new();
+    map.insert(ErlangTerm::Atom("size".to_string()), ErlangTerm::Integer(words as i64));
+    ErlangTerm::Map(map)
+}
+
+/// Number of schedulers and schedulers online
+///
+/// Before the schedulers are initialized, one scheduler per logical
+/// processor is reported, as ERTS starts by default.
+fn scheduler_counts() -> (usize, usize) {
+    match get_global_schedulers() {
+        Some(schedulers) => {
+            let schedulers = schedulers.lock().unwrap();
+            let online = schedulers.iter().filter(|scheduler| scheduler.is_active()).count();
+            (schedulers.len(), online)
+        }
+        None => {
+            let count = std::thread::available_parallelism().map_or(1, |count| count.get());
+            (count, count)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn statistics(item: &str) -> ErlangTerm {
+        InfoBif::statistics_1(&ErlangTerm::Atom(item.to_string())).unwrap()
+    }
+
+    #[test]
+    fn test_memory() {
+        let atom = |name: &str| ErlangTerm::Atom(name.to_string());
+        let ErlangTerm::List(items) = InfoBif::memory_0() else { panic!("memory/0 must return a list") };
+        assert_eq!(items.len(), 9);
+        assert!(matches!(&items[0], ErlangTerm::Tuple(pair) if pair[0] == atom("total")));
+
+        assert!(matches!(InfoBif::memory_1(&atom("ets")), Ok(ErlangTerm::Integer(_))));
+
+        // The sizes of one call are taken from a single snapshot
+        let types = ErlangTerm::List(vec![atom("total"), atom("processes"), atom("system")]);
+        let ErlangTerm::List(pairs) = InfoBif::memory_1(&types).unwrap() else { panic!("expected a list") };
+        let sizes: Vec<i64> = pairs
+            .iter()
+            .map(|pair| match pair {
+                ErlangTerm::Tuple(pair) => match pair[1] {
+                    ErlangTerm::Integer(size) => size,
+                    _ => panic!("expected an integer size"),
+                },
+                _ => panic!("expected {{Type, Size}}"),
+            })
+            .collect();
+        assert_eq!(sizes[0], sizes[1] + sizes[2]);
+
+        assert!(InfoBif::memory_1(&atom("maximum")).is_err());
+        assert!(InfoBif::memory_1(&ErlangTerm::List(vec![atom("total"), ErlangTerm::Integer(1)])).is_err());
+    }
+
+    #[test]
+    fn test_statistics_1_reductions() {
+        get_global_statistics().add_reductions(1, 500);
+        let ErlangTerm::Tuple(first) = statistics("reductions") else { panic!("not a tuple") };
+        assert!(matches!(first[0], ErlangTerm::Integer(total) if total >= 500));
+        get_global_statistics().add_reductions(0, 25);
+        let ErlangTerm::Tuple(second) = statistics("reductions") else { panic!("not a tuple") };
+        assert!(matches!(second[1], ErlangTerm::Integer(since) if since >= 25));
+    }
+
+    #[test]
+    fn test_statistics_1_io_and_garbage_collection() {
+        get_global_statistics().add_bytes_in(3);
+        get_global_statistics().record_garbage_collection(64);
+        let ErlangTerm::Tuple(io) = statistics("io") else { panic!("not a tuple") };
+        assert!(matches!(&io[0], ErlangTerm::Tuple(input)
+            if input[0] == ErlangTerm::Atom("input".to_string())
+                && matches!(input[1], ErlangTerm::Integer(n) if n >= 3)));
+        let ErlangTerm::Tuple(gc) = statistics("garbage_collection") else { panic!("not a tuple") };
+        assert_eq!(gc.len(), 3);
+        assert!(matches!(gc[1], ErlangTerm::Integer(words) if words >= 64));
+    }
+
+    #[test]
+    fn test_statistics_1_run_queue_lengths() {
+        get_global_statistics().set_run_queue_length(3, 2);
+        let ErlangTerm::List(lengths) = statistics("run_queue_lengths") else { panic!("not a list") };
+        assert!(lengths.len() >= 4);
+        assert_eq!(lengths[3], ErlangTerm::Integer(2));
+        assert_eq!(statistics("run_queue"), statistics("total_run_queue_lengths"));
+    }
+
+    #[test]
+    fn test_statistics_1_invalid_item() {
+        assert!(InfoBif::statistics_1(&ErlangTerm::Atom("bogus".to_string())).is_err());
+        assert!(InfoBif::statistics_1(&ErlangTerm::Integer(1)).is_err());
+    }
+
     #[test]
     fn test_system_info_1_scheduler_id() {
         let result = InfoBif::system_info_1(&ErlangTerm::Atom("scheduler_id".to_string())).unwrap();
@@ -752,15 +1304,127 @@ mod tests {
     }
 
     #[test]
-    fn test_system_info_1_port_limit_not_supported() {
-        // Ports are not supported in this version
-        let result = InfoBif::system_info_1(&ErlangTerm::Atom("port_limit".to_string()));
-        assert!(result.is_err());
-        if let Err(InfoError::BadArgument(msg)) = result {
-            assert!(msg.contains("port_limit") || msg.contains("Unknown system info item"));
-        } else {
-            panic!("Expected BadArgument error for port_limit");
-        }
+    fn test_system_info_1_port_count_and_limit() {
+        let limit = InfoBif::system_info_1(&ErlangTerm::Atom("port_limit".to_string())).unwrap();
+        assert_eq!(limit, ErlangTerm::Integer(get_global_port_table().max_ports() as i64));
+        let count = InfoBif::system_info_1(&ErlangTerm::Atom("port_count".to_string())).unwrap();
+        assert!(matches!(count, ErlangTerm::Integer(n) if n >= 0));
+    }
+
+    #[test]
+    fn test_system_info_1_atom_count_and_limit() {
+        use entities_data_handling::AtomEncoding;
+
+        get_global_atom_table().put_index(b"system_info_atom_count", AtomEncoding::SevenBitAscii, false).unwrap();
+        let count = InfoBif::system_info_1(&ErlangTerm::Atom("atom_count".to_string())).unwrap();
+        assert!(matches!(count, ErlangTerm::Integer(n) if n >= 1));
+        let limit = InfoBif::system_info_1(&ErlangTerm::Atom("atom_limit".to_string())).unwrap();
+        assert_eq!(limit, ErlangTerm::Integer(1_048_576));
+    }
+
+    #[test]
+    fn test_system_info_1_process_count() {
+        use std::sync::Arc;
+
+        let process = Arc::new(Process::new(990001));
+        get_global_process_table().insert(990001, process);
+        let count = InfoBif::system_info_1(&ErlangTerm::Atom("process_count".to_string())).unwrap();
+        assert!(matches!(count, ErlangTerm::Integer(n) if n >= 1));
+        get_global_process_table().remove(990001);
+    }
+
+    #[test]
+    fn test_system_info_1_schedulers() {
+        let info = |item: &str| InfoBif::system_info_1(&ErlangTerm::Atom(item.to_string())).unwrap();
+        let ErlangTerm::Integer(schedulers) = info("schedulers") else { panic!("not an integer") };
+        let ErlangTerm::Integer(online) = info("schedulers_online") else { panic!("not an integer") };
+        assert!(schedulers >= 1 && online <= schedulers);
+        assert_eq!(info("dirty_cpu_schedulers"), ErlangTerm::Integer(schedulers));
+        assert_eq!(info("dirty_io_schedulers"), ErlangTerm::Integer(10));
+        assert!(matches!(info("logical_processors"), ErlangTerm::Integer(n) if n >= 1));
+    }
+
+    #[test]
+    fn test_system_flag_2_backtrace_depth() {
+        let flag = ErlangTerm::Atom("backtrace_depth".to_string());
+        let old = InfoBif::system_flag_2(&flag, &ErlangTerm::Integer(1000)).unwrap();
+        assert_eq!(InfoBif::system_info_1(&flag).unwrap(), ErlangTerm::Integer(MAX_BACKTRACE_DEPTH as i64));
+        InfoBif::system_flag_2(&flag, &old).unwrap();
+    }
+
+    #[test]
+    fn test_system_flag_2_heap_defaults() {
+        // Set the defaults to their current values: other tests check the
+        // heap size of new processes
+        let min = ErlangTerm::Atom("min_heap_size".to_string());
+        assert_eq!(InfoBif::system_flag_2(&min, &ErlangTerm::Integer(233)), Ok(ErlangTerm::Integer(233)));
+        assert_eq!(
+            InfoBif::system_info_1(&min).unwrap(),
+            ErlangTerm::Tuple(vec![min.clone(), ErlangTerm::Integer(233)])
+        );
+
+        let max = ErlangTerm::Atom("max_heap_size".to_string());
+        assert_eq!(InfoBif::system_flag_2(&max, &max_heap_size_map(0)), Ok(max_heap_size_map(0)));
+        assert!(InfoBif::system_flag_2(&max, &ErlangTerm::Integer(10)).is_err());
+    }
+
+    #[test]
+    fn test_system_flag_2_invalid() {
+        let flag = |name: &str| ErlangTerm::Atom(name.to_string());
+        assert!(InfoBif::system_flag_2(&flag("fullsweep_after"), &ErlangTerm::Integer(-1)).is_err());
+        assert!(InfoBif::system_flag_2(&flag("min_heap_size"), &flag("big")).is_err());
+        assert!(InfoBif::system_flag_2(&flag("bogus"), &ErlangTerm::Integer(1)).is_err());
+        assert!(InfoBif::system_flag_2(&ErlangTerm::Integer(1), &ErlangTerm::Integer(1)).is_err());
+        // The schedulers are not initialized in these tests
+        assert!(InfoBif::system_flag_2(&flag("schedulers_online"), &ErlangTerm::Integer(1)).is_err());
+    }
+
+    #[test]
+    fn test_system_info_1_allocator() {
+        let result = InfoBif::system_info_1(&ErlangTerm::Atom("allocator".to_string())).unwrap();
+        let ErlangTerm::Tuple(parts) = result else { panic!("not a tuple") };
+        assert_eq!(parts.len(), 4);
+        assert_eq!(parts[0], ErlangTerm::Atom("rust_std".to_string()));
+        assert!(matches!(&parts[2], ErlangTerm::List(features) if features.contains(&ErlangTerm::Atom("bf".to_string()))));
+        assert!(matches!(
+            InfoBif::system_info_1(&ErlangTerm::Atom("ets_count".to_string())),
+            Err(InfoError::NotSupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_system_info_1_allocator_report() {
+        let allocator = |name: &str| {
+            InfoBif::system_info_1(&ErlangTerm::Tuple(vec![
+                ErlangTerm::Atom("allocator".to_string()),
+                ErlangTerm::Atom(name.to_string()),
+            ]))
+            .unwrap()
+        };
+        assert_eq!(allocator("no_such_alloc"), ErlangTerm::Atom("false".to_string()));
+
+        let ErlangTerm::List(instances) = allocator("ll_alloc") else { panic!("not a list") };
+        let [ErlangTerm::Tuple(instance)] = instances.as_slice() else { panic!("not one instance") };
+        assert_eq!(instance[0], ErlangTerm::Atom("instance".to_string()));
+        let ErlangTerm::List(report) = &instance[2] else { panic!("no report") };
+        let keys: Vec<_> = report
+            .iter()
+            .map(|entry| match entry {
+                ErlangTerm::Tuple(pair) => pair[0].clone(),
+                _ => panic!("not a pair"),
+            })
+            .collect();
+        assert_eq!(
+            keys,
+            ["options", "mbcs", "sbcs", "calls"].map(|key| ErlangTerm::Atom(key.to_string()))
+        );
+
+        let ErlangTerm::List(names) =
+            InfoBif::system_info_1(&ErlangTerm::Atom("alloc_util_allocators".to_string())).unwrap()
+        else {
+            panic!("not a list")
+        };
+        assert!(names.contains(&ErlangTerm::Atom("binary_alloc".to_string())));
     }
 
     #[test]
@@ -788,6 +1452,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_processes_0() {
+        use entities_process::Process;
+        use std::sync::Arc;
+
+        let table = get_global_process_table();
+        table.insert(41201, Arc::new(Process::new(41201)));
+        let ErlangTerm::List(pids) = InfoBif::processes_0() else { panic!("Expected List") };
+        assert!(pids.contains(&ErlangTerm::Pid(41201)));
+        table.remove(41201);
+        let ErlangTerm::List(pids) = InfoBif::processes_0() else { panic!("Expected List") };
+        assert!(!pids.contains(&ErlangTerm::Pid(41201)));
+    }
+
     #[test]
     fn test_process_info_1_invalid_pid() {
         let result = InfoBif::process_info_1(&ErlangTerm::Integer(123));
@@ -1158,6 +1836,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_process_info_2_spawn_metadata() {
+        use infrastructure_utilities::process_table::get_global_process_table;
+        use entities_process::{InitialCall, Process};
+        use std::sync::Arc;
+
+        let table = get_global_process_table();
+        let process = Process::spawned(40651, Some(40650), InitialCall::new("my_server", "init", 1));
+        table.insert(40651, Arc::new(process));
+
+        let result = InfoBif::process_info_2(
+            &ErlangTerm::Pid(40651),
+            &ErlangTerm::List(vec![
+                ErlangTerm::Atom("parent".to_string()),
+                ErlangTerm::Atom("initial_call".to_string()),
+            ]),
+        ).unwrap();
+        assert_eq!(
+            result,
+            ErlangTerm::List(vec![
+                ErlangTerm::Tuple(vec![
+                    ErlangTerm::Atom("parent".to_string()),
+                    ErlangTerm::Pid(40650),
+                ]),
+                ErlangTerm::Tuple(vec![
+                    ErlangTerm::Atom("initial_call".to_string()),
+                    ErlangTerm::Tuple(vec![
+                        ErlangTerm::Atom("my_server".to_string()),
+                        ErlangTerm::Atom("init".to_string()),
+                        ErlangTerm::Integer(1),
+                    ]),
+                ]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_process_info_2_parent_undefined() {
+        use infrastructure_utilities::process_table::get_global_process_table;
+        use entities_process::{InitialCall, Process};
+        use std::sync::Arc;
+
+        let table = get_global_process_table();
+        table.insert(40652, Arc::new(Process::spawned(40652, None, InitialCall::new("init", "boot", 1))));
+
+        let result = InfoBif::process_info_2(
+            &ErlangTerm::Pid(40652),
+            &ErlangTerm::Atom("parent".to_string()),
+        ).unwrap();
+        assert_eq!(result, ErlangTerm::Atom("undefined".to_string()));
+
+        let result = InfoBif::process_info_2(
+            &ErlangTerm::Pid(40652),
+            &ErlangTerm::List(vec![ErlangTerm::Integer(1)]),
+        );
+        assert!(matches!(result, Err(InfoError::BadArgument(_))));
+    }
+
     #[test]
     fn test_process_info_2_dictionary() {
         // Set up: Create a process in the process table
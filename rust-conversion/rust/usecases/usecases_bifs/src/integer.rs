@@ -0,0 +1,313 @@
+//! Integer Conversion Built-in Functions
+//!
+//! Provides the conversions between integers and their text:
+//! - `integer_to_binary/1,2` and `integer_to_list/1,2`
+//! - `binary_to_integer/1,2` and `list_to_integer/1,2`
+//!
+//! Bases 2 to 36 are supported. Digits above 9 are written as upper case
+//! letters and read in either case. Text is an optional `+` or `-` sign
+//! followed by at least one digit; anything else, including whitespace, is
+//! a badarg.
+//!
+//! Integers that fit in 64 bits are converted without going through a
+//! bignum, so only the result is allocated. Longer text falls back to
+//! [`BigNumber`], and results are normalized as by the arithmetic BIFs:
+//! small when they fit, bignum otherwise.
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ *
+ * Creation productivity increased for code in this file by using AALang and GAB.
+ * See https://github.com/yenrab/AALang-Gab
+ */
+
+use crate::arith::integer_result;
+use crate::op::ErlangTerm;
+use entities_data_handling::binary::RefcBinary;
+use entities_utilities::BigNumber;
+
+/// Smallest base accepted by the conversions
+pub const MIN_BASE: u32 = 2;
+/// Largest base accepted by the conversions
+pub const MAX_BASE: u32 = 36;
+
+/// Most characters of a 64-bit integer written in base 2, with its sign
+const MAX_SMALL_DIGITS: usize = 65;
+
+/// Error type for integer conversion BIFs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegerError {
+    /// Bad argument (`badarg`)
+    BadArgument(String),
+}
+
+impl std::fmt::Display for IntegerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntegerError::BadArgument(msg) => write!(f, "Bad argument: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for IntegerError {}
+
+/// Integer conversion BIF operations
+pub struct IntegerBif;
+
+impl IntegerBif {
+    /// Write an integer as a binary (`integer_to_binary/2`)
+    ///
+    /// # Returns
+    /// * `Ok(RefcBinary)` - The text of the integer
+    /// * `Err(IntegerError::BadArgument)` - `value` is not an integer or
+    ///   `base` is out of range
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::integer::IntegerBif;
+    /// use usecases_bifs::op::ErlangTerm;
+    ///
+    /// let text = IntegerBif::integer_to_binary(&ErlangTerm::Integer(-255), 16).unwrap();
+    /// assert_eq!(text.data(), b"-FF");
+    /// ```
+    pub fn integer_to_binary(value: &ErlangTerm, base: u32) -> Result<RefcBinary, IntegerError> {
+        check_base(base)?;
+        match value {
+            ErlangTerm::Integer(n) => {
+                let mut buffer = [0u8; MAX_SMALL_DIGITS];
+                Ok(RefcBinary::new(write_small(*n, base, &mut buffer).to_vec()))
+            }
+            ErlangTerm::BigInteger(bn) => {
+                let mut text = bn.to_string_base(base).into_bytes();
+                text.make_ascii_uppercase();
+                Ok(RefcBinary::new(text))
+            }
+            _ => Err(IntegerError::BadArgument(format!("{:?} is not an integer", value))),
+        }
+    }
+
+    /// Write an integer as text (`integer_to_list/2`)
+    ///
+    /// See [`IntegerBif::integer_to_binary`].
+    pub fn integer_to_list(value: &ErlangTerm, base: u32) -> Result<String, IntegerError> {
+        check_base(base)?;
+        match value {
+            ErlangTerm::Integer(n) => {
+                let mut buffer = [0u8; MAX_SMALL_DIGITS];
+                let text = write_small(*n, base, &mut buffer);
+                Ok(String::from_utf8_lossy(text).into_owned())
+            }
+            ErlangTerm::BigInteger(bn) => Ok(bn.to_string_base(base).to_uppercase()),
+            _ => Err(IntegerError::BadArgument(format!("{:?} is not an integer", value))),
+        }
+    }
+
+    /// Read an integer from a binary (`binary_to_integer/2`)
+    ///
+    /// # Returns
+    /// * `Ok(ErlangTerm)` - The integer, small if it fits in 64 bits
+    /// * `Err(IntegerError::BadArgument)` - The binary is not an integer in
+    ///   `base` or `base` is out of range
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::integer::IntegerBif;
+    /// use usecases_bifs::op::ErlangTerm;
+    ///
+    /// assert_eq!(IntegerBif::binary_to_integer(b"-ff", 16), Ok(ErlangTerm::Integer(-255)));
+    /// assert!(IntegerBif::binary_to_integer(b"1 ", 10).is_err());
+    /// ```
+    pub fn binary_to_integer(binary: &[u8], base: u32) -> Result<ErlangTerm, IntegerError> {
+        check_base(base)?;
+        let bad = || IntegerError::BadArgument(format!("{:?} is not an integer in base {}", String::from_utf8_lossy(binary), base));
+        if let Some(n) = read_small(binary, base).map_err(|_| bad())? {
+            return Ok(ErlangTerm::Integer(n));
+        }
+        // Too long for 64 bits; the bignum parser checks the remaining digits
+        let text = std::str::from_utf8(binary).map_err(|_| bad())?;
+        BigNumber::from_string_base(text, base).map(integer_result).ok_or_else(bad)
+    }
+
+    /// Read an integer from text (`list_to_integer/2`)
+    ///
+    /// See [`IntegerBif::binary_to_integer`].
+    pub fn list_to_integer(text: &str, base: u32) -> Result<ErlangTerm, IntegerError> {
+        Self::binary_to_integer(text.as_bytes(), base)
+    }
+}
+
+fn check_base(base: u32) -> Result<(), IntegerError> {
+    if (MIN_BASE..=MAX_BASE).contains(&base) {
+        Ok(())
+    } else {
+        Err(IntegerError::BadArgument(format!("base {} is not in {}..{}", base, MIN_BASE, MAX_BASE)))
+    }
+}
+
+/// Write a 64-bit integer at the end of a buffer
+///
+/// # Returns
+/// The written part of the buffer
+fn write_small(value: i64, base: u32, buffer: &mut [u8; MAX_SMALL_DIGITS]) -> &[u8] {
+    let mut magnitude = value.unsigned_abs();
+    let base = base as u64;
+    let mut start = buffer.len();
+    loop {
+        start -= 1;
+        buffer[start] = digit_char((magnitude % base) as u8);
+        magnitude /= base;
+        if magnitude == 0 {
+            break;
+        }
+    }
+    if value < 0 {
+        start -= 1;
+        buffer[start] = b'-';
+    }
+    &buffer[start..]
+}
+
+fn digit_char(digit: u8) -> u8 {
+    if digit < 10 {
+        b'0' + digit
+    } else {
+        b'A' + digit - 10
+    }
+}
+
+/// Read an integer that fits in 64 bits
+///
+/// The value is accumulated negated, so that `i64::MIN` fits.
+///
+/// # Returns
+/// * `Ok(Some(n))` - The integer
+/// * `Ok(None)` - The text is valid so far but does not fit in 64 bits
+/// * `Err(())` - The text is not an integer in `base`
+fn read_small(text: &[u8], base: u32) -> Result<Option<i64>, ()> {
+    let (negative, digits) = match text.first() {
+        Some(b'-') => (true, &text[1..]),
+        Some(b'+') => (false, &text[1..]),
+        _ => (false, text),
+    };
+    if digits.is_empty() {
+        return Err(());
+    }
+    let mut accumulated: i64 = 0;
+    for &byte in digits {
+        let digit = (byte as char).to_digit(base).ok_or(())?;
+        match accumulated
+            .checked_mul(base as i64)
+            .and_then(|n| n.checked_sub(digit as i64))
+        {
+            Some(n) => accumulated = n,
+            None => return Ok(None),
+        }
+    }
+    if negative {
+        Ok(Some(accumulated))
+    } else {
+        Ok(accumulated.checked_neg())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(text: &str) -> ErlangTerm {
+        ErlangTerm::BigInteger(BigNumber::from_string_base(text, 10).unwrap())
+    }
+
+    #[test]
+    fn test_integer_to_binary() {
+        let cases: [(i64, u32, &[u8]); 7] = [
+            (0, 10, b"0"),
+            (-255, 16, b"-FF"),
+            (35, 36, b"Z"),
+            (5, 2, b"101"),
+            (i64::MAX, 10, b"9223372036854775807"),
+            (i64::MIN, 16, b"-8000000000000000"),
+            (i64::MIN, 2, b"-1000000000000000000000000000000000000000000000000000000000000000"),
+        ];
+        for (value, base, text) in cases {
+            let binary = IntegerBif::integer_to_binary(&ErlangTerm::Integer(value), base).unwrap();
+            assert_eq!(binary.data(), text);
+        }
+        let binary = IntegerBif::integer_to_binary(&big("-18446744073709551616"), 16).unwrap();
+        assert_eq!(binary.data(), b"-10000000000000000");
+        assert_eq!(IntegerBif::integer_to_list(&big("1000000000000000000000"), 16), Ok("3635C9ADC5DEA00000".to_string()));
+
+        assert!(IntegerBif::integer_to_binary(&ErlangTerm::Float(1.0), 10).is_err());
+        assert!(IntegerBif::integer_to_binary(&ErlangTerm::Integer(1), 1).is_err());
+        assert!(IntegerBif::integer_to_list(&ErlangTerm::Integer(1), 37).is_err());
+    }
+
+    #[test]
+    fn test_binary_to_integer_across_small_boundary() {
+        let cases = [
+            ("9223372036854775807", ErlangTerm::Integer(i64::MAX)),
+            ("-9223372036854775808", ErlangTerm::Integer(i64::MIN)),
+            ("9223372036854775808", big("9223372036854775808")),
+            ("-9223372036854775809", big("-9223372036854775809")),
+            ("+000000000000000000000000000042", ErlangTerm::Integer(42)),
+            ("-0", ErlangTerm::Integer(0)),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(IntegerBif::binary_to_integer(text.as_bytes(), 10), Ok(expected.clone()), "{}", text);
+            assert_eq!(IntegerBif::list_to_integer(text, 10), Ok(expected));
+        }
+        assert_eq!(IntegerBif::binary_to_integer(b"7fffffffffffffff", 16), Ok(ErlangTerm::Integer(i64::MAX)));
+        assert_eq!(IntegerBif::binary_to_integer(b"10000000000000000", 16), Ok(big("18446744073709551616")));
+    }
+
+    #[test]
+    fn test_round_trip_all_bases() {
+        let values = [
+            ErlangTerm::Integer(0),
+            ErlangTerm::Integer(-1),
+            ErlangTerm::Integer(i64::MAX),
+            ErlangTerm::Integer(i64::MIN),
+            big("9223372036854775808"),
+            big("-123456789012345678901234567890123456789"),
+        ];
+        for base in MIN_BASE..=MAX_BASE {
+            for value in &values {
+                let binary = IntegerBif::integer_to_binary(value, base).unwrap();
+                assert_eq!(IntegerBif::binary_to_integer(binary.data(), base).as_ref(), Ok(value));
+                let lower = binary.data().to_ascii_lowercase();
+                assert_eq!(IntegerBif::binary_to_integer(&lower, base).as_ref(), Ok(value));
+            }
+        }
+    }
+
+    #[test]
+    fn test_binary_to_integer_bad_arguments() {
+        for text in ["", "-", "+", " 1", "1 ", "1.0", "--1", "12a", "١"] {
+            assert!(IntegerBif::binary_to_integer(text.as_bytes(), 10).is_err(), "{:?}", text);
+        }
+        assert!(IntegerBif::binary_to_integer(b"2", 2).is_err());
+        assert!(IntegerBif::binary_to_integer(b"100000000000000000000000x", 10).is_err());
+        assert!(IntegerBif::binary_to_integer(b"1", 0).is_err());
+        assert!(IntegerBif::binary_to_integer(b"1", 37).is_err());
+    }
+}
@@ -0,0 +1,336 @@
+//! Group Leader and I/O Request Built-in Functions
+//!
+//! Provides the group leader BIFs and routing of I/O protocol requests:
+//! - `erlang:group_leader/0`
+//! - `erlang:group_leader/2`
+//! - Routing of `io_request`s to the caller's group leader
+//!
+//! Every process has a group leader; I/O functions send their requests to
+//! it. A process spawned by another process inherits its parent's group
+//! leader. The process acting as a group leader is registered here with the
+//! I/O device that serves its requests: either an [`IoServer`], such as a
+//! shell's group process, or a port that output is written to directly.
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ *
+ * Creation productivity increased for code in this file by using AALang and GAB.
+ * See https://github.com/yenrab/AALang-Gab
+ */
+
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, RwLock};
+
+use entities_process::{Process, ProcessId};
+use infrastructure_utilities::process_table::get_global_process_table;
+
+use crate::op::ErlangTerm;
+use crate::port::{PortBif, PortError};
+
+/// Error type for group leader and I/O operations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IoError {
+    /// Bad argument (not a pid, or no such process)
+    BadArgument(String),
+    /// The group leader is not alive or is not registered as an I/O device
+    Terminated(ProcessId),
+    /// The caller was suspended on the busy port of its group leader
+    Suspended(u64),
+}
+
+impl std::fmt::Display for IoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IoError::BadArgument(msg) => write!(f, "Bad argument: {}", msg),
+            IoError::Terminated(pid) => write!(f, "Group leader <0.{}.0> terminated", pid),
+            IoError::Suspended(port) => write!(f, "Suspended on busy port #Port<0.{}>", port),
+        }
+    }
+}
+
+impl std::error::Error for IoError {}
+
+/// An I/O protocol request (`{io_request, From, ReplyAs, Request}`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IoRequest {
+    /// `{put_chars, unicode, Chars}`
+    PutChars(Vec<u8>),
+    /// `{get_line, unicode, Prompt}`
+    GetLine {
+        /// Prompt written before reading
+        prompt: Vec<u8>,
+    },
+    /// `{get_chars, unicode, Prompt, Count}`
+    GetChars {
+        /// Prompt written before reading
+        prompt: Vec<u8>,
+        /// Number of characters to read
+        count: usize,
+    },
+}
+
+/// Reply to an I/O protocol request (`{io_reply, ReplyAs, Reply}`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IoReply {
+    /// `ok`
+    Ok,
+    /// Data read from the device
+    Data(Vec<u8>),
+    /// `eof`
+    Eof,
+    /// `{error, Reason}`
+    Error(String),
+}
+
+/// A process that serves I/O requests, such as a shell's group process
+pub trait IoServer: Send + Sync {
+    /// Serve one request from `from`
+    fn io_request(&self, from: ProcessId, request: &IoRequest) -> IoReply;
+}
+
+/// The device serving the I/O requests sent to a group leader
+#[derive(Clone)]
+pub enum IoDevice {
+    /// Requests are served by an I/O server
+    Server(Arc<dyn IoServer>),
+    /// Output is written to a port; input requests are not supported
+    Port(u64),
+}
+
+/// I/O devices by the pid of the group leader they serve
+static IO_DEVICES: LazyLock<RwLock<HashMap<ProcessId, IoDevice>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Group leader and I/O BIF operations
+pub struct IoBif;
+
+impl IoBif {
+    /// Get the caller's group leader (group_leader/0)
+    ///
+    /// # Arguments
+    /// * `process` - Calling process
+    ///
+    /// # Returns
+    /// The group leader's pid
+    pub fn group_leader_0(process: &Process) -> ErlangTerm {
+        ErlangTerm::Pid(process.group_leader())
+    }
+
+    /// Set the group leader of a process (group_leader/2)
+    ///
+    /// # Arguments
+    /// * `leader` - New group leader
+    /// * `pid` - Process whose group leader is set
+    ///
+    /// # Returns
+    /// * `Ok(ErlangTerm::Atom("true"))` - The group leader was set
+    /// * `Err(IoError::BadArgument)` - `pid` is not alive
+    pub fn group_leader_2(leader: ProcessId, pid: ProcessId) -> Result<ErlangTerm, IoError> {
+        let process = get_global_process_table()
+            .lookup(pid)
+            .ok_or_else(|| IoError::BadArgument(format!("process {} is not alive", pid)))?;
+        process.set_group_leader(leader);
+        Ok(ErlangTerm::Atom("true".to_string()))
+    }
+
+    /// Register the I/O device serving the requests sent to `group_leader`
+    ///
+    /// # Returns
+    /// The device registered before, if any
+    pub fn register_io_device(group_leader: ProcessId, device: IoDevice) -> Option<IoDevice> {
+        IO_DEVICES.write().unwrap().insert(group_leader, device)
+    }
+
+    /// Open the standard output port and make it the I/O device of
+    /// `group_leader`, as for the `user` group leader
+    ///
+    /// The port is opened on the `fd` driver for output only; standard input
+    /// is left to its current reader.
+    ///
+    /// # Returns
+    /// The port number
+    #[cfg(unix)]
+    pub fn register_standard_output(
+        group_leader: ProcessId,
+    ) -> Result<u64, infrastructure_driver_api::DriverError> {
+        let port = adapters_system_integration_unix::open_standard_output()?;
+        Self::register_io_device(group_leader, IoDevice::Port(port.id()));
+        Ok(port.id())
+    }
+
+    /// Remove the I/O device of `group_leader`, as when it exits
+    pub fn unregister_io_device(group_leader: ProcessId) -> Option<IoDevice> {
+        IO_DEVICES.write().unwrap().remove(&group_leader)
+    }
+
+    /// Send an I/O request to the caller's group leader
+    ///
+    /// # Arguments
+    /// * `process` - Calling process
+    /// * `request` - I/O request
+    ///
+    /// # Returns
+    /// * `Ok(IoReply)` - Reply from the group leader's device
+    /// * `Err(IoError::Terminated)` - The group leader has no I/O device
+    /// * `Err(IoError::Suspended)` - The caller was suspended on a busy port;
+    ///   the request is reissued when it is resumed
+    pub fn io_request(process: &Process, request: &IoRequest) -> Result<IoReply, IoError> {
+        Self::io_request_to(process.id(), process.group_leader(), request)
+    }
+
+    /// Send an I/O request to a specific I/O device (`io:request/2`)
+    ///
+    /// # Arguments
+    /// * `from` - Calling process
+    /// * `device` - Pid of the group leader or I/O server
+    /// * `request` - I/O request
+    pub fn io_request_to(from: ProcessId, device: ProcessId, request: &IoRequest) -> Result<IoReply, IoError> {
+        let io_device = IO_DEVICES
+            .read()
+            .unwrap()
+            .get(&device)
+            .cloned()
+            .ok_or(IoError::Terminated(device))?;
+        match io_device {
+            IoDevice::Server(server) => Ok(server.io_request(from, request)),
+            IoDevice::Port(port) => match request {
+                IoRequest::PutChars(chars) => {
+                    match PortBif::port_command_2(from, &ErlangTerm::Port(port), &ErlangTerm::Binary(chars.clone())) {
+                        Ok(_) => Ok(IoReply::Ok),
+                        Err(PortError::Suspended(port)) => Err(IoError::Suspended(port)),
+                        Err(_) => Ok(IoReply::Error("terminated".to_string())),
+                    }
+                }
+                IoRequest::GetLine { .. } | IoRequest::GetChars { .. } => {
+                    Ok(IoReply::Error("enotsup".to_string()))
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use infrastructure_driver_api::{
+        get_global_driver_registry, get_global_port_table, DriverEntry, DriverError, DriverPort,
+    };
+    use std::sync::Mutex;
+
+    /// I/O server that records output and answers reads from a fixed line
+    struct TestServer {
+        output: Mutex<Vec<(ProcessId, Vec<u8>)>>,
+    }
+
+    impl IoServer for TestServer {
+        fn io_request(&self, from: ProcessId, request: &IoRequest) -> IoReply {
+            match request {
+                IoRequest::PutChars(chars) => {
+                    self.output.lock().unwrap().push((from, chars.clone()));
+                    IoReply::Ok
+                }
+                IoRequest::GetLine { .. } => IoReply::Data(b"hello\n".to_vec()),
+                IoRequest::GetChars { .. } => IoReply::Eof,
+            }
+        }
+    }
+
+    struct TestDriver;
+
+    impl DriverEntry for TestDriver {
+        fn driver_name(&self) -> &str {
+            "io_bif_test_drv"
+        }
+
+        fn output(&self, port: &DriverPort, data: &[u8]) -> Result<(), DriverError> {
+            port.driver_enq(data);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_group_leader_0_and_2() {
+        let table = get_global_process_table();
+        let process = Arc::new(Process::new(41101));
+        table.insert(41101, Arc::clone(&process));
+
+        assert_eq!(IoBif::group_leader_0(&process), ErlangTerm::Pid(41101));
+        assert_eq!(
+            IoBif::group_leader_2(41100, 41101),
+            Ok(ErlangTerm::Atom("true".to_string()))
+        );
+        assert_eq!(IoBif::group_leader_0(&process), ErlangTerm::Pid(41100));
+        assert!(IoBif::group_leader_2(41100, 41199).is_err());
+        table.remove(41101);
+    }
+
+    #[test]
+    fn test_io_request_routed_to_group_leader() {
+        let server = Arc::new(TestServer { output: Mutex::new(Vec::new()) });
+        IoBif::register_io_device(41110, IoDevice::Server(server.clone()));
+        let process = Process::new(41111);
+        process.set_group_leader(41110);
+
+        assert_eq!(IoBif::io_request(&process, &IoRequest::PutChars(b"hi".to_vec())), Ok(IoReply::Ok));
+        assert_eq!(
+            IoBif::io_request(&process, &IoRequest::GetLine { prompt: b"> ".to_vec() }),
+            Ok(IoReply::Data(b"hello\n".to_vec()))
+        );
+        assert_eq!(*server.output.lock().unwrap(), vec![(41111, b"hi".to_vec())]);
+
+        IoBif::unregister_io_device(41110);
+        assert_eq!(
+            IoBif::io_request(&process, &IoRequest::PutChars(b"hi".to_vec())),
+            Err(IoError::Terminated(41110))
+        );
+    }
+
+    #[test]
+    fn test_io_request_to_port() {
+        let _ = get_global_driver_registry().add_driver_entry(Arc::new(TestDriver));
+        let port = get_global_port_table()
+            .open_port(get_global_driver_registry(), "io_bif_test_drv")
+            .unwrap();
+        IoBif::register_io_device(41120, IoDevice::Port(port.id()));
+        let process = Process::new(41121);
+        process.set_group_leader(41120);
+
+        assert_eq!(IoBif::io_request(&process, &IoRequest::PutChars(b"out".to_vec())), Ok(IoReply::Ok));
+        assert_eq!(port.driver_sizeq(), 3);
+        assert_eq!(
+            IoBif::io_request(&process, &IoRequest::GetChars { prompt: Vec::new(), count: 1 }),
+            Ok(IoReply::Error("enotsup".to_string()))
+        );
+        IoBif::unregister_io_device(41120);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_register_standard_output() {
+        let port = IoBif::register_standard_output(41120).unwrap();
+        let driver_port = get_global_port_table().lookup(port).unwrap();
+        assert_eq!(driver_port.driver_name(), "fd");
+        assert!(matches!(IoBif::unregister_io_device(41120), Some(IoDevice::Port(id)) if id == port));
+        get_global_port_table().close_port(port);
+    }
+}
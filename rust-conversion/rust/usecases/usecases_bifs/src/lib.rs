@@ -13,18 +13,33 @@
 //! ## Modules
 //!
 //! - **[`regex`](regex/index.html)**: Regular expression matching and compilation
+//! - **[`binary`](binary/index.html)**: Binary searching, splitting and slicing (`binary` module)
+//! - **[`float`](float/index.html)**: Float formatting and parsing (`float_to_list`, `float_to_binary`, `list_to_float`)
+//! - **[`arith`](arith/index.html)**: Arithmetic (`+`, `-`, `*`, `/`, `div`, `rem`, `abs`) with bignum promotion
+//! - **[`integer`](integer/index.html)**: Integer text conversion in bases 2 to 36 (`integer_to_binary`, `binary_to_integer`)
 //! - **[`checksum`](checksum/index.html)**: Checksum calculation (CRC, Adler, etc.)
+//! - **[`crypto`](crypto/index.html)**: Hashes, HMAC and strong random bytes
 //! - **[`trace`](trace/index.html)**: Tracing and debugging functionality
+//! - **[`seq_trace`](seq_trace/index.html)**: Sequential tracing tokens and events
+//! - **[`display`](display/index.html)**: Low-level term output (`erlang:display/1`, `erts_debug:display/1`)
 //! - **[`dynamic_library`](dynamic_library/index.html)**: Dynamic library loading and management
 //! - **[`os`](os/index.html)**: Operating system interface operations
 //! - **[`counters`](counters/index.html)**: Atomic counter operations
 //! - **[`unique`](unique/index.html)**: Unique reference and integer generation
 //! - **[`op`](op/index.html)**: Logical, comparison, and type-checking operations
-//! - **[`guard`](guard/index.html)**: Guard expression evaluation
+//! - **[`guard`](guard/index.html)**: Guard BIFs and the guard sandbox evaluating guard expressions
 //! - **[`lists`](lists/index.html)**: List manipulation operations
+//! - **[`maps`](maps/index.html)**: Map merging, traversal and iterators
 //! - **[`persistent`](persistent/index.html)**: Persistent term storage operations
 //! - **[`load`](load/index.html)**: Module loading and code management
 //! - **[`info`](info/index.html)**: System information queries
+//! - **[`time`](time/index.html)**: Monotonic time, system time, time offset and unit conversion
+//! - **[`timer`](timer/index.html)**: `send_after`, `start_timer`, `cancel_timer` and `read_timer`
+//! - **[`port`](port/index.html)**: Port command, control and call through port drivers
+//! - **[`send`](send/index.html)**: `send/3` options and suspension on busy ports and nodes
+//! - **[`io`](io/index.html)**: Group leaders and routing of I/O requests to them
+//! - **[`alias`](alias/index.html)**: Process aliases and sending to them
+//! - **[`halt`](halt/index.html)**: `erlang:halt/0,1,2`: flushing shutdown, abort and crash dumps
 //!
 //! ## Architecture
 //!
@@ -65,8 +80,15 @@
  */
 
 pub mod regex;
+pub mod binary;
+pub mod float;
+pub mod arith;
+pub mod integer;
 pub mod checksum;
+pub mod crypto;
 pub mod trace;
+pub mod seq_trace;
+pub mod display;
 pub mod dynamic_library;
 pub mod os;
 pub mod counters;
@@ -74,13 +96,31 @@ pub mod unique;
 pub mod op;
 pub mod guard;
 pub mod lists;
+pub mod maps;
 pub mod persistent;
 pub mod load;
 pub mod info;
+pub mod time;
+pub mod timer;
+pub mod port;
+pub mod send;
+pub mod io;
+pub mod alias;
+pub mod halt;
 
-pub use regex::{RegexBif, CompiledRegex, MatchResult, Capture, RegexError as RegexErr};
+pub use regex::{
+    RegexBif, CompiledRegex, MatchResult, Capture, RegexError as RegexErr, CompileOption, RunOption,
+    RunResult, ValueSpec, GroupRef, CaptureType, CaptureValue, SplitOption,
+};
+pub use binary::{BinaryBif, BinaryError, CompiledPattern, Part};
+pub use float::{FloatBif, FloatError, FloatOption};
+pub use arith::{ArithBif, ArithError};
+pub use integer::{IntegerBif, IntegerError};
 pub use checksum::ChecksumBif;
+pub use crypto::{CryptoBif, CryptoError, HashAlgorithm, HashState, MacState};
 pub use trace::TraceBif;
+pub use seq_trace::{SeqTraceBif, SeqTraceError, SeqTraceEvent, SeqTraceFlag, SeqTraceInfo};
+pub use display::{DisplayBif, DisplayDevice, DisplayError};
 pub use dynamic_library::{
     DynamicLibraryLoader, LibraryId, ProcessId, LibraryStatus, LoadOptions,
     MonitorOption, ReloadOption, LoadResult, UnloadResult, LibraryInfo, LibraryError
@@ -89,9 +129,16 @@ pub use os::{OsBif, OsError};
 pub use counters::{CountersBif, CounterRef, CounterInfo, CountersError};
 pub use unique::{UniqueBif, Reference, UniqueIntegerOption, UniqueError};
 pub use op::{OpBif, OpError};
-pub use guard::{GuardBif, GuardError};
-pub use lists::{ListsBif, ListsError};
+pub use guard::{is_guard_bif, GuardBif, GuardError, GuardFailure, GuardSandbox, GUARD_BIFS};
+pub use lists::{ListsBif, ListsContinuation, ListsError, ListsTrap};
+pub use maps::{IteratorOrder, MapOrIterator, MapsBif, MapsError};
 pub use persistent::{PersistentBif, PersistentError};
 pub use load::{LoadBif, LoadError, ModuleStatus};
 pub use info::{InfoBif, InfoError};
+pub use timer::{TimerBif, TimerError};
+pub use port::{PortBif, PortError};
+pub use send::{SendBif, SendError, SendOptions, SendRequest};
+pub use alias::{AliasBif, AliasError};
+pub use halt::{HaltAction, HaltBif, HaltError, HaltOptions};
+pub use io::{IoBif, IoDevice, IoError, IoReply, IoRequest, IoServer};
 
@@ -6,8 +6,15 @@
 //! - List membership (member/2)
 //! - List reversal (reverse/2)
 //! - Key-based tuple search (keyfind/3, keymember/3, keysearch/3)
+//! - Key-based tuple replacement (keystore/4)
+//! - Integer sequences (seq/2, seq/3)
 //!
 //! This module implements safe Rust equivalents of Erlang list BIFs.
+//!
+//! As in erl_bif_lists.c, the traversing BIFs also come in `_yielding`
+//! variants that run on a reduction budget. When the budget runs out on a
+//! long list they return a [`ListsContinuation`], which is passed to
+//! [`ListsBif::resume`] once the process is scheduled again.
 
 /*
  * %CopyrightBegin%
@@ -235,7 +242,8 @@ impl ListsBif {
     /// Check if an element is a member of a list
     ///
     /// Returns `true` if the element is found in the list, `false` otherwise.
-    /// Uses structural equality (==) for comparison.
+    /// Uses exact equality (=:=) for comparison, so `1` is not a member of
+    /// `[1.0]`.
     ///
     /// # Arguments
     /// * `term` - Element to search for
@@ -273,13 +281,8 @@ impl ListsBif {
         match list {
             ErlangTerm::Nil => Ok(ErlangTerm::Atom("false".to_string())),
             ErlangTerm::List(list_vec) => {
-                for elem in list_vec {
-                    // Use structural equality (==)
-                    if elem.eq(term) {
-                        return Ok(ErlangTerm::Atom("true".to_string()));
-                    }
-                }
-                Ok(ErlangTerm::Atom("false".to_string()))
+                let found = list_vec.iter().any(|elem| elem.exact_eq(term));
+                Ok(ErlangTerm::Atom(found.to_string()))
             }
             _ => Err(ListsError::BadArgument(
                 "Second argument must be a list".to_string(),
@@ -433,30 +436,7 @@ impl ListsBif {
         pos: &ErlangTerm,
         list: &ErlangTerm,
     ) -> Result<ErlangTerm, ListsError> {
-        // Validate position
-        let pos_val = match pos {
-            ErlangTerm::Integer(n) if *n >= 1 => *n as usize,
-            ErlangTerm::BigInteger(bn) => {
-                if let Some(n) = bn.to_i64() {
-                    if n >= 1 {
-                        n as usize
-                    } else {
-                        return Err(ListsError::BadPosition(
-                            "Position must be >= 1".to_string(),
-                        ));
-                    }
-                } else {
-                    return Err(ListsError::BadPosition(
-                        "Position too large".to_string(),
-                    ));
-                }
-            }
-            _ => {
-                return Err(ListsError::BadPosition(
-                    "Position must be an integer >= 1".to_string(),
-                ));
-            }
-        };
+        let pos_val = Self::key_position(pos)?;
 
         // Search through list
         let list_vec = match list {
@@ -471,19 +451,10 @@ impl ListsBif {
             }
         };
 
-        for elem in list_vec {
-            if let ErlangTerm::Tuple(tuple_vec) = elem {
-                if pos_val <= tuple_vec.len() {
-                    let element = &tuple_vec[pos_val - 1]; // Convert to 0-indexed
-                    // Use structural equality (==)
-                    if element.eq(key) {
-                        return Ok(elem.clone());
-                    }
-                }
-            }
+        match list_vec.iter().find(|elem| Self::key_matches(elem, key, pos_val)) {
+            Some(elem) => Ok(elem.clone()),
+            None => Ok(ErlangTerm::Atom("false".to_string())),
         }
-
-        Ok(ErlangTerm::Atom("false".to_string()))
     }
 
     /// Check if a tuple with the given key exists in a list
@@ -630,6 +601,513 @@ impl ListsBif {
             _ => Ok(ErlangTerm::Atom("false".to_string())),
         }
     }
+
+    /// Store a tuple in a list by key
+    ///
+    /// Returns a copy of `list` where the first tuple whose element at `pos`
+    /// compares equal (==) to `key` is replaced by `new_tuple`. If there is
+    /// no such tuple, `new_tuple` is appended.
+    ///
+    /// # Arguments
+    /// * `key` - Key value to search for
+    /// * `pos` - Position (1-indexed) in tuple to compare
+    /// * `list` - List of tuples
+    /// * `new_tuple` - Tuple to store
+    ///
+    /// # Returns
+    /// * `Ok(ErlangTerm::List)` - The updated list
+    /// * `Err(ListsError)` - If arguments are invalid
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::lists::ListsBif;
+    /// use usecases_bifs::op::ErlangTerm;
+    ///
+    /// let entry = |key: &str, value| {
+    ///     ErlangTerm::Tuple(vec![ErlangTerm::Atom(key.to_string()), ErlangTerm::Integer(value)])
+    /// };
+    /// let list = ErlangTerm::List(vec![entry("a", 1), entry("b", 2)]);
+    ///
+    /// // Replace the tuple with key b
+    /// let result = ListsBif::keystore_4(
+    ///     &ErlangTerm::Atom("b".to_string()),
+    ///     &ErlangTerm::Integer(1),
+    ///     &list,
+    ///     &entry("b", 3),
+    /// ).unwrap();
+    /// assert_eq!(result, ErlangTerm::List(vec![entry("a", 1), entry("b", 3)]));
+    ///
+    /// // Append a tuple with a new key
+    /// let result = ListsBif::keystore_4(
+    ///     &ErlangTerm::Atom("c".to_string()),
+    ///     &ErlangTerm::Integer(1),
+    ///     &list,
+    ///     &entry("c", 3),
+    /// ).unwrap();
+    /// assert_eq!(result, ErlangTerm::List(vec![entry("a", 1), entry("b", 2), entry("c", 3)]));
+    /// ```
+    pub fn keystore_4(
+        key: &ErlangTerm,
+        pos: &ErlangTerm,
+        list: &ErlangTerm,
+        new_tuple: &ErlangTerm,
+    ) -> Result<ErlangTerm, ListsError> {
+        let pos_val = Self::key_position(pos)?;
+        if !matches!(new_tuple, ErlangTerm::Tuple(_)) {
+            return Err(ListsError::BadArgument(
+                "Fourth argument must be a tuple".to_string(),
+            ));
+        }
+        let mut result = match list {
+            ErlangTerm::List(l) => l.clone(),
+            ErlangTerm::Nil => Vec::new(),
+            _ => {
+                return Err(ListsError::BadArgument(
+                    "Third argument must be a list".to_string(),
+                ));
+            }
+        };
+
+        match result.iter_mut().find(|elem| Self::key_matches(elem, key, pos_val)) {
+            Some(elem) => *elem = new_tuple.clone(),
+            None => result.push(new_tuple.clone()),
+        }
+        Ok(ErlangTerm::List(result))
+    }
+
+    /// Create a sequence of integers from `from` to `to` (inclusive)
+    ///
+    /// As in `lists:seq/2`, `to` may be one less than `from`, giving an
+    /// empty list.
+    ///
+    /// # Arguments
+    /// * `from` - First integer
+    /// * `to` - Last integer
+    ///
+    /// # Returns
+    /// * `Ok(ErlangTerm)` - The sequence, `Nil` if empty
+    /// * `Err(ListsError)` - If the arguments are not integers or `to < from - 1`
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::lists::ListsBif;
+    /// use usecases_bifs::op::ErlangTerm;
+    ///
+    /// let result = ListsBif::seq_2(&ErlangTerm::Integer(1), &ErlangTerm::Integer(3)).unwrap();
+    /// assert_eq!(result, ErlangTerm::List(vec![
+    ///     ErlangTerm::Integer(1),
+    ///     ErlangTerm::Integer(2),
+    ///     ErlangTerm::Integer(3),
+    /// ]));
+    ///
+    /// let result = ListsBif::seq_2(&ErlangTerm::Integer(1), &ErlangTerm::Integer(0)).unwrap();
+    /// assert_eq!(result, ErlangTerm::Nil);
+    ///
+    /// assert!(ListsBif::seq_2(&ErlangTerm::Integer(1), &ErlangTerm::Integer(-1)).is_err());
+    /// ```
+    pub fn seq_2(from: &ErlangTerm, to: &ErlangTerm) -> Result<ErlangTerm, ListsError> {
+        Self::seq_3(from, to, &ErlangTerm::Integer(1))
+    }
+
+    /// Create a sequence of integers from `from`, stepping by `incr`, up to
+    /// and not past `to`
+    ///
+    /// The guards of `lists:seq/3` apply: with a positive increment `to`
+    /// may not be below `from - incr`, with a negative increment it may
+    /// not be above `from - incr`, and a zero increment requires
+    /// `from == to`.
+    ///
+    /// # Arguments
+    /// * `from` - First integer
+    /// * `to` - Limit
+    /// * `incr` - Increment
+    ///
+    /// # Returns
+    /// * `Ok(ErlangTerm)` - The sequence, `Nil` if empty
+    /// * `Err(ListsError)` - If the arguments fail the guards
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::lists::ListsBif;
+    /// use usecases_bifs::op::ErlangTerm;
+    ///
+    /// let result = ListsBif::seq_3(
+    ///     &ErlangTerm::Integer(10),
+    ///     &ErlangTerm::Integer(3),
+    ///     &ErlangTerm::Integer(-3),
+    /// ).unwrap();
+    /// assert_eq!(result, ErlangTerm::List(vec![
+    ///     ErlangTerm::Integer(10),
+    ///     ErlangTerm::Integer(7),
+    ///     ErlangTerm::Integer(4),
+    /// ]));
+    /// ```
+    pub fn seq_3(from: &ErlangTerm, to: &ErlangTerm, incr: &ErlangTerm) -> Result<ErlangTerm, ListsError> {
+        let integer = |term: &ErlangTerm| match term {
+            ErlangTerm::Integer(n) => Ok(*n as i128),
+            ErlangTerm::BigInteger(bn) => bn.to_i64().map(|n| n as i128).ok_or_else(|| {
+                ListsError::BadArgument("Sequence bounds too large".to_string())
+            }),
+            _ => Err(ListsError::BadArgument(
+                "Sequence arguments must be integers".to_string(),
+            )),
+        };
+        let (first, last, step) = (integer(from)?, integer(to)?, integer(incr)?);
+
+        let count = if step > 0 && first - step <= last || step < 0 && first - step >= last {
+            (last - first + step) / step
+        } else if step == 0 && first == last {
+            1
+        } else {
+            return Err(ListsError::BadArgument(format!(
+                "No sequence from {} to {} in steps of {}",
+                first, last, step
+            )));
+        };
+        if count == 0 {
+            return Ok(ErlangTerm::Nil);
+        }
+        Ok(ErlangTerm::List(
+            (0..count)
+                .map(|i| ErlangTerm::Integer((first + i * step) as i64))
+                .collect(),
+        ))
+    }
+
+    /// Check membership like [`member_2`](Self::member_2), yielding when
+    /// `reds_left` reductions are used up
+    ///
+    /// # Arguments
+    /// * `term` - Element to search for
+    /// * `list` - List to search in
+    /// * `reds_left` - Reductions the process has left
+    ///
+    /// # Returns
+    /// * `Ok(ListsTrap)` - The result, or a continuation to resume
+    /// * `Err(ListsError)` - If `list` is not a list
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::lists::{ListsBif, ListsTrap};
+    /// use usecases_bifs::op::ErlangTerm;
+    ///
+    /// let list = ErlangTerm::List((0..100).map(ErlangTerm::Integer).collect());
+    /// let mut trap = ListsBif::member_2_yielding(&ErlangTerm::Integer(99), &list, 2).unwrap();
+    /// let mut yields = 0;
+    /// while let ListsTrap::Yield(continuation) = trap {
+    ///     yields += 1;
+    ///     trap = ListsBif::resume(continuation, 2);
+    /// }
+    /// assert!(yields > 0);
+    /// assert!(matches!(trap, ListsTrap::Done { result: ErlangTerm::Atom(ref a), .. } if a == "true"));
+    /// ```
+    pub fn member_2_yielding(
+        term: &ErlangTerm,
+        list: &ErlangTerm,
+        reds_left: usize,
+    ) -> Result<ListsTrap, ListsError> {
+        let elements = match list {
+            ErlangTerm::List(l) => l.clone(),
+            ErlangTerm::Nil => Vec::new(),
+            _ => {
+                return Err(ListsError::BadArgument(
+                    "Second argument must be a list".to_string(),
+                ))
+            }
+        };
+        let traversal = Traversal::Member { term: term.clone() };
+        Ok(Self::resume(ListsContinuation::new(traversal, elements), reds_left))
+    }
+
+    /// Reverse a list like [`reverse_2`](Self::reverse_2), yielding when
+    /// `reds_left` reductions are used up
+    ///
+    /// # Arguments
+    /// * `list` - List to reverse
+    /// * `tail` - Tail to append
+    /// * `reds_left` - Reductions the process has left
+    ///
+    /// # Returns
+    /// * `Ok(ListsTrap)` - The result, or a continuation to resume
+    /// * `Err(ListsError)` - If `list` is not a list
+    pub fn reverse_2_yielding(
+        list: &ErlangTerm,
+        tail: &ErlangTerm,
+        reds_left: usize,
+    ) -> Result<ListsTrap, ListsError> {
+        let elements = match list {
+            ErlangTerm::Nil => {
+                return Ok(ListsTrap::Done {
+                    result: tail.clone(),
+                    reductions: 1,
+                })
+            }
+            ErlangTerm::List(l) => l.clone(),
+            _ => {
+                return Err(ListsError::BadArgument(
+                    "First argument must be a list".to_string(),
+                ))
+            }
+        };
+        let traversal = Traversal::Reverse {
+            acc: Vec::with_capacity(elements.len()),
+            tail: tail.clone(),
+        };
+        Ok(Self::resume(ListsContinuation::new(traversal, elements), reds_left))
+    }
+
+    /// Find a tuple by key like [`keyfind_3`](Self::keyfind_3), yielding
+    /// when `reds_left` reductions are used up
+    ///
+    /// # Arguments
+    /// * `key` - Key value to search for
+    /// * `pos` - Position (1-indexed) in tuple to compare
+    /// * `list` - List of tuples to search
+    /// * `reds_left` - Reductions the process has left
+    ///
+    /// # Returns
+    /// * `Ok(ListsTrap)` - The result, or a continuation to resume
+    /// * `Err(ListsError)` - If arguments are invalid
+    pub fn keyfind_3_yielding(
+        key: &ErlangTerm,
+        pos: &ErlangTerm,
+        list: &ErlangTerm,
+        reds_left: usize,
+    ) -> Result<ListsTrap, ListsError> {
+        Self::key_yielding(key, pos, list, KeyResult::Find, reds_left)
+    }
+
+    /// Check for a tuple by key like [`keymember_3`](Self::keymember_3),
+    /// yielding when `reds_left` reductions are used up
+    pub fn keymember_3_yielding(
+        key: &ErlangTerm,
+        pos: &ErlangTerm,
+        list: &ErlangTerm,
+        reds_left: usize,
+    ) -> Result<ListsTrap, ListsError> {
+        Self::key_yielding(key, pos, list, KeyResult::Member, reds_left)
+    }
+
+    /// Search for a tuple by key like [`keysearch_3`](Self::keysearch_3),
+    /// yielding when `reds_left` reductions are used up
+    pub fn keysearch_3_yielding(
+        key: &ErlangTerm,
+        pos: &ErlangTerm,
+        list: &ErlangTerm,
+        reds_left: usize,
+    ) -> Result<ListsTrap, ListsError> {
+        Self::key_yielding(key, pos, list, KeyResult::Search, reds_left)
+    }
+
+    /// Continue a yielded lists BIF with a new reduction budget
+    ///
+    /// Visits at most [`LIST_ELEMENTS_PER_REDUCTION`] elements per
+    /// reduction in `reds_left` (at least one reduction's worth).
+    ///
+    /// # Arguments
+    /// * `continuation` - State returned in [`ListsTrap::Yield`]
+    /// * `reds_left` - Reductions the process has left
+    ///
+    /// # Returns
+    /// The result, or a new continuation if the budget ran out again
+    pub fn resume(mut continuation: ListsContinuation, reds_left: usize) -> ListsTrap {
+        let max_iter = reds_left.max(1) * LIST_ELEMENTS_PER_REDUCTION;
+        let ListsContinuation {
+            traversal,
+            elements,
+            position,
+        } = &mut continuation;
+        let mut iter = 0;
+        let result = loop {
+            if *position == elements.len() {
+                break traversal.finish();
+            }
+            if iter == max_iter {
+                return ListsTrap::Yield(continuation);
+            }
+            let elem = &elements[*position];
+            match traversal {
+                Traversal::Member { term } => {
+                    if elem.exact_eq(term) {
+                        break ErlangTerm::Atom("true".to_string());
+                    }
+                }
+                Traversal::Key { key, pos, result } => {
+                    if Self::key_matches(elem, key, *pos) {
+                        break result.found(elem);
+                    }
+                }
+                Traversal::Reverse { acc, .. } => {
+                    acc.push(elements[elements.len() - 1 - *position].clone());
+                }
+            }
+            *position += 1;
+            iter += 1;
+        };
+        ListsTrap::Done {
+            result,
+            reductions: iter.div_ceil(LIST_ELEMENTS_PER_REDUCTION).max(1),
+        }
+    }
+
+    fn key_yielding(
+        key: &ErlangTerm,
+        pos: &ErlangTerm,
+        list: &ErlangTerm,
+        result: KeyResult,
+        reds_left: usize,
+    ) -> Result<ListsTrap, ListsError> {
+        let pos = Self::key_position(pos)?;
+        let elements = match list {
+            ErlangTerm::List(l) => l.clone(),
+            ErlangTerm::Nil => Vec::new(),
+            _ => {
+                return Err(ListsError::BadArgument(
+                    "Third argument must be a list".to_string(),
+                ))
+            }
+        };
+        let traversal = Traversal::Key {
+            key: key.clone(),
+            pos,
+            result,
+        };
+        Ok(Self::resume(ListsContinuation::new(traversal, elements), reds_left))
+    }
+
+    /// Validate a 1-indexed tuple position of the key BIFs
+    fn key_position(pos: &ErlangTerm) -> Result<usize, ListsError> {
+        match pos {
+            ErlangTerm::Integer(n) if *n >= 1 => Ok(*n as usize),
+            ErlangTerm::BigInteger(bn) => match bn.to_i64() {
+                Some(n) if n >= 1 => Ok(n as usize),
+                Some(_) => Err(ListsError::BadPosition(
+                    "Position must be >= 1".to_string(),
+                )),
+                None => Err(ListsError::BadPosition(
+                    "Position too large".to_string(),
+                )),
+            },
+            _ => Err(ListsError::BadPosition(
+                "Position must be an integer >= 1".to_string(),
+            )),
+        }
+    }
+
+    /// Whether `elem` is a tuple whose element at `pos` compares equal (==)
+    /// to `key`
+    ///
+    /// Unlike `member/2`, key lookups coerce numbers, also inside tuples and
+    /// lists: the key `1` matches `1.0`.
+    fn key_matches(elem: &ErlangTerm, key: &ErlangTerm, pos: usize) -> bool {
+        match elem {
+            ErlangTerm::Tuple(tuple_vec) => tuple_vec.get(pos - 1).is_some_and(|element| {
+                element == key || element.compare(key) == Some(std::cmp::Ordering::Equal)
+            }),
+            _ => false,
+        }
+    }
+}
+
+/// List elements a yielding lists BIF visits per reduction
+///
+/// Matches the ten iterations per reduction of erl_bif_lists.c.
+pub const LIST_ELEMENTS_PER_REDUCTION: usize = 10;
+
+/// Progress of a yielding lists BIF
+#[derive(Debug, Clone, PartialEq)]
+pub enum ListsTrap {
+    /// The BIF finished
+    Done {
+        /// BIF result
+        result: ErlangTerm,
+        /// Reductions used by this call
+        reductions: usize,
+    },
+    /// The reduction budget ran out; all of it was used
+    Yield(ListsContinuation),
+}
+
+/// Saved state of a yielded lists BIF (the arguments of its trap)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListsContinuation {
+    traversal: Traversal,
+    elements: Vec<ErlangTerm>,
+    /// Elements visited so far
+    position: usize,
+}
+
+impl ListsContinuation {
+    fn new(traversal: Traversal, elements: Vec<ErlangTerm>) -> Self {
+        Self {
+            traversal,
+            elements,
+            position: 0,
+        }
+    }
+
+    /// Number of list elements still to visit
+    pub fn remaining(&self) -> usize {
+        self.elements.len() - self.position
+    }
+}
+
+/// Operation a continuation performs on each element
+#[derive(Debug, Clone, PartialEq)]
+enum Traversal {
+    Member {
+        term: ErlangTerm,
+    },
+    Key {
+        key: ErlangTerm,
+        pos: usize,
+        result: KeyResult,
+    },
+    /// Reversed elements so far, and the tail to append
+    Reverse {
+        acc: Vec<ErlangTerm>,
+        tail: ErlangTerm,
+    },
+}
+
+impl Traversal {
+    /// Result after visiting every element
+    fn finish(&mut self) -> ErlangTerm {
+        match self {
+            Traversal::Member { .. } => ErlangTerm::Atom("false".to_string()),
+            Traversal::Key { .. } => ErlangTerm::Atom("false".to_string()),
+            Traversal::Reverse { acc, tail } => {
+                let mut reversed = std::mem::take(acc);
+                match tail {
+                    ErlangTerm::List(tail_vec) => reversed.extend_from_slice(tail_vec),
+                    ErlangTerm::Nil => {}
+                    _ => reversed.push(tail.clone()),
+                }
+                ErlangTerm::List(reversed)
+            }
+        }
+    }
+}
+
+/// Which key BIF a key traversal answers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyResult {
+    Find,
+    Member,
+    Search,
+}
+
+impl KeyResult {
+    fn found(self, tuple: &ErlangTerm) -> ErlangTerm {
+        match self {
+            KeyResult::Find => tuple.clone(),
+            KeyResult::Member => ErlangTerm::Atom("true".to_string()),
+            KeyResult::Search => ErlangTerm::Tuple(vec![
+                ErlangTerm::Atom("value".to_string()),
+                tuple.clone(),
+            ]),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -875,8 +1353,8 @@ mod tests {
     }
 
     #[test]
-    fn test_member_2_type_coercion() {
-        // Test member with type coercion (Integer == Float)
+    fn test_member_2_exact_equality() {
+        // member uses =:=, so there is no type coercion (Integer /= Float)
         let list = ErlangTerm::List(vec![
             ErlangTerm::Integer(1),
             ErlangTerm::Float(2.0),
@@ -886,9 +1364,9 @@ mod tests {
         let result1 = ListsBif::member_2(&ErlangTerm::Integer(1), &list).unwrap();
         assert_eq!(result1, ErlangTerm::Atom("true".to_string()));
         
-        // Integer(2) should match Float(2.0) due to structural equality
+        // Integer(2) should not match Float(2.0)
         let result2 = ListsBif::member_2(&ErlangTerm::Integer(2), &list).unwrap();
-        assert_eq!(result2, ErlangTerm::Atom("true".to_string()));
+        assert_eq!(result2, ErlangTerm::Atom("false".to_string()));
     }
 
     #[test]
@@ -984,6 +1462,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_key_lookups_compare_with_coercion() {
+        // keyfind(1, 1, [{1.0}]) is {1.0}, and keys inside tuples coerce too
+        let list = ErlangTerm::List(vec![
+            ErlangTerm::Tuple(vec![ErlangTerm::Float(1.0)]),
+            ErlangTerm::Tuple(vec![ErlangTerm::Tuple(vec![ErlangTerm::Float(2.0)])]),
+        ]);
+        let pos = ErlangTerm::Integer(1);
+        assert_eq!(
+            ListsBif::keyfind_3(&ErlangTerm::Integer(1), &pos, &list).unwrap(),
+            ErlangTerm::Tuple(vec![ErlangTerm::Float(1.0)])
+        );
+        let nested = ErlangTerm::Tuple(vec![ErlangTerm::Integer(2)]);
+        assert_eq!(ListsBif::keymember_3(&nested, &pos, &list).unwrap(), ErlangTerm::Atom("true".to_string()));
+        assert_eq!(
+            ListsBif::keysearch_3(&ErlangTerm::Integer(1), &pos, &list).unwrap(),
+            ErlangTerm::Tuple(vec![
+                ErlangTerm::Atom("value".to_string()),
+                ErlangTerm::Tuple(vec![ErlangTerm::Float(1.0)]),
+            ])
+        );
+        assert_eq!(
+            ListsBif::keyfind_3(&ErlangTerm::Integer(3), &pos, &list).unwrap(),
+            ErlangTerm::Atom("false".to_string())
+        );
+
+        // member/2 stays exact
+        let floats = ErlangTerm::List(vec![ErlangTerm::Float(1.0)]);
+        assert_eq!(ListsBif::member_2(&ErlangTerm::Integer(1), &floats).unwrap(), ErlangTerm::Atom("false".to_string()));
+    }
+
     #[test]
     fn test_keyfind_3_not_found() {
         let list = ErlangTerm::List(vec![
@@ -1320,5 +1829,132 @@ mod tests {
         .unwrap();
         assert_eq!(result, ErlangTerm::Atom("false".to_string()));
     }
-}
 
+    fn key_tuple(key: &str, value: i64) -> ErlangTerm {
+        ErlangTerm::Tuple(vec![ErlangTerm::Atom(key.to_string()), ErlangTerm::Integer(value)])
+    }
+
+    /// Resume until done, returning the result and the number of yields
+    fn run_to_completion(mut trap: ListsTrap, reds_left: usize) -> (ErlangTerm, usize) {
+        let mut yields = 0;
+        loop {
+            match trap {
+                ListsTrap::Done { result, .. } => return (result, yields),
+                ListsTrap::Yield(continuation) => {
+                    yields += 1;
+                    trap = ListsBif::resume(continuation, reds_left);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_keystore_4() {
+        let list = ErlangTerm::List(vec![key_tuple("a", 1), key_tuple("b", 2), key_tuple("b", 3)]);
+        let b = ErlangTerm::Atom("b".to_string());
+        let pos = ErlangTerm::Integer(1);
+
+        // Only the first match is replaced
+        let result = ListsBif::keystore_4(&b, &pos, &list, &key_tuple("b", 9)).unwrap();
+        assert_eq!(
+            result,
+            ErlangTerm::List(vec![key_tuple("a", 1), key_tuple("b", 9), key_tuple("b", 3)])
+        );
+
+        let result = ListsBif::keystore_4(&b, &pos, &ErlangTerm::Nil, &key_tuple("b", 9)).unwrap();
+        assert_eq!(result, ErlangTerm::List(vec![key_tuple("b", 9)]));
+
+        // Keys compare with ==
+        let list = ErlangTerm::List(vec![ErlangTerm::Tuple(vec![ErlangTerm::Float(1.0)])]);
+        let new = ErlangTerm::Tuple(vec![ErlangTerm::Integer(1)]);
+        let result = ListsBif::keystore_4(&ErlangTerm::Integer(1), &pos, &list, &new).unwrap();
+        assert_eq!(result, ErlangTerm::List(vec![new.clone()]));
+
+        assert!(ListsBif::keystore_4(&b, &pos, &list, &ErlangTerm::Integer(1)).is_err());
+        assert!(ListsBif::keystore_4(&b, &ErlangTerm::Integer(0), &list, &new).is_err());
+        assert!(ListsBif::keystore_4(&b, &pos, &ErlangTerm::Integer(1), &new).is_err());
+    }
+
+    #[test]
+    fn test_seq() {
+        let ints = |values: &[i64]| ErlangTerm::List(values.iter().map(|&v| ErlangTerm::Integer(v)).collect());
+        let seq3 = |from, to, incr| {
+            ListsBif::seq_3(&ErlangTerm::Integer(from), &ErlangTerm::Integer(to), &ErlangTerm::Integer(incr))
+        };
+
+        assert_eq!(
+            ListsBif::seq_2(&ErlangTerm::Integer(-1), &ErlangTerm::Integer(2)),
+            Ok(ints(&[-1, 0, 1, 2]))
+        );
+        assert_eq!(seq3(1, 10, 4), Ok(ints(&[1, 5, 9])));
+        assert_eq!(seq3(1, 0, 1), Ok(ErlangTerm::Nil));
+        assert_eq!(seq3(5, 5, 0), Ok(ints(&[5])));
+        assert_eq!(seq3(1, -2, -3), Ok(ints(&[1, -2])));
+        assert!(seq3(0, 2, -1).is_err());
+        assert!(seq3(1, 5, 0).is_err());
+        assert!(seq3(10, 1, 2).is_err());
+        assert_eq!(
+            seq3(i64::MAX - 1, i64::MAX, 1),
+            Ok(ints(&[i64::MAX - 1, i64::MAX]))
+        );
+        assert!(ListsBif::seq_2(&ErlangTerm::Float(1.0), &ErlangTerm::Integer(2)).is_err());
+    }
+
+    #[test]
+    fn test_yielding_matches_plain_bifs() {
+        let list = ErlangTerm::List((0..250).map(|i| key_tuple("k", i)).collect());
+        let last = key_tuple("k", 249);
+        let tail = ErlangTerm::List(vec![ErlangTerm::Integer(-1)]);
+        let pos = ErlangTerm::Integer(2);
+        let key = ErlangTerm::Integer(249);
+
+        let (result, yields) = run_to_completion(ListsBif::member_2_yielding(&last, &list, 5).unwrap(), 5);
+        assert_eq!(result, ListsBif::member_2(&last, &list).unwrap());
+        assert_eq!(yields, 4);
+
+        let (result, _) = run_to_completion(ListsBif::reverse_2_yielding(&list, &tail, 3).unwrap(), 3);
+        assert_eq!(result, ListsBif::reverse_2(&list, &tail).unwrap());
+
+        let (result, _) = run_to_completion(ListsBif::keyfind_3_yielding(&key, &pos, &list, 1).unwrap(), 1);
+        assert_eq!(result, ListsBif::keyfind_3(&key, &pos, &list).unwrap());
+        let (result, _) = run_to_completion(ListsBif::keymember_3_yielding(&key, &pos, &list, 1).unwrap(), 1);
+        assert_eq!(result, ErlangTerm::Atom("true".to_string()));
+        let (result, _) = run_to_completion(ListsBif::keysearch_3_yielding(&key, &pos, &list, 1).unwrap(), 1);
+        assert_eq!(result, ListsBif::keysearch_3(&key, &pos, &list).unwrap());
+
+        let missing = ErlangTerm::Integer(1000);
+        let (result, _) = run_to_completion(ListsBif::keyfind_3_yielding(&missing, &pos, &list, 1).unwrap(), 1);
+        assert_eq!(result, ErlangTerm::Atom("false".to_string()));
+    }
+
+    #[test]
+    fn test_yielding_reductions() {
+        let list = ErlangTerm::List((0..100).map(ErlangTerm::Integer).collect());
+        let missing = ErlangTerm::Integer(-1);
+
+        // A budget covering the list finishes, charging per ten elements
+        match ListsBif::member_2_yielding(&missing, &list, 10).unwrap() {
+            ListsTrap::Done { result, reductions } => {
+                assert_eq!(result, ErlangTerm::Atom("false".to_string()));
+                assert_eq!(reductions, 10);
+            }
+            ListsTrap::Yield(_) => panic!("expected member to finish"),
+        }
+
+        // A smaller budget yields with the rest of the list left
+        match ListsBif::member_2_yielding(&missing, &list, 4).unwrap() {
+            ListsTrap::Yield(continuation) => assert_eq!(continuation.remaining(), 60),
+            ListsTrap::Done { .. } => panic!("expected member to yield"),
+        }
+
+        // Empty lists and early matches still cost a reduction
+        let trap = ListsBif::member_2_yielding(&missing, &ErlangTerm::Nil, 0).unwrap();
+        assert!(matches!(trap, ListsTrap::Done { reductions: 1, .. }));
+        let trap = ListsBif::reverse_2_yielding(&ErlangTerm::Nil, &list, 0).unwrap();
+        assert_eq!(trap, ListsTrap::Done { result: list.clone(), reductions: 1 });
+
+        assert!(ListsBif::member_2_yielding(&missing, &ErlangTerm::Integer(1), 1).is_err());
+        assert!(ListsBif::reverse_2_yielding(&ErlangTerm::Integer(1), &list, 1).is_err());
+        assert!(ListsBif::keyfind_3_yielding(&missing, &ErlangTerm::Integer(0), &list, 1).is_err());
+    }
+}
@@ -40,7 +40,7 @@ use std::sync::atomic::AtomicU64;
 use std::hash::{Hash, Hasher};
 use std::collections::hash_map::DefaultHasher;
 use usecases_process_management::process_code_tracking::{ModuleCodeArea, any_process_uses_module, any_dirty_process_uses_module};
-use code_management_code_loading::{get_global_code_ix, get_global_module_manager};
+use code_management_code_loading::{get_global_code_ix, get_global_code_permissions, get_global_module_manager, CodeOperation};
 
 /// Error type for code loading operations
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -51,6 +51,8 @@ pub enum LoadError {
     SystemLimit(String),
     /// Operation not supported
     NotSupported(String),
+    /// Operation refused by the code access policy
+    NotAllowed(String),
 }
 
 /// Module status
@@ -774,6 +776,7 @@ impl LoadBif {
     ///
     /// # Returns
     /// * `Ok(ErlangTerm::Atom("true"))` - If purged successfully
+    /// * `Err(LoadError::NotAllowed)` - If the code access policy refuses the purge
     /// * `Err(LoadError)` - If operation fails
     ///
     /// # Examples
@@ -819,6 +822,10 @@ impl LoadBif {
             }
         };
 
+        get_global_code_permissions()
+            .check_module_access(&module_name, CodeOperation::Purge, None)
+            .map_err(|denied| LoadError::NotAllowed(denied.to_string()))?;
+
         let registry = ModuleRegistry::get_instance();
         let mut modules = registry.modules.write().unwrap();
 
@@ -998,7 +1005,9 @@ impl LoadBif {
     /// Finish loading prepared code (finish_loading/1)
     ///
     /// Finishes loading prepared code. Takes a list of magic references to
-    /// prepared code and makes the modules active.
+    /// prepared code and makes the modules active. A module the code access
+    /// policy does not allow to be loaded or reloaded fails with reason
+    /// `not_allowed`.
     ///
     /// # Arguments
     /// * `prepared_list` - List of magic references to prepared code
@@ -1075,17 +1084,30 @@ impl LoadBif {
                 if let Some(prepared) = registry.remove(&ref_key) {
                     // Check if module already has old code
                     let modules = module_registry.modules.read().unwrap();
-                    if let Some(entry) = modules.get(&prepared.module) {
-                        if entry.has_old_code {
+                    let operation = match modules.get(&prepared.module) {
+                        Some(entry) if entry.has_old_code => {
                             errors.push((
                                 ErlangTerm::Atom(prepared.module.clone()),
                                 ErlangTerm::Atom("not_purged".to_string()),
                             ));
                             continue;
                         }
-                    }
+                        Some(_) => CodeOperation::Reload,
+                        None => CodeOperation::Load,
+                    };
                     drop(modules);
 
+                    if get_global_code_permissions()
+                        .check_module_access(&prepared.module, operation, None)
+                        .is_err()
+                    {
+                        errors.push((
+                            ErlangTerm::Atom(prepared.module.clone()),
+                            ErlangTerm::Atom("not_allowed".to_string()),
+                        ));
+                        continue;
+                    }
+
                     // Register the module
                     let mut modules = module_registry.modules.write().unwrap();
                     let status = if prepared.has_on_load {
@@ -3354,6 +3376,39 @@ mod tests {
         assert!(result.is_err());
     }
 
+    struct LockedModulePolicy;
+
+    impl code_management_code_loading::CodeAccessPolicy for LockedModulePolicy {
+        fn allow(&self, module: &str, _operation: CodeOperation, _requester: Option<u64>) -> bool {
+            module != "policy_locked"
+        }
+    }
+
+    #[test]
+    fn test_code_access_policy_refuses_load_and_purge() {
+        let permissions = get_global_code_permissions();
+        permissions.set_access_policy(std::sync::Arc::new(LockedModulePolicy));
+        let module = ErlangTerm::Atom("policy_locked".to_string());
+
+        let result = LoadBif::erts_internal_purge_module_2(&module, &ErlangTerm::Atom("force".to_string()));
+        assert!(matches!(result, Err(LoadError::NotAllowed(_))));
+
+        let prepared = LoadBif::erts_internal_prepare_loading_2(&module, &ErlangTerm::Binary(vec![1, 2, 3])).unwrap();
+        let result = LoadBif::finish_loading_1(&ErlangTerm::List(vec![prepared])).unwrap();
+        assert_eq!(
+            result,
+            ErlangTerm::Tuple(vec![
+                ErlangTerm::Atom("error".to_string()),
+                ErlangTerm::List(vec![ErlangTerm::Tuple(vec![
+                    module.clone(),
+                    ErlangTerm::Atom("not_allowed".to_string()),
+                ])]),
+            ])
+        );
+
+        permissions.clear_access_policy();
+    }
+
     #[test]
     fn test_check_old_code_1_invalid_argument() {
         LoadBif::clear_all();
@@ -0,0 +1,680 @@
+//! Map Built-in Functions
+//!
+//! Provides the `maps` module BIFs over the entities [`Map`]:
+//! - Combining maps (merge/2, merge_with/3)
+//! - Higher-order traversal (filter/2, map/2, fold/3)
+//! - Iterators (iterator/1, iterator/2 with the `ordered` and `reversed`
+//!   options, next/1)
+//! - Updates (take/2, update_with/3, update_with/4)
+//!
+//! Erlang funs are passed as Rust closures. An iterator is an ordinary term,
+//! `[Path | Map]` as in maps.erl, so it can be stored in another term (e.g.
+//! a process dictionary or a message) and resumed later with
+//! [`MapsBif::next`]. `Path` is the index of the next entry for unordered
+//! iteration, or the list of keys still to visit for ordered iteration.
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ *
+ * Creation productivity increased for code in this file by using AALang and GAB.
+ * See https://github.com/yenrab/AALang-Gab
+ */
+
+use entities_data_handling::term_hashing::Term;
+use entities_data_handling::Map;
+use entities_utilities::BigNumber;
+use std::cmp::Ordering;
+
+/// Error type for map BIF operations
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapsError {
+    /// Argument is not a map (`{badmap, Term}`)
+    BadMap(Term),
+    /// Key is not in the map (`{badkey, Key}`)
+    BadKey(Term),
+    /// Argument is not a valid map iterator
+    BadIterator(Term),
+}
+
+impl std::fmt::Display for MapsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapsError::BadMap(term) => write!(f, "Bad map: {:?}", term),
+            MapsError::BadKey(key) => write!(f, "Bad key: {:?}", key),
+            MapsError::BadIterator(term) => write!(f, "Bad iterator: {:?}", term),
+        }
+    }
+}
+
+impl std::error::Error for MapsError {}
+
+/// Iteration order of a map iterator (the option of `maps:iterator/2`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IteratorOrder {
+    /// Internal map order (`undefined`)
+    Undefined,
+    /// Ascending term order of the keys (`ordered`)
+    Ordered,
+    /// Descending term order of the keys (`reversed`)
+    Reversed,
+}
+
+/// A map or a map iterator, as accepted by filter/2, map/2 and fold/3
+#[derive(Debug, Clone, Copy)]
+pub enum MapOrIterator<'a> {
+    /// Traverse the map in internal order
+    Map(&'a Map),
+    /// Traverse the remaining entries of an iterator, in its order
+    Iterator(&'a Term),
+}
+
+impl<'a> From<&'a Map> for MapOrIterator<'a> {
+    fn from(map: &'a Map) -> Self {
+        MapOrIterator::Map(map)
+    }
+}
+
+impl<'a> From<&'a Term> for MapOrIterator<'a> {
+    fn from(iterator: &'a Term) -> Self {
+        MapOrIterator::Iterator(iterator)
+    }
+}
+
+/// Map Built-in Functions
+pub struct MapsBif;
+
+impl MapsBif {
+    /// Merge two maps (`maps:merge/2`)
+    ///
+    /// Values in `map2` take precedence over values in `map1`.
+    pub fn merge(map1: &Map, map2: &Map) -> Map {
+        map1.merge(map2)
+    }
+
+    /// Merge two maps, combining the values of keys in both
+    /// (`maps:merge_with/3`)
+    ///
+    /// # Arguments
+    /// * `combiner` - Called as `combiner(Key, Value1, Value2)` for each key
+    ///   in both maps
+    /// * `map1` - First map
+    /// * `map2` - Second map
+    ///
+    /// # Examples
+    /// ```
+    /// use entities_data_handling::Map;
+    /// use entities_data_handling::term_hashing::Term;
+    /// use usecases_bifs::maps::MapsBif;
+    ///
+    /// let map1 = Map::from_list(vec![(Term::Small(1), Term::Small(10)), (Term::Small(2), Term::Small(20))]);
+    /// let map2 = Map::from_list(vec![(Term::Small(2), Term::Small(5)), (Term::Small(3), Term::Small(30))]);
+    /// let merged = MapsBif::merge_with(
+    ///     |_key, v1, v2| match (v1, v2) {
+    ///         (Term::Small(a), Term::Small(b)) => Term::Small(a + b),
+    ///         _ => v2.clone(),
+    ///     },
+    ///     &map1,
+    ///     &map2,
+    /// );
+    /// assert_eq!(merged.get(&Term::Small(2)), Some(&Term::Small(25)));
+    /// assert_eq!(merged.size(), 3);
+    /// ```
+    pub fn merge_with(
+        mut combiner: impl FnMut(&Term, &Term, &Term) -> Term,
+        map1: &Map,
+        map2: &Map,
+    ) -> Map {
+        let mut merged = map1.clone();
+        for (key, value2) in map2.to_list() {
+            let value = match map1.get(&key) {
+                Some(value1) => combiner(&key, value1, &value2),
+                None => value2,
+            };
+            merged.put(key, value);
+        }
+        merged
+    }
+
+    /// Keep the entries for which `pred(Key, Value)` is true
+    /// (`maps:filter/2`)
+    ///
+    /// # Returns
+    /// * `Ok(Map)` - The filtered map
+    /// * `Err(MapsError)` - If an iterator argument is invalid
+    pub fn filter<'a>(
+        mut pred: impl FnMut(&Term, &Term) -> bool,
+        map_or_iterator: impl Into<MapOrIterator<'a>>,
+    ) -> Result<Map, MapsError> {
+        let mut filtered = Map::new();
+        Self::for_each(map_or_iterator.into(), |key, value| {
+            if pred(&key, &value) {
+                filtered.put(key, value);
+            }
+        })?;
+        Ok(filtered)
+    }
+
+    /// Replace each value by `fun(Key, Value)` (`maps:map/2`)
+    ///
+    /// # Returns
+    /// * `Ok(Map)` - The mapped map
+    /// * `Err(MapsError)` - If an iterator argument is invalid
+    pub fn map<'a>(
+        mut fun: impl FnMut(&Term, &Term) -> Term,
+        map_or_iterator: impl Into<MapOrIterator<'a>>,
+    ) -> Result<Map, MapsError> {
+        let mut mapped = Map::new();
+        Self::for_each(map_or_iterator.into(), |key, value| {
+            let value = fun(&key, &value);
+            mapped.put(key, value);
+        })?;
+        Ok(mapped)
+    }
+
+    /// Fold `fun(Key, Value, Acc)` over the entries (`maps:fold/3`)
+    ///
+    /// With an ordered iterator the entries are folded in key order.
+    ///
+    /// # Returns
+    /// * `Ok(Acc)` - The final accumulator
+    /// * `Err(MapsError)` - If an iterator argument is invalid
+    ///
+    /// # Examples
+    /// ```
+    /// use entities_data_handling::Map;
+    /// use entities_data_handling::term_hashing::Term;
+    /// use usecases_bifs::maps::{IteratorOrder, MapsBif};
+    ///
+    /// let map = Map::from_list(vec![
+    ///     (Term::Small(3), Term::Nil),
+    ///     (Term::Small(1), Term::Nil),
+    ///     (Term::Small(2), Term::Nil),
+    /// ]);
+    /// let iterator = MapsBif::iterator(&map, IteratorOrder::Reversed);
+    /// let keys = MapsBif::fold(|key, _value, mut acc: Vec<Term>| {
+    ///     acc.push(key.clone());
+    ///     acc
+    /// }, Vec::new(), &iterator).unwrap();
+    /// assert_eq!(keys, vec![Term::Small(3), Term::Small(2), Term::Small(1)]);
+    /// ```
+    pub fn fold<'a, A>(
+        mut fun: impl FnMut(&Term, &Term, A) -> A,
+        init: A,
+        map_or_iterator: impl Into<MapOrIterator<'a>>,
+    ) -> Result<A, MapsError> {
+        let mut acc = Some(init);
+        Self::for_each(map_or_iterator.into(), |key, value| {
+            acc = acc.take().map(|acc| fun(&key, &value, acc));
+        })?;
+        Ok(acc.expect("accumulator is restored after each call"))
+    }
+
+    /// Create an iterator over a map (`maps:iterator/2`)
+    ///
+    /// # Arguments
+    /// * `map` - Map to iterate over
+    /// * `order` - Order of the keys
+    ///
+    /// # Returns
+    /// The iterator term, to be passed to [`next`](Self::next)
+    pub fn iterator(map: &Map, order: IteratorOrder) -> Term {
+        let path = match order {
+            IteratorOrder::Undefined => Term::Small(0),
+            IteratorOrder::Ordered | IteratorOrder::Reversed => {
+                let mut keys: Vec<Term> = map.keys().into_iter().cloned().collect();
+                keys.sort_by(term_order);
+                if order == IteratorOrder::Reversed {
+                    keys.reverse();
+                }
+                list_term(keys, Term::Nil)
+            }
+        };
+        cons(path, Term::Map(map.to_list()))
+    }
+
+    /// Get the next entry of an iterator (`maps:next/1`)
+    ///
+    /// The iterator itself is not changed; the returned iterator continues
+    /// after the returned entry. A `{Key, Value, Iterator}` tuple is also
+    /// accepted, as `maps:next/1` does.
+    ///
+    /// # Returns
+    /// * `Ok(Some((Key, Value, Iterator)))` - The next entry
+    /// * `Ok(None)` - If the iterator is exhausted (`none`)
+    /// * `Err(MapsError::BadIterator)` - If `iterator` is not an iterator
+    ///
+    /// # Examples
+    /// ```
+    /// use entities_data_handling::Map;
+    /// use entities_data_handling::term_hashing::Term;
+    /// use usecases_bifs::maps::{IteratorOrder, MapsBif};
+    ///
+    /// let map = Map::from_list(vec![(Term::Small(2), Term::Nil), (Term::Small(1), Term::Nil)]);
+    /// let iterator = MapsBif::iterator(&map, IteratorOrder::Ordered);
+    /// let (key, _, iterator) = MapsBif::next(&iterator).unwrap().unwrap();
+    /// assert_eq!(key, Term::Small(1));
+    /// let (key, _, iterator) = MapsBif::next(&iterator).unwrap().unwrap();
+    /// assert_eq!(key, Term::Small(2));
+    /// assert_eq!(MapsBif::next(&iterator).unwrap(), None);
+    /// ```
+    pub fn next(iterator: &Term) -> Result<Option<(Term, Term, Term)>, MapsError> {
+        let bad_iterator = || MapsError::BadIterator(iterator.clone());
+        let (path, entries) = match iterator {
+            Term::Tuple(elements) if elements.len() == 3 => {
+                return Ok(Some((elements[0].clone(), elements[1].clone(), elements[2].clone())));
+            }
+            Term::List { head, tail } => match tail.as_ref() {
+                Term::Map(entries) => (head.as_ref(), entries),
+                _ => return Err(bad_iterator()),
+            },
+            _ => return Err(bad_iterator()),
+        };
+        match path {
+            Term::Small(index) => {
+                let index = usize::try_from(*index).map_err(|_| bad_iterator())?;
+                match entries.get(index) {
+                    Some((key, value)) => {
+                        let rest = cons(Term::Small(index as i64 + 1), Term::Map(entries.clone()));
+                        Ok(Some((key.clone(), value.clone(), rest)))
+                    }
+                    None if index == entries.len() => Ok(None),
+                    None => Err(bad_iterator()),
+                }
+            }
+            Term::List { head: key, tail: keys } => {
+                let value = entries
+                    .iter()
+                    .find(|(k, _)| k == key.as_ref())
+                    .map(|(_, value)| value.clone())
+                    .ok_or_else(bad_iterator)?;
+                let rest = cons(keys.as_ref().clone(), Term::Map(entries.clone()));
+                Ok(Some((key.as_ref().clone(), value, rest)))
+            }
+            Term::Nil => Ok(None),
+            _ => Err(bad_iterator()),
+        }
+    }
+
+    /// Remove a key, returning its value and the remaining map
+    /// (`maps:take/2`)
+    ///
+    /// # Returns
+    /// `Some((Value, Map))`, or `None` if the key is not in the map (`error`)
+    pub fn take(key: &Term, map: &Map) -> Option<(Term, Map)> {
+        let mut rest = map.clone();
+        rest.take(key).map(|(_, value)| (value, rest))
+    }
+
+    /// Replace the value of a key by `fun(Value)` (`maps:update_with/3`)
+    ///
+    /// # Returns
+    /// * `Ok(Map)` - The updated map
+    /// * `Err(MapsError::BadKey)` - If the key is not in the map
+    pub fn update_with(key: &Term, fun: impl FnOnce(&Term) -> Term, map: &Map) -> Result<Map, MapsError> {
+        let value = map.get(key).ok_or_else(|| MapsError::BadKey(key.clone()))?;
+        let value = fun(value);
+        let mut updated = map.clone();
+        updated.put(key.clone(), value);
+        Ok(updated)
+    }
+
+    /// Replace the value of a key by `fun(Value)`, or insert `init` if the
+    /// key is not in the map (`maps:update_with/4`)
+    pub fn update_with_init(key: &Term, fun: impl FnOnce(&Term) -> Term, init: Term, map: &Map) -> Map {
+        let value = match map.get(key) {
+            Some(value) => fun(value),
+            None => init,
+        };
+        let mut updated = map.clone();
+        updated.put(key.clone(), value);
+        updated
+    }
+
+    /// Call `visit` for each remaining entry of a map or iterator, in order
+    fn for_each(map_or_iterator: MapOrIterator, mut visit: impl FnMut(Term, Term)) -> Result<(), MapsError> {
+        match map_or_iterator {
+            MapOrIterator::Map(map) => {
+                for (key, value) in map.to_list() {
+                    visit(key, value);
+                }
+            }
+            MapOrIterator::Iterator(iterator) => {
+                let mut next = Self::next(iterator)?;
+                while let Some((key, value, iterator)) = next {
+                    visit(key, value);
+                    next = Self::next(&iterator)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn cons(head: Term, tail: Term) -> Term {
+    Term::List {
+        head: Box::new(head),
+        tail: Box::new(tail),
+    }
+}
+
+fn list_term(elements: Vec<Term>, tail: Term) -> Term {
+    elements.into_iter().rev().fold(tail, |tail, head| cons(head, tail))
+}
+
+/// Total term order, as used by `ordered` iterators (erts_internal:cmp_term)
+///
+/// Types order as number < atom < reference < fun < port < pid < tuple <
+/// map < nil < list < bitstring. Integers order before floats of equal
+/// value, so distinct map keys never compare equal. Atoms are compared by
+/// atom index, as [`Term::Atom`] carries no name.
+fn term_order(a: &Term, b: &Term) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    // Lists are compared element by element, looping over the tails
+    loop {
+        match (a, b) {
+            (Term::List { head: ha, tail: ta }, Term::List { head: hb, tail: tb }) => {
+                match term_order(ha, hb) {
+                    Ordering::Equal => (a, b) = (ta.as_ref(), tb.as_ref()),
+                    unequal => return unequal,
+                }
+            }
+            _ => return term_order_shallow(a, b),
+        }
+    }
+}
+
+fn term_order_shallow(a: &Term, b: &Term) -> Ordering {
+    let by_type = type_rank(a).cmp(&type_rank(b));
+    if by_type != Ordering::Equal {
+        return by_type;
+    }
+    match (a, b) {
+        (Term::Atom(x), Term::Atom(y)) => x.cmp(y),
+        (
+            Term::Ref { node: nx, ids: ix, creation: cx },
+            Term::Ref { node: ny, ids: iy, creation: cy },
+        ) => (nx, ix.iter().rev().collect::<Vec<_>>(), cx).cmp(&(ny, iy.iter().rev().collect::<Vec<_>>(), cy)),
+        (
+            Term::Fun { module: mx, function: fx, arity: ax, .. },
+            Term::Fun { module: my, function: fy, arity: ay, .. },
+        ) => (mx, fx, ax).cmp(&(my, fy, ay)),
+        (
+            Term::Port { node: nx, id: ix, creation: cx },
+            Term::Port { node: ny, id: iy, creation: cy },
+        ) => (nx, ix, cx).cmp(&(ny, iy, cy)),
+        (
+            Term::Pid { node: nx, id: ix, serial: sx, creation: cx },
+            Term::Pid { node: ny, id: iy, serial: sy, creation: cy },
+        ) => (nx, sx, ix, cx).cmp(&(ny, sy, iy, cy)),
+        (Term::Tuple(x), Term::Tuple(y)) => x.len().cmp(&y.len()).then_with(|| elements_order(x, y)),
+        (Term::Map(x), Term::Map(y)) => {
+            let sorted = |entries: &[(Term, Term)]| {
+                let mut entries = entries.to_vec();
+                entries.sort_by(|(k1, _), (k2, _)| term_order(k1, k2));
+                entries.into_iter().unzip::<_, _, Vec<Term>, Vec<Term>>()
+            };
+            let ((kx, vx), (ky, vy)) = (sorted(x), sorted(y));
+            x.len()
+                .cmp(&y.len())
+                .then_with(|| elements_order(&kx, &ky))
+                .then_with(|| elements_order(&vx, &vy))
+        }
+        (
+            Term::Binary { data: dx, bit_size: sx, .. },
+            Term::Binary { data: dy, bit_size: sy, .. },
+        ) => dx[..sx / 8].cmp(&dy[..sy / 8]).then_with(|| sx.cmp(sy)),
+        _ if type_rank(a) == 0 => number_order(a, b),
+        _ => Ordering::Equal,
+    }
+}
+
+fn elements_order(x: &[Term], y: &[Term]) -> Ordering {
+    x.iter()
+        .zip(y)
+        .map(|(a, b)| term_order(a, b))
+        .find(|ordering| *ordering != Ordering::Equal)
+        .unwrap_or_else(|| x.len().cmp(&y.len()))
+}
+
+fn type_rank(term: &Term) -> u8 {
+    match term {
+        Term::Small(_) | Term::Big(_) | Term::Rational(_) | Term::Float(_) => 0,
+        Term::Atom(_) => 1,
+        Term::Ref { .. } => 2,
+        Term::Fun { .. } => 3,
+        Term::Port { .. } => 4,
+        Term::Pid { .. } => 5,
+        Term::Tuple(_) => 6,
+        Term::Map(_) => 7,
+        Term::Nil => 8,
+        Term::List { .. } => 9,
+        _ => 10,
+    }
+}
+
+/// Order of two numbers by value, then integers before floats
+fn number_order(a: &Term, b: &Term) -> Ordering {
+    let by_value = match (a, b) {
+        (Term::Small(x), Term::Small(y)) => x.cmp(y),
+        (Term::Big(x), Term::Big(y)) => x.comp(y).cmp(&0),
+        (Term::Small(x), Term::Big(y)) => BigNumber::from_i64(*x).comp(y).cmp(&0),
+        (Term::Big(x), Term::Small(y)) => x.comp(&BigNumber::from_i64(*y)).cmp(&0),
+        _ => number_value(a).partial_cmp(&number_value(b)).unwrap_or(Ordering::Equal),
+    };
+    let kind = |term: &Term| match term {
+        Term::Float(_) => 1,
+        _ => 0,
+    };
+    by_value.then_with(|| kind(a).cmp(&kind(b)))
+}
+
+fn number_value(term: &Term) -> f64 {
+    match term {
+        Term::Small(n) => *n as f64,
+        Term::Big(n) => n.to_f64().unwrap_or(if n.is_positive() { f64::INFINITY } else { f64::NEG_INFINITY }),
+        Term::Rational(r) => r.to_f64(),
+        Term::Float(f) => *f,
+        _ => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn small_map(pairs: &[(i64, i64)]) -> Map {
+        Map::from_list(pairs.iter().map(|&(k, v)| (Term::Small(k), Term::Small(v))).collect())
+    }
+
+    fn drain(mut iterator: Term) -> Vec<(Term, Term)> {
+        let mut entries = Vec::new();
+        while let Some((key, value, next)) = MapsBif::next(&iterator).unwrap() {
+            entries.push((key, value));
+            iterator = next;
+        }
+        entries
+    }
+
+    #[test]
+    fn test_merge_and_merge_with() {
+        let map1 = small_map(&[(1, 1), (2, 2)]);
+        let map2 = small_map(&[(2, 20), (3, 30)]);
+        assert_eq!(MapsBif::merge(&map1, &map2), small_map(&[(1, 1), (2, 20), (3, 30)]));
+
+        let mut calls = Vec::new();
+        let merged = MapsBif::merge_with(
+            |key, v1, v2| {
+                calls.push(key.clone());
+                Term::Tuple(vec![v1.clone(), v2.clone()])
+            },
+            &map1,
+            &map2,
+        );
+        assert_eq!(calls, vec![Term::Small(2)]);
+        assert_eq!(
+            merged.get(&Term::Small(2)),
+            Some(&Term::Tuple(vec![Term::Small(2), Term::Small(20)]))
+        );
+        assert_eq!(merged.get(&Term::Small(3)), Some(&Term::Small(30)));
+    }
+
+    #[test]
+    fn test_filter_map_fold() {
+        let map = small_map(&[(1, 10), (2, 20), (3, 30)]);
+        let even = MapsBif::filter(|key, _| matches!(key, Term::Small(k) if k % 2 == 0), &map).unwrap();
+        assert_eq!(even, small_map(&[(2, 20)]));
+
+        let doubled = MapsBif::map(
+            |_, value| match value {
+                Term::Small(v) => Term::Small(v * 2),
+                other => other.clone(),
+            },
+            &map,
+        )
+        .unwrap();
+        assert_eq!(doubled, small_map(&[(1, 20), (2, 40), (3, 60)]));
+
+        let sum = MapsBif::fold(
+            |_, value, acc| match value {
+                Term::Small(v) => acc + v,
+                _ => acc,
+            },
+            0,
+            &map,
+        )
+        .unwrap();
+        assert_eq!(sum, 60);
+    }
+
+    #[test]
+    fn test_iterator_orders() {
+        let map = small_map(&[(3, 0), (1, 0), (2, 0)]);
+        let keys = |order| -> Vec<Term> {
+            drain(MapsBif::iterator(&map, order)).into_iter().map(|(k, _)| k).collect()
+        };
+        assert_eq!(keys(IteratorOrder::Undefined), vec![Term::Small(3), Term::Small(1), Term::Small(2)]);
+        assert_eq!(keys(IteratorOrder::Ordered), vec![Term::Small(1), Term::Small(2), Term::Small(3)]);
+        assert_eq!(keys(IteratorOrder::Reversed), vec![Term::Small(3), Term::Small(2), Term::Small(1)]);
+
+        // An exhausted iterator stays exhausted
+        assert_eq!(MapsBif::next(&MapsBif::iterator(&Map::new(), IteratorOrder::Ordered)), Ok(None));
+    }
+
+    #[test]
+    fn test_iterator_resumes_from_stored_term() {
+        let map = small_map(&[(1, 10), (2, 20), (3, 30)]);
+        let iterator = MapsBif::iterator(&map, IteratorOrder::Ordered);
+        let (_, _, rest) = MapsBif::next(&iterator).unwrap().unwrap();
+
+        // Store the iterator in a tuple, then take it out and resume
+        let stored = Term::Tuple(vec![Term::Atom(1), rest]);
+        let Term::Tuple(elements) = stored.clone() else { unreachable!() };
+        assert_eq!(drain(elements[1].clone()), vec![
+            (Term::Small(2), Term::Small(20)),
+            (Term::Small(3), Term::Small(30)),
+        ]);
+
+        // The first entry is still available from the original iterator
+        assert_eq!(drain(iterator).len(), 3);
+
+        // Filter over the remaining entries only
+        let rest = MapsBif::filter(|_, _| true, &elements[1]).unwrap();
+        assert_eq!(rest, small_map(&[(2, 20), (3, 30)]));
+    }
+
+    #[test]
+    fn test_next_errors_and_tuple() {
+        let tuple = Term::Tuple(vec![Term::Small(1), Term::Small(2), Term::Nil]);
+        assert_eq!(MapsBif::next(&tuple), Ok(Some((Term::Small(1), Term::Small(2), Term::Nil))));
+
+        for bad in [
+            Term::Small(1),
+            cons(Term::Small(0), Term::Nil),
+            cons(Term::Small(-1), Term::Map(vec![])),
+            cons(Term::Small(2), Term::Map(vec![])),
+            cons(cons(Term::Small(7), Term::Nil), Term::Map(vec![])),
+        ] {
+            assert_eq!(MapsBif::next(&bad), Err(MapsError::BadIterator(bad.clone())));
+        }
+    }
+
+    #[test]
+    fn test_take_and_update_with() {
+        let map = small_map(&[(1, 10), (2, 20)]);
+        assert_eq!(MapsBif::take(&Term::Small(1), &map), Some((Term::Small(10), small_map(&[(2, 20)]))));
+        assert_eq!(MapsBif::take(&Term::Small(3), &map), None);
+
+        let increment = |value: &Term| match value {
+            Term::Small(v) => Term::Small(v + 1),
+            other => other.clone(),
+        };
+        assert_eq!(
+            MapsBif::update_with(&Term::Small(2), increment, &map),
+            Ok(small_map(&[(1, 10), (2, 21)]))
+        );
+        assert_eq!(
+            MapsBif::update_with(&Term::Small(3), increment, &map),
+            Err(MapsError::BadKey(Term::Small(3)))
+        );
+        assert_eq!(
+            MapsBif::update_with_init(&Term::Small(3), increment, Term::Small(0), &map),
+            small_map(&[(1, 10), (2, 20), (3, 0)])
+        );
+    }
+
+    #[test]
+    fn test_term_order() {
+        let mut terms = vec![
+            Term::Binary { data: b"a".to_vec(), bit_offset: 0, bit_size: 8 },
+            cons(Term::Small(1), Term::Nil),
+            Term::Nil,
+            Term::Map(vec![]),
+            Term::Tuple(vec![Term::Small(1)]),
+            Term::Tuple(vec![]),
+            Term::Pid { node: 0, id: 1, serial: 0, creation: 0 },
+            Term::Atom(3),
+            Term::Float(1.0),
+            Term::Small(1),
+            Term::Float(0.5),
+            Term::Big(BigNumber::from_i64(i64::MAX).plus(&BigNumber::from_i64(1))),
+        ];
+        terms.sort_by(term_order);
+        assert_eq!(terms[0], Term::Float(0.5));
+        assert_eq!(terms[1], Term::Small(1));
+        assert_eq!(terms[2], Term::Float(1.0));
+        assert!(matches!(terms[3], Term::Big(_)));
+        assert_eq!(terms[4], Term::Atom(3));
+        assert!(matches!(terms[5], Term::Pid { .. }));
+        assert_eq!(terms[6], Term::Tuple(vec![]));
+        assert_eq!(terms[7], Term::Tuple(vec![Term::Small(1)]));
+        assert_eq!(terms[8], Term::Map(vec![]));
+        assert_eq!(terms[9], Term::Nil);
+        assert_eq!(terms[10], cons(Term::Small(1), Term::Nil));
+        assert!(matches!(terms[11], Term::Binary { .. }));
+
+        // Shorter lists order first
+        let short = list_term(vec![Term::Small(1)], Term::Nil);
+        let long = list_term(vec![Term::Small(1), Term::Small(0)], Term::Nil);
+        assert_eq!(term_order(&short, &long), Ordering::Less);
+    }
+}
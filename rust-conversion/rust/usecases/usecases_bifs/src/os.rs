@@ -3,10 +3,25 @@
 //! Provides operating system interface BIFs:
 //! - Environment variable operations
 //! - Process ID retrieval
-//! - Timestamp operations
+//! - Timestamp, system time and performance counter operations
+//! - Running shell commands (`os:cmd/1,2`)
 //! - Signal handling
 //!
 //! This module uses safe Rust standard library functions instead of unsafe FFI calls.
+//!
+//! ## Environment
+//!
+//! The environment is read from the OS once and then kept in a cache guarded
+//! by a lock; `putenv` and `unsetenv` only change the cache. The OS
+//! environment is never modified, since `setenv` races with `getenv` calls
+//! made by other threads, including those in linked C libraries. Programs
+//! started by `os:cmd` are given the cached environment.
+//!
+//! ## Signals
+//!
+//! `set_signal/2` records the disposition in the global signal service of
+//! `infrastructure_utilities`, from which handled signals are delivered to
+//! `erl_signal_server`.
 
 /*
  * %CopyrightBegin%
@@ -35,9 +50,46 @@
  * See https://github.com/yenrab/AALang-Gab
  */
 
+use std::collections::HashMap;
 use std::env;
 use std::process;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::{OnceLock, RwLock};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+#[cfg(unix)]
+use std::time::Duration;
+
+#[cfg(unix)]
+use adapters_system_integration_unix::{PortMessage, SpawnOptions, SpawnPort};
+use infrastructure_time_management::clock::get_clock;
+use infrastructure_time_management::time_unit::{convert_time_unit, TimeUnit};
+use infrastructure_utilities::signals::{get_global_signal_service, Signal, SignalAction};
+
+/// Shell used to run `os:cmd` commands
+#[cfg(unix)]
+const CMD_SHELL: &str = "/bin/sh";
+
+/// How long `os:cmd` waits for output before checking again
+#[cfg(unix)]
+const CMD_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Cached environment of the emulator
+static ENV_CACHE: OnceLock<RwLock<HashMap<String, String>>> = OnceLock::new();
+
+/// Start of the performance counter
+static PERF_COUNTER_START: OnceLock<Instant> = OnceLock::new();
+
+/// Get the environment cache, reading the OS environment on first use
+///
+/// Variables whose name or value is not valid Unicode are left out.
+fn env_cache() -> &'static RwLock<HashMap<String, String>> {
+    ENV_CACHE.get_or_init(|| {
+        RwLock::new(
+            env::vars_os()
+                .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)))
+                .collect(),
+        )
+    })
+}
 
 /// OS BIF operations
 pub struct OsBif;
@@ -79,10 +131,19 @@ impl OsBif {
             .collect()
     }
 
+    /// Get the OS process id of the emulator as an integer
+    ///
+    /// The value behind `os:getpid/0`, and the `os_pid` reported for the
+    /// emulator itself.
+    pub fn pid() -> u32 {
+        process::id()
+    }
+
     /// Get all environment variables
     ///
     /// Equivalent to `os:getenv/0` in Erlang.
-    /// Returns all environment variables as a vector of (key, value) tuples.
+    /// Returns all environment variables as a vector of (key, value) tuples,
+    /// sorted by name.
     ///
     /// # Returns
     /// Vector of (key, value) string tuples
@@ -107,7 +168,14 @@ impl OsBif {
     /// assert_eq!(env1.len(), env2.len());
     /// ```
     pub fn env() -> Vec<(String, String)> {
-        env::vars().collect()
+        let mut vars: Vec<(String, String)> = env_cache()
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        vars.sort();
+        vars
     }
 
     /// Get a specific environment variable
@@ -140,13 +208,13 @@ impl OsBif {
     /// assert_eq!(value, Some("test_value".to_string()));
     /// ```
     pub fn getenv(key: &str) -> Option<String> {
-        env::var(key).ok()
+        env_cache().read().unwrap().get(key).cloned()
     }
 
     /// Set an environment variable
     ///
     /// Equivalent to `os:putenv/2` in Erlang.
-    /// Sets an environment variable for the current process.
+    /// Sets an environment variable in the environment cache.
     ///
     /// # Arguments
     /// * `key` - The environment variable name
@@ -154,7 +222,8 @@ impl OsBif {
     ///
     /// # Returns
     /// * `Ok(())` if successful
-    /// * `Err(error)` if setting failed
+    /// * `Err(OsError::InvalidArgument)` if the name is empty or contains `=`,
+    ///   or either string contains a NUL character
     ///
     /// # Examples
     /// ```
@@ -175,21 +244,28 @@ impl OsBif {
     /// assert_eq!(OsBif::getenv("VAR2"), Some("value2".to_string()));
     /// ```
     pub fn putenv(key: &str, value: &str) -> Result<(), OsError> {
-        env::set_var(key, value);
+        check_env_name(key)?;
+        if value.contains('\0') {
+            return Err(OsError::InvalidArgument(format!(
+                "Value of environment variable {} contains NUL",
+                key
+            )));
+        }
+        env_cache().write().unwrap().insert(key.to_string(), value.to_string());
         Ok(())
     }
 
     /// Unset an environment variable
     ///
     /// Equivalent to `os:unsetenv/1` in Erlang.
-    /// Removes an environment variable from the current process.
+    /// Removes an environment variable from the environment cache.
     ///
     /// # Arguments
     /// * `key` - The environment variable name to remove
     ///
     /// # Returns
     /// * `Ok(())` if successful
-    /// * `Err(error)` if unsetting failed
+    /// * `Err(OsError::InvalidArgument)` if the name is not a valid variable name
     ///
     /// # Examples
     /// ```
@@ -212,7 +288,8 @@ impl OsBif {
     /// assert_eq!(OsBif::getenv("VAR2"), None);
     /// ```
     pub fn unsetenv(key: &str) -> Result<(), OsError> {
-        env::remove_var(key);
+        check_env_name(key)?;
+        env_cache().write().unwrap().remove(key);
         Ok(())
     }
 
@@ -257,23 +334,113 @@ impl OsBif {
         (megaseconds, seconds, microseconds)
     }
 
+    /// Get the OS system time in the given unit
+    ///
+    /// Equivalent to `os:system_time/1` in Erlang. Unlike Erlang system time,
+    /// this follows the OS clock without time offset corrections.
+    pub fn system_time(unit: TimeUnit) -> i64 {
+        get_clock().os_system_time(unit)
+    }
+
+    /// Get the OS performance counter
+    ///
+    /// Equivalent to `os:perf_counter/0` in Erlang. The counter is in
+    /// `perf_counter` units from an arbitrary starting point, and is only
+    /// meaningful for measuring elapsed time.
+    pub fn perf_counter() -> i64 {
+        let start = PERF_COUNTER_START.get_or_init(Instant::now);
+        i64::try_from(start.elapsed().as_nanos()).unwrap_or(i64::MAX)
+    }
+
+    /// Get the OS performance counter in the given unit
+    ///
+    /// Equivalent to `os:perf_counter/1` in Erlang.
+    pub fn perf_counter_in(unit: TimeUnit) -> i64 {
+        convert_time_unit(Self::perf_counter(), TimeUnit::PerfCounter, unit)
+    }
+
+    /// Run a command in a shell and return its output
+    ///
+    /// Equivalent to `os:cmd/1` in Erlang.
+    ///
+    /// # Arguments
+    /// * `command` - Command line passed to `/bin/sh -c`
+    ///
+    /// # Returns
+    /// * `Ok(output)` - Everything the command wrote to stdout and stderr
+    /// * `Err(OsError)` - The shell could not be started
+    pub fn cmd(command: &str) -> Result<Vec<u8>, OsError> {
+        Self::cmd_with_max_size(command, None)
+    }
+
+    /// Run a command in a shell, returning at most `max_size` bytes of output
+    ///
+    /// Equivalent to `os:cmd/2` with the `max_size` option in Erlang. The
+    /// command runs through the spawn driver with the cached environment,
+    /// and its stderr is merged into its stdout. Once `max_size` bytes have
+    /// been read the output is returned and dropping the port kills the
+    /// command if it is still running.
+    ///
+    /// # Arguments
+    /// * `command` - Command line passed to `/bin/sh -c`
+    /// * `max_size` - Maximum number of output bytes, or `None` for no limit
+    #[cfg(unix)]
+    pub fn cmd_with_max_size(command: &str, max_size: Option<usize>) -> Result<Vec<u8>, OsError> {
+        let options = SpawnOptions {
+            args: vec!["-c".to_string(), command.to_string()],
+            env: Self::env(),
+            clear_env: true,
+            binary: true,
+            exit_status: true,
+            stderr_to_stdout: true,
+            ..SpawnOptions::default()
+        };
+        let mut port = SpawnPort::spawn(CMD_SHELL, &options)
+            .map_err(|err| OsError::SystemError(err.to_string()))?;
+        port.close_output();
+
+        let mut output = Vec::new();
+        loop {
+            match port.recv_timeout(CMD_POLL_INTERVAL) {
+                Some(PortMessage::Data(data)) => {
+                    output.extend_from_slice(data.bytes());
+                    if let Some(max_size) = max_size {
+                        if output.len() >= max_size {
+                            output.truncate(max_size);
+                            break;
+                        }
+                    }
+                }
+                Some(PortMessage::ExitStatus(_)) => break,
+                Some(PortMessage::Line { .. }) | None => {}
+            }
+        }
+        Ok(output)
+    }
+
+    /// Run a command in a shell, returning at most `max_size` bytes of output
+    ///
+    /// The spawn driver is only available on Unix.
+    #[cfg(not(unix))]
+    pub fn cmd_with_max_size(_command: &str, _max_size: Option<usize>) -> Result<Vec<u8>, OsError> {
+        Err(OsError::NotSupported("os:cmd requires the Unix spawn driver".to_string()))
+    }
+
     /// Set signal handling
     ///
     /// Equivalent to `os:set_signal/2` in Erlang.
     /// Sets how a signal should be handled: ignore, default, or handle.
+    /// Handled signals are delivered to `erl_signal_server` as
+    /// `{notify, Signal}` by the global signal service.
     ///
     /// # Arguments
-    /// * `signal` - The signal name (e.g., "SIGINT", "SIGTERM")
+    /// * `signal` - The signal name, in either case (e.g., "sigint", "SIGTERM")
     /// * `action` - The action to take: "ignore", "default", or "handle"
     ///
     /// # Returns
     /// * `Ok(())` if successful
-    /// * `Err(OsError)` if the signal or action is invalid
-    ///
-    /// # Note
-    /// This is a simplified implementation. Full signal handling would require
-    /// platform-specific code. For now, this validates inputs and returns success.
-    /// Actual signal handling should be implemented in the adapters layer.
+    /// * `Err(OsError::InvalidArgument)` if the signal or action is invalid
+    /// * `Err(OsError::SystemError)` if the OS rejected the disposition
     ///
     /// # Example
     /// ```
@@ -281,25 +448,35 @@ impl OsBif {
     /// OsBif::set_signal("SIGINT", "ignore").unwrap();
     /// ```
     pub fn set_signal(signal: &str, action: &str) -> Result<(), OsError> {
-        // Validate action
-        match action {
-            "ignore" | "default" | "handle" => {
-                // Validate signal name format (should be an atom-like string)
-                if signal.is_empty() {
-                    return Err(OsError::InvalidArgument(
-                        "Signal name cannot be empty".to_string(),
-                    ));
-                }
-                // In a full implementation, we would set the signal handler here
-                // For now, we just validate and return success
-                Ok(())
-            }
-            _ => Err(OsError::InvalidArgument(format!(
+        let action = SignalAction::from_name(action).ok_or_else(|| {
+            OsError::InvalidArgument(format!(
                 "Invalid action: {}. Must be 'ignore', 'default', or 'handle'",
                 action
-            ))),
+            ))
+        })?;
+        if signal.is_empty() {
+            return Err(OsError::InvalidArgument(
+                "Signal name cannot be empty".to_string(),
+            ));
         }
+        let signal = Signal::from_name(signal)
+            .ok_or_else(|| OsError::InvalidArgument(format!("Unknown signal: {}", signal)))?;
+        get_global_signal_service()
+            .set_signal(signal, action)
+            .map_err(|err| OsError::SystemError(format!("Cannot set {} to {:?}: {}", signal.name(), action, err)))?;
+        Ok(())
+    }
+}
+
+/// Check an environment variable name for putenv/unsetenv
+fn check_env_name(key: &str) -> Result<(), OsError> {
+    if key.is_empty() || key.contains('=') || key.contains('\0') {
+        return Err(OsError::InvalidArgument(format!(
+            "Invalid environment variable name: {:?}",
+            key
+        )));
     }
+    Ok(())
 }
 
 /// Error type for OS BIF operations
@@ -353,13 +530,13 @@ mod tests {
     #[test]
     fn test_getenv_existing() {
         // Set a test environment variable
-        env::set_var("TEST_OS_BIF_VAR", "test_value");
+        OsBif::putenv("TEST_OS_BIF_VAR", "test_value").unwrap();
         
         let value = OsBif::getenv("TEST_OS_BIF_VAR");
         assert_eq!(value, Some("test_value".to_string()));
         
         // Cleanup
-        env::remove_var("TEST_OS_BIF_VAR");
+        OsBif::unsetenv("TEST_OS_BIF_VAR").unwrap();
     }
 
     #[test]
@@ -374,25 +551,26 @@ mod tests {
         let result = OsBif::putenv("TEST_OS_BIF_PUTENV", "test_value");
         assert!(result.is_ok());
         
-        // Verify it was set
-        let value = env::var("TEST_OS_BIF_PUTENV");
-        assert_eq!(value, Ok("test_value".to_string()));
+        // Verify it was set in the cache, leaving the OS environment alone
+        let value = OsBif::getenv("TEST_OS_BIF_PUTENV");
+        assert_eq!(value, Some("test_value".to_string()));
+        assert!(env::var("TEST_OS_BIF_PUTENV").is_err());
         
         // Cleanup
-        env::remove_var("TEST_OS_BIF_PUTENV");
+        OsBif::unsetenv("TEST_OS_BIF_PUTENV").unwrap();
     }
 
     #[test]
     fn test_unsetenv() {
         // Set a variable first
-        env::set_var("TEST_OS_BIF_UNSETENV", "test_value");
+        OsBif::putenv("TEST_OS_BIF_UNSETENV", "test_value").unwrap();
         
         let result = OsBif::unsetenv("TEST_OS_BIF_UNSETENV");
         assert!(result.is_ok());
         
         // Verify it was removed
-        let value = env::var("TEST_OS_BIF_UNSETENV");
-        assert!(value.is_err());
+        let value = OsBif::getenv("TEST_OS_BIF_UNSETENV");
+        assert!(value.is_none());
     }
 
     #[test]
@@ -485,7 +663,7 @@ mod tests {
         assert_eq!(value, Some("updated".to_string()));
         
         // Cleanup
-        env::remove_var("TEST_OS_BIF_OVERWRITE");
+        OsBif::unsetenv("TEST_OS_BIF_OVERWRITE").unwrap();
     }
 
     #[test]
@@ -499,5 +677,65 @@ mod tests {
         let time2 = m2 * 1_000_000 + s2;
         assert!(time2 >= time1 || (time2 == time1 && u2 >= u1));
     }
-}
 
+    #[test]
+    fn test_putenv_invalid_name() {
+        assert!(matches!(OsBif::putenv("", "value"), Err(OsError::InvalidArgument(_))));
+        assert!(matches!(OsBif::putenv("A=B", "value"), Err(OsError::InvalidArgument(_))));
+        assert!(matches!(OsBif::putenv("TEST_OS_BIF_NUL", "a\0b"), Err(OsError::InvalidArgument(_))));
+        assert!(matches!(OsBif::unsetenv("A=B"), Err(OsError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn test_env_cache_concurrent() {
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                std::thread::spawn(move || {
+                    let key = format!("TEST_OS_BIF_THREAD_{}", t);
+                    for i in 0..100 {
+                        OsBif::putenv(&key, &i.to_string()).unwrap();
+                        assert_eq!(OsBif::getenv(&key), Some(i.to_string()));
+                    }
+                    OsBif::unsetenv(&key).unwrap();
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_perf_counter() {
+        let t1 = OsBif::perf_counter();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let t2 = OsBif::perf_counter();
+        assert!(t2 - t1 >= 2_000_000);
+        assert!(OsBif::perf_counter_in(TimeUnit::Second) <= OsBif::perf_counter() / 1_000_000_000 + 1);
+        assert!(OsBif::system_time(TimeUnit::Second) > 0);
+        assert_eq!(OsBif::pid(), process::id());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cmd() {
+        OsBif::putenv("TEST_OS_BIF_CMD", "cached").unwrap();
+        assert_eq!(OsBif::cmd("echo $TEST_OS_BIF_CMD; echo err 1>&2").unwrap(), b"cached\nerr\n".to_vec());
+        OsBif::unsetenv("TEST_OS_BIF_CMD").unwrap();
+        assert_eq!(OsBif::cmd("echo \"[$TEST_OS_BIF_CMD]\"").unwrap(), b"[]\n".to_vec());
+
+        let output = OsBif::cmd_with_max_size("yes", Some(10)).unwrap();
+        assert_eq!(output, b"y\ny\ny\ny\ny\n".to_vec());
+    }
+
+    #[test]
+    fn test_set_signal_service() {
+        OsBif::set_signal("sigusr2", "handle").unwrap();
+        assert_eq!(get_global_signal_service().action(Signal::Sigusr2), SignalAction::Handle);
+        get_global_signal_service().notify(Signal::Sigusr2);
+        assert!(get_global_signal_service().take_pending().contains(&Signal::Sigusr2));
+        OsBif::set_signal("SIGUSR2", "default").unwrap();
+        assert_eq!(get_global_signal_service().action(Signal::Sigusr2), SignalAction::Default);
+        assert!(matches!(OsBif::set_signal("sigkill", "ignore"), Err(OsError::InvalidArgument(_))));
+    }
+}
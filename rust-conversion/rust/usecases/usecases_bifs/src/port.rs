@@ -0,0 +1,528 @@
+//! Port Built-in Functions
+//!
+//! Provides the port BIFs that reach port drivers through the driver API:
+//! - `erlang:port_command/2,3`
+//! - `erlang:port_control/3`
+//! - `erlang:port_call/3`
+//! - `erlang:ports/0`
+//!
+//! Ports are looked up in the global port table of `infrastructure_driver_api`
+//! and requests are dispatched to the callbacks of the driver entry the port
+//! was opened on.
+//!
+//! Once the schedulers run, `port_command` does not call the driver itself:
+//! the data is queued as a port task and the scheduler owning the port
+//! executes it with reductions. Before that, it is written directly.
+//!
+//! ## Busy ports
+//!
+//! A process sending to a busy port is suspended on the port and
+//! `port_command` returns [`PortError::Suspended`]; the call is reissued
+//! once the process is resumed. The scheduler resumes it when `driver_deq`
+//! drains the driver queue or the driver clears the busy state. The `force` option
+//! sends regardless, and `nosuspend` returns `false` instead of suspending.
+//!
+//! ## Call data
+//!
+//! `port_call/3` passes its data to the driver's `call` callback as bytes
+//! and returns the reply as a binary; encoding to and decoding from the
+//! external term format is left to the caller.
+//!
+//! ## External format
+//!
+//! A port identifier is encoded with the name and creation of the local
+//! node, as `NEW_PORT_EXT` or, once its serial no longer fits in 28 bits,
+//! `V4_PORT_EXT`. Decoding only yields a port when the node name and
+//! creation are those of the local node; ports of other nodes and of
+//! earlier incarnations of this node are rejected.
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ *
+ * Creation productivity increased for code in this file by using AALang and GAB.
+ * See https://github.com/yenrab/AALang-Gab
+ */
+
+use crate::op::ErlangTerm;
+use crate::unique::UniqueBif;
+use entities_process::{BusyDestination, ProcessId};
+use infrastructure_code_loading::decode_port::decode_port;
+use infrastructure_code_loading::encode_port::{encode_port, ErlangPort};
+use infrastructure_driver_api::{get_global_port_table, DriverError, DriverPort};
+use infrastructure_utilities::process_table::get_global_process_table;
+use infrastructure_utilities::statistics::get_global_statistics;
+use usecases_scheduling::{erts_schedule_port_task, erts_schedulers_running, PortTask, PortTaskType};
+use std::sync::Arc;
+
+/// Error type for port BIF operations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortError {
+    /// Bad argument (e.g., closed port, invalid data, operation rejected by the driver)
+    BadArgument(String),
+    /// Operation not supported by the driver
+    NotSupported(String),
+    /// The calling process was suspended on a busy port; reissue the call when resumed
+    Suspended(u64),
+}
+
+impl std::fmt::Display for PortError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PortError::BadArgument(msg) => write!(f, "Bad argument: {}", msg),
+            PortError::NotSupported(msg) => write!(f, "Not supported: {}", msg),
+            PortError::Suspended(port) => write!(f, "Suspended on busy port #Port<0.{}>", port),
+        }
+    }
+}
+
+impl std::error::Error for PortError {}
+
+impl From<DriverError> for PortError {
+    fn from(err: DriverError) -> Self {
+        match err {
+            DriverError::NotSupported => PortError::NotSupported(err.to_string()),
+            other => PortError::BadArgument(other.to_string()),
+        }
+    }
+}
+
+/// Port BIF operations
+pub struct PortBif;
+
+impl PortBif {
+    /// Send data to a port (port_command/2)
+    ///
+    /// Equivalent to `port_command/3` with no options.
+    ///
+    /// # Arguments
+    /// * `caller` - Calling process, suspended if the port is busy
+    /// * `port` - Port identifier
+    /// * `data` - I/O data to send
+    ///
+    /// # Returns
+    /// * `Ok(ErlangTerm::Atom("true"))` - Data was sent
+    /// * `Err(PortError::Suspended)` - The caller was suspended on the busy port
+    /// * `Err(PortError)` - Bad port or data
+    pub fn port_command_2(caller: ProcessId, port: &ErlangTerm, data: &ErlangTerm) -> Result<ErlangTerm, PortError> {
+        Self::port_command_3(caller, port, data, &ErlangTerm::Nil)
+    }
+
+    /// Send data to a port (port_command/3)
+    ///
+    /// # Arguments
+    /// * `caller` - Calling process, suspended if the port is busy
+    /// * `port` - Port identifier
+    /// * `data` - I/O data to send
+    /// * `options` - List of `force` and `nosuspend`
+    ///
+    /// # Returns
+    /// * `Ok(ErlangTerm::Atom("true"))` - Data was sent
+    /// * `Ok(ErlangTerm::Atom("false"))` - The port was busy and `nosuspend` was given
+    /// * `Err(PortError::Suspended)` - The caller was suspended on the busy port
+    /// * `Err(PortError)` - Bad port, data or options
+    pub fn port_command_3(
+        caller: ProcessId,
+        port: &ErlangTerm,
+        data: &ErlangTerm,
+        options: &ErlangTerm,
+    ) -> Result<ErlangTerm, PortError> {
+        let port = lookup_port(port)?;
+        let bytes = iodata_to_bytes(data)?;
+        let (force, nosuspend) = parse_command_options(options)?;
+
+        if !force && port.is_busy() {
+            if nosuspend {
+                return Ok(ErlangTerm::Atom("false".to_string()));
+            }
+            if port.suspend_sender(caller) {
+                // Resumed by the scheduler once the port is no longer busy
+                if let Some(process) = get_global_process_table().lookup(caller) {
+                    process.suspend_on_busy(BusyDestination::Port(port.id()));
+                }
+                return Err(PortError::Suspended(port.id()));
+            }
+        }
+
+        get_global_statistics().add_bytes_out(bytes.len() as u64);
+        if erts_schedulers_running() {
+            erts_schedule_port_task(port.id(), PortTask::with_data(PortTaskType::Command, bytes))
+                .map_err(|err| PortError::BadArgument(err.to_string()))?;
+        } else {
+            port.output(&bytes)?;
+        }
+        Ok(ErlangTerm::Atom("true".to_string()))
+    }
+
+    /// Perform a synchronous control operation on a port (port_control/3)
+    ///
+    /// # Arguments
+    /// * `port` - Port identifier
+    /// * `operation` - Integer passed to the driver's `control` callback
+    /// * `data` - I/O data passed to the driver
+    ///
+    /// # Returns
+    /// * `Ok(ErlangTerm::List)` - Reply bytes from the driver
+    /// * `Err(PortError)` - Bad arguments, or the driver rejected the operation
+    pub fn port_control_3(port: &ErlangTerm, operation: &ErlangTerm, data: &ErlangTerm) -> Result<ErlangTerm, PortError> {
+        let port = lookup_port(port)?;
+        let command = command_number(operation)?;
+        let bytes = iodata_to_bytes(data)?;
+        let reply = port.control(command, &bytes)?;
+        count_io(&bytes, &reply);
+        Ok(ErlangTerm::List(
+            reply.into_iter().map(|b| ErlangTerm::Integer(b as i64)).collect(),
+        ))
+    }
+
+    /// Perform a synchronous call to a port (port_call/3)
+    ///
+    /// # Arguments
+    /// * `port` - Port identifier
+    /// * `operation` - Integer passed to the driver's `call` callback
+    /// * `data` - I/O data passed to the driver
+    ///
+    /// # Returns
+    /// * `Ok(ErlangTerm::Binary)` - Reply from the driver
+    /// * `Err(PortError)` - Bad arguments, or the driver rejected the call
+    pub fn port_call_3(port: &ErlangTerm, operation: &ErlangTerm, data: &ErlangTerm) -> Result<ErlangTerm, PortError> {
+        let port = lookup_port(port)?;
+        let command = command_number(operation)?;
+        let bytes = iodata_to_bytes(data)?;
+        let reply = port.call(command, &bytes)?;
+        count_io(&bytes, &reply);
+        Ok(ErlangTerm::Binary(reply))
+    }
+
+    /// List the open ports (ports/0)
+    ///
+    /// # Returns
+    /// List of port identifiers, in table order
+    pub fn ports_0() -> ErlangTerm {
+        ErlangTerm::List(
+            get_global_port_table()
+                .list()
+                .into_iter()
+                .map(ErlangTerm::Port)
+                .collect(),
+        )
+    }
+
+    /// Convert a port identifier to its external (EI) representation
+    ///
+    /// The port is given the name and creation of the local node.
+    pub fn port_to_external(port: &ErlangTerm) -> Result<ErlangPort, PortError> {
+        match port {
+            ErlangTerm::Port(id) => {
+                let (node, creation) = UniqueBif::local_node();
+                Ok(ErlangPort { node, id: *id, creation })
+            }
+            _ => Err(PortError::BadArgument("not a port".to_string())),
+        }
+    }
+
+    /// Convert an external (EI) port to a port identifier
+    ///
+    /// # Errors
+    /// Returns `PortError::BadArgument` if the port belongs to another node
+    /// or to an earlier incarnation of the local node.
+    pub fn port_from_external(external: &ErlangPort) -> Result<ErlangTerm, PortError> {
+        let (node, creation) = UniqueBif::local_node();
+        if external.node != node || external.creation != creation {
+            return Err(PortError::BadArgument(format!(
+                "port {} belongs to {} (creation {})",
+                external.id, external.node, external.creation
+            )));
+        }
+        Ok(ErlangTerm::Port(external.id))
+    }
+
+    /// Encode a port identifier as `NEW_PORT_EXT` or `V4_PORT_EXT`
+    ///
+    /// # Arguments
+    /// * `port` - Port identifier
+    /// * `buf` - Optional buffer to write to (None for size calculation)
+    /// * `index` - Current index in buffer
+    pub fn encode_port(port: &ErlangTerm, buf: &mut Option<&mut [u8]>, index: &mut usize) -> Result<(), PortError> {
+        let external = Self::port_to_external(port)?;
+        encode_port(buf, index, &external).map_err(|err| PortError::BadArgument(format!("{:?}", err)))
+    }
+
+    /// Decode a local port identifier encoded in any of the external port formats
+    ///
+    /// # Arguments
+    /// * `buf` - Buffer containing EI-encoded data
+    /// * `index` - Current index in buffer
+    pub fn decode_port(buf: &[u8], index: &mut usize) -> Result<ErlangTerm, PortError> {
+        let external = decode_port(buf, index).map_err(|err| PortError::BadArgument(format!("{:?}", err)))?;
+        Self::port_from_external(&external)
+    }
+}
+
+/// Look up an open port in the global port table
+fn lookup_port(port: &ErlangTerm) -> Result<Arc<DriverPort>, PortError> {
+    match port {
+        ErlangTerm::Port(id) => get_global_port_table()
+            .lookup(*id)
+            .filter(|port| !port.is_closed())
+            .ok_or_else(|| PortError::BadArgument(format!("port {} is not open", id))),
+        _ => Err(PortError::BadArgument("not a port".to_string())),
+    }
+}
+
+/// Count the bytes of a synchronous port operation in `statistics(io)`
+fn count_io(sent: &[u8], reply: &[u8]) {
+    let statistics = get_global_statistics();
+    statistics.add_bytes_out(sent.len() as u64);
+    statistics.add_bytes_in(reply.len() as u64);
+}
+
+/// Convert the operation argument of port_control/port_call
+fn command_number(operation: &ErlangTerm) -> Result<u32, PortError> {
+    match operation {
+        ErlangTerm::Integer(n) => u32::try_from(*n)
+            .map_err(|_| PortError::BadArgument(format!("operation {} out of range", n))),
+        _ => Err(PortError::BadArgument("operation must be an integer".to_string())),
+    }
+}
+
+/// Parse the options of port_command/3
+///
+/// # Returns
+/// `(force, nosuspend)`
+fn parse_command_options(options: &ErlangTerm) -> Result<(bool, bool), PortError> {
+    let items = match options {
+        ErlangTerm::Nil => return Ok((false, false)),
+        ErlangTerm::List(items) => items,
+        _ => return Err(PortError::BadArgument("options must be a list".to_string())),
+    };
+    let mut force = false;
+    let mut nosuspend = false;
+    for item in items {
+        match item {
+            ErlangTerm::Atom(name) if name == "force" => force = true,
+            ErlangTerm::Atom(name) if name == "nosuspend" => nosuspend = true,
+            other => return Err(PortError::BadArgument(format!("invalid option {:?}", other))),
+        }
+    }
+    Ok((force, nosuspend))
+}
+
+/// Flatten I/O data (binaries, bytes and nested lists of them) into bytes
+fn iodata_to_bytes(data: &ErlangTerm) -> Result<Vec<u8>, PortError> {
+    fn collect(term: &ErlangTerm, top: bool, out: &mut Vec<u8>) -> Result<(), PortError> {
+        match term {
+            ErlangTerm::Binary(bytes) => out.extend_from_slice(bytes),
+            ErlangTerm::Bitstring(bytes, bits) if bits % 8 == 0 => out.extend_from_slice(&bytes[..bits / 8]),
+            ErlangTerm::Nil => {}
+            ErlangTerm::List(items) => {
+                for item in items {
+                    collect(item, false, out)?;
+                }
+            }
+            ErlangTerm::Integer(b) if !top && (0..=255).contains(b) => out.push(*b as u8),
+            _ => return Err(PortError::BadArgument("not iodata".to_string())),
+        }
+        Ok(())
+    }
+    let mut out = Vec::new();
+    collect(data, true, &mut out)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use infrastructure_driver_api::{get_global_driver_registry, DriverEntry};
+
+    struct TestDriver;
+
+    impl DriverEntry for TestDriver {
+        fn driver_name(&self) -> &str {
+            "port_bif_test_drv"
+        }
+
+        fn output(&self, port: &DriverPort, data: &[u8]) -> Result<(), DriverError> {
+            port.driver_enq(data);
+            Ok(())
+        }
+
+        fn control(&self, port: &DriverPort, command: u32, data: &[u8]) -> Result<Vec<u8>, DriverError> {
+            match command {
+                1 => Ok(data.iter().rev().copied().collect()),
+                2 => Ok(vec![port.driver_sizeq() as u8]),
+                _ => Err(DriverError::Failed("unknown command".to_string())),
+            }
+        }
+    }
+
+    fn open_test_port() -> Arc<DriverPort> {
+        let _ = get_global_driver_registry().add_driver_entry(Arc::new(TestDriver));
+        get_global_port_table()
+            .open_port(get_global_driver_registry(), "port_bif_test_drv")
+            .unwrap()
+    }
+
+    fn bytes(data: &[u8]) -> ErlangTerm {
+        ErlangTerm::List(data.iter().map(|&b| ErlangTerm::Integer(b as i64)).collect())
+    }
+
+    #[test]
+    fn test_iodata_to_bytes() {
+        let data = ErlangTerm::List(vec![
+            ErlangTerm::Integer(b'a' as i64),
+            ErlangTerm::Binary(b"bc".to_vec()),
+            ErlangTerm::List(vec![ErlangTerm::Integer(b'd' as i64)]),
+            ErlangTerm::Nil,
+        ]);
+        assert_eq!(iodata_to_bytes(&data).unwrap(), b"abcd".to_vec());
+        assert!(iodata_to_bytes(&ErlangTerm::Integer(1)).is_err());
+        assert!(iodata_to_bytes(&ErlangTerm::List(vec![ErlangTerm::Integer(256)])).is_err());
+    }
+
+    #[test]
+    fn test_port_control() {
+        let port = open_test_port();
+        let port_term = ErlangTerm::Port(port.id());
+
+        let (input, output) = get_global_statistics().io();
+        let reply = PortBif::port_control_3(&port_term, &ErlangTerm::Integer(1), &bytes(b"abc")).unwrap();
+        assert_eq!(reply, bytes(b"cba"));
+        let (input_after, output_after) = get_global_statistics().io();
+        assert!(input_after >= input + 3 && output_after >= output + 3);
+
+        let result = PortBif::port_control_3(&port_term, &ErlangTerm::Integer(9), &ErlangTerm::Nil);
+        assert!(matches!(result, Err(PortError::BadArgument(_))));
+        let result = PortBif::port_control_3(&port_term, &ErlangTerm::Integer(-1), &ErlangTerm::Nil);
+        assert!(matches!(result, Err(PortError::BadArgument(_))));
+        let result = PortBif::port_call_3(&port_term, &ErlangTerm::Integer(1), &ErlangTerm::Nil);
+        assert!(matches!(result, Err(PortError::NotSupported(_))));
+    }
+
+    #[test]
+    fn test_port_command_busy_port() {
+        let port = open_test_port();
+        port.set_busy_limits(4, 0);
+        let port_term = ErlangTerm::Port(port.id());
+        let data = ErlangTerm::Binary(b"data".to_vec());
+        let t = ErlangTerm::Atom("true".to_string());
+
+        assert_eq!(PortBif::port_command_2(7, &port_term, &data), Ok(t.clone()));
+        assert!(port.is_busy());
+
+        let nosuspend = ErlangTerm::List(vec![ErlangTerm::Atom("nosuspend".to_string())]);
+        assert_eq!(
+            PortBif::port_command_3(7, &port_term, &data, &nosuspend),
+            Ok(ErlangTerm::Atom("false".to_string()))
+        );
+        assert_eq!(PortBif::port_command_2(7, &port_term, &data), Err(PortError::Suspended(port.id())));
+        assert_eq!(port.suspended_senders(), vec![7]);
+
+        let force = ErlangTerm::List(vec![ErlangTerm::Atom("force".to_string())]);
+        assert_eq!(PortBif::port_command_3(8, &port_term, &data, &force), Ok(t.clone()));
+        assert_eq!(port.driver_sizeq(), 8);
+
+        port.driver_deq(8);
+        assert_eq!(port.take_resumed(), vec![7]);
+        assert_eq!(PortBif::port_command_2(7, &port_term, &data), Ok(t));
+    }
+
+    #[test]
+    fn test_busy_port_suspends_and_resumes_caller() {
+        let port = open_test_port();
+        let port_term = ErlangTerm::Port(port.id());
+        let process = Arc::new(entities_process::Process::new(867001));
+        get_global_process_table().insert(867001, Arc::clone(&process));
+        port.set_busy_port(true);
+
+        let data = ErlangTerm::Binary(b"x".to_vec());
+        assert_eq!(PortBif::port_command_2(867001, &port_term, &data), Err(PortError::Suspended(port.id())));
+        assert!(process.is_suspended());
+        assert_eq!(process.busy_destination(), Some(BusyDestination::Port(port.id())));
+
+        port.set_busy_port(false);
+        let runq = usecases_scheduling::RunQueue::new(0, 0);
+        let resumed = get_global_port_table().take_resumed();
+        assert!(resumed.contains(&867001));
+        assert_eq!(usecases_scheduling::resume_busy_senders(&resumed, &runq), 1);
+        assert!(!process.is_suspended());
+
+        get_global_process_table().remove(867001);
+        get_global_port_table().close_port(port.id());
+    }
+
+    #[test]
+    fn test_port_command_bad_arguments() {
+        let port = open_test_port();
+        let port_term = ErlangTerm::Port(port.id());
+        let data = ErlangTerm::Binary(Vec::new());
+
+        let result = PortBif::port_command_2(1, &ErlangTerm::Pid(1), &data);
+        assert!(matches!(result, Err(PortError::BadArgument(_))));
+        let result = PortBif::port_command_3(1, &port_term, &data, &ErlangTerm::List(vec![ErlangTerm::Atom("bad".to_string())]));
+        assert!(matches!(result, Err(PortError::BadArgument(_))));
+
+        get_global_port_table().close_port(port.id());
+        let result = PortBif::port_command_2(1, &port_term, &data);
+        assert!(matches!(result, Err(PortError::BadArgument(_))));
+    }
+
+    #[test]
+    fn test_ports_0() {
+        let port = open_test_port();
+        let ErlangTerm::List(ports) = PortBif::ports_0() else {
+            panic!("ports/0 must return a list");
+        };
+        assert!(ports.contains(&ErlangTerm::Port(port.id())));
+
+        get_global_port_table().close_port(port.id());
+        let ErlangTerm::List(ports) = PortBif::ports_0() else {
+            panic!("ports/0 must return a list");
+        };
+        assert!(!ports.contains(&ErlangTerm::Port(port.id())));
+    }
+
+    #[test]
+    fn test_port_external_format() {
+        use infrastructure_code_loading::constants::{ERL_NEW_PORT_EXT, ERL_V4_PORT_EXT};
+        use infrastructure_driver_api::make_port_id;
+
+        for (id, tag) in [(5, ERL_NEW_PORT_EXT), (make_port_id(5, 3), ERL_V4_PORT_EXT)] {
+            let port = ErlangTerm::Port(id);
+            let mut size = 0;
+            PortBif::encode_port(&port, &mut None, &mut size).unwrap();
+            let mut buf = vec![0u8; size];
+            let mut index = 0;
+            PortBif::encode_port(&port, &mut Some(&mut buf), &mut index).unwrap();
+            assert_eq!(index, size);
+            assert_eq!(buf[0], tag);
+
+            let mut index = 0;
+            assert_eq!(PortBif::decode_port(&buf, &mut index), Ok(port));
+            assert_eq!(index, size);
+        }
+
+        let foreign = ErlangPort { node: "other@host".to_string(), id: 5, creation: 1 };
+        assert!(matches!(PortBif::port_from_external(&foreign), Err(PortError::BadArgument(_))));
+        assert!(PortBif::encode_port(&ErlangTerm::Pid(1), &mut None, &mut 0).is_err());
+    }
+}
@@ -5,6 +5,13 @@
 //! This module implements regex operations using the Rust `regex` crate.
 //! Note: The C code uses PCRE2, but we use Rust's regex crate for safe Rust implementation.
 //! For internal usecases, this provides equivalent functionality.
+//!
+//! The `re` surface is covered by [`RegexBif::compile_with`],
+//! [`RegexBif::run_with`], [`RegexBif::replace`] and [`RegexBif::split`].
+//! Where PCRE semantics cannot be reproduced the divergence is reported as
+//! [`RegexError::Unsupported`]: patterns using backreferences, lookaround,
+//! atomic groups, possessive quantifiers, recursion or verbs, and the
+//! `dollar_endonly`, `firstline` and `no_auto_capture` options.
 
 /*
  * %CopyrightBegin%
@@ -81,12 +88,7 @@ impl RegexBif {
         builder.multi_line(multiline);
         builder.dot_matches_new_line(dot_matches_newline);
         
-        builder
-            .build()
-            .map(|re| CompiledRegex {
-                regex: Arc::new(re),
-            })
-            .map_err(|e| RegexError::InvalidPattern(e.to_string()))
+        Self::build(&builder, pattern)
     }
 
     /// Compile a regular expression with default options
@@ -304,11 +306,289 @@ impl RegexBif {
 
         results
     }
+
+    /// Compile a regular expression with `re:compile/2` options
+    ///
+    /// PCRE features the `regex` crate does not provide, such as
+    /// backreferences in the pattern or lookaround, are reported as
+    /// [`RegexError::Unsupported`] rather than as an invalid pattern.
+    ///
+    /// # Arguments
+    /// * `pattern` - The regex pattern string
+    /// * `options` - Compile options
+    ///
+    /// # Returns
+    /// Compiled regex or error
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::regex::{CompileOption, RegexBif, RegexError};
+    ///
+    /// // Lazy quantifiers become greedy and vice versa
+    /// let re = RegexBif::compile_with(r"a+", &[CompileOption::Ungreedy]).unwrap();
+    /// assert_eq!(RegexBif::run(&re, "aaa", 0).unwrap().captures[0].text, "a");
+    ///
+    /// // Lookahead has no equivalent
+    /// let re = RegexBif::compile_with(r"foo(?=bar)", &[]);
+    /// assert_eq!(re.err(), Some(RegexError::Unsupported("lookahead".to_string())));
+    /// ```
+    pub fn compile_with(pattern: &str, options: &[CompileOption]) -> Result<CompiledRegex, RegexError> {
+        let mut builder = RegexBuilder::new(pattern);
+        let (mut anchored, mut unicode) = (false, false);
+        for option in options {
+            match option {
+                CompileOption::Caseless => builder.case_insensitive(true),
+                CompileOption::Multiline => builder.multi_line(true),
+                CompileOption::Dotall => builder.dot_matches_new_line(true),
+                CompileOption::Extended => builder.ignore_whitespace(true),
+                CompileOption::Ungreedy => builder.swap_greed(true),
+                CompileOption::Anchored => {
+                    anchored = true;
+                    &mut builder
+                }
+                CompileOption::Unicode => {
+                    unicode = true;
+                    &mut builder
+                }
+                CompileOption::DollarEndonly | CompileOption::Firstline | CompileOption::NoAutoCapture => {
+                    return Err(RegexError::Unsupported(option.name().to_string()));
+                }
+            };
+        }
+        let mut compiled = Self::build(&builder, pattern)?;
+        compiled.anchored = anchored;
+        compiled.unicode = unicode;
+        Ok(compiled)
+    }
+
+    /// Build a compiled regex, classifying build errors
+    ///
+    /// Unsupported PCRE features are checked first, since some of them,
+    /// like possessive quantifiers, parse with a different meaning.
+    fn build(builder: &RegexBuilder, pattern: &str) -> Result<CompiledRegex, RegexError> {
+        if let Some(feature) = unsupported_feature(pattern) {
+            return Err(RegexError::Unsupported(feature.to_string()));
+        }
+        builder
+            .build()
+            .map(|re| CompiledRegex {
+                regex: Arc::new(re),
+                anchored: false,
+                unicode: false,
+            })
+            .map_err(|e| RegexError::InvalidPattern(e.to_string()))
+    }
+
+    /// Run a regex with `re:run/3` options
+    ///
+    /// Unlike [`RegexBif::run`], the subject before the offset stays
+    /// visible to the pattern: `^` and `\b` see the whole subject, as with
+    /// PCRE's start offset. Offsets in the result are byte offsets into
+    /// `text`.
+    ///
+    /// With `global`, the search continues after each match; after an
+    /// empty match it resumes one character later.
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::regex::{CaptureType, CaptureValue, RegexBif, RunOption, RunResult, ValueSpec};
+    ///
+    /// let re = RegexBif::compile_simple(r"(?<key>\w+)=(?<value>\w+)").unwrap();
+    /// let options = [
+    ///     RunOption::Global,
+    ///     RunOption::Capture(ValueSpec::AllNames, CaptureType::Binary),
+    /// ];
+    /// let result = RegexBif::run_with(&re, "a=1 b=2", &options).unwrap();
+    /// let text = |s: &str| CaptureValue::Text(s.to_string());
+    /// assert_eq!(
+    ///     result,
+    ///     RunResult::Global(vec![vec![text("a"), text("1")], vec![text("b"), text("2")]])
+    /// );
+    /// ```
+    pub fn run_with(regex: &CompiledRegex, text: &str, options: &[RunOption]) -> Result<RunResult, RegexError> {
+        let run = RunSettings::new(regex, options);
+        let (spec, capture_type) = run.capture.clone().unwrap_or((ValueSpec::All, CaptureType::Index));
+        let slots = regex.slots(&spec)?;
+        let matches = Self::matches(regex, text, &run)?;
+        if matches.is_empty() {
+            return Ok(RunResult::NoMatch);
+        }
+        if spec == ValueSpec::None {
+            return Ok(RunResult::Match);
+        }
+        let mut captured = matches
+            .iter()
+            .map(|groups| slots.iter().map(|slot| capture_value(text, slot.and_then(|i| groups[i]), capture_type)).collect())
+            .collect::<Vec<Vec<CaptureValue>>>();
+        if run.global {
+            Ok(RunResult::Global(captured))
+        } else {
+            Ok(RunResult::Captured(captured.remove(0)))
+        }
+    }
+
+    /// Replace matches with a template (`re:replace/4`)
+    ///
+    /// In the template, `&` and `\0` insert the whole match, `\N`, `\gN`
+    /// and `\g{N}` insert group `N` (empty if it did not participate), and
+    /// `\&` and `\\` insert a literal `&` and `\`.
+    ///
+    /// # Arguments
+    /// * `options` - Run options; `Capture` is not allowed
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::regex::{RegexBif, RunOption};
+    ///
+    /// let re = RegexBif::compile_simple(r"(\w+)@(\w+)").unwrap();
+    /// let result = RegexBif::replace(&re, "me@home you@work", r"\2:\1", &[RunOption::Global]).unwrap();
+    /// assert_eq!(result, "home:me work:you");
+    ///
+    /// let re = RegexBif::compile_simple(r"b").unwrap();
+    /// assert_eq!(RegexBif::replace(&re, "abcb", "[&]", &[]).unwrap(), "a[b]cb");
+    /// ```
+    pub fn replace(
+        regex: &CompiledRegex,
+        text: &str,
+        replacement: &str,
+        options: &[RunOption],
+    ) -> Result<String, RegexError> {
+        let run = RunSettings::new(regex, options);
+        if run.capture.is_some() {
+            return Err(RegexError::InvalidOption("capture".to_string()));
+        }
+        let template = parse_template(replacement);
+        let mut result = String::with_capacity(text.len());
+        let mut last = 0;
+        for groups in Self::matches(regex, text, &run)? {
+            let (start, end) = groups[0].expect("group 0 is set in a match");
+            result.push_str(&text[last..start]);
+            for part in &template {
+                match part {
+                    TemplatePart::Literal(literal) => result.push_str(literal),
+                    TemplatePart::Group(i) => {
+                        if let Some(Some((start, end))) = groups.get(*i) {
+                            result.push_str(&text[*start..*end]);
+                        }
+                    }
+                }
+            }
+            last = end;
+        }
+        result.push_str(&text[last..]);
+        Ok(result)
+    }
+
+    /// Split text at matches (`re:split/3`)
+    ///
+    /// The text of captured groups is included between the parts, as in
+    /// Erlang. An empty match that would produce an empty part with only
+    /// empty groups does not split.
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::regex::{RegexBif, SplitOption};
+    ///
+    /// let re = RegexBif::compile_simple(r"[lg]").unwrap();
+    /// assert_eq!(RegexBif::split(&re, "Erlang", &[]).unwrap(), vec!["Er", "an", ""]);
+    /// assert_eq!(RegexBif::split(&re, "Erlang", &[SplitOption::Trim]).unwrap(), vec!["Er", "an"]);
+    /// assert_eq!(RegexBif::split(&re, "Erlang", &[SplitOption::Parts(2)]).unwrap(), vec!["Er", "ang"]);
+    /// ```
+    pub fn split(regex: &CompiledRegex, text: &str, options: &[SplitOption]) -> Result<Vec<String>, RegexError> {
+        Ok(Self::split_grouped(regex, text, options)?.into_iter().flatten().collect())
+    }
+
+    /// Split text at matches, grouping each part with the groups captured
+    /// by the match that ends it (`re:split/3` with `group`)
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::regex::RegexBif;
+    ///
+    /// let re = RegexBif::compile_simple(r"([lg])").unwrap();
+    /// assert_eq!(
+    ///     RegexBif::split_grouped(&re, "Erlang", &[]).unwrap(),
+    ///     vec![vec!["Er", "l"], vec!["an", "g"], vec![""]]
+    /// );
+    /// ```
+    pub fn split_grouped(
+        regex: &CompiledRegex,
+        text: &str,
+        options: &[SplitOption],
+    ) -> Result<Vec<Vec<String>>, RegexError> {
+        let (mut limit, mut trim) = (None, false);
+        for option in options {
+            match option {
+                SplitOption::Parts(0) => {
+                    limit = None;
+                    trim = true;
+                }
+                SplitOption::Parts(parts) => limit = Some(*parts),
+                SplitOption::Trim => trim = true,
+            }
+        }
+        let run = RunSettings::new(regex, &[RunOption::Global]);
+        let mut parts = Vec::new();
+        let mut last = 0;
+        for groups in Self::matches(regex, text, &run)? {
+            if limit.is_some_and(|limit| parts.len() + 1 >= limit) {
+                break;
+            }
+            let (start, end) = groups[0].expect("group 0 is set in a match");
+            let subs = groups[1..].iter().map(|group| group.map_or("", |(s, e)| &text[s..e]));
+            if start == last && start == end && subs.clone().all(str::is_empty) {
+                continue;
+            }
+            let mut part = vec![text[last..start].to_string()];
+            part.extend(subs.map(str::to_string));
+            parts.push(part);
+            last = end;
+        }
+        parts.push(vec![text[last..].to_string()]);
+        if trim {
+            while parts.last().is_some_and(|part| part.iter().all(String::is_empty)) {
+                parts.pop();
+            }
+        }
+        Ok(parts)
+    }
+
+    /// Find the matches selected by the run settings
+    ///
+    fn matches(regex: &CompiledRegex, text: &str, run: &RunSettings) -> Result<Vec<GroupRanges>, RegexError> {
+        if run.offset > text.len() || !text.is_char_boundary(run.offset) {
+            return Err(RegexError::InvalidOffset);
+        }
+        let mut matches = Vec::new();
+        let mut position = run.offset;
+        while let Some(captures) = regex.regex.captures_at(text, position) {
+            let whole = captures.get(0).expect("group 0 is set in a match");
+            if run.anchored && whole.start() != position {
+                break;
+            }
+            matches.push(captures.iter().map(|m| m.map(|m| (m.start(), m.end()))).collect());
+            if !run.global {
+                break;
+            }
+            position = whole.end();
+            if whole.is_empty() {
+                match text[position..].chars().next() {
+                    Some(c) => position += c.len_utf8(),
+                    None => break,
+                }
+            }
+        }
+        Ok(matches)
+    }
 }
 
 /// Compiled regular expression
 pub struct CompiledRegex {
     regex: Arc<Regex>,
+    /// Matches must start at the start offset
+    anchored: bool,
+    /// Compiled with the `unicode` option
+    unicode: bool,
 }
 
 impl CompiledRegex {
@@ -341,6 +621,55 @@ impl CompiledRegex {
     pub fn as_ref(&self) -> &Regex {
         self.regex.as_ref()
     }
+
+    /// Names of the named groups in alphabetical order
+    /// (`re:inspect(MP, namelist)`)
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.regex.capture_names().flatten().collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Number of capture groups, not counting the whole match
+    pub fn group_count(&self) -> usize {
+        self.regex.captures_len() - 1
+    }
+
+    /// Whether the regex was compiled with the `anchored` option
+    pub fn is_anchored(&self) -> bool {
+        self.anchored
+    }
+
+    /// Whether the regex was compiled with the `unicode` option
+    pub fn is_unicode(&self) -> bool {
+        self.unicode
+    }
+
+    /// Resolve a value spec to group numbers, `None` for groups that do
+    /// not exist
+    fn slots(&self, spec: &ValueSpec) -> Result<Vec<Option<usize>>, RegexError> {
+        let groups = self.regex.captures_len();
+        let index = |name: &str| {
+            self.regex
+                .capture_names()
+                .position(|group| group == Some(name))
+                .ok_or_else(|| RegexError::UnknownGroup(name.to_string()))
+        };
+        Ok(match spec {
+            ValueSpec::All => (0..groups).map(Some).collect(),
+            ValueSpec::AllButFirst => (1..groups).map(Some).collect(),
+            ValueSpec::First => vec![Some(0)],
+            ValueSpec::None => Vec::new(),
+            ValueSpec::AllNames => self.names().into_iter().map(|name| index(name).map(Some)).collect::<Result<_, _>>()?,
+            ValueSpec::List(refs) => refs
+                .iter()
+                .map(|group| match group {
+                    GroupRef::Index(i) => Ok((*i < groups).then_some(*i)),
+                    GroupRef::Name(name) => index(name).map(Some),
+                })
+                .collect::<Result<_, _>>()?,
+        })
+    }
 }
 
 /// Capture group information
@@ -363,6 +692,280 @@ pub struct MatchResult {
     pub captures: Vec<Capture>,
 }
 
+/// Compile option of `re:compile/2`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompileOption {
+    /// Match letters case-insensitively (`caseless`)
+    Caseless,
+    /// `^` and `$` also match at newlines (`multiline`)
+    Multiline,
+    /// `.` also matches newlines (`dotall`)
+    Dotall,
+    /// Ignore whitespace and `#` comments in the pattern (`extended`)
+    Extended,
+    /// The pattern and subject are Unicode (`unicode`)
+    ///
+    /// `\w`, `\d`, `\s` and `\b` always follow Unicode, as with PCRE's
+    /// `ucp`; the option is recorded for [`CompiledRegex::is_unicode`].
+    Unicode,
+    /// Invert the greediness of quantifiers (`ungreedy`)
+    Ungreedy,
+    /// Matches must start at the start offset (`anchored`)
+    Anchored,
+    /// `$` matches only at the end of the subject (`dollar_endonly`);
+    /// not supported
+    DollarEndonly,
+    /// Matches must start before the first newline (`firstline`); not
+    /// supported
+    Firstline,
+    /// Plain parentheses do not capture (`no_auto_capture`); not supported
+    NoAutoCapture,
+}
+
+impl CompileOption {
+    /// Atom name of the option
+    pub fn name(&self) -> &'static str {
+        match self {
+            CompileOption::Caseless => "caseless",
+            CompileOption::Multiline => "multiline",
+            CompileOption::Dotall => "dotall",
+            CompileOption::Extended => "extended",
+            CompileOption::Unicode => "unicode",
+            CompileOption::Ungreedy => "ungreedy",
+            CompileOption::Anchored => "anchored",
+            CompileOption::DollarEndonly => "dollar_endonly",
+            CompileOption::Firstline => "firstline",
+            CompileOption::NoAutoCapture => "no_auto_capture",
+        }
+    }
+}
+
+/// Option of `re:run/3` and `re:replace/4`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunOption {
+    /// Find all matches (`global`)
+    Global,
+    /// Start matching at this byte offset (`{offset, Offset}`)
+    Offset(usize),
+    /// The match must start at the offset (`anchored`)
+    Anchored,
+    /// Groups to return and how (`{capture, ValueSpec, Type}`)
+    Capture(ValueSpec, CaptureType),
+}
+
+/// Groups returned by `re:run/3`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueSpec {
+    /// All groups, starting with the whole match (`all`)
+    All,
+    /// All groups except the whole match (`all_but_first`)
+    AllButFirst,
+    /// Only the whole match (`first`)
+    First,
+    /// No groups; the result is only whether there was a match (`none`)
+    None,
+    /// Named groups, in alphabetical order of their names (`all_names`)
+    AllNames,
+    /// The listed groups, by number or name
+    List(Vec<GroupRef>),
+}
+
+/// Reference to a capture group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupRef {
+    /// Group number, 0 being the whole match
+    Index(usize),
+    /// Group name
+    Name(String),
+}
+
+/// Representation of captured groups
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureType {
+    /// `{Offset, Length}` pairs (`index`)
+    Index,
+    /// Text (`list`)
+    List,
+    /// Text (`binary`)
+    Binary,
+}
+
+/// Captured group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureValue {
+    /// Byte offset and length; `(-1, 0)` for a group that did not
+    /// participate or does not exist
+    Index(isize, usize),
+    /// Matched text; empty for a group that did not participate
+    Text(String),
+}
+
+/// Result of `re:run/3`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunResult {
+    /// No match (`nomatch`)
+    NoMatch,
+    /// A match, with no groups requested (`match`)
+    Match,
+    /// The groups of the match (`{match, Captured}`)
+    Captured(Vec<CaptureValue>),
+    /// The groups of every match, with `global` (`{match, [Captured]}`)
+    Global(Vec<Vec<CaptureValue>>),
+}
+
+/// Option of `re:split/3`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitOption {
+    /// Split into at most this many parts (`{parts, N}`); 0 means no limit
+    /// with trailing empty parts removed
+    Parts(usize),
+    /// Remove trailing empty parts (`trim`)
+    Trim,
+}
+
+/// Byte range of every group in a match, `None` for groups that did not
+/// participate
+type GroupRanges = Vec<Option<(usize, usize)>>;
+
+/// Settings from run options
+struct RunSettings {
+    global: bool,
+    offset: usize,
+    anchored: bool,
+    capture: Option<(ValueSpec, CaptureType)>,
+}
+
+impl RunSettings {
+    fn new(regex: &CompiledRegex, options: &[RunOption]) -> Self {
+        let mut settings = Self {
+            global: false,
+            offset: 0,
+            anchored: regex.anchored,
+            capture: None,
+        };
+        for option in options {
+            match option {
+                RunOption::Global => settings.global = true,
+                RunOption::Offset(offset) => settings.offset = *offset,
+                RunOption::Anchored => settings.anchored = true,
+                RunOption::Capture(spec, capture_type) => settings.capture = Some((spec.clone(), *capture_type)),
+            }
+        }
+        settings
+    }
+}
+
+/// Represent a group as requested
+fn capture_value(text: &str, group: Option<(usize, usize)>, capture_type: CaptureType) -> CaptureValue {
+    match (group, capture_type) {
+        (Some((start, end)), CaptureType::Index) => CaptureValue::Index(start as isize, end - start),
+        (None, CaptureType::Index) => CaptureValue::Index(-1, 0),
+        (Some((start, end)), _) => CaptureValue::Text(text[start..end].to_string()),
+        (None, _) => CaptureValue::Text(String::new()),
+    }
+}
+
+/// Piece of a replacement template
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TemplatePart {
+    Literal(String),
+    Group(usize),
+}
+
+/// Parse a `re:replace/4` template
+fn parse_template(template: &str) -> Vec<TemplatePart> {
+    fn number(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<usize> {
+        let mut digits = String::new();
+        while let Some(c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+            digits.push(*c);
+            chars.next();
+        }
+        digits.parse().ok()
+    }
+
+    fn push_group(literal: &mut String, parts: &mut Vec<TemplatePart>, group: usize) {
+        if !literal.is_empty() {
+            parts.push(TemplatePart::Literal(std::mem::take(literal)));
+        }
+        parts.push(TemplatePart::Group(group));
+    }
+
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '&' => push_group(&mut literal, &mut parts, 0),
+            '\\' => match chars.peek().copied() {
+                Some(d) if d.is_ascii_digit() => {
+                    let group = number(&mut chars).unwrap_or(usize::MAX);
+                    push_group(&mut literal, &mut parts, group);
+                }
+                Some('g') => {
+                    chars.next();
+                    let braced = chars.next_if_eq(&'{').is_some();
+                    match number(&mut chars) {
+                        Some(group) if !braced || chars.next_if_eq(&'}').is_some() => {
+                            push_group(&mut literal, &mut parts, group)
+                        }
+                        // Not a group reference; keep it as written
+                        _ => literal.push_str(if braced { "g{" } else { "g" }),
+                    }
+                }
+                Some(escaped) => {
+                    chars.next();
+                    literal.push(escaped);
+                }
+                None => literal.push('\\'),
+            },
+            c => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        parts.push(TemplatePart::Literal(literal));
+    }
+    parts
+}
+
+/// PCRE feature used by `pattern` that the `regex` crate lacks, if any
+fn unsupported_feature(pattern: &str) -> Option<&'static str> {
+    let bytes = pattern.as_bytes();
+    let mut in_class = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        let rest = &bytes[i..];
+        match bytes[i] {
+            b'\\' => {
+                match bytes.get(i + 1) {
+                    Some(b'1'..=b'9' | b'k' | b'g') if !in_class => return Some("backreference"),
+                    Some(b'K') if !in_class => return Some("match reset"),
+                    _ => {}
+                }
+                i += 2;
+                continue;
+            }
+            b'[' if !in_class => in_class = true,
+            b']' if in_class => in_class = false,
+            _ if in_class => {}
+            b'(' if rest.starts_with(b"(?=") || rest.starts_with(b"(?!") => return Some("lookahead"),
+            b'(' if rest.starts_with(b"(?<=") || rest.starts_with(b"(?<!") => return Some("lookbehind"),
+            b'(' if rest.starts_with(b"(?>") => return Some("atomic group"),
+            b'(' if rest.starts_with(b"(?R")
+                || rest.starts_with(b"(?&")
+                || rest.starts_with(b"(?P>")
+                || rest.get(2).is_some_and(u8::is_ascii_digit) && rest.starts_with(b"(?") =>
+            {
+                return Some("recursion")
+            }
+            b'(' if rest.starts_with(b"(*") => return Some("verb"),
+            b'*' | b'+' | b'?' | b'}' if bytes.get(i + 1) == Some(&b'+') => return Some("possessive quantifier"),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
 /// Regex operation errors
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RegexError {
@@ -370,6 +973,12 @@ pub enum RegexError {
     InvalidPattern(String),
     /// Invalid offset
     InvalidOffset,
+    /// PCRE feature or option the regex engine does not provide
+    Unsupported(String),
+    /// Option not valid for the operation
+    InvalidOption(String),
+    /// Named group not present in the pattern
+    UnknownGroup(String),
 }
 
 impl std::fmt::Display for RegexError {
@@ -377,6 +986,9 @@ impl std::fmt::Display for RegexError {
         match self {
             RegexError::InvalidPattern(msg) => write!(f, "Invalid pattern: {}", msg),
             RegexError::InvalidOffset => write!(f, "Invalid offset"),
+            RegexError::Unsupported(feature) => write!(f, "Unsupported: {}", feature),
+            RegexError::InvalidOption(option) => write!(f, "Invalid option: {}", option),
+            RegexError::UnknownGroup(name) => write!(f, "Unknown group: {}", name),
 }
     }
 }
@@ -607,4 +1219,160 @@ mod tests {
         assert_eq!(result.captures[0].start, 7);
         assert_eq!(result.captures[0].text, "hello");
     }
+
+    fn index(offset: isize, length: usize) -> CaptureValue {
+        CaptureValue::Index(offset, length)
+    }
+
+    fn text(s: &str) -> CaptureValue {
+        CaptureValue::Text(s.to_string())
+    }
+
+    #[test]
+    fn test_compile_with_options() {
+        let re = RegexBif::compile_with(r"^abc$", &[CompileOption::Caseless, CompileOption::Multiline]).unwrap();
+        assert!(RegexBif::run(&re, "x\nABC\ny", 0).unwrap().matched);
+
+        let re = RegexBif::compile_with(r"a.c", &[CompileOption::Dotall]).unwrap();
+        assert!(RegexBif::run(&re, "a\nc", 0).unwrap().matched);
+
+        let re = RegexBif::compile_with("a b # comment", &[CompileOption::Extended]).unwrap();
+        assert!(RegexBif::run(&re, "ab", 0).unwrap().matched);
+
+        let re = RegexBif::compile_with(r"a+", &[CompileOption::Ungreedy]).unwrap();
+        assert_eq!(RegexBif::run(&re, "aaa", 0).unwrap().captures[0].text, "a");
+
+        let re = RegexBif::compile_with(r"\w+", &[CompileOption::Unicode, CompileOption::Anchored]).unwrap();
+        assert!(re.is_unicode() && re.is_anchored());
+        assert_eq!(RegexBif::run_with(&re, " ärm", &[]).unwrap(), RunResult::NoMatch);
+        assert_eq!(
+            RegexBif::run_with(&re, " ärm", &[RunOption::Offset(1)]).unwrap(),
+            RunResult::Captured(vec![index(1, 4)])
+        );
+
+        assert_eq!(
+            RegexBif::compile_with("a", &[CompileOption::Firstline]).err(),
+            Some(RegexError::Unsupported("firstline".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_unsupported_pcre_features() {
+        let unsupported = |pattern| match RegexBif::compile_simple(pattern) {
+            Err(RegexError::Unsupported(feature)) => feature,
+            other => panic!("{}: {:?}", pattern, other.map(|_| ())),
+        };
+        assert_eq!(unsupported(r"(a)\1"), "backreference");
+        assert_eq!(unsupported(r"(?<n>a)\k<n>"), "backreference");
+        assert_eq!(unsupported(r"a(?=b)"), "lookahead");
+        assert_eq!(unsupported(r"a(?!b)"), "lookahead");
+        assert_eq!(unsupported(r"(?<=a)b"), "lookbehind");
+        assert_eq!(unsupported(r"(?>a+)b"), "atomic group");
+        assert_eq!(unsupported(r"a++b"), "possessive quantifier");
+        assert_eq!(unsupported(r"\((?R)?\)"), "recursion");
+        assert_eq!(unsupported(r"(*UTF8)a"), "verb");
+        // Escaped and bracketed lookalikes are plain errors or valid
+        assert!(matches!(RegexBif::compile_simple(r"[(?=]\++("), Err(RegexError::InvalidPattern(_))));
+        assert!(RegexBif::compile_simple(r"[(?=+]\++").is_ok());
+    }
+
+    #[test]
+    fn test_run_with_capture_specs() {
+        let re = RegexBif::compile_simple(r"(?<year>\d{4})-(?<month>\d\d)(-(?<day>\d\d))?").unwrap();
+        assert_eq!(re.names(), vec!["day", "month", "year"]);
+        assert_eq!(re.group_count(), 4);
+        let run = |spec, capture_type| RegexBif::run_with(&re, "on 2024-05", &[RunOption::Capture(spec, capture_type)]).unwrap();
+
+        assert_eq!(
+            run(ValueSpec::All, CaptureType::Index),
+            RunResult::Captured(vec![index(3, 7), index(3, 4), index(8, 2), index(-1, 0), index(-1, 0)])
+        );
+        assert_eq!(run(ValueSpec::First, CaptureType::List), RunResult::Captured(vec![text("2024-05")]));
+        assert_eq!(
+            run(ValueSpec::AllButFirst, CaptureType::Binary),
+            RunResult::Captured(vec![text("2024"), text("05"), text(""), text("")])
+        );
+        assert_eq!(run(ValueSpec::AllNames, CaptureType::Binary), RunResult::Captured(vec![text(""), text("05"), text("2024")]));
+        assert_eq!(run(ValueSpec::None, CaptureType::Index), RunResult::Match);
+        assert_eq!(
+            run(
+                ValueSpec::List(vec![GroupRef::Name("month".to_string()), GroupRef::Index(1), GroupRef::Index(9)]),
+                CaptureType::Index
+            ),
+            RunResult::Captured(vec![index(8, 2), index(3, 4), index(-1, 0)])
+        );
+        let unknown = RunOption::Capture(ValueSpec::List(vec![GroupRef::Name("hour".to_string())]), CaptureType::Index);
+        assert_eq!(RegexBif::run_with(&re, "2024-05", &[unknown]), Err(RegexError::UnknownGroup("hour".to_string())));
+    }
+
+    #[test]
+    fn test_run_with_global_and_offset() {
+        let re = RegexBif::compile_simple(r"\d+").unwrap();
+        let global = RegexBif::run_with(&re, "1 22 333", &[RunOption::Global, RunOption::Offset(1)]).unwrap();
+        assert_eq!(global, RunResult::Global(vec![vec![index(2, 2)], vec![index(5, 3)]]));
+        assert_eq!(RegexBif::run_with(&re, "abc", &[RunOption::Global]).unwrap(), RunResult::NoMatch);
+
+        // The subject before the offset is visible to assertions
+        let re = RegexBif::compile_simple(r"^b|\bc").unwrap();
+        assert_eq!(RegexBif::run_with(&re, "abc", &[RunOption::Offset(1)]).unwrap(), RunResult::NoMatch);
+
+        // Empty matches, as PCRE reports them
+        let re = RegexBif::compile_simple(r"a*").unwrap();
+        let global = RegexBif::run_with(&re, "aab", &[RunOption::Global]).unwrap();
+        assert_eq!(global, RunResult::Global(vec![vec![index(0, 2)], vec![index(2, 0)], vec![index(3, 0)]]));
+
+        // Anchored global matching stops at the first gap
+        let re = RegexBif::compile_simple(r"\w").unwrap();
+        let anchored = RegexBif::run_with(&re, "ab c", &[RunOption::Global, RunOption::Anchored]).unwrap();
+        assert_eq!(anchored, RunResult::Global(vec![vec![index(0, 1)], vec![index(1, 1)]]));
+
+        assert_eq!(RegexBif::run_with(&re, "abc", &[RunOption::Offset(4)]), Err(RegexError::InvalidOffset));
+        assert_eq!(RegexBif::run_with(&re, "ä", &[RunOption::Offset(1)]), Err(RegexError::InvalidOffset));
+    }
+
+    #[test]
+    fn test_replace_templates() {
+        let re = RegexBif::compile_simple(r"(\w)(\w)?").unwrap();
+        let replace = |template| RegexBif::replace(&re, "abc", template, &[RunOption::Global]).unwrap();
+        assert_eq!(replace(r"\2\1"), "bac");
+        assert_eq!(replace(r"[&]"), "[ab][c]");
+        assert_eq!(replace(r"\0\g1\g{2}"), "ababcc");
+        assert_eq!(replace(r"\&\\\x"), r"&\x&\x");
+        assert_eq!(replace(r"\12"), "");
+        assert_eq!(replace(r"\g{x}"), "g{x}g{x}");
+
+        let re = RegexBif::compile_simple(r"o").unwrap();
+        assert_eq!(RegexBif::replace(&re, "foo boo", "0", &[]).unwrap(), "f0o boo");
+        assert_eq!(RegexBif::replace(&re, "foo boo", "0", &[RunOption::Global, RunOption::Offset(3)]).unwrap(), "foo b00");
+        assert_eq!(
+            RegexBif::replace(&re, "foo", "0", &[RunOption::Capture(ValueSpec::All, CaptureType::Index)]),
+            Err(RegexError::InvalidOption("capture".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_split_parts_and_trim() {
+        let re = RegexBif::compile_simple(r",").unwrap();
+        assert_eq!(RegexBif::split(&re, "a,b,,c,,", &[]).unwrap(), vec!["a", "b", "", "c", "", ""]);
+        assert_eq!(RegexBif::split(&re, "a,b,,c,,", &[SplitOption::Trim]).unwrap(), vec!["a", "b", "", "c"]);
+        assert_eq!(RegexBif::split(&re, "a,b,,c,,", &[SplitOption::Parts(0)]).unwrap(), vec!["a", "b", "", "c"]);
+        assert_eq!(RegexBif::split(&re, "a,b,,c,,", &[SplitOption::Parts(3)]).unwrap(), vec!["a", "b", ",c,,"]);
+        assert_eq!(RegexBif::split(&re, "a,b", &[SplitOption::Parts(1)]).unwrap(), vec!["a,b"]);
+        assert_eq!(RegexBif::split(&re, "", &[]).unwrap(), vec![""]);
+        assert!(RegexBif::split(&re, "", &[SplitOption::Trim]).unwrap().is_empty());
+
+        // Empty matches split between characters
+        let re = RegexBif::compile_simple(r"").unwrap();
+        assert_eq!(RegexBif::split(&re, "abc", &[SplitOption::Trim]).unwrap(), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_split_with_groups() {
+        let re = RegexBif::compile_simple(r"(\d)|-").unwrap();
+        assert_eq!(RegexBif::split(&re, "a1b-c", &[]).unwrap(), vec!["a", "1", "b", "", "c"]);
+        assert_eq!(
+            RegexBif::split_grouped(&re, "a1b-c", &[]).unwrap(),
+            vec![vec!["a", "1"], vec!["b", ""], vec!["c"]]
+        );
+    }
 }
@@ -0,0 +1,340 @@
+//! Send Built-in Functions
+//!
+//! Provides sending with options and backpressure:
+//! - `erlang:send/3` with the `nosuspend` and `noconnect` options
+//! - `erlang:send_nosuspend/2,3`
+//!
+//! Sending to a local process never blocks. Sending to a port or to another
+//! node can: a busy port (see [`port`](crate::port)) or a busy distribution
+//! entry (one whose output queue has reached `dist_buf_busy_limit`)
+//! suspends the sender, and the send returns [`SendError::Suspended`]. The
+//! sender is recorded on the destination and on its own process, and is
+//! resumed with [`SendBif::resume_senders`] once the destination drains;
+//! the send is then reissued.
+//!
+//! With `nosuspend` the send returns `nosuspend` instead of suspending, and
+//! with `noconnect` a send to a node that is not connected returns
+//! `noconnect` instead of setting up the connection.
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ *
+ * Creation productivity increased for code in this file by using AALang and GAB.
+ * See https://github.com/yenrab/AALang-Gab
+ */
+
+use entities_process::{BusyDestination, Eterm, Message, Process, ProcessId};
+use infrastructure_driver_api::get_global_port_table;
+use infrastructure_utilities::dist_table::get_global_dist_table;
+use infrastructure_utilities::process_table::get_global_process_table;
+use usecases_scheduling::{resume_busy_senders, RunQueue};
+
+use crate::op::ErlangTerm;
+use crate::port::{PortBif, PortError};
+
+/// A message to send, with its destination
+#[derive(Debug, Clone, PartialEq)]
+pub enum SendRequest {
+    /// Message to a local process
+    Local {
+        /// Receiving process
+        to: ProcessId,
+        /// Message term
+        payload: Eterm,
+    },
+    /// `{Pid, {command, Data}}` to a port, given as `Data`
+    PortCommand {
+        /// Port number
+        port: u64,
+        /// I/O data for the port
+        data: ErlangTerm,
+    },
+    /// Message to a process on another node
+    Remote {
+        /// Node name
+        node: String,
+        /// Distribution control message and message, encoded
+        data: Vec<u8>,
+    },
+}
+
+/// Options of `erlang:send/3` and `erlang:send_nosuspend/3`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SendOptions {
+    /// Return `nosuspend` instead of suspending on a busy destination
+    pub nosuspend: bool,
+    /// Return `noconnect` instead of connecting to a node
+    pub noconnect: bool,
+}
+
+impl SendOptions {
+    /// Parse a list of `nosuspend` and `noconnect`
+    pub fn parse(options: &ErlangTerm) -> Result<Self, SendError> {
+        let items: &[ErlangTerm] = match options {
+            ErlangTerm::Nil => &[],
+            ErlangTerm::List(items) => items,
+            _ => return Err(SendError::BadArgument("options must be a list".to_string())),
+        };
+        let mut parsed = SendOptions::default();
+        for item in items {
+            match item {
+                ErlangTerm::Atom(name) if name == "nosuspend" => parsed.nosuspend = true,
+                ErlangTerm::Atom(name) if name == "noconnect" => parsed.noconnect = true,
+                other => return Err(SendError::BadArgument(format!("invalid send option {:?}", other))),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// Error type for send operations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SendError {
+    /// Bad argument (invalid options, bad port or data)
+    BadArgument(String),
+    /// The sender was suspended on a busy destination; reissue the send when resumed
+    Suspended(BusyDestination),
+}
+
+impl std::fmt::Display for SendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SendError::BadArgument(msg) => write!(f, "Bad argument: {}", msg),
+            SendError::Suspended(BusyDestination::Port(port)) => {
+                write!(f, "Suspended on busy port #Port<0.{}>", port)
+            }
+            SendError::Suspended(BusyDestination::Dist(id)) => {
+                write!(f, "Suspended on busy distribution entry {}", id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SendError {}
+
+/// Send BIF operations
+pub struct SendBif;
+
+impl SendBif {
+    /// Send a message with options (erlang:send/3)
+    ///
+    /// # Arguments
+    /// * `sender` - Sending process, suspended if the destination is busy
+    /// * `request` - Message and destination
+    /// * `options` - List of `nosuspend` and `noconnect`
+    ///
+    /// # Returns
+    /// * `Ok(ErlangTerm::Atom("ok"))` - The message was sent
+    /// * `Ok(ErlangTerm::Atom("nosuspend"))` - The destination was busy and `nosuspend` was given
+    /// * `Ok(ErlangTerm::Atom("noconnect"))` - The node was not connected and `noconnect` was given
+    /// * `Err(SendError::Suspended)` - The sender was suspended on the busy destination
+    /// * `Err(SendError::BadArgument)` - Bad options, port or data
+    pub fn send_3(sender: &Process, request: &SendRequest, options: &ErlangTerm) -> Result<ErlangTerm, SendError> {
+        let options = SendOptions::parse(options)?;
+        Self::send(sender, request, options)
+    }
+
+    /// Send a message without suspending (erlang:send_nosuspend/2)
+    ///
+    /// # Returns
+    /// * `Ok(ErlangTerm::Atom("true"))` - The message was sent
+    /// * `Ok(ErlangTerm::Atom("false"))` - The destination was busy
+    /// * `Err(SendError::BadArgument)` - Bad port or data
+    pub fn send_nosuspend_2(sender: &Process, request: &SendRequest) -> Result<ErlangTerm, SendError> {
+        Self::send_nosuspend_3(sender, request, &ErlangTerm::Nil)
+    }
+
+    /// Send a message without suspending, with options (erlang:send_nosuspend/3)
+    ///
+    /// # Arguments
+    /// * `options` - List of `noconnect`
+    ///
+    /// # Returns
+    /// * `Ok(ErlangTerm::Atom("true"))` - The message was sent
+    /// * `Ok(ErlangTerm::Atom("false"))` - The destination was busy, or the
+    ///   node was not connected and `noconnect` was given
+    /// * `Err(SendError::BadArgument)` - Bad options, port or data
+    pub fn send_nosuspend_3(sender: &Process, request: &SendRequest, options: &ErlangTerm) -> Result<ErlangTerm, SendError> {
+        let mut options = SendOptions::parse(options)?;
+        options.nosuspend = true;
+        let sent = Self::send(sender, request, options)? == ErlangTerm::Atom("ok".to_string());
+        Ok(ErlangTerm::Atom(sent.to_string()))
+    }
+
+    /// Resume the processes suspended on a destination that has drained
+    ///
+    /// Called by the scheduler after a busy port has executed its output
+    /// or the distribution controller has written queued data.
+    ///
+    /// # Returns
+    /// Number of processes that became runnable
+    pub fn resume_senders(destination: BusyDestination, runq: &RunQueue) -> usize {
+        let senders = match destination {
+            BusyDestination::Port(id) => get_global_port_table()
+                .lookup(id)
+                .map(|port| port.take_resumed())
+                .unwrap_or_default(),
+            BusyDestination::Dist(id) => get_global_dist_table()
+                .lookup_id(id)
+                .map(|entry| entry.take_resumed())
+                .unwrap_or_default(),
+        };
+        resume_busy_senders(&senders, runq)
+    }
+
+    fn send(sender: &Process, request: &SendRequest, options: SendOptions) -> Result<ErlangTerm, SendError> {
+        let outcome = match request {
+            SendRequest::Local { to, payload } => {
+                if let Some(receiver) = get_global_process_table().lookup(*to) {
+                    receiver.send_message(Message::new(*payload));
+                }
+                "ok"
+            }
+            SendRequest::PortCommand { port, data } => Self::send_port(sender, *port, data, options)?,
+            SendRequest::Remote { node, data } => Self::send_remote(sender, node, data, options)?,
+        };
+        Ok(ErlangTerm::Atom(outcome.to_string()))
+    }
+
+    fn send_port(sender: &Process, port: u64, data: &ErlangTerm, options: SendOptions) -> Result<&'static str, SendError> {
+        let port_options = if options.nosuspend {
+            ErlangTerm::List(vec![ErlangTerm::Atom("nosuspend".to_string())])
+        } else {
+            ErlangTerm::Nil
+        };
+        match PortBif::port_command_3(sender.id(), &ErlangTerm::Port(port), data, &port_options) {
+            Ok(ErlangTerm::Atom(sent)) if sent == "true" => Ok("ok"),
+            Ok(_) => Ok("nosuspend"),
+            Err(PortError::Suspended(port)) => Err(SendError::Suspended(BusyDestination::Port(port))),
+            Err(err) => Err(SendError::BadArgument(err.to_string())),
+        }
+    }
+
+    fn send_remote(sender: &Process, node: &str, data: &[u8], options: SendOptions) -> Result<&'static str, SendError> {
+        let table = get_global_dist_table();
+        let entry = match table.lookup(node) {
+            Some(entry) if entry.is_connected() => entry,
+            _ if options.noconnect => return Ok("noconnect"),
+            _ => {
+                // Queue on the pending connection; the data goes out once it is up
+                let entry = table.find_or_insert(node);
+                entry.set_pending();
+                entry
+            }
+        };
+        if entry.is_busy() {
+            if options.nosuspend {
+                return Ok("nosuspend");
+            }
+            if entry.suspend_sender(sender.id()) {
+                sender.suspend_on_busy(BusyDestination::Dist(entry.id()));
+                return Err(SendError::Suspended(BusyDestination::Dist(entry.id())));
+            }
+        }
+        entry.enqueue(data.to_vec());
+        Ok("ok")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entities_process::Process;
+    use std::sync::Arc;
+
+    fn atom(name: &str) -> ErlangTerm {
+        ErlangTerm::Atom(name.to_string())
+    }
+
+    fn remote(node: &str, data: &[u8]) -> SendRequest {
+        SendRequest::Remote { node: node.to_string(), data: data.to_vec() }
+    }
+
+    #[test]
+    fn test_send_options() {
+        let options = ErlangTerm::List(vec![atom("noconnect"), atom("nosuspend")]);
+        assert_eq!(SendOptions::parse(&options), Ok(SendOptions { nosuspend: true, noconnect: true }));
+        assert!(SendOptions::parse(&ErlangTerm::List(vec![atom("force")])).is_err());
+        assert!(SendOptions::parse(&atom("nosuspend")).is_err());
+    }
+
+    #[test]
+    fn test_send_local() {
+        let receiver = Arc::new(Process::new(41124));
+        get_global_process_table().insert(41124, Arc::clone(&receiver));
+        let sender = Process::new(41125);
+        let request = SendRequest::Local { to: 41124, payload: 5 };
+        assert_eq!(SendBif::send_3(&sender, &request, &ErlangTerm::Nil), Ok(atom("ok")));
+        assert_eq!(SendBif::send_nosuspend_2(&sender, &request), Ok(atom("true")));
+        assert_eq!(receiver.message_queue_len(), 2);
+        get_global_process_table().remove(41124);
+    }
+
+    #[test]
+    fn test_send_noconnect() {
+        let sender = Process::new(41126);
+        let noconnect = ErlangTerm::List(vec![atom("noconnect")]);
+        let request = remote("noconnect@host", b"msg");
+        assert_eq!(SendBif::send_3(&sender, &request, &noconnect), Ok(atom("noconnect")));
+        assert_eq!(SendBif::send_nosuspend_3(&sender, &request, &noconnect), Ok(atom("false")));
+        assert!(get_global_dist_table().lookup("noconnect@host").is_none());
+
+        // Without noconnect the data is queued on a pending connection
+        assert_eq!(SendBif::send_3(&sender, &request, &ErlangTerm::Nil), Ok(atom("ok")));
+        let entry = get_global_dist_table().lookup("noconnect@host").unwrap();
+        assert_eq!(entry.connection_state(), infrastructure_utilities::DistConnectionState::Pending);
+        assert_eq!(entry.queue_size(), 3);
+        get_global_dist_table().remove("noconnect@host");
+    }
+
+    #[test]
+    fn test_send_busy_dist_entry() {
+        let sender = Arc::new(Process::new(41127));
+        get_global_process_table().insert(41127, Arc::clone(&sender));
+        let entry = get_global_dist_table().find_or_insert("busy@host");
+        entry.set_connected();
+        entry.set_busy_limit(4);
+        let request = remote("busy@host", b"data");
+
+        assert_eq!(SendBif::send_3(&sender, &request, &ErlangTerm::Nil), Ok(atom("ok")));
+        assert_eq!(SendBif::send_nosuspend_2(&sender, &request), Ok(atom("false")));
+        let nosuspend = ErlangTerm::List(vec![atom("nosuspend")]);
+        assert_eq!(SendBif::send_3(&sender, &request, &nosuspend), Ok(atom("nosuspend")));
+
+        let busy = BusyDestination::Dist(entry.id());
+        assert_eq!(SendBif::send_3(&sender, &request, &ErlangTerm::Nil), Err(SendError::Suspended(busy)));
+        assert!(sender.is_suspended());
+        assert_eq!(sender.busy_destination(), Some(busy));
+
+        let runq = RunQueue::new(0, 0);
+        assert_eq!(SendBif::resume_senders(busy, &runq), 0);
+        assert_eq!(entry.dequeue(), Some(b"data".to_vec()));
+        assert_eq!(SendBif::resume_senders(busy, &runq), 1);
+        assert!(!sender.is_suspended());
+        assert_eq!(runq.total_len(), 1);
+        assert_eq!(SendBif::send_3(&sender, &request, &ErlangTerm::Nil), Ok(atom("ok")));
+
+        get_global_dist_table().remove("busy@host");
+        get_global_process_table().remove(41127);
+    }
+}
@@ -0,0 +1,548 @@
+//! Sequential Trace Built-in Functions
+//!
+//! Provides the `seq_trace` module BIFs:
+//! - Tokens (set_token/1, set_token/2, get_token/0, get_token/1)
+//! - Trace output (print/1, print/2)
+//! - The system tracer (set_system_tracer/1, get_system_tracer/0)
+//! - Resetting all tokens (reset_trace/0)
+//!
+//! Message passing goes through [`SeqTraceBif::send`] and
+//! [`SeqTraceBif::receive`], which pass the sender's token on with the
+//! message and emit `send` and `'receive'` events. Messages to other nodes
+//! carry the token in the `SEND_TT`/`REG_SEND_TT` distribution control
+//! message built by [`SeqTraceBif::dist_send_control`].
+//!
+//! Events go to the system tracer, where they queue until the tracer takes
+//! them with [`SeqTraceBif::take_events`]. Events emitted while there is no
+//! system tracer are dropped.
+//!
+//! Based on seq_trace_set_token_2, seq_trace_print_2 and friends in
+//! erl_bif_trace.c and seq_trace_output_generic in erl_trace.c
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ *
+ * Creation productivity increased for code in this file by using AALang and GAB.
+ * See https://github.com/yenrab/AALang-Gab
+ */
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{LazyLock, Mutex};
+
+use entities_data_handling::atom::AtomEncoding;
+use entities_data_handling::term_hashing::Term;
+use entities_process::seq_trace::{
+    SEQ_TRACE_MONOTONIC_TIMESTAMP, SEQ_TRACE_NOW_TIMESTAMP, SEQ_TRACE_PRINT, SEQ_TRACE_RECEIVE,
+    SEQ_TRACE_SEND, SEQ_TRACE_STRICT_MONOTONIC_TIMESTAMP,
+};
+use entities_process::{Eterm, Message, Process, ProcessId, SeqTraceToken};
+use infrastructure_time_management::TimeUnit;
+use infrastructure_utilities::atom_table::get_global_atom_table;
+use infrastructure_utilities::process_table::get_global_process_table;
+
+use crate::time::TimeBif;
+use crate::unique::{UniqueBif, UniqueIntegerOption};
+
+/// Distribution operation `SEND_TT`: `{12, '', ToPid, TraceToken}`
+pub const DOP_SEND_TT: i64 = 12;
+/// Distribution operation `REG_SEND_TT`: `{16, FromPid, '', ToName, TraceToken}`
+pub const DOP_REG_SEND_TT: i64 = 16;
+/// Distribution operation `SEND`: `{2, '', ToPid}`
+pub const DOP_SEND: i64 = 2;
+/// Distribution operation `REG_SEND`: `{6, FromPid, '', ToName}`
+pub const DOP_REG_SEND: i64 = 6;
+
+/// Error type for sequential trace operations
+#[derive(Debug, Clone, PartialEq)]
+pub enum SeqTraceError {
+    /// Invalid flag name, token or control message
+    BadArgument,
+}
+
+impl std::fmt::Display for SeqTraceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SeqTraceError::BadArgument => write!(f, "Bad argument"),
+        }
+    }
+}
+
+impl std::error::Error for SeqTraceError {}
+
+/// A token flag settable with `seq_trace:set_token/2`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeqTraceFlag {
+    /// `send`
+    Send,
+    /// `'receive'`
+    Receive,
+    /// `print`
+    Print,
+    /// `timestamp`
+    Timestamp,
+    /// `monotonic_timestamp`
+    MonotonicTimestamp,
+    /// `strict_monotonic_timestamp`
+    StrictMonotonicTimestamp,
+}
+
+impl SeqTraceFlag {
+    /// Parse a flag from its atom name
+    pub fn from_name(name: &str) -> Result<Self, SeqTraceError> {
+        match name {
+            "send" => Ok(SeqTraceFlag::Send),
+            "receive" => Ok(SeqTraceFlag::Receive),
+            "print" => Ok(SeqTraceFlag::Print),
+            "timestamp" => Ok(SeqTraceFlag::Timestamp),
+            "monotonic_timestamp" => Ok(SeqTraceFlag::MonotonicTimestamp),
+            "strict_monotonic_timestamp" => Ok(SeqTraceFlag::StrictMonotonicTimestamp),
+            _ => Err(SeqTraceError::BadArgument),
+        }
+    }
+
+    fn bit(self) -> u32 {
+        match self {
+            SeqTraceFlag::Send => SEQ_TRACE_SEND,
+            SeqTraceFlag::Receive => SEQ_TRACE_RECEIVE,
+            SeqTraceFlag::Print => SEQ_TRACE_PRINT,
+            SeqTraceFlag::Timestamp => SEQ_TRACE_NOW_TIMESTAMP,
+            SeqTraceFlag::MonotonicTimestamp => SEQ_TRACE_MONOTONIC_TIMESTAMP,
+            SeqTraceFlag::StrictMonotonicTimestamp => SEQ_TRACE_STRICT_MONOTONIC_TIMESTAMP,
+        }
+    }
+}
+
+/// Timestamp of a trace event, as selected by the token's timestamp flag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeqTraceTimestamp {
+    /// `{MegaSecs, Secs, MicroSecs}` from `erlang:timestamp/0`
+    Now(i64, i64, i64),
+    /// Monotonic time in native units
+    Monotonic(i64),
+    /// Monotonic time in native units and a strictly monotonic unique integer
+    StrictMonotonic(i64, i64),
+}
+
+/// What a trace event records
+#[derive(Debug, Clone, PartialEq)]
+pub enum SeqTraceInfo {
+    /// `{send, Serial, From, To, Message}`
+    Send { from: Term, to: Term, message: Eterm },
+    /// `{'receive', Serial, From, To, Message}`
+    Receive { from: Term, to: Term, message: Eterm },
+    /// `{print, Serial, From, [], Info}`
+    Print { from: Term, info: Term },
+}
+
+/// A sequential trace event, `{seq_trace, Label, SeqTraceInfo[, Timestamp]}`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeqTraceEvent {
+    /// Label of the token
+    pub label: Eterm,
+    /// `{PreviousSerial, ThisSerial}` of the token
+    pub serial: (u64, u64),
+    /// Event details
+    pub info: SeqTraceInfo,
+    /// Timestamp, if the token has a timestamp flag
+    pub timestamp: Option<SeqTraceTimestamp>,
+}
+
+/// Destination of a message to another node
+#[derive(Debug, Clone, PartialEq)]
+pub enum DistDestination {
+    /// A remote process identifier
+    Pid(Term),
+    /// A registered name (atom) on the remote node
+    Name(Term),
+}
+
+/// System tracer and the events waiting for it
+#[derive(Debug, Default)]
+struct TracerState {
+    tracer: Option<ProcessId>,
+    events: HashMap<ProcessId, VecDeque<SeqTraceEvent>>,
+}
+
+static TRACER: LazyLock<Mutex<TracerState>> = LazyLock::new(|| Mutex::new(TracerState::default()));
+
+/// Sequential Trace Built-in Functions
+pub struct SeqTraceBif;
+
+impl SeqTraceBif {
+    /// Set or clear a token flag (`seq_trace:set_token(Flag, Bool)`)
+    ///
+    /// A process without a token gets a new one with label 0.
+    ///
+    /// # Returns
+    /// Whether the flag was set before
+    pub fn set_token_flag(process: &Process, flag: SeqTraceFlag, on: bool) -> bool {
+        let mut token = process.seq_trace_token().unwrap_or_else(|| SeqTraceToken::new(0, process.id()));
+        let was_set = token.set_flag(flag.bit(), on);
+        process.set_seq_trace_token(Some(token));
+        was_set
+    }
+
+    /// Set the token label (`seq_trace:set_token(label, Label)`)
+    ///
+    /// # Returns
+    /// The previous label, or `None` if the process had no token
+    pub fn set_token_label(process: &Process, label: Eterm) -> Option<Eterm> {
+        let previous = process.seq_trace_token();
+        let mut token = previous.unwrap_or_else(|| SeqTraceToken::new(label, process.id()));
+        token.label = label;
+        process.set_seq_trace_token(Some(token));
+        previous.map(|token| token.label)
+    }
+
+    /// Replace the whole token (`seq_trace:set_token/1`)
+    ///
+    /// `None` (`[]`) clears the token.
+    ///
+    /// # Returns
+    /// The previous token
+    pub fn set_token(process: &Process, token: Option<SeqTraceToken>) -> Option<SeqTraceToken> {
+        process.set_seq_trace_token(token)
+    }
+
+    /// Get the token (`seq_trace:get_token/0`)
+    pub fn get_token(process: &Process) -> Option<SeqTraceToken> {
+        process.seq_trace_token()
+    }
+
+    /// Get one token flag (`seq_trace:get_token(Flag)`)
+    ///
+    /// # Returns
+    /// `None` (`[]`) if the process has no token
+    pub fn get_token_flag(process: &Process, flag: SeqTraceFlag) -> Option<bool> {
+        process.seq_trace_token().map(|token| token.has_flag(flag.bit()))
+    }
+
+    /// Emit a `print` event (`seq_trace:print/1`)
+    ///
+    /// # Returns
+    /// `true` if an event was emitted, i.e. the process has a token with
+    /// the `print` flag set
+    pub fn print(process: &Process, info: Term) -> bool {
+        match process.seq_trace_token() {
+            Some(token) if token.has_flag(SEQ_TRACE_PRINT) => {
+                Self::emit(&token, SeqTraceInfo::Print { from: local_pid(token.sender), info });
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Emit a `print` event if the token has label `label` (`seq_trace:print/2`)
+    pub fn print_label(process: &Process, label: Eterm, info: Term) -> bool {
+        match process.seq_trace_token() {
+            Some(token) if token.label == label => Self::print(process, info),
+            _ => false,
+        }
+    }
+
+    /// Clear the tokens and clocks of all processes (`seq_trace:reset_trace/0`)
+    pub fn reset_trace() {
+        get_global_process_table().foreach(|_, process| process.reset_seq_trace());
+    }
+
+    /// Set the system tracer (`seq_trace:set_system_tracer/1`)
+    ///
+    /// # Returns
+    /// The previous system tracer
+    pub fn set_system_tracer(tracer: Option<ProcessId>) -> Option<ProcessId> {
+        std::mem::replace(&mut TRACER.lock().unwrap().tracer, tracer)
+    }
+
+    /// Get the system tracer (`seq_trace:get_system_tracer/0`)
+    pub fn get_system_tracer() -> Option<ProcessId> {
+        TRACER.lock().unwrap().tracer
+    }
+
+    /// Take the trace events queued for `tracer`, oldest first
+    pub fn take_events(tracer: ProcessId) -> Vec<SeqTraceEvent> {
+        TRACER.lock().unwrap().events.remove(&tracer).map(Vec::from).unwrap_or_default()
+    }
+
+    /// Send a message between local processes (`To ! Message`)
+    ///
+    /// The sender's token goes with the message, and a `send` event is
+    /// emitted if the token has the `send` flag.
+    pub fn send(sender: &Process, receiver: &Process, payload: Eterm) {
+        let token = sender.seq_trace_send();
+        if let Some(token) = token.filter(|token| token.has_flag(SEQ_TRACE_SEND)) {
+            Self::emit(&token, SeqTraceInfo::Send {
+                from: local_pid(sender.id()),
+                to: local_pid(receiver.id()),
+                message: payload,
+            });
+        }
+        receiver.send_message(Message::new(payload).with_seq_trace_token(token));
+    }
+
+    /// Receive a message with `after 0`
+    ///
+    /// The process takes over the message's token, and a `'receive'` event
+    /// is emitted if that token has the `'receive'` flag.
+    pub fn receive<F>(process: &Process, matches: F) -> Option<Message>
+    where
+        F: FnMut(&Message) -> bool,
+    {
+        let message = process.receive_after_0(matches)?;
+        if let Some(token) = message.seq_trace_token.filter(|token| token.has_flag(SEQ_TRACE_RECEIVE)) {
+            Self::emit(&token, SeqTraceInfo::Receive {
+                from: local_pid(token.sender),
+                to: local_pid(process.id()),
+                message: message.payload,
+            });
+        }
+        Some(message)
+    }
+
+    /// Build the distribution control message for sending `payload` to
+    /// another node
+    ///
+    /// A traced sender gets the `SEND_TT`/`REG_SEND_TT` form with its token
+    /// and emits a `send` event; otherwise the plain `SEND`/`REG_SEND` form
+    /// is built.
+    pub fn dist_send_control(sender: &Process, to: DistDestination, payload: Eterm) -> Term {
+        let token = sender.seq_trace_send();
+        let from = local_pid(sender.id());
+        let unused = empty_atom();
+        if let Some(token) = token.filter(|token| token.has_flag(SEQ_TRACE_SEND)) {
+            let to = match &to {
+                DistDestination::Pid(pid) | DistDestination::Name(pid) => pid.clone(),
+            };
+            Self::emit(&token, SeqTraceInfo::Send { from: from.clone(), to, message: payload });
+        }
+        let mut control = match to {
+            DistDestination::Pid(pid) => vec![Term::Small(if token.is_some() { DOP_SEND_TT } else { DOP_SEND }), unused, pid],
+            DistDestination::Name(name) => {
+                let op = if token.is_some() { DOP_REG_SEND_TT } else { DOP_REG_SEND };
+                vec![Term::Small(op), from, unused, name]
+            }
+        };
+        if let Some(token) = token {
+            control.push(token_to_term(&token));
+        }
+        Term::Tuple(control)
+    }
+
+    /// Deliver a message that arrived from another node with `control`
+    ///
+    /// The token of a `SEND_TT`/`REG_SEND_TT` control message goes with the
+    /// message to `receiver`.
+    ///
+    /// # Errors
+    /// `SeqTraceError::BadArgument` if `control` is not a send control message
+    pub fn dist_deliver(receiver: &Process, control: &Term, payload: Eterm) -> Result<(), SeqTraceError> {
+        let token = match control {
+            Term::Tuple(elements) => match (elements.first(), elements.len()) {
+                (Some(Term::Small(DOP_SEND)), 3) | (Some(Term::Small(DOP_REG_SEND)), 4) => None,
+                (Some(Term::Small(DOP_SEND_TT)), 4) | (Some(Term::Small(DOP_REG_SEND_TT)), 5) => {
+                    Some(token_from_term(elements.last().unwrap())?)
+                }
+                _ => return Err(SeqTraceError::BadArgument),
+            },
+            _ => return Err(SeqTraceError::BadArgument),
+        };
+        receiver.send_message(Message::new(payload).with_seq_trace_token(token));
+        Ok(())
+    }
+
+    /// Queue an event for the system tracer
+    fn emit(token: &SeqTraceToken, info: SeqTraceInfo) {
+        let mut state = TRACER.lock().unwrap();
+        let Some(tracer) = state.tracer else { return };
+        let event = SeqTraceEvent {
+            label: token.label,
+            serial: (token.last_count, token.serial),
+            info,
+            timestamp: timestamp(token),
+        };
+        state.events.entry(tracer).or_default().push_back(event);
+    }
+}
+
+/// Timestamp for an event, strict monotonic taking precedence
+fn timestamp(token: &SeqTraceToken) -> Option<SeqTraceTimestamp> {
+    if token.has_flag(SEQ_TRACE_STRICT_MONOTONIC_TIMESTAMP) {
+        let unique = UniqueBif::unique_integer_with_options(&[UniqueIntegerOption::Monotonic]).unwrap_or(0);
+        Some(SeqTraceTimestamp::StrictMonotonic(TimeBif::monotonic_time(TimeUnit::Native), unique))
+    } else if token.has_flag(SEQ_TRACE_MONOTONIC_TIMESTAMP) {
+        Some(SeqTraceTimestamp::Monotonic(TimeBif::monotonic_time(TimeUnit::Native)))
+    } else if token.has_flag(SEQ_TRACE_NOW_TIMESTAMP) {
+        let (mega, secs, micro) = TimeBif::timestamp();
+        Some(SeqTraceTimestamp::Now(mega, secs, micro))
+    } else {
+        None
+    }
+}
+
+/// Pid term of a local process: the low 32 bits of the process identifier
+/// are the pid number and the high 32 bits the serial
+pub(crate) fn local_pid(id: ProcessId) -> Term {
+    Term::Pid { node: 0, id: id as u32, serial: (id >> 32) as u32, creation: 0 }
+}
+
+fn empty_atom() -> Term {
+    let index = get_global_atom_table().put_index(b"", AtomEncoding::SevenBitAscii, false).unwrap_or(0);
+    Term::Atom(index as u32)
+}
+
+/// The token as `{Flags, Label, Serial, From, LastCnt}`
+fn token_to_term(token: &SeqTraceToken) -> Term {
+    Term::Tuple(vec![
+        Term::Small(token.flags as i64),
+        Term::Small(token.label as i64),
+        Term::Small(token.serial as i64),
+        local_pid(token.sender),
+        Term::Small(token.last_count as i64),
+    ])
+}
+
+fn token_from_term(term: &Term) -> Result<SeqTraceToken, SeqTraceError> {
+    let non_negative = |term: &Term| match term {
+        Term::Small(n) if *n >= 0 => Ok(*n as u64),
+        _ => Err(SeqTraceError::BadArgument),
+    };
+    match term {
+        Term::Tuple(elements) if elements.len() == 5 => {
+            let Term::Pid { id, serial, .. } = elements[3] else {
+                return Err(SeqTraceError::BadArgument);
+            };
+            Ok(SeqTraceToken {
+                flags: u32::try_from(non_negative(&elements[0])?).map_err(|_| SeqTraceError::BadArgument)?,
+                label: non_negative(&elements[1])?,
+                serial: non_negative(&elements[2])?,
+                sender: (u64::from(serial) << 32) | u64::from(id),
+                last_count: non_negative(&elements[4])?,
+            })
+        }
+        _ => Err(SeqTraceError::BadArgument),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that set the global system tracer run one at a time
+    static TRACER_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+    fn traced(id: ProcessId, label: Eterm, flags: &[SeqTraceFlag]) -> Process {
+        let process = Process::new(id);
+        SeqTraceBif::set_token_label(&process, label);
+        for &flag in flags {
+            SeqTraceBif::set_token_flag(&process, flag, true);
+        }
+        process
+    }
+
+    #[test]
+    fn test_flag_names() {
+        assert_eq!(SeqTraceFlag::from_name("receive"), Ok(SeqTraceFlag::Receive));
+        assert_eq!(SeqTraceFlag::from_name("label"), Err(SeqTraceError::BadArgument));
+    }
+
+    #[test]
+    fn test_set_and_get_token() {
+        let process = Process::new(1);
+        assert_eq!(SeqTraceBif::get_token_flag(&process, SeqTraceFlag::Send), None);
+        assert!(!SeqTraceBif::set_token_flag(&process, SeqTraceFlag::Send, true));
+        assert!(SeqTraceBif::set_token_flag(&process, SeqTraceFlag::Send, true));
+        assert_eq!(SeqTraceBif::get_token_flag(&process, SeqTraceFlag::Send), Some(true));
+        assert_eq!(SeqTraceBif::set_token_label(&process, 5), Some(0));
+        assert_eq!(SeqTraceBif::get_token(&process).unwrap().label, 5);
+        assert!(SeqTraceBif::set_token(&process, None).is_some());
+        assert_eq!(SeqTraceBif::get_token(&process), None);
+    }
+
+    #[test]
+    fn test_send_and_receive_events() {
+        let _guard = TRACER_TEST_LOCK.lock().unwrap();
+        SeqTraceBif::set_system_tracer(Some(100));
+        let a = traced(1, 9, &[SeqTraceFlag::Send, SeqTraceFlag::Receive]);
+        let b = Process::new(2);
+
+        SeqTraceBif::send(&a, &b, 42);
+        let message = SeqTraceBif::receive(&b, |_| true).unwrap();
+        assert_eq!(message.payload, 42);
+        assert_eq!(SeqTraceBif::get_token(&b).unwrap().label, 9);
+
+        let events = SeqTraceBif::take_events(100);
+        SeqTraceBif::set_system_tracer(None);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].serial, (0, 1));
+        assert_eq!(events[0].info, SeqTraceInfo::Send { from: local_pid(1), to: local_pid(2), message: 42 });
+        assert_eq!(events[1].info, SeqTraceInfo::Receive { from: local_pid(1), to: local_pid(2), message: 42 });
+        assert_eq!(events[1].timestamp, None);
+    }
+
+    #[test]
+    fn test_print_requires_flag_and_label() {
+        let _guard = TRACER_TEST_LOCK.lock().unwrap();
+        SeqTraceBif::set_system_tracer(Some(101));
+        let untraced = Process::new(1);
+        assert!(!SeqTraceBif::print(&untraced, Term::Small(1)));
+        let quiet = traced(2, 3, &[]);
+        assert!(!SeqTraceBif::print(&quiet, Term::Small(2)));
+        let printing = traced(3, 3, &[SeqTraceFlag::Print, SeqTraceFlag::MonotonicTimestamp]);
+        assert!(!SeqTraceBif::print_label(&printing, 4, Term::Small(3)));
+        assert!(SeqTraceBif::print_label(&printing, 3, Term::Small(4)));
+
+        let events = SeqTraceBif::take_events(101);
+        SeqTraceBif::set_system_tracer(None);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].info, SeqTraceInfo::Print { from: local_pid(3), info: Term::Small(4) });
+        assert!(matches!(events[0].timestamp, Some(SeqTraceTimestamp::Monotonic(_))));
+    }
+
+    #[test]
+    fn test_no_events_without_tracer() {
+        let _guard = TRACER_TEST_LOCK.lock().unwrap();
+        SeqTraceBif::set_system_tracer(None);
+        let a = traced(1, 1, &[SeqTraceFlag::Send]);
+        SeqTraceBif::send(&a, &Process::new(2), 1);
+        assert_eq!(SeqTraceBif::get_system_tracer(), None);
+        assert!(SeqTraceBif::take_events(1).is_empty());
+    }
+
+    #[test]
+    fn test_dist_control_carries_token() {
+        let remote = Term::Pid { node: 3, id: 7, serial: 0, creation: 1 };
+        let plain = SeqTraceBif::dist_send_control(&Process::new(1), DistDestination::Pid(remote.clone()), 5);
+        assert!(matches!(&plain, Term::Tuple(e) if e.len() == 3 && e[0] == Term::Small(DOP_SEND)));
+
+        let sender = traced(2, 8, &[]);
+        let control = SeqTraceBif::dist_send_control(&sender, DistDestination::Pid(remote), 5);
+        let receiver = Process::new(3);
+        SeqTraceBif::dist_deliver(&receiver, &control, 5).unwrap();
+        SeqTraceBif::receive(&receiver, |_| true).unwrap();
+        let token = SeqTraceBif::get_token(&receiver).unwrap();
+        assert_eq!((token.label, token.serial, token.sender), (8, 1, 2));
+
+        let name = Term::Atom(0);
+        let control = SeqTraceBif::dist_send_control(&sender, DistDestination::Name(name), 6);
+        assert!(matches!(&control, Term::Tuple(e) if e.len() == 5 && e[0] == Term::Small(DOP_REG_SEND_TT)));
+        assert_eq!(SeqTraceBif::dist_deliver(&receiver, &Term::Small(1), 6), Err(SeqTraceError::BadArgument));
+    }
+}
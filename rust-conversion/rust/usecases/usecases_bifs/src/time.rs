@@ -0,0 +1,213 @@
+//! Time Built-in Functions
+//!
+//! Provides the Erlang time BIFs on top of the runtime clock:
+//! - `erlang:monotonic_time/0,1`
+//! - `erlang:system_time/0,1` and `os:system_time/0,1`
+//! - `erlang:time_offset/0,1` and `erlang:timestamp/0`
+//! - `erlang:convert_time_unit/3`
+//! - `erlang:system_flag(time_offset, finalize)` and
+//!   `erlang:monitor(time_offset, clock_service)`
+//!
+//! The clock itself lives in `infrastructure_time_management`; these
+//! functions validate arguments and select the unit.
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ *
+ * Creation productivity increased for code in this file by using AALang and GAB.
+ * See https://github.com/yenrab/AALang-Gab
+ */
+
+use std::sync::mpsc::Receiver;
+use infrastructure_time_management::clock::{get_clock, TimeOffsetEvent, TimeOffsetState, TimeWarpMode};
+use infrastructure_time_management::time_unit::{convert_time_unit, TimeUnit};
+
+/// Time built-in functions
+pub struct TimeBif;
+
+impl TimeBif {
+    /// Parse a time unit argument given as an atom name
+    ///
+    /// # Errors
+    /// Returns `TimeError::BadArgument` for unknown unit names.
+    pub fn unit_from_atom(name: &str) -> Result<TimeUnit, TimeError> {
+        TimeUnit::from_name(name)
+            .ok_or_else(|| TimeError::BadArgument(format!("invalid time unit: {}", name)))
+    }
+
+    /// Parse a time unit argument given as an integer (parts per second)
+    ///
+    /// # Errors
+    /// Returns `TimeError::BadArgument` if `parts` is not positive.
+    pub fn unit_from_integer(parts: i64) -> Result<TimeUnit, TimeError> {
+        TimeUnit::from_parts_per_second(parts)
+            .ok_or_else(|| TimeError::BadArgument(format!("invalid time unit: {}", parts)))
+    }
+
+    /// Erlang monotonic time in the given unit (`erlang:monotonic_time/1`)
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::time::TimeBif;
+    /// use infrastructure_time_management::TimeUnit;
+    ///
+    /// let t1 = TimeBif::monotonic_time(TimeUnit::Native);
+    /// let t2 = TimeBif::monotonic_time(TimeUnit::Native);
+    /// assert!(t2 >= t1);
+    /// ```
+    pub fn monotonic_time(unit: TimeUnit) -> i64 {
+        get_clock().monotonic_time(unit)
+    }
+
+    /// Erlang system time in the given unit (`erlang:system_time/1`)
+    pub fn system_time(unit: TimeUnit) -> i64 {
+        get_clock().system_time(unit)
+    }
+
+    /// OS system time in the given unit (`os:system_time/1`)
+    pub fn os_system_time(unit: TimeUnit) -> i64 {
+        get_clock().os_system_time(unit)
+    }
+
+    /// Current time offset in the given unit (`erlang:time_offset/1`)
+    pub fn time_offset(unit: TimeUnit) -> i64 {
+        get_clock().time_offset(unit)
+    }
+
+    /// Erlang system time as `{MegaSecs, Secs, MicroSecs}` (`erlang:timestamp/0`)
+    pub fn timestamp() -> (i64, i64, i64) {
+        get_clock().timestamp()
+    }
+
+    /// Convert a time value between units (`erlang:convert_time_unit/3`)
+    ///
+    /// # Errors
+    /// Returns `TimeError::BadArgument` if either unit has zero parts per second.
+    ///
+    /// # Examples
+    /// ```
+    /// use usecases_bifs::time::TimeBif;
+    /// use infrastructure_time_management::TimeUnit;
+    ///
+    /// let ms = TimeBif::convert_time_unit(2, TimeUnit::Second, TimeUnit::Millisecond).unwrap();
+    /// assert_eq!(ms, 2000);
+    /// ```
+    pub fn convert_time_unit(time: i64, from: TimeUnit, to: TimeUnit) -> Result<i64, TimeError> {
+        for unit in [from, to] {
+            if unit.parts_per_second() == 0 {
+                return Err(TimeError::BadArgument("invalid time unit: 0".to_string()));
+            }
+        }
+        Ok(convert_time_unit(time, from, to))
+    }
+
+    /// Time warp mode (`erlang:system_info(time_warp_mode)`)
+    pub fn time_warp_mode() -> TimeWarpMode {
+        get_clock().time_warp_mode()
+    }
+
+    /// State of the time offset (`erlang:system_info(time_offset)`)
+    pub fn time_offset_state() -> TimeOffsetState {
+        get_clock().time_offset_state()
+    }
+
+    /// Finalize the time offset (`erlang:system_flag(time_offset, finalize)`)
+    ///
+    /// Returns the state before finalization.
+    pub fn finalize_time_offset() -> TimeOffsetState {
+        get_clock().finalize_time_offset()
+    }
+
+    /// Monitor time offset changes (`erlang:monitor(time_offset, clock_service)`)
+    pub fn monitor_time_offset() -> Receiver<TimeOffsetEvent> {
+        get_clock().subscribe()
+    }
+}
+
+/// Error type for time operations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeError {
+    /// Invalid argument provided
+    BadArgument(String),
+}
+
+impl std::fmt::Display for TimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeError::BadArgument(msg) => write!(f, "Bad argument: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for TimeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_parsing() {
+        assert_eq!(TimeBif::unit_from_atom("native").unwrap(), TimeUnit::Native);
+        assert_eq!(TimeBif::unit_from_atom("second").unwrap(), TimeUnit::Second);
+        assert!(TimeBif::unit_from_atom("hour").is_err());
+        assert_eq!(TimeBif::unit_from_integer(100).unwrap(), TimeUnit::PartsPerSecond(100));
+        assert!(TimeBif::unit_from_integer(0).is_err());
+        assert!(TimeBif::unit_from_integer(-5).is_err());
+    }
+
+    #[test]
+    fn test_convert_time_unit() {
+        assert_eq!(
+            TimeBif::convert_time_unit(1500, TimeUnit::Millisecond, TimeUnit::Second).unwrap(),
+            1
+        );
+        assert_eq!(
+            TimeBif::convert_time_unit(-1, TimeUnit::Native, TimeUnit::Second).unwrap(),
+            -1
+        );
+        assert!(TimeBif::convert_time_unit(1, TimeUnit::PartsPerSecond(0), TimeUnit::Second).is_err());
+    }
+
+    #[test]
+    fn test_system_time_is_monotonic_plus_offset() {
+        let monotonic = TimeBif::monotonic_time(TimeUnit::Native);
+        let offset = TimeBif::time_offset(TimeUnit::Native);
+        let system = TimeBif::system_time(TimeUnit::Native);
+        // Offsets only change in multi time warp mode, and never in a test process
+        assert!(system >= monotonic + offset);
+    }
+
+    #[test]
+    fn test_timestamp_matches_system_time() {
+        let (mega, secs, micro) = TimeBif::timestamp();
+        let seconds = TimeBif::system_time(TimeUnit::Second);
+        assert!(micro < 1_000_000);
+        assert!((seconds - (mega * 1_000_000 + secs)).abs() <= 1);
+    }
+
+    #[test]
+    fn test_os_system_time_close_to_system_time() {
+        let diff = TimeBif::system_time(TimeUnit::Millisecond) - TimeBif::os_system_time(TimeUnit::Millisecond);
+        assert!(diff.abs() < 1_000);
+    }
+}
@@ -0,0 +1,319 @@
+//! Timer Built-in Functions
+//!
+//! Provides the Erlang BIF timers:
+//! - `erlang:send_after/3,4`
+//! - `erlang:start_timer/3,4`
+//! - `erlang:cancel_timer/1,2` with the `async` and `info` options
+//! - `erlang:read_timer/1,2` with the `async` option
+//!
+//! Timers live in the global BIF timer service of `usecases_scheduling`.
+//! Schedulers expire them on each pass of their loop and deliver the timeout
+//! messages, and asynchronous cancel/read results, to the processes.
+//! Absolute times (`{abs, true}`) are Erlang monotonic times in
+//! milliseconds.
+//!
+//! Timer references are returned as `ErlangTerm::Reference` holding the
+//! timer reference number, and taken as that number. The argument types
+//! of the BIFs are checked by the dispatcher against their argument
+//! specifications, so only option lists are validated here.
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 1996-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ *
+ * Creation productivity increased for code in this file by using AALang and GAB.
+ * See https://github.com/yenrab/AALang-Gab
+ */
+
+use adapters_time_management::{CancelTimerOptions, ReadTimerOptions, TimerId, TimerOptions, TimerReply};
+use entities_process::{Eterm, Process, ProcessId};
+use infrastructure_time_management::time_unit::TimeUnit;
+use usecases_scheduling::get_global_bif_timers;
+
+use crate::op::ErlangTerm;
+use crate::time::TimeBif;
+
+/// Timer built-in functions
+pub struct TimerBif;
+
+impl TimerBif {
+    /// Start a timer that sends `msg` to `dest` (`erlang:send_after/3`)
+    ///
+    /// # Arguments
+    /// * `time` - Timeout in milliseconds
+    /// * `dest` - Destination pid
+    /// * `msg` - Message to send when the timer expires
+    ///
+    /// # Returns
+    /// The timer reference, as `ErlangTerm::Reference`
+    pub fn send_after_3(time: u64, dest: ProcessId, msg: Eterm) -> ErlangTerm {
+        let timer_ref = get_global_bif_timers().send_after(time, dest, msg, TimerOptions::default());
+        ErlangTerm::Reference(timer_ref.0)
+    }
+
+    /// Start a timer that sends `msg` to `dest`, with options (`erlang:send_after/4`)
+    ///
+    /// # Arguments
+    /// * `time` - Timeout in milliseconds, or the monotonic time to expire at
+    /// * `options` - List of `{abs, Bool}`
+    ///
+    /// # Returns
+    /// * `Ok(ErlangTerm::Reference)` - The timer reference
+    /// * `Err(TimerError::BadArgument)` - Bad options, or a negative relative time
+    pub fn send_after_4(time: i64, dest: ProcessId, msg: Eterm, options: &ErlangTerm) -> Result<ErlangTerm, TimerError> {
+        Ok(Self::send_after_3(Self::timeout(time, options)?, dest, msg))
+    }
+
+    /// Start a timer that sends `{timeout, TimerRef, msg}` to `dest`
+    /// (`erlang:start_timer/3`)
+    ///
+    /// # Returns
+    /// The timer reference, as `ErlangTerm::Reference`
+    pub fn start_timer_3(time: u64, dest: ProcessId, msg: Eterm) -> ErlangTerm {
+        let timer_ref = get_global_bif_timers().start_timer(time, dest, msg, TimerOptions::default());
+        ErlangTerm::Reference(timer_ref.0)
+    }
+
+    /// Start a timer that sends `{timeout, TimerRef, msg}` to `dest`, with
+    /// options (`erlang:start_timer/4`)
+    ///
+    /// # Arguments
+    /// * `time` - Timeout in milliseconds, or the monotonic time to expire at
+    /// * `options` - List of `{abs, Bool}`
+    pub fn start_timer_4(time: i64, dest: ProcessId, msg: Eterm, options: &ErlangTerm) -> Result<ErlangTerm, TimerError> {
+        Ok(Self::start_timer_3(Self::timeout(time, options)?, dest, msg))
+    }
+
+    /// Cancel a timer (`erlang:cancel_timer/1`)
+    ///
+    /// # Arguments
+    /// * `timer_ref` - Timer reference number
+    ///
+    /// # Returns
+    /// * `ErlangTerm::Integer` - Milliseconds that were left
+    /// * `ErlangTerm::Atom("false")` - The timer had already expired or been cancelled
+    pub fn cancel_timer_1(caller: &Process, timer_ref: u64) -> ErlangTerm {
+        let reply = get_global_bif_timers().cancel_timer(TimerId(timer_ref), caller.id(), CancelTimerOptions::default());
+        reply_term(reply)
+    }
+
+    /// Cancel a timer with options (`erlang:cancel_timer/2`)
+    ///
+    /// With `{async, true}` the result is sent to the caller as
+    /// `{cancel_timer, TimerRef, Result}` and `ok` is returned. With
+    /// `{info, false}` no result is reported at all and `ok` is returned.
+    ///
+    /// # Arguments
+    /// * `options` - List of `{async, Bool}` and `{info, Bool}`
+    pub fn cancel_timer_2(caller: &Process, timer_ref: u64, options: &ErlangTerm) -> Result<ErlangTerm, TimerError> {
+        let mut parsed = CancelTimerOptions::default();
+        for (key, value) in bool_options(options)? {
+            match key {
+                "async" => parsed.asynchronous = value,
+                "info" => parsed.info = value,
+                _ => return Err(TimerError::BadArgument(format!("invalid cancel_timer option: {}", key))),
+            }
+        }
+        let reply = get_global_bif_timers().cancel_timer(TimerId(timer_ref), caller.id(), parsed);
+        Ok(reply_term(reply))
+    }
+
+    /// Read the time left on a timer (`erlang:read_timer/1`)
+    ///
+    /// # Arguments
+    /// * `timer_ref` - Timer reference number
+    ///
+    /// # Returns
+    /// * `ErlangTerm::Integer` - Milliseconds left
+    /// * `ErlangTerm::Atom("false")` - The timer has expired or been cancelled
+    pub fn read_timer_1(caller: &Process, timer_ref: u64) -> ErlangTerm {
+        let reply = get_global_bif_timers().read_timer(TimerId(timer_ref), caller.id(), ReadTimerOptions::default());
+        reply_term(reply)
+    }
+
+    /// Read the time left on a timer with options (`erlang:read_timer/2`)
+    ///
+    /// With `{async, true}` the result is sent to the caller as
+    /// `{read_timer, TimerRef, Result}` and `ok` is returned.
+    ///
+    /// # Arguments
+    /// * `options` - List of `{async, Bool}`
+    pub fn read_timer_2(caller: &Process, timer_ref: u64, options: &ErlangTerm) -> Result<ErlangTerm, TimerError> {
+        let mut parsed = ReadTimerOptions::default();
+        for (key, value) in bool_options(options)? {
+            match key {
+                "async" => parsed.asynchronous = value,
+                _ => return Err(TimerError::BadArgument(format!("invalid read_timer option: {}", key))),
+            }
+        }
+        let reply = get_global_bif_timers().read_timer(TimerId(timer_ref), caller.id(), parsed);
+        Ok(reply_term(reply))
+    }
+
+    /// Relative timeout in milliseconds of `send_after/4` and `start_timer/4`
+    fn timeout(time: i64, options: &ErlangTerm) -> Result<u64, TimerError> {
+        let mut abs = false;
+        for (key, value) in bool_options(options)? {
+            match key {
+                "abs" => abs = value,
+                _ => return Err(TimerError::BadArgument(format!("invalid timer option: {}", key))),
+            }
+        }
+        let timeout = if abs {
+            // Absolute times are Erlang monotonic times; the timer service has its own time base
+            time.saturating_sub(TimeBif::monotonic_time(TimeUnit::Millisecond)).max(0)
+        } else if time >= 0 {
+            time
+        } else {
+            return Err(TimerError::BadArgument(format!("negative timeout: {}", time)));
+        };
+        Ok(timeout as u64)
+    }
+}
+
+/// Parse a list of `{Key, Bool}` options
+fn bool_options(options: &ErlangTerm) -> Result<Vec<(&str, bool)>, TimerError> {
+    let items: &[ErlangTerm] = match options {
+        ErlangTerm::Nil => &[],
+        ErlangTerm::List(items) => items,
+        _ => return Err(TimerError::BadArgument("options must be a list".to_string())),
+    };
+    items
+        .iter()
+        .map(|item| match item {
+            ErlangTerm::Tuple(pair) if pair.len() == 2 => match (&pair[0], &pair[1]) {
+                (ErlangTerm::Atom(key), ErlangTerm::Atom(value)) if value == "true" || value == "false" => {
+                    Ok((key.as_str(), value == "true"))
+                }
+                _ => Err(TimerError::BadArgument(format!("invalid timer option: {:?}", item))),
+            },
+            _ => Err(TimerError::BadArgument(format!("invalid timer option: {:?}", item))),
+        })
+        .collect()
+}
+
+/// Term of a cancel/read result
+fn reply_term(reply: TimerReply) -> ErlangTerm {
+    match reply {
+        TimerReply::Remaining(ms) => ErlangTerm::Integer(ms as i64),
+        TimerReply::NotFound => ErlangTerm::Atom("false".to_string()),
+        TimerReply::Ok => ErlangTerm::Atom("ok".to_string()),
+    }
+}
+
+/// Error type for timer operations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimerError {
+    /// Invalid argument provided
+    BadArgument(String),
+}
+
+impl std::fmt::Display for TimerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimerError::BadArgument(msg) => write!(f, "Bad argument: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for TimerError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entities_process::term_tags::make_small;
+    use infrastructure_utilities::process_table::get_global_process_table;
+    use std::sync::Arc;
+    use usecases_scheduling::erts_bump_timers;
+
+    fn atom(name: &str) -> ErlangTerm {
+        ErlangTerm::Atom(name.to_string())
+    }
+
+    fn option(key: &str, value: bool) -> ErlangTerm {
+        ErlangTerm::Tuple(vec![atom(key), atom(&value.to_string())])
+    }
+
+    #[test]
+    fn test_read_and_cancel_timer() {
+        let caller = Process::new(863001);
+        let ErlangTerm::Reference(timer) = TimerBif::send_after_3(60_000, 1, make_small(1)) else {
+            panic!("send_after did not return a reference");
+        };
+        match TimerBif::read_timer_1(&caller, timer) {
+            ErlangTerm::Integer(ms) => assert!(ms > 59_000 && ms <= 60_000),
+            other => panic!("unexpected read_timer result {:?}", other),
+        }
+        assert!(matches!(TimerBif::cancel_timer_1(&caller, timer), ErlangTerm::Integer(_)));
+        assert_eq!(TimerBif::cancel_timer_1(&caller, timer), atom("false"));
+        assert_eq!(TimerBif::read_timer_1(&caller, timer), atom("false"));
+    }
+
+    #[test]
+    fn test_cancel_timer_options() {
+        let caller = Process::new(863002);
+        let ErlangTerm::Reference(timer) = TimerBif::start_timer_3(60_000, 1, make_small(1)) else {
+            panic!("start_timer did not return a reference");
+        };
+        let no_info = ErlangTerm::List(vec![option("info", false)]);
+        assert_eq!(TimerBif::cancel_timer_2(&caller, timer, &no_info).unwrap(), atom("ok"));
+        let bad = ErlangTerm::List(vec![option("abs", true)]);
+        assert!(TimerBif::cancel_timer_2(&caller, timer, &bad).is_err());
+    }
+
+    #[test]
+    fn test_async_read_timer_reply_is_delivered() {
+        let process = Arc::new(Process::new(863003));
+        get_global_process_table().insert(863003, Arc::clone(&process));
+        let ErlangTerm::Reference(timer) = TimerBif::send_after_3(60_000, 1, make_small(1)) else {
+            panic!("send_after did not return a reference");
+        };
+        let options = ErlangTerm::List(vec![option("async", true)]);
+        assert_eq!(TimerBif::read_timer_2(&process, timer, &options).unwrap(), atom("ok"));
+        erts_bump_timers();
+        assert_eq!(process.message_queue_len(), 1);
+        TimerBif::cancel_timer_1(&process, timer);
+        get_global_process_table().remove(863003);
+    }
+
+    #[test]
+    fn test_bad_arguments() {
+        assert!(TimerBif::send_after_4(-1, 1, 0, &ErlangTerm::Nil).is_err());
+        assert!(TimerBif::start_timer_4(1, 1, 0, &atom("abs")).is_err());
+        let caller = Process::new(863004);
+        assert!(TimerBif::read_timer_2(&caller, 1, &ErlangTerm::List(vec![option("info", true)])).is_err());
+    }
+
+    #[test]
+    fn test_absolute_time() {
+        let caller = Process::new(863005);
+        let now = TimeBif::monotonic_time(TimeUnit::Millisecond);
+        let abs = ErlangTerm::List(vec![option("abs", true)]);
+        let ErlangTerm::Reference(timer) = TimerBif::send_after_4(now + 30_000, 1, 0, &abs).unwrap() else {
+            panic!("send_after did not return a reference");
+        };
+        match TimerBif::cancel_timer_1(&caller, timer) {
+            ErlangTerm::Integer(ms) => assert!(ms > 29_000 && ms <= 30_000),
+            other => panic!("unexpected cancel_timer result {:?}", other),
+        }
+    }
+}
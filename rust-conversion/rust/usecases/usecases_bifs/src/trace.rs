@@ -67,6 +67,8 @@ pub struct TraceFlags {
     pub call: bool,
     /// Enable return tracing
     pub return_trace: bool,
+    /// Enable return_to tracing (return from call-traced functions to the caller)
+    pub return_to: bool,
     /// Enable send tracing
     pub send: bool,
     /// Enable receive tracing
@@ -84,6 +86,7 @@ impl Default for TraceFlags {
         Self {
             call: false,
             return_trace: false,
+            return_to: false,
             send: false,
             receive: false,
             garbage_collection: false,
@@ -988,6 +991,7 @@ mod tests {
         let flags = TraceFlags {
             call: true,
             return_trace: true,
+            return_to: true,
             send: true,
             receive: true,
             garbage_collection: true,
@@ -4,7 +4,9 @@
 //! - References (make_ref)
 //! - Unique integers (with optional monotonic and positive flags)
 //!
-//! This module uses safe Rust atomic operations and thread IDs for unique value generation.
+//! Unique integers are generated from per-thread counters combined with a
+//! thread slot id, as in the C runtime, while monotonic integers share a
+//! single global atomic counter to keep a strict global order.
 
 /*
  * %CopyrightBegin%
@@ -35,45 +37,181 @@
 
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
+use infrastructure_code_loading::decode_ref::{decode_ref, DecodeError};
+use infrastructure_code_loading::encode_ref::{encode_ref, EncodeError, ErlangRef};
+use entities_process::{AliasKey, ProcessId};
+
+/// Bits of the reference counter stored in the first reference number
+///
+/// Matches `ERTS_REF_NUMBER0_MASK` in the C runtime (`_REF_NUM_SIZE` = 18).
+const REF_NUMBER0_BITS: u32 = 18;
+
+/// Reference numbers in a pid reference (a process alias)
+///
+/// A pid reference carries the pid of the process it belongs to in two
+/// extra numbers after the three ordinary ones.
+const PID_REF_NUMBERS: usize = 5;
+
+/// Default node name used until distribution is started
+pub const DEFAULT_NODE_NAME: &str = "nonode@nohost";
+
+/// Node name and creation embedded in newly created references
+static LOCAL_NODE: std::sync::RwLock<Option<(String, u32)>> = std::sync::RwLock::new(None);
+
+/// Serializes the tests that set the local node with the tests that read it
+#[cfg(test)]
+pub(crate) static LOCAL_NODE_TEST_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
 
 /// Reference identifier
 ///
 /// Represents a unique reference in the system.
 /// In Erlang, references are used for various purposes including
 /// process monitoring, message tagging, etc.
+///
+/// A reference carries the node name and creation of the node that made
+/// it, plus three 32-bit reference numbers laid out like the C runtime:
+///
+/// - `numbers[0]`: low 18 bits of the per-thread counter
+/// - `numbers[1]`: next 32 bits of the counter
+/// - `numbers[2]`: thread slot in the low 16 bits, remaining counter bits above
+///
+/// References decoded from other nodes keep their numbers verbatim, so a
+/// reference survives an encode/decode round-trip unchanged.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Reference {
-    /// Thread ID that created the reference
-    thread_id: u32,
-    /// Reference value (atomic counter)
-    value: u64,
-    /// Reference number (for multi-part references)
-    ref_number: u32,
+    /// Node that created the reference
+    node: String,
+    /// Creation of the node that created the reference
+    creation: u32,
+    /// Reference numbers (as sent in `NEWER_REFERENCE_EXT`)
+    numbers: Vec<u32>,
 }
 
 impl Reference {
-    /// Create a new reference
-    fn new(thread_id: u32, value: u64, ref_number: u32) -> Self {
+    /// Create a new local reference from a thread slot and counter value
+    fn new(node: String, creation: u32, thread_id: u32, value: u64) -> Self {
+        let numbers = vec![
+            (value & ((1 << REF_NUMBER0_BITS) - 1)) as u32,
+            (value >> REF_NUMBER0_BITS) as u32,
+            (thread_id & MAX_THREAD_SLOT)
+                | (((value >> (REF_NUMBER0_BITS + 32)) as u32) << THREAD_ID_BITS),
+        ];
         Self {
-            thread_id,
-            value,
-            ref_number,
+            node,
+            creation,
+            numbers,
         }
     }
 
+    /// Create a pid reference for `pid` from a thread slot and counter value
+    fn new_pid_ref(node: String, creation: u32, thread_id: u32, value: u64, pid: ProcessId) -> Self {
+        let mut reference = Self::new(node, creation, thread_id, value);
+        reference.numbers.push(pid as u32);
+        reference.numbers.push((pid >> 32) as u32);
+        reference
+    }
+
+    /// Check if this is a pid reference, as returned by `alias/0,1`
+    pub fn is_pid_ref(&self) -> bool {
+        self.numbers.len() == PID_REF_NUMBERS
+    }
+
+    /// Get the pid a pid reference belongs to
+    pub fn pid(&self) -> Option<ProcessId> {
+        self.is_pid_ref()
+            .then(|| u64::from(self.numbers[3]) | (u64::from(self.numbers[4]) << 32))
+    }
+
+    /// Get the key the reference is stored under in a process's alias table
+    pub fn alias_key(&self) -> AliasKey {
+        let number = |i: usize| self.numbers.get(i).copied().unwrap_or(0);
+        [number(0), number(1), number(2)]
+    }
+
     /// Get the thread ID
     pub fn thread_id(&self) -> u32 {
-        self.thread_id
+        self.numbers.get(2).map_or(0, |n| n & MAX_THREAD_SLOT)
     }
 
     /// Get the reference value
+    ///
+    /// Reassembles the per-thread counter that was spread over the
+    /// reference numbers.
     pub fn value(&self) -> u64 {
-        self.value
+        let n0 = self.numbers.first().copied().unwrap_or(0) as u64;
+        let n1 = self.numbers.get(1).copied().unwrap_or(0) as u64;
+        let n2 = self.numbers.get(2).copied().unwrap_or(0) as u64;
+        (n0 & ((1 << REF_NUMBER0_BITS) - 1))
+            | (n1 << REF_NUMBER0_BITS)
+            | ((n2 >> THREAD_ID_BITS) << (REF_NUMBER0_BITS + 32))
     }
 
     /// Get the reference number
     pub fn ref_number(&self) -> u32 {
-        self.ref_number
+        (self.value() & 0xFFFFFFFF) as u32
+    }
+
+    /// Get the name of the node that created the reference
+    pub fn node(&self) -> &str {
+        &self.node
+    }
+
+    /// Get the creation of the node that created the reference
+    pub fn creation(&self) -> u32 {
+        self.creation
+    }
+
+    /// Get the raw reference numbers
+    pub fn numbers(&self) -> &[u32] {
+        &self.numbers
+    }
+
+    /// Convert to the external (EI) reference representation
+    pub fn to_external(&self) -> ErlangRef {
+        ErlangRef {
+            node: self.node.clone(),
+            len: self.numbers.len() as u16,
+            creation: self.creation,
+            ids: self.numbers.clone(),
+        }
+    }
+
+    /// Build a reference from its external (EI) representation
+    ///
+    /// # Errors
+    /// Returns `UniqueError::InvalidArgument` if the reference has no
+    /// numbers or more than the five allowed by the external format.
+    pub fn from_external(external: &ErlangRef) -> Result<Self, UniqueError> {
+        if external.ids.is_empty() || external.ids.len() > 5 {
+            return Err(UniqueError::InvalidArgument(format!(
+                "reference must have 1 to 5 numbers, got {}",
+                external.ids.len()
+            )));
+        }
+        Ok(Self {
+            node: external.node.clone(),
+            creation: external.creation,
+            numbers: external.ids.clone(),
+        })
+    }
+
+    /// Encode the reference as `NEWER_REFERENCE_EXT`
+    ///
+    /// # Arguments
+    /// * `buf` - Optional buffer to write to (None for size calculation)
+    /// * `index` - Current index in buffer
+    pub fn encode(&self, buf: &mut Option<&mut [u8]>, index: &mut usize) -> Result<(), EncodeError> {
+        encode_ref(buf, index, &self.to_external())
+    }
+
+    /// Decode a reference encoded in any of the external reference formats
+    ///
+    /// # Arguments
+    /// * `buf` - Buffer containing EI-encoded data
+    /// * `index` - Current index in buffer
+    pub fn decode(buf: &[u8], index: &mut usize) -> Result<Self, DecodeError> {
+        let external = decode_ref(buf, index)?;
+        Self::from_external(&external).map_err(|_| DecodeError::InvalidLength)
     }
 }
 
@@ -86,12 +224,49 @@ pub enum UniqueIntegerOption {
     Positive,
 }
 
+/// Number of low bits of a unique integer reserved for the thread slot
+///
+/// Mirrors `ERTS_UNIQUE_INT_THR_ID_BITS` in the C runtime: each scheduler
+/// owns a slot and only ever increments its own counter, so the slot id in
+/// the low bits keeps values from different threads disjoint.
+const THREAD_ID_BITS: u32 = 16;
+
+/// Highest thread slot that gets a private counter
+///
+/// Threads beyond this limit share slot 0, which is backed by an atomic
+/// counter in the same way the C runtime handles non-scheduler threads.
+const MAX_THREAD_SLOT: u32 = (1 << THREAD_ID_BITS) - 1;
+
+/// Mask applied to per-thread counters so that the combined value stays
+/// within the non-negative range of an `i64`.
+const COUNTER_MASK: u64 = (1 << (63 - THREAD_ID_BITS)) - 1;
+
+/// Next thread slot to hand out (slot 0 is the shared slot)
+static NEXT_THREAD_SLOT: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(1);
+
+/// Counter backing the shared slot 0
+static SHARED_SLOT_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// Thread slot assigned to the current thread (`None` until first use)
+    static THREAD_SLOT: std::cell::Cell<Option<u32>> = const { std::cell::Cell::new(None) };
+    /// Per-thread unique integer counter (only touched by the owning thread)
+    static THREAD_COUNTER: std::cell::Cell<u64> = const { std::cell::Cell::new(0) };
+    /// Per-thread reference counter (`None` until the first reference)
+    static THREAD_REF_COUNTER: std::cell::Cell<Option<u64>> = const { std::cell::Cell::new(None) };
+}
+
 /// Unique integer generator
 ///
-/// Generates unique integers using thread IDs and atomic counters.
-/// Supports both monotonic and non-monotonic generation.
+/// Generates unique integers the same way `erl_bif_unique.c` does:
+///
+/// - Non-monotonic values combine a per-thread counter with the thread's
+///   slot id. Each thread only increments its own counter, so no shared
+///   cache line is written on the fast path.
+/// - Monotonic values come from a single global atomic counter, giving a
+///   strict global order across all threads.
 pub struct UniqueIntegerGenerator {
-    /// Global counter for unique integers
+    /// Reference counter backing the shared thread slot 0
     global_counter: AtomicU64,
     /// Monotonic counter for strictly increasing values
     monotonic_counter: AtomicU64,
@@ -99,6 +274,12 @@ pub struct UniqueIntegerGenerator {
     ref_init_value: u64,
 }
 
+impl Default for UniqueIntegerGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl UniqueIntegerGenerator {
     /// Create a new unique integer generator
     ///
@@ -126,60 +307,72 @@ impl UniqueIntegerGenerator {
         value
     }
 
-    /// Get a thread ID for the current thread
+    /// Get the unique integer slot of the current thread
     ///
-    /// Uses thread-local storage to assign unique IDs to threads.
-    /// In a full implementation, this would map to scheduler IDs.
-    fn get_thread_id() -> u32 {
-        use std::cell::Cell;
-        use std::sync::atomic::{AtomicU32, Ordering};
-        
-        static NEXT_THREAD_ID: AtomicU32 = AtomicU32::new(1);
-        
-        thread_local! {
-            static THREAD_ID: Cell<Option<u32>> = Cell::new(None);
-        }
-        
-        THREAD_ID.with(|id| {
-            if let Some(tid) = id.get() {
-                tid
-            } else {
-                // Assign a unique ID to this thread
-                let tid = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
-                id.set(Some(tid));
-                tid
+    /// Slots `1..=MAX_THREAD_SLOT` are private to one thread each; once
+    /// they are exhausted, new threads are placed in the shared slot 0.
+    fn thread_slot() -> u32 {
+        THREAD_SLOT.with(|slot| {
+            if let Some(id) = slot.get() {
+                return id;
             }
+            let id = NEXT_THREAD_SLOT
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |next| {
+                    if next <= MAX_THREAD_SLOT {
+                        Some(next + 1)
+                    } else {
+                        None
+                    }
+                })
+                .unwrap_or(0);
+            slot.set(Some(id));
+            id
         })
     }
 
+    /// Produce the raw (non-negative) unique value for the current thread
+    ///
+    /// The result is `(counter << THREAD_ID_BITS) | slot`, which is the
+    /// layout used by the C runtime's `raw_unique_integer`.
+    fn raw_unique_integer() -> u64 {
+        let slot = Self::thread_slot();
+        let counter = if slot == 0 {
+            SHARED_SLOT_COUNTER.fetch_add(1, Ordering::Relaxed)
+        } else {
+            THREAD_COUNTER.with(|counter| {
+                let value = counter.get();
+                counter.set(value.wrapping_add(1));
+                value
+            })
+        };
+        ((counter & COUNTER_MASK) << THREAD_ID_BITS) | slot as u64
+    }
+
     /// Generate a unique integer
     ///
+    /// Only the calling thread's own counter is touched, so concurrent
+    /// callers never contend with each other.
+    ///
     /// # Arguments
     /// * `positive` - If true, only generate positive integers
     ///
     /// # Returns
     /// Unique integer value
     pub fn unique_integer(&self, positive: bool) -> i64 {
-        let thread_id = Self::get_thread_id();
-        let unique = self.global_counter.fetch_add(1, Ordering::Relaxed);
-        
-        // Combine thread ID and unique value
-        let combined = ((thread_id as u64) << 32) | (unique & 0xFFFFFFFF);
-        
-        let result = if positive {
-            // Ensure positive (add 1 to avoid 0)
-            (combined as i64).saturating_add(1).max(1)
+        let raw = Self::raw_unique_integer() as i64;
+        if positive {
+            // Shift by one so that zero is never produced
+            raw + 1
         } else {
-            // Can be negative, but ensure it's within i64 range
-            combined as i64
-        };
-        
-        result
+            raw
+        }
     }
 
     /// Generate a monotonic unique integer
     ///
-    /// Generates strictly increasing unique integers.
+    /// Generates strictly increasing unique integers. All threads draw
+    /// from the same atomic counter, so the order is global: a value
+    /// observed by any thread is smaller than every value generated after it.
     ///
     /// # Arguments
     /// * `positive` - If true, only generate positive integers
@@ -187,7 +380,7 @@ impl UniqueIntegerGenerator {
     /// # Returns
     /// Monotonic unique integer value
     pub fn unique_integer_monotonic(&self, positive: bool) -> i64 {
-        let raw = self.monotonic_counter.fetch_add(1, Ordering::AcqRel);
+        let raw = self.monotonic_counter.fetch_add(1, Ordering::SeqCst);
         
         if positive {
             // Monotonic positive: start from 1
@@ -196,23 +389,39 @@ impl UniqueIntegerGenerator {
             // Monotonic with offset to allow negative values
             // Use MIN_SMALL equivalent offset
             const MIN_SMALL: i64 = i64::MIN;
-            let offset = MIN_SMALL;
-            (raw as i64).saturating_add(offset)
+            (raw as i64).wrapping_add(MIN_SMALL)
         }
     }
 
     /// Create a new reference
     ///
-    /// Generates a unique reference identifier.
+    /// Generates a unique reference identifier. Like the C runtime, each
+    /// thread increments its own reference counter (seeded from the
+    /// generator's start value) and tags the result with its thread slot.
     ///
     /// # Returns
     /// New reference
     pub fn make_ref(&self) -> Reference {
-        let thread_id = Self::get_thread_id();
-        let value = self.global_counter.fetch_add(1, Ordering::Relaxed);
-        let ref_number = (value & 0xFFFFFFFF) as u32;
-        
-        Reference::new(thread_id, value, ref_number)
+        let thread_id = Self::thread_slot();
+        let value = if thread_id == 0 {
+            self.global_counter.fetch_add(1, Ordering::Relaxed)
+        } else {
+            THREAD_REF_COUNTER.with(|counter| {
+                let value = counter.get().unwrap_or(self.ref_init_value);
+                counter.set(Some(value.wrapping_add(1)));
+                value
+            })
+        };
+        let (node, creation) = UniqueBif::local_node();
+
+        Reference::new(node, creation, thread_id, value)
+    }
+
+    /// Create a new pid reference belonging to `pid`
+    pub fn make_pid_ref(&self, pid: ProcessId) -> Reference {
+        let plain = self.make_ref();
+        let (node, creation) = (plain.node.clone(), plain.creation);
+        Reference::new_pid_ref(node, creation, plain.thread_id(), plain.value(), pid)
     }
 }
 
@@ -222,13 +431,32 @@ impl UniqueIntegerGenerator {
 static GLOBAL_GENERATOR: std::sync::OnceLock<UniqueIntegerGenerator> = std::sync::OnceLock::new();
 
 fn get_generator() -> &'static UniqueIntegerGenerator {
-    GLOBAL_GENERATOR.get_or_init(|| UniqueIntegerGenerator::new())
+    GLOBAL_GENERATOR.get_or_init(UniqueIntegerGenerator::new)
 }
 
 /// Unique BIF operations
 pub struct UniqueBif;
 
 impl UniqueBif {
+    /// Set the node name and creation embedded in new references
+    ///
+    /// Called when distribution is started (or the node is renamed).
+    /// References created earlier keep the node they were created on.
+    pub fn set_local_node(node: &str, creation: u32) {
+        let mut local = LOCAL_NODE.write().unwrap_or_else(|e| e.into_inner());
+        *local = Some((node.to_string(), creation));
+    }
+
+    /// Get the node name and creation embedded in new references
+    ///
+    /// Defaults to `nonode@nohost` with creation 0.
+    pub fn local_node() -> (String, u32) {
+        let local = LOCAL_NODE.read().unwrap_or_else(|e| e.into_inner());
+        local
+            .clone()
+            .unwrap_or_else(|| (DEFAULT_NODE_NAME.to_string(), 0))
+    }
+
     /// Create a new reference
     ///
     /// Equivalent to `make_ref/0` in Erlang.
@@ -263,6 +491,11 @@ impl UniqueBif {
         get_generator().make_ref()
     }
 
+    /// Create a new pid reference belonging to `pid` (used for aliases)
+    pub fn make_pid_ref(pid: ProcessId) -> Reference {
+        get_generator().make_pid_ref(pid)
+    }
+
     /// Generate a unique integer
     ///
     /// Equivalent to `erlang:unique_integer/0` in Erlang.
@@ -382,6 +615,26 @@ impl std::error::Error for UniqueError {}
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_make_pid_ref() {
+        let pid = (7u64 << 32) | 4711;
+        let alias = UniqueBif::make_pid_ref(pid);
+        assert!(alias.is_pid_ref());
+        assert_eq!(alias.pid(), Some(pid));
+        assert!(!UniqueBif::make_ref().is_pid_ref());
+        assert_eq!(UniqueBif::make_ref().pid(), None);
+
+        let mut size = 0;
+        alias.encode(&mut None, &mut size).unwrap();
+        let mut buf = vec![0u8; size];
+        let mut index = 0;
+        alias.encode(&mut Some(&mut buf[..]), &mut index).unwrap();
+        let mut index = 0;
+        let decoded = Reference::decode(&buf, &mut index).unwrap();
+        assert_eq!(decoded, alias);
+        assert_eq!(decoded.alias_key(), alias.alias_key());
+    }
+
     #[test]
     fn test_make_ref() {
         let ref1 = UniqueBif::make_ref();
@@ -685,5 +938,234 @@ mod tests {
         // Should be the same reference (singleton)
         assert!(std::ptr::eq(gen1, gen2));
     }
+
+    #[test]
+    fn test_concurrent_unique_integer_many_threads() {
+        use std::collections::HashSet;
+        use std::thread;
+
+        let handles: Vec<_> = (0..16)
+            .map(|_| {
+                thread::spawn(|| {
+                    (0..5000)
+                        .map(|_| UniqueBif::unique_integer())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let mut seen = HashSet::new();
+        for handle in handles {
+            for value in handle.join().unwrap() {
+                assert!(seen.insert(value), "duplicate unique integer {}", value);
+            }
+        }
+        assert_eq!(seen.len(), 16 * 5000);
+    }
+
+    #[test]
+    fn test_concurrent_unique_integer_positive() {
+        use std::collections::HashSet;
+        use std::thread;
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                thread::spawn(|| {
+                    (0..2000)
+                        .map(|_| {
+                            UniqueBif::unique_integer_with_options(&[UniqueIntegerOption::Positive])
+                                .unwrap()
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let mut seen = HashSet::new();
+        for handle in handles {
+            for value in handle.join().unwrap() {
+                assert!(value > 0);
+                assert!(seen.insert(value));
+            }
+        }
+    }
+
+    #[test]
+    fn test_concurrent_monotonic_global_order() {
+        use std::collections::HashSet;
+        use std::thread;
+
+        let generator = std::sync::Arc::new(UniqueIntegerGenerator::new());
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let generator = generator.clone();
+                thread::spawn(move || {
+                    (0..2000)
+                        .map(|_| generator.unique_integer_monotonic(true))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let mut all = Vec::new();
+        for handle in handles {
+            let values = handle.join().unwrap();
+            // Each thread observes a strictly increasing sequence
+            assert!(values.windows(2).all(|w| w[0] < w[1]));
+            all.extend(values);
+        }
+
+        // Globally the values are unique and form a dense range starting at 1
+        let unique: HashSet<_> = all.iter().copied().collect();
+        assert_eq!(unique.len(), all.len());
+        assert_eq!(*all.iter().min().unwrap(), 1);
+        assert_eq!(*all.iter().max().unwrap(), all.len() as i64);
+    }
+
+    #[test]
+    fn test_monotonic_happens_before_across_threads() {
+        use std::thread;
+
+        // A value generated after joining another thread must be greater
+        // than every value that thread produced.
+        let generator = std::sync::Arc::new(UniqueIntegerGenerator::new());
+        let worker = {
+            let generator = generator.clone();
+            thread::spawn(move || generator.unique_integer_monotonic(false))
+        };
+        let observed = worker.join().unwrap();
+        assert!(generator.unique_integer_monotonic(false) > observed);
+    }
+
+    #[test]
+    fn test_unique_integer_encodes_thread_slot() {
+        use std::thread;
+
+        let main_slot = UniqueIntegerGenerator::thread_slot();
+        let value = UniqueBif::unique_integer();
+        assert_eq!((value as u64) & MAX_THREAD_SLOT as u64, main_slot as u64);
+
+        let other_slot = thread::spawn(UniqueIntegerGenerator::thread_slot).join().unwrap();
+        assert_ne!(main_slot, other_slot);
+    }
+
+    #[test]
+    fn test_reference_layout() {
+        let reference = UniqueBif::make_ref();
+        assert_eq!(reference.numbers().len(), 3);
+        assert_eq!(reference.thread_id(), UniqueIntegerGenerator::thread_slot());
+        assert!(reference.numbers()[0] < (1 << REF_NUMBER0_BITS));
+
+        let next = UniqueBif::make_ref();
+        assert_eq!(next.value(), reference.value().wrapping_add(1));
+    }
+
+    #[test]
+    fn test_reference_value_roundtrip_through_numbers() {
+        let reference = Reference::new("a@b".to_string(), 7, 42, u64::MAX - 3);
+        assert_eq!(reference.value(), u64::MAX - 3);
+        assert_eq!(reference.thread_id(), 42);
+        assert_eq!(reference.node(), "a@b");
+        assert_eq!(reference.creation(), 7);
+    }
+
+    #[test]
+    fn test_reference_external_roundtrip() {
+        let reference = Reference::new("node@host".to_string(), 0x1234_5678, 3, 0xDEAD_BEEF_CAFE);
+        let mut size = 0;
+        reference.encode(&mut None, &mut size).unwrap();
+
+        let mut buf = vec![0u8; size];
+        let mut index = 0;
+        reference.encode(&mut Some(&mut buf), &mut index).unwrap();
+        assert_eq!(index, size);
+        assert_eq!(buf[0], infrastructure_code_loading::constants::ERL_NEWER_REFERENCE_EXT);
+
+        let mut index = 0;
+        let decoded = Reference::decode(&buf, &mut index).unwrap();
+        assert_eq!(index, size);
+        assert_eq!(decoded, reference);
+        assert_eq!(decoded.value(), 0xDEAD_BEEF_CAFE);
+    }
+
+    #[test]
+    fn test_reference_foreign_numbers_preserved() {
+        let external = ErlangRef {
+            node: "other@host".to_string(),
+            len: 5,
+            creation: 9,
+            ids: vec![0xFFFF_FFFF, 1, 2, 3, 4],
+        };
+        let reference = Reference::from_external(&external).unwrap();
+        assert_eq!(reference.to_external(), external);
+    }
+
+    #[test]
+    fn test_reference_from_external_invalid() {
+        let external = ErlangRef {
+            node: "other@host".to_string(),
+            len: 0,
+            creation: 1,
+            ids: vec![],
+        };
+        assert!(Reference::from_external(&external).is_err());
+    }
+
+    /// Sets the local node for a test and restores the previous setting when
+    /// dropped, also if the test fails
+    struct LocalNodeGuard {
+        previous: Option<(String, u32)>,
+        _lock: std::sync::MutexGuard<'static, ()>,
+    }
+
+    impl LocalNodeGuard {
+        fn set(node: &str, creation: u32) -> Self {
+            let lock = LOCAL_NODE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let previous = LOCAL_NODE.read().unwrap_or_else(|e| e.into_inner()).clone();
+            UniqueBif::set_local_node(node, creation);
+            Self { previous, _lock: lock }
+        }
+    }
+
+    impl Drop for LocalNodeGuard {
+        fn drop(&mut self) {
+            *LOCAL_NODE.write().unwrap_or_else(|e| e.into_inner()) = self.previous.take();
+        }
+    }
+
+    #[test]
+    fn test_set_local_node() {
+        let guard = LocalNodeGuard::set("test_node@localhost", 31);
+        let previous = guard.previous.clone();
+        let reference = UniqueBif::make_ref();
+        assert_eq!(reference.node(), "test_node@localhost");
+        assert_eq!(reference.creation(), 31);
+
+        drop(guard);
+        assert_eq!(*LOCAL_NODE.read().unwrap(), previous);
+    }
+
+    #[test]
+    fn test_concurrent_make_ref_thread_slots() {
+        use std::collections::HashSet;
+        use std::thread;
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                thread::spawn(|| (0..1000).map(|_| UniqueBif::make_ref()).collect::<Vec<_>>())
+            })
+            .collect();
+
+        let mut seen = HashSet::new();
+        let mut slots = HashSet::new();
+        for handle in handles {
+            let refs = handle.join().unwrap();
+            slots.insert(refs[0].thread_id());
+            for reference in refs {
+                assert!(seen.insert(reference));
+            }
+        }
+        assert_eq!(slots.len(), 8);
+    }
 }
 
@@ -11,8 +11,16 @@ use usecases_bifs::counters::{CountersBif, CounterRef, CountersError};
 use usecases_bifs::unique::{UniqueBif, Reference, UniqueIntegerOption};
 use usecases_bifs::op::{OpBif, OpError, ErlangTerm};
 use usecases_bifs::guard::{GuardBif, GuardError};
-use usecases_bifs::lists::{ListsBif, ListsError};
+use usecases_bifs::lists::{ListsBif, ListsTrap};
 use usecases_bifs::persistent::{PersistentBif, PersistentError};
+use usecases_bifs::checksum::{ChecksumBif, Crc32cContext, XxHash64Context};
+use usecases_bifs::crypto::{CryptoBif, HashAlgorithm};
+use usecases_bifs::regex::{RegexBif, RunOption, RunResult, SplitOption, CompileOption};
+use usecases_bifs::binary::{BinaryBif, MatchOption, Part, ReplaceOption};
+use usecases_bifs::maps::{IteratorOrder, MapsBif};
+use usecases_bifs::display::{DisplayBif, DisplayError};
+use usecases_bifs::seq_trace::{DistDestination, SeqTraceBif, SeqTraceFlag, SeqTraceInfo};
+use entities_data_handling::binary::RefcBinary;
 use usecases_nif_compilation::{NifCompiler, CompileOptions};
 use std::fs;
 use std::io::Write;
@@ -90,6 +98,7 @@ fn test_full_compilation_and_loading_pipeline() {
         release: false,
         cargo_flags: Vec::new(),
         output_dir: None,
+        offline: None,
     };
     
     let compile_result = compiler.compile(&source_path, compile_options);
@@ -197,6 +206,7 @@ fn test_verification_before_compilation() {
         release: false,
         cargo_flags: Vec::new(),
         output_dir: None,
+        offline: None,
     };
     
     // Compilation should fail due to unsafe code
@@ -225,6 +235,7 @@ fn test_compilation_without_verification() {
         release: false,
         cargo_flags: Vec::new(),
         output_dir: None,
+        offline: None,
     };
     
     // Compilation should proceed (but will fail because we can't actually compile without cargo setup)
@@ -275,6 +286,7 @@ fn test_compilation_options_integration() {
         release: true,
         cargo_flags: Vec::new(),
         output_dir: None,
+        offline: None,
     };
     
     // This might succeed or fail depending on cargo availability
@@ -287,6 +299,7 @@ fn test_compilation_options_integration() {
         release: false,
         cargo_flags: Vec::new(),
         output_dir: Some(output_dir.clone()),
+        offline: None,
     };
     
     let result = compiler.compile(&source_path, output_options);
@@ -355,13 +368,8 @@ fn test_compilation_with_various_rust_constructs() {
     // Test compilation with various Rust language constructs
     let temp_dir = tempfile::tempdir().unwrap();
     
+    // create_test_rust_library adds the safety marker
     let complex_code = r#"
-/// Safety marker
-#[no_mangle]
-pub extern "C" fn rust_safe_library_marker() -> u32 {
-    0x53414645
-}
-
 /// Function with match
 #[no_mangle]
 pub extern "C" fn match_function(x: i32) -> i32 {
@@ -2074,8 +2082,8 @@ fn test_lists_bif_member_2_workflow() {
 }
 
 #[test]
-fn test_lists_bif_member_2_with_type_coercion() {
-    // Test member with type coercion (Integer == Float)
+fn test_lists_bif_member_2_exact_equality() {
+    // member uses =:=, so there is no type coercion (Integer /= Float)
     let list = ErlangTerm::List(vec![
         ErlangTerm::Integer(1),
         ErlangTerm::Float(2.0),
@@ -2086,9 +2094,9 @@ fn test_lists_bif_member_2_with_type_coercion() {
     let result1 = ListsBif::member_2(&ErlangTerm::Integer(1), &list).unwrap();
     assert_eq!(result1, ErlangTerm::Atom("true".to_string()));
     
-    // Integer(2) should match Float(2.0) due to structural equality
+    // Integer(2) should not match Float(2.0)
     let result2 = ListsBif::member_2(&ErlangTerm::Integer(2), &list).unwrap();
-    assert_eq!(result2, ErlangTerm::Atom("true".to_string()));
+    assert_eq!(result2, ErlangTerm::Atom("false".to_string()));
 }
 
 #[test]
@@ -3111,3 +3119,301 @@ fn test_load_bif_call_on_load_function_not_supported() {
         panic!("Expected NotSupported error");
     }
 }
+
+#[test]
+fn test_time_bifs_integration() {
+    use infrastructure_time_management::TimeUnit;
+    use usecases_bifs::time::{TimeBif, TimeError};
+
+    let unit = TimeBif::unit_from_atom("millisecond").unwrap();
+    let t1 = TimeBif::monotonic_time(unit);
+    std::thread::sleep(std::time::Duration::from_millis(2));
+    let t2 = TimeBif::monotonic_time(unit);
+    assert!(t2 > t1);
+
+    let native = TimeBif::system_time(TimeUnit::Native);
+    let seconds = TimeBif::convert_time_unit(native, TimeUnit::Native, TimeUnit::Second).unwrap();
+    assert!(seconds > 1_600_000_000);
+
+    assert!(matches!(TimeBif::unit_from_atom("minute"), Err(TimeError::BadArgument(_))));
+}
+
+#[test]
+fn test_checksum_fragment_integrity() {
+    // Checksum a payload split into distribution fragments
+    let payload: Vec<u8> = (0..10_000u32).map(|i| (i % 253) as u8).collect();
+    let mut crc = Crc32cContext::new();
+    let mut hash = XxHash64Context::new(0);
+    for fragment in payload.chunks(1460) {
+        crc.update(fragment);
+        hash.update(fragment);
+    }
+    assert_eq!(crc.finalize(), ChecksumBif::crc32c(&payload));
+    assert_eq!(hash.finalize(), ChecksumBif::xxhash64(&payload, 0));
+
+    // A single flipped bit is detected by both
+    let mut corrupted = payload.clone();
+    corrupted[5000] ^= 0x10;
+    assert_ne!(ChecksumBif::crc32c(&corrupted), ChecksumBif::crc32c(&payload));
+    assert_ne!(ChecksumBif::xxhash64(&corrupted, 0), ChecksumBif::xxhash64(&payload, 0));
+}
+
+#[test]
+fn test_crypto_challenge_digest() {
+    // Both sides of a handshake derive the same digest from a random
+    // challenge and a shared secret, and reject a different secret
+    let challenge = CryptoBif::strong_rand_bytes(4).unwrap();
+    let algorithm = HashAlgorithm::from_name("sha256").unwrap();
+    let digest = CryptoBif::mac(algorithm, b"cookie", &challenge);
+
+    let mut verifier = CryptoBif::mac_init(algorithm, b"cookie");
+    verifier.update(&challenge);
+    assert_eq!(CryptoBif::hash_equals(&digest, &verifier.finalize()), Ok(true));
+    let forged = CryptoBif::mac(algorithm, b"wrong cookie", &challenge);
+    assert_eq!(CryptoBif::hash_equals(&digest, &forged), Ok(false));
+}
+
+#[test]
+fn test_re_compile_run_replace_split() {
+    let re = RegexBif::compile_with(r"(?<key>[a-z]+)\s*=\s*(?<value>\w*)", &[CompileOption::Caseless]).unwrap();
+    let config = "Name = beam; Port = 4369; Empty =";
+
+    match RegexBif::run_with(&re, config, &[RunOption::Global]).unwrap() {
+        RunResult::Global(matches) => assert_eq!(matches.len(), 3),
+        other => panic!("unexpected result {:?}", other),
+    }
+    let normalized = RegexBif::replace(&re, config, r"\1:\2", &[RunOption::Global]).unwrap();
+    assert_eq!(normalized, "Name:beam; Port:4369; Empty:");
+
+    let separator = RegexBif::compile_simple(r";\s*").unwrap();
+    assert_eq!(
+        RegexBif::split(&separator, &normalized, &[SplitOption::Parts(2)]).unwrap(),
+        vec!["Name:beam", "Port:4369; Empty:"]
+    );
+}
+
+#[test]
+fn test_binary_split_and_replace_headers() {
+    // Split a CRLF-delimited header block into lines without copying, then
+    // rewrite the separators of one line
+    let block = RefcBinary::new(b"Host: beam\r\nPort: 4369\r\n\r\n".to_vec());
+    let crlf = BinaryBif::compile_pattern(&[b"\r\n"]).unwrap();
+    let lines = BinaryBif::split(
+        &block,
+        &crlf,
+        &[usecases_bifs::binary::SplitOption::Global, usecases_bifs::binary::SplitOption::TrimAll],
+    )
+    .unwrap();
+    assert_eq!(lines.len(), 2);
+    assert!(lines.iter().all(|line| line.shares_storage(&block)));
+
+    let separators = BinaryBif::compile_pattern(&[b": ", b":"]).unwrap();
+    let port = lines[1].data();
+    assert_eq!(BinaryBif::match_(port, &separators, &[]).unwrap(), Some((4, 2)));
+    let scope = [MatchOption::Scope(Part::new(0, 4))];
+    assert_eq!(BinaryBif::match_(port, &separators, &scope).unwrap(), None);
+    let rewritten = BinaryBif::replace(port, &separators, b"=<>", &[ReplaceOption::InsertReplaced(vec![2])]).unwrap();
+    assert_eq!(rewritten, b"Port=<: >4369");
+
+    let value = BinaryBif::part(&lines[1], Part::new(port.len(), -4)).unwrap();
+    assert_eq!(value.data(), b"4369");
+    assert_eq!(BinaryBif::copy(value.data(), 1).data(), b"4369");
+    let names: Vec<&[u8]> = lines.iter().map(|line| line.data()).collect();
+    assert_eq!(BinaryBif::longest_common_prefix(&names).unwrap(), 0);
+}
+
+#[test]
+fn test_lists_yielding_keystore_and_seq() {
+    // Build a long property list, update it, then look an entry up with a
+    // small reduction budget as a scheduler would
+    let ErlangTerm::List(keys) = ListsBif::seq_3(&ErlangTerm::Integer(1), &ErlangTerm::Integer(999), &ErlangTerm::Integer(2)).unwrap() else {
+        panic!("expected a list");
+    };
+    let entries: Vec<ErlangTerm> = keys
+        .into_iter()
+        .map(|key| ErlangTerm::Tuple(vec![key, ErlangTerm::Atom("old".to_string())]))
+        .collect();
+    let updated = ErlangTerm::Tuple(vec![ErlangTerm::Integer(999), ErlangTerm::Atom("new".to_string())]);
+    let list = ListsBif::keystore_4(&ErlangTerm::Integer(999), &ErlangTerm::Integer(1), &ErlangTerm::List(entries), &updated).unwrap();
+
+    let mut trap = ListsBif::keyfind_3_yielding(&ErlangTerm::Float(999.0), &ErlangTerm::Integer(1), &list, 20).unwrap();
+    let mut yields = 0;
+    let result = loop {
+        match trap {
+            ListsTrap::Done { result, .. } => break result,
+            ListsTrap::Yield(continuation) => {
+                yields += 1;
+                trap = ListsBif::resume(continuation, 20);
+            }
+        }
+    };
+    assert_eq!(result, updated);
+    assert_eq!(yields, 2);
+
+    // member is exact, so the float key is not a member of the keys
+    let keys = ListsBif::seq_2(&ErlangTerm::Integer(995), &ErlangTerm::Integer(999)).unwrap();
+    assert_eq!(ListsBif::member_2(&ErlangTerm::Float(999.0), &keys).unwrap(), ErlangTerm::Atom("false".to_string()));
+    assert_eq!(ListsBif::member_2(&ErlangTerm::Integer(999), &keys).unwrap(), ErlangTerm::Atom("true".to_string()));
+}
+
+#[test]
+fn test_maps_merge_and_resumable_ordered_iteration() {
+    use entities_data_handling::term_hashing::Term;
+    use entities_data_handling::Map;
+
+    // Count word occurrences from two sources
+    let counts = |pairs: &[(u32, i64)]| Map::from_list(pairs.iter().map(|&(w, n)| (Term::Atom(w), Term::Small(n))).collect());
+    let add = |_: &Term, a: &Term, b: &Term| match (a, b) {
+        (Term::Small(a), Term::Small(b)) => Term::Small(a + b),
+        _ => unreachable!(),
+    };
+    let totals = MapsBif::merge_with(add, &counts(&[(3, 1), (1, 2)]), &counts(&[(2, 5), (3, 4)]));
+    let totals = MapsBif::update_with_init(&Term::Atom(4), |v| v.clone(), Term::Small(1), &totals);
+
+    // Walk the totals in key order, one entry per "scheduling slot", keeping
+    // the iterator as a term in between
+    let mut state = Term::Tuple(vec![MapsBif::iterator(&totals, IteratorOrder::Ordered)]);
+    let mut visited = Vec::new();
+    loop {
+        let Term::Tuple(saved) = &state else { unreachable!() };
+        match MapsBif::next(&saved[0]).unwrap() {
+            Some((key, value, rest)) => {
+                visited.push((key, value));
+                state = Term::Tuple(vec![rest]);
+            }
+            None => break,
+        }
+    }
+    assert_eq!(
+        visited,
+        vec![
+            (Term::Atom(1), Term::Small(2)),
+            (Term::Atom(2), Term::Small(5)),
+            (Term::Atom(3), Term::Small(5)),
+            (Term::Atom(4), Term::Small(1)),
+        ]
+    );
+
+    let (taken, rest) = MapsBif::take(&Term::Atom(3), &totals).unwrap();
+    assert_eq!(taken, Term::Small(5));
+    let sum = MapsBif::fold(|_, v, acc| if let Term::Small(v) = v { acc + v } else { acc }, 0, &rest).unwrap();
+    assert_eq!(sum, 8);
+}
+
+#[test]
+fn test_display_bifs_write_terms_and_strings() {
+    use entities_data_handling::term_hashing::Term;
+
+    let text = Term::List {
+        head: Box::new(Term::Binary { data: b"count: ".to_vec(), bit_offset: 0, bit_size: 56 }),
+        tail: Box::new(Term::Nil),
+    };
+    let nested = (1..=20).rev().fold(Term::Nil, |tail, n| Term::List {
+        head: Box::new(Term::Tuple(vec![Term::Small(n), Term::Float(n as f64 / 2.0)])),
+        tail: Box::new(tail),
+    });
+
+    let mut out = Vec::new();
+    assert_eq!(DisplayBif::display_string_to(&mut out, &text), Ok(true));
+    assert_eq!(DisplayBif::display_to(&mut out, &Term::Small(20)), Ok(true));
+    let printed = DisplayBif::debug_display_to(&mut out, &Term::Tuple(vec![Term::Nil, Term::Small(-1)])).unwrap();
+    assert_eq!(printed, "{[],-1}\n");
+    assert_eq!(String::from_utf8(out).unwrap(), "count: 20\n{[],-1}\n");
+
+    assert_eq!(DisplayBif::format_depth(&nested, 4).unwrap(), "[{1,0.5},{2,...},{...}|...]");
+    assert_eq!(DisplayBif::display_string_to(&mut Vec::new(), &nested), Err(DisplayError::BadArgument));
+}
+
+#[test]
+fn test_seq_trace_follows_request_reply_across_nodes() {
+    use entities_data_handling::term_hashing::Term;
+    use entities_process::Process;
+
+    let client = Process::new(10);
+    let server = Process::new(11);
+    let remote_server = Process::new(12);
+    SeqTraceBif::set_system_tracer(Some(99));
+    SeqTraceBif::set_token_label(&client, 4);
+    SeqTraceBif::set_token_flag(&client, SeqTraceFlag::Send, true);
+    SeqTraceBif::set_token_flag(&client, SeqTraceFlag::Receive, true);
+
+    // client -> server -> (other node) remote_server -> client
+    SeqTraceBif::send(&client, &server, 1);
+    SeqTraceBif::receive(&server, |_| true).unwrap();
+    let remote = Term::Pid { node: 1, id: 12, serial: 0, creation: 1 };
+    let control = SeqTraceBif::dist_send_control(&server, DistDestination::Pid(remote), 2);
+    SeqTraceBif::dist_deliver(&remote_server, &control, 2).unwrap();
+    SeqTraceBif::receive(&remote_server, |_| true).unwrap();
+    SeqTraceBif::send(&remote_server, &client, 3);
+    SeqTraceBif::receive(&client, |_| true).unwrap();
+
+    let events = SeqTraceBif::take_events(99);
+    SeqTraceBif::set_system_tracer(None);
+    let serials: Vec<(u64, u64)> = events.iter().map(|e| e.serial).collect();
+    assert_eq!(serials, vec![(0, 1), (0, 1), (1, 2), (1, 2), (2, 3), (2, 3)]);
+    assert!(events.iter().all(|e| e.label == 4));
+    assert!(matches!(events[5].info, SeqTraceInfo::Receive { message: 3, .. }));
+}
+
+#[test]
+fn test_statistics_reports_scheduler_counters() {
+    use infrastructure_utilities::statistics::get_global_statistics;
+    use usecases_bifs::info::InfoBif;
+
+    let statistics = |item: &str| InfoBif::statistics_1(&ErlangTerm::Atom(item.to_string())).unwrap();
+
+    // Take a baseline so the next reading only covers the work below
+    statistics("reductions");
+    statistics("runtime");
+    get_global_statistics().add_reductions(0, 2000);
+    get_global_statistics().add_runtime(1, std::time::Duration::from_millis(5));
+
+    let ErlangTerm::Tuple(reductions) = statistics("reductions") else { panic!("not a tuple") };
+    assert!(matches!(reductions[1], ErlangTerm::Integer(since) if since >= 2000));
+    let ErlangTerm::Tuple(runtime) = statistics("runtime") else { panic!("not a tuple") };
+    assert!(matches!(runtime[1], ErlangTerm::Integer(since) if since >= 5));
+    let ErlangTerm::Tuple(wall_clock) = statistics("wall_clock") else { panic!("not a tuple") };
+    assert_eq!(wall_clock.len(), 2);
+}
+
+#[test]
+fn test_system_info_reads_global_tables() {
+    use infrastructure_driver_api::get_global_port_table;
+    use infrastructure_utilities::atom_table::get_global_atom_table;
+    use usecases_bifs::info::InfoBif;
+
+    let info = |item: &str| InfoBif::system_info_1(&ErlangTerm::Atom(item.to_string())).unwrap();
+
+    assert_eq!(info("atom_limit"), ErlangTerm::Integer(get_global_atom_table().limit() as i64));
+    assert_eq!(info("port_limit"), ErlangTerm::Integer(get_global_port_table().max_ports() as i64));
+    assert_eq!(info("wordsize"), ErlangTerm::Integer(std::mem::size_of::<usize>() as i64));
+    let ErlangTerm::Integer(online) = info("schedulers_online") else { panic!("not an integer") };
+    let ErlangTerm::Integer(schedulers) = info("schedulers") else { panic!("not an integer") };
+    assert!(online >= 1 && online <= schedulers);
+}
+
+#[test]
+fn test_system_flag_adjusts_schedulers_and_spawn_defaults() {
+    use entities_process::Process;
+    use usecases_bifs::info::InfoBif;
+    use usecases_scheduling::erts_init_scheduling;
+
+    let atom = |name: &str| ErlangTerm::Atom(name.to_string());
+    let flag = |name: &str, value: i64| InfoBif::system_flag_2(&atom(name), &ErlangTerm::Integer(value)).unwrap();
+
+    erts_init_scheduling(4, 4, 1, 4, 4, 10).unwrap();
+    assert_eq!(flag("schedulers_online", 2), ErlangTerm::Integer(4));
+    assert_eq!(InfoBif::system_info_1(&atom("schedulers_online")).unwrap(), ErlangTerm::Integer(2));
+    assert_eq!(flag("schedulers_online", 4), ErlangTerm::Integer(2));
+    assert_eq!(flag("dirty_cpu_schedulers_online", 1), ErlangTerm::Integer(4));
+    assert_eq!(InfoBif::system_info_1(&atom("dirty_cpu_schedulers_online")).unwrap(), ErlangTerm::Integer(1));
+    assert!(InfoBif::system_flag_2(&atom("schedulers_online"), &ErlangTerm::Integer(5)).is_err());
+
+    assert_eq!(flag("min_heap_size", 610), ErlangTerm::Integer(233));
+    assert_eq!(flag("fullsweep_after", 20), ErlangTerm::Integer(65535));
+    let process = Process::new(7_000_001);
+    flag("min_heap_size", 233);
+    flag("fullsweep_after", 65535);
+    assert_eq!(process.min_heap_size(), 610);
+    assert_eq!(process.fullsweep_after(), 20);
+}
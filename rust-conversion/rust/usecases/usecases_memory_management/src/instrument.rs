@@ -0,0 +1,738 @@
+//! Allocator Instrumentation
+//!
+//! Tracks carriers, blocks and calls per allocator type and reports them.
+//! Based on the statistics kept in erl_alloc_util.c and the allocation
+//! tagging in erl_alloc_util.c/instrument.erl.
+//!
+//! Each allocator type (`eheap_alloc`, `binary_alloc`, ...) has an
+//! [`InstrumentedAllocator`] wrapping one of the allocation strategies.
+//! Blocks at or above the single-block carrier threshold get a carrier of
+//! their own straight from the system; smaller blocks are placed in
+//! multi-block carriers by the strategy. The strategies keep freed blocks
+//! on their free lists, so multi-block carriers are never given back.
+//!
+//! When a super carrier is reserved (`+MMscs`), single-block carriers are
+//! mapped from it, falling back to the system once it is full. Literal areas
+//! are always placed in a super carrier of their own, so the code loader can
+//! tell literals by address ([`AllocatorRegistry::is_literal`]).
+//!
+//! [`allocator_info`] gives the data behind
+//! `erlang:system_info({allocator, Alloc})`. When allocation tagging is on,
+//! [`allocations`] gives block size histograms per tag and allocator type,
+//! as `instrument:allocations/0` does.
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 2002-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ *
+ * Creation productivity increased for code in this file by using AALang and GAB.
+ * See https://github.com/yenrab/AALang-Gab
+ */
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+
+use super::afit::AFitAllocator;
+use super::allocator::{Allocator, AllocationError, AllocatorType, DefaultAllocator};
+use super::bestfit::BestFitAllocator;
+use super::firstfit::FirstFitAllocator;
+use super::goodfit::GoodFitAllocator;
+use entities_system_integration_common::{get_global_super_carrier, MmapError, SuperCarrier};
+
+/// Default single-block carrier threshold (`+M<S>sbct`, 512 kilobytes)
+pub const DEFAULT_SBC_THRESHOLD: usize = 512 * 1024;
+
+/// Default multi-block carrier size (`+M<S>lmbcs`, 5 megabytes)
+pub const DEFAULT_MBC_SIZE: usize = 5 * 1024 * 1024;
+
+/// Default smallest block size counted in the first histogram slot
+/// (`histogram_start` of `instrument:allocations/1`)
+pub const DEFAULT_HISTOGRAM_START: usize = 128;
+
+/// Default number of histogram slots (`histogram_width`)
+pub const DEFAULT_HISTOGRAM_WIDTH: usize = 18;
+
+/// Size of the super carrier reserved for literal areas
+///
+/// ERTS reserves 1 GB (ERTS_LITERAL_VIRTUAL_AREA_SIZE); the reservation here
+/// is smaller since it is backed lazily by the system allocator rather than
+/// reserved address space.
+pub const DEFAULT_LITERAL_AREA_SIZE: usize = 256 * 1024 * 1024;
+
+/// Size single-block carriers are rounded up to
+const CARRIER_PAGE_SIZE: usize = 4096;
+
+/// Allocator types of the runtime system
+///
+/// Based on the alloc_util allocators in erl_alloc.types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AllocatorKind {
+    /// Temporary allocations (`temp_alloc`)
+    Temp,
+    /// Short-lived allocations (`sl_alloc`)
+    ShortLived,
+    /// Standard allocations (`std_alloc`)
+    Std,
+    /// Long-lived allocations (`ll_alloc`)
+    LongLived,
+    /// Process heaps (`eheap_alloc`)
+    Eheap,
+    /// ETS tables (`ets_alloc`)
+    Ets,
+    /// Fixed-size structures (`fix_alloc`)
+    Fix,
+    /// Literal areas (`literal_alloc`)
+    Literal,
+    /// Binaries (`binary_alloc`)
+    Binary,
+    /// Driver data (`driver_alloc`)
+    Driver,
+}
+
+impl AllocatorKind {
+    /// All allocator types, in the order `erlang:system_info(alloc_util_allocators)` lists them
+    pub const ALL: [AllocatorKind; 10] = [
+        AllocatorKind::Temp,
+        AllocatorKind::ShortLived,
+        AllocatorKind::Std,
+        AllocatorKind::LongLived,
+        AllocatorKind::Eheap,
+        AllocatorKind::Ets,
+        AllocatorKind::Fix,
+        AllocatorKind::Literal,
+        AllocatorKind::Binary,
+        AllocatorKind::Driver,
+    ];
+
+    /// Get the allocator name as used by `erlang:system_info/1`
+    pub fn name(&self) -> &'static str {
+        match self {
+            AllocatorKind::Temp => "temp_alloc",
+            AllocatorKind::ShortLived => "sl_alloc",
+            AllocatorKind::Std => "std_alloc",
+            AllocatorKind::LongLived => "ll_alloc",
+            AllocatorKind::Eheap => "eheap_alloc",
+            AllocatorKind::Ets => "ets_alloc",
+            AllocatorKind::Fix => "fix_alloc",
+            AllocatorKind::Literal => "literal_alloc",
+            AllocatorKind::Binary => "binary_alloc",
+            AllocatorKind::Driver => "driver_alloc",
+        }
+    }
+
+    /// Look up an allocator type by name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|kind| kind.name() == name)
+    }
+
+    /// Get the allocation strategy the allocator type uses by default
+    ///
+    /// Temporary allocations use a fit, long-lived allocations best fit and
+    /// the rest address order first fit, as in erl_alloc.c.
+    pub fn default_strategy(&self) -> AllocatorType {
+        match self {
+            AllocatorKind::Temp => AllocatorType::AFit,
+            AllocatorKind::LongLived => AllocatorType::BestFit,
+            _ => AllocatorType::FirstFit,
+        }
+    }
+}
+
+/// Carrier and block statistics of single-block or multi-block carriers
+///
+/// Based on CarriersStats_t in erl_alloc_util.h
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CarrierStats {
+    /// Blocks currently allocated
+    pub blocks: usize,
+    /// Bytes in currently allocated blocks
+    pub blocks_size: usize,
+    /// Highest number of blocks allocated at once
+    pub max_blocks: usize,
+    /// Highest number of bytes allocated at once
+    pub max_blocks_size: usize,
+    /// Carriers currently held
+    pub carriers: usize,
+    /// Bytes in currently held carriers
+    pub carriers_size: usize,
+}
+
+impl CarrierStats {
+    fn add_block(&mut self, size: usize) {
+        self.blocks += 1;
+        self.blocks_size += size;
+        self.max_blocks = self.max_blocks.max(self.blocks);
+        self.max_blocks_size = self.max_blocks_size.max(self.blocks_size);
+    }
+
+    fn remove_block(&mut self, size: usize) {
+        self.blocks = self.blocks.saturating_sub(1);
+        self.blocks_size = self.blocks_size.saturating_sub(size);
+    }
+}
+
+/// Number of calls made to an allocator
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CallStats {
+    /// Allocation calls
+    pub alloc: u64,
+    /// Deallocation calls
+    pub free: u64,
+    /// Reallocation calls
+    pub realloc: u64,
+}
+
+/// Report of one allocator type
+///
+/// The data behind `erlang:system_info({allocator, Alloc})`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocatorInfo {
+    /// Allocator type
+    pub kind: AllocatorKind,
+    /// Allocation strategy used in multi-block carriers
+    pub strategy: AllocatorType,
+    /// Single-block carrier threshold
+    pub sbc_threshold: usize,
+    /// Multi-block carrier size
+    pub mbc_size: usize,
+    /// Multi-block carrier statistics
+    pub mbcs: CarrierStats,
+    /// Single-block carrier statistics
+    pub sbcs: CarrierStats,
+    /// Call counts
+    pub calls: CallStats,
+}
+
+/// Tagged block, kept while allocation tagging is on
+struct TaggedBlock {
+    size: usize,
+    tag: String,
+}
+
+/// Mutable statistics of an instrumented allocator
+#[derive(Default)]
+struct InstrumentState {
+    mbcs: CarrierStats,
+    sbcs: CarrierStats,
+    calls: CallStats,
+    tagging: bool,
+    tagged: HashMap<usize, TaggedBlock>,
+}
+
+/// Source of single-block carriers mapped from a super carrier
+///
+/// Carriers that do not fit fall back to the system unless the allocator is
+/// restricted to the super carrier (`+MMsco`).
+struct SuperCarrierAllocator {
+    carrier: Arc<SuperCarrier>,
+    only: bool,
+}
+
+impl Allocator for SuperCarrierAllocator {
+    fn alloc(&self, size: usize) -> Result<*mut u8, AllocationError> {
+        match self.carrier.map(size) {
+            Ok(ptr) => Ok(ptr),
+            Err(MmapError::InvalidSize) => Err(AllocationError::InvalidSize),
+            Err(_) if self.only => Err(AllocationError::OutOfMemory),
+            Err(_) => DefaultAllocator.alloc(size),
+        }
+    }
+
+    fn realloc(&self, ptr: *mut u8, old_size: usize, new_size: usize) -> Result<*mut u8, AllocationError> {
+        let new_ptr = self.alloc(new_size)?;
+        super::allocator::safe_copy_memory(new_ptr, ptr, old_size.min(new_size));
+        self.dealloc(ptr, old_size);
+        Ok(new_ptr)
+    }
+
+    fn dealloc(&self, ptr: *mut u8, size: usize) {
+        if self.carrier.contains(ptr) {
+            let _ = self.carrier.unmap(ptr);
+        } else {
+            DefaultAllocator.dealloc(ptr, size);
+        }
+    }
+}
+
+/// Allocator type wrapping an allocation strategy and keeping its statistics
+///
+/// Based on Allctr_t in erl_alloc_util.h
+pub struct InstrumentedAllocator {
+    kind: AllocatorKind,
+    strategy: AllocatorType,
+    inner: Box<dyn Allocator + Send + Sync>,
+    /// Source of single-block carriers
+    carriers: Box<dyn Allocator + Send + Sync>,
+    /// Super carrier carriers are mapped from, if any
+    super_carrier: Option<Arc<SuperCarrier>>,
+    /// Every block gets a carrier in the super carrier
+    super_carrier_only: bool,
+    sbc_threshold: usize,
+    mbc_size: usize,
+    state: Mutex<InstrumentState>,
+}
+
+impl InstrumentedAllocator {
+    /// Create an allocator type using the given strategy and the default
+    /// carrier settings
+    pub fn new(kind: AllocatorKind, strategy: AllocatorType) -> Self {
+        Self::with_carrier_sizes(kind, strategy, DEFAULT_SBC_THRESHOLD, DEFAULT_MBC_SIZE)
+    }
+
+    /// Create an allocator type with the given single-block carrier
+    /// threshold and multi-block carrier size
+    pub fn with_carrier_sizes(
+        kind: AllocatorKind,
+        strategy: AllocatorType,
+        sbc_threshold: usize,
+        mbc_size: usize,
+    ) -> Self {
+        let inner: Box<dyn Allocator + Send + Sync> = match strategy {
+            AllocatorType::GoodFit => Box::new(GoodFitAllocator::new()),
+            AllocatorType::BestFit => Box::new(BestFitAllocator::new()),
+            AllocatorType::AFit => Box::new(AFitAllocator::new()),
+            AllocatorType::FirstFit => Box::new(FirstFitAllocator::new()),
+        };
+        Self {
+            kind,
+            strategy,
+            inner,
+            carriers: Box::new(DefaultAllocator),
+            super_carrier: None,
+            super_carrier_only: false,
+            sbc_threshold: sbc_threshold.max(1),
+            mbc_size: mbc_size.max(1),
+            state: Mutex::new(InstrumentState::default()),
+        }
+    }
+
+    /// Map single-block carriers from a super carrier
+    ///
+    /// With `only` set every block is given a carrier in the super carrier
+    /// and allocation fails once it is full; otherwise only single-block
+    /// carriers are mapped from it, falling back to the system.
+    pub fn with_super_carrier(mut self, carrier: Arc<SuperCarrier>, only: bool) -> Self {
+        self.carriers = Box::new(SuperCarrierAllocator {
+            carrier: Arc::clone(&carrier),
+            only,
+        });
+        self.super_carrier = Some(carrier);
+        self.super_carrier_only = only;
+        self
+    }
+
+    /// Check if a block was placed in the allocator's super carrier
+    pub fn in_super_carrier(&self, ptr: *const u8) -> bool {
+        self.super_carrier.as_ref().is_some_and(|carrier| carrier.contains(ptr))
+    }
+
+    /// Get the allocator type
+    pub fn kind(&self) -> AllocatorKind {
+        self.kind
+    }
+
+    /// Get the statistics of the allocator
+    pub fn info(&self) -> AllocatorInfo {
+        let state = self.state.lock().unwrap();
+        AllocatorInfo {
+            kind: self.kind,
+            strategy: self.strategy,
+            sbc_threshold: self.sbc_threshold,
+            mbc_size: self.mbc_size,
+            mbcs: state.mbcs,
+            sbcs: state.sbcs,
+            calls: state.calls,
+        }
+    }
+
+    /// Turn allocation tagging on or off
+    ///
+    /// Turning it off forgets the tags of blocks still allocated.
+    pub fn set_tagging(&self, enabled: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.tagging = enabled;
+        if !enabled {
+            state.tagged.clear();
+        }
+    }
+
+    /// Check if allocation tagging is on
+    pub fn is_tagging(&self) -> bool {
+        self.state.lock().unwrap().tagging
+    }
+
+    /// Allocate a block, tagging it when allocation tagging is on
+    ///
+    /// # Arguments
+    /// * `size` - Size in bytes to allocate
+    /// * `tag` - What the block is for, e.g. `"heap"` or `"binary"`
+    pub fn alloc_tagged(&self, size: usize, tag: &str) -> Result<*mut u8, AllocationError> {
+        let ptr = self.alloc(size)?;
+        let mut state = self.state.lock().unwrap();
+        if state.tagging {
+            state.tagged.insert(ptr as usize, TaggedBlock { size, tag: tag.to_string() });
+        }
+        Ok(ptr)
+    }
+
+    /// Get block size histograms of the tagged blocks, per tag
+    ///
+    /// Slot 0 counts blocks smaller than `histogram_start`, slot `n` blocks
+    /// smaller than `histogram_start << n`; the last slot counts the rest.
+    pub fn allocations(&self, histogram_start: usize, histogram_width: usize) -> BTreeMap<String, Vec<u64>> {
+        let state = self.state.lock().unwrap();
+        let mut histograms: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+        if histogram_width == 0 {
+            return histograms;
+        }
+        for block in state.tagged.values() {
+            let histogram = histograms
+                .entry(block.tag.clone())
+                .or_insert_with(|| vec![0; histogram_width]);
+            histogram[histogram_slot(block.size, histogram_start, histogram_width)] += 1;
+        }
+        histograms
+    }
+
+    fn is_sbc(&self, size: usize) -> bool {
+        self.super_carrier_only || size >= self.sbc_threshold
+    }
+
+    fn record_alloc(&self, state: &mut InstrumentState, size: usize) {
+        if self.is_sbc(size) {
+            state.sbcs.add_block(size);
+            state.sbcs.carriers += 1;
+            state.sbcs.carriers_size += sbc_carrier_size(size);
+        } else {
+            state.mbcs.add_block(size);
+            while state.mbcs.carriers_size < state.mbcs.blocks_size {
+                state.mbcs.carriers += 1;
+                state.mbcs.carriers_size += self.mbc_size;
+            }
+        }
+    }
+
+    fn record_free(&self, state: &mut InstrumentState, size: usize) {
+        if self.is_sbc(size) {
+            state.sbcs.remove_block(size);
+            state.sbcs.carriers = state.sbcs.carriers.saturating_sub(1);
+            state.sbcs.carriers_size = state.sbcs.carriers_size.saturating_sub(sbc_carrier_size(size));
+        } else {
+            state.mbcs.remove_block(size);
+        }
+    }
+
+    fn carrier_allocator(&self, size: usize) -> &dyn Allocator {
+        if self.is_sbc(size) {
+            self.carriers.as_ref()
+        } else {
+            self.inner.as_ref()
+        }
+    }
+}
+
+impl Allocator for InstrumentedAllocator {
+    fn alloc(&self, size: usize) -> Result<*mut u8, AllocationError> {
+        let ptr = self.carrier_allocator(size).alloc(size)?;
+        let mut state = self.state.lock().unwrap();
+        state.calls.alloc += 1;
+        self.record_alloc(&mut state, size);
+        Ok(ptr)
+    }
+
+    fn realloc(&self, ptr: *mut u8, old_size: usize, new_size: usize) -> Result<*mut u8, AllocationError> {
+        let new_ptr = if self.is_sbc(old_size) == self.is_sbc(new_size) {
+            self.carrier_allocator(new_size).realloc(ptr, old_size, new_size)?
+        } else {
+            // Moving between a single-block and a multi-block carrier
+            let new_ptr = self.carrier_allocator(new_size).alloc(new_size)?;
+            super::allocator::safe_copy_memory(new_ptr, ptr, old_size.min(new_size));
+            self.carrier_allocator(old_size).dealloc(ptr, old_size);
+            new_ptr
+        };
+        let mut state = self.state.lock().unwrap();
+        state.calls.realloc += 1;
+        self.record_free(&mut state, old_size);
+        self.record_alloc(&mut state, new_size);
+        if let Some(mut block) = state.tagged.remove(&(ptr as usize)) {
+            block.size = new_size;
+            state.tagged.insert(new_ptr as usize, block);
+        }
+        Ok(new_ptr)
+    }
+
+    fn dealloc(&self, ptr: *mut u8, size: usize) {
+        if ptr.is_null() {
+            return;
+        }
+        self.carrier_allocator(size).dealloc(ptr, size);
+        let mut state = self.state.lock().unwrap();
+        state.calls.free += 1;
+        self.record_free(&mut state, size);
+        state.tagged.remove(&(ptr as usize));
+    }
+}
+
+/// Size of the carrier holding a single block
+fn sbc_carrier_size(size: usize) -> usize {
+    size.div_ceil(CARRIER_PAGE_SIZE) * CARRIER_PAGE_SIZE
+}
+
+/// Histogram slot counting a block of the given size
+fn histogram_slot(size: usize, histogram_start: usize, histogram_width: usize) -> usize {
+    let mut slot = 0;
+    let mut limit = histogram_start.max(1);
+    while slot + 1 < histogram_width && size >= limit {
+        slot += 1;
+        limit = limit.saturating_mul(2);
+    }
+    slot
+}
+
+/// The allocator types of the runtime system
+pub struct AllocatorRegistry {
+    allocators: RwLock<HashMap<AllocatorKind, Arc<InstrumentedAllocator>>>,
+    /// Super carrier literal areas are placed in, reserved on first use
+    literal_area: OnceLock<Option<Arc<SuperCarrier>>>,
+}
+
+impl AllocatorRegistry {
+    /// Create a registry with no allocators started
+    pub fn new() -> Self {
+        Self {
+            allocators: RwLock::new(HashMap::new()),
+            literal_area: OnceLock::new(),
+        }
+    }
+
+    /// Get an allocator type, starting it with its default strategy on first use
+    ///
+    /// `literal_alloc` places every block in the literal area; the other
+    /// types map single-block carriers from the global super carrier when
+    /// one is reserved.
+    pub fn allocator(&self, kind: AllocatorKind) -> Arc<InstrumentedAllocator> {
+        if let Some(allocator) = self.allocators.read().unwrap().get(&kind) {
+            return Arc::clone(allocator);
+        }
+        let mut allocators = self.allocators.write().unwrap();
+        Arc::clone(
+            allocators
+                .entry(kind)
+                .or_insert_with(|| Arc::new(self.start_allocator(kind))),
+        )
+    }
+
+    /// Get an allocator type if it has been started
+    pub(crate) fn started(&self, kind: AllocatorKind) -> Option<Arc<InstrumentedAllocator>> {
+        self.allocators.read().unwrap().get(&kind).cloned()
+    }
+
+    fn start_allocator(&self, kind: AllocatorKind) -> InstrumentedAllocator {
+        let allocator = InstrumentedAllocator::new(kind, kind.default_strategy());
+        if kind == AllocatorKind::Literal {
+            return match self.literal_area() {
+                Some(area) => allocator.with_super_carrier(area, true),
+                None => allocator,
+            };
+        }
+        match get_global_super_carrier() {
+            Some(carrier) => allocator.with_super_carrier(carrier, false),
+            None => allocator,
+        }
+    }
+
+    /// Get the super carrier literal areas are placed in
+    ///
+    /// Returns `None` if it could not be reserved.
+    pub fn literal_area(&self) -> Option<Arc<SuperCarrier>> {
+        self.literal_area
+            .get_or_init(|| SuperCarrier::reserve(DEFAULT_LITERAL_AREA_SIZE).ok().map(Arc::new))
+            .clone()
+    }
+
+    /// Check if an address lies in the literal area
+    pub fn is_literal(&self, ptr: *const u8) -> bool {
+        self.literal_area
+            .get()
+            .and_then(Option::as_ref)
+            .is_some_and(|area| area.contains(ptr))
+    }
+
+    /// Get the report of an allocator type
+    pub fn info(&self, kind: AllocatorKind) -> AllocatorInfo {
+        self.allocator(kind).info()
+    }
+
+    /// Turn allocation tagging on or off for all allocator types
+    pub fn set_tagging(&self, enabled: bool) {
+        for kind in AllocatorKind::ALL {
+            self.allocator(kind).set_tagging(enabled);
+        }
+    }
+
+    /// Get block size histograms of the tagged blocks, per tag and allocator type
+    pub fn allocations(
+        &self,
+        histogram_start: usize,
+        histogram_width: usize,
+    ) -> BTreeMap<String, BTreeMap<AllocatorKind, Vec<u64>>> {
+        let mut result: BTreeMap<String, BTreeMap<AllocatorKind, Vec<u64>>> = BTreeMap::new();
+        for kind in AllocatorKind::ALL {
+            for (tag, histogram) in self.allocator(kind).allocations(histogram_start, histogram_width) {
+                result.entry(tag).or_default().insert(kind, histogram);
+            }
+        }
+        result
+    }
+}
+
+impl Default for AllocatorRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Global allocator registry instance
+static GLOBAL_ALLOCATORS: OnceLock<AllocatorRegistry> = OnceLock::new();
+
+/// Get the global allocator registry
+pub fn get_global_allocators() -> &'static AllocatorRegistry {
+    GLOBAL_ALLOCATORS.get_or_init(AllocatorRegistry::new)
+}
+
+/// Get the report of an allocator type of the runtime system
+pub fn allocator_info(kind: AllocatorKind) -> AllocatorInfo {
+    get_global_allocators().info(kind)
+}
+
+/// Get block size histograms of the tagged blocks of the runtime system,
+/// with the default histogram settings
+pub fn allocations() -> BTreeMap<String, BTreeMap<AllocatorKind, Vec<u64>>> {
+    get_global_allocators().allocations(DEFAULT_HISTOGRAM_START, DEFAULT_HISTOGRAM_WIDTH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_and_carrier_stats() {
+        let allocator =
+            InstrumentedAllocator::with_carrier_sizes(AllocatorKind::Ets, AllocatorType::FirstFit, 1024, 4096);
+        let small = allocator.alloc(100).unwrap();
+        let large = allocator.alloc(5000).unwrap();
+        let info = allocator.info();
+        assert_eq!(info.mbcs.blocks, 1);
+        assert_eq!(info.mbcs.blocks_size, 100);
+        assert_eq!(info.mbcs.carriers, 1);
+        assert_eq!(info.mbcs.carriers_size, 4096);
+        assert_eq!(info.sbcs.blocks, 1);
+        assert_eq!(info.sbcs.carriers, 1);
+        assert_eq!(info.sbcs.carriers_size, 8192);
+
+        let small = allocator.realloc(small, 100, 200).unwrap();
+        allocator.dealloc(large, 5000);
+        allocator.dealloc(small, 200);
+        let info = allocator.info();
+        assert_eq!(info.calls, CallStats { alloc: 2, free: 2, realloc: 1 });
+        assert_eq!(info.mbcs.blocks, 0);
+        assert_eq!(info.mbcs.max_blocks_size, 200);
+        assert_eq!(info.mbcs.carriers, 1);
+        assert_eq!(info.sbcs.carriers, 0);
+        assert_eq!(info.sbcs.max_blocks, 1);
+    }
+
+    #[test]
+    fn test_tagged_allocations() {
+        let allocator = InstrumentedAllocator::new(AllocatorKind::Binary, AllocatorType::GoodFit);
+        let untagged = allocator.alloc_tagged(64, "binary").unwrap();
+        assert!(allocator.allocations(128, 4).is_empty());
+
+        allocator.set_tagging(true);
+        let a = allocator.alloc_tagged(64, "binary").unwrap();
+        let b = allocator.alloc_tagged(300, "binary").unwrap();
+        let c = allocator.alloc_tagged(100_000, "heap").unwrap();
+        let histograms = allocator.allocations(128, 4);
+        assert_eq!(histograms["binary"], vec![1, 0, 1, 0]);
+        assert_eq!(histograms["heap"], vec![0, 0, 0, 1]);
+
+        allocator.dealloc(b, 300);
+        assert_eq!(allocator.allocations(128, 4)["binary"], vec![1, 0, 0, 0]);
+        for (ptr, size) in [(untagged, 64), (a, 64), (c, 100_000)] {
+            allocator.dealloc(ptr, size);
+        }
+        assert!(allocator.allocations(128, 4).is_empty());
+    }
+
+    #[test]
+    fn test_super_carrier_placement() {
+        let carrier = Arc::new(SuperCarrier::reserve(64 * 1024).unwrap());
+        let allocator =
+            InstrumentedAllocator::with_carrier_sizes(AllocatorKind::Std, AllocatorType::FirstFit, 1024, 4096)
+                .with_super_carrier(Arc::clone(&carrier), false);
+        let sbc = allocator.alloc(40 * 1024).unwrap();
+        assert!(allocator.in_super_carrier(sbc));
+        // Falls back to the system once the super carrier is full
+        let overflow = allocator.alloc(40 * 1024).unwrap();
+        assert!(!allocator.in_super_carrier(overflow));
+        let mbc = allocator.alloc(64).unwrap();
+        assert!(!allocator.in_super_carrier(mbc));
+        allocator.dealloc(sbc, 40 * 1024);
+        allocator.dealloc(overflow, 40 * 1024);
+        allocator.dealloc(mbc, 64);
+        assert_eq!(carrier.mapped_size(), 0);
+
+        let only = InstrumentedAllocator::new(AllocatorKind::Literal, AllocatorType::FirstFit)
+            .with_super_carrier(Arc::clone(&carrier), true);
+        let literal = only.alloc(64).unwrap();
+        assert!(only.in_super_carrier(literal));
+        assert_eq!(only.info().sbcs.blocks, 1);
+        assert_eq!(only.alloc(64 * 1024), Err(AllocationError::OutOfMemory));
+        only.dealloc(literal, 64);
+    }
+
+    #[test]
+    fn test_literal_area() {
+        let registry = AllocatorRegistry::new();
+        let literal = registry.allocator(AllocatorKind::Literal);
+        let area = literal.alloc(1000).unwrap();
+        assert!(registry.is_literal(area));
+        let heap = registry.allocator(AllocatorKind::Eheap).alloc(1000).unwrap();
+        assert!(!registry.is_literal(heap));
+        literal.dealloc(area, 1000);
+        registry.allocator(AllocatorKind::Eheap).dealloc(heap, 1000);
+    }
+
+    #[test]
+    fn test_allocator_kind_names() {
+        for kind in AllocatorKind::ALL {
+            assert_eq!(AllocatorKind::from_name(kind.name()), Some(kind));
+        }
+        assert_eq!(AllocatorKind::from_name("sys_alloc"), None);
+        let registry = AllocatorRegistry::new();
+        assert!(Arc::ptr_eq(
+            &registry.allocator(AllocatorKind::Eheap),
+            &registry.allocator(AllocatorKind::Eheap)
+        ));
+        assert_eq!(registry.info(AllocatorKind::LongLived).strategy, AllocatorType::BestFit);
+    }
+}
@@ -26,6 +26,12 @@
 //!
 //! - **[`allocator`](allocator/index.html)**: Common allocator interface and types
 //!
+//! - **[`instrument`](instrument/index.html)**: Per allocator type carrier, block and
+//!   call statistics, allocation tagging, and carrier placement in super carriers
+//!
+//! - **[`memory`](memory/index.html)**: Aggregation of allocators, tables and counters
+//!   into the `erlang:memory/0,1` categories
+//!
 //! ## Architecture
 //!
 //! This crate is based on the C implementation in `erl_goodfit_alloc.c` and related
@@ -41,6 +47,12 @@ pub mod goodfit;
 pub mod bestfit;
 pub mod afit;
 pub mod firstfit;
+pub mod instrument;
+pub mod memory;
 
 pub use allocator::{Allocator, AllocatorType, AllocationError};
-
+pub use instrument::{
+    allocations, allocator_info, get_global_allocators, AllocatorInfo, AllocatorKind, AllocatorRegistry,
+    CallStats, CarrierStats, InstrumentedAllocator,
+};
+pub use memory::{erts_memory, MemoryInfo, MEMORY_TYPES};
@@ -0,0 +1,234 @@
+//! Memory Accounting
+//!
+//! Aggregates the memory of the runtime system into the `erlang:memory/0,1`
+//! categories. Based on `erts_memory()` in erl_alloc.c.
+//!
+//! Each category adds up the carriers of its allocator type and the memory
+//! counted outside the allocators on the sharded counters of
+//! `infrastructure_utilities::memory`:
+//! - `processes` / `processes_used` - `eheap_alloc` carriers / blocks and
+//!   the processes in the process table
+//! - `atom` / `atom_used` - the atom table
+//! - `binary` - `binary_alloc`
+//! - `code` - loaded modules and `literal_alloc`
+//! - `ets` - `ets_alloc`
+//! - `system` - everything not in `processes`, including `atom`, `binary`,
+//!   `code` and `ets`
+//! - `total` - `processes` + `system`
+//!
+//! Allocator types that have not been started are not started by a query.
+
+/*
+ * %CopyrightBegin%
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Copyright Lee Barney 2025. All Rights Reserved.
+ *
+ * This file is derived from work copyrighted by Ericsson AB 2002-2025.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * %CopyrightEnd%
+ */
+
+use code_management_code_loading::module_management::get_global_module_manager;
+use entities_data_handling::AtomTable;
+use infrastructure_utilities::atom_table::get_global_atom_table;
+use infrastructure_utilities::memory::{get_global_memory_counters, MemoryCounters, MemoryKind};
+
+use super::instrument::{get_global_allocators, AllocatorKind, AllocatorRegistry};
+
+/// Memory types of `erlang:memory/0`, in the order it lists them
+pub const MEMORY_TYPES: [&str; 9] = [
+    "total",
+    "processes",
+    "processes_used",
+    "system",
+    "atom",
+    "atom_used",
+    "binary",
+    "code",
+    "ets",
+];
+
+/// Allocator types whose carriers only count towards `system`
+const SYSTEM_ALLOCATORS: [AllocatorKind; 6] = [
+    AllocatorKind::Temp,
+    AllocatorKind::ShortLived,
+    AllocatorKind::Std,
+    AllocatorKind::LongLived,
+    AllocatorKind::Fix,
+    AllocatorKind::Driver,
+];
+
+/// Memory of the runtime system per `erlang:memory/0` category, in bytes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryInfo {
+    /// Memory allocated for processes and the system
+    pub total: usize,
+    /// Memory allocated for processes
+    pub processes: usize,
+    /// Memory used by processes
+    pub processes_used: usize,
+    /// Memory allocated for the runtime system, not for processes
+    pub system: usize,
+    /// Memory allocated for atoms
+    pub atom: usize,
+    /// Memory used by atoms
+    pub atom_used: usize,
+    /// Memory allocated for binaries
+    pub binary: usize,
+    /// Memory allocated for Erlang code
+    pub code: usize,
+    /// Memory allocated for ETS tables
+    pub ets: usize,
+}
+
+impl MemoryInfo {
+    /// Get a category by its `erlang:memory/1` name
+    ///
+    /// # Returns
+    /// The bytes of the category, or `None` for an unknown name
+    pub fn get(&self, memory_type: &str) -> Option<usize> {
+        match memory_type {
+            "total" => Some(self.total),
+            "processes" => Some(self.processes),
+            "processes_used" => Some(self.processes_used),
+            "system" => Some(self.system),
+            "atom" => Some(self.atom),
+            "atom_used" => Some(self.atom_used),
+            "binary" => Some(self.binary),
+            "code" => Some(self.code),
+            "ets" => Some(self.ets),
+            _ => None,
+        }
+    }
+
+    /// Get all categories in [`MEMORY_TYPES`] order
+    pub fn items(&self) -> Vec<(&'static str, usize)> {
+        MEMORY_TYPES
+            .iter()
+            .map(|memory_type| (*memory_type, self.get(memory_type).unwrap_or(0)))
+            .collect()
+    }
+}
+
+/// Get the memory of the runtime system (`erlang:memory/0`)
+pub fn erts_memory() -> MemoryInfo {
+    memory_info(
+        get_global_allocators(),
+        get_global_memory_counters(),
+        get_global_atom_table(),
+        get_global_module_manager().module_table_sz() as usize,
+    )
+}
+
+/// Aggregate the memory categories
+///
+/// # Arguments
+/// * `allocators` - Allocator types
+/// * `counters` - Memory counted outside the allocators
+/// * `atoms` - Atom table
+/// * `code_size` - Bytes of loaded code
+fn memory_info(
+    allocators: &AllocatorRegistry,
+    counters: &MemoryCounters,
+    atoms: &AtomTable,
+    code_size: usize,
+) -> MemoryInfo {
+    // Allocated (carriers) and used (blocks) bytes of an allocator type
+    let usage = |kind: AllocatorKind| -> (usize, usize) {
+        allocators.started(kind).map_or((0, 0), |allocator| {
+            let info = allocator.info();
+            (
+                info.mbcs.carriers_size + info.sbcs.carriers_size,
+                info.mbcs.blocks_size + info.sbcs.blocks_size,
+            )
+        })
+    };
+
+    let (eheap_carriers, eheap_blocks) = usage(AllocatorKind::Eheap);
+    let processes = eheap_carriers + counters.get(MemoryKind::Processes);
+    let processes_used = (eheap_blocks + counters.get(MemoryKind::ProcessesUsed)).min(processes);
+
+    let atom = atoms.allocated_bytes() + counters.get(MemoryKind::Atom);
+    let atom_used = (atoms.used_bytes() + counters.get(MemoryKind::AtomUsed)).min(atom);
+    let binary = usage(AllocatorKind::Binary).0 + counters.get(MemoryKind::Binary);
+    let code = code_size + usage(AllocatorKind::Literal).0 + counters.get(MemoryKind::Code);
+    let ets = usage(AllocatorKind::Ets).0 + counters.get(MemoryKind::Ets);
+    let other: usize = SYSTEM_ALLOCATORS.iter().map(|kind| usage(*kind).0).sum();
+    let system = atom + binary + code + ets + other + counters.get(MemoryKind::System);
+
+    MemoryInfo {
+        total: processes + system,
+        processes,
+        processes_used,
+        system,
+        atom,
+        atom_used,
+        binary,
+        code,
+        ets,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allocator::Allocator;
+    use entities_data_handling::AtomEncoding;
+
+    #[test]
+    fn test_memory_categories() {
+        let allocators = AllocatorRegistry::new();
+        let counters = MemoryCounters::new();
+        let atoms = AtomTable::new(100);
+        atoms.put_index(b"memory_test", AtomEncoding::SevenBitAscii, false).unwrap();
+
+        let empty = memory_info(&allocators, &counters, &atoms, 0);
+        assert_eq!(empty.processes, 0);
+        assert_eq!(empty.binary, 0);
+        assert!(empty.atom_used > 0 && empty.atom_used <= empty.atom);
+
+        let binary = allocators.allocator(AllocatorKind::Binary);
+        let block = binary.alloc(1000).unwrap();
+        counters.add(MemoryKind::Processes, 4096);
+        counters.add(MemoryKind::ProcessesUsed, 1024);
+        counters.add(MemoryKind::Ets, 300);
+
+        let info = memory_info(&allocators, &counters, &atoms, 5000);
+        assert_eq!(info.processes, 4096);
+        assert_eq!(info.processes_used, 1024);
+        assert!(info.binary >= 1000);
+        assert!(info.code >= 5000);
+        assert_eq!(info.ets, 300);
+        assert_eq!(info.system, info.atom + info.binary + info.code + info.ets);
+        assert_eq!(info.total, info.processes + info.system);
+        binary.dealloc(block, 1000);
+    }
+
+    #[test]
+    fn test_memory_info_items() {
+        let info = MemoryInfo {
+            total: 9,
+            ets: 1,
+            ..MemoryInfo::default()
+        };
+        let items = info.items();
+        assert_eq!(items.len(), MEMORY_TYPES.len());
+        assert_eq!(items[0], ("total", 9));
+        assert_eq!(items[8], ("ets", 1));
+        assert_eq!(info.get("maximum"), None);
+    }
+}
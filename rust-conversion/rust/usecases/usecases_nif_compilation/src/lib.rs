@@ -16,9 +16,14 @@
 //!   Detects Rust source files, compiles them on-the-fly, and loads the resulting
 //!   libraries safely.
 //!
+//! - **[`nif_cache`](nif_cache/index.html)**: Cache of compiled NIF libraries keyed
+//!   by a hash of the source, toolchain version and compile options, with a size
+//!   limit and invalidation, so repeated compiles of the same source skip cargo.
+//!
 //! - **[`safe_rust_verifier`](safe_rust_verifier/index.html)**: Verification of Rust
-//!   code to ensure it contains only safe Rust (no unsafe blocks). This provides
-//!   an additional safety layer before loading NIFs.
+//!   code to ensure it contains only safe Rust (no unsafe blocks), uses no
+//!   forbidden crates and exports no colliding `#[no_mangle]` symbols. This
+//!   provides an additional safety layer before loading NIFs.
 //!
 //! - **[`nif_interface_verifier`](nif_interface_verifier/index.html)**: Verification
 //!   that Rust NIF code has the proper interface requirements, including `nif_init()`
@@ -30,6 +35,8 @@
 //! - Verifying that Rust code contains no unsafe blocks
 //! - Verifying that NIF code has proper interface requirements (`nif_init()`, `#[no_mangle]`, `extern "C"`)
 //! - Using safe compilation and loading procedures
+//! - Optionally compiling offline against an allow-listed set of vendored
+//!   dependencies without build scripts or procedural macros
 //! - Providing clear error messages for unsafe code and interface issues
 //!
 //! ## Examples
@@ -52,11 +59,17 @@
 //! - [`code_management_code_loading`](../../code_management/code_management_code_loading/index.html): Code loading infrastructure
 
 pub mod nif_compiler;
+pub mod nif_cache;
 pub mod safe_rust_verifier;
 pub mod nif_interface_verifier;
 
-pub use nif_compiler::{NifCompiler, CompileOptions, CompileResult, CompileError};
-pub use safe_rust_verifier::{SafeRustVerifier, VerificationResult, VerificationError};
+pub use nif_compiler::{
+    NifCompiler, CompileOptions, CompileResult, CompileError, OfflineOptions, VendoredDependency,
+};
+pub use nif_cache::{NifArtifactCache, DEFAULT_CACHE_SIZE_LIMIT};
+pub use safe_rust_verifier::{
+    SafeRustVerifier, VerificationResult, VerificationError, UnsafeLocation, DEFAULT_FORBIDDEN_CRATES,
+};
 pub use nif_interface_verifier::{
     NifInterfaceVerifier, NifInterfaceResult, NifInterfaceError,
 };
@@ -0,0 +1,297 @@
+//! NIF Artifact Cache
+//!
+//! Caches compiled NIF libraries so that compiling the same source again,
+//! with the same toolchain and options, skips cargo entirely. Each entry is a
+//! directory named by the cache key holding the built library:
+//!
+//! ```text
+//! <cache dir>/<key>/lib<crate>.so
+//! ```
+//!
+//! The key hashes the source, the toolchain version and every option that
+//! changes the built artifact. When the cache grows beyond its size limit the
+//! least recently used entries are evicted.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::nif_compiler::CompileOptions;
+
+/// Default cache size limit (512 MiB)
+pub const DEFAULT_CACHE_SIZE_LIMIT: u64 = 512 * 1024 * 1024;
+
+/// Cache of compiled NIF libraries
+#[derive(Debug, Clone)]
+pub struct NifArtifactCache {
+    /// Directory holding the cache entries
+    dir: PathBuf,
+    /// Maximum total size of the cached libraries in bytes
+    size_limit: u64,
+}
+
+impl NifArtifactCache {
+    /// Create a cache in `dir` limited to [`DEFAULT_CACHE_SIZE_LIMIT`] bytes
+    ///
+    /// The directory is created on first use.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self::with_size_limit(dir, DEFAULT_CACHE_SIZE_LIMIT)
+    }
+
+    /// Create a cache in `dir` limited to `size_limit` bytes
+    pub fn with_size_limit(dir: impl Into<PathBuf>, size_limit: u64) -> Self {
+        Self {
+            dir: dir.into(),
+            size_limit,
+        }
+    }
+
+    /// Directory holding the cache entries
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Maximum total size of the cached libraries in bytes
+    pub fn size_limit(&self) -> u64 {
+        self.size_limit
+    }
+
+    /// Compute the cache key of a compilation
+    ///
+    /// # Arguments
+    /// * `source` - Contents of the NIF source file
+    /// * `toolchain` - Toolchain version, as reported by `rustc -vV`
+    /// * `options` - Compilation options
+    ///
+    /// # Returns
+    /// A 128-bit key as 32 hex digits
+    pub fn key(source: &[u8], toolchain: &str, options: &CompileOptions) -> String {
+        // Two independently seeded 64-bit hashes give a 128-bit key
+        let mut halves = [DefaultHasher::new(), DefaultHasher::new()];
+        for (seed, hasher) in halves.iter_mut().enumerate() {
+            seed.hash(hasher);
+            source.hash(hasher);
+            toolchain.hash(hasher);
+            options.verify_safe.hash(hasher);
+            options.release.hash(hasher);
+            options.cargo_flags.hash(hasher);
+            if let Some(offline) = &options.offline {
+                for dependency in &offline.allowed_dependencies {
+                    dependency.name.hash(hasher);
+                    dependency.version.hash(hasher);
+                }
+                if let Some(lockfile) = &offline.lockfile {
+                    fs::read(lockfile).unwrap_or_default().hash(hasher);
+                }
+            }
+        }
+        format!("{:016x}{:016x}", halves[0].finish(), halves[1].finish())
+    }
+
+    /// Look up a cached library
+    ///
+    /// A hit marks the entry as recently used.
+    ///
+    /// # Returns
+    /// Path of the cached library, or `None` on a miss
+    pub fn lookup(&self, key: &str, library_name: &str) -> Option<PathBuf> {
+        let path = self.dir.join(key).join(library_name);
+        if !path.is_file() {
+            return None;
+        }
+        if let Ok(file) = fs::File::options().append(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        Some(path)
+    }
+
+    /// Store a built library in the cache, then evict least recently used
+    /// entries until the cache fits its size limit
+    ///
+    /// The new entry itself is never evicted, even if it alone exceeds the limit.
+    ///
+    /// # Returns
+    /// Path of the cached library
+    pub fn store(&self, key: &str, library_path: &Path, library_name: &str) -> io::Result<PathBuf> {
+        fs::create_dir_all(&self.dir)?;
+        // Copy into a staging directory first, so a concurrent lookup never
+        // sees a partially written library
+        let staging = self.dir.join(format!(".{}.{}.tmp", key, std::process::id()));
+        fs::create_dir_all(&staging)?;
+        fs::copy(library_path, staging.join(library_name))?;
+        let entry = self.dir.join(key);
+        if entry.exists() {
+            fs::remove_dir_all(&entry)?;
+        }
+        fs::rename(&staging, &entry)?;
+
+        self.evict_to_limit(key)?;
+        Ok(entry.join(library_name))
+    }
+
+    /// Remove a cache entry
+    ///
+    /// # Returns
+    /// `true` if the entry existed
+    pub fn invalidate(&self, key: &str) -> io::Result<bool> {
+        let entry = self.dir.join(key);
+        if !entry.is_dir() {
+            return Ok(false);
+        }
+        fs::remove_dir_all(entry)?;
+        Ok(true)
+    }
+
+    /// Remove all cache entries
+    pub fn clear(&self) -> io::Result<()> {
+        for (entry, _, _) in self.entries()? {
+            fs::remove_dir_all(entry)?;
+        }
+        Ok(())
+    }
+
+    /// Total size of the cached libraries in bytes
+    pub fn size(&self) -> io::Result<u64> {
+        Ok(self.entries()?.iter().map(|(_, size, _)| size).sum())
+    }
+
+    /// Evict least recently used entries, except `keep`, until the cache
+    /// fits its size limit
+    fn evict_to_limit(&self, keep: &str) -> io::Result<()> {
+        let mut entries = self.entries()?;
+        let mut size: u64 = entries.iter().map(|(_, size, _)| size).sum();
+        entries.sort_by_key(|(_, _, used)| *used);
+        for (entry, entry_size, _) in entries {
+            if size <= self.size_limit {
+                break;
+            }
+            if entry.file_name().is_some_and(|name| name == keep) {
+                continue;
+            }
+            fs::remove_dir_all(&entry)?;
+            size -= entry_size;
+        }
+        Ok(())
+    }
+
+    /// List the cache entries with their size and last use
+    fn entries(&self) -> io::Result<Vec<(PathBuf, u64, SystemTime)>> {
+        let read_dir = match fs::read_dir(&self.dir) {
+            Ok(read_dir) => read_dir,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+        let mut entries = Vec::new();
+        for entry in read_dir {
+            let entry = entry?;
+            let is_staging = entry.file_name().to_string_lossy().starts_with('.');
+            if !entry.file_type()?.is_dir() || is_staging {
+                continue;
+            }
+            let mut size = 0;
+            let mut used = SystemTime::UNIX_EPOCH;
+            for file in fs::read_dir(entry.path())? {
+                let metadata = file?.metadata()?;
+                size += metadata.len();
+                used = used.max(metadata.modified()?);
+            }
+            entries.push((entry.path(), size, used));
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nif_compiler::{OfflineOptions, VendoredDependency};
+    use std::time::Duration;
+
+    fn write_library(dir: &Path, name: &str, size: usize) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, vec![0u8; size]).unwrap();
+        path
+    }
+
+    fn age(path: &Path, seconds: u64) {
+        let file = fs::File::options().append(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(seconds)).unwrap();
+    }
+
+    #[test]
+    fn test_key_covers_source_toolchain_and_options() {
+        let options = CompileOptions::default();
+        let key = NifArtifactCache::key(b"fn a() {}", "rustc 1.80.0", &options);
+        assert_eq!(key.len(), 32);
+        assert_eq!(key, NifArtifactCache::key(b"fn a() {}", "rustc 1.80.0", &options));
+        assert_ne!(key, NifArtifactCache::key(b"fn b() {}", "rustc 1.80.0", &options));
+        assert_ne!(key, NifArtifactCache::key(b"fn a() {}", "rustc 1.81.0", &options));
+
+        let release = CompileOptions { release: true, ..Default::default() };
+        assert_ne!(key, NifArtifactCache::key(b"fn a() {}", "rustc 1.80.0", &release));
+        let offline = CompileOptions {
+            offline: Some(OfflineOptions {
+                allowed_dependencies: vec![VendoredDependency::new("itoa", "1.0.9")],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert_ne!(key, NifArtifactCache::key(b"fn a() {}", "rustc 1.80.0", &offline));
+        // Where the library is copied to does not change the artifact
+        let output = CompileOptions { output_dir: Some(PathBuf::from("/tmp")), ..Default::default() };
+        assert_eq!(key, NifArtifactCache::key(b"fn a() {}", "rustc 1.80.0", &output));
+    }
+
+    #[test]
+    fn test_store_lookup_and_invalidate() {
+        let dir = tempfile::tempdir().unwrap();
+        let build = tempfile::tempdir().unwrap();
+        let cache = NifArtifactCache::new(dir.path().join("cache"));
+        assert_eq!(cache.lookup("k1", "libx.so"), None);
+        assert_eq!(cache.size().unwrap(), 0);
+
+        let built = write_library(build.path(), "libx.so", 10);
+        let cached = cache.store("k1", &built, "libx.so").unwrap();
+        assert_eq!(cache.lookup("k1", "libx.so"), Some(cached.clone()));
+        assert_eq!(fs::read(&cached).unwrap().len(), 10);
+        assert_eq!(cache.size().unwrap(), 10);
+
+        assert!(cache.invalidate("k1").unwrap());
+        assert!(!cache.invalidate("k1").unwrap());
+        assert_eq!(cache.lookup("k1", "libx.so"), None);
+
+        cache.store("k2", &built, "libx.so").unwrap();
+        cache.clear().unwrap();
+        assert_eq!(cache.size().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_store_evicts_least_recently_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let build = tempfile::tempdir().unwrap();
+        let cache = NifArtifactCache::with_size_limit(dir.path(), 25);
+        let built = write_library(build.path(), "libx.so", 10);
+
+        let old = cache.store("old", &built, "libx.so").unwrap();
+        let used = cache.store("used", &built, "libx.so").unwrap();
+        age(&old, 60);
+        age(&used, 120);
+        // A hit makes "used" the most recently used entry
+        cache.lookup("used", "libx.so").unwrap();
+
+        cache.store("new", &built, "libx.so").unwrap();
+        assert_eq!(cache.lookup("old", "libx.so"), None);
+        assert!(cache.lookup("used", "libx.so").is_some());
+        assert!(cache.lookup("new", "libx.so").is_some());
+        assert_eq!(cache.size().unwrap(), 20);
+
+        // An entry larger than the limit is kept until the next store
+        let large = write_library(build.path(), "liby.so", 30);
+        cache.store("large", &large, "liby.so").unwrap();
+        assert!(cache.lookup("large", "liby.so").is_some());
+        assert_eq!(cache.size().unwrap(), 30);
+    }
+}
@@ -2,12 +2,22 @@
 //!
 //! Compiles Rust NIF source files on-the-fly using cargo.
 //! Integrates with safe Rust verification to ensure only safe code is compiled.
+//!
+//! ## Offline Compilation
+//!
+//! With [`CompileOptions::offline`] set, compilation never touches the network:
+//! only the allow-listed dependencies are copied from a vendor directory into
+//! the build, cargo's crates.io source is replaced by them, cargo runs with
+//! `--offline` and `--locked`, and dependencies with build scripts or
+//! procedural macros are rejected so no code runs at build time. This makes on-the-fly NIF compilation usable
+//! in locked-down production environments.
 
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use crate::safe_rust_verifier::{SafeRustVerifier, VerificationResult};
 use crate::nif_interface_verifier::{NifInterfaceVerifier, NifInterfaceResult};
+use crate::nif_cache::NifArtifactCache;
 
 /// Options for NIF compilation
 #[derive(Debug, Clone)]
@@ -20,6 +30,8 @@ pub struct CompileOptions {
     pub cargo_flags: Vec<String>,
     /// Output directory for compiled library
     pub output_dir: Option<PathBuf>,
+    /// Compile offline against vendored dependencies
+    pub offline: Option<OfflineOptions>,
 }
 
 impl Default for CompileOptions {
@@ -29,6 +41,39 @@ impl Default for CompileOptions {
             release: false,
             cargo_flags: Vec::new(),
             output_dir: None,
+            offline: None,
+        }
+    }
+}
+
+/// Options for offline compilation against vendored dependencies
+#[derive(Debug, Clone, Default)]
+pub struct OfflineOptions {
+    /// Directory of vendored crate sources, as produced by `cargo vendor`
+    pub vendor_dir: PathBuf,
+    /// Dependencies NIFs may use; only these crates are vendored into the build,
+    /// so they must include the dependencies of the dependencies
+    pub allowed_dependencies: Vec<VendoredDependency>,
+    /// Cargo.lock pinning the allowed dependencies; when not given, one is
+    /// generated offline from the vendored crates
+    pub lockfile: Option<PathBuf>,
+}
+
+/// A dependency available to NIFs in offline compilation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VendoredDependency {
+    /// Crate name
+    pub name: String,
+    /// Exact crate version
+    pub version: String,
+}
+
+impl VendoredDependency {
+    /// Create a vendored dependency
+    pub fn new(name: &str, version: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
         }
     }
 }
@@ -51,6 +96,8 @@ pub enum CompileError {
     NotRustFile(PathBuf),
     /// Safe Rust verification failed
     UnsafeCodeFound(Vec<crate::safe_rust_verifier::UnsafeLocation>),
+    /// Safe Rust verification found `#[no_mangle]` symbols exported twice
+    SymbolCollision(Vec<crate::safe_rust_verifier::UnsafeLocation>),
     /// Cargo not found in PATH
     CargoNotFound,
     /// Compilation failed
@@ -73,6 +120,17 @@ pub enum CompileError {
         /// List of interface errors
         errors: Vec<crate::nif_interface_verifier::NifInterfaceError>,
     },
+    /// Allow-listed dependency not found in the vendor directory
+    DependencyNotVendored {
+        /// Crate name
+        name: String,
+        /// Crate version
+        version: String,
+    },
+    /// Vendored dependency has a build script, which offline compilation rejects
+    BuildScriptNotAllowed(String),
+    /// Vendored dependency is a procedural macro crate, which offline compilation rejects
+    ProcMacroNotAllowed(String),
 }
 
 impl std::fmt::Display for CompileError {
@@ -87,6 +145,9 @@ impl std::fmt::Display for CompileError {
             CompileError::UnsafeCodeFound(locations) => {
                 write!(f, "Unsafe code found in {} locations", locations.len())
             }
+            CompileError::SymbolCollision(locations) => {
+                write!(f, "Exported symbol collision in {} locations", locations.len())
+            }
             CompileError::CargoNotFound => {
                 write!(f, "Cargo not found in PATH. Please install Rust toolchain.")
             }
@@ -112,6 +173,15 @@ impl std::fmt::Display for CompileError {
                 }
                 Ok(())
             }
+            CompileError::DependencyNotVendored { name, version } => {
+                write!(f, "Dependency not found in vendor directory: {} {}", name, version)
+            }
+            CompileError::BuildScriptNotAllowed(name) => {
+                write!(f, "Dependency has a build script, not allowed in offline compilation: {}", name)
+            }
+            CompileError::ProcMacroNotAllowed(name) => {
+                write!(f, "Dependency is a procedural macro, not allowed in offline compilation: {}", name)
+            }
         }
     }
 }
@@ -121,6 +191,8 @@ impl std::error::Error for CompileError {}
 /// Compiler for Rust NIFs
 pub struct NifCompiler {
     verifier: SafeRustVerifier,
+    /// Cache of compiled libraries, if enabled
+    cache: Option<NifArtifactCache>,
 }
 
 impl NifCompiler {
@@ -128,9 +200,41 @@ impl NifCompiler {
     pub fn new() -> Self {
         Self {
             verifier: SafeRustVerifier::new(),
+            cache: None,
         }
     }
 
+    /// Create a NIF compiler that caches compiled libraries
+    ///
+    /// Compiling a source again with the same toolchain and options returns
+    /// the cached library, with `was_cached` set, instead of running cargo.
+    pub fn with_cache(cache: NifArtifactCache) -> Self {
+        Self {
+            verifier: SafeRustVerifier::new(),
+            cache: Some(cache),
+        }
+    }
+
+    /// Get the artifact cache, if enabled
+    pub fn cache(&self) -> Option<&NifArtifactCache> {
+        self.cache.as_ref()
+    }
+
+    /// Remove the cached library built from a source file with the given options
+    ///
+    /// # Returns
+    /// * `Ok(true)` if a cached library was removed
+    /// * `Ok(false)` if none was cached or caching is disabled
+    /// * `Err(CompileError)` if the source or the cache cannot be accessed
+    pub fn invalidate_cached(&self, source_path: &Path, options: &CompileOptions) -> Result<bool, CompileError> {
+        let Some(cache) = &self.cache else {
+            return Ok(false);
+        };
+        let source = fs::read(source_path).map_err(|e| CompileError::IoError(e.to_string()))?;
+        let key = NifArtifactCache::key(&source, &Self::toolchain_version(), options);
+        cache.invalidate(&key).map_err(|e| CompileError::IoError(e.to_string()))
+    }
+
     /// Compile a Rust NIF source file
     ///
     /// This function:
@@ -170,6 +274,9 @@ impl NifCompiler {
                 VerificationResult::Unsafe { locations } => {
                     return Err(CompileError::UnsafeCodeFound(locations));
                 }
+                VerificationResult::SymbolCollision { locations } => {
+                    return Err(CompileError::SymbolCollision(locations));
+                }
             }
         }
 
@@ -189,10 +296,6 @@ impl NifCompiler {
             return Err(CompileError::CargoNotFound);
         }
 
-        // Create a temporary directory for the crate
-        let temp_dir = tempfile::tempdir()
-            .map_err(|e| CompileError::TempDirCreationFailed(e.to_string()))?;
-
         // Generate crate name from source file name
         let crate_name = source_path
             .file_stem()
@@ -200,9 +303,36 @@ impl NifCompiler {
             .unwrap_or("nif_lib")
             .to_string()
             .replace('-', "_");
+        let library_name = Self::library_file_name(&crate_name);
+
+        // Reuse a library built from the same source, toolchain and options
+        let cache_key = match &self.cache {
+            Some(cache) => {
+                let source = fs::read(source_path)
+                    .map_err(|e| CompileError::IoError(e.to_string()))?;
+                let key = NifArtifactCache::key(&source, &Self::toolchain_version(), &options);
+                if let Some(cached) = cache.lookup(&key, &library_name) {
+                    return Ok(CompileResult {
+                        library_path: Self::copy_to_output_dir(cached, &library_name, &options)?,
+                        was_cached: true,
+                    });
+                }
+                Some(key)
+            }
+            None => None,
+        };
+
+        // Create a temporary directory for the crate
+        let temp_dir = tempfile::tempdir()
+            .map_err(|e| CompileError::TempDirCreationFailed(e.to_string()))?;
 
         // Create Cargo.toml
-        let cargo_toml_content = self.generate_cargo_toml(&crate_name);
+        let dependencies = options
+            .offline
+            .as_ref()
+            .map(|offline| offline.allowed_dependencies.as_slice())
+            .unwrap_or_default();
+        let cargo_toml_content = self.generate_cargo_toml(&crate_name, dependencies);
         let cargo_toml_path = temp_dir.path().join("Cargo.toml");
         fs::write(&cargo_toml_path, cargo_toml_content)
             .map_err(|e| CompileError::CargoTomlWriteFailed(e.to_string()))?;
@@ -217,23 +347,14 @@ impl NifCompiler {
         fs::copy(source_path, &lib_rs_path)
             .map_err(|e| CompileError::IoError(e.to_string()))?;
 
-        // Build cargo command
-        let mut cargo_cmd = Command::new("cargo");
-        cargo_cmd
-            .arg("build")
-            .arg("--lib")
-            .current_dir(temp_dir.path());
-
-        if options.release {
-            cargo_cmd.arg("--release");
-        }
-
-        for flag in &options.cargo_flags {
-            cargo_cmd.arg(flag);
+        // Vendor the allowed dependencies and pin them
+        if let Some(offline) = &options.offline {
+            self.vendor_dependencies(temp_dir.path(), offline)?;
         }
 
         // Execute cargo build
-        let output = cargo_cmd
+        let output = self
+            .cargo_build_command(temp_dir.path(), &options)
             .output()
             .map_err(|e| CompileError::IoError(e.to_string()))?;
 
@@ -252,6 +373,31 @@ impl NifCompiler {
             temp_dir.path().join("target").join("debug")
         };
 
+        let library_path = build_dir.join(&library_name);
+
+        if !library_path.exists() {
+            return Err(CompileError::LibraryNotFound(library_path));
+        }
+
+        // Store the library in the cache, which outlives the build directory
+        let library_path = match (&self.cache, &cache_key) {
+            (Some(cache), Some(key)) => cache
+                .store(key, &library_path, &library_name)
+                .map_err(|e| CompileError::IoError(e.to_string()))?,
+            _ => library_path,
+        };
+
+        // If output_dir is specified, copy the library there
+        let final_library_path = Self::copy_to_output_dir(library_path, &library_name, &options)?;
+
+        Ok(CompileResult {
+            library_path: final_library_path,
+            was_cached: false,
+        })
+    }
+
+    /// File name of the library cargo builds for a crate on this platform
+    fn library_file_name(crate_name: &str) -> String {
         // Determine library extension based on platform
         let lib_ext = if cfg!(target_os = "windows") {
             "dll"
@@ -267,34 +413,70 @@ impl NifCompiler {
             "lib"
         };
 
-        let library_name = format!("{}{}.{}", lib_prefix, crate_name.replace('-', "_"), lib_ext);
-        let library_path = build_dir.join(&library_name);
+        format!("{}{}.{}", lib_prefix, crate_name.replace('-', "_"), lib_ext)
+    }
 
-        if !library_path.exists() {
-            return Err(CompileError::LibraryNotFound(library_path));
+    /// Copy a library to the output directory, if one is specified
+    fn copy_to_output_dir(
+        library_path: PathBuf,
+        library_name: &str,
+        options: &CompileOptions,
+    ) -> Result<PathBuf, CompileError> {
+        let Some(output_dir) = &options.output_dir else {
+            return Ok(library_path);
+        };
+        fs::create_dir_all(output_dir)
+            .map_err(|e| CompileError::IoError(e.to_string()))?;
+        let final_path = output_dir.join(library_name);
+        fs::copy(&library_path, &final_path)
+            .map_err(|e| CompileError::IoError(e.to_string()))?;
+        Ok(final_path)
+    }
+
+    /// Version of the Rust toolchain cargo builds with, part of the cache key
+    fn toolchain_version() -> String {
+        Command::new("rustc")
+            .arg("-vV")
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    /// Build the cargo command compiling the crate in `crate_dir`
+    fn cargo_build_command(&self, crate_dir: &Path, options: &CompileOptions) -> Command {
+        let mut cargo_cmd = Command::new("cargo");
+        cargo_cmd
+            .arg("build")
+            .arg("--lib")
+            .current_dir(crate_dir);
+
+        if options.release {
+            cargo_cmd.arg("--release");
         }
 
-        // If output_dir is specified, copy the library there
-        let final_library_path = if let Some(output_dir) = &options.output_dir {
-            fs::create_dir_all(output_dir)
-                .map_err(|e| CompileError::IoError(e.to_string()))?;
-            let final_path = output_dir.join(&library_name);
-            fs::copy(&library_path, &final_path)
-                .map_err(|e| CompileError::IoError(e.to_string()))?;
-            final_path
-        } else {
-            library_path
-        };
+        if options.offline.is_some() {
+            cargo_cmd
+                .arg("--offline")
+                .arg("--locked")
+                .env("CARGO_NET_OFFLINE", "true");
+        }
 
-        Ok(CompileResult {
-            library_path: final_library_path,
-            was_cached: false,
-        })
+        for flag in &options.cargo_flags {
+            cargo_cmd.arg(flag);
+        }
+
+        cargo_cmd
     }
 
-    /// Generate Cargo.toml content for a NIF library
-    fn generate_cargo_toml(&self, crate_name: &str) -> String {
-        format!(
+    /// Generate Cargo.toml content for a NIF library, with exact-version dependencies
+    fn generate_cargo_toml(
+        &self,
+        crate_name: &str,
+        dependencies: &[VendoredDependency],
+    ) -> String {
+        let mut content = format!(
             r#"[package]
 name = "{}"
 version = "0.1.0"
@@ -304,7 +486,158 @@ edition = "2021"
 crate-type = ["cdylib"]
 "#,
             crate_name
-        )
+        );
+        if !dependencies.is_empty() {
+            content.push_str("\n[dependencies]\n");
+            for dependency in dependencies {
+                content.push_str(&format!("{} = \"={}\"\n", dependency.name, dependency.version));
+            }
+        }
+        content
+    }
+
+    /// Copy the allowed dependencies into `crate_dir/vendor`, point cargo's
+    /// crates.io source at them, and provide a Cargo.lock for `--locked`
+    fn vendor_dependencies(&self, crate_dir: &Path, offline: &OfflineOptions) -> Result<(), CompileError> {
+        let vendor_dir = crate_dir.join("vendor");
+        fs::create_dir_all(&vendor_dir)
+            .map_err(|e| CompileError::IoError(e.to_string()))?;
+
+        for dependency in &offline.allowed_dependencies {
+            let source = Self::find_vendored_crate(&offline.vendor_dir, dependency)?;
+            if Self::has_build_script(&source) {
+                return Err(CompileError::BuildScriptNotAllowed(dependency.name.clone()));
+            }
+            if Self::is_proc_macro(&source) {
+                return Err(CompileError::ProcMacroNotAllowed(dependency.name.clone()));
+            }
+            let dir_name = format!("{}-{}", dependency.name, dependency.version);
+            Self::copy_dir_all(&source, &vendor_dir.join(dir_name))
+                .map_err(|e| CompileError::IoError(e.to_string()))?;
+        }
+
+        let cargo_dir = crate_dir.join(".cargo");
+        fs::create_dir_all(&cargo_dir)
+            .map_err(|e| CompileError::IoError(e.to_string()))?;
+        fs::write(cargo_dir.join("config.toml"), Self::generate_cargo_config())
+            .map_err(|e| CompileError::IoError(e.to_string()))?;
+
+        match &offline.lockfile {
+            Some(lockfile) => {
+                fs::copy(lockfile, crate_dir.join("Cargo.lock"))
+                    .map_err(|e| CompileError::IoError(e.to_string()))?;
+            }
+            None => {
+                let output = Command::new("cargo")
+                    .arg("generate-lockfile")
+                    .arg("--offline")
+                    .env("CARGO_NET_OFFLINE", "true")
+                    .current_dir(crate_dir)
+                    .output()
+                    .map_err(|e| CompileError::IoError(e.to_string()))?;
+                if !output.status.success() {
+                    return Err(CompileError::CompilationFailed {
+                        message: "Cargo lockfile generation failed".to_string(),
+                        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Generate the cargo configuration replacing crates.io with the vendored crates
+    fn generate_cargo_config() -> String {
+        r#"[source.crates-io]
+replace-with = "vendored-sources"
+
+[source.vendored-sources]
+directory = "vendor"
+
+[net]
+offline = true
+"#
+        .to_string()
+    }
+
+    /// Find a dependency in a `cargo vendor` directory, which names crate
+    /// directories `name` or, when several versions are vendored, `name-version`
+    fn find_vendored_crate(vendor_dir: &Path, dependency: &VendoredDependency) -> Result<PathBuf, CompileError> {
+        let versioned = vendor_dir.join(format!("{}-{}", dependency.name, dependency.version));
+        let unversioned = vendor_dir.join(&dependency.name);
+        [versioned, unversioned]
+            .into_iter()
+            .find(|dir| {
+                fs::read_to_string(dir.join("Cargo.toml")).ok().is_some_and(|manifest| {
+                    Self::manifest_package_value(&manifest, "version").as_deref()
+                        == Some(dependency.version.as_str())
+                })
+            })
+            .ok_or_else(|| CompileError::DependencyNotVendored {
+                name: dependency.name.clone(),
+                version: dependency.version.clone(),
+            })
+    }
+
+    /// Check whether a crate has a build script, either an explicit
+    /// `build = "..."` or an auto-detected `build.rs`
+    fn has_build_script(crate_dir: &Path) -> bool {
+        let manifest = fs::read_to_string(crate_dir.join("Cargo.toml")).unwrap_or_default();
+        match Self::manifest_package_value(&manifest, "build").as_deref() {
+            Some("false") => false,
+            Some(_) => true,
+            None => crate_dir.join("build.rs").exists(),
+        }
+    }
+
+    /// Check whether a crate is a procedural macro, whose code the compiler
+    /// runs while building its dependents
+    fn is_proc_macro(crate_dir: &Path) -> bool {
+        let manifest = fs::read_to_string(crate_dir.join("Cargo.toml")).unwrap_or_default();
+        ["proc-macro", "proc_macro"]
+            .iter()
+            .any(|key| Self::manifest_value(&manifest, "lib", key).as_deref() == Some("true"))
+    }
+
+    /// Read a key of the `[package]` section of a manifest, without quotes
+    fn manifest_package_value(manifest: &str, key: &str) -> Option<String> {
+        Self::manifest_value(manifest, "package", key)
+    }
+
+    /// Read a key of a section of a manifest, without quotes
+    fn manifest_value(manifest: &str, section: &str, key: &str) -> Option<String> {
+        let header = format!("[{}]", section);
+        let mut in_section = false;
+        for line in manifest.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_section = line == header;
+                continue;
+            }
+            if !in_section {
+                continue;
+            }
+            if let Some((name, value)) = line.split_once('=') {
+                if name.trim() == key {
+                    return Some(value.trim().trim_matches('"').to_string());
+                }
+            }
+        }
+        None
+    }
+
+    /// Recursively copy a directory
+    fn copy_dir_all(source: &Path, destination: &Path) -> std::io::Result<()> {
+        fs::create_dir_all(destination)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            let target = destination.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                Self::copy_dir_all(&entry.path(), &target)?;
+            } else {
+                fs::copy(entry.path(), target)?;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -340,13 +673,14 @@ impl From<crate::safe_rust_verifier::VerificationError> for CompileError {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::nif_cache::NifArtifactCache;
     use std::fs;
     use std::path::PathBuf;
 
     #[test]
     fn test_generate_cargo_toml() {
         let compiler = NifCompiler::new();
-        let toml = compiler.generate_cargo_toml("test_nif");
+        let toml = compiler.generate_cargo_toml("test_nif", &[]);
         assert!(toml.contains("name = \"test_nif\""));
         assert!(toml.contains("crate-type = [\"cdylib\"]"));
     }
@@ -398,6 +732,7 @@ mod tests {
             release: true,
             cargo_flags: vec!["--verbose".to_string()],
             output_dir: output_dir.clone(),
+            offline: None,
         };
         assert!(!options.verify_safe);
         assert!(options.release);
@@ -451,6 +786,7 @@ mod tests {
             crate::safe_rust_verifier::UnsafeLocation {
                 file: PathBuf::from("test.rs"),
                 line: Some(10),
+                column: None,
                 description: "unsafe block".to_string(),
             },
         ];
@@ -503,6 +839,25 @@ mod tests {
         let _ = std::fs::remove_file(&rs_path);
     }
 
+    #[test]
+    fn test_compile_with_symbol_collision() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let rs_path = temp_dir.path().join("collision.rs");
+        std::fs::write(&rs_path, r#"
+            #[no_mangle]
+            pub extern "C" fn rust_safe_library_marker() -> u32 { 0 }
+
+            #[no_mangle]
+            pub extern "C" fn rust_safe_library_marker() -> u32 { 1 }
+        "#).unwrap();
+
+        let result = NifCompiler::new().compile(&rs_path, CompileOptions { verify_safe: true, ..Default::default() });
+        match result {
+            Err(CompileError::SymbolCollision(locations)) => assert_eq!(locations[0].line, Some(6)),
+            other => panic!("expected a symbol collision, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_compile_without_verify_safe() {
         // Test compilation without safe verification (should still fail on other errors)
@@ -564,12 +919,12 @@ mod tests {
         let compiler = NifCompiler::new();
         
         // Test with normal name
-        let toml1 = compiler.generate_cargo_toml("my_nif");
+        let toml1 = compiler.generate_cargo_toml("my_nif", &[]);
         assert!(toml1.contains("name = \"my_nif\""));
         
         // Test with hyphenated name (generate_cargo_toml receives already converted name)
         // The conversion happens in compile() function
-        let toml2 = compiler.generate_cargo_toml("my_nif");
+        let toml2 = compiler.generate_cargo_toml("my_nif", &[]);
         assert!(toml2.contains("name = \"my_nif\""));
     }
 
@@ -580,6 +935,7 @@ mod tests {
             crate::safe_rust_verifier::UnsafeLocation {
                 file: PathBuf::from("test.rs"),
                 line: Some(10),
+                column: None,
                 description: "unsafe block".to_string(),
             },
         ];
@@ -634,6 +990,7 @@ pub fn test() -> i32 { 42 }
             release: false,
             cargo_flags: Vec::new(),
             output_dir: None,
+            offline: None,
         };
         
         // This will test the platform-specific library extension logic
@@ -690,6 +1047,7 @@ pub extern "C" fn rust_safe_library_marker() -> u32 { 0x53414645 }
             release: false,
             cargo_flags: vec!["--verbose".to_string(), "--message-format=short".to_string()],
             output_dir: None,
+            offline: None,
         };
         
         // This tests that the cargo_flags loop executes
@@ -715,6 +1073,7 @@ pub extern "C" fn rust_safe_library_marker() -> u32 { 0x53414645 }
             release: false,
             cargo_flags: Vec::new(),
             output_dir: Some(output_dir.clone()),
+            offline: None,
         };
         
         // This tests the output_dir branch (lines 254-260)
@@ -926,6 +1285,7 @@ pub extern "C" fn nif_init() -> *const u8 {
             release: false,
             cargo_flags: Vec::new(),
             output_dir: Some(output_dir.clone()),
+            offline: None,
         };
         
         // This tests the output_dir branch where library is copied
@@ -976,6 +1336,7 @@ pub extern "C" fn nif_init() -> *const u8 {
             release: false,
             cargo_flags: vec!["--verbose".to_string(), "--offline".to_string()],
             output_dir: None,
+            offline: None,
         };
         
         // This tests the cargo_flags loop
@@ -1067,5 +1428,219 @@ pub extern "C" fn nif_init() -> *const u8 {
         let error_ref: &dyn Error = &error;
         assert!(error_ref.source().is_none());
     }
+
+    /// Write a vendored crate as `cargo vendor` lays it out
+    fn write_vendored_crate(vendor_dir: &Path, dir_name: &str, name: &str, version: &str, build_rs: bool) {
+        let crate_dir = vendor_dir.join(dir_name);
+        fs::create_dir_all(crate_dir.join("src")).unwrap();
+        fs::write(
+            crate_dir.join("Cargo.toml"),
+            format!("[package]\nname = \"{}\"\nversion = \"{}\"\nedition = \"2021\"\n\n[lib]\npath = \"src/lib.rs\"\n", name, version),
+        )
+        .unwrap();
+        fs::write(crate_dir.join("src").join("lib.rs"), "pub fn answer() -> i32 { 42 }\n").unwrap();
+        fs::write(crate_dir.join(".cargo-checksum.json"), r#"{"files":{},"package":null}"#).unwrap();
+        if build_rs {
+            fs::write(crate_dir.join("build.rs"), "fn main() {}\n").unwrap();
+        }
+    }
+
+    #[test]
+    fn test_cargo_build_command_offline_flags() {
+        let compiler = NifCompiler::new();
+        let online = compiler.cargo_build_command(Path::new("/tmp"), &CompileOptions::default());
+        assert!(!online.get_args().any(|arg| arg == "--offline"));
+
+        let options = CompileOptions {
+            offline: Some(OfflineOptions::default()),
+            ..Default::default()
+        };
+        let offline = compiler.cargo_build_command(Path::new("/tmp"), &options);
+        let args: Vec<_> = offline.get_args().collect();
+        assert!(args.contains(&std::ffi::OsStr::new("--offline")));
+        assert!(args.contains(&std::ffi::OsStr::new("--locked")));
+    }
+
+    #[test]
+    fn test_generate_cargo_toml_with_dependencies() {
+        let compiler = NifCompiler::new();
+        let toml = compiler.generate_cargo_toml(
+            "test_nif",
+            &[VendoredDependency::new("itoa", "1.0.9")],
+        );
+        assert!(toml.contains("[dependencies]\nitoa = \"=1.0.9\""));
+        assert!(!compiler.generate_cargo_toml("test_nif", &[]).contains("[dependencies]"));
+    }
+
+    #[test]
+    fn test_find_vendored_crate_and_build_scripts() {
+        let vendor_dir = tempfile::tempdir().unwrap();
+        write_vendored_crate(vendor_dir.path(), "plain", "plain", "1.0.0", false);
+        write_vendored_crate(vendor_dir.path(), "scripted-0.2.0", "scripted", "0.2.0", true);
+
+        let plain = NifCompiler::find_vendored_crate(vendor_dir.path(), &VendoredDependency::new("plain", "1.0.0")).unwrap();
+        assert!(!NifCompiler::has_build_script(&plain));
+        let scripted = NifCompiler::find_vendored_crate(vendor_dir.path(), &VendoredDependency::new("scripted", "0.2.0")).unwrap();
+        assert!(NifCompiler::has_build_script(&scripted));
+        assert!(matches!(
+            NifCompiler::find_vendored_crate(vendor_dir.path(), &VendoredDependency::new("plain", "2.0.0")),
+            Err(CompileError::DependencyNotVendored { .. })
+        ));
+
+        let manifest = "[package]\nname = \"x\"\nbuild = false\n\n[dependencies]\nbuild = \"1\"\n";
+        assert_eq!(NifCompiler::manifest_package_value(manifest, "build").as_deref(), Some("false"));
+        assert_eq!(NifCompiler::manifest_package_value(manifest, "version"), None);
+    }
+
+    #[test]
+    fn test_compile_offline_rejects_build_scripts_and_missing_dependencies() {
+        let compiler = NifCompiler::new();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source_path = temp_dir.path().join("offline_nif.rs");
+        fs::write(&source_path, r#"
+#[no_mangle]
+pub extern "C" fn nif_init() -> *const u8 {
+    std::ptr::null()
+}
+"#).unwrap();
+        let vendor_dir = temp_dir.path().join("vendor");
+        write_vendored_crate(&vendor_dir, "scripted", "scripted", "0.2.0", true);
+
+        let options = |dependency: VendoredDependency| CompileOptions {
+            offline: Some(OfflineOptions {
+                vendor_dir: vendor_dir.clone(),
+                allowed_dependencies: vec![dependency],
+                lockfile: None,
+            }),
+            ..Default::default()
+        };
+        let result = compiler.compile(&source_path, options(VendoredDependency::new("scripted", "0.2.0")));
+        if !matches!(result, Err(CompileError::CargoNotFound)) {
+            assert!(matches!(result, Err(CompileError::BuildScriptNotAllowed(name)) if name == "scripted"));
+        }
+        let result = compiler.compile(&source_path, options(VendoredDependency::new("missing", "1.0.0")));
+        if !matches!(result, Err(CompileError::CargoNotFound)) {
+            assert!(matches!(result, Err(CompileError::DependencyNotVendored { .. })));
+        }
+
+        let err = CompileError::BuildScriptNotAllowed("scripted".to_string());
+        assert!(format!("{}", err).contains("build script"));
+    }
+
+    #[test]
+    fn test_compile_offline_rejects_proc_macros() {
+        let compiler = NifCompiler::new();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source_path = temp_dir.path().join("offline_nif.rs");
+        fs::write(&source_path, r#"
+#[no_mangle]
+pub extern "C" fn nif_init() -> *const u8 {
+    std::ptr::null()
 }
+"#).unwrap();
+        let vendor_dir = temp_dir.path().join("vendor");
+        write_vendored_crate(&vendor_dir, "derive", "derive", "1.0.0", false);
+        let manifest = vendor_dir.join("derive").join("Cargo.toml");
+        let content = fs::read_to_string(&manifest).unwrap().replace("[lib]
+", "[lib]
+proc-macro = true
+");
+        fs::write(&manifest, content).unwrap();
+        write_vendored_crate(&vendor_dir, "plain", "plain", "1.0.0", false);
+
+        assert!(NifCompiler::is_proc_macro(&vendor_dir.join("derive")));
+        assert!(!NifCompiler::is_proc_macro(&vendor_dir.join("plain")));
+        let manifest = "[package]\nname = \"x\"\n\n[lib]\nproc_macro = true\n";
+        assert_eq!(NifCompiler::manifest_value(manifest, "lib", "proc_macro").as_deref(), Some("true"));
+        assert_eq!(NifCompiler::manifest_value(manifest, "package", "proc_macro"), None);
+
+        let options = CompileOptions {
+            offline: Some(OfflineOptions {
+                vendor_dir: vendor_dir.clone(),
+                allowed_dependencies: vec![VendoredDependency::new("derive", "1.0.0")],
+                lockfile: None,
+            }),
+            ..Default::default()
+        };
+        let result = compiler.compile(&source_path, options);
+        if !matches!(result, Err(CompileError::CargoNotFound)) {
+            assert!(matches!(result, Err(CompileError::ProcMacroNotAllowed(name)) if name == "derive"));
+        }
 
+        let err = CompileError::ProcMacroNotAllowed("derive".to_string());
+        assert!(format!("{}", err).contains("procedural macro"));
+    }
+
+    #[test]
+    fn test_compile_offline_with_vendored_dependency() {
+        let compiler = NifCompiler::new();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source_path = temp_dir.path().join("vendored_nif.rs");
+        fs::write(&source_path, r#"
+#[no_mangle]
+pub extern "C" fn nif_init() -> *const u8 {
+    std::ptr::null()
+}
+
+pub fn answer() -> i32 {
+    plain::answer()
+}
+"#).unwrap();
+        let vendor_dir = temp_dir.path().join("vendor");
+        write_vendored_crate(&vendor_dir, "plain", "plain", "1.0.0", false);
+
+        let options = CompileOptions {
+            offline: Some(OfflineOptions {
+                vendor_dir,
+                allowed_dependencies: vec![VendoredDependency::new("plain", "1.0.0")],
+                lockfile: None,
+            }),
+            output_dir: Some(temp_dir.path().join("out")),
+            ..Default::default()
+        };
+        match compiler.compile(&source_path, options) {
+            Ok(result) => assert!(result.library_path.exists()),
+            Err(CompileError::CargoNotFound) => {}
+            Err(err) => panic!("offline compilation failed: {}", err),
+        }
+    }
+
+    #[test]
+    fn test_compile_with_cache_skips_recompilation() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let compiler = NifCompiler::with_cache(NifArtifactCache::new(temp_dir.path().join("cache")));
+        assert!(compiler.cache().is_some());
+        let source_path = temp_dir.path().join("cached_nif.rs");
+        fs::write(&source_path, r#"
+#[no_mangle]
+pub extern "C" fn nif_init() -> *const u8 {
+    std::ptr::null()
+}
+"#).unwrap();
+        let options = CompileOptions {
+            output_dir: Some(temp_dir.path().join("out")),
+            ..Default::default()
+        };
+
+        let first = match compiler.compile(&source_path, options.clone()) {
+            Ok(result) => result,
+            Err(CompileError::CargoNotFound) => return,
+            Err(err) => panic!("compilation failed: {}", err),
+        };
+        assert!(!first.was_cached);
+        assert!(first.library_path.exists());
+        assert!(compiler.cache().unwrap().size().unwrap() > 0);
+
+        let second = compiler.compile(&source_path, options.clone()).unwrap();
+        assert!(second.was_cached);
+        assert_eq!(second.library_path, first.library_path);
+
+        // Different options build a different artifact
+        let release = CompileOptions { release: true, ..options.clone() };
+        assert!(!compiler.invalidate_cached(&source_path, &release).unwrap());
+
+        assert!(compiler.invalidate_cached(&source_path, &options).unwrap());
+        assert!(!compiler.compile(&source_path, options).unwrap().was_cached);
+        assert!(!NifCompiler::new().invalidate_cached(&source_path, &CompileOptions::default()).unwrap());
+    }
+}
@@ -3,12 +3,38 @@
 //! Verifies that Rust NIF code has the proper interface requirements:
 //! - `nif_init()` function with `#[no_mangle]` and `extern "C"`
 //! - NIF functions with `#[no_mangle]` and `extern "C"`
-//! - Proper function signatures matching Erlang's expectations
-
+//! - Optional `nif_load()`, `nif_upgrade()` and `nif_unload()` lifecycle
+//!   callbacks with `#[no_mangle]`, `extern "C"` and the signatures the NIF
+//!   loader calls them with when the library is loaded, upgraded on module
+//!   reload, and unloaded on purge
+//! - Proper function signatures matching Erlang's expectations: every NIF
+//!   listed in a `FunctionMetadata` entry of the function table is exported
+//!   and has the `(env, argc, argv) -> NifTerm` signature the runtime calls
+//!   it with, and the table's arities are valid and unique
+
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
-use syn::{File, Item, ItemFn, ItemStatic, Attribute, Signature, ReturnType, Type, TypePath};
-use crate::safe_rust_verifier::VerificationError;
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+use syn::{
+    File, Item, ItemFn, ItemStatic, Attribute, Signature, ReturnType, Type, TypePath, Expr,
+    ExprLit, ExprStruct, FnArg, Lit, Macro, Member, Token,
+};
+use crate::safe_rust_verifier::{exported_symbol, VerificationError};
+
+/// Maximum arity of an Erlang function
+const MAX_ARITY: u64 = 255;
+
+/// Type names accepted for a NIF term (`ERL_NIF_TERM`)
+const TERM_TYPES: [&str; 4] = ["NifTerm", "ERL_NIF_TERM", "Eterm", "u64"];
+
+/// Type names accepted for a NIF's argument count
+const ARGC_TYPES: [&str; 2] = ["c_int", "i32"];
+
+/// Signature every NIF in the function table must have
+const NIF_SIGNATURE: &str =
+    "extern \"C\" fn(env: *mut ErlNifEnv, argc: c_int, argv: *const NifTerm) -> NifTerm";
 
 /// Result of NIF interface verification
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -52,6 +78,27 @@ pub enum NifInterfaceError {
     EmptyRustNifMetadata,
     /// nif_get_metadata() return type should be *const RustNifMetadata
     WrongMetadataReturnType,
+    /// Lifecycle callback (nif_load, nif_upgrade or nif_unload) has wrong signature
+    LifecycleCallbackWrongSignature {
+        function_name: String,
+        reason: String,
+    },
+    /// Function table entry names a symbol no function exports
+    FunctionNotExported {
+        /// Erlang name and arity of the entry (`name/arity`)
+        function_name: String,
+        symbol_name: String,
+    },
+    /// Function table entry arity is not an integer literal up to 255
+    InvalidFunctionArity {
+        function_name: String,
+        reason: String,
+    },
+    /// Function table lists the same name and arity twice
+    DuplicateFunctionEntry {
+        /// Erlang name and arity of the entry (`name/arity`)
+        function_name: String,
+    },
 }
 
 impl std::fmt::Display for NifInterfaceError {
@@ -87,10 +134,38 @@ impl std::fmt::Display for NifInterfaceError {
             NifInterfaceError::WrongMetadataReturnType => {
                 write!(f, "nif_get_metadata() must return *const RustNifMetadata")
             }
+            NifInterfaceError::LifecycleCallbackWrongSignature { function_name, reason } => {
+                write!(f, "Lifecycle callback '{}' has wrong signature: {}", function_name, reason)
+            }
+            NifInterfaceError::FunctionNotExported { function_name, symbol_name } => {
+                write!(
+                    f,
+                    "NIF {} names symbol '{}', but no #[no_mangle] function exports it",
+                    function_name, symbol_name
+                )
+            }
+            NifInterfaceError::InvalidFunctionArity { function_name, reason } => {
+                write!(f, "NIF '{}' has invalid arity: {}", function_name, reason)
+            }
+            NifInterfaceError::DuplicateFunctionEntry { function_name } => {
+                write!(f, "NIF {} is listed more than once in the function table", function_name)
+            }
         }
     }
 }
 
+/// Lifecycle callbacks the NIF loader looks up, with their parameter count
+/// and whether they return a status code
+///
+/// - `nif_load(priv_data, load_info) -> i32`
+/// - `nif_upgrade(priv_data, old_priv_data, load_info) -> i32`
+/// - `nif_unload(priv_data)`
+const LIFECYCLE_CALLBACKS: [(&str, usize, bool); 3] = [
+    ("nif_load", 2, true),
+    ("nif_upgrade", 3, true),
+    ("nif_unload", 1, false),
+];
+
 /// Verifier for NIF interface requirements
 pub struct NifInterfaceVerifier;
 
@@ -179,6 +254,12 @@ impl NifInterfaceVerifier {
                 if fn_name == "rust_safe_library_marker" || fn_name == "nif_init" {
                     continue;
                 }
+
+                // Lifecycle callbacks are looked up by name, so they must be exported
+                if LIFECYCLE_CALLBACKS.iter().any(|(name, _, _)| fn_name == *name) {
+                    self.check_lifecycle_callback(item_fn, &mut errors);
+                    continue;
+                }
                 
                 // Check if this has #[no_mangle] - if so, it's intended to be a NIF function
                 if Self::has_no_mangle(&item_fn.attrs) {
@@ -187,6 +268,11 @@ impl NifInterfaceVerifier {
             }
         }
 
+        // Check the NIFs listed in the function table
+        let mut table = FunctionTableCollector::default();
+        table.visit_file(&ast);
+        self.check_function_table(&table, &mut errors);
+
         if errors.is_empty() {
             Ok(NifInterfaceResult::Valid)
         } else {
@@ -355,6 +441,230 @@ impl NifInterfaceVerifier {
         // The exact signature can vary depending on the NIF implementation
         // Full signature validation would require more complex type checking
     }
+
+    /// Check the function table entries against the exported functions
+    fn check_function_table(&self, table: &FunctionTableCollector, errors: &mut Vec<NifInterfaceError>) {
+        let mut seen = HashSet::new();
+        let mut checked_symbols = HashSet::new();
+        for entry in &table.entries {
+            let name = entry.name.clone().unwrap_or_else(|| "<unnamed>".to_string());
+            let arity = match entry.arity {
+                Some(arity) if arity <= MAX_ARITY => arity,
+                Some(arity) => {
+                    errors.push(NifInterfaceError::InvalidFunctionArity {
+                        function_name: name,
+                        reason: format!("{} exceeds the maximum arity {}", arity, MAX_ARITY),
+                    });
+                    continue;
+                }
+                None => {
+                    errors.push(NifInterfaceError::InvalidFunctionArity {
+                        function_name: name,
+                        reason: "arity must be an integer literal".to_string(),
+                    });
+                    continue;
+                }
+            };
+            let function_name = format!("{}/{}", name, arity);
+            if !seen.insert((name, arity)) {
+                errors.push(NifInterfaceError::DuplicateFunctionEntry { function_name });
+                continue;
+            }
+
+            let Some(symbol_name) = &entry.symbol_name else {
+                continue;
+            };
+            match table.exported.get(symbol_name) {
+                Some(item_fn) => {
+                    if checked_symbols.insert(symbol_name.clone()) {
+                        self.check_nif_signature(item_fn, errors);
+                    }
+                }
+                None => errors.push(NifInterfaceError::FunctionNotExported {
+                    function_name,
+                    symbol_name: symbol_name.clone(),
+                }),
+            }
+        }
+    }
+
+    /// Check that a NIF has the `(env, argc, argv) -> NifTerm` signature
+    fn check_nif_signature(&self, item_fn: &ItemFn, errors: &mut Vec<NifInterfaceError>) {
+        let function_name = item_fn.sig.ident.to_string();
+        let mut wrong = |reason: String| {
+            errors.push(NifInterfaceError::FunctionWrongSignature {
+                function_name: function_name.clone(),
+                reason: format!("{}; expected {}", reason, NIF_SIGNATURE),
+            });
+        };
+
+        let types: Vec<Option<&Type>> = item_fn
+            .sig
+            .inputs
+            .iter()
+            .map(|input| match input {
+                FnArg::Typed(pat_type) => Some(pat_type.ty.as_ref()),
+                FnArg::Receiver(_) => None,
+            })
+            .collect();
+        if types.len() != 3 {
+            wrong(format!("takes {} parameter(s) instead of 3", types.len()));
+        } else {
+            if !types[0].is_some_and(|ty| self.is_pointer_type(ty)) {
+                wrong("env (parameter 1) must be a pointer to the environment".to_string());
+            }
+            if !types[1].is_some_and(|ty| Self::is_named_type(ty, &ARGC_TYPES)) {
+                wrong("argc (parameter 2) must be c_int".to_string());
+            }
+            let argv_is_terms = matches!(
+                types[2],
+                Some(Type::Ptr(ptr)) if ptr.const_token.is_some() && Self::is_named_type(&ptr.elem, &TERM_TYPES)
+            );
+            if !argv_is_terms {
+                wrong("argv (parameter 3) must be *const NifTerm".to_string());
+            }
+        }
+
+        let returns_term = match &item_fn.sig.output {
+            ReturnType::Type(_, ty) => Self::is_named_type(ty, &TERM_TYPES),
+            ReturnType::Default => false,
+        };
+        if !returns_term {
+            wrong("must return NifTerm".to_string());
+        }
+    }
+
+    /// Check if a type is a path whose last segment is one of `names`
+    fn is_named_type(ty: &Type, names: &[&str]) -> bool {
+        match ty {
+            Type::Path(type_path) => type_path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| names.iter().any(|name| segment.ident == name)),
+            _ => false,
+        }
+    }
+
+    /// Check a lifecycle callback (nif_load, nif_upgrade or nif_unload)
+    fn check_lifecycle_callback(&self, item_fn: &ItemFn, errors: &mut Vec<NifInterfaceError>) {
+        let fn_name = item_fn.sig.ident.to_string();
+        let Some((_, arity, returns_status)) = LIFECYCLE_CALLBACKS
+            .iter()
+            .find(|(name, _, _)| fn_name == *name)
+        else {
+            return;
+        };
+
+        if !Self::has_no_mangle(&item_fn.attrs) {
+            errors.push(NifInterfaceError::FunctionMissingNoMangle {
+                function_name: fn_name.clone(),
+            });
+        }
+        if !Self::has_extern_c(&item_fn.sig) {
+            errors.push(NifInterfaceError::FunctionMissingExternC {
+                function_name: fn_name.clone(),
+            });
+        }
+
+        if item_fn.sig.inputs.len() != *arity {
+            errors.push(NifInterfaceError::LifecycleCallbackWrongSignature {
+                function_name: fn_name.clone(),
+                reason: format!("expected {} parameter(s), found {}", arity, item_fn.sig.inputs.len()),
+            });
+        }
+        let returns_value = !matches!(item_fn.sig.output, ReturnType::Default);
+        if returns_value != *returns_status {
+            let reason = if *returns_status {
+                "must return an i32 status (0 on success)"
+            } else {
+                "must not return a value"
+            };
+            errors.push(NifInterfaceError::LifecycleCallbackWrongSignature {
+                function_name: fn_name,
+                reason: reason.to_string(),
+            });
+        }
+    }
+}
+
+/// A `FunctionMetadata` entry of the function table
+#[derive(Debug, Default)]
+struct FunctionTableEntry {
+    /// Erlang function name
+    name: Option<String>,
+    /// Arity, if given as an integer literal
+    arity: Option<u64>,
+    /// Exported symbol implementing the NIF
+    symbol_name: Option<String>,
+}
+
+/// Syntax tree pass collecting the function table and the exported functions
+#[derive(Default)]
+struct FunctionTableCollector {
+    entries: Vec<FunctionTableEntry>,
+    /// Exported functions by symbol
+    exported: HashMap<String, ItemFn>,
+}
+
+impl FunctionTableCollector {
+    /// String value of a field given as `"x"`, `"x".to_string()`,
+    /// `"x".into()` or `String::from("x")`
+    fn string_value(expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Lit(ExprLit { lit: Lit::Str(value), .. }) => Some(value.value()),
+            Expr::MethodCall(call) if call.args.is_empty() => Self::string_value(&call.receiver),
+            Expr::Call(call) if call.args.len() == 1 => Self::string_value(&call.args[0]),
+            Expr::Reference(reference) => Self::string_value(&reference.expr),
+            _ => None,
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for FunctionTableCollector {
+    fn visit_item_fn(&mut self, item_fn: &'ast ItemFn) {
+        if let Some(symbol) = exported_symbol(&item_fn.attrs, &item_fn.sig.ident) {
+            self.exported.entry(symbol).or_insert_with(|| item_fn.clone());
+        }
+        visit::visit_item_fn(self, item_fn);
+    }
+
+    fn visit_expr_struct(&mut self, expr_struct: &'ast ExprStruct) {
+        let is_entry = expr_struct
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "FunctionMetadata");
+        if is_entry {
+            let mut entry = FunctionTableEntry::default();
+            for field in &expr_struct.fields {
+                let Member::Named(member) = &field.member else {
+                    continue;
+                };
+                if member == "name" {
+                    entry.name = Self::string_value(&field.expr);
+                } else if member == "symbol_name" {
+                    entry.symbol_name = Self::string_value(&field.expr);
+                } else if member == "arity" {
+                    if let Expr::Lit(ExprLit { lit: Lit::Int(arity), .. }) = &field.expr {
+                        entry.arity = arity.base10_parse().ok();
+                    }
+                }
+            }
+            self.entries.push(entry);
+        }
+        visit::visit_expr_struct(self, expr_struct);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        // Function tables are usually built with vec![...]
+        if let Ok(exprs) = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) {
+            for expr in &exprs {
+                self.visit_expr(expr);
+            }
+        }
+        visit::visit_macro(self, mac);
+    }
 }
 
 impl Default for NifInterfaceVerifier {
@@ -985,5 +1295,192 @@ mod tests {
         // Mutable pointer should be accepted
         assert_eq!(result, NifInterfaceResult::Valid);
     }
-}
 
+    #[test]
+    fn test_verify_lifecycle_callbacks() {
+        let verifier = NifInterfaceVerifier::new();
+        let code = r#"
+            #[no_mangle]
+            pub extern "C" fn nif_init() -> *mut u8 {
+                std::ptr::null_mut()
+            }
+
+            #[no_mangle]
+            pub extern "C" fn nif_load(priv_data: &mut PrivData, load_info: u64) -> i32 {
+                0
+            }
+
+            #[no_mangle]
+            pub extern "C" fn nif_upgrade(priv_data: &mut PrivData, old: &mut PrivData, load_info: u64) -> i32 {
+                0
+            }
+
+            #[no_mangle]
+            pub extern "C" fn nif_unload(priv_data: &mut PrivData) {}
+        "#;
+
+        let result = verifier.verify_content(code, Path::new("test.rs")).unwrap();
+        assert_eq!(result, NifInterfaceResult::Valid);
+    }
+
+    #[test]
+    fn test_verify_lifecycle_callbacks_wrong_signature() {
+        let verifier = NifInterfaceVerifier::new();
+        let code = r#"
+            #[no_mangle]
+            pub extern "C" fn nif_init() -> *mut u8 {
+                std::ptr::null_mut()
+            }
+
+            pub extern "C" fn nif_upgrade(priv_data: &mut PrivData, load_info: u64) -> i32 {
+                0
+            }
+
+            #[no_mangle]
+            pub extern "C" fn nif_unload(priv_data: &mut PrivData) -> i32 {
+                0
+            }
+        "#;
+
+        let result = verifier.verify_content(code, Path::new("test.rs")).unwrap();
+        match result {
+            NifInterfaceResult::Invalid { errors } => {
+                assert_eq!(errors.len(), 3);
+                assert!(errors.contains(&NifInterfaceError::FunctionMissingNoMangle {
+                    function_name: "nif_upgrade".to_string(),
+                }));
+                assert!(errors.iter().any(|e| matches!(
+                    e,
+                    NifInterfaceError::LifecycleCallbackWrongSignature { function_name, .. }
+                        if function_name == "nif_upgrade"
+                )));
+                assert!(errors.iter().any(|e| matches!(
+                    e,
+                    NifInterfaceError::LifecycleCallbackWrongSignature { function_name, .. }
+                        if function_name == "nif_unload"
+                )));
+                assert!(errors[1].to_string().contains("Lifecycle callback"));
+            }
+            _ => panic!("Expected invalid result"),
+        }
+    }
+
+    fn interface_errors(code: &str) -> Vec<NifInterfaceError> {
+        match NifInterfaceVerifier::new().verify_content(code, Path::new("test.rs")).unwrap() {
+            NifInterfaceResult::Valid => Vec::new(),
+            NifInterfaceResult::Invalid { errors } => errors,
+        }
+    }
+
+    const FUNCTION_TABLE_PRELUDE: &str = r#"
+        use std::os::raw::{c_int, c_void};
+
+        #[no_mangle]
+        pub extern "C" fn nif_get_metadata() -> *const RustNifMetadata {
+            &NIF_METADATA
+        }
+    "#;
+
+    #[test]
+    fn test_verify_function_table_signatures() {
+        let code = format!("{}{}", FUNCTION_TABLE_PRELUDE, r#"
+            #[no_mangle]
+            pub extern "C" fn nif_add(env: *mut c_void, argc: c_int, argv: *const NifTerm) -> NifTerm {
+                0
+            }
+
+            #[export_name = "nif_sub"]
+            pub extern "C" fn subtract(_env: &NifEnv, _argc: i32, _argv: *const u64) -> u64 {
+                0
+            }
+
+            static NIF_METADATA: RustNifMetadata = RustNifMetadata {
+                module_name: "math".to_string(),
+                version: (2, 17),
+                min_erts_version: None,
+                functions: vec![
+                    FunctionMetadata { name: "add".to_string(), arity: 2, symbol_name: "nif_add".to_string(), flags: 0 },
+                    FunctionMetadata { name: "sub".into(), arity: 2, symbol_name: String::from("nif_sub"), flags: 0 },
+                ],
+            };
+        "#);
+        assert_eq!(interface_errors(&code), Vec::new());
+    }
+
+    #[test]
+    fn test_verify_function_table_wrong_signature() {
+        let code = format!("{}{}", FUNCTION_TABLE_PRELUDE, r#"
+            #[no_mangle]
+            pub extern "C" fn nif_add(env: *mut c_void, argc: usize, argv: *mut NifTerm) -> i32 {
+                0
+            }
+
+            #[no_mangle]
+            pub extern "C" fn nif_neg(a: i64) -> u64 {
+                0
+            }
+
+            static NIF_METADATA: RustNifMetadata = RustNifMetadata {
+                functions: vec![
+                    FunctionMetadata { name: "add".to_string(), arity: 2, symbol_name: "nif_add".to_string(), flags: 0 },
+                    FunctionMetadata { name: "neg".to_string(), arity: 1, symbol_name: "nif_neg".to_string(), flags: 0 },
+                ],
+            };
+        "#);
+        let errors = interface_errors(&code);
+        let reasons: Vec<_> = errors
+            .iter()
+            .map(|error| match error {
+                NifInterfaceError::FunctionWrongSignature { function_name, reason } => {
+                    assert!(reason.contains("expected extern \"C\" fn(env"));
+                    format!("{}: {}", function_name, reason.split(';').next().unwrap())
+                }
+                other => panic!("Unexpected error {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                "nif_add: argc (parameter 2) must be c_int",
+                "nif_add: argv (parameter 3) must be *const NifTerm",
+                "nif_add: must return NifTerm",
+                "nif_neg: takes 1 parameter(s) instead of 3",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_verify_function_table_entries() {
+        let code = format!("{}{}", FUNCTION_TABLE_PRELUDE, r#"
+            #[no_mangle]
+            pub extern "C" fn nif_add(env: *mut c_void, argc: c_int, argv: *const NifTerm) -> NifTerm {
+                0
+            }
+
+            const ARITY: u32 = 2;
+
+            static NIF_METADATA: RustNifMetadata = RustNifMetadata {
+                functions: vec![
+                    FunctionMetadata { name: "add".to_string(), arity: 2, symbol_name: "nif_add".to_string(), flags: 0 },
+                    FunctionMetadata { name: "add".to_string(), arity: 2, symbol_name: "nif_add".to_string(), flags: 0 },
+                    FunctionMetadata { name: "mul".to_string(), arity: 2, symbol_name: "nif_mul".to_string(), flags: 0 },
+                    FunctionMetadata { name: "big".to_string(), arity: 256, symbol_name: "nif_add".to_string(), flags: 0 },
+                    FunctionMetadata { name: "named".to_string(), arity: ARITY, symbol_name: "nif_add".to_string(), flags: 0 },
+                ],
+            };
+        "#);
+        let errors = interface_errors(&code);
+        assert_eq!(errors.len(), 4);
+        assert_eq!(errors[0], NifInterfaceError::DuplicateFunctionEntry { function_name: "add/2".to_string() });
+        assert_eq!(
+            errors[1],
+            NifInterfaceError::FunctionNotExported {
+                function_name: "mul/2".to_string(),
+                symbol_name: "nif_mul".to_string(),
+            }
+        );
+        assert!(matches!(&errors[2], NifInterfaceError::InvalidFunctionArity { function_name, .. } if function_name == "big"));
+        assert!(matches!(&errors[3], NifInterfaceError::InvalidFunctionArity { function_name, .. } if function_name == "named"));
+        assert!(errors[1].to_string().contains("no #[no_mangle] function exports it"));
+    }
+}
@@ -1,11 +1,21 @@
 //! Safe Rust Verifier
 //!
 //! Verifies that Rust source code contains only safe Rust (no unsafe blocks).
-//! Uses the `syn` crate to parse Rust code and detect unsafe blocks.
+//! Uses the `syn` crate to parse Rust code and walks the syntax tree to detect
+//! unsafe code, forbidden crates and colliding exported symbols, reporting the
+//! line and column of each finding.
 
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
-use syn::{File, Item, ItemFn, Expr, spanned::Spanned};
+use proc_macro2::{Ident, Span, TokenStream, TokenTree};
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+use syn::{
+    Attribute, Block, Expr, ExprLit, ExprUnsafe, File, ImplItemFn, ItemExternCrate, ItemFn,
+    ItemForeignMod, ItemImpl, ItemStatic, ItemTrait, ItemUse, Lit, Macro, Meta, Token,
+    TraitItemFn, UseTree,
+};
 
 /// Result of safe Rust verification
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -17,6 +27,11 @@ pub enum VerificationResult {
         /// List of locations where unsafe code was found
         locations: Vec<UnsafeLocation>,
     },
+    /// Code is safe, but exports a symbol more than once
+    SymbolCollision {
+        /// Locations of the exports colliding with an earlier one
+        locations: Vec<UnsafeLocation>,
+    },
 }
 
 /// Location where unsafe code was found
@@ -24,12 +39,27 @@ pub enum VerificationResult {
 pub struct UnsafeLocation {
     /// File path where unsafe code was found
     pub file: PathBuf,
-    /// Line number (approximate, based on span)
+    /// Line number (1-based)
     pub line: Option<usize>,
+    /// Column number (1-based)
+    pub column: Option<usize>,
     /// Description of the unsafe code found
     pub description: String,
 }
 
+impl std::fmt::Display for UnsafeLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+            if let Some(column) = self.column {
+                write!(f, ":{}", column)?;
+            }
+        }
+        write!(f, ": {}", self.description)
+    }
+}
+
 /// Error that can occur during verification
 #[derive(Debug, Clone)]
 pub enum VerificationError {
@@ -64,22 +94,45 @@ impl std::fmt::Display for VerificationError {
 
 impl std::error::Error for VerificationError {}
 
+/// Crates rejected by default because they give safe code direct access
+/// to raw memory, the operating system or dynamic loading
+pub const DEFAULT_FORBIDDEN_CRATES: &[&str] = &[
+    "libc",
+    "libloading",
+    "memmap2",
+    "nix",
+    "winapi",
+    "windows_sys",
+];
+
+/// Assembly macros, rejected wherever they are invoked
+const ASM_MACROS: &[&str] = &["asm", "global_asm", "naked_asm"];
+
 /// Verifier for safe Rust code
-pub struct SafeRustVerifier;
+///
+/// Walks the syntax tree of the source and reports, with line and column:
+/// - unsafe functions, methods, trait methods, impls, traits and blocks,
+///   including inside macro invocations whose arguments parse as expressions
+///   or statements; for other macro invocations and `macro_rules!`
+///   definitions any `unsafe` token is reported
+/// - `extern` blocks, whose foreign functions can be declared `safe`
+/// - `unsafe(..)` attributes such as `#[unsafe(no_mangle)]`, naked
+///   functions and the `asm!`, `global_asm!` and `naked_asm!` macros
+/// - `extern crate`, `use` and paths naming a forbidden crate
+/// - `#[no_mangle]` and `#[export_name]` items exporting the same symbol
+pub struct SafeRustVerifier {
+    forbidden_crates: Vec<String>,
+}
 
 impl SafeRustVerifier {
-    /// Create a new verifier
+    /// Create a new verifier rejecting the [`DEFAULT_FORBIDDEN_CRATES`]
     pub fn new() -> Self {
-        Self
+        Self::with_forbidden_crates(DEFAULT_FORBIDDEN_CRATES.iter().map(|name| name.to_string()).collect())
     }
 
-    /// Helper to extract line number from span
-    fn get_line_from_span(span: impl Spanned) -> Option<usize> {
-        // Try to get line number from span
-        // proc_macro2::Span doesn't directly expose line numbers in stable Rust
-        // We'll use None for now - this is acceptable as the file path is still useful
-        let _ = span;
-        None
+    /// Create a new verifier rejecting the given crates
+    pub fn with_forbidden_crates(forbidden_crates: Vec<String>) -> Self {
+        Self { forbidden_crates }
     }
 
     /// Verify that a Rust source file contains only safe Rust
@@ -119,6 +172,8 @@ impl SafeRustVerifier {
     /// # Returns
     /// * `Ok(VerificationResult::Safe)` if the content contains only safe Rust
     /// * `Ok(VerificationResult::Unsafe { locations })` if unsafe code is found
+    /// * `Ok(VerificationResult::SymbolCollision { locations })` if the code is
+    ///   safe but exports a symbol twice
     /// * `Err(VerificationError)` if verification fails
     pub fn verify_content(
         &self,
@@ -129,221 +184,250 @@ impl SafeRustVerifier {
         let ast: File = syn::parse_str(content)
             .map_err(|e| VerificationError::ParseError(file_path.to_path_buf(), e.to_string()))?;
 
-        // Check for unsafe code
-        let mut unsafe_locations = Vec::new();
+        let mut visitor = UnsafeVisitor {
+            file: file_path,
+            forbidden_crates: &self.forbidden_crates,
+            exported_symbols: HashMap::new(),
+            locations: Vec::new(),
+            collisions: Vec::new(),
+        };
+        visitor.visit_file(&ast);
 
-        // Check all items in the file
-        for item in &ast.items {
-            self.check_item(item, file_path, &mut unsafe_locations);
+        if !visitor.locations.is_empty() {
+            Ok(VerificationResult::Unsafe {
+                locations: visitor.locations,
+            })
+        } else if !visitor.collisions.is_empty() {
+            Ok(VerificationResult::SymbolCollision {
+                locations: visitor.collisions,
+            })
+        } else {
+            Ok(VerificationResult::Safe)
         }
+    }
+}
 
-        if unsafe_locations.is_empty() {
-            Ok(VerificationResult::Safe)
+/// Syntax tree pass collecting unsafe code, forbidden crates and symbol collisions
+struct UnsafeVisitor<'a> {
+    file: &'a Path,
+    forbidden_crates: &'a [String],
+    /// Exported symbols and the line they were first exported at
+    exported_symbols: HashMap<String, Option<usize>>,
+    locations: Vec<UnsafeLocation>,
+    /// Exports colliding with an earlier export
+    collisions: Vec<UnsafeLocation>,
+}
+
+impl UnsafeVisitor<'_> {
+    /// Record a finding at the start of a span
+    fn report(&mut self, span: Span, description: String) {
+        let location = self.location(span, description);
+        self.locations.push(location);
+    }
+
+    /// Location of the start of a span
+    fn location(&self, span: Span, description: String) -> UnsafeLocation {
+        let start = span.start();
+        let (line, column) = if start.line == 0 {
+            (None, None)
         } else {
-            Ok(VerificationResult::Unsafe {
-                locations: unsafe_locations,
-            })
+            (Some(start.line), Some(start.column + 1))
+        };
+        UnsafeLocation {
+            file: self.file.to_path_buf(),
+            line,
+            column,
+            description,
         }
     }
 
-    /// Recursively check an item for unsafe code
-    fn check_item(
-        &self,
-        item: &Item,
-        file_path: &Path,
-        unsafe_locations: &mut Vec<UnsafeLocation>,
-    ) {
-        match item {
-            Item::Fn(item_fn) => {
-                self.check_function(item_fn, file_path, unsafe_locations);
-            }
-            Item::Impl(item_impl) => {
-                // Check if the impl block is unsafe
-                if item_impl.unsafety.is_some() {
-                    let line = Self::get_line_from_span(item_impl.span());
-                    unsafe_locations.push(UnsafeLocation {
-                        file: file_path.to_path_buf(),
-                        line,
-                        description: "Unsafe impl block".to_string(),
-                    });
-                }
-                // Check methods in the impl block
-                for item in &item_impl.items {
-                    if let syn::ImplItem::Fn(method) = item {
-                        self.check_impl_method(method, file_path, unsafe_locations);
-                    }
-                }
+    fn is_forbidden_crate(&self, ident: &Ident) -> bool {
+        self.forbidden_crates.iter().any(|name| ident == name)
+    }
+
+    /// Record the symbol exported by an item with `#[no_mangle]` or
+    /// `#[export_name]`, reporting a collision with an earlier export
+    fn check_exported_symbol(&mut self, attrs: &[Attribute], ident: &Ident) {
+        let Some(symbol) = exported_symbol(attrs, ident) else {
+            return;
+        };
+        let line = Some(ident.span().start().line).filter(|line| *line > 0);
+        match self.exported_symbols.get(&symbol) {
+            Some(first) => {
+                let first = first.map_or_else(|| "earlier".to_string(), |line| format!("at line {}", line));
+                let location = self.location(ident.span(), format!("Symbol collision: {} already exported {}", symbol, first));
+                self.collisions.push(location);
             }
-            Item::Trait(item_trait) => {
-                // Check methods in the trait
-                for item in &item_trait.items {
-                    if let syn::TraitItem::Fn(method) = item {
-                        if method.sig.unsafety.is_some() {
-                            let line = Self::get_line_from_span(method.span());
-                            unsafe_locations.push(UnsafeLocation {
-                                file: file_path.to_path_buf(),
-                                line,
-                                description: "Unsafe trait method".to_string(),
-                            });
-                        }
-                    }
-                }
+            None => {
+                self.exported_symbols.insert(symbol, line);
             }
-            Item::Mod(item_mod) => {
-                // Check if the module has inline content
-                if let Some((_, items)) = &item_mod.content {
-                    for item in items {
-                        self.check_item(item, file_path, unsafe_locations);
-                    }
+        }
+    }
+
+    /// Check the crate a `use` tree starts from
+    fn check_use_tree(&mut self, tree: &UseTree) {
+        match tree {
+            UseTree::Path(path) => self.check_use_root(&path.ident),
+            UseTree::Name(name) => self.check_use_root(&name.ident),
+            UseTree::Rename(rename) => self.check_use_root(&rename.ident),
+            UseTree::Group(group) => {
+                for tree in &group.items {
+                    self.check_use_tree(tree);
                 }
             }
-            Item::Static(item_static) => {
-                // Check the initializer expression for unsafe blocks
-                self.check_expr(&item_static.expr, file_path, unsafe_locations);
-            }
-            Item::Const(item_const) => {
-                // Check the initializer expression for unsafe blocks
-                self.check_expr(&item_const.expr, file_path, unsafe_locations);
-            }
-            _ => {
-                // Other items don't typically contain unsafe blocks directly
+            UseTree::Glob(_) => {}
+        }
+    }
+
+    fn check_use_root(&mut self, ident: &Ident) {
+        if self.is_forbidden_crate(ident) {
+            self.report(ident.span(), format!("Forbidden crate: {}", ident));
+        }
+    }
+
+    /// Report every `unsafe` token in a token stream
+    fn check_tokens(&mut self, tokens: TokenStream, macro_name: &str) {
+        for token in tokens {
+            match token {
+                TokenTree::Ident(ident) if ident == "unsafe" => {
+                    self.report(ident.span(), format!("Unsafe code in macro: {}!", macro_name));
+                }
+                TokenTree::Group(group) => self.check_tokens(group.stream(), macro_name),
+                _ => {}
             }
         }
     }
+}
 
-    /// Check a function for unsafe code
-    fn check_function(
-        &self,
-        item_fn: &ItemFn,
-        file_path: &Path,
-        unsafe_locations: &mut Vec<UnsafeLocation>,
-    ) {
-        // Check if the function itself is unsafe
-        if item_fn.sig.unsafety.is_some() {
-            let line = Self::get_line_from_span(item_fn.span());
-            unsafe_locations.push(UnsafeLocation {
-                file: file_path.to_path_buf(),
-                line,
-                description: format!("Unsafe function: {}", item_fn.sig.ident),
-            });
+impl<'ast> Visit<'ast> for UnsafeVisitor<'_> {
+    fn visit_item_fn(&mut self, item_fn: &'ast ItemFn) {
+        if let Some(unsafety) = &item_fn.sig.unsafety {
+            self.report(unsafety.span, format!("Unsafe function: {}", item_fn.sig.ident));
         }
+        self.check_exported_symbol(&item_fn.attrs, &item_fn.sig.ident);
+        visit::visit_item_fn(self, item_fn);
+    }
 
-        // Check the function body for unsafe blocks
-        self.check_block(&item_fn.block, file_path, unsafe_locations);
+    fn visit_impl_item_fn(&mut self, method: &'ast ImplItemFn) {
+        if let Some(unsafety) = &method.sig.unsafety {
+            self.report(unsafety.span, format!("Unsafe method: {}", method.sig.ident));
+        }
+        self.check_exported_symbol(&method.attrs, &method.sig.ident);
+        visit::visit_impl_item_fn(self, method);
     }
 
-    /// Check an impl method for unsafe code
-    fn check_impl_method(
-        &self,
-        method: &syn::ImplItemFn,
-        file_path: &Path,
-        unsafe_locations: &mut Vec<UnsafeLocation>,
-    ) {
-        // Check if the method itself is unsafe
-        if method.sig.unsafety.is_some() {
-            let line = Self::get_line_from_span(method.span());
-            unsafe_locations.push(UnsafeLocation {
-                file: file_path.to_path_buf(),
-                line,
-                description: format!("Unsafe method: {}", method.sig.ident),
-            });
+    fn visit_trait_item_fn(&mut self, method: &'ast TraitItemFn) {
+        if let Some(unsafety) = &method.sig.unsafety {
+            self.report(unsafety.span, format!("Unsafe trait method: {}", method.sig.ident));
         }
+        visit::visit_trait_item_fn(self, method);
+    }
 
-        // Check the method body for unsafe blocks
-        self.check_block(&method.block, file_path, unsafe_locations);
+    fn visit_item_impl(&mut self, item_impl: &'ast ItemImpl) {
+        if let Some(unsafety) = &item_impl.unsafety {
+            self.report(unsafety.span, "Unsafe impl block".to_string());
+        }
+        visit::visit_item_impl(self, item_impl);
     }
 
-    /// Check a block for unsafe blocks
-    fn check_block(
-        &self,
-        block: &syn::Block,
-        file_path: &Path,
-        unsafe_locations: &mut Vec<UnsafeLocation>,
-    ) {
-        for stmt in &block.stmts {
-            self.check_stmt(stmt, file_path, unsafe_locations);
+    fn visit_item_trait(&mut self, item_trait: &'ast ItemTrait) {
+        if let Some(unsafety) = &item_trait.unsafety {
+            self.report(unsafety.span, format!("Unsafe trait: {}", item_trait.ident));
         }
+        visit::visit_item_trait(self, item_trait);
     }
 
-    /// Check a statement for unsafe blocks
-    fn check_stmt(
-        &self,
-        stmt: &syn::Stmt,
-        file_path: &Path,
-        unsafe_locations: &mut Vec<UnsafeLocation>,
-    ) {
-        match stmt {
-            syn::Stmt::Expr(expr, _) => {
-                self.check_expr(expr, file_path, unsafe_locations);
-            }
-            syn::Stmt::Item(item) => {
-                self.check_item(item, file_path, unsafe_locations);
-            }
-            syn::Stmt::Local(local) => {
-                if let Some(init) = &local.init {
-                    self.check_expr(&init.expr, file_path, unsafe_locations);
-                }
-            }
-            syn::Stmt::Macro(_) => {
-                // Macros are checked at expansion time, not here
-            }
+    fn visit_expr_unsafe(&mut self, unsafe_block: &'ast ExprUnsafe) {
+        self.report(unsafe_block.unsafe_token.span, "Unsafe block".to_string());
+        visit::visit_expr_unsafe(self, unsafe_block);
+    }
+
+    fn visit_item_foreign_mod(&mut self, foreign_mod: &'ast ItemForeignMod) {
+        self.report(foreign_mod.abi.extern_token.span, "Extern block".to_string());
+        visit::visit_item_foreign_mod(self, foreign_mod);
+    }
+
+    fn visit_attribute(&mut self, attr: &'ast Attribute) {
+        let path = attr.path();
+        if path.is_ident("unsafe") {
+            self.report(path.segments[0].ident.span(), "Unsafe attribute".to_string());
+        } else if path.is_ident("naked") {
+            self.report(path.segments[0].ident.span(), "Naked function".to_string());
         }
+        visit::visit_attribute(self, attr);
     }
 
-    /// Check an expression for unsafe blocks
-    fn check_expr(
-        &self,
-        expr: &Expr,
-        file_path: &Path,
-        unsafe_locations: &mut Vec<UnsafeLocation>,
-    ) {
-        match expr {
-            Expr::Unsafe(unsafe_block) => {
-                let line = Self::get_line_from_span(unsafe_block.span());
-                unsafe_locations.push(UnsafeLocation {
-                    file: file_path.to_path_buf(),
-                    line,
-                    description: "Unsafe block".to_string(),
-                });
-                // Check the block inside the unsafe block
-                self.check_block(&unsafe_block.block, file_path, unsafe_locations);
-            }
-            Expr::Block(block_expr) => {
-                self.check_block(&block_expr.block, file_path, unsafe_locations);
-            }
-            Expr::If(if_expr) => {
-                self.check_expr(&if_expr.cond, file_path, unsafe_locations);
-                self.check_block(&if_expr.then_branch, file_path, unsafe_locations);
-                if let Some((_, else_expr)) = &if_expr.else_branch {
-                    self.check_expr(else_expr, file_path, unsafe_locations);
-                }
-            }
-            Expr::Match(match_expr) => {
-                self.check_expr(&match_expr.expr, file_path, unsafe_locations);
-                for arm in &match_expr.arms {
-                    self.check_expr(&arm.body, file_path, unsafe_locations);
-                }
+    fn visit_item_static(&mut self, item_static: &'ast ItemStatic) {
+        self.check_exported_symbol(&item_static.attrs, &item_static.ident);
+        visit::visit_item_static(self, item_static);
+    }
+
+    fn visit_item_extern_crate(&mut self, extern_crate: &'ast ItemExternCrate) {
+        if self.is_forbidden_crate(&extern_crate.ident) {
+            self.report(
+                extern_crate.ident.span(),
+                format!("Forbidden extern crate: {}", extern_crate.ident),
+            );
+        }
+        visit::visit_item_extern_crate(self, extern_crate);
+    }
+
+    fn visit_item_use(&mut self, item_use: &'ast ItemUse) {
+        self.check_use_tree(&item_use.tree);
+        visit::visit_item_use(self, item_use);
+    }
+
+    fn visit_path(&mut self, path: &'ast syn::Path) {
+        if path.segments.len() > 1 && self.is_forbidden_crate(&path.segments[0].ident) {
+            let ident = &path.segments[0].ident;
+            self.report(ident.span(), format!("Forbidden crate: {}", ident));
+        }
+        visit::visit_path(self, path);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        let macro_name = mac
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string())
+            .unwrap_or_default();
+        if ASM_MACROS.contains(&macro_name.as_str()) {
+            self.report(mac.path.segments[0].ident.span(), format!("Inline assembly: {}!", macro_name));
+        }
+        // Function-like macros mostly take expressions or statements; check
+        // those like ordinary code and fall back to the raw tokens otherwise
+        if let Ok(exprs) = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) {
+            for expr in &exprs {
+                self.visit_expr(expr);
             }
-            Expr::Call(call_expr) => {
-                self.check_expr(&call_expr.func, file_path, unsafe_locations);
-                for arg in &call_expr.args {
-                    self.check_expr(arg, file_path, unsafe_locations);
-                }
+        } else if let Ok(stmts) = mac.parse_body_with(Block::parse_within) {
+            for stmt in &stmts {
+                self.visit_stmt(stmt);
             }
-            Expr::MethodCall(method_call) => {
-                self.check_expr(&method_call.receiver, file_path, unsafe_locations);
-                for arg in &method_call.args {
-                    self.check_expr(arg, file_path, unsafe_locations);
+        } else {
+            self.check_tokens(mac.tokens.clone(), &macro_name);
+        }
+        visit::visit_macro(self, mac);
+    }
+}
+
+/// Symbol an item is exported under, if it is exported unmangled
+/// with `#[no_mangle]` or `#[export_name]`
+pub(crate) fn exported_symbol(attrs: &[Attribute], ident: &Ident) -> Option<String> {
+    for attr in attrs {
+        if attr.path().is_ident("no_mangle") {
+            return Some(ident.to_string());
+        }
+        if attr.path().is_ident("export_name") {
+            if let Meta::NameValue(name_value) = &attr.meta {
+                if let Expr::Lit(ExprLit { lit: Lit::Str(name), .. }) = &name_value.value {
+                    return Some(name.value());
                 }
             }
-            Expr::Closure(closure) => {
-                self.check_expr(&closure.body, file_path, unsafe_locations);
-            }
-            _ => {
-                // Other expressions don't typically contain unsafe blocks
-            }
         }
     }
+    None
 }
 
 impl Default for SafeRustVerifier {
@@ -601,6 +685,7 @@ mod tests {
             locations: vec![UnsafeLocation {
                 file: PathBuf::from("test.rs"),
                 line: Some(10),
+                column: None,
                 description: "test".to_string(),
             }],
         };
@@ -612,6 +697,7 @@ mod tests {
         let loc = UnsafeLocation {
             file: PathBuf::from("test.rs"),
             line: Some(10),
+            column: None,
             description: "test".to_string(),
         };
         let _ = loc.clone();
@@ -652,11 +738,13 @@ mod tests {
         let loc1 = UnsafeLocation {
             file: PathBuf::from("test.rs"),
             line: Some(10),
+            column: None,
             description: "test".to_string(),
         };
         let loc2 = UnsafeLocation {
             file: PathBuf::from("test.rs"),
             line: Some(10),
+            column: None,
             description: "test".to_string(),
         };
         assert_eq!(loc1, loc2);
@@ -828,9 +916,7 @@ mod tests {
         let safe_code = r#"
             use std::collections::HashMap;
             type MyType = i32;
-            extern "C" {
-                fn external_function();
-            }
+            const LIMIT: usize = 16;
         "#;
 
         let result = verifier
@@ -887,5 +973,220 @@ mod tests {
         // static mut is safe Rust (just mutable), so should pass
         assert_eq!(result, VerificationResult::Safe);
     }
-}
 
+    fn unsafe_locations(verifier: &SafeRustVerifier, code: &str) -> Vec<UnsafeLocation> {
+        match verifier.verify_content(code, Path::new("test.rs")).unwrap() {
+            VerificationResult::Unsafe { locations } => locations,
+            VerificationResult::SymbolCollision { .. } | VerificationResult::Safe => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_verify_reports_line_and_column() {
+        let verifier = SafeRustVerifier::new();
+        let code = "pub fn f() {\n    let x = 1;\n    unsafe { x }\n}\n";
+        let locations = unsafe_locations(&verifier, code);
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].line, Some(3));
+        assert_eq!(locations[0].column, Some(5));
+        assert_eq!(locations[0].to_string(), "test.rs:3:5: Unsafe block");
+    }
+
+    #[test]
+    fn test_verify_unsafe_inside_macros() {
+        let verifier = SafeRustVerifier::new();
+        let code = r#"
+            pub fn f() {
+                println!("{}", unsafe { 1 });
+                let v = vec![unsafe { 2 }; 3];
+            }
+
+            macro_rules! sneaky {
+                () => { unsafe { 3 } };
+            }
+        "#;
+        let locations = unsafe_locations(&verifier, code);
+        assert_eq!(locations.len(), 3);
+        assert_eq!(locations[0].description, "Unsafe block");
+        assert_eq!(locations[1].description, "Unsafe block");
+        assert_eq!(locations[2].description, "Unsafe code in macro: macro_rules!");
+        assert_eq!(locations[2].line, Some(8));
+    }
+
+    #[test]
+    fn test_verify_unsafe_in_nested_expressions() {
+        let verifier = SafeRustVerifier::new();
+        let code = r#"
+            pub fn f() -> i32 {
+                let values = [1, 2].iter().map(|v| v + unsafe { 1 }).sum::<i32>();
+                while values > 0 { unsafe {} }
+                values
+            }
+        "#;
+        assert_eq!(unsafe_locations(&verifier, code).len(), 2);
+    }
+
+    #[test]
+    fn test_verify_unsafe_trait() {
+        let verifier = SafeRustVerifier::new();
+        let locations = unsafe_locations(&verifier, "unsafe trait Marker {}");
+        assert_eq!(locations[0].description, "Unsafe trait: Marker");
+    }
+
+    #[test]
+    fn test_verify_forbidden_crates() {
+        let verifier = SafeRustVerifier::new();
+        let code = r#"
+            extern crate libc;
+            use nix::unistd;
+            use std::{collections::HashMap, io};
+
+            pub fn f() -> i32 {
+                winapi::um::get();
+                0
+            }
+        "#;
+        let descriptions: Vec<_> = unsafe_locations(&verifier, code)
+            .into_iter()
+            .map(|location| location.description)
+            .collect();
+        assert_eq!(
+            descriptions,
+            vec!["Forbidden extern crate: libc", "Forbidden crate: nix", "Forbidden crate: winapi"]
+        );
+
+        let custom = SafeRustVerifier::with_forbidden_crates(vec!["serde".to_string()]);
+        assert_eq!(
+            custom.verify_content("extern crate libc;", Path::new("test.rs")).unwrap(),
+            VerificationResult::Safe
+        );
+        assert_eq!(unsafe_locations(&custom, "use serde::Serialize;").len(), 1);
+    }
+
+    #[test]
+    fn test_verify_no_mangle_symbol_collisions() {
+        let verifier = SafeRustVerifier::new();
+        let code = r#"
+            #[no_mangle]
+            pub extern "C" fn nif_add() -> i32 { 0 }
+
+            mod other {
+                #[no_mangle]
+                pub extern "C" fn nif_add() -> i32 { 1 }
+            }
+
+            #[export_name = "nif_add"]
+            pub extern "C" fn renamed() -> i32 { 2 }
+
+            #[no_mangle]
+            pub extern "C" fn nif_sub() -> i32 { 3 }
+        "#;
+        let VerificationResult::SymbolCollision { locations } = verifier.verify_content(code, Path::new("test.rs")).unwrap() else {
+            panic!("expected a symbol collision");
+        };
+        assert_eq!(locations.len(), 2);
+        assert!(locations
+            .iter()
+            .all(|location| location.description == "Symbol collision: nif_add already exported at line 3"));
+
+        // Unsafe code is reported ahead of collisions
+        let unsafe_code = format!("{}\npub fn f() {{ unsafe {{}} }}", code);
+        assert_eq!(unsafe_locations(&verifier, &unsafe_code).len(), 1);
+        assert_eq!(locations[0].line, Some(7));
+        assert_eq!(locations[1].line, Some(11));
+    }
+
+    #[test]
+    fn test_verify_unsafe_extern_block() {
+        let verifier = SafeRustVerifier::new();
+        let code = r#"
+            unsafe extern "C" {
+                pub safe fn abort() -> !;
+            }
+
+            pub fn stop() -> ! {
+                abort()
+            }
+        "#;
+        let locations = unsafe_locations(&verifier, code);
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].description, "Extern block");
+        assert_eq!(locations[0].line, Some(2));
+    }
+
+    #[test]
+    fn test_verify_extern_block() {
+        let verifier = SafeRustVerifier::new();
+        let locations = unsafe_locations(&verifier, r#"extern "C" {}"#);
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].description, "Extern block");
+
+        // Functions with an extern ABI are not extern blocks
+        assert_eq!(unsafe_locations(&verifier, r#"pub extern "C" fn f() {}"#).len(), 0);
+    }
+
+    #[test]
+    fn test_verify_unsafe_no_mangle() {
+        let verifier = SafeRustVerifier::new();
+        let code = r#"
+            #[unsafe(no_mangle)]
+            pub extern "C" fn malloc() -> usize { 0 }
+        "#;
+        let locations = unsafe_locations(&verifier, code);
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].description, "Unsafe attribute");
+        assert_eq!(locations[0].line, Some(2));
+    }
+
+    #[test]
+    fn test_verify_unsafe_export_name() {
+        let verifier = SafeRustVerifier::new();
+        let code = r#"
+            #[unsafe(export_name = "free")]
+            pub extern "C" fn release() {}
+        "#;
+        let locations = unsafe_locations(&verifier, code);
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].description, "Unsafe attribute");
+    }
+
+    #[test]
+    fn test_verify_global_asm() {
+        let verifier = SafeRustVerifier::new();
+        let code = r#"
+            std::arch::global_asm!(".globl nif_init", "nif_init:", "ret");
+        "#;
+        let locations = unsafe_locations(&verifier, code);
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].description, "Inline assembly: global_asm!");
+    }
+
+    #[test]
+    fn test_verify_asm() {
+        let verifier = SafeRustVerifier::new();
+        let code = r#"
+            pub fn nop() {
+                core::arch::asm!("nop");
+            }
+        "#;
+        let locations = unsafe_locations(&verifier, code);
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].description, "Inline assembly: asm!");
+    }
+
+    #[test]
+    fn test_verify_naked_function() {
+        let verifier = SafeRustVerifier::new();
+        let code = r#"
+            #[naked]
+            pub extern "C" fn entry() {
+                naked_asm!("ret")
+            }
+        "#;
+        let descriptions: Vec<_> = unsafe_locations(&verifier, code)
+            .into_iter()
+            .map(|location| location.description)
+            .collect();
+        assert_eq!(descriptions, ["Naked function", "Inline assembly: naked_asm!"]);
+    }
+}
@@ -307,6 +307,7 @@ pub fn f() -> i32 { 1 }
         release: false,
         cargo_flags: Vec::new(),
         output_dir: Some(output_dir.clone()),
+        offline: None,
     };
     
     let result = compiler.compile(&source_path, options);
@@ -335,6 +336,7 @@ pub fn f() -> i32 { 1 }
         release: true, // Release mode
         cargo_flags: Vec::new(),
         output_dir: None,
+        offline: None,
     };
     
     let result = compiler.compile(&source_path, options);
@@ -424,6 +426,7 @@ pub extern "C" fn multiply(a: i32, b: i32) -> i32 {
         release: false,
         cargo_flags: Vec::new(),
         output_dir: None,
+        offline: None,
     };
     
     let compile_result = compiler.compile(&source_path, compile_options);
@@ -23,7 +23,7 @@ use infrastructure_utilities::process_table::{get_global_process_table, ProcessT
 ///
 /// # Note
 /// The global process table is initialized lazily on first access via `get_global_process_table()`.
-/// This function ensures it's initialized with the correct size limit.
+/// This function sets its size limit to `proc_tab_sz`.
 pub fn erts_init_process(
     _ncpu: usize,
     proc_tab_sz: usize,
@@ -39,23 +39,14 @@ pub fn erts_init_process(
     
     // Initialize process table
     // In C: erts_ptab_init_table(&erts_proc, ...)
-    // In Rust: The global process table is initialized lazily, but we can ensure
-    // it's initialized with the correct size by accessing it
-    // Note: The current implementation uses OnceLock which doesn't allow
-    // setting max_size after initialization. For now, we'll just ensure
-    // the table is initialized. A future enhancement could allow setting
-    // max_size during initialization.
-    let _table = get_global_process_table();
+    // In Rust: The global process table is initialized lazily; the size
+    // limit (`+P`) is applied to it here
+    get_global_process_table().set_max_size(proc_tab_sz);
     
-    // In a full implementation, we would:
+    // In a full implementation, we would also:
     // 1. Initialize process locks based on ncpu
     // 2. Initialize process list allocator
-    // 3. Initialize process table with proc_tab_sz
-    // 4. Set up invalid process structure
-    
-    // For now, the process table is initialized on first access
-    // The size limit would need to be set during table creation
-    // This is a limitation of the current OnceLock-based design
+    // 3. Set up invalid process structure
     
     Ok(())
 }
@@ -69,8 +60,7 @@ mod tests {
         let result = erts_init_process(4, 1_048_576, false);
         assert!(result.is_ok());
         
-        // Verify process table is accessible
-        let _table = get_global_process_table();
+        assert_eq!(get_global_process_table().max_size(), Some(1_048_576));
     }
 }
 
@@ -25,10 +25,23 @@
 //!   functionality for monitoring which modules and code areas processes are using.
 //!   Essential for safe code loading and hot code swapping.
 //!
+//! - **[`process_spawn`](process_spawn/index.html)**: Process creation for `spawn_opt`,
+//!   with links and monitors to the parent set up at spawn
+//!
+//! - **[`process_suspend`](process_suspend/index.html)**: `suspend_process/2` and
+//!   `resume_process/1`, with nested suspends and cleanup when the suspender exits
+//!
+//! - **[`process_gc`](process_gc/index.html)**: Bump allocation of process heaps, heap growth and
+//!   shrink policies on garbage collection, and enforcement of the `max_heap_size` limit
+//!
+//! - **[`process_sys_task`](process_sys_task/index.html)**: System tasks (garbage collection,
+//!   `check_process_code`, `copy_literals`) requested on other processes, run at the
+//!   requester's priority and answered with a reply message
+//!
 //! ## Architecture
 //!
 //! This crate is based on the C implementation in `erl_process_lock.c`, `erl_process_dump.c`,
-//! `erl_process_dict.c`, and `beam_bif_load.c`. It depends on both the Entities and
+//! `erl_process_dict.c`, `erl_gc.c`, and `beam_bif_load.c`. It depends on both the Entities and
 //! Infrastructure layers for process table and registry operations.
 //!
 //! ## See Also
@@ -40,6 +53,10 @@ pub mod process_lock;
 pub mod process_dump;
 pub mod process_dict;
 pub mod process_code_tracking;
+pub mod process_spawn;
+pub mod process_suspend;
+pub mod process_gc;
+pub mod process_sys_task;
 pub mod initialization;
 
 pub use process_lock::ProcessLock;
@@ -54,5 +71,11 @@ pub use process_code_tracking::{
     pointer_in_module_area,
     ModuleCodeArea,
 };
+pub use process_spawn::{erl_spawn_opt, SpawnError, SpawnResult};
+pub use process_suspend::{
+    erts_resume_process, erts_resume_suspended_by, erts_suspend_process, SuspendError, SuspendOpts,
+};
+pub use process_gc::{erts_garbage_collect, heap_alloc, heap_resize, next_heap_size, GcOutcome, HeapResize, MaxHeapSizeReport};
+pub use process_sys_task::{erts_execute_system_tasks, erts_request_system_task, SystemTaskRequest};
 pub use initialization::erts_init_process;
 
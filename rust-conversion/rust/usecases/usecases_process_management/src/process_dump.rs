@@ -106,6 +106,19 @@ impl ProcessDump {
             format!("=== Process Not Found ===\nProcess ID: {} not found in process table\n", process_id)
         }
     }
+
+    /// Dump all processes, as the process section of a crash dump does
+    ///
+    /// Works on a snapshot of the process table, so processes may spawn
+    /// and exit while the dump is written.
+    ///
+    /// # Returns
+    /// The dumps of the processes in the table, in table order
+    pub fn dump_all() -> String {
+        let mut output = String::new();
+        get_global_process_table().foreach(|_, process| output.push_str(&Self::dump(process)));
+        output
+    }
 }
 
 #[cfg(test)]
@@ -140,6 +153,17 @@ mod tests {
         assert!(dump_not_found.contains("not found"));
     }
 
+    #[test]
+    fn test_process_dump_all() {
+        use std::sync::Arc;
+
+        let table = get_global_process_table();
+        table.insert(41202, Arc::new(Process::new(41202)));
+        assert!(ProcessDump::dump_all().contains("Process ID: 41202"));
+        table.remove(41202);
+        assert!(!ProcessDump::dump_all().contains("Process ID: 41202"));
+    }
+
     #[test]
     fn test_process_dump_contains_info() {
         let process = Process::new(456);
@@ -0,0 +1,482 @@
+//! Process Garbage Collection Module
+//!
+//! Provides heap sizing for process garbage collection and enforcement of the
+//! `max_heap_size` process limit.
+//! Based on erts_garbage_collect() and the max heap size check in
+//! erl_gc.c
+//!
+//! The heap model has no tracing collector: every word below the heap top is
+//! treated as live. The words needed after a collection are the live words,
+//! the words the caller needs, and, for processes with `on_heap` message
+//! queue data, the heap fragments of queued messages. Processes with
+//! `off_heap` message queue data keep their messages outside the heap, so
+//! they do not count towards the heap size or the `max_heap_size` limit.
+//!
+//! Heaps are bump allocated ([`heap_alloc`]) and collected when full. A heap
+//! too small for the words needed grows to the next size in the heap size
+//! series. A fullsweep that leaves the heap over 75% full postpones growing
+//! it to the next collection, and one that leaves it under 25% full shrinks
+//! it towards twice the words needed, but not below `min_heap_size`. Based on
+//! adjust_after_fullsweep() in erl_gc.c. A resized heap is counted in the
+//! memory of the process table.
+//!
+//! After each collection the NIF resource terms of the process are swept:
+//! resource terms above the heap top are dead, and the references they held
+//! are released, which destructs resources nobody else refers to.
+//!
+//! Debug builds check the heap of the process after each collection and
+//! panic if it is corrupt.
+
+use std::fmt;
+
+use entities_process::term_tags::{pointer_index, TAG_PRIMARY_BOXED, TAG_PRIMARY_MASK};
+use entities_process::{Eterm, MessageQueueData, Process, ProcessId};
+use infrastructure_debugging::debug_check_process_heap;
+use infrastructure_nif_api::gc_sweep_resources;
+use infrastructure_utilities::process_table::get_global_process_table;
+use infrastructure_utilities::statistics::get_global_statistics;
+
+/// Outcome of a garbage collection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GcOutcome {
+    /// The heap was collected and resized
+    Collected {
+        /// Heap size in words after the collection
+        heap_size: usize,
+        /// Whether the collection was a fullsweep
+        major: bool,
+    },
+    /// The heap would exceed `max_heap_size`
+    MaxHeapSizeExceeded {
+        /// Heap size in words the collection needed
+        heap_size: usize,
+        /// The process was marked as exiting with reason `kill`
+        killed: bool,
+        /// The error report to log, if `error_logger` is set
+        report: Option<MaxHeapSizeReport>,
+    },
+}
+
+/// Error report for a process that exceeded its `max_heap_size`
+///
+/// Based on `reached_max_heap_size()` from erl_gc.c. The collector does not
+/// log it itself; the caller passes it on to the logger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaxHeapSizeReport {
+    /// Process that exceeded the limit
+    pub pid: ProcessId,
+    /// Heap size in words the collection needed
+    pub heap_size: usize,
+    /// The `max_heap_size` limit in words
+    pub limit: usize,
+    /// The process was marked as exiting with reason `kill`
+    pub killed: bool,
+}
+
+impl fmt::Display for MaxHeapSizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Process {} exceeded max_heap_size: heap size {} words, limit {} words",
+            self.pid, self.heap_size, self.limit
+        )?;
+        if self.killed {
+            write!(f, ", killing process")?;
+        }
+        Ok(())
+    }
+}
+
+/// Number of heap sizes in the Fibonacci part of the heap size series
+///
+/// Past them, at 833026 words, heap sizes grow by 20% at a time.
+const FIBONACCI_HEAP_SIZES: usize = 23;
+
+/// Heap size series, as built by erts_init_gc() in erl_gc.c
+fn heap_sizes() -> impl Iterator<Item = usize> {
+    let mut sizes = [12usize, 38usize];
+    (0usize..).map(move |index| {
+        let size = sizes[0];
+        let next = if index + 2 < FIBONACCI_HEAP_SIZES {
+            // One extra word for the block header
+            sizes[0].saturating_add(sizes[1]).saturating_add(1)
+        } else {
+            sizes[1].saturating_add(sizes[1] / 5)
+        };
+        sizes = [sizes[1], next];
+        size
+    })
+}
+
+/// Next heap size of at least `words` words
+///
+/// Based on erts_next_heap_size() from erl_gc.c; heap sizes grow in a
+/// Fibonacci sequence (12, 38, 51, 90, 142, 233, 376, 610, ...) up to 833026
+/// words and by 20% after that.
+pub fn next_heap_size(words: usize) -> usize {
+    heap_sizes().find(|&size| size >= words).unwrap_or(usize::MAX)
+}
+
+/// Heap size following `heap_size` in the heap size series
+fn grown_heap_size(heap_size: usize) -> usize {
+    heap_sizes().find(|&size| size > heap_size).unwrap_or(usize::MAX)
+}
+
+/// Heap resize decided by a collection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeapResize {
+    /// The heap keeps its size
+    Keep,
+    /// The heap grows to the given size
+    Grow(usize),
+    /// The heap shrinks to the given size
+    Shrink(usize),
+    /// The heap keeps its size and grows at the next collection
+    PostponeGrow,
+}
+
+/// Decide how a collection resizes the heap
+///
+/// Based on adjust_after_fullsweep() in erl_gc.c
+///
+/// # Arguments
+/// * `heap_size` - Current heap size in words
+/// * `need_after` - Words in use after the collection, including the
+///   words the caller needs
+/// * `min_heap_size` - Minimum heap size of the process
+/// * `major` - Whether the collection is a fullsweep
+/// * `grow_pending` - Whether an earlier fullsweep postponed growing the heap
+pub fn heap_resize(
+    heap_size: usize,
+    need_after: usize,
+    min_heap_size: usize,
+    major: bool,
+    grow_pending: bool,
+) -> HeapResize {
+    if heap_size < need_after {
+        return HeapResize::Grow(next_heap_size(need_after).max(next_heap_size(min_heap_size)));
+    }
+    if grow_pending {
+        return HeapResize::Grow(grown_heap_size(heap_size));
+    }
+    if !major {
+        return HeapResize::Keep;
+    }
+    let min_heap_size = next_heap_size(min_heap_size);
+    if 3 * heap_size < 4 * need_after {
+        // Over 75% full
+        HeapResize::PostponeGrow
+    } else if 4 * need_after < heap_size && heap_size > min_heap_size {
+        // Under 25% full
+        let wanted = 2 * need_after;
+        let size = if wanted < min_heap_size { min_heap_size } else { next_heap_size(wanted) };
+        if size < heap_size {
+            HeapResize::Shrink(size)
+        } else {
+            HeapResize::Keep
+        }
+    } else {
+        HeapResize::Keep
+    }
+}
+
+/// Garbage collect a process so that `need` more words fit on its heap
+///
+/// Based on `erts_garbage_collect()` from erl_gc.c
+///
+/// The collection is a fullsweep when `fullsweep_after` generational
+/// collections have been done since the last one. The heap is resized as
+/// decided by [`heap_resize`]. If the resulting heap is
+/// larger than the process's `max_heap_size`, the heap is left unchanged; the
+/// outcome carries an error report for the caller to log if `error_logger` is
+/// set, and the process is marked as exiting if `kill` is set.
+///
+/// # Arguments
+/// * `process` - Process to collect
+/// * `need` - Words the caller needs to allocate after the collection
+///
+/// # Returns
+/// The outcome of the collection
+pub fn erts_garbage_collect(process: &mut Process, need: usize) -> GcOutcome {
+    let live = process.heap_top_index();
+    let messages = match process.message_queue_data() {
+        MessageQueueData::OnHeap => process.message_fragment_words(),
+        MessageQueueData::OffHeap => 0,
+    };
+    let major = process.gen_gcs() >= process.fullsweep_after();
+    let old_size = process.heap_sz();
+    let resize = heap_resize(
+        old_size,
+        live + need + messages,
+        process.min_heap_size(),
+        major,
+        process.heap_grow_pending(),
+    );
+    let heap_size = match resize {
+        HeapResize::Grow(size) | HeapResize::Shrink(size) => size,
+        HeapResize::Keep | HeapResize::PostponeGrow => old_size,
+    };
+
+    let limit = process.max_heap_size_limit();
+    if limit.size != 0 && heap_size > limit.size {
+        if limit.kill {
+            process.set_exiting();
+        }
+        let report = limit.error_logger.then(|| MaxHeapSizeReport {
+            pid: process.id(),
+            heap_size,
+            limit: limit.size,
+            killed: limit.kill,
+        });
+        return GcOutcome::MaxHeapSizeExceeded { heap_size, killed: limit.kill, report };
+    }
+
+    let heap_size = process.resize_heap(heap_size);
+    if heap_size != old_size {
+        get_global_process_table().update_footprint(process.id(), process);
+    }
+    process.set_heap_grow(resize == HeapResize::PostponeGrow);
+    process.record_gc(major);
+    gc_sweep_resources(process.id(), |term| resource_term_live(term, live));
+    debug_check_process_heap(process);
+    get_global_statistics().record_garbage_collection(old_size.saturating_sub(live) as u64);
+
+    GcOutcome::Collected { heap_size, major }
+}
+
+/// Whether a resource term survives a collection keeping `live` heap words
+///
+/// Resource terms that could not be put on the heap are not boxed; they are
+/// kept until the process exits.
+fn resource_term_live(term: Eterm, live: usize) -> bool {
+    term & TAG_PRIMARY_MASK != TAG_PRIMARY_BOXED || pointer_index(term) < live
+}
+
+/// Allocate `words` words on a process heap, collecting it when it is full
+///
+/// Based on HAlloc() in erl_gc.h: words are bump allocated from the heap
+/// top, and a full heap is collected with room for `words` more.
+///
+/// # Returns
+/// * `Ok(index)` - Heap index where the allocation starts
+/// * `Err(outcome)` - The collection exceeded `max_heap_size`
+pub fn heap_alloc(process: &mut Process, words: usize) -> Result<usize, GcOutcome> {
+    if let Some(index) = process.allocate_heap_words(words) {
+        return Ok(index);
+    }
+    match erts_garbage_collect(process, words) {
+        GcOutcome::Collected { .. } => Ok(process
+            .allocate_heap_words(words)
+            .expect("heap was collected with room for the allocation")),
+        exceeded => Err(exceeded),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entities_process::{InitialCall, MaxHeapSize, Message, ProcessState, SpawnOpts};
+
+    fn spawn(opts: &SpawnOpts) -> Process {
+        Process::spawned_with_opts(1, None, InitialCall::new("m", "f", 0), opts)
+    }
+
+    #[test]
+    fn test_next_heap_size() {
+        assert_eq!(next_heap_size(1), 12);
+        assert_eq!(next_heap_size(200), 233);
+        assert_eq!(next_heap_size(233), 233);
+        assert_eq!(next_heap_size(234), 376);
+        // Last Fibonacci size, then 20% growth
+        assert_eq!(next_heap_size(833_000), 833_026);
+        assert_eq!(next_heap_size(833_027), 999_631);
+        assert_eq!(next_heap_size(999_632), 1_199_557);
+    }
+
+    #[test]
+    fn test_heap_resize_points() {
+        assert_eq!(heap_resize(233, 300, 233, false, false), HeapResize::Grow(376));
+        assert_eq!(heap_resize(376, 300, 233, false, false), HeapResize::Keep);
+        // Over 75% full after a fullsweep
+        assert_eq!(heap_resize(376, 283, 233, true, false), HeapResize::PostponeGrow);
+        assert_eq!(heap_resize(376, 282, 233, true, false), HeapResize::Keep);
+        assert_eq!(heap_resize(376, 0, 233, false, true), HeapResize::Grow(610));
+        // Under 25% full after a fullsweep
+        assert_eq!(heap_resize(6772, 1692, 233, true, false), HeapResize::Shrink(4185));
+        assert_eq!(heap_resize(6772, 1693, 233, true, false), HeapResize::Keep);
+        assert_eq!(heap_resize(6772, 10, 233, true, false), HeapResize::Shrink(233));
+        assert_eq!(heap_resize(6772, 10, 1000, true, false), HeapResize::Shrink(1598));
+        assert_eq!(heap_resize(233, 10, 233, true, false), HeapResize::Keep);
+    }
+
+    #[test]
+    fn test_gc_shrinks_after_fullsweep() {
+        let mut process = spawn(&SpawnOpts {
+            fullsweep_after: Some(0),
+            ..SpawnOpts::default()
+        });
+        process.resize_heap(6772);
+        process.allocate_heap_words(100).unwrap();
+        assert_eq!(
+            erts_garbage_collect(&mut process, 0),
+            GcOutcome::Collected { heap_size: 233, major: true }
+        );
+    }
+
+    #[test]
+    fn test_gc_postpones_growth() {
+        let mut process = spawn(&SpawnOpts {
+            fullsweep_after: Some(0),
+            ..SpawnOpts::default()
+        });
+        process.resize_heap(376);
+        process.allocate_heap_words(300).unwrap();
+        assert_eq!(
+            erts_garbage_collect(&mut process, 0),
+            GcOutcome::Collected { heap_size: 376, major: true }
+        );
+        assert!(process.heap_grow_pending());
+        assert_eq!(
+            erts_garbage_collect(&mut process, 0),
+            GcOutcome::Collected { heap_size: 610, major: true }
+        );
+        assert!(!process.heap_grow_pending());
+    }
+
+    #[test]
+    fn test_heap_alloc_collects_when_full() {
+        let mut process = spawn(&SpawnOpts::default());
+        assert_eq!(heap_alloc(&mut process, 200), Ok(0));
+        assert_eq!(process.heap_sz(), 233);
+        assert_eq!(heap_alloc(&mut process, 100), Ok(200));
+        assert_eq!(process.heap_sz(), 376);
+
+        let mut limited = spawn(&SpawnOpts {
+            max_heap_size: Some(MaxHeapSize { size: 300, kill: false, error_logger: false }),
+            ..SpawnOpts::default()
+        });
+        assert!(matches!(
+            heap_alloc(&mut limited, 400),
+            Err(GcOutcome::MaxHeapSizeExceeded { heap_size: 610, .. })
+        ));
+    }
+
+    #[test]
+    fn test_gc_grows_heap() {
+        let mut process = spawn(&SpawnOpts::default());
+        process.allocate_heap_words(200).unwrap();
+        assert_eq!(
+            erts_garbage_collect(&mut process, 100),
+            GcOutcome::Collected { heap_size: 376, major: false }
+        );
+        assert_eq!(process.heap_sz(), 376);
+        assert!(process.allocate_heap_words(100).is_some());
+    }
+
+    #[test]
+    fn test_fullsweep_after() {
+        let mut process = spawn(&SpawnOpts {
+            fullsweep_after: Some(1),
+            ..SpawnOpts::default()
+        });
+        assert!(matches!(erts_garbage_collect(&mut process, 0), GcOutcome::Collected { major: false, .. }));
+        assert!(matches!(erts_garbage_collect(&mut process, 0), GcOutcome::Collected { major: true, .. }));
+        assert_eq!(process.gen_gcs(), 0);
+    }
+
+    #[test]
+    fn test_max_heap_size_kill() {
+        let mut process = spawn(&SpawnOpts {
+            max_heap_size: Some(MaxHeapSize { size: 300, kill: true, error_logger: false }),
+            ..SpawnOpts::default()
+        });
+        assert_eq!(
+            erts_garbage_collect(&mut process, 400),
+            GcOutcome::MaxHeapSizeExceeded { heap_size: 610, killed: true, report: None }
+        );
+        assert_eq!(process.get_state(), ProcessState::Exiting);
+        assert_eq!(process.heap_sz(), 233);
+    }
+
+    #[test]
+    fn test_max_heap_size_error_report() {
+        let mut process = spawn(&SpawnOpts {
+            max_heap_size: Some(MaxHeapSize { size: 300, kill: true, error_logger: true }),
+            ..SpawnOpts::default()
+        });
+        let report = MaxHeapSizeReport { pid: process.id(), heap_size: 610, limit: 300, killed: true };
+        assert_eq!(
+            erts_garbage_collect(&mut process, 400),
+            GcOutcome::MaxHeapSizeExceeded { heap_size: 610, killed: true, report: Some(report) }
+        );
+        assert_eq!(
+            report.to_string(),
+            format!(
+                "Process {} exceeded max_heap_size: heap size 610 words, limit 300 words, killing process",
+                process.id()
+            )
+        );
+    }
+
+    #[test]
+    fn test_off_heap_messages_do_not_count() {
+        let limit = Some(MaxHeapSize { size: 300, kill: false, error_logger: false });
+        let on_heap = SpawnOpts { max_heap_size: limit, ..SpawnOpts::default() };
+        let off_heap = SpawnOpts {
+            message_queue_data: MessageQueueData::OffHeap,
+            ..on_heap.clone()
+        };
+        for (opts, exceeded) in [(on_heap, true), (off_heap, false)] {
+            let mut process = spawn(&opts);
+            process.send_message(Message::with_heap_fragment(0, vec![0; 250]));
+            let outcome = erts_garbage_collect(&mut process, 0);
+            assert_eq!(matches!(outcome, GcOutcome::MaxHeapSizeExceeded { killed: false, .. }), exceeded);
+        }
+    }
+
+    #[test]
+    fn test_gc_releases_dead_resource_terms() {
+        use infrastructure_nif_api::{
+            enif_alloc_resource, enif_make_resource, enif_release_resource, ErlNifResourceType,
+            ErlNifResourceTypeInit, NifEnv,
+        };
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let destructed = Arc::new(AtomicUsize::new(0));
+        let counter = destructed.clone();
+        let init = ErlNifResourceTypeInit {
+            dtor: Some(Arc::new(move |_: &mut [u8]| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })),
+            ..Default::default()
+        };
+        let resource_type = ErlNifResourceType::with_init("gc".to_string(), "process_gc".to_string(), init);
+
+        // Resource terms at heap words 0 and 2 of process 40694
+        let env = NifEnv::from_process(Arc::new(Process::new(40694)));
+        for _ in 0..2 {
+            let resource = enif_alloc_resource(&resource_type, 1).unwrap();
+            enif_make_resource(&env, &resource);
+            enif_release_resource(resource);
+        }
+
+        // Only the first term is below the heap top of the collected process
+        let mut process = Process::spawned_with_opts(40694, None, InitialCall::new("m", "f", 0), &SpawnOpts::default());
+        process.allocate_heap_words(2).unwrap();
+        erts_garbage_collect(&mut process, 0);
+        assert_eq!(destructed.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "is corrupt")]
+    fn test_gc_checks_heap_in_debug_builds() {
+        use entities_process::term_tags::make_boxed;
+
+        let mut process = spawn(&SpawnOpts::default());
+        let index = process.allocate_heap_words(1).unwrap();
+        process.heap_slice_mut()[index] = make_boxed(100);
+        erts_garbage_collect(&mut process, 0);
+    }
+}
@@ -0,0 +1,215 @@
+//! Process Spawn Module
+//!
+//! Provides process creation for `spawn_opt`.
+//! Based on erl_create_process() from erl_process.c
+//!
+//! The new process is created with its spawn options applied and, when
+//! `link` or `monitor` is given, with the link or monitor to its parent
+//! already in place before it is inserted into the process table. The
+//! parent side is set up before the spawn returns, and the new process does
+//! not run until the caller schedules it, so no exit signal or `'DOWN'`
+//! message can be missed.
+//!
+//! A spawned process inherits the group leader of its parent.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use entities_process::{process_defaults, InitialCall, Monitor, MonitorKind, Process, ProcessId, SpawnOpts};
+use infrastructure_utilities::process_table::{ProcessTable, ProcessTableError};
+
+/// Next reference number for monitors
+static NEXT_MONITOR_REF: AtomicU64 = AtomicU64::new(1);
+
+/// Allocate a reference number for a new monitor
+pub(crate) fn next_monitor_ref() -> u64 {
+    NEXT_MONITOR_REF.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Result of a successful spawn
+#[derive(Debug)]
+pub struct SpawnResult {
+    /// Identifier of the new process
+    pub pid: ProcessId,
+    /// The new process, already in the process table
+    pub process: Arc<Process>,
+    /// Reference of the parent's monitor, if `monitor` was given
+    pub monitor_ref: Option<u64>,
+}
+
+/// Errors from `spawn_opt`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpawnError {
+    /// Invalid or conflicting spawn options (`badarg`)
+    BadOpt(String),
+    /// The process table is full (`system_limit`)
+    SystemLimit,
+}
+
+impl std::fmt::Display for SpawnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpawnError::BadOpt(msg) => write!(f, "Bad spawn option: {}", msg),
+            SpawnError::SystemLimit => write!(f, "Process table is full"),
+        }
+    }
+}
+
+impl std::error::Error for SpawnError {}
+
+impl From<ProcessTableError> for SpawnError {
+    fn from(_: ProcessTableError) -> Self {
+        SpawnError::SystemLimit
+    }
+}
+
+/// Create a process with spawn options
+///
+/// Based on `erl_create_process()` from erl_process.c
+///
+/// # Arguments
+/// * `table` - Process table to insert the new process into
+/// * `parent` - Spawning process, or `None` if spawned by the runtime
+/// * `initial_call` - Function the process is spawned to run
+/// * `opts` - Spawn options
+///
+/// # Returns
+/// * `Ok(SpawnResult)` - The new process and the monitor reference, if any
+/// * `Err(SpawnError::BadOpt)` - Conflicting heap limits, or `link`/`monitor`
+///   without a parent
+/// * `Err(SpawnError::SystemLimit)` - The process table is full
+pub fn erl_spawn_opt(
+    table: &ProcessTable,
+    parent: Option<&Process>,
+    initial_call: InitialCall,
+    opts: &SpawnOpts,
+) -> Result<SpawnResult, SpawnError> {
+    opts.validate(process_defaults().min_heap_size)
+        .map_err(SpawnError::BadOpt)?;
+    if parent.is_none() && (opts.link || opts.monitor) {
+        return Err(SpawnError::BadOpt(
+            "link and monitor need a parent process".to_string(),
+        ));
+    }
+
+    let parent_id = parent.map(Process::id);
+    let group_leader = parent.map(Process::group_leader);
+    let monitor_ref = opts
+        .monitor
+        .then(next_monitor_ref);
+
+    let (pid, process) = table.new_element(|id| {
+        let process = Process::spawned_with_opts(id, parent_id, initial_call.clone(), opts);
+        if let Some(group_leader) = group_leader {
+            process.set_group_leader(group_leader);
+        }
+        if let Some(parent_id) = parent_id {
+            if opts.link {
+                process.add_link(parent_id);
+            }
+            if let Some(reference) = monitor_ref {
+                process.add_monitor(Monitor {
+                    kind: MonitorKind::Process,
+                    reference,
+                    watcher: parent_id,
+                    target: id,
+                });
+            }
+        }
+        Arc::new(process)
+    })?;
+
+    if let Some(parent) = parent {
+        if opts.link {
+            parent.add_link(pid);
+        }
+        if let Some(reference) = monitor_ref {
+            parent.add_monitor(Monitor {
+                kind: MonitorKind::Process,
+                reference,
+                watcher: parent.id(),
+                target: pid,
+            });
+        }
+    }
+
+    Ok(SpawnResult { pid, process, monitor_ref })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entities_process::{MaxHeapSize, ProcessPriority};
+
+    #[test]
+    fn test_spawn_link_and_monitor() {
+        let table = ProcessTable::new();
+        let parent = Process::new(1);
+        let opts = SpawnOpts {
+            link: true,
+            monitor: true,
+            priority: ProcessPriority::Low,
+            ..SpawnOpts::default()
+        };
+        let result = erl_spawn_opt(&table, Some(&parent), InitialCall::new("m", "f", 0), &opts).unwrap();
+        let reference = result.monitor_ref.unwrap();
+
+        assert_eq!(result.process.links(), vec![1]);
+        assert_eq!(parent.links(), vec![result.pid]);
+        assert_eq!(
+            parent.monitors(),
+            vec![Monitor { kind: MonitorKind::Process, reference, watcher: 1, target: result.pid }]
+        );
+        assert_eq!(result.process.monitors(), parent.monitors());
+        assert_eq!(result.process.priority(), ProcessPriority::Low);
+        assert_eq!(result.process.group_leader(), 1);
+        assert!(table.lookup(result.pid).is_some());
+    }
+
+    #[test]
+    fn test_spawn_inherits_group_leader() {
+        let table = ProcessTable::new();
+        let parent = Process::new(1);
+        parent.set_group_leader(42);
+        let result = erl_spawn_opt(&table, Some(&parent), InitialCall::new("m", "f", 0), &SpawnOpts::default()).unwrap();
+        assert_eq!(result.process.group_leader(), 42);
+
+        let orphan = erl_spawn_opt(&table, None, InitialCall::new("m", "f", 0), &SpawnOpts::default()).unwrap();
+        assert_eq!(orphan.process.group_leader(), orphan.pid);
+    }
+
+    #[test]
+    fn test_spawn_rejects_bad_opts() {
+        let table = ProcessTable::new();
+        let opts = SpawnOpts {
+            link: true,
+            ..SpawnOpts::default()
+        };
+        assert!(matches!(
+            erl_spawn_opt(&table, None, InitialCall::new("m", "f", 0), &opts),
+            Err(SpawnError::BadOpt(_))
+        ));
+
+        let opts = SpawnOpts {
+            min_heap_size: Some(1000),
+            max_heap_size: Some(MaxHeapSize::new(500)),
+            ..SpawnOpts::default()
+        };
+        assert!(matches!(
+            erl_spawn_opt(&table, None, InitialCall::new("m", "f", 0), &opts),
+            Err(SpawnError::BadOpt(_))
+        ));
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn test_spawn_table_full() {
+        let table = ProcessTable::with_max_size(1);
+        let opts = SpawnOpts::default();
+        erl_spawn_opt(&table, None, InitialCall::new("m", "f", 0), &opts).unwrap();
+        assert_eq!(
+            erl_spawn_opt(&table, None, InitialCall::new("m", "f", 0), &opts).unwrap_err(),
+            SpawnError::SystemLimit
+        );
+    }
+}
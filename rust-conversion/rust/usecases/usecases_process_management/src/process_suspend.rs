@@ -0,0 +1,221 @@
+//! Process Suspend Module
+//!
+//! Provides `erlang:suspend_process/1,2` and `erlang:resume_process/1`.
+//! Based on the suspend monitor handling in erl_bif_info.c and
+//! erl_process.c
+//!
+//! Suspends nest per suspending process. The first suspend by a process
+//! sets up a suspend monitor on both sides, so that every suspend it still
+//! holds is undone when it exits (see [`erts_resume_suspended_by`]). The
+//! caller takes a suspended process off its run queue and enqueues it again
+//! once it is no longer suspended.
+
+use std::sync::Arc;
+
+use entities_process::{Monitor, MonitorKind, Process, ProcessId};
+use infrastructure_utilities::process_table::ProcessTable;
+
+use crate::process_spawn::next_monitor_ref;
+
+/// Options for `erlang:suspend_process/2`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SuspendOpts {
+    /// Do not suspend again if the caller already has the process suspended
+    pub unless_suspending: bool,
+    /// Return without waiting for the process to be suspended
+    pub asynchronous: bool,
+}
+
+/// Errors from suspend and resume
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuspendError {
+    /// A process tried to suspend or resume itself (`badarg`)
+    SelfSuspend,
+    /// The caller has not suspended the process (`badarg`)
+    NotSuspended,
+    /// The process is exiting (`badarg`)
+    Exiting,
+}
+
+impl std::fmt::Display for SuspendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SuspendError::SelfSuspend => write!(f, "A process cannot suspend itself"),
+            SuspendError::NotSuspended => write!(f, "Process is not suspended by the caller"),
+            SuspendError::Exiting => write!(f, "Process is exiting"),
+        }
+    }
+}
+
+impl std::error::Error for SuspendError {}
+
+/// Suspend a process (`erlang:suspend_process/2`)
+///
+/// # Arguments
+/// * `suspender` - Calling process
+/// * `suspendee` - Process to suspend
+/// * `opts` - Suspend options
+///
+/// # Returns
+/// * `Ok(true)` - The process was suspended
+/// * `Ok(false)` - `unless_suspending` was given and the caller already had
+///   the process suspended
+/// * `Err(SuspendError)` - The suspend is a `badarg`
+pub fn erts_suspend_process(
+    suspender: &Process,
+    suspendee: &Process,
+    opts: SuspendOpts,
+) -> Result<bool, SuspendError> {
+    check_suspendee(suspender, suspendee)?;
+    if opts.unless_suspending && suspendee.suspend_count(suspender.id()) > 0 {
+        return Ok(false);
+    }
+    if suspendee.suspend(suspender.id()) == 1 {
+        let monitor = Monitor {
+            kind: MonitorKind::Suspend,
+            reference: next_monitor_ref(),
+            watcher: suspender.id(),
+            target: suspendee.id(),
+        };
+        suspender.add_monitor(monitor);
+        suspendee.add_monitor(monitor);
+    }
+    Ok(true)
+}
+
+/// Resume a process (`erlang:resume_process/1`)
+///
+/// Undoes one suspend done by the caller.
+///
+/// # Arguments
+/// * `suspender` - Calling process
+/// * `suspendee` - Process to resume
+///
+/// # Returns
+/// * `Ok(true)` - The process is no longer suspended and must be scheduled again
+/// * `Ok(false)` - Other suspends still hold the process
+/// * `Err(SuspendError)` - The resume is a `badarg`
+pub fn erts_resume_process(suspender: &Process, suspendee: &Process) -> Result<bool, SuspendError> {
+    check_suspendee(suspender, suspendee)?;
+    let still_suspended = suspendee
+        .resume(suspender.id())
+        .ok_or(SuspendError::NotSuspended)?;
+    if suspendee.suspend_count(suspender.id()) == 0 {
+        remove_suspend_monitor(suspender, suspendee.id());
+        remove_suspend_monitor(suspendee, suspender.id());
+    }
+    Ok(!still_suspended)
+}
+
+/// Undo every suspend held by an exiting process
+///
+/// Follows the suspend monitors of `suspender` and resumes each process it
+/// has suspended, however many times.
+///
+/// # Arguments
+/// * `suspender` - Exiting process
+/// * `table` - Process table to look up the suspended processes in
+///
+/// # Returns
+/// The processes that are no longer suspended and must be scheduled again
+pub fn erts_resume_suspended_by(suspender: &Process, table: &ProcessTable) -> Vec<Arc<Process>> {
+    let mut resumed = Vec::new();
+    for monitor in suspender.monitors() {
+        if monitor.kind != MonitorKind::Suspend || monitor.watcher != suspender.id() {
+            continue;
+        }
+        suspender.remove_monitor(monitor.reference);
+        if let Some(suspendee) = table.lookup(monitor.target) {
+            suspendee.remove_monitor(monitor.reference);
+            if !suspendee.resume_all(suspender.id()) {
+                resumed.push(suspendee);
+            }
+        }
+    }
+    resumed
+}
+
+/// Check that `suspendee` can be suspended or resumed by `suspender`
+fn check_suspendee(suspender: &Process, suspendee: &Process) -> Result<(), SuspendError> {
+    if suspender.id() == suspendee.id() {
+        return Err(SuspendError::SelfSuspend);
+    }
+    if suspendee.flags() & 0x20 != 0 {
+        // ERTS_PSFLG_EXITING
+        return Err(SuspendError::Exiting);
+    }
+    Ok(())
+}
+
+/// Remove the suspend monitor between `process` and `other`
+fn remove_suspend_monitor(process: &Process, other: ProcessId) {
+    let reference = process
+        .monitors()
+        .into_iter()
+        .find(|m| m.kind == MonitorKind::Suspend && (m.watcher == other || m.target == other))
+        .map(|m| m.reference);
+    if let Some(reference) = reference {
+        process.remove_monitor(reference);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suspend_nests_and_monitors_once() {
+        let suspender = Process::new(1);
+        let suspendee = Process::new(2);
+        let opts = SuspendOpts::default();
+        assert_eq!(erts_suspend_process(&suspender, &suspendee, opts), Ok(true));
+        assert_eq!(erts_suspend_process(&suspender, &suspendee, opts), Ok(true));
+        assert_eq!(suspendee.rcount(), 2);
+        assert_eq!(suspender.monitors().len(), 1);
+        assert_eq!(suspender.monitors(), suspendee.monitors());
+
+        assert_eq!(erts_resume_process(&suspender, &suspendee), Ok(false));
+        assert_eq!(erts_resume_process(&suspender, &suspendee), Ok(true));
+        assert!(!suspendee.is_suspended());
+        assert!(suspender.monitors().is_empty());
+        assert!(suspendee.monitors().is_empty());
+        assert_eq!(erts_resume_process(&suspender, &suspendee), Err(SuspendError::NotSuspended));
+    }
+
+    #[test]
+    fn test_unless_suspending() {
+        let suspender = Process::new(1);
+        let suspendee = Process::new(2);
+        let opts = SuspendOpts { unless_suspending: true, ..SuspendOpts::default() };
+        assert_eq!(erts_suspend_process(&suspender, &suspendee, opts), Ok(true));
+        assert_eq!(erts_suspend_process(&suspender, &suspendee, opts), Ok(false));
+        assert_eq!(suspendee.rcount(), 1);
+    }
+
+    #[test]
+    fn test_suspend_self_is_badarg() {
+        let process = Process::new(1);
+        assert_eq!(
+            erts_suspend_process(&process, &process, SuspendOpts::default()),
+            Err(SuspendError::SelfSuspend)
+        );
+    }
+
+    #[test]
+    fn test_suspender_exit_resumes() {
+        let table = ProcessTable::new();
+        let suspender = Process::new(1);
+        let suspendee = Arc::new(Process::new(2));
+        let other = Process::new(3);
+        table.insert(2, Arc::clone(&suspendee));
+
+        erts_suspend_process(&suspender, &suspendee, SuspendOpts::default()).unwrap();
+        erts_suspend_process(&suspender, &suspendee, SuspendOpts::default()).unwrap();
+        erts_suspend_process(&other, &suspendee, SuspendOpts::default()).unwrap();
+
+        assert!(erts_resume_suspended_by(&suspender, &table).is_empty());
+        assert_eq!(suspendee.rcount(), 1);
+        assert_eq!(erts_resume_process(&other, &suspendee), Ok(true));
+        assert!(suspendee.monitors().is_empty());
+    }
+}
@@ -0,0 +1,227 @@
+//! Process System Task Module
+//!
+//! Provides requesting system tasks on other processes and executing the
+//! system tasks queued on a process.
+//! Based on erts_internal:request_system_task/3 and execute_sys_tasks() in
+//! erl_process.c
+//!
+//! A request queues the task on the target at the requester's priority and
+//! returns a request identifier; the caller then schedules the target, which
+//! runs at the priority of its most urgent task
+//! ([`Process::effective_priority`]). The scheduler executes the queued tasks
+//! before the process runs Erlang code again, and each executed task sends
+//! `{Kind, RequestId, Result}` to its requester:
+//! - `garbage_collect` - `true`, or `false` if the collection exceeded
+//!   `max_heap_size`
+//! - `check_process_code` - `true` if the process executes or refers to the
+//!   code, otherwise `false`
+//! - `copy_literals` - `ok`. Process heaps hold their terms by index, never
+//!   pointers into a literal area, so there is nothing to copy before the
+//!   area is released
+//!
+//! If the target is not alive, the reply is sent at once with `false`
+//! (`ok` for `copy_literals`).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use entities_data_handling::AtomEncoding;
+use entities_process::term_tags::{make_arityval, make_atom, make_small};
+use entities_process::{
+    Eterm, Message, Process, ProcessId, ProcessState, SystemTask, SystemTaskKind, FRAGMENT_ROOT,
+};
+use infrastructure_utilities::atom_table::get_global_atom_table;
+use infrastructure_utilities::process_table::ProcessTable;
+
+use crate::process_code_tracking::{check_process_uses_module, ModuleCodeArea};
+use crate::process_gc::{erts_garbage_collect, GcOutcome};
+
+/// Header of a 3-tuple
+const ARITYVAL_3: Eterm = make_arityval(3);
+
+/// Next system task request identifier
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+/// A system task request
+#[derive(Debug, Clone)]
+pub struct SystemTaskRequest {
+    /// Identifier the reply carries
+    pub request_id: u64,
+    /// Process the task was queued on, to be scheduled by the caller; `None`
+    /// if it is not alive and the reply was sent already
+    pub target: Option<Arc<Process>>,
+}
+
+/// Request a system task on a process
+///
+/// # Arguments
+/// * `requester` - Process the reply is sent to; the task runs at its priority
+/// * `target` - Process to run the task on
+/// * `kind` - Task to run
+/// * `table` - Process table to look up the processes in
+pub fn erts_request_system_task(
+    requester: &Process,
+    target: ProcessId,
+    kind: SystemTaskKind,
+    table: &ProcessTable,
+) -> SystemTaskRequest {
+    let task = SystemTask {
+        kind,
+        requester: requester.id(),
+        request_id: NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed),
+        priority: requester.priority(),
+    };
+    let target = table
+        .lookup(target)
+        .filter(|process| process.get_state() != ProcessState::Exiting);
+    match &target {
+        Some(process) => process.schedule_system_task(task),
+        None => reply(&task, noproc_result(&kind), table),
+    }
+    SystemTaskRequest {
+        request_id: task.request_id,
+        target,
+    }
+}
+
+/// Execute the system tasks queued on a process
+///
+/// Called by the scheduler, while it has the process to itself, before the
+/// process runs Erlang code. Tasks of an exiting process are answered as if
+/// it were not alive.
+///
+/// # Arguments
+/// * `process` - Process to execute the tasks on
+/// * `table` - Process table to look up the requesters in
+///
+/// # Returns
+/// Number of tasks executed
+pub fn erts_execute_system_tasks(process: &mut Process, table: &ProcessTable) -> usize {
+    let mut executed = 0;
+    while let Some(task) = process.take_system_task() {
+        let result = if process.get_state() == ProcessState::Exiting {
+            noproc_result(&task.kind)
+        } else {
+            execute(process, &task.kind)
+        };
+        reply(&task, result, table);
+        executed += 1;
+    }
+    executed
+}
+
+/// Run a task, returning the result atom of the reply
+fn execute(process: &mut Process, kind: &SystemTaskKind) -> &'static str {
+    match *kind {
+        SystemTaskKind::GarbageCollect => match erts_garbage_collect(process, 0) {
+            GcOutcome::Collected { .. } => "true",
+            GcOutcome::MaxHeapSizeExceeded { .. } => "false",
+        },
+        SystemTaskKind::CheckProcessCode { code_start, code_size } => {
+            let module_code = ModuleCodeArea::new(code_start as *const u8, code_size);
+            if check_process_uses_module(process, &module_code) {
+                "true"
+            } else {
+                "false"
+            }
+        }
+        SystemTaskKind::CopyLiterals { .. } => "ok",
+    }
+}
+
+/// Result atom of a task whose target is not alive
+fn noproc_result(kind: &SystemTaskKind) -> &'static str {
+    match kind {
+        SystemTaskKind::CopyLiterals { .. } => "ok",
+        _ => "false",
+    }
+}
+
+/// Send `{Kind, RequestId, Result}` to the requester of a task
+fn reply(task: &SystemTask, result: &str, table: &ProcessTable) {
+    let Some(requester) = table.lookup(task.requester) else {
+        return;
+    };
+    let words = vec![
+        ARITYVAL_3,
+        atom_term(task.kind.name()),
+        make_small(task.request_id as i64),
+        atom_term(result),
+    ];
+    requester.send_message(Message::with_heap_fragment(FRAGMENT_ROOT, words));
+}
+
+/// Atom term of a reply atom (`make_atom`)
+fn atom_term(name: &str) -> Eterm {
+    let index = get_global_atom_table()
+        .put_index(name.as_bytes(), AtomEncoding::SevenBitAscii, false)
+        .expect("reply atoms are valid atoms");
+    make_atom(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entities_process::ProcessPriority;
+
+    fn reply_of(process: &Process) -> Vec<Eterm> {
+        let message = process.receive_after_0(|_| true).expect("a reply");
+        message.heap_fragment.expect("a heap fragment").to_vec()
+    }
+
+    #[test]
+    fn test_request_and_execute_system_tasks() {
+        let table = ProcessTable::new();
+        let mut requester = Process::new(1);
+        requester.set_priority(ProcessPriority::High);
+        table.insert(1, Arc::new(requester));
+        table.insert(2, Arc::new(Process::new(2)));
+        let requester = table.lookup(1).unwrap();
+
+        let gc = erts_request_system_task(&requester, 2, SystemTaskKind::GarbageCollect, &table);
+        let cpc = erts_request_system_task(
+            &requester,
+            2,
+            SystemTaskKind::CheckProcessCode { code_start: 0x1000, code_size: 16 },
+            &table,
+        );
+        let target = gc.target.expect("target is alive");
+        assert_eq!(target.effective_priority(), ProcessPriority::High);
+        assert_eq!(requester.message_queue_len(), 0);
+
+        // The scheduler owns the process while it executes its tasks
+        drop(cpc.target);
+        table.remove(2);
+        let mut target = Arc::try_unwrap(target).unwrap();
+        assert_eq!(erts_execute_system_tasks(&mut target, &table), 2);
+        assert!(!target.has_system_tasks());
+
+        let gc_reply = reply_of(&requester);
+        assert_eq!(gc_reply[0], ARITYVAL_3);
+        assert_eq!(gc_reply[1], atom_term("garbage_collect"));
+        assert_eq!(gc_reply[2], make_small(gc.request_id as i64));
+        assert_eq!(gc_reply[3], atom_term("true"));
+        let cpc_reply = reply_of(&requester);
+        assert_eq!(cpc_reply[1], atom_term("check_process_code"));
+        assert_eq!(cpc_reply[3], atom_term("false"));
+    }
+
+    #[test]
+    fn test_request_on_dead_process_replies_at_once() {
+        let table = ProcessTable::new();
+        table.insert(1, Arc::new(Process::new(1)));
+        let requester = table.lookup(1).unwrap();
+
+        let request = erts_request_system_task(
+            &requester,
+            99,
+            SystemTaskKind::CopyLiterals { area_start: 0, area_size: 0 },
+            &table,
+        );
+        assert!(request.target.is_none());
+        let reply = reply_of(&requester);
+        assert_eq!(reply[1], atom_term("copy_literals"));
+        assert_eq!(reply[2], (request.request_id << 4) | 0xF);
+        assert_eq!(reply[3], atom_term("ok"));
+    }
+}
@@ -0,0 +1,192 @@
+//! BIF Timer Scheduling
+//!
+//! Connects the BIF timer service of `adapters_time_management` to the
+//! schedulers. Based on erts_bump_timers() from erl_hl_timer.c
+//!
+//! Timers created by `erlang:send_after`, `erlang:start_timer` and the
+//! replies to asynchronous `cancel_timer`/`read_timer` requests live in one
+//! global [`BifTimers`] service. No thread is dedicated to it: every
+//! scheduler calls [`erts_bump_timers`] on each pass of its loop, which
+//! expires due timers and delivers their messages, and an idle scheduler
+//! sleeps no longer than [`erts_next_timeout`] allows.
+//!
+//! Delivered messages:
+//! - `send_after` timers deliver `Msg`
+//! - `start_timer` timers deliver `{timeout, TimerRef, Msg}`
+//! - asynchronous requests deliver `{cancel_timer, TimerRef, Result}` and
+//!   `{read_timer, TimerRef, Result}`, where `Result` is the remaining time
+//!   in milliseconds or `false`
+//!
+//! Timeout messages carry the timer reference number
+//! ([`Message::timer_ref`]), so they can be flushed with
+//! `Process::flush_timer_message` after a cancel.
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use adapters_time_management::{BifTimers, TimerEvent, TimerKind, TimerRef, TimerReply};
+use entities_data_handling::AtomEncoding;
+use entities_process::term_tags::{
+    make_arityval, make_atom, make_boxed, make_small, HEADER_ARITY_OFFS, REF_SUBTAG,
+};
+use entities_process::{Eterm, Message, ProcessId, FRAGMENT_ROOT};
+use infrastructure_utilities::atom_table::get_global_atom_table;
+use infrastructure_utilities::process_table::{get_global_process_table, ProcessTable};
+
+/// BIF timer service addressing processes and carrying message terms
+pub type BifTimerService = BifTimers<ProcessId, Eterm>;
+
+/// Event of the global BIF timer service
+pub type BifTimerEvent = TimerEvent<ProcessId, Eterm>;
+
+static GLOBAL_BIF_TIMERS: OnceLock<BifTimerService> = OnceLock::new();
+
+/// Get the global BIF timer service
+pub fn get_global_bif_timers() -> &'static BifTimerService {
+    GLOBAL_BIF_TIMERS.get_or_init(BifTimerService::new)
+}
+
+/// Expire due BIF timers and deliver their messages
+///
+/// Called by every scheduler on each pass of its loop. Delivery happens
+/// under the timer lock, so a concurrent `cancel_timer` either cancels a
+/// timer before it fires or finds its message in the mailbox.
+///
+/// # Returns
+/// Number of messages delivered
+pub fn erts_bump_timers() -> usize {
+    let table = get_global_process_table();
+    let mut delivered = 0;
+    get_global_bif_timers().bump_with(|event| {
+        if deliver_timer_event(table, event) {
+            delivered += 1;
+        }
+    });
+    delivered
+}
+
+/// Longest time an idle scheduler may sleep before a BIF timer is due
+///
+/// # Returns
+/// `Some(Duration::ZERO)` if events are already pending, `None` if there
+/// are no timers
+pub fn erts_next_timeout() -> Option<Duration> {
+    get_global_bif_timers().next_timeout()
+}
+
+/// Deliver one timer event to its destination process
+///
+/// # Returns
+/// `true` if the destination was alive and got the message
+pub fn deliver_timer_event(table: &ProcessTable, event: BifTimerEvent) -> bool {
+    let (to, message) = timer_event_message(event);
+    match table.lookup(to) {
+        Some(process) => {
+            process.send_message(message);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Build the message a timer event delivers, with its destination
+pub fn timer_event_message(event: BifTimerEvent) -> (ProcessId, Message) {
+    match event {
+        TimerEvent::Timeout { timer_ref, kind: TimerKind::SendAfter, dest, msg } => {
+            (dest, Message::from_timer(msg, timer_ref.0))
+        }
+        TimerEvent::Timeout { timer_ref, kind: TimerKind::StartTimer, dest, msg } => {
+            let mut message = tagged_tuple("timeout", timer_ref, msg);
+            message.timer_ref = Some(timer_ref.0);
+            (dest, message)
+        }
+        TimerEvent::CancelResult { to, timer_ref, result } => {
+            (to, tagged_tuple("cancel_timer", timer_ref, reply_term(result)))
+        }
+        TimerEvent::ReadResult { to, timer_ref, result } => {
+            (to, tagged_tuple("read_timer", timer_ref, reply_term(result)))
+        }
+    }
+}
+
+/// Build `{Tag, TimerRef, Value}` in a heap fragment
+///
+/// The tuple is followed by the reference object the second element points to.
+fn tagged_tuple(tag: &str, timer_ref: TimerRef, value: Eterm) -> Message {
+    let words = vec![
+        make_arityval(3),
+        atom_term(tag),
+        make_boxed(4),
+        value,
+        (1 << HEADER_ARITY_OFFS) | REF_SUBTAG,
+        timer_ref.0,
+    ];
+    Message::with_heap_fragment(FRAGMENT_ROOT, words)
+}
+
+/// Term of a cancel/read result: remaining milliseconds, `false` or `ok`
+fn reply_term(reply: TimerReply) -> Eterm {
+    match reply {
+        TimerReply::Remaining(ms) => make_small(ms as i64),
+        TimerReply::NotFound => atom_term("false"),
+        TimerReply::Ok => atom_term("ok"),
+    }
+}
+
+fn atom_term(name: &str) -> Eterm {
+    let index = get_global_atom_table()
+        .put_index(name.as_bytes(), AtomEncoding::SevenBitAscii, false)
+        .expect("timer message atoms are valid atoms");
+    make_atom(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adapters_time_management::{TimerId, TimerOptions};
+    use entities_process::Process;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_start_timer_message() {
+        let (to, message) = timer_event_message(TimerEvent::Timeout {
+            timer_ref: TimerId(7),
+            kind: TimerKind::StartTimer,
+            dest: 3,
+            msg: make_small(1),
+        });
+        assert_eq!(to, 3);
+        assert_eq!(message.payload, FRAGMENT_ROOT);
+        assert_eq!(message.timer_ref, Some(7));
+        let words = message.heap_fragment.unwrap();
+        assert_eq!(words[0], make_arityval(3));
+        assert_eq!(words[1], atom_term("timeout"));
+        assert_eq!(words[3], make_small(1));
+        assert_eq!(words[5], 7);
+    }
+
+    #[test]
+    fn test_send_after_delivered_by_bump() {
+        let table = get_global_process_table();
+        let process = Arc::new(Process::new(862001));
+        table.insert(862001, Arc::clone(&process));
+
+        let timers = get_global_bif_timers();
+        let timer_ref = timers.send_after(0, 862001, make_small(5), TimerOptions::default());
+        assert!(erts_next_timeout().is_some());
+        erts_bump_timers();
+        assert!(process.flush_timer_message(timer_ref.0));
+        table.remove(862001);
+    }
+
+    #[test]
+    fn test_reply_to_dead_process_is_dropped() {
+        let table = ProcessTable::new();
+        let event = TimerEvent::ReadResult {
+            to: 862002,
+            timer_ref: TimerId(1),
+            result: TimerReply::NotFound,
+        };
+        assert!(!deliver_timer_event(&table, event));
+    }
+}
@@ -3,12 +3,31 @@
 //! Provides initialization functions for the scheduling system.
 //! Based on erts_init_scheduling() from erl_process.c
 
+use crate::run_queue::{dequeue_process, enqueue_process, Priority};
 use crate::scheduler::Scheduler;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
 /// Global schedulers (initialized by erts_init_scheduling)
 static GLOBAL_SCHEDULERS: std::sync::OnceLock<Arc<Mutex<Vec<Scheduler>>>> = std::sync::OnceLock::new();
 
+/// Global dirty scheduler counts (set by erts_init_scheduling)
+static GLOBAL_DIRTY_SCHEDULERS: std::sync::OnceLock<DirtySchedulers> = std::sync::OnceLock::new();
+
+/// Dirty CPU schedulers currently online (changed by system_flag/2)
+static DIRTY_CPU_SCHEDULERS_ONLINE: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of dirty schedulers configured at initialization
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirtySchedulers {
+    /// Dirty CPU schedulers
+    pub cpu: usize,
+    /// Dirty CPU schedulers online
+    pub cpu_online: usize,
+    /// Dirty IO schedulers
+    pub io: usize,
+}
+
 /// Initialize scheduling system
 ///
 /// Based on `erts_init_scheduling()` from erl_process.c
@@ -40,9 +59,9 @@ pub fn erts_init_scheduling(
     no_schedulers: usize,
     no_schedulers_online: usize,
     _no_poll_threads: usize,
-    _no_dirty_cpu_schedulers: usize,
-    _no_dirty_cpu_schedulers_online: usize,
-    _no_dirty_io_schedulers: usize,
+    no_dirty_cpu_schedulers: usize,
+    no_dirty_cpu_schedulers_online: usize,
+    no_dirty_io_schedulers: usize,
 ) -> Result<(), String> {
     // Validate parameters
     if no_schedulers_online > no_schedulers {
@@ -93,6 +112,12 @@ pub fn erts_init_scheduling(
     GLOBAL_SCHEDULERS
         .set(Arc::new(Mutex::new(schedulers)))
         .map_err(|_| "Schedulers already initialized".to_string())?;
+    let _ = GLOBAL_DIRTY_SCHEDULERS.set(DirtySchedulers {
+        cpu: no_dirty_cpu_schedulers,
+        cpu_online: no_dirty_cpu_schedulers_online,
+        io: no_dirty_io_schedulers,
+    });
+    DIRTY_CPU_SCHEDULERS_ONLINE.store(no_dirty_cpu_schedulers_online, Ordering::Release);
 
     // In a full implementation, we would also:
     // - Create dirty CPU schedulers
@@ -111,6 +136,93 @@ pub fn get_global_schedulers() -> Option<&'static Arc<Mutex<Vec<Scheduler>>>> {
     GLOBAL_SCHEDULERS.get()
 }
 
+/// Get the dirty scheduler counts
+///
+/// # Returns
+/// The counts given to `erts_init_scheduling`, or `None` before initialization
+pub fn get_global_dirty_schedulers() -> Option<DirtySchedulers> {
+    let dirty = GLOBAL_DIRTY_SCHEDULERS.get()?;
+    Some(DirtySchedulers {
+        cpu_online: DIRTY_CPU_SCHEDULERS_ONLINE.load(Ordering::Acquire),
+        ..*dirty
+    })
+}
+
+/// Change the number of schedulers online
+///
+/// Based on `erts_set_schedulers_online()` from erl_process.c
+///
+/// Schedulers with an index below `online` are activated and the others are
+/// suspended; scheduler threads of suspended schedulers idle until they are
+/// brought back online. Processes queued on a suspended scheduler are moved
+/// to the run queues of the schedulers still online.
+///
+/// # Arguments
+/// * `online` - Number of schedulers to keep online (1 to the number of schedulers)
+///
+/// # Returns
+/// * `Ok(old)` - Number of schedulers online before the change
+/// * `Err(String)` - Schedulers not initialized, or `online` out of range
+pub fn set_schedulers_online(online: usize) -> Result<usize, String> {
+    let schedulers = get_global_schedulers().ok_or("Schedulers not initialized")?;
+    let schedulers = schedulers.lock().unwrap();
+    bring_schedulers_online(&schedulers, online)
+}
+
+/// Activate the first `online` schedulers and suspend the rest, moving the
+/// processes queued on suspended schedulers to the online ones
+fn bring_schedulers_online(schedulers: &[Scheduler], online: usize) -> Result<usize, String> {
+    if online < 1 || online > schedulers.len() {
+        return Err(format!(
+            "schedulers_online must be between 1 and {}, got {}",
+            schedulers.len(),
+            online
+        ));
+    }
+
+    let old = schedulers.iter().filter(|scheduler| scheduler.is_active()).count();
+    for scheduler in schedulers {
+        let active = scheduler.index() < online;
+        scheduler.set_active(active);
+        scheduler.set_sleeping(!active);
+    }
+
+    // Migrate work off the suspended schedulers
+    let mut target = 0;
+    for scheduler in &schedulers[online..] {
+        let runq = scheduler.runq();
+        let runq = runq.lock().unwrap();
+        for prio in [Priority::Max, Priority::High, Priority::Normal] {
+            while let Some(process) = dequeue_process(&runq, prio) {
+                let to = schedulers[target].runq();
+                enqueue_process(&to.lock().unwrap(), prio, process);
+                target = (target + 1) % online;
+            }
+        }
+    }
+    Ok(old)
+}
+
+/// Change the number of dirty CPU schedulers online
+///
+/// # Arguments
+/// * `online` - Number of dirty CPU schedulers to keep online (1 to the
+///   number of dirty CPU schedulers)
+///
+/// # Returns
+/// * `Ok(old)` - Number of dirty CPU schedulers online before the change
+/// * `Err(String)` - Schedulers not initialized, or `online` out of range
+pub fn set_dirty_cpu_schedulers_online(online: usize) -> Result<usize, String> {
+    let dirty = GLOBAL_DIRTY_SCHEDULERS.get().ok_or("Schedulers not initialized")?;
+    if online < 1 || online > dirty.cpu {
+        return Err(format!(
+            "dirty_cpu_schedulers_online must be between 1 and {}, got {}",
+            dirty.cpu, online
+        ));
+    }
+    Ok(DIRTY_CPU_SCHEDULERS_ONLINE.swap(online, Ordering::AcqRel))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -126,6 +238,7 @@ mod tests {
         let schedulers = schedulers.unwrap();
         let scheds = schedulers.lock().unwrap();
         assert_eq!(scheds.len(), 4);
+        assert_eq!(get_global_dirty_schedulers(), Some(DirtySchedulers { cpu: 0, cpu_online: 0, io: 0 }));
     }
 
     #[test]
@@ -142,5 +255,36 @@ mod tests {
         let result = erts_init_scheduling(0, 0, 1, 0, 0, 0);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_bring_schedulers_online_migrates_processes() {
+        use entities_process::Process;
+
+        let schedulers: Vec<Scheduler> = (0..3).map(|i| Scheduler::new(i, 0)).collect();
+        for scheduler in &schedulers {
+            scheduler.set_active(true);
+        }
+        let runq = schedulers[2].runq();
+        enqueue_process(&runq.lock().unwrap(), Priority::Normal, Arc::new(Process::new(1)));
+        enqueue_process(&runq.lock().unwrap(), Priority::High, Arc::new(Process::new(2)));
+
+        assert_eq!(bring_schedulers_online(&schedulers, 2), Ok(3));
+        assert!(schedulers[1].is_active());
+        assert!(!schedulers[2].is_active() && schedulers[2].is_sleeping());
+        assert_eq!(schedulers[2].runq().lock().unwrap().total_len(), 0);
+        let moved: usize = schedulers[..2].iter().map(|s| s.runq().lock().unwrap().total_len()).sum();
+        assert_eq!(moved, 2);
+
+        assert_eq!(bring_schedulers_online(&schedulers, 3), Ok(2));
+        assert!(schedulers[2].is_active());
+        assert!(bring_schedulers_online(&schedulers, 0).is_err());
+        assert!(bring_schedulers_online(&schedulers, 4).is_err());
+    }
+
+    #[test]
+    fn test_set_dirty_cpu_schedulers_online_range() {
+        // No test initializes dirty CPU schedulers, so any count is out of range
+        assert!(set_dirty_cpu_schedulers_online(1).is_err());
+    }
 }
 
@@ -15,12 +15,18 @@
 //! - **[`run_queue`](run_queue/index.html)**: Run queue management with priority queues
 //!   for scheduling processes at different priority levels
 //!
+//! - **[`port_task`](port_task/index.html)**: Port task queues; port operations are
+//!   queued on the port and executed with reductions by the owning scheduler
+//!
+//! - **[`bif_timers`](bif_timers/index.html)**: The global BIF timer service; schedulers
+//!   expire timers on each pass of their loop and deliver the timeout messages
+//!
 //! - **[`scheduler`](scheduler/index.html)**: Scheduler functions including the main
 //!   scheduler loop, scheduler wake/sleep, and scheduler state management
 //!
 //! ## Architecture
 //!
-//! This crate is based on the C implementation in `erl_process.c`. It depends on:
+//! This crate is based on the C implementation in `erl_process.c` and `erl_port_task.c`. It depends on:
 //! - `entities_process` for Process structures
 //! - `infrastructure_utilities` for process table access
 //! - `usecases_process_management` for process state management
@@ -37,12 +43,16 @@
 //! - [`infrastructure_utilities`](../../infrastructure/infrastructure_utilities/index.html): Infrastructure utilities
 
 pub mod run_queue;
+pub mod port_task;
+pub mod bif_timers;
 pub mod scheduler;
 pub mod initialization;
 pub mod threads;
 
-pub use run_queue::{RunQueue, RunPrioQueue, RunQueueInfo, Priority, dequeue_process, enqueue_process, check_requeue_process};
-pub use scheduler::{Scheduler, schedule_process, erts_schedule, wake_scheduler, init_scheduler_suspend, ScheduleError};
-pub use initialization::{erts_init_scheduling, get_global_schedulers};
-pub use threads::{erts_start_schedulers, erts_stop_schedulers};
+pub use run_queue::{RunQueue, RunPrioQueue, RunQueueInfo, Priority, dequeue_process, enqueue_process, remove_process, check_requeue_process};
+pub use port_task::{PortTask, PortTaskType, PortTaskQueue, PortTaskExecution, PortTaskError, erts_port_task_schedule, erts_port_task_execute, erts_schedule_port_task, erts_port_task_free, PORT_REDS_LIMIT};
+pub use bif_timers::{BifTimerEvent, BifTimerService, deliver_timer_event, erts_bump_timers, erts_next_timeout, get_global_bif_timers, timer_event_message};
+pub use scheduler::{Scheduler, schedule_process, schedule_process_at_priority, request_system_task, suspend_scheduled_process, resume_scheduled_process, resume_busy_senders, erts_schedule, wake_scheduler, init_scheduler_suspend, ScheduleError};
+pub use initialization::{erts_init_scheduling, get_global_dirty_schedulers, get_global_schedulers, set_dirty_cpu_schedulers_online, set_schedulers_online, DirtySchedulers};
+pub use threads::{erts_active_schedulers, erts_halt_schedulers, erts_schedulers_running, erts_start_schedulers, erts_stop_schedulers};
 
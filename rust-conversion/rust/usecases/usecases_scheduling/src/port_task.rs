@@ -0,0 +1,541 @@
+//! Port Task Scheduling
+//!
+//! Provides port task queues and their execution on scheduler run queues.
+//! Based on erts_port_task_schedule() and erts_port_task_execute() from
+//! erl_port_task.c
+//!
+//! Operations on a port (driver output, commands and control requests from
+//! processes, I/O readiness, timeouts) are not executed directly by the
+//! caller. They are queued as port tasks on the port, and the port itself
+//! is put in the port queue of the run queue it is bound to. A scheduler
+//! serving that run queue later pops the port and executes its tasks,
+//! charging reductions for each one, until the queue is empty or the
+//! reduction budget ([`PORT_REDS_LIMIT`]) is used up. A port with tasks
+//! left is put back at the end of the port queue.
+//!
+//! A port is in the port queue at most once. Tasks scheduled while the
+//! port is queued or executing are simply appended to its task queue.
+//!
+//! Every port has one task queue, created the first time a task is scheduled
+//! with [`erts_schedule_port_task`] and bound to a run queue picked from the
+//! port identifier. Schedulers execute the ports in their port queue on
+//! each pass of their loop.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::initialization::get_global_schedulers;
+use crate::run_queue::RunQueue;
+
+/// Reductions a port may execute before it has to yield
+///
+/// Based on CONTEXT_REDS from erl_vm.h
+pub const PORT_REDS_LIMIT: i64 = 4000;
+
+/// Reductions charged for a timeout task (ERTS_PORT_REDS_TIMEOUT)
+pub const PORT_REDS_TIMEOUT: i64 = 200;
+/// Reductions charged for an input task (ERTS_PORT_REDS_INPUT)
+pub const PORT_REDS_INPUT: i64 = 200;
+/// Reductions charged for an output task (ERTS_PORT_REDS_OUTPUT)
+pub const PORT_REDS_OUTPUT: i64 = 200;
+/// Reductions charged for an event task (ERTS_PORT_REDS_EVENT)
+pub const PORT_REDS_EVENT: i64 = 200;
+/// Reductions charged for a signal from a process (command, control, call)
+pub const PORT_REDS_PROC_SIG: i64 = 100;
+
+/// Type of a port task
+///
+/// Based on ErtsPortTaskType from erl_port_task.h. Process-to-port signals
+/// (ERTS_PORT_TASK_PROC_SIG) are split into the operations they carry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PortTaskType {
+    /// Driver timer timeout
+    Timeout,
+    /// Input ready on a driver event
+    Input,
+    /// Output ready on a driver event
+    Output,
+    /// Generic driver event
+    Event,
+    /// `port_command/2` or `Port ! {Pid, {command, Data}}`
+    Command,
+    /// `port_control/3`
+    Control,
+    /// `erlang:port_call/3`
+    Call,
+}
+
+impl PortTaskType {
+    /// Reductions charged for executing a task of this type
+    ///
+    /// Work reported by the driver callback is charged on top of this.
+    pub fn reductions(self) -> i64 {
+        match self {
+            PortTaskType::Timeout => PORT_REDS_TIMEOUT,
+            PortTaskType::Input => PORT_REDS_INPUT,
+            PortTaskType::Output => PORT_REDS_OUTPUT,
+            PortTaskType::Event => PORT_REDS_EVENT,
+            PortTaskType::Command | PortTaskType::Control | PortTaskType::Call => {
+                PORT_REDS_PROC_SIG
+            }
+        }
+    }
+}
+
+/// A task queued on a port
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortTask {
+    /// Task type
+    pub task_type: PortTaskType,
+    /// Data for the operation (command data, control buffer, ...)
+    pub data: Vec<u8>,
+    /// Driver event (file descriptor) of `Input`, `Output` and `Event` tasks
+    pub event: i32,
+}
+
+impl PortTask {
+    /// Create a task without data
+    pub fn new(task_type: PortTaskType) -> Self {
+        Self {
+            task_type,
+            data: Vec::new(),
+            event: -1,
+        }
+    }
+
+    /// Create a task carrying data
+    pub fn with_data(task_type: PortTaskType, data: Vec<u8>) -> Self {
+        Self {
+            task_type,
+            data,
+            event: -1,
+        }
+    }
+
+    /// Create a task for readiness of a driver event
+    pub fn with_event(task_type: PortTaskType, event: i32) -> Self {
+        Self {
+            task_type,
+            data: Vec::new(),
+            event,
+        }
+    }
+}
+
+/// Scheduling state of a port, protected by the queue lock
+#[derive(Debug, Default)]
+struct PortTaskState {
+    /// Pending tasks (FIFO)
+    tasks: VecDeque<PortTask>,
+    /// Port is in the port queue of its run queue
+    in_runq: bool,
+    /// Port tasks are being executed by a scheduler
+    executing: bool,
+    /// Port has been closed; no more tasks are accepted
+    closed: bool,
+}
+
+/// Port task queue of a single port
+///
+/// Based on ErtsPortTaskSched from erl_port_task.h. A port is bound to the
+/// run queue of its owning scheduler and its tasks are only executed there.
+#[derive(Debug)]
+pub struct PortTaskQueue {
+    /// Port identifier
+    port_id: u64,
+    /// Index of the run queue the port is bound to
+    runq_index: usize,
+    /// Scheduling state
+    state: Mutex<PortTaskState>,
+}
+
+impl PortTaskQueue {
+    /// Create a task queue for a port bound to run queue `runq_index`
+    pub fn new(port_id: u64, runq_index: usize) -> Self {
+        Self {
+            port_id,
+            runq_index,
+            state: Mutex::new(PortTaskState::default()),
+        }
+    }
+
+    /// Get the port identifier
+    pub fn port_id(&self) -> u64 {
+        self.port_id
+    }
+
+    /// Get the index of the run queue the port is bound to
+    pub fn runq_index(&self) -> usize {
+        self.runq_index
+    }
+
+    /// Number of pending tasks
+    pub fn pending(&self) -> usize {
+        self.state.lock().unwrap().tasks.len()
+    }
+
+    /// Check if the port is waiting in a port queue
+    pub fn is_scheduled(&self) -> bool {
+        self.state.lock().unwrap().in_runq
+    }
+
+    /// Check if the port has been closed
+    pub fn is_closed(&self) -> bool {
+        self.state.lock().unwrap().closed
+    }
+
+    /// Close the port
+    ///
+    /// Pending tasks are aborted and returned. Tasks scheduled afterwards are
+    /// rejected with [`PortTaskError::PortClosed`]. If the port is still in
+    /// the port queue, the scheduler drops it when it is popped.
+    ///
+    /// Based on erts_port_task_abort() and port termination in erl_port_task.c
+    pub fn close(&self) -> Vec<PortTask> {
+        let mut state = self.state.lock().unwrap();
+        state.closed = true;
+        state.tasks.drain(..).collect()
+    }
+}
+
+/// Result of executing a port from the port queue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortTaskExecution {
+    /// Port that was executed
+    pub port_id: u64,
+    /// Number of tasks executed
+    pub tasks_executed: usize,
+    /// Reductions charged
+    pub reductions: i64,
+    /// Port had tasks left and was put back in the port queue
+    pub rescheduled: bool,
+}
+
+/// Port task errors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortTaskError {
+    /// Port has been closed
+    PortClosed,
+    /// Schedulers have not been initialized
+    NoSchedulers,
+    /// Port is bound to a different run queue
+    RunQueueMismatch {
+        /// Run queue the port is bound to
+        expected: usize,
+        /// Run queue the task was scheduled on
+        actual: usize,
+    },
+}
+
+impl std::fmt::Display for PortTaskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PortTaskError::PortClosed => write!(f, "Port is closed"),
+            PortTaskError::NoSchedulers => write!(f, "Schedulers are not initialized"),
+            PortTaskError::RunQueueMismatch { expected, actual } => write!(
+                f,
+                "Port is bound to run queue {} but was scheduled on run queue {}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PortTaskError {}
+
+/// Schedule a task on a port
+///
+/// Based on erts_port_task_schedule() from erl_port_task.c
+///
+/// The task is appended to the port's task queue. If the port is neither
+/// queued nor executing, it is also enqueued in the port queue of `runq`.
+///
+/// # Arguments
+/// * `runq` - Run queue of the scheduler owning the port
+/// * `port` - Port task queue
+/// * `task` - Task to schedule
+///
+/// # Returns
+/// * `Ok(())` - Task scheduled
+/// * `Err(PortTaskError)` - Port is closed or bound to another run queue
+pub fn erts_port_task_schedule(
+    runq: &RunQueue,
+    port: &Arc<PortTaskQueue>,
+    task: PortTask,
+) -> Result<(), PortTaskError> {
+    if runq.index() != port.runq_index {
+        return Err(PortTaskError::RunQueueMismatch {
+            expected: port.runq_index,
+            actual: runq.index(),
+        });
+    }
+
+    let mut state = port.state.lock().unwrap();
+    if state.closed {
+        return Err(PortTaskError::PortClosed);
+    }
+    state.tasks.push_back(task);
+    if !state.in_runq && !state.executing {
+        state.in_runq = true;
+        runq.enqueue_port(Arc::clone(port));
+    }
+    Ok(())
+}
+
+/// Task queues of the ports that have had tasks scheduled
+fn port_task_queues() -> &'static Mutex<HashMap<u64, Arc<PortTaskQueue>>> {
+    static QUEUES: OnceLock<Mutex<HashMap<u64, Arc<PortTaskQueue>>>> = OnceLock::new();
+    QUEUES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Schedule a task on a port by identifier
+///
+/// The port's task queue is created on first use and bound to the run queue
+/// of scheduler `port_id % schedulers`; the task is then scheduled there
+/// with [`erts_port_task_schedule`].
+///
+/// # Arguments
+/// * `port_id` - Port identifier
+/// * `task` - Task to schedule
+///
+/// # Returns
+/// * `Ok(())` - Task scheduled
+/// * `Err(PortTaskError::NoSchedulers)` - Schedulers are not initialized
+/// * `Err(PortTaskError::PortClosed)` - The port's task queue was closed
+pub fn erts_schedule_port_task(port_id: u64, task: PortTask) -> Result<(), PortTaskError> {
+    let schedulers = get_global_schedulers().ok_or(PortTaskError::NoSchedulers)?;
+    let runq = {
+        let schedulers = schedulers.lock().unwrap();
+        if schedulers.is_empty() {
+            return Err(PortTaskError::NoSchedulers);
+        }
+        schedulers[(port_id % schedulers.len() as u64) as usize].runq()
+    };
+    let runq = runq.lock().unwrap();
+    let port = Arc::clone(
+        port_task_queues()
+            .lock()
+            .unwrap()
+            .entry(port_id)
+            .or_insert_with(|| Arc::new(PortTaskQueue::new(port_id, runq.index()))),
+    );
+    erts_port_task_schedule(&runq, &port, task)
+}
+
+/// Close the task queue of a port that has been closed
+///
+/// Pending tasks are aborted and returned, and the next task scheduled on
+/// the port identifier gets a fresh queue.
+pub fn erts_port_task_free(port_id: u64) -> Vec<PortTask> {
+    match port_task_queues().lock().unwrap().remove(&port_id) {
+        Some(port) => port.close(),
+        None => Vec::new(),
+    }
+}
+
+/// Execute the first port in the port queue of a run queue
+///
+/// Based on erts_port_task_execute() from erl_port_task.c
+///
+/// Pops a port and executes its tasks in order by calling `handler` with the
+/// port identifier and the task. Each task is charged
+/// [`PortTaskType::reductions`] plus the reductions returned by `handler`.
+/// Execution stops when the task queue is empty or [`PORT_REDS_LIMIT`]
+/// reductions have been used; a port with tasks left is put back at the end
+/// of the port queue. The reductions are accounted on the run queue.
+///
+/// # Arguments
+/// * `runq` - Run queue to take the port from
+/// * `handler` - Executes one task, returning extra reductions used
+///
+/// # Returns
+/// * `Some(PortTaskExecution)` - A port was executed
+/// * `None` - The port queue was empty
+pub fn erts_port_task_execute<F>(runq: &RunQueue, mut handler: F) -> Option<PortTaskExecution>
+where
+    F: FnMut(u64, &PortTask) -> i64,
+{
+    let port = runq.dequeue_port()?;
+    {
+        let mut state = port.state.lock().unwrap();
+        state.in_runq = false;
+        state.executing = true;
+    }
+
+    let mut tasks_executed = 0;
+    let mut reductions = 0;
+    while reductions < PORT_REDS_LIMIT {
+        let task = {
+            let mut state = port.state.lock().unwrap();
+            if state.closed {
+                break;
+            }
+            match state.tasks.pop_front() {
+                Some(task) => task,
+                None => break,
+            }
+        };
+        reductions += task.task_type.reductions() + handler(port.port_id, &task).max(0);
+        tasks_executed += 1;
+    }
+
+    let rescheduled = {
+        let mut state = port.state.lock().unwrap();
+        state.executing = false;
+        if !state.closed && !state.tasks.is_empty() {
+            state.in_runq = true;
+            true
+        } else {
+            false
+        }
+    };
+    runq.add_port_reds(reductions);
+    let port_id = port.port_id;
+    if rescheduled {
+        runq.enqueue_port(port);
+    }
+
+    Some(PortTaskExecution {
+        port_id,
+        tasks_executed,
+        reductions,
+        rescheduled,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn port(id: u64) -> Arc<PortTaskQueue> {
+        Arc::new(PortTaskQueue::new(id, 0))
+    }
+
+    #[test]
+    fn test_schedule_enqueues_port_once() {
+        let runq = RunQueue::new(0, 0);
+        let p = port(1);
+        erts_port_task_schedule(&runq, &p, PortTask::new(PortTaskType::Output)).unwrap();
+        erts_port_task_schedule(&runq, &p, PortTask::new(PortTaskType::Input)).unwrap();
+        assert_eq!(runq.ports_len(), 1);
+        assert_eq!(runq.total_len(), 1);
+        assert_eq!(p.pending(), 2);
+        assert!(p.is_scheduled());
+    }
+
+    #[test]
+    fn test_execute_runs_tasks_in_order() {
+        let runq = RunQueue::new(0, 0);
+        let p = port(7);
+        for task_type in [PortTaskType::Command, PortTaskType::Control, PortTaskType::Output] {
+            erts_port_task_schedule(&runq, &p, PortTask::new(task_type)).unwrap();
+        }
+
+        let mut seen = Vec::new();
+        let result = erts_port_task_execute(&runq, |id, task| {
+            seen.push((id, task.task_type));
+            0
+        })
+        .unwrap();
+
+        assert_eq!(
+            seen,
+            vec![
+                (7, PortTaskType::Command),
+                (7, PortTaskType::Control),
+                (7, PortTaskType::Output)
+            ]
+        );
+        assert_eq!(result.tasks_executed, 3);
+        assert_eq!(result.reductions, 2 * PORT_REDS_PROC_SIG + PORT_REDS_OUTPUT);
+        assert!(!result.rescheduled);
+        assert_eq!(runq.total_len(), 0);
+        assert_eq!(runq.ports_info().reds(), result.reductions);
+        assert!(erts_port_task_execute(&runq, |_, _| 0).is_none());
+    }
+
+    #[test]
+    fn test_execute_yields_after_reduction_limit() {
+        let runq = RunQueue::new(0, 0);
+        let busy = port(1);
+        let other = port(2);
+        for _ in 0..10 {
+            erts_port_task_schedule(&runq, &busy, PortTask::new(PortTaskType::Output)).unwrap();
+        }
+        erts_port_task_schedule(&runq, &other, PortTask::new(PortTaskType::Input)).unwrap();
+
+        // Each task costs 1000 + 200 reductions, so the budget allows 4
+        let first = erts_port_task_execute(&runq, |_, _| 1000).unwrap();
+        assert_eq!(first.port_id, 1);
+        assert_eq!(first.tasks_executed, 4);
+        assert!(first.rescheduled);
+        assert_eq!(busy.pending(), 6);
+
+        // The other port runs before the busy port gets another turn
+        let second = erts_port_task_execute(&runq, |_, _| 0).unwrap();
+        assert_eq!(second.port_id, 2);
+        let third = erts_port_task_execute(&runq, |_, _| 0).unwrap();
+        assert_eq!(third.port_id, 1);
+        assert_eq!(third.tasks_executed, 6);
+    }
+
+    #[test]
+    fn test_schedule_during_execution_reschedules() {
+        let runq = RunQueue::new(0, 0);
+        let p = port(3);
+        erts_port_task_schedule(&runq, &p, PortTask::new(PortTaskType::Command)).unwrap();
+
+        let result = erts_port_task_execute(&runq, |_, _| {
+            // A task arriving while the port executes must not queue it twice
+            erts_port_task_schedule(&runq, &p, PortTask::new(PortTaskType::Output)).unwrap();
+            assert_eq!(runq.ports_len(), 0);
+            PORT_REDS_LIMIT
+        })
+        .unwrap();
+        assert!(result.rescheduled);
+        assert_eq!(runq.ports_len(), 1);
+        assert_eq!(p.pending(), 1);
+    }
+
+    #[test]
+    fn test_closed_port() {
+        let runq = RunQueue::new(0, 0);
+        let p = port(4);
+        erts_port_task_schedule(&runq, &p, PortTask::with_data(PortTaskType::Command, b"hi".to_vec()))
+            .unwrap();
+        let aborted = p.close();
+        assert_eq!(aborted.len(), 1);
+        assert_eq!(aborted[0].data, b"hi".to_vec());
+        assert_eq!(
+            erts_port_task_schedule(&runq, &p, PortTask::new(PortTaskType::Output)),
+            Err(PortTaskError::PortClosed)
+        );
+
+        let result = erts_port_task_execute(&runq, |_, _| panic!("closed port executed")).unwrap();
+        assert_eq!(result.tasks_executed, 0);
+        assert!(!result.rescheduled);
+        assert_eq!(runq.total_len(), 0);
+    }
+
+    #[test]
+    fn test_port_task_free_without_queue() {
+        assert!(erts_port_task_free(u64::MAX).is_empty());
+        let task = PortTask::with_event(PortTaskType::Input, 4);
+        assert_eq!(task.event, 4);
+        assert!(task.data.is_empty());
+    }
+
+    #[test]
+    fn test_run_queue_mismatch() {
+        let runq = RunQueue::new(1, 0);
+        let p = port(5);
+        assert_eq!(
+            erts_port_task_schedule(&runq, &p, PortTask::new(PortTaskType::Timeout)),
+            Err(PortTaskError::RunQueueMismatch { expected: 0, actual: 1 })
+        );
+        assert_eq!(
+            PortTaskError::RunQueueMismatch { expected: 0, actual: 1 }.to_string(),
+            "Port is bound to run queue 0 but was scheduled on run queue 1"
+        );
+    }
+}
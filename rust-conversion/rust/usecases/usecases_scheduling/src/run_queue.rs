@@ -4,11 +4,14 @@
 //! Based on ErtsRunQueue, ErtsRunPrioQueue, and ErtsRunQueueInfo from erl_process.h
 //!
 //! The run queue maintains multiple priority queues for processes at different
-//! priority levels: MAX, HIGH, NORMAL, and LOW.
+//! priority levels: MAX, HIGH, NORMAL, and LOW, plus a queue of ports that
+//! have pending port tasks (see [`port_task`](crate::port_task)).
 
 use std::sync::{Arc, Mutex};
 use std::collections::VecDeque;
-use entities_process::Process;
+use entities_process::{Process, ProcessId, ProcessPriority};
+use infrastructure_utilities::statistics::get_global_statistics;
+use crate::port_task::PortTaskQueue;
 
 /// Process priority levels
 ///
@@ -46,6 +49,17 @@ impl Priority {
     }
 }
 
+impl From<ProcessPriority> for Priority {
+    fn from(priority: ProcessPriority) -> Self {
+        match priority {
+            ProcessPriority::Max => Priority::Max,
+            ProcessPriority::High => Priority::High,
+            ProcessPriority::Normal => Priority::Normal,
+            ProcessPriority::Low => Priority::Low,
+        }
+    }
+}
+
 /// Run queue information for a priority level
 ///
 /// Tracks the length, maximum length, and reductions for processes at a priority level.
@@ -164,6 +178,13 @@ impl RunPrioQueue {
     pub fn len(&self) -> usize {
         self.queue.lock().unwrap().len()
     }
+
+    /// Remove the process with identifier `id` from the queue
+    pub fn remove(&self, id: ProcessId) -> Option<Arc<Process>> {
+        let mut queue = self.queue.lock().unwrap();
+        let position = queue.iter().position(|p| p.id() == id)?;
+        queue.remove(position)
+    }
 }
 
 impl Default for RunPrioQueue {
@@ -186,7 +207,11 @@ pub struct RunQueue {
     prio_queues: [RunPrioQueue; 3],
     /// Information for each priority level
     prio_info: [Mutex<RunQueueInfo>; Priority::LEVELS],
-    /// Total length across all priority levels
+    /// Ports with pending port tasks (FIFO)
+    ports: Mutex<VecDeque<Arc<PortTaskQueue>>>,
+    /// Information for the port queue
+    ports_info: Mutex<RunQueueInfo>,
+    /// Total length across all priority levels and the port queue
     total_len: Mutex<usize>,
     /// Maximum total length (0 = unlimited)
     max_len: usize,
@@ -213,6 +238,8 @@ impl RunQueue {
                 Mutex::new(RunQueueInfo::new()), // NORMAL
                 Mutex::new(RunQueueInfo::new()), // LOW
             ],
+            ports: Mutex::new(VecDeque::new()),
+            ports_info: Mutex::new(RunQueueInfo::new()),
             total_len: Mutex::new(0),
             max_len,
             index,
@@ -229,6 +256,47 @@ impl RunQueue {
         *self.total_len.lock().unwrap()
     }
 
+    /// Number of ports waiting in the port queue
+    pub fn ports_len(&self) -> usize {
+        self.ports.lock().unwrap().len()
+    }
+
+    /// Get a snapshot of the port queue information (length and reductions)
+    pub fn ports_info(&self) -> RunQueueInfo {
+        self.ports_info.lock().unwrap().clone()
+    }
+
+    /// Enqueue a port at the end of the port queue
+    ///
+    /// Based on enqueue_port() from erl_port_task.c
+    pub(crate) fn enqueue_port(&self, port: Arc<PortTaskQueue>) {
+        self.ports_info.lock().unwrap().inc_len();
+        let mut total = self.total_len.lock().unwrap();
+        *total += 1;
+        get_global_statistics().set_run_queue_length(self.index, *total);
+        drop(total);
+        self.ports.lock().unwrap().push_back(port);
+    }
+
+    /// Dequeue a port from the front of the port queue
+    ///
+    /// Based on pop_port() from erl_port_task.c
+    pub(crate) fn dequeue_port(&self) -> Option<Arc<PortTaskQueue>> {
+        let port = self.ports.lock().unwrap().pop_front()?;
+        self.ports_info.lock().unwrap().dec_len();
+        let mut total = self.total_len.lock().unwrap();
+        if *total > 0 {
+            *total -= 1;
+        }
+        get_global_statistics().set_run_queue_length(self.index, *total);
+        Some(port)
+    }
+
+    /// Account reductions executed by ports on this run queue
+    pub(crate) fn add_port_reds(&self, reds: i64) {
+        self.ports_info.lock().unwrap().add_reds(reds);
+    }
+
     /// Get priority queue for a priority level
     ///
     /// LOW priority processes use the NORMAL queue
@@ -253,6 +321,7 @@ impl RunQueue {
         
         let mut total = self.total_len.lock().unwrap();
         *total += 1;
+        get_global_statistics().set_run_queue_length(self.index, *total);
     }
 
     /// Decrement run queue length
@@ -265,6 +334,7 @@ impl RunQueue {
         if *total > 0 {
             *total -= 1;
         }
+        get_global_statistics().set_run_queue_length(self.index, *total);
     }
 }
 
@@ -333,6 +403,29 @@ pub fn enqueue_process(runq: &RunQueue, prio: Priority, process: Arc<Process>) {
     queue.enqueue(process);
 }
 
+/// Remove a process from a run queue, wherever it is queued
+///
+/// Used when a queued process is suspended; it is enqueued again when it
+/// is resumed.
+///
+/// # Arguments
+/// * `runq` - Run queue to remove the process from
+/// * `process` - Process to remove; its priority, or the priority it
+///   inherits from its system tasks, selects the queue
+///
+/// # Returns
+/// `true` if the process was queued
+pub fn remove_process(runq: &RunQueue, process: &Process) -> bool {
+    let priorities = [process.effective_priority(), process.priority()];
+    for prio in priorities.into_iter().map(Priority::from) {
+        if runq.get_prio_queue(prio).remove(process.id()).is_some() {
+            runq.dec_len(prio);
+            return true;
+        }
+    }
+    false
+}
+
 /// Check if a process should be requeued
 ///
 /// Based on check_requeue_process() from erl_process.c
@@ -407,5 +500,27 @@ mod tests {
         assert_eq!(runq.index(), 0);
         assert_eq!(runq.total_len(), 0);
     }
+
+    #[test]
+    fn test_remove_process() {
+        let runq = RunQueue::new(6, 0);
+        let first = Arc::new(Process::new(1));
+        enqueue_process(&runq, Priority::Normal, Arc::clone(&first));
+        enqueue_process(&runq, Priority::Normal, Arc::new(Process::new(2)));
+        assert!(remove_process(&runq, &first));
+        assert!(!remove_process(&runq, &first));
+        assert_eq!(runq.total_len(), 1);
+        assert_eq!(dequeue_process(&runq, Priority::Normal).map(|p| p.id()), Some(2));
+    }
+
+    #[test]
+    fn test_run_queue_publishes_length() {
+        let runq = RunQueue::new(5, 0);
+        enqueue_process(&runq, Priority::Normal, Arc::new(Process::new(1)));
+        enqueue_process(&runq, Priority::High, Arc::new(Process::new(2)));
+        assert_eq!(get_global_statistics().run_queue_lengths()[5], 2);
+        dequeue_process(&runq, Priority::High);
+        assert_eq!(get_global_statistics().run_queue_lengths()[5], 1);
+    }
 }
 
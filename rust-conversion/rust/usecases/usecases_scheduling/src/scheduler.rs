@@ -7,8 +7,10 @@
 //! and scheduler state management.
 
 use std::sync::{Arc, Mutex};
-use entities_process::{Process, ProcessState};
-use crate::run_queue::{RunQueue, Priority, dequeue_process, enqueue_process};
+use entities_process::{Process, ProcessId, ProcessState, SystemTaskKind};
+use infrastructure_utilities::process_table::get_global_process_table;
+use usecases_process_management::process_sys_task::erts_request_system_task;
+use crate::run_queue::{RunQueue, Priority, dequeue_process, enqueue_process, remove_process};
 
 /// Scheduler state
 ///
@@ -85,6 +87,7 @@ impl Scheduler {
 ///
 /// # Note
 /// The process must be in a state that allows scheduling (not exiting, etc.)
+/// A suspended process is not enqueued; it is enqueued when it is resumed.
 pub fn schedule_process(
     process: Arc<Process>,
     runq: &RunQueue,
@@ -95,6 +98,9 @@ pub fn schedule_process(
     if matches!(state, ProcessState::Exiting | ProcessState::Free) {
         return Err(ScheduleError::ProcessExiting);
     }
+    if process.is_suspended() {
+        return Ok(());
+    }
 
     // Enqueue the process
     enqueue_process(runq, priority, process);
@@ -102,6 +108,110 @@ pub fn schedule_process(
     Ok(())
 }
 
+/// Schedule a process at its own priority
+///
+/// Used for newly spawned processes and whenever a process becomes runnable;
+/// the priority is the one given with `spawn_opt` or `process_flag/2`, or
+/// the priority of a queued system task if that is higher.
+///
+/// # Arguments
+/// * `process` - Process to schedule
+/// * `runq` - Run queue to enqueue into
+pub fn schedule_process_at_priority(
+    process: Arc<Process>,
+    runq: &RunQueue,
+) -> Result<(), ScheduleError> {
+    let priority = Priority::from(process.effective_priority());
+    schedule_process(process, runq, priority)
+}
+
+/// Request a system task on a process and schedule it
+///
+/// Based on erts_internal_request_system_task() from erl_process.c
+///
+/// The target inherits the requester's priority until the task has been
+/// executed; a target queued at a lower priority is moved to the queue of
+/// the task's priority.
+///
+/// # Arguments
+/// * `requester` - Process the reply is sent to
+/// * `target` - Process to run the task on
+/// * `kind` - Task to run
+/// * `runq` - Run queue to enqueue the target into
+///
+/// # Returns
+/// The request identifier the reply carries
+pub fn request_system_task(
+    requester: &Process,
+    target: ProcessId,
+    kind: SystemTaskKind,
+    runq: &RunQueue,
+) -> u64 {
+    let request = erts_request_system_task(requester, target, kind, get_global_process_table());
+    if let Some(target) = request.target {
+        remove_process(runq, &target);
+        // An exiting target answers the task itself when it is executed
+        let _ = schedule_process_at_priority(target, runq);
+    }
+    request.request_id
+}
+
+/// Take a process that has just been suspended off its run queue
+///
+/// Based on the suspend handling in erts_schedule() from erl_process.c
+///
+/// # Arguments
+/// * `process` - Suspended process
+/// * `runq` - Run queue the process may be queued in
+///
+/// # Returns
+/// `true` if the process was queued and has been removed; pass it to
+/// [`resume_scheduled_process`] when it is resumed
+pub fn suspend_scheduled_process(process: &Process, runq: &RunQueue) -> bool {
+    process.is_suspended() && remove_process(runq, process)
+}
+
+/// Enqueue a runnable process again after it has been resumed
+///
+/// Does nothing while the process is still suspended by another process.
+///
+/// # Arguments
+/// * `process` - Resumed process
+/// * `runq` - Run queue to enqueue into
+pub fn resume_scheduled_process(
+    process: Arc<Process>,
+    runq: &RunQueue,
+) -> Result<(), ScheduleError> {
+    schedule_process_at_priority(process, runq)
+}
+
+/// Resume processes that were suspended sending to a busy port or
+/// distribution entry that has since drained
+///
+/// Based on erts_resume() and the busy port/dist wakeup in io.c and dist.c
+///
+/// # Arguments
+/// * `senders` - Processes released by the destination (from its `take_resumed`)
+/// * `runq` - Run queue to enqueue resumed processes into
+///
+/// # Returns
+/// Number of processes that became runnable
+pub fn resume_busy_senders(senders: &[ProcessId], runq: &RunQueue) -> usize {
+    let table = get_global_process_table();
+    let mut resumed = 0;
+    for &id in senders {
+        let Some(process) = table.lookup(id) else {
+            continue;
+        };
+        if process.resume_from_busy() == Some(false)
+            && schedule_process_at_priority(process, runq).is_ok()
+        {
+            resumed += 1;
+        }
+    }
+    resumed
+}
+
 /// Main scheduler function
 ///
 /// Based on erts_schedule() from erl_process.c
@@ -290,6 +400,64 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_schedule_process_at_priority() {
+        use entities_process::{InitialCall, Process, ProcessPriority, SpawnOpts};
+        use std::sync::Arc;
+
+        let opts = SpawnOpts {
+            priority: ProcessPriority::High,
+            ..SpawnOpts::default()
+        };
+        let process = Arc::new(Process::spawned_with_opts(1, None, InitialCall::new("m", "f", 0), &opts));
+        let runq = RunQueue::new(0, 0);
+        schedule_process_at_priority(process, &runq).unwrap();
+        assert!(dequeue_process(&runq, Priority::Normal).is_none());
+        assert_eq!(dequeue_process(&runq, Priority::High).map(|p| p.id()), Some(1));
+    }
+
+    #[test]
+    fn test_suspend_and_resume_scheduled_process() {
+        use entities_process::Process;
+        use std::sync::Arc;
+
+        let process = Arc::new(Process::new(1));
+        let runq = RunQueue::new(0, 0);
+        schedule_process_at_priority(Arc::clone(&process), &runq).unwrap();
+
+        process.suspend(2);
+        assert!(suspend_scheduled_process(&process, &runq));
+        assert_eq!(runq.total_len(), 0);
+        schedule_process_at_priority(Arc::clone(&process), &runq).unwrap();
+        assert_eq!(runq.total_len(), 0);
+
+        process.resume(2);
+        resume_scheduled_process(Arc::clone(&process), &runq).unwrap();
+        assert_eq!(dequeue_process(&runq, Priority::Normal).map(|p| p.id()), Some(1));
+    }
+
+    #[test]
+    fn test_resume_busy_senders() {
+        use entities_process::{BusyDestination, Process};
+        use std::sync::Arc;
+
+        let busy = Arc::new(Process::new(41121));
+        let also_suspended = Arc::new(Process::new(41122));
+        get_global_process_table().insert(41121, Arc::clone(&busy));
+        get_global_process_table().insert(41122, Arc::clone(&also_suspended));
+        busy.suspend_on_busy(BusyDestination::Port(1));
+        also_suspended.suspend_on_busy(BusyDestination::Port(1));
+        also_suspended.suspend(3);
+
+        let runq = RunQueue::new(0, 0);
+        assert_eq!(resume_busy_senders(&[41121, 41122, 41123], &runq), 1);
+        assert_eq!(dequeue_process(&runq, Priority::Normal).map(|p| p.id()), Some(41121));
+        assert!(also_suspended.is_suspended());
+        assert_eq!(also_suspended.busy_destination(), None);
+        get_global_process_table().remove(41121);
+        get_global_process_table().remove(41122);
+    }
+
     #[test]
     fn test_schedule_error_display() {
         let error1 = ScheduleError::ProcessExiting;
@@ -398,5 +566,27 @@ mod tests {
         let executed = erts_schedule(&scheduler, 3);
         assert_eq!(executed, 3);
     }
-}
 
+    #[test]
+    fn test_request_system_task_inherits_priority() {
+        use entities_process::ProcessPriority;
+
+        let table = get_global_process_table();
+        let mut requester = Process::new(0x5157_0001);
+        requester.set_priority(ProcessPriority::High);
+        let mut target = Process::new(0x5157_0002);
+        target.set_priority(ProcessPriority::Low);
+        let target = Arc::new(target);
+        table.insert(target.id(), Arc::clone(&target));
+
+        let runq = RunQueue::new(0, 1000);
+        schedule_process_at_priority(Arc::clone(&target), &runq).unwrap();
+        request_system_task(&requester, target.id(), SystemTaskKind::GarbageCollect, &runq);
+
+        assert!(dequeue_process(&runq, Priority::Normal).is_none());
+        let queued = dequeue_process(&runq, Priority::High).unwrap();
+        assert_eq!(queued.id(), target.id());
+        assert!(queued.has_system_tasks());
+        table.remove(target.id());
+    }
+}
@@ -5,14 +5,50 @@
 
 use crate::scheduler::Scheduler;
 use crate::initialization::get_global_schedulers;
+use crate::bif_timers::{erts_bump_timers, erts_next_timeout};
+use crate::scheduler::resume_busy_senders;
+use crate::port_task::{erts_port_task_execute, erts_port_task_free, PortTask, PortTaskType};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::cell::Cell;
+use std::time::{Duration, Instant};
 use entities_process::{Process, ProcessState};
+use infrastructure_debugging::dump_on_panic;
+use infrastructure_driver_api::{get_global_async_pool, get_global_port_table};
+use infrastructure_nif_api::{notify_process_down, release_process_resources};
+use infrastructure_utilities::process_table::get_global_process_table;
+use infrastructure_utilities::thr_progress::get_global_thr_progress;
 
 /// Global flag to signal scheduler threads to stop
 static SCHEDULER_RUNNING: AtomicBool = AtomicBool::new(false);
 
+/// Number of scheduler threads inside their scheduling loop
+static ACTIVE_SCHEDULERS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// Set on scheduler threads while they run their scheduling loop
+    static IS_SCHEDULER: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Counts a scheduler thread as active until it leaves its loop
+struct ActiveScheduler;
+
+impl ActiveScheduler {
+    fn enter() -> Self {
+        ACTIVE_SCHEDULERS.fetch_add(1, Ordering::AcqRel);
+        IS_SCHEDULER.with(|is_scheduler| is_scheduler.set(true));
+        ActiveScheduler
+    }
+}
+
+impl Drop for ActiveScheduler {
+    fn drop(&mut self) {
+        IS_SCHEDULER.with(|is_scheduler| is_scheduler.set(false));
+        ACTIVE_SCHEDULERS.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
 /// Start all scheduler threads
 ///
 /// Based on `erts_start_schedulers()` from erl_process.c
@@ -47,7 +83,10 @@ pub fn erts_start_schedulers() -> Result<Vec<thread::JoinHandle<()>>, String> {
         let handle = thread::Builder::new()
             .name(format!("erts_sched_{}", index + 1))
             .spawn(move || {
-                scheduler_thread_func(schedulers_for_thread, running_clone, scheduler_index);
+                // A panic escaping the scheduling loop takes the runtime down
+                dump_on_panic(|| {
+                    scheduler_thread_func(schedulers_for_thread, running_clone, scheduler_index);
+                });
             })
             .map_err(|e| format!("Failed to create scheduler thread {}: {}", index + 1, e))?;
         
@@ -81,6 +120,11 @@ fn scheduler_thread_func(
     // 3. Set up signal handling
     // 4. Enter the main scheduling loop
     
+    // Schedulers are managed threads of the thread progress subsystem
+    let progress = get_global_thr_progress();
+    let progress_index = progress.register_managed_thread();
+    let _active = ActiveScheduler::enter();
+
     // Main scheduling loop
     while running.load(Ordering::Acquire) && SCHEDULER_RUNNING.load(Ordering::Acquire) {
         // Get scheduler reference (we need to clone the runq Arc to use it outside the lock)
@@ -97,7 +141,9 @@ fn scheduler_thread_func(
             // Check if scheduler is active
             if !scheduler.is_active() {
                 // Scheduler is offline, sleep briefly and check again
+                progress.prepare_wait(progress_index);
                 thread::sleep(std::time::Duration::from_millis(10));
+                progress.finalize_wait(progress_index);
                 continue;
             }
             
@@ -105,6 +151,12 @@ fn scheduler_thread_func(
             scheduler.runq()
         };
         
+        // Hand completed async jobs to their drivers' ready_async callbacks
+        get_global_async_pool().deliver_ready(get_global_port_table());
+
+        // Deliver the messages of expired BIF timers
+        erts_bump_timers();
+
         // Now we can work with the run queue without holding the schedulers lock
         let runq_guard = runq_arc.lock().unwrap();
         
@@ -137,35 +189,55 @@ fn scheduler_thread_func(
                         crate::run_queue::enqueue_process(&runq_guard, prio, process);
                     }
                 }
-                Ok(ExecutionResult::NormalExit) => {
-                    // Process finished normally, remove from process table
-                    use infrastructure_utilities::process_table::get_global_process_table;
-                    let table = get_global_process_table();
-                    table.remove(process.id());
-                }
-                Ok(ExecutionResult::ErrorExit) => {
-                    // Process exited with error
-                    use infrastructure_utilities::process_table::get_global_process_table;
-                    let table = get_global_process_table();
-                    table.remove(process.id());
+                Ok(ExecutionResult::NormalExit) | Ok(ExecutionResult::ErrorExit) => {
+                    process_exited(&process);
                 }
                 Err(e) => {
                     eprintln!("Error executing process {}: {}", process.id(), e);
-                    // Remove failed process
-                    use infrastructure_utilities::process_table::get_global_process_table;
-                    let table = get_global_process_table();
-                    table.remove(process.id());
+                    process_exited(&process);
                 }
             }
             
             executed += 1;
         }
         
+        // Execute the next port with pending tasks
+        let runq_guard = runq_arc.lock().unwrap();
+        if erts_port_task_execute(&runq_guard, execute_port_task).is_some() {
+            executed += 1;
+        }
+
+        // Resume processes suspended on ports that are no longer busy
+        let resumed = get_global_port_table().take_resumed();
+        if !resumed.is_empty() {
+            resume_busy_senders(&resumed, &runq_guard);
+        }
+        drop(runq_guard);
+
+        // No process is executing, so this is a progress point
+        progress.update(progress_index);
+
         if executed == 0 {
-            // No processes available, sleep briefly
-            thread::sleep(std::time::Duration::from_millis(1));
+            // No processes available, sleep briefly, but not past the next BIF timer
+            let idle = Duration::from_millis(1);
+            progress.prepare_wait(progress_index);
+            thread::sleep(erts_next_timeout().map_or(idle, |timeout| timeout.min(idle)));
+            progress.finalize_wait(progress_index);
         }
     }
+
+    progress.prepare_wait(progress_index);
+}
+
+/// Clean up after a process has exited
+///
+/// Based on erts_continue_exit_process() from erl_process.c: the process
+/// is removed from the process table, the NIF resources it referred to are
+/// released and the resource monitors on it fire.
+fn process_exited(process: &Process) {
+    get_global_process_table().remove(process.id());
+    release_process_resources(process.id());
+    notify_process_down(process.id());
 }
 
 /// Process execution result
@@ -203,13 +275,45 @@ fn execute_process(process: Arc<Process>) -> Result<ExecutionResult, String> {
     }
 }
 
+/// Execute a port task on the port's driver
+///
+/// Called by `erts_port_task_execute` for each task of the port it runs.
+/// Control and call requests are answered synchronously by their BIFs and
+/// are never queued, so only commands and input readiness reach drivers.
+///
+/// # Returns
+/// Extra reductions used by the driver
+fn execute_port_task(port_id: u64, task: &PortTask) -> i64 {
+    let Some(port) = get_global_port_table().lookup(port_id) else {
+        // The port was closed with tasks pending
+        erts_port_task_free(port_id);
+        return 0;
+    };
+    match task.task_type {
+        PortTaskType::Command => {
+            if let Err(e) = port.output(&task.data) {
+                eprintln!("Error executing command on port {}: {}", port_id, e);
+            }
+        }
+        PortTaskType::Input => {
+            port.ready_input(task.event);
+        }
+        PortTaskType::Timeout
+        | PortTaskType::Output
+        | PortTaskType::Event
+        | PortTaskType::Control
+        | PortTaskType::Call => {}
+    }
+    0
+}
+
 /// Check if a process should be rescheduled
 ///
-/// Determines if a process that yielded should be rescheduled.
-fn should_reschedule(_process: &Process) -> bool {
-    // For now, always reschedule if process hasn't exited
-    // In the full implementation, we'd check process state
-    true
+/// Determines if a process that yielded should be rescheduled. A process
+/// suspended while it ran, by `suspend_process` or on a busy port, is
+/// enqueued again when it is resumed.
+fn should_reschedule(process: &Process) -> bool {
+    !process.is_suspended()
 }
 
 /// Stop all scheduler threads
@@ -227,10 +331,70 @@ pub fn erts_stop_schedulers(handles: Vec<thread::JoinHandle<()>>) {
     }
 }
 
+/// Check if the scheduler threads have been told to run
+pub fn erts_schedulers_running() -> bool {
+    SCHEDULER_RUNNING.load(Ordering::Acquire)
+}
+
+/// Get the number of scheduler threads inside their scheduling loop
+pub fn erts_active_schedulers() -> usize {
+    ACTIVE_SCHEDULERS.load(Ordering::Acquire)
+}
+
+/// Stop the scheduler threads without their join handles
+///
+/// Used when halting the runtime: the halting code (`erlang:halt/1,2`) may
+/// run on a scheduler thread and does not own the handles. Signals all
+/// scheduler threads to stop and waits for the others to leave their
+/// scheduling loop; the calling scheduler, if any, is not waited for.
+///
+/// Based on the scheduler stop in `erts_exit_flush_async()` and
+/// `erts_halt()` from erl_process.c
+///
+/// # Arguments
+/// * `timeout` - Maximum time to wait, or `None` to wait until they stop
+///
+/// # Returns
+/// `true` if the other schedulers stopped, `false` on timeout
+pub fn erts_halt_schedulers(timeout: Option<Duration>) -> bool {
+    SCHEDULER_RUNNING.store(false, Ordering::Release);
+
+    let own = if IS_SCHEDULER.with(Cell::get) { 1 } else { 0 };
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    while ACTIVE_SCHEDULERS.load(Ordering::Acquire) > own {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::initialization::erts_init_scheduling;
+    use crate::port_task::{erts_port_task_schedule, PortTaskQueue};
+    use crate::run_queue::RunQueue;
+    use infrastructure_driver_api::{get_global_driver_registry, DriverEntry, DriverError, DriverPort};
+
+    /// Driver recording output in its queue and input events as their fd byte
+    struct RecordingDriver;
+
+    impl DriverEntry for RecordingDriver {
+        fn driver_name(&self) -> &str {
+            "threads_test_drv"
+        }
+
+        fn output(&self, port: &DriverPort, data: &[u8]) -> Result<(), DriverError> {
+            port.driver_enq(data);
+            Ok(())
+        }
+
+        fn ready_input(&self, port: &DriverPort, event: i32) {
+            port.driver_enq(&[event as u8]);
+        }
+    }
 
     #[test]
     fn test_start_schedulers() {
@@ -247,5 +411,55 @@ mod tests {
         // Stop schedulers
         erts_stop_schedulers(handles);
     }
-}
 
+    #[test]
+    fn test_execute_port_tasks_on_driver() {
+        let _ = get_global_driver_registry().add_driver_entry(Arc::new(RecordingDriver));
+        let port = get_global_port_table()
+            .open_port(get_global_driver_registry(), "threads_test_drv")
+            .unwrap();
+        let runq = RunQueue::new(0, 0);
+        let queue = Arc::new(PortTaskQueue::new(port.id(), 0));
+        erts_port_task_schedule(&runq, &queue, PortTask::with_data(PortTaskType::Command, b"ab".to_vec())).unwrap();
+        erts_port_task_schedule(&runq, &queue, PortTask::with_event(PortTaskType::Input, 7)).unwrap();
+
+        let execution = erts_port_task_execute(&runq, execute_port_task).unwrap();
+        assert_eq!(execution.tasks_executed, 2);
+        assert_eq!(port.driver_peekq(), vec![b'a', b'b', 7]);
+    }
+
+    #[test]
+    fn test_halt_schedulers_without_schedulers() {
+        // The test thread is not a scheduler, so it is not excluded from the wait
+        assert!(!IS_SCHEDULER.with(Cell::get));
+        let _ = erts_halt_schedulers(Some(Duration::from_millis(100)));
+        assert!(!erts_schedulers_running());
+    }
+
+    #[test]
+    fn test_process_exit_fires_resource_monitors() {
+        use infrastructure_nif_api::{
+            enif_alloc_resource, enif_monitor_process, ErlNifMonitor, ErlNifResourceType,
+            ErlNifResourceTypeInit,
+        };
+
+        let downs = Arc::new(Mutex::new(Vec::new()));
+        let recorded = downs.clone();
+        let init = ErlNifResourceTypeInit {
+            down: Some(Arc::new(move |_: &mut [u8], pid: u64, _: &ErlNifMonitor| {
+                recorded.lock().unwrap().push(pid);
+            })),
+            ..Default::default()
+        };
+        let resource_type = ErlNifResourceType::with_init("down".to_string(), "threads".to_string(), init);
+        let resource = enif_alloc_resource(&resource_type, 1).unwrap();
+
+        let process = Arc::new(Process::new(40695));
+        get_global_process_table().insert(40695, Arc::clone(&process));
+        enif_monitor_process(&resource, 40695).unwrap();
+
+        process_exited(&process);
+        assert!(get_global_process_table().lookup(40695).is_none());
+        assert_eq!(*downs.lock().unwrap(), vec![40695]);
+    }
+}
@@ -164,3 +164,32 @@ fn test_priority_conversion() {
     assert_eq!(Priority::from_index(4), None);
 }
 
+
+#[test]
+fn test_port_tasks_share_run_queue_with_processes() {
+    let scheduler = Scheduler::new(0, 1000);
+    let runq = scheduler.runq();
+    let runq_guard = runq.lock().unwrap();
+
+    let port = Arc::new(PortTaskQueue::new(42, scheduler.index()));
+    enqueue_process(&runq_guard, Priority::Normal, Arc::new(Process::new(1)));
+    erts_port_task_schedule(&runq_guard, &port, PortTask::with_data(PortTaskType::Command, b"data".to_vec())).unwrap();
+    erts_port_task_schedule(&runq_guard, &port, PortTask::new(PortTaskType::Control)).unwrap();
+    assert_eq!(runq_guard.total_len(), 2);
+
+    let mut output = Vec::new();
+    let execution = erts_port_task_execute(&runq_guard, |port_id, task| {
+        assert_eq!(port_id, 42);
+        output.extend_from_slice(&task.data);
+        task.data.len() as i64
+    })
+    .unwrap();
+    assert_eq!(output, b"data".to_vec());
+    assert_eq!(execution.tasks_executed, 2);
+    assert_eq!(execution.reductions, PortTaskType::Command.reductions() + PortTaskType::Control.reductions() + 4);
+    assert_eq!(runq_guard.ports_info().reds(), execution.reductions);
+
+    // Only the process is left
+    assert_eq!(runq_guard.total_len(), 1);
+    assert!(dequeue_process(&runq_guard, Priority::Normal).is_some());
+}